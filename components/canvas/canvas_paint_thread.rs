@@ -5,7 +5,7 @@
 use azure::azure::{AzColor, AzFloat};
 use azure::azure_hl::{AntialiasMode, CapStyle, CompositionOp, JoinStyle};
 use azure::azure_hl::{BackendType, DrawOptions, DrawTarget, Pattern, StrokeOptions, SurfaceFormat};
-use azure::azure_hl::{ColorPattern, DrawSurfaceOptions, Filter, PathBuilder};
+use azure::azure_hl::{ColorPattern, DrawSurfaceOptions, Filter, Path, PathBuilder};
 use canvas_traits::*;
 use euclid::matrix2d::Matrix2D;
 use euclid::point::Point2D;
@@ -16,6 +16,7 @@ use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::ipc::IpcSharedMemory;
 use num_traits::ToPrimitive;
 use std::borrow::ToOwned;
+use std::f32::consts::PI;
 use std::mem;
 use util::thread::spawn_named;
 use webrender_traits;
@@ -141,6 +142,12 @@ impl<'a> CanvasPaintThread<'a> {
                             Canvas2dMsg::IsPointInPath(x, y, fill_rule, chan) => {
                                 painter.is_point_in_path(x, y, fill_rule, chan)
                             },
+                            Canvas2dMsg::FillPath2D(ref segments) => painter.fill_path_2d(segments),
+                            Canvas2dMsg::StrokePath2D(ref segments) => painter.stroke_path_2d(segments),
+                            Canvas2dMsg::ClipPath2D(ref segments) => painter.clip_path_2d(segments),
+                            Canvas2dMsg::IsPointInPath2D(ref segments, x, y, fill_rule, chan) => {
+                                painter.is_point_in_path_2d(segments, x, y, fill_rule, chan)
+                            },
                             Canvas2dMsg::DrawImage(imagedata, image_size, dest_rect, source_rect,
                                                    smoothing_enabled) => {
                                 painter.draw_image(imagedata, image_size, dest_rect, source_rect, smoothing_enabled)
@@ -169,6 +176,9 @@ impl<'a> CanvasPaintThread<'a> {
                             Canvas2dMsg::ArcTo(ref cp1, ref cp2, radius) => {
                                 painter.arc_to(cp1, cp2, radius)
                             }
+                            Canvas2dMsg::Ellipse(ref center, radius_x, radius_y, rotation, start, end, ccw) => {
+                                painter.ellipse(center, radius_x, radius_y, rotation, start, end, ccw)
+                            }
                             Canvas2dMsg::RestoreContext => painter.restore_context_state(),
                             Canvas2dMsg::SaveContext => painter.save_context_state(),
                             Canvas2dMsg::SetFillStyle(style) => painter.set_fill_style(style),
@@ -188,6 +198,7 @@ impl<'a> CanvasPaintThread<'a> {
                             Canvas2dMsg::SetShadowOffsetY(value) => painter.set_shadow_offset_y(value),
                             Canvas2dMsg::SetShadowBlur(value) => painter.set_shadow_blur(value),
                             Canvas2dMsg::SetShadowColor(ref color) => painter.set_shadow_color(color.to_azcolor()),
+                            Canvas2dMsg::Encode(encoding, chan) => painter.encode(encoding, chan),
                         }
                     },
                     CanvasMsg::Common(message) => {
@@ -203,7 +214,8 @@ impl<'a> CanvasPaintThread<'a> {
                             }
                         }
                     }
-                    CanvasMsg::WebGL(_) => panic!("Wrong message sent to Canvas2D thread"),
+                    CanvasMsg::WebGL(_) |
+                    CanvasMsg::WebGL2(_) => panic!("Wrong message sent to Canvas2D thread"),
                 }
             }
         });
@@ -330,12 +342,50 @@ impl<'a> CanvasPaintThread<'a> {
         chan.send(result).unwrap();
     }
 
-    fn draw_image(&self, image_data: Vec<u8>, image_size: Size2D<f64>,
+    /// Builds the path of a `Path2D` out of its segments.
+    fn path_2d(&self, segments: &[PathSegment]) -> Path {
+        let path_builder = self.drawtarget.create_path_builder();
+        PathSegmentSink::new(&path_builder, Matrix2D::identity()).add(segments);
+        path_builder.finish()
+    }
+
+    fn fill_path_2d(&self, segments: &[PathSegment]) {
+        if is_zero_size_gradient(&self.state.fill_style) {
+            return; // Paint nothing if gradient size is zero.
+        }
+
+        self.drawtarget.fill(&self.path_2d(segments),
+                             self.state.fill_style.to_pattern_ref(),
+                             &self.state.draw_options);
+    }
+
+    fn stroke_path_2d(&self, segments: &[PathSegment]) {
+        if is_zero_size_gradient(&self.state.stroke_style) {
+            return; // Paint nothing if gradient size is zero.
+        }
+
+        self.drawtarget.stroke(&self.path_2d(segments),
+                               self.state.stroke_style.to_pattern_ref(),
+                               &self.state.stroke_opts,
+                               &self.state.draw_options);
+    }
+
+    fn clip_path_2d(&self, segments: &[PathSegment]) {
+        self.drawtarget.push_clip(&self.path_2d(segments));
+    }
+
+    fn is_point_in_path_2d(&self, segments: &[PathSegment], x: f64, y: f64,
+                           _fill_rule: FillRule, chan: IpcSender<bool>) {
+        let result = self.path_2d(segments).contains_point(x, y, &self.state.transform);
+        chan.send(result).unwrap();
+    }
+
+    fn draw_image(&self, image_data: IpcSharedMemory, image_size: Size2D<f64>,
                   dest_rect: Rect<f64>, source_rect: Rect<f64>, smoothing_enabled: bool) {
         // We round up the floating pixel values to draw the pixels
         let source_rect = source_rect.ceil();
         // It discards the extra pixels (if any) that won't be painted
-        let image_data = crop_image(image_data, image_size, source_rect);
+        let image_data = crop_image(image_data.to_vec(), image_size, source_rect);
 
         if self.need_to_draw_shadow() {
             let rect = Rect::new(Point2D::new(dest_rect.origin.x as f32, dest_rect.origin.y as f32),
@@ -388,6 +438,7 @@ impl<'a> CanvasPaintThread<'a> {
         // TODO: avoid double byte_swap.
         byte_swap(&mut image_data);
 
+        let image_data = IpcSharedMemory::from_bytes(&image_data);
         let msg = CanvasMsg::Canvas2d(Canvas2dMsg::DrawImage(
             image_data, source_rect.size, dest_rect, source_rect, smoothing_enabled));
         renderer.send(msg).unwrap();
@@ -441,56 +492,25 @@ impl<'a> CanvasPaintThread<'a> {
               cp2: &Point2D<AzFloat>,
               radius: AzFloat) {
         let cp0 = self.path_builder.get_current_point();
-        let cp1 = *cp1;
-        let cp2 = *cp2;
-
-        if (cp0.x == cp1.x && cp0.y == cp1.y) || cp1 == cp2 || radius == 0.0 {
-            self.line_to(&cp1);
-            return;
-        }
-
-        // if all three control points lie on a single straight line,
-        // connect the first two by a straight line
-        let direction = (cp2.x - cp1.x) * (cp0.y - cp1.y) + (cp2.y - cp1.y) * (cp1.x - cp0.x);
-        if direction == 0.0 {
-            self.line_to(&cp1);
-            return;
-        }
-
-        // otherwise, draw the Arc
-        let a2 = (cp0.x - cp1.x).powi(2) + (cp0.y - cp1.y).powi(2);
-        let b2 = (cp1.x - cp2.x).powi(2) + (cp1.y - cp2.y).powi(2);
-        let d = {
-            let c2 = (cp0.x - cp2.x).powi(2) + (cp0.y - cp2.y).powi(2);
-            let cosx = (a2 + b2 - c2) / (2.0 * (a2 * b2).sqrt());
-            let sinx = (1.0 - cosx.powi(2)).sqrt();
-            radius / ((1.0 - cosx) / sinx)
-        };
-
-        // first tangent point
-        let anx = (cp1.x - cp0.x) / a2.sqrt();
-        let any = (cp1.y - cp0.y) / a2.sqrt();
-        let tp1 = Point2D::new(cp1.x - anx * d, cp1.y - any * d);
-
-        // second tangent point
-        let bnx = (cp1.x - cp2.x) / b2.sqrt();
-        let bny = (cp1.y - cp2.y) / b2.sqrt();
-        let tp2 = Point2D::new(cp1.x - bnx * d, cp1.y - bny * d);
-
-        // arc center and angles
-        let anticlockwise = direction < 0.0;
-        let cx = tp1.x + any * radius * if anticlockwise { 1.0 } else { -1.0 };
-        let cy = tp1.y - anx * radius * if anticlockwise { 1.0 } else { -1.0 };
-        let angle_start = (tp1.y - cy).atan2(tp1.x - cx);
-        let angle_end = (tp2.y - cy).atan2(tp2.x - cx);
-
-        self.line_to(&tp1);
-        if [cx, cy, angle_start, angle_end].iter().all(|x| x.is_finite()) {
-            self.arc(&Point2D::new(cx, cy), radius,
-                     angle_start, angle_end, anticlockwise);
+        let (tangent_point, arc) = arc_to_geometry(cp0, *cp1, *cp2, radius);
+        self.line_to(&tangent_point);
+        if let Some((center, angle_start, angle_end, anticlockwise)) = arc {
+            self.arc(&center, radius, angle_start, angle_end, anticlockwise);
         }
     }
 
+    fn ellipse(&self,
+               center: &Point2D<AzFloat>,
+               radius_x: AzFloat,
+               radius_y: AzFloat,
+               rotation: AzFloat,
+               start_angle: AzFloat,
+               end_angle: AzFloat,
+               ccw: bool) {
+        add_ellipse(&self.path_builder, &Matrix2D::identity(), *center,
+                    radius_x, radius_y, rotation, start_angle, end_angle, ccw);
+    }
+
     fn set_fill_style(&mut self, style: FillOrStrokeStyle) {
         if let Some(pattern) = style.to_azure_pattern(&self.drawtarget) {
             self.state.fill_style = pattern
@@ -559,16 +579,28 @@ impl<'a> CanvasPaintThread<'a> {
         })
     }
 
-    fn image_data(&self, dest_rect: Rect<i32>, canvas_size: Size2D<f64>, chan: IpcSender<Vec<u8>>) {
+    fn image_data(&self, dest_rect: Rect<i32>, canvas_size: Size2D<f64>, chan: IpcSender<IpcSharedMemory>) {
         let mut dest_data = self.read_pixels(dest_rect, canvas_size);
 
         // bgra -> rgba
         byte_swap(&mut dest_data);
-        chan.send(dest_data).unwrap();
+        chan.send(IpcSharedMemory::from_bytes(&dest_data)).unwrap();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#a-serialisation-of-the-bitmap-as-a-file
+    fn encode(&self, encoding: ImageEncoding, chan: IpcSender<Vec<u8>>) {
+        let size = self.drawtarget.get_size();
+        let rect = Rect::new(Point2D::zero(), Size2D::new(size.width, size.height));
+        let mut data = self.read_pixels(rect, rect.size.to_f64());
+
+        // bgra -> rgba
+        byte_swap(&mut data);
+        let size = Size2D::new(size.width as u32, size.height as u32);
+        chan.send(encode_image(&data, size, encoding)).unwrap();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-putimagedata
-    fn put_image_data(&mut self, imagedata: Vec<u8>,
+    fn put_image_data(&mut self, imagedata: IpcSharedMemory,
                       offset: Point2D<f64>,
                       image_data_size: Size2D<f64>,
                       mut dirty_rect: Rect<f64>) {
@@ -804,6 +836,242 @@ fn is_zero_size_gradient(pattern: &Pattern) -> bool {
     false
 }
 
+/// The geometry of `arcTo()` from the current point `cp0`: the point a line
+/// is drawn to, and the center, start and end angles and direction of the
+/// arc that follows it, if any.
+fn arc_to_geometry(cp0: Point2D<AzFloat>,
+                   cp1: Point2D<AzFloat>,
+                   cp2: Point2D<AzFloat>,
+                   radius: AzFloat)
+                   -> (Point2D<AzFloat>, Option<(Point2D<AzFloat>, AzFloat, AzFloat, bool)>) {
+    if (cp0.x == cp1.x && cp0.y == cp1.y) || cp1 == cp2 || radius == 0.0 {
+        return (cp1, None);
+    }
+
+    // if all three control points lie on a single straight line,
+    // connect the first two by a straight line
+    let direction = (cp2.x - cp1.x) * (cp0.y - cp1.y) + (cp2.y - cp1.y) * (cp1.x - cp0.x);
+    if direction == 0.0 {
+        return (cp1, None);
+    }
+
+    // otherwise, draw the Arc
+    let a2 = (cp0.x - cp1.x).powi(2) + (cp0.y - cp1.y).powi(2);
+    let b2 = (cp1.x - cp2.x).powi(2) + (cp1.y - cp2.y).powi(2);
+    let d = {
+        let c2 = (cp0.x - cp2.x).powi(2) + (cp0.y - cp2.y).powi(2);
+        let cosx = (a2 + b2 - c2) / (2.0 * (a2 * b2).sqrt());
+        let sinx = (1.0 - cosx.powi(2)).sqrt();
+        radius / ((1.0 - cosx) / sinx)
+    };
+
+    // first tangent point
+    let anx = (cp1.x - cp0.x) / a2.sqrt();
+    let any = (cp1.y - cp0.y) / a2.sqrt();
+    let tp1 = Point2D::new(cp1.x - anx * d, cp1.y - any * d);
+
+    // second tangent point
+    let bnx = (cp1.x - cp2.x) / b2.sqrt();
+    let bny = (cp1.y - cp2.y) / b2.sqrt();
+    let tp2 = Point2D::new(cp1.x - bnx * d, cp1.y - bny * d);
+
+    // arc center and angles
+    let anticlockwise = direction < 0.0;
+    let cx = tp1.x + any * radius * if anticlockwise { 1.0 } else { -1.0 };
+    let cy = tp1.y - anx * radius * if anticlockwise { 1.0 } else { -1.0 };
+    let angle_start = (tp1.y - cy).atan2(tp1.x - cx);
+    let angle_end = (tp2.y - cy).atan2(tp2.x - cx);
+
+    if [cx, cy, angle_start, angle_end].iter().all(|x| x.is_finite()) {
+        (tp1, Some((Point2D::new(cx, cy), angle_start, angle_end, anticlockwise)))
+    } else {
+        (tp1, None)
+    }
+}
+
+/// The point of an ellipse at an angle, and the derivative of the ellipse
+/// there.
+fn ellipse_point(center: Point2D<AzFloat>,
+                 radius_x: AzFloat,
+                 radius_y: AzFloat,
+                 rotation: AzFloat,
+                 angle: AzFloat)
+                 -> (Point2D<AzFloat>, Point2D<AzFloat>) {
+    let (sin_rotation, cos_rotation) = rotation.sin_cos();
+    let (sin, cos) = angle.sin_cos();
+    let point = Point2D::new(center.x + radius_x * cos * cos_rotation - radius_y * sin * sin_rotation,
+                             center.y + radius_x * cos * sin_rotation + radius_y * sin * cos_rotation);
+    let tangent = Point2D::new(-radius_x * sin * cos_rotation - radius_y * cos * sin_rotation,
+                               -radius_x * sin * sin_rotation + radius_y * cos * cos_rotation);
+    (point, tangent)
+}
+
+/// Adds a line to the start of an elliptical arc and the arc itself, as
+/// cubic Bézier curves of at most a quarter turn each, mapping every point
+/// through `transform`. Returns the untransformed end point of the arc.
+/// https://html.spec.whatwg.org/multipage/#dom-context-2d-ellipse
+fn add_ellipse(path_builder: &PathBuilder,
+               transform: &Matrix2D<AzFloat>,
+               center: Point2D<AzFloat>,
+               radius_x: AzFloat,
+               radius_y: AzFloat,
+               rotation: AzFloat,
+               start_angle: AzFloat,
+               end_angle: AzFloat,
+               anticlockwise: bool)
+               -> Point2D<AzFloat> {
+    let full_turn = 2. * PI;
+    let sweep = if !anticlockwise && end_angle - start_angle >= full_turn {
+        full_turn
+    } else if anticlockwise && start_angle - end_angle >= full_turn {
+        -full_turn
+    } else {
+        let sweep = (end_angle - start_angle) % full_turn;
+        if !anticlockwise && sweep < 0. {
+            sweep + full_turn
+        } else if anticlockwise && sweep > 0. {
+            sweep - full_turn
+        } else {
+            sweep
+        }
+    };
+
+    let point_at = |angle| ellipse_point(center, radius_x, radius_y, rotation, angle);
+
+    let curves = (sweep.abs() / (full_turn / 4.)).ceil().max(1.) as usize;
+    let step = sweep / curves as AzFloat;
+    let k = 4. / 3. * (step / 4.).tan();
+    let (mut point, mut tangent) = point_at(start_angle);
+    path_builder.line_to(transform.transform_point(&point));
+    for i in 0..curves {
+        let (end, end_tangent) = point_at(start_angle + step * (i + 1) as AzFloat);
+        let cp1 = Point2D::new(point.x + k * tangent.x, point.y + k * tangent.y);
+        let cp2 = Point2D::new(end.x - k * end_tangent.x, end.y - k * end_tangent.y);
+        path_builder.bezier_curve_to(&transform.transform_point(&cp1),
+                                     &transform.transform_point(&cp2),
+                                     &transform.transform_point(&end));
+        point = end;
+        tangent = end_tangent;
+    }
+    point
+}
+
+/// Adds the segments of a `Path2D` to a path builder, mapping their points
+/// through the transform they were added with.
+struct PathSegmentSink<'a> {
+    path_builder: &'a PathBuilder,
+    transform: Matrix2D<AzFloat>,
+    /// The last point of the path, untransformed.
+    current_point: Option<Point2D<AzFloat>>,
+    /// The first point of the current subpath, untransformed.
+    subpath_start: Option<Point2D<AzFloat>>,
+}
+
+impl<'a> PathSegmentSink<'a> {
+    fn new(path_builder: &'a PathBuilder, transform: Matrix2D<AzFloat>) -> PathSegmentSink<'a> {
+        PathSegmentSink {
+            path_builder: path_builder,
+            transform: transform,
+            current_point: None,
+            subpath_start: None,
+        }
+    }
+
+    fn move_to(&mut self, point: Point2D<AzFloat>) {
+        self.path_builder.move_to(self.transform.transform_point(&point));
+        self.current_point = Some(point);
+        self.subpath_start = Some(point);
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#ensure-there-is-a-subpath
+    fn ensure_subpath(&mut self, point: Point2D<AzFloat>) {
+        if self.current_point.is_none() {
+            self.move_to(point);
+        }
+    }
+
+    fn line_to(&mut self, point: Point2D<AzFloat>) {
+        self.ensure_subpath(point);
+        self.path_builder.line_to(self.transform.transform_point(&point));
+        self.current_point = Some(point);
+    }
+
+    fn add(&mut self, segments: &[PathSegment]) {
+        for segment in segments {
+            match *segment {
+                PathSegment::ClosePath => {
+                    if self.current_point.is_some() {
+                        self.path_builder.close();
+                        self.current_point = self.subpath_start;
+                    }
+                }
+                PathSegment::MoveTo(point) => self.move_to(point),
+                PathSegment::LineTo(point) => self.line_to(point),
+                PathSegment::QuadraticCurveTo(cp, point) => {
+                    self.ensure_subpath(cp);
+                    self.path_builder.quadratic_curve_to(&self.transform.transform_point(&cp),
+                                                         &self.transform.transform_point(&point));
+                    self.current_point = Some(point);
+                }
+                PathSegment::BezierCurveTo(cp1, cp2, point) => {
+                    self.ensure_subpath(cp1);
+                    self.path_builder.bezier_curve_to(&self.transform.transform_point(&cp1),
+                                                      &self.transform.transform_point(&cp2),
+                                                      &self.transform.transform_point(&point));
+                    self.current_point = Some(point);
+                }
+                PathSegment::ArcTo(cp1, cp2, radius) => {
+                    let cp0 = match self.current_point {
+                        Some(cp0) => cp0,
+                        None => {
+                            self.move_to(cp1);
+                            continue;
+                        }
+                    };
+                    let (tangent_point, arc) = arc_to_geometry(cp0, cp1, cp2, radius);
+                    self.line_to(tangent_point);
+                    if let Some((center, angle_start, angle_end, anticlockwise)) = arc {
+                        self.current_point = Some(add_ellipse(self.path_builder, &self.transform, center,
+                                                              radius, radius, 0., angle_start, angle_end,
+                                                              anticlockwise));
+                    }
+                }
+                PathSegment::Rect(ref rect) => {
+                    self.move_to(rect.origin);
+                    self.line_to(rect.top_right());
+                    self.line_to(rect.bottom_right());
+                    self.line_to(rect.bottom_left());
+                    self.path_builder.close();
+                    self.current_point = self.subpath_start;
+                }
+                PathSegment::Arc(center, radius, start_angle, end_angle, anticlockwise) => {
+                    self.ensure_subpath(ellipse_point(center, radius, radius, 0., start_angle).0);
+                    self.current_point = Some(add_ellipse(self.path_builder, &self.transform, center,
+                                                          radius, radius, 0., start_angle, end_angle,
+                                                          anticlockwise));
+                }
+                PathSegment::Ellipse(center, radius_x, radius_y, rotation, start_angle, end_angle,
+                                     anticlockwise) => {
+                    self.ensure_subpath(ellipse_point(center, radius_x, radius_y, rotation, start_angle).0);
+                    self.current_point = Some(add_ellipse(self.path_builder, &self.transform, center,
+                                                          radius_x, radius_y, rotation, start_angle,
+                                                          end_angle, anticlockwise));
+                }
+                PathSegment::AddPath(ref segments, ref matrix) => {
+                    let mut sink = PathSegmentSink::new(self.path_builder, self.transform.pre_mul(matrix));
+                    sink.add(segments);
+                    if let Some(point) = sink.current_point {
+                        self.current_point = Some(matrix.transform_point(&point));
+                    }
+                    if let Some(point) = sink.subpath_start {
+                        self.subpath_start = Some(matrix.transform_point(&point));
+                    }
+                }
+            }
+        }
+    }
+}
+
 pub trait PointToi32 {
     fn to_i32(&self) -> Point2D<i32>;
 }
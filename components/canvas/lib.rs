@@ -22,4 +22,7 @@ extern crate util;
 extern crate webrender_traits;
 
 pub mod canvas_paint_thread;
+mod webgl2;
+mod webgl_extensions;
 pub mod webgl_paint_thread;
+pub mod webxr_thread;
@@ -0,0 +1,159 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Support for the WebGL 2.0 commands that WebRender does not know about. These are applied
+//! directly to the GL context of a readback WebGL thread.
+
+#![allow(unsafe_code)]
+
+use canvas_traits::webgl2::{WebGL2Command, WebGLSamplerId, WebGLTransformFeedbackId};
+use canvas_traits::webgl2::WebGLVertexArrayId;
+use gleam::gl;
+use std::ffi::CString;
+use std::ptr;
+
+/// The version of the GL context that is current on this thread.
+pub struct GLVersion {
+    pub is_es: bool,
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl GLVersion {
+    pub fn current() -> Option<GLVersion> {
+        let version = gl::get_string(gl::VERSION);
+        let (is_es, numbers) = match version.find("OpenGL ES ") {
+            Some(index) => (true, &version[index + "OpenGL ES ".len()..]),
+            None => (false, &version[..]),
+        };
+        let mut parts = numbers.split(|c: char| !c.is_digit(10));
+        let major = match parts.next().and_then(|part| part.parse::<u32>().ok()) {
+            Some(major) => major,
+            None => return None,
+        };
+        let minor = parts.next().and_then(|part| part.parse::<u32>().ok()).unwrap_or(0);
+        Some(GLVersion {
+            is_es: is_es,
+            major: major,
+            minor: minor,
+        })
+    }
+
+    /// Returns whether this is at least the given desktop OpenGL or OpenGL ES version.
+    pub fn at_least(&self, desktop: (u32, u32), es: (u32, u32)) -> bool {
+        let required = if self.is_es { es } else { desktop };
+        (self.major, self.minor) >= required
+    }
+}
+
+/// Returns whether the GL context that is current on this thread can back a WebGL 2.0 context,
+/// which requires desktop OpenGL 3.3 or OpenGL ES 3.0.
+pub fn is_supported() -> bool {
+    GLVersion::current().map_or(false, |version| version.at_least((3, 3), (3, 0)))
+}
+
+/// Applies a WebGL 2.0 command to the GL context that is current on this thread.
+pub fn apply(command: WebGL2Command) {
+    match command {
+        WebGL2Command::CreateVertexArray(chan) => {
+            let mut name = 0;
+            unsafe { gl::GenVertexArrays(1, &mut name); }
+            chan.send(if name == 0 { None } else { Some(WebGLVertexArrayId(name)) }).unwrap();
+        }
+        WebGL2Command::DeleteVertexArray(id) => unsafe {
+            gl::DeleteVertexArrays(1, &id.0);
+        },
+        WebGL2Command::BindVertexArray(id) => unsafe {
+            gl::BindVertexArray(id.map_or(0, |id| id.0));
+        },
+        WebGL2Command::DrawArraysInstanced(mode, first, count, primcount) => unsafe {
+            gl::DrawArraysInstanced(mode, first, count, primcount);
+        },
+        WebGL2Command::DrawElementsInstanced(mode, count, index_type, offset, primcount) => unsafe {
+            gl::DrawElementsInstanced(mode, count, index_type, offset as *const _, primcount);
+        },
+        WebGL2Command::VertexAttribDivisor(index, divisor) => unsafe {
+            gl::VertexAttribDivisor(index, divisor);
+        },
+        WebGL2Command::TexImage3D(target, level, internal_format, width, height, depth,
+                                  format, data_type, data) => unsafe {
+            let pixels = if data.is_empty() { ptr::null() } else { data.as_ptr() };
+            gl::TexImage3D(target, level, internal_format, width, height, depth, 0,
+                           format, data_type, pixels as *const _);
+        },
+        WebGL2Command::TexSubImage3D(target, level, x, y, z, width, height, depth,
+                                     format, data_type, data) => unsafe {
+            gl::TexSubImage3D(target, level, x, y, z, width, height, depth,
+                              format, data_type, data.as_ptr() as *const _);
+        },
+        WebGL2Command::BindBufferBase(target, index, buffer) => unsafe {
+            gl::BindBufferBase(target, index, buffer.map_or(0, |id| id.get()));
+        },
+        WebGL2Command::BindBufferRange(target, index, buffer, offset, size) => unsafe {
+            gl::BindBufferRange(target, index, buffer.map_or(0, |id| id.get()),
+                                offset as gl::GLintptr, size as gl::GLsizeiptr);
+        },
+        WebGL2Command::GetUniformBlockIndex(program, name, chan) => {
+            let index = match CString::new(name) {
+                Ok(name) => unsafe { gl::GetUniformBlockIndex(program.get(), name.as_ptr()) },
+                Err(_) => gl::INVALID_INDEX,
+            };
+            chan.send(index).unwrap();
+        }
+        WebGL2Command::UniformBlockBinding(program, index, binding) => unsafe {
+            gl::UniformBlockBinding(program.get(), index, binding);
+        },
+        WebGL2Command::CreateTransformFeedback(chan) => {
+            let mut name = 0;
+            unsafe { gl::GenTransformFeedbacks(1, &mut name); }
+            chan.send(if name == 0 { None } else { Some(WebGLTransformFeedbackId(name)) }).unwrap();
+        }
+        WebGL2Command::DeleteTransformFeedback(id) => unsafe {
+            gl::DeleteTransformFeedbacks(1, &id.0);
+        },
+        WebGL2Command::BindTransformFeedback(target, id) => unsafe {
+            gl::BindTransformFeedback(target, id.map_or(0, |id| id.0));
+        },
+        WebGL2Command::BeginTransformFeedback(primitive_mode) => unsafe {
+            gl::BeginTransformFeedback(primitive_mode);
+        },
+        WebGL2Command::EndTransformFeedback => unsafe {
+            gl::EndTransformFeedback();
+        },
+        WebGL2Command::PauseTransformFeedback => unsafe {
+            gl::PauseTransformFeedback();
+        },
+        WebGL2Command::ResumeTransformFeedback => unsafe {
+            gl::ResumeTransformFeedback();
+        },
+        WebGL2Command::TransformFeedbackVaryings(program, varyings, buffer_mode) => {
+            let varyings: Vec<CString> = match varyings.into_iter().map(CString::new).collect() {
+                Ok(varyings) => varyings,
+                Err(_) => return,
+            };
+            let pointers: Vec<_> = varyings.iter().map(|varying| varying.as_ptr()).collect();
+            unsafe {
+                gl::TransformFeedbackVaryings(program.get(), pointers.len() as gl::GLsizei,
+                                              pointers.as_ptr(), buffer_mode);
+            }
+        }
+        WebGL2Command::CreateSampler(chan) => {
+            let mut name = 0;
+            unsafe { gl::GenSamplers(1, &mut name); }
+            chan.send(if name == 0 { None } else { Some(WebGLSamplerId(name)) }).unwrap();
+        }
+        WebGL2Command::DeleteSampler(id) => unsafe {
+            gl::DeleteSamplers(1, &id.0);
+        },
+        WebGL2Command::BindSampler(unit, id) => unsafe {
+            gl::BindSampler(unit, id.map_or(0, |id| id.0));
+        },
+        WebGL2Command::SamplerParameteri(id, pname, param) => unsafe {
+            gl::SamplerParameteri(id.0, pname, param);
+        },
+        WebGL2Command::SamplerParameterf(id, pname, param) => unsafe {
+            gl::SamplerParameterf(id.0, pname, param);
+        },
+    }
+}
@@ -0,0 +1,46 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Works out which WebGL extensions the GL driver can back.
+
+#![allow(unsafe_code)]
+
+use canvas_traits::webgl_extensions::WebGLExtensionSupport;
+use gleam::gl;
+use webgl2::GLVersion;
+
+const MAX_TEXTURE_MAX_ANISOTROPY_EXT: gl::GLenum = 0x84FF;
+
+/// Queries the GL context that is current on this thread for the extensions it supports.
+///
+/// The extensions that add entry points are only supported where those entry points are part of
+/// core GL, since that is all we load, and only when `renders_directly` is set: they are applied
+/// through `WebGL2Command`s, which WebRender doesn't understand.
+pub fn query_support(renders_directly: bool) -> WebGLExtensionSupport {
+    let version = match GLVersion::current() {
+        Some(version) => version,
+        None => return WebGLExtensionSupport::default(),
+    };
+    let extensions = gl::get_string(gl::EXTENSIONS);
+    let has_extension = |name: &str| extensions.split_whitespace().any(|ext| ext == name);
+
+    let max_texture_anisotropy = if has_extension("GL_EXT_texture_filter_anisotropic") {
+        let mut value = 0.;
+        unsafe { gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY_EXT, &mut value); }
+        Some(value)
+    } else {
+        None
+    };
+
+    WebGLExtensionSupport {
+        instanced_arrays: renders_directly && version.at_least((3, 3), (3, 0)),
+        texture_float: version.at_least((3, 0), (3, 0)) ||
+                       has_extension("GL_ARB_texture_float") ||
+                       has_extension("GL_OES_texture_float"),
+        element_index_uint: version.at_least((0, 0), (3, 0)) ||
+                            has_extension("GL_OES_element_index_uint"),
+        max_texture_anisotropy: max_texture_anisotropy,
+        vertex_array_object: renders_directly && version.at_least((3, 0), (3, 0)),
+    }
+}
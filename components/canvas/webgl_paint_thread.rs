@@ -4,6 +4,8 @@
 
 use canvas_traits::{CanvasCommonMsg, CanvasData, CanvasMsg, CanvasPixelData};
 use canvas_traits::{FromLayoutMsg, byte_swap};
+use canvas_traits::webgl2::{WebGL2Command, WebGLVersion};
+use canvas_traits::webgl_extensions::WebGLExtensionSupport;
 use euclid::size::Size2D;
 use gleam::gl;
 use ipc_channel::ipc::{self, IpcSender, IpcSharedMemory};
@@ -13,6 +15,8 @@ use std::borrow::ToOwned;
 use std::sync::mpsc::channel;
 use util::opts;
 use util::thread::spawn_named;
+use webgl2;
+use webgl_extensions;
 use webrender_traits;
 
 enum GLContextWrapper {
@@ -73,6 +77,16 @@ impl GLContextWrapper {
         }
     }
 
+    fn is_webgl2_capable(&self) -> bool {
+        self.make_current();
+        webgl2::is_supported()
+    }
+
+    fn query_extension_support(&self, renders_directly: bool) -> WebGLExtensionSupport {
+        self.make_current();
+        webgl_extensions::query_support(renders_directly)
+    }
+
     pub fn apply_command(&self, cmd: webrender_traits::WebGLCommand) {
         match *self {
             GLContextWrapper::Native(ref ctx) => {
@@ -97,10 +111,15 @@ pub struct WebGLPaintThread {
 
 fn create_readback_painter(size: Size2D<i32>,
                            attrs: GLContextAttributes,
+                           version: WebGLVersion,
                            webrender_api: Option<webrender_traits::RenderApi>)
-    -> Result<(WebGLPaintThread, GLLimits), String> {
+    -> Result<(WebGLPaintThread, GLLimits, WebGLExtensionSupport), String> {
     let context = try!(GLContextWrapper::new(size, attrs));
+    if version == WebGLVersion::WebGL2 && !context.is_webgl2_capable() {
+        return Err("The GL backend does not support WebGL 2.0".to_owned());
+    }
     let limits = context.get_limits();
+    let extension_support = context.query_extension_support(true);
     let webrender_api_and_image_key = webrender_api.map(|wr| {
         let key = wr.alloc_image();
         (wr, key)
@@ -110,14 +129,34 @@ fn create_readback_painter(size: Size2D<i32>,
         data: WebGLPaintTaskData::Readback(context, webrender_api_and_image_key)
     };
 
-    Ok((painter, limits))
+    Ok((painter, limits, extension_support))
+}
+
+/// Queries the extensions a driver supports for WebGL threads whose rendering happens in
+/// WebRender, which leaves us without a GL context of our own to ask.
+fn query_webrender_extension_support() -> WebGLExtensionSupport {
+    match GLContextWrapper::new(Size2D::new(1, 1), GLContextAttributes::default()) {
+        Ok(context) => context.query_extension_support(false),
+        Err(msg) => {
+            warn!("Couldn't create a context to query WebGL extensions: {}", msg);
+            WebGLExtensionSupport::default()
+        }
+    }
 }
 
 impl WebGLPaintThread {
     fn new(size: Size2D<i32>,
            attrs: GLContextAttributes,
+           version: WebGLVersion,
            webrender_api_sender: Option<webrender_traits::RenderApiSender>)
-        -> Result<(WebGLPaintThread, GLLimits), String> {
+        -> Result<(WebGLPaintThread, GLLimits, WebGLExtensionSupport), String> {
+        // WebRender only understands WebGL 1.0 commands, so WebGL 2.0 contexts are always
+        // backed by a context of our own.
+        if version == WebGLVersion::WebGL2 {
+            let wr_api = webrender_api_sender.map(|sender| sender.create_api());
+            return create_readback_painter(size, attrs, version, wr_api);
+        }
+
         if let Some(sender) = webrender_api_sender {
             let wr_api = sender.create_api();
             match wr_api.request_webgl_context(&size, attrs) {
@@ -126,15 +165,15 @@ impl WebGLPaintThread {
                         data: WebGLPaintTaskData::WebRender(wr_api, id),
                         size: size
                     };
-                    Ok((painter, limits))
+                    Ok((painter, limits, query_webrender_extension_support()))
                 },
                 Err(msg) => {
                     warn!("Initial context creation failed, falling back to readback: {}", msg);
-                    create_readback_painter(size, attrs, Some(wr_api))
+                    create_readback_painter(size, attrs, version, Some(wr_api))
                 }
             }
         } else {
-            create_readback_painter(size, attrs, None)
+            create_readback_painter(size, attrs, version, None)
         }
     }
 
@@ -150,18 +189,29 @@ impl WebGLPaintThread {
         }
     }
 
+    fn handle_webgl2_message(&self, message: WebGL2Command) {
+        debug!("WebGL 2.0 message: {:?}", message);
+        match self.data {
+            WebGLPaintTaskData::WebRender(..) => {
+                panic!("WebGL 2.0 message sent to a WebRender WebGLThread")
+            }
+            WebGLPaintTaskData::Readback(..) => webgl2::apply(message),
+        }
+    }
+
     /// Creates a new `WebGLPaintThread` and returns an `IpcSender` to
     /// communicate with it.
     pub fn start(size: Size2D<i32>,
                  attrs: GLContextAttributes,
+                 version: WebGLVersion,
                  webrender_api_sender: Option<webrender_traits::RenderApiSender>)
-                 -> Result<(IpcSender<CanvasMsg>, GLLimits), String> {
+                 -> Result<(IpcSender<CanvasMsg>, GLLimits, WebGLExtensionSupport), String> {
         let (sender, receiver) = ipc::channel::<CanvasMsg>().unwrap();
         let (result_chan, result_port) = channel();
         spawn_named("WebGLThread".to_owned(), move || {
-            let mut painter = match WebGLPaintThread::new(size, attrs, webrender_api_sender) {
-                Ok((thread, limits)) => {
-                    result_chan.send(Ok(limits)).unwrap();
+            let mut painter = match WebGLPaintThread::new(size, attrs, version, webrender_api_sender) {
+                Ok((thread, limits, extension_support)) => {
+                    result_chan.send(Ok((limits, extension_support))).unwrap();
                     thread
                 },
                 Err(e) => {
@@ -173,6 +223,7 @@ impl WebGLPaintThread {
             loop {
                 match receiver.recv().unwrap() {
                     CanvasMsg::WebGL(message) => painter.handle_webgl_message(message),
+                    CanvasMsg::WebGL2(message) => painter.handle_webgl2_message(message),
                     CanvasMsg::Common(message) => {
                         match message {
                             CanvasCommonMsg::Close => break,
@@ -191,7 +242,7 @@ impl WebGLPaintThread {
             }
         });
 
-        result_port.recv().unwrap().map(|limits| (sender, limits))
+        result_port.recv().unwrap().map(|(limits, extension_support)| (sender, limits, extension_support))
     }
 
     fn send_data(&mut self, chan: IpcSender<CanvasData>) {
@@ -0,0 +1,234 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::{CanvasData, CanvasMsg, FromLayoutMsg};
+use canvas_traits::webxr::{WebXRError, WebXRMsg, WebXRResult, XREye, XRFrameData};
+use canvas_traits::webxr::{XRRigidTransformData, XRSessionId, XRSessionMode, XRViewData};
+use canvas_traits::webxr::perspective_projection;
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::thread;
+use std::time::{Duration, Instant};
+use util::prefs::PREFS;
+use util::thread::spawn_named;
+
+/// A headset that immersive sessions can present to.
+///
+/// Backends for real hardware implement this; `MockDevice` is used for
+/// testing when the `dom.webxr.test` preference is set.
+pub trait XRDevice {
+    fn supports_mode(&self, mode: XRSessionMode) -> bool;
+
+    /// Starts presenting to the device. Only one session may present at a time.
+    fn start_presenting(&mut self) -> WebXRResult<()>;
+
+    fn stop_presenting(&mut self);
+
+    /// Blocks until the device is ready to display the next frame, and
+    /// returns the poses predicted for it.
+    fn wait_for_frame(&mut self) -> XRFrameData;
+
+    /// Displays the rendered contents of a WebGL drawing buffer.
+    fn submit_frame(&mut self, frame: CanvasData);
+}
+
+/// The height of a standing viewer's eyes, in metres.
+const MOCK_EYE_HEIGHT: f32 = 1.6;
+/// The distance between the viewer's pupils, in metres.
+const MOCK_IPD: f32 = 0.064;
+/// The mock headset displays 90 frames per second.
+const MOCK_FRAME_INTERVAL_MS: u64 = 11;
+/// The viewer turns around once every ten seconds.
+const MOCK_RADIANS_PER_FRAME: f32 = 2. * PI / 900.;
+
+/// A stereo headset whose viewer stands still and slowly turns around.
+pub struct MockDevice {
+    presenting: bool,
+    frame_count: u32,
+    last_frame: Option<Instant>,
+    submitted_frames: u32,
+}
+
+impl MockDevice {
+    pub fn new() -> MockDevice {
+        MockDevice {
+            presenting: false,
+            frame_count: 0,
+            last_frame: None,
+            submitted_frames: 0,
+        }
+    }
+
+    /// The number of frames that have been submitted to the device.
+    pub fn submitted_frames(&self) -> u32 {
+        self.submitted_frames
+    }
+
+    fn view(&self, eye: XREye, viewer: &XRRigidTransformData) -> XRViewData {
+        let offset = match eye {
+            XREye::Left => -MOCK_IPD / 2.,
+            XREye::Right => MOCK_IPD / 2.,
+            XREye::None => 0.,
+        };
+        let eye_offset = XRRigidTransformData {
+            position: [offset, 0., 0.],
+            orientation: [0., 0., 0., 1.],
+        };
+        XRViewData {
+            eye: eye,
+            projection: perspective_projection(PI / 2., 1., 0.1, 1000.),
+            transform: viewer.then(&eye_offset),
+        }
+    }
+}
+
+impl XRDevice for MockDevice {
+    fn supports_mode(&self, mode: XRSessionMode) -> bool {
+        mode == XRSessionMode::ImmersiveVR
+    }
+
+    fn start_presenting(&mut self) -> WebXRResult<()> {
+        if self.presenting {
+            return Err(WebXRError::InvalidState);
+        }
+        self.presenting = true;
+        Ok(())
+    }
+
+    fn stop_presenting(&mut self) {
+        self.presenting = false;
+        self.last_frame = None;
+    }
+
+    fn wait_for_frame(&mut self) -> XRFrameData {
+        let interval = Duration::from_millis(MOCK_FRAME_INTERVAL_MS);
+        if let Some(last_frame) = self.last_frame {
+            let elapsed = last_frame.elapsed();
+            if elapsed < interval {
+                thread::sleep(interval - elapsed);
+            }
+        }
+        self.last_frame = Some(Instant::now());
+        self.frame_count += 1;
+
+        let viewer = XRRigidTransformData::from_yaw([0., MOCK_EYE_HEIGHT, 0.],
+                                                    self.frame_count as f32 * MOCK_RADIANS_PER_FRAME);
+        XRFrameData {
+            viewer: viewer,
+            views: vec![self.view(XREye::Left, &viewer), self.view(XREye::Right, &viewer)],
+        }
+    }
+
+    fn submit_frame(&mut self, _frame: CanvasData) {
+        self.submitted_frames += 1;
+        debug!("Mock XR device received frame {}", self.submitted_frames);
+    }
+}
+
+pub trait WebXRThreadFactory {
+    fn new() -> Self;
+}
+
+impl WebXRThreadFactory for IpcSender<WebXRMsg> {
+    fn new() -> IpcSender<WebXRMsg> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        spawn_named("WebXRThread".to_owned(), move || {
+            WebXRManager::new(receiver, connect_devices()).start();
+        });
+        sender
+    }
+}
+
+/// Connects to the headsets available to this process.
+fn connect_devices() -> Vec<Box<XRDevice>> {
+    let mut devices: Vec<Box<XRDevice>> = vec![];
+    if PREFS.get("dom.webxr.test").as_boolean().unwrap_or(false) {
+        devices.push(Box::new(MockDevice::new()));
+    }
+    devices
+}
+
+pub struct WebXRManager {
+    receiver: IpcReceiver<WebXRMsg>,
+    devices: Vec<Box<XRDevice>>,
+    /// The index of the device each immersive session presents to.
+    sessions: HashMap<XRSessionId, usize>,
+    next_session_id: u32,
+}
+
+impl WebXRManager {
+    pub fn new(receiver: IpcReceiver<WebXRMsg>, devices: Vec<Box<XRDevice>>) -> WebXRManager {
+        WebXRManager {
+            receiver: receiver,
+            devices: devices,
+            sessions: HashMap::new(),
+            next_session_id: 0,
+        }
+    }
+
+    /// Handles messages until `WebXRMsg::Exit` is received.
+    pub fn start(&mut self) {
+        loop {
+            match self.receiver.recv() {
+                Ok(WebXRMsg::IsSessionSupported(mode, sender)) => {
+                    let supported = self.devices.iter().any(|device| device.supports_mode(mode));
+                    let _ = sender.send(supported);
+                },
+                Ok(WebXRMsg::RequestSession(mode, sender)) => {
+                    let _ = sender.send(self.request_session(mode));
+                },
+                Ok(WebXRMsg::RequestFrame(session, sender)) => {
+                    let frame = match self.sessions.get(&session) {
+                        Some(&index) => Ok(self.devices[index].wait_for_frame()),
+                        None => Err(WebXRError::InvalidState),
+                    };
+                    let _ = sender.send(frame);
+                },
+                Ok(WebXRMsg::SubmitFrame(session, renderer)) => {
+                    self.submit_frame(session, renderer);
+                },
+                Ok(WebXRMsg::EndSession(session)) => {
+                    if let Some(index) = self.sessions.remove(&session) {
+                        self.devices[index].stop_presenting();
+                    }
+                },
+                Ok(WebXRMsg::Exit) => break,
+                Err(e) => {
+                    warn!("WebXR thread failed to receive a message ({:?})", e);
+                    break;
+                },
+            }
+        }
+    }
+
+    fn request_session(&mut self, mode: XRSessionMode) -> WebXRResult<XRSessionId> {
+        let index = match self.devices.iter().position(|device| device.supports_mode(mode)) {
+            Some(index) => index,
+            None => return Err(WebXRError::NotSupported),
+        };
+        try!(self.devices[index].start_presenting());
+        let session = XRSessionId(self.next_session_id);
+        self.next_session_id += 1;
+        self.sessions.insert(session, index);
+        Ok(session)
+    }
+
+    /// Fetches the drawing buffer of a WebGL context the same way the
+    /// compositor does, and hands it to the session's device.
+    fn submit_frame(&mut self, session: XRSessionId, renderer: IpcSender<CanvasMsg>) {
+        let index = match self.sessions.get(&session) {
+            Some(&index) => index,
+            None => return,
+        };
+        let (sender, receiver) = ipc::channel().unwrap();
+        if renderer.send(CanvasMsg::FromLayout(FromLayoutMsg::SendData(sender))).is_err() {
+            warn!("Failed to request an XR frame from its WebGL context");
+            return;
+        }
+        if let Ok(frame) = receiver.recv() {
+            self.devices[index].submit_frame(frame);
+        }
+    }
+}
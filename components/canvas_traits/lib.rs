@@ -16,10 +16,15 @@ extern crate cssparser;
 extern crate euclid;
 extern crate gfx_traits;
 extern crate heapsize;
+extern crate image;
 extern crate ipc_channel;
 extern crate serde;
 extern crate webrender_traits;
 
+pub mod webgl2;
+pub mod webgl_extensions;
+pub mod webxr;
+
 use azure::azure::{AzColor, AzFloat};
 use azure::azure_hl::{CapStyle, CompositionOp, JoinStyle};
 use azure::azure_hl::{ColorPattern, DrawTarget, Pattern};
@@ -31,9 +36,13 @@ use euclid::point::Point2D;
 use euclid::rect::Rect;
 use euclid::size::Size2D;
 use gfx_traits::color;
+use image::ColorType;
+use image::jpeg::JPEGEncoder;
+use image::png::PNGEncoder;
 use ipc_channel::ipc::{IpcSender, IpcSharedMemory};
 use std::default::Default;
 use std::str::FromStr;
+use webgl2::WebGL2Command;
 use webrender_traits::{WebGLCommand, WebGLContextId};
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -42,12 +51,34 @@ pub enum FillRule {
     Evenodd,
 }
 
+/// What a `Path2D` is made of, which the canvas builds a path out of when
+/// the path is drawn.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PathSegment {
+    ClosePath,
+    MoveTo(Point2D<f32>),
+    LineTo(Point2D<f32>),
+    QuadraticCurveTo(Point2D<f32>, Point2D<f32>),
+    BezierCurveTo(Point2D<f32>, Point2D<f32>, Point2D<f32>),
+    ArcTo(Point2D<f32>, Point2D<f32>, f32),
+    Rect(Rect<f32>),
+    /// The center, the radius, the start and end angles, and whether the
+    /// arc is anticlockwise.
+    Arc(Point2D<f32>, f32, f32, f32, bool),
+    /// The center, the radii, the rotation, the start and end angles, and
+    /// whether the arc is anticlockwise.
+    Ellipse(Point2D<f32>, f32, f32, f32, f32, f32, bool),
+    /// The segments of another path, which `addPath()` added with a transform.
+    AddPath(Vec<PathSegment>, Matrix2D<f32>),
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub enum CanvasMsg {
     Canvas2d(Canvas2dMsg),
     Common(CanvasCommonMsg),
     FromLayout(FromLayoutMsg),
     WebGL(WebGLCommand),
+    WebGL2(WebGL2Command),
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -77,7 +108,7 @@ pub enum FromLayoutMsg {
 pub enum Canvas2dMsg {
     Arc(Point2D<f32>, f32, f32, f32, bool),
     ArcTo(Point2D<f32>, Point2D<f32>, f32),
-    DrawImage(Vec<u8>, Size2D<f64>, Rect<f64>, Rect<f64>, bool),
+    DrawImage(IpcSharedMemory, Size2D<f64>, Rect<f64>, Rect<f64>, bool),
     DrawImageSelf(Size2D<f64>, Rect<f64>, Rect<f64>, bool),
     DrawImageInOther(
         IpcSender<CanvasMsg>, Size2D<f64>, Rect<f64>, Rect<f64>, bool, IpcSender<()>),
@@ -85,20 +116,25 @@ pub enum Canvas2dMsg {
     BezierCurveTo(Point2D<f32>, Point2D<f32>, Point2D<f32>),
     ClearRect(Rect<f32>),
     Clip,
+    ClipPath2D(Vec<PathSegment>),
     ClosePath,
+    Ellipse(Point2D<f32>, f32, f32, f32, f32, f32, bool),
     Fill,
+    FillPath2D(Vec<PathSegment>),
     FillRect(Rect<f32>),
-    GetImageData(Rect<i32>, Size2D<f64>, IpcSender<Vec<u8>>),
+    GetImageData(Rect<i32>, Size2D<f64>, IpcSender<IpcSharedMemory>),
     IsPointInPath(f64, f64, FillRule, IpcSender<bool>),
+    IsPointInPath2D(Vec<PathSegment>, f64, f64, FillRule, IpcSender<bool>),
     LineTo(Point2D<f32>),
     MoveTo(Point2D<f32>),
-    PutImageData(Vec<u8>, Point2D<f64>, Size2D<f64>, Rect<f64>),
+    PutImageData(IpcSharedMemory, Point2D<f64>, Size2D<f64>, Rect<f64>),
     QuadraticCurveTo(Point2D<f32>, Point2D<f32>),
     Rect(Rect<f32>),
     RestoreContext,
     SaveContext,
     StrokeRect(Rect<f32>),
     Stroke,
+    StrokePath2D(Vec<PathSegment>),
     SetFillStyle(FillOrStrokeStyle),
     SetStrokeStyle(FillOrStrokeStyle),
     SetLineWidth(f32),
@@ -112,6 +148,8 @@ pub enum Canvas2dMsg {
     SetShadowOffsetY(f64),
     SetShadowBlur(f64),
     SetShadowColor(RGBA),
+    /// Encodes the whole canvas, and sends the encoded file back.
+    Encode(ImageEncoding, IpcSender<Vec<u8>>),
 }
 
 #[derive(Clone, Deserialize, Serialize, HeapSizeOf)]
@@ -570,3 +608,56 @@ pub fn byte_swap_and_premultiply(data: &mut [u8]) {
         i += 4;
     }
 }
+
+/// The file formats that the bitmap of a canvas can be serialised to.
+/// https://html.spec.whatwg.org/multipage/#a-serialisation-of-the-bitmap-as-a-file
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum ImageEncoding {
+    Png,
+    /// A JPEG file, with a quality between 0 and 100.
+    Jpeg(u8),
+}
+
+impl ImageEncoding {
+    /// Returns the encoding of the given MIME type, with the given quality between 0 and 1 if
+    /// the format has one. Types that aren't supported, such as image/webp, fall back to PNG.
+    pub fn from_mime_type(mime_type: &str, quality: Option<f64>) -> ImageEncoding {
+        if mime_type.eq_ignore_ascii_case("image/jpeg") {
+            let quality = match quality {
+                Some(quality) if quality >= 0. && quality <= 1. => quality,
+                _ => 0.92,
+            };
+            ImageEncoding::Jpeg((quality * 100.).round() as u8)
+        } else {
+            ImageEncoding::Png
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match *self {
+            ImageEncoding::Png => "image/png",
+            ImageEncoding::Jpeg(_) => "image/jpeg",
+        }
+    }
+}
+
+/// Encodes non-premultiplied RGBA pixels of the given size to a file of the given format.
+pub fn encode_image(pixels: &[u8], size: Size2D<u32>, encoding: ImageEncoding) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    match encoding {
+        ImageEncoding::Png => {
+            let encoder = PNGEncoder::new(&mut encoded);
+            encoder.encode(pixels, size.width, size.height, ColorType::RGBA(8)).unwrap();
+        }
+        ImageEncoding::Jpeg(quality) => {
+            // JPEG has no alpha channel, so the pixels are composited onto opaque black.
+            let rgb: Vec<u8> = pixels.chunks(4).flat_map(|pixel| {
+                let alpha = pixel[3] as u32;
+                (0..3).map(move |i| (pixel[i] as u32 * alpha / 255) as u8)
+            }).collect();
+            let mut encoder = JPEGEncoder::new_with_quality(&mut encoded, quality);
+            encoder.encode(&rgb, size.width, size.height, ColorType::RGB(8)).unwrap();
+        }
+    }
+    encoded
+}
@@ -0,0 +1,63 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use ipc_channel::ipc::IpcSender;
+use webrender_traits::{WebGLBufferId, WebGLProgramId};
+
+/// The version of the WebGL API a context was created for.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, HeapSizeOf, PartialEq, Serialize)]
+pub enum WebGLVersion {
+    WebGL1,
+    WebGL2,
+}
+
+/// Identifies a vertex array object created on a WebGL thread.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, HeapSizeOf, PartialEq, Serialize)]
+pub struct WebGLVertexArrayId(pub u32);
+
+/// Identifies a sampler object created on a WebGL thread.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, HeapSizeOf, PartialEq, Serialize)]
+pub struct WebGLSamplerId(pub u32);
+
+/// Identifies a transform feedback object created on a WebGL thread.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, HeapSizeOf, PartialEq, Serialize)]
+pub struct WebGLTransformFeedbackId(pub u32);
+
+/// The commands a WebGL 2.0 context adds on top of the ones it shares with WebGL 1.0, which are
+/// sent as `WebGLCommand`s. WebGL 1.0 extensions that expose the same functionality send them
+/// too. WebRender does not know about these, so they are only accepted by WebGL threads that
+/// render through their own GL context.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum WebGL2Command {
+    CreateVertexArray(IpcSender<Option<WebGLVertexArrayId>>),
+    DeleteVertexArray(WebGLVertexArrayId),
+    BindVertexArray(Option<WebGLVertexArrayId>),
+    /// The mode, first vertex, count and instance count.
+    DrawArraysInstanced(u32, i32, i32, i32),
+    /// The mode, count, index type, offset and instance count.
+    DrawElementsInstanced(u32, i32, u32, i64, i32),
+    VertexAttribDivisor(u32, u32),
+    /// The target, level, internal format, width, height, depth, format, type and pixels.
+    TexImage3D(u32, i32, i32, i32, i32, i32, u32, u32, Vec<u8>),
+    /// The target, level, x, y and z offsets, width, height, depth, format, type and pixels.
+    TexSubImage3D(u32, i32, i32, i32, i32, i32, i32, i32, u32, u32, Vec<u8>),
+    BindBufferBase(u32, u32, Option<WebGLBufferId>),
+    /// The target, index, buffer, offset and size.
+    BindBufferRange(u32, u32, Option<WebGLBufferId>, isize, isize),
+    GetUniformBlockIndex(WebGLProgramId, String, IpcSender<u32>),
+    UniformBlockBinding(WebGLProgramId, u32, u32),
+    CreateTransformFeedback(IpcSender<Option<WebGLTransformFeedbackId>>),
+    DeleteTransformFeedback(WebGLTransformFeedbackId),
+    BindTransformFeedback(u32, Option<WebGLTransformFeedbackId>),
+    BeginTransformFeedback(u32),
+    EndTransformFeedback,
+    PauseTransformFeedback,
+    ResumeTransformFeedback,
+    TransformFeedbackVaryings(WebGLProgramId, Vec<String>, u32),
+    CreateSampler(IpcSender<Option<WebGLSamplerId>>),
+    DeleteSampler(WebGLSamplerId),
+    BindSampler(u32, Option<WebGLSamplerId>),
+    SamplerParameteri(WebGLSamplerId, u32, i32),
+    SamplerParameterf(WebGLSamplerId, u32, f32),
+}
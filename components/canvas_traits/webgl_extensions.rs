@@ -0,0 +1,19 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+/// The WebGL extensions the GL driver behind a WebGL thread is able to back. Script only
+/// advertises the extensions that are set here.
+#[derive(Clone, Copy, Debug, Default, Deserialize, HeapSizeOf, PartialEq, Serialize)]
+pub struct WebGLExtensionSupport {
+    /// `ANGLE_instanced_arrays`.
+    pub instanced_arrays: bool,
+    /// `OES_texture_float`.
+    pub texture_float: bool,
+    /// `OES_element_index_uint`.
+    pub element_index_uint: bool,
+    /// `EXT_texture_filter_anisotropic`, with the driver's `MAX_TEXTURE_MAX_ANISOTROPY_EXT`.
+    pub max_texture_anisotropy: Option<f32>,
+    /// `OES_vertex_array_object`.
+    pub vertex_array_object: bool,
+}
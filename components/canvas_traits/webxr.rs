@@ -0,0 +1,159 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use CanvasMsg;
+use ipc_channel::ipc::IpcSender;
+
+/// https://immersive-web.github.io/webxr/#xrsessionmode-enum
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum XRSessionMode {
+    Inline,
+    ImmersiveVR,
+}
+
+/// Identifies an immersive session started on the WebXR thread.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct XRSessionId(pub u32);
+
+/// https://immersive-web.github.io/webxr/#enumdef-xreye
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum XREye {
+    None,
+    Left,
+    Right,
+}
+
+/// A position, in metres, and an orientation, as a unit quaternion `[x, y, z, w]`.
+/// https://immersive-web.github.io/webxr/#xrrigidtransform
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct XRRigidTransformData {
+    pub position: [f32; 3],
+    pub orientation: [f32; 4],
+}
+
+impl XRRigidTransformData {
+    pub fn identity() -> XRRigidTransformData {
+        XRRigidTransformData {
+            position: [0., 0., 0.],
+            orientation: [0., 0., 0., 1.],
+        }
+    }
+
+    /// A rotation of `angle` radians around the Y axis, followed by a translation.
+    pub fn from_yaw(position: [f32; 3], angle: f32) -> XRRigidTransformData {
+        let half = angle / 2.;
+        XRRigidTransformData {
+            position: position,
+            orientation: [0., half.sin(), 0., half.cos()],
+        }
+    }
+
+    fn quaternion(&self) -> (f32, f32, f32, f32) {
+        (self.orientation[0], self.orientation[1], self.orientation[2], self.orientation[3])
+    }
+
+    /// Rotates `v` by this transform's orientation.
+    fn rotate(&self, v: [f32; 3]) -> [f32; 3] {
+        let (x, y, z, w) = self.quaternion();
+        // t = 2 * cross(q.xyz, v)
+        let t = [2. * (y * v[2] - z * v[1]),
+                 2. * (z * v[0] - x * v[2]),
+                 2. * (x * v[1] - y * v[0])];
+        // v + w * t + cross(q.xyz, t)
+        [v[0] + w * t[0] + (y * t[2] - z * t[1]),
+         v[1] + w * t[1] + (z * t[0] - x * t[2]),
+         v[2] + w * t[2] + (x * t[1] - y * t[0])]
+    }
+
+    /// The transform that applies `other` first, then `self`.
+    pub fn then(&self, other: &XRRigidTransformData) -> XRRigidTransformData {
+        let (ax, ay, az, aw) = self.quaternion();
+        let (bx, by, bz, bw) = other.quaternion();
+        let rotated = self.rotate(other.position);
+        XRRigidTransformData {
+            position: [self.position[0] + rotated[0],
+                       self.position[1] + rotated[1],
+                       self.position[2] + rotated[2]],
+            orientation: [aw * bx + ax * bw + ay * bz - az * by,
+                          aw * by - ax * bz + ay * bw + az * bx,
+                          aw * bz + ax * by - ay * bx + az * bw,
+                          aw * bw - ax * bx - ay * by - az * bz],
+        }
+    }
+
+    /// https://immersive-web.github.io/webxr/#dom-xrrigidtransform-inverse
+    pub fn inverse(&self) -> XRRigidTransformData {
+        let (x, y, z, w) = self.quaternion();
+        let conjugate = XRRigidTransformData {
+            position: [0., 0., 0.],
+            orientation: [-x, -y, -z, w],
+        };
+        let position = conjugate.rotate(self.position);
+        XRRigidTransformData {
+            position: [-position[0], -position[1], -position[2]],
+            orientation: conjugate.orientation,
+        }
+    }
+
+    /// The column-major 4x4 matrix of this transform.
+    /// https://immersive-web.github.io/webxr/#dom-xrrigidtransform-matrix
+    pub fn to_matrix(&self) -> [f32; 16] {
+        let (x, y, z, w) = self.quaternion();
+        let (px, py, pz) = (self.position[0], self.position[1], self.position[2]);
+        [1. - 2. * (y * y + z * z), 2. * (x * y + z * w), 2. * (x * z - y * w), 0.,
+         2. * (x * y - z * w), 1. - 2. * (x * x + z * z), 2. * (y * z + x * w), 0.,
+         2. * (x * z + y * w), 2. * (y * z - x * w), 1. - 2. * (x * x + y * y), 0.,
+         px, py, pz, 1.]
+    }
+}
+
+/// Builds a column-major perspective projection matrix.
+pub fn perspective_projection(vertical_fov: f32, aspect: f32, near: f32, far: f32) -> [f32; 16] {
+    let f = 1. / (vertical_fov / 2.).tan();
+    let range = 1. / (near - far);
+    [f / aspect, 0., 0., 0.,
+     0., f, 0., 0.,
+     0., 0., (far + near) * range, -1.,
+     0., 0., 2. * far * near * range, 0.]
+}
+
+/// A view of the scene rendered for one eye.
+/// https://immersive-web.github.io/webxr/#xrview
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct XRViewData {
+    pub eye: XREye,
+    pub projection: [f32; 16],
+    /// The pose of the eye in the device's local space.
+    pub transform: XRRigidTransformData,
+}
+
+/// The poses predicted by a device for the frame it is about to display.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct XRFrameData {
+    /// The pose of the viewer in the device's local space.
+    pub viewer: XRRigidTransformData,
+    pub views: Vec<XRViewData>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum WebXRError {
+    /// No connected device supports the requested session mode.
+    NotSupported,
+    /// The device is already presenting, or the session has ended.
+    InvalidState,
+}
+
+pub type WebXRResult<T> = Result<T, WebXRError>;
+
+#[derive(Deserialize, Serialize)]
+pub enum WebXRMsg {
+    IsSessionSupported(XRSessionMode, IpcSender<bool>),
+    RequestSession(XRSessionMode, IpcSender<WebXRResult<XRSessionId>>),
+    /// Waits for the device to be ready for the next frame and replies with its poses.
+    RequestFrame(XRSessionId, IpcSender<WebXRResult<XRFrameData>>),
+    /// Presents the current contents of a WebGL context's drawing buffer.
+    SubmitFrame(XRSessionId, IpcSender<CanvasMsg>),
+    EndSession(XRSessionId),
+    Exit,
+}
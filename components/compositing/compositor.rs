@@ -14,7 +14,8 @@ use euclid::point::TypedPoint2D;
 use euclid::rect::TypedRect;
 use euclid::scale_factor::ScaleFactor;
 use euclid::size::TypedSize2D;
-use gfx_traits::{ChromeToPaintMsg, PaintRequest, ScrollPolicy, StackingContextId};
+use gfx_traits::{ChromeToPaintMsg, PaintRequest, ScrollContainerScrollbars, ScrollPolicy, ScrollbarAxis};
+use gfx_traits::{ScrollbarInfo, ScrollbarPress, StackingContextId};
 use gfx_traits::{color, Epoch, FrameTreeId, FragmentType, LayerId, LayerKind, LayerProperties};
 use gfx_traits::print_tree::PrintTree;
 use gleam::gl;
@@ -55,7 +56,8 @@ use util::opts;
 use util::prefs::PREFS;
 use webrender;
 use webrender_traits::{self, ScrollEventPhase};
-use windowing::{self, MouseWindowEvent, WindowEvent, WindowMethods, WindowNavigateMsg};
+use windowing::{self, DialogResponder, MouseWindowEvent, ShareResponder, WindowEvent, WindowMethods};
+use windowing::WindowNavigateMsg;
 
 #[derive(Debug, PartialEq)]
 enum UnableToComposite {
@@ -242,6 +244,9 @@ pub struct IOCompositor<Window: WindowMethods> {
     /// Whether a scroll is in progress; i.e. whether the user's fingers are down.
     scroll_in_progress: bool,
 
+    /// The scrollbar thumb that the user is dragging, if any.
+    scrollbar_drag: Option<ScrollbarDrag>,
+
     /// The webrender renderer, if enabled.
     webrender: Option<webrender::Renderer>,
 
@@ -284,6 +289,41 @@ struct HitTestResult {
     point: TypedPoint2D<f32, LayerPixel>,
 }
 
+/// A scroll container whose scrollbars the compositor hit tests.
+#[derive(Clone, Copy, PartialEq)]
+enum ScrollContainer {
+    /// A scroll container with a layer of its own, which is scrolled by moving the layer.
+    Layer(PipelineId, LayerId),
+    /// A scroll container that WebRender renders. WebRender scrolls the scroll layer under a
+    /// point, so this keeps the point in device pixels at which its scrollbar was pressed.
+    WebRender(PipelineId, StackingContextId, TypedPoint2D<i32, DevicePixel>),
+}
+
+struct ScrollbarHitTestResult {
+    /// The scroll container whose scrollbar contains the requested point
+    container: ScrollContainer,
+    /// The scrollbars of the scroll container
+    scrollbars: ScrollbarInfo,
+    /// The scrollbar containing the requested point
+    axis: ScrollbarAxis,
+    /// The point in the coordinate system of the scrollbars
+    point: Point2D<f32>,
+    /// How far the scroll container is scrolled
+    scroll_position: Point2D<f32>,
+}
+
+/// A drag of the thumb of a scrollbar, which scrolls its scroll container as the mouse moves.
+#[derive(Clone, Copy)]
+struct ScrollbarDrag {
+    container: ScrollContainer,
+    scrollbars: ScrollbarInfo,
+    axis: ScrollbarAxis,
+    /// The scroll position of the scroll container when the drag started.
+    start_scroll_position: Point2D<f32>,
+    /// The point at which the drag started, in the coordinate system of the root layer.
+    start_point: TypedPoint2D<f32, LayerPixel>,
+}
+
 struct PipelineDetails {
     /// The pipeline associated with this PipelineDetails object.
     pipeline: Option<CompositionPipeline>,
@@ -299,6 +339,9 @@ struct PipelineDetails {
 
     /// Whether this pipeline is visible
     visible: bool,
+
+    /// The scrollbars of the scroll containers of this pipeline, if WebRender renders it.
+    scrollbars: Vec<ScrollContainerScrollbars>,
 }
 
 impl PipelineDetails {
@@ -309,6 +352,7 @@ impl PipelineDetails {
             animations_running: false,
             animation_callbacks_running: false,
             visible: true,
+            scrollbars: Vec::new(),
         }
     }
 }
@@ -497,6 +541,7 @@ impl<Window: WindowMethods> IOCompositor<Window> {
             pending_subpages: HashSet::new(),
             last_mouse_move_recipient: None,
             scroll_in_progress: false,
+            scrollbar_drag: None,
             webrender: state.webrender,
             webrender_api: webrender_api,
         }
@@ -580,6 +625,10 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                 self.change_running_animations_state(pipeline_id, animation_state);
             }
 
+            (Msg::SetScrollbars(pipeline_id, scrollbars), ShutdownState::NotShuttingDown) => {
+                self.pipeline_details(pipeline_id).scrollbars = scrollbars;
+            }
+
             (Msg::ChangePageTitle(pipeline_id, title), ShutdownState::NotShuttingDown) => {
                 self.change_page_title(pipeline_id, title);
             }
@@ -663,6 +712,47 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                 }
             }
 
+            (Msg::PromptMediaPermission(origin, audio, video, send),
+             ShutdownState::NotShuttingDown) => {
+                let granted = self.window.prompt_media_permission(origin, audio, video);
+                if let Err(e) = send.send(granted) {
+                    warn!("Sending response to media permission prompt failed ({}).", e);
+                }
+            }
+
+            (Msg::PromptPersistentStorage(origin, send), ShutdownState::NotShuttingDown) => {
+                let granted = self.window.allow_persistent_storage(origin);
+                if let Err(e) = send.send(granted) {
+                    warn!("Sending response to persistent storage request failed ({}).", e);
+                }
+            }
+
+            (Msg::PromptUsbDevice(origin, devices, send), ShutdownState::NotShuttingDown) => {
+                let chosen = self.window.choose_usb_device(origin, devices);
+                if let Err(e) = send.send(chosen) {
+                    warn!("Sending response to USB device chooser failed ({}).", e);
+                }
+            }
+
+            (Msg::ShowInputPicker(kind, value, send), ShutdownState::NotShuttingDown) => {
+                let picked = self.window.show_input_picker(kind, value);
+                if let Err(e) = send.send(picked) {
+                    warn!("Sending response to input picker failed ({}).", e);
+                }
+            }
+
+            (Msg::ShowDialog(origin, dialog, send), ShutdownState::NotShuttingDown) => {
+                self.window.show_dialog(origin, dialog, DialogResponder::new(send));
+            }
+
+            (Msg::Share(origin, data, send), ShutdownState::NotShuttingDown) => {
+                self.window.share(origin, data, ShareResponder::new(send));
+            }
+
+            (Msg::AudioPlaybackChanged(audible), ShutdownState::NotShuttingDown) => {
+                self.window.audio_playback_changed(audible);
+            }
+
             (Msg::Status(message), ShutdownState::NotShuttingDown) => {
                 self.window.status(message);
             }
@@ -923,6 +1013,8 @@ impl<Window: WindowMethods> IOCompositor<Window> {
             subpage_pipeline_id: None,
             establishes_3d_context: true,
             scrolls_overflow_area: false,
+            sticky: None,
+            scrollbars: None,
         };
 
         let root_layer = CompositorData::new_layer(pipeline.id,
@@ -1128,6 +1220,8 @@ impl<Window: WindowMethods> IOCompositor<Window> {
             subpage_pipeline_id: Some(subpage_pipeline_id),
             establishes_3d_context: true,
             scrolls_overflow_area: true,
+            sticky: None,
+            scrollbars: None,
         };
 
         let wants_scroll_events = if subpage_layer_properties.scrolls_overflow_area {
@@ -1364,6 +1458,20 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                     warn!("Sending reload to constellation failed ({}).", e);
                 }
             }
+
+            WindowEvent::Stop => {
+                let msg = ConstellationMsg::Stop;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending stop to constellation failed ({}).", e);
+                }
+            }
+
+            WindowEvent::SetMuted(muted) => {
+                let msg = ConstellationMsg::SetMuted(muted);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending mute to constellation failed ({}).", e);
+                }
+            }
         }
     }
 
@@ -1421,6 +1529,10 @@ impl<Window: WindowMethods> IOCompositor<Window> {
             MouseWindowEvent::MouseUp(_, p) => p,
         };
 
+        if self.handle_scrollbar_mouse_event(&mouse_window_event, point) {
+            return
+        }
+
         if self.webrender_api.is_some() {
             let root_pipeline_id = match self.get_root_pipeline_id() {
                 Some(root_pipeline_id) => root_pipeline_id,
@@ -1441,7 +1553,7 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                         MouseButtonEvent(MouseEventType::MouseUp, button, translated_point)
                     }
                 };
-                let msg = ConstellationControlMsg::SendEvent(root_pipeline_id, event_to_send);
+                let msg = ConstellationControlMsg::SendEvent(root_pipeline_id, event_to_send, precise_time_ns());
                 if let Err(e) = pipeline.script_chan.send(msg) {
                     warn!("Sending control event to script failed ({}).", e);
                 }
@@ -1455,12 +1567,123 @@ impl<Window: WindowMethods> IOCompositor<Window> {
         }
     }
 
+    /// Lets the user drag the thumb of a scrollbar and click its track to scroll by a page.
+    /// Returns true if the event was used by a scrollbar, in which case content doesn't see it.
+    fn handle_scrollbar_mouse_event(&mut self,
+                                    mouse_window_event: &MouseWindowEvent,
+                                    point: TypedPoint2D<f32, DevicePixel>)
+                                    -> bool {
+        if let MouseWindowEvent::MouseUp(..) = *mouse_window_event {
+            if self.scrollbar_drag.take().is_some() {
+                return true
+            }
+        }
+
+        let result = match self.find_scrollbar_at_point(point) {
+            Some(result) => result,
+            None => return false,
+        };
+        match *mouse_window_event {
+            MouseWindowEvent::MouseDown(MouseButton::Left, _) => {}
+            _ => return true,
+        }
+
+        match result.scrollbars.press(result.axis, &result.point, &result.scroll_position) {
+            Some(ScrollbarPress::DragThumb) => {
+                self.scrollbar_drag = Some(ScrollbarDrag {
+                    container: result.container,
+                    scrollbars: result.scrollbars,
+                    axis: result.axis,
+                    start_scroll_position: result.scroll_position,
+                    start_point: point / self.scene.scale,
+                })
+            }
+            Some(ScrollbarPress::ScrollTo(position)) => {
+                self.scroll_container_to(result.container, &result.scroll_position, &position)
+            }
+            None => {}
+        }
+        true
+    }
+
+    /// Scrolls the scroll container whose thumb is being dragged so that the thumb follows the
+    /// mouse.
+    fn drag_scrollbar_thumb(&mut self, drag: ScrollbarDrag, point: TypedPoint2D<f32, LayerPixel>) {
+        let current_position = match self.scroll_position(drag.container) {
+            Some(position) => position,
+            None => {
+                self.scrollbar_drag = None;
+                return
+            }
+        };
+        let pointer_delta = (point - drag.start_point).to_untyped();
+        let pointer_delta = match drag.axis {
+            ScrollbarAxis::Horizontal => pointer_delta.x,
+            ScrollbarAxis::Vertical => pointer_delta.y,
+        };
+        let new_position = drag.scrollbars.drag_thumb(drag.axis, &drag.start_scroll_position, pointer_delta);
+        self.scroll_container_to(drag.container, &current_position, &new_position);
+    }
+
+    /// Returns how far a scroll container is scrolled, or `None` if it is gone.
+    fn scroll_position(&self, container: ScrollContainer) -> Option<Point2D<f32>> {
+        match container {
+            ScrollContainer::Layer(pipeline_id, layer_id) => {
+                self.find_layer_with_pipeline_and_layer_id(pipeline_id, layer_id).map(|layer| {
+                    (TypedPoint2D::zero() - layer.extra_data.borrow().scroll_offset).to_untyped()
+                })
+            }
+            ScrollContainer::WebRender(pipeline_id, stacking_context_id, _) => {
+                self.webrender_scroll_offsets(pipeline_id)
+                    .get(&stacking_context_id)
+                    .map(|offset| Point2D::zero() - *offset)
+            }
+        }
+    }
+
+    fn scroll_container_to(&mut self,
+                           container: ScrollContainer,
+                           current_position: &Point2D<f32>,
+                           position: &Point2D<f32>) {
+        match container {
+            ScrollContainer::Layer(pipeline_id, layer_id) => {
+                if self.move_layer(pipeline_id, layer_id, TypedPoint2D::from_untyped(position)) {
+                    self.perform_updates_after_scroll();
+                    self.send_viewport_rects_for_all_layers()
+                }
+            }
+            ScrollContainer::WebRender(_, _, cursor) => {
+                // Scroll offsets are the negation of scroll positions.
+                let delta = TypedPoint2D::from_untyped(&(*current_position - *position)) * self.scene.scale;
+                self.on_scroll_window_event(delta, cursor)
+            }
+        }
+    }
+
+    /// Returns the scroll offsets of the stacking contexts of a pipeline that WebRender renders.
+    fn webrender_scroll_offsets(&self, pipeline_id: PipelineId) -> HashMap<StackingContextId, Point2D<f32>> {
+        let mut offsets = HashMap::new();
+        if let Some(ref webrender_api) = self.webrender_api {
+            for scroll_layer_state in webrender_api.get_scroll_layer_state() {
+                if scroll_layer_state.pipeline_id.from_webrender() == pipeline_id {
+                    offsets.insert(scroll_layer_state.stacking_context_id.from_webrender(),
+                                   scroll_layer_state.scroll_offset);
+                }
+            }
+        }
+        offsets
+    }
+
     fn on_mouse_window_move_event_class(&mut self, cursor: TypedPoint2D<f32, DevicePixel>) {
         if opts::get().convert_mouse_to_touch {
             self.on_touch_move(TouchId(0), cursor);
             return
         }
 
+        if let Some(drag) = self.scrollbar_drag {
+            return self.drag_scrollbar_thumb(drag, cursor / self.scene.scale)
+        }
+
         if self.webrender_api.is_some() {
             let root_pipeline_id = match self.get_root_pipeline_id() {
                 Some(root_pipeline_id) => root_pipeline_id,
@@ -1472,7 +1695,7 @@ impl<Window: WindowMethods> IOCompositor<Window> {
 
             let dppx = self.page_zoom * self.device_pixels_per_screen_px();
             let event_to_send = MouseMoveEvent(Some((cursor / dppx).to_untyped()));
-            let msg = ConstellationControlMsg::SendEvent(root_pipeline_id, event_to_send);
+            let msg = ConstellationControlMsg::SendEvent(root_pipeline_id, event_to_send, precise_time_ns());
             if let Some(pipeline) = self.pipeline(root_pipeline_id) {
                 if let Err(e) = pipeline.script_chan.send(msg) {
                     warn!("Sending mouse control event to script failed ({}).", e);
@@ -1491,7 +1714,8 @@ impl<Window: WindowMethods> IOCompositor<Window> {
                             let _ = pipeline.script_chan
                                             .send(ConstellationControlMsg::SendEvent(
                                                 last_pipeline_id.clone(),
-                                                MouseMoveEvent(None)));
+                                                MouseMoveEvent(None),
+                                                precise_time_ns()));
                         }
                     }
                 }
@@ -2443,6 +2667,91 @@ impl<Window: WindowMethods> IOCompositor<Window> {
         }
     }
 
+    fn find_scrollbar_at_point_for_layer(&self,
+                                         layer: Rc<Layer<CompositorData>>,
+                                         point_in_parent_layer: TypedPoint2D<f32, LayerPixel>)
+                                         -> Option<ScrollbarHitTestResult> {
+        let layer_bounds = *layer.bounds.borrow();
+        let point = point_in_parent_layer - layer_bounds.origin;
+
+        // Scrollbars are drawn over the content that they scroll.
+        if layer_bounds.contains(&point_in_parent_layer) {
+            let extra_data = layer.extra_data.borrow();
+            if let Some(scrollbars) = extra_data.scrollbars {
+                if let Some(axis) = scrollbars.hit_test(&point.to_untyped()) {
+                    return Some(ScrollbarHitTestResult {
+                        container: ScrollContainer::Layer(extra_data.pipeline_id, extra_data.id),
+                        scrollbars: scrollbars,
+                        axis: axis,
+                        point: point.to_untyped(),
+                        scroll_position: (TypedPoint2D::zero() - extra_data.scroll_offset).to_untyped(),
+                    })
+                }
+            }
+        }
+
+        let child_point = point - layer.extra_data.borrow().scroll_offset;
+        for child in layer.children().iter().rev() {
+            if let Some(result) = self.find_scrollbar_at_point_for_layer(child.clone(), child_point) {
+                return Some(result)
+            }
+        }
+        None
+    }
+
+    /// Returns the scrollbar of the root pipeline at `point`, when WebRender renders it.
+    fn find_webrender_scrollbar_at_point(&self, point: TypedPoint2D<f32, DevicePixel>)
+                                         -> Option<ScrollbarHitTestResult> {
+        let root_pipeline_id = match self.get_root_pipeline_id() {
+            Some(root_pipeline_id) => root_pipeline_id,
+            None => return None,
+        };
+        let scroll_containers = match self.pipeline_details.get(&root_pipeline_id) {
+            Some(details) if !details.scrollbars.is_empty() => &details.scrollbars,
+            _ => return None,
+        };
+
+        let cursor = match point.cast() {
+            Some(cursor) => cursor,
+            None => return None,
+        };
+        let dppx = self.page_zoom * self.device_pixels_per_screen_px();
+        let client_point = (point / dppx).to_untyped();
+        let scroll_offsets = self.webrender_scroll_offsets(root_pipeline_id);
+        let scroll_offset = |id: &StackingContextId| scroll_offsets.get(id).cloned().unwrap_or(Point2D::zero());
+
+        // Later scroll containers are painted over earlier ones.
+        for scroll_container in scroll_containers.iter().rev() {
+            let scrolled_point = scroll_container.scrolled_by.iter().fold(client_point, |point, id| {
+                point - scroll_offset(id)
+            });
+            let point = scrolled_point - scroll_container.origin;
+            if let Some(axis) = scroll_container.scrollbars.hit_test(&point) {
+                let id = scroll_container.stacking_context_id;
+                return Some(ScrollbarHitTestResult {
+                    container: ScrollContainer::WebRender(root_pipeline_id, id, cursor),
+                    scrollbars: scroll_container.scrollbars,
+                    axis: axis,
+                    point: point,
+                    scroll_position: Point2D::zero() - scroll_offset(&id),
+                })
+            }
+        }
+        None
+    }
+
+    fn find_scrollbar_at_point(&self,
+                               point: TypedPoint2D<f32, DevicePixel>)
+                               -> Option<ScrollbarHitTestResult> {
+        if self.webrender_api.is_some() {
+            return self.find_webrender_scrollbar_at_point(point)
+        }
+        match self.scene.root {
+            Some(ref layer) => self.find_scrollbar_at_point_for_layer(layer.clone(), point / self.scene.scale),
+            None => None,
+        }
+    }
+
     fn find_layer_with_pipeline_and_layer_id(&self,
                                              pipeline_id: PipelineId,
                                              layer_id: LayerId)
@@ -7,7 +7,7 @@ use compositor::IOCompositor;
 use euclid::point::TypedPoint2D;
 use euclid::rect::TypedRect;
 use euclid::size::TypedSize2D;
-use gfx_traits::{Epoch, LayerId, LayerProperties, ScrollPolicy};
+use gfx_traits::{Epoch, LayerId, LayerProperties, ScrollPolicy, ScrollbarInfo, StickyFrameInfo};
 use layers::color::Color;
 use layers::geometry::LayerPixel;
 use layers::layers::{Layer, LayerBufferSet};
@@ -18,6 +18,7 @@ use script_traits::ConstellationControlMsg;
 use script_traits::MouseEventType;
 use script_traits::TouchpadPressurePhase;
 use std::rc::Rc;
+use time::precise_time_ns;
 use windowing::{MouseWindowEvent, WindowMethods};
 
 #[derive(Debug)]
@@ -47,6 +48,14 @@ pub struct CompositorData {
 
     /// The pipeline ID of this layer, if it represents a subpage.
     pub subpage_info: Option<PipelineId>,
+
+    /// The constraints of this layer if it is sticky positioned. Sticky layers are moved on
+    /// scroll without waiting for layout.
+    pub sticky: Option<StickyFrameInfo>,
+
+    /// The scrollbars painted in this layer, which the compositor hit tests so that they can be
+    /// used without waiting for script.
+    pub scrollbars: Option<ScrollbarInfo>,
 }
 
 impl CompositorData {
@@ -64,6 +73,8 @@ impl CompositorData {
             painted_epoch: Epoch(0),
             scroll_offset: TypedPoint2D::zero(),
             subpage_info: layer_properties.subpage_pipeline_id,
+            sticky: layer_properties.sticky,
+            scrollbars: layer_properties.scrollbars,
         };
 
         Rc::new(Layer::new(TypedRect::from_untyped(&layer_properties.rect),
@@ -150,8 +161,11 @@ pub trait CompositorLayer {
                                             new_offset: TypedPoint2D<f32, LayerPixel>)
                                             -> ScrollEventResult;
 
+    /// Moves this layer and its descendants to `new_offset`. `viewport` is the visible part of
+    /// the nearest scrolling ancestor, in the coordinate system of this layer's parent.
     fn scroll_layer_and_all_child_layers(&self,
-                                         new_offset: TypedPoint2D<f32, LayerPixel>)
+                                         new_offset: TypedPoint2D<f32, LayerPixel>,
+                                         viewport: &TypedRect<f32, LayerPixel>)
                                          -> bool;
 
     /// Return a flag describing how this layer deals with scroll events.
@@ -219,6 +233,8 @@ impl CompositorLayer for Layer<CompositorData> {
     fn update_layer_except_bounds(&self, layer_properties: LayerProperties) {
         self.extra_data.borrow_mut().scroll_policy = layer_properties.scroll_policy;
         self.extra_data.borrow_mut().subpage_info = layer_properties.subpage_pipeline_id;
+        self.extra_data.borrow_mut().sticky = layer_properties.sticky;
+        self.extra_data.borrow_mut().scrollbars = layer_properties.scrollbars;
         *self.transform.borrow_mut() = layer_properties.transform;
         *self.perspective.borrow_mut() = layer_properties.perspective;
 
@@ -361,9 +377,10 @@ impl CompositorLayer for Layer<CompositorData> {
         // but scroll_layer_and_all_child_layers actually moves the child layers.
         self.extra_data.borrow_mut().scroll_offset = new_offset;
 
+        let viewport = TypedRect::new(TypedPoint2D::zero() - new_offset, layer_size);
         let mut result = false;
         for child in &*self.children() {
-            result |= child.scroll_layer_and_all_child_layers(new_offset);
+            result |= child.scroll_layer_and_all_child_layers(new_offset, &viewport);
         }
 
         if result {
@@ -402,7 +419,7 @@ impl CompositorLayer for Layer<CompositorData> {
                           event: CompositorEvent) where Window: WindowMethods {
         if let Some(pipeline) = compositor.pipeline(self.pipeline_id()) {
             let _ = pipeline.script_chan
-                    .send(ConstellationControlMsg::SendEvent(pipeline.id.clone(), event));
+                    .send(ConstellationControlMsg::SendEvent(pipeline.id.clone(), event, precise_time_ns()));
         }
     }
 
@@ -414,14 +431,27 @@ impl CompositorLayer for Layer<CompositorData> {
                                             where Window: WindowMethods {
         if let Some(pipeline) = compositor.pipeline(self.pipeline_id()) {
             let message = TouchpadPressureEvent(cursor.to_untyped(), pressure, phase);
-            let _ = pipeline.script_chan.send(ConstellationControlMsg::SendEvent(pipeline.id.clone(), message));
+            let msg = ConstellationControlMsg::SendEvent(pipeline.id.clone(), message, precise_time_ns());
+            let _ = pipeline.script_chan.send(msg);
         }
     }
 
-    fn scroll_layer_and_all_child_layers(&self, new_offset: TypedPoint2D<f32, LayerPixel>)
+    fn scroll_layer_and_all_child_layers(&self,
+                                         new_offset: TypedPoint2D<f32, LayerPixel>,
+                                         viewport: &TypedRect<f32, LayerPixel>)
                                          -> bool {
         let mut result = false;
 
+        // Sticky layers are pushed back into the viewport, within the bounds of their
+        // containing block.
+        let sticky_offset = match self.extra_data.borrow().sticky {
+            Some(ref sticky) => {
+                TypedPoint2D::from_untyped(&sticky.offset_for_viewport(&viewport.to_untyped()))
+            }
+            None => TypedPoint2D::zero(),
+        };
+        let new_offset = new_offset + sticky_offset;
+
         // Only scroll this layer if it's not fixed-positioned.
         if self.extra_data.borrow().scroll_policy != ScrollPolicy::FixedPosition {
             let new_offset = new_offset.to_untyped();
@@ -429,9 +459,13 @@ impl CompositorLayer for Layer<CompositorData> {
             result = true
         }
 
-        let offset_for_children = new_offset + self.extra_data.borrow().scroll_offset;
+        let scroll_offset = self.extra_data.borrow().scroll_offset;
+        let offset_for_children = new_offset + scroll_offset;
+        let viewport_for_children =
+            viewport.translate(&(TypedPoint2D::zero() - self.bounds.borrow().origin - scroll_offset));
         for child in &*self.children() {
-            result |= child.scroll_layer_and_all_child_layers(offset_for_children);
+            result |= child.scroll_layer_and_all_child_layers(offset_for_children,
+                                                              &viewport_for_children);
         }
 
         result
@@ -8,14 +8,15 @@ use SendableFrameTree;
 use compositor::CompositingReason;
 use euclid::point::Point2D;
 use euclid::size::Size2D;
-use gfx_traits::{Epoch, FrameTreeId, LayerId, LayerProperties, PaintListener};
+use gfx_traits::{Epoch, FrameTreeId, LayerId, LayerProperties, PaintListener, ScrollContainerScrollbars};
 use ipc_channel::ipc::IpcSender;
 use layers::layers::{BufferRequest, LayerBufferSet};
 use layers::platform::surface::{NativeDisplay, NativeSurface};
 use msg::constellation_msg::{Image, Key, KeyModifiers, KeyState, PipelineId};
 use profile_traits::mem;
 use profile_traits::time;
-use script_traits::{AnimationState, ConstellationMsg, EventResult};
+use script_traits::{AnimationState, ConstellationMsg, DialogResponse, EventResult, InputPickerKind};
+use script_traits::{ShareData, ShareError, SimpleDialog};
 use std::fmt::{Debug, Error, Formatter};
 use std::sync::mpsc::{Receiver, Sender, channel};
 use style_traits::cursor::Cursor;
@@ -140,6 +141,9 @@ pub enum Msg {
     ChangePageUrl(PipelineId, Url),
     /// Alerts the compositor that the given pipeline has changed whether it is running animations.
     ChangeRunningAnimationsState(PipelineId, AnimationState),
+    /// Replaces the scrollbars of a pipeline that WebRender renders, which the compositor hit
+    /// tests since it has no layers for them.
+    SetScrollbars(PipelineId, Vec<ScrollContainerScrollbars>),
     /// Replaces the current frame tree, typically called during main frame navigation.
     SetFrameTree(SendableFrameTree, IpcSender<()>),
     /// The load of a page has begun: (can go back, can go forward).
@@ -177,6 +181,21 @@ pub enum Msg {
     Status(Option<String>),
     /// Get Window Informations size and position
     GetClientWindow(IpcSender<(Size2D<u32>, Point2D<i32>)>),
+    /// Ask the user whether the given origin may capture from a microphone
+    /// (first boolean) and a camera (second boolean).
+    PromptMediaPermission(String, bool, bool, IpcSender<bool>),
+    /// Ask whether the storage of the given origin may be made persistent.
+    PromptPersistentStorage(String, IpcSender<bool>),
+    /// Ask the user to choose a USB device that the given origin may use.
+    PromptUsbDevice(String, Vec<(String, String)>, IpcSender<Option<String>>),
+    /// Show a platform picker for a date, time or color control with the given value.
+    ShowInputPicker(InputPickerKind, String, IpcSender<Option<String>>),
+    /// Share data from a page from the given origin through the platform's share mechanism.
+    Share(String, ShareData, IpcSender<Result<(), ShareError>>),
+    /// Show an alert, confirm or prompt dialog for a page from the given origin.
+    ShowDialog(String, SimpleDialog, IpcSender<DialogResponse>),
+    /// Whether the root browsing context started or stopped making sound.
+    AudioPlaybackChanged(bool),
     /// Move the window to a point
     MoveTo(Point2D<i32>),
     /// Resize the window to size
@@ -206,6 +225,7 @@ impl Debug for Msg {
             Msg::ScrollFragmentPoint(..) => write!(f, "ScrollFragmentPoint"),
             Msg::AssignPaintedBuffers(..) => write!(f, "AssignPaintedBuffers"),
             Msg::ChangeRunningAnimationsState(..) => write!(f, "ChangeRunningAnimationsState"),
+            Msg::SetScrollbars(..) => write!(f, "SetScrollbars"),
             Msg::ChangePageTitle(..) => write!(f, "ChangePageTitle"),
             Msg::ChangePageUrl(..) => write!(f, "ChangePageUrl"),
             Msg::SetFrameTree(..) => write!(f, "SetFrameTree"),
@@ -226,6 +246,13 @@ impl Debug for Msg {
             Msg::CollectMemoryReports(..) => write!(f, "CollectMemoryReports"),
             Msg::Status(..) => write!(f, "Status"),
             Msg::GetClientWindow(..) => write!(f, "GetClientWindow"),
+            Msg::PromptMediaPermission(..) => write!(f, "PromptMediaPermission"),
+            Msg::PromptPersistentStorage(..) => write!(f, "PromptPersistentStorage"),
+            Msg::PromptUsbDevice(..) => write!(f, "PromptUsbDevice"),
+            Msg::ShowInputPicker(..) => write!(f, "ShowInputPicker"),
+            Msg::ShowDialog(..) => write!(f, "ShowDialog"),
+            Msg::Share(..) => write!(f, "Share"),
+            Msg::AudioPlaybackChanged(..) => write!(f, "AudioPlaybackChanged"),
             Msg::MoveTo(..) => write!(f, "MoveTo"),
             Msg::ResizeTo(..) => write!(f, "ResizeTo"),
             Msg::PipelineVisibilityChanged(..) => write!(f, "PipelineVisibilityChanged"),
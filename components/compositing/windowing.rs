@@ -9,16 +9,51 @@ use euclid::{Point2D, Size2D};
 use euclid::point::TypedPoint2D;
 use euclid::scale_factor::ScaleFactor;
 use euclid::size::TypedSize2D;
+use ipc_channel::ipc::IpcSender;
 use layers::geometry::DevicePixel;
 use layers::platform::surface::NativeDisplay;
 use msg::constellation_msg::{Key, KeyModifiers, KeyState};
 use net_traits::net_error_list::NetError;
-use script_traits::{MouseButton, TouchEventType, TouchId, TouchpadPressurePhase};
+use script_traits::{DialogResponse, InputPickerKind, MouseButton, ShareData, ShareError, SimpleDialog};
+use script_traits::{TouchEventType, TouchId, TouchpadPressurePhase};
 use std::fmt::{Debug, Error, Formatter};
 use style_traits::cursor::Cursor;
 use url::Url;
 use util::geometry::ScreenPx;
 
+/// Sends the user's answer to a simple dialog back to the page that is waiting for it.
+/// An embedder can keep it until the user answered, rather than blocking the compositor.
+/// Dropping it without an answer counts as dismissing the dialog.
+pub struct DialogResponder(IpcSender<DialogResponse>);
+
+impl DialogResponder {
+    pub fn new(sender: IpcSender<DialogResponse>) -> DialogResponder {
+        DialogResponder(sender)
+    }
+
+    pub fn respond(self, response: DialogResponse) {
+        if let Err(e) = self.0.send(response) {
+            warn!("Sending response to dialog failed ({}).", e);
+        }
+    }
+}
+
+/// Tells the page that asked to share data whether it was shared. An embedder can keep it
+/// while the user picks a share target. Dropping it without an answer counts as a failure.
+pub struct ShareResponder(IpcSender<Result<(), ShareError>>);
+
+impl ShareResponder {
+    pub fn new(sender: IpcSender<Result<(), ShareError>>) -> ShareResponder {
+        ShareResponder(sender)
+    }
+
+    pub fn respond(self, result: Result<(), ShareError>) {
+        if let Err(e) = self.0.send(result) {
+            warn!("Sending response to share request failed ({}).", e);
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum MouseWindowEvent {
     Click(MouseButton, TypedPoint2D<f32, DevicePixel>),
@@ -79,6 +114,10 @@ pub enum WindowEvent {
     KeyEvent(Option<char>, Key, KeyState, KeyModifiers),
     /// Sent when Ctr+R/Apple+R is called to reload the current page.
     Reload,
+    /// Sent when Esc is pressed to stop loading the current page.
+    Stop,
+    /// Sent when the user mutes or unmutes the page.
+    SetMuted(bool),
 }
 
 impl Debug for WindowEvent {
@@ -102,6 +141,8 @@ impl Debug for WindowEvent {
             WindowEvent::Navigation(..) => write!(f, "Navigation"),
             WindowEvent::Quit => write!(f, "Quit"),
             WindowEvent::Reload => write!(f, "Reload"),
+            WindowEvent::Stop => write!(f, "Stop"),
+            WindowEvent::SetMuted(..) => write!(f, "SetMuted"),
         }
     }
 }
@@ -165,4 +206,30 @@ pub trait WindowMethods {
 
     /// Add a favicon
     fn set_favicon(&self, url: Url);
+
+    /// Asks the user whether `origin` may capture from a microphone and/or a camera.
+    fn prompt_media_permission(&self, origin: String, audio: bool, video: bool) -> bool;
+
+    /// Whether the storage of `origin` may be made persistent, so that it isn't cleared
+    /// under storage pressure.
+    fn allow_persistent_storage(&self, origin: String) -> bool;
+
+    /// Asks the user to choose one of `devices`, given by their id and a label, for `origin`
+    /// to use. Returns the id of the chosen device, or `None` if none was chosen.
+    fn choose_usb_device(&self, origin: String, devices: Vec<(String, String)>) -> Option<String>;
+
+    /// Shows a platform date, time or color picker starting at `value`. Returns the picked
+    /// value, or `None` if the picker was dismissed or the platform has none.
+    fn show_input_picker(&self, kind: InputPickerKind, value: String) -> Option<String>;
+
+    /// Shows an alert, confirm or prompt dialog on behalf of a page from `origin`, and
+    /// answers it through `responder` once the user did.
+    fn show_dialog(&self, origin: String, dialog: SimpleDialog, responder: DialogResponder);
+
+    /// Hands `data` from a page from `origin` to the platform's share mechanism, and tells
+    /// `responder` whether it was shared once the user picked a share target or dismissed it.
+    fn share(&self, origin: String, data: ShareData, responder: ShareResponder);
+
+    /// Called when the page starts or stops making sound, for an audio indicator.
+    fn audio_playback_changed(&self, audible: bool);
 }
@@ -13,10 +13,14 @@ use backtrace::Backtrace;
 use canvas::canvas_paint_thread::CanvasPaintThread;
 use canvas::webgl_paint_thread::WebGLPaintThread;
 use canvas_traits::CanvasMsg;
+use canvas_traits::webgl2::WebGLVersion;
+use canvas_traits::webgl_extensions::WebGLExtensionSupport;
+use canvas_traits::webxr::WebXRMsg;
 use compositing::SendableFrameTree;
 use compositing::compositor_thread::CompositorProxy;
 use compositing::compositor_thread::Msg as ToCompositorMsg;
 use devtools_traits::{ChromeToDevtoolsControlMsg, DevtoolsControlMsg};
+use dialogs::ModalPrompts;
 use euclid::scale_factor::ScaleFactor;
 use euclid::size::{Size2D, TypedSize2D};
 use gfx::font_cache_thread::FontCacheThread;
@@ -40,14 +44,14 @@ use profile_traits::time;
 use rand::{Rng, SeedableRng, StdRng, random};
 use script_traits::{AnimationState, AnimationTickType, CompositorEvent};
 use script_traits::{ConstellationControlMsg, ConstellationMsg as FromCompositorMsg};
-use script_traits::{DocumentState, LayoutControlMsg};
-use script_traits::{IFrameLoadInfo, IFrameSandboxState, TimerEventRequest};
+use script_traits::{DocumentState, HistoryStateId, LayoutControlMsg};
+use script_traits::{DialogResponse, IFrameLoadInfo, IFrameSandboxState, InputPickerKind, SimpleDialog};
 use script_traits::{LayoutMsg as FromLayoutMsg, ScriptMsg as FromScriptMsg, ScriptThreadFactory};
-use script_traits::{LogEntry, ServiceWorkerMsg, webdriver_msg};
-use script_traits::{MozBrowserErrorType, MozBrowserEvent, WebDriverCommandMsg, WindowSizeData};
-use script_traits::{SWManagerMsg, ScopeThings};
+use script_traits::{LogEntry, ServiceWorkerMsg, TimerEventRequest, webdriver_msg};
+use script_traits::{MozBrowserErrorType, MozBrowserEvent, NewLayoutInfo, WebDriverCommandMsg, WindowSizeData};
+use script_traits::{SWManagerMsg, ScopeThings, ShareData, ShareError};
 use std::borrow::ToOwned;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::io::Error as IOError;
 use std::iter::once;
 use std::marker::PhantomData;
@@ -60,13 +64,16 @@ use std::time::Instant;
 use style_traits::PagePx;
 use style_traits::cursor::Cursor;
 use style_traits::viewport::ViewportConstraints;
+use time::precise_time_ns;
 use timer_scheduler::TimerScheduler;
 use url::Url;
+use usb_traits::UsbMethodMsg;
 use util::opts;
 use util::prefs::PREFS;
 use util::remutex::ReentrantMutex;
 use util::thread::spawn_named;
 use webrender_traits;
+use webrtc_traits::WebRTCMsg;
 
 #[derive(Debug, PartialEq)]
 enum ReadyToSave {
@@ -120,6 +127,15 @@ pub struct Constellation<Message, LTF, STF> {
     /// A channel through which messages can be sent to the bluetooth thread.
     bluetooth_thread: IpcSender<BluetoothMethodMsg>,
 
+    /// A channel through which messages can be sent to the USB thread.
+    usb_thread: IpcSender<UsbMethodMsg>,
+
+    /// A channel through which messages can be sent to the WebRTC thread.
+    webrtc_thread: IpcSender<WebRTCMsg>,
+
+    /// A channel through which messages can be sent to the WebXR thread.
+    webxr_thread: IpcSender<WebXRMsg>,
+
     /// Sender to Service Worker Manager thread
     swmanager_chan: Option<IpcSender<ServiceWorkerMsg>>,
 
@@ -153,6 +169,10 @@ pub struct Constellation<Message, LTF, STF> {
     /// Navigation operations that are in progress.
     pending_frames: Vec<FrameChange>,
 
+    /// A hidden pipeline that prerenders a page that a document is likely to navigate to,
+    /// with the URL it loads. It isn't attached to any frame yet.
+    prerendered_pipeline: Option<(Url, PipelineId)>,
+
     /// A channel through which messages can be sent to the time profiler.
     time_profiler_chan: time::ProfilerChan,
 
@@ -188,6 +208,15 @@ pub struct Constellation<Message, LTF, STF> {
     /// The random number generator and probability for closing pipelines.
     /// This is for testing the hardening of the constellation.
     random_pipeline_closure: Option<(StdRng, f32)>,
+
+    /// The origins whose pages the user asked not to be shown any more dialogs from.
+    dialogs_prevented: HashSet<String>,
+
+    /// The dialogs waiting for the embedder of a mozbrowser iframe to answer them.
+    modal_prompts: ModalPrompts,
+
+    /// The pipelines whose media is making sound.
+    audible_pipelines: HashSet<PipelineId>,
 }
 
 /// State needed to construct a constellation.
@@ -198,6 +227,12 @@ pub struct InitialConstellationState {
     pub devtools_chan: Option<Sender<DevtoolsControlMsg>>,
     /// A channel to the bluetooth thread.
     pub bluetooth_thread: IpcSender<BluetoothMethodMsg>,
+    /// A channel to the USB thread.
+    pub usb_thread: IpcSender<UsbMethodMsg>,
+    /// A channel to the WebRTC thread.
+    pub webrtc_thread: IpcSender<WebRTCMsg>,
+    /// A channel to the WebXR thread.
+    pub webxr_thread: IpcSender<WebXRMsg>,
     /// A channel to the image cache thread.
     pub image_cache_thread: ImageCacheThread,
     /// A channel to the font cache thread.
@@ -221,6 +256,13 @@ struct FrameState {
     instant: Instant,
     pipeline_id: PipelineId,
     frame_id: FrameId,
+    /// Which of the entries of its document this is.
+    state_id: HistoryStateId,
+    /// The URL of a same-document entry created by `pushState`, `replaceState`
+    /// or a fragment navigation. `None` means the document's own URL.
+    url: Option<Url>,
+    /// The structured-clone serialized state object of this entry.
+    state: Option<Vec<u8>>,
 }
 
 impl FrameState {
@@ -229,6 +271,9 @@ impl FrameState {
             instant: Instant::now(),
             pipeline_id: pipeline_id,
             frame_id: frame_id,
+            state_id: HistoryStateId(0),
+            url: None,
+            state: None,
         }
     }
 }
@@ -263,6 +308,28 @@ impl Frame {
     fn replace_current(&mut self, pipeline_id: PipelineId) -> FrameState {
         replace(&mut self.current, FrameState::new(pipeline_id, self.id))
     }
+
+    /// Adds a new entry for the current document, as done by `pushState`.
+    fn push_state(&mut self, state_id: HistoryStateId, url: Url, state: Option<Vec<u8>>) {
+        let pipeline_id = self.current.pipeline_id;
+        self.prev.push(self.current.clone());
+        self.current = FrameState::new(pipeline_id, self.id);
+        self.current.state_id = state_id;
+        self.current.url = Some(url);
+        self.current.state = state;
+    }
+
+    /// Updates the current entry in place, as done by `replaceState`.
+    fn replace_state(&mut self, url: Url, state: Option<Vec<u8>>) {
+        self.current.url = Some(url);
+        self.current.state = state;
+    }
+
+    /// Whether any entry of this frame, current or not, belongs to the given pipeline.
+    fn has_entry_for(&self, pipeline_id: PipelineId) -> bool {
+        self.prev.iter().chain(self.next.iter()).chain(once(&self.current))
+            .any(|entry| entry.pipeline_id == pipeline_id)
+    }
 }
 
 /// Represents a pending change in the frame tree, that will be applied
@@ -485,6 +552,9 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 compositor_proxy: state.compositor_proxy,
                 devtools_chan: state.devtools_chan,
                 bluetooth_thread: state.bluetooth_thread,
+                usb_thread: state.usb_thread,
+                webrtc_thread: state.webrtc_thread,
+                webxr_thread: state.webxr_thread,
                 public_resource_threads: state.public_resource_threads,
                 private_resource_threads: state.private_resource_threads,
                 image_cache_thread: state.image_cache_thread,
@@ -495,6 +565,7 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 pipelines: HashMap::new(),
                 frames: HashMap::new(),
                 pending_frames: vec!(),
+                prerendered_pipeline: None,
                 next_pipeline_namespace_id: PipelineNamespaceId(0),
                 root_frame_id: None,
                 next_frame_id: FrameId(0),
@@ -524,6 +595,9 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                     info!("Using seed {} for random pipeline closure.", seed);
                     (rng, prob)
                 }),
+                dialogs_prevented: HashSet::new(),
+                modal_prompts: ModalPrompts::new(),
+                audible_pipelines: HashSet::new(),
             };
             let namespace_id = constellation.next_pipeline_namespace_id();
             PipelineNamespace::install(namespace_id);
@@ -556,6 +630,7 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                     old_pipeline_id: Option<PipelineId>,
                     initial_window_size: Option<TypedSize2D<f32, PagePx>>,
                     script_channel: Option<IpcSender<ConstellationControlMsg>>,
+                    new_layout_info_chan: Option<IpcSender<NewLayoutInfo>>,
                     load_data: LoadData,
                     is_private: bool) {
         if self.shutting_down { return; }
@@ -574,15 +649,23 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
             None
         };
 
+        let muted = old_pipeline_id.or(parent_info.map(|(parent_pipeline_id, _)| parent_pipeline_id))
+                                   .and_then(|id| self.pipelines.get(&id))
+                                   .map_or(false, |pipeline| pipeline.muted);
+
         let result = Pipeline::spawn::<Message, LTF, STF>(InitialPipelineState {
             id: pipeline_id,
             parent_info: parent_info,
+            old_pipeline_id: old_pipeline_id,
             constellation_chan: self.script_sender.clone(),
             layout_to_constellation_chan: self.layout_sender.clone(),
             scheduler_chan: self.scheduler_chan.clone(),
             compositor_proxy: self.compositor_proxy.clone_compositor_proxy(),
             devtools_chan: self.devtools_chan.clone(),
             bluetooth_thread: self.bluetooth_thread.clone(),
+            usb_thread: self.usb_thread.clone(),
+            webrtc_thread: self.webrtc_thread.clone(),
+            webxr_thread: self.webxr_thread.clone(),
             swmanager_thread: self.swmanager_sender.clone(),
             image_cache_thread: self.image_cache_thread.clone(),
             font_cache_thread: self.font_cache_thread.clone(),
@@ -591,10 +674,12 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
             mem_profiler_chan: self.mem_profiler_chan.clone(),
             window_size: initial_window_size,
             script_chan: script_channel,
+            new_layout_info_chan: new_layout_info_chan,
             load_data: load_data,
             device_pixel_ratio: self.window_size.device_pixel_ratio,
             pipeline_namespace_id: self.next_pipeline_namespace_id(),
             prev_visibility: prev_visibility,
+            muted: muted,
             webrender_api_sender: self.webrender_api_sender.clone(),
             is_private: is_private,
         });
@@ -630,10 +715,13 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         }
     }
 
-    fn joint_session_future(&self, frame_id_root: FrameId) -> Vec<(Instant, FrameId, PipelineId)> {
+    /// The future entries of the joint session history, as (sort key, frame, entry instant)
+    /// triples. Entries are identified by their instant, since same-document entries share
+    /// a pipeline.
+    fn joint_session_future(&self, frame_id_root: FrameId) -> Vec<(Instant, FrameId, Instant)> {
         let mut future = vec!();
         for frame in self.full_frame_tree_iter(frame_id_root) {
-            future.extend(frame.next.iter().map(|entry| (entry.instant, entry.frame_id, entry.pipeline_id)));
+            future.extend(frame.next.iter().map(|entry| (entry.instant, entry.frame_id, entry.instant)));
         }
 
         // reverse sorting
@@ -641,12 +729,14 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         future
     }
 
-    fn joint_session_past(&self, frame_id_root: FrameId) -> Vec<(Instant, FrameId, PipelineId)> {
+    /// The past entries of the joint session history, as (sort key, frame, entry instant)
+    /// triples.
+    fn joint_session_past(&self, frame_id_root: FrameId) -> Vec<(Instant, FrameId, Instant)> {
         let mut past = vec!();
         for frame in self.full_frame_tree_iter(frame_id_root) {
             let mut prev_instant = frame.current.instant;
             for entry in frame.prev.iter().rev() {
-                past.push((prev_instant, entry.frame_id, entry.pipeline_id));
+                past.push((prev_instant, entry.frame_id, entry.instant));
                 prev_instant = entry.instant;
             }
         }
@@ -732,6 +822,39 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 // store service worker manager for communicating with it.
                 self.swmanager_chan = Some(sw_sender);
             }
+            SWManagerMsg::PostMessageToClient(pipeline_id, msg, scope_url) => {
+                let result = match self.pipelines.get(&pipeline_id) {
+                    None => return warn!("Service worker message to pipeline {:?} after closure.", pipeline_id),
+                    Some(pipeline) => {
+                        let msg = ConstellationControlMsg::PostMessageFromServiceWorker(pipeline_id, msg, scope_url);
+                        pipeline.script_chan.send(msg)
+                    },
+                };
+                if let Err(e) = result {
+                    self.handle_send_error(pipeline_id, e);
+                }
+            }
+            SWManagerMsg::ClaimClients(script_url, scope_url, scopes) => {
+                // Script decides which of the scopes each client belongs to, we only
+                // skip the pipelines of other origins.
+                let scope_origin = scope_url.origin();
+                let mut errors = vec![];
+                for (pipeline_id, pipeline) in &self.pipelines {
+                    if pipeline.url.origin() != scope_origin {
+                        continue;
+                    }
+                    let msg = ConstellationControlMsg::ClaimedByServiceWorker(*pipeline_id,
+                                                                              script_url.clone(),
+                                                                              scope_url.clone(),
+                                                                              scopes.clone());
+                    if let Err(e) = pipeline.script_chan.send(msg) {
+                        errors.push((*pipeline_id, e));
+                    }
+                }
+                for (pipeline_id, e) in errors {
+                    self.handle_send_error(pipeline_id, e);
+                }
+            }
         }
     }
 
@@ -806,6 +929,16 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 debug!("constellation got reload message");
                 self.handle_reload_msg();
             }
+            FromCompositorMsg::Stop => {
+                debug!("constellation got stop message");
+                self.handle_stop_msg();
+            }
+            FromCompositorMsg::SetMuted(muted) => {
+                debug!("constellation got set muted message");
+                if let Some(root_frame_id) = self.root_frame_id {
+                    self.set_frame_tree_muted(root_frame_id, muted);
+                }
+            }
             FromCompositorMsg::LogEntry(pipeline_id, thread_name, entry) => {
                 self.handle_log_entry(pipeline_id, thread_name, entry);
             }
@@ -824,6 +957,12 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                        load_info.new_pipeline_id);
                 self.handle_script_loaded_url_in_iframe_msg(load_info);
             }
+            FromScriptMsg::ScriptNewIFrame(load_info, layout_sender) => {
+                debug!("constellation got new iframe message {:?} {:?}",
+                       load_info.parent_pipeline_id,
+                       load_info.new_pipeline_id);
+                self.handle_script_new_iframe(load_info, layout_sender);
+            }
             FromScriptMsg::ChangeRunningAnimationsState(pipeline_id, animation_state) => {
                 self.handle_change_running_animations_state(pipeline_id, animation_state)
             }
@@ -849,6 +988,22 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 debug!("constellation got joint session history length message from script");
                 self.handle_joint_session_history_length(pipeline_id, sender);
             }
+            FromScriptMsg::GetTopLevelPipeline(pipeline_id, sender) => {
+                debug!("constellation got top-level pipeline message from script");
+                self.handle_get_top_level_pipeline(pipeline_id, sender);
+            }
+            FromScriptMsg::PostMessage(pipeline_id, origin, data) => {
+                debug!("constellation got postMessage message from script");
+                self.handle_post_message_msg(pipeline_id, origin, data);
+            }
+            FromScriptMsg::PushHistoryState(pipeline_id, state_id, url, state) => {
+                debug!("constellation got push history state message from script");
+                self.handle_push_history_state_msg(pipeline_id, state_id, url, state);
+            }
+            FromScriptMsg::ReplaceHistoryState(pipeline_id, url, state) => {
+                debug!("constellation got replace history state message from script");
+                self.handle_replace_history_state_msg(pipeline_id, url, state);
+            }
             // Notification that the new document is ready to become active
             FromScriptMsg::ActivateDocument(pipeline_id) => {
                 debug!("constellation got activate document message");
@@ -876,7 +1031,7 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
             }
             FromScriptMsg::ForwardMouseButtonEvent(pipeline_id, event_type, button, point) => {
                 let event = CompositorEvent::MouseButtonEvent(event_type, button, point);
-                let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
+                let msg = ConstellationControlMsg::SendEvent(pipeline_id, event, precise_time_ns());
                 let result = match self.pipelines.get(&pipeline_id) {
                     None => { debug!("Pipeline {:?} got mouse button event after closure.", pipeline_id); return; }
                     Some(pipeline) => pipeline.script_chan.send(msg),
@@ -887,7 +1042,7 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
             }
             FromScriptMsg::ForwardMouseMoveEvent(pipeline_id, point) => {
                 let event = CompositorEvent::MouseMoveEvent(Some(point));
-                let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
+                let msg = ConstellationControlMsg::SendEvent(pipeline_id, event, precise_time_ns());
                 let result = match self.pipelines.get(&pipeline_id) {
                     None => { debug!("Pipeline {:?} got mouse move event after closure.", pipeline_id); return; }
                     Some(pipeline) => pipeline.script_chan.send(msg),
@@ -911,6 +1066,18 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 debug!("constellation got set visibility change complete message");
                 self.handle_visibility_change_complete(pipeline_id, visible);
             }
+            FromScriptMsg::SetMuted(pipeline_id, muted) => {
+                debug!("constellation got set muted message");
+                self.handle_set_muted_msg(pipeline_id, muted);
+            }
+            FromScriptMsg::AudioPlaybackChanged(pipeline_id, audible) => {
+                debug!("constellation got audio playback changed message");
+                self.handle_audio_playback_changed(pipeline_id, audible);
+            }
+            FromScriptMsg::CancelNavigation(pipeline_id) => {
+                debug!("constellation got cancel navigation message");
+                self.handle_cancel_navigation_msg(pipeline_id);
+            }
             FromScriptMsg::RemoveIFrame(pipeline_id, sender) => {
                 debug!("constellation got remove iframe message");
                 self.handle_remove_iframe_msg(pipeline_id);
@@ -920,6 +1087,10 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                     }
                 }
             }
+            FromScriptMsg::Prerender(source_id, load_data) => {
+                debug!("constellation got prerender message");
+                self.handle_prerender_msg(source_id, load_data);
+            }
             FromScriptMsg::NewFavicon(url) => {
                 debug!("constellation got new favicon message");
                 self.compositor_proxy.send(ToCompositorMsg::NewFavicon(url));
@@ -932,9 +1103,9 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 debug!("constellation got create-canvas-paint-thread message");
                 self.handle_create_canvas_paint_thread_msg(&size, sender)
             }
-            FromScriptMsg::CreateWebGLPaintThread(size, attributes, sender) => {
+            FromScriptMsg::CreateWebGLPaintThread(size, attributes, version, sender) => {
                 debug!("constellation got create-WebGL-paint-thread message");
-                self.handle_create_webgl_paint_thread_msg(&size, attributes, sender)
+                self.handle_create_webgl_paint_thread_msg(&size, attributes, version, sender)
             }
             FromScriptMsg::NodeStatus(message) => {
                 debug!("constellation got NodeStatus message");
@@ -944,9 +1115,37 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 debug!("constellation got SetDocumentState message");
                 self.document_states.insert(pipeline_id, state);
             }
-            FromScriptMsg::Alert(pipeline_id, message, sender) => {
-                debug!("constellation got Alert message");
-                self.handle_alert(pipeline_id, message, sender);
+            FromScriptMsg::ShowDialog(pipeline_id, dialog, sender) => {
+                debug!("constellation got ShowDialog message");
+                self.handle_show_dialog(pipeline_id, dialog, sender);
+            }
+            FromScriptMsg::PreventAdditionalDialogs(pipeline_id) => {
+                debug!("constellation got PreventAdditionalDialogs message");
+                self.handle_prevent_additional_dialogs(pipeline_id);
+            }
+            FromScriptMsg::AnswerModalPrompt(mozbrowser_iframe_id, response) => {
+                debug!("constellation got AnswerModalPrompt message");
+                self.handle_answer_modal_prompt(mozbrowser_iframe_id, response);
+            }
+            FromScriptMsg::RequestMediaPermission(pipeline_id, audio, video, sender) => {
+                debug!("constellation got RequestMediaPermission message");
+                self.handle_request_media_permission(pipeline_id, audio, video, sender);
+            }
+            FromScriptMsg::RequestPersistentStorage(pipeline_id, sender) => {
+                debug!("constellation got RequestPersistentStorage message");
+                self.handle_request_persistent_storage(pipeline_id, sender);
+            }
+            FromScriptMsg::Share(pipeline_id, data, sender) => {
+                debug!("constellation got Share message");
+                self.handle_share(pipeline_id, data, sender);
+            }
+            FromScriptMsg::ChooseUsbDevice(pipeline_id, devices, sender) => {
+                debug!("constellation got ChooseUsbDevice message");
+                self.handle_choose_usb_device(pipeline_id, devices, sender);
+            }
+            FromScriptMsg::ShowInputPicker(pipeline_id, kind, value, sender) => {
+                debug!("constellation got ShowInputPicker message");
+                self.handle_show_input_picker(pipeline_id, kind, value, sender);
             }
 
             FromScriptMsg::ScrollFragmentPoint(pipeline_id, layer_id, point, smooth) => {
@@ -994,9 +1193,9 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 debug!("constellation got store registration scope message");
                 self.handle_register_serviceworker(scope_things, scope);
             }
-            FromScriptMsg::ForwardDOMMessage(msg_vec, scope_url) => {
+            FromScriptMsg::ForwardDOMMessage(msg_vec, client, scope_url) => {
                 if let Some(ref mgr) = self.swmanager_chan {
-                    let _ = mgr.send(ServiceWorkerMsg::ForwardDOMMessage(msg_vec, scope_url));
+                    let _ = mgr.send(ServiceWorkerMsg::ForwardDOMMessage(msg_vec, client, scope_url));
                 } else {
                     warn!("Unable to forward DOMMessage for postMessage call");
                 }
@@ -1016,6 +1215,9 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 debug!("constellation got viewport-constrained event message");
                 self.handle_viewport_constrained_msg(pipeline_id, constraints);
             }
+            FromLayoutMsg::SetScrollbars(pipeline_id, scrollbars) => {
+                self.compositor_proxy.send(ToCompositorMsg::SetScrollbars(pipeline_id, scrollbars))
+            }
         }
     }
 
@@ -1071,6 +1273,21 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
             warn!("Exit bluetooth thread failed ({})", e);
         }
 
+        debug!("Exiting USB thread.");
+        if let Err(e) = self.usb_thread.send(UsbMethodMsg::Exit) {
+            warn!("Exit USB thread failed ({})", e);
+        }
+
+        debug!("Exiting WebRTC thread.");
+        if let Err(e) = self.webrtc_thread.send(WebRTCMsg::Exit) {
+            warn!("Exit WebRTC thread failed ({})", e);
+        }
+
+        debug!("Exiting WebXR thread.");
+        if let Err(e) = self.webxr_thread.send(WebXRMsg::Exit) {
+            warn!("Exit WebXR thread failed ({})", e);
+        }
+
         debug!("Exiting service worker manager thread.");
         if let Some(mgr) = self.swmanager_chan.as_ref() {
             if let Err(e) = mgr.send(ServiceWorkerMsg::Exit) {
@@ -1144,7 +1361,8 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
 
             let new_pipeline_id = PipelineId::new();
             let load_data = LoadData::new(failure_url, None, None);
-            self.new_pipeline(new_pipeline_id, parent_info, Some(pipeline_id), window_size, None, load_data, false);
+            self.new_pipeline(new_pipeline_id, parent_info, Some(pipeline_id), window_size,
+                              None, None, load_data, false);
 
             self.pending_frames.push(FrameChange {
                 old_pipeline_id: Some(pipeline_id),
@@ -1173,7 +1391,7 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         let window_size = self.window_size.visible_viewport;
         let root_pipeline_id = PipelineId::new();
         debug_assert!(PipelineId::fake_root_pipeline_id() == root_pipeline_id);
-        self.new_pipeline(root_pipeline_id, None, None, Some(window_size), None,
+        self.new_pipeline(root_pipeline_id, None, None, Some(window_size), None, None,
                           LoadData::new(url.clone(), None, None), false);
         self.handle_load_start_msg(root_pipeline_id);
         self.pending_frames.push(FrameChange {
@@ -1268,8 +1486,11 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
 
             // FIXME(#10968): this should probably match the origin check in
             //                HTMLIFrameElement::contentDocument.
-            let same_script = source_url.host() == load_data.url.host() &&
-                              source_url.port() == load_data.url.port() &&
+            // An about:srcdoc document has the origin of its parent.
+            let same_host = load_data.srcdoc.is_some() ||
+                            (source_url.host() == load_data.url.host() &&
+                             source_url.port() == load_data.url.port());
+            let same_script = same_host &&
                               load_info.sandbox == IFrameSandboxState::IFrameUnsandboxed &&
                               source_pipeline.is_private == is_private;
 
@@ -1300,6 +1521,7 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                           load_info.old_pipeline_id,
                           window_size,
                           script_chan,
+                          None,
                           load_data,
                           is_private);
 
@@ -1311,6 +1533,37 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         });
     }
 
+    // A newly inserted iframe is getting its initial about:blank document. That document has
+    // the origin of its parent, so the new pipeline always shares the parent's script thread,
+    // which is blocked until it receives the layout info to build the document synchronously.
+    fn handle_script_new_iframe(&mut self, load_info: IFrameLoadInfo, layout_sender: IpcSender<NewLayoutInfo>) {
+        let (script_chan, is_private) = match self.pipelines.get(&load_info.parent_pipeline_id) {
+            Some(parent_pipeline) => (parent_pipeline.script_chan.clone(),
+                                      load_info.is_private || parent_pipeline.is_private),
+            None => return warn!("Script created iframe in closed pipeline {}.", load_info.parent_pipeline_id),
+        };
+
+        let load_data = load_info.load_data.unwrap_or_else(|| {
+            LoadData::new(Url::parse("about:blank").expect("infallible"), None, None)
+        });
+
+        self.new_pipeline(load_info.new_pipeline_id,
+                          Some((load_info.parent_pipeline_id, load_info.frame_type)),
+                          None,
+                          None,
+                          Some(script_chan),
+                          Some(layout_sender),
+                          load_data,
+                          is_private);
+
+        self.pending_frames.push(FrameChange {
+            old_pipeline_id: None,
+            new_pipeline_id: load_info.new_pipeline_id,
+            document_ready: false,
+            replace: false,
+        });
+    }
+
     fn handle_set_cursor_msg(&mut self, cursor: Cursor) {
         self.compositor_proxy.send(ToCompositorMsg::SetCursor(cursor))
     }
@@ -1344,47 +1597,171 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         }
     }
 
-    fn handle_alert(&mut self,
+    fn handle_request_media_permission(&mut self,
+                                       pipeline_id: PipelineId,
+                                       audio: bool,
+                                       video: bool,
+                                       sender: IpcSender<bool>) {
+        let origin = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.url.origin().unicode_serialization(),
+            None => {
+                warn!("Media permission requested by Pipeline {:?} after closure.", pipeline_id);
+                if let Err(e) = sender.send(false) {
+                    self.handle_send_error(pipeline_id, e);
+                }
+                return;
+            },
+        };
+        self.compositor_proxy.send(ToCompositorMsg::PromptMediaPermission(origin, audio, video, sender));
+    }
+
+    fn handle_request_persistent_storage(&mut self, pipeline_id: PipelineId, sender: IpcSender<bool>) {
+        let origin = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.url.origin().unicode_serialization(),
+            None => {
+                warn!("Persistent storage requested by Pipeline {:?} after closure.", pipeline_id);
+                if let Err(e) = sender.send(false) {
+                    self.handle_send_error(pipeline_id, e);
+                }
+                return;
+            },
+        };
+        self.compositor_proxy.send(ToCompositorMsg::PromptPersistentStorage(origin, sender));
+    }
+
+    fn handle_share(&mut self,
                     pipeline_id: PipelineId,
-                    message: String,
-                    sender: IpcSender<bool>) {
-        let display_alert_dialog = if PREFS.is_mozbrowser_enabled() {
-            let parent_pipeline_info = self.pipelines.get(&pipeline_id).and_then(|source| source.parent_info);
-            if parent_pipeline_info.is_some() {
-                let root_pipeline_id = self.root_frame_id
-                    .and_then(|root_frame_id| self.frames.get(&root_frame_id))
-                    .map(|root_frame| root_frame.current.pipeline_id);
+                    data: ShareData,
+                    sender: IpcSender<Result<(), ShareError>>) {
+        let origin = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.url.origin().unicode_serialization(),
+            None => {
+                warn!("Share requested by Pipeline {:?} after closure.", pipeline_id);
+                if let Err(e) = sender.send(Err(ShareError::Failed)) {
+                    self.handle_send_error(pipeline_id, e);
+                }
+                return;
+            },
+        };
+        self.compositor_proxy.send(ToCompositorMsg::Share(origin, data, sender));
+    }
 
-                let ancestor_info = self.get_mozbrowser_ancestor_info(pipeline_id);
-                if let Some((ancestor_id, mozbrowser_iframe_id)) = ancestor_info {
-                    if root_pipeline_id == Some(ancestor_id) {
-                        match root_pipeline_id.and_then(|pipeline_id| self.pipelines.get(&pipeline_id)) {
-                            Some(root_pipeline) => {
-                                // https://developer.mozilla.org/en-US/docs/Web/Events/mozbrowsershowmodalprompt
-                                let event = MozBrowserEvent::ShowModalPrompt("alert".to_owned(), "Alert".to_owned(),
-                                                                             String::from(message), "".to_owned());
-                                root_pipeline.trigger_mozbrowser_event(Some(mozbrowser_iframe_id), event);
-                            }
-                            None => return warn!("Alert sent to Pipeline {:?} after closure.", root_pipeline_id),
-                        }
-                    } else {
-                        warn!("A non-current frame is trying to show an alert.")
+    fn handle_choose_usb_device(&mut self,
+                                pipeline_id: PipelineId,
+                                devices: Vec<(String, String)>,
+                                sender: IpcSender<Option<String>>) {
+        let origin = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.url.origin().unicode_serialization(),
+            None => {
+                warn!("USB device requested by Pipeline {:?} after closure.", pipeline_id);
+                if let Err(e) = sender.send(None) {
+                    self.handle_send_error(pipeline_id, e);
+                }
+                return;
+            },
+        };
+        self.compositor_proxy.send(ToCompositorMsg::PromptUsbDevice(origin, devices, sender));
+    }
+
+    fn handle_show_input_picker(&mut self,
+                                pipeline_id: PipelineId,
+                                kind: InputPickerKind,
+                                value: String,
+                                sender: IpcSender<Option<String>>) {
+        // Pickers are only shown for pipelines that are still around; anything else
+        // gets an answer straight away so that script doesn't block forever.
+        if !self.pipelines.contains_key(&pipeline_id) {
+            warn!("Input picker requested by Pipeline {:?} after closure.", pipeline_id);
+            if let Err(e) = sender.send(None) {
+                self.handle_send_error(pipeline_id, e);
+            }
+            return;
+        }
+        self.compositor_proxy.send(ToCompositorMsg::ShowInputPicker(kind, value, sender));
+    }
+
+    fn handle_show_dialog(&mut self,
+                          pipeline_id: PipelineId,
+                          dialog: SimpleDialog,
+                          sender: IpcSender<DialogResponse>) {
+        let origin = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.url.origin().unicode_serialization(),
+            None => {
+                warn!("Dialog requested by Pipeline {:?} after closure.", pipeline_id);
+                return self.dismiss_dialog(pipeline_id, sender);
+            },
+        };
+
+        // https://html.spec.whatwg.org/multipage/#cannot-show-simple-dialogs
+        if self.dialogs_prevented.contains(&origin) {
+            return self.dismiss_dialog(pipeline_id, sender);
+        }
+
+        let in_mozbrowser = PREFS.is_mozbrowser_enabled() &&
+            self.pipelines.get(&pipeline_id).and_then(|source| source.parent_info).is_some();
+        if !in_mozbrowser {
+            return self.compositor_proxy.send(ToCompositorMsg::ShowDialog(origin, dialog, sender));
+        }
+
+        let root_pipeline_id = self.root_frame_id
+            .and_then(|root_frame_id| self.frames.get(&root_frame_id))
+            .map(|root_frame| root_frame.current.pipeline_id);
+
+        let ancestor_info = self.get_mozbrowser_ancestor_info(pipeline_id);
+        if let Some((ancestor_id, mozbrowser_iframe_id)) = ancestor_info {
+            if root_pipeline_id == Some(ancestor_id) {
+                let shown = match root_pipeline_id.and_then(|pipeline_id| self.pipelines.get(&pipeline_id)) {
+                    Some(root_pipeline) => {
+                        // https://developer.mozilla.org/en-US/docs/Web/Events/mozbrowsershowmodalprompt
+                        let (prompt_type, title, message, default) = match dialog {
+                            SimpleDialog::Alert(message) => ("alert", "Alert", message, String::new()),
+                            SimpleDialog::Confirm(message) => ("confirm", "Confirm", message, String::new()),
+                            SimpleDialog::Prompt(message, default) => ("prompt", "Prompt", message, default),
+                        };
+                        let event = MozBrowserEvent::ShowModalPrompt(prompt_type.to_owned(), title.to_owned(),
+                                                                     message, default);
+                        root_pipeline.trigger_mozbrowser_event(Some(mozbrowser_iframe_id), event);
+                        true
+                    }
+                    None => {
+                        warn!("Dialog sent to Pipeline {:?} after closure.", root_pipeline_id);
+                        false
                     }
+                };
+                if shown {
+                    // The embedder answers through answerModalPrompt().
+                    return self.modal_prompts.push(mozbrowser_iframe_id, pipeline_id, sender);
                 }
-                false
             } else {
-                true
+                warn!("A non-current frame is trying to show a dialog.")
             }
-        } else {
-            true
-        };
+        }
 
-        let result = sender.send(display_alert_dialog);
-        if let Err(e) = result {
+        // Nobody was asked, so nobody will answer.
+        self.dismiss_dialog(pipeline_id, sender);
+    }
+
+    fn handle_answer_modal_prompt(&mut self, mozbrowser_iframe_id: PipelineId, response: DialogResponse) {
+        match self.modal_prompts.answer(mozbrowser_iframe_id) {
+            Some((pipeline_id, sender)) => if let Err(e) = sender.send(response) {
+                self.handle_send_error(pipeline_id, e);
+            },
+            None => warn!("Answer to a modal prompt that mozbrowser {:?} isn't showing.", mozbrowser_iframe_id),
+        }
+    }
+
+    fn dismiss_dialog(&mut self, pipeline_id: PipelineId, sender: IpcSender<DialogResponse>) {
+        if let Err(e) = sender.send(DialogResponse::dismissed()) {
             self.handle_send_error(pipeline_id, e);
         }
     }
 
+    fn handle_prevent_additional_dialogs(&mut self, pipeline_id: PipelineId) {
+        if let Some(pipeline) = self.pipelines.get(&pipeline_id) {
+            self.dialogs_prevented.insert(pipeline.url.origin().unicode_serialization());
+        }
+    }
+
     fn handle_load_url_msg(&mut self, source_id: PipelineId, load_data: LoadData, replace: bool) {
         self.load_url(source_id, load_data, replace);
     }
@@ -1439,7 +1816,8 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 // Create the new pipeline
                 let window_size = self.pipelines.get(&source_id).and_then(|source| source.size);
                 let new_pipeline_id = PipelineId::new();
-                self.new_pipeline(new_pipeline_id, None, None, window_size, None, load_data, false);
+                self.new_pipeline(new_pipeline_id, None, Some(source_id), window_size, None, None, load_data,
+                                  false);
                 self.pending_frames.push(FrameChange {
                     old_pipeline_id: Some(source_id),
                     new_pipeline_id: new_pipeline_id,
@@ -1457,6 +1835,30 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         }
     }
 
+    /// Loads the page of a `<link rel=prerender>` in a hidden pipeline. Only one page is
+    /// prerendered at a time, so an earlier prerendered pipeline is closed.
+    fn handle_prerender_msg(&mut self, source_id: PipelineId, load_data: LoadData) {
+        if self.prerendered_pipeline.as_ref().map_or(false, |&(ref url, _)| *url == load_data.url) {
+            return;
+        }
+        if let Some((_, old_pipeline_id)) = self.prerendered_pipeline.take() {
+            self.close_pipeline(old_pipeline_id, ExitPipelineMode::Normal);
+        }
+
+        let url = load_data.url.clone();
+        let (window_size, is_private) = match self.pipelines.get(&source_id) {
+            Some(source) => (source.size, source.is_private),
+            None => return warn!("Prerender from closed pipeline {:?}", source_id),
+        };
+        let new_pipeline_id = PipelineId::new();
+        self.new_pipeline(new_pipeline_id, None, None, window_size, None, None, load_data, is_private);
+        match self.pipelines.get_mut(&new_pipeline_id) {
+            Some(pipeline) => pipeline.change_visibility(false),
+            None => return,
+        }
+        self.prerendered_pipeline = Some((url, new_pipeline_id));
+    }
+
     fn handle_load_start_msg(&mut self, pipeline_id: PipelineId) {
         if let Some(frame_id) = self.get_top_level_frame_for_pipeline(Some(pipeline_id)) {
             let forward = !self.joint_session_future(frame_id).is_empty();
@@ -1501,8 +1903,8 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 let mut future = self.joint_session_future(frame_id);
                 for _ in 0..delta {
                     match future.pop() {
-                        Some((_, frame_id, pipeline_id)) => {
-                            traversal_info.insert(frame_id, pipeline_id);
+                        Some((_, frame_id, instant)) => {
+                            traversal_info.insert(frame_id, instant);
                         },
                         None => return warn!("invalid traversal delta"),
                     }
@@ -1512,16 +1914,61 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 let mut past = self.joint_session_past(frame_id);
                 for _ in 0..delta {
                     match past.pop() {
-                        Some((_, frame_id, pipeline_id)) => {
-                            traversal_info.insert(frame_id, pipeline_id);
+                        Some((_, frame_id, instant)) => {
+                            traversal_info.insert(frame_id, instant);
                         },
                         None => return warn!("invalid traversal delta"),
                     }
                 }
             },
         };
-        for (frame_id, pipeline_id) in traversal_info {
-            self.traverse_frame_to_pipeline(frame_id, pipeline_id);
+        for (frame_id, instant) in traversal_info {
+            self.traverse_frame_to_entry(frame_id, instant);
+        }
+    }
+
+    fn handle_push_history_state_msg(&mut self,
+                                     pipeline_id: PipelineId,
+                                     state_id: HistoryStateId,
+                                     url: Url,
+                                     state: Option<Vec<u8>>) {
+        let frame_id = match self.pipelines.get(&pipeline_id).and_then(|pipeline| pipeline.frame) {
+            Some(frame_id) => frame_id,
+            None => return warn!("Pushed history state for pipeline {:?} without a frame.", pipeline_id),
+        };
+        match self.frames.get_mut(&frame_id) {
+            Some(ref mut frame) if frame.current.pipeline_id == pipeline_id => {
+                frame.push_state(state_id, url, state)
+            },
+            Some(_) => return warn!("Pushed history state for inactive pipeline {:?}.", pipeline_id),
+            None => return warn!("Pushed history state after frame {:?} closure.", frame_id),
+        }
+
+        // Adding an entry discards the forward history, like a regular navigation.
+        let top_level_frame_id = match self.get_top_level_frame_for_pipeline(Some(pipeline_id)) {
+            Some(frame_id) => frame_id,
+            None => return warn!("Tried to remove forward history after root frame closure."),
+        };
+        self.clear_joint_session_future(top_level_frame_id);
+
+        let forward = !self.joint_session_future(top_level_frame_id).is_empty();
+        let back = !self.joint_session_past(top_level_frame_id).is_empty();
+        let root = self.root_frame_id.is_none() || self.root_frame_id == Some(top_level_frame_id);
+        self.compositor_proxy.send(ToCompositorMsg::LoadComplete(back, forward, root));
+    }
+
+    fn handle_replace_history_state_msg(&mut self,
+                                        pipeline_id: PipelineId,
+                                        url: Url,
+                                        state: Option<Vec<u8>>) {
+        let frame_id = match self.pipelines.get(&pipeline_id).and_then(|pipeline| pipeline.frame) {
+            Some(frame_id) => frame_id,
+            None => return warn!("Replaced history state for pipeline {:?} without a frame.", pipeline_id),
+        };
+        match self.frames.get_mut(&frame_id) {
+            Some(ref mut frame) if frame.current.pipeline_id == pipeline_id => frame.replace_state(url, state),
+            Some(_) => warn!("Replaced history state for inactive pipeline {:?}.", pipeline_id),
+            None => warn!("Replaced history state after frame {:?} closure.", frame_id),
         }
     }
 
@@ -1544,6 +1991,27 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         let _ = sender.send(length as u32);
     }
 
+    // https://html.spec.whatwg.org/multipage/#top-level-browsing-context
+    fn handle_get_top_level_pipeline(&self, pipeline_id: PipelineId, sender: IpcSender<PipelineId>) {
+        let mut top_level_id = pipeline_id;
+        while let Some((parent_id, FrameType::IFrame)) = self.pipelines.get(&top_level_id)
+                                                                       .and_then(|pipeline| pipeline.parent_info) {
+            top_level_id = parent_id;
+        }
+        let _ = sender.send(top_level_id);
+    }
+
+    fn handle_post_message_msg(&mut self, pipeline_id: PipelineId, origin: Option<Url>, data: Vec<u8>) {
+        let msg = ConstellationControlMsg::PostMessage(pipeline_id, origin, data);
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => return warn!("postMessage to closed pipeline {}.", pipeline_id),
+            Some(pipeline) => pipeline.script_chan.send(msg),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn handle_key_msg(&mut self, ch: Option<char>, key: Key, state: KeyState, mods: KeyModifiers) {
         // Send to the explicitly focused pipeline (if it exists), or the root
         // frame's current pipeline. If neither exist, fall back to sending to
@@ -1556,7 +2024,7 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         match pipeline_id {
             Some(pipeline_id) => {
                 let event = CompositorEvent::KeyEvent(ch, key, state, mods);
-                let msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
+                let msg = ConstellationControlMsg::SendEvent(pipeline_id, event, precise_time_ns());
                 let result = match self.pipelines.get(&pipeline_id) {
                     Some(pipeline) => pipeline.script_chan.send(msg),
                     None => return debug!("Pipeline {:?} got key event after closure.", pipeline_id),
@@ -1590,6 +2058,38 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#stop-document-loading
+    fn handle_stop_msg(&mut self) {
+        let root_pipeline_id = self.root_frame_id
+            .and_then(|root_frame_id| self.frames.get(&root_frame_id))
+            .map(|root_frame| root_frame.current.pipeline_id);
+
+        if let Some(pipeline_id) = root_pipeline_id {
+            // Step 3
+            self.handle_cancel_navigation_msg(pipeline_id);
+            // Step 4
+            let msg = ConstellationControlMsg::StopLoading(pipeline_id);
+            let result = match self.pipelines.get(&pipeline_id) {
+                Some(pipeline) => pipeline.script_chan.send(msg),
+                None => return debug!("Pipeline {:?} got stop event after closure.", pipeline_id),
+            };
+            if let Err(e) = result {
+                self.handle_send_error(pipeline_id, e);
+            }
+        }
+    }
+
+    fn handle_cancel_navigation_msg(&mut self, pipeline_id: PipelineId) {
+        let pending_pipeline_ids: Vec<PipelineId> = self.pending_frames.iter()
+            .filter(|frame_change| frame_change.old_pipeline_id == Some(pipeline_id))
+            .map(|frame_change| frame_change.new_pipeline_id)
+            .collect();
+        for pending_pipeline_id in pending_pipeline_ids {
+            debug!("Cancelling navigation of {:?} to {:?}.", pipeline_id, pending_pipeline_id);
+            self.close_pipeline(pending_pipeline_id, ExitPipelineMode::Force);
+        }
+    }
+
     fn handle_get_pipeline_title_msg(&mut self, pipeline_id: PipelineId) {
         let result = match self.pipelines.get(&pipeline_id) {
             None => return self.compositor_proxy.send(ToCompositorMsg::ChangePageTitle(pipeline_id, None)),
@@ -1723,6 +2223,69 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         }
     }
 
+    fn handle_set_muted_msg(&mut self, pipeline_id: PipelineId, muted: bool) {
+        match self.pipelines.get(&pipeline_id).and_then(|pipeline| pipeline.frame) {
+            Some(frame_id) => self.set_frame_tree_muted(frame_id, muted),
+            None => warn!("No frame associated with pipeline {:?}", pipeline_id),
+        }
+    }
+
+    /// Mutes or unmutes all the pipelines of a top-level browsing context, including those
+    /// in its session history.
+    fn set_frame_tree_muted(&mut self, frame_id: FrameId, muted: bool) {
+        let pipeline_ids: Vec<PipelineId> = self.full_frame_tree_iter(frame_id)
+                                                .flat_map(|frame| frame.next.iter()
+                                                          .chain(frame.prev.iter())
+                                                          .chain(once(&frame.current)))
+                                                .map(|state| state.pipeline_id)
+                                                .collect();
+        for id in pipeline_ids {
+            if let Some(pipeline) = self.pipelines.get_mut(&id) {
+                pipeline.change_muted(muted);
+            }
+        }
+    }
+
+    fn handle_audio_playback_changed(&mut self, pipeline_id: PipelineId, audible: bool) {
+        if !self.pipelines.contains_key(&pipeline_id) {
+            return warn!("Audio playback changed for Pipeline {:?} after closure.", pipeline_id);
+        }
+        let context = self.get_mozbrowser_ancestor_info(pipeline_id);
+        let was_audible = self.is_audible(context);
+        if audible {
+            self.audible_pipelines.insert(pipeline_id);
+        } else {
+            self.audible_pipelines.remove(&pipeline_id);
+        }
+        if self.is_audible(context) != was_audible {
+            self.notify_audio_playback(context, !was_audible);
+        }
+    }
+
+    /// Whether any pipeline of a top-level browsing context is making sound. The context is
+    /// given as the parent and child pipelines of its mozbrowser iframe, or `None` for the
+    /// root frame.
+    fn is_audible(&self, context: Option<(PipelineId, PipelineId)>) -> bool {
+        self.audible_pipelines.iter().any(|&id| self.get_mozbrowser_ancestor_info(id) == context)
+    }
+
+    /// Tells the embedder that a top-level browsing context started or stopped making
+    /// sound, for its audio indicator.
+    fn notify_audio_playback(&self, context: Option<(PipelineId, PipelineId)>, audible: bool) {
+        match context {
+            // https://developer.mozilla.org/en-US/docs/Web/Events/mozbrowseraudioplaybackchange
+            Some((parent_pipeline_id, mozbrowser_iframe_id)) => {
+                if !PREFS.is_mozbrowser_enabled() { return; }
+                match self.pipelines.get(&parent_pipeline_id) {
+                    Some(parent) => parent.trigger_mozbrowser_event(Some(mozbrowser_iframe_id),
+                                                                    MozBrowserEvent::AudioPlaybackChange(audible)),
+                    None => warn!("Audio playback change sent to Pipeline {:?} after closure.", parent_pipeline_id),
+                }
+            },
+            None => self.compositor_proxy.send(ToCompositorMsg::AudioPlaybackChanged(audible)),
+        }
+    }
+
     fn handle_create_canvas_paint_thread_msg(
             &mut self,
             size: &Size2D<i32>,
@@ -1739,9 +2302,10 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
             &mut self,
             size: &Size2D<i32>,
             attributes: GLContextAttributes,
-            response_sender: IpcSender<Result<(IpcSender<CanvasMsg>, GLLimits), String>>) {
+            version: WebGLVersion,
+            response_sender: IpcSender<Result<(IpcSender<CanvasMsg>, GLLimits, WebGLExtensionSupport), String>>) {
         let webrender_api = self.webrender_api_sender.clone();
-        let response = WebGLPaintThread::start(*size, attributes, webrender_api);
+        let response = WebGLPaintThread::start(*size, attributes, version, webrender_api);
 
         if let Err(e) = response_sender.send(response) {
             warn!("Create WebGL paint thread response failed ({})", e);
@@ -1786,7 +2350,7 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                 };
                 for (key, mods, state) in cmd {
                     let event = CompositorEvent::KeyEvent(None, key, state, mods);
-                    let control_msg = ConstellationControlMsg::SendEvent(pipeline_id, event);
+                    let control_msg = ConstellationControlMsg::SendEvent(pipeline_id, event, precise_time_ns());
                     if let Err(e) = script_channel.send(control_msg) {
                         return self.handle_send_error(pipeline_id, e);
                     }
@@ -1807,24 +2371,24 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         }
     }
 
-    fn traverse_frame_to_pipeline(&mut self, frame_id: FrameId, next_pipeline_id: PipelineId) {
+    fn traverse_frame_to_entry(&mut self, frame_id: FrameId, instant: Instant) {
         // Check if the currently focused pipeline is the pipeline being replaced
         // (or a child of it). This has to be done here, before the current
         // frame tree is modified below.
         let update_focus_pipeline = self.focused_pipeline_in_tree(frame_id);
 
-        let prev_pipeline_id = match self.frames.get_mut(&frame_id) {
+        let traversal = match self.frames.get_mut(&frame_id) {
             Some(frame) => {
                 let prev = frame.current.pipeline_id;
-                // Check that this frame contains the pipeline passed in, so that this does not
-                // change Frame's state before realizing `next_pipeline_id` is invalid.
-                let mut contains_pipeline = false;
+                // Check that this frame contains the entry passed in, so that this does not
+                // change Frame's state before realizing `instant` is invalid.
+                let mut contains_entry = false;
 
-                if frame.next.iter().find(|entry| next_pipeline_id == entry.pipeline_id).is_some() {
-                    contains_pipeline = true;
+                if frame.next.iter().find(|entry| instant == entry.instant).is_some() {
+                    contains_entry = true;
                     frame.prev.push(frame.current.clone());
                     while let Some(entry) = frame.next.pop() {
-                        if entry.pipeline_id == next_pipeline_id {
+                        if entry.instant == instant {
                             frame.current = entry;
                             break;
                         } else {
@@ -1833,12 +2397,12 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                     }
                 }
 
-                if !contains_pipeline &&
-                   frame.prev.iter().find(|entry| next_pipeline_id == entry.pipeline_id).is_some() {
-                    contains_pipeline = true;
+                if !contains_entry &&
+                   frame.prev.iter().find(|entry| instant == entry.instant).is_some() {
+                    contains_entry = true;
                     frame.next.push(frame.current.clone());
                     while let Some(entry) = frame.prev.pop() {
-                        if entry.pipeline_id == next_pipeline_id {
+                        if entry.instant == instant {
                             frame.current = entry;
                             break;
                         } else {
@@ -1847,15 +2411,27 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                     }
                 }
 
-                if !contains_pipeline {
-                    return warn!("Tried to traverse frame {:?} to pipeline {:?} it does not contain.",
-                        frame_id, next_pipeline_id);
+                if !contains_entry {
+                    return warn!("Tried to traverse frame {:?} to entry {:?} it does not contain.",
+                        frame_id, instant);
                 }
 
-                prev
+                // The document only needs to hear about the traversal if it owns
+                // more than one entry; otherwise its state cannot have changed.
+                let next = frame.current.pipeline_id;
+                let notify_state = next == prev ||
+                    frame.prev.iter().chain(frame.next.iter()).any(|entry| entry.pipeline_id == next);
+
+                (prev, next, frame.current.clone(), notify_state)
             },
             None => return warn!("no frame to traverse"),
         };
+        let (prev_pipeline_id, next_pipeline_id, next_entry, notify_state) = traversal;
+
+        // A same-document traversal leaves the frame tree as it is.
+        if prev_pipeline_id == next_pipeline_id {
+            return self.update_history_state(next_entry);
+        }
 
         let pipeline_info = self.pipelines.get(&prev_pipeline_id).and_then(|p| p.parent_info);
 
@@ -1873,6 +2449,9 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         if let Some(next_pipeline) = self.pipelines.get(&next_pipeline_id) {
             next_pipeline.thaw();
         }
+        if notify_state {
+            self.update_history_state(next_entry);
+        }
 
         // Set paint permissions correctly for the compositor layers.
         self.revoke_paint_permission(prev_pipeline_id);
@@ -1898,6 +2477,22 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
         }
     }
 
+    /// Tells a document which of its session history entries became current.
+    fn update_history_state(&mut self, entry: FrameState) {
+        let pipeline_id = entry.pipeline_id;
+        let result = match self.pipelines.get(&pipeline_id) {
+            None => return warn!("Pipeline {:?} traversed after closure.", pipeline_id),
+            Some(pipeline) => {
+                let url = entry.url.unwrap_or_else(|| pipeline.url.clone());
+                let msg = ConstellationControlMsg::UpdateHistoryState(pipeline_id, entry.state_id, url, entry.state);
+                pipeline.script_chan.send(msg)
+            },
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
     fn get_top_level_frame_for_pipeline(&self, pipeline_id: Option<PipelineId>) -> Option<FrameId> {
         if PREFS.is_mozbrowser_enabled() {
             pipeline_id.and_then(|id| self.get_mozbrowser_ancestor_info(id))
@@ -1953,7 +2548,11 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
                         frame.replace_current(frame_change.new_pipeline_id)
                     });
                     if let Some(evicted) = evicted {
-                        self.close_pipeline(evicted.pipeline_id, ExitPipelineMode::Normal);
+                        let still_in_use = self.frames.get(&frame_id)
+                            .map_or(false, |frame| frame.has_entry_for(evicted.pipeline_id));
+                        if !still_in_use {
+                            self.close_pipeline(evicted.pipeline_id, ExitPipelineMode::Normal);
+                        }
                     }
                 } else {
                     if let Some(ref mut frame) = self.frames.get_mut(&frame_id) {
@@ -2247,7 +2846,12 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
             }
         }
         for entry in evicted_pipelines {
-            self.close_pipeline(entry.pipeline_id, ExitPipelineMode::Normal);
+            // Same-document entries share their pipeline with entries that are kept.
+            let still_in_use = self.frames.get(&entry.frame_id)
+                .map_or(false, |frame| frame.has_entry_for(entry.pipeline_id));
+            if !still_in_use {
+                self.close_pipeline(entry.pipeline_id, ExitPipelineMode::Normal);
+            }
         }
     }
 
@@ -2273,8 +2877,12 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
             pipelines_to_close
         };
 
+        let mut closed_pipelines = HashSet::new();
         for entry in pipelines_to_close {
-            self.close_pipeline(entry.pipeline_id, exit_mode);
+            // Same-document entries share a pipeline, so only close each one once.
+            if closed_pipelines.insert(entry.pipeline_id) {
+                self.close_pipeline(entry.pipeline_id, exit_mode);
+            }
         }
 
         if self.frames.remove(&frame_id).is_none() {
@@ -2292,6 +2900,14 @@ impl<Message, LTF, STF> Constellation<Message, LTF, STF>
 
     // Close all pipelines at and beneath a given frame
     fn close_pipeline(&mut self, pipeline_id: PipelineId, exit_mode: ExitPipelineMode) {
+        // A closed pipeline makes no more sound.
+        if self.audible_pipelines.contains(&pipeline_id) {
+            self.handle_audio_playback_changed(pipeline_id, false);
+        }
+
+        // Nobody is left to answer the dialogs shown in it, or to read the answers to its own.
+        self.modal_prompts.remove_pipeline(pipeline_id);
+
         // Store information about the frames to be closed. Then close the
         // frames, before removing ourself from the pipelines hash map. This
         // ordering is vital - so that if close_frames() ends up closing
@@ -0,0 +1,65 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The simple dialogs of pages in mozbrowser iframes, which wait for the
+//! embedder to answer the mozbrowsershowmodalprompt events they were shown
+//! through.
+
+use ipc_channel::ipc::IpcSender;
+use msg::constellation_msg::PipelineId;
+use script_traits::DialogResponse;
+use std::collections::{HashMap, VecDeque};
+
+/// The dialogs waiting for an answer, by the pipeline of the mozbrowser
+/// iframe they were shown in, in the order their events were sent.
+pub struct ModalPrompts {
+    prompts: HashMap<PipelineId, VecDeque<(PipelineId, IpcSender<DialogResponse>)>>,
+}
+
+impl ModalPrompts {
+    pub fn new() -> ModalPrompts {
+        ModalPrompts {
+            prompts: HashMap::new(),
+        }
+    }
+
+    /// Waits for the embedder of `mozbrowser_iframe_id` to answer a dialog
+    /// that `pipeline_id` showed.
+    pub fn push(&mut self,
+                mozbrowser_iframe_id: PipelineId,
+                pipeline_id: PipelineId,
+                sender: IpcSender<DialogResponse>) {
+        self.prompts.entry(mozbrowser_iframe_id).or_insert_with(VecDeque::new).push_back((pipeline_id, sender));
+    }
+
+    /// Takes the oldest dialog shown in `mozbrowser_iframe_id`, which is the
+    /// one its embedder answers, with the pipeline that showed it.
+    pub fn answer(&mut self, mozbrowser_iframe_id: PipelineId) -> Option<(PipelineId, IpcSender<DialogResponse>)> {
+        let (prompt, empty) = match self.prompts.get_mut(&mozbrowser_iframe_id) {
+            Some(queue) => (queue.pop_front(), queue.is_empty()),
+            None => return None,
+        };
+        if empty {
+            self.prompts.remove(&mozbrowser_iframe_id);
+        }
+        prompt
+    }
+
+    /// Forgets the dialogs that a closed pipeline showed or that were shown
+    /// in it. Their senders are dropped, which their pages take as the
+    /// dialogs being dismissed.
+    pub fn remove_pipeline(&mut self, pipeline_id: PipelineId) {
+        self.prompts.remove(&pipeline_id);
+        for queue in self.prompts.values_mut() {
+            queue.retain(|&(id, _)| id != pipeline_id);
+        }
+        let empty: Vec<PipelineId> = self.prompts.iter()
+            .filter(|&(_, queue)| queue.is_empty())
+            .map(|(&id, _)| id)
+            .collect();
+        for id in empty {
+            self.prompts.remove(&id);
+        }
+    }
+}
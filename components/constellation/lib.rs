@@ -36,12 +36,16 @@ extern crate rand;
 extern crate script_traits;
 extern crate serde;
 extern crate style_traits;
+extern crate time;
 extern crate url;
+extern crate usb_traits;
 #[macro_use]
 extern crate util;
 extern crate webrender_traits;
+extern crate webrtc_traits;
 
 mod constellation;
+pub mod dialogs;
 mod pipeline;
 #[cfg(not(target_os = "windows"))]
 mod sandboxing;
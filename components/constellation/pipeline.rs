@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use canvas_traits::webxr::WebXRMsg;
 use compositing::CompositionPipeline;
 use compositing::CompositorProxy;
 use compositing::compositor_thread::Msg as CompositorMsg;
@@ -34,11 +35,13 @@ use std::process;
 use std::sync::mpsc::{Sender, channel};
 use style_traits::{PagePx, ViewportPx};
 use url::Url;
+use usb_traits::UsbMethodMsg;
 use util;
 use util::ipc::OptionalIpcSender;
 use util::opts::{self, Opts};
 use util::prefs::{PREFS, Pref};
 use webrender_traits;
+use webrtc_traits::WebRTCMsg;
 
 pub enum ChildProcess {
     #[cfg(not(target_os = "windows"))]
@@ -71,6 +74,9 @@ pub struct Pipeline {
     /// Whether this pipeline should be treated as visible for the purposes of scheduling and
     /// resource management.
     pub visible: bool,
+    /// Whether the media of this pipeline is muted, along with the rest of its top-level
+    /// browsing context.
+    pub muted: bool,
     /// Frame that contains this Pipeline. Can be `None` if the pipeline is not apart of the
     /// frame tree.
     pub frame: Option<FrameId>,
@@ -86,6 +92,8 @@ pub struct InitialPipelineState {
     /// The ID of the parent pipeline and frame type, if any.
     /// If `None`, this is the root.
     pub parent_info: Option<(PipelineId, FrameType)>,
+    /// The ID of the pipeline that this one replaces by navigating, if any.
+    pub old_pipeline_id: Option<PipelineId>,
     /// A channel to the associated constellation.
     pub constellation_chan: IpcSender<ScriptMsg>,
     /// A channel for the layout thread to send messages to the constellation.
@@ -98,6 +106,12 @@ pub struct InitialPipelineState {
     pub devtools_chan: Option<Sender<DevtoolsControlMsg>>,
     /// A channel to the bluetooth thread.
     pub bluetooth_thread: IpcSender<BluetoothMethodMsg>,
+    /// A channel to the USB thread.
+    pub usb_thread: IpcSender<UsbMethodMsg>,
+    /// A channel to the WebRTC thread.
+    pub webrtc_thread: IpcSender<WebRTCMsg>,
+    /// A channel to the WebXR thread.
+    pub webxr_thread: IpcSender<WebXRMsg>,
     /// A channel to the service worker manager thread
     pub swmanager_thread: IpcSender<SWManagerMsg>,
     /// A channel to the image cache thread.
@@ -117,12 +131,17 @@ pub struct InitialPipelineState {
     /// A channel to the script thread, if applicable. If this is `Some`,
     /// then `parent_info` must also be `Some`.
     pub script_chan: Option<IpcSender<ConstellationControlMsg>>,
+    /// A channel to a script thread that is waiting to attach the new layout synchronously.
+    /// If this is `Some`, the layout info is sent here instead of as an `AttachLayout` message.
+    pub new_layout_info_chan: Option<IpcSender<NewLayoutInfo>>,
     /// Information about the page to load.
     pub load_data: LoadData,
     /// The ID of the pipeline namespace for this script thread.
     pub pipeline_namespace_id: PipelineNamespaceId,
     /// Pipeline visibility to be inherited
     pub prev_visibility: Option<bool>,
+    /// Whether the pipeline is muted, as inherited from its parent or the pipeline it replaces.
+    pub muted: bool,
     /// Optional webrender api (if enabled).
     pub webrender_api_sender: Option<webrender_traits::RenderApiSender>,
     /// Whether this pipeline is considered private.
@@ -154,6 +173,7 @@ impl Pipeline {
                 let new_layout_info = NewLayoutInfo {
                     parent_pipeline_id: parent_pipeline_id,
                     new_pipeline_id: state.id,
+                    old_pipeline_id: state.old_pipeline_id,
                     frame_type: frame_type,
                     load_data: state.load_data.clone(),
                     paint_chan: layout_to_paint_chan.clone().to_opaque(),
@@ -163,8 +183,13 @@ impl Pipeline {
                     layout_threads: PREFS.get("layout.threads").as_u64().expect("count") as usize,
                 };
 
-                if let Err(e) = script_chan.send(ConstellationControlMsg::AttachLayout(new_layout_info)) {
-                    warn!("Sending to script during pipeline creation failed ({})", e);
+                match state.new_layout_info_chan {
+                    Some(new_layout_info_chan) => if let Err(e) = new_layout_info_chan.send(new_layout_info) {
+                        warn!("Sending layout info to script during pipeline creation failed ({})", e);
+                    },
+                    None => if let Err(e) = script_chan.send(ConstellationControlMsg::AttachLayout(new_layout_info)) {
+                        warn!("Sending to script during pipeline creation failed ({})", e);
+                    },
                 }
                 (script_chan, None)
             }
@@ -217,10 +242,14 @@ impl Pipeline {
             let unprivileged_pipeline_content = UnprivilegedPipelineContent {
                 id: state.id,
                 parent_info: state.parent_info,
+                old_pipeline_id: state.old_pipeline_id,
                 constellation_chan: state.constellation_chan,
                 scheduler_chan: state.scheduler_chan,
                 devtools_chan: script_to_devtools_chan,
                 bluetooth_thread: state.bluetooth_thread,
+                usb_thread: state.usb_thread,
+                webrtc_thread: state.webrtc_thread,
+                webxr_thread: state.webxr_thread,
                 swmanager_thread: state.swmanager_thread,
                 image_cache_thread: state.image_cache_thread,
                 font_cache_thread: state.font_cache_thread,
@@ -263,9 +292,13 @@ impl Pipeline {
                                      state.is_private,
                                      state.load_data.url,
                                      state.window_size,
-                                     state.prev_visibility.unwrap_or(true));
+                                     state.prev_visibility.unwrap_or(true),
+                                     state.muted);
 
         pipeline.notify_visibility();
+        if pipeline.muted {
+            pipeline.notify_muted();
+        }
 
         Ok((pipeline, child_process))
     }
@@ -279,7 +312,8 @@ impl Pipeline {
            is_private: bool,
            url: Url,
            size: Option<TypedSize2D<f32, PagePx>>,
-           visible: bool)
+           visible: bool,
+           muted: bool)
            -> Pipeline {
         Pipeline {
             id: id,
@@ -294,6 +328,7 @@ impl Pipeline {
             size: size,
             running_animations: false,
             visible: visible,
+            muted: muted,
             is_private: is_private,
             frame: None,
         }
@@ -402,17 +437,35 @@ impl Pipeline {
         self.notify_visibility();
     }
 
+    fn notify_muted(&self) {
+        if let Err(e) = self.script_chan.send(ConstellationControlMsg::SetMuted(self.id, self.muted)) {
+            warn!("Sending mute to script failed ({}).", e);
+        }
+    }
+
+    pub fn change_muted(&mut self, muted: bool) {
+        if muted == self.muted {
+            return;
+        }
+        self.muted = muted;
+        self.notify_muted();
+    }
+
 }
 
 #[derive(Deserialize, Serialize)]
 pub struct UnprivilegedPipelineContent {
     id: PipelineId,
     parent_info: Option<(PipelineId, FrameType)>,
+    old_pipeline_id: Option<PipelineId>,
     constellation_chan: IpcSender<ScriptMsg>,
     layout_to_constellation_chan: IpcSender<LayoutMsg>,
     scheduler_chan: IpcSender<TimerEventRequest>,
     devtools_chan: Option<IpcSender<ScriptToDevtoolsControlMsg>>,
     bluetooth_thread: IpcSender<BluetoothMethodMsg>,
+    usb_thread: IpcSender<UsbMethodMsg>,
+    webrtc_thread: IpcSender<WebRTCMsg>,
+    webxr_thread: IpcSender<WebXRMsg>,
     swmanager_thread: IpcSender<SWManagerMsg>,
     image_cache_thread: ImageCacheThread,
     font_cache_thread: FontCacheThread,
@@ -448,6 +501,9 @@ impl UnprivilegedPipelineContent {
             constellation_chan: self.constellation_chan,
             scheduler_chan: self.scheduler_chan,
             bluetooth_thread: self.bluetooth_thread,
+            usb_thread: self.usb_thread,
+            webrtc_thread: self.webrtc_thread,
+            webxr_thread: self.webxr_thread,
             resource_threads: self.resource_threads,
             image_cache_thread: self.image_cache_thread.clone(),
             time_profiler_chan: self.time_profiler_chan.clone(),
@@ -473,7 +529,8 @@ impl UnprivilegedPipelineContent {
                     self.layout_content_process_shutdown_chan,
                     self.webrender_api_sender,
                     self.prefs.get("layout.threads").expect("exists").value()
-                        .as_u64().expect("count") as usize);
+                        .as_u64().expect("count") as usize,
+                    self.old_pipeline_id);
 
         if wait_for_completion {
             let _ = self.script_content_process_shutdown_port.recv();
@@ -23,7 +23,8 @@ use euclid::num::{One, Zero};
 use euclid::rect::TypedRect;
 use euclid::side_offsets::SideOffsets2D;
 use fnv::FnvHasher;
-use gfx_traits::{LayerId, ScrollPolicy, StackingContextId};
+use gfx_traits::{LayerId, ScrollContainerScrollbars, ScrollPolicy, ScrollbarInfo, StackingContextId};
+use gfx_traits::StickyFrameInfo;
 use gfx_traits::print_tree::PrintTree;
 use ipc_channel::ipc::IpcSharedMemory;
 use msg::constellation_msg::PipelineId;
@@ -40,7 +41,8 @@ use std::marker::PhantomData;
 use std::mem;
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
-use style::computed_values::{border_style, filter, image_rendering, mix_blend_mode};
+use style::computed_values::{backface_visibility, border_style, filter, image_rendering};
+use style::computed_values::{mix_blend_mode, transform_style};
 use style_traits::cursor::Cursor;
 use text::TextRun;
 use text::glyph::ByteIndex;
@@ -77,6 +79,12 @@ pub struct LayerInfo {
 
     /// The color of the background in this layer. Used for unpainted content.
     pub background_color: Color,
+
+    /// The sticky positioning constraints of this layer, if it is sticky positioned.
+    pub sticky: Option<StickyFrameInfo>,
+
+    /// The scrollbars painted in this layer, if it is the outer layer of a scroll container.
+    pub scrollbars: Option<ScrollbarInfo>,
 }
 
 impl LayerInfo {
@@ -91,6 +99,8 @@ impl LayerInfo {
             subpage_pipeline_id: subpage_pipeline_id,
             next_layer_id: id.companion_layer_id(),
             background_color: background_color,
+            sticky: None,
+            scrollbars: None,
         }
     }
 }
@@ -222,6 +232,7 @@ impl DisplayList {
                items: Vec<DisplayItem>)
                -> DisplayList {
         let mut offsets = FnvHashMap(HashMap::with_hasher(Default::default()));
+        root_stacking_context.compute_back_facing(&Matrix4D::identity());
         DisplayList::sort_and_count_stacking_contexts(&mut root_stacking_context, &mut offsets, 0);
 
         let mut display_list = DisplayList {
@@ -233,6 +244,61 @@ impl DisplayList {
         display_list
     }
 
+    /// Whether this list paints any text, images or canvases, rather than only
+    /// backgrounds, borders and the like.
+    /// https://w3c.github.io/paint-timing/#contentful
+    pub fn is_contentful(&self) -> bool {
+        self.list.iter().any(DisplayItem::is_contentful)
+    }
+
+    /// Returns the node whose text or image covers the largest part of `viewport`, with
+    /// the area it covers in square pixels. The text of a node is measured as the sum of
+    /// its lines, and images shown larger than their natural size count as if they were
+    /// shown at that size.
+    /// https://w3c.github.io/largest-contentful-paint/#sec-effective-visual-size
+    pub fn largest_contentful_node(&self, viewport: &Rect<Au>) -> Option<(OpaqueNode, f64)> {
+        fn area(rect: &Rect<Au>) -> f64 {
+            rect.size.width.to_f64_px() * rect.size.height.to_f64_px()
+        }
+
+        let mut text_areas: HashMap<OpaqueNode, f64> = HashMap::new();
+        let mut largest: Option<(OpaqueNode, f64)> = None;
+        for item in &self.list {
+            let item = match *item {
+                DisplayItem::LayeredItemClass(ref layered_item) => &layered_item.item,
+                ref item => item,
+            };
+            let base = item.base();
+            let visible = match base.bounds.intersection(&base.clip.bounding_rect())
+                                           .and_then(|rect| rect.intersection(viewport)) {
+                Some(visible) => visible,
+                None => continue,
+            };
+            let node = base.metadata.node;
+            let candidate_area = match *item {
+                DisplayItem::TextClass(_) => {
+                    let text_area = text_areas.entry(node).or_insert(0.);
+                    *text_area += area(&visible);
+                    *text_area
+                }
+                DisplayItem::ImageClass(ref image) => {
+                    let natural_area = image.webrender_image.width as f64 * image.webrender_image.height as f64;
+                    let shown_area = area(&base.bounds);
+                    if shown_area > natural_area {
+                        area(&visible) * natural_area / shown_area
+                    } else {
+                        area(&visible)
+                    }
+                }
+                _ => continue,
+            };
+            if largest.map_or(true, |(_, largest_area)| candidate_area > largest_area) {
+                largest = Some((node, candidate_area));
+            }
+        }
+        largest
+    }
+
     pub fn get_offset_for_item(&self, item: &DisplayItem) -> u32 {
         let offsets = &self.offsets[&item.base().stacking_context_id];
         match item.base().section {
@@ -269,7 +335,21 @@ impl DisplayList {
                                   BuildHasherDefault<FnvHasher>>,
             mut current_offset: u32)
             -> u32 {
-        stacking_context.children.sort();
+        if stacking_context.transform_style == transform_style::T::preserve_3d {
+            // Children that share our 3D rendering context are painted back to front. Planes that
+            // intersect are not split, so they are ordered by the depth of their centers.
+            stacking_context.children.sort_by(|a, b| {
+                match a.cmp(b) {
+                    Ordering::Equal => {
+                        a.depth_in_parent().partial_cmp(&b.depth_in_parent())
+                                           .unwrap_or(Ordering::Equal)
+                    }
+                    ordering => ordering,
+                }
+            });
+        } else {
+            stacking_context.children.sort();
+        }
 
         let start_offset = current_offset;
         let mut block_backgrounds_and_borders_offset = None;
@@ -404,7 +484,7 @@ impl DisplayList {
                 }
             }
 
-            if child.intersects_rect_in_parent_context(tile_rect) {
+            if child.intersects_rect_in_parent_context(tile_rect) && !child.is_hidden_back_face() {
                 self.draw_stacking_context(child,
                                            traversal,
                                            paint_context,
@@ -441,7 +521,8 @@ impl DisplayList {
 
         let draw_target = paint_context.get_or_create_temporary_draw_target(
             &stacking_context.filters,
-            stacking_context.blend_mode);
+            stacking_context.blend_mode,
+            stacking_context.isolated);
 
         let old_transform = paint_context.draw_target.get_transform();
         let pixels_per_px = paint_context.screen_pixels_per_px();
@@ -541,6 +622,17 @@ impl DisplayList {
                                             &mut result);
         result
     }
+
+    /// Returns the scrollbars of the scroll containers in this display list, for the compositor
+    /// to hit test when WebRender is in use.
+    pub fn scrollbars(&self) -> Vec<ScrollContainerScrollbars> {
+        let mut result = Vec::new();
+        // The document itself is scrolled with the root stacking context.
+        self.root_stacking_context.collect_scrollbars(&Point2D::zero(),
+                                                      &[StackingContextId::root()],
+                                                      &mut result);
+        result
+    }
 }
 
 fn transformed_tile_rect(tile_rect: TypedRect<usize, ScreenPx>,
@@ -602,6 +694,10 @@ pub struct StackingContext {
     /// The blend mode with which this stacking context blends with its backdrop.
     pub blend_mode: mix_blend_mode::T,
 
+    /// Whether this stacking context is composited as a group of its own before it is drawn onto
+    /// its backdrop, so that the blend modes of its contents don't reach what is beneath it.
+    pub isolated: bool,
+
     /// A transform to be applied to this stacking context.
     pub transform: Matrix4D<f32>,
 
@@ -611,6 +707,16 @@ pub struct StackingContext {
     /// Whether this stacking context creates a new 3d rendering context.
     pub establishes_3d_context: bool,
 
+    /// Whether the children of this stacking context share its 3d rendering context.
+    pub transform_style: transform_style::T,
+
+    /// Whether this stacking context is drawn when its back face is towards the viewer.
+    pub backface_visibility: backface_visibility::T,
+
+    /// Whether the back face of this stacking context is towards the viewer in its 3d rendering
+    /// context. This is worked out when the display list is built.
+    pub is_back_facing: bool,
+
     /// Whether this stacking context scrolls its overflow area.
     pub scrolls_overflow_area: bool,
 
@@ -631,9 +737,12 @@ impl StackingContext {
                z_index: i32,
                filters: filter::T,
                blend_mode: mix_blend_mode::T,
+               isolated: bool,
                transform: Matrix4D<f32>,
                perspective: Matrix4D<f32>,
                establishes_3d_context: bool,
+               transform_style: transform_style::T,
+               backface_visibility: backface_visibility::T,
                scrolls_overflow_area: bool,
                layer_info: Option<LayerInfo>)
                -> StackingContext {
@@ -645,9 +754,13 @@ impl StackingContext {
             z_index: z_index,
             filters: filters,
             blend_mode: blend_mode,
+            isolated: isolated,
             transform: transform,
             perspective: perspective,
             establishes_3d_context: establishes_3d_context,
+            transform_style: transform_style,
+            backface_visibility: backface_visibility,
+            is_back_facing: false,
             scrolls_overflow_area: scrolls_overflow_area,
             layer_info: layer_info,
             children: Vec::new(),
@@ -660,6 +773,7 @@ impl StackingContext {
         // child stacking contexts.
         for child in &children {
             self.update_overflow_for_new_child(&child);
+            self.update_isolation_for_new_child(&child);
         }
 
         self.children = children;
@@ -667,6 +781,7 @@ impl StackingContext {
 
     pub fn add_child(&mut self, child: Box<StackingContext>) {
         self.update_overflow_for_new_child(&child);
+        self.update_isolation_for_new_child(&child);
         self.children.push(child);
     }
 
@@ -688,6 +803,67 @@ impl StackingContext {
         &self.children
     }
 
+    /// Whether this stacking context is left out of painting and hit testing because its back
+    /// face is towards the viewer.
+    pub fn is_hidden_back_face(&self) -> bool {
+        self.is_back_facing && self.backface_visibility == backface_visibility::T::hidden
+    }
+
+    /// Works out which descendants have their back face towards the viewer. `transform` takes
+    /// this stacking context into the plane of its 3d rendering context.
+    /// https://drafts.csswg.org/css-transforms-2/#backface-visibility-property
+    fn compute_back_facing(&mut self, transform: &Matrix4D<f32>) {
+        // Unless we preserve 3d, our children are flattened into our plane.
+        let transform = if self.transform_style == transform_style::T::preserve_3d {
+            *transform
+        } else {
+            Matrix4D::identity()
+        };
+        let perspective = self.perspective;
+        let is_back_facing = self.is_back_facing;
+        for child in &mut self.children {
+            if child.context_type != StackingContextType::Real {
+                // Pseudo-stacking contexts are painted as part of us.
+                child.is_back_facing = is_back_facing;
+                child.compute_back_facing(&transform);
+                continue
+            }
+
+            let origin = Matrix4D::create_translation(child.bounds.origin.x.to_f32_px(),
+                                                      child.bounds.origin.y.to_f32_px(),
+                                                      0.0);
+            let child_transform = child.transform
+                                       .post_mul(&origin)
+                                       .post_mul(&perspective)
+                                       .post_mul(&transform);
+            // The back face is towards the viewer if the inverse of the accumulated transform
+            // flips the z axis.
+            child.is_back_facing = match child_transform.inverse() {
+                Some(inverse) => inverse.m33 < 0.0,
+                None => false,
+            };
+            child.compute_back_facing(&child_transform);
+        }
+    }
+
+    /// The depth of the center of this stacking context in the space of its parent, larger values
+    /// being closer to the viewer.
+    fn depth_in_parent(&self) -> f32 {
+        let center_x = self.bounds.size.width.to_f32_px() / 2.0;
+        let center_y = self.bounds.size.height.to_f32_px() / 2.0;
+        center_x * self.transform.m13 + center_y * self.transform.m23 + self.transform.m43
+    }
+
+    /// Children that blend do so with the rest of their parent stacking context only, so their
+    /// parent is isolated. Pseudo-stacking contexts are painted as part of their parent, which is
+    /// isolated in their place.
+    fn update_isolation_for_new_child(&mut self, child: &StackingContext) {
+        if child.blend_mode != mix_blend_mode::T::normal ||
+                (child.context_type != StackingContextType::Real && child.isolated) {
+            self.isolated = true
+        }
+    }
+
     fn update_overflow_for_new_child(&mut self, child: &StackingContext) {
         if self.context_type == StackingContextType::Real &&
            child.context_type == StackingContextType::Real &&
@@ -719,6 +895,42 @@ impl StackingContext {
         geometry::f32_rect_to_au_rect(overflow)
     }
 
+    fn collect_scrollbars(&self,
+                          parent_origin: &Point2D<Au>,
+                          scrolled_by: &[StackingContextId],
+                          result: &mut Vec<ScrollContainerScrollbars>) {
+        let is_fixed = match self.layer_info {
+            Some(ref layer_info) => layer_info.scroll_policy == ScrollPolicy::FixedPosition,
+            None => false,
+        };
+
+        // Fixed stacking contexts are positioned relative to the viewport, and nothing scrolls
+        // them. Transforms are ignored, as they are by the layers of the compositor.
+        let (origin, mut scrolled_by) = if is_fixed {
+            (self.bounds.origin, vec![])
+        } else if self.context_type == StackingContextType::Real {
+            (*parent_origin + self.bounds.origin, scrolled_by.to_vec())
+        } else {
+            (*parent_origin, scrolled_by.to_vec())
+        };
+
+        if let Some(scrollbars) = self.layer_info.as_ref().and_then(|layer_info| layer_info.scrollbars) {
+            result.push(ScrollContainerScrollbars {
+                stacking_context_id: self.id,
+                scrolled_by: scrolled_by.clone(),
+                origin: Point2D::new(origin.x.to_f32_px(), origin.y.to_f32_px()),
+                scrollbars: scrollbars,
+            })
+        }
+
+        if self.scrolls_overflow_area {
+            scrolled_by.push(self.id)
+        }
+        for child in self.children() {
+            child.collect_scrollbars(&origin, &scrolled_by, result)
+        }
+    }
+
     fn hit_test<'a>(&self,
                     traversal: &mut DisplayListTraversal<'a>,
                     translated_point: &Point2D<Au>,
@@ -766,8 +978,13 @@ impl StackingContext {
                     result.push(meta);
                 }
             }
+            // A hidden back face still has to be traversed, but nothing in it can be hit.
+            let result_count = result.len();
             child.hit_test(traversal, &translated_point, client_point,
                            scroll_offsets, result);
+            if child.is_hidden_back_face() {
+                result.truncate(result_count);
+            }
         }
 
         while let Some(item) = traversal.advance(self) {
@@ -1119,7 +1336,7 @@ pub struct TextDisplayItem {
     /// The position of the start of the baseline of this text.
     pub baseline_origin: Point2D<Au>,
 
-    /// The orientation of the text: upright or sideways left/right.
+    /// The orientation of the text: upright, sideways left/right, or vertical.
     pub orientation: TextOrientation,
 
     /// The blur radius for this text. If zero, this text is not blurred.
@@ -1131,6 +1348,8 @@ pub enum TextOrientation {
     Upright,
     SidewaysLeft,
     SidewaysRight,
+    /// Upright glyphs stacked top to bottom, centered on the baseline origin.
+    Vertical,
 }
 
 /// Paints an image.
@@ -1155,6 +1374,10 @@ pub struct ImageDisplayItem {
     /// The algorithm we should use to stretch the image. See `image_rendering` in CSS-IMAGES-3 §
     /// 5.3.
     pub image_rendering: image_rendering::T,
+
+    /// The blend mode with which this image blends with what is painted beneath it in its stacking
+    /// context. This is only ever set for background layers.
+    pub blend_mode: mix_blend_mode::T,
 }
 
 #[derive(Clone, HeapSizeOf, Deserialize, Serialize)]
@@ -1186,6 +1409,10 @@ pub struct GradientDisplayItem {
 
     /// A list of color stops.
     pub stops: Vec<GradientStop>,
+
+    /// The blend mode with which this gradient blends with what is painted beneath it in its
+    /// stacking context.
+    pub blend_mode: mix_blend_mode::T,
 }
 
 /// Paints a border.
@@ -1339,6 +1566,17 @@ pub enum BoxShadowClipMode {
 }
 
 impl DisplayItem {
+    /// Whether this item paints text, an image or a canvas.
+    pub fn is_contentful(&self) -> bool {
+        match *self {
+            DisplayItem::TextClass(_) |
+            DisplayItem::ImageClass(_) |
+            DisplayItem::WebGLClass(_) => true,
+            DisplayItem::LayeredItemClass(ref layered_item) => layered_item.item.is_contentful(),
+            _ => false,
+        }
+    }
+
     /// Paints this display item into the given painting context.
     fn draw_into_context(&self, paint_context: &mut PaintContext) {
         let this_clip = &self.base().clip;
@@ -1369,7 +1607,8 @@ impl DisplayItem {
                     &image_item.image_data
                                .as_ref()
                                .expect("Non-WR painting needs image data!")[..],
-                    image_item.image_rendering.clone());
+                    image_item.image_rendering.clone(),
+                    image_item.blend_mode);
             }
 
             DisplayItem::WebGLClass(_) => {
@@ -1388,7 +1627,8 @@ impl DisplayItem {
                 paint_context.draw_linear_gradient(&gradient.base.bounds,
                                                    &gradient.start_point,
                                                    &gradient.end_point,
-                                                   &gradient.stops);
+                                                   &gradient.stops,
+                                                   gradient.blend_mode);
             }
 
             DisplayItem::LineClass(ref line) => {
@@ -56,6 +56,8 @@ pub trait FontHandleMethods: Sized {
     fn glyph_index(&self, codepoint: char) -> Option<GlyphId>;
     fn glyph_h_advance(&self, GlyphId) -> Option<FractionalPixel>;
     fn glyph_h_kerning(&self, glyph0: GlyphId, glyph1: GlyphId) -> FractionalPixel;
+    /// How far the ink of the given glyph extends below the baseline, in pixels.
+    fn glyph_descent(&self, GlyphId) -> Option<FractionalPixel>;
     /// Can this font do basic horizontal LTR shaping without Harfbuzz?
     fn can_do_fast_shaping(&self) -> bool;
     fn metrics(&self) -> FontMetrics;
@@ -112,6 +114,7 @@ pub struct Font {
     shaper: Option<Shaper>,
     shape_cache: RefCell<HashMap<ShapeCacheEntry, Arc<GlyphStore>>>,
     glyph_advance_cache: RefCell<HashMap<u32, FractionalPixel>>,
+    glyph_descent_cache: RefCell<HashMap<u32, Au>>,
     pub font_key: Option<webrender_traits::FontKey>,
 }
 
@@ -133,6 +136,7 @@ impl Font {
             metrics: metrics,
             shape_cache: RefCell::new(HashMap::new()),
             glyph_advance_cache: RefCell::new(HashMap::new()),
+            glyph_descent_cache: RefCell::new(HashMap::new()),
             font_key: font_key,
         }
     }
@@ -148,6 +152,8 @@ bitflags! {
         const DISABLE_KERNING_SHAPING_FLAG = 0x04,
         #[doc = "Text direction is right-to-left."]
         const RTL_FLAG = 0x08,
+        #[doc = "Set if the text is set vertically, with upright glyphs stacked top to bottom."]
+        const VERTICAL_FLAG = 0x10,
     }
 }
 
@@ -279,6 +285,12 @@ impl Font {
             }
         })
     }
+
+    pub fn glyph_descent(&self, glyph: GlyphId) -> Au {
+        *self.glyph_descent_cache.borrow_mut().entry(glyph).or_insert_with(|| {
+            self.handle.glyph_descent(glyph).map_or(Au(0), Au::from_f64_px)
+        })
+    }
 }
 
 #[derive(Debug)]
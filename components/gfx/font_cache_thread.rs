@@ -245,7 +245,7 @@ impl FontCache {
                         }
                         ResponseAction::DataAvailable(new_bytes) => {
                             if *response_valid.lock().unwrap() {
-                                bytes.lock().unwrap().extend(new_bytes.into_iter())
+                                bytes.lock().unwrap().extend_from_slice(&new_bytes)
                             }
                         }
                         ResponseAction::ResponseComplete(response) => {
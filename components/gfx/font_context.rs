@@ -30,7 +30,7 @@ use webrender_traits;
 
 #[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
 fn create_scaled_font(template: &Arc<FontTemplateData>, pt_size: Au) -> ScaledFont {
-    ScaledFont::new(BackendType::Skia, FontInfo::FontData(&template.bytes),
+    ScaledFont::new(BackendType::Skia, FontInfo::FontData(&*template.bytes),
                     pt_size.to_f32_px())
 }
 
@@ -50,6 +50,7 @@ extern crate gfx_traits;
 extern crate harfbuzz_sys as harfbuzz;
 
 extern crate heapsize;
+extern crate hyphenation;
 extern crate ipc_channel;
 extern crate layers;
 #[allow(unused_extern_crates)]
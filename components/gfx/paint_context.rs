@@ -17,7 +17,7 @@ use azure::azure_hl::{Path, PathBuilder, Pattern, PatternRef, SurfacePattern};
 use azure::scaled_font::ScaledFont;
 use display_list::{BLUR_INFLATION_FACTOR, BorderRadii, BoxShadowClipMode, ClippingRegion};
 use display_list::{TextDisplayItem, WebRenderImageInfo};
-use display_list::TextOrientation::{SidewaysLeft, SidewaysRight, Upright};
+use display_list::TextOrientation::{SidewaysLeft, SidewaysRight, Upright, Vertical};
 use euclid::matrix2d::Matrix2D;
 use euclid::point::Point2D;
 use euclid::rect::{Rect, TypedRect};
@@ -29,7 +29,7 @@ use font_context::FontContext;
 use gfx_traits::{LayerKind, color};
 use net_traits::image::base::PixelFormat;
 use range::Range;
-use std::{f32, mem, ptr};
+use std::{cmp, f32, mem, ptr};
 use std::default::Default;
 use style::computed_values::{border_style, filter, image_rendering, mix_blend_mode};
 use style_traits::PagePx;
@@ -192,7 +192,8 @@ impl<'a> PaintContext<'a> {
                       tile_spacing: &Size2D<Au>,
                       image_info: &WebRenderImageInfo,
                       image_data: &[u8],
-                      image_rendering: image_rendering::T) {
+                      image_rendering: image_rendering::T,
+                      blend_mode: mix_blend_mode::T) {
         let size = Size2D::new(image_info.width as i32, image_info.height as i32);
         let (pixel_width, source_format) = match image_info.format {
             PixelFormat::RGBA8 => (4, SurfaceFormat::B8G8R8A8),
@@ -227,7 +228,9 @@ impl<'a> PaintContext<'a> {
         };
 
         let draw_surface_options = DrawSurfaceOptions::new(draw_surface_filter, true);
-        let draw_options = DrawOptions::new(1.0, CompositionOp::Over, AntialiasMode::None);
+        let draw_options = DrawOptions::new(1.0,
+                                            blend_mode.to_azure_composition_op(),
+                                            AntialiasMode::None);
 
         // Fast path: No need to create a pattern.
         if bounds.size == *stretch_size && *tile_spacing == Size2D::zero() {
@@ -258,7 +261,7 @@ impl<'a> PaintContext<'a> {
 
         // Slow path: Both stretch and a pattern are needed.
         let draw_surface_options = DrawSurfaceOptions::new(draw_surface_filter, true);
-        let draw_options = DrawOptions::new(1.0, CompositionOp::Over, AntialiasMode::None);
+        let temporary_draw_options = DrawOptions::new(1.0, CompositionOp::Over, AntialiasMode::None);
         let temporary_target_size = (*stretch_size + *tile_spacing).to_nearest_azure_size(scale);
         let temporary_draw_target =
             self.draw_target.create_similar_draw_target(&temporary_target_size.to_azure_int_size(),
@@ -268,14 +271,14 @@ impl<'a> PaintContext<'a> {
                                            temporary_dest_rect,
                                            source_rect,
                                            draw_surface_options,
-                                           draw_options);
+                                           temporary_draw_options);
 
         let temporary_surface = temporary_draw_target.snapshot();
         let pattern = SurfacePattern::new(temporary_surface.azure_source_surface,
                                           true,
                                           true,
                                           &matrix);
-        draw_target_ref.fill_rect(&dest_rect, PatternRef::Surface(&pattern), None);
+        draw_target_ref.fill_rect(&dest_rect, PatternRef::Surface(&pattern), Some(&draw_options));
     }
 
     pub fn clear(&self) {
@@ -1356,7 +1359,7 @@ impl<'a> PaintContext<'a> {
         // For sideways text, it’s easier to do the rotation such that its center (the baseline’s
         // start point) is at (0, 0) coordinates.
         let baseline_origin = match text.orientation {
-            Upright => origin,
+            Upright | Vertical => origin,
             SidewaysLeft => {
                 let x = origin.x.to_f32_px();
                 let y = origin.y.to_f32_px();
@@ -1385,6 +1388,7 @@ impl<'a> PaintContext<'a> {
                            &*text.text_run,
                            &text.range,
                            baseline_origin,
+                           text.orientation == Vertical,
                            text.text_color,
                            opts::get().enable_text_antialiasing);
         }
@@ -1401,7 +1405,8 @@ impl<'a> PaintContext<'a> {
                                 bounds: &Rect<Au>,
                                 start_point: &Point2D<Au>,
                                 end_point: &Point2D<Au>,
-                                stops: &[GradientStop]) {
+                                stops: &[GradientStop],
+                                blend_mode: mix_blend_mode::T) {
         self.draw_target.make_current();
 
         let stops = self.draw_target.create_gradient_stops(stops, ExtendMode::Clamp);
@@ -1410,18 +1415,22 @@ impl<'a> PaintContext<'a> {
                                                  &end_point.to_nearest_azure_point(scale),
                                                  stops,
                                                  &Matrix2D::identity());
+        let draw_options = DrawOptions::new(1.0,
+                                            blend_mode.to_azure_composition_op(),
+                                            AntialiasMode::None);
         self.draw_target.fill_rect(&self.to_nearest_azure_rect(&bounds),
                                    PatternRef::LinearGradient(&pattern),
-                                   None);
+                                   Some(&draw_options));
     }
 
     pub fn get_or_create_temporary_draw_target(&mut self,
                                                filters: &filter::T,
-                                               blend_mode: mix_blend_mode::T)
+                                               blend_mode: mix_blend_mode::T,
+                                               isolated: bool)
                                                -> DrawTarget {
         // Determine if we need a temporary draw target.
         if !filters::temporary_draw_target_needed_for_style_filters(filters) &&
-                blend_mode == mix_blend_mode::T::normal {
+                blend_mode == mix_blend_mode::T::normal && !isolated {
             // Reuse the draw target, but remove the transient clip. If we don't do the latter,
             // we'll be in a state whereby the paint subcontext thinks it has no transient clip
             // (see `StackingContext::optimize_and_draw_into_context`) but it actually does,
@@ -1530,7 +1539,19 @@ impl<'a> PaintContext<'a> {
 
         // If we have blur, create a new draw target.
         let pixels_per_px = self.screen_pixels_per_px();
-        let shadow_bounds = box_bounds.translate(offset).inflate(spread_radius, spread_radius);
+        let shadow_bounds = match clip_mode {
+            // The spread of an inset shadow shrinks the box that the shadow is cast around, down
+            // to nothing at its center.
+            BoxShadowClipMode::Inset => {
+                let bounds = box_bounds.translate(offset);
+                let spread_radius = cmp::min(spread_radius,
+                                             cmp::min(bounds.size.width, bounds.size.height) / 2);
+                bounds.inflate(-spread_radius, -spread_radius)
+            }
+            BoxShadowClipMode::Outset | BoxShadowClipMode::None => {
+                box_bounds.translate(offset).inflate(spread_radius, spread_radius)
+            }
+        };
         let side_inflation = blur_radius * BLUR_INFLATION_FACTOR;
         let inflated_shadow_bounds = shadow_bounds.inflate(side_inflation, side_inflation);
         let temporary_draw_target =
@@ -1777,6 +1798,7 @@ trait ScaledFontExtensionMethods {
                  run: &TextRun,
                  range: &Range<ByteIndex>,
                  baseline_origin: Point2D<Au>,
+                 vertical: bool,
                  color: Color,
                  antialias: bool);
 }
@@ -1788,6 +1810,7 @@ impl ScaledFontExtensionMethods for ScaledFont {
                  run: &TextRun,
                  range: &Range<ByteIndex>,
                  baseline_origin: Point2D<Au>,
+                 vertical: bool,
                  color: Color,
                  antialias: bool) {
         let pattern = ColorPattern::new(color);
@@ -1825,7 +1848,11 @@ impl ScaledFontExtensionMethods for ScaledFont {
                     };
                     azglyphs.push(azglyph)
                 }
-                origin.x = origin.x + glyph_advance;
+                if vertical {
+                    origin.y = origin.y + glyph_advance;
+                } else {
+                    origin.x = origin.x + glyph_advance;
+                }
             };
         }
 
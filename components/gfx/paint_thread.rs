@@ -94,6 +94,11 @@ impl PaintLayer {
                 establishes_3d_context: establishes_3d_context,
                 scrolls_overflow_area: scrolls_overflow_area,
                 subpage_pipeline_id: layer_info.subpage_pipeline_id,
+                sticky: layer_info.sticky.map(|sticky| {
+                    sticky.translate(&Point2D::new(parent_origin.x.to_f32_px(),
+                                                   parent_origin.y.to_f32_px()))
+                }),
+                scrollbars: layer_info.scrollbars,
             },
             starting_stacking_context_id: stacking_context.id,
             display_list_indices: None,
@@ -131,6 +136,8 @@ impl PaintLayer {
                 establishes_3d_context: false,
                 scrolls_overflow_area: false,
                 subpage_pipeline_id: layer_info.subpage_pipeline_id,
+                sticky: None,
+                scrollbars: None,
             },
             starting_stacking_context_id: stacking_context_id,
             display_list_indices: Some((item_index, item_index)),
@@ -261,6 +268,10 @@ impl LayerCreator {
                                             transform,
                                             perspective);
             }
+            if kid.is_hidden_back_face() {
+                traversal.skip_past_stacking_context(kid);
+                continue
+            }
             self.create_layers_for_stacking_context(kid,
                                                     traversal,
                                                     parent_origin,
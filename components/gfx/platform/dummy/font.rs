@@ -71,6 +71,9 @@ impl FontHandleMethods for FontHandle {
     fn glyph_h_advance(&self, glyph: GlyphId) -> Option<FractionalPixel> {
         None
     }
+    fn glyph_descent(&self, glyph: GlyphId) -> Option<FractionalPixel> {
+        None
+    }
     fn metrics(&self) -> FontMetrics {
         unimplemented!()
     }
@@ -2,13 +2,14 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use ipc_channel::ipc::IpcSharedMemory;
 use std::io::Error;
 use string_cache::Atom;
 use webrender_traits::NativeFontHandle;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct FontTemplateData {
-    pub bytes: Vec<u8>,
+    pub bytes: IpcSharedMemory,
     pub identifier: Atom,
 }
 
@@ -24,12 +25,12 @@ impl FontTemplateData {
         };
 
         Ok(FontTemplateData {
-            bytes: bytes,
+            bytes: IpcSharedMemory::from_bytes(&bytes),
             identifier: identifier,
         })
     }
     pub fn bytes(&self) -> Vec<u8> {
-        self.bytes.clone()
+        self.bytes.to_vec()
     }
     pub fn bytes_if_in_memory(&self) -> Option<Vec<u8>> {
         Some(self.bytes())
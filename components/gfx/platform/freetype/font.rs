@@ -75,7 +75,7 @@ impl FontHandleMethods for FontHandle {
         let ft_ctx: FT_Library = fctx.ctx.ctx;
         if ft_ctx.is_null() { return Err(()); }
 
-        return create_face_from_buffer(ft_ctx, &template.bytes, pt_size).map(|face| {
+        return create_face_from_buffer(ft_ctx, &*template.bytes, pt_size).map(|face| {
             let mut handle = FontHandle {
                   face: face,
                   font_data: template.clone(),
@@ -202,6 +202,24 @@ impl FontHandleMethods for FontHandle {
         }
     }
 
+    fn glyph_descent(&self, glyph: GlyphId) -> Option<FractionalPixel> {
+        assert!(!self.face.is_null());
+        unsafe {
+            let res = FT_Load_Glyph(self.face, glyph as FT_UInt, 0);
+            if res.succeeded() {
+                let void_glyph = (*self.face).glyph;
+                let slot: FT_GlyphSlot = mem::transmute(void_glyph);
+                assert!(!slot.is_null());
+                let metrics = &(*slot).metrics;
+                let descent = (metrics.height - metrics.horiBearingY) as i32;
+                Some(fixed_to_float_ft(descent) as FractionalPixel)
+            } else {
+                debug!("Unable to load glyph {}. reason: {}", glyph, res);
+                None
+            }
+        }
+    }
+
     fn metrics(&self) -> FontMetrics {
         /* TODO(Issue #76): complete me */
         let face = self.face_rec_mut();
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use ipc_channel::ipc::IpcSharedMemory;
 use std::fs::File;
 use std::io::{Read, Error};
 use string_cache::Atom;
@@ -10,10 +11,12 @@ use webrender_traits::NativeFontHandle;
 /// Platform specific font representation for Linux.
 /// The identifier is an absolute path, and the bytes
 /// field is the loaded data that can be passed to
-/// freetype and azure directly.
+/// freetype and azure directly. The data lives in shared
+/// memory, so that all the pipelines using this font map
+/// the same copy instead of each receiving their own.
 #[derive(Deserialize, Serialize, Debug)]
 pub struct FontTemplateData {
-    pub bytes: Vec<u8>,
+    pub bytes: IpcSharedMemory,
     pub identifier: Atom,
 }
 
@@ -33,7 +36,7 @@ impl FontTemplateData {
         };
 
         Ok(FontTemplateData {
-            bytes: bytes,
+            bytes: IpcSharedMemory::from_bytes(&bytes),
             identifier: identifier,
         })
     }
@@ -42,7 +45,7 @@ impl FontTemplateData {
     /// operation (depending on the platform) which performs synchronous disk I/O
     /// and should never be done lightly.
     pub fn bytes(&self) -> Vec<u8> {
-        self.bytes.clone()
+        self.bytes.to_vec()
     }
 
     /// Returns a clone of the bytes in this font if they are in memory. This function never
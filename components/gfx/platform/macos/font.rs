@@ -290,6 +290,12 @@ impl FontHandleMethods for FontHandle {
         Some(advance as FractionalPixel)
     }
 
+    fn glyph_descent(&self, glyph: GlyphId) -> Option<FractionalPixel> {
+        let glyphs = [glyph as CGGlyph];
+        let rect = self.ctfont.get_bounding_rects_for_glyphs(kCTFontDefaultOrientation, &glyphs);
+        Some(-rect.origin.y as FractionalPixel)
+    }
+
     fn metrics(&self) -> FontMetrics {
         let bounding_rect: CGRect = self.ctfont.bounding_box();
         let ascent = self.ctfont.ascent() as f64;
@@ -7,6 +7,7 @@ use core_graphics::data_provider::CGDataProvider;
 use core_graphics::font::CGFont;
 use core_text;
 use core_text::font::CTFont;
+use ipc_channel::ipc::IpcSharedMemory;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{Error, Visitor};
 use std::borrow::ToOwned;
@@ -34,7 +35,9 @@ pub struct FontTemplateData {
     ctfont: CachedCTFont,
 
     pub identifier: Atom,
-    pub font_data: Option<Vec<u8>>
+    /// The data of a web font, in shared memory so that all the pipelines using it map the same
+    /// copy.
+    pub font_data: Option<IpcSharedMemory>
 }
 
 unsafe impl Send for FontTemplateData {}
@@ -45,7 +48,7 @@ impl FontTemplateData {
         Ok(FontTemplateData {
             ctfont: CachedCTFont(Mutex::new(HashMap::new())),
             identifier: identifier.to_owned(),
-            font_data: font_data
+            font_data: font_data.map(|bytes| IpcSharedMemory::from_bytes(&bytes))
         })
     }
 
@@ -59,7 +62,7 @@ impl FontTemplateData {
             let clamped_pt_size = pt_size.max(0.01);
             let ctfont = match self.font_data {
                 Some(ref bytes) => {
-                    let fontprov = CGDataProvider::from_buffer(bytes);
+                    let fontprov = CGDataProvider::from_buffer(&**bytes);
                     let cgfont_result = CGFont::from_data_provider(fontprov);
                     match cgfont_result {
                         Ok(cgfont) => {
@@ -102,7 +105,7 @@ impl FontTemplateData {
     /// Returns a clone of the bytes in this font if they are in memory. This function never
     /// performs disk I/O.
     pub fn bytes_if_in_memory(&self) -> Option<Vec<u8>> {
-        self.font_data.clone()
+        self.font_data.as_ref().map(|bytes| bytes.to_vec())
     }
 
     /// Returns the native font that underlies this font template, if applicable.
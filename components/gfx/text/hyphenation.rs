@@ -0,0 +1,122 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Hyphenation opportunities within words, for `hyphens: manual` and `hyphens: auto`. The latter
+//! uses the TeX pattern dictionaries bundled with the `hyphenation` crate.
+
+use hyphenation::{self, Corpus, Hyphenation, Language};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+thread_local! {
+    /// The dictionaries loaded so far on this thread, or `None` for those that failed to load.
+    static CORPORA: RefCell<HashMap<&'static str, Option<Rc<Corpus>>>> = RefCell::new(HashMap::new())
+}
+
+/// Returns the dictionary for a lowercase BCP 47 language tag, preferring one for the full tag
+/// (e.g. `en-gb`) over one for the primary language subtag (e.g. `en`).
+fn language_for_tag(tag: &str) -> Option<(&'static str, Language)> {
+    let language = match tag {
+        "en-gb" => Some(("en-gb", Language::English_GB)),
+        "de-ch" => Some(("de-ch", Language::German_Swiss)),
+        "de-1901" => Some(("de-1901", Language::German_1901)),
+        "el-polyton" => Some(("el-polyton", Language::Greek_Poly)),
+        "sr-latn" => Some(("sr-latn", Language::Serbocroatian_Latin)),
+        _ => None,
+    };
+    if language.is_some() {
+        return language
+    }
+
+    Some(match tag.split('-').next().unwrap_or("") {
+        "af" => ("af", Language::Afrikaans),
+        "bg" => ("bg", Language::Bulgarian),
+        "ca" => ("ca", Language::Catalan),
+        "cs" => ("cs", Language::Czech),
+        "cy" => ("cy", Language::Welsh),
+        "da" => ("da", Language::Danish),
+        "de" => ("de", Language::German_1996),
+        "el" => ("el", Language::Greek_Mono),
+        "en" => ("en", Language::English_US),
+        "es" => ("es", Language::Spanish),
+        "et" => ("et", Language::Estonian),
+        "eu" => ("eu", Language::Basque),
+        "fi" => ("fi", Language::Finnish),
+        "fr" => ("fr", Language::French),
+        "ga" => ("ga", Language::Irish),
+        "gl" => ("gl", Language::Galician),
+        "hr" => ("hr", Language::Croatian),
+        "hu" => ("hu", Language::Hungarian),
+        "hy" => ("hy", Language::Armenian),
+        "id" => ("id", Language::Indonesian),
+        "is" => ("is", Language::Icelandic),
+        "it" => ("it", Language::Italian),
+        "ka" => ("ka", Language::Georgian),
+        "la" => ("la", Language::Latin),
+        "lt" => ("lt", Language::Lithuanian),
+        "lv" => ("lv", Language::Latvian),
+        "nb" | "no" => ("nb", Language::Norwegian_Bokmal),
+        "nl" => ("nl", Language::Dutch),
+        "nn" => ("nn", Language::Norwegian_Nynorsk),
+        "pl" => ("pl", Language::Polish),
+        "pt" => ("pt", Language::Portuguese),
+        "ro" => ("ro", Language::Romanian),
+        "ru" => ("ru", Language::Russian),
+        "sk" => ("sk", Language::Slovak),
+        "sl" => ("sl", Language::Slovenian),
+        "sr" => ("sr", Language::Serbian_Cyrillic),
+        "sv" => ("sv", Language::Swedish),
+        "tr" => ("tr", Language::Turkish),
+        "uk" => ("uk", Language::Ukrainian),
+        _ => return None,
+    })
+}
+
+/// Returns the byte offsets within `word` at which it may be hyphenated, in increasing order.
+/// `lang` is the lowercase BCP 47 tag of the content language; words in an unknown language, or
+/// one with no bundled dictionary, are never hyphenated.
+pub fn hyphenation_opportunities(word: &str, lang: &str) -> Vec<usize> {
+    let (key, language) = match language_for_tag(lang) {
+        Some(language) => language,
+        None => return vec![],
+    };
+
+    let corpus = CORPORA.with(|corpora| {
+        corpora.borrow_mut().entry(key).or_insert_with(|| {
+            match hyphenation::load(language) {
+                Ok(corpus) => Some(Rc::new(corpus)),
+                Err(_) => {
+                    warn!("failed to load the hyphenation dictionary for {:?}", key);
+                    None
+                }
+            }
+        }).clone()
+    });
+
+    match corpus {
+        Some(corpus) => word.opportunities(&*corpus),
+        None => vec![],
+    }
+}
+
+/// Returns the last byte offset within `word` at which it may be hyphenated such that `fits`
+/// accepts it, trying them from the end of the word (CSS-TEXT-3 § 6.1). Soft hyphens (U+00AD)
+/// are always hyphenation opportunities. With `hyphens: auto`, `lang` is the lowercase BCP 47 tag
+/// of the content language, and the opportunities found in its dictionary are tried as well.
+pub fn last_hyphenation_point<F>(word: &str, lang: Option<&str>, fits: F) -> Option<usize>
+                                 where F: Fn(usize) -> bool {
+    let mut opportunities: Vec<usize> = word.char_indices()
+                                            .filter(|&(_, ch)| ch == '\u{ad}')
+                                            .map(|(index, ch)| index + ch.len_utf8())
+                                            .filter(|&index| index < word.len())
+                                            .collect();
+    if let Some(lang) = lang {
+        opportunities.extend(hyphenation_opportunities(word, lang));
+        opportunities.sort();
+        opportunities.dedup();
+    }
+
+    opportunities.into_iter().rev().find(|&index| fits(index))
+}
@@ -7,8 +7,8 @@
 use app_units::Au;
 use euclid::Point2D;
 use font::{DISABLE_KERNING_SHAPING_FLAG, Font, FontTableMethods, FontTableTag};
-use font::{IGNORE_LIGATURES_SHAPING_FLAG, KERN, RTL_FLAG, ShapingOptions};
-use harfbuzz::{HB_DIRECTION_LTR, HB_DIRECTION_RTL, HB_MEMORY_MODE_READONLY};
+use font::{IGNORE_LIGATURES_SHAPING_FLAG, KERN, RTL_FLAG, ShapingOptions, VERTICAL_FLAG};
+use harfbuzz::{HB_DIRECTION_LTR, HB_DIRECTION_RTL, HB_DIRECTION_TTB, HB_MEMORY_MODE_READONLY};
 use harfbuzz::{hb_blob_create, hb_face_create_for_tables};
 use harfbuzz::{hb_buffer_create, hb_font_destroy};
 use harfbuzz::{hb_buffer_get_glyph_infos, hb_shape};
@@ -29,6 +29,7 @@ use harfbuzz::hb_font_funcs_create;
 use harfbuzz::hb_font_funcs_set_glyph_func;
 use harfbuzz::hb_font_funcs_set_glyph_h_advance_func;
 use harfbuzz::hb_font_funcs_set_glyph_h_kerning_func;
+use harfbuzz::hb_font_funcs_set_glyph_v_advance_func;
 use harfbuzz::hb_font_set_funcs;
 use harfbuzz::hb_font_set_ppem;
 use harfbuzz::hb_font_set_scale;
@@ -90,7 +91,9 @@ impl ShapedGlyphData {
     }
 
     /// Returns shaped glyph data for one glyph, and updates the y-position of the pen.
-    pub fn entry_for_glyph(&self, i: usize, y_pos: &mut Au) -> ShapedGlyphEntry {
+    ///
+    /// In vertical text the pen moves down the line, so the advance is the vertical one.
+    pub fn entry_for_glyph(&self, i: usize, y_pos: &mut Au, vertical: bool) -> ShapedGlyphEntry {
         assert!(i < self.count);
 
         unsafe {
@@ -106,6 +109,20 @@ impl ShapedGlyphData {
             let x_advance = Au::from_f64_px(x_advance);
             let y_advance = Au::from_f64_px(y_advance);
 
+            if vertical {
+                // Harfbuzz's y axis points up, so both the advance and the offset are flipped.
+                let offset = if x_offset == Au(0) && y_offset == Au(0) {
+                    None
+                } else {
+                    Some(Point2D::new(x_offset, -y_offset))
+                };
+                return ShapedGlyphEntry {
+                    codepoint: (*glyph_info_i).codepoint as GlyphId,
+                    advance: -y_advance,
+                    offset: offset,
+                }
+            }
+
             let offset = if x_offset == Au(0) && y_offset == Au(0) && y_advance == Au(0) {
                 None
             } else {
@@ -189,7 +206,9 @@ impl ShaperMethods for Shaper {
     fn shape_text(&self, text: &str, options: &ShapingOptions, glyphs: &mut GlyphStore) {
         unsafe {
             let hb_buffer: *mut hb_buffer_t = hb_buffer_create();
-            hb_buffer_set_direction(hb_buffer, if options.flags.contains(RTL_FLAG) {
+            hb_buffer_set_direction(hb_buffer, if options.flags.contains(VERTICAL_FLAG) {
+                HB_DIRECTION_TTB
+            } else if options.flags.contains(RTL_FLAG) {
                 HB_DIRECTION_RTL
             } else {
                 HB_DIRECTION_LTR
@@ -268,6 +287,7 @@ impl Shaper {
         let mut byte_range = 0..0;
 
         let mut y_pos = Au(0);
+        let vertical = options.flags.contains(VERTICAL_FLAG);
 
         // main loop over each glyph. each iteration usually processes 1 glyph and 1+ chars.
         // in cases with complex glyph-character associations, 2+ glyphs and 1+ chars can be
@@ -356,7 +376,7 @@ impl Shaper {
                                               true);
                     glyphs.add_glyph_for_byte_index(byte_idx, character, &data);
                 } else {
-                    let shape = glyph_data.entry_for_glyph(glyph_span.start, &mut y_pos);
+                    let shape = glyph_data.entry_for_glyph(glyph_span.start, &mut y_pos, vertical);
                     let advance = self.advance_for_shaped_glyph(shape.advance, character, options);
                     let data = GlyphData::new(shape.codepoint,
                                               advance,
@@ -370,7 +390,7 @@ impl Shaper {
                 let mut datas = vec!();
 
                 for glyph_i in glyph_span.clone() {
-                    let shape = glyph_data.entry_for_glyph(glyph_i, &mut y_pos);
+                    let shape = glyph_data.entry_for_glyph(glyph_i, &mut y_pos, vertical);
                     datas.push(GlyphData::new(shape.codepoint,
                                               shape.advance,
                                               shape.offset,
@@ -421,6 +441,8 @@ lazy_static! {
             hb_funcs, Some(glyph_h_advance_func), ptr::null_mut(), None);
         hb_font_funcs_set_glyph_h_kerning_func(
             hb_funcs, Some(glyph_h_kerning_func), ptr::null_mut(), None);
+        hb_font_funcs_set_glyph_v_advance_func(
+            hb_funcs, Some(glyph_v_advance_func), ptr::null_mut(), None);
 
         ptr::Unique::new(hb_funcs)
     };
@@ -461,6 +483,21 @@ extern fn glyph_h_advance_func(_: *mut hb_font_t,
     }
 }
 
+extern fn glyph_v_advance_func(_: *mut hb_font_t,
+                               font_data: *mut c_void,
+                               _: hb_codepoint_t,
+                               _: *mut c_void)
+                            -> hb_position_t {
+    let font: *mut Font = font_data as *mut Font;
+    assert!(!font.is_null());
+
+    // Upright glyphs are set one em apart. Harfbuzz's y axis points up, hence the sign.
+    unsafe {
+        let advance = (*font).metrics.em_size.to_f64_px();
+        Shaper::float_to_fixed(-advance)
+    }
+}
+
 fn glyph_space_advance(font: *const Font) -> (hb_codepoint_t, f64) {
     let space_unicode = ' ';
     let space_glyph: hb_codepoint_t;
@@ -4,15 +4,16 @@
 
 use app_units::Au;
 use font::{Font, FontHandleMethods, FontMetrics, IS_WHITESPACE_SHAPING_FLAG, RunMetrics};
-use font::ShapingOptions;
+use font::{ShapingOptions, VERTICAL_FLAG};
 use platform::font_template::FontTemplateData;
 use range::Range;
 use std::cell::Cell;
 use std::cmp::{Ordering, max};
+use std::collections::HashMap;
 use std::slice::Iter;
 use std::sync::Arc;
 use style::str::char_is_whitespace;
-use text::glyph::{ByteIndex, GlyphStore};
+use text::glyph::{ByteIndex, GlyphId, GlyphStore};
 use webrender_traits;
 use xi_unicode::LineBreakIterator;
 
@@ -33,7 +34,12 @@ pub struct TextRun {
     /// The glyph runs that make up this text run.
     pub glyphs: Arc<Vec<GlyphRun>>,
     pub bidi_level: u8,
+    /// Whether this run was shaped as upright glyphs set vertically.
+    pub vertical: bool,
     pub extra_word_spacing: Au,
+    /// How far the ink of each glyph in this run extends below the baseline. Used to skip
+    /// descenders when painting underlines.
+    pub glyph_descents: Arc<HashMap<GlyphId, Au>>,
 }
 
 impl Drop for TextRun {
@@ -181,6 +187,13 @@ impl<'a> Iterator for CharacterSliceIterator<'a> {
 impl<'a> TextRun {
     pub fn new(font: &mut Font, text: String, options: &ShapingOptions, bidi_level: u8) -> TextRun {
         let glyphs = TextRun::break_and_shape(font, &text, options);
+        let mut glyph_descents = HashMap::new();
+        for glyph_run in &glyphs {
+            let range = Range::new(ByteIndex(0), glyph_run.range.length());
+            for glyph in glyph_run.glyph_store.iter_glyphs_for_byte_range(&range) {
+                glyph_descents.entry(glyph.id()).or_insert_with(|| font.glyph_descent(glyph.id()));
+            }
+        }
         TextRun {
             text: Arc::new(text),
             font_metrics: font.metrics.clone(),
@@ -189,7 +202,9 @@ impl<'a> TextRun {
             actual_pt_size: font.actual_pt_size,
             glyphs: Arc::new(glyphs),
             bidi_level: bidi_level,
+            vertical: options.flags.contains(VERTICAL_FLAG),
             extra_word_spacing: Au(0),
+            glyph_descents: Arc::new(glyph_descents),
         }
     }
 
@@ -266,6 +281,30 @@ impl<'a> TextRun {
                         self.font_metrics.descent)
     }
 
+    /// Returns the inline-direction extents, relative to the start of `range` in visual order,
+    /// of the glyphs whose ink extends more than `depth` below the baseline.
+    pub fn descender_extents_for_range(&self, range: &Range<ByteIndex>, depth: Au)
+                                       -> Vec<(Au, Au)> {
+        let mut extents = vec![];
+        let mut origin = Au(0);
+        for slice in self.natural_word_slices_in_visual_order(range) {
+            for glyph in slice.glyphs.iter_glyphs_for_byte_range(&slice.range) {
+                let advance = if glyph.char_is_space() {
+                    glyph.advance() + self.extra_word_spacing
+                } else {
+                    glyph.advance()
+                };
+                if !slice.glyphs.is_whitespace() &&
+                        self.glyph_descents.get(&glyph.id()).map_or(false, |&d| d > depth) {
+                    let start = origin + glyph.offset().map_or(Au(0), |offset| offset.x);
+                    extents.push((start, start + advance));
+                }
+                origin = origin + advance;
+            }
+        }
+        extents
+    }
+
     pub fn min_width_for_range(&self, range: &Range<ByteIndex>) -> Au {
         debug!("iterating outer range {:?}", range);
         self.natural_word_slices_in_range(range).fold(Au(0), |max_piece_width, slice| {
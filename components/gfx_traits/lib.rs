@@ -28,7 +28,9 @@ pub mod print_tree;
 pub use paint_listener::PaintListener;
 use azure::azure_hl::Color;
 use euclid::Matrix4D;
+use euclid::point::Point2D;
 use euclid::rect::Rect;
+use euclid::size::Size2D;
 use layers::layers::BufferRequest;
 use msg::constellation_msg::PipelineId;
 use profile_traits::mem::ReportsChan;
@@ -76,6 +78,269 @@ pub enum ScrollPolicy {
     FixedPosition,
 }
 
+/// The constraints of a `position: sticky` layer, which the compositor uses to move the layer
+/// as its scrolling ancestor scrolls. All rectangles are in the coordinate system of the parent
+/// layer, in pixels.
+///
+/// https://drafts.csswg.org/css-position/#sticky-pos
+#[derive(Clone, Copy, PartialEq, Deserialize, Serialize, Debug, HeapSizeOf)]
+pub struct StickyFrameInfo {
+    /// The resolved `top` offset, if it is not `auto`.
+    pub top: Option<f32>,
+    /// The resolved `right` offset, if it is not `auto`.
+    pub right: Option<f32>,
+    /// The resolved `bottom` offset, if it is not `auto`.
+    pub bottom: Option<f32>,
+    /// The resolved `left` offset, if it is not `auto`.
+    pub left: Option<f32>,
+    /// The border box of the element at its in-flow position.
+    pub frame: Rect<f32>,
+    /// The content box of the element's containing block, which the element never leaves.
+    pub containing_block: Rect<f32>,
+}
+
+impl StickyFrameInfo {
+    /// Returns these constraints moved by `offset`.
+    pub fn translate(&self, offset: &Point2D<f32>) -> StickyFrameInfo {
+        StickyFrameInfo {
+            frame: self.frame.translate(offset),
+            containing_block: self.containing_block.translate(offset),
+            ..*self
+        }
+    }
+
+    /// Returns the offset from its in-flow position at which the element is drawn when
+    /// `viewport` is the visible part of its scrolling ancestor.
+    pub fn offset_for_viewport(&self, viewport: &Rect<f32>) -> Point2D<f32> {
+        fn offset_along_axis(frame_start: f32,
+                             frame_end: f32,
+                             containing_block_start: f32,
+                             containing_block_end: f32,
+                             viewport_start: f32,
+                             viewport_end: f32,
+                             start_margin: Option<f32>,
+                             end_margin: Option<f32>)
+                             -> f32 {
+            // When both margins apply and cannot both be satisfied, the start margin wins.
+            let mut offset = 0.0f32;
+            if let Some(margin) = end_margin {
+                offset = offset.min(viewport_end - margin - frame_end);
+            }
+            if let Some(margin) = start_margin {
+                offset = offset.max(viewport_start + margin - frame_start);
+            }
+
+            // The element may only move within its containing block, and never further away
+            // from it than its in-flow position.
+            let min = (containing_block_start - frame_start).min(0.0);
+            let max = (containing_block_end - frame_end).max(0.0);
+            offset.max(min).min(max)
+        }
+
+        Point2D::new(offset_along_axis(self.frame.min_x(),
+                                       self.frame.max_x(),
+                                       self.containing_block.min_x(),
+                                       self.containing_block.max_x(),
+                                       viewport.min_x(),
+                                       viewport.max_x(),
+                                       self.left,
+                                       self.right),
+                     offset_along_axis(self.frame.min_y(),
+                                       self.frame.max_y(),
+                                       self.containing_block.min_y(),
+                                       self.containing_block.max_y(),
+                                       viewport.min_y(),
+                                       viewport.max_y(),
+                                       self.top,
+                                       self.bottom))
+    }
+}
+
+/// The direction in which a scrollbar scrolls.
+#[derive(Clone, Copy, PartialEq, Deserialize, Serialize, Debug, HeapSizeOf)]
+pub enum ScrollbarAxis {
+    Horizontal,
+    Vertical,
+}
+
+/// The scrollbars that layout paints for a scroll container, which the compositor hit tests so
+/// that the thumb can be dragged and the track clicked. All rectangles are in the coordinate
+/// system of the scroll container's layer, in pixels.
+///
+/// https://drafts.csswg.org/css-scrollbars-1/
+#[derive(Clone, Copy, PartialEq, Deserialize, Serialize, Debug, HeapSizeOf)]
+pub struct ScrollbarInfo {
+    /// The track of the horizontal scrollbar, if it is shown.
+    pub horizontal_track: Option<Rect<f32>>,
+    /// The track of the vertical scrollbar, if it is shown.
+    pub vertical_track: Option<Rect<f32>>,
+    /// The size of the visible part of the scroll container, which is its padding box.
+    pub client_size: Size2D<f32>,
+    /// The size of the scrollable overflow of the scroll container.
+    pub content_size: Size2D<f32>,
+}
+
+impl ScrollbarInfo {
+    pub fn track(&self, axis: ScrollbarAxis) -> Option<Rect<f32>> {
+        match axis {
+            ScrollbarAxis::Horizontal => self.horizontal_track,
+            ScrollbarAxis::Vertical => self.vertical_track,
+        }
+    }
+
+    /// Returns the scrollbar whose track contains `point`, if any.
+    pub fn hit_test(&self, point: &Point2D<f32>) -> Option<ScrollbarAxis> {
+        for &axis in &[ScrollbarAxis::Vertical, ScrollbarAxis::Horizontal] {
+            if let Some(track) = self.track(axis) {
+                if track.contains(point) {
+                    return Some(axis)
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the length of the thumb of the scrollbar along `axis`, so that the thumb is to
+    /// the track as the visible part of the scroll container is to its scrollable overflow.
+    pub fn thumb_length(&self, axis: ScrollbarAxis) -> f32 {
+        let (track_length, client_length, content_length) = match (axis, self.track(axis)) {
+            (_, None) => return 0.0,
+            (ScrollbarAxis::Horizontal, Some(track)) => {
+                (track.size.width, self.client_size.width, self.content_size.width)
+            }
+            (ScrollbarAxis::Vertical, Some(track)) => {
+                (track.size.height, self.client_size.height, self.content_size.height)
+            }
+        };
+        if content_length <= client_length {
+            return track_length
+        }
+        (track_length * client_length / content_length).max(track_length.min(20.0))
+    }
+
+    /// Returns how far the scroll container can scroll along `axis`.
+    pub fn scroll_range(&self, axis: ScrollbarAxis) -> f32 {
+        match axis {
+            ScrollbarAxis::Horizontal => self.content_size.width - self.client_size.width,
+            ScrollbarAxis::Vertical => self.content_size.height - self.client_size.height,
+        }.max(0.0)
+    }
+
+    /// Returns how far the thumb of the scrollbar along `axis` can move within its track.
+    pub fn thumb_travel(&self, axis: ScrollbarAxis) -> f32 {
+        let track_length = match (axis, self.track(axis)) {
+            (_, None) => return 0.0,
+            (ScrollbarAxis::Horizontal, Some(track)) => track.size.width,
+            (ScrollbarAxis::Vertical, Some(track)) => track.size.height,
+        };
+        (track_length - self.thumb_length(axis)).max(0.0)
+    }
+
+    /// Returns the thumb of the scrollbar along `axis` when the scroll container is scrolled
+    /// `fraction` of the way from the start to the end of its scrollable overflow.
+    pub fn thumb(&self, axis: ScrollbarAxis, fraction: f32) -> Option<Rect<f32>> {
+        let track = match self.track(axis) {
+            Some(track) => track,
+            None => return None,
+        };
+        let start = self.thumb_travel(axis) * fraction.max(0.0).min(1.0);
+        let length = self.thumb_length(axis);
+        Some(match axis {
+            ScrollbarAxis::Horizontal => {
+                Rect::new(Point2D::new(track.origin.x + start, track.origin.y),
+                          Size2D::new(length, track.size.height))
+            }
+            ScrollbarAxis::Vertical => {
+                Rect::new(Point2D::new(track.origin.x, track.origin.y + start),
+                          Size2D::new(track.size.width, length))
+            }
+        })
+    }
+
+    /// Returns what pressing the mouse at `point` on the scrollbar along `axis` does when the
+    /// scroll container is scrolled to `scroll_position`, or `None` if it has no such scrollbar.
+    pub fn press(&self, axis: ScrollbarAxis, point: &Point2D<f32>, scroll_position: &Point2D<f32>)
+                 -> Option<ScrollbarPress> {
+        let scroll_range = self.scroll_range(axis);
+        let position = component_along_axis(scroll_position, axis);
+        let fraction = if scroll_range > 0.0 { position / scroll_range } else { 0.0 };
+        let thumb = match self.thumb(axis, fraction) {
+            Some(thumb) => thumb,
+            None => return None,
+        };
+        if thumb.contains(point) {
+            return Some(ScrollbarPress::DragThumb)
+        }
+
+        // Pressing the track scrolls by a page towards the point pressed.
+        let page = component_along_axis(&Point2D::new(self.client_size.width, self.client_size.height), axis);
+        let delta = if component_along_axis(point, axis) < component_along_axis(&thumb.origin, axis) {
+            -page
+        } else {
+            page
+        };
+        let new_position = (position + delta).max(0.0).min(scroll_range);
+        Some(ScrollbarPress::ScrollTo(with_component_along_axis(scroll_position, axis, new_position)))
+    }
+
+    /// Returns the scroll position that keeps the thumb of the scrollbar along `axis` under the
+    /// mouse, once the mouse has moved `pointer_delta` along it since the thumb was pressed at
+    /// `start_scroll_position`.
+    pub fn drag_thumb(&self, axis: ScrollbarAxis, start_scroll_position: &Point2D<f32>, pointer_delta: f32)
+                      -> Point2D<f32> {
+        // The content moves as much further than the thumb as its scrollable overflow is longer
+        // than the space the thumb has to move in.
+        let thumb_travel = self.thumb_travel(axis);
+        if thumb_travel <= 0.0 {
+            return *start_scroll_position
+        }
+        let scroll_range = self.scroll_range(axis);
+        let start = component_along_axis(start_scroll_position, axis);
+        let new_position = (start + pointer_delta * scroll_range / thumb_travel).max(0.0).min(scroll_range);
+        with_component_along_axis(start_scroll_position, axis, new_position)
+    }
+}
+
+/// What pressing the mouse on a scrollbar does.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ScrollbarPress {
+    /// The thumb was pressed, and follows the mouse until it is released.
+    DragThumb,
+    /// The track was pressed, and the scroll container scrolls by a page to this position.
+    ScrollTo(Point2D<f32>),
+}
+
+/// Returns the coordinate of `point` along the axis of a scrollbar.
+fn component_along_axis(point: &Point2D<f32>, axis: ScrollbarAxis) -> f32 {
+    match axis {
+        ScrollbarAxis::Horizontal => point.x,
+        ScrollbarAxis::Vertical => point.y,
+    }
+}
+
+/// Returns `point` with its coordinate along the axis of a scrollbar replaced by `value`.
+fn with_component_along_axis(point: &Point2D<f32>, axis: ScrollbarAxis, value: f32) -> Point2D<f32> {
+    match axis {
+        ScrollbarAxis::Horizontal => Point2D::new(value, point.y),
+        ScrollbarAxis::Vertical => Point2D::new(point.x, value),
+    }
+}
+
+/// The scrollbars of a scroll container that WebRender renders. Layout sends these to the
+/// compositor, since it has no layers to hit test when WebRender is in use.
+#[derive(Clone, PartialEq, Deserialize, Serialize, Debug, HeapSizeOf)]
+pub struct ScrollContainerScrollbars {
+    /// The stacking context of the scroll container, whose children it scrolls.
+    pub stacking_context_id: StackingContextId,
+    /// The stacking contexts that scroll the scroll container itself, outermost first.
+    pub scrolled_by: Vec<StackingContextId>,
+    /// The origin of the border box of the scroll container, in the coordinate system of the
+    /// document when nothing is scrolled.
+    pub origin: Point2D<f32>,
+    /// The scrollbars, relative to `origin`.
+    pub scrollbars: ScrollbarInfo,
+}
+
 #[derive(Clone, PartialEq, Eq, Copy, Hash, Deserialize, Serialize, HeapSizeOf)]
 pub struct LayerId(
     /// The type of the layer. This serves to differentiate layers that share fragments.
@@ -151,6 +416,10 @@ pub struct LayerProperties {
     pub establishes_3d_context: bool,
     /// Whether this layer scrolls its overflow area.
     pub scrolls_overflow_area: bool,
+    /// The constraints of this layer, if it is sticky positioned.
+    pub sticky: Option<StickyFrameInfo>,
+    /// The scrollbars painted in this layer, if it scrolls its overflow area.
+    pub scrollbars: Option<ScrollbarInfo>,
 }
 
 /// A newtype struct for denoting the age of messages; prevents race conditions.
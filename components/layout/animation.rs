@@ -9,18 +9,22 @@ use flow::{self, Flow};
 use gfx::display_list::OpaqueNode;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
+use opaque_node::OpaqueNodeMethods;
 use script_layout_interface::restyle_damage::RestyleDamage;
-use script_traits::{AnimationState, LayoutMsg as ConstellationMsg};
+use script_traits::{AnimationEventType, AnimationState, ConstellationControlMsg, LayoutMsg as ConstellationMsg};
 use std::collections::HashMap;
 use std::sync::mpsc::Receiver;
+use string_cache::Atom;
 use style::animation::{Animation, update_style_for_animation};
 use style::dom::TRestyleDamage;
 use style::timer::Timer;
 
 /// Processes any new animations that were discovered after style recalculation.
 /// Also expire any old animations that have completed, inserting them into
-/// `expired_animations`.
+/// `expired_animations`, and sends the events of keyframes animations to
+/// script.
 pub fn update_animation_state(constellation_chan: &IpcSender<ConstellationMsg>,
+                              script_chan: &IpcSender<ConstellationControlMsg>,
                               running_animations: &mut HashMap<OpaqueNode, Vec<Animation>>,
                               expired_animations: &mut HashMap<OpaqueNode, Vec<Animation>>,
                               new_animations_receiver: &Receiver<Animation>,
@@ -60,11 +64,21 @@ pub fn update_animation_state(constellation_chan: &IpcSender<ConstellationMsg>,
     }
 
     let now = timer.seconds();
+    let send_event = |node: &OpaqueNode, name: &Atom, event_type: AnimationEventType, elapsed_time: f64| {
+        let msg = ConstellationControlMsg::AnimationEvent(pipeline_id,
+                                                          node.to_untrusted_node_address(),
+                                                          event_type,
+                                                          String::from(&**name),
+                                                          elapsed_time);
+        let _ = script_chan.send(msg);
+    };
+
     // Expire old running animations.
     //
-    // TODO: Do not expunge Keyframes animations, since we need that state if
-    // the animation gets re-triggered. Probably worth splitting in two
-    // different maps, or at least using a linked list?
+    // Keyframes animations that ran all their iterations are kept until the
+    // node stops using them, since they may fill forwards, and must not start
+    // again when the node is restyled.
+    let mut some_animation_finished = false;
     let mut keys_to_remove = vec![];
     for (key, running_animations) in running_animations.iter_mut() {
         let mut animations_still_running = vec![];
@@ -73,10 +87,26 @@ pub fn update_animation_state(constellation_chan: &IpcSender<ConstellationMsg>,
                 Animation::Transition(_, started_at, ref frame, _expired) => {
                     now < started_at + frame.duration
                 }
-                Animation::Keyframes(_, _, ref mut state) => {
-                    // This animation is still running, or we need to keep
-                    // iterating.
-                    now < state.started_at + state.duration || state.tick()
+                Animation::Keyframes(ref node, ref name, ref mut state) => {
+                    if !state.started && now >= state.started_at {
+                        state.started = true;
+                        send_event(node, name, AnimationEventType::AnimationStart, 0.);
+                    }
+
+                    if state.finished || now < state.started_at + state.duration {
+                        // This animation is still running, or filling
+                        // forwards.
+                    } else if state.tick() {
+                        // We need to keep iterating.
+                        if !state.is_paused() {
+                            send_event(node, name, AnimationEventType::AnimationIteration, state.elapsed_time());
+                        }
+                    } else {
+                        state.finished = true;
+                        some_animation_finished = true;
+                        send_event(node, name, AnimationEventType::AnimationEnd, state.elapsed_time());
+                    }
+                    true
                 }
             };
 
@@ -108,10 +138,14 @@ pub fn update_animation_state(constellation_chan: &IpcSender<ConstellationMsg>,
                           .push(new_running_animation);
     }
 
-    let animation_state = if running_animations.is_empty() {
-        AnimationState::NoAnimationsPresent
-    } else {
+    // Animations that just finished need one more tick to fill forwards or
+    // restore the style from the cascade.
+    let animations_present = some_animation_finished ||
+        running_animations.values().any(|animations| animations.iter().any(|animation| !animation.is_finished()));
+    let animation_state = if animations_present {
         AnimationState::AnimationsPresent
+    } else {
+        AnimationState::NoAnimationsPresent
     };
 
     constellation_chan.send(ConstellationMsg::ChangeRunningAnimationsState(pipeline_id, animation_state))
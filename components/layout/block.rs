@@ -32,7 +32,7 @@ use context::{LayoutContext, SharedLayoutContext};
 use display_list_builder::{BorderPaintingMode, DisplayListBuildState, FragmentDisplayListBuilding};
 use display_list_builder::BlockFlowDisplayListBuilding;
 use euclid::{Point2D, Rect, Size2D};
-use floats::{ClearType, FloatKind, Floats, PlacementInfo};
+use floats::{ClearType, FloatKind, FloatShape, Floats, PlacementInfo};
 use flow::{self, BaseFlow, EarlyAbsolutePositionInfo, Flow, FlowClass, ForceNonfloatedFlag};
 use flow::{BLOCK_POSITION_IS_STATIC, CLEARS_LEFT, CLEARS_RIGHT};
 use flow::{CONTAINS_TEXT_OR_REPLACED_FRAGMENTS, INLINE_POSITION_IS_STATIC};
@@ -41,7 +41,8 @@ use flow::{ImmutableFlowUtils, LateAbsolutePositionInfo, MutableFlowUtils, Opaqu
 use flow::IS_ABSOLUTELY_POSITIONED;
 use flow_list::FlowList;
 use flow_ref::FlowRef;
-use fragment::{CoordinateSystem, Fragment, FragmentBorderBoxIterator, HAS_LAYER, Overflow};
+use fragment::{CoordinateSystem, Fragment, FragmentBorderBoxIterator, HAS_LAYER, IS_FLEX_ITEM};
+use fragment::Overflow;
 use fragment::SpecificFragmentInfo;
 use gfx::display_list::{ClippingRegion, StackingContext};
 use gfx_traits::{LayerId, StackingContextId};
@@ -53,20 +54,34 @@ use rustc_serialize::{Encodable, Encoder};
 use script_layout_interface::restyle_damage::{BUBBLE_ISIZES, REFLOW, REFLOW_OUT_OF_FLOW};
 use script_layout_interface::restyle_damage::REPOSITION;
 use std::cmp::{max, min};
+use std::f64;
 use std::fmt;
 use std::sync::Arc;
-use style::computed_values::{border_collapse, box_sizing, display, float, overflow_x, overflow_y};
-use style::computed_values::{position, text_align, transform, transform_style};
+use style::computed_values::{border_collapse, box_sizing, break_after, break_before, break_inside};
+use style::computed_values::{display, float};
+use style::computed_values::{overflow_x, position, text_align, text_overflow, transform, transform_style};
 use style::context::{SharedStyleContext, StyleContext};
 use style::logical_geometry::{LogicalPoint, LogicalRect, LogicalSize, WritingMode};
 use style::properties::ServoComputedValues;
-use style::values::computed::{LengthOrNone, LengthOrPercentageOrNone};
+use style::values::computed::{ExtremumLength, LengthOrNone, LengthOrPercentageOrNone};
 use style::values::computed::{LengthOrPercentage, LengthOrPercentageOrAuto};
+use style::values::computed::basic_shape::{BasicShape, ShapeBox, ShapeRadius, ShapeSource};
 use util::geometry::max_rect;
 
 /// The number of screens of data we're allowed to generate display lists for in each direction.
 const DISPLAY_PORT_SIZE_FACTOR: i32 = 8;
 
+/// Resolves a `<shape-radius>`, given the distances from the center of the shape to the sides of
+/// its reference box that it is measured against.
+fn shape_radius(radius: ShapeRadius, percentage_basis: Au, sides: &[Au]) -> Au {
+    let sides = sides.iter().map(|side| max(*side, Au(0)));
+    match radius {
+        ShapeRadius::Length(length) => specified(length, percentage_basis),
+        ShapeRadius::ClosestSide => sides.min().unwrap_or(Au(0)),
+        ShapeRadius::FarthestSide => sides.max().unwrap_or(Au(0)),
+    }
+}
+
 /// Information specific to floated blocks.
 #[derive(Clone, RustcEncodable)]
 pub struct FloatedBlockInfo {
@@ -331,19 +346,25 @@ impl CandidateBSizeIterator {
                 MaybeAuto::Specified(block_container_block_size.scale_by(percent))
             }
             (LengthOrPercentageOrAuto::Calc(calc), Some(block_container_block_size)) => {
-                MaybeAuto::Specified(calc.length() + block_container_block_size.scale_by(calc.percentage()))
+                MaybeAuto::Specified(calc.to_used_value(block_container_block_size))
             }
             (LengthOrPercentageOrAuto::Percentage(_), None) |
             (LengthOrPercentageOrAuto::Auto, _) |
             (LengthOrPercentageOrAuto::Calc(_), _) => MaybeAuto::Auto,
             (LengthOrPercentageOrAuto::Length(length), _) => MaybeAuto::Specified(length),
         };
+        let block_size = match block_size {
+            MaybeAuto::Auto => {
+                fragment.block_size_from_aspect_ratio().map_or(MaybeAuto::Auto, MaybeAuto::Specified)
+            }
+            block_size => block_size,
+        };
         let max_block_size = match (fragment.style.max_block_size(), block_container_block_size) {
             (LengthOrPercentageOrNone::Percentage(percent), Some(block_container_block_size)) => {
                 Some(block_container_block_size.scale_by(percent))
             }
             (LengthOrPercentageOrNone::Calc(calc), Some(block_container_block_size)) => {
-                Some(block_container_block_size.scale_by(calc.percentage()) + calc.length())
+                Some(calc.to_used_value(block_container_block_size))
             }
             (LengthOrPercentageOrNone::Calc(_), _) |
             (LengthOrPercentageOrNone::Percentage(_), None) |
@@ -355,9 +376,9 @@ impl CandidateBSizeIterator {
                 block_container_block_size.scale_by(percent)
             }
             (LengthOrPercentage::Calc(calc), Some(block_container_block_size)) => {
-                calc.length() + block_container_block_size.scale_by(calc.percentage())
+                calc.to_used_value(block_container_block_size)
             }
-            (LengthOrPercentage::Calc(calc), None) => calc.length(),
+            (LengthOrPercentage::Calc(calc), None) => calc.to_used_value(Au(0)),
             (LengthOrPercentage::Percentage(_), None) => Au(0),
             (LengthOrPercentage::Length(length), _) => length,
         };
@@ -439,6 +460,39 @@ fn translate_including_floats(cur_b: &mut Au, delta: Au, floats: &mut Floats) {
     floats.translate(LogicalSize::new(writing_mode, Au(0), -delta));
 }
 
+/// Whether `break-inside` keeps a fragmentation break from happening inside this flow, when
+/// fragmenting into pages if `is_paged` is true and into columns otherwise.
+fn avoids_break_inside(flow: &Flow, is_paged: bool) -> bool {
+    flow.is_block_like() && match flow.as_block().fragment.style.get_box().break_inside {
+        break_inside::T::avoid => true,
+        break_inside::T::avoid_page => is_paged,
+        break_inside::T::avoid_column => !is_paged,
+        break_inside::T::auto => false,
+    }
+}
+
+/// Whether `break-before` forces a fragmentation break before this flow.
+fn forces_break_before(flow: &Flow, is_paged: bool) -> bool {
+    flow.is_block_like() && match flow.as_block().fragment.style.get_box().break_before {
+        break_before::T::page | break_before::T::left | break_before::T::right |
+        break_before::T::recto | break_before::T::verso => is_paged,
+        break_before::T::column => !is_paged,
+        break_before::T::auto | break_before::T::avoid | break_before::T::avoid_page |
+        break_before::T::avoid_column => false,
+    }
+}
+
+/// Whether `break-after` forces a fragmentation break after this flow.
+fn forces_break_after(flow: &Flow, is_paged: bool) -> bool {
+    flow.is_block_like() && match flow.as_block().fragment.style.get_box().break_after {
+        break_after::T::page | break_after::T::left | break_after::T::right |
+        break_after::T::recto | break_after::T::verso => is_paged,
+        break_after::T::column => !is_paged,
+        break_after::T::auto | break_after::T::avoid | break_after::T::avoid_page |
+        break_after::T::avoid_column => false,
+    }
+}
+
 /// The real assign-block-sizes traversal for flows with position 'absolute'.
 ///
 /// This is a traversal of an Absolute Flow tree.
@@ -518,6 +572,9 @@ pub struct BlockFlow {
 
     /// Various flags.
     flags: BlockFlowFlags,
+
+    /// The `::backdrop` painted over the viewport behind this block, if it is in the top layer.
+    pub backdrop: Option<Box<Fragment>>,
 }
 
 bitflags! {
@@ -546,6 +603,7 @@ impl BlockFlow {
             fragment: fragment,
             float: float_kind.map(|kind| box FloatedBlockInfo::new(kind)),
             flags: BlockFlowFlags::empty(),
+            backdrop: None,
         }
     }
 
@@ -746,6 +804,7 @@ impl BlockFlow {
             base: self.base.clone_with_children(new_children),
             fragment: self.fragment.clone(),
             float: self.float.clone(),
+            backdrop: self.backdrop.clone(),
             ..*self
         }
     }
@@ -815,6 +874,7 @@ impl BlockFlow {
             let mut floats = self.base.floats.clone();
             let thread_id = self.base.thread_id;
             let (mut had_floated_children, mut had_children_with_clearance) = (false, false);
+            let mut previous_forces_break = false;
             for (child_index, kid) in self.base.child_iter_mut().enumerate() {
                 if flow::base(kid).flags.contains(IS_ABSOLUTELY_POSITIONED) {
                     // Assume that the *hypothetical box* for an absolute flow starts immediately
@@ -840,9 +900,27 @@ impl BlockFlow {
 
                 let previous_b = cur_b;
                 if let Some(ctx) = fragmentation_context {
+                    // Forced breaks move this child and the ones after it to the next fragment,
+                    // unless nothing has been put in this one yet.
+                    let forces_break = previous_forces_break ||
+                        forces_break_before(&*kid, ctx.is_paged);
+                    if forces_break && !ctx.this_fragment_is_empty {
+                        break_at = Some((child_index, None));
+                        break
+                    }
+                    previous_forces_break = forces_break_after(&*kid, ctx.is_paged);
+
+                    // A child that avoids breaks inside itself is laid out whole. If it does not
+                    // fit, the check below moves all of it to the next fragment.
+                    let available_block_size = if avoids_break_inside(&*kid, ctx.is_paged) {
+                        MAX_AU
+                    } else {
+                        ctx.available_block_size - cur_b
+                    };
                     let child_ctx = FragmentationContext {
-                        available_block_size: ctx.available_block_size - cur_b,
+                        available_block_size: available_block_size,
                         this_fragment_is_empty: ctx.this_fragment_is_empty,
+                        is_paged: ctx.is_paged,
                     };
                     if let Some(remaining) = kid.fragment(layout_context, Some(child_ctx)) {
                         break_at = Some((child_index + 1, Some(remaining)));
@@ -986,6 +1064,11 @@ impl BlockFlow {
                 block_size = block_size + floats.clearance(ClearType::Both);
             }
 
+            // Boxes with size containment are sized as if they were empty.
+            if self.fragment.style.has_size_containment() {
+                block_size = Au(0);
+            }
+
             if self.base.flags.contains(IS_ABSOLUTELY_POSITIONED) {
                 // FIXME(#2003, pcwalton): The max is taken here so that you can scroll the page,
                 // but this is not correct behavior according to CSS 2.1 § 10.5. Instead I think we
@@ -1132,6 +1215,7 @@ impl BlockFlow {
             ceiling: clearance + float_info.float_ceiling,
             max_inline_size: float_info.containing_inline_size,
             kind: float_info.float_kind,
+            shape: self.float_shape(float_info.containing_inline_size).map(Arc::new),
         };
 
         // Place the float and return the `Floats` back to the parent flow.
@@ -1161,6 +1245,106 @@ impl BlockFlow {
                                                           self.base.position.size);
     }
 
+    /// Computes the float area given by `shape-outside`, in the writing mode of our floats and
+    /// relative to the start corner of our margin box.
+    ///
+    /// https://drafts.csswg.org/css-shapes/#shape-outside-property
+    fn float_shape(&self, containing_inline_size: Au) -> Option<FloatShape> {
+        let style = self.fragment.style();
+        let (basic_shape, reference_box) = match style.get_box().shape_outside {
+            // FIXME: Shapes from images are not supported.
+            ShapeSource::None | ShapeSource::Url(..) => return None,
+            ShapeSource::Box(reference_box) => (None, reference_box),
+            ShapeSource::Shape(ref basic_shape, reference_box) => {
+                (Some(basic_shape), reference_box.unwrap_or(ShapeBox::Margin))
+            }
+        };
+
+        let writing_mode = style.writing_mode;
+        let margin = self.fragment.margin;
+        let border_box_size = self.fragment.border_box.size;
+        let margin_box_size =
+            LogicalSize::new(writing_mode,
+                             border_box_size.inline + margin.inline_start_end(),
+                             border_box_size.block + margin.block_start_end()).to_physical(writing_mode);
+        let border_box = LogicalRect::new(writing_mode,
+                                          margin.inline_start,
+                                          margin.block_start,
+                                          border_box_size.inline,
+                                          border_box_size.block);
+        let reference_box = match reference_box {
+            ShapeBox::Margin => border_box + margin,
+            ShapeBox::Border => border_box,
+            ShapeBox::Padding => border_box - self.fragment.border_width(),
+            ShapeBox::Content => border_box - self.fragment.border_padding,
+        }.to_physical(writing_mode, margin_box_size);
+
+        let floats_writing_mode = self.base.floats.writing_mode;
+        let shape_margin = specified(style.get_box().shape_margin, containing_inline_size);
+        let (width, height) = (reference_box.size.width, reference_box.size.height);
+        let point = |x: LengthOrPercentage, y: LengthOrPercentage| {
+            Point2D::new(reference_box.origin.x + specified(x, width),
+                         reference_box.origin.y + specified(y, height))
+        };
+        let ellipse = |center: Point2D<Au>, radius_x: Au, radius_y: Au| {
+            FloatShape::Ellipse(
+                LogicalPoint::from_physical(floats_writing_mode, center, margin_box_size),
+                LogicalSize::from_physical(floats_writing_mode,
+                                           Size2D::new(radius_x + shape_margin,
+                                                       radius_y + shape_margin)))
+        };
+
+        Some(match basic_shape {
+            None => {
+                FloatShape::Inset(LogicalRect::from_physical(
+                    floats_writing_mode,
+                    reference_box.inflate(shape_margin, shape_margin),
+                    margin_box_size))
+            }
+            Some(&BasicShape::Inset(ref inset)) => {
+                let origin = point(inset.left, inset.top);
+                let size = Size2D::new(width - specified(inset.left, width) -
+                                       specified(inset.right, width),
+                                       height - specified(inset.top, height) -
+                                       specified(inset.bottom, height));
+                FloatShape::Inset(LogicalRect::from_physical(
+                    floats_writing_mode,
+                    Rect::new(origin, size).inflate(shape_margin, shape_margin),
+                    margin_box_size))
+            }
+            Some(&BasicShape::Circle(ref circle)) => {
+                let center = point(circle.position.horizontal, circle.position.vertical);
+                let sides = [center.x - reference_box.origin.x,
+                             reference_box.max_x() - center.x,
+                             center.y - reference_box.origin.y,
+                             reference_box.max_y() - center.y];
+                let diagonal = Au::from_f64_px(
+                    width.to_f64_px().hypot(height.to_f64_px()) / f64::consts::SQRT_2);
+                let radius = shape_radius(circle.radius, diagonal, &sides);
+                ellipse(center, radius, radius)
+            }
+            Some(&BasicShape::Ellipse(ref ellipse_shape)) => {
+                let center = point(ellipse_shape.position.horizontal,
+                                   ellipse_shape.position.vertical);
+                let radius_x = shape_radius(ellipse_shape.semiaxis_x,
+                                            width,
+                                            &[center.x - reference_box.origin.x,
+                                              reference_box.max_x() - center.x]);
+                let radius_y = shape_radius(ellipse_shape.semiaxis_y,
+                                            height,
+                                            &[center.y - reference_box.origin.y,
+                                              reference_box.max_y() - center.y]);
+                ellipse(center, radius_x, radius_y)
+            }
+            Some(&BasicShape::Polygon(ref polygon)) => {
+                let vertices = polygon.coordinates.iter().map(|&(x, y)| {
+                    LogicalPoint::from_physical(floats_writing_mode, point(x, y), margin_box_size)
+                }).collect();
+                FloatShape::Polygon(vertices, shape_margin)
+            }
+        })
+    }
+
     pub fn explicit_block_containing_size(&self, shared_context: &SharedStyleContext) -> Option<Au> {
         if self.is_root() || self.is_fixed() {
             let viewport_size = LogicalSize::from_physical(self.fragment.style.writing_mode,
@@ -1176,10 +1360,15 @@ impl BlockFlow {
 
     pub fn explicit_block_size(&self, containing_block_size: Option<Au>) -> Option<Au> {
         let content_block_size = self.fragment.style().content_block_size();
+        if content_block_size == LengthOrPercentageOrAuto::Auto {
+            if let Some(block_size) = self.fragment.block_size_from_aspect_ratio() {
+                return Some(block_size)
+            }
+        }
 
         match (content_block_size, containing_block_size) {
             (LengthOrPercentageOrAuto::Calc(calc), Some(container_size)) => {
-                Some(container_size.scale_by(calc.percentage()) + calc.length())
+                Some(calc.to_used_value(container_size))
             }
             (LengthOrPercentageOrAuto::Length(length), _) => Some(length),
             (LengthOrPercentageOrAuto::Percentage(percent), Some(container_size)) => {
@@ -1385,7 +1574,7 @@ impl BlockFlow {
                 let kid_base = flow::mut_base(kid);
                 if kid_base.flags.contains(INLINE_POSITION_IS_STATIC) {
                     kid_base.position.start.i =
-                        if kid_mode.is_bidi_ltr() == containing_block_mode.is_bidi_ltr() {
+                        if kid_mode.has_same_inline_direction(containing_block_mode) {
                             inline_start_content_edge
                         } else {
                             // The kid's inline 'start' is at the parent's 'end'
@@ -1418,10 +1607,26 @@ impl BlockFlow {
                 kid.as_mut_inline().first_line_indentation =
                     specified(self.fragment.style().get_inheritedtext().text_indent,
                               containing_block_size);
+                kid.as_mut_inline().text_overflow = self.text_overflow_for_inline_children();
             }
         }
     }
 
+    /// Returns the `text-overflow` that applies to the lines of our inline children. Per
+    /// CSS-OVERFLOW-3 § 4.1 it only takes effect if we clip our overflow in the inline direction.
+    fn text_overflow_for_inline_children(&self) -> text_overflow::T {
+        let style = self.fragment.style();
+        let inline_overflow = if style.writing_mode.is_vertical() {
+            style.get_box().overflow_y.0
+        } else {
+            style.get_box().overflow_x
+        };
+        match inline_overflow {
+            overflow_x::T::visible => text_overflow::T::clip,
+            _ => style.get_text().text_overflow,
+        }
+    }
+
     /// Determines the type of formatting context this is. See the definition of
     /// `FormattingContextType`.
     pub fn formatting_context_type(&self) -> FormattingContextType {
@@ -1437,8 +1642,12 @@ impl BlockFlow {
             display::T::inline_block => {
                 FormattingContextType::Other
             }
-            _ if style.get_box().overflow_x != overflow_x::T::visible ||
-                    style.get_box().overflow_y != overflow_y::T(overflow_x::T::visible) ||
+            // Layout and paint containment establish an independent formatting context.
+            // Unlike the other values, `overflow: clip` does not make the box a scroll
+            // container, so it does not establish one.
+            _ if style.is_scroll_container() ||
+                    style.has_layout_containment() ||
+                    style.has_paint_containment() ||
                     style.is_multicol() => {
                 FormattingContextType::Block
             }
@@ -1472,6 +1681,7 @@ impl BlockFlow {
             ceiling: self.base.position.start.b,
             max_inline_size: MAX_AU,
             kind: FloatKind::Left,
+            shape: None,
         };
 
         // Offset our position by whatever displacement is needed to not impact the floats.
@@ -1586,6 +1796,22 @@ impl BlockFlow {
             max(computation.content_intrinsic_sizes.preferred_inline_size,
                 preferred_inline_size_of_children_without_text_or_replaced_fragments);
 
+        // A block sized by `min-content` or `max-content` contributes that size to both of its
+        // intrinsic sizes.
+        //
+        // FIXME: `min-width` and `max-width` keywords should clamp the contributions too.
+        match self.fragment.style.logical_inline_size().keyword() {
+            Some(ExtremumLength::MinContent) => {
+                computation.content_intrinsic_sizes.preferred_inline_size =
+                    computation.content_intrinsic_sizes.minimum_inline_size
+            }
+            Some(ExtremumLength::MaxContent) => {
+                computation.content_intrinsic_sizes.minimum_inline_size =
+                    computation.content_intrinsic_sizes.preferred_inline_size
+            }
+            Some(ExtremumLength::FitContent) | None => {}
+        }
+
         self.base.intrinsic_inline_sizes = computation.finish();
         self.base.flags = flags
     }
@@ -1597,6 +1823,12 @@ impl BlockFlow {
             return
         }
 
+        // So do sticky ones, so that the compositor can move them while scrolling.
+        if self.is_sticky() {
+            self.base.flags.insert(NEEDS_LAYER);
+            return
+        }
+
         // This flow needs a layer if it has a 3d transform, or provides perspective
         // to child layers. See http://dev.w3.org/csswg/css-transforms/#3d-rendering-contexts.
         let has_3d_transform = self.fragment.style().transform_requires_layer();
@@ -1703,6 +1935,18 @@ impl BlockFlow {
             speculated_left_float_size - speculated_right_float_size
     }
 
+    /// Returns the content inline-size that a sizing keyword gives this block, when
+    /// `available_inline_size` is available to its content box. Intrinsic inline-sizes must have
+    /// been computed.
+    fn inline_size_for_keyword(&self, keyword: ExtremumLength, available_inline_size: Au) -> Au {
+        let surrounding_inline_size = self.fragment.surrounding_intrinsic_inline_size();
+        let intrinsic_inline_sizes = &self.base.intrinsic_inline_sizes;
+        model::size_for_keyword(keyword,
+                                intrinsic_inline_sizes.minimum_inline_size - surrounding_inline_size,
+                                intrinsic_inline_sizes.preferred_inline_size - surrounding_inline_size,
+                                available_inline_size)
+    }
+
     fn definitely_has_zero_block_size(&self) -> bool {
         if !self.fragment.style.content_block_size().is_definitely_zero() {
             return false
@@ -1716,7 +1960,8 @@ impl BlockFlow {
     }
 
     pub fn mark_as_flex(&mut self) {
-        self.flags.insert(IS_FLEX)
+        self.flags.insert(IS_FLEX);
+        self.fragment.flags.insert(IS_FLEX_ITEM)
     }
 
     pub fn is_flex(&self) -> bool {
@@ -1746,9 +1991,12 @@ impl Flow for BlockFlow {
     fn bubble_inline_sizes(&mut self) {
         // If this block has a fixed width, just use that for the minimum and preferred width,
         // rather than bubbling up children inline width.
-        let consult_children = match self.fragment.style().get_position().width {
+        let consult_children = match self.fragment.style().get_position().width.length_or_auto() {
             LengthOrPercentageOrAuto::Length(_) => false,
-            _ => true,
+            // Boxes with size containment are sized as if they were empty in the inline axis.
+            //
+            // FIXME: this should be the inline axis of the container's writing mode.
+            _ => !self.fragment.style().has_inline_size_containment(),
         };
         self.bubble_inline_sizes_for_block(consult_children);
         self.fragment.restyle_damage.remove(BUBBLE_ISIZES);
@@ -1870,9 +2118,7 @@ impl Flow for BlockFlow {
             self.fragment.flags.insert(HAS_LAYER)
         }
 
-        // FIXME (mbrubeck): Get the real container size, taking the container writing mode into
-        // account.  Must handle vertical writing modes.
-        let container_size = Size2D::new(self.base.block_container_inline_size, Au(0));
+        let container_size = self.base.block_container_physical_size();
 
         if self.is_root() {
             self.base.clip = ClippingRegion::max();
@@ -1968,10 +2214,6 @@ impl Flow for BlockFlow {
                     .late_absolute_position_info
                     .stacking_relative_position_of_absolute_containing_block
             };
-        let late_absolute_position_info_for_children = LateAbsolutePositionInfo {
-            stacking_relative_position_of_absolute_containing_block:
-                stacking_relative_position_of_absolute_containing_block_for_children,
-        };
         let container_size_for_children =
             self.base.position.size.to_physical(self.base.writing_mode);
 
@@ -2030,6 +2272,13 @@ impl Flow for BlockFlow {
             &mut clip,
             &stacking_relative_border_box);
 
+        let late_absolute_position_info_for_children = LateAbsolutePositionInfo {
+            stacking_relative_position_of_absolute_containing_block:
+                stacking_relative_position_of_absolute_containing_block_for_children,
+            stacking_relative_content_box_of_containing_block:
+                self.fragment.stacking_relative_content_box(&stacking_relative_border_box),
+        };
+
         // Process children.
         for kid in self.base.child_iter_mut() {
             // If this layer preserves the 3d context of children,
@@ -2087,20 +2336,18 @@ impl Flow for BlockFlow {
                             transform::ComputedOperation::Translate(tx, ty, _) => {
                                 // N.B. When the clipping value comes from us, it
                                 // shouldn't be transformed.
-                                let tx = if let overflow_x::T::hidden = kid.fragment.style().get_box()
-                                                                           .overflow_x {
-                                    Au(0)
-                                } else {
-                                    model::specified(tx, kid.base.block_container_inline_size)
+                                let tx = match kid.fragment.style().get_box().overflow_x {
+                                    overflow_x::T::hidden | overflow_x::T::clip => Au(0),
+                                    _ => model::specified(tx, kid.base.block_container_inline_size),
                                 };
-                                let ty = if let overflow_x::T::hidden = kid.fragment.style().get_box()
-                                                                           .overflow_y.0 {
-                                    Au(0)
-                                } else {
-                                    model::specified(
-                                        ty,
-                                        kid.base.block_container_explicit_block_size.unwrap_or(Au(0))
-                                    )
+                                let ty = match kid.fragment.style().get_box().overflow_y.0 {
+                                    overflow_x::T::hidden | overflow_x::T::clip => Au(0),
+                                    _ => {
+                                        model::specified(
+                                            ty,
+                                            kid.base.block_container_explicit_block_size.unwrap_or(Au(0))
+                                        )
+                                    }
                                 };
                                 let off = Point2D::new(tx, ty);
                                 clip = clip.translate(&-off);
@@ -2342,11 +2589,23 @@ pub trait ISizeAndMarginsComputer {
             (_, box_sizing::T::content_box) => {}
         }
 
+        let available_inline_size = containing_block_inline_size -
+            block.fragment.border_padding.inline_start_end();
+
+        // The sizing keywords size blocks to their contents. Replaced content keeps its natural
+        // size, as with `auto`.
+        if let Some(keyword) = style.logical_inline_size().keyword() {
+            if !block.is_replaced_content() {
+                let available_inline_size = available_inline_size -
+                    block.fragment.margin.inline_start_end();
+                computed_inline_size =
+                    MaybeAuto::Specified(block.inline_size_for_keyword(keyword, available_inline_size))
+            }
+        }
+
         let margin = style.logical_margin();
         let position = style.logical_position();
 
-        let available_inline_size = containing_block_inline_size -
-            block.fragment.border_padding.inline_start_end();
         ISizeConstraintInput::new(computed_inline_size,
                                   MaybeAuto::from_style(margin.inline_start,
                                                         containing_block_inline_size),
@@ -2390,9 +2649,8 @@ pub trait ISizeAndMarginsComputer {
             fragment.border_box.size.inline = inline_size;
 
             // Start border edge.
-            // FIXME (mbrubeck): Handle vertical writing modes.
             fragment.border_box.start.i =
-                if container_mode.is_bidi_ltr() == block_mode.is_bidi_ltr() {
+                if container_mode.has_same_inline_direction(block_mode) {
                     fragment.margin.inline_start
                 } else {
                     // The parent's "start" direction is the child's "end" direction.
@@ -2458,11 +2716,27 @@ pub trait ISizeAndMarginsComputer {
 
         let mut solution = self.solve_inline_size_constraints(block, &input);
 
+        // The sizing keywords in 'min-inline-size' and 'max-inline-size' are resolved against the
+        // contents of non-replaced blocks, and ignored otherwise.
+        let (min_inline_size_keyword, max_inline_size_keyword) = if block.is_replaced_content() {
+            (None, None)
+        } else {
+            let style = block.fragment.style();
+            (style.logical_min_inline_size().keyword(), style.logical_max_inline_size().keyword())
+        };
+        let available_inline_size = input.available_inline_size -
+            input.inline_start_margin.specified_or_zero() -
+            input.inline_end_margin.specified_or_zero();
+
         // If the tentative used inline-size is greater than 'max-inline-size', inline-size should
         // be recalculated, but this time using the computed value of 'max-inline-size' as the
         // computed value for 'inline-size'.
-        match specified_or_none(block.fragment().style().max_inline_size(),
-                                containing_block_inline_size) {
+        let max_inline_size = match max_inline_size_keyword {
+            Some(keyword) => Some(block.inline_size_for_keyword(keyword, available_inline_size)),
+            None => specified_or_none(block.fragment().style().max_inline_size(),
+                                      containing_block_inline_size),
+        };
+        match max_inline_size {
             Some(max_inline_size) if max_inline_size < solution.inline_size => {
                 input.computed_inline_size = MaybeAuto::Specified(max_inline_size);
                 solution = self.solve_inline_size_constraints(block, &input);
@@ -2473,8 +2747,10 @@ pub trait ISizeAndMarginsComputer {
         // If the resulting inline-size is smaller than 'min-inline-size', inline-size should be
         // recalculated, but this time using the value of 'min-inline-size' as the computed value
         // for 'inline-size'.
-        let computed_min_inline_size = specified(block.fragment().style().min_inline_size(),
-                                                 containing_block_inline_size);
+        let computed_min_inline_size = match min_inline_size_keyword {
+            Some(keyword) => block.inline_size_for_keyword(keyword, available_inline_size),
+            None => specified(block.fragment().style().min_inline_size(), containing_block_inline_size),
+        };
         if computed_min_inline_size > solution.inline_size {
             input.computed_inline_size = MaybeAuto::Specified(computed_min_inline_size);
             solution = self.solve_inline_size_constraints(block, &input);
@@ -2507,8 +2783,7 @@ pub trait ISizeAndMarginsComputer {
         let container_mode = block.base.block_container_writing_mode;
         let block_align = block.base.flags.text_align();
 
-        // FIXME (mbrubeck): Handle vertical writing modes.
-        let parent_has_same_direction = container_mode.is_bidi_ltr() == block_mode.is_bidi_ltr();
+        let parent_has_same_direction = container_mode.has_same_inline_direction(block_mode);
 
         // If inline-size is not 'auto', and inline-size + margins > available_inline-size, all
         // 'auto' margins are treated as 0.
@@ -2642,8 +2917,7 @@ impl ISizeAndMarginsComputer for AbsoluteNonReplaced {
         let block_mode = block.base.writing_mode;
         let container_mode = block.base.block_container_writing_mode;
 
-        // FIXME (mbrubeck): Handle vertical writing modes.
-        let parent_has_same_direction = container_mode.is_bidi_ltr() == block_mode.is_bidi_ltr();
+        let parent_has_same_direction = container_mode.has_same_inline_direction(block_mode);
 
         let (inline_start, inline_size, margin_inline_start, margin_inline_end) =
             match (inline_start, inline_end, computed_inline_size) {
@@ -22,7 +22,7 @@ use floats::FloatKind;
 use flow::{self, AbsoluteDescendants, IS_ABSOLUTELY_POSITIONED, ImmutableFlowUtils};
 use flow::{CAN_BE_FRAGMENTED, MutableFlowUtils, MutableOwnedFlowUtils};
 use flow_ref::{self, FlowRef};
-use fragment::{CanvasFragmentInfo, ImageFragmentInfo, InlineAbsoluteFragmentInfo};
+use fragment::{CanvasFragmentInfo, CueLines, ImageFragmentInfo, InlineAbsoluteFragmentInfo};
 use fragment::{Fragment, GeneratedContentInfo, IframeFragmentInfo};
 use fragment::{InlineAbsoluteHypotheticalFragmentInfo, TableColumnFragmentInfo};
 use fragment::{InlineBlockFragmentInfo, SpecificFragmentInfo, UnscannedTextFragmentInfo};
@@ -32,8 +32,11 @@ use inline::{FIRST_FRAGMENT_OF_ELEMENT, InlineFlow, InlineFragmentNodeFlags};
 use inline::{InlineFragmentNodeInfo, LAST_FRAGMENT_OF_ELEMENT};
 use linked_list::prepend_from;
 use list_item::{ListItemFlow, ListStyleTypeContent};
+use math::{MathFlow, MathLayoutKind};
 use multicol::{MulticolColumnFlow, MulticolFlow};
+use paged::{self, PagedFlow};
 use parallel;
+use ruby::RubyFlow;
 use script_layout_interface::{LayoutElementType, LayoutNodeType, is_image_data};
 use script_layout_interface::restyle_damage::{BUBBLE_ISIZES, RECONSTRUCT_FLOW, RestyleDamage};
 use script_layout_interface::wrapper_traits::{PseudoElementType, ThreadSafeLayoutElement, ThreadSafeLayoutNode};
@@ -44,7 +47,7 @@ use std::mem;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use style::computed_values::{caption_side, display, empty_cells, float, list_style_position};
-use style::computed_values::content::ContentItem;
+use style::computed_values::content::{self, ContentItem};
 use style::computed_values::position;
 use style::context::SharedStyleContext;
 use style::properties::{self, ServoComputedValues};
@@ -56,7 +59,7 @@ use table_colgroup::TableColGroupFlow;
 use table_row::TableRowFlow;
 use table_rowgroup::TableRowGroupFlow;
 use table_wrapper::TableWrapperFlow;
-use text::TextRunScanner;
+use text::{self, TextRunScanner};
 use traversal::PostorderNodeMutTraversal;
 use url::Url;
 use util::opts;
@@ -309,12 +312,14 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
             Some(LayoutNodeType::Element(LayoutElementType::HTMLImageElement)) => {
                 let image_info = box ImageFragmentInfo::new(node,
                                                             node.image_url(),
+                                                            node.image_density(),
                                                             &self.layout_context.shared);
                 SpecificFragmentInfo::Image(image_info)
             }
             Some(LayoutNodeType::Element(LayoutElementType::HTMLObjectElement)) => {
                 let image_info = box ImageFragmentInfo::new(node,
                                                             node.object_data(),
+                                                            1.0,
                                                             &self.layout_context.shared);
                 SpecificFragmentInfo::Image(image_info)
             }
@@ -331,9 +336,23 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
             Some(LayoutNodeType::Element(LayoutElementType::HTMLTableSectionElement)) => {
                 SpecificFragmentInfo::TableRow
             }
-            Some(LayoutNodeType::Element(LayoutElementType::HTMLCanvasElement)) => {
-                let data = node.canvas_data().unwrap();
-                SpecificFragmentInfo::Canvas(box CanvasFragmentInfo::new(node, data, self.style_context()))
+            Some(LayoutNodeType::Element(LayoutElementType::HTMLCanvasElement)) |
+            Some(LayoutNodeType::Element(LayoutElementType::HTMLVideoElement)) => {
+                let mut data = node.canvas_data().unwrap();
+                let cues = mem::replace(&mut data.cues, vec![]);
+                let mut canvas_info = box CanvasFragmentInfo::new(node, data, self.style_context());
+                if !cues.is_empty() {
+                    let style = node.cue_style();
+                    let mut font_context = self.layout_context.font_context();
+                    canvas_info.cues = cues.iter().map(|cue| {
+                        CueLines {
+                            runs: text::cue_text_runs(&mut font_context, &style, &cue.text),
+                            settings: cue.settings,
+                        }
+                    }).collect();
+                    canvas_info.cue_style = Some(style);
+                }
+                SpecificFragmentInfo::Canvas(canvas_info)
             }
             _ => {
                 // This includes pseudo-elements.
@@ -443,11 +462,14 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
         // We must scan for runs before computing minimum ascent and descent because scanning
         // for runs might collapse so much whitespace away that only hypothetical fragments
         // remain. In that case the inline flow will compute its ascent and descent to be zero.
-        let scanned_fragments =
-            TextRunScanner::new().scan_for_runs(&mut self.layout_context.font_context(),
-                                                fragments.fragments);
-        let mut inline_flow_ref: FlowRef = Arc::new(
-            InlineFlow::from_fragments(scanned_fragments, node.style(self.style_context()).writing_mode));
+        let node_style = node.style(self.style_context());
+        let (scanned_fragments, bidi_paragraph_level) =
+            TextRunScanner::new().scan_paragraph_for_runs(&mut self.layout_context.font_context(),
+                                                          fragments.fragments,
+                                                          &*node_style);
+        let mut inline_flow = InlineFlow::from_fragments(scanned_fragments, node_style.writing_mode);
+        inline_flow.bidi_paragraph_level = bidi_paragraph_level;
+        let mut inline_flow_ref: FlowRef = Arc::new(inline_flow);
 
         // Add all the inline-block fragments as children of the inline flow.
         for inline_block_flow in &inline_block_flows {
@@ -474,10 +496,12 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
             let inline_flow = flow_ref::deref_mut(&mut inline_flow_ref).as_mut_inline();
 
 
+            let quirks_mode = self.style_context().stylist.quirks_mode();
             let (ascent, descent) =
                 inline_flow.compute_minimum_ascent_and_descent(&mut self.layout_context
                                                                         .font_context(),
-                                                               &**node.style(self.style_context()));
+                                                               &**node.style(self.style_context()),
+                                                               quirks_mode);
             inline_flow.minimum_block_size_above_baseline = ascent;
             inline_flow.minimum_depth_below_baseline = descent;
         }
@@ -769,11 +793,43 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
             return self.build_flow_for_multicol(node, float_kind)
         }
 
-        let flow: FlowRef = Arc::new(
-            BlockFlow::from_fragment(self.build_fragment_for_block(node), float_kind));
+        if let Some(LayoutNodeType::Element(_)) = node.type_id() {
+            if opts::get().paginate && float_kind.is_none() &&
+                    PagedFlow::is_root_element(&node.as_element()) {
+                return self.build_flow_for_paged(node)
+            }
+            if let Some(kind) = MathLayoutKind::for_element(&node.as_element()) {
+                let fragment = self.build_fragment_for_block(node);
+                let flow: FlowRef = Arc::new(MathFlow::from_fragment(fragment, float_kind, kind));
+                return self.build_flow_for_block_like(flow, node)
+            }
+            if RubyFlow::is_ruby_element(&node.as_element()) {
+                let fragment = self.build_fragment_for_block(node);
+                let flow: FlowRef = Arc::new(RubyFlow::from_fragment(fragment, float_kind));
+                return self.build_flow_for_block_like(flow, node)
+            }
+        }
+
+        let mut block_flow =
+            BlockFlow::from_fragment(self.build_fragment_for_block(node), float_kind);
+        block_flow.backdrop = self.build_fragment_for_backdrop(node);
+        let flow: FlowRef = Arc::new(block_flow);
         self.build_flow_for_block_like(flow, node)
     }
 
+    /// Builds the fragment for the `::backdrop` of a node in the top layer, if it has one.
+    fn build_fragment_for_backdrop(&mut self, node: &ConcreteThreadSafeLayoutNode)
+                                   -> Option<Box<Fragment>> {
+        node.backdrop_style().map(|style| {
+            box Fragment::from_opaque_node_and_style(node.opaque(),
+                                                     PseudoElementType::Normal,
+                                                     style.clone(),
+                                                     style,
+                                                     node.restyle_damage(),
+                                                     SpecificFragmentInfo::Generic)
+        })
+    }
+
     /// Bubbles up {ib} splits.
     fn accumulate_inline_block_splits(&mut self,
                                       splits: LinkedList<InlineBlockSplit>,
@@ -1160,6 +1216,40 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
         ConstructionResult::Flow(flow, abs_descendants)
     }
 
+    /// Builds the flow for the root element of a paginated document. This yields a `PagedFlow`
+    /// with the `BlockFlow` of the root element underneath it, which it breaks into pages.
+    fn build_flow_for_paged(&mut self, node: &ConcreteThreadSafeLayoutNode) -> ConstructionResult {
+        let mut style = node.style(self.style_context()).clone();
+        paged::anonymous_style_for_pages(&mut style);
+        let fragment = Fragment::from_opaque_node_and_style(node.opaque(),
+                                                            PseudoElementType::Normal,
+                                                            style,
+                                                            node.selected_style(self.style_context()).clone(),
+                                                            node.restyle_damage(),
+                                                            SpecificFragmentInfo::Generic);
+        let mut flow: FlowRef = Arc::new(PagedFlow::from_fragment(fragment));
+
+        let page_fragment = self.build_fragment_for_block(node);
+        let page_flow: FlowRef = Arc::new(BlockFlow::from_fragment(page_fragment, None));
+        let construction_result = self.build_flow_for_block_like(page_flow, node);
+
+        let mut abs_descendants = AbsoluteDescendants::new();
+        if let ConstructionResult::Flow(page_flow, page_abs_descendants) = construction_result {
+            flow.add_new_child(page_flow);
+            abs_descendants.push_descendants(page_abs_descendants);
+        }
+
+        // The flow is done.
+        flow.finish();
+        if flow.contains_positioned_fragments() {
+            // This is the containing block for all the absolute descendants.
+            flow.set_absolute_descendants(abs_descendants);
+            abs_descendants = AbsoluteDescendants::new();
+        }
+
+        ConstructionResult::Flow(flow, abs_descendants)
+    }
+
     /// Builds a flow for a node with `display: table`. This yields a `TableWrapperFlow` with
     /// possibly other `TableCaptionFlow`s or `TableFlow`s underneath it.
     fn build_flow_for_table_wrapper(&mut self, node: &ConcreteThreadSafeLayoutNode, float_value: float::T)
@@ -1265,33 +1355,61 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
     fn build_flow_for_list_item(&mut self, node: &ConcreteThreadSafeLayoutNode, flotation: float::T)
                                 -> ConstructionResult {
         let flotation = FloatKind::from_property(flotation);
-        let marker_fragments = match node.style(self.style_context()).get_list().list_style_image.0 {
-            Some(ref url) => {
+
+        // The marker box is styled by the list item's `::marker`, whose `content` replaces the
+        // marker that `list-style-image` and `list-style-type` would otherwise produce.
+        let marker_style = node.marker_style();
+        let marker_fragment = |specific| {
+            Fragment::from_opaque_node_and_style(node.opaque(),
+                                                 PseudoElementType::Normal,
+                                                 marker_style.clone(),
+                                                 marker_style.clone(),
+                                                 node.restyle_damage(),
+                                                 specific)
+        };
+        let marker_fragments = match (&marker_style.get_counters().content,
+                                      &marker_style.get_list().list_style_image.0) {
+            (&content::T::Content(ref content_items), _) => {
+                let mut unscanned_marker_fragments = LinkedList::new();
+                for content_item in content_items.iter().cloned() {
+                    let specific_fragment_info = match content_item {
+                        ContentItem::String(string) => {
+                            let info = box UnscannedTextFragmentInfo::new(string, None);
+                            SpecificFragmentInfo::UnscannedText(info)
+                        }
+                        content_item => {
+                            let content_item = box GeneratedContentInfo::ContentItem(content_item);
+                            SpecificFragmentInfo::GeneratedContent(content_item)
+                        }
+                    };
+                    unscanned_marker_fragments.push_back(marker_fragment(specific_fragment_info))
+                }
+                TextRunScanner::new().scan_for_runs(&mut self.layout_context.font_context(),
+                                                    unscanned_marker_fragments).fragments
+            }
+            (_, &Some(ref url)) => {
                 let image_info = box ImageFragmentInfo::new(node,
                                                             Some((*url).clone()),
+                                                            1.0,
                                                             &self.layout_context.shared);
-                vec![Fragment::new(node, SpecificFragmentInfo::Image(image_info), self.layout_context)]
+                vec![marker_fragment(SpecificFragmentInfo::Image(image_info))]
             }
-            None => {
-                match ListStyleTypeContent::from_list_style_type(node.style(self.style_context())
-                                                                     .get_list()
-                                                                     .list_style_type) {
+            (_, &None) => {
+                match ListStyleTypeContent::from_list_style_type(marker_style.get_list().list_style_type) {
                     ListStyleTypeContent::None => Vec::new(),
                     ListStyleTypeContent::StaticText(ch) => {
                         let text = format!("{}\u{a0}", ch);
                         let mut unscanned_marker_fragments = LinkedList::new();
-                        unscanned_marker_fragments.push_back(Fragment::new(
-                            node,
+                        unscanned_marker_fragments.push_back(marker_fragment(
                             SpecificFragmentInfo::UnscannedText(
-                                box UnscannedTextFragmentInfo::new(text, None)),
-                            self.layout_context));
+                                box UnscannedTextFragmentInfo::new(text, None))));
                         let marker_fragments = TextRunScanner::new().scan_for_runs(
                             &mut self.layout_context.font_context(),
                             unscanned_marker_fragments);
                         marker_fragments.fragments
                     }
                     ListStyleTypeContent::GeneratedContent(info) => {
-                        vec![Fragment::new(node, SpecificFragmentInfo::GeneratedContent(info), self.layout_context)]
+                        vec![marker_fragment(SpecificFragmentInfo::GeneratedContent(info))]
                     }
                 }
             }
@@ -1406,7 +1524,9 @@ impl<'a, ConcreteThreadSafeLayoutNode: ThreadSafeLayoutNode>
             return false
         }
 
-        if node.can_be_fragmented() || node.style(self.style_context()).is_multicol() {
+        // Everything in a paginated document can be broken into pages.
+        if node.can_be_fragmented() || node.style(self.style_context()).is_multicol() ||
+                opts::get().paginate {
             return false
         }
 
@@ -1701,7 +1821,8 @@ impl<ConcreteThreadSafeLayoutNode> NodeUtils for ConcreteThreadSafeLayoutNode
             Some(LayoutNodeType::Document) |
             Some(LayoutNodeType::Element(LayoutElementType::HTMLImageElement)) |
             Some(LayoutNodeType::Element(LayoutElementType::HTMLIFrameElement)) |
-            Some(LayoutNodeType::Element(LayoutElementType::HTMLCanvasElement)) => true,
+            Some(LayoutNodeType::Element(LayoutElementType::HTMLCanvasElement)) |
+            Some(LayoutNodeType::Element(LayoutElementType::HTMLVideoElement)) => true,
             Some(LayoutNodeType::Element(LayoutElementType::HTMLObjectElement)) => self.has_object_data(),
             Some(LayoutNodeType::Element(_)) => false,
             None => self.get_pseudo_element_type().is_replaced_content(),
@@ -1879,8 +2000,8 @@ fn bidi_control_chars(style: &Arc<ServoComputedValues>) -> Option<(&'static str,
         (isolate, rtl)          => Some(("\u{2067}", "\u{2069}")),
         (bidi_override, ltr)    => Some(("\u{202D}", "\u{202C}")),
         (bidi_override, rtl)    => Some(("\u{202E}", "\u{202C}")),
-        (isolate_override, ltr) => Some(("\u{2068}\u{202D}", "\u{202C}\u{2069}")),
-        (isolate_override, rtl) => Some(("\u{2068}\u{202E}", "\u{202C}\u{2069}")),
+        (isolate_override, ltr) => Some(("\u{2066}\u{202D}", "\u{202C}\u{2069}")),
+        (isolate_override, rtl) => Some(("\u{2067}\u{202E}", "\u{202C}\u{2069}")),
         (plaintext, _)          => Some(("\u{2068}", "\u{2069}")),
     }
 }
@@ -16,12 +16,14 @@ use gfx::font_context::FontContext;
 use gfx_traits::LayerId;
 use heapsize::HeapSizeOf;
 use ipc_channel::ipc::{self, IpcSharedMemory};
+use msg::constellation_msg::PipelineId;
 use net_traits::image::base::Image;
 use net_traits::image_cache_thread::{ImageCacheChan, ImageCacheThread, ImageResponse, ImageState};
 use net_traits::image_cache_thread::{ImageOrMetadataAvailable, UsePlaceholder};
 use std::cell::{RefCell, RefMut};
 use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
+use std::mem;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex, RwLock};
 use style::context::{LocalStyleContext, StyleContext, SharedStyleContext};
@@ -29,6 +31,9 @@ use url::Url;
 use util::opts;
 
 struct LocalLayoutContext {
+    /// The pipeline whose document the style caches below were filled from.
+    pipeline_id: PipelineId,
+
     style_context: LocalStyleContext,
 
     font_context: RefCell<FontContext>,
@@ -55,26 +60,42 @@ fn create_or_get_local_context(shared_layout_context: &SharedLayoutContext)
     LOCAL_CONTEXT_KEY.with(|r| {
         let mut r = r.borrow_mut();
         if let Some(context) = r.clone() {
-            if shared_layout_context.style_context.screen_size_changed {
-                context.style_context.applicable_declarations_cache.borrow_mut().evict_all();
+            if context.pipeline_id == shared_layout_context.pipeline_id {
+                if shared_layout_context.style_context.screen_size_changed {
+                    context.style_context.applicable_declarations_cache.borrow_mut().evict_all();
+                }
+                return context
             }
-            context
-        } else {
-            let font_cache_thread = shared_layout_context.font_cache_thread.lock().unwrap().clone();
-            let local_style_data = shared_layout_context.style_context.local_context_creation_data.lock().unwrap();
-
-            let context = Rc::new(LocalLayoutContext {
-                style_context: LocalStyleContext::new(&local_style_data),
-                font_context: RefCell::new(FontContext::new(font_cache_thread)),
-            });
-            *r = Some(context.clone());
-            context
         }
+
+        let font_cache_thread = shared_layout_context.font_cache_thread.lock().unwrap().clone();
+        let local_style_data = shared_layout_context.style_context.local_context_creation_data.lock().unwrap();
+
+        // The style caches refer to the nodes and rules of the previous document, but the font
+        // context is still warm if this thread has been reused for a new pipeline.
+        let font_context = match r.take() {
+            Some(context) => {
+                mem::replace(&mut *context.font_context.borrow_mut(),
+                             FontContext::new(font_cache_thread))
+            }
+            None => FontContext::new(font_cache_thread),
+        };
+
+        let context = Rc::new(LocalLayoutContext {
+            pipeline_id: shared_layout_context.pipeline_id,
+            style_context: LocalStyleContext::new(&local_style_data),
+            font_context: RefCell::new(font_context),
+        });
+        *r = Some(context.clone());
+        context
     })
 }
 
 /// Layout information shared among all workers. This must be thread-safe.
 pub struct SharedLayoutContext {
+    /// The pipeline being laid out.
+    pub pipeline_id: PipelineId,
+
     /// Bits shared by the layout and style system.
     pub style_context: SharedStyleContext,
 
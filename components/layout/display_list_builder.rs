@@ -17,9 +17,11 @@ use canvas_traits::{CanvasData, CanvasMsg, FromLayoutMsg};
 use context::SharedLayoutContext;
 use euclid::{Matrix4D, Point2D, Point3D, Radians, Rect, SideOffsets2D, Size2D};
 use flex::FlexFlow;
-use flow::{BaseFlow, Flow, IS_ABSOLUTELY_POSITIONED};
+use flow::{self, BaseFlow, ESTABLISHES_STACKING_CONTEXT, Flow, IS_ABSOLUTELY_POSITIONED};
 use flow_ref;
-use fragment::{CoordinateSystem, Fragment, HAS_LAYER, ImageFragmentInfo, ScannedTextFragmentInfo};
+use fragment::{CanvasFragmentInfo, CoordinateSystem, Fragment, HAS_LAYER, ImageFragmentInfo};
+use fragment::IS_TRUNCATED_BY_ELLIPSIS;
+use fragment::ScannedTextFragmentInfo;
 use fragment::SpecificFragmentInfo;
 use gfx::display_list::{BLUR_INFLATION_FACTOR, BaseDisplayItem, BorderDisplayItem};
 use gfx::display_list::{BorderRadii, BoxShadowClipMode, BoxShadowDisplayItem, ClippingRegion};
@@ -29,35 +31,49 @@ use gfx::display_list::{LayerInfo, LayeredItem, LineDisplayItem, OpaqueNode};
 use gfx::display_list::{SolidColorDisplayItem, StackingContext, StackingContextType};
 use gfx::display_list::{TextDisplayItem, TextOrientation, WebRenderImageInfo};
 use gfx::paint_thread::THREAD_TINT_COLORS;
-use gfx_traits::{ScrollPolicy, StackingContextId, color};
+use gfx::text::glyph::ByteIndex;
+use gfx_traits::{ScrollPolicy, ScrollbarAxis, ScrollbarInfo, StackingContextId, StickyFrameInfo};
+use gfx_traits::color;
 use inline::{FIRST_FRAGMENT_OF_ELEMENT, InlineFlow, LAST_FRAGMENT_OF_ELEMENT};
 use ipc_channel::ipc;
 use list_item::ListItemFlow;
+use math::{MathFlow, MathLayoutKind};
 use model::{self, MaybeAuto, ToGfxMatrix};
+use multicol::MulticolFlow;
+use paged::PagedFlow;
 use net_traits::image::base::PixelFormat;
 use net_traits::image_cache_thread::UsePlaceholder;
 use range::Range;
+use script_layout_interface::{CueAlign, CueSettings};
 use script_layout_interface::restyle_damage::REPAINT;
 use std::{cmp, f32};
 use std::default::Default;
 use std::sync::Arc;
 use style::computed_values::{background_attachment, background_clip, background_origin};
-use style::computed_values::{background_repeat, background_size, border_style};
-use style::computed_values::{cursor, image_rendering, overflow_x, pointer_events, position};
-use style::computed_values::{transform, transform_style, visibility};
+use style::computed_values::{background_repeat, background_size, border_image_outset};
+use style::computed_values::{border_image_repeat, border_image_slice, border_image_width};
+use style::computed_values::border_style;
+use style::computed_values::{cursor, display, image_rendering, isolation, mix_blend_mode, object_fit, overflow_x};
+use style::computed_values::{pointer_events, position, scrollbar_color};
+use style::computed_values::text_decoration_skip_ink;
+use style::computed_values::{text_decoration_style, text_decoration_thickness, transform};
+use style::computed_values::{transform_style, visibility};
 use style::computed_values::_servo_overflow_clip_box as overflow_clip_box;
+use style::computed_values::_servo_text_decorations_in_effect::Decoration;
 use style::computed_values::filter::Filter;
 use style::computed_values::text_shadow::TextShadow;
-use style::logical_geometry::{LogicalPoint, LogicalRect, LogicalSize, WritingMode};
+use style::logical_geometry::{LogicalMargin, LogicalRect, LogicalSize, WritingMode};
 use style::properties::{self, ServoComputedValues};
 use style::properties::style_structs;
 use style::values::RGBA;
 use style::values::computed;
-use style::values::computed::{LengthOrNone, LengthOrPercentage, LengthOrPercentageOrAuto, LinearGradient};
+use style::values::computed::{ConicGradient, GradientItem, LengthOrNone, LengthOrPercentage};
+use style::values::computed::{LengthOrPercentageOrAuto, LinearGradient};
 use style::values::specified::{AngleOrCorner, HorizontalDirection, VerticalDirection};
 use style_traits::cursor::Cursor;
 use table_cell::CollapsedBordersForCell;
 use url::Url;
+use util::geometry::f32_rect_to_au_rect;
 use util::opts;
 
 fn get_cyclic<T>(arr: &[T], index: usize) -> &T {
@@ -173,8 +189,20 @@ pub trait FragmentDisplayListBuilding {
                                                          absolute_bounds: &Rect<Au>,
                                                          clip: &ClippingRegion,
                                                          gradient: &LinearGradient,
+                                                         blend_mode: mix_blend_mode::T,
                                                          style: &ServoComputedValues);
 
+    /// Adds the display items necessary to paint the background conic gradient of this fragment
+    /// to the appropriate section of the display list.
+    fn build_display_list_for_background_conic_gradient(&self,
+                                                        state: &mut DisplayListBuildState,
+                                                        display_list_section: DisplayListSection,
+                                                        absolute_bounds: &Rect<Au>,
+                                                        clip: &ClippingRegion,
+                                                        gradient: &ConicGradient,
+                                                        blend_mode: mix_blend_mode::T,
+                                                        style: &ServoComputedValues);
+
     /// Adds the display items necessary to paint the borders of this fragment to a display list if
     /// necessary.
     fn build_display_list_for_borders_if_applicable(
@@ -186,6 +214,17 @@ pub trait FragmentDisplayListBuilding {
             display_list_section: DisplayListSection,
             clip: &ClippingRegion);
 
+    /// Adds the display items necessary to paint the border image of this fragment to the display
+    /// list, returning false if there is no border image or it hasn't loaded yet, in which case
+    /// the borders are painted with their styles instead.
+    fn build_display_list_for_border_image_if_applicable(&self,
+                                                         state: &mut DisplayListBuildState,
+                                                         style: &ServoComputedValues,
+                                                         bounds: &Rect<Au>,
+                                                         display_list_section: DisplayListSection,
+                                                         clip: &ClippingRegion)
+                                                         -> bool;
+
     /// Adds the display items necessary to paint the outline of this fragment to the display list
     /// if necessary.
     fn build_display_list_for_outline_if_applicable(&self,
@@ -194,14 +233,16 @@ pub trait FragmentDisplayListBuilding {
                                                     bounds: &Rect<Au>,
                                                     clip: &ClippingRegion);
 
-    /// Adds the display items necessary to paint the box shadow of this fragment to the display
-    /// list if necessary.
+    /// Adds the display items necessary to paint the box shadows of this fragment that have the
+    /// given clip mode to the display list if necessary. Outset shadows are painted beneath the
+    /// background and inset shadows above it.
     fn build_display_list_for_box_shadow_if_applicable(&self,
                                                        state: &mut DisplayListBuildState,
                                                        style: &ServoComputedValues,
                                                        display_list_section: DisplayListSection,
                                                        absolute_bounds: &Rect<Au>,
-                                                       clip: &ClippingRegion);
+                                                       clip: &ClippingRegion,
+                                                       clip_mode: BoxShadowClipMode);
 
     /// Adds display items necessary to draw debug boxes around a scanned text fragment.
     fn build_debug_borders_around_text_fragments(&self,
@@ -272,14 +313,23 @@ pub trait FragmentDisplayListBuilding {
                                             text_shadow: Option<&TextShadow>,
                                             clip: &ClippingRegion);
 
-    /// Creates the display item for a text decoration: underline, overline, or line-through.
+    /// Creates the display items for a text decoration: underline, overline, or line-through.
     fn build_display_list_for_text_decoration(&self,
                                               state: &mut DisplayListBuildState,
+                                              decoration_style: text_decoration_style::T,
                                               color: &RGBA,
                                               stacking_relative_box: &LogicalRect<Au>,
                                               clip: &ClippingRegion,
                                               blur_radius: Au);
 
+    /// Creates the display items for the cues shown over a video, each placed by its settings
+    /// and kept clear of the ones before it.
+    fn build_display_list_for_cues(&self,
+                                   state: &mut DisplayListBuildState,
+                                   canvas_fragment_info: &CanvasFragmentInfo,
+                                   stacking_relative_content_box: &Rect<Au>,
+                                   clip: &ClippingRegion);
+
     /// A helper method that `build_display_list` calls to create per-fragment-type display items.
     fn build_fragment_type_specific_display_items(&mut self,
                                                   state: &mut DisplayListBuildState,
@@ -293,6 +343,12 @@ pub trait FragmentDisplayListBuilding {
                                scroll_policy: ScrollPolicy,
                                mode: StackingContextCreationMode)
                                -> Box<StackingContext>;
+
+    /// Computes the constraints that the compositor needs to move this fragment as its
+    /// scrolling ancestor scrolls, if this fragment is sticky positioned. `border_box` is the
+    /// border box of this fragment relative to its parent stacking context.
+    fn sticky_frame_info(&self, border_box: &Rect<Au>, base_flow: &BaseFlow)
+                         -> Option<StickyFrameInfo>;
 }
 
 fn handle_overlapping_radii(size: &Size2D<Au>, radii: &BorderRadii<Au>) -> BorderRadii<Au> {
@@ -337,6 +393,84 @@ fn build_border_radius(abs_bounds: &Rect<Au>, border_style: &style_structs::Bord
     })
 }
 
+/// Places the box of a cue over `video`, clear of the boxes of the cues placed before it, or
+/// returns `None` if there is no room left for it. Cues are laid out horizontally, left to
+/// right, so the start and end alignments are those of the left and right ones.
+/// https://w3c.github.io/webvtt/#apply-webvtt-cue-settings
+fn place_cue_box(settings: &CueSettings,
+                 video: &Rect<Au>,
+                 block_size: Au,
+                 line_height: Au,
+                 other_boxes: &[Rect<Au>])
+                 -> Option<Rect<Au>> {
+    // Steps 2 to 5: the box is as wide as its size allows without leaving the video, and
+    // lies after, around or before its position, according to the alignment.
+    let position = settings.position.unwrap_or(match settings.align {
+        CueAlign::Start | CueAlign::Left => 0.,
+        CueAlign::Center => 50.,
+        CueAlign::End | CueAlign::Right => 100.,
+    });
+    let (maximum_size, left) = match settings.align {
+        CueAlign::Start | CueAlign::Left => (100. - position, position),
+        CueAlign::Center => (if position <= 50. { position * 2. } else { (100. - position) * 2. }, position),
+        CueAlign::End | CueAlign::Right => (position, position),
+    };
+    let size = settings.size.min(maximum_size);
+    let left = match settings.align {
+        CueAlign::Start | CueAlign::Left => left,
+        CueAlign::Center => left - size / 2.,
+        CueAlign::End | CueAlign::Right => left - size,
+    };
+    let x = video.origin.x + video.size.width.scale_by((left / 100.) as f32);
+    let inline_size = video.size.width.scale_by((size / 100.) as f32);
+
+    // Step 7: a line given as a percentage is a place in the video, which the box is moved
+    // back into if it sticks out.
+    if !settings.snap_to_lines {
+        let line = settings.line.unwrap_or(100.);
+        let y = video.origin.y + video.size.height.scale_by((line / 100.) as f32);
+        let y = cmp::max(video.origin.y, cmp::min(y, video.max_y() - block_size));
+        return Some(Rect::new(Point2D::new(x, y), Size2D::new(inline_size, block_size)));
+    }
+
+    // Step 10: a line number counts lines from the top if positive and from the bottom if
+    // negative, with the cues of no particular line on the last one.
+    let line = settings.line.unwrap_or(-1.) as i32;
+    let (mut y, mut step) = if line < 0 {
+        (video.max_y() + line_height * line, -line_height)
+    } else {
+        (video.origin.y + line_height * line, line_height)
+    };
+    if step == Au(0) {
+        return None;
+    }
+
+    // The box is moved a line at a time away from its line until it overlaps no other cue,
+    // and once it would leave the video, the other way. If it leaves the video that way
+    // too, the cue isn't shown.
+    let mut switched = false;
+    loop {
+        let cue_box = Rect::new(Point2D::new(x, y), Size2D::new(inline_size, block_size));
+        let inside = cue_box.origin.y >= video.origin.y && cue_box.max_y() <= video.max_y();
+        if inside && !other_boxes.iter().any(|other_box| other_box.intersects(&cue_box)) {
+            return Some(cue_box);
+        }
+        let outside = if step < Au(0) {
+            cue_box.origin.y < video.origin.y
+        } else {
+            cue_box.max_y() > video.max_y()
+        };
+        if outside {
+            if switched {
+                return None;
+            }
+            switched = true;
+            step = -step;
+        }
+        y = y + step;
+    }
+}
+
 impl FragmentDisplayListBuilding for Fragment {
     fn build_display_list_for_background_if_applicable(&self,
                                                        state: &mut DisplayListBuildState,
@@ -400,6 +534,7 @@ impl FragmentDisplayListBuilding for Fragment {
         // http://www.w3.org/TR/CSS21/colors.html#background
         let background = style.get_background();
         for (i, background_image) in background.background_image.0.iter().enumerate().rev() {
+            let blend_mode = *get_cyclic(&background.background_blend_mode.0, i);
             match background_image.0 {
                 None => {}
                 Some(computed::Image::LinearGradient(ref gradient)) => {
@@ -408,8 +543,18 @@ impl FragmentDisplayListBuilding for Fragment {
                                                                            &bounds,
                                                                            &clip,
                                                                            gradient,
+                                                                           blend_mode,
                                                                            style);
                 }
+                Some(computed::Image::ConicGradient(ref gradient)) => {
+                    self.build_display_list_for_background_conic_gradient(state,
+                                                                          display_list_section,
+                                                                          &bounds,
+                                                                          &clip,
+                                                                          gradient,
+                                                                          blend_mode,
+                                                                          style);
+                }
                 Some(computed::Image::Url(ref image_url, ref _extra_data)) => {
                     self.build_display_list_for_background_image(state,
                                                                  style,
@@ -631,6 +776,7 @@ impl FragmentDisplayListBuilding for Fragment {
               stretch_size: stretch_size,
               tile_spacing: tile_spacing,
               image_rendering: style.get_inheritedbox().image_rendering.clone(),
+              blend_mode: *get_cyclic(&background.background_blend_mode.0, index),
             }));
 
         }
@@ -642,6 +788,7 @@ impl FragmentDisplayListBuilding for Fragment {
                                                          absolute_bounds: &Rect<Au>,
                                                          clip: &ClippingRegion,
                                                          gradient: &LinearGradient,
+                                                         blend_mode: mix_blend_mode::T,
                                                          style: &ServoComputedValues) {
         let mut clip = clip.clone();
         clip.intersect_rect(absolute_bounds);
@@ -686,68 +833,26 @@ impl FragmentDisplayListBuilding for Fragment {
         let length = Au::from_f32_px(
             (delta.x.to_f32_px() * 2.0).hypot(delta.y.to_f32_px() * 2.0));
 
-        // Determine the position of each stop per CSS-IMAGES § 3.4.
-        //
-        // FIXME(#3908, pcwalton): Make sure later stops can't be behind earlier stops.
-        let mut stops = Vec::with_capacity(gradient.stops.len());
-        let mut stop_run = None;
-        for (i, stop) in gradient.stops.iter().enumerate() {
-            let offset = match stop.position {
-                None => {
-                    if stop_run.is_none() {
-                        // Initialize a new stop run.
-                        let start_offset = if i == 0 {
-                            0.0
-                        } else {
-                            // `unwrap()` here should never fail because this is the beginning of
-                            // a stop run, which is always bounded by a length or percentage.
-                            position_to_offset(gradient.stops[i - 1].position.unwrap(), length)
-                        };
-                        let (end_index, end_offset) =
-                            match gradient.stops[i..]
-                                          .iter()
-                                          .enumerate()
-                                          .find(|&(_, ref stop)| stop.position.is_some()) {
-                                None => (gradient.stops.len() - 1, 1.0),
-                                Some((end_index, end_stop)) => {
-                                    // `unwrap()` here should never fail because this is the end of
-                                    // a stop run, which is always bounded by a length or
-                                    // percentage.
-                                    (end_index,
-                                     position_to_offset(end_stop.position.unwrap(), length))
-                                }
-                            };
-                        stop_run = Some(StopRun {
-                            start_offset: start_offset,
-                            end_offset: end_offset,
-                            start_index: i,
-                            stop_count: end_index - i,
-                        })
-                    }
+        let mut stops = convert_gradient_stops(&gradient.stops, length, style);
+        if gradient.repeating {
+            stops = repeat_gradient_stops(&stops, 0.0, 1.0);
+        }
 
-                    let stop_run = stop_run.unwrap();
-                    let stop_run_length = stop_run.end_offset - stop_run.start_offset;
-                    if stop_run.stop_count == 0 {
-                        stop_run.end_offset
-                    } else {
-                        stop_run.start_offset +
-                            stop_run_length * (i - stop_run.start_index) as f32 /
-                                (stop_run.stop_count as f32)
-                    }
-                }
-                Some(position) => {
-                    stop_run = None;
-                    position_to_offset(position, length)
-                }
-            };
-            stops.push(GradientStop {
-                offset: offset,
-                color: style.resolve_color(stop.color).to_gfx_color()
-            })
+        // Stops before the start or past the end of the gradient line extend it, so that the
+        // painted line runs from the first stop to the last.
+        let start_offset = stops[0].offset.min(0.0);
+        let end_offset = stops[stops.len() - 1].offset.max(1.0);
+        for stop in &mut stops {
+            stop.offset = (stop.offset - start_offset) / (end_offset - start_offset);
         }
 
         let center = Point2D::new(absolute_bounds.origin.x + absolute_bounds.size.width / 2,
                                   absolute_bounds.origin.y + absolute_bounds.size.height / 2);
+        let line_start = center - delta;
+        let line_point = |offset: f32| {
+            Point2D::new(line_start.x + (delta.x * 2).scale_by(offset),
+                         line_start.y + (delta.y * 2).scale_by(offset))
+        };
 
         let base = state.create_base_display_item(absolute_bounds,
                                                   &clip,
@@ -756,27 +861,176 @@ impl FragmentDisplayListBuilding for Fragment {
                                                   display_list_section);
         let gradient_display_item = DisplayItem::GradientClass(box GradientDisplayItem {
             base: base,
-            start_point: center - delta,
-            end_point: center + delta,
+            start_point: line_point(start_offset),
+            end_point: line_point(end_offset),
             stops: stops,
+            blend_mode: blend_mode,
         });
 
         state.add_display_item(gradient_display_item);
     }
 
+    fn build_display_list_for_background_conic_gradient(&self,
+                                                        state: &mut DisplayListBuildState,
+                                                        display_list_section: DisplayListSection,
+                                                        absolute_bounds: &Rect<Au>,
+                                                        clip: &ClippingRegion,
+                                                        gradient: &ConicGradient,
+                                                        blend_mode: mix_blend_mode::T,
+                                                        style: &ServoComputedValues) {
+        let mut clip = clip.clone();
+        clip.intersect_rect(absolute_bounds);
+
+        let width = absolute_bounds.size.width.to_f32_px();
+        let height = absolute_bounds.size.height.to_f32_px();
+        if width <= 0.0 || height <= 0.0 {
+            return
+        }
+
+        // The positions of conic stops are all percentages of a turn, so the length is unused.
+        let mut stops = convert_gradient_stops(&gradient.stops, Au(1), style);
+        if gradient.repeating {
+            stops = repeat_gradient_stops(&stops, 0.0, 1.0);
+        }
+
+        let left = absolute_bounds.origin.x.to_f32_px();
+        let center_x = left + model::specified(gradient.position.horizontal,
+                                               absolute_bounds.size.width).to_f32_px();
+        let center_y = absolute_bounds.origin.y.to_f32_px() +
+            model::specified(gradient.position.vertical, absolute_bounds.size.height).to_f32_px();
+        let start_angle = gradient.angle.radians();
+        let turn = 2.0 * f32::consts::PI;
+
+        // Neither painting backend has conic gradients, so the box is painted as rows about a pixel
+        // high, each a horizontal linear gradient following the angle around the center along the
+        // middle of the row.
+        let row_count = cmp::max(cmp::min(height as i32, MAX_CONIC_GRADIENT_ROWS), 1);
+        for row in 0..row_count {
+            let top = absolute_bounds.origin.y + absolute_bounds.size.height * row / row_count;
+            let bottom = absolute_bounds.origin.y + absolute_bounds.size.height * (row + 1) / row_count;
+            let row_bounds = Rect::new(Point2D::new(absolute_bounds.origin.x, top),
+                                       Size2D::new(absolute_bounds.size.width, bottom - top));
+            if !clip.might_intersect_rect(&row_bounds) {
+                continue
+            }
+
+            // The vertical offset of the row from the center, which is never zero so that the angle
+            // only jumps by a half turn at the center itself.
+            let y = (top + bottom).to_f32_px() / 2.0;
+            let dy = if y == center_y { -0.01 } else { y - center_y };
+
+            // Angles are clockwise from the top. Below the center they are in (π/2, 3π/2) and fall
+            // from left to right; above it they are in (-π/2, π/2) and rise.
+            let angle_at = |x: f32| {
+                let angle = (x - center_x).atan2(-dy);
+                if dy > 0.0 && angle < 0.0 { angle + turn } else { angle }
+            };
+            let (left_angle, right_angle) = (angle_at(left), angle_at(left + width));
+            let (low, high) = (left_angle.min(right_angle), left_angle.max(right_angle));
+
+            // Sample the row evenly by angle, at its ends, and on both sides of each stop and of the
+            // starting angle, where the colors may change abruptly.
+            let mut samples = vec![(low, None), (high, None)];
+            let step = turn / CONIC_GRADIENT_SAMPLES_PER_TURN;
+            let mut sample = (low / step).ceil();
+            while sample * step < high {
+                samples.push((sample * step, None));
+                sample += 1.0;
+            }
+            let boundaries = stops.iter()
+                                  .map(|stop| stop.offset)
+                                  .filter(|&offset| offset > 0.0 && offset < 1.0)
+                                  .chain(Some(0.0));
+            for offset in boundaries {
+                let mut turns = ((low - start_angle) / turn - offset).ceil();
+                while start_angle + (offset + turns) * turn < high {
+                    samples.push((start_angle + (offset + turns) * turn, Some(offset)));
+                    turns += 1.0;
+                }
+            }
+            samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(cmp::Ordering::Equal));
+
+            let mut row_stops = Vec::with_capacity(samples.len() + stops.len());
+            for &(angle, boundary) in &samples {
+                let x = center_x - dy * angle.tan();
+                let offset = ((x - left) / width).max(0.0).min(1.0);
+                match boundary {
+                    None => {
+                        let t = (angle - start_angle) / turn;
+                        row_stops.push(GradientStop {
+                            offset: offset,
+                            color: gradient_color_at(&stops, t - t.floor(), false),
+                        })
+                    }
+                    Some(t) => {
+                        // At the starting angle, the last color meets the first.
+                        let before = if t == 0.0 { 1.0 } else { t };
+                        row_stops.push(GradientStop {
+                            offset: offset,
+                            color: gradient_color_at(&stops, before, false),
+                        });
+                        row_stops.push(GradientStop {
+                            offset: offset,
+                            color: gradient_color_at(&stops, t, true),
+                        })
+                    }
+                }
+            }
+            if dy > 0.0 {
+                row_stops.reverse();
+            }
+            let mut max_offset = 0.0f32;
+            for stop in &mut row_stops {
+                max_offset = max_offset.max(stop.offset);
+                stop.offset = max_offset;
+            }
+
+            let middle = (top + bottom) / 2;
+            let base = state.create_base_display_item(&row_bounds,
+                                                      &clip,
+                                                      self.node,
+                                                      style.get_cursor(Cursor::Default),
+                                                      display_list_section);
+            state.add_display_item(DisplayItem::GradientClass(box GradientDisplayItem {
+                base: base,
+                start_point: Point2D::new(absolute_bounds.origin.x, middle),
+                end_point: Point2D::new(absolute_bounds.max_x(), middle),
+                stops: row_stops,
+                blend_mode: blend_mode,
+            }));
+        }
+    }
+
     fn build_display_list_for_box_shadow_if_applicable(&self,
                                                        state: &mut DisplayListBuildState,
                                                        style: &ServoComputedValues,
                                                        display_list_section: DisplayListSection,
                                                        absolute_bounds: &Rect<Au>,
-                                                       clip: &ClippingRegion) {
+                                                       clip: &ClippingRegion,
+                                                       clip_mode: BoxShadowClipMode) {
         // NB: According to CSS-BACKGROUNDS, box shadows render in *reverse* order (front to back).
         for box_shadow in style.get_effects().box_shadow.0.iter().rev() {
-            let bounds =
-                shadow_bounds(&absolute_bounds.translate(&Point2D::new(box_shadow.offset_x,
-                                                                       box_shadow.offset_y)),
-                              box_shadow.blur_radius,
-                              box_shadow.spread_radius);
+            if box_shadow.inset != (clip_mode == BoxShadowClipMode::Inset) {
+                continue
+            }
+
+            // Outset shadows are cast by the border box and painted outside of it. Inset shadows
+            // are cast by the padding box and painted inside of it.
+            let (box_bounds, bounds) = if box_shadow.inset {
+                let border = style.logical_border_width().to_physical(style.writing_mode);
+                let padding_box = Rect::new(
+                    Point2D::new(absolute_bounds.origin.x + border.left,
+                                 absolute_bounds.origin.y + border.top),
+                    Size2D::new(absolute_bounds.size.width - border.horizontal(),
+                                absolute_bounds.size.height - border.vertical()));
+                (padding_box, padding_box)
+            } else {
+                let offset = Point2D::new(box_shadow.offset_x, box_shadow.offset_y);
+                (*absolute_bounds,
+                 shadow_bounds(&absolute_bounds.translate(&offset),
+                               box_shadow.blur_radius,
+                               box_shadow.spread_radius))
+            };
 
             // TODO(pcwalton): Multiple border radii; elliptical border radii.
             let base = state.create_base_display_item(&bounds,
@@ -786,7 +1040,7 @@ impl FragmentDisplayListBuilding for Fragment {
                                                       display_list_section);
             state.add_display_item(DisplayItem::BoxShadowClass(box BoxShadowDisplayItem {
                 base: base,
-                box_bounds: *absolute_bounds,
+                box_bounds: box_bounds,
                 color: style.resolve_color(box_shadow.color).to_gfx_color(),
                 offset: Point2D::new(box_shadow.offset_x, box_shadow.offset_y),
                 blur_radius: box_shadow.blur_radius,
@@ -794,11 +1048,7 @@ impl FragmentDisplayListBuilding for Fragment {
                 border_radius: model::specified_border_radius(style.get_border()
                                                                    .border_top_left_radius,
                                                               absolute_bounds.size.width).width,
-                clip_mode: if box_shadow.inset {
-                    BoxShadowClipMode::Inset
-                } else {
-                    BoxShadowClipMode::Outset
-                },
+                clip_mode: clip_mode,
             }));
         }
     }
@@ -820,6 +1070,18 @@ impl FragmentDisplayListBuilding for Fragment {
             }
             BorderPaintingMode::Hidden => return,
         }
+
+        // Border images don't apply to collapsed borders.
+        if let BorderPaintingMode::Separate = border_painting_mode {
+            if self.build_display_list_for_border_image_if_applicable(state,
+                                                                      style,
+                                                                      bounds,
+                                                                      display_list_section,
+                                                                      clip) {
+                return
+            }
+        }
+
         if border.is_zero() {
             return
         }
@@ -868,6 +1130,216 @@ impl FragmentDisplayListBuilding for Fragment {
         }));
     }
 
+    fn build_display_list_for_border_image_if_applicable(&self,
+                                                         state: &mut DisplayListBuildState,
+                                                         style: &ServoComputedValues,
+                                                         bounds: &Rect<Au>,
+                                                         display_list_section: DisplayListSection,
+                                                         clip: &ClippingRegion)
+                                                         -> bool {
+        let border_style_struct = style.get_border();
+        // TODO: Gradients as border images.
+        let image_url = match border_style_struct.border_image_source.0 {
+            Some(computed::Image::Url(ref image_url, ref _extra_data)) => image_url,
+            _ => return false,
+        };
+        let fetch_image_data_as_well = !opts::get().use_webrender;
+        let (webrender_image, image_data) =
+            match state.shared_layout_context.get_webrender_image_for_url(image_url,
+                                                                          UsePlaceholder::No,
+                                                                          fetch_image_data_as_well) {
+                Some(webrender_image) => webrender_image,
+                None => return false,
+            };
+        let image_data = image_data.map(Arc::new);
+        let image_size = Size2D::new(webrender_image.width as f32, webrender_image.height as f32);
+        if image_size.width <= 0.0 || image_size.height <= 0.0 {
+            return true
+        }
+
+        let border = style.logical_border_width().to_physical(style.writing_mode);
+        let border = SideOffsets2D::new(border.top.to_f32_px(),
+                                        border.right.to_f32_px(),
+                                        border.bottom.to_f32_px(),
+                                        border.left.to_f32_px());
+
+        // The slices are offsets into the image, in image pixels, of the lines that cut it into
+        // four corners, four edges and a middle. Slices that overlap are clamped to the image.
+        let slice = &border_style_struct.border_image_slice;
+        let slice_offset = |offset: border_image_slice::PercentageOrNumber,
+                            size: f32| {
+            let offset = match offset {
+                border_image_slice::PercentageOrNumber::Percentage(percentage) => percentage * size,
+                border_image_slice::PercentageOrNumber::Number(number) => number,
+            };
+            offset.min(size)
+        };
+        let slice = SideOffsets2D::new(slice_offset(slice.top, image_size.height),
+                                       slice_offset(slice.right, image_size.width),
+                                       slice_offset(slice.bottom, image_size.height),
+                                       slice_offset(slice.left, image_size.width));
+
+        // The border image area is the border box extended by the outset.
+        let outset = &border_style_struct.border_image_outset;
+        let outset_length = |outset: border_image_outset::SingleComputedValue,
+                             border_width: f32| {
+            match outset {
+                border_image_outset::SingleComputedValue::Length(length) => {
+                    length.to_f32_px()
+                }
+                border_image_outset::SingleComputedValue::Number(number) => {
+                    number * border_width
+                }
+            }
+        };
+        let area = Rect::new(
+            Point2D::new(bounds.origin.x.to_f32_px() - outset_length(outset.left, border.left),
+                         bounds.origin.y.to_f32_px() - outset_length(outset.top, border.top)),
+            Size2D::new(bounds.size.width.to_f32_px() + outset_length(outset.left, border.left) +
+                        outset_length(outset.right, border.right),
+                        bounds.size.height.to_f32_px() + outset_length(outset.top, border.top) +
+                        outset_length(outset.bottom, border.bottom)));
+
+        // The widths of the regions the corners and edges of the image are painted into.
+        let width = &border_style_struct.border_image_width;
+        let width_length = |width: border_image_width::SingleComputedValue,
+                            border_width: f32,
+                            slice: f32,
+                            area_length: f32| {
+            match width {
+                border_image_width::SingleComputedValue::LengthOrPercentage(
+                        length) => {
+                    model::specified(length, Au::from_f32_px(area_length)).to_f32_px()
+                }
+                border_image_width::SingleComputedValue::Number(number) => {
+                    number * border_width
+                }
+                border_image_width::SingleComputedValue::Auto => slice,
+            }
+        };
+        let mut widths = SideOffsets2D::new(
+            width_length(width.top, border.top, slice.top, area.size.height),
+            width_length(width.right, border.right, slice.right, area.size.width),
+            width_length(width.bottom, border.bottom, slice.bottom, area.size.height),
+            width_length(width.left, border.left, slice.left, area.size.width));
+
+        // Opposite widths that overlap are scaled down, all by the same factor, until they fit.
+        let factor = (area.size.width / (widths.left + widths.right))
+            .min(area.size.height / (widths.top + widths.bottom));
+        if factor < 1.0 {
+            widths = SideOffsets2D::new(widths.top * factor,
+                                        widths.right * factor,
+                                        widths.bottom * factor,
+                                        widths.left * factor);
+        }
+
+        // The source region of the image and the destination region of the border image area of
+        // each of the nine parts, from a column and row of `[start, middle, end]`.
+        let source_edges = [[0.0, slice.left, image_size.width - slice.right, image_size.width],
+                            [0.0, slice.top, image_size.height - slice.bottom, image_size.height]];
+        let destination_edges = [[area.origin.x,
+                                  area.origin.x + widths.left,
+                                  area.max_x() - widths.right,
+                                  area.max_x()],
+                                 [area.origin.y,
+                                  area.origin.y + widths.top,
+                                  area.max_y() - widths.bottom,
+                                  area.max_y()]];
+
+        // Tiles along the edges are scaled to the width of the edge. The middle is scaled like the
+        // top and left edges, or the bottom and right ones if those are missing.
+        let scale = |width: f32, slice: f32| {
+            if width > 0.0 && slice > 0.0 { Some(width / slice) } else { None }
+        };
+        let horizontal_scale = scale(widths.top, slice.top).or(scale(widths.bottom, slice.bottom));
+        let vertical_scale = scale(widths.left, slice.left).or(scale(widths.right, slice.right));
+
+        let repeat = border_style_struct.border_image_repeat;
+        let image_rendering = style.get_inheritedbox().image_rendering.clone();
+        for row in 0..3 {
+            for column in 0..3 {
+                if row == 1 && column == 1 && !border_style_struct.border_image_slice.fill {
+                    continue
+                }
+
+                let source = Rect::new(
+                    Point2D::new(source_edges[0][column], source_edges[1][row]),
+                    Size2D::new(source_edges[0][column + 1] - source_edges[0][column],
+                                source_edges[1][row + 1] - source_edges[1][row]));
+                let destination = Rect::new(
+                    Point2D::new(destination_edges[0][column], destination_edges[1][row]),
+                    Size2D::new(destination_edges[0][column + 1] - destination_edges[0][column],
+                                destination_edges[1][row + 1] - destination_edges[1][row]));
+                if source.size.width <= 0.0 || source.size.height <= 0.0 ||
+                        destination.size.width <= 0.0 || destination.size.height <= 0.0 {
+                    continue
+                }
+
+                // Corners are always stretched. Edges repeat along their length, scaled to their
+                // width, and the middle repeats both ways.
+                let columns = if column == 1 {
+                    let scale = if row == 1 { horizontal_scale } else {
+                        Some(destination.size.height / source.size.height)
+                    };
+                    border_image_tiles(repeat.0,
+                                       destination.size.width,
+                                       scale.map(|scale| source.size.width * scale))
+                } else {
+                    vec![(0.0, destination.size.width)]
+                };
+                let rows = if row == 1 {
+                    let scale = if column == 1 { vertical_scale } else {
+                        Some(destination.size.width / source.size.width)
+                    };
+                    border_image_tiles(repeat.1,
+                                       destination.size.height,
+                                       scale.map(|scale| source.size.height * scale))
+                } else {
+                    vec![(0.0, destination.size.height)]
+                };
+
+                let mut part_clip = clip.clone();
+                part_clip.intersect_rect(&f32_rect_to_au_rect(destination));
+                for &(x, tile_width) in &columns {
+                    for &(y, tile_height) in &rows {
+                        // The whole image is placed so that the source region of this part lands
+                        // on the tile, clipped to the part.
+                        let scale = Size2D::new(tile_width / source.size.width,
+                                                tile_height / source.size.height);
+                        let image_bounds = f32_rect_to_au_rect(Rect::new(
+                            Point2D::new(destination.origin.x + x - source.origin.x * scale.width,
+                                         destination.origin.y + y - source.origin.y * scale.height),
+                            Size2D::new(image_size.width * scale.width,
+                                        image_size.height * scale.height)));
+                        let mut tile_clip = part_clip.clone();
+                        tile_clip.intersect_rect(&f32_rect_to_au_rect(Rect::new(
+                            Point2D::new(destination.origin.x + x, destination.origin.y + y),
+                            Size2D::new(tile_width, tile_height))));
+                        if !tile_clip.might_intersect_rect(&image_bounds) {
+                            continue
+                        }
+
+                        let base = state.create_base_display_item(&image_bounds,
+                                                                  &tile_clip,
+                                                                  self.node,
+                                                                  style.get_cursor(Cursor::Default),
+                                                                  display_list_section);
+                        state.add_display_item(DisplayItem::ImageClass(box ImageDisplayItem {
+                            base: base,
+                            webrender_image: webrender_image,
+                            image_data: image_data.clone(),
+                            stretch_size: image_bounds.size,
+                            tile_spacing: Size2D::zero(),
+                            image_rendering: image_rendering.clone(),
+                            blend_mode: mix_blend_mode::T::normal,
+                        }));
+                    }
+                }
+            }
+        }
+        true
+    }
+
     fn build_display_list_for_outline_if_applicable(&self,
                                                     state: &mut DisplayListBuildState,
                                                     style: &ServoComputedValues,
@@ -1105,6 +1577,13 @@ impl FragmentDisplayListBuilding for Fragment {
             // Add shadows, background, borders, and outlines, if applicable.
             if let Some(ref inline_context) = self.inline_context {
                 for node in inline_context.nodes.iter().rev() {
+                    self.build_display_list_for_box_shadow_if_applicable(
+                        state,
+                        &*node.style,
+                        display_list_section,
+                        &stacking_relative_border_box,
+                        &clip,
+                        BoxShadowClipMode::Outset);
                     self.build_display_list_for_background_if_applicable(
                         state,
                         &*node.style,
@@ -1116,7 +1595,8 @@ impl FragmentDisplayListBuilding for Fragment {
                         &*node.style,
                         display_list_section,
                         &stacking_relative_border_box,
-                        &clip);
+                        &clip,
+                        BoxShadowClipMode::Inset);
 
                     let mut style = node.style.clone();
                     properties::modify_border_style_for_inline_sides(
@@ -1140,6 +1620,12 @@ impl FragmentDisplayListBuilding for Fragment {
             }
 
             if !self.is_scanned_text_fragment() {
+                self.build_display_list_for_box_shadow_if_applicable(state,
+                                                                     &*self.style,
+                                                                     display_list_section,
+                                                                     &stacking_relative_border_box,
+                                                                     &clip,
+                                                                     BoxShadowClipMode::Outset);
                 self.build_display_list_for_background_if_applicable(state,
                                                                      &*self.style,
                                                                      display_list_section,
@@ -1149,7 +1635,8 @@ impl FragmentDisplayListBuilding for Fragment {
                                                                      &*self.style,
                                                                      display_list_section,
                                                                      &stacking_relative_border_box,
-                                                                     &clip);
+                                                                     &clip,
+                                                                     BoxShadowClipMode::Inset);
                 self.build_display_list_for_borders_if_applicable(state,
                                                                   &*self.style,
                                                                   border_painting_mode,
@@ -1275,9 +1762,13 @@ impl FragmentDisplayListBuilding for Fragment {
             SpecificFragmentInfo::Image(ref mut image_fragment) => {
                 // Place the image into the display list.
                 if let Some(ref image) = image_fragment.image {
+                    let (bounds, clip) = object_fit_bounds_and_clip(&*self.style,
+                                                                    &stacking_relative_content_box,
+                                                                    image_fragment.image_natural_size(),
+                                                                    clip);
                     let base = state.create_base_display_item(
-                        &stacking_relative_content_box,
-                        clip,
+                        &bounds,
+                        &clip,
                         self.node,
                         self.style.get_cursor(Cursor::Default),
                         DisplayListSection::Content);
@@ -1285,9 +1776,10 @@ impl FragmentDisplayListBuilding for Fragment {
                         base: base,
                         webrender_image: WebRenderImageInfo::from_image(image),
                         image_data: Some(Arc::new(image.bytes.clone())),
-                        stretch_size: stacking_relative_content_box.size,
+                        stretch_size: bounds.size,
                         tile_spacing: Size2D::zero(),
                         image_rendering: self.style.get_inheritedbox().image_rendering.clone(),
+                        blend_mode: mix_blend_mode::T::normal,
                     }));
                 }
             }
@@ -1309,12 +1801,23 @@ impl FragmentDisplayListBuilding for Fragment {
                                 FromLayoutMsg::SendData(sender))).unwrap();
                             receiver.recv().unwrap()
                         },
-                        None => return,
+                        None => {
+                            return self.build_display_list_for_cues(state,
+                                                                    canvas_fragment_info,
+                                                                    &stacking_relative_content_box,
+                                                                    clip)
+                        }
                     };
 
+                    let natural_size = Size2D::new(canvas_fragment_info.dom_width,
+                                                   canvas_fragment_info.dom_height);
+                    let (bounds, content_clip) = object_fit_bounds_and_clip(&*self.style,
+                                                                            &stacking_relative_content_box,
+                                                                            natural_size,
+                                                                            clip);
                     let base = state.create_base_display_item(
-                        &stacking_relative_content_box,
-                        clip,
+                        &bounds,
+                        &content_clip,
                         self.node,
                         self.style.get_cursor(Cursor::Default),
                         DisplayListSection::Content);
@@ -1329,9 +1832,10 @@ impl FragmentDisplayListBuilding for Fragment {
                                     format: PixelFormat::RGBA8,
                                     key: canvas_data.image_key,
                                 },
-                                stretch_size: stacking_relative_content_box.size,
+                                stretch_size: bounds.size,
                                 tile_spacing: Size2D::zero(),
                                 image_rendering: image_rendering::T::Auto,
+                                blend_mode: mix_blend_mode::T::normal,
                             })
                         }
                         CanvasData::WebGL(context_id) => {
@@ -1353,6 +1857,10 @@ impl FragmentDisplayListBuilding for Fragment {
                                                        color::transparent()),
                         }));
                     }
+                    self.build_display_list_for_cues(state,
+                                                     canvas_fragment_info,
+                                                     &stacking_relative_content_box,
+                                                     clip);
                 }
             }
             SpecificFragmentInfo::UnscannedText(_) => {
@@ -1364,6 +1872,73 @@ impl FragmentDisplayListBuilding for Fragment {
         }
     }
 
+    fn build_display_list_for_cues(&self,
+                                   state: &mut DisplayListBuildState,
+                                   canvas_fragment_info: &CanvasFragmentInfo,
+                                   stacking_relative_content_box: &Rect<Au>,
+                                   clip: &ClippingRegion) {
+        let style = match canvas_fragment_info.cue_style {
+            Some(ref style) => style,
+            None => return,
+        };
+        let background_color = style.resolve_color(style.get_background().background_color);
+        let text_color = style.get_color().color;
+
+        let mut cue_boxes = vec![];
+        for cue in &canvas_fragment_info.cues {
+            let line_height = match cue.runs.first() {
+                Some(run) => run.font_metrics.ascent + run.font_metrics.descent,
+                None => continue,
+            };
+            let cue_box = match place_cue_box(&cue.settings,
+                                              stacking_relative_content_box,
+                                              line_height * cue.runs.len() as i32,
+                                              line_height,
+                                              &cue_boxes) {
+                Some(cue_box) => cue_box,
+                None => continue,
+            };
+            cue_boxes.push(cue_box);
+
+            for (index, run) in cue.runs.iter().enumerate() {
+                let range = Range::new(ByteIndex(0), ByteIndex(run.text.len() as isize));
+                let size = Size2D::new(run.advance_for_range(&range), line_height);
+                let x = match cue.settings.align {
+                    CueAlign::Start | CueAlign::Left => cue_box.origin.x,
+                    CueAlign::Center => cue_box.origin.x + (cue_box.size.width - size.width) / 2,
+                    CueAlign::End | CueAlign::Right => cue_box.max_x() - size.width,
+                };
+                let origin = Point2D::new(x, cue_box.origin.y + line_height * index as i32);
+                let line_box = Rect::new(origin, size);
+
+                let base = state.create_base_display_item(&line_box,
+                                                          clip,
+                                                          self.node,
+                                                          style.get_cursor(Cursor::Default),
+                                                          DisplayListSection::Content);
+                state.add_display_item(DisplayItem::SolidColorClass(box SolidColorDisplayItem {
+                    base: base,
+                    color: background_color.to_gfx_color(),
+                }));
+
+                let base = state.create_base_display_item(&line_box,
+                                                          clip,
+                                                          self.node,
+                                                          style.get_cursor(Cursor::Default),
+                                                          DisplayListSection::Content);
+                state.add_display_item(DisplayItem::TextClass(box TextDisplayItem {
+                    base: base,
+                    text_run: run.clone(),
+                    range: range,
+                    text_color: text_color.to_gfx_color(),
+                    orientation: TextOrientation::Upright,
+                    baseline_origin: Point2D::new(origin.x, origin.y + run.font_metrics.ascent),
+                    blur_radius: Au(0),
+                }));
+            }
+        }
+    }
+
     fn create_stacking_context(&self,
                                id: StackingContextId,
                                base_flow: &BaseFlow,
@@ -1405,7 +1980,7 @@ impl FragmentDisplayListBuilding for Fragment {
         };
 
         let mut transform = Matrix4D::identity();
-        if let Some(ref operations) = self.style().get_effects().transform.0 {
+        if self.style().is_transformed() {
             let transform_origin = self.style().get_effects().transform_origin;
             let transform_origin =
                 Point3D::new(model::specified(transform_origin.horizontal,
@@ -1421,7 +1996,7 @@ impl FragmentDisplayListBuilding for Fragment {
                                                               -transform_origin.y,
                                                               -transform_origin.z);
 
-            for operation in operations {
+            for operation in &self.style().transform_operations() {
                 let matrix = match *operation {
                     transform::ComputedOperation::Rotate(ax, ay, az, theta) => {
                         let theta = 2.0f32 * f32::consts::PI - theta.radians();
@@ -1494,7 +2069,15 @@ impl FragmentDisplayListBuilding for Fragment {
                                 None,
                                 color::transparent()))
         } else if self.flags.contains(HAS_LAYER) {
-            Some(LayerInfo::new(self.layer_id(), scroll_policy, None, color::transparent()))
+            let mut layer_info =
+                LayerInfo::new(self.layer_id(), scroll_policy, None, color::transparent());
+            layer_info.sticky = self.sticky_frame_info(&border_box, base_flow);
+            if mode == StackingContextCreationMode::OuterScrollWrapper {
+                layer_info.scrollbars = scrollbar_info(self.style(),
+                                                       &Rect::new(Point2D::zero(), border_box.size),
+                                                       &base_flow.overflow.scroll.size);
+            }
+            Some(layer_info)
         } else {
             None
         };
@@ -1510,6 +2093,14 @@ impl FragmentDisplayListBuilding for Fragment {
             _ => StackingContextType::Real,
         };
 
+        // Per the compositing spec, `isolation: isolate` makes the stacking context an isolated
+        // group, as do blended background layers, which must not blend with what is behind the
+        // element. Stacking contexts with blended children are isolated as they are added.
+        let isolated = self.style().get_box().isolation == isolation::T::isolate ||
+            self.style().get_background().background_blend_mode.0.iter().any(|blend_mode| {
+                *blend_mode != mix_blend_mode::T::normal
+            });
+
         Box::new(StackingContext::new(id,
                                       context_type,
                                       &border_box,
@@ -1517,13 +2108,44 @@ impl FragmentDisplayListBuilding for Fragment {
                                       self.effective_z_index(),
                                       filters,
                                       self.style().get_effects().mix_blend_mode,
+                                      isolated,
                                       transform,
                                       perspective,
                                       establishes_3d_context,
+                                      transform_style,
+                                      self.style().get_effects().backface_visibility,
                                       scrolls_overflow_area,
                                       layer_info))
     }
 
+    fn sticky_frame_info(&self, border_box: &Rect<Au>, base_flow: &BaseFlow)
+                         -> Option<StickyFrameInfo> {
+        if self.style().get_box().position != position::T::sticky {
+            return None
+        }
+
+        fn resolve(offset: LengthOrPercentageOrAuto, containing_length: Au) -> Option<f32> {
+            match MaybeAuto::from_style(offset, containing_length) {
+                MaybeAuto::Auto => None,
+                MaybeAuto::Specified(length) => Some(length.to_f32_px()),
+            }
+        }
+
+        // FIXME: Percentages should resolve against the scrollport rather than the containing
+        // block.
+        let containing_block = base_flow.late_absolute_position_info
+                                        .stacking_relative_content_box_of_containing_block;
+        let offsets = self.style().get_position();
+        Some(StickyFrameInfo {
+            top: resolve(offsets.top, containing_block.size.height),
+            right: resolve(offsets.right, containing_block.size.width),
+            bottom: resolve(offsets.bottom, containing_block.size.height),
+            left: resolve(offsets.left, containing_block.size.width),
+            frame: to_f32_rect(border_box),
+            containing_block: to_f32_rect(&containing_block),
+        })
+    }
+
     fn adjust_clipping_region_for_children(&self,
                                            current_clip: &mut ClippingRegion,
                                            stacking_relative_border_box: &Rect<Au>) {
@@ -1535,8 +2157,12 @@ impl FragmentDisplayListBuilding for Fragment {
         // Account for style-specified `clip`.
         self.adjust_clip_for_style(current_clip, stacking_relative_border_box);
 
-        let overflow_x = self.style.get_box().overflow_x;
-        let overflow_y = self.style.get_box().overflow_y.0;
+        // Paint containment clips the contents as `overflow: clip` does.
+        let (overflow_x, overflow_y) = if self.style.has_paint_containment() {
+            (overflow_x::T::clip, overflow_x::T::clip)
+        } else {
+            (self.style.get_box().overflow_x, self.style.get_box().overflow_y.0)
+        };
 
         if let (overflow_x::T::visible, overflow_x::T::visible) = (overflow_x, overflow_y) {
             return
@@ -1559,19 +2185,25 @@ impl FragmentDisplayListBuilding for Fragment {
         // FIXME(pcwalton): This may be more complex than it needs to be, since it seems to be
         // impossible with the computed value rules as they are to have `overflow-x: visible` with
         // `overflow-y: <scrolling>` or vice versa!
-        if let overflow_x::T::hidden = self.style.get_box().overflow_x {
-            let mut bounds = current_clip.bounding_rect();
-            let max_x = cmp::min(bounds.max_x(), overflow_clip_rect.max_x());
-            bounds.origin.x = cmp::max(bounds.origin.x, overflow_clip_rect.origin.x);
-            bounds.size.width = max_x - bounds.origin.x;
-            current_clip.intersect_rect(&bounds)
+        match overflow_x {
+            overflow_x::T::hidden | overflow_x::T::clip => {
+                let mut bounds = current_clip.bounding_rect();
+                let max_x = cmp::min(bounds.max_x(), overflow_clip_rect.max_x());
+                bounds.origin.x = cmp::max(bounds.origin.x, overflow_clip_rect.origin.x);
+                bounds.size.width = max_x - bounds.origin.x;
+                current_clip.intersect_rect(&bounds)
+            }
+            _ => {}
         }
-        if let overflow_x::T::hidden = self.style.get_box().overflow_y.0 {
-            let mut bounds = current_clip.bounding_rect();
-            let max_y = cmp::min(bounds.max_y(), overflow_clip_rect.max_y());
-            bounds.origin.y = cmp::max(bounds.origin.y, overflow_clip_rect.origin.y);
-            bounds.size.height = max_y - bounds.origin.y;
-            current_clip.intersect_rect(&bounds)
+        match overflow_y {
+            overflow_x::T::hidden | overflow_x::T::clip => {
+                let mut bounds = current_clip.bounding_rect();
+                let max_y = cmp::min(bounds.max_y(), overflow_clip_rect.max_y());
+                bounds.origin.y = cmp::max(bounds.origin.y, overflow_clip_rect.origin.y);
+                bounds.size.height = max_y - bounds.origin.y;
+                current_clip.intersect_rect(&bounds)
+            }
+            _ => {}
         }
 
         let border_radii = build_border_radius(stacking_relative_border_box,
@@ -1602,7 +2234,9 @@ impl FragmentDisplayListBuilding for Fragment {
 
         // Determine the orientation and cursor to use.
         let (orientation, cursor) = if self.style.writing_mode.is_vertical() {
-            if self.style.writing_mode.is_sideways_left() {
+            if text_fragment.run.vertical {
+                (TextOrientation::Vertical, Cursor::VerticalText)
+            } else if self.style.writing_mode.is_sideways_left() {
                 (TextOrientation::SidewaysLeft, Cursor::VerticalText)
             } else {
                 (TextOrientation::SidewaysRight, Cursor::VerticalText)
@@ -1611,17 +2245,26 @@ impl FragmentDisplayListBuilding for Fragment {
             (TextOrientation::Upright, Cursor::Text)
         };
 
-        // Compute location of the baseline.
-        //
-        // FIXME(pcwalton): Get the real container size.
-        let container_size = Size2D::zero();
+        // Compute location of the baseline. Sideways glyphs have their tops on the line-over side,
+        // which is the right in both vertical writing modes, unless they are turned to the left;
+        // upright vertical glyphs are centered on the line.
         let metrics = &text_fragment.run.font_metrics;
         let stacking_relative_content_box = stacking_relative_content_box.translate(&offset);
-        let baseline_origin = stacking_relative_content_box.origin +
-            LogicalPoint::new(self.style.writing_mode,
-                              Au(0),
-                              metrics.ascent).to_physical(self.style.writing_mode,
-                                                          container_size);
+        let content_box = &stacking_relative_content_box;
+        let baseline_origin = match orientation {
+            TextOrientation::Upright => {
+                Point2D::new(content_box.origin.x, content_box.origin.y + metrics.ascent)
+            }
+            TextOrientation::SidewaysRight => {
+                Point2D::new(content_box.max_x() - metrics.ascent, content_box.origin.y)
+            }
+            TextOrientation::SidewaysLeft => {
+                Point2D::new(content_box.origin.x + metrics.ascent, content_box.max_y())
+            }
+            TextOrientation::Vertical => {
+                Point2D::new(content_box.origin.x + content_box.size.width / 2, content_box.origin.y)
+            }
+        };
 
         // Create the text display item.
         let base = state.create_base_display_item(&stacking_relative_content_box,
@@ -1640,47 +2283,83 @@ impl FragmentDisplayListBuilding for Fragment {
         }));
 
         // Create display items for text decorations.
-        let mut text_decorations = self.style()
-                                       .get_inheritedtext()
-                                       ._servo_text_decorations_in_effect;
-        // Note that the text decoration colors are always the same as the text color.
-        text_decorations.underline = text_decorations.underline.map(|_| text_color);
-        text_decorations.overline = text_decorations.overline.map(|_| text_color);
-        text_decorations.line_through = text_decorations.line_through.map(|_| text_color);
+        let text_decorations = self.style()
+                                   .get_inheritedtext()
+                                   ._servo_text_decorations_in_effect;
+        // Text decorations are painted in the color of the decorating box, except in shadows.
+        let decoration_color = |decoration: &Decoration| {
+            if text_shadow.is_some() {
+                text_color
+            } else {
+                decoration.color
+            }
+        };
 
+        // FIXME(pcwalton): Get the real container size.
+        let container_size = Size2D::zero();
         let stacking_relative_content_box =
             LogicalRect::from_physical(self.style.writing_mode,
                                        stacking_relative_content_box,
                                        container_size);
-        if let Some(ref underline_color) = text_decorations.underline {
+        if let Some(ref underline) = text_decorations.underline {
+            let thickness = decoration_line_thickness(underline, metrics.underline_size);
+            let offset = match self.style().get_inheritedtext().text_underline_offset.0 {
+                Some(offset) => offset,
+                None => -metrics.underline_offset,
+            };
             let mut stacking_relative_box = stacking_relative_content_box;
             stacking_relative_box.start.b = stacking_relative_content_box.start.b +
-                metrics.ascent - metrics.underline_offset;
-            stacking_relative_box.size.block = metrics.underline_size;
-            self.build_display_list_for_text_decoration(state,
-                                                        underline_color,
-                                                        &stacking_relative_box,
-                                                        clip,
-                                                        shadow_blur_radius);
+                metrics.ascent + offset;
+            stacking_relative_box.size.block = thickness;
+
+            // Leave gaps in the underline where descenders cross it.
+            let mut descenders = match self.style().get_inheritedtext().text_decoration_skip_ink {
+                text_decoration_skip_ink::T::auto => {
+                    text_fragment.run.descender_extents_for_range(&text_fragment.range, offset)
+                }
+                text_decoration_skip_ink::T::none => vec![],
+            };
+            let inline_size = stacking_relative_box.size.inline;
+            if !self.style.writing_mode.is_bidi_ltr() {
+                descenders = descenders.iter().rev().map(|&(start, end)| {
+                    (inline_size - end, inline_size - start)
+                }).collect();
+            }
+            for (start, end) in skip_ink_segments(inline_size, &descenders, thickness) {
+                let mut segment_box = stacking_relative_box;
+                segment_box.start.i = stacking_relative_box.start.i + start;
+                segment_box.size.inline = end - start;
+                self.build_display_list_for_text_decoration(state,
+                                                            underline.style,
+                                                            &decoration_color(underline),
+                                                            &segment_box,
+                                                            clip,
+                                                            shadow_blur_radius);
+            }
         }
 
-        if let Some(ref overline_color) = text_decorations.overline {
+        if let Some(ref overline) = text_decorations.overline {
             let mut stacking_relative_box = stacking_relative_content_box;
-            stacking_relative_box.size.block = metrics.underline_size;
+            stacking_relative_box.size.block = decoration_line_thickness(overline,
+                                                                         metrics.underline_size);
             self.build_display_list_for_text_decoration(state,
-                                                        overline_color,
+                                                        overline.style,
+                                                        &decoration_color(overline),
                                                         &stacking_relative_box,
                                                         clip,
                                                         shadow_blur_radius);
         }
 
-        if let Some(ref line_through_color) = text_decorations.line_through {
+        if let Some(ref line_through) = text_decorations.line_through {
+            // Keep the line centered on the font's strikeout position whatever its thickness.
+            let thickness = decoration_line_thickness(line_through, metrics.strikeout_size);
             let mut stacking_relative_box = stacking_relative_content_box;
             stacking_relative_box.start.b = stacking_relative_box.start.b + metrics.ascent -
-                metrics.strikeout_offset;
-            stacking_relative_box.size.block = metrics.strikeout_size;
+                metrics.strikeout_offset + (metrics.strikeout_size - thickness) / 2;
+            stacking_relative_box.size.block = thickness;
             self.build_display_list_for_text_decoration(state,
-                                                        line_through_color,
+                                                        line_through.style,
+                                                        &decoration_color(line_through),
                                                         &stacking_relative_box,
                                                         clip,
                                                         shadow_blur_radius);
@@ -1689,35 +2368,122 @@ impl FragmentDisplayListBuilding for Fragment {
 
     fn build_display_list_for_text_decoration(&self,
                                               state: &mut DisplayListBuildState,
+                                              decoration_style: text_decoration_style::T,
                                               color: &RGBA,
                                               stacking_relative_box: &LogicalRect<Au>,
                                               clip: &ClippingRegion,
                                               blur_radius: Au) {
-        // Perhaps surprisingly, text decorations are box shadows. This is because they may need
-        // to have blur in the case of `text-shadow`, and this doesn't hurt performance because box
-        // shadows are optimized into essentially solid colors if there is no need for the blur.
-        //
         // FIXME(pcwalton, #2795): Get the real container size.
         let container_size = Size2D::zero();
-        let stacking_relative_box = stacking_relative_box.to_physical(self.style.writing_mode,
-                                                                      container_size);
-        let base = state.create_base_display_item(
-            &shadow_bounds(&stacking_relative_box, blur_radius, Au(0)),
-            clip,
-            self.node,
-            self.style.get_cursor(Cursor::Default),
-            DisplayListSection::Content);
-        state.add_display_item(DisplayItem::BoxShadowClass(box BoxShadowDisplayItem {
-            base: base,
-            box_bounds: stacking_relative_box,
-            color: color.to_gfx_color(),
-            offset: Point2D::zero(),
-            blur_radius: blur_radius,
-            spread_radius: Au(0),
-            border_radius: Au(0),
-            clip_mode: BoxShadowClipMode::None,
-        }));
+        let writing_mode = self.style.writing_mode;
+        let thickness = stacking_relative_box.size.block;
+
+        // Dotted and dashed lines are the block-start border of the box the line occupies.
+        let border_style = match decoration_style {
+            text_decoration_style::T::dotted => Some(border_style::T::dotted),
+            text_decoration_style::T::dashed => Some(border_style::T::dashed),
+            _ => None,
+        };
+        if let Some(border_style) = border_style {
+            let stacking_relative_box = stacking_relative_box.to_physical(writing_mode,
+                                                                          container_size);
+            let base = state.create_base_display_item(&stacking_relative_box,
+                                                      clip,
+                                                      self.node,
+                                                      self.style.get_cursor(Cursor::Default),
+                                                      DisplayListSection::Content);
+            let border_widths = LogicalMargin::new(writing_mode, thickness, Au(0), Au(0), Au(0));
+            state.add_display_item(DisplayItem::BorderClass(box BorderDisplayItem {
+                base: base,
+                border_widths: border_widths.to_physical(writing_mode),
+                color: SideOffsets2D::new_all_same(color.to_gfx_color()),
+                style: SideOffsets2D::new_all_same(border_style),
+                radius: Default::default(),
+            }));
+            return
+        }
+
+        let mut lines = vec![];
+        match decoration_style {
+            text_decoration_style::T::double => {
+                let mut second_line = *stacking_relative_box;
+                second_line.start.b = second_line.start.b + thickness * 2;
+                lines.push(*stacking_relative_box);
+                lines.push(second_line);
+            }
+            text_decoration_style::T::wavy => {
+                // Approximate the wave with short steps that rise and fall by up to the thickness
+                // of the line, eight steps per wavelength.
+                let inline_size = stacking_relative_box.size.inline;
+                let step = cmp::max(cmp::max(thickness, Au::from_px(1)),
+                                    inline_size / MAX_WAVY_LINE_STEPS);
+                let mut position = Au(0);
+                let mut index = 0;
+                while position < inline_size {
+                    let phase = index as f32 * f32::consts::FRAC_PI_4;
+                    let mut line = *stacking_relative_box;
+                    line.start.i = line.start.i + position;
+                    line.start.b = line.start.b + thickness.scale_by(phase.sin());
+                    line.size.inline = cmp::min(step, inline_size - position);
+                    lines.push(line);
+                    position = position + step;
+                    index += 1;
+                }
+            }
+            _ => lines.push(*stacking_relative_box),
+        }
+
+        // Perhaps surprisingly, solid text decorations are box shadows. This is because they may
+        // need to have blur in the case of `text-shadow`, and this doesn't hurt performance
+        // because box shadows are optimized into essentially solid colors if there is no need for
+        // the blur.
+        for line in lines {
+            let line = line.to_physical(writing_mode, container_size);
+            let base = state.create_base_display_item(
+                &shadow_bounds(&line, blur_radius, Au(0)),
+                clip,
+                self.node,
+                self.style.get_cursor(Cursor::Default),
+                DisplayListSection::Content);
+            state.add_display_item(DisplayItem::BoxShadowClass(box BoxShadowDisplayItem {
+                base: base,
+                box_bounds: line,
+                color: color.to_gfx_color(),
+                offset: Point2D::zero(),
+                blur_radius: blur_radius,
+                spread_radius: Au(0),
+                border_radius: Au(0),
+                clip_mode: BoxShadowClipMode::None,
+            }));
+        }
+    }
+}
+
+/// Returns the thickness of a text decoration line, given the thickness the font recommends.
+fn decoration_line_thickness(decoration: &Decoration, font_thickness: Au) -> Au {
+    match decoration.thickness {
+        text_decoration_thickness::T::Auto |
+        text_decoration_thickness::T::FromFont => font_thickness,
+        text_decoration_thickness::T::Length(length) => cmp::max(length, Au::from_px(1)),
+    }
+}
+
+/// Splits an underline of the given inline size into the segments that remain after cutting out
+/// the given sorted `gaps`, each widened by `padding` on either side.
+fn skip_ink_segments(inline_size: Au, gaps: &[(Au, Au)], padding: Au) -> Vec<(Au, Au)> {
+    let mut segments = vec![];
+    let mut start = Au(0);
+    for &(gap_start, gap_end) in gaps {
+        let gap_start = cmp::min(gap_start - padding, inline_size);
+        if gap_start > start {
+            segments.push((start, gap_start));
+        }
+        start = cmp::max(start, gap_end + padding);
     }
+    if start < inline_size {
+        segments.push((start, inline_size));
+    }
+    segments
 }
 
 pub trait BlockFlowDisplayListBuilding {
@@ -1728,6 +2494,11 @@ pub trait BlockFlowDisplayListBuilding {
     fn build_display_list_for_block(&mut self,
                                     state: &mut DisplayListBuildState,
                                     border_painting_mode: BorderPaintingMode);
+
+    /// Adds the display items for the scrollbars of this block if it is a scroll container.
+    fn build_display_list_for_scrollbars(&self,
+                                         state: &mut DisplayListBuildState,
+                                         clip: &ClippingRegion);
 }
 
 impl BlockFlowDisplayListBuilding for BlockFlow {
@@ -1737,10 +2508,18 @@ impl BlockFlowDisplayListBuilding for BlockFlow {
                                            -> StackingContextId {
         let block_stacking_context_type = self.block_stacking_context_type();
         if block_stacking_context_type == BlockStackingContextType::NonstackingContext {
+            // Inline blocks, tables and flex containers are painted as if they established a
+            // stacking context, so their display lists can be built on their own as well.
+            match self.fragment.style.get_box().display {
+                display::T::inline_block | display::T::table | display::T::inline_table |
+                display::T::flex => self.base.flags.insert(ESTABLISHES_STACKING_CONTEXT),
+                _ => self.base.flags.remove(ESTABLISHES_STACKING_CONTEXT),
+            }
             self.base.stacking_context_id = parent_id;
             self.base.collect_stacking_contexts_for_children(parent_id, contexts);
             return parent_id;
         }
+        self.base.flags.insert(ESTABLISHES_STACKING_CONTEXT);
 
         let has_scrolling_overflow = self.has_scrolling_overflow();
         let stacking_context_id = if has_scrolling_overflow {
@@ -1830,15 +2609,12 @@ impl BlockFlowDisplayListBuilding for BlockFlow {
     fn build_display_list_for_block(&mut self,
                                     state: &mut DisplayListBuildState,
                                     border_painting_mode: BorderPaintingMode) {
+        // The root of a stacking context paints its background and borders before any of its
+        // children with negative `z-index` (CSS 2.1 Appendix E, step 2).
         let establishes_stacking_context = self.fragment.establishes_stacking_context();
-        let background_border_section = if self.base.flags.is_float() {
+        let background_border_section = if self.base.flags.is_float() ||
+                establishes_stacking_context {
             DisplayListSection::BackgroundAndBorders
-        } else if self.base.flags.contains(IS_ABSOLUTELY_POSITIONED) {
-            if establishes_stacking_context {
-                DisplayListSection::BackgroundAndBorders
-            } else {
-                DisplayListSection::BlockBackgroundsAndBorders
-            }
         } else {
             DisplayListSection::BlockBackgroundsAndBorders
         };
@@ -1852,6 +2628,22 @@ impl BlockFlowDisplayListBuilding for BlockFlow {
             &self.base.clip
         };
 
+        // Elements in the top layer paint their `::backdrop` over the whole viewport, below
+        // themselves but above the rest of the document.
+        if establishes_stacking_context {
+            if let Some(ref backdrop) = self.backdrop {
+                let viewport_size = state.shared_layout_context.style_context.viewport_size;
+                let viewport_rect = Rect::new(-self.base.stacking_relative_position,
+                                              viewport_size);
+                backdrop.build_display_list_for_background_if_applicable(
+                    state,
+                    &*backdrop.style,
+                    DisplayListSection::BackgroundAndBorders,
+                    &viewport_rect,
+                    &ClippingRegion::max());
+            }
+        }
+
         self.fragment
             .build_display_list(state,
                                 &self.base.stacking_relative_position,
@@ -1866,8 +2658,171 @@ impl BlockFlowDisplayListBuilding for BlockFlow {
                                 clip,
                                 &self.base.stacking_relative_position_of_display_port);
 
+        if self.has_scrolling_overflow() {
+            self.build_display_list_for_scrollbars(state, clip);
+        }
+
         self.base.build_display_items_for_debugging_tint(state, self.fragment.node);
     }
+
+    fn build_display_list_for_scrollbars(&self,
+                                         state: &mut DisplayListBuildState,
+                                         clip: &ClippingRegion) {
+        let style = self.fragment.style();
+        let stacking_relative_border_box =
+            self.fragment.stacking_relative_border_box(
+                &self.base.stacking_relative_position,
+                &self.base.early_absolute_position_info.relative_containing_block_size,
+                self.base.early_absolute_position_info.relative_containing_block_mode,
+                CoordinateSystem::Own);
+        let scrollbars = match scrollbar_info(style,
+                                              &stacking_relative_border_box,
+                                              &self.base.overflow.scroll.size) {
+            Some(scrollbars) => scrollbars,
+            None => return,
+        };
+
+        let (thumb_color, track_color) = match style.get_pointing().scrollbar_color {
+            scrollbar_color::T::Auto => {
+                (color::rgba(0.5, 0.5, 0.5, 1.0), color::rgba(0.94, 0.94, 0.94, 1.0))
+            }
+            scrollbar_color::T::Colors { thumb, track } => {
+                (style.resolve_color(thumb).to_gfx_color(),
+                 style.resolve_color(track).to_gfx_color())
+            }
+        };
+
+        // FIXME: The scroll offset lives in the compositor, so the thumb is always drawn at the
+        // start of the track, even though the compositor hit tests it at the current offset.
+        for &axis in &[ScrollbarAxis::Vertical, ScrollbarAxis::Horizontal] {
+            let (track, thumb) = match (scrollbars.track(axis), scrollbars.thumb(axis, 0.0)) {
+                (Some(track), Some(thumb)) => (to_au_rect(&track), to_au_rect(&thumb)),
+                _ => continue,
+            };
+            for &(bounds, color) in &[(track, track_color), (thumb, thumb_color)] {
+                let base = state.create_base_display_item(&bounds,
+                                                          clip,
+                                                          self.fragment.node,
+                                                          style.get_cursor(Cursor::Default),
+                                                          DisplayListSection::Outlines);
+                state.add_display_item(DisplayItem::SolidColorClass(box SolidColorDisplayItem {
+                    base: base,
+                    color: color,
+                }));
+            }
+        }
+    }
+}
+
+/// Returns the rectangle that replaced content with the given natural size is drawn into, per
+/// `object-fit` and `object-position`, and the clip that keeps it within the content box.
+///
+/// https://drafts.csswg.org/css-images-3/#the-object-fit
+fn object_fit_bounds_and_clip(style: &ServoComputedValues,
+                              content_box: &Rect<Au>,
+                              natural_size: Size2D<Au>,
+                              clip: &ClippingRegion)
+                              -> (Rect<Au>, ClippingRegion) {
+    if natural_size.width <= Au(0) || natural_size.height <= Au(0) {
+        return (*content_box, clip.clone())
+    }
+
+    let scale_x = content_box.size.width.to_f32_px() / natural_size.width.to_f32_px();
+    let scale_y = content_box.size.height.to_f32_px() / natural_size.height.to_f32_px();
+    let scale = match style.get_position().object_fit {
+        object_fit::T::fill => None,
+        object_fit::T::contain => Some(scale_x.min(scale_y)),
+        object_fit::T::cover => Some(scale_x.max(scale_y)),
+        object_fit::T::none => Some(1.),
+        object_fit::T::scale_down => Some(scale_x.min(scale_y).min(1.)),
+    };
+    let size = match scale {
+        Some(scale) => Size2D::new(natural_size.width.scale_by(scale),
+                                   natural_size.height.scale_by(scale)),
+        None => content_box.size,
+    };
+
+    // Percentages of `object-position` are of the space left over in the content box.
+    let position = style.get_position().object_position;
+    let origin = Point2D::new(
+        content_box.origin.x + model::specified(position.horizontal,
+                                                content_box.size.width - size.width),
+        content_box.origin.y + model::specified(position.vertical,
+                                                content_box.size.height - size.height));
+
+    let mut clip = clip.clone();
+    clip.intersect_rect(content_box);
+    (Rect::new(origin, size), clip)
+}
+
+/// Returns the scrollbars that a scroll container with the given style, border box and scrollable
+/// overflow shows, if any. The tracks are in the coordinate system of `border_box`.
+///
+/// Scrollbars are painted by layout rather than by the platform, so they sit inside the padding
+/// box along its right and bottom edges, in the space `scrollbar-gutter` reserves.
+fn scrollbar_info(style: &ServoComputedValues, border_box: &Rect<Au>, content_size: &Size2D<Au>)
+                  -> Option<ScrollbarInfo> {
+    if style.get_inheritedbox().visibility != visibility::T::visible {
+        return None
+    }
+    let thickness = model::scrollbar_thickness(style);
+    if thickness == Au(0) {
+        return None
+    }
+
+    let border = style.logical_border_width().to_physical(style.writing_mode);
+    let mut padding_box = *border_box;
+    padding_box.origin.x = padding_box.origin.x + border.left;
+    padding_box.origin.y = padding_box.origin.y + border.top;
+    padding_box.size.width = padding_box.size.width - border.horizontal();
+    padding_box.size.height = padding_box.size.height - border.vertical();
+
+    let show_vertical = match style.get_box().overflow_y.0 {
+        overflow_x::T::scroll => true,
+        overflow_x::T::auto => content_size.height > padding_box.size.height,
+        _ => false,
+    };
+    let show_horizontal = match style.get_box().overflow_x {
+        overflow_x::T::scroll => true,
+        overflow_x::T::auto => content_size.width > padding_box.size.width,
+        _ => false,
+    };
+    if !show_vertical && !show_horizontal {
+        return None
+    }
+
+    let vertical_track = if show_vertical {
+        let length = padding_box.size.height - if show_horizontal { thickness } else { Au(0) };
+        Some(Rect::new(Point2D::new(padding_box.max_x() - thickness, padding_box.origin.y),
+                       Size2D::new(thickness, length)))
+    } else {
+        None
+    };
+    let horizontal_track = if show_horizontal {
+        let length = padding_box.size.width - if show_vertical { thickness } else { Au(0) };
+        Some(Rect::new(Point2D::new(padding_box.origin.x, padding_box.max_y() - thickness),
+                       Size2D::new(length, thickness)))
+    } else {
+        None
+    };
+
+    Some(ScrollbarInfo {
+        horizontal_track: horizontal_track.as_ref().map(to_f32_rect),
+        vertical_track: vertical_track.as_ref().map(to_f32_rect),
+        client_size: Size2D::new(padding_box.size.width.to_f32_px(),
+                                 padding_box.size.height.to_f32_px()),
+        content_size: Size2D::new(content_size.width.to_f32_px(), content_size.height.to_f32_px()),
+    })
+}
+
+fn to_f32_rect(rect: &Rect<Au>) -> Rect<f32> {
+    Rect::new(Point2D::new(rect.origin.x.to_f32_px(), rect.origin.y.to_f32_px()),
+              Size2D::new(rect.size.width.to_f32_px(), rect.size.height.to_f32_px()))
+}
+
+fn to_au_rect(rect: &Rect<f32>) -> Rect<Au> {
+    Rect::new(Point2D::new(Au::from_f32_px(rect.origin.x), Au::from_f32_px(rect.origin.y)),
+              Size2D::new(Au::from_f32_px(rect.size.width), Au::from_f32_px(rect.size.height)))
 }
 
 pub trait InlineFlowDisplayListBuilding {
@@ -1943,6 +2898,9 @@ impl InlineFlowDisplayListBuilding for InlineFlow {
         for index in 0..self.fragments.fragments.len() {
             let (establishes_stacking_context, stacking_context_id) = {
                 let fragment = self.fragments.fragments.get(index).unwrap();
+                if fragment.flags.contains(IS_TRUNCATED_BY_ELLIPSIS) {
+                    continue
+                }
                 (self.base.stacking_context_id != fragment.stacking_context_id,
                  fragment.stacking_context_id)
             };
@@ -2007,6 +2965,153 @@ impl FlexFlowDisplayListBuilding for FlexFlow {
     }
 }
 
+pub trait MulticolFlowDisplayListBuilding {
+    fn build_display_list_for_multicol(&mut self, state: &mut DisplayListBuildState);
+}
+
+impl MulticolFlowDisplayListBuilding for MulticolFlow {
+    fn build_display_list_for_multicol(&mut self, state: &mut DisplayListBuildState) {
+        self.block_flow.build_display_list_for_block(state, BorderPaintingMode::Separate);
+
+        // Column rules are drawn in the middle of the gap between each pair of adjacent columns,
+        // as tall as the columns.
+        // https://drafts.csswg.org/css-multicol/#column-gaps-and-rules
+        let style = self.block_flow.fragment.style.clone();
+        let column_style = style.get_column();
+        let rule_width = column_style.column_rule_width;
+        let rule_style = column_style.column_rule_style;
+        if rule_width == Au(0) || rule_style == border_style::T::none ||
+                rule_style == border_style::T::hidden ||
+                style.get_inheritedbox().visibility != visibility::T::visible {
+            return
+        }
+
+        let writing_mode = style.writing_mode;
+        let column_block_size = self.block_flow.fragment.border_box.size.block -
+            self.block_flow.fragment.border_padding.block_start_end();
+        let offset = (self.column_gap + rule_width) / 2;
+        let color = style.resolve_color(column_style.column_rule_color).to_gfx_color();
+        for column in self.block_flow.base.children.iter().skip(1) {
+            let origin = flow::base(column).stacking_relative_position;
+            let (bounds, border_widths) = if writing_mode.is_vertical() {
+                (Rect::new(Point2D::new(origin.x, origin.y - offset),
+                           Size2D::new(column_block_size, rule_width)),
+                 SideOffsets2D::new(rule_width, Au(0), Au(0), Au(0)))
+            } else {
+                (Rect::new(Point2D::new(origin.x - offset, origin.y),
+                           Size2D::new(rule_width, column_block_size)),
+                 SideOffsets2D::new(Au(0), Au(0), Au(0), rule_width))
+            };
+            let base = state.create_base_display_item(&bounds,
+                                                      &self.block_flow.base.clip,
+                                                      self.block_flow.fragment.node,
+                                                      style.get_cursor(Cursor::Default),
+                                                      DisplayListSection::Content);
+            state.add_display_item(DisplayItem::BorderClass(box BorderDisplayItem {
+                base: base,
+                border_widths: border_widths,
+                color: SideOffsets2D::new_all_same(color),
+                style: SideOffsets2D::new_all_same(rule_style),
+                radius: Default::default(),
+            }));
+        }
+    }
+}
+
+pub trait PagedFlowDisplayListBuilding {
+    fn build_display_list_for_paged(&mut self, state: &mut DisplayListBuildState);
+}
+
+impl PagedFlowDisplayListBuilding for PagedFlow {
+    fn build_display_list_for_paged(&mut self, state: &mut DisplayListBuildState) {
+        self.block_flow.build_display_list_for_block(state, BorderPaintingMode::Separate);
+
+        // Draw the headers and footers in the page margins.
+        for fragment in &mut self.margin_box_fragments {
+            fragment.build_display_list(state,
+                                        &self.block_flow.base.stacking_relative_position,
+                                        &self.block_flow
+                                             .base
+                                             .early_absolute_position_info
+                                             .relative_containing_block_size,
+                                        self.block_flow
+                                            .base
+                                            .early_absolute_position_info
+                                            .relative_containing_block_mode,
+                                        BorderPaintingMode::Separate,
+                                        DisplayListSection::Content,
+                                        &self.block_flow.base.clip,
+                                        &self.block_flow
+                                             .base
+                                             .stacking_relative_position_of_display_port);
+        }
+    }
+}
+
+pub trait MathFlowDisplayListBuilding {
+    fn build_display_list_for_math(&mut self, state: &mut DisplayListBuildState);
+}
+
+impl MathFlowDisplayListBuilding for MathFlow {
+    fn build_display_list_for_math(&mut self, state: &mut DisplayListBuildState) {
+        self.block_flow.build_display_list_for_block(state, BorderPaintingMode::Separate);
+
+        let style = self.block_flow.fragment.style.clone();
+        if style.get_inheritedbox().visibility != visibility::T::visible {
+            return
+        }
+
+        let border_box = self.block_flow.fragment.stacking_relative_border_box(
+            &self.block_flow.base.stacking_relative_position,
+            &self.block_flow.base.early_absolute_position_info.relative_containing_block_size,
+            self.block_flow.base.early_absolute_position_info.relative_containing_block_mode,
+            CoordinateSystem::Own);
+        let border_padding = self.block_flow.fragment.border_padding.to_physical(style.writing_mode);
+        let border = style.logical_border_width().to_physical(style.writing_mode);
+        let content_box = Rect::new(Point2D::new(border_box.origin.x + border_padding.left,
+                                                 border_box.origin.y + border_padding.top),
+                                    Size2D::new(border_box.size.width - border_padding.horizontal(),
+                                                border_box.size.height - border_padding.vertical()));
+        let thickness = self.rule_thickness;
+        let rule_y = border_box.origin.y + self.rule_offset;
+
+        let mut rules = vec![];
+        match self.kind {
+            MathLayoutKind::Fraction => {
+                rules.push(Rect::new(Point2D::new(content_box.origin.x, rule_y),
+                                     Size2D::new(content_box.size.width, thickness)));
+            }
+            MathLayoutKind::Radical => {
+                // The radical sign is drawn with straight strokes in the inline-start padding
+                // that `mathml.css` reserves: a tick, a stem, and the overbar over the content.
+                let stem_x = content_box.origin.x - thickness * 2;
+                let tick_x = border_box.origin.x + border.left;
+                let tick_y = rule_y + (content_box.max_y() - rule_y) / 2;
+                rules.push(Rect::new(Point2D::new(stem_x, rule_y),
+                                     Size2D::new(content_box.max_x() - stem_x, thickness)));
+                rules.push(Rect::new(Point2D::new(stem_x, rule_y),
+                                     Size2D::new(thickness, content_box.max_y() - rule_y)));
+                rules.push(Rect::new(Point2D::new(tick_x, tick_y),
+                                     Size2D::new(cmp::max(Au(0), stem_x - tick_x), thickness)));
+            }
+            MathLayoutKind::Scripts { .. } => {}
+        }
+
+        let color = style.get_color().color.to_gfx_color();
+        for rule in rules {
+            let base = state.create_base_display_item(&rule,
+                                                      &self.block_flow.base.clip,
+                                                      self.block_flow.fragment.node,
+                                                      style.get_cursor(Cursor::Default),
+                                                      DisplayListSection::Content);
+            state.add_display_item(DisplayItem::SolidColorClass(box SolidColorDisplayItem {
+                base: base,
+                color: color,
+            }));
+        }
+    }
+}
+
 trait BaseFlowDisplayListBuilding {
     fn build_display_items_for_debugging_tint(&self,
                                               state: &mut DisplayListBuildState,
@@ -2062,28 +3167,273 @@ impl ServoComputedValuesCursorUtility for ServoComputedValues {
     }
 }
 
-// A helper data structure for gradients.
-#[derive(Copy, Clone)]
-struct StopRun {
-    start_offset: f32,
-    end_offset: f32,
-    start_index: usize,
-    stop_count: usize,
-}
+/// The most stops a repeating gradient is expanded into. Past this the repetitions are so thin
+/// that the gradient is painted as its average color instead.
+const MAX_REPEATED_GRADIENT_STOPS: usize = 4096;
+
+/// The most rows a conic gradient is painted as.
+const MAX_CONIC_GRADIENT_ROWS: i32 = 512;
+
+/// How many times a row of a conic gradient is sampled per turn around the center.
+const CONIC_GRADIENT_SAMPLES_PER_TURN: f32 = 256.0;
+
+/// The most tiles a part of a border image is repeated into along one axis. Past this the part is
+/// stretched instead.
+const MAX_BORDER_IMAGE_TILES: f32 = 256.0;
+
+/// The most steps a wavy text decoration is drawn with. Past this the steps are widened.
+const MAX_WAVY_LINE_STEPS: i32 = 1024;
 
 fn position_to_offset(position: LengthOrPercentage, Au(total_length): Au) -> f32 {
     match position {
-        LengthOrPercentage::Length(Au(length)) => {
-            (1.0f32).min(length as f32 / total_length as f32)
-        }
+        LengthOrPercentage::Length(Au(length)) => length as f32 / total_length as f32,
         LengthOrPercentage::Percentage(percentage) => percentage as f32,
         LengthOrPercentage::Calc(calc) =>
-            (1.0f32).min(calc.percentage() + (calc.length().0 as f32) / (total_length as f32)),
+            calc.to_used_value(Au(total_length)).0 as f32 / total_length as f32,
     }
 }
 
+/// Determines the offset of each stop along a gradient line of the given length per
+/// CSS-IMAGES-4 § 3.5.3, replacing interpolation hints with stops that approximate the transition
+/// they describe. Offsets may be outside the line.
+fn convert_gradient_stops(items: &[GradientItem],
+                          total_length: Au,
+                          style: &ServoComputedValues)
+                          -> Vec<GradientStop> {
+    // The first and last stops default to the ends of the line, and no stop or hint may be before
+    // one preceding it.
+    let mut offsets = Vec::with_capacity(items.len());
+    let mut max_offset = f32::MIN;
+    for (i, item) in items.iter().enumerate() {
+        let position = match *item {
+            GradientItem::ColorStop(ref stop) => stop.position,
+            GradientItem::InterpolationHint(position) => Some(position),
+        };
+        let offset = match position {
+            Some(position) => Some(position_to_offset(position, total_length)),
+            None if i == 0 => Some(0.0),
+            None if i == items.len() - 1 => Some(1.0),
+            None => None,
+        };
+        if let Some(offset) = offset {
+            max_offset = max_offset.max(offset);
+            offsets.push(Some(max_offset));
+        } else {
+            offsets.push(None);
+        }
+    }
+
+    // Space runs of stops without a position evenly between the stops around them. Hints within
+    // a run keep their positions.
+    let color_stops: Vec<usize> = items.iter().enumerate().filter_map(|(i, item)| {
+        match *item {
+            GradientItem::ColorStop(_) => Some(i),
+            GradientItem::InterpolationHint(_) => None,
+        }
+    }).collect();
+    let mut previous = 0;
+    for (n, &i) in color_stops.iter().enumerate().skip(1) {
+        // `unwrap()` can't fail, since the first and last stops always have offsets.
+        if let Some(end_offset) = offsets[i] {
+            let start_offset = offsets[color_stops[previous]].unwrap();
+            for k in (previous + 1)..n {
+                offsets[color_stops[k]] = Some(start_offset + (end_offset - start_offset) *
+                                               (k - previous) as f32 / (n - previous) as f32);
+            }
+            previous = n;
+        }
+    }
+
+    let mut stops: Vec<GradientStop> = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        match *item {
+            GradientItem::ColorStop(ref stop) => {
+                stops.push(GradientStop {
+                    offset: offsets[i].unwrap(),
+                    color: style.resolve_color(stop.color).to_gfx_color(),
+                })
+            }
+            GradientItem::InterpolationHint(_) => {
+                // Hints are always between two color stops.
+                let next = match items[i + 1] {
+                    GradientItem::ColorStop(ref stop) => GradientStop {
+                        offset: offsets[i + 1].unwrap(),
+                        color: style.resolve_color(stop.color).to_gfx_color(),
+                    },
+                    GradientItem::InterpolationHint(_) => unreachable!(),
+                };
+                push_interpolation_hint(&mut stops, &next, offsets[i].unwrap());
+            }
+        }
+    }
+    stops
+}
+
+/// Adds the stops between the last of `stops` and `next` for an interpolation hint at the given
+/// offset. The colors follow a power curve that mixes them evenly at the hint, sampled at tenths of
+/// the distance between the stops.
+fn push_interpolation_hint(stops: &mut Vec<GradientStop>, next: &GradientStop, hint: f32) {
+    let (start_offset, start_color) = {
+        let previous = stops.last().unwrap();
+        (previous.offset, previous.color)
+    };
+    let length = next.offset - start_offset;
+    if length <= 0.0 {
+        return
+    }
+
+    let position = (hint - start_offset) / length;
+    if position <= 0.0 {
+        stops.push(GradientStop {
+            offset: start_offset,
+            color: next.color,
+        });
+        return
+    }
+    if position >= 1.0 {
+        stops.push(GradientStop {
+            offset: next.offset,
+            color: start_color,
+        });
+        return
+    }
+
+    let exponent = 0.5f32.ln() / position.ln();
+    for k in 1..10 {
+        let t = k as f32 / 10.0;
+        stops.push(GradientStop {
+            offset: start_offset + length * t,
+            color: mix_colors(start_color, next.color, t.powf(exponent)),
+        })
+    }
+}
+
+/// Repeats the stops of a repeating gradient, whose positions span the distance between the first
+/// and last of them, as many times as needed to cover the range from `start` to `end`.
+fn repeat_gradient_stops(stops: &[GradientStop], start: f32, end: f32) -> Vec<GradientStop> {
+    let first = stops[0].offset;
+    let span = stops[stops.len() - 1].offset - first;
+    let first_repetition = ((start - first) / span).floor();
+    let last_repetition = ((end - first) / span).floor();
+    let repetitions = last_repetition - first_repetition + 1.0;
+    if span <= 0.0 || repetitions * stops.len() as f32 > MAX_REPEATED_GRADIENT_STOPS as f32 {
+        let color = average_gradient_color(stops);
+        return vec![
+            GradientStop {
+                offset: start,
+                color: color,
+            },
+            GradientStop {
+                offset: end,
+                color: color,
+            },
+        ]
+    }
+
+    let mut repeated = Vec::with_capacity(repetitions as usize * stops.len());
+    for repetition in (first_repetition as i32)..(last_repetition as i32 + 1) {
+        let shift = span * repetition as f32;
+        repeated.extend(stops.iter().map(|stop| {
+            GradientStop {
+                offset: stop.offset + shift,
+                color: stop.color,
+            }
+        }));
+    }
+    repeated
+}
+
+/// The average color between the first and last of `stops`, or of all their colors if they are
+/// at the same position.
+fn average_gradient_color(stops: &[GradientStop]) -> Color {
+    let span = stops[stops.len() - 1].offset - stops[0].offset;
+    if span <= 0.0 {
+        let weight = 1.0 / stops.len() as f32;
+        return stops.iter().fold(color::transparent(), |sum, stop| add_color(sum, stop.color, weight))
+    }
+    stops.windows(2).fold(color::transparent(), |sum, pair| {
+        let weight = (pair[1].offset - pair[0].offset) / span;
+        add_color(sum, mix_colors(pair[0].color, pair[1].color, 0.5), weight)
+    })
+}
+
+/// The color of a gradient at the given offset. Where two stops share the offset, `after` picks
+/// the color of the later one.
+fn gradient_color_at(stops: &[GradientStop], offset: f32, after: bool) -> Color {
+    let next = if after {
+        stops.iter().position(|stop| stop.offset > offset)
+    } else {
+        stops.iter().position(|stop| stop.offset >= offset)
+    };
+    match next {
+        None => stops[stops.len() - 1].color,
+        Some(0) => stops[0].color,
+        Some(i) => {
+            let (start, end) = (&stops[i - 1], &stops[i]);
+            mix_colors(start.color, end.color, (offset - start.offset) / (end.offset - start.offset))
+        }
+    }
+}
+
+fn mix_colors(start: Color, end: Color, t: f32) -> Color {
+    color::new(start.r + (end.r - start.r) * t,
+               start.g + (end.g - start.g) * t,
+               start.b + (end.b - start.b) * t,
+               start.a + (end.a - start.a) * t)
+}
+
+fn add_color(sum: Color, color: Color, weight: f32) -> Color {
+    color::new(sum.r + color.r * weight,
+               sum.g + color.g * weight,
+               sum.b + color.b * weight,
+               sum.a + color.a * weight)
+}
+
 /// Adjusts `content_rect` as necessary for the given spread, and blur so that the resulting
 /// bounding rect contains all of a shadow's ink.
+/// Lays out the tiles of a part of a border image along one axis of a destination that is `length`
+/// long, returning the offset and length of each. `tile_length` is the length of the part when
+/// scaled, if it can be, to the width of its edge. See CSS-BACKGROUNDS § 6.5.
+fn border_image_tiles(repeat: border_image_repeat::RepeatKeyword,
+                      length: f32,
+                      tile_length: Option<f32>)
+                      -> Vec<(f32, f32)> {
+    use style::computed_values::border_image_repeat::RepeatKeyword;
+
+    let tile_length = match tile_length {
+        Some(tile_length) if repeat != RepeatKeyword::stretch && tile_length > 0.0 &&
+                length / tile_length <= MAX_BORDER_IMAGE_TILES => tile_length,
+        _ => return vec![(0.0, length)],
+    };
+    match repeat {
+        RepeatKeyword::stretch => vec![(0.0, length)],
+        RepeatKeyword::repeat => {
+            // Tiles are centered, so that the ends are cut off evenly.
+            let mut offset = (length - tile_length) / 2.0;
+            offset -= (offset / tile_length).ceil() * tile_length;
+            let mut tiles = vec![];
+            while offset < length {
+                tiles.push((offset, tile_length));
+                offset += tile_length;
+            }
+            tiles
+        }
+        RepeatKeyword::round => {
+            let count = (length / tile_length).round().max(1.0);
+            let tile_length = length / count;
+            (0..count as i32).map(|tile| (tile as f32 * tile_length, tile_length)).collect()
+        }
+        RepeatKeyword::space => {
+            // Only whole tiles are painted, with the space left over spread evenly around them.
+            let count = (length / tile_length).floor();
+            let space = (length - count * tile_length) / (count + 1.0);
+            (0..count as i32).map(|tile| {
+                (space + tile as f32 * (tile_length + space), tile_length)
+            }).collect()
+        }
+    }
+}
+
 fn shadow_bounds(content_rect: &Rect<Au>, blur_radius: Au, spread_radius: Au) -> Rect<Au> {
     let inflation = spread_radius + blur_radius * BLUR_INFLATION_FACTOR;
     content_rect.inflate(inflation, inflation)
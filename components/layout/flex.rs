@@ -20,14 +20,14 @@ use fragment::{Fragment, FragmentBorderBoxIterator, Overflow};
 use gfx::display_list::StackingContext;
 use gfx_traits::StackingContextId;
 use layout_debug;
-use model::{Direction, IntrinsicISizes, MaybeAuto, MinMaxConstraint};
+use model::{self, Direction, IntrinsicISizes, MaybeAuto, MinMaxConstraint};
 use model::{specified, specified_or_none};
 use script_layout_interface::restyle_damage::{REFLOW, REFLOW_OUT_OF_FLOW};
 use std::cmp::{max, min};
 use std::ops::Range;
 use std::sync::Arc;
 use style::computed_values::{align_content, align_self, flex_direction, flex_wrap, justify_content};
-use style::computed_values::border_collapse;
+use style::computed_values::{border_collapse, overflow_x};
 use style::context::{SharedStyleContext, StyleContext};
 use style::logical_geometry::LogicalSize;
 use style::properties::ServoComputedValues;
@@ -84,7 +84,7 @@ fn from_flex_basis(flex_basis: LengthOrPercentageOrAutoOrContent,
         (LengthOrPercentageOrAutoOrContent::Percentage(_), None) =>
             MaybeAuto::Auto,
         (LengthOrPercentageOrAutoOrContent::Calc(calc), Some(size)) =>
-            MaybeAuto::Specified(calc.length() + size.scale_by(calc.percentage())),
+            MaybeAuto::Specified(calc.to_used_value(size)),
         (LengthOrPercentageOrAutoOrContent::Calc(_), None) =>
             MaybeAuto::Auto,
         (LengthOrPercentageOrAutoOrContent::Content, _) =>
@@ -101,6 +101,85 @@ fn from_flex_basis(flex_basis: LengthOrPercentageOrAutoOrContent,
     }
 }
 
+/// The used size of a `row-gap` or `column-gap`, which is zero for `normal`.
+/// https://drafts.csswg.org/css-align/#column-row-gap
+fn used_gap(gap: Option<LengthOrPercentage>, containing_length: Au) -> Au {
+    gap.map_or(Au(0), |gap| specified(gap, containing_length))
+}
+
+/// Whether a flex item with this style is a scroll container, and so has no automatic minimum
+/// size.
+fn is_scroll_container(style: &ServoComputedValues) -> bool {
+    style.get_box().overflow_x != overflow_x::T::visible ||
+        style.get_box().overflow_y.0 != overflow_x::T::visible
+}
+
+/// The automatic minimum main size of a flex item that is not a scroll container: its content
+/// size suggestion, or its specified size suggestion if that is smaller, never more than its
+/// maximum main size.
+/// https://drafts.csswg.org/css-flexbox/#min-size-auto
+fn automatic_minimum_size(content_size: Au, specified_size: MaybeAuto, max_size: Au) -> Au {
+    let suggestion = match specified_size {
+        MaybeAuto::Specified(size) => min(size, content_size),
+        MaybeAuto::Auto => content_size,
+    };
+    min(suggestion, max_size)
+}
+
+/// Returns the offset of the first item of a line and the extra space between adjacent items,
+/// for the free space left in the line.
+/// https://drafts.csswg.org/css-flexbox/#justify-content-property
+fn justify_line(justify: justify_content::T, free_space: Au, item_count: i32) -> (Au, Au) {
+    match justify {
+        justify_content::T::flex_start => (Au(0), Au(0)),
+        justify_content::T::flex_end => (free_space, Au(0)),
+        justify_content::T::center => (free_space / 2, Au(0)),
+        // Falls back to `flex-start` when the items overflow.
+        justify_content::T::space_between => {
+            if free_space < Au(0) || item_count <= 1 {
+                (Au(0), Au(0))
+            } else {
+                (Au(0), free_space / (item_count - 1))
+            }
+        }
+        // Falls back to `center` when the items overflow.
+        justify_content::T::space_around => {
+            if free_space < Au(0) {
+                (free_space / 2, Au(0))
+            } else {
+                let interval = free_space / item_count;
+                (interval / 2, interval)
+            }
+        }
+    }
+}
+
+/// Returns the offset of the first line of a multi-line container and the extra space between
+/// adjacent lines. The extra size that `stretch` gives each line is not included.
+/// https://drafts.csswg.org/css-flexbox/#align-content-property
+fn align_lines(align: align_content::T, free_space: Au, line_count: i32) -> (Au, Au) {
+    match align {
+        align_content::T::stretch | align_content::T::flex_start => (Au(0), Au(0)),
+        align_content::T::flex_end => (free_space, Au(0)),
+        align_content::T::center => (free_space / 2, Au(0)),
+        align_content::T::space_between => {
+            if free_space < Au(0) || line_count <= 1 {
+                (Au(0), Au(0))
+            } else {
+                (Au(0), free_space / (line_count - 1))
+            }
+        }
+        align_content::T::space_around => {
+            if free_space < Au(0) {
+                (free_space / 2, Au(0))
+            } else {
+                let interval = free_space / line_count;
+                (interval / 2, interval)
+            }
+        }
+    }
+}
+
 /// Represents a child in a flex container. Most fields here are used in
 /// flex size resolving, and items are sorted by the 'order' property.
 #[derive(Debug)]
@@ -155,14 +234,13 @@ impl FlexItem {
 
     /// Initialize the used flex base size, minimal main size and maximal main size.
     /// For block mode container this method should be called in assign_block_size()
-    /// pass so that the item has already been layouted.
-    pub fn init_sizes(&mut self, containing_length: Au, direction: Direction) {
+    /// pass so that the item has already been layouted. `containing_length` is `None` when the
+    /// main size of the container is indefinite, which only happens in block mode.
+    pub fn init_sizes(&mut self, containing_length: Option<Au>, direction: Direction) {
         let block = flow_ref::deref_mut(&mut self.flow).as_mut_block();
         match direction {
-            // TODO(stshine): the definition of min-{width, height} in style component
-            // should change to LengthOrPercentageOrAuto for automatic implied minimal size.
-            // https://drafts.csswg.org/css-flexbox-1/#min-size-auto
             Direction::Inline => {
+                let containing_length = containing_length.unwrap_or(Au(0));
                 let basis = from_flex_basis(self.style.get_position().flex_basis,
                                             self.style.content_inline_size(),
                                             Some(containing_length));
@@ -173,25 +251,81 @@ impl FlexItem {
                 block.fragment.compute_inline_direction_margins(containing_length);
                 block.fragment.compute_block_direction_margins(containing_length);
 
+                let surrounding_size = block.fragment.surrounding_intrinsic_inline_size();
+                let min_content_size = block.base.intrinsic_inline_sizes.minimum_inline_size
+                    - surrounding_size
+                    + block.fragment.box_sizing_boundary(direction);
                 let content_size = block.base.intrinsic_inline_sizes.preferred_inline_size
-                    - block.fragment.surrounding_intrinsic_inline_size()
+                    - surrounding_size
                     + block.fragment.box_sizing_boundary(direction);
-                self.base_size = basis.specified_or_default(content_size);
-                self.max_size = specified_or_none(self.style.max_inline_size(), containing_length)
-                    .unwrap_or(MAX_AU);
-                self.min_size = specified(self.style.min_inline_size(), containing_length);
+                let available_size = containing_length
+                    - block.fragment.margin.inline_start_end()
+                    - block.fragment.border_padding.inline_start_end()
+                    + block.fragment.box_sizing_boundary(direction);
+                let size_for_keyword = |keyword| {
+                    model::size_for_keyword(keyword, min_content_size, content_size, available_size)
+                };
+
+                // A sizing keyword in the main size is used like the content size would be for
+                // `flex-basis: auto`.
+                self.base_size = match (self.style.get_position().flex_basis,
+                                        self.style.logical_inline_size().keyword()) {
+                    (LengthOrPercentageOrAutoOrContent::Auto, Some(keyword)) => size_for_keyword(keyword),
+                    _ => basis.specified_or_default(content_size),
+                };
+                self.max_size = match self.style.logical_max_inline_size().keyword() {
+                    Some(keyword) => size_for_keyword(keyword),
+                    None => specified_or_none(self.style.max_inline_size(), containing_length)
+                        .unwrap_or(MAX_AU),
+                };
+                let min_inline_size = self.style.logical_min_inline_size();
+                self.min_size = match min_inline_size.keyword() {
+                    Some(keyword) => size_for_keyword(keyword),
+                    None if min_inline_size.is_auto() && !is_scroll_container(&self.style) => {
+                        let specified_size = MaybeAuto::from_style(self.style.content_inline_size(),
+                                                                   containing_length);
+                        automatic_minimum_size(min_content_size, specified_size, self.max_size)
+                    }
+                    None => specified(self.style.min_inline_size(), containing_length),
+                };
             }
             Direction::Block => {
                 let basis = from_flex_basis(self.style.get_position().flex_basis,
                                             self.style.content_block_size(),
-                                            Some(containing_length));
+                                            containing_length);
+                // The item has been laid out already, so its block size is its content size.
                 let content_size = block.fragment.border_box.size.block
                     - block.fragment.border_padding.block_start_end()
                     + block.fragment.box_sizing_boundary(direction);
+                // Unless it has a size of its own, in which case that is how far its children reach.
+                let children_size = match self.style.content_block_size() {
+                    LengthOrPercentageOrAuto::Auto => content_size,
+                    _ => {
+                        block.base.children.iter()
+                            .map(|kid| flow::base(kid))
+                            .filter(|kid| !kid.flags.contains(IS_ABSOLUTELY_POSITIONED))
+                            .map(|kid| kid.position.start.b + kid.position.size.block)
+                            .max()
+                            .map_or(Au(0), |end| max(end - block.fragment.border_padding.block_start, Au(0)))
+                            + block.fragment.box_sizing_boundary(direction)
+                    }
+                };
                 self.base_size = basis.specified_or_default(content_size);
-                self.max_size = specified_or_none(self.style.max_block_size(), containing_length)
+                // Percentages of an indefinite size behave as `auto`.
+                let max_block_size = self.style.max_block_size();
+                self.max_size = containing_length.and_then(|length| specified_or_none(max_block_size, length))
                     .unwrap_or(MAX_AU);
-                self.min_size = specified(self.style.min_block_size(), containing_length);
+                self.min_size = if self.style.logical_min_block_size().is_auto() &&
+                        !is_scroll_container(&self.style) {
+                    let specified_size = match (self.style.content_block_size(), containing_length) {
+                        (LengthOrPercentageOrAuto::Length(length), _) => MaybeAuto::Specified(length),
+                        (size, Some(length)) => MaybeAuto::from_style(size, length),
+                        (_, None) => MaybeAuto::Auto,
+                    };
+                    automatic_minimum_size(children_size, specified_size, self.max_size)
+                } else {
+                    specified(self.style.min_block_size(), containing_length.unwrap_or(Au(0)))
+                };
             }
         }
     }
@@ -395,14 +529,36 @@ impl FlexFlow {
         }
     }
 
+    /// The gap between adjacent items of a line, which is `column-gap` in a
+    /// row flexbox and `row-gap` in a column one.
+    fn main_gap(&self, container_size: Au) -> Au {
+        let style = self.block_flow.fragment.style();
+        let gap = match self.main_mode {
+            Direction::Inline => style.get_column().column_gap.0,
+            Direction::Block => style.get_position().row_gap.0,
+        };
+        used_gap(gap, container_size)
+    }
+
+    /// The gap between adjacent lines, which is the gap of the other axis.
+    fn cross_gap(&self, container_size: Au) -> Au {
+        let style = self.block_flow.fragment.style();
+        let gap = match self.main_mode {
+            Direction::Inline => style.get_position().row_gap.0,
+            Direction::Block => style.get_column().column_gap.0,
+        };
+        used_gap(gap, container_size)
+    }
+
     /// Returns a line start after the last item that is already in a line.
     /// Note that when the container main size is infinite(i.e. A column flexbox with auto height),
-    /// we do not need to do flex resolving and this can be considered as a fast-path, so the
-    /// 'container_size' param does not need to be 'None'. A line has to contain at least one item;
-    /// (except this) if the container can be multi-line the sum of outer main size of items should
-    /// be less than the container size; a line should be filled by items as much as possible.
+    /// we do not need to do flex resolving and this can be considered as a fast-path: the
+    /// 'container_size' param is `None`, all items go in a single line and it has no free space.
+    /// A line has to contain at least one item; (except this) if the container can be multi-line
+    /// the sum of outer main size of items should be less than the container size; a line should
+    /// be filled by items as much as possible.
     /// After been collected in a line a item should have its main sizes initialized.
-    fn get_flex_line(&mut self, container_size: Au) -> Option<FlexLine> {
+    fn get_flex_line(&mut self, container_size: Option<Au>) -> Option<FlexLine> {
         let start = self.lines.last().map(|line| line.range.end).unwrap_or(0);
         if start == self.items.len() {
             return None;
@@ -410,20 +566,25 @@ impl FlexFlow {
         let mut end = start;
         let mut total_line_size = Au(0);
         let mut margin_count = 0;
+        let main_gap = self.main_gap(container_size.unwrap_or(Au(0)));
 
         let items = &mut self.items[start..];
         for mut item in items {
             item.init_sizes(container_size, self.main_mode);
-            let outer_main_size = item.outer_main_size(self.main_mode);
-            if total_line_size + outer_main_size > container_size && end != start && self.is_wrappable {
-                break;
+            let gap = if end == start { Au(0) } else { main_gap };
+            let outer_main_size = gap + item.outer_main_size(self.main_mode);
+            if let Some(container_size) = container_size {
+                if total_line_size + outer_main_size > container_size && end != start && self.is_wrappable {
+                    break;
+                }
             }
             margin_count += item.auto_margin_count(self.main_mode);
             total_line_size += outer_main_size;
             end += 1;
         }
 
-        let line = FlexLine::new(start..end, container_size - total_line_size, margin_count);
+        let free_space = container_size.map_or(Au(0), |size| size - total_line_size);
+        let line = FlexLine::new(start..end, free_space, margin_count);
         Some(line)
     }
 
@@ -431,22 +592,37 @@ impl FlexFlow {
     // Currently, this is the core of BlockFlow::bubble_inline_sizes() with all float logic
     // stripped out, and max replaced with union_nonbreaking_inline.
     fn inline_mode_bubble_inline_sizes(&mut self) {
-        let fixed_width = match self.block_flow.fragment.style().get_position().width {
+        let fixed_width = match self.block_flow.fragment.style().get_position().width.length_or_auto() {
             LengthOrPercentageOrAuto::Length(_) => true,
             _ => false,
         };
 
         let mut computation = self.block_flow.fragment.compute_intrinsic_inline_sizes();
         if !fixed_width {
+            // Percentage gaps resolve against zero here.
+            let main_gap = self.main_gap(Au(0));
+            let mut is_first_item = true;
             for kid in &mut self.items {
                 let base = flow::mut_base(flow_ref::deref_mut(&mut kid.flow));
                 let is_absolutely_positioned = base.flags.contains(IS_ABSOLUTELY_POSITIONED);
                 if !is_absolutely_positioned {
-                    let flex_item_inline_sizes = IntrinsicISizes {
-                        minimum_inline_size: base.intrinsic_inline_sizes.minimum_inline_size,
-                        preferred_inline_size: base.intrinsic_inline_sizes.preferred_inline_size,
-                    };
-                    computation.union_nonbreaking_inline(&flex_item_inline_sizes);
+                    let gap = if is_first_item { Au(0) } else { main_gap };
+                    if self.is_wrappable {
+                        // A multi-line container can put every item on a line of its own, so
+                        // its min-content size is that of its widest item.
+                        computation.content_intrinsic_sizes.minimum_inline_size =
+                            max(computation.content_intrinsic_sizes.minimum_inline_size,
+                                base.intrinsic_inline_sizes.minimum_inline_size);
+                        computation.content_intrinsic_sizes.preferred_inline_size +=
+                            base.intrinsic_inline_sizes.preferred_inline_size + gap;
+                    } else {
+                        let flex_item_inline_sizes = IntrinsicISizes {
+                            minimum_inline_size: base.intrinsic_inline_sizes.minimum_inline_size + gap,
+                            preferred_inline_size: base.intrinsic_inline_sizes.preferred_inline_size + gap,
+                        };
+                        computation.union_nonbreaking_inline(&flex_item_inline_sizes);
+                    }
+                    is_first_item = false;
                 }
             }
         }
@@ -457,7 +633,7 @@ impl FlexFlow {
     // Currently, this is the core of BlockFlow::bubble_inline_sizes() with all float logic
     // stripped out.
     fn block_mode_bubble_inline_sizes(&mut self) {
-        let fixed_width = match self.block_flow.fragment.style().get_position().width {
+        let fixed_width = match self.block_flow.fragment.style().get_position().width.length_or_auto() {
             LengthOrPercentageOrAuto::Length(_) => true,
             _ => false,
         };
@@ -512,14 +688,21 @@ impl FlexFlow {
                     // The inline-start margin edge of the child flow is at our inline-start content edge,
                     // and its inline-size is our content inline-size.
                     kid_base.position.start.i =
-                        if kid_base.writing_mode.is_bidi_ltr() == containing_block_mode.is_bidi_ltr() {
+                        if kid_base.writing_mode.has_same_inline_direction(containing_block_mode) {
                             inline_start_content_edge
                         } else {
                             // The kid's inline 'start' is at the parent's 'end'
                             inline_end_content_edge
                         };
                 }
-                kid_base.block_container_inline_size = container_inline_size;
+                // Lines of a multi-line container sit side by side, so items of auto inline size
+                // are only as wide as their content instead of filling the container.
+                kid_base.block_container_inline_size =
+                    if self.is_wrappable && kid.style.content_inline_size() == LengthOrPercentageOrAuto::Auto {
+                        min(kid_base.intrinsic_inline_sizes.preferred_inline_size, container_inline_size)
+                    } else {
+                        container_inline_size
+                    };
                 kid_base.block_container_writing_mode = containing_block_mode;
                 kid_base.position.start.i = inline_start_content_edge;
             }
@@ -562,43 +745,23 @@ impl FlexFlow {
                                     .map(|x| max(x - box_border, Au(0)));
         let containing_block_text_align =
             self.block_flow.fragment.style().get_inheritedtext().text_align;
+        let main_gap = self.main_gap(inline_size);
+        let justify = self.block_flow.fragment.style().get_position().justify_content;
 
-        while let Some(mut line) = self.get_flex_line(inline_size) {
+        self.lines.clear();
+        while let Some(mut line) = self.get_flex_line(Some(inline_size)) {
             let items = &mut self.items[line.range.clone()];
             line.flex_resolve(items, false);
             // TODO(stshine): if this flex line contain children that have
             // property visibility:collapse, exclude them and resolve again.
 
-            let item_count = items.len() as i32;
-            let mut cur_i = inline_start_content_edge;
-            let item_interval = if line.free_space >= Au(0) && line.auto_margin_count == 0 {
-                match self.block_flow.fragment.style().get_position().justify_content {
-                    justify_content::T::space_between => {
-                        if item_count == 1 {
-                            Au(0)
-                        } else {
-                            line.free_space / (item_count - 1)
-                        }
-                    }
-                    justify_content::T::space_around => {
-                        line.free_space / item_count
-                    }
-                    _ => Au(0),
-                }
+            // Auto margins take up any free space before `justify-content` sees it.
+            let (line_offset, item_interval) = if line.auto_margin_count > 0 && line.free_space > Au(0) {
+                (Au(0), Au(0))
             } else {
-                Au(0)
+                justify_line(justify, line.free_space, items.len() as i32)
             };
-
-            match self.block_flow.fragment.style().get_position().justify_content {
-                // Overflow equally in both ends of line.
-                justify_content::T::center | justify_content::T::space_around => {
-                    cur_i += (line.free_space - item_interval * (item_count - 1)) / 2;
-                }
-                justify_content::T::flex_end => {
-                    cur_i += line.free_space;
-                }
-                _ => {}
-            }
+            let mut cur_i = inline_start_content_edge + line_offset;
 
             for item in items.iter_mut() {
                 let mut block = flow_ref::deref_mut(&mut item.flow).as_mut_block();
@@ -639,30 +802,145 @@ impl FlexFlow {
                     inline_start_content_edge * 2 + content_inline_size - cur_i  - item_outer_size
                 };
                 block.base.position.size.inline = item_outer_size;
-                cur_i += item_outer_size + item_interval;
+                cur_i += item_outer_size + item_interval + main_gap;
             }
             self.lines.push(line);
         }
     }
 
-    // TODO(zentner): This function should actually flex elements!
-    fn block_mode_assign_block_size<'a>(&mut self, layout_context: &'a LayoutContext<'a>) {
-        let mut cur_b = if !self.main_reverse {
-            self.block_flow.fragment.border_padding.block_start
-        } else {
-            self.block_flow.fragment.border_box.size.block
+    /// Flexes the items of a column container along the block axis. The items have been laid out
+    /// by now, so resizing them does not lay their contents out again.
+    fn block_mode_assign_block_size(&mut self) {
+        let _scope = layout_debug_scope!("flex::block_mode_assign_block_size");
+
+        // Without a definite block size every item goes in one line, at its own size.
+        let container_size = match self.available_main_size {
+            AxisSize::Definite(length) => Some(length),
+            _ => None,
         };
-        for kid in &mut self.items {
-            let base = flow::mut_base(flow_ref::deref_mut(&mut kid.flow));
-            if !self.main_reverse {
-                base.position.start.b = cur_b;
-                cur_b = cur_b + base.position.size.block;
+        let main_gap = self.main_gap(container_size.unwrap_or(Au(0)));
+        let justify = self.block_flow.fragment.style().get_position().justify_content;
+        let block_start_content_edge = self.block_flow.fragment.border_padding.block_start;
+
+        self.lines.clear();
+        let mut content_block_size = Au(0);
+        while let Some(mut line) = self.get_flex_line(container_size) {
+            let items = &mut self.items[line.range.clone()];
+            line.flex_resolve(items, false);
+
+            let (line_offset, item_interval) = if line.auto_margin_count > 0 && line.free_space > Au(0) {
+                (Au(0), Au(0))
             } else {
-                cur_b = cur_b - base.position.size.block;
-                base.position.start.b = cur_b;
+                justify_line(justify, line.free_space, items.len() as i32)
+            };
+            let auto_len = if line.auto_margin_count == 0 || line.free_space <= Au(0) {
+                Au(0)
+            } else {
+                line.free_space / line.auto_margin_count
+            };
+
+            let mut cur_b = block_start_content_edge + line_offset;
+            let mut line_main_size = Au(0);
+            for (index, item) in items.iter_mut().enumerate() {
+                let block = flow_ref::deref_mut(&mut item.flow).as_mut_block();
+                block.mark_as_flex();
+
+                let margin = block.fragment.style().logical_margin();
+                let margin_block_start = match margin.block_start {
+                    LengthOrPercentageOrAuto::Auto => auto_len,
+                    _ => block.fragment.margin.block_start,
+                };
+                let margin_block_end = match margin.block_end {
+                    LengthOrPercentageOrAuto::Auto => auto_len,
+                    _ => block.fragment.margin.block_end,
+                };
+                let item_block_size = item.main_size
+                    - block.fragment.box_sizing_boundary(Direction::Block)
+                    + block.fragment.border_padding.block_start_end();
+                let item_outer_size = margin_block_start + item_block_size + margin_block_end;
+                let gap = if index == 0 { Au(0) } else { main_gap };
+
+                block.fragment.margin.block_start = margin_block_start;
+                block.fragment.margin.block_end = margin_block_end;
+                block.fragment.border_box.size.block = item_block_size;
+                block.base.position.size.block = item_block_size;
+                cur_b += gap;
+                block.base.position.start.b = margin_block_start + cur_b;
+                cur_b += item_outer_size + item_interval;
+                line_main_size += gap + item_outer_size;
+            }
+            content_block_size = max(content_block_size, line_main_size);
+            self.lines.push(line);
+        }
+
+        // Without a definite size the container is as tall as its longest line.
+        if container_size.is_none() {
+            let content_block_size = match self.available_main_size {
+                AxisSize::MinMax(ref constraint) => constraint.clamp(content_block_size),
+                _ => content_block_size,
+            };
+            let total_block_size = content_block_size + self.block_flow.fragment.border_padding.block_start_end();
+            self.block_flow.fragment.border_box.size.block = total_block_size;
+            self.block_flow.base.position.size.block = total_block_size;
+        }
+        let content_block_size = self.block_flow.fragment.border_box.size.block
+            - self.block_flow.fragment.border_padding.block_start_end();
+
+        if self.main_reverse {
+            for item in &mut self.items {
+                let base = flow::mut_base(flow_ref::deref_mut(&mut item.flow));
+                base.position.start.b = block_start_content_edge * 2 + content_block_size
+                    - base.position.start.b - base.position.size.block;
             }
         }
-        self.block_flow.assign_block_size(layout_context)
+
+        self.block_mode_place_lines();
+    }
+
+    /// Places the lines of a multi-line column container side by side along the inline axis,
+    /// following `align-content`. Items start out at the inline-start content edge.
+    fn block_mode_place_lines(&mut self) {
+        if !self.is_wrappable {
+            return
+        }
+
+        let content_inline_size = self.block_flow.fragment.border_box.size.inline
+            - self.block_flow.fragment.border_padding.inline_start_end();
+        let cross_gap = self.cross_gap(content_inline_size);
+        let line_count = self.lines.len() as i32;
+
+        let mut total_cross_size = Au(0);
+        for line in self.lines.iter_mut() {
+            for item in &self.items[line.range.clone()] {
+                let ref fragment = item.flow.as_block().fragment;
+                line.cross_size = max(line.cross_size,
+                                      fragment.border_box.size.inline + fragment.margin.inline_start_end());
+            }
+            total_cross_size += line.cross_size;
+        }
+        total_cross_size += cross_gap * (line_count - 1);
+
+        let align = self.block_flow.fragment.style().get_position().align_content;
+        let free_space = content_inline_size - total_cross_size;
+        if align == align_content::T::stretch && free_space > Au(0) {
+            for line in self.lines.iter_mut() {
+                line.cross_size += free_space / line_count;
+            }
+        }
+        let (mut cur_i, line_interval) = align_lines(align, free_space, line_count);
+
+        for line in &self.lines {
+            let line_start = if !self.cross_reverse {
+                cur_i
+            } else {
+                content_inline_size - cur_i - line.cross_size
+            };
+            for item in &mut self.items[line.range.clone()] {
+                let base = flow::mut_base(flow_ref::deref_mut(&mut item.flow));
+                base.position.start.i += line_start;
+            }
+            cur_i += line.cross_size + cross_gap + line_interval;
+        }
     }
 
     fn inline_mode_assign_block_size<'a>(&mut self, layout_context: &'a LayoutContext<'a>) {
@@ -692,38 +970,31 @@ impl FlexFlow {
                                     .explicit_block_size(parent_container_size)
                                     .map(|x| max(x - box_border, Au(0)));
 
+        let cross_gap = self.cross_gap(explicit_content_size.unwrap_or(Au(0)));
+        if line_count > 1 {
+            total_cross_size += cross_gap * (line_count - 1);
+        }
+
         if let Some(container_block_size) = explicit_content_size {
             let free_space = container_block_size - total_cross_size;
             total_cross_size = container_block_size;
 
-            if line_align == align_content::T::stretch && free_space > Au(0) {
+            if !self.is_wrappable {
+                // The line of a single-line container is always as big as the container, and
+                // `align-content` does not apply.
+                // https://drafts.csswg.org/css-flexbox/#algo-single-line
                 for line in self.lines.iter_mut() {
-                    line.cross_size += free_space / line_count;
+                    line.cross_size = container_block_size;
                 }
-            }
-
-            line_interval = match line_align {
-                align_content::T::space_between => {
-                    if line_count == 1 {
-                        Au(0)
-                    } else {
-                        free_space / (line_count - 1)
+            } else {
+                if line_align == align_content::T::stretch && free_space > Au(0) {
+                    for line in self.lines.iter_mut() {
+                        line.cross_size += free_space / line_count;
                     }
                 }
-                align_content::T::space_around => {
-                    free_space / line_count
-                }
-                _ => Au(0),
-            };
-
-            match line_align {
-                align_content::T::center | align_content::T::space_around => {
-                    cur_b += (free_space - line_interval * (line_count - 1)) / 2;
-                }
-                align_content::T::flex_end => {
-                    cur_b += free_space;
-                }
-                _ => {}
+                let (lines_offset, interval) = align_lines(line_align, free_space, line_count);
+                cur_b += lines_offset;
+                line_interval = interval;
             }
         }
 
@@ -787,7 +1058,7 @@ impl FlexFlow {
                         };
                 }
             }
-            cur_b += line_interval + line.cross_size;
+            cur_b += line_interval + line.cross_size + cross_gap;
         }
         let total_block_size = total_cross_size + self.block_flow.fragment.border_padding.block_start_end();
         self.block_flow.fragment.border_box.size.block = total_block_size;
@@ -855,9 +1126,9 @@ impl Flow for FlexFlow {
         let (available_block_size, available_inline_size) = {
             let style = &self.block_flow.fragment.style;
             let (specified_block_size, specified_inline_size) = if style.writing_mode.is_vertical() {
-                (style.get_position().width, style.get_position().height)
+                (style.get_position().width.length_or_auto(), style.get_position().height.length_or_auto())
             } else {
-                (style.get_position().height, style.get_position().width)
+                (style.get_position().height.length_or_auto(), style.get_position().width.length_or_auto())
             };
 
             let available_inline_size = AxisSize::new(specified_inline_size,
@@ -916,7 +1187,7 @@ impl Flow for FlexFlow {
             Direction::Inline =>
                 self.inline_mode_assign_block_size(layout_context),
             Direction::Block  =>
-                self.block_mode_assign_block_size(layout_context)
+                self.block_mode_assign_block_size()
         }
     }
 
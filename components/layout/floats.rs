@@ -9,8 +9,9 @@ use persistent_list::PersistentList;
 use std::cmp::{max, min};
 use std::fmt;
 use std::i32;
+use std::sync::Arc;
 use style::computed_values::float;
-use style::logical_geometry::{LogicalRect, LogicalSize, WritingMode};
+use style::logical_geometry::{LogicalPoint, LogicalRect, LogicalSize, WritingMode};
 use style::values::computed::LengthOrPercentageOrAuto;
 
 /// The kind of float: left or right.
@@ -38,18 +39,106 @@ pub enum ClearType {
     Both,
 }
 
+/// The float area of a float with `shape-outside`, in the writing mode of the `Floats` it is
+/// added to and relative to the start corner of its margin box. Any `shape-margin` is already
+/// included.
+///
+/// https://drafts.csswg.org/css-shapes/#shapes-from-box-values
+#[derive(Clone, Debug)]
+pub enum FloatShape {
+    /// An ellipse, given by its center and its radii. Circles are ellipses with equal radii.
+    Ellipse(LogicalPoint<Au>, LogicalSize<Au>),
+    /// A rectangle.
+    ///
+    /// FIXME: The rounded corners of `inset()` and of reference boxes are ignored.
+    Inset(LogicalRect<Au>),
+    /// A polygon, given by its vertices, with the shape margin around it.
+    Polygon(Vec<LogicalPoint<Au>>, Au),
+}
+
+impl FloatShape {
+    /// Returns the inline-start and inline-end edges of the part of this shape between
+    /// `block_start` and `block_end`, or `None` if the shape does not reach into that range.
+    fn inline_extent(&self, block_start: Au, block_end: Au) -> Option<(Au, Au)> {
+        match *self {
+            FloatShape::Ellipse(center, radii) => {
+                if radii.inline <= Au(0) || radii.block <= Au(0) ||
+                        center.b + radii.block <= block_start ||
+                        center.b - radii.block >= block_end {
+                    return None
+                }
+
+                // The ellipse is widest at the point of the range closest to its center.
+                let b = min(max(center.b, block_start), block_end) - center.b;
+                let ratio = b.to_f64_px() / radii.block.to_f64_px();
+                let half_inline_size =
+                    Au::from_f64_px(radii.inline.to_f64_px() * (1.0 - ratio * ratio).max(0.0).sqrt());
+                Some((center.i - half_inline_size, center.i + half_inline_size))
+            }
+            FloatShape::Inset(rect) => {
+                if rect.size.inline < Au(0) || rect.size.block <= Au(0) ||
+                        rect.start.b + rect.size.block <= block_start ||
+                        rect.start.b >= block_end {
+                    return None
+                }
+                Some((rect.start.i, rect.start.i + rect.size.inline))
+            }
+            FloatShape::Polygon(ref vertices, shape_margin) => {
+                // Approximate the shape margin by growing the range and then the extent by it.
+                let (block_start, block_end) = (block_start - shape_margin,
+                                                block_end + shape_margin);
+                let mut extent: Option<(Au, Au)> = None;
+                for (index, from) in vertices.iter().enumerate() {
+                    let to = &vertices[(index + 1) % vertices.len()];
+                    let (top, bottom) = if from.b <= to.b { (from, to) } else { (to, from) };
+                    if bottom.b < block_start || top.b > block_end {
+                        continue
+                    }
+
+                    // Clip the edge to the range.
+                    let inline_at = |b: Au| {
+                        if bottom.b == top.b {
+                            return top.i
+                        }
+                        let t = (b - top.b).to_f64_px() / (bottom.b - top.b).to_f64_px();
+                        top.i + Au::from_f64_px((bottom.i - top.i).to_f64_px() * t)
+                    };
+                    for &i in &[inline_at(max(top.b, block_start)), inline_at(min(bottom.b, block_end))] {
+                        extent = Some(match extent {
+                            None => (i, i),
+                            Some((start, end)) => (min(start, i), max(end, i)),
+                        })
+                    }
+                }
+                extent.map(|(start, end)| (start - shape_margin, end + shape_margin))
+            }
+        }
+    }
+}
+
+/// The part of a float that content has to stay clear of.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum FloatArea {
+    /// The margin box, which other floats and block formatting contexts avoid.
+    MarginBox,
+    /// The float area given by `shape-outside`, which line boxes avoid.
+    Shape,
+}
+
 /// Information about a single float.
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 struct Float {
     /// The boundaries of this float.
     bounds: LogicalRect<Au>,
     /// The kind of float: left or right.
     kind: FloatKind,
+    /// The float area given by `shape-outside`, if any.
+    shape: Option<Arc<FloatShape>>,
 }
 
 impl fmt::Debug for Float {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "bounds={:?} kind={:?}", self.bounds, self.kind)
+        write!(f, "bounds={:?} kind={:?} shape={:?}", self.bounds, self.kind, self.shape)
     }
 }
 
@@ -96,7 +185,9 @@ pub struct PlacementInfo {
     /// The maximum inline-end position of the float, generally determined by the containing block.
     pub max_inline_size: Au,
     /// The kind of float.
-    pub kind: FloatKind
+    pub kind: FloatKind,
+    /// The float area of the float given by `shape-outside`, if any.
+    pub shape: Option<Arc<FloatShape>>,
 }
 
 impl fmt::Debug for PlacementInfo {
@@ -165,6 +256,15 @@ impl Floats {
     /// inline-size.)
     pub fn available_rect(&self, block_start: Au, block_size: Au, max_x: Au)
                           -> Option<LogicalRect<Au>> {
+        self.available_rect_in_area(block_start, block_size, max_x, FloatArea::MarginBox)
+            .map(|(rect, _)| rect)
+    }
+
+    /// Like `available_rect`, but avoids only the given area of each float. Also returns whether
+    /// the rectangle was narrowed by a float shape, in which case it is only valid for the given
+    /// block-size.
+    fn available_rect_in_area(&self, block_start: Au, block_size: Au, max_x: Au, area: FloatArea)
+                              -> Option<(LogicalRect<Au>, bool)> {
         let list = &self.list;
         let block_start = block_start - self.offset.block;
 
@@ -178,6 +278,7 @@ impl Floats {
         let mut min_inline_end = max_x - self.offset.inline;
         let mut r_block_start = None;
         let mut r_block_end = None;
+        let mut narrowed_by_shape = false;
 
         // Find the float collisions for the given range in the block direction.
         for float in list.floats.iter() {
@@ -186,26 +287,52 @@ impl Floats {
             let float_size = float.bounds.size;
 
             debug!("float_pos: {:?}, float_size: {:?}", float_pos, float_size);
+            if float_pos.b + float_size.block <= block_start ||
+                    float_pos.b >= block_start + block_size {
+                continue
+            }
+
+            // Line boxes only avoid the part of a float shape next to them, which is clipped to
+            // the margin box.
+            let (float_inline_start, float_inline_end, float_block_start, float_block_end) =
+                match (area, &float.shape) {
+                    (FloatArea::Shape, &Some(ref shape)) => {
+                        let band_end = block_start + max(block_size, Au::from_px(1));
+                        match shape.inline_extent(block_start - float_pos.b, band_end - float_pos.b) {
+                            None => continue,
+                            Some((start, end)) => {
+                                narrowed_by_shape = true;
+                                (float_pos.i + max(start, Au(0)),
+                                 float_pos.i + min(end, float_size.inline),
+                                 max(block_start, float_pos.b),
+                                 min(band_end, float_pos.b + float_size.block))
+                            }
+                        }
+                    }
+                    _ => {
+                        (float_pos.i,
+                         float_pos.i + float_size.inline,
+                         float_pos.b,
+                         float_pos.b + float_size.block)
+                    }
+                };
+
             match float.kind {
-                FloatKind::Left if float_pos.i + float_size.inline > max_inline_start &&
-                        float_pos.b + float_size.block > block_start &&
-                        float_pos.b < block_start + block_size => {
-                    max_inline_start = float_pos.i + float_size.inline;
+                FloatKind::Left if float_inline_end > max_inline_start => {
+                    max_inline_start = float_inline_end;
 
-                    l_block_start = Some(float_pos.b);
-                    l_block_end = Some(float_pos.b + float_size.block);
+                    l_block_start = Some(float_block_start);
+                    l_block_end = Some(float_block_end);
 
                     debug!("available_rect: collision with inline_start float: new \
                             max_inline_start is {:?}",
                            max_inline_start);
                 }
-                FloatKind::Right if float_pos.i < min_inline_end &&
-                       float_pos.b + float_size.block > block_start &&
-                       float_pos.b < block_start + block_size => {
-                    min_inline_end = float_pos.i;
+                FloatKind::Right if float_inline_start < min_inline_end => {
+                    min_inline_end = float_inline_start;
 
-                    r_block_start = Some(float_pos.b);
-                    r_block_end = Some(float_pos.b + float_size.block);
+                    r_block_start = Some(float_block_start);
+                    r_block_end = Some(float_block_end);
                     debug!("available_rect: collision with inline_end float: new min_inline_end \
                             is {:?}",
                             min_inline_end);
@@ -245,11 +372,12 @@ impl Floats {
 
         assert!(block_start <= block_end, "Float position error");
 
-        Some(LogicalRect::new(self.writing_mode,
-                              max_inline_start + self.offset.inline,
-                              block_start + self.offset.block,
-                              min_inline_end - max_inline_start,
-                              block_end - block_start))
+        Some((LogicalRect::new(self.writing_mode,
+                               max_inline_start + self.offset.inline,
+                               block_start + self.offset.block,
+                               min_inline_end - max_inline_start,
+                               block_end - block_start),
+              narrowed_by_shape))
     }
 
     /// Adds a new float to the list.
@@ -261,7 +389,8 @@ impl Floats {
                 Some(max_block_start) => max(info.ceiling, max_block_start + self.offset.block),
             },
             max_inline_size: info.max_inline_size,
-            kind: info.kind
+            kind: info.kind,
+            shape: None,
         };
 
         debug!("add_float: added float with info {:?}", new_info);
@@ -272,20 +401,26 @@ impl Floats {
                 self.place_between_floats(&new_info).start - self.offset,
                 info.size,
             ),
-            kind: info.kind
+            kind: info.kind,
+            shape: info.shape.clone(),
         };
 
+        let new_float_block_start = new_float.bounds.start.b;
         self.list.floats = self.list.floats.prepend_elem(new_float);
         self.list.max_block_start = match self.list.max_block_start {
-            None => Some(new_float.bounds.start.b),
-            Some(max_block_start) => Some(max(max_block_start, new_float.bounds.start.b)),
+            None => Some(new_float_block_start),
+            Some(max_block_start) => Some(max(max_block_start, new_float_block_start)),
         }
     }
 
     /// Given the three sides of the bounding rectangle in the block-start direction, finds the
     /// largest block-size that will result in the rectangle not colliding with any floats. Returns
-    /// `None` if that block-size is infinite.
-    fn max_block_size_for_bounds(&self, inline_start: Au, block_start: Au, inline_size: Au)
+    /// `None` if that block-size is infinite. `area` is the part of each float to avoid.
+    fn max_block_size_for_bounds(&self,
+                                 inline_start: Au,
+                                 block_start: Au,
+                                 inline_size: Au,
+                                 area: FloatArea)
                                  -> Option<Au> {
         let list = &self.list;
 
@@ -294,6 +429,10 @@ impl Floats {
         let mut max_block_size = None;
 
         for float in list.floats.iter() {
+            // Float shapes are checked one band at a time by `available_rect_in_area` instead.
+            if area == FloatArea::Shape && float.shape.is_some() {
+                continue
+            }
             if float.bounds.start.b + float.bounds.size.block > block_start &&
                    float.bounds.start.i + float.bounds.size.inline > inline_start &&
                    float.bounds.start.i < inline_start + inline_size {
@@ -308,6 +447,17 @@ impl Floats {
     /// Given placement information, finds the closest place a fragment can be positioned without
     /// colliding with any floats.
     pub fn place_between_floats(&self, info: &PlacementInfo) -> LogicalRect<Au> {
+        self.place_between_floats_in_area(info, FloatArea::MarginBox)
+    }
+
+    /// Like `place_between_floats`, but for a line box, which only avoids the float area given by
+    /// `shape-outside` of floats that have one.
+    pub fn place_line_between_floats(&self, info: &PlacementInfo) -> LogicalRect<Au> {
+        self.place_between_floats_in_area(info, FloatArea::Shape)
+    }
+
+    fn place_between_floats_in_area(&self, info: &PlacementInfo, area: FloatArea)
+                                    -> LogicalRect<Au> {
         debug!("place_between_floats: Placing object with {:?}", info.size);
 
         // If no floats, use this fast path.
@@ -335,9 +485,10 @@ impl Floats {
         // Can't go any higher than previous floats or previous elements in the document.
         let mut float_b = info.ceiling;
         loop {
-            let maybe_location = self.available_rect(float_b,
-                                                     info.size.block,
-                                                     info.max_inline_size);
+            let maybe_location = self.available_rect_in_area(float_b,
+                                                             info.size.block,
+                                                             info.max_inline_size,
+                                                             area);
             debug!("place_float: got available rect: {:?} for block-pos: {:?}",
                    maybe_location,
                    float_b);
@@ -364,7 +515,7 @@ impl Floats {
                         }
                     }
                 }
-                Some(rect) => {
+                Some((rect, narrowed_by_shape)) => {
                     assert!(rect.start.b + rect.size.block != float_b,
                             "Non-terminating float placement");
 
@@ -372,8 +523,15 @@ impl Floats {
                     if rect.size.inline >= info.size.inline {
                         let block_size = self.max_block_size_for_bounds(rect.start.i,
                                                                         rect.start.b,
-                                                                        rect.size.inline);
-                        let block_size = block_size.unwrap_or(Au(i32::MAX));
+                                                                        rect.size.inline,
+                                                                        area);
+                        let mut block_size = block_size.unwrap_or(Au(i32::MAX));
+
+                        // The edge of a float shape moves along the block axis, so the room we
+                        // found next to it is only known to be free for the size we asked for.
+                        if narrowed_by_shape {
+                            block_size = min(block_size, rect.start.b + rect.size.block - float_b)
+                        }
                         return match info.kind {
                             FloatKind::Left => {
                                 LogicalRect::new(
@@ -39,8 +39,10 @@ use gfx_traits::{LayerId, LayerType, StackingContextId};
 use gfx_traits::print_tree::PrintTree;
 use inline::InlineFlow;
 use model::{CollapsibleMargins, IntrinsicISizes, MarginCollapseInfo};
+use math::MathFlow;
 use multicol::MulticolFlow;
 use parallel::FlowParallelInfo;
+use ruby::RubyFlow;
 use rustc_serialize::{Encodable, Encoder};
 use script_layout_interface::restyle_damage::{RECONSTRUCT_FLOW, REFLOW, REFLOW_OUT_OF_FLOW};
 use script_layout_interface::restyle_damage::{REPAINT, REPOSITION, RestyleDamage};
@@ -165,6 +167,16 @@ pub trait Flow: fmt::Debug + Sync + Send + 'static {
         panic!("called as_mut_multicol() on a non-multicol flow")
     }
 
+    /// If this is a math flow, returns the underlying object. Fails otherwise.
+    fn as_math(&self) -> &MathFlow {
+        panic!("called as_math() on a non-math flow")
+    }
+
+    /// If this is a ruby flow, returns the underlying object. Fails otherwise.
+    fn as_ruby(&self) -> &RubyFlow {
+        panic!("called as_ruby() on a non-ruby flow")
+    }
+
     /// If this is a table cell flow, returns the underlying object. Fails otherwise.
     fn as_table_cell(&self) -> &TableCellFlow {
         panic!("called as_table_cell() on a non-tablecell flow")
@@ -271,11 +283,18 @@ pub trait Flow: fmt::Debug + Sync + Send + 'static {
             FlowClass::Block |
             FlowClass::TableCaption |
             FlowClass::TableCell => {
-                // FIXME(#2795): Get the real container size.
-                let container_size = Size2D::zero();
-
-                let overflow_x = self.as_block().fragment.style.get_box().overflow_x;
-                let overflow_y = self.as_block().fragment.style.get_box().overflow_y;
+                // Our children are positioned relative to our border box.
+                let container_size = base(self).position.size.to_physical(base(self).writing_mode);
+
+                // Paint containment clips the overflow as `overflow: clip` does.
+                let (overflow_x, overflow_y) = {
+                    let style = &self.as_block().fragment.style;
+                    if style.has_paint_containment() {
+                        (overflow_x::T::clip, overflow_x::T::clip)
+                    } else {
+                        (style.get_box().overflow_x, style.get_box().overflow_y.0)
+                    }
+                };
 
                 for kid in mut_base(self).children.iter_mut() {
                     let mut kid_overflow = base(kid).overflow;
@@ -283,11 +302,11 @@ pub trait Flow: fmt::Debug + Sync + Send + 'static {
                                                                       container_size);
                     kid_overflow.translate(&kid_position.origin);
 
-                    // If the overflow for this flow is hidden on a given axis, just
+                    // If the overflow for this flow is clipped on a given axis, just
                     // put the existing overflow in the kid rect, so that the union
                     // has no effect on this axis.
                     match overflow_x {
-                        overflow_x::T::hidden => {
+                        overflow_x::T::hidden | overflow_x::T::clip => {
                             kid_overflow.paint.origin.x = overflow.paint.origin.x;
                             kid_overflow.paint.size.width = overflow.paint.size.width;
                             kid_overflow.scroll.origin.x = overflow.scroll.origin.x;
@@ -298,8 +317,8 @@ pub trait Flow: fmt::Debug + Sync + Send + 'static {
                         overflow_x::T::visible => {}
                     }
 
-                    match overflow_y.0 {
-                        overflow_x::T::hidden => {
+                    match overflow_y {
+                        overflow_x::T::hidden | overflow_x::T::clip => {
                             kid_overflow.paint.origin.y = overflow.paint.origin.y;
                             kid_overflow.paint.size.height = overflow.paint.size.height;
                             kid_overflow.scroll.origin.y = overflow.scroll.origin.y;
@@ -374,11 +393,21 @@ pub trait Flow: fmt::Debug + Sync + Send + 'static {
 
     fn contains_positioned_fragments(&self) -> bool {
         self.contains_relatively_positioned_fragments() ||
-            base(self).flags.contains(IS_ABSOLUTELY_POSITIONED)
+            base(self).flags.intersects(IS_ABSOLUTELY_POSITIONED | IS_CONTAINED)
     }
 
+    /// Returns true if this flow is positioned but stays in flow, which is the case for both
+    /// `position: relative` and `position: sticky`.
     fn contains_relatively_positioned_fragments(&self) -> bool {
-        self.positioning() == position::T::relative
+        match self.positioning() {
+            position::T::relative | position::T::sticky => true,
+            _ => false,
+        }
+    }
+
+    /// Return true if this flow has position 'sticky'.
+    fn is_sticky(&self) -> bool {
+        self.positioning() == position::T::sticky
     }
 
     /// Returns true if this is an absolute containing block.
@@ -583,6 +612,9 @@ pub enum FlowClass {
     Multicol,
     MulticolColumn,
     Flex,
+    Math,
+    Ruby,
+    Paged,
 }
 
 impl FlowClass {
@@ -590,7 +622,8 @@ impl FlowClass {
         match self {
             FlowClass::Block | FlowClass::ListItem | FlowClass::Table | FlowClass::TableRowGroup |
             FlowClass::TableRow | FlowClass::TableCaption | FlowClass::TableCell |
-            FlowClass::TableWrapper | FlowClass::Flex => true,
+            FlowClass::TableWrapper | FlowClass::Flex | FlowClass::Math |
+            FlowClass::Ruby => true,
             _ => false,
         }
     }
@@ -681,6 +714,15 @@ bitflags! {
 
         /// Whether this flow contains any text and/or replaced fragments.
         const CONTAINS_TEXT_OR_REPLACED_FRAGMENTS = 0b0001_0000_0000_0000_0000_0000,
+
+        #[doc = "Whether this flow established a stacking context, real or pseudo, the last time \
+                 stacking contexts were collected, or is painted as if it did. The display list \
+                 of this flow and its descendants is built on its own when layout is parallel."]
+        const ESTABLISHES_STACKING_CONTEXT = 0b0010_0000_0000_0000_0000_0000,
+
+        #[doc = "Whether this flow has layout or paint containment, which makes it the containing \
+                 block of its absolutely-positioned descendants."]
+        const IS_CONTAINED = 0b0100_0000_0000_0000_0000_0000,
     }
 }
 
@@ -845,12 +887,17 @@ pub struct LateAbsolutePositionInfo {
     /// context. If the absolute containing block establishes the stacking context for this flow,
     /// and this flow is not itself absolutely-positioned, then this is (0, 0).
     pub stacking_relative_position_of_absolute_containing_block: Point2D<Au>,
+
+    /// The content box of the containing block relative to the nearest ancestor stacking
+    /// context. Sticky positioned flows never leave this rectangle.
+    pub stacking_relative_content_box_of_containing_block: Rect<Au>,
 }
 
 impl LateAbsolutePositionInfo {
     pub fn new() -> LateAbsolutePositionInfo {
         LateAbsolutePositionInfo {
             stacking_relative_position_of_absolute_containing_block: Point2D::zero(),
+            stacking_relative_content_box_of_containing_block: Rect::zero(),
         }
     }
 }
@@ -859,6 +906,8 @@ impl LateAbsolutePositionInfo {
 pub struct FragmentationContext {
     pub available_block_size: Au,
     pub this_fragment_is_empty: bool,
+    /// Whether the fragmentainers are pages rather than columns.
+    pub is_paged: bool,
 }
 
 /// Data common to all flows.
@@ -1096,6 +1145,10 @@ impl BaseFlow {
                         !style.get_counters().counter_increment.0.is_empty() {
                     flags.insert(AFFECTS_COUNTERS)
                 }
+
+                if style.has_layout_containment() || style.has_paint_containment() {
+                    flags.insert(IS_CONTAINED)
+                }
             }
             None => flags.insert(BLOCK_POSITION_IS_STATIC | INLINE_POSITION_IS_STATIC),
         }
@@ -1151,6 +1204,15 @@ impl BaseFlow {
         self.children.iter_mut()
     }
 
+    /// The physical size of the containing block, for converting positions relative to it from
+    /// logical to physical coordinates. Its block size is only known if it was specified.
+    pub fn block_container_physical_size(&self) -> Size2D<Au> {
+        LogicalSize::new(self.block_container_writing_mode,
+                         self.block_container_inline_size,
+                         self.block_container_explicit_block_size.unwrap_or(Au(0)))
+            .to_physical(self.block_container_writing_mode)
+    }
+
     pub fn debug_id(&self) -> usize {
         let p = self as *const _;
         p as usize
@@ -1402,6 +1464,16 @@ impl<'a> ImmutableFlowUtils for &'a Flow {
     }
 
     fn baseline_offset_of_last_line_box_in_flow(self) -> Option<Au> {
+        if self.class() == FlowClass::Math {
+            if let Some(baseline_offset) = self.as_math().baseline_offset {
+                return Some(baseline_offset)
+            }
+        }
+        if self.class() == FlowClass::Ruby {
+            if let Some(baseline_offset) = self.as_ruby().baseline_offset {
+                return Some(baseline_offset)
+            }
+        }
         for kid in base(self).children.iter().rev() {
             if kid.is_inline_flow() {
                 if let Some(baseline_offset) = kid.as_inline().baseline_offset_of_last_line() {
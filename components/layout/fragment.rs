@@ -29,7 +29,7 @@ use net_traits::image::base::{Image, ImageMetadata};
 use net_traits::image_cache_thread::{ImageOrMetadataAvailable, UsePlaceholder};
 use range::*;
 use rustc_serialize::{Encodable, Encoder};
-use script_layout_interface::HTMLCanvasData;
+use script_layout_interface::{CueSettings, HTMLCanvasData, LayoutNodeType};
 use script_layout_interface::restyle_damage::{RECONSTRUCT_FLOW, RestyleDamage};
 use script_layout_interface::wrapper_traits::{PseudoElementType, ThreadSafeLayoutElement, ThreadSafeLayoutNode};
 use std::borrow::ToOwned;
@@ -38,9 +38,11 @@ use std::collections::LinkedList;
 use std::fmt;
 use std::sync::{Arc, Mutex};
 use style::arc_ptr_eq;
-use style::computed_values::{border_collapse, box_sizing, clear, color, display, mix_blend_mode};
-use style::computed_values::{overflow_wrap, overflow_x, position, text_decoration};
-use style::computed_values::{transform_style, vertical_align, white_space, word_break, z_index};
+use style::computed_values::{border_collapse, box_sizing, clear, color, display, hyphenate_character};
+use style::computed_values::{hyphens, isolation};
+use style::computed_values::{mix_blend_mode, overflow_wrap, overflow_x, position};
+use style::computed_values::{text_decoration_line, transform_style, vertical_align, white_space};
+use style::computed_values::{word_break, z_index};
 use style::computed_values::content::ContentItem;
 use style::context::SharedStyleContext;
 use style::dom::TRestyleDamage;
@@ -315,12 +317,23 @@ impl InlineAbsoluteFragmentInfo {
     }
 }
 
+/// The shaped lines of a cue shown over a video, and where it is shown.
+#[derive(Clone)]
+pub struct CueLines {
+    pub runs: Vec<Arc<TextRun>>,
+    pub settings: CueSettings,
+}
+
 #[derive(Clone)]
 pub struct CanvasFragmentInfo {
     pub replaced_image_fragment_info: ReplacedImageFragmentInfo,
     pub ipc_renderer: Option<Arc<Mutex<IpcSender<CanvasMsg>>>>,
     pub dom_width: Au,
     pub dom_height: Au,
+    /// The cues shown over a video.
+    pub cues: Vec<CueLines>,
+    /// The style of the `::cue` of the video, if it shows cues.
+    pub cue_style: Option<Arc<ServoComputedValues>>,
 }
 
 impl CanvasFragmentInfo {
@@ -334,6 +347,8 @@ impl CanvasFragmentInfo {
                               .map(|renderer| Arc::new(Mutex::new(renderer))),
             dom_width: Au::from_px(data.width as i32),
             dom_height: Au::from_px(data.height as i32),
+            cues: vec![],
+            cue_style: None,
         }
     }
 
@@ -364,6 +379,8 @@ pub struct ImageFragmentInfo {
     pub replaced_image_fragment_info: ReplacedImageFragmentInfo,
     pub image: Option<Arc<Image>>,
     pub metadata: Option<ImageMetadata>,
+    /// The pixel density of the image, which its natural size is divided by.
+    pub density: f64,
 }
 
 impl ImageFragmentInfo {
@@ -371,7 +388,7 @@ impl ImageFragmentInfo {
     ///
     /// FIXME(pcwalton): The fact that image fragments store the cache in the fragment makes little
     /// sense to me.
-    pub fn new<N: ThreadSafeLayoutNode>(node: &N, url: Option<Url>,
+    pub fn new<N: ThreadSafeLayoutNode>(node: &N, url: Option<Url>, density: f64,
                                         shared_layout_context: &SharedLayoutContext)
                                         -> ImageFragmentInfo {
         let image_or_metadata = url.and_then(|url| {
@@ -394,6 +411,16 @@ impl ImageFragmentInfo {
             replaced_image_fragment_info: ReplacedImageFragmentInfo::new(node, &shared_layout_context.style_context),
             image: image,
             metadata: metadata,
+            density: density,
+        }
+    }
+
+    /// Returns the physical size of the image at its density, or zero if it hasn't loaded.
+    pub fn image_natural_size(&self) -> Size2D<Au> {
+        match self.metadata {
+            Some(ref metadata) => Size2D::new(Au::from_f64_px(metadata.width as f64 / self.density),
+                                              Au::from_f64_px(metadata.height as f64 / self.density)),
+            None => Size2D::zero(),
         }
     }
 
@@ -401,11 +428,11 @@ impl ImageFragmentInfo {
     pub fn image_inline_size(&mut self) -> Au {
         match self.metadata {
             Some(ref metadata) => {
-                Au::from_px(if self.replaced_image_fragment_info.writing_mode_is_vertical {
+                Au::from_f64_px(if self.replaced_image_fragment_info.writing_mode_is_vertical {
                     metadata.height
                 } else {
                     metadata.width
-                } as i32)
+                } as f64 / self.density)
             }
             None => Au(0)
         }
@@ -415,11 +442,11 @@ impl ImageFragmentInfo {
     pub fn image_block_size(&mut self) -> Au {
         match self.metadata {
             Some(ref metadata) => {
-                Au::from_px(if self.replaced_image_fragment_info.writing_mode_is_vertical {
+                Au::from_f64_px(if self.replaced_image_fragment_info.writing_mode_is_vertical {
                     metadata.width
                 } else {
                     metadata.height
-                } as i32)
+                } as f64 / self.density)
             }
             None => Au(0)
         }
@@ -538,7 +565,7 @@ impl ReplacedImageFragmentInfo {
             }
             (LengthOrPercentageOrAuto::Percentage(_), None) => MaybeAuto::Auto,
             (LengthOrPercentageOrAuto::Calc(calc), Some(container_size)) => {
-                MaybeAuto::Specified(calc.length() + container_size.scale_by(calc.percentage()))
+                MaybeAuto::Specified(calc.to_used_value(container_size))
             }
             (LengthOrPercentageOrAuto::Calc(_), None) => MaybeAuto::Auto,
             (LengthOrPercentageOrAuto::Auto, _) => MaybeAuto::Auto,
@@ -569,24 +596,30 @@ impl ReplacedImageFragmentInfo {
             MaybeAuto::Auto => {
                 let intrinsic_width = fragment_inline_size;
                 let intrinsic_height = fragment_block_size;
-                if intrinsic_height == Au(0) {
-                    intrinsic_width
+                let natural_ratio = if intrinsic_height == Au(0) {
+                    None
                 } else {
-                    let ratio = intrinsic_width.to_f32_px() /
-                                intrinsic_height.to_f32_px();
-
-                    let specified_height = ReplacedImageFragmentInfo::style_length(
-                        style_block_size,
-                        container_block_size);
-                    let specified_height = match specified_height {
-                        MaybeAuto::Auto => intrinsic_height,
-                        MaybeAuto::Specified(h) => h,
-                    };
-                    let specified_height = clamp_size(specified_height,
-                                                      style_min_block_size,
-                                                      style_max_block_size,
-                                                      Au(0));
-                    Au::from_f32_px(specified_height.to_f32_px() * ratio)
+                    Some(intrinsic_width.to_f32_px() / intrinsic_height.to_f32_px())
+                };
+                match model::aspect_ratio(style, natural_ratio) {
+                    None => intrinsic_width,
+                    Some(ratio) => {
+                        let specified_height = ReplacedImageFragmentInfo::style_length(
+                            style_block_size,
+                            container_block_size);
+                        // Without a specified height, an `aspect-ratio` that isn't the natural
+                        // one keeps the natural width.
+                        let specified_height = match specified_height {
+                            MaybeAuto::Auto if Some(ratio) == natural_ratio => intrinsic_height,
+                            MaybeAuto::Auto => Au::from_f32_px(intrinsic_width.to_f32_px() / ratio),
+                            MaybeAuto::Specified(h) => h,
+                        };
+                        let specified_height = clamp_size(specified_height,
+                                                          style_min_block_size,
+                                                          style_max_block_size,
+                                                          Au(0));
+                        Au::from_f32_px(specified_height.to_f32_px() * ratio)
+                    }
                 }
             },
             MaybeAuto::Specified(w) => w,
@@ -622,8 +655,20 @@ impl ReplacedImageFragmentInfo {
             MaybeAuto::Auto => {
                 let intrinsic_width = fragment_inline_size;
                 let intrinsic_height = fragment_block_size;
-                let scale = intrinsic_width.to_f32_px() / inline_size.to_f32_px();
-                Au::from_f32_px(intrinsic_height.to_f32_px() / scale)
+                let natural_ratio = if intrinsic_height == Au(0) {
+                    None
+                } else {
+                    Some(intrinsic_width.to_f32_px() / intrinsic_height.to_f32_px())
+                };
+                match model::aspect_ratio(style, natural_ratio) {
+                    Some(ratio) if Some(ratio) != natural_ratio => {
+                        Au::from_f32_px(inline_size.to_f32_px() / ratio)
+                    }
+                    _ => {
+                        let scale = intrinsic_width.to_f32_px() / inline_size.to_f32_px();
+                        Au::from_f32_px(intrinsic_height.to_f32_px() / scale)
+                    }
+                }
             },
             MaybeAuto::Specified(h) => {
                 h
@@ -689,9 +734,9 @@ impl IframeFragmentInfo {
             (LengthOrPercentageOrAuto::Length(length), _) => length,
             (LengthOrPercentageOrAuto::Percentage(pc), Some(container_size)) => container_size.scale_by(pc),
             (LengthOrPercentageOrAuto::Calc(calc), Some(container_size)) => {
-                container_size.scale_by(calc.percentage()) + calc.length()
+                calc.to_used_value(container_size)
             },
-            (LengthOrPercentageOrAuto::Calc(calc), None) => calc.length(),
+            (LengthOrPercentageOrAuto::Calc(calc), None) => calc.to_used_value(Au(0)),
             (LengthOrPercentageOrAuto::Percentage(_), None) => default_size,
             (LengthOrPercentageOrAuto::Auto, _) => default_size,
         };
@@ -800,12 +845,16 @@ pub struct SplitResult {
     pub inline_end: Option<SplitInfo>,
     /// The text run which is being split.
     pub text_run: Arc<TextRun>,
+    /// Whether the split is inside a word, so that the first line must end with a hyphen.
+    pub hyphenated: bool,
 }
 
 /// Describes how a fragment should be truncated.
 pub struct TruncationResult {
     /// The part of the fragment remaining after truncation.
     pub split: SplitInfo,
+    /// The part of the fragment cut off by truncation, if any.
+    pub remainder: Option<SplitInfo>,
     /// The text run which is being truncated.
     pub text_run: Arc<TextRun>,
 }
@@ -862,6 +911,14 @@ impl Fragment {
         let mut restyle_damage = node.restyle_damage();
         restyle_damage.remove(RECONSTRUCT_FLOW);
 
+        let mut flags = FragmentFlags::empty();
+        if let Some(LayoutNodeType::Element(_)) = node.type_id() {
+            let element = node.as_element();
+            if element.get_local_name() == &atom!("body") && element.get_namespace() == &ns!(html) {
+                flags.insert(IS_BODY_ELEMENT);
+            }
+        }
+
         Fragment {
             node: node.opaque(),
             style: style,
@@ -873,7 +930,7 @@ impl Fragment {
             specific: specific,
             inline_context: None,
             pseudo: node.get_pseudo_element_type().strip(),
-            flags: FragmentFlags::empty(),
+            flags: flags,
             debug_id: DebugId::new(),
             stacking_context_id: StackingContextId::new(0),
         }
@@ -965,15 +1022,48 @@ impl Fragment {
 
     /// Transforms this fragment into an ellipsis fragment, preserving all the other data.
     pub fn transform_into_ellipsis(&self, layout_context: &LayoutContext) -> Fragment {
-        let mut unscanned_ellipsis_fragments = LinkedList::new();
-        unscanned_ellipsis_fragments.push_back(self.transform(
+        self.transform_into_generated_text("…".to_owned(), IS_ELLIPSIS, layout_context)
+    }
+
+    /// Transforms this fragment into the hyphen that ends a line on which it is hyphenated, per
+    /// `hyphenate-character`. Returns `None` if `hyphens` forbids hyphenating this fragment.
+    pub fn transform_into_hyphen(&self, layout_context: &LayoutContext) -> Option<Fragment> {
+        let text_fragment_info = match self.specific {
+            SpecificFragmentInfo::ScannedText(ref text_fragment_info) => text_fragment_info,
+            _ => return None,
+        };
+        let text = match (self.style().get_inheritedtext().hyphens,
+                          &self.style().get_inheritedtext().hyphenate_character) {
+            (hyphens::T::none, _) => return None,
+            (hyphens::T::manual, _) if !text_fragment_info.text().contains('\u{ad}') => return None,
+            // U+2010 HYPHEN, as recommended by CSS-TEXT-4 § 6.2.
+            (_, &hyphenate_character::T::auto) => "\u{2010}".to_owned(),
+            (_, &hyphenate_character::T::String(ref string)) => string.clone(),
+        };
+        let mut hyphen = self.transform_into_generated_text(text, IS_HYPHEN, layout_context);
+        hyphen.border_padding = LogicalMargin::zero(hyphen.style.writing_mode);
+        hyphen.margin = LogicalMargin::zero(hyphen.style.writing_mode);
+        Some(hyphen)
+    }
+
+    /// Transforms this fragment into a scanned text fragment for `text` that inline layout
+    /// inserts, preserving all the other data. `flag` identifies the fragment so that it can be
+    /// discarded before the next reflow.
+    fn transform_into_generated_text(&self,
+                                     text: String,
+                                     flag: FragmentFlags,
+                                     layout_context: &LayoutContext)
+                                     -> Fragment {
+        let mut unscanned_fragments = LinkedList::new();
+        unscanned_fragments.push_back(self.transform(
                 self.border_box.size,
-                SpecificFragmentInfo::UnscannedText(
-                    box UnscannedTextFragmentInfo::new("…".to_owned(), None))));
-        let ellipsis_fragments = TextRunScanner::new().scan_for_runs(&mut layout_context.font_context(),
-                                                                     unscanned_ellipsis_fragments);
-        debug_assert!(ellipsis_fragments.len() == 1);
-        ellipsis_fragments.fragments.into_iter().next().unwrap()
+                SpecificFragmentInfo::UnscannedText(box UnscannedTextFragmentInfo::new(text, None))));
+        let fragments = TextRunScanner::new().scan_for_runs(&mut layout_context.font_context(),
+                                                            unscanned_fragments);
+        debug_assert!(fragments.len() == 1);
+        let mut fragment = fragments.fragments.into_iter().next().unwrap();
+        fragment.flags.insert(flag);
+        fragment
     }
 
     pub fn restyle_damage(&self) -> RestyleDamage {
@@ -1075,7 +1165,8 @@ impl Fragment {
         let padding = if flags.contains(INTRINSIC_INLINE_SIZE_INCLUDES_PADDING) {
             let padding = style.logical_padding();
             (model::specified(padding.inline_start, Au(0)) +
-             model::specified(padding.inline_end, Au(0)))
+             model::specified(padding.inline_end, Au(0)) +
+             self.scrollbar_gutter().inline_start_end())
         } else {
             Au(0)
         };
@@ -1169,6 +1260,18 @@ impl Fragment {
         }
     }
 
+    /// Returns the space that this fragment reserves for its block-axis scrollbar because of
+    /// `scrollbar-gutter`. This sits between the border and the padding and is included in the
+    /// `border_padding` field.
+    pub fn scrollbar_gutter(&self) -> LogicalMargin<Au> {
+        match self.specific {
+            SpecificFragmentInfo::Generic if self.style.get_box().display != display::T::inline => {
+                model::scrollbar_gutter_from_style(self.style())
+            }
+            _ => LogicalMargin::zero(self.style.writing_mode),
+        }
+    }
+
     /// Returns the border width in given direction if this fragment has property
     /// 'box-sizing: border-box'. The `border_padding` field should have been initialized.
     pub fn box_sizing_boundary(&self, direction: Direction) -> Au {
@@ -1183,6 +1286,17 @@ impl Fragment {
         }
     }
 
+    /// Returns the block-size that `aspect-ratio` gives this fragment from its inline-size, in the
+    /// box that `box-sizing` applies to. This is for non-replaced content only, once its
+    /// inline-size has been assigned.
+    pub fn block_size_from_aspect_ratio(&self) -> Option<Au> {
+        model::aspect_ratio(&*self.style, None).map(|ratio| {
+            let inline_size = self.border_box.size.inline - self.border_padding.inline_start_end() +
+                self.box_sizing_boundary(Direction::Inline);
+            Au::from_f32_px(inline_size.to_f32_px() / ratio)
+        })
+    }
+
     /// Computes the margins in the inline direction from the containing block inline-size and the
     /// style. After this call, the inline direction of the `margin` field will be correct.
     ///
@@ -1315,7 +1429,8 @@ impl Fragment {
             }
         };
 
-        self.border_padding = border + padding_from_style + padding_from_inline_fragment_context
+        self.border_padding = border + self.scrollbar_gutter() + padding_from_style +
+            padding_from_inline_fragment_context
     }
 
     // Return offset from original position because of `position: relative`.
@@ -1397,8 +1512,8 @@ impl Fragment {
     /// CSS 2.1 § 16.3.1. Unfortunately, computing this properly doesn't really fit into Servo's
     /// model. Therefore, this is a best lower bound approximation, but the end result may actually
     /// have the various decoration flags turned on afterward.
-    pub fn text_decoration(&self) -> text_decoration::T {
-        self.style().get_text().text_decoration
+    pub fn text_decoration_line(&self) -> text_decoration_line::T {
+        self.style().get_text().text_decoration_line
     }
 
     /// Returns the inline-start offset from margin edge to content edge.
@@ -1469,7 +1584,7 @@ impl Fragment {
                         image_fragment_info.image_inline_size()
                     }
                     LengthOrPercentageOrAuto::Length(length) => length,
-                    LengthOrPercentageOrAuto::Calc(calc) => calc.length(),
+                    LengthOrPercentageOrAuto::Calc(calc) => calc.to_used_value(Au(0)),
                 };
 
                 image_inline_size = max(model::specified(self.style.min_inline_size(), Au(0)), image_inline_size);
@@ -1489,7 +1604,7 @@ impl Fragment {
                         canvas_fragment_info.canvas_inline_size()
                     }
                     LengthOrPercentageOrAuto::Length(length) => length,
-                    LengthOrPercentageOrAuto::Calc(calc) => calc.length(),
+                    LengthOrPercentageOrAuto::Calc(calc) => calc.to_used_value(Au(0)),
                 };
 
                 canvas_inline_size = max(model::specified(self.style.min_inline_size(), Au(0)), canvas_inline_size);
@@ -1620,7 +1735,13 @@ impl Fragment {
     /// A return value of `None` indicates that the fragment could not be split. Otherwise the
     /// information pertaining to the split is returned. The inline-start and inline-end split
     /// information are both optional due to the possibility of them being whitespace.
-    pub fn calculate_split_position(&self, max_inline_size: Au, starts_line: bool)
+    ///
+    /// If `hyphen_inline_size` is present, words that overflow may be hyphenated per `hyphens`,
+    /// leaving room for a hyphen of that size at the end of the line.
+    pub fn calculate_split_position(&self,
+                                    max_inline_size: Au,
+                                    starts_line: bool,
+                                    hyphen_inline_size: Option<Au>)
                                     -> Option<SplitResult> {
         let text_fragment_info = match self.specific {
             SpecificFragmentInfo::ScannedText(ref text_fragment_info)
@@ -1644,6 +1765,7 @@ impl Fragment {
                 self.calculate_split_position_using_breaking_strategy(
                     natural_word_breaking_strategy,
                     max_inline_size,
+                    hyphen_inline_size,
                     flags)
             }
             word_break::T::break_all => {
@@ -1654,6 +1776,7 @@ impl Fragment {
                 self.calculate_split_position_using_breaking_strategy(
                     character_breaking_strategy,
                     max_inline_size,
+                    None,
                     flags)
             }
         }
@@ -1673,6 +1796,7 @@ impl Fragment {
             text_fragment_info.run.character_slices_in_range(&text_fragment_info.range);
         match self.calculate_split_position_using_breaking_strategy(character_breaking_strategy,
                                                                     max_inline_size,
+                                                                    None,
                                                                     SplitOptions::empty()) {
             None => None,
             Some(split_info) => {
@@ -1681,6 +1805,7 @@ impl Fragment {
                     Some(split) => {
                         Some(TruncationResult {
                             split: split,
+                            remainder: split_info.inline_end,
                             text_run: split_info.text_run.clone(),
                         })
                     }
@@ -1695,6 +1820,7 @@ impl Fragment {
             &self,
             slice_iterator: I,
             max_inline_size: Au,
+            hyphen_inline_size: Option<Au>,
             flags: SplitOptions)
             -> Option<SplitResult>
             where I: Iterator<Item=TextRunSlice<'a>> {
@@ -1708,6 +1834,7 @@ impl Fragment {
         let mut inline_start_range = Range::new(text_fragment_info.range.begin(), ByteIndex(0));
         let mut inline_end_range = None;
         let mut overflowing = false;
+        let mut hyphenated = false;
 
         debug!("calculate_split_position_using_breaking_strategy: splitting text fragment \
                 (strlen={}, range={:?}, max_inline_size={:?})",
@@ -1736,8 +1863,23 @@ impl Fragment {
                 continue
             }
 
-            // The advance is more than the remaining inline-size, so split here. First, check to
-            // see if we're going to overflow the line. If so, perform a best-effort split.
+            // The advance is more than the remaining inline-size, so split here. If the word can
+            // be hyphenated so that its start fits alongside a hyphen, split inside it.
+            if let Some(hyphen_inline_size) = hyphen_inline_size {
+                if let Some(hyphenation_point) =
+                        self.hyphenation_point(&slice.text_run_range(),
+                                               remaining_inline_size - hyphen_inline_size) {
+                    inline_start_range.extend_to(hyphenation_point);
+                    let mut inline_end = Range::new(hyphenation_point, ByteIndex(0));
+                    inline_end.extend_to(text_fragment_info.range.end());
+                    inline_end_range = Some(inline_end);
+                    hyphenated = true;
+                    break
+                }
+            }
+
+            // Otherwise, check to see if we're going to overflow the line. If so, perform a
+            // best-effort split.
             let mut remaining_range = slice.text_run_range();
             let split_is_empty = inline_start_range.is_empty() &&
                     !(self.requires_line_break_afterward_if_wrapping_on_newlines() &&
@@ -1776,6 +1918,7 @@ impl Fragment {
                     return self.calculate_split_position_using_breaking_strategy(
                         character_breaking_strategy,
                         max_inline_size,
+                        None,
                         flags)
                 }
 
@@ -1804,9 +1947,34 @@ impl Fragment {
             inline_start: inline_start,
             inline_end: inline_end,
             text_run: text_fragment_info.run.clone(),
+            hyphenated: hyphenated,
         })
     }
 
+    /// Returns the last position inside the word at `word_range` of this text fragment where it
+    /// may be hyphenated such that the part before it fits in `max_inline_size`, per `hyphens`.
+    fn hyphenation_point(&self, word_range: &Range<ByteIndex>, max_inline_size: Au)
+                         -> Option<ByteIndex> {
+        let text_fragment_info = match self.specific {
+            SpecificFragmentInfo::ScannedText(ref text_fragment_info) => text_fragment_info,
+            _ => return None,
+        };
+        let run = &text_fragment_info.run;
+        let word = &run.text[word_range.begin().to_usize()..word_range.end().to_usize()];
+
+        let inherited_text = self.style().get_inheritedtext();
+        let lang = if inherited_text.hyphens == hyphens::T::auto {
+            Some(&*inherited_text._servo_lang.0)
+        } else {
+            None
+        };
+        gfx::text::hyphenation::last_hyphenation_point(word, lang, |index| {
+            let mut prefix_range = *word_range;
+            prefix_range.extend_to(word_range.begin() + ByteIndex(index as isize));
+            run.advance_for_range(&prefix_range) <= max_inline_size
+        }).map(|index| word_range.begin() + ByteIndex(index as isize))
+    }
+
     /// The opposite of `calculate_split_position_using_breaking_strategy`: merges this fragment
     /// with the next one.
     pub fn merge_with(&mut self, next_fragment: Fragment) {
@@ -2077,7 +2245,7 @@ impl Fragment {
                 // See CSS 2.1 § 10.8.1.
                 let flow = &info.flow_ref;
                 let block_flow = flow.as_block();
-                let is_auto = self.style.get_position().height == LengthOrPercentageOrAuto::Auto;
+                let is_auto = self.style.get_position().height.length_or_auto() == LengthOrPercentageOrAuto::Auto;
                 let baseline_offset = match flow.baseline_offset_of_last_line_box_in_flow() {
                     Some(baseline_offset) if is_auto => baseline_offset,
                     _ => block_flow.fragment.border_box.size.block,
@@ -2125,7 +2293,7 @@ impl Fragment {
              &SpecificFragmentInfo::UnscannedText(_)) => {
                 // FIXME: Should probably use a whitelist of styles that can safely differ (#3165)
                 if self.style().get_font() != other.style().get_font() ||
-                        self.text_decoration() != other.text_decoration() ||
+                        self.text_decoration_line() != other.text_decoration_line() ||
                         self.white_space() != other.white_space() ||
                         self.color() != other.color() {
                     return false
@@ -2304,6 +2472,9 @@ impl Fragment {
         if self.flags.contains(HAS_LAYER) {
             return true
         }
+        if self.style().get_box().position == position::T::sticky {
+            return true
+        }
         if self.style().get_effects().opacity != 1.0 {
             return true
         }
@@ -2313,7 +2484,15 @@ impl Fragment {
         if self.style().get_effects().mix_blend_mode != mix_blend_mode::T::normal {
             return true
         }
-        if self.style().get_effects().transform.0.is_some() {
+        if self.style().get_box().isolation == isolation::T::isolate {
+            return true
+        }
+        if self.style().get_background().background_blend_mode.0.iter().any(|blend_mode| {
+            *blend_mode != mix_blend_mode::T::normal
+        }) {
+            return true
+        }
+        if self.style().is_transformed() {
             return true
         }
         match self.style().get_used_transform_style() {
@@ -2323,51 +2502,40 @@ impl Fragment {
             transform_style::T::auto => {}
         }
 
+        // Layout and paint containment make the box a stacking context.
+        if self.style().has_layout_containment() || self.style().has_paint_containment() {
+            return true
+        }
+
+        // Positioned boxes and flex items establish a stacking context unless `z-index` is
+        // `auto`. Otherwise they are painted as pseudo-stacking contexts, so that their
+        // positioned descendants take part in the parent stacking context.
+        if self.style().get_position().z_index != z_index::T::Auto &&
+                (self.style().get_box().position != position::T::static_ ||
+                 self.flags.contains(IS_FLEX_ITEM)) {
+            return true
+        }
+
         // FIXME(pcwalton): Don't unconditionally form stacking contexts for `overflow_x: scroll`
         // and `overflow_y: scroll`. This needs multiple layers per stacking context.
-        match (self.style().get_box().position,
-               self.style().get_position().z_index,
-               self.style().get_box().overflow_x,
+        match (self.style().get_box().overflow_x,
                self.style().get_box().overflow_y.0) {
-            (position::T::absolute,
-             z_index::T::Auto,
-             overflow_x::T::visible,
-             overflow_x::T::visible) |
-            (position::T::fixed,
-             z_index::T::Auto,
-             overflow_x::T::visible,
-             overflow_x::T::visible) |
-            (position::T::relative,
-             z_index::T::Auto,
-             overflow_x::T::visible,
-             overflow_x::T::visible) => false,
-            (position::T::absolute, _, _, _) |
-            (position::T::fixed, _, _, _) |
-            (position::T::relative, _, _, _) |
-            (_, _, overflow_x::T::auto, _) |
-            (_, _, overflow_x::T::scroll, _) |
-            (_, _, _, overflow_x::T::auto) |
-            (_, _, _, overflow_x::T::scroll) => true,
-            (position::T::static_, _, _, _) => false
+            (overflow_x::T::auto, _) |
+            (overflow_x::T::scroll, _) |
+            (_, overflow_x::T::auto) |
+            (_, overflow_x::T::scroll) => true,
+            (_, _) => false,
         }
     }
 
     // Get the effective z-index of this fragment. Z-indices only apply to positioned element
-    // per CSS 2 9.9.1 (http://www.w3.org/TR/CSS2/visuren.html#z-index), so this value may differ
-    // from the value specified in the style.
+    // per CSS 2 9.9.1 (http://www.w3.org/TR/CSS2/visuren.html#z-index) and to flex items
+    // (https://drafts.csswg.org/css-flexbox/#painting), so this value may differ from the value
+    // specified in the style.
     pub fn effective_z_index(&self) -> i32 {
         match self.style().get_box().position {
-            position::T::static_ => {},
-            _ => return self.style().get_position().z_index.number_or_zero(),
-        }
-
-        if self.style().get_effects().transform.0.is_some() {
-            return self.style().get_position().z_index.number_or_zero();
-        }
-
-        match self.style().get_box().display {
-            display::T::flex => self.style().get_position().z_index.number_or_zero(),
-            _ => 0,
+            position::T::static_ if !self.flags.contains(IS_FLEX_ITEM) => 0,
+            _ => self.style().get_position().z_index.number_or_zero(),
         }
     }
 
@@ -2552,6 +2720,11 @@ impl Fragment {
         self.border_box.size.inline + self.margin.inline_start_end()
     }
 
+    /// Whether this is a fragment of the HTML `body` element.
+    pub fn is_body_element(&self) -> bool {
+        self.flags.contains(IS_BODY_ELEMENT)
+    }
+
     /// Returns true if this node *or any of the nodes within its inline fragment context* have
     /// non-`static` `position`.
     pub fn is_positioned(&self) -> bool {
@@ -2863,6 +3036,20 @@ bitflags! {
     pub flags FragmentFlags: u8 {
         /// Whether this fragment has a layer.
         const HAS_LAYER = 0x01,
+        /// Whether this fragment is a flex item, to which `z-index` applies even if it is not
+        /// positioned.
+        const IS_FLEX_ITEM = 0x02,
+        /// Whether this is a fragment of the HTML `body` element, whose background is propagated
+        /// to the canvas when that of the root element is transparent.
+        const IS_BODY_ELEMENT = 0x04,
+        /// Whether this fragment is an ellipsis generated by `text-overflow`. It is discarded
+        /// before the next reflow.
+        const IS_ELLIPSIS = 0x08,
+        /// Whether this fragment is inline content hidden after an ellipsis by `text-overflow`.
+        const IS_TRUNCATED_BY_ELLIPSIS = 0x10,
+        /// Whether this fragment is the hyphen ending a hyphenated line. It is discarded before
+        /// the next reflow.
+        const IS_HYPHEN = 0x20,
     }
 }
 
@@ -26,15 +26,12 @@ use text::TextRunScanner;
 
 // Decimal styles per CSS-COUNTER-STYLES § 6.1:
 static DECIMAL: [char; 10] = [ '0', '1', '2', '3', '4', '5', '6', '7', '8', '9' ];
-// TODO(pcwalton): `decimal-leading-zero`
 static ARABIC_INDIC: [char; 10] = [ '٠', '١', '٢', '٣', '٤', '٥', '٦', '٧', '٨', '٩' ];
-// TODO(pcwalton): `armenian`, `upper-armenian`, `lower-armenian`
 static BENGALI: [char; 10] = [ '০', '১', '২', '৩', '৪', '৫', '৬', '৭', '৮', '৯' ];
 static CAMBODIAN: [char; 10] = [ '០', '១', '២', '៣', '៤', '៥', '៦', '៧', '៨', '៩' ];
 // TODO(pcwalton): Suffix for CJK decimal.
 static CJK_DECIMAL: [char; 10] = [ '〇', '一', '二', '三', '四', '五', '六', '七', '八', '九' ];
 static DEVANAGARI: [char; 10] = [ '०', '१', '२', '३', '४', '५', '६', '७', '८', '९' ];
-// TODO(pcwalton): `georgian`
 static GUJARATI: [char; 10] = ['૦', '૧', '૨', '૩', '૪', '૫', '૬', '૭', '૮', '૯'];
 static GURMUKHI: [char; 10] = ['੦', '੧', '੨', '੩', '੪', '੫', '੬', '੭', '੮', '੯'];
 // TODO(pcwalton): `hebrew`
@@ -45,7 +42,6 @@ static MONGOLIAN: [char; 10] = ['᠐', '᠑', '᠒', '᠓', '᠔', '᠕', '᠖',
 static MYANMAR: [char; 10] = ['၀', '၁', '၂', '၃', '၄', '၅', '၆', '၇', '၈', '၉'];
 static ORIYA: [char; 10] = ['୦', '୧', '୨', '୩', '୪', '୫', '୬', '୭', '୮', '୯'];
 static PERSIAN: [char; 10] = ['۰', '۱', '۲', '۳', '۴', '۵', '۶', '۷', '۸', '۹'];
-// TODO(pcwalton): `lower-roman`, `upper-roman`
 static TELUGU: [char; 10] = ['౦', '౧', '౨', '౩', '౪', '౫', '౬', '౭', '౮', '౯'];
 static THAI: [char; 10] = ['๐', '๑', '๒', '๓', '๔', '๕', '๖', '๗', '๘', '๙'];
 static TIBETAN: [char; 10] = ['༠', '༡', '༢', '༣', '༤', '༥', '༦', '༧', '༨', '༩'];
@@ -94,6 +90,69 @@ static KATAKANA_IROHA: [char; 47] = [
     'ヒ', 'モ', 'セ', 'ス'
 ];
 
+// Additive styles per CSS-COUNTER-STYLES § 6.1, along with the highest value they can represent:
+static UPPER_ROMAN: [(usize, &'static str); 13] = [
+    (1000, "M"), (900, "CM"), (500, "D"), (400, "CD"), (100, "C"), (90, "XC"), (50, "L"),
+    (40, "XL"), (10, "X"), (9, "IX"), (5, "V"), (4, "IV"), (1, "I")
+];
+static LOWER_ROMAN: [(usize, &'static str); 13] = [
+    (1000, "m"), (900, "cm"), (500, "d"), (400, "cd"), (100, "c"), (90, "xc"), (50, "l"),
+    (40, "xl"), (10, "x"), (9, "ix"), (5, "v"), (4, "iv"), (1, "i")
+];
+static ROMAN_MAXIMUM: usize = 3999;
+static UPPER_ARMENIAN: [(usize, &'static str); 36] = [
+    (9000, "Ք"), (8000, "Փ"), (7000, "Ւ"), (6000, "Ց"), (5000, "Ր"), (4000, "Տ"), (3000, "Վ"),
+    (2000, "Ս"), (1000, "Ռ"), (900, "Ջ"), (800, "Պ"), (700, "Չ"), (600, "Ո"), (500, "Շ"),
+    (400, "Ն"), (300, "Յ"), (200, "Մ"), (100, "Ճ"), (90, "Ղ"), (80, "Ձ"), (70, "Հ"), (60, "Կ"),
+    (50, "Ծ"), (40, "Խ"), (30, "Լ"), (20, "Ի"), (10, "Ժ"), (9, "Թ"), (8, "Ը"), (7, "Է"),
+    (6, "Զ"), (5, "Ե"), (4, "Դ"), (3, "Գ"), (2, "Բ"), (1, "Ա")
+];
+static LOWER_ARMENIAN: [(usize, &'static str); 36] = [
+    (9000, "ք"), (8000, "փ"), (7000, "ւ"), (6000, "ց"), (5000, "ր"), (4000, "տ"), (3000, "վ"),
+    (2000, "ս"), (1000, "ռ"), (900, "ջ"), (800, "պ"), (700, "չ"), (600, "ո"), (500, "շ"),
+    (400, "ն"), (300, "յ"), (200, "մ"), (100, "ճ"), (90, "ղ"), (80, "ձ"), (70, "հ"), (60, "կ"),
+    (50, "ծ"), (40, "խ"), (30, "լ"), (20, "ի"), (10, "ժ"), (9, "թ"), (8, "ը"), (7, "է"),
+    (6, "զ"), (5, "ե"), (4, "դ"), (3, "գ"), (2, "բ"), (1, "ա")
+];
+static ARMENIAN_MAXIMUM: usize = 9999;
+static GEORGIAN: [(usize, &'static str); 37] = [
+    (10000, "ჵ"), (9000, "ჰ"), (8000, "ჯ"), (7000, "ჴ"), (6000, "ხ"), (5000, "ჭ"), (4000, "წ"),
+    (3000, "ძ"), (2000, "ც"), (1000, "ჩ"), (900, "შ"), (800, "ყ"), (700, "ღ"), (600, "ქ"),
+    (500, "ფ"), (400, "ჳ"), (300, "ტ"), (200, "ს"), (100, "რ"), (90, "ჟ"), (80, "პ"), (70, "ო"),
+    (60, "ჲ"), (50, "ნ"), (40, "მ"), (30, "ლ"), (20, "კ"), (10, "ი"), (9, "თ"), (8, "ჱ"),
+    (7, "ზ"), (6, "ვ"), (5, "ე"), (4, "დ"), (3, "გ"), (2, "ბ"), (1, "ა")
+];
+static GEORGIAN_MAXIMUM: usize = 19999;
+
+// Longhand East Asian styles per CSS-COUNTER-STYLES § 7.1:
+static JAPANESE_INFORMAL: CjkInformalSystem = CjkInformalSystem {
+    digits: [ '〇', '一', '二', '三', '四', '五', '六', '七', '八', '九' ],
+    negative: "マイナス",
+    chinese: false,
+};
+static SIMP_CHINESE_INFORMAL: CjkInformalSystem = CjkInformalSystem {
+    digits: [ '零', '一', '二', '三', '四', '五', '六', '七', '八', '九' ],
+    negative: "负",
+    chinese: true,
+};
+static TRAD_CHINESE_INFORMAL: CjkInformalSystem = CjkInformalSystem {
+    digits: [ '零', '一', '二', '三', '四', '五', '六', '七', '八', '九' ],
+    negative: "負",
+    chinese: true,
+};
+static CJK_INFORMAL_MAXIMUM: usize = 9999;
+
+/// The symbols of one of the longhand East Asian counter styles.
+struct CjkInformalSystem {
+    /// The symbols for the digits 0 through 9.
+    digits: [char; 10],
+    /// The negative sign.
+    negative: &'static str,
+    /// Whether this is a Chinese style, which keeps a single zero in the middle of a number and
+    /// the one before the thousands and hundreds markers. Japanese drops both.
+    chinese: bool,
+}
+
 /// The generated content resolution traversal.
 pub struct ResolveGeneratedContent<'a> {
     /// The layout context.
@@ -157,10 +216,9 @@ impl<'a,'b> ResolveGeneratedContentFragmentMutator<'a,'b> {
             self.reset_and_increment_counters_as_necessary(fragment);
         }
 
-        let mut list_style_type = fragment.style().get_list().list_style_type;
-        if fragment.style().get_box().display != display::T::list_item {
-            list_style_type = list_style_type::T::none
-        }
+        // List item markers are styled by their `::marker`, which isn't a list item itself but
+        // inherits `list-style-type` from one.
+        let list_style_type = fragment.style().get_list().list_style_type;
 
         let mut new_info = None;
         {
@@ -178,7 +236,8 @@ impl<'a,'b> ResolveGeneratedContentFragmentMutator<'a,'b> {
                                                                fragment.pseudo.clone(),
                                                                fragment.style.clone(),
                                                                list_style_type,
-                                                               RenderingMode::Suffix(".\u{00a0}"))
+                                                               RenderingMode::Suffix(
+                                                                   marker_suffix(list_style_type)))
                 }
                 GeneratedContentInfo::Empty |
                 GeneratedContentInfo::ContentItem(ContentItem::String(_)) => {
@@ -466,6 +525,39 @@ fn push_representation(value: i32, list_style_type: list_style_type::T, accumula
             accumulator.push(static_representation(list_style_type))
         }
         list_style_type::T::decimal => push_numeric_representation(value, &DECIMAL, accumulator),
+        list_style_type::T::decimal_leading_zero => {
+            if value > -10 && value < 10 {
+                let abs_value = handle_negative_value(value, accumulator);
+                accumulator.push(DECIMAL[0]);
+                accumulator.push(DECIMAL[abs_value])
+            } else {
+                push_numeric_representation(value, &DECIMAL, accumulator)
+            }
+        }
+        list_style_type::T::lower_roman => {
+            push_additive_representation(value, &LOWER_ROMAN, ROMAN_MAXIMUM, accumulator)
+        }
+        list_style_type::T::upper_roman => {
+            push_additive_representation(value, &UPPER_ROMAN, ROMAN_MAXIMUM, accumulator)
+        }
+        list_style_type::T::armenian | list_style_type::T::upper_armenian => {
+            push_additive_representation(value, &UPPER_ARMENIAN, ARMENIAN_MAXIMUM, accumulator)
+        }
+        list_style_type::T::lower_armenian => {
+            push_additive_representation(value, &LOWER_ARMENIAN, ARMENIAN_MAXIMUM, accumulator)
+        }
+        list_style_type::T::georgian => {
+            push_additive_representation(value, &GEORGIAN, GEORGIAN_MAXIMUM, accumulator)
+        }
+        list_style_type::T::japanese_informal => {
+            push_cjk_informal_representation(value, &JAPANESE_INFORMAL, accumulator)
+        }
+        list_style_type::T::simp_chinese_informal => {
+            push_cjk_informal_representation(value, &SIMP_CHINESE_INFORMAL, accumulator)
+        }
+        list_style_type::T::trad_chinese_informal | list_style_type::T::cjk_ideographic => {
+            push_cjk_informal_representation(value, &TRAD_CHINESE_INFORMAL, accumulator)
+        }
         list_style_type::T::arabic_indic => {
             push_numeric_representation(value, &ARABIC_INDIC, accumulator)
         }
@@ -495,10 +587,10 @@ fn push_representation(value: i32, list_style_type: list_style_type::T, accumula
         list_style_type::T::telugu => push_numeric_representation(value, &TELUGU, accumulator),
         list_style_type::T::thai => push_numeric_representation(value, &THAI, accumulator),
         list_style_type::T::tibetan => push_numeric_representation(value, &TIBETAN, accumulator),
-        list_style_type::T::lower_alpha => {
+        list_style_type::T::lower_alpha | list_style_type::T::lower_latin => {
             push_alphabetic_representation(value, &LOWER_ALPHA, accumulator)
         }
-        list_style_type::T::upper_alpha => {
+        list_style_type::T::upper_alpha | list_style_type::T::upper_latin => {
             push_alphabetic_representation(value, &UPPER_ALPHA, accumulator)
         }
         list_style_type::T::cjk_earthly_branch => {
@@ -525,6 +617,25 @@ fn push_representation(value: i32, list_style_type: list_style_type::T, accumula
     }
 }
 
+/// Returns the string that follows the counter value in a list item marker per
+/// CSS-COUNTER-STYLES § 6-7. A nonbreaking space (Unicode 0xa0) provides the marker separation.
+fn marker_suffix(list_style_type: list_style_type::T) -> &'static str {
+    match list_style_type {
+        list_style_type::T::cjk_decimal |
+        list_style_type::T::cjk_earthly_branch |
+        list_style_type::T::cjk_heavenly_stem |
+        list_style_type::T::cjk_ideographic |
+        list_style_type::T::japanese_informal |
+        list_style_type::T::simp_chinese_informal |
+        list_style_type::T::trad_chinese_informal |
+        list_style_type::T::hiragana |
+        list_style_type::T::hiragana_iroha |
+        list_style_type::T::katakana |
+        list_style_type::T::katakana_iroha => "、",
+        _ => ".\u{00a0}",
+    }
+}
+
 /// Returns the static character that represents the value rendered using the given list-style, if
 /// possible.
 pub fn static_representation(list_style_type: list_style_type::T) -> char {
@@ -580,6 +691,77 @@ fn push_numeric_representation(value: i32, system: &[char], accumulator: &mut St
     accumulator.extend(string.iter().cloned().rev())
 }
 
+/// Pushes the string that represents the value rendered using the given *additive system* onto the
+/// accumulator per CSS-COUNTER-STYLES § 3.1.7. Values from 1 up to `maximum` can be represented;
+/// others fall back to `decimal`.
+fn push_additive_representation(value: i32,
+                                system: &[(usize, &str)],
+                                maximum: usize,
+                                accumulator: &mut String) {
+    if value < 1 || value as usize > maximum {
+        return push_numeric_representation(value, &DECIMAL, accumulator)
+    }
+
+    let mut value = value as usize;
+    for &(weight, symbol) in system {
+        while value >= weight {
+            accumulator.push_str(symbol);
+            value -= weight
+        }
+        if value == 0 {
+            break
+        }
+    }
+}
+
+/// Pushes the string that represents the value rendered using the given longhand East Asian
+/// system onto the accumulator per CSS-COUNTER-STYLES § 7.1.2. Values beyond ±9999 fall back to
+/// `cjk-decimal`.
+fn push_cjk_informal_representation(value: i32,
+                                    system: &CjkInformalSystem,
+                                    accumulator: &mut String) {
+    if value.abs() as usize > CJK_INFORMAL_MAXIMUM {
+        return push_numeric_representation(value, &CJK_DECIMAL, accumulator)
+    }
+    if value < 0 {
+        accumulator.push_str(system.negative)
+    }
+
+    // Step 1.
+    let abs_value = value.abs() as usize;
+    if abs_value == 0 {
+        accumulator.push(system.digits[0]);
+        return
+    }
+
+    // Steps 2-5.
+    let digits = [abs_value / 1000, abs_value / 100 % 10, abs_value / 10 % 10, abs_value % 10];
+    let markers = [Some('千'), Some('百'), Some('十'), None];
+    let mut started = false;
+    let mut skipped_zero = false;
+    for (&digit, &marker) in digits.iter().zip(markers.iter()) {
+        if digit == 0 {
+            skipped_zero = started;
+            continue
+        }
+        if skipped_zero && system.chinese {
+            accumulator.push(system.digits[0])
+        }
+        skipped_zero = false;
+
+        // The one before a marker is dropped in Japanese, and in Chinese only for the tens of
+        // numbers from 10 to 19.
+        let is_tens = marker == Some('十');
+        if digit != 1 || marker.is_none() || (system.chinese && !(is_tens && !started)) {
+            accumulator.push(system.digits[digit])
+        }
+        if let Some(marker) = marker {
+            accumulator.push(marker)
+        }
+        started = true
+    }
+}
+
 /// If the system uses a negative sign, handle negative values per CSS-COUNTER-STYLES § 2.
 ///
 /// Returns the absolute value of the counter.
@@ -2,21 +2,47 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use block::FormattingContextType;
 use flow::{self, AFFECTS_COUNTERS, Flow, HAS_COUNTER_AFFECTING_CHILDREN, IS_ABSOLUTELY_POSITIONED};
-use script_layout_interface::restyle_damage::{RestyleDamage, REFLOW, RECONSTRUCT_FLOW};
+use flow::ImmutableFlowUtils;
+use script_layout_interface::restyle_damage::{RestyleDamage, REFLOW, REFLOW_OUT_OF_FLOW, RECONSTRUCT_FLOW};
 use style::computed_values::float;
 use style::dom::TRestyleDamage;
 
 bitflags! {
     pub flags SpecialRestyleDamage: u8 {
-        #[doc = "If this flag is set, we need to reflow the entire document. This is more or less a \
-                 temporary hack to deal with cases that we don't handle incrementally yet."]
-        const REFLOW_ENTIRE_DOCUMENT = 0x01,
+        #[doc = "A float needs to be reflowed, which may move everything around it, so the entire \
+                 block formatting context it is in needs to be reflowed. Propagates up the flow \
+                 tree until it reaches the root of that formatting context."]
+        const REFLOW_FORMATTING_CONTEXT = 0x01,
     }
 }
 
+/// Returns true if changes inside the given flow can't affect the layout of its ancestors, because
+/// it has both size and layout containment.
+///
+/// https://drafts.csswg.org/css-contain-2/#containment-types
+fn is_layout_containment_root(flow: &Flow) -> bool {
+    if !flow.is_block_like() {
+        return false
+    }
+    let style = &flow.as_block().fragment.style;
+    style.has_size_containment() && style.has_layout_containment()
+}
+
+/// Returns true if floats inside the given flow can't affect the layout of anything outside it,
+/// because it is the root of their block formatting context.
+fn is_formatting_context_root(flow: &Flow) -> bool {
+    if flow.is_root() {
+        return true
+    }
+    flow.is_block_like() && flow.as_block().formatting_context_type() != FormattingContextType::None
+}
+
 pub trait LayoutDamageComputation {
     fn compute_layout_damage(self) -> SpecialRestyleDamage;
+
+    /// Marks this flow and all of its descendants for reflow.
     fn reflow_entire_document(self);
 }
 
@@ -39,13 +65,22 @@ impl<'a> LayoutDamageComputation for &'a mut Flow {
                 flow::mut_base(kid).restyle_damage.insert(
                     parent_damage.damage_for_child(is_absolutely_positioned,
                                                    child_is_absolutely_positioned));
-                {
+                // Take a snapshot of the kid's own damage, before its children add theirs.
+                let kid_own_damage = flow::base(kid).restyle_damage;
+                let kid_damage = {
                     let kid: &mut Flow = kid;
                     special_damage.insert(kid.compute_layout_damage());
-                }
+                    let mut kid_damage = flow::base(kid).restyle_damage;
+                    if is_layout_containment_root(kid) {
+                        // The kid's size and the layout around it don't depend on its contents,
+                        // so only its own changes can require us to reflow.
+                        kid_damage.remove(REFLOW | REFLOW_OUT_OF_FLOW);
+                        kid_damage.insert(kid_own_damage);
+                    }
+                    kid_damage
+                };
                 self_base.restyle_damage
-                         .insert(flow::base(kid).restyle_damage.damage_for_parent(
-                                 child_is_absolutely_positioned));
+                         .insert(kid_damage.damage_for_parent(child_is_absolutely_positioned));
 
                 has_counter_affecting_children = has_counter_affecting_children ||
                     flow::base(kid).flags.intersects(AFFECTS_COUNTERS |
@@ -53,10 +88,18 @@ impl<'a> LayoutDamageComputation for &'a mut Flow {
             }
         }
 
+        // Floats below us have moved, and we contain them, so reflow everything in our formatting
+        // context. Our own size may change as a result, which the damage we give to our parent
+        // takes care of.
+        if special_damage.contains(REFLOW_FORMATTING_CONTEXT) && is_formatting_context_root(self) {
+            self.reflow_entire_document();
+            special_damage.remove(REFLOW_FORMATTING_CONTEXT);
+        }
+
         let self_base = flow::mut_base(self);
         if self_base.flags.float_kind() != float::T::none &&
                 self_base.restyle_damage.intersects(REFLOW) {
-            special_damage.insert(REFLOW_ENTIRE_DOCUMENT);
+            special_damage.insert(REFLOW_FORMATTING_CONTEXT);
         }
 
         if has_counter_affecting_children {
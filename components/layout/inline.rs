@@ -9,14 +9,14 @@ use block::AbsoluteAssignBSizesTraversal;
 use context::{LayoutContext, SharedLayoutContext};
 use display_list_builder::{FragmentDisplayListBuilding, InlineFlowDisplayListBuilding};
 use display_list_builder::DisplayListBuildState;
-use euclid::{Point2D, Size2D};
+use euclid::Point2D;
 use floats::{FloatKind, Floats, PlacementInfo};
 use flow::{self, BaseFlow, Flow, FlowClass, ForceNonfloatedFlag, IS_ABSOLUTELY_POSITIONED};
 use flow::{CONTAINS_TEXT_OR_REPLACED_FRAGMENTS, EarlyAbsolutePositionInfo, MutableFlowUtils};
 use flow::OpaqueFlow;
 use flow_ref;
 use fragment::{CoordinateSystem, Fragment, FragmentBorderBoxIterator, Overflow};
-use fragment::SpecificFragmentInfo;
+use fragment::{IS_ELLIPSIS, IS_HYPHEN, IS_TRUNCATED_BY_ELLIPSIS, SpecificFragmentInfo};
 use gfx::display_list::{OpaqueNode, StackingContext};
 use gfx::font::FontMetrics;
 use gfx::font_context::FontContext;
@@ -33,9 +33,9 @@ use std::cmp::max;
 use std::collections::VecDeque;
 use std::sync::Arc;
 use style::arc_ptr_eq;
-use style::computed_values::{display, overflow_x, position, text_align, text_justify};
+use style::computed_values::{display, position, text_align, text_justify};
 use style::computed_values::{text_overflow, vertical_align, white_space};
-use style::context::{SharedStyleContext, StyleContext};
+use style::context::{QuirksMode, SharedStyleContext, StyleContext};
 use style::logical_geometry::{LogicalRect, LogicalSize, WritingMode};
 use style::properties::ServoComputedValues;
 use style::values::computed::LengthOrPercentage;
@@ -211,14 +211,20 @@ struct LineBreaker {
     /// The minimum depth below the baseline for each line, as specified by the line height and
     /// font style.
     minimum_depth_below_baseline: Au,
+    /// How inline content overflowing a line is rendered, as determined by our block parent.
+    text_overflow: text_overflow::T,
+    /// Whether an ellipsis has been placed on the pending line, after which all content is hidden.
+    pending_line_is_truncated: bool,
 }
 
 impl LineBreaker {
-    /// Creates a new `LineBreaker` with a set of floats and the indentation of the first line.
+    /// Creates a new `LineBreaker` with a set of floats, the indentation of the first line, and
+    /// the `text-overflow` of the lines.
     fn new(float_context: Floats,
            first_line_indentation: Au,
            minimum_block_size_above_baseline: Au,
-           minimum_depth_below_baseline: Au)
+           minimum_depth_below_baseline: Au,
+           text_overflow: text_overflow::T)
            -> LineBreaker {
         LineBreaker {
             new_fragments: Vec::new(),
@@ -233,6 +239,8 @@ impl LineBreaker {
             first_line_indentation: first_line_indentation,
             minimum_block_size_above_baseline: minimum_block_size_above_baseline,
             minimum_depth_below_baseline: minimum_depth_below_baseline,
+            text_overflow: text_overflow,
+            pending_line_is_truncated: false,
         }
     }
 
@@ -247,6 +255,7 @@ impl LineBreaker {
     /// Reinitializes the pending line to blank data.
     fn reset_line(&mut self) -> Line {
         self.last_known_line_breaking_opportunity = None;
+        self.pending_line_is_truncated = false;
         mem::replace(&mut self.pending_line, Line::new(self.floats.writing_mode,
                                                        self.minimum_block_size_above_baseline,
                                                        self.minimum_depth_below_baseline))
@@ -271,12 +280,15 @@ impl LineBreaker {
         self.reflow_fragments(old_fragment_iter, flow, layout_context);
 
         // Perform unicode bidirectional layout.
-        let para_level = flow.base.writing_mode.to_bidi_level();
+        let para_level = flow.bidi_paragraph_level;
 
         // The text within a fragment is at a single bidi embedding level (because we split
         // fragments on level run boundaries during flow construction), so we can build a level
-        // array with just one entry per fragment.
+        // array with just one entry per fragment. An ellipsis and the content it hides are at the
+        // paragraph level, so that the ellipsis ends up at the end edge of the line (CSS-OVERFLOW-3
+        // § 4.1).
         let levels: Vec<u8> = self.new_fragments.iter().map(|fragment| match fragment.specific {
+            _ if fragment.flags.intersects(IS_ELLIPSIS | IS_TRUNCATED_BY_ELLIPSIS) => para_level,
             SpecificFragmentInfo::ScannedText(ref info) => info.run.bidi_level,
             _ => para_level
         }).collect();
@@ -335,7 +347,18 @@ impl LineBreaker {
     /// reflow-safe; try `next_unbroken_fragment` instead.
     fn next_fragment<I>(&mut self, old_fragment_iter: &mut I) -> Option<Fragment>
                         where I: Iterator<Item=Fragment> {
-        self.work_list.pop_front().or_else(|| old_fragment_iter.next())
+        // Undo any truncation and hyphenation performed during previous reflows.
+        loop {
+            let mut fragment = match self.work_list.pop_front().or_else(|| old_fragment_iter.next()) {
+                None => return None,
+                Some(fragment) => fragment,
+            };
+            if fragment.flags.intersects(IS_ELLIPSIS | IS_HYPHEN) {
+                continue
+            }
+            fragment.flags.remove(IS_TRUNCATED_BY_ELLIPSIS);
+            return Some(fragment)
+        }
     }
 
     /// Acquires a new fragment to lay out from the work list or fragment list, merging it with any
@@ -398,6 +421,9 @@ impl LineBreaker {
         }
         let last_fragment_index = self.pending_line.range.end() - FragmentIndex(1);
         let mut fragment = &mut self.new_fragments[last_fragment_index.get() as usize];
+        if fragment.flags.contains(IS_TRUNCATED_BY_ELLIPSIS) {
+            return
+        }
 
         let old_fragment_inline_size = fragment.border_box.size.inline;
 
@@ -453,13 +479,14 @@ impl LineBreaker {
         };
 
         // Try to place the fragment between floats.
-        let line_bounds = self.floats.place_between_floats(&PlacementInfo {
+        let line_bounds = self.floats.place_line_between_floats(&PlacementInfo {
             size: LogicalSize::new(self.floats.writing_mode,
                                    placement_inline_size,
                                    first_fragment.border_box.size.block),
             ceiling: ceiling,
             max_inline_size: flow.base.position.size.inline,
             kind: FloatKind::Left,
+            shape: None,
         });
 
         let fragment_margin_box_inline_size = first_fragment.margin_box_inline_size();
@@ -611,8 +638,13 @@ impl LineBreaker {
                                     indentation;
         let inline_start_fragment;
         let inline_end_fragment;
+
+        // Measure the hyphen that ends the line if we hyphenate the fragment.
+        let hyphen = fragment.transform_into_hyphen(layout_context);
+        let hyphen_inline_size = hyphen.as_ref().map(|hyphen| hyphen.border_box.size.inline);
         let split_result = match fragment.calculate_split_position(available_inline_size,
-                                                                   self.pending_line_is_empty()) {
+                                                                   self.pending_line_is_empty(),
+                                                                   hyphen_inline_size) {
             None => {
                 // We failed to split. Defer to the next line if we're allowed to; otherwise,
                 // rewind to the last line breaking opportunity.
@@ -629,6 +661,11 @@ impl LineBreaker {
             }
             Some(split_result) => split_result,
         };
+        let hyphen = if split_result.hyphenated {
+            hyphen
+        } else {
+            None
+        };
 
         inline_start_fragment = split_result.inline_start.as_ref().map(|x| {
             fragment.transform_with_split_info(x, split_result.text_run.clone())
@@ -657,9 +694,23 @@ impl LineBreaker {
                 }
                 inline_end_fragment.border_box.size.inline += inline_end_fragment.border_padding.inline_end;
 
+                let hyphen = hyphen.map(|mut hyphen| {
+                    hyphen.inline_context = inline_start_fragment.inline_context.clone();
+                    if let Some(ref mut inline_context) = hyphen.inline_context {
+                        for node in &mut inline_context.nodes {
+                            node.flags.remove(FIRST_FRAGMENT_OF_ELEMENT);
+                        }
+                    }
+                    hyphen
+                });
+
                 self.push_fragment_to_line(layout_context,
                                            inline_start_fragment,
-                                           LineFlushMode::Flush);
+                                           LineFlushMode::No);
+                if let Some(hyphen) = hyphen {
+                    self.push_fragment_to_line(layout_context, hyphen, LineFlushMode::No);
+                }
+                self.flush_current_line();
                 self.work_list.push_front(inline_end_fragment)
             },
             (Some(fragment), None) => {
@@ -689,29 +740,47 @@ impl LineBreaker {
         }
 
         // Determine if an ellipsis will be necessary to account for `text-overflow`.
-        let mut need_ellipsis = false;
         let available_inline_size = self.pending_line.green_zone.inline -
             self.pending_line.bounds.size.inline - indentation;
-        match (fragment.style().get_text().text_overflow,
-               fragment.style().get_box().overflow_x) {
-            (text_overflow::T::clip, _) | (_, overflow_x::T::visible) => {}
-            (text_overflow::T::ellipsis, _) => {
-                need_ellipsis = fragment.margin_box_inline_size() > available_inline_size;
+        let need_ellipsis = match self.text_overflow {
+            text_overflow::T::clip => false,
+            text_overflow::T::ellipsis => {
+                fragment.margin_box_inline_size() > available_inline_size
             }
-        }
+        };
 
-        if !need_ellipsis {
+        if self.pending_line_is_truncated {
+            self.push_truncated_fragment_to_line(fragment);
+        } else if !need_ellipsis {
             self.push_fragment_to_line_ignoring_text_overflow(fragment, layout_context);
         } else {
+            // Keep as much of the fragment as fits alongside the ellipsis, and hide the rest. The
+            // hidden part stays in the fragment list so that the next reflow can bring it back.
             let ellipsis = fragment.transform_into_ellipsis(layout_context);
-            if let Some(truncation_info) =
-                    fragment.truncate_to_inline_size(available_inline_size -
-                                                     ellipsis.margin_box_inline_size()) {
-                let fragment = fragment.transform_with_split_info(&truncation_info.split,
-                                                                  truncation_info.text_run);
-                self.push_fragment_to_line_ignoring_text_overflow(fragment, layout_context);
-            }
+            let truncated_fragment =
+                match fragment.truncate_to_inline_size(available_inline_size -
+                                                       ellipsis.margin_box_inline_size()) {
+                    Some(truncation_info) => {
+                        let visible_fragment =
+                            fragment.transform_with_split_info(&truncation_info.split,
+                                                               truncation_info.text_run.clone());
+                        self.push_fragment_to_line_ignoring_text_overflow(visible_fragment,
+                                                                          layout_context);
+                        truncation_info.remainder.map(|remainder| {
+                            fragment.transform_with_split_info(&remainder,
+                                                               truncation_info.text_run)
+                        })
+                    }
+                    None => Some(fragment),
+                };
             self.push_fragment_to_line_ignoring_text_overflow(ellipsis, layout_context);
+
+            // Nothing after the ellipsis can be broken back onto the line.
+            self.pending_line_is_truncated = true;
+            self.last_known_line_breaking_opportunity = None;
+            if let Some(truncated_fragment) = truncated_fragment {
+                self.push_truncated_fragment_to_line(truncated_fragment)
+            }
         }
 
         if line_flush_mode == LineFlushMode::Flush {
@@ -740,6 +809,14 @@ impl LineBreaker {
         self.new_fragments.push(fragment);
     }
 
+    /// Pushes a fragment hidden by a `text-overflow` ellipsis to the current line. It takes up no
+    /// room on the line.
+    fn push_truncated_fragment_to_line(&mut self, mut fragment: Fragment) {
+        fragment.flags.insert(IS_TRUNCATED_BY_ELLIPSIS);
+        self.pending_line.range.extend_by(FragmentIndex(1));
+        self.new_fragments.push(fragment);
+    }
+
     fn split_line_at_last_known_good_position(&mut self,
                                               layout_context: &LayoutContext,
                                               cur_fragment: Fragment,
@@ -856,6 +933,15 @@ pub struct InlineFlow {
     /// (because percentages are relative to the containing block, and we aren't in a position to
     /// compute things relative to our parent's containing block).
     pub first_line_indentation: Au,
+
+    /// How content overflowing a line is rendered. This is determined by our block parent, since
+    /// `text-overflow` applies to block containers and only when they clip their overflow.
+    pub text_overflow: text_overflow::T,
+
+    /// The embedding level of the bidi paragraph our fragments form. This is determined by our
+    /// block parent, and for `unicode-bidi: plaintext` by the text itself, so it may differ from
+    /// the level of our writing mode.
+    pub bidi_paragraph_level: u8,
 }
 
 impl InlineFlow {
@@ -867,6 +953,8 @@ impl InlineFlow {
             minimum_block_size_above_baseline: Au(0),
             minimum_depth_below_baseline: Au(0),
             first_line_indentation: Au(0),
+            text_overflow: text_overflow::T::clip,
+            bidi_paragraph_level: writing_mode.to_bidi_level(),
         };
 
         if flow.fragments.fragments.iter().any(Fragment::is_unscanned_generated_content) {
@@ -882,7 +970,8 @@ impl InlineFlow {
                                      line: &Line,
                                      line_align: text_align::T,
                                      indentation: Au,
-                                     is_last_line: bool) {
+                                     is_last_line: bool,
+                                     is_ltr: bool) {
         // Figure out how much inline-size we have.
         let slack_inline_size = max(Au(0), line.green_zone.inline - line.bounds.size.inline);
 
@@ -892,8 +981,8 @@ impl InlineFlow {
         }
         let text_justify = fragments.fragments[0].style().get_inheritedtext().text_justify;
 
-        // Translate `left` and `right` to logical directions.
-        let is_ltr = fragments.fragments[0].style().writing_mode.is_bidi_ltr();
+        // Translate `left` and `right` to logical directions, per the direction of the paragraph
+        // rather than that of any one fragment in it.
         let line_align = match (line_align, is_ltr) {
             (text_align::T::left, true) |
             (text_align::T::servo_left, true) |
@@ -1090,8 +1179,7 @@ impl InlineFlow {
                     }
                     vertical_align::T::LengthOrPercentage(LengthOrPercentage::Calc(calc)) => {
                         let line_height = fragment.calculate_line_height(layout_context);
-                        let percentage_length = line_height.scale_by(calc.percentage());
-                        block_start = block_start - percentage_length - calc.length()
+                        block_start = block_start - calc.to_used_value(line_height)
                     }
                 }
             }
@@ -1107,7 +1195,8 @@ impl InlineFlow {
     /// `style` is the style of the block.
     pub fn compute_minimum_ascent_and_descent(&self,
                                               font_context: &mut FontContext,
-                                              style: &ServoComputedValues)
+                                              style: &ServoComputedValues,
+                                              quirks_mode: QuirksMode)
                                               -> (Au, Au) {
         // As a special case, if this flow contains only hypothetical fragments, then the entire
         // flow is hypothetical and takes up no space. See CSS 2.1 § 10.3.7.
@@ -1115,6 +1204,15 @@ impl InlineFlow {
             return (Au(0), Au(0))
         }
 
+        // In quirks mode, line boxes with neither text nor replaced content don't get the strut
+        // of the block or of their inline elements, which then only contribute their contents.
+        //
+        // https://quirks.spec.whatwg.org/#the-line-height-calculation-quirk
+        if quirks_mode == QuirksMode::Quirks &&
+                !self.fragments.fragments.iter().any(|fragment| fragment.is_text_or_replaced()) {
+            return (Au(0), Au(0))
+        }
+
         let font_style = style.get_font_arc();
         let font_metrics = text::font_metrics_for_style(font_context, font_style);
         let line_height = text::line_height_from_style(style, &font_metrics);
@@ -1451,18 +1549,21 @@ impl Flow for InlineFlow {
         let mut scanner = LineBreaker::new(self.base.floats.clone(),
                                            indentation,
                                            self.minimum_block_size_above_baseline,
-                                           self.minimum_depth_below_baseline);
+                                           self.minimum_depth_below_baseline,
+                                           self.text_overflow);
         scanner.scan_for_lines(self, layout_context);
 
         // Now, go through each line and lay out the fragments inside.
         let line_count = self.lines.len();
+        let is_ltr = unicode_bidi::is_ltr(self.bidi_paragraph_level);
         for (line_index, line) in self.lines.iter_mut().enumerate() {
             // Lay out fragments in the inline direction, and justify them if necessary.
             InlineFlow::set_inline_fragment_positions(&mut self.fragments,
                                                       line,
                                                       self.base.flags.text_align(),
                                                       indentation,
-                                                      line_index + 1 == line_count);
+                                                      line_index + 1 == line_count,
+                                                      is_ltr);
 
             // Compute the final positions in the block direction of each fragment.
             InlineFlow::set_block_fragment_positions(&mut self.fragments,
@@ -1545,7 +1646,7 @@ impl Flow for InlineFlow {
         //          <span style="display: inline-block">...</span>
         //      </span>
         let mut containing_block_positions = Vec::new();
-        let container_size = Size2D::new(self.base.block_container_inline_size, Au(0));
+        let container_size = self.base.block_container_physical_size();
         for (fragment_index, fragment) in self.fragments.fragments.iter().enumerate() {
             match fragment.specific {
                 SpecificFragmentInfo::InlineAbsolute(_) => {
@@ -1721,7 +1822,10 @@ impl Flow for InlineFlow {
 
     fn contains_relatively_positioned_fragments(&self) -> bool {
         self.fragments.fragments.iter().any(|fragment| {
-            fragment.style.get_box().position == position::T::relative
+            match fragment.style.get_box().position {
+                position::T::relative | position::T::sticky => true,
+                _ => false,
+            }
         })
     }
 
@@ -77,12 +77,15 @@ pub mod incremental;
 mod inline;
 mod linked_list;
 mod list_item;
+mod math;
 mod model;
 mod multicol;
-mod opaque_node;
+pub mod opaque_node;
+mod paged;
 pub mod parallel;
 mod persistent_list;
 pub mod query;
+mod ruby;
 pub mod sequential;
 mod table;
 mod table_caption;
@@ -97,5 +100,7 @@ pub mod webrender_helpers;
 pub mod wrapper;
 
 // For unit tests:
+pub use block::BlockFlow;
+pub use floats::FloatKind;
 pub use fragment::Fragment;
 pub use fragment::SpecificFragmentInfo;
@@ -0,0 +1,349 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Layout for the MathML elements that are not simple rows of text:
+//! https://www.w3.org/TR/MathML3/chapter3.html
+//!
+//! `mrow`, token elements and `mtable` are laid out by the ordinary inline and table code, thanks
+//! to the rules in `mathml.css`; this flow handles fractions, scripts and radicals.
+
+#![deny(unsafe_code)]
+
+use app_units::Au;
+use block::BlockFlow;
+use context::{LayoutContext, SharedLayoutContext};
+use display_list_builder::{DisplayListBuildState, MathFlowDisplayListBuilding};
+use euclid::Point2D;
+use floats::FloatKind;
+use flow::{self, Flow, FlowClass, ImmutableFlowUtils, OpaqueFlow, IS_ABSOLUTELY_POSITIONED};
+use fragment::{Fragment, FragmentBorderBoxIterator, Overflow};
+use gfx::display_list::StackingContext;
+use gfx_traits::StackingContextId;
+use gfx_traits::print_tree::PrintTree;
+use layout_debug;
+use model::IntrinsicISizes;
+use script_layout_interface::wrapper_traits::ThreadSafeLayoutElement;
+use std::cmp::max;
+use std::fmt;
+use std::sync::Arc;
+use style::context::SharedStyleContext;
+use style::logical_geometry::LogicalSize;
+use style::properties::ServoComputedValues;
+
+/// The ways in which `MathFlow` arranges its children.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MathLayoutKind {
+    /// `mfrac`: the numerator stacked over the denominator, separated by a rule.
+    Fraction,
+    /// `msub`, `msup` and `msubsup`: a base followed by scripts.
+    Scripts { subscript: bool, superscript: bool },
+    /// `msqrt`: the children laid out as a row under a radical sign.
+    Radical,
+}
+
+impl MathLayoutKind {
+    /// Returns how the given element is laid out, if it is one of the MathML elements handled
+    /// by `MathFlow`.
+    pub fn for_element<E>(element: &E) -> Option<MathLayoutKind>
+                          where E: ThreadSafeLayoutElement {
+        if *element.get_namespace() != ns!(mathml) {
+            return None
+        }
+        match &**element.get_local_name() {
+            "mfrac" => Some(MathLayoutKind::Fraction),
+            "msub" => Some(MathLayoutKind::Scripts { subscript: true, superscript: false }),
+            "msup" => Some(MathLayoutKind::Scripts { subscript: false, superscript: true }),
+            "msubsup" => Some(MathLayoutKind::Scripts { subscript: true, superscript: true }),
+            "msqrt" => Some(MathLayoutKind::Radical),
+            _ => None,
+        }
+    }
+}
+
+pub struct MathFlow {
+    pub block_flow: BlockFlow,
+
+    pub kind: MathLayoutKind,
+
+    /// The offset of the fraction rule or of the radical's overbar from the block-start edge of
+    /// the border box.
+    pub rule_offset: Au,
+
+    /// The thickness of the fraction rule and of the strokes of the radical sign.
+    pub rule_thickness: Au,
+
+    /// The offset of the baseline from the block-start edge of the border box, if the children
+    /// don't determine it.
+    pub baseline_offset: Option<Au>,
+}
+
+impl MathFlow {
+    pub fn from_fragment(fragment: Fragment, float_kind: Option<FloatKind>, kind: MathLayoutKind)
+                         -> MathFlow {
+        MathFlow {
+            block_flow: BlockFlow::from_fragment(fragment, float_kind),
+            kind: kind,
+            rule_offset: Au(0),
+            rule_thickness: Au(0),
+            baseline_offset: None,
+        }
+    }
+
+    /// The height of the math axis above the baseline. Fractions are centered on it.
+    fn axis_height(&self) -> Au {
+        self.block_flow.fragment.style.get_font().font_size / 4
+    }
+
+    /// Returns the index of the subscript and superscript children of a scripts element.
+    fn script_indices(subscript: bool, superscript: bool) -> (Option<usize>, Option<usize>) {
+        match (subscript, superscript) {
+            (true, true) => (Some(1), Some(2)),
+            (true, false) => (Some(1), None),
+            (false, true) => (None, Some(1)),
+            (false, false) => (None, None),
+        }
+    }
+
+    fn bubble_inline_sizes_for_scripts(&mut self) {
+        // The base and the scripts sit side by side, and the scripts stack.
+        let mut computation = self.block_flow.fragment.compute_intrinsic_inline_sizes();
+        let mut base_sizes = IntrinsicISizes::new();
+        let mut script_sizes = IntrinsicISizes::new();
+        for (index, kid) in self.block_flow.base.child_iter_mut().enumerate() {
+            let kid_base = flow::base(kid);
+            if kid_base.flags.contains(IS_ABSOLUTELY_POSITIONED) {
+                continue
+            }
+            let sizes = if index == 0 { &mut base_sizes } else { &mut script_sizes };
+            sizes.minimum_inline_size = max(sizes.minimum_inline_size,
+                                            kid_base.intrinsic_inline_sizes.minimum_inline_size);
+            sizes.preferred_inline_size =
+                max(sizes.preferred_inline_size,
+                    kid_base.intrinsic_inline_sizes.preferred_inline_size);
+        }
+        computation.union_nonbreaking_inline(&base_sizes);
+        computation.union_nonbreaking_inline(&script_sizes);
+        self.block_flow.base.intrinsic_inline_sizes = computation.finish();
+    }
+
+    fn assign_inline_sizes_for_scripts(&mut self) {
+        let inline_start_content_edge = self.block_flow.fragment.border_box.start.i +
+            self.block_flow.fragment.border_padding.inline_start;
+        let containing_block_mode = self.block_flow.base.writing_mode;
+
+        // Each child is as wide as its content, and the scripts start where the base ends.
+        let mut base_inline_size = Au(0);
+        for (index, kid) in self.block_flow.base.child_iter_mut().enumerate() {
+            let kid_base = flow::mut_base(kid);
+            let inline_size = kid_base.intrinsic_inline_sizes.preferred_inline_size;
+            kid_base.block_container_inline_size = inline_size;
+            kid_base.block_container_writing_mode = containing_block_mode;
+            kid_base.position.start.i = if index == 0 {
+                inline_start_content_edge
+            } else {
+                inline_start_content_edge + base_inline_size
+            };
+            if index == 0 {
+                base_inline_size = inline_size;
+            }
+        }
+    }
+
+    fn assign_block_size_for_fraction(&mut self) {
+        let border_padding_start = self.block_flow.fragment.border_padding.block_start;
+        let gap = self.rule_thickness * 2;
+
+        let mut block_position = border_padding_start;
+        for (index, kid) in self.block_flow.base.child_iter_mut().enumerate() {
+            let kid_base = flow::mut_base(kid);
+            if index == 1 {
+                self.rule_offset = block_position + gap;
+                block_position = self.rule_offset + self.rule_thickness + gap;
+            }
+            kid_base.position.start.b = block_position;
+            block_position = block_position + kid_base.position.size.block;
+        }
+        if self.block_flow.base.children.len() < 2 {
+            self.rule_offset = block_position + gap;
+            block_position = self.rule_offset + self.rule_thickness + gap;
+        }
+
+        self.baseline_offset = Some(self.rule_offset + self.rule_thickness / 2 +
+                                    self.axis_height());
+        self.set_content_block_size(block_position - border_padding_start);
+    }
+
+    fn assign_block_size_for_scripts(&mut self, subscript: bool, superscript: bool) {
+        let border_padding_start = self.block_flow.fragment.border_padding.block_start;
+        let (subscript_index, superscript_index) = MathFlow::script_indices(subscript,
+                                                                           superscript);
+        let block_size_of = |flow: &MathFlow, index: Option<usize>| {
+            index.and_then(|index| flow.block_flow.base.children.iter().nth(index))
+                 .map_or(Au(0), |kid| flow::base(kid).position.size.block)
+        };
+        let base_block_size = block_size_of(self, Some(0));
+        let subscript_block_size = block_size_of(self, subscript_index);
+        let superscript_block_size = block_size_of(self, superscript_index);
+
+        // The superscript is raised by half the base, and the subscript lowered by as much,
+        // without the two overlapping.
+        let base_start = max(Au(0), superscript_block_size - base_block_size / 2);
+        let subscript_start = max(base_start + base_block_size / 2, superscript_block_size);
+
+        let mut content_block_size = base_start + base_block_size;
+        let mut baseline_offset = None;
+        for (index, kid) in self.block_flow.base.child_iter_mut().enumerate() {
+            let start = if index == 0 {
+                baseline_offset = Some(border_padding_start + base_start +
+                                       kid.baseline_offset_of_last_line_box_in_flow()
+                                          .unwrap_or(base_block_size));
+                base_start
+            } else if Some(index) == subscript_index {
+                subscript_start
+            } else {
+                Au(0)
+            };
+            let kid_base = flow::mut_base(kid);
+            kid_base.position.start.b = border_padding_start + start;
+            content_block_size = max(content_block_size, start + kid_base.position.size.block);
+        }
+
+        self.baseline_offset = baseline_offset;
+        self.set_content_block_size(content_block_size);
+    }
+
+    fn set_content_block_size(&mut self, content_block_size: Au) {
+        let block_size = content_block_size +
+            self.block_flow.fragment.border_padding.block_start_end();
+        self.block_flow.fragment.border_box.size.block = block_size;
+        self.block_flow.base.position.size.block = block_size +
+            self.block_flow.fragment.margin.block_start_end();
+    }
+}
+
+impl Flow for MathFlow {
+    fn class(&self) -> FlowClass {
+        FlowClass::Math
+    }
+
+    fn as_block(&self) -> &BlockFlow {
+        &self.block_flow
+    }
+
+    fn as_mut_block(&mut self) -> &mut BlockFlow {
+        &mut self.block_flow
+    }
+
+    fn as_math(&self) -> &MathFlow {
+        self
+    }
+
+    fn mark_as_root(&mut self) {
+        self.block_flow.mark_as_root();
+    }
+
+    fn bubble_inline_sizes(&mut self) {
+        let _scope = layout_debug_scope!("math::bubble_inline_sizes {:x}",
+                                         self.block_flow.base.debug_id());
+
+        self.block_flow.bubble_inline_sizes();
+        if let MathLayoutKind::Scripts { .. } = self.kind {
+            self.bubble_inline_sizes_for_scripts()
+        }
+    }
+
+    fn assign_inline_sizes(&mut self, shared_context: &SharedStyleContext) {
+        let _scope = layout_debug_scope!("math::assign_inline_sizes {:x}",
+                                         self.block_flow.base.debug_id());
+        debug!("assign_inline_sizes({}): assigning inline_size for flow", "math");
+
+        self.rule_thickness = max(Au::from_px(1),
+                                  self.block_flow.fragment.style.get_font().font_size / 15);
+
+        // Fractions and radicals lay their children out as blocks do: `mathml.css` centers the
+        // numerator and the denominator.
+        self.block_flow.assign_inline_sizes(shared_context);
+        if let MathLayoutKind::Scripts { .. } = self.kind {
+            self.assign_inline_sizes_for_scripts()
+        }
+    }
+
+    fn assign_block_size<'a>(&mut self, layout_context: &'a LayoutContext<'a>) {
+        debug!("assign_block_size: assigning block_size for math");
+
+        self.block_flow.assign_block_size(layout_context);
+        match self.kind {
+            MathLayoutKind::Fraction => self.assign_block_size_for_fraction(),
+            MathLayoutKind::Scripts { subscript, superscript } => {
+                self.assign_block_size_for_scripts(subscript, superscript)
+            }
+            MathLayoutKind::Radical => {
+                // The overbar runs along the block-start padding, which `mathml.css` reserves.
+                self.rule_offset = max(Au(0),
+                                       self.block_flow.fragment.border_padding.block_start -
+                                       self.rule_thickness * 2);
+            }
+        }
+    }
+
+    fn compute_absolute_position(&mut self, layout_context: &SharedLayoutContext) {
+        self.block_flow.compute_absolute_position(layout_context)
+    }
+
+    fn place_float_if_applicable<'a>(&mut self) {
+        self.block_flow.place_float_if_applicable()
+    }
+
+    fn update_late_computed_inline_position_if_necessary(&mut self, inline_position: Au) {
+        self.block_flow.update_late_computed_inline_position_if_necessary(inline_position)
+    }
+
+    fn update_late_computed_block_position_if_necessary(&mut self, block_position: Au) {
+        self.block_flow.update_late_computed_block_position_if_necessary(block_position)
+    }
+
+    fn build_display_list(&mut self, state: &mut DisplayListBuildState) {
+        self.build_display_list_for_math(state);
+    }
+
+    fn collect_stacking_contexts(&mut self,
+                                 parent_id: StackingContextId,
+                                 contexts: &mut Vec<Box<StackingContext>>)
+                                 -> StackingContextId {
+        self.block_flow.collect_stacking_contexts(parent_id, contexts)
+    }
+
+    fn repair_style(&mut self, new_style: &Arc<ServoComputedValues>) {
+        self.block_flow.repair_style(new_style)
+    }
+
+    fn compute_overflow(&self) -> Overflow {
+        self.block_flow.compute_overflow()
+    }
+
+    fn generated_containing_block_size(&self, flow: OpaqueFlow) -> LogicalSize<Au> {
+        self.block_flow.generated_containing_block_size(flow)
+    }
+
+    fn iterate_through_fragment_border_boxes(&self,
+                                             iterator: &mut FragmentBorderBoxIterator,
+                                             level: i32,
+                                             stacking_context_position: &Point2D<Au>) {
+        self.block_flow.iterate_through_fragment_border_boxes(iterator, level, stacking_context_position);
+    }
+
+    fn mutate_fragments(&mut self, mutator: &mut FnMut(&mut Fragment)) {
+        self.block_flow.mutate_fragments(mutator);
+    }
+
+    fn print_extra_flow_children(&self, print_tree: &mut PrintTree) {
+        self.block_flow.print_extra_flow_children(print_tree);
+    }
+}
+
+impl fmt::Debug for MathFlow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MathFlow({:?}): {:?}", self.kind, self.block_flow)
+    }
+}
@@ -11,10 +11,11 @@ use euclid::{Matrix4D, SideOffsets2D, Size2D};
 use fragment::Fragment;
 use std::cmp::{max, min};
 use std::fmt;
+use style::computed_values::{overflow_x, scrollbar_gutter, scrollbar_width};
 use style::computed_values::transform::ComputedMatrix;
 use style::logical_geometry::LogicalMargin;
 use style::properties::ServoComputedValues;
-use style::values::computed::{BorderRadiusSize, LengthOrPercentageOrAuto};
+use style::values::computed::{BorderRadiusSize, ExtremumLength, LengthOrPercentageOrAuto};
 use style::values::computed::{LengthOrPercentage, LengthOrPercentageOrNone};
 
 /// A collapsible margin. See CSS 2.1 § 8.3.1.
@@ -408,7 +409,7 @@ impl MaybeAuto {
                 MaybeAuto::Specified(containing_length.scale_by(percent))
             }
             LengthOrPercentageOrAuto::Calc(calc) => {
-                MaybeAuto::Specified(calc.length() + containing_length.scale_by(calc.percentage()))
+                MaybeAuto::Specified(calc.to_used_value(containing_length))
             }
             LengthOrPercentageOrAuto::Length(length) => MaybeAuto::Specified(length)
         }
@@ -441,7 +442,7 @@ pub fn specified_or_none(length: LengthOrPercentageOrNone, containing_length: Au
         LengthOrPercentageOrNone::None => None,
         LengthOrPercentageOrNone::Percentage(percent) => Some(containing_length.scale_by(percent)),
         LengthOrPercentageOrNone::Calc(calc) =>
-            Some(containing_length.scale_by(calc.percentage()) + calc.length()),
+            Some(calc.to_used_value(containing_length)),
         LengthOrPercentageOrNone::Length(length) => Some(length),
     }
 }
@@ -451,7 +452,36 @@ pub fn specified(length: LengthOrPercentage, containing_length: Au) -> Au {
         LengthOrPercentage::Length(length) => length,
         LengthOrPercentage::Percentage(p) => containing_length.scale_by(p),
         LengthOrPercentage::Calc(calc) =>
-            containing_length.scale_by(calc.percentage()) + calc.length(),
+            calc.to_used_value(containing_length),
+    }
+}
+
+/// Returns the size that a sizing keyword gives a box with the given min-content and max-content
+/// sizes, when `available_size` is available to it.
+///
+/// https://drafts.csswg.org/css-sizing-3/#sizing-values
+pub fn size_for_keyword(keyword: ExtremumLength, min_content: Au, max_content: Au, available_size: Au)
+                        -> Au {
+    match keyword {
+        ExtremumLength::MinContent => min_content,
+        ExtremumLength::MaxContent => max_content,
+        ExtremumLength::FitContent => max(min_content, min(max_content, available_size)),
+    }
+}
+
+/// Returns the ratio of the inline size to the block size that a box with the given style should
+/// have, if any. `natural_ratio` is the same ratio for the content of a replaced element.
+///
+/// https://drafts.csswg.org/css-sizing-4/#aspect-ratio
+pub fn aspect_ratio(style: &ServoComputedValues, natural_ratio: Option<f32>) -> Option<f32> {
+    let aspect_ratio = style.get_position().aspect_ratio;
+    if aspect_ratio.auto && natural_ratio.is_some() {
+        return natural_ratio
+    }
+    match aspect_ratio.ratio() {
+        Some(ratio) if style.writing_mode.is_vertical() => Some(1. / ratio),
+        Some(ratio) => Some(ratio),
+        None => natural_ratio,
     }
 }
 
@@ -473,6 +503,50 @@ pub fn padding_from_style(style: &ServoComputedValues, containing_block_inline_s
         specified(padding_style.padding_left, containing_block_inline_size)))
 }
 
+/// Returns the thickness of the scrollbars that layout paints for the given style.
+///
+/// https://drafts.csswg.org/css-scrollbars-1/#scrollbar-width
+pub fn scrollbar_thickness(style: &ServoComputedValues) -> Au {
+    match style.get_box().scrollbar_width {
+        scrollbar_width::T::auto => Au::from_px(15),
+        scrollbar_width::T::thin => Au::from_px(8),
+        scrollbar_width::T::none => Au(0),
+    }
+}
+
+/// Returns the space that `scrollbar-gutter` reserves for the block-axis scrollbar, which sits
+/// between the inner border edge and the outer padding edge. Only scroll containers reserve it.
+///
+/// https://drafts.csswg.org/css-overflow-4/#scrollbar-gutter-property
+pub fn scrollbar_gutter_from_style(style: &ServoComputedValues) -> LogicalMargin<Au> {
+    let zero = LogicalMargin::zero(style.writing_mode);
+    let block_axis_overflow = if style.writing_mode.is_vertical() {
+        style.get_box().overflow_x
+    } else {
+        style.get_box().overflow_y.0
+    };
+    match block_axis_overflow {
+        overflow_x::T::hidden | overflow_x::T::scroll | overflow_x::T::auto => {}
+        overflow_x::T::visible | overflow_x::T::clip => return zero,
+    }
+
+    // With `auto`, only an always-visible scrollbar takes up space. We don't know whether
+    // `overflow: auto` will overflow until after layout, so it doesn't reserve any.
+    let thickness = scrollbar_thickness(style);
+    match style.get_box().scrollbar_gutter {
+        scrollbar_gutter::T::Auto if block_axis_overflow == overflow_x::T::scroll => {
+            LogicalMargin::new(style.writing_mode, Au(0), thickness, Au(0), Au(0))
+        }
+        scrollbar_gutter::T::Auto => zero,
+        scrollbar_gutter::T::Stable => {
+            LogicalMargin::new(style.writing_mode, Au(0), thickness, Au(0), Au(0))
+        }
+        scrollbar_gutter::T::StableBothEdges => {
+            LogicalMargin::new(style.writing_mode, Au(0), thickness, Au(0), thickness)
+        }
+    }
+}
+
 /// Returns the explicitly-specified margin lengths from the given style. Percentage and auto
 /// margins are returned as zero.
 ///
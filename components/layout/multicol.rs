@@ -6,22 +6,26 @@
 
 #![deny(unsafe_code)]
 
-use app_units::Au;
+use app_units::{Au, MAX_AU};
 use block::BlockFlow;
 use context::{LayoutContext, SharedLayoutContext};
-use display_list_builder::DisplayListBuildState;
+use display_list_builder::{DisplayListBuildState, MulticolFlowDisplayListBuilding};
 use euclid::Point2D;
 use euclid::Size2D;
 use floats::FloatKind;
-use flow::{Flow, FlowClass, OpaqueFlow, mut_base, FragmentationContext};
+use flow::{self, Flow, FlowClass, OpaqueFlow, mut_base, FragmentationContext};
+use flow::IS_ABSOLUTELY_POSITIONED;
 use flow_ref::{self, FlowRef};
 use fragment::{Fragment, FragmentBorderBoxIterator, Overflow};
 use gfx::display_list::StackingContext;
 use gfx_traits::StackingContextId;
 use gfx_traits::print_tree::PrintTree;
+use model;
+use script_layout_interface::restyle_damage::{REFLOW, REFLOW_OUT_OF_FLOW};
 use std::cmp::{min, max};
 use std::fmt;
 use std::sync::Arc;
+use style::computed_values::column_fill;
 use style::context::{StyleContext, SharedStyleContext};
 use style::logical_geometry::LogicalSize;
 use style::properties::ServoComputedValues;
@@ -33,6 +37,12 @@ pub struct MulticolFlow {
     /// Length between the inline-start edge of a column and that of the next.
     /// That is, the used column-width + used column-gap.
     pub column_pitch: Au,
+
+    /// The used column-gap.
+    pub column_gap: Au,
+
+    /// The number of columns the content is balanced over.
+    pub column_count: i32,
 }
 
 pub struct MulticolColumnFlow {
@@ -44,6 +54,8 @@ impl MulticolFlow {
         MulticolFlow {
             block_flow: BlockFlow::from_fragment(fragment, float_kind),
             column_pitch: Au(0),
+            column_gap: Au(0),
+            column_count: 1,
         }
     }
 }
@@ -100,8 +112,10 @@ impl Flow for MulticolFlow {
             let column_style = self.block_flow.fragment.style.get_column();
 
             // `None` is 'normal': "UA-specified length. A value of 1em is suggested."
-            let column_gap = column_style.column_gap.0.unwrap_or_else(||
-                self.block_flow.fragment.style.get_font().font_size);
+            let column_gap = match column_style.column_gap.0 {
+                Some(column_gap) => model::specified(column_gap, content_inline_size),
+                None => self.block_flow.fragment.style.get_font().font_size,
+            };
             let mut column_count;
             if let Some(column_width) = column_style.column_width.0 {
                 column_count =
@@ -115,6 +129,8 @@ impl Flow for MulticolFlow {
             column_width =
                 max(Au(0), (content_inline_size + column_gap) / column_count - column_gap);
             self.column_pitch = column_width + column_gap;
+            self.column_gap = column_gap;
+            self.column_count = column_count;
         }
 
         self.block_flow.fragment.border_box.size.inline = content_inline_size + padding_and_borders;
@@ -127,24 +143,20 @@ impl Flow for MulticolFlow {
     fn assign_block_size<'a>(&mut self, ctx: &'a LayoutContext<'a>) {
         debug!("assign_block_size: assigning block_size for multicol");
 
-        let fragmentation_context = Some(FragmentationContext {
-            this_fragment_is_empty: true,
-            available_block_size: {
-                let style = &self.block_flow.fragment.style;
-                if let LengthOrPercentageOrAuto::Length(length) = style.content_block_size() {
-                    length
-                } else if let LengthOrPercentageOrNone::Length(length) = style.max_block_size() {
-                    length
-                } else {
-                    // FIXME: do column balancing instead
-                    // FIXME: (until column balancing) substract margins/borders/padding
-                    LogicalSize::from_physical(
-                        self.block_flow.base.writing_mode,
-                        ctx.shared_context().viewport_size,
-                    ).block
-                }
-            }
-        });
+        let (specified_block_size, balance) = {
+            let style = &self.block_flow.fragment.style;
+            let specified_block_size = if let LengthOrPercentageOrAuto::Length(length) = style.content_block_size() {
+                Some(length)
+            } else if let LengthOrPercentageOrNone::Length(length) = style.max_block_size() {
+                Some(length)
+            } else {
+                None
+            };
+            // Columns of a box without a block size can only be balanced.
+            let balance = style.get_column().column_fill == column_fill::T::balance ||
+                specified_block_size.is_none();
+            (specified_block_size, balance)
+        };
 
         // Before layout, everything is in a single "column"
         assert!(self.block_flow.base.children.len() == 1);
@@ -153,6 +165,29 @@ impl Flow for MulticolFlow {
         // Pretend there is no children for this:
         self.block_flow.assign_block_size(ctx);
 
+        let available_block_size = if balance {
+            // Measure the content in a single unbroken column first, then lay it out again in
+            // the shortest columns that hold all of it.
+            let unbroken = Some(FragmentationContext {
+                this_fragment_is_empty: true,
+                available_block_size: MAX_AU,
+                is_paged: false,
+            });
+            let remaining = flow_ref::deref_mut(&mut column).fragment(ctx, unbroken);
+            debug_assert!(remaining.is_none());
+            let balanced = balanced_column_block_size(&*column, self.column_count);
+            mark_for_relayout(flow_ref::deref_mut(&mut column));
+            specified_block_size.map_or(balanced, |length| min(length, balanced))
+        } else {
+            specified_block_size.unwrap()
+        };
+
+        let fragmentation_context = Some(FragmentationContext {
+            this_fragment_is_empty: true,
+            available_block_size: available_block_size,
+            is_paged: false,
+        });
+
         loop {
             let remaining = flow_ref::deref_mut(&mut column).fragment(ctx, fragmentation_context);
             self.block_flow.base.children.push_back(column);
@@ -161,6 +196,17 @@ impl Flow for MulticolFlow {
                 None => break
             };
         }
+
+        // Without a block size of its own, the container is as tall as its tallest column.
+        if self.block_flow.fragment.style.content_block_size() == LengthOrPercentageOrAuto::Auto {
+            let content_block_size = self.block_flow.base.children.iter()
+                .map(|column| flow::base(column).position.size.block)
+                .max()
+                .unwrap_or(Au(0));
+            let block_size = content_block_size + self.block_flow.fragment.border_padding.block_start_end();
+            self.block_flow.fragment.border_box.size.block = block_size;
+            self.block_flow.base.position.size.block = block_size;
+        }
     }
 
     fn compute_absolute_position(&mut self, layout_context: &SharedLayoutContext) {
@@ -183,7 +229,7 @@ impl Flow for MulticolFlow {
 
     fn build_display_list(&mut self, state: &mut DisplayListBuildState) {
         debug!("build_display_list_multicol");
-        self.block_flow.build_display_list(state);
+        self.build_display_list_for_multicol(state);
     }
 
     fn collect_stacking_contexts(&mut self,
@@ -306,6 +352,62 @@ impl Flow for MulticolColumnFlow {
     }
 }
 
+/// Returns the smallest column block size that fits the content of `column`, laid out without
+/// breaks, into `column_count` columns. Breaks are assumed to happen between the children of the
+/// column, or anywhere inside a child taller than a column.
+/// https://drafts.csswg.org/css-multicol/#cf
+fn balanced_column_block_size(column: &Flow, column_count: i32) -> Au {
+    let children: Vec<(Au, Au)> = flow::child_iter(column)
+        .map(|kid| flow::base(kid))
+        .filter(|kid| !kid.flags.contains(IS_ABSOLUTELY_POSITIONED))
+        .map(|kid| (kid.position.start.b, kid.position.start.b + kid.position.size.block))
+        .collect();
+    let total_block_size = flow::base(column).position.size.block;
+    if total_block_size == Au(0) || column_count <= 1 {
+        return total_block_size
+    }
+
+    let fits = |block_size: Au| {
+        let mut columns = 1;
+        let mut column_start = children.first().map_or(Au(0), |&(start, _)| start);
+        for &(start, end) in &children {
+            if end - column_start > block_size && start > column_start {
+                columns += 1;
+                column_start = start;
+            }
+            while end - column_start > block_size {
+                columns += 1;
+                column_start = column_start + block_size;
+            }
+        }
+        columns <= column_count
+    };
+
+    // Binary search between an even split of the content and a single column.
+    let mut low = (total_block_size + Au(column_count - 1)) / column_count;
+    let mut high = total_block_size;
+    if fits(low) {
+        return low
+    }
+    while high - low > Au(1) {
+        let middle = (low + high) / 2;
+        if fits(middle) {
+            high = middle
+        } else {
+            low = middle
+        }
+    }
+    high
+}
+
+/// Marks `flow` and its descendants as needing layout again.
+pub fn mark_for_relayout(flow: &mut Flow) {
+    flow::mut_base(flow).restyle_damage.insert(REFLOW | REFLOW_OUT_OF_FLOW);
+    for kid in flow::child_iter_mut(flow) {
+        mark_for_relayout(kid)
+    }
+}
+
 impl fmt::Debug for MulticolFlow {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "MulticolFlow: {:?}", self.block_flow)
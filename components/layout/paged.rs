@@ -0,0 +1,283 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Paged media: https://drafts.csswg.org/css-page-3/
+//!
+//! With `--paginate`, the root element is laid out in a `PagedFlow`, which breaks its flow into
+//! one fragment per page box. The pages are stacked one under the other, each inset by its
+//! margins, and the contents of the margin boxes are painted into the margins as headers and
+//! footers.
+
+#![deny(unsafe_code)]
+
+use app_units::Au;
+use block::BlockFlow;
+use context::{LayoutContext, SharedLayoutContext};
+use display_list_builder::{DisplayListBuildState, PagedFlowDisplayListBuilding};
+use euclid::{Point2D, Rect, Size2D};
+use flow::{self, Flow, FlowClass, FragmentationContext, OpaqueFlow};
+use flow_ref;
+use fragment::{Fragment, FragmentBorderBoxIterator, Overflow, SpecificFragmentInfo};
+use fragment::UnscannedTextFragmentInfo;
+use gfx::display_list::StackingContext;
+use gfx_traits::StackingContextId;
+use gfx_traits::print_tree::PrintTree;
+use multicol::mark_for_relayout;
+use script_layout_interface::restyle_damage::RestyleDamage;
+use script_layout_interface::wrapper_traits::{PseudoElementType, ThreadSafeLayoutElement};
+use std::collections::LinkedList;
+use std::fmt;
+use std::sync::Arc;
+use style::computed_values::display;
+use style::context::SharedStyleContext;
+use style::logical_geometry::{LogicalRect, LogicalSize};
+use style::page::{PageMarginBoxType, PageStyle, margin_box_text};
+use style::properties::{self, ServoComputedValues};
+use text::TextRunScanner;
+
+pub struct PagedFlow {
+    pub block_flow: BlockFlow,
+
+    /// The used style of each page, in order.
+    pub pages: Vec<PageStyle>,
+
+    /// The text of the margin boxes of all the pages, positioned relative to this flow.
+    pub margin_box_fragments: Vec<Fragment>,
+}
+
+impl PagedFlow {
+    /// Returns true if the given element is the root element that `PagedFlow` breaks into pages.
+    pub fn is_root_element<E>(element: &E) -> bool where E: ThreadSafeLayoutElement {
+        *element.get_namespace() == ns!(html) && &**element.get_local_name() == "html"
+    }
+
+    /// Creates a paged flow from the fragment of the root element. The fragment takes an
+    /// anonymous style, so that only the pages draw the box of the root element.
+    pub fn from_fragment(fragment: Fragment) -> PagedFlow {
+        PagedFlow {
+            block_flow: BlockFlow::from_fragment(fragment, None),
+            pages: vec![],
+            margin_box_fragments: vec![],
+        }
+    }
+
+    /// Lays out the text of the margin boxes of every page.
+    ///
+    /// FIXME: Margin boxes are sized to their text and only hold a single line.
+    fn build_margin_box_fragments(&mut self, layout_context: &LayoutContext) {
+        self.margin_box_fragments.clear();
+        let style = self.block_flow.fragment.style.clone();
+        let writing_mode = style.writing_mode;
+        let container_size = Size2D::new(self.pages.first().map_or(Au(0), |page| page.size.width),
+                                         self.block_flow.fragment.border_box.size.block);
+        let page_count = self.pages.len();
+        let mut page_top = Au(0);
+        for (page_index, page) in self.pages.iter().enumerate() {
+            for margin_box in &page.margin_boxes {
+                let text = margin_box_text(&margin_box.content, page_index, page_count);
+                if text.is_empty() {
+                    continue
+                }
+                let mut fragments = LinkedList::new();
+                fragments.push_back(Fragment::from_opaque_node_and_style(
+                    self.block_flow.fragment.node,
+                    PseudoElementType::Normal,
+                    style.clone(),
+                    style.clone(),
+                    RestyleDamage::rebuild_and_reflow(),
+                    SpecificFragmentInfo::UnscannedText(box UnscannedTextFragmentInfo::new(text, None))));
+                let fragments = TextRunScanner::new().scan_for_runs(&mut layout_context.font_context(),
+                                                                    fragments);
+                let mut fragment = match fragments.fragments.into_iter().next() {
+                    Some(fragment) => fragment,
+                    None => continue,
+                };
+
+                let metrics = fragment.inline_metrics(layout_context);
+                let inline_size = fragment.compute_intrinsic_inline_sizes()
+                                          .content_intrinsic_sizes
+                                          .preferred_inline_size;
+                let size = LogicalSize::new(writing_mode,
+                                            inline_size,
+                                            metrics.ascent + metrics.depth_below_baseline);
+                let size = size.to_physical(writing_mode);
+                let origin = margin_box_origin(page, margin_box.box_type, &size);
+                let rect = Rect::new(Point2D::new(origin.x, page_top + origin.y), size);
+                fragment.border_box = LogicalRect::from_physical(writing_mode, rect, container_size);
+                self.margin_box_fragments.push(fragment);
+            }
+            page_top = page_top + page.size.height;
+        }
+    }
+}
+
+/// Returns where a margin box of the given size goes on a page: centered in the top or bottom
+/// margin, and against the left or right margin or centered on the page.
+fn margin_box_origin(page: &PageStyle, box_type: PageMarginBoxType, size: &Size2D<Au>) -> Point2D<Au> {
+    let x = match box_type {
+        PageMarginBoxType::TopLeft | PageMarginBoxType::BottomLeft => page.margins.left,
+        PageMarginBoxType::TopCenter | PageMarginBoxType::BottomCenter => {
+            (page.size.width - size.width) / 2
+        }
+        PageMarginBoxType::TopRight | PageMarginBoxType::BottomRight => {
+            page.size.width - page.margins.right - size.width
+        }
+    };
+    let y = if box_type.is_top() {
+        (page.margins.top - size.height) / 2
+    } else {
+        page.size.height - page.margins.bottom + (page.margins.bottom - size.height) / 2
+    };
+    Point2D::new(x, y)
+}
+
+/// Gives the fragment of a `PagedFlow` the anonymous style it is built with.
+pub fn anonymous_style_for_pages(style: &mut Arc<ServoComputedValues>) {
+    properties::modify_style_for_anonymous_flow(style, display::T::block);
+}
+
+impl Flow for PagedFlow {
+    fn class(&self) -> FlowClass {
+        FlowClass::Paged
+    }
+
+    fn as_mut_block(&mut self) -> &mut BlockFlow {
+        &mut self.block_flow
+    }
+
+    fn as_block(&self) -> &BlockFlow {
+        &self.block_flow
+    }
+
+    fn mark_as_root(&mut self) {
+        self.block_flow.mark_as_root();
+    }
+
+    fn bubble_inline_sizes(&mut self) {
+        self.block_flow.bubble_inline_sizes();
+    }
+
+    fn assign_inline_sizes(&mut self, shared_context: &SharedStyleContext) {
+        debug!("assign_inline_sizes({}): assigning inline_size for flow", "paged");
+        self.block_flow.assign_inline_sizes(shared_context);
+
+        // FIXME: Every page is laid out as wide as the first one. Pages of other widths would
+        // need the content that goes in them laid out again.
+        let first_page = shared_context.stylist.page_style(0);
+        let writing_mode = self.block_flow.base.writing_mode;
+        let page_size = LogicalSize::from_physical(writing_mode, first_page.size);
+        let content_size = LogicalSize::from_physical(writing_mode, first_page.content_box().size);
+        self.block_flow.fragment.border_box.size.inline = page_size.inline;
+        self.block_flow.propagate_assigned_inline_size_to_children(
+            shared_context, Au(0), Au(0), content_size.inline, |_, _, _, _, _, _| {});
+    }
+
+    fn assign_block_size<'a>(&mut self, ctx: &'a LayoutContext<'a>) {
+        debug!("assign_block_size: assigning block_size for paged flow");
+
+        // Before layout, the root element is on a single page.
+        assert!(self.block_flow.base.children.len() == 1);
+        let mut page = self.block_flow.base.children.pop_front().unwrap();
+
+        // Pretend there is no children for this:
+        self.block_flow.assign_block_size(ctx);
+
+        // The traversal laid the content out without breaks; lay it out again into pages.
+        mark_for_relayout(flow_ref::deref_mut(&mut page));
+
+        let writing_mode = self.block_flow.base.writing_mode;
+        self.pages.clear();
+        loop {
+            let page_style = ctx.style_context().stylist.page_style(self.pages.len());
+            let content_size = LogicalSize::from_physical(writing_mode, page_style.content_box().size);
+            let fragmentation_context = Some(FragmentationContext {
+                available_block_size: content_size.block,
+                this_fragment_is_empty: true,
+                is_paged: true,
+            });
+            let remaining = flow_ref::deref_mut(&mut page).fragment(ctx, fragmentation_context);
+            self.block_flow.base.children.push_back(page);
+            self.pages.push(page_style);
+            page = match remaining {
+                Some(remaining) => remaining,
+                None => break
+            };
+        }
+
+        // The pages are stacked one under the other.
+        let block_size = self.pages.iter().fold(Au(0), |block_size, page| block_size + page.size.height);
+        self.block_flow.fragment.border_box.size.block = block_size;
+        self.block_flow.base.position.size.block = block_size;
+
+        self.build_margin_box_fragments(ctx);
+    }
+
+    fn compute_absolute_position(&mut self, layout_context: &SharedLayoutContext) {
+        self.block_flow.compute_absolute_position(layout_context);
+
+        // Each page starts where the one before it ends, and its content is inset by its margins.
+        let mut page_top = Au(0);
+        for (page, page_style) in self.block_flow.base.children.iter_mut().zip(&self.pages) {
+            let content_origin = page_style.content_box().origin;
+            let point = &mut flow::mut_base(page).stacking_relative_position;
+            *point = *point + Size2D::new(content_origin.x, page_top + content_origin.y);
+            page_top = page_top + page_style.size.height;
+        }
+    }
+
+    fn update_late_computed_inline_position_if_necessary(&mut self, inline_position: Au) {
+        self.block_flow.update_late_computed_inline_position_if_necessary(inline_position)
+    }
+
+    fn update_late_computed_block_position_if_necessary(&mut self, block_position: Au) {
+        self.block_flow.update_late_computed_block_position_if_necessary(block_position)
+    }
+
+    fn build_display_list(&mut self, state: &mut DisplayListBuildState) {
+        debug!("build_display_list_paged");
+        self.build_display_list_for_paged(state);
+    }
+
+    fn collect_stacking_contexts(&mut self,
+                                 parent_id: StackingContextId,
+                                 contexts: &mut Vec<Box<StackingContext>>)
+                                 -> StackingContextId {
+        self.block_flow.collect_stacking_contexts(parent_id, contexts)
+    }
+
+    fn repair_style(&mut self, new_style: &Arc<ServoComputedValues>) {
+        let mut style = new_style.clone();
+        anonymous_style_for_pages(&mut style);
+        self.block_flow.repair_style(&style)
+    }
+
+    fn compute_overflow(&self) -> Overflow {
+        self.block_flow.compute_overflow()
+    }
+
+    fn generated_containing_block_size(&self, flow: OpaqueFlow) -> LogicalSize<Au> {
+        self.block_flow.generated_containing_block_size(flow)
+    }
+
+    fn iterate_through_fragment_border_boxes(&self,
+                                             iterator: &mut FragmentBorderBoxIterator,
+                                             level: i32,
+                                             stacking_context_position: &Point2D<Au>) {
+        self.block_flow.iterate_through_fragment_border_boxes(iterator, level, stacking_context_position);
+    }
+
+    fn mutate_fragments(&mut self, mutator: &mut FnMut(&mut Fragment)) {
+        self.block_flow.mutate_fragments(mutator);
+    }
+
+    fn print_extra_flow_children(&self, print_tree: &mut PrintTree) {
+        self.block_flow.print_extra_flow_children(print_tree);
+    }
+}
+
+impl fmt::Debug for PagedFlow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PagedFlow: {:?}", self.block_flow)
+    }
+}
@@ -9,17 +9,20 @@
 #![allow(unsafe_code)]
 
 use context::{LayoutContext, SharedLayoutContext};
+use display_list_builder::DisplayListBuildState;
 use flow::{self, Flow, MutableFlowUtils, PostorderFlowTraversal, PreorderFlowTraversal};
 use flow_ref::FlowRef;
+use gfx::display_list::{DisplayItem, StackingContext};
 use profile_traits::time::{self, TimerMetadata, profile};
 use std::mem;
+use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicIsize, Ordering};
 use style::dom::UnsafeNode;
 use style::parallel::{CHUNK_SIZE, WorkQueueData};
 use style::parallel::run_queue_with_custom_work_data_type;
 use style::workqueue::{WorkQueue, WorkUnit, WorkerProxy};
 use traversal::{AssignISizes, BubbleISizes};
-use traversal::AssignBSizes;
+use traversal::{AssignBSizes, BuildDisplayList};
 use util::opts;
 
 pub use style::parallel::traverse_dom;
@@ -241,3 +244,91 @@ pub fn traverse_flow_tree_preorder(
         });
     }, shared_layout_context);
 }
+
+/// The display items that one work unit of the parallel display list construction built, in
+/// tree order, with the subtrees that it left to other work units in their place.
+type DisplayListParts = Mutex<Vec<DisplayListPart>>;
+
+enum DisplayListPart {
+    Items(Vec<DisplayItem>),
+    Subtree(Arc<DisplayListParts>),
+}
+
+type DisplayListWorkData = (Box<UnsafeFlow>, Arc<DisplayListParts>);
+
+/// Builds the display items of a flow and its descendants, except for the descendants that
+/// establish a stacking context, which are pushed as new work units.
+fn build_display_list(data: DisplayListWorkData,
+                      proxy: &mut WorkerProxy<SharedLayoutContext, DisplayListWorkData>) {
+    let (unsafe_flow, parts) = data;
+
+    // Get a real flow.
+    let flow: &mut Flow = unsafe {
+        mem::transmute(*unsafe_flow)
+    };
+
+    let mut deferred_flows = Vec::new();
+    {
+        let mut traversal = BuildDisplayList {
+            state: DisplayListBuildState::new(proxy.user_data(),
+                                              flow::base(flow).stacking_context_id),
+        };
+        let mut parts = parts.lock().unwrap();
+        traversal.traverse_deferring_stacking_contexts(flow, &mut |state: &mut DisplayListBuildState,
+                                                                   kid: &mut Flow| {
+            let subtree = Arc::new(Mutex::new(Vec::new()));
+            parts.push(DisplayListPart::Items(mem::replace(&mut state.items, Vec::new())));
+            parts.push(DisplayListPart::Subtree(subtree.clone()));
+            deferred_flows.push((box borrowed_flow_to_unsafe_flow(kid), subtree));
+        });
+        parts.push(DisplayListPart::Items(traversal.state.items));
+    }
+
+    for data in deferred_flows {
+        proxy.push(WorkUnit {
+            fun: build_display_list,
+            data: data,
+        });
+    }
+}
+
+/// Appends the display items that the work units of the parallel display list construction
+/// built to `items`, in tree order.
+fn append_display_list_parts(parts: &DisplayListParts, items: &mut Vec<DisplayItem>) {
+    for part in parts.lock().unwrap().drain(..) {
+        match part {
+            DisplayListPart::Items(part_items) => items.extend(part_items),
+            DisplayListPart::Subtree(subtree) => append_display_list_parts(&subtree, items),
+        }
+    }
+}
+
+/// Builds the display items of the flow tree, building the subtrees that establish a stacking
+/// context in parallel.
+///
+/// The items of each subtree are stitched back in where the subtree is in the flow tree, so
+/// they are in the same order as if they had been built sequentially, and `DisplayList::new()`
+/// sorts them into the same paint order.
+pub fn build_display_list_for_subtree(
+        flow_root: &mut Flow,
+        root_stacking_context: &mut StackingContext,
+        shared_layout_context: &SharedLayoutContext,
+        queue: &mut WorkQueue<SharedLayoutContext, WorkQueueData>)
+        -> Vec<DisplayItem> {
+    let mut children = vec![];
+    flow_root.collect_stacking_contexts(root_stacking_context.id,
+                                        &mut children);
+    root_stacking_context.add_children(children);
+
+    let parts = Arc::new(Mutex::new(Vec::new()));
+    run_queue_with_custom_work_data_type(queue, |queue| {
+        queue.push(WorkUnit {
+            fun: build_display_list,
+            data: (box borrowed_flow_to_unsafe_flow(flow_root), parts.clone()),
+        })
+    }, shared_layout_context);
+
+    let mut items = Vec::new();
+    append_display_list_parts(&parts, &mut items);
+    items
+}
@@ -19,16 +19,18 @@ use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse};
 use script_layout_interface::rpc::{HitTestResponse, LayoutRPC};
 use script_layout_interface::rpc::{MarginStyleResponse, NodeGeometryResponse};
 use script_layout_interface::rpc::{NodeLayerIdResponse, NodeOverflowResponse};
-use script_layout_interface::rpc::{OffsetParentResponse, ResolvedStyleResponse};
-use script_layout_interface::wrapper_traits::{LayoutNode, ThreadSafeLayoutNode};
+use script_layout_interface::rpc::{OffsetParentResponse, ResolvedStyleResponse, TextIndexResponse};
+use script_layout_interface::wrapper_traits::{LayoutNode, PseudoElementType, ThreadSafeLayoutNode};
 use script_traits::LayoutMsg as ConstellationMsg;
 use script_traits::UntrustedNodeAddress;
 use sequential;
 use std::cmp::{min, max};
+use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 use string_cache::Atom;
 use style::computed_values;
+use style::container_queries;
 use style::context::StyleContext;
 use style::logical_geometry::{WritingMode, BlockFlowDirection, InlineBaseDirection};
 use style::properties::longhands::{display, position};
@@ -81,6 +83,9 @@ pub struct LayoutThreadData {
     /// A queued response for the offset parent/rect of a node.
     pub margin_style_response: MarginStyleResponse,
 
+    /// A queued response for the index of the character of a text node at a point.
+    pub text_index_response: TextIndexResponse,
+
     /// Scroll offsets of stacking contexts. This will only be populated if WebRender is in use.
     pub stacking_context_scroll_offsets: ScrollOffsetMap,
 }
@@ -205,6 +210,12 @@ impl LayoutRPC for LayoutRPCImpl {
         let rw_data = rw_data.lock().unwrap();
         rw_data.margin_style_response.clone()
     }
+
+    fn text_index(&self) -> TextIndexResponse {
+        let &LayoutRPCImpl(ref rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        TextIndexResponse(rw_data.text_index_response.0)
+    }
 }
 
 struct UnioningFragmentBorderBoxIterator {
@@ -262,6 +273,25 @@ impl FragmentBorderBoxIterator for CollectingFragmentBorderBoxIterator {
     }
 }
 
+/// Collects the content-box sizes of the query containers.
+struct ContainerSizeFragmentBorderBoxIterator {
+    sizes: HashMap<OpaqueNode, Size2D<Au>>,
+}
+
+impl FragmentBorderBoxIterator for ContainerSizeFragmentBorderBoxIterator {
+    fn process(&mut self, fragment: &Fragment, _: i32, border_box: &Rect<Au>) {
+        let border_padding = fragment.border_padding.to_physical(fragment.style.writing_mode);
+        let size = Size2D::new(border_box.size.width - border_padding.horizontal(),
+                               border_box.size.height - border_padding.vertical());
+        self.sizes.entry(fragment.node).or_insert(size);
+    }
+
+    fn should_process(&mut self, fragment: &Fragment) -> bool {
+        fragment.pseudo == PseudoElementType::Normal &&
+        container_queries::is_query_container(&*fragment.style, None)
+    }
+}
+
 enum Side {
     Left,
     Right,
@@ -385,6 +415,63 @@ pub fn process_content_box_request<N: LayoutNode>(
     }
 }
 
+/// Finds the character of a text node under a point, counting the characters rendered for the
+/// node in the fragments before it.
+struct TextIndexIterator {
+    node_address: OpaqueNode,
+    point: Point2D<Au>,
+    preceding_characters: usize,
+    index: Option<usize>,
+}
+
+impl FragmentBorderBoxIterator for TextIndexIterator {
+    fn process(&mut self, fragment: &Fragment, _: i32, border_box: &Rect<Au>) {
+        if self.index.is_some() {
+            return;
+        }
+        let info = match fragment.specific {
+            SpecificFragmentInfo::ScannedText(ref info) => info,
+            _ => return,
+        };
+        if !border_box.contains(&self.point) {
+            self.preceding_characters += info.text().chars().count();
+            return;
+        }
+
+        // The point is over the first half of the character it stops at.
+        let mut advance = border_box.origin.x;
+        let mut index = self.preceding_characters;
+        for slice in info.run.character_slices_in_range(&info.range) {
+            let character_advance = slice.glyphs.advance_for_byte_range(&slice.range,
+                                                                       info.run.extra_word_spacing);
+            if advance + character_advance / 2 > self.point.x {
+                break;
+            }
+            advance = advance + character_advance;
+            index += 1;
+        }
+        self.index = Some(index);
+    }
+
+    fn should_process(&mut self, fragment: &Fragment) -> bool {
+        fragment.contains_node(self.node_address)
+    }
+}
+
+pub fn process_text_index_request<N: LayoutNode>(requested_node: N,
+                                                 point: Point2D<Au>,
+                                                 layout_root: &mut Flow)
+                                                 -> TextIndexResponse {
+    let mut iterator = TextIndexIterator {
+        node_address: requested_node.opaque(),
+        point: point,
+        preceding_characters: 0,
+        index: None,
+    };
+    sequential::iterate_through_flow_tree_fragment_border_boxes(layout_root, &mut iterator);
+    TextIndexResponse(iterator.index)
+}
+
 pub fn process_content_boxes_request<N: LayoutNode>(requested_node: N, layout_root: &mut Flow)
         -> Vec<Rect<Au>> {
     // FIXME(pcwalton): This has not been updated to handle the stacking context relative
@@ -394,6 +481,16 @@ pub fn process_content_boxes_request<N: LayoutNode>(requested_node: N, layout_ro
     iterator.rects
 }
 
+/// Returns the content-box sizes of the query containers in the flow tree, for
+/// the container queries of the next restyle.
+pub fn collect_container_sizes(layout_root: &mut Flow) -> HashMap<OpaqueNode, Size2D<Au>> {
+    let mut iterator = ContainerSizeFragmentBorderBoxIterator {
+        sizes: HashMap::new(),
+    };
+    sequential::iterate_through_flow_tree_fragment_border_boxes(layout_root, &mut iterator);
+    iterator.sizes
+}
+
 struct FragmentLocatingFragmentIterator {
     node_address: OpaqueNode,
     client_rect: Rect<i32>,
@@ -554,6 +651,7 @@ impl FragmentBorderBoxIterator for ParentOffsetBorderBoxIterator {
                 (false, computed_values::position::T::static_, &SpecificFragmentInfo::TableCell) |
                 (false, computed_values::position::T::absolute, _) |
                 (false, computed_values::position::T::relative, _) |
+                (false, computed_values::position::T::sticky, _) |
                 (false, computed_values::position::T::fixed, _) => true,
 
                 // Otherwise, it's not a valid parent
@@ -661,7 +759,10 @@ pub fn process_resolved_style_request<'a, N, C>(requested_node: N,
         Some(PseudoElement::After) => layout_node.get_after_pseudo(),
         Some(PseudoElement::DetailsSummary) |
         Some(PseudoElement::DetailsContent) |
-        Some(PseudoElement::Selection) => None,
+        Some(PseudoElement::Selection) |
+        Some(PseudoElement::Backdrop) |
+        Some(PseudoElement::Cue) |
+        Some(PseudoElement::Marker) => None,
         _ => Some(layout_node)
     };
 
@@ -679,7 +780,7 @@ pub fn process_resolved_style_request<'a, N, C>(requested_node: N,
 
     let positioned = match style.get_box().position {
         position::computed_value::T::relative |
-        /*position::computed_value::T::sticky |*/
+        position::computed_value::T::sticky |
         position::computed_value::T::fixed |
         position::computed_value::T::absolute => true,
         _ => false
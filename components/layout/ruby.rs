@@ -0,0 +1,316 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Layout for ruby annotations: https://www.w3.org/TR/css-ruby-1/
+//!
+//! `ruby.css` makes `ruby` an inline-block and each `rt` a block, so the children of a ruby flow
+//! are inline flows holding the bases and block flows holding the annotations. Each annotation is
+//! paired with the base before it, and the pairs are laid out side by side as columns.
+
+#![deny(unsafe_code)]
+
+use app_units::Au;
+use block::BlockFlow;
+use context::{LayoutContext, SharedLayoutContext};
+use display_list_builder::DisplayListBuildState;
+use euclid::Point2D;
+use floats::FloatKind;
+use flow::{self, Flow, FlowClass, ImmutableFlowUtils, OpaqueFlow, IS_ABSOLUTELY_POSITIONED};
+use fragment::{Fragment, FragmentBorderBoxIterator, Overflow};
+use gfx::display_list::StackingContext;
+use gfx_traits::StackingContextId;
+use gfx_traits::print_tree::PrintTree;
+use layout_debug;
+use model::IntrinsicISizes;
+use script_layout_interface::wrapper_traits::ThreadSafeLayoutElement;
+use std::cmp::max;
+use std::fmt;
+use std::sync::Arc;
+use style::computed_values::{ruby_align, ruby_position};
+use style::context::SharedStyleContext;
+use style::logical_geometry::LogicalSize;
+use style::properties::ServoComputedValues;
+
+/// A base and the annotation set over or under it, as indices into the children of the flow.
+#[derive(Copy, Clone)]
+struct RubyColumn {
+    base: Option<usize>,
+    annotation: Option<usize>,
+    sizes: IntrinsicISizes,
+}
+
+pub struct RubyFlow {
+    pub block_flow: BlockFlow,
+
+    /// The offset of the baseline of the bases from the block-start edge of the border box.
+    pub baseline_offset: Option<Au>,
+}
+
+impl RubyFlow {
+    /// Returns true if the given element is laid out by `RubyFlow`.
+    pub fn is_ruby_element<E>(element: &E) -> bool where E: ThreadSafeLayoutElement {
+        *element.get_namespace() == ns!(html) && &**element.get_local_name() == "ruby"
+    }
+
+    pub fn from_fragment(fragment: Fragment, float_kind: Option<FloatKind>) -> RubyFlow {
+        RubyFlow {
+            block_flow: BlockFlow::from_fragment(fragment, float_kind),
+            baseline_offset: None,
+        }
+    }
+
+    /// Pairs the bases with their annotations. An annotation with no base before it, or after
+    /// another annotation, gets a column of its own.
+    fn columns(&self) -> Vec<RubyColumn> {
+        let mut columns: Vec<RubyColumn> = vec![];
+        for (index, kid) in self.block_flow.base.children.iter().enumerate() {
+            let kid_base = flow::base(kid);
+            if kid_base.flags.contains(IS_ABSOLUTELY_POSITIONED) {
+                continue
+            }
+            let is_annotation = !kid.is_inline_flow();
+            let pairs_with_last = match columns.last() {
+                Some(column) => is_annotation && column.base.is_some() && column.annotation.is_none(),
+                None => false,
+            };
+            if !pairs_with_last {
+                columns.push(RubyColumn {
+                    base: None,
+                    annotation: None,
+                    sizes: IntrinsicISizes::new(),
+                });
+            }
+            let column = columns.last_mut().unwrap();
+            if is_annotation {
+                column.annotation = Some(index)
+            } else {
+                column.base = Some(index)
+            }
+            column.sizes.minimum_inline_size =
+                max(column.sizes.minimum_inline_size,
+                    kid_base.intrinsic_inline_sizes.minimum_inline_size);
+            column.sizes.preferred_inline_size =
+                max(column.sizes.preferred_inline_size,
+                    kid_base.intrinsic_inline_sizes.preferred_inline_size);
+        }
+        columns
+    }
+
+    fn bubble_inline_sizes_for_columns(&mut self) {
+        // The columns sit side by side, and the base and the annotation of each share its width.
+        let mut computation = self.block_flow.fragment.compute_intrinsic_inline_sizes();
+        for column in self.columns() {
+            computation.union_nonbreaking_inline(&column.sizes);
+        }
+        self.block_flow.base.intrinsic_inline_sizes = computation.finish();
+    }
+
+    fn assign_inline_sizes_for_columns(&mut self) {
+        let inline_start_content_edge = self.block_flow.fragment.border_box.start.i +
+            self.block_flow.fragment.border_padding.inline_start;
+        let containing_block_mode = self.block_flow.base.writing_mode;
+        let ruby_align = self.block_flow.fragment.style.get_inheritedtext().ruby_align;
+
+        // The start and the inline size of the column each child is in.
+        let mut placements = vec![None; self.block_flow.base.children.len()];
+        let mut column_start = inline_start_content_edge;
+        for column in self.columns() {
+            let column_inline_size = column.sizes.preferred_inline_size;
+            for index in column.base.iter().chain(column.annotation.iter()) {
+                placements[*index] = Some((column_start, column_inline_size));
+            }
+            column_start = column_start + column_inline_size;
+        }
+
+        // Each child is as wide as its content, and is aligned in its column per `ruby-align`.
+        for (index, kid) in self.block_flow.base.child_iter_mut().enumerate() {
+            let (column_start, column_inline_size) = match placements[index] {
+                Some(placement) => placement,
+                None => continue,
+            };
+            let kid_base = flow::mut_base(kid);
+            let inline_size = kid_base.intrinsic_inline_sizes.preferred_inline_size;
+            kid_base.block_container_inline_size = inline_size;
+            kid_base.block_container_writing_mode = containing_block_mode;
+            kid_base.position.start.i = column_start +
+                ruby_align_offset(ruby_align, column_inline_size - inline_size);
+        }
+    }
+
+    fn assign_block_size_for_columns(&mut self) {
+        let border_padding_start = self.block_flow.fragment.border_padding.block_start;
+        let ruby_position = self.block_flow.fragment.style.get_inheritedtext().ruby_position;
+        let columns = self.columns();
+
+        // Bases are aligned on their baselines, and annotations on the edge next to the bases.
+        let mut base_ascent = Au(0);
+        let mut base_descent = Au(0);
+        let mut annotation_block_size = Au(0);
+        for kid in self.block_flow.base.children.iter() {
+            if flow::base(kid).flags.contains(IS_ABSOLUTELY_POSITIONED) {
+                continue
+            }
+            let block_size = flow::base(kid).position.size.block;
+            if kid.is_inline_flow() {
+                let ascent = kid.as_inline().baseline_offset_of_last_line().unwrap_or(block_size);
+                base_ascent = max(base_ascent, ascent);
+                base_descent = max(base_descent, block_size - ascent);
+            } else {
+                annotation_block_size = max(annotation_block_size, block_size);
+            }
+        }
+
+        let (base_row_start, annotation_row_start) = match ruby_position {
+            ruby_position::T::over => (annotation_block_size, Au(0)),
+            ruby_position::T::under => (Au(0), base_ascent + base_descent),
+        };
+        for (index, kid) in self.block_flow.base.child_iter_mut().enumerate() {
+            let is_base = columns.iter().any(|column| column.base == Some(index));
+            let is_annotation = columns.iter().any(|column| column.annotation == Some(index));
+            let start = if is_base {
+                let block_size = flow::base(kid).position.size.block;
+                let ascent = kid.as_inline().baseline_offset_of_last_line().unwrap_or(block_size);
+                base_row_start + base_ascent - ascent
+            } else if is_annotation {
+                match ruby_position {
+                    ruby_position::T::over => {
+                        annotation_block_size - flow::base(kid).position.size.block
+                    }
+                    ruby_position::T::under => annotation_row_start,
+                }
+            } else {
+                continue
+            };
+            flow::mut_base(kid).position.start.b = border_padding_start + start;
+        }
+
+        self.baseline_offset = Some(border_padding_start + base_row_start + base_ascent);
+        self.set_content_block_size(base_ascent + base_descent + annotation_block_size);
+    }
+
+    fn set_content_block_size(&mut self, content_block_size: Au) {
+        let block_size = content_block_size +
+            self.block_flow.fragment.border_padding.block_start_end();
+        self.block_flow.fragment.border_box.size.block = block_size;
+        self.block_flow.base.position.size.block = block_size +
+            self.block_flow.fragment.margin.block_start_end();
+    }
+}
+
+/// Returns how far into its column a base or an annotation starts. Each of them is a single run
+/// with no justification opportunities, so the distributed alignments center it.
+fn ruby_align_offset(ruby_align: ruby_align::T, free_space: Au) -> Au {
+    match ruby_align {
+        ruby_align::T::start => Au(0),
+        ruby_align::T::center |
+        ruby_align::T::space_between |
+        ruby_align::T::space_around => free_space / 2,
+    }
+}
+
+impl Flow for RubyFlow {
+    fn class(&self) -> FlowClass {
+        FlowClass::Ruby
+    }
+
+    fn as_block(&self) -> &BlockFlow {
+        &self.block_flow
+    }
+
+    fn as_mut_block(&mut self) -> &mut BlockFlow {
+        &mut self.block_flow
+    }
+
+    fn as_ruby(&self) -> &RubyFlow {
+        self
+    }
+
+    fn mark_as_root(&mut self) {
+        self.block_flow.mark_as_root();
+    }
+
+    fn bubble_inline_sizes(&mut self) {
+        let _scope = layout_debug_scope!("ruby::bubble_inline_sizes {:x}",
+                                         self.block_flow.base.debug_id());
+
+        self.block_flow.bubble_inline_sizes();
+        self.bubble_inline_sizes_for_columns()
+    }
+
+    fn assign_inline_sizes(&mut self, shared_context: &SharedStyleContext) {
+        let _scope = layout_debug_scope!("ruby::assign_inline_sizes {:x}",
+                                         self.block_flow.base.debug_id());
+        debug!("assign_inline_sizes({}): assigning inline_size for flow", "ruby");
+
+        self.block_flow.assign_inline_sizes(shared_context);
+        self.assign_inline_sizes_for_columns()
+    }
+
+    fn assign_block_size<'a>(&mut self, layout_context: &'a LayoutContext<'a>) {
+        debug!("assign_block_size: assigning block_size for ruby");
+
+        self.block_flow.assign_block_size(layout_context);
+        self.assign_block_size_for_columns()
+    }
+
+    fn compute_absolute_position(&mut self, layout_context: &SharedLayoutContext) {
+        self.block_flow.compute_absolute_position(layout_context)
+    }
+
+    fn place_float_if_applicable<'a>(&mut self) {
+        self.block_flow.place_float_if_applicable()
+    }
+
+    fn update_late_computed_inline_position_if_necessary(&mut self, inline_position: Au) {
+        self.block_flow.update_late_computed_inline_position_if_necessary(inline_position)
+    }
+
+    fn update_late_computed_block_position_if_necessary(&mut self, block_position: Au) {
+        self.block_flow.update_late_computed_block_position_if_necessary(block_position)
+    }
+
+    fn build_display_list(&mut self, state: &mut DisplayListBuildState) {
+        self.block_flow.build_display_list(state);
+    }
+
+    fn collect_stacking_contexts(&mut self,
+                                 parent_id: StackingContextId,
+                                 contexts: &mut Vec<Box<StackingContext>>)
+                                 -> StackingContextId {
+        self.block_flow.collect_stacking_contexts(parent_id, contexts)
+    }
+
+    fn repair_style(&mut self, new_style: &Arc<ServoComputedValues>) {
+        self.block_flow.repair_style(new_style)
+    }
+
+    fn compute_overflow(&self) -> Overflow {
+        self.block_flow.compute_overflow()
+    }
+
+    fn generated_containing_block_size(&self, flow: OpaqueFlow) -> LogicalSize<Au> {
+        self.block_flow.generated_containing_block_size(flow)
+    }
+
+    fn iterate_through_fragment_border_boxes(&self,
+                                             iterator: &mut FragmentBorderBoxIterator,
+                                             level: i32,
+                                             stacking_context_position: &Point2D<Au>) {
+        self.block_flow.iterate_through_fragment_border_boxes(iterator, level, stacking_context_position);
+    }
+
+    fn mutate_fragments(&mut self, mutator: &mut FnMut(&mut Fragment)) {
+        self.block_flow.mutate_fragments(mutator);
+    }
+
+    fn print_extra_flow_children(&self, print_tree: &mut PrintTree) {
+        self.block_flow.print_extra_flow_children(print_tree);
+    }
+}
+
+impl fmt::Debug for RubyFlow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RubyFlow: {:?}", self.block_flow)
+    }
+}
@@ -25,7 +25,8 @@ use script_layout_interface::restyle_damage::{REFLOW, REFLOW_OUT_OF_FLOW};
 use std::cmp;
 use std::fmt;
 use std::sync::Arc;
-use style::computed_values::{border_collapse, border_spacing, table_layout};
+use std::u32;
+use style::computed_values::{border_collapse, border_spacing};
 use style::context::SharedStyleContext;
 use style::logical_geometry::LogicalSize;
 use style::properties::ServoComputedValues;
@@ -65,12 +66,7 @@ pub struct TableFlow {
 impl TableFlow {
     pub fn from_fragment(fragment: Fragment) -> TableFlow {
         let mut block_flow = BlockFlow::from_fragment(fragment, None);
-        let table_layout =
-            if block_flow.fragment().style().get_table().table_layout == table_layout::T::fixed {
-                TableLayout::Fixed
-            } else {
-                TableLayout::Auto
-            };
+        let table_layout = TableLayout::from_style(block_flow.fragment().style());
         TableFlow {
             block_flow: block_flow,
             column_intrinsic_inline_sizes: Vec::new(),
@@ -82,63 +78,89 @@ impl TableFlow {
     }
 
     /// Update the corresponding value of `self_inline_sizes` if a value of `kid_inline_sizes` has
-    /// a larger value than one of `self_inline_sizes`. Returns the minimum and preferred inline
-    /// sizes.
+    /// a larger value than one of `self_inline_sizes`. Cells that span several columns are set
+    /// aside in `spanning_cells`, to be distributed once all the single-column cells are known.
     fn update_automatic_column_inline_sizes(
             parent_inline_sizes: &mut Vec<ColumnIntrinsicInlineSize>,
-            child_cell_inline_sizes: &[CellIntrinsicInlineSize])
-            -> IntrinsicISizes {
-        let mut total_inline_sizes = IntrinsicISizes::new();
-        let mut column_index = 0;
+            child_cell_inline_sizes: &[CellIntrinsicInlineSize],
+            spanning_cells: &mut Vec<CellIntrinsicInlineSize>) {
         for child_cell_inline_size in child_cell_inline_sizes {
-            for _ in 0..child_cell_inline_size.column_span {
-                if column_index < parent_inline_sizes.len() {
-                    // We already have some intrinsic size information for this column. Merge it in
-                    // according to the rules specified in INTRINSIC § 4.
-                    let parent_sizes = &mut parent_inline_sizes[column_index];
-                    if child_cell_inline_size.column_span > 1 {
-                        // TODO(pcwalton): Perform the recursive algorithm specified in INTRINSIC §
-                        // 4. For now we make this column contribute no width.
-                    } else {
-                        let column_size = &child_cell_inline_size.column_size;
-                        *parent_sizes = ColumnIntrinsicInlineSize {
-                            minimum_length: cmp::max(parent_sizes.minimum_length,
-                                                     column_size.minimum_length),
-                            percentage: parent_sizes.greatest_percentage(column_size),
-                            preferred: cmp::max(parent_sizes.preferred, column_size.preferred),
-                            constrained: parent_sizes.constrained || column_size.constrained,
-                        }
-                    }
-                } else {
-                    // We discovered a new column. Initialize its data.
-                    debug_assert!(column_index == parent_inline_sizes.len());
-                    if child_cell_inline_size.column_span > 1 {
-                        // TODO(pcwalton): Perform the recursive algorithm specified in INTRINSIC §
-                        // 4. For now we make this column contribute no width.
-                        parent_inline_sizes.push(ColumnIntrinsicInlineSize::new())
-                    } else {
-                        parent_inline_sizes.push(child_cell_inline_size.column_size)
-                    }
-                }
+            let column_index = child_cell_inline_size.column_index;
+            let column_end = child_cell_inline_size.column_end();
+            if parent_inline_sizes.len() < column_end {
+                // We discovered new columns. Initialize their data.
+                parent_inline_sizes.resize(column_end, ColumnIntrinsicInlineSize::new())
+            }
 
-                total_inline_sizes.minimum_inline_size = total_inline_sizes.minimum_inline_size +
-                    parent_inline_sizes[column_index].minimum_length;
-                total_inline_sizes.preferred_inline_size =
-                    total_inline_sizes.preferred_inline_size +
-                    parent_inline_sizes[column_index].preferred;
+            if column_end - column_index > 1 {
+                spanning_cells.push(*child_cell_inline_size);
+                continue
+            }
 
-                column_index += 1
+            // Merge the cell's sizes in according to the rules specified in INTRINSIC § 4.
+            let parent_sizes = &mut parent_inline_sizes[column_index];
+            let column_size = &child_cell_inline_size.column_size;
+            *parent_sizes = ColumnIntrinsicInlineSize {
+                minimum_length: cmp::max(parent_sizes.minimum_length, column_size.minimum_length),
+                percentage: parent_sizes.greatest_percentage(column_size),
+                preferred: cmp::max(parent_sizes.preferred, column_size.preferred),
+                constrained: parent_sizes.constrained || column_size.constrained,
             }
         }
+    }
+
+    /// Distributes the inline sizes of cells spanning several columns among those columns, per
+    /// INTRINSIC § 4. Cells with smaller spans go first, so that wider cells only add whatever the
+    /// narrower ones didn't already provide.
+    fn distribute_spanning_cell_inline_sizes(column_inline_sizes: &mut [ColumnIntrinsicInlineSize],
+                                             spanning_cells: &mut [CellIntrinsicInlineSize],
+                                             horizontal_spacing: Au) {
+        spanning_cells.sort_by_key(|cell| cell.column_end() - cell.column_index);
+        for cell in spanning_cells.iter() {
+            let columns = &mut column_inline_sizes[cell.column_index..cell.column_end()];
+            let interior_spacing = horizontal_spacing * (columns.len() as i32 - 1);
+
+            // Widen the columns until they're as wide as the cell needs to be, in proportion to
+            // their preferred inline sizes.
+            let minimum_length = columns.iter().fold(Au(0), |sum, column| sum + column.minimum_length);
+            let excess = cell.column_size.minimum_length - interior_spacing - minimum_length;
+            if excess > Au(0) {
+                let weights = columns.iter().map(|column| column.preferred).collect::<Vec<_>>();
+                for (column, share) in columns.iter_mut().zip(proportional_shares(&weights, excess)) {
+                    column.minimum_length = column.minimum_length + share;
+                    column.preferred = cmp::max(column.preferred, column.minimum_length);
+                }
+            }
+
+            let preferred = columns.iter().fold(Au(0), |sum, column| sum + column.preferred);
+            let excess = cell.column_size.preferred - interior_spacing - preferred;
+            if excess > Au(0) {
+                let weights = columns.iter().map(|column| column.preferred).collect::<Vec<_>>();
+                for (column, share) in columns.iter_mut().zip(proportional_shares(&weights, excess)) {
+                    column.preferred = column.preferred + share;
+                }
+            }
 
-        total_inline_sizes
+            // Any percentage the columns don't already add up to goes, evenly, to the columns that
+            // have none of their own.
+            let percentage = columns.iter().fold(0.0, |sum, column| sum + column.percentage);
+            let columns_without_percentage =
+                columns.iter().filter(|column| column.percentage == 0.0).count();
+            if cell.column_size.percentage > percentage && columns_without_percentage != 0 {
+                let share = (cell.column_size.percentage - percentage) /
+                    columns_without_percentage as CSSFloat;
+                for column in columns.iter_mut().filter(|column| column.percentage == 0.0) {
+                    column.percentage = share
+                }
+            }
+        }
     }
 
     /// Updates the minimum and preferred inline-size calculation for a single row. This is
     /// factored out into a separate function because we process children of rowgroups too.
     fn update_column_inline_sizes_for_row(row: &TableRowFlow,
                                           column_inline_sizes: &mut Vec<ColumnIntrinsicInlineSize>,
-                                          computation: &mut IntrinsicISizesContribution,
+                                          spanning_cells: &mut Vec<CellIntrinsicInlineSize>,
                                           first_row: bool,
                                           table_layout: TableLayout) {
         // Read column inline-sizes from the table-row, and assign inline-size=0 for the columns
@@ -147,19 +169,35 @@ impl TableFlow {
         // FIXME: Need to read inline-sizes from either table-header-group OR the first table-row.
         match table_layout {
             TableLayout::Fixed => {
-                // Fixed table layout only looks at the first row.
+                // Fixed table layout only looks at the first row. Cells spanning several columns
+                // split their inline size evenly among them, and sizes given by column elements
+                // take precedence over those given by cells.
                 //
                 // FIXME(pcwalton): This is really inefficient. We should stop after the first row!
-                if first_row {
-                    for cell_inline_size in &row.cell_intrinsic_inline_sizes {
-                        column_inline_sizes.push(cell_inline_size.column_size);
+                if !first_row {
+                    return
+                }
+                for cell_inline_size in &row.cell_intrinsic_inline_sizes {
+                    let column_span = cell_inline_size.column_end() - cell_inline_size.column_index;
+                    let mut column_size = cell_inline_size.column_size;
+                    column_size.minimum_length = column_size.minimum_length / column_span as i32;
+                    column_size.preferred = column_size.preferred / column_span as i32;
+                    column_size.percentage = column_size.percentage / column_span as CSSFloat;
+                    for column_index in cell_inline_size.column_index..cell_inline_size.column_end() {
+                        if column_index >= column_inline_sizes.len() {
+                            column_inline_sizes.resize(column_index + 1, ColumnIntrinsicInlineSize::new())
+                        } else if column_inline_sizes[column_index].constrained ||
+                                column_inline_sizes[column_index].percentage != 0.0 {
+                            continue
+                        }
+                        column_inline_sizes[column_index] = column_size
                     }
                 }
             }
             TableLayout::Auto => {
-                computation.union_block(&TableFlow::update_automatic_column_inline_sizes(
-                    column_inline_sizes,
-                    &row.cell_intrinsic_inline_sizes))
+                TableFlow::update_automatic_column_inline_sizes(column_inline_sizes,
+                                                                &row.cell_intrinsic_inline_sizes,
+                                                                spanning_cells)
             }
         }
     }
@@ -232,6 +270,7 @@ impl Flow for TableFlow {
         // part of the table, which we don't want to do—it belongs to the table wrapper instead.
 
         // Get column inline sizes from colgroups
+        self.column_intrinsic_inline_sizes.clear();
         for kid in self.block_flow.base.child_iter_mut().filter(|kid| kid.is_table_colgroup()) {
             for specified_inline_size in &kid.as_mut_table_colgroup().inline_sizes {
                 self.column_intrinsic_inline_sizes.push(ColumnIntrinsicInlineSize {
@@ -248,11 +287,34 @@ impl Flow for TableFlow {
                         LengthOrPercentageOrAuto::Percentage(percentage) => percentage,
                     },
                     preferred: Au(0),
-                    constrained: false,
+                    constrained: match *specified_inline_size {
+                        LengthOrPercentageOrAuto::Length(_) => true,
+                        LengthOrPercentageOrAuto::Auto |
+                        LengthOrPercentageOrAuto::Calc(_) |
+                        LengthOrPercentageOrAuto::Percentage(_) => false,
+                    },
                 })
             }
         }
 
+        // Work out which column each cell starts in. Cells only span rows within their own row
+        // group.
+        {
+            let mut rows_spanned_into_columns = Vec::new();
+            for kid in self.block_flow.base.child_iter_mut() {
+                if kid.is_table_rowgroup() {
+                    let mut rows_spanned_into_columns_of_rowgroup = Vec::new();
+                    for grandkid in flow::mut_base(kid).child_iter_mut() {
+                        assign_column_indices_for_row(grandkid.as_mut_table_row(),
+                                                      &mut rows_spanned_into_columns_of_rowgroup)
+                    }
+                } else if kid.is_table_row() {
+                    assign_column_indices_for_row(kid.as_mut_table_row(),
+                                                  &mut rows_spanned_into_columns)
+                }
+            }
+        }
+
         self.collapsed_inline_direction_border_widths_for_table = Vec::new();
         self.collapsed_block_direction_border_widths_for_table = vec![Au(0)];
 
@@ -272,19 +334,19 @@ impl Flow for TableFlow {
             None
         };
 
-        let mut computation = IntrinsicISizesContribution::new();
         let mut previous_collapsed_block_end_borders =
             PreviousBlockCollapsedBorders::FromTable(CollapsedBorder::block_start(
                     &*self.block_flow.fragment.style,
                     CollapsedBorderProvenance::FromTable));
         let mut first_row = true;
+        let mut spanning_cells = Vec::new();
 
         {
             let mut iterator = TableRowIterator::new(&mut self.block_flow.base).peekable();
             while let Some(row) = iterator.next() {
                 TableFlow::update_column_inline_sizes_for_row(row,
                         &mut self.column_intrinsic_inline_sizes,
-                        &mut computation,
+                        &mut spanning_cells,
                         first_row,
                         self.table_layout);
                 if collapsing_borders {
@@ -292,10 +354,10 @@ impl Flow for TableFlow {
                     let next_collapsed_borders_in_block_direction =
                         match next_index_and_sibling {
                             Some(next_sibling) => {
+                                let next_row = next_sibling.as_table_row();
                                 NextBlockCollapsedBorders::FromNextRow(
-                                    &next_sibling.as_table_row()
-                                                 .preliminary_collapsed_borders
-                                                 .block_start)
+                                    &next_row.preliminary_collapsed_borders.block_start,
+                                    &next_row.cell_intrinsic_inline_sizes)
                             }
                             None => {
                                 NextBlockCollapsedBorders::FromTable(
@@ -311,13 +373,29 @@ impl Flow for TableFlow {
                         &mut self.collapsed_block_direction_border_widths_for_table);
                     previous_collapsed_block_end_borders =
                         PreviousBlockCollapsedBorders::FromPreviousRow(
-                            row.final_collapsed_borders.block_end.clone());
+                            row.final_collapsed_borders.block_end.clone(),
+                            row.cell_intrinsic_inline_sizes.clone());
                 }
                 first_row = false
             };
         }
 
+        let horizontal_spacing = self.spacing().horizontal;
+        TableFlow::distribute_spanning_cell_inline_sizes(&mut self.column_intrinsic_inline_sizes,
+                                                         &mut spanning_cells,
+                                                         horizontal_spacing);
 
+        let mut computation = IntrinsicISizesContribution::new();
+        if let TableLayout::Auto = self.table_layout {
+            let mut total_inline_sizes = IntrinsicISizes::new();
+            for column_inline_size in &self.column_intrinsic_inline_sizes {
+                total_inline_sizes.minimum_inline_size = total_inline_sizes.minimum_inline_size +
+                    column_inline_size.minimum_length;
+                total_inline_sizes.preferred_inline_size =
+                    total_inline_sizes.preferred_inline_size + column_inline_size.preferred;
+            }
+            computation.union_block(&total_inline_sizes)
+        }
         computation.surrounding_size = computation.surrounding_size +
                                        self.total_horizontal_spacing();
 
@@ -334,21 +412,6 @@ impl Flow for TableFlow {
         // The position was set to the containing block by the flow's parent.
         let containing_block_inline_size = self.block_flow.base.block_container_inline_size;
 
-        let mut num_unspecified_inline_sizes = 0;
-        let mut num_percentage_inline_sizes = 0;
-        let mut total_column_inline_size = Au(0);
-        let mut total_column_percentage_size = 0.0;
-        for column_inline_size in &self.column_intrinsic_inline_sizes {
-            if column_inline_size.percentage != 0.0 {
-                total_column_percentage_size += column_inline_size.percentage;
-                num_percentage_inline_sizes += 1;
-            } else if column_inline_size.constrained {
-                total_column_inline_size += column_inline_size.minimum_length;
-            } else {
-                num_unspecified_inline_sizes += 1;
-            }
-        }
-
         let inline_size_computer = InternalTable {
             border_collapse: self.block_flow.fragment.style.get_inheritedtable().border_collapse,
         };
@@ -366,40 +429,43 @@ impl Flow for TableFlow {
 
         match self.table_layout {
             TableLayout::Fixed => {
-                // In fixed table layout, we distribute extra space among the unspecified columns
-                // if there are any, or among all the columns if all are specified.
+                // In fixed table layout, columns with a percentage or a length get that size, and
+                // we distribute extra space among the unspecified columns if there are any, or
+                // among all the columns in proportion to their sizes if all are specified.
                 // See: https://drafts.csswg.org/css-tables-3/#distributing-width-to-columns (infobox)
-                self.column_computed_inline_sizes.clear();
-                if num_unspecified_inline_sizes != 0 {
-                    let extra_column_inline_size = content_inline_size - total_column_inline_size;
-                    for column_inline_size in &self.column_intrinsic_inline_sizes {
-                        if !column_inline_size.constrained {
-                            self.column_computed_inline_sizes.push(ColumnComputedInlineSize {
-                                size: extra_column_inline_size / num_unspecified_inline_sizes,
-                            });
+                let specified_column_inline_sizes =
+                    self.column_intrinsic_inline_sizes.iter().map(|column_inline_size| {
+                        if column_inline_size.percentage != 0.0 {
+                            Some(content_inline_size.scale_by(column_inline_size.percentage))
+                        } else if column_inline_size.constrained {
+                            Some(column_inline_size.minimum_length)
                         } else {
-                            self.column_computed_inline_sizes.push(ColumnComputedInlineSize {
-                                size: column_inline_size.minimum_length,
-                            });
+                            None
                         }
-                    }
-                } else if num_percentage_inline_sizes != 0 {
-                    let extra_column_inline_size = content_inline_size - total_column_inline_size;
-                    let ratio = content_inline_size.to_f32_px() /
-                        total_column_percentage_size;
-                    for column_inline_size in &self.column_intrinsic_inline_sizes {
-                        self.column_computed_inline_sizes.push(ColumnComputedInlineSize {
-                            size: extra_column_inline_size.scale_by(ratio * column_inline_size.percentage),
-                        });
-                    }
-                } else {
-                    let ratio = content_inline_size.to_f32_px() /
-                        total_column_inline_size.to_f32_px();
-                    for column_inline_size in &self.column_intrinsic_inline_sizes {
-                        self.column_computed_inline_sizes.push(ColumnComputedInlineSize {
-                            size: column_inline_size.minimum_length.scale_by(ratio),
-                        });
-                    }
+                    }).collect::<Vec<_>>();
+                let num_unspecified_inline_sizes =
+                    specified_column_inline_sizes.iter().filter(|size| size.is_none()).count();
+                let total_column_inline_size =
+                    specified_column_inline_sizes.iter().fold(Au(0), |sum, size| {
+                        sum + size.unwrap_or(Au(0))
+                    });
+                let extra_column_inline_size =
+                    cmp::max(content_inline_size - total_column_inline_size, Au(0));
+
+                self.column_computed_inline_sizes.clear();
+                for specified_column_inline_size in specified_column_inline_sizes {
+                    let size = match specified_column_inline_size {
+                        None => extra_column_inline_size / num_unspecified_inline_sizes as i32,
+                        Some(size) if num_unspecified_inline_sizes != 0 ||
+                                      total_column_inline_size == Au(0) => size,
+                        Some(size) => {
+                            size + extra_column_inline_size.scale_by(
+                                size.to_f32_px() / total_column_inline_size.to_f32_px())
+                        }
+                    };
+                    self.column_computed_inline_sizes.push(ColumnComputedInlineSize {
+                        size: size,
+                    });
                 }
             }
             _ => {
@@ -644,6 +710,7 @@ fn perform_border_collapse_for_row(child_table_row: &mut TableRowFlow,
                                    inline_spacing: &mut Vec<Au>,
                                    block_spacing: &mut Vec<Au>) {
     let number_of_borders_inline_direction = child_table_row.preliminary_collapsed_borders.inline.len();
+    let cells = &child_table_row.cell_intrinsic_inline_sizes;
     // Compute interior inline borders.
     for (i, this_inline_border) in child_table_row.preliminary_collapsed_borders
                                                   .inline
@@ -656,14 +723,39 @@ fn perform_border_collapse_for_row(child_table_row: &mut TableRowFlow,
             child_table_row.final_collapsed_borders.inline[i].combine(&table_inline_borders.end);
         }
 
-        let inline_spacing = inline_spacing.get_mut_or_push(i, Au(0));
-        *inline_spacing = cmp::max(*inline_spacing, child_table_row.final_collapsed_borders.inline[i].width)
+        // The spacing is kept per column edge, so this border applies both to the end of the
+        // previous cell and to the start of this one, which may be in different places if cells
+        // spanning down from earlier rows sit in between.
+        let width = child_table_row.final_collapsed_borders.inline[i].width;
+        let column_edges = [
+            if i > 0 { cells.get(i - 1).map(|cell| cell.column_end()) } else { Some(0) },
+            cells.get(i).map(|cell| cell.column_index),
+        ];
+        for column_edge in column_edges.iter().filter_map(|column_edge| *column_edge) {
+            if inline_spacing.len() <= column_edge {
+                inline_spacing.resize(column_edge + 1, Au(0))
+            }
+            inline_spacing[column_edge] = cmp::max(inline_spacing[column_edge], width)
+        }
     }
 
-    // Compute block-start borders.
+    // Compute block-start borders, from the cells of the previous row sharing a column with each
+    // of ours.
     match previous_block_borders {
-        PreviousBlockCollapsedBorders::FromPreviousRow(previous_block_borders) => {
-            child_table_row.final_collapsed_borders.block_start = previous_block_borders
+        PreviousBlockCollapsedBorders::FromPreviousRow(previous_block_borders, previous_cells) => {
+            let preliminary_block_start = &child_table_row.preliminary_collapsed_borders.block_start;
+            let block_start = cells.iter().enumerate().map(|(i, cell)| {
+                let mut block_start_border =
+                    preliminary_block_start.get(i).map_or(CollapsedBorder::new(), |x| *x);
+                for (previous_block_border, previous_cell) in
+                        previous_block_borders.iter().zip(previous_cells.iter()) {
+                    if previous_cell.shares_a_column_with(cell) {
+                        block_start_border = *previous_block_border
+                    }
+                }
+                block_start_border
+            }).collect();
+            child_table_row.final_collapsed_borders.block_start = block_start
         }
         PreviousBlockCollapsedBorders::FromTable(collapsed_border) => {
             child_table_row.final_collapsed_borders.block_start =
@@ -671,7 +763,8 @@ fn perform_border_collapse_for_row(child_table_row: &mut TableRowFlow,
         }
     }
 
-    // Compute block-end borders.
+    // Compute block-end borders, from the cells of the next row sharing a column with each of
+    // ours.
     let next_block = &mut child_table_row.final_collapsed_borders.block_end;
     block_spacing.push(Au(0));
     let block_spacing = block_spacing.last_mut().unwrap();
@@ -681,9 +774,11 @@ fn perform_border_collapse_for_row(child_table_row: &mut TableRowFlow,
                                                  .enumerate() {
         let next_block = next_block.push_or_set(i, *this_block_border);
         match next_block_borders {
-            NextBlockCollapsedBorders::FromNextRow(next_block_borders) => {
-                if next_block_borders.len() > i {
-                    next_block.combine(&next_block_borders[i])
+            NextBlockCollapsedBorders::FromNextRow(next_block_borders, next_cells) => {
+                for (next_block_border, next_cell) in next_block_borders.iter().zip(next_cells) {
+                    if cells.get(i).map_or(false, |cell| cell.shares_a_column_with(next_cell)) {
+                        next_block.combine(next_block_border)
+                    }
                 }
             }
             NextBlockCollapsedBorders::FromTable(ref next_block_borders) => {
@@ -694,6 +789,144 @@ fn perform_border_collapse_for_row(child_table_row: &mut TableRowFlow,
     }
 }
 
+/// Works out which column each cell of `row` starts in, skipping the columns taken up by cells
+/// spanning down from earlier rows. `rows_spanned_into_columns` holds, for each column, the number
+/// of rows still to come that an earlier cell spans into, and is updated for the next row.
+fn assign_column_indices_for_row(row: &mut TableRowFlow, rows_spanned_into_columns: &mut Vec<u32>) {
+    let mut column_index = 0;
+    for cell in &mut row.cell_intrinsic_inline_sizes {
+        while rows_spanned_into_columns.get(column_index).map_or(false, |rows| *rows > 0) {
+            column_index += 1
+        }
+        cell.column_index = column_index;
+
+        let column_end = cell.column_end();
+        if rows_spanned_into_columns.len() < column_end {
+            rows_spanned_into_columns.resize(column_end, 0)
+        }
+        // A row span of zero means that the cell spans the rest of the row group.
+        let row_span = if cell.row_span == 0 { u32::MAX } else { cell.row_span };
+        for rows in &mut rows_spanned_into_columns[column_index..column_end] {
+            *rows = row_span
+        }
+        column_index = column_end
+    }
+
+    // Account for this row.
+    for rows in rows_spanned_into_columns.iter_mut().filter(|rows| **rows > 0) {
+        *rows -= 1
+    }
+}
+
+/// Splits `amount` into shares in proportion to `weights`, or evenly if the weights are all zero.
+/// The last share takes up any rounding error, so that the shares always add up to `amount`.
+fn proportional_shares(weights: &[Au], amount: Au) -> Vec<Au> {
+    let total_weight = weights.iter().fold(Au(0), |sum, weight| sum + *weight);
+    let mut remaining = amount;
+    weights.iter().enumerate().map(|(i, weight)| {
+        let share = if i + 1 == weights.len() {
+            remaining
+        } else if total_weight == Au(0) {
+            amount / weights.len() as i32
+        } else {
+            amount.scale_by(weight.to_f32_px() / total_weight.to_f32_px())
+        };
+        remaining = remaining - share;
+        share
+    }).collect()
+}
+
+/// Returns the index of the last of `row_count` rows spanned by a cell that starts in `first_row`.
+fn last_row_spanned(first_row: usize, row_span: u32, row_count: usize) -> usize {
+    if row_span == 0 {
+        row_count - 1
+    } else {
+        cmp::min(first_row + row_span as usize, row_count) - 1
+    }
+}
+
+/// Returns the block size of the rows from `first_row` to `last_row` inclusive, plus the spacing
+/// in between them.
+fn spanned_block_size(row_block_sizes: &[Au],
+                      row_spacings: &[Au],
+                      first_row: usize,
+                      last_row: usize)
+                      -> Au {
+    (first_row..(last_row + 1)).fold(Au(0), |block_size, row_index| {
+        let spacing = if row_index > first_row { row_spacings[row_index] } else { Au(0) };
+        block_size + spacing + row_block_sizes[row_index]
+    })
+}
+
+/// Makes sure that the rows among the children of `base` are tall enough for the cells that span
+/// several of them, by growing the last row such a cell spans if necessary, and then stretches
+/// those cells over the rows they span. Rows only know about the cells that start in them, so
+/// this has to be done by their parent.
+fn size_row_spanning_cells(base: &mut BaseFlow, collapsing_borders: bool, block_direction_spacing: Au) {
+    // The block size of each row and the spacing before it, and the first row, row span and
+    // content block size of each cell spanning several rows.
+    let mut row_block_sizes = Vec::new();
+    let mut row_spacings = Vec::new();
+    let mut row_spanning_cells = Vec::new();
+    for kid in base.child_iter_mut() {
+        if !kid.is_table_row() {
+            continue
+        }
+        let row_index = row_block_sizes.len();
+        let row = kid.as_mut_table_row();
+        row_block_sizes.push(row.block_flow.base.position.size.block);
+        row_spacings.push(if collapsing_borders {
+            row.collapsed_border_spacing.block_start
+        } else {
+            block_direction_spacing
+        });
+        for cell in row.block_flow.base.child_iter_mut() {
+            let cell = cell.as_table_cell();
+            if cell.row_span != 1 {
+                row_spanning_cells.push((row_index,
+                                         cell.row_span,
+                                         cell.block_flow.base.position.size.block))
+            }
+        }
+    }
+
+    if row_spanning_cells.is_empty() {
+        return
+    }
+
+    let row_count = row_block_sizes.len();
+    for &(first_row, row_span, block_size) in &row_spanning_cells {
+        let last_row = last_row_spanned(first_row, row_span, row_count);
+        let available_block_size =
+            spanned_block_size(&row_block_sizes, &row_spacings, first_row, last_row);
+        if block_size > available_block_size {
+            row_block_sizes[last_row] = row_block_sizes[last_row] + block_size - available_block_size
+        }
+    }
+
+    let mut row_index = 0;
+    for kid in base.child_iter_mut() {
+        if !kid.is_table_row() {
+            continue
+        }
+        let row = kid.as_mut_table_row();
+        if row.block_flow.base.position.size.block != row_block_sizes[row_index] {
+            row.set_block_size(row_block_sizes[row_index])
+        }
+        for cell in row.block_flow.base.child_iter_mut() {
+            let cell = cell.as_mut_table_cell();
+            if cell.row_span != 1 {
+                let last_row = last_row_spanned(row_index, cell.row_span, row_count);
+                cell.set_block_size(spanned_block_size(&row_block_sizes,
+                                                       &row_spacings,
+                                                       row_index,
+                                                       last_row))
+            }
+        }
+        row_index += 1
+    }
+}
+
 /// Encapsulates functionality shared among all table-like flows: for now, tables and table
 /// rowgroups.
 pub trait TableLikeFlow {
@@ -707,6 +940,10 @@ impl TableLikeFlow for BlockFlow {
                       border_collapse::T::separate || block_direction_spacing == Au(0));
 
         if self.base.restyle_damage.contains(REFLOW) {
+            let collapsing_borders = self.fragment.style.get_inheritedtable().border_collapse ==
+                border_collapse::T::collapse;
+            size_row_spanning_cells(&mut self.base, collapsing_borders, block_direction_spacing);
+
             // Our current border-box position.
             let block_start_border_padding = self.fragment.border_padding.block_start;
             let mut current_block_offset = block_start_border_padding;
@@ -793,13 +1030,17 @@ struct TableInlineCollapsedBorders {
     end: CollapsedBorder,
 }
 
+/// The block-end borders of the previous row, along with the cells they belong to, or the
+/// block-start border of the table if there is no previous row.
 enum PreviousBlockCollapsedBorders {
-    FromPreviousRow(Vec<CollapsedBorder>),
+    FromPreviousRow(Vec<CollapsedBorder>, Vec<CellIntrinsicInlineSize>),
     FromTable(CollapsedBorder),
 }
 
+/// The block-start borders of the next row, along with the cells they belong to, or the
+/// block-end border of the table if there is no next row.
 enum NextBlockCollapsedBorders<'a> {
-    FromNextRow(&'a [CollapsedBorder]),
+    FromNextRow(&'a [CollapsedBorder], &'a [CellIntrinsicInlineSize]),
     FromTable(CollapsedBorder),
 }
 
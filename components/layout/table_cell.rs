@@ -41,6 +41,9 @@ pub struct TableCellFlow {
     /// The column span of this cell.
     pub column_span: u32,
 
+    /// The row span of this cell, or zero if it spans the rest of its row group.
+    pub row_span: u32,
+
     /// Whether this cell is visible. If false, the value of `empty-cells` means that we must not
     /// display this cell.
     pub visible: bool,
@@ -53,6 +56,7 @@ impl TableCellFlow {
             block_flow: BlockFlow::from_fragment(fragment, None),
             collapsed_borders: CollapsedBordersForCell::new(),
             column_span: node.get_colspan(),
+            row_span: node.get_rowspan(),
             visible: visible,
         }
     }
@@ -78,6 +82,14 @@ impl TableCellFlow {
         debug_assert!(remaining.is_none());
     }
 
+    /// Sets the block size of this cell's border box, once the rows it spans have been sized, and
+    /// vertically aligns its children within it.
+    pub fn set_block_size(&mut self, block_size: Au) {
+        self.block_flow.fragment.border_box.size.block = block_size;
+        self.block_flow.base.position.size.block = block_size;
+        self.valign_children();
+    }
+
     /// Position this cell's children according to vertical-align.
     pub fn valign_children(&mut self) {
         // Note to the reader: this code has been tested with negative margins.
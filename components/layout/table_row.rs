@@ -78,8 +78,38 @@ pub struct CellIntrinsicInlineSize {
     pub column_size: ColumnIntrinsicInlineSize,
     /// The column span of this cell.
     pub column_span: u32,
+    /// The row span of this cell, or zero if it spans the rest of its row group.
+    pub row_span: u32,
+    /// The index of the first column this cell occupies. This is filled in by the table, which
+    /// knows about the cells of earlier rows that span down into this one.
+    pub column_index: usize,
 }
 
+impl CellIntrinsicInlineSize {
+    /// The index of the column just past the last one this cell occupies.
+    pub fn column_end(&self) -> usize {
+        self.column_index + max(self.column_span, 1) as usize
+    }
+
+    /// Whether this cell and `other` occupy at least one column in common.
+    pub fn shares_a_column_with(&self, other: &CellIntrinsicInlineSize) -> bool {
+        self.column_index < other.column_end() && other.column_index < self.column_end()
+    }
+}
+
+/// The inline size assigned to a cell, and where it goes in its row.
+#[derive(Copy, Clone, Debug)]
+struct CellComputedInlineSize {
+    /// The inline size of the columns the cell spans, including the spacing between them.
+    size: Au,
+    /// The inline size of any columns between the previous cell and this one, which are taken up
+    /// by cells spanning down from earlier rows, including their spacing.
+    skipped_size: Au,
+    /// The index of the first column the cell occupies.
+    column_index: usize,
+    /// The index of the column just past the last one the cell occupies.
+    column_end: usize,
+}
 
 impl TableRowFlow {
     pub fn from_fragment(fragment: Fragment) -> TableRowFlow {
@@ -119,20 +149,31 @@ impl TableRowFlow {
                                                                          thread_id);
                 }
 
+                let child_row_span;
+                let child_block_size;
                 {
-                    let child_fragment = kid.as_mut_table_cell().fragment();
+                    let child_table_cell = kid.as_mut_table_cell();
+                    child_row_span = child_table_cell.row_span;
+                    let child_content_block_size = child_table_cell.block_flow.base.position.size.block;
+                    let child_fragment = child_table_cell.fragment();
                     // TODO: Percentage block-size
                     let child_specified_block_size =
                         MaybeAuto::from_style(child_fragment.style().content_block_size(),
                                               Au(0)).specified_or_zero();
-                    max_block_size =
-                        max(max_block_size,
-                            child_specified_block_size +
-                            child_fragment.border_padding.block_start_end());
+                    child_block_size = max(child_content_block_size,
+                                           child_specified_block_size +
+                                           child_fragment.border_padding.block_start_end());
                 }
                 let child_node = flow::mut_base(kid);
                 child_node.position.start.b = Au(0);
-                max_block_size = max(max_block_size, child_node.position.size.block);
+
+                // Cells spanning several rows don't contribute to the size of this row alone. Our
+                // parent sizes them once it knows the sizes of all the rows they span.
+                if child_row_span == 1 {
+                    max_block_size = max(max_block_size, child_block_size);
+                } else {
+                    child_node.position.size.block = child_block_size;
+                }
             }
 
             let mut block_size = max_block_size;
@@ -146,31 +187,12 @@ impl TableRowFlow {
                 MaybeAuto::Specified(value) => max(value, block_size),
             };
 
-            // Assign the block-size of own fragment
-            let mut position = self.block_flow.fragment.border_box;
-            position.size.block = block_size;
-            self.block_flow.fragment.border_box = position;
-            self.block_flow.base.position.size.block = block_size;
+            self.set_block_size(block_size);
 
-            // Assign the block-size of kid fragments, which is the same value as own block-size.
             for kid in self.block_flow.base.child_iter_mut() {
-                let child_table_cell = kid.as_mut_table_cell();
-                {
-                    let kid_fragment = child_table_cell.mut_fragment();
-                    let mut position = kid_fragment.border_box;
-                    position.size.block = block_size;
-                    kid_fragment.border_box = position;
-                }
-
-                // Assign the child's block size.
-                child_table_cell.block_flow.base.position.size.block = block_size;
-
-                // Now we know the cell height, vertical align the cell's children.
-                child_table_cell.valign_children();
-
                 // Write in the size of the relative containing block for children. (This
                 // information is also needed to handle RTL.)
-                child_table_cell.block_flow.base.early_absolute_position_info =
+                flow::mut_base(kid).early_absolute_position_info =
                     EarlyAbsolutePositionInfo {
                         relative_containing_block_size: self.block_flow
                                                             .fragment
@@ -187,6 +209,35 @@ impl TableRowFlow {
         self.block_flow.base.restyle_damage.remove(REFLOW_OUT_OF_FLOW | REFLOW);
     }
 
+    /// Assigns the block-size of this row, and of the cells in it that span no other rows, which
+    /// is the same value.
+    pub fn set_block_size(&mut self, block_size: Au) {
+        self.block_flow.fragment.border_box.size.block = block_size;
+        self.block_flow.base.position.size.block = block_size;
+
+        for kid in self.block_flow.base.child_iter_mut() {
+            let child_table_cell = kid.as_mut_table_cell();
+            if child_table_cell.row_span == 1 {
+                child_table_cell.set_block_size(block_size)
+            }
+        }
+    }
+
+    /// Returns the inline size of the given column together with the spacing or collapsed border
+    /// before it, or zero if there is no such column.
+    fn column_inline_size_with_spacing(&self, column_index: usize, collapsing_borders: bool) -> Au {
+        let column_inline_size = match self.column_computed_inline_sizes.get(column_index) {
+            Some(column_computed_inline_size) => column_computed_inline_size.size,
+            None => return Au(0),
+        };
+        let spacing = if collapsing_borders {
+            self.collapsed_border_spacing.inline.get(column_index).map_or(Au(0), |x| *x)
+        } else {
+            self.spacing.horizontal
+        };
+        column_inline_size + spacing
+    }
+
     pub fn populate_collapsed_border_spacing<'a, I>(
             &mut self,
             collapsed_inline_direction_border_widths_for_table: &[Au],
@@ -261,6 +312,7 @@ impl Flow for TableRowFlow {
         self.preliminary_collapsed_borders.reset(
             CollapsedBorder::inline_start(&row_style,
                                           CollapsedBorderProvenance::FromTableRow));
+        self.cell_intrinsic_inline_sizes.clear();
 
         {
             let children_count = self.block_flow.base.children.len();
@@ -272,6 +324,7 @@ impl Flow for TableRowFlow {
                 // fixed and automatic table layout calculation.
                 let child_specified_inline_size;
                 let child_column_span;
+                let child_row_span;
                 {
                     let child_table_cell = kid.as_mut_table_cell();
                     child_specified_inline_size = child_table_cell.block_flow
@@ -279,6 +332,7 @@ impl Flow for TableRowFlow {
                                                                   .style
                                                                   .content_inline_size();
                     child_column_span = child_table_cell.column_span;
+                    child_row_span = child_table_cell.row_span;
 
                     // Perform border collapse if necessary.
                     if collapsing_borders {
@@ -323,6 +377,8 @@ impl Flow for TableRowFlow {
                 self.cell_intrinsic_inline_sizes.push(CellIntrinsicInlineSize {
                     column_size: child_column_inline_size,
                     column_span: child_column_span,
+                    row_span: child_row_span,
+                    column_index: i,
                 });
             }
         }
@@ -351,38 +407,41 @@ impl Flow for TableRowFlow {
                                                       shared_context,
                                                       containing_block_inline_size);
 
-        // Spread out the completed inline sizes among columns with spans > 1.
+        // Spread out the completed inline sizes among columns with spans > 1, and skip over the
+        // columns taken up by cells spanning down from earlier rows.
+        //
+        // If we're in fixed layout mode and there are more cells in this row than columns we know
+        // about, the behavior is undefined according to CSS 2.1 § 17.5.2.1. So just use zero.
+        //
+        // FIXME(pcwalton): $10 says this isn't Web compatible.
+        let collapsing_borders = self.block_flow.fragment.style().get_inheritedtable().border_collapse ==
+            border_collapse::T::collapse;
         let mut computed_inline_size_for_cells = Vec::new();
-        let mut column_computed_inline_size_iterator = self.column_computed_inline_sizes.iter();
+        let mut next_column_index = 0;
         for cell_intrinsic_inline_size in &self.cell_intrinsic_inline_sizes {
-            // Start with the computed inline size for the first column in the span.
-            let mut column_computed_inline_size =
-                match column_computed_inline_size_iterator.next() {
-                    Some(column_computed_inline_size) => *column_computed_inline_size,
-                    None => {
-                        // We're in fixed layout mode and there are more cells in this row than
-                        // columns we know about. According to CSS 2.1 § 17.5.2.1, the behavior is
-                        // now undefined. So just use zero.
-                        //
-                        // FIXME(pcwalton): $10 says this isn't Web compatible.
-                        ColumnComputedInlineSize {
-                            size: Au(0),
-                        }
-                    }
-                };
-
-            // Add in computed inline sizes for any extra columns in the span.
-            for _ in 1..cell_intrinsic_inline_size.column_span {
-                let extra_column_computed_inline_size =
-                    match column_computed_inline_size_iterator.next() {
-                        Some(column_computed_inline_size) => column_computed_inline_size,
-                        None => break,
-                    };
-                column_computed_inline_size.size = column_computed_inline_size.size +
-                    extra_column_computed_inline_size.size + self.spacing.horizontal;
+            let column_index = max(cell_intrinsic_inline_size.column_index, next_column_index);
+            let column_end = max(cell_intrinsic_inline_size.column_end(), column_index + 1);
+            let mut skipped_size = Au(0);
+            for skipped_column_index in next_column_index..column_index {
+                skipped_size = skipped_size +
+                    self.column_inline_size_with_spacing(skipped_column_index, collapsing_borders)
             }
-
-            computed_inline_size_for_cells.push(column_computed_inline_size)
+            let mut size = self.column_computed_inline_sizes
+                               .get(column_index)
+                               .map_or(Au(0), |column_computed_inline_size| {
+                column_computed_inline_size.size
+            });
+            for extra_column_index in (column_index + 1)..column_end {
+                size = size +
+                    self.column_inline_size_with_spacing(extra_column_index, collapsing_borders)
+            }
+            computed_inline_size_for_cells.push(CellComputedInlineSize {
+                size: size,
+                skipped_size: skipped_size,
+                column_index: column_index,
+                column_end: column_end,
+            });
+            next_column_index = column_end;
         }
 
         // Set up border collapse info.
@@ -525,7 +584,7 @@ impl CollapsedBordersForRow {
 
 #[derive(Clone, Debug)]
 pub struct CollapsedBorderSpacingForRow {
-    /// The spacing in between each column.
+    /// The spacing before each column, plus the spacing after the last one.
     inline: Vec<Au>,
     /// The spacing above this row.
     pub block_start: Au,
@@ -753,7 +812,7 @@ fn set_inline_position_of_child_flow(
         child_index: usize,
         row_writing_mode: WritingMode,
         table_writing_mode: WritingMode,
-        column_computed_inline_sizes: &[ColumnComputedInlineSize],
+        cell_computed_inline_sizes: &[CellComputedInlineSize],
         border_spacing: &border_spacing::T,
         border_collapse_info: &Option<BorderCollapseInfoForChildTableCell>,
         parent_content_inline_size: Au,
@@ -764,6 +823,17 @@ fn set_inline_position_of_child_flow(
     }
 
     let reverse_column_order = table_writing_mode.is_bidi_ltr() != row_writing_mode.is_bidi_ltr();
+    let cell_computed_inline_size = match cell_computed_inline_sizes.get(child_index) {
+        Some(cell_computed_inline_size) => *cell_computed_inline_size,
+        None => return,
+    };
+
+    // Move over past any columns taken up by cells spanning down from earlier rows.
+    if reverse_column_order {
+        *inline_end_margin_edge = *inline_end_margin_edge + cell_computed_inline_size.skipped_size
+    } else {
+        *inline_start_margin_edge = *inline_start_margin_edge + cell_computed_inline_size.skipped_size
+    }
 
     // Handle border collapsing, if necessary.
     let child_table_cell = child_flow.as_mut_table_cell();
@@ -789,11 +859,11 @@ fn set_inline_position_of_child_flow(
                                                       .map_or(CollapsedBorder::new(), |x| *x),
                 inline_start_width: border_collapse_info.collapsed_border_spacing_for_row
                                                         .inline
-                                                        .get(child_index)
+                                                        .get(cell_computed_inline_size.column_index)
                                                         .map_or(Au(0), |x| *x),
                 inline_end_width: border_collapse_info.collapsed_border_spacing_for_row
                                                       .inline
-                                                      .get(child_index + 1)
+                                                      .get(cell_computed_inline_size.column_end)
                                                       .map_or(Au(0), |x| *x),
                 block_start_width: border_collapse_info.collapsed_border_spacing_for_row
                                                        .block_start,
@@ -819,7 +889,7 @@ fn set_inline_position_of_child_flow(
         }
     }
 
-    let column_inline_size = column_computed_inline_sizes[child_index].size;
+    let column_inline_size = cell_computed_inline_size.size;
     let kid_base = &mut child_table_cell.block_flow.base;
     kid_base.block_container_inline_size = column_inline_size;
 
@@ -20,7 +20,7 @@ use context::{LayoutContext, SharedLayoutContext};
 use display_list_builder::DisplayListBuildState;
 use euclid::Point2D;
 use floats::FloatKind;
-use flow::{Flow, FlowClass, ImmutableFlowUtils, INLINE_POSITION_IS_STATIC, OpaqueFlow};
+use flow::{self, Flow, FlowClass, ImmutableFlowUtils, INLINE_POSITION_IS_STATIC, OpaqueFlow};
 use fragment::{Fragment, FragmentBorderBoxIterator, Overflow};
 use gfx::display_list::StackingContext;
 use gfx_traits::StackingContextId;
@@ -45,6 +45,19 @@ pub enum TableLayout {
     Auto
 }
 
+impl TableLayout {
+    /// Returns the table layout algorithm to use for a table with the given style. Per CSS 2.1 §
+    /// 17.5.2, the fixed algorithm is only used if the table has an explicit inline size.
+    pub fn from_style(style: &ServoComputedValues) -> TableLayout {
+        if style.get_table().table_layout == table_layout::T::fixed &&
+                style.content_inline_size() != LengthOrPercentageOrAuto::Auto {
+            TableLayout::Fixed
+        } else {
+            TableLayout::Auto
+        }
+    }
+}
+
 /// A table wrapper flow based on a block formatting context.
 #[derive(RustcEncodable)]
 pub struct TableWrapperFlow {
@@ -60,12 +73,7 @@ pub struct TableWrapperFlow {
 impl TableWrapperFlow {
     pub fn from_fragment(fragment: Fragment, float_kind: Option<FloatKind>) -> TableWrapperFlow {
         let mut block_flow = BlockFlow::from_fragment(fragment, float_kind);
-        let table_layout = if block_flow.fragment().style().get_table().table_layout ==
-                              table_layout::T::fixed {
-            TableLayout::Fixed
-        } else {
-            TableLayout::Auto
-        };
+        let table_layout = TableLayout::from_style(block_flow.fragment().style());
         TableWrapperFlow {
             block_flow: block_flow,
             column_intrinsic_inline_sizes: vec!(),
@@ -86,6 +94,13 @@ impl TableWrapperFlow {
         (table_border_padding, spacing)
     }
 
+    /// Returns the largest minimum inline size among the captions of this table.
+    fn minimum_width_of_captions(&self) -> Au {
+        self.block_flow.base.children.iter().filter(|kid| kid.is_table_caption()).fold(
+            Au(0),
+            |accumulator, kid| max(accumulator, flow::base(kid).intrinsic_inline_sizes.minimum_inline_size))
+    }
+
     /// Calculates table column sizes for automatic layout per INTRINSIC § 4.3.
     fn calculate_table_column_sizes_for_automatic_layout(
             &mut self,
@@ -222,6 +237,13 @@ impl TableWrapperFlow {
                 accumulator + column_intrinsic_inline_sizes.preferred
             });
 
+        // Per CSS 2.1 § 17.5.2, the table is made wide enough for its captions too.
+        let minimum_width_of_captions = self.minimum_width_of_captions();
+        let minimum_width_of_all_columns = max(minimum_width_of_all_columns,
+                                               minimum_width_of_captions);
+        let preferred_width_of_all_columns = max(preferred_width_of_all_columns,
+                                                 minimum_width_of_captions);
+
         // Delegate to the appropriate inline size computer to find the constraint inputs and write
         // the constraint solutions in.
         let border_collapse = self.block_flow.fragment.style.get_inheritedtable().border_collapse;
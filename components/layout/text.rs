@@ -10,7 +10,7 @@ use app_units::Au;
 use fragment::{Fragment, REQUIRES_LINE_BREAK_AFTERWARD_IF_WRAPPING_ON_NEWLINES, ScannedTextFlags};
 use fragment::{SELECTED, ScannedTextFragmentInfo, SpecificFragmentInfo, UnscannedTextFragmentInfo};
 use gfx::font::{DISABLE_KERNING_SHAPING_FLAG, FontMetrics, IGNORE_LIGATURES_SHAPING_FLAG};
-use gfx::font::{RTL_FLAG, RunMetrics, ShapingFlags, ShapingOptions};
+use gfx::font::{RTL_FLAG, RunMetrics, ShapingFlags, ShapingOptions, VERTICAL_FLAG};
 use gfx::font_context::FontContext;
 use gfx::text::glyph::ByteIndex;
 use gfx::text::text_run::TextRun;
@@ -64,21 +64,67 @@ impl TextRunScanner {
         }
     }
 
+    /// Scans fragments that aren't the inline content of a block container, e.g. generated text,
+    /// for text runs. Their bidi paragraph level is that of the first fragment.
     pub fn scan_for_runs(&mut self,
                          font_context: &mut FontContext,
-                         mut fragments: LinkedList<Fragment>)
+                         fragments: LinkedList<Fragment>)
                          -> InlineFragments {
+        debug_assert!(!fragments.is_empty());
+        let para_level = fragments.front().unwrap().style.writing_mode.to_bidi_level();
+        self.scan_for_runs_with_bidi_paragraph(font_context, fragments, Some(para_level), ("", "")).0
+    }
+
+    /// Scans the inline content of a block container for text runs. The content forms a bidi
+    /// paragraph whose level is set by the `direction` and `unicode-bidi` of the block per
+    /// CSS-WRITING-MODES-3 § 2.4, and that level is returned along with the fragments.
+    pub fn scan_paragraph_for_runs(&mut self,
+                                   font_context: &mut FontContext,
+                                   fragments: LinkedList<Fragment>,
+                                   block_style: &ServoComputedValues)
+                                   -> (InlineFragments, u8) {
+        use style::computed_values::direction::T::{ltr, rtl};
+        use style::computed_values::unicode_bidi::T::*;
+
+        // On a block container, `plaintext` computes the paragraph level from its text (UAX #9
+        // rules P2 and P3), and the override values apply to its inline content. `embed` and
+        // `isolate` have no effect, since the block is a paragraph of its own.
+        let para_level = block_style.writing_mode.to_bidi_level();
+        let (para_level, control_chars) = match (block_style.get_text().unicode_bidi,
+                                                 block_style.get_inheritedbox().direction) {
+            (plaintext, _) => (None, ("", "")),
+            (bidi_override, ltr) | (isolate_override, ltr) => (Some(para_level), ("\u{202D}", "\u{202C}")),
+            (bidi_override, rtl) | (isolate_override, rtl) => (Some(para_level), ("\u{202E}", "\u{202C}")),
+            (normal, _) | (embed, _) | (isolate, _) => (Some(para_level), ("", "")),
+        };
+        self.scan_for_runs_with_bidi_paragraph(font_context, fragments, para_level, control_chars)
+    }
+
+    /// Scans fragments for text runs, resolving bidi embedding levels for them as one paragraph
+    /// at `para_level`, or at the level of its first strong character if `None`. The text is
+    /// wrapped in the given pair of bidi control characters first.
+    fn scan_for_runs_with_bidi_paragraph(&mut self,
+                                         font_context: &mut FontContext,
+                                         mut fragments: LinkedList<Fragment>,
+                                         para_level: Option<u8>,
+                                         control_chars: (&str, &str))
+                                         -> (InlineFragments, u8) {
         debug!("TextRunScanner: scanning {} fragments for text runs...", fragments.len());
         debug_assert!(!fragments.is_empty());
 
         // Calculate bidi embedding levels, so we can split bidirectional fragments for reordering.
-        let text = text(&fragments);
-        let para_level = fragments.front().unwrap().style.writing_mode.to_bidi_level();
-        let bidi_info = process_text(&text, Some(para_level));
+        let (start_control_chars, end_control_chars) = control_chars;
+        let text = format!("{}{}{}", start_control_chars, text(&fragments), end_control_chars);
+        let bidi_info = process_text(&text, para_level);
+        let para_level = match bidi_info.paragraphs.first() {
+            Some(paragraph) => paragraph.level,
+            None => para_level.unwrap_or(0),
+        };
+        let levels = &bidi_info.levels[start_control_chars.len()..];
 
         // Optimization: If all the text is LTR, don't bother splitting on bidi levels.
-        let bidi_levels = if bidi_info.levels.iter().cloned().any(is_rtl) {
-            Some(&bidi_info.levels[..])
+        let bidi_levels = if levels.iter().cloned().any(is_rtl) {
+            Some(levels)
         } else {
             None
         };
@@ -111,9 +157,9 @@ impl TextRunScanner {
         }
 
         debug!("TextRunScanner: complete.");
-        InlineFragments {
+        (InlineFragments {
             fragments: new_fragments,
-        }
+        }, para_level)
     }
 
     /// A "clump" is a range of inline flow leaves that can be merged together into a single
@@ -151,6 +197,8 @@ impl TextRunScanner {
             let letter_spacing;
             let word_spacing;
             let text_rendering;
+            let text_orientation;
+            let is_vertical;
             {
                 let in_fragment = self.clump.front().unwrap();
                 let font_style = in_fragment.style().get_font_arc();
@@ -169,6 +217,8 @@ impl TextRunScanner {
                                .map(|lop| lop.to_hash_key())
                                .unwrap_or((Au(0), NotNaN::new(0.0).unwrap()));
                 text_rendering = inherited_text_style.text_rendering;
+                text_orientation = in_fragment.style().get_inheritedbox().text_orientation;
+                is_vertical = in_fragment.style().writing_mode.is_vertical();
             }
 
             // First, transform/compress text of all the nodes.
@@ -303,6 +353,9 @@ impl TextRunScanner {
                 if is_rtl(run_info.bidi_level) {
                     options.flags.insert(RTL_FLAG);
                 }
+                if is_vertical && is_upright(text_orientation, run_info.script) {
+                    options.flags.insert(VERTICAL_FLAG);
+                }
                 let mut font = fontgroup.fonts.get(run_info.font_index).unwrap().borrow_mut();
                 ScannedTextRun {
                     run: Arc::new(TextRun::new(&mut *font,
@@ -413,20 +466,9 @@ impl TextRunScanner {
 #[inline]
 fn bounding_box_for_run_metrics(metrics: &RunMetrics, writing_mode: WritingMode)
                                 -> LogicalSize<Au> {
-    // This does nothing, but it will fail to build
-    // when more values are added to the `text-orientation` CSS property.
-    // This will be a reminder to update the code below.
-    let dummy: Option<text_orientation::T> = None;
-    match dummy {
-        Some(text_orientation::T::sideways_right) |
-        Some(text_orientation::T::sideways_left) |
-        Some(text_orientation::T::sideways) |
-        None => {}
-    }
-
-    // In vertical sideways or horizontal upright text,
-    // the "width" of text metrics is always inline
-    // This will need to be updated when other text orientations are supported.
+    // The "width" of text metrics is always inline: for sideways and horizontal text because the
+    // glyphs are laid out along the x axis, and for upright vertical text because the shaper
+    // reports vertical advances.
     LogicalSize::new(
         writing_mode,
         metrics.bounding_box.size.width,
@@ -446,6 +488,22 @@ pub fn font_metrics_for_style(font_context: &mut FontContext, font_style: Arc<st
     font.metrics.clone()
 }
 
+/// Shapes each line of the text of a cue shown over a video, in the first font of `style`.
+pub fn cue_text_runs(font_context: &mut FontContext, style: &ServoComputedValues, text: &str)
+                     -> Vec<Arc<TextRun>> {
+    let fontgroup = font_context.layout_font_group_for_style(style.get_font_arc());
+    let mut font = fontgroup.fonts[0].borrow_mut();
+    let options = ShapingOptions {
+        letter_spacing: None,
+        word_spacing: (Au(0), NotNaN::new(0.0).unwrap()),
+        script: Script::Common,
+        flags: ShapingFlags::empty(),
+    };
+    text.lines().filter(|line| !line.trim().is_empty()).map(|line| {
+        Arc::new(TextRun::new(&mut *font, line.trim().to_owned(), &options, 0))
+    }).collect()
+}
+
 /// Returns the line block-size needed by the given computed style and font size.
 pub fn line_height_from_style(style: &ServoComputedValues, metrics: &FontMetrics) -> Au {
     let font_size = style.get_font().font_size;
@@ -706,6 +764,28 @@ fn is_compatible(a: Script, b: Script) -> bool {
     a == b || !is_specific(a) || !is_specific(b)
 }
 
+/// Whether a run of text in the given script is set with upright glyphs in vertical text.
+///
+/// For `text-orientation: mixed`, this approximates the Unicode `Vertical_Orientation` property
+/// by script: scripts that are normally written vertically stay upright, everything else is
+/// turned sideways.
+/// https://drafts.csswg.org/css-writing-modes-3/#text-orientation
+fn is_upright(text_orientation: text_orientation::T, script: Script) -> bool {
+    match text_orientation {
+        text_orientation::T::upright => true,
+        text_orientation::T::mixed => {
+            match script {
+                Script::Bopomofo | Script::Han | Script::Hangul | Script::Hiragana |
+                Script::Katakana | Script::Yi => true,
+                _ => false,
+            }
+        }
+        text_orientation::T::sideways |
+        text_orientation::T::sideways_right |
+        text_orientation::T::sideways_left => false,
+    }
+}
+
 /// Returns true if the script is not invalid or inherited.
 fn is_specific(script: Script) -> bool {
     script != Script::Common && script != Script::Inherited
@@ -8,7 +8,8 @@ use construct::FlowConstructor;
 use context::{LayoutContext, SharedLayoutContext};
 use display_list_builder::DisplayListBuildState;
 use flow::{self, PreorderFlowTraversal};
-use flow::{CAN_BE_FRAGMENTED, Flow, ImmutableFlowUtils, PostorderFlowTraversal};
+use flow::{CAN_BE_FRAGMENTED, ESTABLISHES_STACKING_CONTEXT, Flow, ImmutableFlowUtils};
+use flow::PostorderFlowTraversal;
 use gfx::display_list::OpaqueNode;
 use script_layout_interface::restyle_damage::{BUBBLE_ISIZES, REFLOW, REFLOW_OUT_OF_FLOW, REPAINT, RestyleDamage};
 use script_layout_interface::wrapper_traits::{LayoutNode, ThreadSafeLayoutNode};
@@ -215,16 +216,38 @@ pub struct BuildDisplayList<'a> {
 impl<'a> BuildDisplayList<'a> {
     #[inline]
     pub fn traverse(&mut self, flow: &mut Flow) {
+        self.process(flow);
+
+        for kid in flow::child_iter_mut(flow) {
+            self.traverse(kid);
+        }
+    }
+
+    /// Builds the display list of `flow` and its descendants, except for the descendants that
+    /// establish a stacking context, which are passed to `defer` to be built separately. `defer`
+    /// is called when the items of the flows before the deferred one in tree order are built.
+    #[inline]
+    pub fn traverse_deferring_stacking_contexts<F>(&mut self, flow: &mut Flow, defer: &mut F)
+                                                   where F: FnMut(&mut DisplayListBuildState<'a>, &mut Flow) {
+        self.process(flow);
+
+        for kid in flow::child_iter_mut(flow) {
+            if flow::base(kid).flags.contains(ESTABLISHES_STACKING_CONTEXT) {
+                defer(&mut self.state, kid)
+            } else {
+                self.traverse_deferring_stacking_contexts(kid, defer)
+            }
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, flow: &mut Flow) {
         if self.should_process() {
             self.state.push_stacking_context_id(flow::base(flow).stacking_context_id);
             flow.build_display_list(&mut self.state);
             flow::mut_base(flow).restyle_damage.remove(REPAINT);
             self.state.pop_stacking_context_id();
         }
-
-        for kid in flow::child_iter_mut(flow) {
-            self.traverse(kid);
-        }
     }
 
     #[inline]
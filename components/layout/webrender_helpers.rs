@@ -9,10 +9,10 @@
 
 use app_units::Au;
 use azure::azure_hl::Color;
-use euclid::{Point2D, Rect, Size2D};
+use euclid::{Matrix4D, Point2D, Rect, Size2D};
 use gfx::display_list::{BorderRadii, BoxShadowClipMode, ClippingRegion};
 use gfx::display_list::{DisplayItem, DisplayList, DisplayListTraversal};
-use gfx::display_list::{GradientStop, StackingContext, StackingContextType};
+use gfx::display_list::{GradientStop, StackingContext, StackingContextType, TextOrientation};
 use gfx_traits::{FragmentType, ScrollPolicy, StackingContextId};
 use style::computed_values::{image_rendering, mix_blend_mode};
 use style::computed_values::filter::{self, Filter};
@@ -40,6 +40,15 @@ trait WebRenderStackingContextConverter {
                                          builder: &mut webrender_traits::DisplayListBuilder,
                                          frame_builder: &mut WebRenderFrameBuilder,
                                          force_positioned_stacking_level: bool);
+
+    fn convert_item_to_webrender(&self,
+                                 item: &DisplayItem,
+                                 api: &mut webrender_traits::RenderApi,
+                                 pipeline_id: webrender_traits::PipelineId,
+                                 scroll_layer_id: Option<webrender_traits::ScrollLayerId>,
+                                 scroll_policy: ScrollPolicy,
+                                 builder: &mut webrender_traits::DisplayListBuilder,
+                                 frame_builder: &mut WebRenderFrameBuilder);
 }
 
 pub trait WebRenderDisplayListConverter {
@@ -211,6 +220,19 @@ impl ToBlendMode for mix_blend_mode::T {
     }
 }
 
+trait ToScrollPolicy {
+    fn to_scroll_policy(&self) -> webrender_traits::ScrollPolicy;
+}
+
+impl ToScrollPolicy for ScrollPolicy {
+    fn to_scroll_policy(&self) -> webrender_traits::ScrollPolicy {
+        match *self {
+            ScrollPolicy::Scrollable => webrender_traits::ScrollPolicy::Scrollable,
+            ScrollPolicy::FixedPosition => webrender_traits::ScrollPolicy::Fixed,
+        }
+    }
+}
+
 trait ToImageRendering {
     fn to_image_rendering(&self) -> webrender_traits::ImageRendering;
 }
@@ -262,7 +284,13 @@ impl WebRenderStackingContextConverter for StackingContext {
                                          _force_positioned_stacking_level: bool) {
         for child in self.children() {
             while let Some(item) = traversal.advance(self) {
-                item.convert_to_webrender(builder, frame_builder);
+                self.convert_item_to_webrender(item,
+                                               api,
+                                               pipeline_id,
+                                               scroll_layer_id,
+                                               scroll_policy,
+                                               builder,
+                                               frame_builder);
             }
 
             if child.context_type == StackingContextType::Real {
@@ -293,10 +321,62 @@ impl WebRenderStackingContextConverter for StackingContext {
         }
 
         while let Some(item) = traversal.advance(self) {
-            item.convert_to_webrender(builder, frame_builder);
+            self.convert_item_to_webrender(item,
+                                           api,
+                                           pipeline_id,
+                                           scroll_layer_id,
+                                           scroll_policy,
+                                           builder,
+                                           frame_builder);
         }
     }
 
+    fn convert_item_to_webrender(&self,
+                                 item: &DisplayItem,
+                                 api: &mut webrender_traits::RenderApi,
+                                 pipeline_id: webrender_traits::PipelineId,
+                                 scroll_layer_id: Option<webrender_traits::ScrollLayerId>,
+                                 scroll_policy: ScrollPolicy,
+                                 builder: &mut webrender_traits::DisplayListBuilder,
+                                 frame_builder: &mut WebRenderFrameBuilder) {
+        let blend_mode = match *item {
+            DisplayItem::ImageClass(ref item) => item.blend_mode,
+            DisplayItem::GradientClass(ref item) => item.blend_mode,
+            _ => mix_blend_mode::T::normal,
+        };
+        if blend_mode == mix_blend_mode::T::normal {
+            return item.convert_to_webrender(builder, frame_builder)
+        }
+
+        // WebRender only blends whole stacking contexts, so a blended background layer is
+        // wrapped in a stacking context of its own that shares this one's coordinate space.
+        let scroll_layer_id = if self.scrolls_overflow_area {
+            scroll_layer_id
+        } else {
+            None
+        };
+        let bounds = Rect::new(Point2D::zero(), self.bounds.size);
+        let stacking_context_id = StackingContextId::new_outer(FragmentType::FragmentBody);
+        let mut sc =
+            webrender_traits::StackingContext::new(stacking_context_id.convert_to_webrender(),
+                                                   scroll_layer_id,
+                                                   scroll_policy.to_scroll_policy(),
+                                                   bounds.to_rectf(),
+                                                   self.overflow.to_rectf(),
+                                                   0,
+                                                   &Matrix4D::identity(),
+                                                   &Matrix4D::identity(),
+                                                   false,
+                                                   blend_mode.to_blend_mode(),
+                                                   vec![],
+                                                   &mut frame_builder.auxiliary_lists_builder);
+
+        let mut layer_builder = webrender_traits::DisplayListBuilder::new();
+        item.convert_to_webrender(&mut layer_builder, frame_builder);
+        frame_builder.add_display_list(api, layer_builder.finalize(), &mut sc);
+        builder.push_stacking_context(frame_builder.add_stacking_context(api, pipeline_id, sc));
+    }
+
     fn convert_to_webrender<'a>(&self,
                                 traversal: &mut DisplayListTraversal<'a>,
                                 api: &mut webrender_traits::RenderApi,
@@ -310,17 +390,24 @@ impl WebRenderStackingContextConverter for StackingContext {
             scroll_policy = layer_info.scroll_policy
         }
 
-        let webrender_scroll_policy = match scroll_policy {
-            ScrollPolicy::Scrollable => webrender_traits::ScrollPolicy::Scrollable,
-            ScrollPolicy::FixedPosition => webrender_traits::ScrollPolicy::Fixed,
-        };
-
         let webrender_stacking_context_id = self.id.convert_to_webrender();
 
+        // WebRender only composites a stacking context as a group of its own when it filters or
+        // blends, so isolated groups that do neither get an opacity filter that changes nothing.
+        let mut filters = self.filters.to_filter_ops();
+        if self.isolated && filters.is_empty() && self.blend_mode == mix_blend_mode::T::normal {
+            filters.push(webrender_traits::FilterOp::Opacity(1.0))
+        }
+
+        // WebRender has no notion of back faces, so hidden ones are made fully transparent.
+        if self.is_hidden_back_face() {
+            filters.push(webrender_traits::FilterOp::Opacity(0.0))
+        }
+
         let mut sc =
             webrender_traits::StackingContext::new(webrender_stacking_context_id,
                                                    scroll_layer_id,
-                                                   webrender_scroll_policy,
+                                                   scroll_policy.to_scroll_policy(),
                                                    self.bounds.to_rectf(),
                                                    self.overflow.to_rectf(),
                                                    self.z_index,
@@ -328,7 +415,7 @@ impl WebRenderStackingContextConverter for StackingContext {
                                                    &self.perspective,
                                                    self.establishes_3d_context,
                                                    self.blend_mode.to_blend_mode(),
-                                                   self.filters.to_filter_ops(),
+                                                   filters,
                                                    &mut frame_builder.auxiliary_lists_builder);
 
         let mut builder = webrender_traits::DisplayListBuilder::new();
@@ -391,6 +478,7 @@ impl WebRenderDisplayItemConverter for DisplayItem {
             DisplayItem::TextClass(ref item) => {
                 let mut origin = item.baseline_origin.clone();
                 let mut glyphs = vec!();
+                let vertical = item.orientation == TextOrientation::Vertical;
 
                 for slice in item.text_run.natural_word_slices_in_visual_order(&item.range) {
                     for glyph in slice.glyphs.iter_glyphs_for_byte_range(&slice.range) {
@@ -408,7 +496,11 @@ impl WebRenderDisplayItemConverter for DisplayItem {
                             };
                             glyphs.push(glyph);
                         }
-                        origin.x = origin.x + glyph_advance;
+                        if vertical {
+                            origin.y = origin.y + glyph_advance;
+                        } else {
+                            origin.x = origin.x + glyph_advance;
+                        }
                     };
                 }
 
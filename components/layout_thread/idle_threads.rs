@@ -0,0 +1,92 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The threads whose layout thread has exited, kept for a while so that a
+//! later pipeline of the same origin in the same browsing context can reuse
+//! them with their font and style caches still warm.
+
+use msg::constellation_msg::PipelineId;
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use url::Origin;
+
+/// An idle thread, and the channel on which it waits to be handed a new
+/// pipeline.
+struct IdleThread<T> {
+    id: usize,
+    lineage: PipelineId,
+    origin: Origin,
+    sender: Sender<T>,
+}
+
+/// The idle threads, keyed by the lineage of the pipelines they ran and by
+/// origin. A lineage is the first pipeline of a browsing context, and each
+/// pipeline that replaces another by navigating inherits its lineage.
+pub struct IdleThreads<T> {
+    lineages: HashMap<PipelineId, PipelineId>,
+    idle_threads: Vec<IdleThread<T>>,
+    next_id: usize,
+}
+
+impl<T> IdleThreads<T> {
+    pub fn new() -> IdleThreads<T> {
+        IdleThreads {
+            lineages: HashMap::new(),
+            idle_threads: vec![],
+            next_id: 0,
+        }
+    }
+
+    /// Records that the layout thread of `pipeline_id` is starting, in place
+    /// of the one of `old_pipeline_id` if it replaces a pipeline, and returns
+    /// its lineage.
+    pub fn start_pipeline(&mut self,
+                          pipeline_id: PipelineId,
+                          old_pipeline_id: Option<PipelineId>)
+                          -> PipelineId {
+        let lineage = old_pipeline_id.and_then(|id| self.lineages.get(&id).cloned())
+                                     .unwrap_or(pipeline_id);
+        self.lineages.insert(pipeline_id, lineage);
+        lineage
+    }
+
+    /// Records that the layout thread of `pipeline_id` has exited.
+    pub fn finish_pipeline(&mut self, pipeline_id: PipelineId) {
+        self.lineages.remove(&pipeline_id);
+    }
+
+    /// Takes the channel of a thread that ran a pipeline of `lineage` and
+    /// `origin`, if one is idle.
+    pub fn take(&mut self, lineage: PipelineId, origin: &Origin) -> Option<Sender<T>> {
+        let index = self.idle_threads.iter().position(|thread| {
+            thread.lineage == lineage && thread.origin == *origin
+        });
+        index.map(|index| self.idle_threads.remove(index).sender)
+    }
+
+    /// Makes a thread that ran a pipeline of `lineage` and `origin` idle, and
+    /// returns the id with which to remove it once it stops waiting.
+    pub fn add(&mut self, lineage: PipelineId, origin: Origin, sender: Sender<T>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.idle_threads.push(IdleThread {
+            id: id,
+            lineage: lineage,
+            origin: origin,
+            sender: sender,
+        });
+        id
+    }
+
+    /// Removes an idle thread, returning false if a pipeline took it first.
+    pub fn remove(&mut self, id: usize) -> bool {
+        match self.idle_threads.iter().position(|thread| thread.id == id) {
+            Some(index) => {
+                self.idle_threads.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -39,10 +39,13 @@ extern crate script_layout_interface;
 extern crate script_traits;
 extern crate serde_json;
 extern crate style;
+extern crate time as std_time;
 extern crate url;
 extern crate util;
 extern crate webrender_traits;
 
+pub mod idle_threads;
+
 use app_units::Au;
 use azure::azure::AzColor;
 use euclid::Matrix4D;
@@ -57,23 +60,26 @@ use gfx::font;
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context;
 use gfx::paint_thread::LayoutToPaintMsg;
-use gfx_traits::{Epoch, FragmentType, LayerId, ScrollPolicy, StackingContextId, color};
+use gfx_traits::{Epoch, FragmentType, LayerId, ScrollContainerScrollbars, ScrollPolicy, StackingContextId};
+use gfx_traits::color;
 use heapsize::HeapSizeOf;
+use idle_threads::IdleThreads;
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use layout::animation;
 use layout::construct::ConstructionResult;
 use layout::context::{LayoutContext, SharedLayoutContext, heap_size_of_local_context};
 use layout::display_list_builder::ToGfxColor;
-use layout::flow::{self, Flow, ImmutableFlowUtils, MutableFlowUtils, MutableOwnedFlowUtils};
+use layout::flow::{self, Flow, FlowClass, ImmutableFlowUtils, MutableFlowUtils, MutableOwnedFlowUtils};
 use layout::flow_ref::{self, FlowRef};
-use layout::incremental::{LayoutDamageComputation, REFLOW_ENTIRE_DOCUMENT};
+use layout::incremental::{LayoutDamageComputation, REFLOW_FORMATTING_CONTEXT};
 use layout::layout_debug;
+use layout::opaque_node::OpaqueNodeMethods;
 use layout::parallel;
 use layout::query::{LayoutRPCImpl, LayoutThreadData, process_content_box_request, process_content_boxes_request};
 use layout::query::{process_margin_style_query, process_node_overflow_request, process_resolved_style_request};
 use layout::query::{process_node_geometry_request, process_node_layer_id_request, process_node_scroll_area_request};
-use layout::query::process_offset_parent_query;
+use layout::query::{collect_container_sizes, process_offset_parent_query, process_text_index_request};
 use layout::sequential;
 use layout::traversal::{ComputeAbsolutePositions, RecalcStyleAndConstructFlows};
 use layout::webrender_helpers::{WebRenderDisplayListConverter, WebRenderFrameBuilder};
@@ -92,26 +98,30 @@ use script_layout_interface::reporter::CSSErrorReporter;
 use script_layout_interface::restyle_damage::{REFLOW, REFLOW_OUT_OF_FLOW, REPAINT, REPOSITION};
 use script_layout_interface::restyle_damage::STORE_OVERFLOW;
 use script_layout_interface::rpc::{LayoutRPC, MarginStyleResponse, NodeOverflowResponse, OffsetParentResponse};
+use script_layout_interface::rpc::TextIndexResponse;
 use script_layout_interface::wrapper_traits::LayoutNode;
 use script_traits::{ConstellationControlMsg, LayoutControlMsg, LayoutMsg as ConstellationMsg};
+use script_traits::PaintMetricType;
 use script_traits::{StackingContextScrollState, UntrustedNodeAddress};
 use std::borrow::ToOwned;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::BuildHasherDefault;
 use std::ops::{Deref, DerefMut};
 use std::process;
 use std::sync::{Arc, Mutex, MutexGuard, RwLock};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{Receiver, Sender, channel};
+use std::time::Duration;
 use style::animation::Animation;
-use style::computed_values::{filter, mix_blend_mode};
-use style::context::{LocalStyleContextCreationInfo, ReflowGoal, SharedStyleContext};
+use style::computed_values::{backface_visibility, filter, mix_blend_mode, transform_style};
+use style::context::{LocalStyleContextCreationInfo, QuirksMode, ReflowGoal, SharedStyleContext};
 use style::dom::{TDocument, TElement, TNode};
 use style::error_reporting::{ParseErrorReporter, StdoutErrorReporter};
 use style::logical_geometry::LogicalPoint;
 use style::media_queries::{Device, MediaType};
 use style::parallel::WorkQueueData;
 use style::parser::ParserContextExtraData;
+use style::properties::ServoComputedValues;
 use style::refcell::RefCell;
 use style::selector_matching::Stylist;
 use style::stylesheets::{CSSRuleIteratorExt, Origin, Stylesheet, UserAgentStylesheets};
@@ -182,6 +192,17 @@ pub struct LayoutThread {
     /// Is this the first reflow in this LayoutThread?
     first_reflow: bool,
 
+    /// Whether the script thread was told about the first display list with any items.
+    first_paint_reported: bool,
+
+    /// Whether the script thread was told about the first display list with text,
+    /// images or canvases.
+    first_contentful_paint_reported: bool,
+
+    /// The area of the largest text or image the script thread was told about, in
+    /// square pixels.
+    largest_contentful_paint_area: f64,
+
     /// The workers that we use for parallel operation.
     parallel_traversal: Option<WorkQueue<SharedLayoutContext, WorkQueueData>>,
 
@@ -212,6 +233,9 @@ pub struct LayoutThread {
     /// The list of animations that have expired since the last style recalculation.
     expired_animations: Arc<RwLock<HashMap<OpaqueNode, Vec<Animation>>>>,
 
+    /// The content-box sizes of the query containers, as of the last layout.
+    container_sizes: Arc<HashMap<OpaqueNode, Size2D<Au>>>,
+
     /// A counter for epoch messages
     epoch: Epoch,
 
@@ -235,6 +259,9 @@ pub struct LayoutThread {
     // Webrender interface, if enabled.
     webrender_api: Option<webrender_traits::RenderApi>,
 
+    /// The scrollbars last sent to the compositor, which hit tests them when WebRender is in use.
+    webrender_scrollbars: Vec<ScrollContainerScrollbars>,
+
     /// The timer object to control the timing of the animations. This should
     /// only be a test-mode timer during testing for animations.
     timer: Timer,
@@ -262,38 +289,125 @@ impl LayoutThreadFactory for LayoutThread {
               mem_profiler_chan: mem::ProfilerChan,
               content_process_shutdown_chan: IpcSender<()>,
               webrender_api_sender: Option<webrender_traits::RenderApiSender>,
-              layout_threads: usize) {
-        thread::spawn_named(format!("LayoutThread {:?}", id),
-                      move || {
+              layout_threads: usize,
+              old_pipeline_id: Option<PipelineId>) {
+        let lineage = IDLE_LAYOUT_THREADS.lock().unwrap().start_pipeline(id, old_pipeline_id);
+        let init = LayoutThreadInit {
+            id: id,
+            lineage: lineage,
+            url: url,
+            is_iframe: is_iframe,
+            chan: chan,
+            pipeline_port: pipeline_port,
+            constellation_chan: constellation_chan,
+            script_chan: script_chan,
+            paint_chan: paint_chan,
+            image_cache_thread: image_cache_thread,
+            font_cache_thread: font_cache_thread,
+            time_profiler_chan: time_profiler_chan,
+            mem_profiler_chan: mem_profiler_chan,
+            content_process_shutdown_chan: content_process_shutdown_chan,
+            webrender_api_sender: webrender_api_sender,
+            layout_threads: layout_threads,
+        };
+
+        // Reuse the thread of an exited pipeline of the same origin in this browsing context if
+        // there is one, so that its font and style caches stay warm across navigations.
+        let init = match reuse_idle_layout_thread(init) {
+            Ok(()) => return,
+            Err(init) => init,
+        };
+
+        // The thread may go on to lay out later pipelines of this browsing context and origin,
+        // so it is named after them rather than after this pipeline.
+        let name = format!("LayoutThread {:?} {}", lineage, init.url.origin().ascii_serialization());
+        thread::spawn_named(name, move || {
             thread_state::initialize(thread_state::LAYOUT);
-            PipelineId::install(id);
-            { // Ensures layout thread is destroyed before we send shutdown message
-                let sender = chan.0;
-                let layout = LayoutThread::new(id,
-                                             url,
-                                             is_iframe,
-                                             chan.1,
-                                             pipeline_port,
-                                             constellation_chan,
-                                             script_chan,
-                                             paint_chan,
-                                             image_cache_thread,
-                                             font_cache_thread,
-                                             time_profiler_chan,
-                                             mem_profiler_chan.clone(),
-                                             webrender_api_sender,
-                                             layout_threads);
-
-                let reporter_name = format!("layout-reporter-{}", id);
-                mem_profiler_chan.run_with_memory_reporting(|| {
-                    layout.start();
-                }, reporter_name, sender, Msg::CollectReports);
+            let mut init = init;
+            let mut parallel_traversal = None;
+            loop {
+                let id = init.id;
+                let lineage = init.lineage;
+                let origin = init.url.origin();
+                let content_process_shutdown_chan = init.content_process_shutdown_chan.clone();
+                // Ensures layout thread is destroyed before we send shutdown message
+                parallel_traversal = LayoutThread::run(init, parallel_traversal);
+                IDLE_LAYOUT_THREADS.lock().unwrap().finish_pipeline(id);
+                let _ = content_process_shutdown_chan.send(());
+
+                init = match wait_for_reuse(lineage, origin) {
+                    Some(init) => init,
+                    None => break,
+                };
+            }
+            if let Some(mut traversal) = parallel_traversal {
+                traversal.shutdown()
             }
-            let _ = content_process_shutdown_chan.send(());
         });
     }
 }
 
+/// How long the thread of an exited layout thread waits to be reused by a new pipeline before
+/// shutting down, unless the `layout.idle_thread_timeout_ms` pref says otherwise.
+const DEFAULT_IDLE_LAYOUT_THREAD_TIMEOUT_MS: u64 = 5000;
+
+/// The arguments of `LayoutThreadFactory::create`, sent to an idle thread to reuse it.
+struct LayoutThreadInit {
+    id: PipelineId,
+    lineage: PipelineId,
+    url: Url,
+    is_iframe: bool,
+    chan: (Sender<Msg>, Receiver<Msg>),
+    pipeline_port: IpcReceiver<LayoutControlMsg>,
+    constellation_chan: IpcSender<ConstellationMsg>,
+    script_chan: IpcSender<ConstellationControlMsg>,
+    paint_chan: OptionalIpcSender<LayoutToPaintMsg>,
+    image_cache_thread: ImageCacheThread,
+    font_cache_thread: FontCacheThread,
+    time_profiler_chan: time::ProfilerChan,
+    mem_profiler_chan: mem::ProfilerChan,
+    content_process_shutdown_chan: IpcSender<()>,
+    webrender_api_sender: Option<webrender_traits::RenderApiSender>,
+    layout_threads: usize,
+}
+
+lazy_static! {
+    static ref IDLE_LAYOUT_THREADS: Mutex<IdleThreads<LayoutThreadInit>> = Mutex::new(IdleThreads::new());
+}
+
+/// Hands the new pipeline to an idle thread of the same browsing context and origin, or returns
+/// it if there is none.
+fn reuse_idle_layout_thread(init: LayoutThreadInit) -> Result<(), LayoutThreadInit> {
+    let mut idle_threads = IDLE_LAYOUT_THREADS.lock().unwrap();
+    match idle_threads.take(init.lineage, &init.url.origin()) {
+        // The idle thread only drops its receiver after removing itself from the list,
+        // which it can't do while we hold the lock.
+        Some(sender) => sender.send(init).map_err(|error| error.0),
+        None => Err(init),
+    }
+}
+
+/// Registers the current thread as idle, and waits for a new pipeline of the given browsing
+/// context and origin.
+fn wait_for_reuse(lineage: PipelineId, origin: url::Origin) -> Option<LayoutThreadInit> {
+    let (sender, receiver) = channel();
+    let id = IDLE_LAYOUT_THREADS.lock().unwrap().add(lineage, origin, sender);
+
+    let timeout = PREFS.get("layout.idle_thread_timeout_ms").as_u64()
+                       .unwrap_or(DEFAULT_IDLE_LAYOUT_THREAD_TIMEOUT_MS);
+    if let Ok(init) = receiver.recv_timeout(Duration::from_millis(timeout)) {
+        return Some(init)
+    }
+
+    // A pipeline may have picked this thread right after the timeout; if so, the message is
+    // already in the channel, since it is sent with the lock held.
+    if IDLE_LAYOUT_THREADS.lock().unwrap().remove(id) {
+        None
+    } else {
+        receiver.try_recv().ok()
+    }
+}
+
 /// The `LayoutThread` `rw_data` lock must remain locked until the first reflow,
 /// as RPC calls don't make sense until then. Use this in combination with
 /// `LayoutThread::lock_rw_data` and `LayoutThread::return_rw_data`.
@@ -380,6 +494,38 @@ fn add_font_face_rules(stylesheet: &Stylesheet,
 }
 
 impl LayoutThread {
+    /// Runs a layout thread for the given pipeline on the current thread until it exits, and
+    /// returns its workers so that they can be reused.
+    fn run(init: LayoutThreadInit,
+           parallel_traversal: Option<WorkQueue<SharedLayoutContext, WorkQueueData>>)
+           -> Option<WorkQueue<SharedLayoutContext, WorkQueueData>> {
+        PipelineId::install(init.id);
+        let sender = init.chan.0;
+        let mem_profiler_chan = init.mem_profiler_chan;
+        let layout = LayoutThread::new(init.id,
+                                       init.url,
+                                       init.is_iframe,
+                                       init.chan.1,
+                                       init.pipeline_port,
+                                       init.constellation_chan,
+                                       init.script_chan,
+                                       init.paint_chan,
+                                       init.image_cache_thread,
+                                       init.font_cache_thread,
+                                       init.time_profiler_chan,
+                                       mem_profiler_chan.clone(),
+                                       init.webrender_api_sender,
+                                       init.layout_threads,
+                                       parallel_traversal);
+
+        let reporter_name = format!("layout-reporter-{}", init.id);
+        let mut parallel_traversal = None;
+        mem_profiler_chan.run_with_memory_reporting(|| {
+            parallel_traversal = layout.start();
+        }, reporter_name, sender, Msg::CollectReports);
+        parallel_traversal
+    }
+
     /// Creates a new `LayoutThread` structure.
     fn new(id: PipelineId,
            url: Url,
@@ -394,13 +540,16 @@ impl LayoutThread {
            time_profiler_chan: time::ProfilerChan,
            mem_profiler_chan: mem::ProfilerChan,
            webrender_api_sender: Option<webrender_traits::RenderApiSender>,
-           layout_threads: usize)
+           layout_threads: usize,
+           parallel_traversal: Option<WorkQueue<SharedLayoutContext, WorkQueueData>>)
            -> LayoutThread {
         let device = Device::new(
-            MediaType::Screen,
+            media_type(),
             opts::get().initial_window_size.to_f32() * ScaleFactor::new(1.0));
         let parallel_traversal = if layout_threads != 1 {
-            WorkQueue::new("LayoutWorker", thread_state::LAYOUT, layout_threads).ok()
+            parallel_traversal.or_else(|| {
+                WorkQueue::new("LayoutWorker", thread_state::LAYOUT, layout_threads).ok()
+            })
         } else {
             None
         };
@@ -445,6 +594,9 @@ impl LayoutThread {
             image_cache_thread: image_cache_thread,
             font_cache_thread: font_cache_thread,
             first_reflow: true,
+            first_paint_reported: false,
+            first_contentful_paint_reported: false,
+            largest_contentful_paint_area: 0.,
             image_cache_receiver: image_cache_receiver,
             image_cache_sender: ImageCacheChan(ipc_image_cache_sender),
             font_cache_receiver: font_cache_receiver,
@@ -458,9 +610,11 @@ impl LayoutThread {
             visible_rects: Arc::new(HashMap::with_hasher(Default::default())),
             running_animations: Arc::new(RwLock::new(HashMap::new())),
             expired_animations: Arc::new(RwLock::new(HashMap::new())),
+            container_sizes: Arc::new(HashMap::new()),
             epoch: Epoch(0),
             viewport_size: Size2D::new(Au(0), Au(0)),
             webrender_api: webrender_api_sender.map(|wr| wr.create_api()),
+            webrender_scrollbars: Vec::new(),
             rw_data: Arc::new(Mutex::new(
                 LayoutThreadData {
                     constellation_chan: constellation_chan,
@@ -476,6 +630,7 @@ impl LayoutThread {
                     resolved_style_response: None,
                     offset_parent_response: OffsetParentResponse::empty(),
                     margin_style_response: MarginStyleResponse::empty(),
+                    text_index_response: TextIndexResponse(None),
                     stacking_context_scroll_offsets: HashMap::new(),
                 })),
             error_reporter: CSSErrorReporter {
@@ -495,8 +650,8 @@ impl LayoutThread {
         }
     }
 
-    /// Starts listening on the port.
-    fn start(mut self) {
+    /// Starts listening on the port. Returns the workers once the layout thread exits.
+    fn start(mut self) -> Option<WorkQueue<SharedLayoutContext, WorkQueueData>> {
         let rw_data = self.rw_data.clone();
         let mut possibly_locked_rw_data = Some(rw_data.lock().unwrap());
         let mut rw_data = RwData {
@@ -506,6 +661,7 @@ impl LayoutThread {
         while self.handle_request(&mut rw_data) {
             // Loop indefinitely.
         }
+        self.parallel_traversal.take()
     }
 
     // Create a layout context for use in building display lists, hit testing, &c.
@@ -517,6 +673,7 @@ impl LayoutThread {
         let local_style_context_creation_data = LocalStyleContextCreationInfo::new(self.new_animations_sender.clone());
 
         SharedLayoutContext {
+            pipeline_id: self.id,
             style_context: SharedStyleContext {
                 viewport_size: self.viewport_size.clone(),
                 screen_size_changed: screen_size_changed,
@@ -528,6 +685,7 @@ impl LayoutThread {
                 error_reporter: self.error_reporter.clone(),
                 local_context_creation_data: Mutex::new(local_style_context_creation_data),
                 timer: self.timer.clone(),
+                container_sizes: self.container_sizes.clone(),
             },
             image_cache_thread: self.image_cache_thread.clone(),
             image_cache_sender: Mutex::new(self.image_cache_sender.clone()),
@@ -648,7 +806,7 @@ impl LayoutThread {
             Msg::AddStylesheet(style_info) => {
                 self.handle_add_stylesheet(style_info, possibly_locked_rw_data)
             }
-            Msg::SetQuirksMode => self.handle_set_quirks_mode(possibly_locked_rw_data),
+            Msg::SetQuirksMode(mode) => self.handle_set_quirks_mode(mode, possibly_locked_rw_data),
             Msg::GetRPC(response_chan) => {
                 response_chan.send(box LayoutRPCImpl(self.rw_data.clone()) as
                                    Box<LayoutRPC + Send>).unwrap();
@@ -770,7 +928,8 @@ impl LayoutThread {
                              self.mem_profiler_chan.clone(),
                              info.content_process_shutdown_chan,
                              self.webrender_api.as_ref().map(|wr| wr.clone_sender()),
-                             info.layout_threads);
+                             info.layout_threads,
+                             info.old_pipeline_id);
     }
 
     /// Enters a quiescent state in which no new messages will be processed until an `ExitNow` is
@@ -802,10 +961,7 @@ impl LayoutThread {
     /// Shuts down the layout thread now. If there are any DOM nodes left, layout will now (safely)
     /// crash.
     fn exit_now(&mut self) {
-        if let Some(ref mut traversal) = self.parallel_traversal {
-            traversal.shutdown()
-        }
-
+        // The workers are shut down by the thread once it is no longer reused.
         let _ = self.paint_chan.send(LayoutToPaintMsg::Exit);
     }
 
@@ -838,10 +994,10 @@ impl LayoutThread {
         }
     }
 
-    /// Sets quirks mode for the document, causing the quirks mode stylesheet to be used.
-    fn handle_set_quirks_mode<'a, 'b>(&self, possibly_locked_rw_data: &mut RwData<'a, 'b>) {
+    /// Sets the mode of the document, causing the quirks mode stylesheet to be used in quirks mode.
+    fn handle_set_quirks_mode<'a, 'b>(&self, mode: QuirksMode, possibly_locked_rw_data: &mut RwData<'a, 'b>) {
         let mut rw_data = possibly_locked_rw_data.lock();
-        Arc::get_mut(&mut rw_data.stylist).unwrap().set_quirks_mode(true);
+        Arc::get_mut(&mut rw_data.stylist).unwrap().set_quirks_mode(mode);
         possibly_locked_rw_data.block(rw_data);
     }
 
@@ -904,6 +1060,40 @@ impl LayoutThread {
 
     /// Computes the stacking-relative positions of all flows and, if the painting is dirty and the
     /// reflow goal and query type need it, builds the display list.
+    /// Tells the script thread when the first display list, and the first one
+    /// with contentful items, is about to be painted, and whenever a display list
+    /// shows a larger text or image than any before it.
+    /// https://w3c.github.io/paint-timing/#mark-paint-timing
+    /// https://w3c.github.io/largest-contentful-paint/#sec-report-largest-contentful-paint
+    fn report_paint_metrics(&mut self, display_list: &DisplayList) {
+        if display_list.list.is_empty() {
+            return;
+        }
+        let now = std_time::precise_time_ns();
+        if !self.first_paint_reported {
+            self.first_paint_reported = true;
+            let msg = ConstellationControlMsg::PaintMetric(self.id, PaintMetricType::FirstPaint, now);
+            let _ = self.script_chan.send(msg);
+        }
+        if !self.first_contentful_paint_reported && display_list.is_contentful() {
+            self.first_contentful_paint_reported = true;
+            let msg = ConstellationControlMsg::PaintMetric(self.id, PaintMetricType::FirstContentfulPaint, now);
+            let _ = self.script_chan.send(msg);
+        }
+
+        let viewport = Rect::new(Point2D::new(Au(0), Au(0)), self.viewport_size);
+        if let Some((node, area)) = display_list.largest_contentful_node(&viewport) {
+            if area > self.largest_contentful_paint_area {
+                self.largest_contentful_paint_area = area;
+                let msg = ConstellationControlMsg::LargestContentfulPaint(self.id,
+                                                                          node.to_untrusted_node_address(),
+                                                                          area,
+                                                                          now);
+                let _ = self.script_chan.send(msg);
+            }
+        }
+    }
+
     fn compute_abs_pos_and_build_display_list(&mut self,
                                               data: &Reflow,
                                               query_type: Option<&ReflowQueryType>,
@@ -944,16 +1134,28 @@ impl LayoutThread {
                                                  0,
                                                  filter::T::new(Vec::new()),
                                                  mix_blend_mode::T::normal,
+                                                 false,
                                                  Matrix4D::identity(),
                                                  Matrix4D::identity(),
                                                  true,
+                                                 transform_style::T::flat,
+                                                 backface_visibility::T::visible,
                                                  false,
                                                  None);
 
-                        let display_list_entries =
-                            sequential::build_display_list_for_subtree(layout_root,
-                                                                       &mut root_stacking_context,
-                                                                       shared_layout_context);
+                        let display_list_entries = match self.parallel_traversal {
+                            Some(ref mut traversal) => {
+                                parallel::build_display_list_for_subtree(layout_root,
+                                                                         &mut root_stacking_context,
+                                                                         shared_layout_context,
+                                                                         traversal)
+                            }
+                            None => {
+                                sequential::build_display_list_for_subtree(layout_root,
+                                                                           &mut root_stacking_context,
+                                                                           shared_layout_context)
+                            }
+                        };
 
                         debug!("Done building display list.");
 
@@ -1007,6 +1209,8 @@ impl LayoutThread {
 
             debug!("Layout done!");
 
+            self.report_paint_metrics(&display_list);
+
             self.epoch.next();
 
             if let Some(ref mut webrender_api) = self.webrender_api {
@@ -1043,6 +1247,14 @@ impl LayoutThread {
                     frame_builder.stacking_contexts,
                     frame_builder.display_lists,
                     frame_builder.auxiliary_lists_builder.finalize());
+
+                let scrollbars = display_list.scrollbars();
+                if scrollbars != self.webrender_scrollbars {
+                    self.constellation_chan
+                        .send(ConstellationMsg::SetScrollbars(self.id, scrollbars.clone()))
+                        .unwrap();
+                    self.webrender_scrollbars = scrollbars;
+                }
             } else {
                 self.paint_chan
                     .send(LayoutToPaintMsg::PaintInit(self.epoch, display_list))
@@ -1103,6 +1315,9 @@ impl LayoutThread {
                     ReflowQueryType::MarginStyleQuery(_) => {
                         rw_data.margin_style_response = MarginStyleResponse::empty();
                     },
+                    ReflowQueryType::TextIndexQuery(..) => {
+                        rw_data.text_index_response = TextIndexResponse(None);
+                    },
                     ReflowQueryType::NoQuery => {}
                 }
                 return;
@@ -1121,7 +1336,7 @@ impl LayoutThread {
                                               Au::from_f32_px(initial_viewport.height));
 
         // Calculate the actual viewport as per DEVICE-ADAPT § 6
-        let device = Device::new(MediaType::Screen, initial_viewport);
+        let device = Device::new(media_type(), initial_viewport);
         Arc::get_mut(&mut rw_data.stylist).unwrap().set_device(device, &data.document_stylesheets);
 
         let constraints = rw_data.stylist.viewport_constraints().clone();
@@ -1203,38 +1418,7 @@ impl LayoutThread {
                                                                          data.reflow_info.goal);
 
         if node.is_dirty() || node.has_dirty_descendants() {
-            // Recalculate CSS styles and rebuild flows and fragments.
-            profile(time::ProfilerCategory::LayoutStyleRecalc,
-                    self.profiler_metadata(),
-                    self.time_profiler_chan.clone(),
-                    || {
-                // Perform CSS selector matching and flow construction.
-                match self.parallel_traversal {
-                    None => {
-                        sequential::traverse_dom::<ServoLayoutNode, RecalcStyleAndConstructFlows>(
-                            node, &shared_layout_context);
-                    }
-                    Some(ref mut traversal) => {
-                        parallel::traverse_dom::<ServoLayoutNode, RecalcStyleAndConstructFlows>(
-                            node, &shared_layout_context, traversal);
-                    }
-                }
-            });
-
-            // TODO(pcwalton): Measure energy usage of text shaping, perhaps?
-            let text_shaping_time =
-                (font::get_and_reset_text_shaping_performance_counter() as u64) /
-                (self.layout_threads as u64);
-            time::send_profile_data(time::ProfilerCategory::LayoutTextShaping,
-                                    self.profiler_metadata(),
-                                    self.time_profiler_chan.clone(),
-                                    0,
-                                    text_shaping_time,
-                                    0,
-                                    0);
-
-            // Retrieve the (possibly rebuilt) root flow.
-            self.root_flow = self.try_get_layout_root(node);
+            self.recalc_style_and_construct_flows(node, &shared_layout_context);
         }
 
         if opts::get().dump_style_tree {
@@ -1248,11 +1432,112 @@ impl LayoutThread {
                                                      &mut rw_data,
                                                      &mut shared_layout_context);
 
+        // Container queries are evaluated against the sizes of the query
+        // containers in the previous layout, so if any of them changed, restyle
+        // their contents and lay out again. This is only done once per reflow,
+        // so nested containers may take several reflows to settle.
+        if self.update_container_sizes(node) {
+            shared_layout_context = self.build_shared_layout_context(&*rw_data,
+                                                                     viewport_size_changed,
+                                                                     data.reflow_info.goal);
+            self.recalc_style_and_construct_flows(node, &shared_layout_context);
+            self.perform_post_style_recalc_layout_passes(&data.reflow_info,
+                                                         Some(&data.query_type),
+                                                         Some(&document),
+                                                         &mut rw_data,
+                                                         &mut shared_layout_context);
+        }
+
         self.respond_to_query_if_necessary(&data.query_type,
                                            &mut *rw_data,
                                            &mut shared_layout_context);
     }
 
+    /// Recalculates CSS styles and rebuilds flows and fragments for the dirty
+    /// nodes under `node`.
+    fn recalc_style_and_construct_flows(&mut self,
+                                        node: ServoLayoutNode,
+                                        shared_layout_context: &SharedLayoutContext) {
+        profile(time::ProfilerCategory::LayoutStyleRecalc,
+                self.profiler_metadata(),
+                self.time_profiler_chan.clone(),
+                || {
+            // Perform CSS selector matching and flow construction.
+            match self.parallel_traversal {
+                None => {
+                    sequential::traverse_dom::<ServoLayoutNode, RecalcStyleAndConstructFlows>(
+                        node, shared_layout_context);
+                }
+                Some(ref mut traversal) => {
+                    parallel::traverse_dom::<ServoLayoutNode, RecalcStyleAndConstructFlows>(
+                        node, shared_layout_context, traversal);
+                }
+            }
+        });
+
+        // TODO(pcwalton): Measure energy usage of text shaping, perhaps?
+        let text_shaping_time =
+            (font::get_and_reset_text_shaping_performance_counter() as u64) /
+            (self.layout_threads as u64);
+        time::send_profile_data(time::ProfilerCategory::LayoutTextShaping,
+                                self.profiler_metadata(),
+                                self.time_profiler_chan.clone(),
+                                0,
+                                text_shaping_time,
+                                0,
+                                0);
+
+        // Retrieve the (possibly rebuilt) root flow.
+        self.root_flow = self.try_get_layout_root(node);
+    }
+
+    /// Records the sizes of the query containers after a layout, and marks the
+    /// query containers whose size changed as dirty, so that their contents are
+    /// restyled. Returns whether any of them changed.
+    fn update_container_sizes(&mut self, node: ServoLayoutNode) -> bool {
+        let sizes = match self.root_flow.clone() {
+            Some(mut root_flow) => collect_container_sizes(flow_ref::deref_mut(&mut root_flow)),
+            None => return false,
+        };
+
+        let mut changed = HashSet::new();
+        for (container, size) in &sizes {
+            if self.container_sizes.get(container) != Some(size) {
+                changed.insert(*container);
+            }
+        }
+        for container in self.container_sizes.keys() {
+            if !sizes.contains_key(container) {
+                changed.insert(*container);
+            }
+        }
+        self.container_sizes = Arc::new(sizes);
+        if changed.is_empty() {
+            return false
+        }
+
+        let mut iter = node.traverse_preorder();
+        let mut next = iter.next();
+        while let Some(node) = next {
+            if changed.contains(&node.opaque()) {
+                // NB: The dirty bit is propagated down the tree.
+                unsafe { node.set_dirty(true); }
+
+                let mut current = node.parent_node();
+                while let Some(node) = current {
+                    if node.has_dirty_descendants() { break; }
+                    unsafe { node.set_dirty_descendants(true); }
+                    current = node.parent_node();
+                }
+
+                next = iter.next_skipping_children();
+            } else {
+                next = iter.next();
+            }
+        }
+        true
+    }
+
     fn respond_to_query_if_necessary(&mut self,
                                      query_type: &ReflowQueryType,
                                      rw_data: &mut LayoutThreadData,
@@ -1320,6 +1605,11 @@ impl LayoutThread {
                 let node = unsafe { ServoLayoutNode::new(&node) };
                 rw_data.margin_style_response = process_margin_style_query(node);
             },
+            ReflowQueryType::TextIndexQuery(node, point) => {
+                let node = unsafe { ServoLayoutNode::new(&node) };
+                let point = Point2D::new(Au::from_f32_px(point.x), Au::from_f32_px(point.y));
+                rw_data.text_index_response = process_text_index_request(node, point, root_flow);
+            },
             ReflowQueryType::NoQuery => {}
         }
     }
@@ -1477,21 +1767,34 @@ impl LayoutThread {
         if let Some(mut root_flow) = self.root_flow.clone() {
             // Kick off animations if any were triggered, expire completed ones.
             animation::update_animation_state(&self.constellation_chan,
+                                              &self.script_chan,
                                               &mut *self.running_animations.write().unwrap(),
                                               &mut *self.expired_animations.write().unwrap(),
                                               &self.new_animations_receiver,
                                               self.id,
                                               &self.timer);
 
+            // A restyle drops the values of the keyframes animations of the
+            // restyled nodes, so apply them again. Animation ticks have just
+            // applied them.
+            if document.is_some() {
+                let animations = self.running_animations.read().unwrap();
+                animation::recalc_style_for_animations(layout_context,
+                                                       flow_ref::deref_mut(&mut root_flow),
+                                                       &animations);
+            }
+
             profile(time::ProfilerCategory::LayoutRestyleDamagePropagation,
                     self.profiler_metadata(),
                     self.time_profiler_chan.clone(),
                     || {
                 // Call `compute_layout_damage` even in non-incremental mode, because it sets flags
                 // that are needed in both incremental and non-incremental traversals.
+                // Changes to floats reflow only the formatting context they are in. The root flow
+                // always handles that itself, so this is just a fallback.
                 let damage = flow_ref::deref_mut(&mut root_flow).compute_layout_damage();
 
-                if opts::get().nonincremental_layout || damage.contains(REFLOW_ENTIRE_DOCUMENT) {
+                if opts::get().nonincremental_layout || damage.contains(REFLOW_FORMATTING_CONTEXT) {
                     flow_ref::deref_mut(&mut root_flow).reflow_entire_document()
                 }
             });
@@ -1627,25 +1930,50 @@ impl LayoutThread {
 // clearing the frame buffer to white. This ensures that setting a background
 // color on an iframe element, while the iframe content itself has a default
 // transparent background color is handled correctly.
+/// The background color of the canvas, which is that of the root element or, if the root element
+/// has no background, that of the HTML `body` element.
+/// https://drafts.csswg.org/css-backgrounds/#special-backgrounds
 fn get_root_flow_background_color(flow: &mut Flow) -> AzColor {
+    fn background_color(style: &ServoComputedValues) -> AzColor {
+        style.resolve_color(style.get_background().background_color).to_gfx_color()
+    }
+
+    // The root flow of a paginated document holds the pages of the root element.
+    if flow.class() == FlowClass::Paged {
+        return match flow::mut_base(flow).children.iter_mut().next() {
+            Some(page) => get_root_flow_background_color(page),
+            None => color::transparent(),
+        }
+    }
+
     if !flow.is_block_like() {
         return color::transparent()
     }
 
     let block_flow = flow.as_mut_block();
-    let kid = match block_flow.base.children.iter_mut().next() {
-        None => return color::transparent(),
-        Some(kid) => kid,
-    };
-    if !kid.is_block_like() {
-        return color::transparent()
+    let root_color = background_color(&block_flow.fragment.style);
+    let root_has_image = block_flow.fragment.style.get_background().background_image.0.iter().any(|image| {
+        image.0.is_some()
+    });
+    if root_color.a != 0.0 || root_has_image {
+        return root_color
     }
 
-    let kid_block_flow = kid.as_block();
-    kid_block_flow.fragment
-                  .style
-                  .resolve_color(kid_block_flow.fragment.style.get_background().background_color)
-                  .to_gfx_color()
+    for kid in block_flow.base.children.iter_mut() {
+        if kid.is_block_like() && kid.as_block().fragment.is_body_element() {
+            return background_color(&kid.as_block().fragment.style)
+        }
+    }
+    color::transparent()
+}
+
+/// The medium that documents are laid out for.
+fn media_type() -> MediaType {
+    if opts::get().paginate {
+        MediaType::Print
+    } else {
+        MediaType::Screen
+    }
 }
 
 fn get_ua_stylesheets() -> Result<UserAgentStylesheets, &'static str> {
@@ -1664,7 +1992,8 @@ fn get_ua_stylesheets() -> Result<UserAgentStylesheets, &'static str> {
     let mut user_or_user_agent_stylesheets = vec!();
     // FIXME: presentational-hints.css should be at author origin with zero specificity.
     //        (Does it make a difference?)
-    for &filename in &["user-agent.css", "servo.css", "presentational-hints.css"] {
+    for &filename in &["user-agent.css", "servo.css", "forms.css", "mathml.css", "ruby.css",
+                      "top-layer.css", "webvtt.css", "selection.css", "presentational-hints.css"] {
         user_or_user_agent_stylesheets.push(try!(parse_ua_stylesheet(filename)));
     }
     for &(ref contents, ref url) in &opts::get().user_stylesheets {
@@ -1690,7 +2019,8 @@ fn reflow_query_type_needs_display_list(query_type: &ReflowQueryType) -> bool {
         ReflowQueryType::NodeGeometryQuery(_) | ReflowQueryType::NodeScrollGeometryQuery(_) |
         ReflowQueryType::NodeOverflowQuery(_) | ReflowQueryType::NodeLayerIdQuery(_) |
         ReflowQueryType::ResolvedStyleQuery(..) | ReflowQueryType::OffsetParentQuery(_) |
-        ReflowQueryType::MarginStyleQuery(_) | ReflowQueryType::NoQuery => false,
+        ReflowQueryType::MarginStyleQuery(_) | ReflowQueryType::TextIndexQuery(..) |
+        ReflowQueryType::NoQuery => false,
     }
 }
 
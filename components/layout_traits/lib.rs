@@ -49,5 +49,6 @@ pub trait LayoutThreadFactory {
               mem_profiler_chan: mem::ProfilerChan,
               content_process_shutdown_chan: IpcSender<()>,
               webrender_api_sender: Option<webrender_traits::RenderApiSender>,
-              layout_threads: usize);
+              layout_threads: usize,
+              old_pipeline_id: Option<PipelineId>);
 }
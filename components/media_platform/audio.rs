@@ -0,0 +1,219 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Audio output and capture, through the simple PCM API of ALSA.
+
+use self::alsa::Pcm;
+
+/// The default output device, which plays interleaved samples. It drops what
+/// it didn't play yet when it is dropped.
+pub struct AudioOutput {
+    pcm: Pcm,
+}
+
+impl AudioOutput {
+    pub fn open(sample_rate: u32, channels: usize) -> Result<AudioOutput, ()> {
+        Ok(AudioOutput {
+            pcm: try!(Pcm::open_playback("default", sample_rate, channels)),
+        })
+    }
+
+    /// Plays `samples`, blocking until the device took all of them.
+    pub fn write(&mut self, samples: &[f32]) -> Result<(), ()> {
+        self.pcm.write(samples)
+    }
+}
+
+/// Whether the default input device can be opened.
+pub fn has_input_device() -> bool {
+    Pcm::exists("default")
+}
+
+/// An input device, which captures interleaved samples.
+pub struct AudioInput {
+    pcm: Pcm,
+}
+
+impl AudioInput {
+    /// Opens the input device named `name`, e.g. `default`.
+    pub fn open(name: &str, sample_rate: u32, channels: usize) -> Result<AudioInput, ()> {
+        Ok(AudioInput {
+            pcm: try!(Pcm::open_capture(name, sample_rate, channels)),
+        })
+    }
+
+    /// Fills `samples`, blocking until the device captured enough of them.
+    pub fn read(&mut self, samples: &mut [f32]) -> Result<(), ()> {
+        self.pcm.read(samples)
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "alsa"))]
+#[allow(unsafe_code)]
+mod alsa {
+    use libc::{c_char, c_int, c_long, c_uint, c_ulong, c_void};
+    use std::ffi::CString;
+    use std::ptr;
+
+    #[allow(non_camel_case_types)]
+    enum snd_pcm_t {}
+
+    const SND_PCM_STREAM_PLAYBACK: c_int = 0;
+    const SND_PCM_STREAM_CAPTURE: c_int = 1;
+    const SND_PCM_FORMAT_FLOAT_LE: c_int = 14;
+    const SND_PCM_ACCESS_RW_INTERLEAVED: c_int = 3;
+    /// How much audio the device buffers, in microseconds.
+    const LATENCY: c_uint = 50000;
+
+    #[link(name = "asound")]
+    extern "C" {
+        fn snd_pcm_open(pcm: *mut *mut snd_pcm_t, name: *const c_char, stream: c_int, mode: c_int) -> c_int;
+        fn snd_pcm_set_params(pcm: *mut snd_pcm_t,
+                              format: c_int,
+                              access: c_int,
+                              channels: c_uint,
+                              rate: c_uint,
+                              soft_resample: c_int,
+                              latency: c_uint)
+                              -> c_int;
+        fn snd_pcm_writei(pcm: *mut snd_pcm_t, buffer: *const c_void, size: c_ulong) -> c_long;
+        fn snd_pcm_readi(pcm: *mut snd_pcm_t, buffer: *mut c_void, size: c_ulong) -> c_long;
+        fn snd_pcm_recover(pcm: *mut snd_pcm_t, err: c_int, silent: c_int) -> c_int;
+        fn snd_pcm_drop(pcm: *mut snd_pcm_t) -> c_int;
+        fn snd_pcm_close(pcm: *mut snd_pcm_t) -> c_int;
+    }
+
+    /// A PCM device opened for interleaved 32-bit float samples.
+    pub struct Pcm {
+        pcm: *mut snd_pcm_t,
+        channels: usize,
+    }
+
+    // The handle is only used by one thread at a time.
+    unsafe impl Send for Pcm {}
+
+    fn open_pcm(name: &str, stream: c_int) -> Result<*mut snd_pcm_t, ()> {
+        let name = try!(CString::new(name).map_err(|_| ()));
+        let mut pcm = ptr::null_mut();
+        if unsafe { snd_pcm_open(&mut pcm, name.as_ptr(), stream, 0) } < 0 {
+            return Err(());
+        }
+        Ok(pcm)
+    }
+
+    impl Pcm {
+        pub fn exists(name: &str) -> bool {
+            match open_pcm(name, SND_PCM_STREAM_CAPTURE) {
+                Ok(pcm) => {
+                    unsafe { snd_pcm_close(pcm) };
+                    true
+                },
+                Err(()) => false,
+            }
+        }
+
+        fn open(name: &str, stream: c_int, sample_rate: u32, channels: usize) -> Result<Pcm, ()> {
+            let pcm = try!(open_pcm(name, stream));
+            unsafe {
+                if snd_pcm_set_params(pcm,
+                                      SND_PCM_FORMAT_FLOAT_LE,
+                                      SND_PCM_ACCESS_RW_INTERLEAVED,
+                                      channels as c_uint,
+                                      sample_rate as c_uint,
+                                      1,
+                                      LATENCY) < 0 {
+                    snd_pcm_close(pcm);
+                    return Err(());
+                }
+            }
+            Ok(Pcm {
+                pcm: pcm,
+                channels: channels,
+            })
+        }
+
+        pub fn open_playback(name: &str, sample_rate: u32, channels: usize) -> Result<Pcm, ()> {
+            Pcm::open(name, SND_PCM_STREAM_PLAYBACK, sample_rate, channels)
+        }
+
+        pub fn open_capture(name: &str, sample_rate: u32, channels: usize) -> Result<Pcm, ()> {
+            Pcm::open(name, SND_PCM_STREAM_CAPTURE, sample_rate, channels)
+        }
+
+        pub fn write(&mut self, samples: &[f32]) -> Result<(), ()> {
+            let mut written = 0;
+            while written < samples.len() {
+                let remaining = &samples[written..];
+                let frames = (remaining.len() / self.channels) as c_ulong;
+                let result = unsafe { snd_pcm_writei(self.pcm, remaining.as_ptr() as *const c_void, frames) };
+                if result < 0 {
+                    // Recover from underruns.
+                    if unsafe { snd_pcm_recover(self.pcm, result as c_int, 1) } < 0 {
+                        warn!("Failed to write to the audio output device");
+                        return Err(());
+                    }
+                } else {
+                    written += result as usize * self.channels;
+                }
+            }
+            Ok(())
+        }
+
+        pub fn read(&mut self, samples: &mut [f32]) -> Result<(), ()> {
+            let mut read = 0;
+            while read < samples.len() {
+                let remaining = &mut samples[read..];
+                let frames = (remaining.len() / self.channels) as c_ulong;
+                let result = unsafe { snd_pcm_readi(self.pcm, remaining.as_mut_ptr() as *mut c_void, frames) };
+                if result < 0 {
+                    // Recover from overruns.
+                    if unsafe { snd_pcm_recover(self.pcm, result as c_int, 1) } < 0 {
+                        warn!("Failed to read from the audio input device");
+                        return Err(());
+                    }
+                } else {
+                    read += result as usize * self.channels;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for Pcm {
+        fn drop(&mut self) {
+            unsafe {
+                snd_pcm_drop(self.pcm);
+                snd_pcm_close(self.pcm);
+            }
+        }
+    }
+}
+
+/// There are no devices without ALSA.
+#[cfg(not(all(target_os = "linux", feature = "alsa")))]
+mod alsa {
+    pub enum Pcm {}
+
+    impl Pcm {
+        pub fn exists(_name: &str) -> bool {
+            false
+        }
+
+        pub fn open_playback(_name: &str, _sample_rate: u32, _channels: usize) -> Result<Pcm, ()> {
+            Err(())
+        }
+
+        pub fn open_capture(_name: &str, _sample_rate: u32, _channels: usize) -> Result<Pcm, ()> {
+            Err(())
+        }
+
+        pub fn write(&mut self, _samples: &[f32]) -> Result<(), ()> {
+            match *self {}
+        }
+
+        pub fn read(&mut self, _samples: &mut [f32]) -> Result<(), ()> {
+            match *self {}
+        }
+    }
+}
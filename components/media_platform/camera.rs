@@ -0,0 +1,344 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Video capture, through the streaming I/O of Video4Linux2, from devices
+//! that support YUYV frames.
+
+pub use self::v4l2::{Camera, devices};
+
+/// A camera that can be opened.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraInfo {
+    /// The path of the device, e.g. `/dev/video0`.
+    pub path: String,
+    pub label: String,
+}
+
+#[cfg(all(target_os = "linux", feature = "v4l2"))]
+#[allow(unsafe_code)]
+mod v4l2 {
+    use libc::{self, c_int, c_ulong, c_void};
+    use std::ffi::CString;
+    use std::io;
+    use std::mem;
+    use std::ptr;
+    use std::slice;
+    use super::CameraInfo;
+
+    const V4L2_CAP_VIDEO_CAPTURE: u32 = 0x00000001;
+    const V4L2_CAP_STREAMING: u32 = 0x04000000;
+    const V4L2_BUF_TYPE_VIDEO_CAPTURE: u32 = 1;
+    const V4L2_MEMORY_MMAP: u32 = 1;
+    const V4L2_FIELD_NONE: u32 = 1;
+    const V4L2_PIX_FMT_YUYV: u32 = 0x56595559;
+    /// How many buffers the driver fills while a frame is being converted.
+    const BUFFER_COUNT: u32 = 4;
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct v4l2_capability {
+        driver: [u8; 16],
+        card: [u8; 32],
+        bus_info: [u8; 32],
+        version: u32,
+        capabilities: u32,
+        device_caps: u32,
+        reserved: [u32; 3],
+    }
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct v4l2_pix_format {
+        width: u32,
+        height: u32,
+        pixelformat: u32,
+        field: u32,
+        bytesperline: u32,
+        sizeimage: u32,
+        colorspace: u32,
+        priv_: u32,
+        flags: u32,
+        ycbcr_enc: u32,
+        quantization: u32,
+        xfer_func: u32,
+    }
+
+    /// The `fmt` union of `v4l2_format`, of which only the pixel format of
+    /// single-planar capture is used.
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct v4l2_format_fmt {
+        pix: v4l2_pix_format,
+        raw_data: [u8; 152],
+        align: [usize; 0],
+    }
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct v4l2_format {
+        type_: u32,
+        fmt: v4l2_format_fmt,
+    }
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct v4l2_requestbuffers {
+        count: u32,
+        type_: u32,
+        memory: u32,
+        reserved: [u32; 2],
+    }
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct v4l2_buffer {
+        index: u32,
+        type_: u32,
+        bytesused: u32,
+        flags: u32,
+        field: u32,
+        timestamp: libc::timeval,
+        timecode: [u32; 4],
+        sequence: u32,
+        memory: u32,
+        /// The `m` union, whose `offset` member is the only one used.
+        m: c_ulong,
+        length: u32,
+        reserved2: u32,
+        reserved: u32,
+    }
+
+    /// The number of an ioctl, as computed by the `_IOC` macro.
+    fn ioc<T>(direction: c_ulong, number: c_ulong) -> c_ulong {
+        (direction << 30) | ((mem::size_of::<T>() as c_ulong) << 16) | ((b'V' as c_ulong) << 8) | number
+    }
+
+    const READ: c_ulong = 2;
+    const WRITE: c_ulong = 1;
+
+    fn vidioc_querycap() -> c_ulong { ioc::<v4l2_capability>(READ, 0) }
+    fn vidioc_s_fmt() -> c_ulong { ioc::<v4l2_format>(READ | WRITE, 5) }
+    fn vidioc_reqbufs() -> c_ulong { ioc::<v4l2_requestbuffers>(READ | WRITE, 8) }
+    fn vidioc_querybuf() -> c_ulong { ioc::<v4l2_buffer>(READ | WRITE, 9) }
+    fn vidioc_qbuf() -> c_ulong { ioc::<v4l2_buffer>(READ | WRITE, 15) }
+    fn vidioc_dqbuf() -> c_ulong { ioc::<v4l2_buffer>(READ | WRITE, 17) }
+    fn vidioc_streamon() -> c_ulong { ioc::<c_int>(WRITE, 18) }
+    fn vidioc_streamoff() -> c_ulong { ioc::<c_int>(WRITE, 19) }
+
+    /// Retries an ioctl interrupted by a signal.
+    unsafe fn ioctl<T>(fd: c_int, request: c_ulong, arg: *mut T) -> Result<(), io::Error> {
+        loop {
+            if libc::ioctl(fd, request, arg as *mut c_void) >= 0 {
+                return Ok(());
+            }
+            let error = io::Error::last_os_error();
+            if error.kind() != io::ErrorKind::Interrupted {
+                return Err(error);
+            }
+        }
+    }
+
+    fn open_device(path: &str) -> Option<c_int> {
+        let path = match CString::new(path) {
+            Ok(path) => path,
+            Err(_) => return None,
+        };
+        let fd = unsafe { libc::open(path.as_ptr(), libc::O_RDWR) };
+        if fd < 0 { None } else { Some(fd) }
+    }
+
+    /// The name of the device, if it can capture frames with streaming I/O.
+    fn query_capture_device(fd: c_int) -> Option<String> {
+        unsafe {
+            let mut capability: v4l2_capability = mem::zeroed();
+            if ioctl(fd, vidioc_querycap(), &mut capability).is_err() {
+                return None;
+            }
+            let required = V4L2_CAP_VIDEO_CAPTURE | V4L2_CAP_STREAMING;
+            if capability.capabilities & required != required {
+                return None;
+            }
+            let length = capability.card.iter().position(|&byte| byte == 0).unwrap_or(capability.card.len());
+            Some(String::from_utf8_lossy(&capability.card[..length]).into_owned())
+        }
+    }
+
+    /// The cameras that can be opened.
+    pub fn devices() -> Vec<CameraInfo> {
+        (0..64).filter_map(|index| {
+            let path = format!("/dev/video{}", index);
+            let fd = match open_device(&path) {
+                Some(fd) => fd,
+                None => return None,
+            };
+            let label = query_capture_device(fd);
+            unsafe { libc::close(fd) };
+            label.map(|label| {
+                CameraInfo {
+                    path: path,
+                    label: label,
+                }
+            })
+        }).collect()
+    }
+
+    struct MappedBuffer {
+        start: *mut c_void,
+        length: usize,
+    }
+
+    /// A camera that is streaming YUYV frames. It stops when it is dropped.
+    pub struct Camera {
+        fd: c_int,
+        buffers: Vec<MappedBuffer>,
+        width: u32,
+        height: u32,
+    }
+
+    // The device is only used by one thread at a time.
+    unsafe impl Send for Camera {}
+
+    impl Camera {
+        /// Opens the camera at `path`, asking for frames of the given size,
+        /// of which the driver picks the closest one it supports.
+        pub fn open(path: &str, width: u32, height: u32) -> Result<Camera, ()> {
+            let mut camera = Camera {
+                fd: try!(open_device(path).ok_or(())),
+                buffers: vec![],
+                width: 0,
+                height: 0,
+            };
+            match unsafe { camera.start_streaming(width, height) } {
+                Ok(()) => Ok(camera),
+                Err(error) => {
+                    warn!("Failed to start capturing from {}: {}", path, error);
+                    Err(())
+                },
+            }
+        }
+
+        pub fn width(&self) -> u32 {
+            self.width
+        }
+
+        pub fn height(&self) -> u32 {
+            self.height
+        }
+
+        unsafe fn start_streaming(&mut self, width: u32, height: u32) -> Result<(), io::Error> {
+            let mut format: v4l2_format = mem::zeroed();
+            format.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+            format.fmt.pix.width = width;
+            format.fmt.pix.height = height;
+            format.fmt.pix.pixelformat = V4L2_PIX_FMT_YUYV;
+            format.fmt.pix.field = V4L2_FIELD_NONE;
+            try!(ioctl(self.fd, vidioc_s_fmt(), &mut format));
+            if format.fmt.pix.pixelformat != V4L2_PIX_FMT_YUYV ||
+               format.fmt.pix.bytesperline != format.fmt.pix.width * 2 {
+                return Err(io::Error::new(io::ErrorKind::Other, "unsupported pixel format"));
+            }
+            self.width = format.fmt.pix.width;
+            self.height = format.fmt.pix.height;
+
+            let mut request: v4l2_requestbuffers = mem::zeroed();
+            request.count = BUFFER_COUNT;
+            request.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+            request.memory = V4L2_MEMORY_MMAP;
+            try!(ioctl(self.fd, vidioc_reqbufs(), &mut request));
+
+            for index in 0..request.count {
+                let mut buffer: v4l2_buffer = mem::zeroed();
+                buffer.index = index;
+                buffer.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                buffer.memory = V4L2_MEMORY_MMAP;
+                try!(ioctl(self.fd, vidioc_querybuf(), &mut buffer));
+                let start = libc::mmap(ptr::null_mut(),
+                                       buffer.length as usize,
+                                       libc::PROT_READ | libc::PROT_WRITE,
+                                       libc::MAP_SHARED,
+                                       self.fd,
+                                       buffer.m as u32 as libc::off_t);
+                if start == libc::MAP_FAILED {
+                    return Err(io::Error::last_os_error());
+                }
+                self.buffers.push(MappedBuffer {
+                    start: start,
+                    length: buffer.length as usize,
+                });
+                try!(ioctl(self.fd, vidioc_qbuf(), &mut buffer));
+            }
+
+            let mut buffer_type = V4L2_BUF_TYPE_VIDEO_CAPTURE as c_int;
+            ioctl(self.fd, vidioc_streamon(), &mut buffer_type)
+        }
+
+        /// Waits for the next frame, and calls `f` with its YUYV data, which
+        /// is shorter than a whole frame if the driver didn't fill it.
+        pub fn read<F, R>(&mut self, f: F) -> Result<R, ()>
+            where F: FnOnce(&[u8]) -> R
+        {
+            unsafe {
+                let mut buffer: v4l2_buffer = mem::zeroed();
+                buffer.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                buffer.memory = V4L2_MEMORY_MMAP;
+                if let Err(error) = ioctl(self.fd, vidioc_dqbuf(), &mut buffer) {
+                    warn!("Failed to capture a frame: {}", error);
+                    return Err(());
+                }
+                let result = {
+                    let mapped = &self.buffers[buffer.index as usize];
+                    let length = (buffer.bytesused as usize).min(mapped.length);
+                    f(slice::from_raw_parts(mapped.start as *const u8, length))
+                };
+                try!(ioctl(self.fd, vidioc_qbuf(), &mut buffer).map_err(|_| ()));
+                Ok(result)
+            }
+        }
+    }
+
+    impl Drop for Camera {
+        fn drop(&mut self) {
+            unsafe {
+                let mut buffer_type = V4L2_BUF_TYPE_VIDEO_CAPTURE as c_int;
+                let _ = ioctl(self.fd, vidioc_streamoff(), &mut buffer_type);
+                for buffer in self.buffers.drain(..) {
+                    libc::munmap(buffer.start, buffer.length);
+                }
+                libc::close(self.fd);
+            }
+        }
+    }
+}
+
+/// There are no cameras without Video4Linux2.
+#[cfg(not(all(target_os = "linux", feature = "v4l2")))]
+mod v4l2 {
+    use super::CameraInfo;
+
+    pub fn devices() -> Vec<CameraInfo> {
+        vec![]
+    }
+
+    pub enum Camera {}
+
+    impl Camera {
+        pub fn open(_path: &str, _width: u32, _height: u32) -> Result<Camera, ()> {
+            Err(())
+        }
+
+        pub fn width(&self) -> u32 {
+            match *self {}
+        }
+
+        pub fn height(&self) -> u32 {
+            match *self {}
+        }
+
+        pub fn read<F, R>(&mut self, _f: F) -> Result<R, ()>
+            where F: FnOnce(&[u8]) -> R
+        {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,22 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The media devices and codecs of the platform, which link to native
+//! libraries.
+//!
+//! Each backend is only built on the platforms that have its library, and
+//! with the cargo feature named after it. Without it, its devices and codecs
+//! fail to open, as they do when the hardware is missing, and script falls
+//! back to what it does then.
+
+#![deny(unsafe_code)]
+
+extern crate libc;
+#[macro_use]
+extern crate log;
+
+pub mod audio;
+pub mod camera;
+pub mod opus;
+pub mod vpx;
@@ -0,0 +1,186 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Audio encoding and decoding with libopus.
+
+pub use self::libopus::{Decoder, Encoder};
+
+#[cfg(feature = "opus")]
+#[allow(unsafe_code)]
+mod libopus {
+    use libc::{c_int, c_uchar};
+
+    const OPUS_OK: c_int = 0;
+    const OPUS_APPLICATION_AUDIO: c_int = 2049;
+    const OPUS_SET_BITRATE_REQUEST: c_int = 4002;
+    const OPUS_GET_LOOKAHEAD_REQUEST: c_int = 4027;
+
+    #[allow(non_camel_case_types)]
+    enum opus_encoder {}
+    #[allow(non_camel_case_types)]
+    enum opus_decoder {}
+
+    #[link(name = "opus")]
+    extern "C" {
+        fn opus_encoder_create(fs: i32, channels: c_int, application: c_int, error: *mut c_int) -> *mut opus_encoder;
+        fn opus_encoder_ctl(encoder: *mut opus_encoder, request: c_int, ...) -> c_int;
+        fn opus_encode_float(encoder: *mut opus_encoder,
+                             pcm: *const f32,
+                             frame_size: c_int,
+                             data: *mut c_uchar,
+                             max_data_bytes: i32)
+                             -> i32;
+        fn opus_encoder_destroy(encoder: *mut opus_encoder);
+        fn opus_decoder_create(fs: i32, channels: c_int, error: *mut c_int) -> *mut opus_decoder;
+        fn opus_decode_float(decoder: *mut opus_decoder,
+                             data: *const c_uchar,
+                             len: i32,
+                             pcm: *mut f32,
+                             frame_size: c_int,
+                             decode_fec: c_int)
+                             -> c_int;
+        fn opus_decoder_destroy(decoder: *mut opus_decoder);
+    }
+
+    /// Encodes interleaved samples into packets.
+    pub struct Encoder {
+        encoder: *mut opus_encoder,
+        channel_count: usize,
+        lookahead: u32,
+    }
+
+    // The encoder is only used by one thread at a time.
+    unsafe impl Send for Encoder {}
+
+    impl Encoder {
+        /// Creates an encoder for general audio, at one of the sample rates
+        /// that libopus supports.
+        pub fn new(sample_rate: u32, channel_count: usize, bit_rate: u32) -> Result<Encoder, ()> {
+            let mut error = OPUS_OK;
+            let encoder = unsafe {
+                opus_encoder_create(sample_rate as i32, channel_count as c_int, OPUS_APPLICATION_AUDIO, &mut error)
+            };
+            if encoder.is_null() || error != OPUS_OK {
+                return Err(());
+            }
+            let mut lookahead: i32 = 0;
+            unsafe {
+                opus_encoder_ctl(encoder, OPUS_SET_BITRATE_REQUEST, bit_rate.max(500).min(512000) as i32);
+                opus_encoder_ctl(encoder, OPUS_GET_LOOKAHEAD_REQUEST, &mut lookahead as *mut i32);
+            }
+            Ok(Encoder {
+                encoder: encoder,
+                channel_count: channel_count,
+                lookahead: lookahead.max(0) as u32,
+            })
+        }
+
+        /// How many frames the first packets are ahead of the audio.
+        pub fn lookahead(&self) -> u32 {
+            self.lookahead
+        }
+
+        /// Encodes the frames of `samples` in a packet, returning its size.
+        pub fn encode(&mut self, samples: &[f32], packet: &mut [u8]) -> Result<usize, ()> {
+            let size = unsafe {
+                opus_encode_float(self.encoder,
+                                  samples.as_ptr(),
+                                  (samples.len() / self.channel_count) as c_int,
+                                  packet.as_mut_ptr(),
+                                  packet.len() as i32)
+            };
+            if size < 0 {
+                warn!("Failed to encode audio with Opus");
+                return Err(());
+            }
+            Ok(size as usize)
+        }
+    }
+
+    impl Drop for Encoder {
+        fn drop(&mut self) {
+            unsafe { opus_encoder_destroy(self.encoder) };
+        }
+    }
+
+    /// Decodes packets to interleaved samples at 48kHz, which is what Opus
+    /// always encodes.
+    pub struct Decoder {
+        decoder: *mut opus_decoder,
+        channel_count: usize,
+    }
+
+    // The decoder is only used by one thread at a time.
+    unsafe impl Send for Decoder {}
+
+    impl Decoder {
+        pub fn new(channel_count: usize) -> Result<Decoder, ()> {
+            let mut error = OPUS_OK;
+            let decoder = unsafe { opus_decoder_create(48000, channel_count as c_int, &mut error) };
+            if decoder.is_null() || error != OPUS_OK {
+                return Err(());
+            }
+            Ok(Decoder {
+                decoder: decoder,
+                channel_count: channel_count,
+            })
+        }
+
+        /// Decodes a packet into `samples`, returning the number of frames
+        /// it held.
+        pub fn decode(&mut self, packet: &[u8], samples: &mut [f32]) -> Result<usize, ()> {
+            let frames = unsafe {
+                opus_decode_float(self.decoder,
+                                  packet.as_ptr(),
+                                  packet.len() as i32,
+                                  samples.as_mut_ptr(),
+                                  (samples.len() / self.channel_count) as c_int,
+                                  0)
+            };
+            if frames < 0 {
+                warn!("Failed to decode audio with Opus");
+                return Err(());
+            }
+            Ok(frames as usize)
+        }
+    }
+
+    impl Drop for Decoder {
+        fn drop(&mut self) {
+            unsafe { opus_decoder_destroy(self.decoder) };
+        }
+    }
+}
+
+/// Opus can't be encoded or decoded without libopus.
+#[cfg(not(feature = "opus"))]
+mod libopus {
+    pub enum Encoder {}
+
+    impl Encoder {
+        pub fn new(_sample_rate: u32, _channel_count: usize, _bit_rate: u32) -> Result<Encoder, ()> {
+            Err(())
+        }
+
+        pub fn lookahead(&self) -> u32 {
+            match *self {}
+        }
+
+        pub fn encode(&mut self, _samples: &[f32], _packet: &mut [u8]) -> Result<usize, ()> {
+            match *self {}
+        }
+    }
+
+    pub enum Decoder {}
+
+    impl Decoder {
+        pub fn new(_channel_count: usize) -> Result<Decoder, ()> {
+            Err(())
+        }
+
+        pub fn decode(&mut self, _packet: &[u8], _samples: &mut [f32]) -> Result<usize, ()> {
+            match *self {}
+        }
+    }
+}
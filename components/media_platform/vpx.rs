@@ -0,0 +1,426 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Video encoding and decoding of VP8 with libvpx, from and to I420 frames.
+
+pub use self::libvpx::{Decoder, Encoder};
+
+/// A frame output by the encoder.
+pub struct EncodedFrame {
+    /// The presentation time of the frame, in milliseconds.
+    pub timestamp: u64,
+    pub key_frame: bool,
+    pub data: Vec<u8>,
+}
+
+#[cfg(feature = "vpx")]
+#[allow(unsafe_code)]
+mod libvpx {
+    use libc::{c_char, c_int, c_long, c_uint, c_ulong, c_void, size_t};
+    use std::mem;
+    use std::ptr;
+    use std::slice;
+    use super::EncodedFrame;
+
+    /// The encoder and decoder ABIs of libvpx 1.6, which the structures below
+    /// are laid out for.
+    const VPX_ENCODER_ABI_VERSION: c_int = 12;
+    const VPX_DECODER_ABI_VERSION: c_int = 10;
+    const VPX_CODEC_OK: c_int = 0;
+    const VPX_IMG_FMT_I420: c_int = 0x102;
+    const VPX_CODEC_CX_FRAME_PKT: c_int = 0;
+    const VPX_FRAME_IS_KEY: u32 = 0x1;
+    const VPX_EFLAG_FORCE_KF: c_long = 1;
+    const VPX_DL_REALTIME: c_ulong = 1;
+
+    #[allow(non_camel_case_types)]
+    enum vpx_codec_iface {}
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct vpx_rational {
+        num: c_int,
+        den: c_int,
+    }
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct vpx_fixed_buf {
+        buf: *mut c_void,
+        sz: size_t,
+    }
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct vpx_codec_enc_cfg {
+        g_usage: c_uint,
+        g_threads: c_uint,
+        g_profile: c_uint,
+        g_w: c_uint,
+        g_h: c_uint,
+        g_bit_depth: c_int,
+        g_input_bit_depth: c_uint,
+        g_timebase: vpx_rational,
+        g_error_resilient: u32,
+        g_pass: c_int,
+        g_lag_in_frames: c_uint,
+        rc_dropframe_thresh: c_uint,
+        rc_resize_allowed: c_uint,
+        rc_scaled_width: c_uint,
+        rc_scaled_height: c_uint,
+        rc_resize_up_thresh: c_uint,
+        rc_resize_down_thresh: c_uint,
+        rc_end_usage: c_int,
+        rc_twopass_stats_in: vpx_fixed_buf,
+        rc_firstpass_mb_stats_in: vpx_fixed_buf,
+        rc_target_bitrate: c_uint,
+        rc_min_quantizer: c_uint,
+        rc_max_quantizer: c_uint,
+        rc_undershoot_pct: c_uint,
+        rc_overshoot_pct: c_uint,
+        rc_buf_sz: c_uint,
+        rc_buf_initial_sz: c_uint,
+        rc_buf_optimal_sz: c_uint,
+        rc_2pass_vbr_bias_pct: c_uint,
+        rc_2pass_vbr_minsection_pct: c_uint,
+        rc_2pass_vbr_maxsection_pct: c_uint,
+        kf_mode: c_int,
+        kf_min_dist: c_uint,
+        kf_max_dist: c_uint,
+        ss_number_layers: c_uint,
+        ss_enable_auto_alt_ref: [c_int; 5],
+        ss_target_bitrate: [c_uint; 5],
+        ts_number_layers: c_uint,
+        ts_target_bitrate: [c_uint; 5],
+        ts_rate_decimator: [c_uint; 5],
+        ts_periodicity: c_uint,
+        ts_layer_id: [c_uint; 16],
+        layer_target_bitrate: [c_uint; 12],
+        temporal_layering_mode: c_int,
+    }
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct vpx_codec_ctx {
+        name: *const c_char,
+        iface: *mut vpx_codec_iface,
+        err: c_int,
+        err_detail: *const c_char,
+        init_flags: c_long,
+        config: *const c_void,
+        priv_: *mut c_void,
+    }
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct vpx_image {
+        fmt: c_int,
+        cs: c_int,
+        range: c_int,
+        w: c_uint,
+        h: c_uint,
+        bit_depth: c_uint,
+        d_w: c_uint,
+        d_h: c_uint,
+        r_w: c_uint,
+        r_h: c_uint,
+        x_chroma_shift: c_uint,
+        y_chroma_shift: c_uint,
+        planes: [*mut u8; 4],
+        stride: [c_int; 4],
+        bps: c_int,
+        user_priv: *mut c_void,
+        img_data: *mut u8,
+        img_data_owner: c_int,
+        self_allocd: c_int,
+        fb_priv: *mut c_void,
+    }
+
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct vpx_codec_cx_frame {
+        buf: *mut c_void,
+        sz: size_t,
+        pts: i64,
+        duration: c_ulong,
+        flags: u32,
+        partition_id: c_int,
+    }
+
+    /// An output packet of the encoder, of which only the frame member of the
+    /// `data` union is read.
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct vpx_codec_cx_pkt {
+        kind: c_int,
+        frame: vpx_codec_cx_frame,
+    }
+
+    #[link(name = "vpx")]
+    extern "C" {
+        fn vpx_codec_vp8_cx() -> *mut vpx_codec_iface;
+        fn vpx_codec_enc_config_default(iface: *mut vpx_codec_iface,
+                                        cfg: *mut vpx_codec_enc_cfg,
+                                        usage: c_uint)
+                                        -> c_int;
+        fn vpx_codec_enc_init_ver(ctx: *mut vpx_codec_ctx,
+                                  iface: *mut vpx_codec_iface,
+                                  cfg: *const vpx_codec_enc_cfg,
+                                  flags: c_long,
+                                  ver: c_int)
+                                  -> c_int;
+        fn vpx_codec_encode(ctx: *mut vpx_codec_ctx,
+                            img: *const vpx_image,
+                            pts: i64,
+                            duration: c_ulong,
+                            flags: c_long,
+                            deadline: c_ulong)
+                            -> c_int;
+        fn vpx_codec_get_cx_data(ctx: *mut vpx_codec_ctx, iter: *mut *const c_void) -> *const vpx_codec_cx_pkt;
+        fn vpx_codec_vp8_dx() -> *mut vpx_codec_iface;
+        fn vpx_codec_dec_init_ver(ctx: *mut vpx_codec_ctx,
+                                  iface: *mut vpx_codec_iface,
+                                  cfg: *const c_void,
+                                  flags: c_long,
+                                  ver: c_int)
+                                  -> c_int;
+        fn vpx_codec_decode(ctx: *mut vpx_codec_ctx,
+                            data: *const u8,
+                            data_sz: c_uint,
+                            user_priv: *mut c_void,
+                            deadline: c_long)
+                            -> c_int;
+        fn vpx_codec_get_frame(ctx: *mut vpx_codec_ctx, iter: *mut *const c_void) -> *const vpx_image;
+        fn vpx_codec_destroy(ctx: *mut vpx_codec_ctx) -> c_int;
+        fn vpx_img_alloc(img: *mut vpx_image, fmt: c_int, d_w: c_uint, d_h: c_uint, align: c_uint) -> *mut vpx_image;
+        fn vpx_img_free(img: *mut vpx_image);
+    }
+
+    /// Encodes I420 frames of a fixed size, with a timebase of milliseconds.
+    pub struct Encoder {
+        /// The context is boxed, as libvpx expects it not to move once
+        /// initialized.
+        context: Box<vpx_codec_ctx>,
+        image: *mut vpx_image,
+    }
+
+    // The encoder is only used by one thread at a time.
+    unsafe impl Send for Encoder {}
+
+    impl Encoder {
+        pub fn new(width: u32, height: u32, bit_rate: u32) -> Result<Encoder, ()> {
+            unsafe {
+                let iface = vpx_codec_vp8_cx();
+                let mut config: vpx_codec_enc_cfg = mem::zeroed();
+                if vpx_codec_enc_config_default(iface, &mut config, 0) != VPX_CODEC_OK {
+                    return Err(());
+                }
+                config.g_w = width;
+                config.g_h = height;
+                config.g_timebase = vpx_rational { num: 1, den: 1000 };
+                config.rc_target_bitrate = (bit_rate / 1000).max(1);
+                // Output every frame as soon as it is encoded.
+                config.g_lag_in_frames = 0;
+
+                let mut context: Box<vpx_codec_ctx> = Box::new(mem::zeroed());
+                if vpx_codec_enc_init_ver(&mut *context, iface, &config, 0, VPX_ENCODER_ABI_VERSION) != VPX_CODEC_OK {
+                    return Err(());
+                }
+                let image = vpx_img_alloc(ptr::null_mut(), VPX_IMG_FMT_I420, width, height, 1);
+                if image.is_null() {
+                    vpx_codec_destroy(&mut *context);
+                    return Err(());
+                }
+                Ok(Encoder {
+                    context: context,
+                    image: image,
+                })
+            }
+        }
+
+        /// Encodes a frame, given as its Y, U and V planes without padding,
+        /// which is shown at `timestamp` for `duration` milliseconds. Returns
+        /// the frames the encoder output.
+        pub fn encode(&mut self,
+                      planes: [&[u8]; 3],
+                      timestamp: u64,
+                      duration: u64,
+                      force_key_frame: bool)
+                      -> Result<Vec<EncodedFrame>, ()> {
+            unsafe {
+                let image = &mut *self.image;
+                let luma_width = image.d_w as usize;
+                let chroma_width = (luma_width + 1) / 2;
+                for (plane, &data) in planes.iter().enumerate() {
+                    let width = if plane == 0 { luma_width } else { chroma_width };
+                    let stride = image.stride[plane] as usize;
+                    for (row, line) in data.chunks(width).enumerate() {
+                        let start = image.planes[plane].offset((row * stride) as isize);
+                        slice::from_raw_parts_mut(start, width).copy_from_slice(line);
+                    }
+                }
+                let flags = if force_key_frame { VPX_EFLAG_FORCE_KF } else { 0 };
+                if vpx_codec_encode(&mut *self.context,
+                                    self.image,
+                                    timestamp as i64,
+                                    duration as c_ulong,
+                                    flags,
+                                    VPX_DL_REALTIME) != VPX_CODEC_OK {
+                    warn!("Failed to encode a video frame with VP8");
+                    return Err(());
+                }
+            }
+            Ok(self.encoded_frames())
+        }
+
+        /// Returns the frames the encoder still holds.
+        pub fn flush(&mut self) -> Vec<EncodedFrame> {
+            unsafe {
+                if vpx_codec_encode(&mut *self.context, ptr::null(), 0, 0, 0, VPX_DL_REALTIME) != VPX_CODEC_OK {
+                    return vec![];
+                }
+            }
+            self.encoded_frames()
+        }
+
+        fn encoded_frames(&mut self) -> Vec<EncodedFrame> {
+            let mut frames = vec![];
+            let mut iterator = ptr::null();
+            unsafe {
+                loop {
+                    let packet = vpx_codec_get_cx_data(&mut *self.context, &mut iterator);
+                    if packet.is_null() {
+                        break;
+                    }
+                    let packet = &*packet;
+                    if packet.kind != VPX_CODEC_CX_FRAME_PKT {
+                        continue;
+                    }
+                    let data = slice::from_raw_parts(packet.frame.buf as *const u8, packet.frame.sz as usize);
+                    frames.push(EncodedFrame {
+                        timestamp: packet.frame.pts as u64,
+                        key_frame: packet.frame.flags & VPX_FRAME_IS_KEY != 0,
+                        data: data.to_vec(),
+                    });
+                }
+            }
+            frames
+        }
+    }
+
+    impl Drop for Encoder {
+        fn drop(&mut self) {
+            unsafe {
+                vpx_img_free(self.image);
+                vpx_codec_destroy(&mut *self.context);
+            }
+        }
+    }
+
+    /// Decodes VP8 frames to I420.
+    pub struct Decoder {
+        /// The context is boxed, as libvpx expects it not to move once
+        /// initialized.
+        context: Box<vpx_codec_ctx>,
+    }
+
+    // The decoder is only used by one thread at a time.
+    unsafe impl Send for Decoder {}
+
+    impl Decoder {
+        pub fn new() -> Result<Decoder, ()> {
+            unsafe {
+                let mut context: Box<vpx_codec_ctx> = Box::new(mem::zeroed());
+                if vpx_codec_dec_init_ver(&mut *context, vpx_codec_vp8_dx(), ptr::null(), 0,
+                                          VPX_DECODER_ABI_VERSION) != VPX_CODEC_OK {
+                    return Err(());
+                }
+                Ok(Decoder {
+                    context: context,
+                })
+            }
+        }
+
+        /// Decodes a compressed frame, calling `f` with the size of each frame
+        /// to show and its Y, U and V planes, along with their strides.
+        pub fn decode<F>(&mut self, data: &[u8], mut f: F) -> Result<(), ()>
+            where F: FnMut(u32, u32, [(&[u8], usize); 3])
+        {
+            unsafe {
+                if vpx_codec_decode(&mut *self.context, data.as_ptr(), data.len() as c_uint,
+                                    ptr::null_mut(), 0) != VPX_CODEC_OK {
+                    warn!("Failed to decode a VP8 frame");
+                    return Err(());
+                }
+                let mut iterator = ptr::null();
+                loop {
+                    let image = vpx_codec_get_frame(&mut *self.context, &mut iterator);
+                    if image.is_null() {
+                        return Ok(());
+                    }
+                    let image = &*image;
+                    if image.fmt != VPX_IMG_FMT_I420 {
+                        continue;
+                    }
+                    let (width, height) = (image.d_w, image.d_h);
+                    let chroma_height = ((height + 1) / 2) as usize;
+                    let plane = |index: usize, rows: usize| {
+                        let stride = image.stride[index] as usize;
+                        (slice::from_raw_parts(image.planes[index] as *const u8, stride * rows), stride)
+                    };
+                    f(width, height, [plane(0, height as usize), plane(1, chroma_height), plane(2, chroma_height)]);
+                }
+            }
+        }
+    }
+
+    impl Drop for Decoder {
+        fn drop(&mut self) {
+            unsafe {
+                vpx_codec_destroy(&mut *self.context);
+            }
+        }
+    }
+}
+
+/// VP8 can't be encoded or decoded without libvpx.
+#[cfg(not(feature = "vpx"))]
+mod libvpx {
+    use super::EncodedFrame;
+
+    pub enum Encoder {}
+
+    impl Encoder {
+        pub fn new(_width: u32, _height: u32, _bit_rate: u32) -> Result<Encoder, ()> {
+            Err(())
+        }
+
+        pub fn encode(&mut self,
+                      _planes: [&[u8]; 3],
+                      _timestamp: u64,
+                      _duration: u64,
+                      _force_key_frame: bool)
+                      -> Result<Vec<EncodedFrame>, ()> {
+            match *self {}
+        }
+
+        pub fn flush(&mut self) -> Vec<EncodedFrame> {
+            match *self {}
+        }
+    }
+
+    pub enum Decoder {}
+
+    impl Decoder {
+        pub fn new() -> Result<Decoder, ()> {
+            Err(())
+        }
+
+        pub fn decode<F>(&mut self, _data: &[u8], _f: F) -> Result<(), ()>
+            where F: FnMut(u32, u32, [(&[u8], usize); 3])
+        {
+            match *self {}
+        }
+    }
+}
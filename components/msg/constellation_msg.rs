@@ -200,6 +200,12 @@ pub struct LoadData {
     pub data: Option<Vec<u8>>,
     pub referrer_policy: Option<ReferrerPolicy>,
     pub referrer_url: Option<Url>,
+    /// The value of an iframe's srcdoc attribute, which an about:srcdoc document is
+    /// parsed from instead of being fetched.
+    pub srcdoc: Option<String>,
+    /// The name of the encoding that the document is decoded with, instead of determining
+    /// it, when it is loaded again after a `<meta charset>` changed it.
+    pub encoding: Option<String>,
 }
 
 impl LoadData {
@@ -211,6 +217,8 @@ impl LoadData {
             data: None,
             referrer_policy: referrer_policy,
             referrer_url: referrer_url,
+            srcdoc: None,
+            encoding: None,
         }
     }
 }
@@ -40,6 +40,7 @@ pub fn factory(mut load_data: LoadData,
                 status: Some((200, b"OK".to_vec())),
                 https_state: HttpsState::None,
                 referrer: None,
+                timing: Default::default(),
             };
             if let Ok(chan) = start_sending_sniffed_opt(start_chan,
                                                         metadata,
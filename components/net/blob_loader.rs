@@ -74,6 +74,7 @@ fn load_blob<UI: 'static + UIProvider>
                     status: Some((200, b"OK".to_vec())),
                     https_state: HttpsState::None,
                     referrer: None,
+                    timing: Default::default(),
                 };
 
                 if let Ok(chan) =
@@ -0,0 +1,181 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Tells script about the characteristic notifications and device advertisements it watches,
+//! as the bluetooth backend signals them.
+
+use ipc_channel::ipc::IpcSender;
+use net_traits::bluetooth_thread::{BluetoothDeviceMsg, BluetoothEventMsg};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// The characteristics and devices that script watches, shared by the bluetooth thread and the
+/// thread that listens to the backend.
+#[derive(Default)]
+pub struct Watched {
+    /// The listeners of the characteristics whose notifications are started, by characteristic id,
+    /// which is the D-Bus object path of the characteristic with BlueZ.
+    pub characteristics: HashMap<String, IpcSender<BluetoothEventMsg>>,
+    /// The listeners of the devices whose advertisements are watched, by the id of the device in
+    /// the backend, with what the device last advertised.
+    pub devices: HashMap<String, (IpcSender<BluetoothEventMsg>, BluetoothDeviceMsg)>,
+    /// Whether a thread listens to the backend.
+    listening: bool,
+}
+
+impl Watched {
+    /// Marks the listening thread as gone if nothing is watched anymore, and returns whether it
+    /// should exit.
+    fn stop_if_unwatched(&mut self) -> bool {
+        if self.characteristics.is_empty() && self.devices.is_empty() {
+            self.listening = false;
+        }
+        !self.listening
+    }
+
+    fn characteristic_value_changed(&self, id: &str, value: Vec<u8>) {
+        if let Some(listener) = self.characteristics.get(id) {
+            let _ = listener.send(BluetoothEventMsg::CharacteristicValueChanged(id.to_owned(), value));
+        }
+    }
+}
+
+fn advertisement_received(listener: &IpcSender<BluetoothEventMsg>, device: &BluetoothDeviceMsg) {
+    let _ = listener.send(BluetoothEventMsg::AdvertisementReceived(BluetoothDeviceMsg {
+        id: device.id.clone(),
+        name: device.name.clone(),
+        appearance: device.appearance,
+        tx_power: device.tx_power,
+        rssi: device.rssi,
+    }));
+}
+
+/// Starts a thread that listens to the backend for the changes of what is watched, unless one
+/// already does. It exits once nothing is watched anymore.
+pub fn listen(watched: &Arc<Mutex<Watched>>) {
+    {
+        let mut watched = watched.lock().unwrap();
+        if watched.listening {
+            return;
+        }
+        watched.listening = true;
+    }
+    backend::listen(watched.clone());
+}
+
+#[cfg(target_os = "linux")]
+pub use self::backend::properties_changed;
+
+#[cfg(target_os = "linux")]
+mod backend {
+    use dbus::{BusType, Connection, ConnectionItem, Message, MessageItem};
+    use std::sync::{Arc, Mutex};
+    use super::{Watched, advertisement_received};
+    use util::thread::spawn_named;
+
+    const PROPERTIES_CHANGED_RULE: &'static str =
+        "type='signal',sender='org.bluez',interface='org.freedesktop.DBus.Properties',member='PropertiesChanged'";
+    const CHARACTERISTIC_INTERFACE: &'static str = "org.bluez.GattCharacteristic1";
+    const DEVICE_INTERFACE: &'static str = "org.bluez.Device1";
+    // How long to wait for a signal before checking whether anything is still watched
+    const IDLE_TIMEOUT_MS: i32 = 1000;
+
+    /// BlueZ signals the new value of a notifying characteristic, and the new signal strength of
+    /// a device it hears advertising while discovering, as changes of their D-Bus properties.
+    pub fn listen(watched: Arc<Mutex<Watched>>) {
+        spawn_named("BluetoothEvents".to_owned(), move || {
+            let connection = match Connection::get_private(BusType::System) {
+                Ok(connection) => connection,
+                Err(error) => {
+                    warn!("Can't listen to bluetooth events: {:?}", error);
+                    watched.lock().unwrap().listening = false;
+                    return;
+                },
+            };
+            if let Err(error) = connection.add_match(PROPERTIES_CHANGED_RULE) {
+                warn!("Can't listen to bluetooth events: {:?}", error);
+                watched.lock().unwrap().listening = false;
+                return;
+            }
+            for item in connection.iter(IDLE_TIMEOUT_MS) {
+                let mut watched = watched.lock().unwrap();
+                if watched.stop_if_unwatched() {
+                    return;
+                }
+                if let ConnectionItem::Signal(message) = item {
+                    properties_changed(&mut watched, &message);
+                }
+            }
+            watched.lock().unwrap().listening = false;
+        });
+    }
+
+    // https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-properties
+    pub fn properties_changed(watched: &mut Watched, message: &Message) {
+        let path = match message.path() {
+            Some(path) => path.to_string(),
+            None => return,
+        };
+        let items = message.get_items();
+        let (interface, changed) = match (items.get(0), items.get(1)) {
+            (Some(&MessageItem::Str(ref interface)), Some(&MessageItem::Array(ref changed, _))) => {
+                (interface, changed)
+            },
+            _ => return,
+        };
+
+        if interface == CHARACTERISTIC_INTERFACE {
+            for (name, value) in properties(changed) {
+                if let ("Value", &MessageItem::Array(ref bytes, _)) = (name, value) {
+                    let value = bytes.iter().filter_map(|byte| match *byte {
+                        MessageItem::Byte(byte) => Some(byte),
+                        _ => None,
+                    }).collect();
+                    watched.characteristic_value_changed(&path, value);
+                }
+            }
+        } else if interface == DEVICE_INTERFACE {
+            if let Some(&mut (ref listener, ref mut device)) = watched.devices.get_mut(&path) {
+                let mut advertised = false;
+                for (name, value) in properties(changed) {
+                    match (name, value) {
+                        ("Name", &MessageItem::Str(ref name)) => device.name = Some(name.clone()),
+                        ("Appearance", &MessageItem::UInt16(appearance)) => device.appearance = Some(appearance),
+                        ("TxPower", &MessageItem::Int16(tx_power)) => device.tx_power = Some(tx_power as i8),
+                        ("RSSI", &MessageItem::Int16(rssi)) => {
+                            device.rssi = Some(rssi as i8);
+                            advertised = true;
+                        },
+                        _ => {},
+                    }
+                }
+                if advertised {
+                    advertisement_received(listener, device);
+                }
+            }
+        }
+    }
+
+    /// The names and values of the properties in the `a{sv}` dictionary of a signal.
+    fn properties(dictionary: &[MessageItem]) -> Vec<(&str, &MessageItem)> {
+        dictionary.iter().filter_map(|entry| match *entry {
+            MessageItem::DictEntry(ref name, ref value) => match (&**name, &**value) {
+                (&MessageItem::Str(ref name), &MessageItem::Variant(ref value)) => Some((&**name, &**value)),
+                _ => None,
+            },
+            _ => None,
+        }).collect()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod backend {
+    use std::sync::{Arc, Mutex};
+    use super::Watched;
+
+    /// The other backends don't signal changes, so nothing is reported to script.
+    pub fn listen(watched: Arc<Mutex<Watched>>) {
+        watched.lock().unwrap().listening = false;
+    }
+}
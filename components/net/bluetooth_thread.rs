@@ -2,8 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use bluetooth_events::{self, Watched};
 use device::bluetooth::BluetoothAdapter;
 use device::bluetooth::BluetoothDevice;
+use device::bluetooth::BluetoothDiscoverySession;
 use device::bluetooth::BluetoothGATTCharacteristic;
 use device::bluetooth::BluetoothGATTDescriptor;
 use device::bluetooth::BluetoothGATTService;
@@ -11,12 +13,13 @@ use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use net_traits::bluetooth_scanfilter::{BluetoothScanfilter, BluetoothScanfilterSequence, RequestDeviceoptions};
 use net_traits::bluetooth_thread::{BluetoothCharacteristicMsg, BluetoothCharacteristicsMsg};
 use net_traits::bluetooth_thread::{BluetoothDescriptorMsg, BluetoothDescriptorsMsg};
-use net_traits::bluetooth_thread::{BluetoothDeviceMsg, BluetoothError, BluetoothMethodMsg};
+use net_traits::bluetooth_thread::{BluetoothDeviceMsg, BluetoothError, BluetoothEventMsg, BluetoothMethodMsg};
 use net_traits::bluetooth_thread::{BluetoothResult, BluetoothServiceMsg, BluetoothServicesMsg};
 use rand::{self, Rng};
 use std::borrow::ToOwned;
 use std::collections::{HashMap, HashSet};
 use std::string::String;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 #[cfg(target_os = "linux")]
@@ -139,6 +142,16 @@ fn matches_filter(device: &BluetoothDevice, filter: &BluetoothScanfilter) -> boo
     true
 }
 
+fn device_msg(device_id: String, device: &BluetoothDevice) -> BluetoothDeviceMsg {
+    BluetoothDeviceMsg {
+        id: device_id,
+        name: device.get_name().ok(),
+        appearance: device.get_appearance().ok(),
+        tx_power: device.get_tx_power().ok().map(|p| p as i8),
+        rssi: device.get_rssi().ok().map(|p| p as i8),
+    }
+}
+
 fn matches_filters(device: &BluetoothDevice, filters: &BluetoothScanfilterSequence) -> bool {
     if filters.has_empty_or_invalid_filter() {
         return false;
@@ -159,6 +172,10 @@ pub struct BluetoothManager {
     cached_characteristics: HashMap<String, BluetoothGATTCharacteristic>,
     cached_descriptors: HashMap<String, BluetoothGATTDescriptor>,
     allowed_services: HashMap<String, HashSet<String>>,
+    // The characteristics and devices whose changes are sent to script
+    watched: Arc<Mutex<Watched>>,
+    // Keeps the adapter scanning while any advertisements are watched
+    discovery_session: Option<BluetoothDiscoverySession>,
 }
 
 impl BluetoothManager {
@@ -175,6 +192,8 @@ impl BluetoothManager {
             cached_characteristics: HashMap::new(),
             cached_descriptors: HashMap::new(),
             allowed_services: HashMap::new(),
+            watched: Arc::new(Mutex::new(Watched::default())),
+            discovery_session: None,
         }
     }
 
@@ -220,6 +239,18 @@ impl BluetoothManager {
                 BluetoothMethodMsg::WriteValue(id, value, sender) => {
                     self.write_value(id, value, sender)
                 },
+                BluetoothMethodMsg::StartNotifications(id, listener, sender) => {
+                    self.start_notifications(id, listener, sender)
+                },
+                BluetoothMethodMsg::StopNotifications(id, sender) => {
+                    self.stop_notifications(id, sender)
+                },
+                BluetoothMethodMsg::WatchAdvertisements(device_id, listener, sender) => {
+                    self.watch_advertisements(device_id, listener, sender)
+                },
+                BluetoothMethodMsg::UnwatchAdvertisements(device_id, sender) => {
+                    self.unwatch_advertisements(device_id, sender)
+                },
                 BluetoothMethodMsg::Exit => {
                     break
                 },
@@ -484,14 +515,7 @@ impl BluetoothManager {
             }
             self.allowed_services.insert(device_id.clone(), services);
             if let Some(device) = self.get_device(&mut adapter, &device_id) {
-                let message = Ok(BluetoothDeviceMsg {
-                                     id: device_id,
-                                     name: device.get_name().ok(),
-                                     appearance: device.get_appearance().ok(),
-                                     tx_power: device.get_tx_power().ok().map(|p| p as i8),
-                                     rssi: device.get_rssi().ok().map(|p| p as i8),
-                                 });
-                return drop(sender.send(message));
+                return drop(sender.send(Ok(device_msg(device_id, device))));
             }
         }
         return drop(sender.send(Err(BluetoothError::NotFound)));
@@ -806,4 +830,74 @@ impl BluetoothManager {
         };
         let _ = sender.send(message);
     }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-startnotifications
+    fn start_notifications(&mut self,
+                           id: String,
+                           listener: IpcSender<BluetoothEventMsg>,
+                           sender: IpcSender<BluetoothResult<bool>>) {
+        let mut adapter = get_adapter_or_return_error!(self, sender);
+        let started = match self.get_gatt_characteristic(&mut adapter, &id) {
+            Some(c) => c.start_notify().is_ok(),
+            None => return drop(sender.send(Err(BluetoothError::NotFound))),
+        };
+        if !started {
+            return drop(sender.send(Err(BluetoothError::NotSupported)));
+        }
+        self.watched.lock().unwrap().characteristics.insert(id, listener);
+        bluetooth_events::listen(&self.watched);
+        let _ = sender.send(Ok(true));
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-stopnotifications
+    fn stop_notifications(&mut self, id: String, sender: IpcSender<BluetoothResult<bool>>) {
+        let mut adapter = get_adapter_or_return_error!(self, sender);
+        let was_notifying = self.watched.lock().unwrap().characteristics.remove(&id).is_some();
+        if was_notifying {
+            if let Some(c) = self.get_gatt_characteristic(&mut adapter, &id) {
+                let _ = c.stop_notify();
+            }
+        }
+        let _ = sender.send(Ok(true));
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-watchadvertisements
+    fn watch_advertisements(&mut self,
+                            device_id: String,
+                            listener: IpcSender<BluetoothEventMsg>,
+                            sender: IpcSender<BluetoothResult<bool>>) {
+        let mut adapter = get_adapter_or_return_error!(self, sender);
+        let (backend_id, message) = match self.get_device(&mut adapter, &device_id) {
+            Some(device) => (device.get_id(), device_msg(device_id.clone(), device)),
+            None => return drop(sender.send(Err(BluetoothError::NotFound))),
+        };
+        if self.discovery_session.is_none() {
+            let session = match adapter.create_discovery_session() {
+                Ok(session) => session,
+                Err(_) => return drop(sender.send(Err(BluetoothError::NotSupported))),
+            };
+            if session.start_discovery().is_err() {
+                return drop(sender.send(Err(BluetoothError::NotSupported)));
+            }
+            self.discovery_session = Some(session);
+        }
+        self.watched.lock().unwrap().devices.insert(backend_id, (listener, message));
+        bluetooth_events::listen(&self.watched);
+        let _ = sender.send(Ok(true));
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-unwatchadvertisements
+    fn unwatch_advertisements(&mut self, device_id: String, sender: IpcSender<BluetoothResult<bool>>) {
+        let backend_id = self.cached_devices.get(&device_id).map(|device| device.get_id());
+        let mut watched = self.watched.lock().unwrap();
+        if let Some(backend_id) = backend_id {
+            watched.devices.remove(&backend_id);
+        }
+        if watched.devices.is_empty() {
+            if let Some(session) = self.discovery_session.take() {
+                let _ = session.stop_discovery();
+            }
+        }
+        let _ = sender.send(Ok(true));
+    }
 }
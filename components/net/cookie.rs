@@ -13,6 +13,10 @@ use std::net::{Ipv4Addr, Ipv6Addr};
 use time::{Tm, now, at, Duration};
 use url::Url;
 
+/// The largest cookie, counting both its name and its value, that will be stored.
+/// http://tools.ietf.org/html/rfc6265#section-6.1
+pub const MAX_COOKIE_SIZE: usize = 4096;
+
 /// A stored cookie that wraps the definition in cookie-rs. This is used to implement
 /// various behaviours defined in the spec that rely on an associated request URL,
 /// which cookie-rs and hyper's header parsing do not support.
@@ -30,6 +34,11 @@ impl Cookie {
     /// http://tools.ietf.org/html/rfc6265#section-5.3
     pub fn new_wrapped(mut cookie: cookie_rs::Cookie, request: &Url, source: CookieSource)
                        -> Option<Cookie> {
+        // http://tools.ietf.org/html/rfc6265#section-6.1
+        if cookie.name.len() + cookie.value.len() > MAX_COOKIE_SIZE {
+            return None;
+        }
+
         // Step 3
         let (persistent, expiry_time) = match (&cookie.max_age, &cookie.expires) {
             (&Some(max_age), _) => {
@@ -43,6 +52,11 @@ impl Cookie {
 
         // Step 4
         let mut domain = cookie.domain.clone().unwrap_or("".to_owned());
+        // http://tools.ietf.org/html/rfc6265#section-5.2.3
+        if domain.starts_with('.') {
+            domain.remove(0);
+        }
+        let mut domain = domain.to_lowercase();
 
         // Step 5
         if is_pub_domain(&domain) {
@@ -93,6 +107,11 @@ impl Cookie {
         self.last_access = now();
     }
 
+    /// http://tools.ietf.org/html/rfc6265#section-5.3 step 3
+    pub fn is_expired(&self) -> bool {
+        self.expiry_time.map_or(false, |expiry| expiry.to_timespec() <= now().to_timespec())
+    }
+
     // http://tools.ietf.org/html/rfc6265#section-5.1.4
     pub fn default_path(request_path: &str) -> &str {
         // Step 2
@@ -152,10 +171,14 @@ impl Cookie {
                 return false;
             }
         } else {
-            if let (Some(domain), &Some(ref cookie_domain)) = (domain, &self.cookie.domain) {
-                if !Cookie::domain_match(domain, cookie_domain) {
-                    return false;
+            match (domain, &self.cookie.domain) {
+                (Some(domain), &Some(ref cookie_domain)) => {
+                    if !Cookie::domain_match(domain, cookie_domain) {
+                        return false;
+                    }
                 }
+                // A domain cookie can never match a URL without a host.
+                _ => return false,
             }
         }
 
@@ -171,6 +194,9 @@ impl Cookie {
         if self.cookie.httponly && source == CookieSource::NonHTTP {
             return false;
         }
+        if self.is_expired() {
+            return false;
+        }
 
         true
     }
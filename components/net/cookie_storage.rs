@@ -11,6 +11,13 @@ use net_traits::CookieSource;
 use std::cmp::Ordering;
 use url::Url;
 
+/// The most cookies kept for a single domain before the least recently used are evicted.
+/// http://tools.ietf.org/html/rfc6265#section-6.1
+pub const MAX_COOKIES_PER_DOMAIN: usize = 150;
+
+/// The most cookies kept in total before the least recently used are evicted.
+pub const MAX_COOKIES: usize = 3000;
+
 #[derive(Clone, RustcDecodable, RustcEncodable)]
 pub struct CookieStorage {
     version: u32,
@@ -65,7 +72,63 @@ impl CookieStorage {
         }
 
         // Step 12
+        let domain = cookie.cookie.domain.clone();
         self.cookies.push(cookie);
+        self.evict(domain);
+    }
+
+    /// Remove expired cookies, then the least recently accessed cookies of
+    /// `domain` and finally of the whole jar until both are within their limits.
+    /// http://tools.ietf.org/html/rfc6265#section-5.3 step 12
+    fn evict(&mut self, domain: Option<String>) {
+        self.remove_expired();
+
+        loop {
+            let oldest = self.least_recently_accessed(|c| c.cookie.domain == domain);
+            match oldest {
+                Some((index, count)) if count > MAX_COOKIES_PER_DOMAIN => {
+                    self.cookies.remove(index);
+                }
+                _ => break,
+            }
+        }
+
+        while self.cookies.len() > MAX_COOKIES {
+            let (index, _) = self.least_recently_accessed(|_| true).unwrap();
+            self.cookies.remove(index);
+        }
+    }
+
+    fn remove_expired(&mut self) {
+        self.cookies.retain(|c| !c.is_expired());
+    }
+
+    /// The index of the least recently accessed cookie satisfying `predicate`,
+    /// along with how many cookies satisfy it.
+    fn least_recently_accessed<F>(&self, predicate: F) -> Option<(usize, usize)>
+        where F: Fn(&Cookie) -> bool
+    {
+        let mut matching = self.cookies.iter().enumerate().filter(|&(_, c)| predicate(c));
+        let first = match matching.next() {
+            Some(first) => first,
+            None => return None,
+        };
+        let (oldest, count) = matching.fold((first, 1), |(oldest, count), candidate| {
+            if candidate.1.last_access.to_timespec() < (oldest.1).last_access.to_timespec() {
+                (candidate, count + 1)
+            } else {
+                (oldest, count + 1)
+            }
+        });
+        Some((oldest.0, count))
+    }
+
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
     }
 
     pub fn cookie_comparator(a: &Cookie, b: &Cookie) -> Ordering {
@@ -85,6 +148,7 @@ impl CookieStorage {
 
     // http://tools.ietf.org/html/rfc6265#section-5.4
     pub fn cookies_for_url(&mut self, url: &Url, source: CookieSource) -> Option<String> {
+        self.remove_expired();
         let filterer = |c: &&mut Cookie| -> bool {
             info!(" === SENT COOKIE : {} {} {:?} {:?}",
                   c.cookie.name, c.cookie.value, c.cookie.domain, c.cookie.path);
@@ -118,6 +182,7 @@ impl CookieStorage {
 
     pub fn cookies_data_for_url<'a>(&'a mut self, url: &'a Url,
                                     source: CookieSource) -> Box<Iterator<Item=cookie_rs::Cookie> + 'a> {
+        self.remove_expired();
         Box::new(self.cookies.iter_mut().filter(move |c| { c.appropriate_for_url(url, source) }).map(|c| {
             c.touch();
             c.cookie.clone()
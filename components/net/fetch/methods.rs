@@ -36,9 +36,11 @@ use std::iter::FromIterator;
 use std::mem::swap;
 use std::ops::Deref;
 use std::rc::Rc;
-use std::sync::mpsc::{channel, Sender, Receiver};
+use std::sync::mpsc::{Receiver, Sender, SyncSender, sync_channel};
+use time;
 use unicase::UniCase;
 use url::{Origin as UrlOrigin, Url};
+use util::compat;
 use util::thread::spawn_named;
 use uuid;
 
@@ -55,7 +57,11 @@ pub struct FetchContext {
     pub devtools_chan: Option<Sender<DevtoolsControlMsg>>,
 }
 
-type DoneChannel = Option<(Sender<Data>, Receiver<Data>)>;
+/// How many chunks of a response body the thread reading it from the network gets ahead
+/// of the fetch handing them to its target, so that a slow target slows the reading down.
+const DONE_CHANNEL_CHUNKS: usize = 16;
+
+type DoneChannel = Option<(SyncSender<Data>, Receiver<Data>)>;
 
 /// [Fetch](https://fetch.spec.whatwg.org#concept-fetch)
 pub fn fetch(request: Rc<Request>, target: &mut Target, context: FetchContext) -> Response {
@@ -125,6 +131,11 @@ pub fn fetch_with_cors_cache(request: Rc<Request>,
         // TODO: create a fetch record and append it to request's client's fetch group list
     }
 
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-fetchstart
+    let mut timing = request.timing.get();
+    timing.fetch_start = time::precise_time_ns();
+    request.timing.set(timing);
+
     // Step 7
     main_fetch(request, cache, false, false, target, &mut None, &context)
 }
@@ -672,6 +683,15 @@ fn http_redirect_fetch(request: Rc<Request>,
     // Step 8
     request.redirect_count.set(request.redirect_count.get() + 1);
 
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-redirectstart
+    let mut timing = request.timing.get();
+    if timing.redirect_start == 0 {
+        timing.redirect_start = timing.fetch_start;
+    }
+    timing.redirect_end = time::precise_time_ns();
+    timing.redirect_count = request.redirect_count.get() as u16;
+    request.timing.set(timing);
+
     // Step 9
     request.same_origin_data.set(false);
 
@@ -701,11 +721,16 @@ fn http_redirect_fetch(request: Rc<Request>,
     let status_code = response.actual_response().status.unwrap();
     if ((status_code == StatusCode::MovedPermanently || status_code == StatusCode::Found) &&
         *request.method.borrow() == Method::Post) ||
-        status_code == StatusCode::SeeOther {
+        (status_code == StatusCode::SeeOther && *request.method.borrow() != Method::Head) {
         *request.method.borrow_mut() = Method::Get;
         *request.body.borrow_mut() = None;
     }
 
+    // If the body is kept for the next request (307, 308, and non-POST 301/302),
+    // it has to be replayable. Request bodies are always buffered byte sequences
+    // at the moment, so there is no body whose source would be null here; once
+    // stream bodies are supported this must return a network error for them.
+
     // Step 14
     request.url_list.borrow_mut().push(location_url);
 
@@ -767,7 +792,8 @@ fn http_network_or_cache_fetch(request: Rc<Request>,
 
     // Step 8
     if !http_request.headers.borrow().has::<UserAgent>() {
-        http_request.headers.borrow_mut().set(UserAgent(context.user_agent.clone()));
+        let user_agent = compat::user_agent_for_url(&http_request.current_url(), &context.user_agent);
+        http_request.headers.borrow_mut().set(UserAgent(user_agent));
     }
 
     match http_request.cache_mode.get() {
@@ -965,14 +991,18 @@ fn http_network_fetch(request: Rc<Request>,
     // do not. Once we support other kinds of fetches we'll need to be more fine grained here
     // since things like image fetches are classified differently by devtools
     let is_xhr = request.destination == Destination::None;
+    let mut timing = request.timing.get();
     let wrapped_response = obtain_response(&factory, &url, &request.method.borrow(),
                                            &request.headers.borrow(),
-                                           &cancellation_listener, &request.body.borrow(), &request.method.borrow(),
-                                           &request.pipeline_id.get(), request.redirect_count.get() + 1,
-                                           request_id.as_ref().map(Deref::deref), is_xhr);
+                                           &cancellation_listener, &request.body.borrow(),
+                                           &request.pipeline_id.get(),
+                                           request_id.as_ref().map(Deref::deref), is_xhr,
+                                           &mut timing);
+    request.timing.set(timing);
 
     let pipeline_id = request.pipeline_id.get();
     let mut response = Response::new();
+    response.timing = timing;
     match wrapped_response {
         Ok((res, msg)) => {
             response.url = Some(url.clone());
@@ -984,7 +1014,7 @@ fn http_network_fetch(request: Rc<Request>,
             let res_body = response.body.clone();
 
             // We're about to spawn a thread to be waited on here
-            *done_chan = Some(channel());
+            *done_chan = Some(sync_channel(DONE_CHANNEL_CHUNKS));
             let meta = match response.metadata().expect("Response metadata should exist at this stage") {
                 FetchMetadata::Unfiltered(m) => m,
                 FetchMetadata::Filtered { unsafe_, .. } => unsafe_
@@ -1017,11 +1047,16 @@ fn http_network_fetch(request: Rc<Request>,
                         loop {
                             match read_block(&mut res) {
                                 Ok(ReadResult::Payload(chunk)) => {
-                                    if let ResponseBody::Receiving(ref mut body) = *res_body.lock().unwrap() {
-                                        body.extend_from_slice(&chunk);
-                                        if let Some(ref sender) = done_sender {
-                                            let _ = sender.send(Data::Payload(chunk));
-                                        }
+                                    let receiving = match *res_body.lock().unwrap() {
+                                        ResponseBody::Receiving(ref mut body) => {
+                                            body.extend_from_slice(&chunk);
+                                            true
+                                        },
+                                        _ => false,
+                                    };
+                                    // The body is unlocked while this blocks on a full channel.
+                                    if let (true, Some(sender)) = (receiving, done_sender.as_ref()) {
+                                        let _ = sender.send(Data::Payload(chunk));
                                     }
                                 },
                                 Ok(ReadResult::EOF) | Err(_) => {
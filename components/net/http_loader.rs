@@ -30,7 +30,7 @@ use log;
 use mime_classifier::MimeClassifier;
 use msg::constellation_msg::{PipelineId, ReferrerPolicy};
 use net_traits::{CookieSource, IncludeSubdomains, LoadConsumer, LoadContext, LoadData};
-use net_traits::{CustomResponse, CustomResponseMediator, Metadata, NetworkError};
+use net_traits::{CustomResponse, CustomResponseMediator, Metadata, NetworkError, ResourceFetchTiming};
 use net_traits::ProgressMsg::{Done, Payload};
 use net_traits::hosts::replace_hosts;
 use net_traits::response::HttpsState;
@@ -53,6 +53,7 @@ use time::Tm;
 #[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
 use tinyfiledialogs;
 use url::{Position, Url, Origin};
+use util::compat;
 use util::prefs::PREFS;
 use util::thread::spawn_named;
 use uuid;
@@ -664,7 +665,7 @@ pub fn modify_request_headers(headers: &mut Headers,
     // allowed.
     // https://fetch.spec.whatwg.org/#concept-http-network-or-cache-fetch step 8
     if !headers.has::<UserAgent>() {
-        headers.set(UserAgent(user_agent.to_owned()));
+        headers.set(UserAgent(compat::user_agent_for_url(url, user_agent)));
     }
 
     set_default_accept(headers);
@@ -742,14 +743,12 @@ pub fn obtain_response<A>(request_factory: &HttpRequestFactory<R=A>,
                           request_headers: &Headers,
                           cancel_listener: &CancellationListener,
                           data: &Option<Vec<u8>>,
-                          load_data_method: &Method,
                           pipeline_id: &Option<PipelineId>,
-                          iters: u32,
                           request_id: Option<&str>,
-                          is_xhr: bool)
+                          is_xhr: bool,
+                          timing: &mut ResourceFetchTiming)
                           -> Result<(A::R, Option<ChromeToDevtoolsControlMsg>), LoadError>
                           where A: HttpRequest + 'static  {
-    let null_data = None;
     let response;
     let connection_url = replace_hosts(&url);
     let mut msg;
@@ -762,24 +761,15 @@ pub fn obtain_response<A>(request_factory: &HttpRequestFactory<R=A>,
     loop {
         let mut headers = request_headers.clone();
 
-        // Avoid automatically sending request body if a redirect has occurred.
-        //
-        // TODO - This is the wrong behaviour according to the RFC. However, I'm not
-        // sure how much "correctness" vs. real-world is important in this case.
-        //
-        // https://tools.ietf.org/html/rfc7231#section-6.4
-        let is_redirected_request = iters != 1;
-        let request_body;
-        match data {
-            &Some(ref d) if !is_redirected_request => {
-                headers.set(ContentLength(d.len() as u64));
-                request_body = data;
-            }
-            _ => {
-                if *load_data_method != Method::Get && *load_data_method != Method::Head {
+        // Callers are responsible for dropping the body when a redirect
+        // requires it, so whatever we're given here is sent as-is.
+        // https://fetch.spec.whatwg.org/#http-redirect-fetch
+        match *data {
+            Some(ref d) => headers.set(ContentLength(d.len() as u64)),
+            None => {
+                if *method != Method::Get && *method != Method::Head {
                     headers.set(ContentLength(0))
                 }
-                request_body = &null_data;
             }
         }
 
@@ -793,9 +783,18 @@ pub fn obtain_response<A>(request_factory: &HttpRequestFactory<R=A>,
 
         let connect_start = precise_time_ms();
 
+        // Name resolution happens inside connection setup, so the lookup and
+        // connect phases can't be told apart and share the same timestamps.
+        timing.domain_lookup_start = time::precise_time_ns();
+        timing.connect_start = timing.domain_lookup_start;
+        timing.secure_connection_start = if url.scheme() == "https" { timing.connect_start } else { 0 };
+
         let req = try!(request_factory.create(connection_url.clone(), method.clone(),
                                               headers.clone()));
 
+        timing.domain_lookup_end = time::precise_time_ns();
+        timing.connect_end = timing.domain_lookup_end;
+
         let connect_end = precise_time_ms();
 
         if cancel_listener.is_cancelled() {
@@ -804,7 +803,9 @@ pub fn obtain_response<A>(request_factory: &HttpRequestFactory<R=A>,
 
         let send_start = precise_time_ms();
 
-        let maybe_response = req.send(request_body);
+        timing.request_start = time::precise_time_ns();
+        let maybe_response = req.send(data);
+        timing.response_start = time::precise_time_ns();
 
         let send_end = precise_time_ms();
 
@@ -813,7 +814,7 @@ pub fn obtain_response<A>(request_factory: &HttpRequestFactory<R=A>,
                 Some(prepare_devtools_request(
                     request_id.into(),
                     url.clone(), method.clone(), headers,
-                    request_body.clone(), pipeline_id, time::now(),
+                    data.clone(), pipeline_id, time::now(),
                     connect_end - connect_start, send_end - send_start, is_xhr))
             } else {
                 None
@@ -875,8 +876,13 @@ pub fn load<A, B>(load_data: &LoadData,
     let mut doc_url = load_data.url.clone();
     let mut redirected_to = HashSet::new();
     let mut method = load_data.method.clone();
+    // Request body - dropped when a redirect rewrites the method to GET
+    let mut body = load_data.data.clone();
     // URL of referrer - to be updated with redirects
     let mut referrer_url = load_data.referrer_url.clone();
+    // https://w3c.github.io/navigation-timing/#dom-performancetiming-fetchstart
+    let mut timing = ResourceFetchTiming::default();
+    timing.fetch_start = time::precise_time_ns();
 
     let mut new_auth_header: Option<Authorization<Basic>> = None;
 
@@ -993,9 +999,8 @@ pub fn load<A, B>(load_data: &LoadData,
 
         let (response, msg) =
             try!(obtain_response(request_factory, &doc_url, &method, &request_headers,
-                                 &cancel_listener, &load_data.data, &load_data.method,
-                                 &load_data.pipeline_id, iters,
-                                 request_id.as_ref().map(Deref::deref), false));
+                                 &cancel_listener, &body, &load_data.pipeline_id,
+                                 request_id.as_ref().map(Deref::deref), false, &mut timing));
 
         process_response_headers(&response, &doc_url, &http_state.cookie_jar, &http_state.hsts_list, &load_data);
 
@@ -1049,11 +1054,16 @@ pub fn load<A, B>(load_data: &LoadData,
                 };
 
                 // According to https://tools.ietf.org/html/rfc7231#section-6.4.2,
-                // historically UAs have rewritten POST->GET on 301 and 302 responses.
-                if method == Method::Post &&
+                // historically UAs have rewritten POST->GET on 301 and 302 responses,
+                // and 303 always switches to GET. 307 and 308 preserve both the method
+                // and the body.
+                // https://fetch.spec.whatwg.org/#http-redirect-fetch step 11
+                if (method == Method::Post &&
                     (response.status() == StatusCode::MovedPermanently ||
-                        response.status() == StatusCode::Found) {
+                        response.status() == StatusCode::Found)) ||
+                    (response.status() == StatusCode::SeeOther && method != Method::Head) {
                     method = Method::Get;
+                    body = None;
                 }
 
                 if redirected_to.contains(&new_doc_url) {
@@ -1063,6 +1073,13 @@ pub fn load<A, B>(load_data: &LoadData,
                 info!("redirecting to {}", new_doc_url);
                 doc_url = new_doc_url;
 
+                // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-redirectstart
+                if timing.redirect_start == 0 {
+                    timing.redirect_start = timing.fetch_start;
+                }
+                timing.redirect_end = time::precise_time_ns();
+                timing.redirect_count += 1;
+
                 redirected_to.insert(doc_url.clone());
             }
         }
@@ -1091,6 +1108,7 @@ pub fn load<A, B>(load_data: &LoadData,
             HttpsState::None
         };
         metadata.referrer = referrer_url.clone();
+        metadata.timing = timing;
 
         // --- Tell devtools that we got a response
         // Send an HttpResponse message to devtools with the corresponding request_id
@@ -7,7 +7,7 @@ use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use msg::constellation_msg::{PipelineId, ReferrerPolicy};
 use net_traits::{AsyncResponseTarget, CoreResourceMsg, CoreResourceThread, LoadConsumer, LoadData, LoadOrigin};
-use net_traits::{LoadContext, NetworkError, ResponseAction};
+use net_traits::{IpcBytes, LoadContext, Metadata, NetworkError, ResponseAction};
 use net_traits::image::base::{Image, ImageMetadata, PixelFormat, load_from_memory};
 use net_traits::image_cache_thread::{ImageCacheChan, ImageCacheCommand, ImageCacheThread, ImageState};
 use net_traits::image_cache_thread::{ImageCacheResult, ImageOrMetadataAvailable, ImageResponse, UsePlaceholder};
@@ -22,6 +22,7 @@ use std::sync::Arc;
 use std::sync::mpsc::{Receiver, Sender, channel};
 use threadpool::ThreadPool;
 use url::Url;
+use util::prefs::PREFS;
 use util::resource_files::resources_dir_path;
 use util::thread::spawn_named;
 use webrender_traits;
@@ -44,13 +45,20 @@ struct PendingLoad {
     // Image metadata, if available.
     metadata: Option<ImageMetadata>,
 
+    // The metadata of the network response, once its headers have arrived.
+    fetch_metadata: Option<Metadata>,
+
     // Once loading is complete, the result of the operation.
     result: Option<Result<(), NetworkError>>,
     listeners: Vec<ImageListener>,
 
     // The url being loaded. Do not forget that this may be several Mb
     // if we are loading a data: url.
-    url: Arc<Url>
+    url: Arc<Url>,
+
+    // The complete compressed bytes, once they have been sent to the decoder. They are kept so
+    // that the image can be decoded again after being evicted.
+    compressed_bytes: Option<Arc<Vec<u8>>>,
 }
 
 enum LoadResult {
@@ -64,9 +72,11 @@ impl PendingLoad {
         PendingLoad {
             bytes: vec!(),
             metadata: None,
+            fetch_metadata: None,
             result: None,
             listeners: vec!(),
             url: url,
+            compressed_bytes: None,
         }
     }
 
@@ -163,16 +173,47 @@ impl AllPendingLoads {
 /// fetched again.
 struct CompletedLoad {
     image_response: ImageResponse,
+
+    // The bytes the image was decoded from, if it can be evicted and decoded again.
+    compressed_bytes: Option<Arc<Vec<u8>>>,
+
+    // The value of the cache's use counter when this image was last requested.
+    last_used: u64,
 }
 
 impl CompletedLoad {
-    fn new(image_response: ImageResponse) -> CompletedLoad {
+    fn new(image_response: ImageResponse,
+           compressed_bytes: Option<Arc<Vec<u8>>>,
+           last_used: u64) -> CompletedLoad {
         CompletedLoad {
             image_response: image_response,
+            compressed_bytes: compressed_bytes,
+            last_used: last_used,
         }
     }
+
+    // The memory taken by the decoded image, if it can be evicted.
+    fn evictable_size(&self) -> Option<usize> {
+        match (&self.image_response, &self.compressed_bytes) {
+            (&ImageResponse::Loaded(ref image), &Some(_)) => Some(image.bytes.len()),
+            _ => None,
+        }
+    }
+}
+
+/// An image whose decoded frames were evicted from the cache.
+struct EvictedLoad {
+    // The bytes the image was decoded from.
+    compressed_bytes: Arc<Vec<u8>>,
+
+    // The value of the cache's use counter when the image was evicted.
+    evicted_at: u64,
 }
 
+/// The default memory budget for decoded images, when the
+/// `image.cache.memory-limit-mb` pref is not set.
+const DEFAULT_MEMORY_LIMIT_MB: usize = 256;
+
 /// Stores information to notify a client when the state
 /// of an image changes.
 struct ImageListener {
@@ -210,7 +251,7 @@ impl ImageListener {
         }
     }
 
-    fn notify(&self, image_response: ImageResponse) {
+    fn notify(&self, image_response: ImageResponse, metadata: Option<Metadata>) {
         if !self.send_metadata_msg {
             if let ImageResponse::MetadataLoaded(_) = image_response {
                 return;
@@ -221,6 +262,7 @@ impl ImageListener {
         let msg = ImageCacheResult {
             responder: self.responder.clone(),
             image_response: image_response,
+            metadata: metadata,
         };
         sender.send(msg).ok();
     }
@@ -249,6 +291,23 @@ struct ImageCache {
     // Images that have finished loading (successful or not)
     completed_loads: HashMap<Arc<Url>, CompletedLoad>,
 
+    // Images whose decoded frames were evicted to stay within the memory budget, with the
+    // compressed bytes to decode them from when they are requested again.
+    evicted_loads: HashMap<Arc<Url>, EvictedLoad>,
+
+    // The memory taken by the decoded images in `completed_loads` that can be evicted.
+    decoded_size: usize,
+
+    // The memory taken by the compressed bytes in `evicted_loads`.
+    evicted_size: usize,
+
+    // The most memory that evictable decoded images may take, and separately, that the
+    // compressed bytes of evicted images may take.
+    memory_limit: usize,
+
+    // Incremented every time an image is used, to find the least recently used ones.
+    use_counter: u64,
+
     // The placeholder image used when an image fails to load
     placeholder_image: Option<Arc<Image>>,
 
@@ -351,6 +410,13 @@ impl ImageCache {
             thread_pool: ThreadPool::new(4),
             pending_loads: AllPendingLoads::new(),
             completed_loads: HashMap::new(),
+            evicted_loads: HashMap::new(),
+            decoded_size: 0,
+            evicted_size: 0,
+            memory_limit: PREFS.get("image.cache.memory-limit-mb").as_u64()
+                               .map_or(DEFAULT_MEMORY_LIMIT_MB, |limit| limit as usize) *
+                          1024 * 1024,
+            use_counter: 0,
             core_resource_thread: core_resource_thread,
             placeholder_image: placeholder_image,
             webrender_api: webrender_api,
@@ -410,6 +476,12 @@ impl ImageCache {
             ImageCacheCommand::StoreDecodeImage(url, image_vector) => {
                 self.store_decode_image(url, image_vector);
             }
+            ImageCacheCommand::DecodeImage(bytes, consumer) => {
+                // Nothing is cached: the image has no URL, and belongs to its consumer.
+                self.thread_pool.execute(move || {
+                    let _ = consumer.send(load_from_memory(&bytes));
+                });
+            }
         };
 
         None
@@ -418,7 +490,10 @@ impl ImageCache {
     // Handle progress messages from the resource thread
     fn handle_progress(&mut self, msg: ResourceLoadInfo) {
         match (msg.action, msg.key) {
-            (ResponseAction::HeadersAvailable(_), _) => {}
+            (ResponseAction::HeadersAvailable(metadata), _) => {
+                let pending_load = self.pending_loads.get_by_key_mut(&msg.key).unwrap();
+                pending_load.fetch_metadata = metadata.ok();
+            }
             (ResponseAction::DataAvailable(data), _) => {
                 let pending_load = self.pending_loads.get_by_key_mut(&msg.key).unwrap();
                 pending_load.bytes.extend_from_slice(&data);
@@ -430,7 +505,7 @@ impl ImageCache {
                                                          height: dimensions.height };
                         pending_load.metadata = Some(img_metadata.clone());
                         for listener in &pending_load.listeners {
-                            listener.notify(ImageResponse::MetadataLoaded(img_metadata.clone()).clone());
+                            listener.notify(ImageResponse::MetadataLoaded(img_metadata.clone()).clone(), None);
                         }
                     }
                 }
@@ -438,19 +513,12 @@ impl ImageCache {
             (ResponseAction::ResponseComplete(result), key) => {
                 match result {
                     Ok(()) => {
-                        let pending_load = self.pending_loads.get_by_key_mut(&msg.key).unwrap();
-                        pending_load.result = Some(result);
-                        let bytes = mem::replace(&mut pending_load.bytes, vec!());
-                        let sender = self.decoder_sender.clone();
-
-                        self.thread_pool.execute(move || {
-                            let image = load_from_memory(&bytes);
-                            let msg = DecoderMsg {
-                                key: key,
-                                image: image
-                            };
-                            sender.send(msg).unwrap();
-                        });
+                        let bytes = {
+                            let pending_load = self.pending_loads.get_by_key_mut(&msg.key).unwrap();
+                            pending_load.result = Some(result);
+                            Arc::new(mem::replace(&mut pending_load.bytes, vec!()))
+                        };
+                        self.decode(key, bytes);
                     }
                     Err(_) => {
                         match self.placeholder_image.clone() {
@@ -466,6 +534,21 @@ impl ImageCache {
         }
     }
 
+    // Decode the complete bytes of a pending load on one of the worker threads.
+    fn decode(&mut self, key: LoadKey, bytes: Arc<Vec<u8>>) {
+        self.pending_loads.get_by_key_mut(&key).unwrap().compressed_bytes = Some(bytes.clone());
+        let sender = self.decoder_sender.clone();
+
+        self.thread_pool.execute(move || {
+            let image = load_from_memory(&bytes);
+            let msg = DecoderMsg {
+                key: key,
+                image: image
+            };
+            sender.send(msg).unwrap();
+        });
+    }
+
     // Handle a message from one of the decoder worker threads
     fn handle_decoder(&mut self, msg: DecoderMsg) {
         let image = match msg.image {
@@ -497,11 +580,71 @@ impl ImageCache {
             LoadResult::None => ImageResponse::None,
         };
 
-        let completed_load = CompletedLoad::new(image_response.clone());
+        self.use_counter += 1;
+        let completed_load = CompletedLoad::new(image_response.clone(),
+                                                pending_load.compressed_bytes,
+                                                self.use_counter);
+        self.decoded_size += completed_load.evictable_size().unwrap_or(0);
         self.completed_loads.insert(pending_load.url, completed_load);
 
+        // The listener that started the load reports its fetch; the others shared it.
+        let mut fetch_metadata = pending_load.fetch_metadata;
         for listener in pending_load.listeners {
-            listener.notify(image_response.clone());
+            listener.notify(image_response.clone(), fetch_metadata.take());
+        }
+
+        self.evict_if_over_memory_limit();
+    }
+
+    // Drop the least recently used decoded images until they fit in the memory budget. Their
+    // compressed bytes are kept, so they are decoded again if they are requested later, until
+    // those don't fit in the budget either and the oldest are fetched again instead.
+    // Clients that still hold an evicted image can read its pixels until they drop it, but
+    // WebRender no longer has it.
+    fn evict_if_over_memory_limit(&mut self) {
+        while self.decoded_size > self.memory_limit {
+            let least_recently_used = self.completed_loads.iter()
+                .filter_map(|(url, completed_load)| {
+                    completed_load.evictable_size().map(|size| (url, size, completed_load.last_used))
+                })
+                .min_by_key(|&(_, _, last_used)| last_used)
+                .map(|(url, size, _)| (url.clone(), size));
+            let (url, size) = match least_recently_used {
+                Some(least_recently_used) => least_recently_used,
+                None => break,
+            };
+
+            let completed_load = match self.completed_loads.remove(&url) {
+                Some(completed_load) => completed_load,
+                None => break,
+            };
+            self.decoded_size -= size;
+            debug!("Evicting decoded image {} from the image cache", url);
+
+            if let ImageResponse::Loaded(ref image) = completed_load.image_response {
+                if let (Some(webrender_api), Some(image_key)) = (self.webrender_api.as_ref(), image.id) {
+                    webrender_api.delete_image(image_key);
+                }
+            }
+            if let Some(compressed_bytes) = completed_load.compressed_bytes {
+                self.use_counter += 1;
+                self.evicted_size += compressed_bytes.len();
+                self.evicted_loads.insert(url, EvictedLoad {
+                    compressed_bytes: compressed_bytes,
+                    evicted_at: self.use_counter,
+                });
+            }
+        }
+
+        while self.evicted_size > self.memory_limit {
+            let oldest = self.evicted_loads.iter()
+                .min_by_key(|&(_, evicted_load)| evicted_load.evicted_at)
+                .map(|(url, _)| url.clone());
+            let evicted_load = match oldest.and_then(|url| self.evicted_loads.remove(&url)) {
+                Some(evicted_load) => evicted_load,
+                None => break,
+            };
+            self.evicted_size -= evicted_load.compressed_bytes.len();
         }
     }
 
@@ -519,11 +662,26 @@ impl ImageCache {
         // Let's avoid copying url everywhere.
         let ref_url = Arc::new(url);
 
+        // If the decoded image was evicted, decode it again without going to the network.
+        if let Some(evicted_load) = self.evicted_loads.remove(&ref_url) {
+            self.evicted_size -= evicted_load.compressed_bytes.len();
+            let load_key = {
+                let (_, load_key, pending_load) = self.pending_loads.get_cached(ref_url.clone());
+                pending_load.add_listener(image_listener);
+                pending_load.result = Some(Ok(()));
+                load_key
+            };
+            self.decode(load_key, evicted_load.compressed_bytes);
+            return
+        }
+
         // Check if already completed
-        match self.completed_loads.get(&ref_url) {
+        self.use_counter += 1;
+        match self.completed_loads.get_mut(&ref_url) {
             Some(completed_load) => {
                 // It's already completed, return a notify straight away
-                image_listener.notify(completed_load.image_response.clone());
+                completed_load.last_used = self.use_counter;
+                image_listener.notify(completed_load.image_response.clone(), None);
             }
             None => {
                 // Check if the load is already pending
@@ -577,8 +735,10 @@ impl ImageCache {
                                       url: Url,
                                       placeholder: UsePlaceholder)
                                       -> Result<ImageOrMetadataAvailable, ImageState> {
-        match self.completed_loads.get(&url) {
+        self.use_counter += 1;
+        match self.completed_loads.get_mut(&url) {
             Some(completed_load) => {
+                completed_load.last_used = self.use_counter;
                 match (completed_load.image_response.clone(), placeholder) {
                     (ImageResponse::Loaded(image), _) |
                     (ImageResponse::PlaceholderLoaded(image), UsePlaceholder::Yes) => {
@@ -612,7 +772,7 @@ impl ImageCache {
                           loaded_bytes: Vec<u8>) {
         let (cache_result, load_key, _) = self.pending_loads.get_cached(Arc::new(ref_url));
         assert!(cache_result == CacheResult::Miss);
-        let action = ResponseAction::DataAvailable(loaded_bytes);
+        let action = ResponseAction::DataAvailable(IpcBytes::from_vec(loaded_bytes));
         let _ = self.progress_sender.send(ResourceLoadInfo {
             action: action,
             key: load_key,
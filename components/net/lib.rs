@@ -17,6 +17,8 @@ extern crate bitflags;
 extern crate brotli;
 extern crate content_blocker as content_blocker_parser;
 extern crate cookie as cookie_rs;
+#[cfg(target_os = "linux")]
+extern crate dbus;
 extern crate device;
 extern crate devtools_traits;
 extern crate flate2;
@@ -50,6 +52,7 @@ extern crate websocket;
 
 pub mod about_loader;
 pub mod blob_loader;
+pub mod bluetooth_events;
 pub mod bluetooth_thread;
 pub mod chrome_loader;
 pub mod connector;
@@ -63,6 +66,7 @@ pub mod hsts;
 pub mod http_loader;
 pub mod image_cache_thread;
 pub mod mime_classifier;
+pub mod prefetch_cache;
 pub mod pub_domains;
 pub mod resource_thread;
 pub mod storage_thread;
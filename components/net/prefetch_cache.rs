@@ -0,0 +1,119 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Responses fetched ahead of a likely navigation by `<link rel=prefetch>`. Each one is
+//! kept until a navigation to its URL uses it, or until it expires.
+//! https://html.spec.whatwg.org/multipage/#link-type-prefetch
+
+use hyper::header::{CacheControl, CacheDirective};
+use net_traits::Metadata;
+use std::collections::HashMap;
+use time;
+use url::Url;
+
+/// How long a prefetched response waits for a navigation, in nanoseconds.
+const PREFETCH_LIFETIME: u64 = 5 * 60 * 1000000000;
+
+/// How many bytes of response bodies are kept at most.
+const PREFETCH_CACHE_CAPACITY: usize = 16 * 1024 * 1024;
+
+struct PrefetchedResponse {
+    metadata: Metadata,
+    body: Vec<u8>,
+    /// When the response was stored, as a `time::precise_time_ns` value.
+    fetched_at: u64,
+}
+
+pub struct PrefetchCache {
+    entries: HashMap<Url, PrefetchedResponse>,
+    /// The size of all the bodies in `entries`.
+    size: usize,
+}
+
+/// Navigations to a URL with a fragment use the response prefetched for the URL without it.
+fn cache_key(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url
+}
+
+impl PrefetchCache {
+    pub fn new() -> PrefetchCache {
+        PrefetchCache {
+            entries: HashMap::new(),
+            size: 0,
+        }
+    }
+
+    /// Whether a response may be kept for a later navigation: it must be successful, and
+    /// must not forbid being stored.
+    pub fn is_storable(metadata: &Metadata) -> bool {
+        if metadata.status.as_ref().map_or(true, |&(code, _)| code != 200) {
+            return false;
+        }
+        match metadata.headers.as_ref().and_then(|headers| headers.get::<CacheControl>()) {
+            Some(&CacheControl(ref directives)) => !directives.contains(&CacheDirective::NoStore),
+            None => true,
+        }
+    }
+
+    /// Whether a response is waiting for a navigation to `url`.
+    pub fn contains(&self, url: &Url) -> bool {
+        self.entries.get(&cache_key(url)).map_or(false, |entry| !is_expired(entry, time::precise_time_ns()))
+    }
+
+    /// Keeps the response to a prefetch of `url`, replacing any earlier one. Expired
+    /// responses, and then the oldest ones, are dropped to make room for it.
+    pub fn store(&mut self, url: &Url, metadata: Metadata, body: Vec<u8>) {
+        if !PrefetchCache::is_storable(&metadata) || body.len() > PREFETCH_CACHE_CAPACITY {
+            return;
+        }
+        let now = time::precise_time_ns();
+        let key = cache_key(url);
+        self.remove(&key);
+        let expired: Vec<Url> = self.entries.iter()
+                                            .filter(|&(_, entry)| is_expired(entry, now))
+                                            .map(|(url, _)| url.clone())
+                                            .collect();
+        for url in expired {
+            self.remove(&url);
+        }
+        while self.size + body.len() > PREFETCH_CACHE_CAPACITY {
+            let oldest = self.entries.iter()
+                                     .min_by_key(|&(_, entry)| entry.fetched_at)
+                                     .map(|(url, _)| url.clone());
+            match oldest {
+                Some(url) => { self.remove(&url); },
+                None => break,
+            }
+        }
+        self.size += body.len();
+        self.entries.insert(key, PrefetchedResponse {
+            metadata: metadata,
+            body: body,
+            fetched_at: now,
+        });
+    }
+
+    /// Removes the response prefetched for `url` and returns it, unless it expired.
+    pub fn take(&mut self, url: &Url) -> Option<(Metadata, Vec<u8>)> {
+        match self.remove(&cache_key(url)) {
+            Some(ref entry) if is_expired(entry, time::precise_time_ns()) => None,
+            Some(entry) => Some((entry.metadata, entry.body)),
+            None => None,
+        }
+    }
+
+    fn remove(&mut self, key: &Url) -> Option<PrefetchedResponse> {
+        let entry = self.entries.remove(key);
+        if let Some(ref entry) = entry {
+            self.size -= entry.body.len();
+        }
+        entry
+    }
+}
+
+fn is_expired(entry: &PrefetchedResponse, now: u64) -> bool {
+    now - entry.fetched_at > PREFETCH_LIFETIME
+}
@@ -20,18 +20,20 @@ use hsts::HstsList;
 use http_loader::{self, HttpState};
 use hyper::client::pool::Pool;
 use hyper::header::{ContentType, Header, SetCookie};
+use hyper::method::Method;
 use hyper::mime::{Mime, SubLevel, TopLevel};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcReceiver, IpcReceiverSet, IpcSender};
 use mime_classifier::{ApacheBugFlag, MimeClassifier, NoSniffFlag};
 use net_traits::{AsyncResponseTarget, CoreResourceThread, Metadata, ProgressMsg, ResponseAction};
-use net_traits::{CookieSource, CoreResourceMsg, FetchResponseMsg, FetchTaskTarget, LoadConsumer};
-use net_traits::{CustomResponseMediator, LoadData, LoadResponse, NetworkError, ResourceId};
-use net_traits::{ResourceThreads, WebSocketCommunicate, WebSocketConnectData};
+use net_traits::{CookieSource, CoreResourceMsg, FetchResponseMsg, FetchResponseSender, FetchTaskTarget};
+use net_traits::{CustomResponseMediator, IpcBytes, LoadConsumer, LoadData, LoadResponse, NetworkError};
+use net_traits::{ResourceId, ResourceThreads, WebSocketCommunicate, WebSocketConnectData};
 use net_traits::LoadContext;
 use net_traits::ProgressMsg::Done;
 use net_traits::request::{Request, RequestInit};
 use net_traits::storage_thread::StorageThreadMsg;
+use prefetch_cache::PrefetchCache;
 use profile_traits::time::ProfilerChan;
 use rustc_serialize::{Decodable, Encodable};
 use rustc_serialize::json;
@@ -45,7 +47,7 @@ use std::io::prelude::*;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::sync::mpsc::{Receiver, Sender, channel};
 use storage_thread::StorageThreadFactory;
 use url::Url;
@@ -66,6 +68,7 @@ pub struct ResourceGroup {
     auth_cache: Arc<RwLock<AuthCache>>,
     hsts_list: Arc<RwLock<HstsList>>,
     connector: Arc<Pool<Connector>>,
+    prefetch_cache: Arc<Mutex<PrefetchCache>>,
 }
 
 impl ProgressSender {
@@ -75,7 +78,7 @@ impl ProgressSender {
             ProgressSender::Channel(ref c) => c.send(msg).map_err(|_| ()),
             ProgressSender::Listener(ref b) => {
                 let action = match msg {
-                    ProgressMsg::Payload(buf) => ResponseAction::DataAvailable(buf),
+                    ProgressMsg::Payload(buf) => ResponseAction::DataAvailable(IpcBytes::from_vec(buf)),
                     ProgressMsg::Done(status) => ResponseAction::ResponseComplete(status),
                 };
                 b.invoke_with_listener(action);
@@ -227,12 +230,14 @@ fn create_resource_groups(config_dir: Option<&Path>)
         auth_cache: Arc::new(RwLock::new(auth_cache)),
         hsts_list: Arc::new(RwLock::new(hsts_list.clone())),
         connector: create_http_connector(),
+        prefetch_cache: Arc::new(Mutex::new(PrefetchCache::new())),
     };
     let private_resource_group = ResourceGroup {
         cookie_jar: Arc::new(RwLock::new(CookieStorage::new())),
         auth_cache: Arc::new(RwLock::new(AuthCache::new())),
         hsts_list: Arc::new(RwLock::new(HstsList::new())),
         connector: create_http_connector(),
+        prefetch_cache: Arc::new(Mutex::new(PrefetchCache::new())),
     };
     (resource_group, private_resource_group)
 }
@@ -276,8 +281,10 @@ impl ResourceChannelManager {
         match msg {
             CoreResourceMsg::Load(load_data, consumer, id_sender) =>
                 self.resource_manager.load(load_data, consumer, id_sender, control_sender.clone(), group),
-            CoreResourceMsg::Fetch(init, sender) =>
-                self.resource_manager.fetch(init, sender, group),
+            CoreResourceMsg::Fetch(init, sender, acknowledgements) =>
+                self.resource_manager.fetch(init, sender, acknowledgements, group),
+            CoreResourceMsg::Prefetch(load_data) =>
+                self.resource_manager.prefetch(load_data, control_sender.clone(), group),
             CoreResourceMsg::WebsocketConnect(connect, connect_data) =>
                 self.resource_manager.websocket_connect(connect, connect_data, group),
             CoreResourceMsg::SetCookiesForUrl(request, cookie_list, source) =>
@@ -545,6 +552,19 @@ impl CoreResourceManager {
             CancellableResource::new(cancel_receiver, current_res_id, resource_thread)
         });
 
+        // A navigation uses the response of an earlier prefetch of its URL.
+        if let (&LoadContext::Browsing, &Method::Get) = (&load_data.context, &load_data.method) {
+            let prefetched = resource_grp.prefetch_cache.lock().unwrap().take(&load_data.url);
+            if let Some((metadata, body)) = prefetched {
+                debug!("using prefetched response for {}", load_data.url);
+                if let Ok(progress_sender) = start_sending_opt(consumer, metadata, None) {
+                    let _ = progress_sender.send(ProgressMsg::Payload(body));
+                    let _ = progress_sender.send(Done(Ok(())));
+                }
+                return;
+            }
+        }
+
         let cancel_listener = CancellationListener::new(cancel_resource);
         let loader = match load_data.url.scheme() {
             "chrome" => from_factory(chrome_loader::factory),
@@ -583,6 +603,7 @@ impl CoreResourceManager {
     fn fetch(&self,
              init: RequestInit,
              sender: IpcSender<FetchResponseMsg>,
+             acknowledgements: Option<IpcReceiver<usize>>,
              group: &ResourceGroup) {
         let http_state = HttpState {
             hsts_list: group.hsts_list.clone(),
@@ -598,12 +619,45 @@ impl CoreResourceManager {
             // todo load context / mimesniff in fetch
             // todo referrer policy?
             // todo service worker stuff
-            let mut target = Some(Box::new(sender) as Box<FetchTaskTarget + Send + 'static>);
+            let target = FetchResponseSender::new(sender, acknowledgements);
+            let mut target = Some(Box::new(target) as Box<FetchTaskTarget + Send + 'static>);
             let context = FetchContext { state: http_state, user_agent: ua, devtools_chan: dc };
             fetch(Rc::new(request), &mut target, context);
         })
     }
 
+    /// Loads a document that a navigation is likely to need into the prefetch cache of
+    /// `group`, unless a response for it is waiting there already.
+    /// https://html.spec.whatwg.org/multipage/#link-type-prefetch
+    fn prefetch(&mut self, load_data: LoadData, resource_thread: CoreResourceThread, group: &ResourceGroup) {
+        match load_data.url.scheme() {
+            "http" | "https" => {},
+            _ => return,
+        }
+        let url = load_data.url.clone();
+        if group.prefetch_cache.lock().unwrap().contains(&url) {
+            return;
+        }
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.load(load_data, LoadConsumer::Channel(sender), None, resource_thread, group);
+        let prefetch_cache = group.prefetch_cache.clone();
+        spawn_named(format!("prefetch for {}", url), move || {
+            let response = match receiver.recv() {
+                Ok(response) => response,
+                Err(_) => return,
+            };
+            let mut body = vec![];
+            loop {
+                match response.progress_port.recv() {
+                    Ok(ProgressMsg::Payload(data)) => body.extend_from_slice(&data),
+                    Ok(Done(Ok(()))) => break,
+                    Ok(Done(Err(_))) | Err(_) => return,
+                }
+            }
+            prefetch_cache.lock().unwrap().store(&url, response.metadata, body);
+        });
+    }
+
     fn websocket_connect(&self,
                          connect: WebSocketCommunicate,
                          connect_data: WebSocketConnectData,
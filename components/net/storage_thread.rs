@@ -3,15 +3,15 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
-use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use net_traits::storage_thread::{StorageEstimate, StorageThreadMsg, StorageType};
 use resource_thread;
 use std::borrow::ToOwned;
-use std::collections::BTreeMap;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use url::Url;
 use util::thread::spawn_named;
 
+/// The number of bytes that each origin may store, shared between all of its storage.
 const QUOTA_SIZE_LIMIT: usize = 5 * 1024 * 1024;
 
 pub trait StorageThreadFactory {
@@ -33,6 +33,8 @@ struct StorageManager {
     port: IpcReceiver<StorageThreadMsg>,
     session_data: HashMap<String, (usize, BTreeMap<String, String>)>,
     local_data: HashMap<String, (usize, BTreeMap<String, String>)>,
+    /// The origins whose data was made persistent with `navigator.storage.persist()`.
+    persisted_origins: HashSet<String>,
     config_dir: Option<PathBuf>,
 }
 
@@ -41,13 +43,16 @@ impl StorageManager {
            config_dir: Option<PathBuf>)
            -> StorageManager {
         let mut local_data = HashMap::new();
+        let mut persisted_origins = HashSet::new();
         if let Some(ref config_dir) = config_dir {
             resource_thread::read_json_from_file(&mut local_data, config_dir, "local_data.json");
+            resource_thread::read_json_from_file(&mut persisted_origins, config_dir, "persisted_origins.json");
         }
         StorageManager {
             port: port,
             session_data: HashMap::new(),
             local_data: local_data,
+            persisted_origins: persisted_origins,
             config_dir: config_dir,
         }
     }
@@ -78,9 +83,20 @@ impl StorageManager {
                 StorageThreadMsg::Clear(sender, url, storage_type) => {
                     self.clear(sender, url, storage_type)
                 }
+                StorageThreadMsg::Estimate(sender, url) => {
+                    self.estimate(sender, url)
+                }
+                StorageThreadMsg::Persist(sender, url) => {
+                    self.persist(sender, url)
+                }
+                StorageThreadMsg::Persisted(sender, url) => {
+                    self.persisted(sender, url)
+                }
                 StorageThreadMsg::Exit(sender) => {
                     if let Some(ref config_dir) = self.config_dir {
                         resource_thread::write_json_to_file(&self.local_data, config_dir, "local_data.json");
+                        resource_thread::write_json_to_file(&self.persisted_origins, config_dir,
+                                                            "persisted_origins.json");
                     }
                     let _ = sender.send(());
                     break
@@ -148,16 +164,8 @@ impl StorageManager {
                 value: String) {
         let origin = self.origin_as_string(url);
 
-        let (this_storage_size, other_storage_size) = {
-            let local_data = self.select_data(StorageType::Local);
-            let session_data = self.select_data(StorageType::Session);
-            let local_data_size = local_data.get(&origin).map_or(0, |&(total, _)| total);
-            let session_data_size = session_data.get(&origin).map_or(0, |&(total, _)| total);
-            match storage_type {
-                StorageType::Local => (local_data_size, session_data_size),
-                StorageType::Session => (session_data_size, local_data_size),
-            }
-        };
+        let this_storage_size = self.select_data(storage_type).get(&origin).map_or(0, |&(total, _)| total);
+        let other_storage_size = self.usage(&origin) - this_storage_size;
 
         let data = self.select_data_mut(storage_type);
         if !data.contains_key(&origin) {
@@ -232,6 +240,36 @@ impl StorageManager {
                         }})).unwrap();
     }
 
+    /// The number of bytes stored by `origin`, which counts against its quota.
+    fn usage(&self, origin: &str) -> usize {
+        self.local_data.get(origin).map_or(0, |&(total, _)| total) +
+        self.session_data.get(origin).map_or(0, |&(total, _)| total)
+    }
+
+    fn estimate(&self, sender: IpcSender<StorageEstimate>, url: Url) {
+        let origin = self.origin_as_string(url);
+        sender.send(StorageEstimate {
+            usage: self.usage(&origin) as u64,
+            quota: QUOTA_SIZE_LIMIT as u64,
+        }).unwrap();
+    }
+
+    /// Sends whether the data of the origin is persistent afterwards, which is false for
+    /// an opaque origin
+    fn persist(&mut self, sender: IpcSender<bool>, url: Url) {
+        let persisted = url.origin().is_tuple();
+        if persisted {
+            let origin = self.origin_as_string(url);
+            self.persisted_origins.insert(origin);
+        }
+        sender.send(persisted).unwrap();
+    }
+
+    fn persisted(&self, sender: IpcSender<bool>, url: Url) {
+        let origin = self.origin_as_string(url);
+        sender.send(self.persisted_origins.contains(&origin)).unwrap();
+    }
+
     fn origin_as_string(&self, url: Url) -> String {
         url.origin().ascii_serialization()
     }
@@ -63,6 +63,15 @@ pub type BluetoothDescriptorsMsg = Vec<BluetoothDescriptorMsg>;
 
 pub type BluetoothResult<T> = Result<T, BluetoothError>;
 
+/// A change the bluetooth thread tells script about while it watches a characteristic or a device.
+#[derive(Deserialize, Serialize)]
+pub enum BluetoothEventMsg {
+    /// The characteristic with the given id notified or indicated the given value.
+    CharacteristicValueChanged(String, Vec<u8>),
+    /// The watched device was heard advertising.
+    AdvertisementReceived(BluetoothDeviceMsg),
+}
+
 #[derive(Deserialize, Serialize)]
 pub enum BluetoothMethodMsg {
     RequestDevice(RequestDeviceoptions, IpcSender<BluetoothResult<BluetoothDeviceMsg>>),
@@ -78,5 +87,9 @@ pub enum BluetoothMethodMsg {
     GetDescriptors(String, Option<String>, IpcSender<BluetoothResult<BluetoothDescriptorsMsg>>),
     ReadValue(String, IpcSender<BluetoothResult<Vec<u8>>>),
     WriteValue(String, Vec<u8>, IpcSender<BluetoothResult<bool>>),
+    StartNotifications(String, IpcSender<BluetoothEventMsg>, IpcSender<BluetoothResult<bool>>),
+    StopNotifications(String, IpcSender<BluetoothResult<bool>>),
+    WatchAdvertisements(String, IpcSender<BluetoothEventMsg>, IpcSender<BluetoothResult<bool>>),
+    UnwatchAdvertisements(String, IpcSender<BluetoothResult<bool>>),
     Exit,
 }
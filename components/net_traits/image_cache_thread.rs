@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use Metadata;
 use image::base::ImageMetadata;
 use ipc_channel::ipc::{self, IpcSender};
 use msg::constellation_msg::Image;
@@ -14,18 +15,18 @@ use url::Url;
 /// and/or repaint.
 #[derive(Clone, Deserialize, Serialize)]
 pub struct ImageResponder {
-    sender: IpcSender<ImageResponse>,
+    sender: IpcSender<(ImageResponse, Option<Metadata>)>,
 }
 
 impl ImageResponder {
-    pub fn new(sender: IpcSender<ImageResponse>) -> ImageResponder {
+    pub fn new(sender: IpcSender<(ImageResponse, Option<Metadata>)>) -> ImageResponder {
         ImageResponder {
             sender: sender,
         }
     }
 
-    pub fn respond(&self, response: ImageResponse) {
-        self.sender.send(response).unwrap()
+    pub fn respond(&self, response: ImageResponse, metadata: Option<Metadata>) {
+        self.sender.send((response, metadata)).unwrap()
     }
 }
 
@@ -67,6 +68,9 @@ pub struct ImageCacheChan(pub IpcSender<ImageCacheResult>);
 pub struct ImageCacheResult {
     pub responder: Option<ImageResponder>,
     pub image_response: ImageResponse,
+    /// The metadata of the network response the image was loaded from. Only the request
+    /// that started the load receives it, so that each fetch is reported once.
+    pub metadata: Option<Metadata>,
 }
 
 /// Commands that the image cache understands.
@@ -97,6 +101,10 @@ pub enum ImageCacheCommand {
     /// decoding the result into pixel data
     StoreDecodeImage(Url, Vec<u8>),
 
+    /// Decode the given image bytes on one of the decoder threads, without caching the result,
+    /// and send the image to the consumer.
+    DecodeImage(Vec<u8>, IpcSender<Option<Image>>),
+
     /// Clients must wait for a response before shutting down the ResourceThread
     Exit(IpcSender<()>),
 }
@@ -169,6 +177,12 @@ impl ImageCacheThread {
         let _ = self.chan.send(msg);
     }
 
+    /// Asynchronously decode the given image bytes. See ImageCacheCommand::DecodeImage.
+    pub fn decode_image(&self, image_data: Vec<u8>, consumer: IpcSender<Option<Image>>) {
+        let msg = ImageCacheCommand::DecodeImage(image_data, consumer);
+        let _ = self.chan.send(msg);
+    }
+
     /// Shutdown the image cache thread.
     pub fn exit(&self) {
         let (response_chan, response_port) = ipc::channel().unwrap();
@@ -39,11 +39,13 @@ use hyper::http::RawStatus;
 use hyper::method::Method;
 use hyper::mime::{Attr, Mime};
 use hyper_serde::Serde;
-use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender, IpcSharedMemory};
 use msg::constellation_msg::{PipelineId, ReferrerPolicy};
 use request::{Request, RequestInit};
 use response::{HttpsState, Response};
+use std::cmp;
 use std::io::Error as IOError;
+use std::ops::Deref;
 use std::thread;
 use storage_thread::StorageThreadMsg;
 use url::Url;
@@ -164,6 +166,47 @@ pub trait LoadOrigin {
     fn pipeline_id(&self) -> Option<PipelineId>;
 }
 
+/// Payloads at least this large are sent in a shared memory segment, so that only a handle to
+/// the segment goes through the IPC channel instead of the bytes themselves.
+pub const SHARED_MEMORY_THRESHOLD: usize = 64 * 1024;
+
+/// A chunk of a response body on its way to another process.
+#[derive(Clone, Deserialize, Serialize)]
+pub enum IpcBytes {
+    /// A small chunk, serialized into the IPC message.
+    Inline(Vec<u8>),
+    /// A large chunk, in a shared memory segment.
+    Shared(IpcSharedMemory),
+}
+
+impl IpcBytes {
+    pub fn from_vec(bytes: Vec<u8>) -> IpcBytes {
+        if bytes.len() >= SHARED_MEMORY_THRESHOLD {
+            IpcBytes::Shared(IpcSharedMemory::from_bytes(&bytes))
+        } else {
+            IpcBytes::Inline(bytes)
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        match self {
+            IpcBytes::Inline(bytes) => bytes,
+            IpcBytes::Shared(bytes) => bytes.to_vec(),
+        }
+    }
+}
+
+impl Deref for IpcBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match *self {
+            IpcBytes::Inline(ref bytes) => bytes,
+            IpcBytes::Shared(ref bytes) => bytes,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub enum FetchResponseMsg {
     // todo: should have fields for transmitted/total bytes
@@ -171,7 +214,7 @@ pub enum FetchResponseMsg {
     ProcessRequestEOF,
     // todo: send more info about the response (or perhaps the entire Response)
     ProcessResponse(Result<FetchMetadata, NetworkError>),
-    ProcessResponseChunk(Vec<u8>),
+    ProcessResponseChunk(IpcBytes),
     ProcessResponseEOF(Result<(), NetworkError>),
 }
 
@@ -219,34 +262,80 @@ pub trait FetchResponseListener {
     fn process_request_body(&mut self);
     fn process_request_eof(&mut self);
     fn process_response(&mut self, metadata: Result<FetchMetadata, NetworkError>);
-    fn process_response_chunk(&mut self, chunk: Vec<u8>);
+    /// A chunk of the response body, left in the shared memory it may have arrived in.
+    fn process_response_chunk(&mut self, chunk: IpcBytes);
     fn process_response_eof(&mut self, response: Result<(), NetworkError>);
 }
 
-impl FetchTaskTarget for IpcSender<FetchResponseMsg> {
+/// The most response body bytes that a fetch sends ahead of its consumer, if the consumer
+/// acknowledges the bytes it took in.
+pub const RESPONSE_BODY_WINDOW: usize = 4 * 1024 * 1024;
+
+/// Sends the progress of a fetch to its consumer, possibly in another process.
+pub struct FetchResponseSender {
+    sender: IpcSender<FetchResponseMsg>,
+    /// The number of body bytes the consumer took in since it was last heard from, if
+    /// it applies backpressure.
+    acknowledgements: Option<IpcReceiver<usize>>,
+    /// The number of body bytes sent that the consumer did not acknowledge yet.
+    unacknowledged: usize,
+}
+
+impl FetchResponseSender {
+    pub fn new(sender: IpcSender<FetchResponseMsg>,
+               acknowledgements: Option<IpcReceiver<usize>>)
+               -> FetchResponseSender {
+        FetchResponseSender {
+            sender: sender,
+            acknowledgements: acknowledgements,
+            unacknowledged: 0,
+        }
+    }
+
+    /// Blocks until the consumer has room for more of the body.
+    fn wait_for_consumer(&mut self) {
+        while self.unacknowledged >= RESPONSE_BODY_WINDOW {
+            let acknowledged = match self.acknowledgements {
+                Some(ref acknowledgements) => acknowledgements.recv(),
+                None => return,
+            };
+            match acknowledged {
+                Ok(bytes) => self.unacknowledged -= cmp::min(bytes, self.unacknowledged),
+                // The consumer went away; there is nobody left to wait for.
+                Err(_) => self.acknowledgements = None,
+            }
+        }
+    }
+}
+
+impl FetchTaskTarget for FetchResponseSender {
     fn process_request_body(&mut self, _: &Request) {
-        let _ = self.send(FetchResponseMsg::ProcessRequestBody);
+        let _ = self.sender.send(FetchResponseMsg::ProcessRequestBody);
     }
 
     fn process_request_eof(&mut self, _: &Request) {
-        let _ = self.send(FetchResponseMsg::ProcessRequestEOF);
+        let _ = self.sender.send(FetchResponseMsg::ProcessRequestEOF);
     }
 
     fn process_response(&mut self, response: &Response) {
-        let _ = self.send(FetchResponseMsg::ProcessResponse(response.metadata()));
+        let _ = self.sender.send(FetchResponseMsg::ProcessResponse(response.metadata()));
     }
 
     fn process_response_chunk(&mut self, chunk: Vec<u8>) {
-        let _ = self.send(FetchResponseMsg::ProcessResponseChunk(chunk));
+        self.wait_for_consumer();
+        if self.acknowledgements.is_some() {
+            self.unacknowledged += chunk.len();
+        }
+        let _ = self.sender.send(FetchResponseMsg::ProcessResponseChunk(IpcBytes::from_vec(chunk)));
     }
 
     fn process_response_eof(&mut self, response: &Response) {
         if response.is_network_error() {
             // todo: finer grained errors
-            let _ = self.send(FetchResponseMsg::ProcessResponseEOF(
-                              Err(NetworkError::Internal("Network error".into()))));
+            let _ = self.sender.send(FetchResponseMsg::ProcessResponseEOF(
+                                     Err(NetworkError::Internal("Network error".into()))));
         } else {
-            let _ = self.send(FetchResponseMsg::ProcessResponseEOF(Ok(())));
+            let _ = self.sender.send(FetchResponseMsg::ProcessResponseEOF(Ok(())));
         }
     }
 }
@@ -275,7 +364,7 @@ pub enum ResponseAction {
     /// Invoke headers_available
     HeadersAvailable(Result<Metadata, NetworkError>),
     /// Invoke data_available
-    DataAvailable(Vec<u8>),
+    DataAvailable(IpcBytes),
     /// Invoke response_complete
     ResponseComplete(Result<(), NetworkError>)
 }
@@ -285,7 +374,7 @@ impl<T: AsyncResponseListener> Action<T> for ResponseAction {
     fn process(self, listener: &mut T) {
         match self {
             ResponseAction::HeadersAvailable(m) => listener.headers_available(m),
-            ResponseAction::DataAvailable(d) => listener.data_available(d),
+            ResponseAction::DataAvailable(d) => listener.data_available(d.into_vec()),
             ResponseAction::ResponseComplete(r) => listener.response_complete(r),
         }
     }
@@ -433,7 +522,12 @@ pub struct WebSocketConnectData {
 pub enum CoreResourceMsg {
     /// Request the data associated with a particular URL
     Load(LoadData, LoadConsumer, Option<IpcSender<ResourceId>>),
-    Fetch(RequestInit, IpcSender<FetchResponseMsg>),
+    /// Fetch a request, sending its progress to the given channel. If a receiver for
+    /// acknowledgements is given, the body is only read from the network as fast as the
+    /// consumer acknowledges the bytes it took in.
+    Fetch(RequestInit, IpcSender<FetchResponseMsg>, Option<IpcReceiver<usize>>),
+    /// Load a URL that a navigation is likely to need soon, and keep the response for it
+    Prefetch(LoadData),
     /// Try to make a websocket connection to a URL.
     WebsocketConnect(WebSocketCommunicate, WebSocketConnectData),
     /// Store a set of cookies for a given originating URL
@@ -526,21 +620,41 @@ impl PendingAsyncLoad {
     }
 
     /// Initiate the network request associated with this pending load, using the provided target.
-    pub fn load_async(mut self, listener: AsyncResponseTarget) {
+    pub fn load_async(self, listener: AsyncResponseTarget) {
+        self.start_load(listener, None)
+    }
+
+    /// Initiate the network request associated with this pending load, using the provided target,
+    /// and return a receiver for the id with which the request can be cancelled.
+    pub fn load_async_cancellable(self, listener: AsyncResponseTarget) -> IpcReceiver<ResourceId> {
+        let (id_sender, id_receiver) = ipc::channel().unwrap();
+        self.start_load(listener, Some(id_sender));
+        id_receiver
+    }
+
+    fn start_load(mut self, listener: AsyncResponseTarget, id_sender: Option<IpcSender<ResourceId>>) {
         self.guard.neuter();
 
         let load_data = LoadData::new(self.context.clone(),
                                       self.url.clone(),
                                       &self);
         let consumer = LoadConsumer::Listener(listener);
-        self.core_resource_thread.send(CoreResourceMsg::Load(load_data, consumer, None)).unwrap();
+        self.core_resource_thread.send(CoreResourceMsg::Load(load_data, consumer, id_sender)).unwrap();
+    }
+
+    /// Initiate this load as a prefetch, whose response is kept for a later navigation.
+    pub fn prefetch(mut self) {
+        self.guard.neuter();
+
+        let load_data = LoadData::new(self.context.clone(), self.url.clone(), &self);
+        self.core_resource_thread.send(CoreResourceMsg::Prefetch(load_data)).unwrap();
     }
 
     /// Initiate the fetch associated with this pending load.
     pub fn fetch_async(mut self, request: RequestInit, fetch_target: IpcSender<FetchResponseMsg>) {
         self.guard.neuter();
 
-        self.core_resource_thread.send(CoreResourceMsg::Fetch(request, fetch_target)).unwrap();
+        self.core_resource_thread.send(CoreResourceMsg::Fetch(request, fetch_target, None)).unwrap();
     }
 }
 
@@ -590,6 +704,29 @@ pub struct Metadata {
 
     /// Referrer Url
     pub referrer: Option<Url>,
+
+    /// Timestamps recorded while fetching the resource
+    pub timing: ResourceFetchTiming,
+}
+
+/// Timestamps recorded while fetching a resource, as used by the Navigation and
+/// Resource Timing APIs. All times are `time::precise_time_ns` values; zero means
+/// the step did not happen (e.g. there was no redirect).
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, HeapSizeOf)]
+pub struct ResourceFetchTiming {
+    pub fetch_start: u64,
+    pub redirect_start: u64,
+    pub redirect_end: u64,
+    pub domain_lookup_start: u64,
+    pub domain_lookup_end: u64,
+    pub connect_start: u64,
+    pub connect_end: u64,
+    pub secure_connection_start: u64,
+    pub request_start: u64,
+    pub response_start: u64,
+    pub response_end: u64,
+    /// The number of redirects followed before the final response.
+    pub redirect_count: u16,
 }
 
 impl Metadata {
@@ -604,6 +741,7 @@ impl Metadata {
             status: Some((200, b"OK".to_vec())),
             https_state: HttpsState::None,
             referrer: None,
+            timing: Default::default(),
         }
     }
 
@@ -657,8 +795,19 @@ pub fn load_whole_resource(context: LoadContext,
                            url: Url,
                            load_origin: &LoadOrigin)
         -> Result<(Metadata, Vec<u8>), NetworkError> {
+    load_whole_resource_with_headers(context, core_resource_thread, url, load_origin, Headers::new())
+}
+
+/// Like `load_whole_resource`, with extra headers sent on the initial request.
+pub fn load_whole_resource_with_headers(context: LoadContext,
+                                        core_resource_thread: &CoreResourceThread,
+                                        url: Url,
+                                        load_origin: &LoadOrigin,
+                                        headers: Headers)
+        -> Result<(Metadata, Vec<u8>), NetworkError> {
     let (start_chan, start_port) = ipc::channel().unwrap();
-    let load_data = LoadData::new(context, url, load_origin);
+    let mut load_data = LoadData::new(context, url, load_origin);
+    load_data.headers = headers;
     core_resource_thread.send(CoreResourceMsg::Load(load_data, LoadConsumer::Channel(start_chan), None)).unwrap();
     let response = start_port.recv().unwrap();
 
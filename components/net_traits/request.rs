@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use ResourceFetchTiming;
 use hyper::header::Headers;
 use hyper::method::Method;
 use msg::constellation_msg::{PipelineId, ReferrerPolicy};
@@ -207,6 +208,8 @@ pub struct Request {
     pub redirect_count: Cell<u32>,
     pub response_tainting: Cell<ResponseTainting>,
     pub done: Cell<bool>,
+    /// Timestamps recorded while fetching, for the Resource Timing API
+    pub timing: Cell<ResourceFetchTiming>,
 }
 
 impl Request {
@@ -245,7 +248,8 @@ impl Request {
             url_list: RefCell::new(vec![url]),
             redirect_count: Cell::new(0),
             response_tainting: Cell::new(ResponseTainting::Basic),
-            done: Cell::new(false)
+            done: Cell::new(false),
+            timing: Cell::new(Default::default()),
         }
     }
 
@@ -4,7 +4,7 @@
 
 //! The [Response](https://fetch.spec.whatwg.org/#responses) object
 //! resulting from a [fetch operation](https://fetch.spec.whatwg.org/#concept-fetch)
-use {FetchMetadata, FilteredMetadata, Metadata, NetworkError};
+use {FetchMetadata, FilteredMetadata, Metadata, NetworkError, ResourceFetchTiming};
 use hyper::header::{AccessControlExposeHeaders, ContentType, Headers};
 use hyper::status::StatusCode;
 use hyper_serde::Serde;
@@ -96,6 +96,8 @@ pub struct Response {
     pub internal_response: Option<Box<Response>>,
     /// whether or not to try to return the internal_response when asked for actual_response
     pub return_internal: Cell<bool>,
+    /// Timestamps recorded while fetching this response
+    pub timing: ResourceFetchTiming,
 }
 
 impl Response {
@@ -112,7 +114,8 @@ impl Response {
             cache_state: CacheState::None,
             https_state: HttpsState::None,
             internal_response: None,
-            return_internal: Cell::new(true)
+            return_internal: Cell::new(true),
+            timing: Default::default(),
         }
     }
 
@@ -129,7 +132,8 @@ impl Response {
             cache_state: CacheState::None,
             https_state: HttpsState::None,
             internal_response: None,
-            return_internal: Cell::new(true)
+            return_internal: Cell::new(true),
+            timing: Default::default(),
         }
     }
 
@@ -235,6 +239,7 @@ impl Response {
             metadata.headers = Some(Serde(response.headers.clone()));
             metadata.status = response.raw_status.clone();
             metadata.https_state = response.https_state;
+            metadata.timing = response.timing;
             metadata
         };
 
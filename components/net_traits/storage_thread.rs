@@ -11,6 +11,13 @@ pub enum StorageType {
     Local
 }
 
+/// The usage and quota of the storage of an origin, in bytes.
+#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+pub struct StorageEstimate {
+    pub usage: u64,
+    pub quota: u64,
+}
+
 /// Request operations on the storage data associated with a particular url
 #[derive(Deserialize, Serialize)]
 pub enum StorageThreadMsg {
@@ -35,6 +42,17 @@ pub enum StorageThreadMsg {
     /// clears the associated storage data by removing all the key/value pairs
     Clear(IpcSender<bool>, Url, StorageType),
 
+    /// gets the number of bytes stored by the origin of the url, across all storage types,
+    /// and the quota that they share
+    Estimate(IpcSender<StorageEstimate>, Url),
+
+    /// marks the data of the origin of the url as persistent, so that it isn't cleared
+    /// under storage pressure
+    Persist(IpcSender<bool>, Url),
+
+    /// gets whether the data of the origin of the url is persistent
+    Persisted(IpcSender<bool>, Url),
+
     /// send a reply when done cleaning up thread resources and then shut it down
     Exit(IpcSender<()>)
 }
@@ -0,0 +1,245 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! A module for writing time profiler traces out in the Trace Event Format, so that they can
+//! be loaded into chrome://tracing.
+
+use profile_traits::time::{ProfilerCategory, TimerMetadata};
+use serde_json;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::sync::atomic::{ATOMIC_BOOL_INIT, AtomicBool, Ordering};
+use time::category_name;
+use util::prefs::PREFS;
+
+/// Whether recording is paused. This is a static so that it can be flipped from a signal
+/// handler.
+static PAUSED: AtomicBool = ATOMIC_BOOL_INIT;
+
+/// Pauses recording if it is running, and resumes it otherwise.
+pub fn toggle_recording() {
+    PAUSED.fetch_xor(true, Ordering::SeqCst);
+}
+
+/// The groups of profiler categories that can be selected for tracing. Each group is shown
+/// as its own track in the trace viewer.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TraceCategory {
+    Script,
+    Style,
+    Layout,
+    DisplayList,
+    Compositor,
+    Painting,
+    Network,
+    Image,
+    Metrics,
+}
+
+impl TraceCategory {
+    pub fn all() -> Vec<TraceCategory> {
+        vec![TraceCategory::Script,
+             TraceCategory::Style,
+             TraceCategory::Layout,
+             TraceCategory::DisplayList,
+             TraceCategory::Compositor,
+             TraceCategory::Painting,
+             TraceCategory::Network,
+             TraceCategory::Image,
+             TraceCategory::Metrics]
+    }
+
+    pub fn from_name(name: &str) -> Option<TraceCategory> {
+        TraceCategory::all().into_iter().find(|category| category.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TraceCategory::Script => "script",
+            TraceCategory::Style => "style",
+            TraceCategory::Layout => "layout",
+            TraceCategory::DisplayList => "display-list",
+            TraceCategory::Compositor => "compositor",
+            TraceCategory::Painting => "painting",
+            TraceCategory::Network => "network",
+            TraceCategory::Image => "image",
+            TraceCategory::Metrics => "metrics",
+        }
+    }
+
+    pub fn of(category: ProfilerCategory) -> TraceCategory {
+        match category {
+            ProfilerCategory::Compositing => TraceCategory::Compositor,
+            ProfilerCategory::LayoutStyleRecalc |
+            ProfilerCategory::LayoutSelectorMatch |
+            ProfilerCategory::LayoutRestyleDamagePropagation => TraceCategory::Style,
+            ProfilerCategory::LayoutDispListBuild |
+            ProfilerCategory::LayoutDisplayListSorting => TraceCategory::DisplayList,
+            ProfilerCategory::LayoutPerform |
+            ProfilerCategory::LayoutTextShaping |
+            ProfilerCategory::LayoutNonIncrementalReset |
+            ProfilerCategory::LayoutTreeBuilder |
+            ProfilerCategory::LayoutDamagePropagate |
+            ProfilerCategory::LayoutGeneratedContent |
+            ProfilerCategory::LayoutFloatPlacementSpeculation |
+            ProfilerCategory::LayoutMain |
+            ProfilerCategory::LayoutStoreOverflow |
+            ProfilerCategory::LayoutParallelWarmup => TraceCategory::Layout,
+            ProfilerCategory::NetHTTPRequestResponse => TraceCategory::Network,
+            ProfilerCategory::PaintingPerTile |
+            ProfilerCategory::PaintingPrepBuff |
+            ProfilerCategory::Painting => TraceCategory::Painting,
+            ProfilerCategory::ImageDecoding |
+            ProfilerCategory::ImageSaving => TraceCategory::Image,
+            ProfilerCategory::ScriptAttachLayout |
+            ProfilerCategory::ScriptConstellationMsg |
+            ProfilerCategory::ScriptDevtoolsMsg |
+            ProfilerCategory::ScriptDocumentEvent |
+            ProfilerCategory::ScriptDomEvent |
+            ProfilerCategory::ScriptEvaluate |
+            ProfilerCategory::ScriptEvent |
+            ProfilerCategory::ScriptFileRead |
+            ProfilerCategory::ScriptImageCacheMsg |
+            ProfilerCategory::ScriptInputEvent |
+            ProfilerCategory::ScriptNetworkEvent |
+            ProfilerCategory::ScriptParseHTML |
+            ProfilerCategory::ScriptPlannedNavigation |
+            ProfilerCategory::ScriptResize |
+            ProfilerCategory::ScriptSetScrollState |
+            ProfilerCategory::ScriptSetViewport |
+            ProfilerCategory::ScriptTimerEvent |
+            ProfilerCategory::ScriptStylesheetLoad |
+            ProfilerCategory::ScriptUpdateReplacedElement |
+            ProfilerCategory::ScriptWebSocketEvent |
+            ProfilerCategory::ScriptWorkerEvent |
+            ProfilerCategory::ScriptServiceWorkerEvent => TraceCategory::Script,
+            ProfilerCategory::TimeToFirstPaint |
+            ProfilerCategory::TimeToFirstContentfulPaint |
+            ProfilerCategory::ApplicationHeartbeat => TraceCategory::Metrics,
+        }
+    }
+
+    /// The trace viewer groups events by thread id, so each category gets one of its own.
+    fn track(&self) -> u32 {
+        *self as u32 + 1
+    }
+}
+
+/// Reads the categories to trace from the `profiler.chrome_trace.categories` pref, a comma
+/// separated list of category names. All categories are traced if the pref is not set.
+pub fn categories_from_prefs() -> HashSet<TraceCategory> {
+    let pref = PREFS.get("profiler.chrome_trace.categories");
+    let names = match pref.as_string() {
+        Some(names) if !names.trim().is_empty() => names,
+        _ => return TraceCategory::all().into_iter().collect(),
+    };
+    names.split(',').filter_map(|name| {
+        let category = TraceCategory::from_name(name.trim());
+        if category.is_none() {
+            warn!("Unknown trace category {:?}", name.trim());
+        }
+        category
+    }).collect()
+}
+
+#[derive(Serialize)]
+struct TraceEvent<'a> {
+    name: &'a str,
+    cat: &'a str,
+    ph: &'a str,
+    /// The start time, in microseconds.
+    ts: f64,
+    /// The duration, in microseconds.
+    dur: f64,
+    pid: u32,
+    tid: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<&'a TimerMetadata>,
+}
+
+#[derive(Serialize)]
+struct ThreadNameEvent<'a> {
+    name: &'a str,
+    ph: &'a str,
+    pid: u32,
+    tid: u32,
+    args: ThreadName<'a>,
+}
+
+#[derive(Serialize)]
+struct ThreadName<'a> {
+    name: &'a str,
+}
+
+/// An RAII class for writing the trace file.
+pub struct ChromeTrace {
+    file: fs::File,
+    categories: HashSet<TraceCategory>,
+    wrote_event: bool,
+}
+
+impl ChromeTrace {
+    /// Create a new ChromeTrace, which only records events out of `categories`, and write
+    /// the start of the trace out to disk.
+    pub fn new(mut file: fs::File, categories: HashSet<TraceCategory>) -> ChromeTrace {
+        writeln!(&mut file, "{{\"traceEvents\":[").unwrap();
+        let mut trace = ChromeTrace {
+            file: file,
+            categories: categories,
+            wrote_event: false,
+        };
+        for category in TraceCategory::all() {
+            if !trace.categories.contains(&category) {
+                continue;
+            }
+            let event = ThreadNameEvent {
+                name: "thread_name",
+                ph: "M",
+                pid: 0,
+                tid: category.track(),
+                args: ThreadName { name: category.name() },
+            };
+            trace.write_separator();
+            serde_json::to_writer(&mut trace.file, &event).unwrap();
+        }
+        trace
+    }
+
+    /// Write one complete event to the trace file, unless its category is not being traced.
+    pub fn write_one(&mut self,
+                     category: &(ProfilerCategory, Option<TimerMetadata>),
+                     time: (u64, u64)) {
+        let trace_category = TraceCategory::of(category.0);
+        if PAUSED.load(Ordering::SeqCst) || !self.categories.contains(&trace_category) {
+            return;
+        }
+        let event = TraceEvent {
+            name: category_name(category.0),
+            cat: trace_category.name(),
+            ph: "X",
+            ts: time.0 as f64 / 1000.,
+            dur: time.1.saturating_sub(time.0) as f64 / 1000.,
+            pid: 0,
+            tid: trace_category.track(),
+            args: category.1.as_ref(),
+        };
+        self.write_separator();
+        serde_json::to_writer(&mut self.file, &event).unwrap();
+    }
+
+    fn write_separator(&mut self) {
+        if self.wrote_event {
+            writeln!(&mut self.file, ",").unwrap();
+        }
+        self.wrote_event = true;
+    }
+}
+
+impl Drop for ChromeTrace {
+    /// Close the event list on destruction, so that the trace is valid JSON.
+    fn drop(&mut self) {
+        writeln!(&mut self.file, "\n]}}").unwrap();
+    }
+}
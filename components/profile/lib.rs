@@ -31,6 +31,7 @@ extern crate task_info;
 extern crate time as std_time;
 extern crate util;
 
+pub mod chrome_trace;
 #[allow(unsafe_code)]
 mod heartbeats;
 #[allow(unsafe_code)]
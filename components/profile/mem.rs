@@ -146,7 +146,8 @@ impl Profiler {
                         ReportKind::ExplicitNonHeapSize |
                         ReportKind::ExplicitUnknownLocationSize =>
                             report.path.insert(0, String::from("explicit")),
-                        ReportKind::NonExplicitSize => {},
+                        ReportKind::NonExplicitSize |
+                        ReportKind::CpuTime => {},
                     }
 
                     // Update the reported fractions of the heaps, when appropriate.
@@ -170,7 +171,11 @@ impl Profiler {
                     }
 
                     // Insert the report.
-                    forest.insert(&report.path, report.size);
+                    let is_time = match report.kind {
+                        ReportKind::CpuTime => true,
+                        _ => false,
+                    };
+                    forest.insert(&report.path, report.size, is_time);
                 }
             }
         }
@@ -178,11 +183,13 @@ impl Profiler {
         // Compute and insert the heap-unclassified values.
         if let Some(jemalloc_heap_allocated_size) = jemalloc_heap_allocated_size {
             forest.insert(&path!["explicit", "jemalloc-heap-unclassified"],
-                          jemalloc_heap_allocated_size - jemalloc_heap_reported_size);
+                          jemalloc_heap_allocated_size - jemalloc_heap_reported_size,
+                          false);
         }
         if let Some(system_heap_allocated_size) = system_heap_allocated_size {
             forest.insert(&path!["explicit", "system-heap-unclassified"],
-                          system_heap_allocated_size - system_heap_reported_size);
+                          system_heap_allocated_size - system_heap_reported_size,
+                          false);
         }
 
         forest.print();
@@ -209,15 +216,19 @@ struct ReportsTree {
 
     /// Child nodes.
     children: Vec<ReportsTree>,
+
+    /// Whether the sizes in this tree are CPU times in microseconds, rather than sizes in bytes.
+    is_time: bool,
 }
 
 impl ReportsTree {
-    fn new(path_seg: String) -> ReportsTree {
+    fn new(path_seg: String, is_time: bool) -> ReportsTree {
         ReportsTree {
             size: 0,
             count: 0,
             path_seg: path_seg,
-            children: vec![]
+            children: vec![],
+            is_time: is_time,
         }
     }
 
@@ -239,7 +250,7 @@ impl ReportsTree {
             let i = match t.find_child(&path_seg) {
                 Some(i) => i,
                 None => {
-                    let new_t = ReportsTree::new(path_seg.clone());
+                    let new_t = ReportsTree::new(path_seg.clone(), t.is_time);
                     t.children.push(new_t);
                     t.children.len() - 1
                 },
@@ -280,10 +291,14 @@ impl ReportsTree {
             indent_str.push_str("   ");
         }
 
-        let mebi = 1024f64 * 1024f64;
+        let (size, unit) = if self.is_time {
+            ((self.size as f64) / 1000f64, "ms ")
+        } else {
+            ((self.size as f64) / (1024f64 * 1024f64), "MiB")
+        };
         let count_str = if self.count > 1 { format!(" [{}]", self.count) } else { "".to_owned() };
-        println!("|{}{:8.2} MiB -- {}{}",
-                 indent_str, (self.size as f64) / mebi, self.path_seg, count_str);
+        println!("|{}{:8.2} {} -- {}{}",
+                 indent_str, size, unit, self.path_seg, count_str);
 
         for child in &self.children {
             child.print(depth + 1);
@@ -305,11 +320,11 @@ impl ReportsForest {
     }
 
     // Insert the path and size into the forest, adding any trees and nodes as necessary.
-    fn insert(&mut self, path: &[String], size: usize) {
+    fn insert(&mut self, path: &[String], size: usize, is_time: bool) {
         let (head, tail) = path.split_first().unwrap();
         // Get the right tree, creating it if necessary.
         if !self.trees.contains_key(head) {
-            self.trees.insert(head.clone(), ReportsTree::new(head.clone()));
+            self.trees.insert(head.clone(), ReportsTree::new(head.clone(), is_time));
         }
         let t = self.trees.get_mut(head).unwrap();
 
@@ -4,6 +4,7 @@
 
 //! Timing functions.
 
+use chrome_trace::{ChromeTrace, categories_from_prefs};
 use heartbeats;
 use ipc_channel::ipc::{self, IpcReceiver};
 use profile_traits::energy::{energy_interval_ms, read_energy_uj};
@@ -104,54 +105,60 @@ impl Formattable for ProfilerCategory {
             ProfilerCategory::LayoutTextShaping => "| + ",
             _ => ""
         };
-        let name = match *self {
-            ProfilerCategory::Compositing => "Compositing",
-            ProfilerCategory::LayoutPerform => "Layout",
-            ProfilerCategory::LayoutStyleRecalc => "Style Recalc",
-            ProfilerCategory::LayoutTextShaping => "Text Shaping",
-            ProfilerCategory::LayoutRestyleDamagePropagation => "Restyle Damage Propagation",
-            ProfilerCategory::LayoutNonIncrementalReset => "Non-incremental reset (temporary)",
-            ProfilerCategory::LayoutSelectorMatch => "Selector Matching",
-            ProfilerCategory::LayoutTreeBuilder => "Tree Building",
-            ProfilerCategory::LayoutDamagePropagate => "Damage Propagation",
-            ProfilerCategory::LayoutDisplayListSorting => "Sorting Display List",
-            ProfilerCategory::LayoutGeneratedContent => "Generated Content Resolution",
-            ProfilerCategory::LayoutFloatPlacementSpeculation => "Float Placement Speculation",
-            ProfilerCategory::LayoutMain => "Primary Layout Pass",
-            ProfilerCategory::LayoutStoreOverflow => "Store Overflow",
-            ProfilerCategory::LayoutParallelWarmup => "Parallel Warmup",
-            ProfilerCategory::LayoutDispListBuild => "Display List Construction",
-            ProfilerCategory::NetHTTPRequestResponse => "Network HTTP Request/Response",
-            ProfilerCategory::PaintingPerTile => "Painting Per Tile",
-            ProfilerCategory::PaintingPrepBuff => "Buffer Prep",
-            ProfilerCategory::Painting => "Painting",
-            ProfilerCategory::ImageDecoding => "Image Decoding",
-            ProfilerCategory::ImageSaving => "Image Saving",
-            ProfilerCategory::ScriptAttachLayout => "Script Attach Layout",
-            ProfilerCategory::ScriptConstellationMsg => "Script Constellation Msg",
-            ProfilerCategory::ScriptDevtoolsMsg => "Script Devtools Msg",
-            ProfilerCategory::ScriptDocumentEvent => "Script Document Event",
-            ProfilerCategory::ScriptDomEvent => "Script Dom Event",
-            ProfilerCategory::ScriptEvaluate => "Script JS Evaluate",
-            ProfilerCategory::ScriptFileRead => "Script File Read",
-            ProfilerCategory::ScriptImageCacheMsg => "Script Image Cache Msg",
-            ProfilerCategory::ScriptInputEvent => "Script Input Event",
-            ProfilerCategory::ScriptNetworkEvent => "Script Network Event",
-            ProfilerCategory::ScriptParseHTML => "Script Parse HTML",
-            ProfilerCategory::ScriptPlannedNavigation => "Script Planned Navigation",
-            ProfilerCategory::ScriptResize => "Script Resize",
-            ProfilerCategory::ScriptEvent => "Script Event",
-            ProfilerCategory::ScriptUpdateReplacedElement => "Script Update Replaced Element",
-            ProfilerCategory::ScriptSetScrollState => "Script Set Scroll State",
-            ProfilerCategory::ScriptSetViewport => "Script Set Viewport",
-            ProfilerCategory::ScriptTimerEvent => "Script Timer Event",
-            ProfilerCategory::ScriptStylesheetLoad => "Script Stylesheet Load",
-            ProfilerCategory::ScriptWebSocketEvent => "Script Web Socket Event",
-            ProfilerCategory::ScriptWorkerEvent => "Script Worker Event",
-            ProfilerCategory::ScriptServiceWorkerEvent => "Script Service Worker Event",
-            ProfilerCategory::ApplicationHeartbeat => "Application Heartbeat",
-        };
-        format!("{}{}", padding, name)
+        format!("{}{}", padding, category_name(*self))
+    }
+}
+
+/// The human-readable name of a profiler category.
+pub fn category_name(category: ProfilerCategory) -> &'static str {
+    match category {
+        ProfilerCategory::Compositing => "Compositing",
+        ProfilerCategory::LayoutPerform => "Layout",
+        ProfilerCategory::LayoutStyleRecalc => "Style Recalc",
+        ProfilerCategory::LayoutTextShaping => "Text Shaping",
+        ProfilerCategory::LayoutRestyleDamagePropagation => "Restyle Damage Propagation",
+        ProfilerCategory::LayoutNonIncrementalReset => "Non-incremental reset (temporary)",
+        ProfilerCategory::LayoutSelectorMatch => "Selector Matching",
+        ProfilerCategory::LayoutTreeBuilder => "Tree Building",
+        ProfilerCategory::LayoutDamagePropagate => "Damage Propagation",
+        ProfilerCategory::LayoutDisplayListSorting => "Sorting Display List",
+        ProfilerCategory::LayoutGeneratedContent => "Generated Content Resolution",
+        ProfilerCategory::LayoutFloatPlacementSpeculation => "Float Placement Speculation",
+        ProfilerCategory::LayoutMain => "Primary Layout Pass",
+        ProfilerCategory::LayoutStoreOverflow => "Store Overflow",
+        ProfilerCategory::LayoutParallelWarmup => "Parallel Warmup",
+        ProfilerCategory::LayoutDispListBuild => "Display List Construction",
+        ProfilerCategory::NetHTTPRequestResponse => "Network HTTP Request/Response",
+        ProfilerCategory::PaintingPerTile => "Painting Per Tile",
+        ProfilerCategory::PaintingPrepBuff => "Buffer Prep",
+        ProfilerCategory::Painting => "Painting",
+        ProfilerCategory::ImageDecoding => "Image Decoding",
+        ProfilerCategory::ImageSaving => "Image Saving",
+        ProfilerCategory::ScriptAttachLayout => "Script Attach Layout",
+        ProfilerCategory::ScriptConstellationMsg => "Script Constellation Msg",
+        ProfilerCategory::ScriptDevtoolsMsg => "Script Devtools Msg",
+        ProfilerCategory::ScriptDocumentEvent => "Script Document Event",
+        ProfilerCategory::ScriptDomEvent => "Script Dom Event",
+        ProfilerCategory::ScriptEvaluate => "Script JS Evaluate",
+        ProfilerCategory::ScriptFileRead => "Script File Read",
+        ProfilerCategory::ScriptImageCacheMsg => "Script Image Cache Msg",
+        ProfilerCategory::ScriptInputEvent => "Script Input Event",
+        ProfilerCategory::ScriptNetworkEvent => "Script Network Event",
+        ProfilerCategory::ScriptParseHTML => "Script Parse HTML",
+        ProfilerCategory::ScriptPlannedNavigation => "Script Planned Navigation",
+        ProfilerCategory::ScriptResize => "Script Resize",
+        ProfilerCategory::ScriptEvent => "Script Event",
+        ProfilerCategory::ScriptUpdateReplacedElement => "Script Update Replaced Element",
+        ProfilerCategory::ScriptSetScrollState => "Script Set Scroll State",
+        ProfilerCategory::ScriptSetViewport => "Script Set Viewport",
+        ProfilerCategory::ScriptTimerEvent => "Script Timer Event",
+        ProfilerCategory::ScriptStylesheetLoad => "Script Stylesheet Load",
+        ProfilerCategory::ScriptWebSocketEvent => "Script Web Socket Event",
+        ProfilerCategory::ScriptWorkerEvent => "Script Worker Event",
+        ProfilerCategory::ScriptServiceWorkerEvent => "Script Service Worker Event",
+        ProfilerCategory::TimeToFirstPaint => "Time To First Paint",
+        ProfilerCategory::TimeToFirstContentfulPaint => "Time To First Contentful Paint",
+        ProfilerCategory::ApplicationHeartbeat => "Application Heartbeat",
     }
 }
 
@@ -164,10 +171,14 @@ pub struct Profiler {
     output: Option<OutputOptions>,
     pub last_msg: Option<ProfilerMsg>,
     trace: Option<TraceDump>,
+    chrome_trace: Option<ChromeTrace>,
 }
 
 impl Profiler {
-    pub fn create(output: &Option<OutputOptions>, file_path: Option<String>) -> ProfilerChan {
+    pub fn create(output: &Option<OutputOptions>,
+                  file_path: Option<String>,
+                  chrome_trace_path: Option<String>)
+                  -> ProfilerChan {
         let (chan, port) = ipc::channel().unwrap();
         match *output {
             Some(ref option) => {
@@ -178,7 +189,8 @@ impl Profiler {
                         .map(path::Path::new)
                         .map(fs::File::create)
                         .map(|res| TraceDump::new(res.unwrap()));
-                    let mut profiler = Profiler::new(port, trace, Some(outputoption));
+                    let chrome_trace = create_chrome_trace(chrome_trace_path);
+                    let mut profiler = Profiler::new(port, trace, chrome_trace, Some(outputoption));
                     profiler.start();
                 });
                 // decide if we need to spawn the timer thread
@@ -200,14 +212,15 @@ impl Profiler {
             },
             None => {
                 // this is when the -p option hasn't been specified
-                if file_path.is_some() {
+                if file_path.is_some() || chrome_trace_path.is_some() {
                     // Spawn the time profiler
                     spawn_named("Time profiler".to_owned(), move || {
                         let trace = file_path.as_ref()
                             .map(path::Path::new)
                             .map(fs::File::create)
                             .map(|res| TraceDump::new(res.unwrap()));
-                        let mut profiler = Profiler::new(port, trace, None);
+                        let chrome_trace = create_chrome_trace(chrome_trace_path);
+                        let mut profiler = Profiler::new(port, trace, chrome_trace, None);
                         profiler.start();
                     });
                 } else {
@@ -275,13 +288,18 @@ impl Profiler {
         profiler_chan
     }
 
-    pub fn new(port: IpcReceiver<ProfilerMsg>, trace: Option<TraceDump>, output: Option<OutputOptions>) -> Profiler {
+    pub fn new(port: IpcReceiver<ProfilerMsg>,
+               trace: Option<TraceDump>,
+               chrome_trace: Option<ChromeTrace>,
+               output: Option<OutputOptions>)
+               -> Profiler {
         Profiler {
             port: port,
             buckets: BTreeMap::new(),
             output: output,
             last_msg: None,
             trace: trace,
+            chrome_trace: chrome_trace,
         }
     }
 
@@ -309,6 +327,9 @@ impl Profiler {
                 if let Some(ref mut trace) = self.trace {
                     trace.write_one(&k, t, e);
                 }
+                if let Some(ref mut chrome_trace) = self.chrome_trace {
+                    chrome_trace.write_one(&k, t);
+                }
                 let ms = (t.1 - t.0) as f64 / 1000000f64;
                 self.find_or_insert(k, ms);
             },
@@ -319,6 +340,8 @@ impl Profiler {
             ProfilerMsg::Exit(chan) => {
                 heartbeats::cleanup();
                 self.print_buckets();
+                // Dropping the trace finishes writing it out.
+                self.chrome_trace = None;
                 let _ = chan.send(());
                 return false;
             },
@@ -404,6 +427,13 @@ impl Profiler {
     }
 }
 
+fn create_chrome_trace(path: Option<String>) -> Option<ChromeTrace> {
+    path.map(|path| {
+        let file = fs::File::create(path::Path::new(&path)).unwrap();
+        ChromeTrace::new(file, categories_from_prefs())
+    })
+}
+
 fn enforce_range<T>(min: T, max: T, value: T) -> T where T: Ord {
     assert!(min <= max);
     match value.cmp(&max) {
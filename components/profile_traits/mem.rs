@@ -93,6 +93,11 @@ pub enum ReportKind {
     /// measurements grouped under "explicit", e.g. by grouping those measurements in a way that's
     /// different to how they are grouped under "explicit".
     NonExplicitSize,
+
+    /// A measurement of CPU time rather than of memory, in microseconds. Reports of this kind
+    /// are printed in their own trees, e.g. the time the script thread spent running tasks for
+    /// each origin.
+    CpuTime,
 }
 
 /// A single memory-related measurement.
@@ -104,7 +109,7 @@ pub struct Report {
     /// The report kind.
     pub kind: ReportKind,
 
-    /// The size, in bytes.
+    /// The size, in bytes, or the time, in microseconds, for `ReportKind::CpuTime` reports.
     pub size: usize,
 }
 
@@ -81,6 +81,8 @@ pub enum ProfilerCategory {
     ScriptWebSocketEvent,
     ScriptWorkerEvent,
     ScriptServiceWorkerEvent,
+    TimeToFirstPaint,
+    TimeToFirstContentfulPaint,
     ApplicationHeartbeat,
 }
 
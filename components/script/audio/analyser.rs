@@ -0,0 +1,149 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The analysis done by `AnalyserNode`s. The rendering thread only records
+//! the most recent input of the node, which the node analyses when script
+//! asks for it.
+
+use audio::BlockInfo;
+use audio::block::Block;
+use audio::node::AudioNodeEngine;
+use std::f32::consts::PI;
+use std::sync::{Arc, Mutex};
+
+/// The largest `fftSize`.
+pub const MAX_FFT_SIZE: usize = 32768;
+
+/// The most recent frames of the input of an analyser, down-mixed to mono.
+pub struct AnalyserInput {
+    /// A ring buffer of `MAX_FFT_SIZE` frames.
+    frames: Vec<f32>,
+    /// The index of the oldest frame.
+    next: usize,
+}
+
+impl AnalyserInput {
+    pub fn new() -> AnalyserInput {
+        AnalyserInput {
+            frames: vec![0.; MAX_FFT_SIZE],
+            next: 0,
+        }
+    }
+
+    fn push(&mut self, samples: &[f32]) {
+        for sample in samples {
+            self.frames[self.next] = *sample;
+            self.next = (self.next + 1) % MAX_FFT_SIZE;
+        }
+    }
+
+    /// Copies the last `output.len()` frames, oldest first.
+    pub fn copy_latest(&self, output: &mut [f32]) {
+        let start = self.next + MAX_FFT_SIZE - output.len();
+        for (i, sample) in output.iter_mut().enumerate() {
+            *sample = self.frames[(start + i) % MAX_FFT_SIZE];
+        }
+    }
+}
+
+/// Passes its input through, recording it for analysis.
+pub struct AnalyserEngine {
+    input: Arc<Mutex<AnalyserInput>>,
+}
+
+impl AnalyserEngine {
+    pub fn new(input: Arc<Mutex<AnalyserInput>>) -> AnalyserEngine {
+        AnalyserEngine {
+            input: input,
+        }
+    }
+}
+
+impl AudioNodeEngine for AnalyserEngine {
+    fn process(&mut self, input: Block, _info: &BlockInfo) -> Block {
+        self.input.lock().unwrap().push(&input.down_mix_to_mono());
+        input
+    }
+}
+
+/// Computes the smoothed magnitude spectrum of the input of an analyser.
+/// https://webaudio.github.io/web-audio-api/#current-frequency-data
+pub struct FrequencyAnalyser {
+    /// The smoothed magnitudes of the previous analysis.
+    magnitudes: Vec<f32>,
+}
+
+impl FrequencyAnalyser {
+    pub fn new() -> FrequencyAnalyser {
+        FrequencyAnalyser {
+            magnitudes: vec![],
+        }
+    }
+
+    /// Analyses the last `fft_size` frames of `input`, and returns the
+    /// magnitude of each frequency bin in decibels.
+    pub fn analyse(&mut self, input: &AnalyserInput, fft_size: usize, smoothing: f32) -> Vec<f32> {
+        let mut real = vec![0.; fft_size];
+        input.copy_latest(&mut real);
+        // Apply a Blackman window.
+        let (a0, a1, a2) = (0.42, 0.5, 0.08);
+        for (n, sample) in real.iter_mut().enumerate() {
+            let x = 2. * PI * n as f32 / fft_size as f32;
+            *sample *= a0 - a1 * x.cos() + a2 * (2. * x).cos();
+        }
+        let mut imaginary = vec![0.; fft_size];
+        fft(&mut real, &mut imaginary);
+
+        let bins = fft_size / 2;
+        if self.magnitudes.len() != bins {
+            self.magnitudes = vec![0.; bins];
+        }
+        for (k, magnitude) in self.magnitudes.iter_mut().enumerate() {
+            let current = (real[k] * real[k] + imaginary[k] * imaginary[k]).sqrt() / fft_size as f32;
+            *magnitude = smoothing * *magnitude + (1. - smoothing) * current;
+        }
+        self.magnitudes.iter().map(|magnitude| 20. * magnitude.log10()).collect()
+    }
+}
+
+/// An in-place radix-2 fast Fourier transform, for power-of-two lengths.
+pub fn fft(real: &mut [f32], imaginary: &mut [f32]) {
+    let n = real.len();
+    debug_assert!(n.is_power_of_two() && imaginary.len() == n);
+
+    // Reorder the input by bit-reversed index.
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imaginary.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2. * PI / len as f32;
+        let mut start = 0;
+        while start < n {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_real = real[b] * cos - imaginary[b] * sin;
+                let t_imaginary = real[b] * sin + imaginary[b] * cos;
+                real[b] = real[a] - t_real;
+                imaginary[b] = imaginary[a] - t_imaginary;
+                real[a] += t_real;
+                imaginary[a] += t_imaginary;
+            }
+            start += len;
+        }
+        len <<= 1;
+    }
+}
@@ -0,0 +1,72 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::FRAMES_PER_BLOCK;
+
+/// One render quantum of audio, with any number of channels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub channels: Vec<Vec<f32>>,
+}
+
+impl Block {
+    pub fn silence(channel_count: usize) -> Block {
+        Block {
+            channels: vec![vec![0.; FRAMES_PER_BLOCK]; channel_count],
+        }
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Adds the samples of `other` to this block, converting them to this
+    /// block's channel count with the speaker mixing rules for mono and stereo
+    /// and the discrete ones otherwise.
+    /// https://webaudio.github.io/web-audio-api/#channel-up-mixing-and-down-mixing
+    pub fn mix(&mut self, other: &Block) {
+        match (other.channel_count(), self.channel_count()) {
+            (1, _) if self.channel_count() <= 2 => {
+                for channel in &mut self.channels {
+                    add(channel, &other.channels[0], 1.);
+                }
+            },
+            (2, 1) => {
+                add(&mut self.channels[0], &other.channels[0], 0.5);
+                add(&mut self.channels[0], &other.channels[1], 0.5);
+            },
+            _ => {
+                for (channel, input) in self.channels.iter_mut().zip(other.channels.iter()) {
+                    add(channel, input, 1.);
+                }
+            },
+        }
+    }
+
+    /// The average of all channels.
+    pub fn down_mix_to_mono(&self) -> Vec<f32> {
+        let mut mono = vec![0.; FRAMES_PER_BLOCK];
+        let scale = 1. / self.channel_count() as f32;
+        for channel in &self.channels {
+            add(&mut mono, channel, scale);
+        }
+        mono
+    }
+
+    /// Interleaves the samples of all channels, as most output devices expect.
+    pub fn interleave(&self, samples: &mut Vec<f32>) {
+        samples.clear();
+        for frame in 0..FRAMES_PER_BLOCK {
+            for channel in &self.channels {
+                samples.push(channel[frame]);
+            }
+        }
+    }
+}
+
+fn add(output: &mut [f32], input: &[f32], scale: f32) {
+    for (output, input) in output.iter_mut().zip(input.iter()) {
+        *output += input * scale;
+    }
+}
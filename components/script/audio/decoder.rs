@@ -0,0 +1,170 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Decoding of audio files for `decodeAudioData()`.
+//!
+//! Only WAVE files with PCM or floating point samples can be decoded so far;
+//! compressed formats need a media backend, which Servo doesn't have yet.
+
+use audio::AudioBufferData;
+use std::mem;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DecodeError {
+    /// The data isn't in a format that can be decoded.
+    UnsupportedFormat,
+    /// The data is truncated or inconsistent.
+    Malformed,
+}
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Decodes a complete audio file, and resamples it to `sample_rate`.
+pub fn decode_audio_data(data: &[u8], sample_rate: f32) -> Result<AudioBufferData, DecodeError> {
+    let decoded = try!(decode_wave(data));
+    Ok(resample(decoded, sample_rate))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, DecodeError> {
+    if offset + 2 > data.len() {
+        return Err(DecodeError::Malformed);
+    }
+    Ok(data[offset] as u16 | (data[offset + 1] as u16) << 8)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, DecodeError> {
+    Ok(try!(read_u16(data, offset)) as u32 | (try!(read_u16(data, offset + 2)) as u32) << 16)
+}
+
+/// The `fmt ` chunk of a WAVE file.
+struct WaveFormat {
+    format: u16,
+    channels: usize,
+    sample_rate: u32,
+    bits_per_sample: usize,
+}
+
+/// http://www-mmsp.ece.mcgill.ca/Documents/AudioFormats/WAVE/WAVE.html
+fn decode_wave(data: &[u8]) -> Result<AudioBufferData, DecodeError> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(DecodeError::UnsupportedFormat);
+    }
+
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let id = &data[offset..offset + 4];
+        let size = try!(read_u32(data, offset + 4)) as usize;
+        let body = offset + 8;
+        if body + size > data.len() {
+            return Err(DecodeError::Malformed);
+        }
+        let chunk = &data[body..body + size];
+        match id {
+            b"fmt " => {
+                let mut tag = try!(read_u16(chunk, 0));
+                if tag == WAVE_FORMAT_EXTENSIBLE {
+                    // The actual format is at the start of the subformat GUID.
+                    tag = try!(read_u16(chunk, 24));
+                }
+                format = Some(WaveFormat {
+                    format: tag,
+                    channels: try!(read_u16(chunk, 2)) as usize,
+                    sample_rate: try!(read_u32(chunk, 4)),
+                    bits_per_sample: try!(read_u16(chunk, 14)) as usize,
+                });
+            },
+            b"data" => {
+                let format = match format {
+                    Some(format) => format,
+                    None => return Err(DecodeError::Malformed),
+                };
+                return decode_samples(&format, chunk);
+            },
+            _ => {},
+        }
+        // Chunks are padded to an even size.
+        offset = body + size + size % 2;
+    }
+    Err(DecodeError::Malformed)
+}
+
+fn decode_samples(format: &WaveFormat, data: &[u8]) -> Result<AudioBufferData, DecodeError> {
+    if format.channels == 0 || format.sample_rate == 0 {
+        return Err(DecodeError::Malformed);
+    }
+    let bytes = format.bits_per_sample / 8;
+    let decode: fn(&[u8]) -> f32 = match (format.format, format.bits_per_sample) {
+        (WAVE_FORMAT_PCM, 8) => decode_u8,
+        (WAVE_FORMAT_PCM, 16) => decode_i16,
+        (WAVE_FORMAT_PCM, 24) => decode_i24,
+        (WAVE_FORMAT_PCM, 32) => decode_i32,
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => decode_f32,
+        _ => return Err(DecodeError::UnsupportedFormat),
+    };
+
+    let frame_size = bytes * format.channels;
+    let frames = data.len() / frame_size;
+    let mut channels = vec![Vec::with_capacity(frames); format.channels];
+    for frame in data.chunks(frame_size).take(frames) {
+        for (channel, sample) in channels.iter_mut().zip(frame.chunks(bytes)) {
+            channel.push(decode(sample));
+        }
+    }
+    Ok(AudioBufferData {
+        channels: channels,
+        sample_rate: format.sample_rate as f32,
+    })
+}
+
+fn decode_u8(sample: &[u8]) -> f32 {
+    (sample[0] as f32 - 128.) / 128.
+}
+
+fn decode_i16(sample: &[u8]) -> f32 {
+    (sample[0] as u16 | (sample[1] as u16) << 8) as i16 as f32 / 32768.
+}
+
+fn decode_i24(sample: &[u8]) -> f32 {
+    // Shift the sample to the top of an i32 to extend its sign.
+    let bits = (sample[0] as u32) << 8 | (sample[1] as u32) << 16 | (sample[2] as u32) << 24;
+    (bits as i32 >> 8) as f32 / 8388608.
+}
+
+fn decode_i32(sample: &[u8]) -> f32 {
+    le_u32(sample) as i32 as f32 / 2147483648.
+}
+
+#[allow(unsafe_code)]
+fn decode_f32(sample: &[u8]) -> f32 {
+    unsafe { mem::transmute::<u32, f32>(le_u32(sample)) }
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24
+}
+
+/// Converts audio to another sample rate, interpolating linearly.
+pub fn resample(data: AudioBufferData, sample_rate: f32) -> AudioBufferData {
+    if data.sample_rate == sample_rate || data.len() == 0 {
+        return data;
+    }
+    let ratio = data.sample_rate as f64 / sample_rate as f64;
+    let len = (data.len() as f64 / ratio).ceil() as usize;
+    let channels = data.channels.iter().map(|channel| {
+        (0..len).map(|frame| {
+            let position = frame as f64 * ratio;
+            let index = position.floor() as usize;
+            let next = (index + 1).min(channel.len() - 1);
+            let fraction = (position - index as f64) as f32;
+            channel[index] + (channel[next] - channel[index]) * fraction
+        }).collect()
+    }).collect();
+    AudioBufferData {
+        channels: channels,
+        sample_rate: sample_rate,
+    }
+}
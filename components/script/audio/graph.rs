@@ -0,0 +1,194 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::{BlockInfo, DESTINATION_NODE_ID, NodeId};
+use audio::block::Block;
+use audio::node::{AudioNodeEngine, AudioNodeInit, NodeMessage};
+use audio::param::{AutomationEvent, ParamType};
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+struct GraphNode {
+    engine: Box<AudioNodeEngine>,
+    /// The nodes connected to the input of this node.
+    inputs: Vec<NodeId>,
+    /// The nodes connected to the parameters of this node.
+    param_inputs: Vec<(ParamType, NodeId)>,
+    /// The last block rendered by this node.
+    output: Block,
+}
+
+/// The engines of the nodes of a context, and the connections between them.
+pub struct AudioGraph {
+    nodes: HashMap<NodeId, GraphNode>,
+    /// The order the nodes are rendered in, so that every node comes after
+    /// the nodes connected to it. It is computed again when the connections
+    /// change.
+    order: Option<Vec<NodeId>>,
+    /// The source nodes that finished playing in the last block.
+    ended: Vec<NodeId>,
+}
+
+impl AudioGraph {
+    pub fn new() -> AudioGraph {
+        let mut graph = AudioGraph {
+            nodes: HashMap::new(),
+            order: None,
+            ended: vec![],
+        };
+        graph.add_node(DESTINATION_NODE_ID, AudioNodeInit::Destination);
+        graph
+    }
+
+    pub fn add_node(&mut self, id: NodeId, init: AudioNodeInit) {
+        self.nodes.insert(id, GraphNode {
+            engine: init.into_engine(),
+            inputs: vec![],
+            param_inputs: vec![],
+            output: Block::silence(1),
+        });
+        self.order = None;
+    }
+
+    pub fn connect(&mut self, from: NodeId, to: NodeId) {
+        if let Some(node) = self.nodes.get_mut(&to) {
+            if !node.inputs.contains(&from) {
+                node.inputs.push(from);
+            }
+        }
+        self.order = None;
+    }
+
+    pub fn connect_param(&mut self, from: NodeId, to: NodeId, param: ParamType) {
+        if let Some(node) = self.nodes.get_mut(&to) {
+            if !node.param_inputs.contains(&(param, from)) {
+                node.param_inputs.push((param, from));
+            }
+        }
+        self.order = None;
+    }
+
+    pub fn disconnect(&mut self, from: NodeId, to: Option<NodeId>) {
+        for (id, node) in &mut self.nodes {
+            if to.map_or(false, |to| to != *id) {
+                continue;
+            }
+            node.inputs.retain(|input| *input != from);
+            let removed: Vec<ParamType> = node.param_inputs.iter()
+                                                           .filter(|&&(_, input)| input == from)
+                                                           .map(|&(param, _)| param)
+                                                           .collect();
+            node.param_inputs.retain(|&(_, input)| input != from);
+            for param in removed {
+                if !node.param_inputs.iter().any(|&(other, _)| other == param) {
+                    if let Some(param) = node.engine.param(param) {
+                        param.set_input(None);
+                    }
+                }
+            }
+        }
+        self.order = None;
+    }
+
+    pub fn set_param(&mut self, id: NodeId, param: ParamType, event: AutomationEvent) {
+        if let Some(param) = self.nodes.get_mut(&id).and_then(|node| node.engine.param(param)) {
+            param.insert_event(event);
+        }
+    }
+
+    pub fn message(&mut self, id: NodeId, message: NodeMessage) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.engine.message(message);
+        }
+    }
+
+    /// Renders a block with every node, and returns the block rendered by the
+    /// destination.
+    pub fn render(&mut self, info: &BlockInfo) -> Block {
+        if self.order.is_none() {
+            self.order = Some(self.compute_order());
+        }
+        let order = self.order.take().unwrap();
+        for id in &order {
+            let (input, param_inputs) = self.mix_inputs(*id);
+            let node = self.nodes.get_mut(id).unwrap();
+            for (param, input) in param_inputs {
+                if let Some(param) = node.engine.param(param) {
+                    param.set_input(Some(input));
+                }
+            }
+            node.output = node.engine.process(input, info);
+            if node.engine.take_ended() {
+                self.ended.push(*id);
+            }
+        }
+        self.order = Some(order);
+        self.nodes[&DESTINATION_NODE_ID].output.clone()
+    }
+
+    pub fn take_ended_sources(&mut self) -> Vec<NodeId> {
+        mem::replace(&mut self.ended, vec![])
+    }
+
+    /// Sums the outputs of the nodes connected to a node: into a block with
+    /// as many channels as the widest of them for its input, and into a single
+    /// channel for each connected parameter.
+    fn mix_inputs(&self, id: NodeId) -> (Block, Vec<(ParamType, Vec<f32>)>) {
+        let node = &self.nodes[&id];
+        let outputs: Vec<&Block> = node.inputs.iter()
+                                              .filter_map(|input| self.nodes.get(input))
+                                              .map(|input| &input.output)
+                                              .collect();
+        let channel_count = outputs.iter().map(|output| output.channel_count()).max().unwrap_or(1);
+        let mut input = Block::silence(channel_count);
+        for output in outputs {
+            input.mix(output);
+        }
+
+        let mut param_inputs: Vec<(ParamType, Vec<f32>)> = vec![];
+        for &(param, from) in &node.param_inputs {
+            let output = match self.nodes.get(&from) {
+                Some(node) => node.output.down_mix_to_mono(),
+                None => continue,
+            };
+            match param_inputs.iter().position(|&(other, _)| other == param) {
+                Some(index) => {
+                    for (mixed, sample) in param_inputs[index].1.iter_mut().zip(output.iter()) {
+                        *mixed += *sample;
+                    }
+                },
+                None => param_inputs.push((param, output)),
+            }
+        }
+        (input, param_inputs)
+    }
+
+    /// Orders the nodes so that each comes after the nodes connected to it. In
+    /// a cycle, the node the cycle was entered from gets the block its input
+    /// rendered before.
+    fn compute_order(&self) -> Vec<NodeId> {
+        fn visit(graph: &AudioGraph, id: NodeId, visited: &mut HashSet<NodeId>, order: &mut Vec<NodeId>) {
+            if !visited.insert(id) {
+                return;
+            }
+            let node = match graph.nodes.get(&id) {
+                Some(node) => node,
+                None => return,
+            };
+            for input in node.inputs.iter().chain(node.param_inputs.iter().map(|&(_, ref input)| input)) {
+                visit(graph, *input, visited, order);
+            }
+            order.push(id);
+        }
+
+        let mut ids: Vec<NodeId> = self.nodes.keys().cloned().collect();
+        ids.sort_by_key(|id| id.0);
+        let mut visited = HashSet::new();
+        let mut order = vec![];
+        for id in ids {
+            visit(self, id, &mut visited, &mut order);
+        }
+        order
+    }
+}
@@ -0,0 +1,227 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The rendering side of the Web Audio API.
+//!
+//! Every `AudioContext` owns an audio rendering thread, which holds the
+//! engines of the context's nodes in an `AudioGraph`. The DOM objects only
+//! keep the ids of their engines, and control them with `AudioRenderMsg`s.
+//! The graph is rendered one block of `FRAMES_PER_BLOCK` frames at a time,
+//! and each block is written to an `AudioSink`, which blocks until the output
+//! device is ready for more.
+
+pub mod analyser;
+pub mod block;
+pub mod decoder;
+pub mod graph;
+pub mod node;
+pub mod param;
+pub mod sink;
+
+use self::graph::AudioGraph;
+use self::node::{AudioNodeInit, NodeMessage};
+use self::param::{AutomationEvent, ParamType};
+use self::sink::AudioSink;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError, channel};
+use util::thread::spawn_named;
+
+/// The number of frames rendered at once, also known as a render quantum.
+/// https://webaudio.github.io/web-audio-api/#render-quantum
+pub const FRAMES_PER_BLOCK: usize = 128;
+
+/// Identifies a node within the graph of its context.
+#[derive(Clone, Copy, Debug, Eq, Hash, HeapSizeOf, PartialEq)]
+pub struct NodeId(pub u32);
+
+/// The id of the `AudioDestinationNode`, which every graph starts with.
+pub const DESTINATION_NODE_ID: NodeId = NodeId(0);
+
+pub enum AudioRenderMsg {
+    CreateNode(NodeId, AudioNodeInit),
+    /// Connects the output of the first node to the input of the second.
+    Connect(NodeId, NodeId),
+    /// Connects the output of a node to a parameter of another.
+    ConnectParam(NodeId, NodeId, ParamType),
+    /// Removes the connections from the output of a node, to the given node
+    /// only if there is one.
+    Disconnect(NodeId, Option<NodeId>),
+    SetParam(NodeId, ParamType, AutomationEvent),
+    NodeMessage(NodeId, NodeMessage),
+    /// Sets the function that the thread notifies its context with, on the
+    /// rendering thread.
+    SetEventHandler(Box<Fn(AudioRenderEvent) + Send>),
+    Resume,
+    Suspend,
+    /// Stops rendering and ends the thread.
+    Close,
+}
+
+/// The contents of an `AudioBuffer`, as acquired by the nodes that play it.
+/// https://webaudio.github.io/web-audio-api/#acquire-the-content
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioBufferData {
+    pub channels: Vec<Vec<f32>>,
+    pub sample_rate: f32,
+}
+
+impl AudioBufferData {
+    /// The number of frames in each channel.
+    pub fn len(&self) -> usize {
+        self.channels.first().map_or(0, |channel| channel.len())
+    }
+}
+
+/// Notifications from the rendering thread to its context.
+#[derive(Clone, Copy, Debug)]
+pub enum AudioRenderEvent {
+    /// A source node finished playing.
+    Ended(NodeId),
+}
+
+/// Information about the block being rendered.
+pub struct BlockInfo {
+    pub sample_rate: f32,
+    /// The context time of the first frame of the block, in seconds.
+    pub time: f64,
+}
+
+impl BlockInfo {
+    /// The context time of a frame of the block.
+    #[inline]
+    pub fn frame_time(&self, frame: usize) -> f64 {
+        self.time + frame as f64 / self.sample_rate as f64
+    }
+}
+
+/// The handle to the rendering thread of a context.
+pub struct AudioRenderThread {
+    sender: Sender<AudioRenderMsg>,
+    /// The number of frames rendered so far, from which the context's current
+    /// time is derived.
+    rendered_frames: Arc<AtomicUsize>,
+    sample_rate: f32,
+}
+
+impl AudioRenderThread {
+    /// Starts rendering a new graph to `sink`.
+    pub fn start(sample_rate: f32, sink: Box<AudioSink>) -> AudioRenderThread {
+        let (sender, receiver) = channel();
+        let rendered_frames = Arc::new(AtomicUsize::new(0));
+        let frames = rendered_frames.clone();
+        spawn_named("AudioRenderThread".to_owned(), move || {
+            AudioRenderer {
+                receiver: receiver,
+                graph: AudioGraph::new(),
+                sink: sink,
+                event_handler: None,
+                rendered_frames: frames,
+                sample_rate: sample_rate,
+                running: true,
+            }.run();
+        });
+        AudioRenderThread {
+            sender: sender,
+            rendered_frames: rendered_frames,
+            sample_rate: sample_rate,
+        }
+    }
+
+    pub fn send(&self, msg: AudioRenderMsg) {
+        // The thread is gone once the context is closed, and messages to it
+        // are then meaningless.
+        let _ = self.sender.send(msg);
+    }
+
+    /// The time of the next block to be rendered, in seconds.
+    pub fn current_time(&self) -> f64 {
+        self.rendered_frames.load(Ordering::Relaxed) as f64 / self.sample_rate as f64
+    }
+}
+
+struct AudioRenderer {
+    receiver: Receiver<AudioRenderMsg>,
+    graph: AudioGraph,
+    sink: Box<AudioSink>,
+    event_handler: Option<Box<Fn(AudioRenderEvent) + Send>>,
+    rendered_frames: Arc<AtomicUsize>,
+    sample_rate: f32,
+    running: bool,
+}
+
+impl AudioRenderer {
+    fn run(&mut self) {
+        if self.sink.start(self.sample_rate, 2).is_err() {
+            warn!("Failed to open the audio output device");
+        }
+        loop {
+            // Apply the changes made since the last block first, and wait
+            // for the context to be resumed while it is suspended.
+            let msg = if self.running {
+                match self.receiver.try_recv() {
+                    Ok(msg) => Some(msg),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => break,
+                }
+            } else {
+                match self.receiver.recv() {
+                    Ok(msg) => Some(msg),
+                    Err(_) => break,
+                }
+            };
+            match msg {
+                Some(AudioRenderMsg::Close) => break,
+                Some(msg) => {
+                    self.handle_msg(msg);
+                    continue;
+                },
+                None => {},
+            }
+
+            let frames = self.rendered_frames.load(Ordering::Relaxed);
+            let info = BlockInfo {
+                sample_rate: self.sample_rate,
+                time: frames as f64 / self.sample_rate as f64,
+            };
+            let block = self.graph.render(&info);
+            for id in self.graph.take_ended_sources() {
+                if let Some(ref event_handler) = self.event_handler {
+                    event_handler(AudioRenderEvent::Ended(id));
+                }
+            }
+            self.sink.push(&block);
+            self.rendered_frames.store(frames + FRAMES_PER_BLOCK, Ordering::Relaxed);
+        }
+        self.sink.stop();
+    }
+
+    fn handle_msg(&mut self, msg: AudioRenderMsg) {
+        match msg {
+            AudioRenderMsg::CreateNode(id, init) => self.graph.add_node(id, init),
+            AudioRenderMsg::Connect(from, to) => self.graph.connect(from, to),
+            AudioRenderMsg::ConnectParam(from, to, param) => self.graph.connect_param(from, to, param),
+            AudioRenderMsg::Disconnect(from, to) => self.graph.disconnect(from, to),
+            AudioRenderMsg::SetParam(id, param, event) => self.graph.set_param(id, param, event),
+            AudioRenderMsg::NodeMessage(id, message) => self.graph.message(id, message),
+            AudioRenderMsg::SetEventHandler(event_handler) => self.event_handler = Some(event_handler),
+            AudioRenderMsg::Resume => {
+                if !self.running {
+                    self.running = true;
+                    if self.sink.start(self.sample_rate, 2).is_err() {
+                        warn!("Failed to open the audio output device");
+                    }
+                }
+            },
+            AudioRenderMsg::Suspend => {
+                if self.running {
+                    self.running = false;
+                    self.sink.stop();
+                }
+            },
+            AudioRenderMsg::Close => unreachable!(),
+        }
+    }
+}
+
@@ -0,0 +1,478 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::{AudioBufferData, BlockInfo, FRAMES_PER_BLOCK};
+use audio::analyser::{AnalyserEngine, AnalyserInput};
+use audio::block::Block;
+use audio::param::{Param, ParamType};
+use std::f32::consts::PI;
+use std::mem;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// The kinds of nodes a graph can hold, with what their engine starts with.
+pub enum AudioNodeInit {
+    Destination,
+    Gain,
+    Oscillator,
+    BufferSource,
+    BiquadFilter,
+    Analyser(Arc<Mutex<AnalyserInput>>),
+}
+
+impl AudioNodeInit {
+    pub fn into_engine(self) -> Box<AudioNodeEngine> {
+        match self {
+            AudioNodeInit::Destination => box DestinationEngine,
+            AudioNodeInit::Gain => box GainEngine { gain: Param::new(1.) },
+            AudioNodeInit::Oscillator => box OscillatorEngine::new(),
+            AudioNodeInit::BufferSource => box BufferSourceEngine::new(),
+            AudioNodeInit::BiquadFilter => box BiquadFilterEngine::new(),
+            AudioNodeInit::Analyser(input) => box AnalyserEngine::new(input),
+        }
+    }
+}
+
+/// https://webaudio.github.io/web-audio-api/#enumdef-oscillatortype
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OscillatorType {
+    Sine,
+    Square,
+    Sawtooth,
+    Triangle,
+}
+
+/// https://webaudio.github.io/web-audio-api/#enumdef-biquadfiltertype
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterType {
+    Lowpass,
+    Highpass,
+    Bandpass,
+    Lowshelf,
+    Highshelf,
+    Peaking,
+    Notch,
+    Allpass,
+}
+
+/// Changes to the state of a node, other than to its parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeMessage {
+    /// Starts a source node at the given time, from the given offset into its
+    /// buffer, for at most the given duration.
+    Start(f64, f64, Option<f64>),
+    Stop(f64),
+    SetOscillatorType(OscillatorType),
+    SetBuffer(Option<Arc<AudioBufferData>>),
+    SetLoop(bool),
+    SetLoopStart(f64),
+    SetLoopEnd(f64),
+    SetFilterType(FilterType),
+}
+
+/// The part of a node that lives on the rendering thread.
+pub trait AudioNodeEngine: Send {
+    /// Renders the next block from the mixed input of the node.
+    fn process(&mut self, input: Block, info: &BlockInfo) -> Block;
+
+    fn param(&mut self, _param: ParamType) -> Option<&mut Param> {
+        None
+    }
+
+    fn message(&mut self, _message: NodeMessage) {}
+
+    /// Whether the node is a source that finished playing since the last time
+    /// this was called.
+    fn take_ended(&mut self) -> bool {
+        false
+    }
+}
+
+/// When a source node plays.
+#[derive(Default)]
+struct Schedule {
+    start: Option<f64>,
+    stop: Option<f64>,
+    finished: bool,
+    /// Whether the node finished playing and the context wasn't told yet.
+    ended: bool,
+}
+
+impl Schedule {
+    fn start(&mut self, when: f64) {
+        if self.start.is_none() {
+            self.start = Some(when);
+        }
+    }
+
+    /// The frames of the block during which the source plays.
+    fn frames(&mut self, info: &BlockInfo) -> Range<usize> {
+        let start = match self.start {
+            Some(start) if !self.finished => start,
+            _ => return 0..0,
+        };
+        let end = match self.stop {
+            Some(stop) if stop < info.frame_time(FRAMES_PER_BLOCK) => {
+                self.finish();
+                frame_index(stop, info)
+            },
+            _ => FRAMES_PER_BLOCK,
+        };
+        frame_index(start, info)..end
+    }
+
+    fn finish(&mut self) {
+        if !self.finished {
+            self.finished = true;
+            self.ended = true;
+        }
+    }
+
+    fn take_ended(&mut self) -> bool {
+        mem::replace(&mut self.ended, false)
+    }
+}
+
+/// The first frame of the block at or after `time`.
+fn frame_index(time: f64, info: &BlockInfo) -> usize {
+    let frame = ((time - info.time) * info.sample_rate as f64).ceil();
+    frame.max(0.).min(FRAMES_PER_BLOCK as f64) as usize
+}
+
+/// Applies a detune, in cents, to a frequency.
+fn detuned(frequency: f32, detune: f32) -> f32 {
+    frequency * 2f32.powf(detune / 1200.)
+}
+
+struct DestinationEngine;
+
+impl AudioNodeEngine for DestinationEngine {
+    fn process(&mut self, input: Block, _info: &BlockInfo) -> Block {
+        let mut output = Block::silence(2);
+        output.mix(&input);
+        output
+    }
+}
+
+struct GainEngine {
+    gain: Param,
+}
+
+impl AudioNodeEngine for GainEngine {
+    fn process(&mut self, mut input: Block, info: &BlockInfo) -> Block {
+        let gain = self.gain.values(info);
+        for channel in &mut input.channels {
+            for (sample, gain) in channel.iter_mut().zip(gain.iter()) {
+                *sample *= *gain;
+            }
+        }
+        input
+    }
+
+    fn param(&mut self, param: ParamType) -> Option<&mut Param> {
+        match param {
+            ParamType::Gain => Some(&mut self.gain),
+            _ => None,
+        }
+    }
+}
+
+struct OscillatorEngine {
+    oscillator_type: OscillatorType,
+    frequency: Param,
+    detune: Param,
+    /// The position within the current period, between 0 and 1.
+    phase: f32,
+    schedule: Schedule,
+}
+
+impl OscillatorEngine {
+    fn new() -> OscillatorEngine {
+        OscillatorEngine {
+            oscillator_type: OscillatorType::Sine,
+            frequency: Param::new(440.),
+            detune: Param::new(0.),
+            phase: 0.,
+            schedule: Default::default(),
+        }
+    }
+}
+
+impl AudioNodeEngine for OscillatorEngine {
+    fn process(&mut self, _input: Block, info: &BlockInfo) -> Block {
+        let frequency = self.frequency.values(info);
+        let detune = self.detune.values(info);
+        let mut output = Block::silence(1);
+        for frame in self.schedule.frames(info) {
+            let phase = self.phase;
+            output.channels[0][frame] = match self.oscillator_type {
+                OscillatorType::Sine => (2. * PI * phase).sin(),
+                OscillatorType::Square => if phase < 0.5 { 1. } else { -1. },
+                OscillatorType::Sawtooth => 2. * ((phase + 0.5) % 1.) - 1.,
+                OscillatorType::Triangle => 1. - 4. * ((phase + 0.25) % 1. - 0.5).abs(),
+            };
+            let step = detuned(frequency[frame], detune[frame]) / info.sample_rate;
+            let phase = phase + step;
+            self.phase = phase - phase.floor();
+        }
+        output
+    }
+
+    fn param(&mut self, param: ParamType) -> Option<&mut Param> {
+        match param {
+            ParamType::Frequency => Some(&mut self.frequency),
+            ParamType::Detune => Some(&mut self.detune),
+            _ => None,
+        }
+    }
+
+    fn message(&mut self, message: NodeMessage) {
+        match message {
+            NodeMessage::Start(when, _, _) => self.schedule.start(when),
+            NodeMessage::Stop(when) => self.schedule.stop = Some(when),
+            NodeMessage::SetOscillatorType(oscillator_type) => self.oscillator_type = oscillator_type,
+            _ => {},
+        }
+    }
+
+    fn take_ended(&mut self) -> bool {
+        self.schedule.take_ended()
+    }
+}
+
+struct BufferSourceEngine {
+    buffer: Option<Arc<AudioBufferData>>,
+    playback_rate: Param,
+    detune: Param,
+    looping: bool,
+    loop_start: f64,
+    loop_end: f64,
+    /// The position of the next frame to play in the buffer, in frames of the
+    /// buffer.
+    position: f64,
+    schedule: Schedule,
+}
+
+impl BufferSourceEngine {
+    fn new() -> BufferSourceEngine {
+        BufferSourceEngine {
+            buffer: None,
+            playback_rate: Param::new(1.),
+            detune: Param::new(0.),
+            looping: false,
+            loop_start: 0.,
+            loop_end: 0.,
+            position: 0.,
+            schedule: Default::default(),
+        }
+    }
+}
+
+impl AudioNodeEngine for BufferSourceEngine {
+    fn process(&mut self, _input: Block, info: &BlockInfo) -> Block {
+        // The rate only changes once per block.
+        let playback_rate = self.playback_rate.block_value(info);
+        let detune = self.detune.block_value(info);
+        let frames = self.schedule.frames(info);
+        let buffer = match self.buffer {
+            Some(ref buffer) if buffer.len() > 0 => buffer.clone(),
+            _ => return Block::silence(1),
+        };
+
+        let len = buffer.len() as f64;
+        let step = detuned(playback_rate, detune) as f64 * buffer.sample_rate as f64 / info.sample_rate as f64;
+        let (loop_start, loop_end) = {
+            let start = (self.loop_start * buffer.sample_rate as f64).max(0.).min(len);
+            let end = self.loop_end * buffer.sample_rate as f64;
+            if end > start && end <= len { (start, end) } else { (start, len) }
+        };
+
+        let mut output = Block::silence(buffer.channels.len());
+        for frame in frames {
+            if self.looping && loop_end > loop_start {
+                while self.position >= loop_end {
+                    self.position -= loop_end - loop_start;
+                }
+            } else if self.position >= len || self.position < 0. {
+                self.schedule.finish();
+                break;
+            }
+            // Interpolate linearly between the two nearest frames.
+            let index = self.position.floor();
+            let next = if index + 1. < len { index + 1. } else if self.looping { loop_start } else { index };
+            let fraction = (self.position - index) as f32;
+            for (output, channel) in output.channels.iter_mut().zip(buffer.channels.iter()) {
+                let (a, b) = (channel[index as usize], channel[next as usize]);
+                output[frame] = a + (b - a) * fraction;
+            }
+            self.position += step;
+        }
+        output
+    }
+
+    fn param(&mut self, param: ParamType) -> Option<&mut Param> {
+        match param {
+            ParamType::PlaybackRate => Some(&mut self.playback_rate),
+            ParamType::Detune => Some(&mut self.detune),
+            _ => None,
+        }
+    }
+
+    fn message(&mut self, message: NodeMessage) {
+        match message {
+            NodeMessage::Start(when, offset, duration) => {
+                let sample_rate = self.buffer.as_ref().map_or(0., |buffer| buffer.sample_rate);
+                self.position = offset * sample_rate as f64;
+                self.schedule.start(when);
+                if let Some(duration) = duration {
+                    let stop = when + duration;
+                    self.schedule.stop = Some(self.schedule.stop.map_or(stop, |other| other.min(stop)));
+                }
+            },
+            NodeMessage::Stop(when) => self.schedule.stop = Some(when),
+            NodeMessage::SetBuffer(buffer) => self.buffer = buffer,
+            NodeMessage::SetLoop(looping) => self.looping = looping,
+            NodeMessage::SetLoopStart(loop_start) => self.loop_start = loop_start,
+            NodeMessage::SetLoopEnd(loop_end) => self.loop_end = loop_end,
+            _ => {},
+        }
+    }
+
+    fn take_ended(&mut self) -> bool {
+        self.schedule.take_ended()
+    }
+}
+
+/// The coefficients of a biquad filter, normalized so that `a0` is 1.
+#[derive(Clone, Copy, Debug, Default)]
+struct BiquadCoefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+/// The last two inputs and outputs of one channel of a filter.
+#[derive(Clone, Copy, Debug, Default)]
+struct BiquadState {
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+struct BiquadFilterEngine {
+    filter_type: FilterType,
+    frequency: Param,
+    detune: Param,
+    q: Param,
+    gain: Param,
+    states: Vec<BiquadState>,
+}
+
+impl BiquadFilterEngine {
+    fn new() -> BiquadFilterEngine {
+        BiquadFilterEngine {
+            filter_type: FilterType::Lowpass,
+            frequency: Param::new(350.),
+            detune: Param::new(0.),
+            q: Param::new(1.),
+            gain: Param::new(0.),
+            states: vec![],
+        }
+    }
+
+    /// https://webaudio.github.io/web-audio-api/#filters-characteristics
+    fn coefficients(&mut self, info: &BlockInfo) -> BiquadCoefficients {
+        let frequency = detuned(self.frequency.block_value(info), self.detune.block_value(info));
+        let q = self.q.block_value(info);
+        let gain = self.gain.block_value(info);
+
+        let nyquist = info.sample_rate / 2.;
+        let w0 = 2. * PI * frequency.max(0.).min(nyquist) / info.sample_rate;
+        let (sin, cos) = (w0.sin(), w0.cos());
+        let a = 10f32.powf(gain / 40.);
+        // The Q of lowpass and highpass filters is in decibels.
+        let alpha_db = sin / (2. * 10f32.powf(q / 20.));
+        let alpha = sin / (2. * q.max(0.0001));
+        let alpha_shelf = sin / 2. * 2f32.sqrt();
+        let sqrt_a = 2. * a.sqrt() * alpha_shelf;
+
+        let (b0, b1, b2, a0, a1, a2) = match self.filter_type {
+            FilterType::Lowpass => {
+                ((1. - cos) / 2., 1. - cos, (1. - cos) / 2., 1. + alpha_db, -2. * cos, 1. - alpha_db)
+            },
+            FilterType::Highpass => {
+                ((1. + cos) / 2., -(1. + cos), (1. + cos) / 2., 1. + alpha_db, -2. * cos, 1. - alpha_db)
+            },
+            FilterType::Bandpass => (alpha, 0., -alpha, 1. + alpha, -2. * cos, 1. - alpha),
+            FilterType::Notch => (1., -2. * cos, 1., 1. + alpha, -2. * cos, 1. - alpha),
+            FilterType::Allpass => (1. - alpha, -2. * cos, 1. + alpha, 1. + alpha, -2. * cos, 1. - alpha),
+            FilterType::Peaking => {
+                (1. + alpha * a, -2. * cos, 1. - alpha * a, 1. + alpha / a, -2. * cos, 1. - alpha / a)
+            },
+            FilterType::Lowshelf => {
+                (a * ((a + 1.) - (a - 1.) * cos + sqrt_a),
+                 2. * a * ((a - 1.) - (a + 1.) * cos),
+                 a * ((a + 1.) - (a - 1.) * cos - sqrt_a),
+                 (a + 1.) + (a - 1.) * cos + sqrt_a,
+                 -2. * ((a - 1.) + (a + 1.) * cos),
+                 (a + 1.) + (a - 1.) * cos - sqrt_a)
+            },
+            FilterType::Highshelf => {
+                (a * ((a + 1.) + (a - 1.) * cos + sqrt_a),
+                 -2. * a * ((a - 1.) + (a + 1.) * cos),
+                 a * ((a + 1.) + (a - 1.) * cos - sqrt_a),
+                 (a + 1.) - (a - 1.) * cos + sqrt_a,
+                 2. * ((a - 1.) - (a + 1.) * cos),
+                 (a + 1.) - (a - 1.) * cos - sqrt_a)
+            },
+        };
+        BiquadCoefficients {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+}
+
+impl AudioNodeEngine for BiquadFilterEngine {
+    fn process(&mut self, mut input: Block, info: &BlockInfo) -> Block {
+        // FIXME: the parameters should be applied at every frame, but the
+        // coefficients are only computed once per block.
+        let c = self.coefficients(info);
+        self.states.resize(input.channel_count(), BiquadState::default());
+        for (channel, state) in input.channels.iter_mut().zip(self.states.iter_mut()) {
+            for sample in channel.iter_mut() {
+                let x = *sample;
+                let y = c.b0 * x + c.b1 * state.x1 + c.b2 * state.x2 - c.a1 * state.y1 - c.a2 * state.y2;
+                state.x2 = state.x1;
+                state.x1 = x;
+                state.y2 = state.y1;
+                state.y1 = y;
+                *sample = y;
+            }
+        }
+        input
+    }
+
+    fn param(&mut self, param: ParamType) -> Option<&mut Param> {
+        match param {
+            ParamType::Frequency => Some(&mut self.frequency),
+            ParamType::Detune => Some(&mut self.detune),
+            ParamType::Q => Some(&mut self.q),
+            ParamType::Gain => Some(&mut self.gain),
+            _ => None,
+        }
+    }
+
+    fn message(&mut self, message: NodeMessage) {
+        if let NodeMessage::SetFilterType(filter_type) = message {
+            self.filter_type = filter_type;
+        }
+    }
+}
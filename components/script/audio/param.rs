@@ -0,0 +1,176 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::{BlockInfo, FRAMES_PER_BLOCK};
+use std::collections::VecDeque;
+
+/// The parameters of the nodes, which need not be unique across node types.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ParamType {
+    Frequency,
+    Detune,
+    Gain,
+    Q,
+    PlaybackRate,
+}
+
+/// A change to the value of a parameter.
+/// https://webaudio.github.io/web-audio-api/#dfn-automation-event
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AutomationEvent {
+    /// Sets the value right away, as the `value` setter does.
+    SetValue(f32),
+    SetValueAtTime(f32, f64),
+    LinearRampToValueAtTime(f32, f64),
+    ExponentialRampToValueAtTime(f32, f64),
+    /// Approaches a target value from the given time, with the given time
+    /// constant.
+    SetTargetAtTime(f32, f64, f64),
+    CancelScheduledValues(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EventKind {
+    SetValue,
+    LinearRamp,
+    ExponentialRamp,
+    SetTarget(f64),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TimelineEvent {
+    kind: EventKind,
+    value: f32,
+    time: f64,
+}
+
+/// The value of a parameter over time, computed one frame at a time.
+/// https://webaudio.github.io/web-audio-api/#computation-of-value
+pub struct Param {
+    /// The value of the parameter at the last frame computed, without the
+    /// input from connected nodes.
+    value: f32,
+    /// The time of the last frame computed.
+    time: f64,
+    /// The time and value of the last event reached, where a ramp to the next
+    /// event starts.
+    ramp_start: (f64, f32),
+    /// The target value and time constant of a `setTargetAtTime()` in progress.
+    target: Option<(f32, f64)>,
+    /// The events that haven't been reached yet, in time order.
+    events: VecDeque<TimelineEvent>,
+    /// The sum of the outputs of the nodes connected to this parameter, for
+    /// the current block.
+    input: Option<Vec<f32>>,
+}
+
+impl Param {
+    pub fn new(value: f32) -> Param {
+        Param {
+            value: value,
+            time: 0.,
+            ramp_start: (0., value),
+            target: None,
+            events: VecDeque::new(),
+            input: None,
+        }
+    }
+
+    pub fn insert_event(&mut self, event: AutomationEvent) {
+        let (kind, value, time) = match event {
+            AutomationEvent::SetValue(value) => {
+                self.value = value;
+                self.target = None;
+                self.ramp_start = (self.time, value);
+                return;
+            },
+            AutomationEvent::CancelScheduledValues(time) => {
+                self.events.retain(|event| event.time < time);
+                return;
+            },
+            AutomationEvent::SetValueAtTime(value, time) => (EventKind::SetValue, value, time),
+            AutomationEvent::LinearRampToValueAtTime(value, time) => (EventKind::LinearRamp, value, time),
+            AutomationEvent::ExponentialRampToValueAtTime(value, time) => {
+                (EventKind::ExponentialRamp, value, time)
+            },
+            AutomationEvent::SetTargetAtTime(value, time, time_constant) => {
+                (EventKind::SetTarget(time_constant), value, time)
+            },
+        };
+        // A ramp without any event before it starts now.
+        if self.events.is_empty() && self.target.is_none() {
+            self.ramp_start = (self.time, self.value);
+        }
+        // Events at the same time are applied in the order they were added.
+        let index = self.events.iter().position(|event| event.time > time).unwrap_or(self.events.len());
+        self.events.insert(index, TimelineEvent {
+            kind: kind,
+            value: value,
+            time: time,
+        });
+    }
+
+    /// Sets the mixed output of the connected nodes for the next block.
+    pub fn set_input(&mut self, input: Option<Vec<f32>>) {
+        self.input = input;
+    }
+
+    /// Computes the value of the parameter at every frame of a block.
+    pub fn values(&mut self, info: &BlockInfo) -> Vec<f32> {
+        (0..FRAMES_PER_BLOCK).map(|frame| self.value_at(frame, info)).collect()
+    }
+
+    /// Computes the value of the parameter at the first frame of a block, for
+    /// nodes that only update their state once per block.
+    pub fn block_value(&mut self, info: &BlockInfo) -> f32 {
+        let value = self.value_at(0, info);
+        // Keep the timeline in step with the rest of the block.
+        self.advance(info.frame_time(FRAMES_PER_BLOCK - 1));
+        value
+    }
+
+    fn value_at(&mut self, frame: usize, info: &BlockInfo) -> f32 {
+        self.advance(info.frame_time(frame));
+        self.value + self.input.as_ref().map_or(0., |input| input[frame])
+    }
+
+    fn advance(&mut self, time: f64) {
+        let last_time = self.time;
+        self.time = time;
+
+        while self.events.front().map_or(false, |event| event.time <= time) {
+            let event = self.events.pop_front().unwrap();
+            self.target = None;
+            match event.kind {
+                EventKind::SetTarget(time_constant) => self.target = Some((event.value, time_constant)),
+                _ => self.value = event.value,
+            }
+            self.ramp_start = (event.time, self.value);
+        }
+
+        if let Some(next) = self.events.front() {
+            let (start_time, start_value) = self.ramp_start;
+            let progress = ((time - start_time) / (next.time - start_time)) as f32;
+            match next.kind {
+                EventKind::LinearRamp => {
+                    self.value = start_value + (next.value - start_value) * progress;
+                    return;
+                },
+                // Exponential ramps between values of different signs, or
+                // from zero, hold the previous value instead.
+                EventKind::ExponentialRamp if start_value * next.value > 0. => {
+                    self.value = start_value * (next.value / start_value).powf(progress);
+                    return;
+                },
+                _ => {},
+            }
+        }
+
+        if let Some((target, time_constant)) = self.target {
+            let elapsed = time - last_time.max(self.ramp_start.0);
+            let decay = if time_constant > 0. { (-elapsed / time_constant).exp() } else { 0. };
+            self.value = target + (self.value - target) * decay as f32;
+        }
+    }
+}
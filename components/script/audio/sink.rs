@@ -0,0 +1,236 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Audio output.
+
+use audio::FRAMES_PER_BLOCK;
+use audio::block::Block;
+use media_platform::audio::AudioOutput;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a sink has to output silence to stop counting as audible, in
+/// seconds, so that short pauses in the sound don't flicker tab indicators.
+const AUDIBLE_HOLD_TIME: f32 = 1.;
+
+/// Where the rendering thread writes the blocks of its destination.
+pub trait AudioSink: Send {
+    /// Opens the output device.
+    fn start(&mut self, sample_rate: f32, channels: usize) -> Result<(), ()>;
+
+    /// Plays a block, blocking until the device is ready for it so that the
+    /// rendering thread keeps pace with the device.
+    fn push(&mut self, block: &Block);
+
+    /// Closes the output device, dropping what it didn't play yet.
+    fn stop(&mut self);
+}
+
+/// The output of the platform, which keeps the pace of a real device even if
+/// none can be opened.
+pub fn default_sink() -> Box<AudioSink> {
+    box DeviceSink::new()
+}
+
+/// Plays to the output device of the platform.
+pub struct DeviceSink {
+    output: Option<AudioOutput>,
+    samples: Vec<f32>,
+    /// Keeps the pace when the device couldn't be opened.
+    fallback: NullSink,
+}
+
+impl DeviceSink {
+    pub fn new() -> DeviceSink {
+        DeviceSink {
+            output: None,
+            samples: vec![],
+            fallback: NullSink::new(),
+        }
+    }
+}
+
+impl AudioSink for DeviceSink {
+    fn start(&mut self, sample_rate: f32, channels: usize) -> Result<(), ()> {
+        self.stop();
+        try!(self.fallback.start(sample_rate, channels));
+        self.output = Some(try!(AudioOutput::open(sample_rate as u32, channels)));
+        Ok(())
+    }
+
+    fn push(&mut self, block: &Block) {
+        let written = match self.output {
+            Some(ref mut output) => {
+                block.interleave(&mut self.samples);
+                output.write(&self.samples)
+            },
+            None => return self.fallback.push(block),
+        };
+        if written.is_err() {
+            self.stop();
+        }
+    }
+
+    fn stop(&mut self) {
+        self.output = None;
+        self.fallback.stop();
+    }
+}
+
+/// Discards its input at the pace of a real device, for when there's no
+/// device to play to.
+pub struct NullSink {
+    block_duration: Duration,
+    next_block: Option<Instant>,
+}
+
+impl NullSink {
+    pub fn new() -> NullSink {
+        NullSink {
+            block_duration: Duration::from_millis(0),
+            next_block: None,
+        }
+    }
+}
+
+impl AudioSink for NullSink {
+    fn start(&mut self, sample_rate: f32, _channels: usize) -> Result<(), ()> {
+        let nanoseconds = FRAMES_PER_BLOCK as f64 / sample_rate as f64 * 1e9;
+        self.block_duration = Duration::new(0, nanoseconds as u32);
+        self.next_block = None;
+        Ok(())
+    }
+
+    fn push(&mut self, _block: &Block) {
+        let now = Instant::now();
+        let next_block = self.next_block.unwrap_or(now);
+        if next_block > now {
+            thread::sleep(next_block - now);
+        }
+        self.next_block = Some(next_block + self.block_duration);
+    }
+
+    fn stop(&mut self) {
+        self.next_block = None;
+    }
+}
+
+/// The audio output of a pipeline, which all the sinks of its media elements
+/// and audio contexts go through: it mutes them along with the top-level
+/// browsing context, and tells whether any of them is making sound.
+pub struct PipelineAudio {
+    muted: AtomicBool,
+    audible: Mutex<AudibleSinks>,
+}
+
+struct AudibleSinks {
+    count: usize,
+    /// Called with whether the pipeline is audible whenever that changes.
+    listener: Box<Fn(bool) + Send>,
+}
+
+impl PipelineAudio {
+    pub fn new(listener: Box<Fn(bool) + Send>) -> Arc<PipelineAudio> {
+        Arc::new(PipelineAudio {
+            muted: AtomicBool::new(false),
+            audible: Mutex::new(AudibleSinks {
+                count: 0,
+                listener: listener,
+            }),
+        })
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted.load(Ordering::Relaxed)
+    }
+
+    /// Mutes or unmutes the sinks of the pipeline. Muted sinks keep the pace
+    /// of the device, and still count as audible.
+    pub fn set_muted(&self, muted: bool) {
+        self.muted.store(muted, Ordering::Relaxed);
+    }
+
+    fn set_sink_audible(&self, audible: bool) {
+        let mut sinks = self.audible.lock().unwrap();
+        if audible {
+            sinks.count += 1;
+            if sinks.count == 1 {
+                (sinks.listener)(true);
+            }
+        } else {
+            sinks.count -= 1;
+            if sinks.count == 0 {
+                (sinks.listener)(false);
+            }
+        }
+    }
+}
+
+/// A sink that plays to another one on behalf of a pipeline.
+pub struct PipelineSink {
+    sink: Box<AudioSink>,
+    audio: Arc<PipelineAudio>,
+    sample_rate: f32,
+    audible: bool,
+    /// The number of silent frames output since the last sound.
+    silent_frames: usize,
+}
+
+impl PipelineSink {
+    pub fn new(sink: Box<AudioSink>, audio: Arc<PipelineAudio>) -> PipelineSink {
+        PipelineSink {
+            sink: sink,
+            audio: audio,
+            sample_rate: 0.,
+            audible: false,
+            silent_frames: 0,
+        }
+    }
+
+    fn set_audible(&mut self, audible: bool) {
+        if self.audible != audible {
+            self.audible = audible;
+            self.audio.set_sink_audible(audible);
+        }
+    }
+}
+
+impl AudioSink for PipelineSink {
+    fn start(&mut self, sample_rate: f32, channels: usize) -> Result<(), ()> {
+        self.sample_rate = sample_rate;
+        self.silent_frames = 0;
+        self.sink.start(sample_rate, channels)
+    }
+
+    fn push(&mut self, block: &Block) {
+        let frames = block.channels.get(0).map_or(0, |channel| channel.len());
+        if block.channels.iter().any(|channel| channel.iter().any(|&sample| sample != 0.)) {
+            self.silent_frames = 0;
+            self.set_audible(true);
+        } else {
+            self.silent_frames += frames;
+            if self.silent_frames as f32 >= AUDIBLE_HOLD_TIME * self.sample_rate {
+                self.set_audible(false);
+            }
+        }
+        if self.audio.is_muted() {
+            self.sink.push(&Block { channels: vec![vec![0.; frames]; block.channel_count()] });
+        } else {
+            self.sink.push(block);
+        }
+    }
+
+    fn stop(&mut self) {
+        self.set_audible(false);
+        self.sink.stop();
+    }
+}
+
+impl Drop for PipelineSink {
+    fn drop(&mut self) {
+        self.set_audible(false);
+    }
+}
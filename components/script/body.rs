@@ -12,18 +12,73 @@ use dom::blob::{Blob, BlobImpl};
 use dom::formdata::FormData;
 use dom::promise::Promise;
 use encoding::all::UTF_8;
-use encoding::types::{DecoderTrap, Encoding};
+use encoding::types::Encoding;
 use js::jsapi::JSContext;
 use js::jsapi::JS_ClearPendingException;
 use js::jsapi::JS_ParseJSON;
 use js::jsapi::Value as JSValue;
 use js::jsval::UndefinedValue;
 use mime::{Mime, TopLevel, SubLevel};
+use net_traits::IpcBytes;
+use std::cmp;
 use std::rc::Rc;
 use std::str;
 use style::refcell::Ref;
 use url::form_urlencoded;
 
+/// The bytes of a body, in the chunks they were received in. Large chunks from the network
+/// stay in the shared memory they arrived in until the body is consumed.
+#[derive(Clone)]
+pub struct BodyChunks(Vec<IpcBytes>);
+
+impl BodyChunks {
+    pub fn new() -> BodyChunks {
+        BodyChunks(vec![])
+    }
+
+    pub fn from_vec(bytes: Vec<u8>) -> BodyChunks {
+        BodyChunks(vec![IpcBytes::Inline(bytes)])
+    }
+
+    pub fn push(&mut self, chunk: IpcBytes) {
+        self.0.push(chunk);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|chunk| chunk.len()).sum()
+    }
+
+    /// The bytes in a single buffer. A body that arrived inline in one chunk is not copied.
+    pub fn into_vec(mut self) -> Vec<u8> {
+        if self.0.len() == 1 {
+            return self.0.pop().unwrap().into_vec();
+        }
+        let mut bytes = Vec::with_capacity(self.len());
+        for chunk in &self.0 {
+            bytes.extend_from_slice(chunk);
+        }
+        bytes
+    }
+
+    /// Decodes the body as UTF-8 chunk by chunk, without gathering the bytes first.
+    /// Malformed sequences are replaced with U+FFFD.
+    pub fn decode_utf8(&self) -> String {
+        let mut decoder = UTF_8.raw_decoder();
+        let mut text = String::with_capacity(self.len());
+        for chunk in &self.0 {
+            let mut input = &chunk[..];
+            while let (_, Some(error)) = decoder.raw_feed(input, &mut text) {
+                text.push('\u{FFFD}');
+                input = &input[cmp::max(error.upto, 0) as usize..];
+            }
+        }
+        if decoder.raw_finish(&mut text).is_some() {
+            text.push('\u{FFFD}');
+        }
+        text
+    }
+}
+
 pub enum BodyType {
     ArrayBuffer,
     Blob,
@@ -77,35 +132,33 @@ pub fn consume_body<T: BodyOperations + Reflectable>(object: &T, body_type: Body
 // https://fetch.spec.whatwg.org/#concept-body-package-data
 #[allow(unsafe_code)]
 fn run_package_data_algorithm<T: BodyOperations + Reflectable>(object: &T,
-                                                               bytes: Option<Vec<u8>>,
+                                                               bytes: Option<BodyChunks>,
                                                                body_type: BodyType,
                                                                mime_type: Ref<Vec<u8>>)
                                                                -> Fallible<FetchedData> {
     let bytes = match bytes {
         Some(b) => b,
-        _ => vec![],
+        _ => BodyChunks::new(),
     };
     let cx = object.global().r().get_cx();
     let mime = &*mime_type;
     match body_type {
         BodyType::Text => run_text_data_algorithm(bytes),
         BodyType::Json => run_json_data_algorithm(cx, bytes),
-        BodyType::Blob => run_blob_data_algorithm(object.global().r(), bytes, mime),
-        BodyType::FormData => run_form_data_algorithm(object.global().r(), bytes, mime),
+        BodyType::Blob => run_blob_data_algorithm(object.global().r(), bytes.into_vec(), mime),
+        BodyType::FormData => run_form_data_algorithm(object.global().r(), bytes.into_vec(), mime),
         _ => Err(Error::Type("Unable to process body type".to_string()))
     }
 }
 
-fn run_text_data_algorithm(bytes: Vec<u8>) -> Fallible<FetchedData> {
-    let text = UTF_8.decode(&bytes, DecoderTrap::Replace).unwrap();
-    Ok(FetchedData::Text(text))
+fn run_text_data_algorithm(bytes: BodyChunks) -> Fallible<FetchedData> {
+    Ok(FetchedData::Text(bytes.decode_utf8()))
 }
 
 #[allow(unsafe_code)]
 fn run_json_data_algorithm(cx: *mut JSContext,
-                           bytes: Vec<u8>) -> Fallible<FetchedData> {
-    let json_text = UTF_8.decode(&bytes, DecoderTrap::Replace).unwrap();
-    let json_text: Vec<u16> = json_text.encode_utf16().collect();
+                           bytes: BodyChunks) -> Fallible<FetchedData> {
+    let json_text: Vec<u16> = bytes.decode_utf8().encode_utf16().collect();
     rooted!(in(cx) let mut rval = UndefinedValue());
     unsafe {
         if !JS_ParseJSON(cx,
@@ -158,7 +211,7 @@ fn run_form_data_algorithm(root: GlobalRef, bytes: Vec<u8>, mime: &[u8]) -> Fall
 
 pub trait BodyOperations {
     fn get_body_used(&self) -> bool;
-    fn take_body(&self) -> Option<Vec<u8>>;
+    fn take_body(&self) -> Option<BodyChunks>;
     fn is_locked(&self) -> bool;
     fn get_mime_type(&self) -> Ref<Vec<u8>>;
 }
@@ -7,10 +7,10 @@
 
 use dom::bindings::js::JS;
 use dom::document::Document;
-use ipc_channel::ipc::IpcSender;
+use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use msg::constellation_msg::{PipelineId, ReferrerPolicy};
 use net_traits::{AsyncResponseTarget, PendingAsyncLoad, LoadContext};
-use net_traits::{FetchResponseMsg, ResourceThreads, IpcSend};
+use net_traits::{CoreResourceMsg, FetchResponseMsg, ResourceId, ResourceThreads, IpcSend};
 use net_traits::request::RequestInit;
 use std::thread;
 use url::Url;
@@ -97,6 +97,10 @@ pub struct DocumentLoader {
     resource_threads: ResourceThreads,
     pipeline: Option<PipelineId>,
     blocking_loads: Vec<LoadType>,
+    /// The unfinished loads started by `load_async`, with receivers for the ids
+    /// of their network requests, with which they can be cancelled.
+    #[ignore_heap_size_of = "Defined in ipc-channel"]
+    cancellable_loads: Vec<(LoadType, IpcReceiver<ResourceId>)>,
     events_inhibited: bool,
 }
 
@@ -114,6 +118,7 @@ impl DocumentLoader {
             resource_threads: resource_threads,
             pipeline: pipeline,
             blocking_loads: initial_loads,
+            cancellable_loads: vec![],
             events_inhibited: false,
         }
     }
@@ -137,7 +142,7 @@ impl DocumentLoader {
                               url,
                               self.pipeline,
                               referrer_policy.or(referrer.get_referrer_policy()),
-                              Some(referrer.url().clone()))
+                              Some(referrer.url()))
     }
 
     /// Create and initiate a new network request.
@@ -146,8 +151,31 @@ impl DocumentLoader {
                       listener: AsyncResponseTarget,
                       referrer: &Document,
                       referrer_policy: Option<ReferrerPolicy>) {
-        let pending = self.prepare_async_load(load, referrer, referrer_policy);
-        pending.load_async(listener)
+        let pending = self.prepare_async_load(load.clone(), referrer, referrer_policy);
+        self.cancellable_loads.push((load, pending.load_async_cancellable(listener)));
+    }
+
+    /// Prefetch a document that a navigation is likely to need. Unlike other loads,
+    /// this doesn't block the load event.
+    pub fn prefetch(&mut self, url: Url, referrer: &Document) {
+        let pending = PendingAsyncLoad::new(LoadContext::Browsing,
+                                            self.resource_threads.sender(),
+                                            url,
+                                            self.pipeline,
+                                            referrer.get_referrer_policy(),
+                                            Some(referrer.url()));
+        pending.prefetch();
+    }
+
+    /// Cancel the network requests started by `load_async` that are still in progress.
+    /// Their listeners are told that the load was cancelled, and finish the loads as usual.
+    /// Fetches can't be cancelled yet, and complete normally.
+    pub fn cancel_all_loads(&mut self) {
+        for (_, id_receiver) in self.cancellable_loads.drain(..) {
+            if let Ok(id) = id_receiver.recv() {
+                let _ = self.resource_threads.send(CoreResourceMsg::Cancel(id));
+            }
+        }
     }
 
     /// Initiate a new fetch.
@@ -165,6 +193,9 @@ impl DocumentLoader {
     pub fn finish_load(&mut self, load: &LoadType) {
         let idx = self.blocking_loads.iter().position(|unfinished| *unfinished == *load);
         self.blocking_loads.remove(idx.expect(&format!("unknown completed load {:?}", load)));
+        if let Some(idx) = self.cancellable_loads.iter().position(|&(ref unfinished, _)| *unfinished == *load) {
+            self.cancellable_loads.remove(idx);
+        }
     }
 
     pub fn is_blocked(&self) -> bool {
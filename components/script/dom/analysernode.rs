@@ -0,0 +1,188 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::NodeId;
+use audio::analyser::{AnalyserInput, FrequencyAnalyser, MAX_FFT_SIZE};
+use audio::node::AudioNodeInit;
+use dom::audiocontext::AudioContext;
+use dom::audionode::AudioNode;
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::AnalyserNodeBinding;
+use dom::bindings::codegen::Bindings::AnalyserNodeBinding::AnalyserNodeMethods;
+use dom::bindings::conversions::{ArrayBufferViewContents, array_buffer_view_data_checked};
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::reflect_dom_object;
+use js::jsapi::{JSContext, JSObject};
+use std::cell::Cell;
+use std::cmp;
+use std::sync::{Arc, Mutex};
+
+/// The smallest `fftSize`.
+const MIN_FFT_SIZE: u32 = 32;
+
+// https://webaudio.github.io/web-audio-api/#analysernode
+#[dom_struct]
+pub struct AnalyserNode {
+    audionode: AudioNode,
+    /// The latest input of the node, as recorded by the rendering thread.
+    #[ignore_heap_size_of = "Defined in the audio module"]
+    input: Arc<Mutex<AnalyserInput>>,
+    #[ignore_heap_size_of = "Defined in the audio module"]
+    analyser: DOMRefCell<FrequencyAnalyser>,
+    fft_size: Cell<u32>,
+    min_decibels: Cell<f64>,
+    max_decibels: Cell<f64>,
+    smoothing_time_constant: Cell<f64>,
+}
+
+impl AnalyserNode {
+    fn new_inherited(context: &AudioContext, node_id: NodeId, input: Arc<Mutex<AnalyserInput>>) -> AnalyserNode {
+        AnalyserNode {
+            audionode: AudioNode::new_inherited(context, node_id, 1, 1),
+            input: input,
+            analyser: DOMRefCell::new(FrequencyAnalyser::new()),
+            fft_size: Cell::new(2048),
+            min_decibels: Cell::new(-100.),
+            max_decibels: Cell::new(-30.),
+            smoothing_time_constant: Cell::new(0.8),
+        }
+    }
+
+    pub fn new(global: GlobalRef, context: &AudioContext) -> Root<AnalyserNode> {
+        let input = Arc::new(Mutex::new(AnalyserInput::new()));
+        let node_id = context.create_node(AudioNodeInit::Analyser(input.clone()));
+        reflect_dom_object(box AnalyserNode::new_inherited(context, node_id, input),
+                           global,
+                           AnalyserNodeBinding::Wrap)
+    }
+
+    /// https://webaudio.github.io/web-audio-api/#current-frequency-data
+    fn frequency_data(&self) -> Vec<f32> {
+        let input = self.input.lock().unwrap();
+        self.analyser.borrow_mut().analyse(&input,
+                                           self.fft_size.get() as usize,
+                                           self.smoothing_time_constant.get() as f32)
+    }
+
+    /// https://webaudio.github.io/web-audio-api/#current-time-domain-data
+    fn time_domain_data(&self) -> Vec<f32> {
+        let mut data = vec![0.; self.fft_size.get() as usize];
+        self.input.lock().unwrap().copy_latest(&mut data);
+        data
+    }
+}
+
+#[allow(unsafe_code)]
+fn array_data<'a, T: ArrayBufferViewContents>(array: *mut JSObject, name: &str) -> Fallible<&'a mut [T]> {
+    unsafe { array_buffer_view_data_checked::<T>(array) }
+        .ok_or(Error::Type(format!("Argument is not a {}", name)))
+}
+
+/// Scales a value from `min..max` to a byte.
+fn to_byte(value: f64, min: f64, max: f64) -> u8 {
+    (255. / (max - min) * (value - min)).floor().max(0.).min(255.) as u8
+}
+
+impl AnalyserNodeMethods for AnalyserNode {
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-getfloatfrequencydata
+    fn GetFloatFrequencyData(&self, _cx: *mut JSContext, array: *mut JSObject) -> ErrorResult {
+        let array = try!(array_data::<f32>(array, "Float32Array"));
+        let data = self.frequency_data();
+        let len = cmp::min(array.len(), data.len());
+        array[..len].copy_from_slice(&data[..len]);
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-getbytefrequencydata
+    fn GetByteFrequencyData(&self, _cx: *mut JSContext, array: *mut JSObject) -> ErrorResult {
+        let array = try!(array_data::<u8>(array, "Uint8Array"));
+        let (min, max) = (self.min_decibels.get(), self.max_decibels.get());
+        for (byte, value) in array.iter_mut().zip(self.frequency_data()) {
+            *byte = to_byte(value as f64, min, max);
+        }
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-getfloattimedomaindata
+    fn GetFloatTimeDomainData(&self, _cx: *mut JSContext, array: *mut JSObject) -> ErrorResult {
+        let array = try!(array_data::<f32>(array, "Float32Array"));
+        let data = self.time_domain_data();
+        let len = cmp::min(array.len(), data.len());
+        array[..len].copy_from_slice(&data[..len]);
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-getbytetimedomaindata
+    fn GetByteTimeDomainData(&self, _cx: *mut JSContext, array: *mut JSObject) -> ErrorResult {
+        let array = try!(array_data::<u8>(array, "Uint8Array"));
+        for (byte, value) in array.iter_mut().zip(self.time_domain_data()) {
+            *byte = to_byte(value as f64, -1., 1.);
+        }
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-fftsize
+    fn FftSize(&self) -> u32 {
+        self.fft_size.get()
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-fftsize
+    fn SetFftSize(&self, value: u32) -> ErrorResult {
+        if !value.is_power_of_two() || value < MIN_FFT_SIZE || value > MAX_FFT_SIZE as u32 {
+            return Err(Error::IndexSize);
+        }
+        self.fft_size.set(value);
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-frequencybincount
+    fn FrequencyBinCount(&self) -> u32 {
+        self.fft_size.get() / 2
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-mindecibels
+    fn MinDecibels(&self) -> Finite<f64> {
+        Finite::wrap(self.min_decibels.get())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-mindecibels
+    fn SetMinDecibels(&self, value: Finite<f64>) -> ErrorResult {
+        if *value >= self.max_decibels.get() {
+            return Err(Error::IndexSize);
+        }
+        self.min_decibels.set(*value);
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-maxdecibels
+    fn MaxDecibels(&self) -> Finite<f64> {
+        Finite::wrap(self.max_decibels.get())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-maxdecibels
+    fn SetMaxDecibels(&self, value: Finite<f64>) -> ErrorResult {
+        if *value <= self.min_decibels.get() {
+            return Err(Error::IndexSize);
+        }
+        self.max_decibels.set(*value);
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-smoothingtimeconstant
+    fn SmoothingTimeConstant(&self) -> Finite<f64> {
+        Finite::wrap(self.smoothing_time_constant.get())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-analysernode-smoothingtimeconstant
+    fn SetSmoothingTimeConstant(&self, value: Finite<f64>) -> ErrorResult {
+        if *value < 0. || *value > 1. {
+            return Err(Error::IndexSize);
+        }
+        self.smoothing_time_constant.set(*value);
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/extensions/ANGLE_instanced_arrays/
+use dom::bindings::codegen::Bindings::ANGLEInstancedArraysBinding;
+use dom::bindings::codegen::Bindings::ANGLEInstancedArraysBinding::ANGLEInstancedArraysMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::webglrenderingcontext::WebGLRenderingContext;
+
+#[dom_struct]
+pub struct ANGLEInstancedArrays {
+    reflector_: Reflector,
+    ctx: JS<WebGLRenderingContext>,
+}
+
+impl ANGLEInstancedArrays {
+    fn new_inherited(ctx: &WebGLRenderingContext) -> ANGLEInstancedArrays {
+        ANGLEInstancedArrays {
+            reflector_: Reflector::new(),
+            ctx: JS::from_ref(ctx),
+        }
+    }
+
+    pub fn new(global: GlobalRef, ctx: &WebGLRenderingContext) -> Root<ANGLEInstancedArrays> {
+        reflect_dom_object(box ANGLEInstancedArrays::new_inherited(ctx),
+                           global,
+                           ANGLEInstancedArraysBinding::Wrap)
+    }
+}
+
+impl ANGLEInstancedArraysMethods for ANGLEInstancedArrays {
+    // https://www.khronos.org/registry/webgl/extensions/ANGLE_instanced_arrays/
+    fn DrawArraysInstancedANGLE(&self, mode: u32, first: i32, count: i32, primcount: i32) {
+        self.ctx.draw_arrays_instanced(mode, first, count, primcount)
+    }
+
+    // https://www.khronos.org/registry/webgl/extensions/ANGLE_instanced_arrays/
+    fn DrawElementsInstancedANGLE(&self, mode: u32, count: i32, type_: u32, offset: i64, primcount: i32) {
+        self.ctx.draw_elements_instanced(mode, count, type_, offset, primcount)
+    }
+
+    // https://www.khronos.org/registry/webgl/extensions/ANGLE_instanced_arrays/
+    fn VertexAttribDivisorANGLE(&self, index: u32, divisor: u32) {
+        self.ctx.vertex_attrib_divisor(index, divisor)
+    }
+}
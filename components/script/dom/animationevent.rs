@@ -0,0 +1,94 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::AnimationEventBinding;
+use dom::bindings::codegen::Bindings::AnimationEventBinding::AnimationEventMethods;
+use dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::Root;
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::event::Event;
+use string_cache::Atom;
+
+// https://drafts.csswg.org/css-animations/#interface-animationevent
+#[dom_struct]
+pub struct AnimationEvent {
+    event: Event,
+    animation_name: DOMString,
+    elapsed_time: Finite<f32>,
+    pseudo_element: DOMString,
+}
+
+impl AnimationEvent {
+    fn new_inherited(animation_name: DOMString,
+                     elapsed_time: Finite<f32>,
+                     pseudo_element: DOMString)
+                     -> AnimationEvent {
+        AnimationEvent {
+            event: Event::new_inherited(),
+            animation_name: animation_name,
+            elapsed_time: elapsed_time,
+            pseudo_element: pseudo_element,
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               type_: Atom,
+               bubbles: bool,
+               cancelable: bool,
+               animation_name: DOMString,
+               elapsed_time: Finite<f32>,
+               pseudo_element: DOMString)
+               -> Root<AnimationEvent> {
+        let ev = reflect_dom_object(box AnimationEvent::new_inherited(animation_name,
+                                                                      elapsed_time,
+                                                                      pseudo_element),
+                                    global,
+                                    AnimationEventBinding::Wrap);
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    pub fn Constructor(global: GlobalRef,
+                       type_: DOMString,
+                       init: &AnimationEventBinding::AnimationEventInit)
+                       -> Fallible<Root<AnimationEvent>> {
+        Ok(AnimationEvent::new(global,
+                               Atom::from(type_),
+                               init.parent.bubbles,
+                               init.parent.cancelable,
+                               init.animationName.clone(),
+                               init.elapsedTime,
+                               init.pseudoElement.clone()))
+    }
+}
+
+impl AnimationEventMethods for AnimationEvent {
+    // https://drafts.csswg.org/css-animations/#dom-animationevent-animationname
+    fn AnimationName(&self) -> DOMString {
+        self.animation_name.clone()
+    }
+
+    // https://drafts.csswg.org/css-animations/#dom-animationevent-elapsedtime
+    fn ElapsedTime(&self) -> Finite<f32> {
+        self.elapsed_time
+    }
+
+    // https://drafts.csswg.org/css-animations/#dom-animationevent-pseudoelement
+    fn PseudoElement(&self) -> DOMString {
+        self.pseudo_element.clone()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
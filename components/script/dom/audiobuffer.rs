@@ -0,0 +1,156 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::AudioBufferData;
+use core::nonzero::NonZero;
+use dom::bindings::codegen::Bindings::AudioBufferBinding;
+use dom::bindings::codegen::Bindings::AudioBufferBinding::AudioBufferMethods;
+use dom::bindings::conversions::{array_buffer_view_data, array_buffer_view_data_checked};
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use js::jsapi::{Heap, JSContext, JSObject, JS_NewFloat32Array};
+use std::cmp;
+use std::sync::Arc;
+
+/// The largest number of channels a buffer can have.
+pub const MAX_CHANNEL_COUNT: u32 = 32;
+
+/// The range of sample rates buffers can have.
+pub const MIN_SAMPLE_RATE: f32 = 3000.;
+pub const MAX_SAMPLE_RATE: f32 = 384000.;
+
+// https://webaudio.github.io/web-audio-api/#audiobuffer
+#[dom_struct]
+pub struct AudioBuffer {
+    reflector_: Reflector,
+    /// A `Float32Array` for each channel. This is never resized, so that the
+    /// heap cells keep their addresses.
+    channels: Vec<Heap<*mut JSObject>>,
+    length: u32,
+    sample_rate: f32,
+}
+
+impl AudioBuffer {
+    /// Creates a buffer holding `data`, or silence if there's none.
+    #[allow(unsafe_code)]
+    pub fn new(global: GlobalRef,
+               number_of_channels: u32,
+               length: u32,
+               sample_rate: f32,
+               data: Option<&AudioBufferData>)
+               -> Root<AudioBuffer> {
+        let buffer = box AudioBuffer {
+            reflector_: Reflector::new(),
+            channels: (0..number_of_channels).map(|_| Heap::default()).collect(),
+            length: length,
+            sample_rate: sample_rate,
+        };
+
+        let cx = global.get_cx();
+        for (index, channel) in buffer.channels.iter().enumerate() {
+            unsafe {
+                let array = JS_NewFloat32Array(cx, length);
+                assert!(!array.is_null());
+                if let Some(data) = data {
+                    array_buffer_view_data::<f32>(array).unwrap().copy_from_slice(&data.channels[index]);
+                }
+                channel.set(array);
+            }
+        }
+
+        reflect_dom_object(buffer, global, AudioBufferBinding::Wrap)
+    }
+
+    /// Copies the samples of the buffer for a node to play.
+    /// https://webaudio.github.io/web-audio-api/#acquire-the-content
+    #[allow(unsafe_code)]
+    pub fn acquire_contents(&self) -> Arc<AudioBufferData> {
+        let channels = self.channels.iter().map(|channel| {
+            unsafe { array_buffer_view_data::<f32>(channel.get()).unwrap().to_vec() }
+        }).collect();
+        Arc::new(AudioBufferData {
+            channels: channels,
+            sample_rate: self.sample_rate,
+        })
+    }
+
+    /// Checks the arguments of `copyFromChannel()` and `copyToChannel()`, and
+    /// returns the samples of the channel from `start` on.
+    #[allow(unsafe_code)]
+    fn channel_data_from(&self, channel: u32, start: u32) -> Fallible<&mut [f32]> {
+        if channel >= self.channels.len() as u32 || start > self.length {
+            return Err(Error::IndexSize);
+        }
+        let data = unsafe { array_buffer_view_data::<f32>(self.channels[channel as usize].get()).unwrap() };
+        Ok(&mut data[start as usize..])
+    }
+}
+
+#[allow(unsafe_code)]
+fn float32_array_data<'a>(array: *mut JSObject) -> Fallible<&'a mut [f32]> {
+    unsafe { array_buffer_view_data_checked::<f32>(array) }
+        .ok_or(Error::Type("Argument is not a Float32Array".to_owned()))
+}
+
+impl AudioBufferMethods for AudioBuffer {
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffer-samplerate
+    fn SampleRate(&self) -> Finite<f32> {
+        Finite::wrap(self.sample_rate)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffer-length
+    fn Length(&self) -> u32 {
+        self.length
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffer-duration
+    fn Duration(&self) -> Finite<f64> {
+        Finite::wrap(self.length as f64 / self.sample_rate as f64)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffer-numberofchannels
+    fn NumberOfChannels(&self) -> u32 {
+        self.channels.len() as u32
+    }
+
+    #[allow(unsafe_code)]
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffer-getchanneldata
+    fn GetChannelData(&self, _cx: *mut JSContext, channel: u32) -> Fallible<NonZero<*mut JSObject>> {
+        match self.channels.get(channel as usize) {
+            Some(channel) => Ok(unsafe { NonZero::new(channel.get()) }),
+            None => Err(Error::IndexSize),
+        }
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffer-copyfromchannel
+    fn CopyFromChannel(&self,
+                       _cx: *mut JSContext,
+                       destination: *mut JSObject,
+                       channel_number: u32,
+                       start_in_channel: u32)
+                       -> ErrorResult {
+        let destination = try!(float32_array_data(destination));
+        let source = try!(self.channel_data_from(channel_number, start_in_channel));
+        let len = cmp::min(destination.len(), source.len());
+        destination[..len].copy_from_slice(&source[..len]);
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffer-copytochannel
+    fn CopyToChannel(&self,
+                     _cx: *mut JSContext,
+                     source: *mut JSObject,
+                     channel_number: u32,
+                     start_in_channel: u32)
+                     -> ErrorResult {
+        let source = try!(float32_array_data(source));
+        let destination = try!(self.channel_data_from(channel_number, start_in_channel));
+        let len = cmp::min(destination.len(), source.len());
+        destination[..len].copy_from_slice(&source[..len]);
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::{AudioRenderMsg, NodeId};
+use audio::node::{AudioNodeInit, NodeMessage};
+use audio::param::ParamType;
+use dom::audiobuffer::AudioBuffer;
+use dom::audiocontext::AudioContext;
+use dom::audionode::AudioNode;
+use dom::audioparam::AudioParam;
+use dom::audioscheduledsourcenode::AudioScheduledSourceNode;
+use dom::bindings::codegen::Bindings::AudioBufferSourceNodeBinding;
+use dom::bindings::codegen::Bindings::AudioBufferSourceNodeBinding::AudioBufferSourceNodeMethods;
+use dom::bindings::error::{Error, ErrorResult};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::oscillatornode::MAX_DETUNE;
+use std::cell::Cell;
+use std::f32;
+
+// https://webaudio.github.io/web-audio-api/#audiobuffersourcenode
+#[dom_struct]
+pub struct AudioBufferSourceNode {
+    source_node: AudioScheduledSourceNode,
+    buffer: MutNullableHeap<JS<AudioBuffer>>,
+    /// https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-buffer-set
+    buffer_set: Cell<bool>,
+    playback_rate: JS<AudioParam>,
+    detune: JS<AudioParam>,
+    loop_enabled: Cell<bool>,
+    loop_start: Cell<f64>,
+    loop_end: Cell<f64>,
+}
+
+impl AudioBufferSourceNode {
+    fn new_inherited(context: &AudioContext,
+                     node_id: NodeId,
+                     playback_rate: &AudioParam,
+                     detune: &AudioParam)
+                     -> AudioBufferSourceNode {
+        AudioBufferSourceNode {
+            source_node: AudioScheduledSourceNode::new_inherited(context, node_id),
+            buffer: Default::default(),
+            buffer_set: Cell::new(false),
+            playback_rate: JS::from_ref(playback_rate),
+            detune: JS::from_ref(detune),
+            loop_enabled: Cell::new(false),
+            loop_start: Cell::new(0.),
+            loop_end: Cell::new(0.),
+        }
+    }
+
+    pub fn new(global: GlobalRef, context: &AudioContext) -> Root<AudioBufferSourceNode> {
+        let node_id = context.create_node(AudioNodeInit::BufferSource);
+        let playback_rate =
+            AudioParam::new(global, context, node_id, ParamType::PlaybackRate, 1., f32::MIN, f32::MAX);
+        let detune = AudioParam::new(global, context, node_id, ParamType::Detune, 0., -MAX_DETUNE, MAX_DETUNE);
+        reflect_dom_object(box AudioBufferSourceNode::new_inherited(context, node_id, &playback_rate, &detune),
+                           global,
+                           AudioBufferSourceNodeBinding::Wrap)
+    }
+
+    fn send(&self, message: NodeMessage) {
+        let node = self.upcast::<AudioNode>();
+        node.context().send(AudioRenderMsg::NodeMessage(node.node_id(), message));
+    }
+
+    /// Hands the current contents of the buffer to the engine.
+    fn acquire_buffer(&self) {
+        let buffer = self.buffer.get().map(|buffer| buffer.acquire_contents());
+        self.send(NodeMessage::SetBuffer(buffer));
+    }
+}
+
+impl AudioBufferSourceNodeMethods for AudioBufferSourceNode {
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-buffer
+    fn GetBuffer(&self) -> Option<Root<AudioBuffer>> {
+        self.buffer.get()
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-buffer
+    fn SetBuffer(&self, buffer: Option<&AudioBuffer>) -> ErrorResult {
+        // Steps 2-3.
+        if buffer.is_some() {
+            if self.buffer_set.get() {
+                return Err(Error::InvalidState);
+            }
+            self.buffer_set.set(true);
+        }
+        // Step 4.
+        self.buffer.set(buffer);
+        self.acquire_buffer();
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-playbackrate
+    fn PlaybackRate(&self) -> Root<AudioParam> {
+        Root::from_ref(&*self.playback_rate)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-detune
+    fn Detune(&self) -> Root<AudioParam> {
+        Root::from_ref(&*self.detune)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-loop
+    fn Loop(&self) -> bool {
+        self.loop_enabled.get()
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-loop
+    fn SetLoop(&self, value: bool) {
+        self.loop_enabled.set(value);
+        self.send(NodeMessage::SetLoop(value));
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-loopstart
+    fn LoopStart(&self) -> Finite<f64> {
+        Finite::wrap(self.loop_start.get())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-loopstart
+    fn SetLoopStart(&self, value: Finite<f64>) {
+        self.loop_start.set(*value);
+        self.send(NodeMessage::SetLoopStart(*value));
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-loopend
+    fn LoopEnd(&self) -> Finite<f64> {
+        Finite::wrap(self.loop_end.get())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-loopend
+    fn SetLoopEnd(&self, value: Finite<f64>) {
+        self.loop_end.set(*value);
+        self.send(NodeMessage::SetLoopEnd(*value));
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiobuffersourcenode-start
+    fn Start(&self, when: Finite<f64>, offset: Finite<f64>, duration: Option<Finite<f64>>) -> ErrorResult {
+        // Step 1.
+        if self.source_node.has_started() {
+            return Err(Error::InvalidState);
+        }
+        // Step 2.
+        if *offset < 0. || duration.map_or(false, |duration| *duration < 0.) {
+            return Err(Error::Range("The offset and duration must not be negative".to_owned()));
+        }
+        // Step 5: the buffer may have been written to since it was set.
+        self.acquire_buffer();
+        self.source_node.start(*when, *offset, duration.map(|duration| *duration))
+    }
+}
@@ -0,0 +1,395 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::{AudioBufferData, AudioRenderEvent, AudioRenderMsg, AudioRenderThread, NodeId};
+use audio::decoder::{DecodeError, decode_audio_data};
+use audio::node::AudioNodeInit;
+use audio::sink::AudioSink;
+use dom::analysernode::AnalyserNode;
+use dom::audiobuffer::{AudioBuffer, MAX_CHANNEL_COUNT, MAX_SAMPLE_RATE, MIN_SAMPLE_RATE};
+use dom::audiobuffersourcenode::AudioBufferSourceNode;
+use dom::audiodestinationnode::AudioDestinationNode;
+use dom::audionode::AudioNode;
+use dom::audioscheduledsourcenode::AudioScheduledSourceNode;
+use dom::biquadfilternode::BiquadFilterNode;
+use dom::bindings::callback::ExceptionHandling;
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::AudioContextBinding;
+use dom::bindings::codegen::Bindings::AudioContextBinding::{AudioContextMethods, AudioContextState};
+use dom::bindings::codegen::Bindings::AudioContextBinding::{DecodeErrorCallback, DecodeSuccessCallback};
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::conversions::array_buffer_or_view_to_vec;
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::refcounted::{Trusted, TrustedPromise};
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::domexception::{DOMErrorName, DOMException};
+use dom::eventtarget::EventTarget;
+use dom::gainnode::GainNode;
+use dom::oscillatornode::OscillatorNode;
+use dom::promise::Promise;
+use js::jsapi::{JSAutoCompartment, JSContext, JSObject};
+use script_thread::Runnable;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use string_cache::Atom;
+use task_source::TaskSource;
+use util::thread::spawn_named;
+
+/// The sample rate contexts render at.
+/// FIXME: this should be the preferred rate of the output device.
+const SAMPLE_RATE: f32 = 44100.;
+
+/// The callbacks of a `decodeAudioData()` call, which stay on the script
+/// thread while the data is decoded.
+#[derive(JSTraceable, HeapSizeOf)]
+struct DecodeCallbacks {
+    #[ignore_heap_size_of = "Rc has unclear ownership semantics"]
+    success: Option<Rc<DecodeSuccessCallback>>,
+    #[ignore_heap_size_of = "Rc has unclear ownership semantics"]
+    error: Option<Rc<DecodeErrorCallback>>,
+}
+
+// https://webaudio.github.io/web-audio-api/#audiocontext
+#[dom_struct]
+pub struct AudioContext {
+    eventtarget: EventTarget,
+    /// The thread rendering the graph of this context. Its event handler
+    /// holds on to the context, so that a context isn't collected while it
+    /// can still make sound, until it is closed.
+    #[ignore_heap_size_of = "Defined in the audio module"]
+    render_thread: AudioRenderThread,
+    sample_rate: f32,
+    destination: MutNullableHeap<JS<AudioDestinationNode>>,
+    /// https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-control-thread-state
+    state: Cell<AudioContextState>,
+    /// The id of the next node, after the destination's.
+    next_node_id: Cell<u32>,
+    /// The source nodes that were started and didn't end yet, which are kept
+    /// alive while they play.
+    playing_sources: DOMRefCell<HashMap<NodeId, JS<AudioScheduledSourceNode>>>,
+    /// The callbacks of the `decodeAudioData()` calls in progress, by id.
+    pending_decodes: DOMRefCell<HashMap<u32, DecodeCallbacks>>,
+    next_decode_id: Cell<u32>,
+}
+
+impl AudioContext {
+    fn new_inherited(sink: Box<AudioSink>) -> AudioContext {
+        AudioContext {
+            eventtarget: EventTarget::new_inherited(),
+            render_thread: AudioRenderThread::start(SAMPLE_RATE, sink),
+            sample_rate: SAMPLE_RATE,
+            destination: Default::default(),
+            state: Cell::new(AudioContextState::Running),
+            next_node_id: Cell::new(1),
+            playing_sources: DOMRefCell::new(HashMap::new()),
+            pending_decodes: DOMRefCell::new(HashMap::new()),
+            next_decode_id: Cell::new(0),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<AudioContext> {
+        let window = global.as_window();
+        let context = reflect_dom_object(box AudioContext::new_inherited(window.audio_sink()),
+                                         global,
+                                         AudioContextBinding::Wrap);
+        let destination = AudioDestinationNode::new(global, &context);
+        context.destination.set(Some(&destination));
+
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let trusted = Trusted::new(&*context);
+        let event_handler = move |event: AudioRenderEvent| {
+            let runnable = box AudioRenderEventRunnable {
+                context: trusted.clone(),
+                event: event,
+            };
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        };
+        context.send(AudioRenderMsg::SetEventHandler(box event_handler));
+        context
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audiocontext-audiocontext
+    pub fn Constructor(global: GlobalRef) -> Fallible<Root<AudioContext>> {
+        Ok(AudioContext::new(global))
+    }
+
+    pub fn send(&self, msg: AudioRenderMsg) {
+        self.render_thread.send(msg);
+    }
+
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Creates the engine of a new node, and returns its id.
+    pub fn create_node(&self, init: AudioNodeInit) -> NodeId {
+        let id = NodeId(self.next_node_id.get());
+        self.next_node_id.set(id.0 + 1);
+        self.send(AudioRenderMsg::CreateNode(id, init));
+        id
+    }
+
+    /// Keeps a started source node alive until it ends.
+    pub fn add_playing_source(&self, source: &AudioScheduledSourceNode) {
+        let id = source.upcast::<AudioNode>().node_id();
+        self.playing_sources.borrow_mut().insert(id, JS::from_ref(source));
+    }
+
+    fn handle_render_event(&self, event: AudioRenderEvent) {
+        match event {
+            AudioRenderEvent::Ended(id) => {
+                let source = self.playing_sources.borrow_mut().remove(&id);
+                if let Some(source) = source {
+                    source.upcast::<EventTarget>().fire_simple_event("ended");
+                }
+            },
+        }
+    }
+
+    /// Sets the state of the context, and queues a task to fire
+    /// `statechange` if it changed.
+    fn set_state(&self, state: AudioContextState) {
+        if self.state.get() == state {
+            return;
+        }
+        self.state.set(state);
+        let global = self.global();
+        let window = global.r().as_window();
+        window.dom_manipulation_task_source().queue_simple_event(self.upcast(), Atom::from("statechange"), window);
+    }
+
+    /// Settles the promise of a `decodeAudioData()` call, after calling its
+    /// callbacks.
+    /// https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-decodeaudiodata
+    #[allow(unrooted_must_root)]
+    fn finish_decode(&self, id: u32, promise: Rc<Promise>, result: Result<AudioBufferData, DecodeError>) {
+        let callbacks = self.pending_decodes.borrow_mut().remove(&id).unwrap();
+        let global = self.global();
+        let cx = global.r().get_cx();
+        match result {
+            Ok(data) => {
+                let buffer = AudioBuffer::new(global.r(),
+                                              data.channels.len() as u32,
+                                              data.len() as u32,
+                                              data.sample_rate,
+                                              Some(&data));
+                if let Some(success) = callbacks.success {
+                    let _ = success.Call__(&buffer, ExceptionHandling::Report);
+                }
+                promise.resolve_native(cx, &buffer);
+            },
+            Err(_) => {
+                if let Some(error) = callbacks.error {
+                    let exception = DOMException::new(global.r(), DOMErrorName::EncodingError);
+                    let _ = error.Call__(&exception, ExceptionHandling::Report);
+                }
+                promise.reject_error(cx, Error::Encoding);
+            },
+        }
+    }
+}
+
+impl AudioContextMethods for AudioContext {
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-destination
+    fn Destination(&self) -> Root<AudioDestinationNode> {
+        self.destination.get().unwrap()
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-samplerate
+    fn SampleRate(&self) -> Finite<f32> {
+        Finite::wrap(self.sample_rate)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-currenttime
+    fn CurrentTime(&self) -> Finite<f64> {
+        Finite::wrap(self.render_thread.current_time())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-state
+    fn State(&self) -> AudioContextState {
+        self.state.get()
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-onstatechange
+    event_handler!(statechange, GetOnstatechange, SetOnstatechange);
+
+    #[allow(unrooted_must_root)]
+    // https://webaudio.github.io/web-audio-api/#dom-audiocontext-resume
+    fn Resume(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let cx = global.r().get_cx();
+        match self.state.get() {
+            AudioContextState::Closed => promise.reject_error(cx, Error::InvalidState),
+            AudioContextState::Suspended => {
+                self.send(AudioRenderMsg::Resume);
+                self.set_state(AudioContextState::Running);
+                promise.resolve_native(cx, &());
+            },
+            AudioContextState::Running => promise.resolve_native(cx, &()),
+        }
+        promise
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://webaudio.github.io/web-audio-api/#dom-audiocontext-suspend
+    fn Suspend(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let cx = global.r().get_cx();
+        match self.state.get() {
+            AudioContextState::Closed => promise.reject_error(cx, Error::InvalidState),
+            AudioContextState::Running => {
+                self.send(AudioRenderMsg::Suspend);
+                self.set_state(AudioContextState::Suspended);
+                promise.resolve_native(cx, &());
+            },
+            AudioContextState::Suspended => promise.resolve_native(cx, &()),
+        }
+        promise
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://webaudio.github.io/web-audio-api/#dom-audiocontext-close
+    fn Close(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let cx = global.r().get_cx();
+        if self.state.get() == AudioContextState::Closed {
+            promise.reject_error(cx, Error::InvalidState);
+            return promise;
+        }
+        // The rendering thread drops its event handler as it ends, which
+        // lets the context be collected.
+        self.send(AudioRenderMsg::Close);
+        self.playing_sources.borrow_mut().clear();
+        self.set_state(AudioContextState::Closed);
+        promise.resolve_native(cx, &());
+        promise
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-createbuffer
+    fn CreateBuffer(&self,
+                    number_of_channels: u32,
+                    length: u32,
+                    sample_rate: Finite<f32>)
+                    -> Fallible<Root<AudioBuffer>> {
+        if number_of_channels == 0 || number_of_channels > MAX_CHANNEL_COUNT || length == 0 ||
+           *sample_rate < MIN_SAMPLE_RATE || *sample_rate > MAX_SAMPLE_RATE {
+            return Err(Error::NotSupported);
+        }
+        Ok(AudioBuffer::new(self.global().r(), number_of_channels, length, *sample_rate, None))
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-decodeaudiodata
+    fn DecodeAudioData(&self,
+                       _cx: *mut JSContext,
+                       audio_data: *mut JSObject,
+                       success_callback: Option<Rc<DecodeSuccessCallback>>,
+                       error_callback: Option<Rc<DecodeErrorCallback>>)
+                       -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let cx = global.r().get_cx();
+        let data = match array_buffer_or_view_to_vec(audio_data) {
+            Some(data) => data,
+            None => {
+                promise.reject_error(cx, Error::Type("Argument is not an ArrayBuffer".to_owned()));
+                return promise;
+            },
+        };
+
+        let id = self.next_decode_id.get();
+        self.next_decode_id.set(id + 1);
+        self.pending_decodes.borrow_mut().insert(id, DecodeCallbacks {
+            success: success_callback,
+            error: error_callback,
+        });
+
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let runnable = box DecodeAudioDataRunnable {
+            context: Trusted::new(self),
+            id: id,
+            promise: TrustedPromise::new(promise.clone()),
+            result: Err(DecodeError::Malformed),
+        };
+        let sample_rate = self.sample_rate;
+        spawn_named("AudioDecoder".to_owned(), move || {
+            let mut runnable = runnable;
+            runnable.result = decode_audio_data(&data, sample_rate);
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        });
+        promise
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-createbuffersource
+    fn CreateBufferSource(&self) -> Root<AudioBufferSourceNode> {
+        AudioBufferSourceNode::new(self.global().r(), self)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-createoscillator
+    fn CreateOscillator(&self) -> Root<OscillatorNode> {
+        OscillatorNode::new(self.global().r(), self)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-creategain
+    fn CreateGain(&self) -> Root<GainNode> {
+        GainNode::new(self.global().r(), self)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-createbiquadfilter
+    fn CreateBiquadFilter(&self) -> Root<BiquadFilterNode> {
+        BiquadFilterNode::new(self.global().r(), self)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-baseaudiocontext-createanalyser
+    fn CreateAnalyser(&self) -> Root<AnalyserNode> {
+        AnalyserNode::new(self.global().r(), self)
+    }
+}
+
+/// Delivers a notification from the rendering thread to its context.
+struct AudioRenderEventRunnable {
+    context: Trusted<AudioContext>,
+    event: AudioRenderEvent,
+}
+
+impl Runnable for AudioRenderEventRunnable {
+    fn name(&self) -> &'static str { "AudioRenderEventRunnable" }
+
+    fn handler(self: Box<Self>) {
+        self.context.root().handle_render_event(self.event);
+    }
+}
+
+/// Delivers the result of decoding the data of a `decodeAudioData()` call.
+struct DecodeAudioDataRunnable {
+    context: Trusted<AudioContext>,
+    id: u32,
+    promise: TrustedPromise,
+    result: Result<AudioBufferData, DecodeError>,
+}
+
+impl Runnable for DecodeAudioDataRunnable {
+    fn name(&self) -> &'static str { "DecodeAudioDataRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let context = this.context.root();
+        let promise = this.promise.root();
+        let global = context.global();
+        let _ac = JSAutoCompartment::new(global.r().get_cx(), promise.reflector().get_jsobject().get());
+        context.finish_decode(this.id, promise, this.result);
+    }
+}
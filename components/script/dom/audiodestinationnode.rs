@@ -0,0 +1,42 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::DESTINATION_NODE_ID;
+use dom::audiocontext::AudioContext;
+use dom::audionode::AudioNode;
+use dom::bindings::codegen::Bindings::AudioDestinationNodeBinding;
+use dom::bindings::codegen::Bindings::AudioDestinationNodeBinding::AudioDestinationNodeMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::reflect_dom_object;
+
+/// The number of channels the rendering thread outputs.
+const MAX_CHANNEL_COUNT: u32 = 2;
+
+// https://webaudio.github.io/web-audio-api/#audiodestinationnode
+#[dom_struct]
+pub struct AudioDestinationNode {
+    audionode: AudioNode,
+}
+
+impl AudioDestinationNode {
+    fn new_inherited(context: &AudioContext) -> AudioDestinationNode {
+        AudioDestinationNode {
+            audionode: AudioNode::new_inherited(context, DESTINATION_NODE_ID, 1, 0),
+        }
+    }
+
+    pub fn new(global: GlobalRef, context: &AudioContext) -> Root<AudioDestinationNode> {
+        reflect_dom_object(box AudioDestinationNode::new_inherited(context),
+                           global,
+                           AudioDestinationNodeBinding::Wrap)
+    }
+}
+
+impl AudioDestinationNodeMethods for AudioDestinationNode {
+    // https://webaudio.github.io/web-audio-api/#dom-audiodestinationnode-maxchannelcount
+    fn MaxChannelCount(&self) -> u32 {
+        MAX_CHANNEL_COUNT
+    }
+}
@@ -0,0 +1,119 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::{AudioRenderMsg, NodeId};
+use dom::audiocontext::AudioContext;
+use dom::audioparam::AudioParam;
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::AudioNodeBinding::AudioNodeMethods;
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::js::{JS, Root};
+use dom::eventtarget::EventTarget;
+
+// https://webaudio.github.io/web-audio-api/#audionode
+#[dom_struct]
+pub struct AudioNode {
+    eventtarget: EventTarget,
+    context: JS<AudioContext>,
+    /// The engine of this node on the rendering thread.
+    node_id: NodeId,
+    number_of_inputs: u32,
+    number_of_outputs: u32,
+    /// The nodes the output of this node is connected to.
+    outputs: DOMRefCell<Vec<JS<AudioNode>>>,
+}
+
+impl AudioNode {
+    pub fn new_inherited(context: &AudioContext,
+                         node_id: NodeId,
+                         number_of_inputs: u32,
+                         number_of_outputs: u32)
+                         -> AudioNode {
+        AudioNode {
+            eventtarget: EventTarget::new_inherited(),
+            context: JS::from_ref(context),
+            node_id: node_id,
+            number_of_inputs: number_of_inputs,
+            number_of_outputs: number_of_outputs,
+            outputs: DOMRefCell::new(vec![]),
+        }
+    }
+
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    pub fn context(&self) -> &AudioContext {
+        &*self.context
+    }
+}
+
+impl AudioNodeMethods for AudioNode {
+    // https://webaudio.github.io/web-audio-api/#dom-audionode-connect
+    fn Connect(&self, destination: &AudioNode, output: u32, input: u32) -> Fallible<Root<AudioNode>> {
+        // Step 1.
+        if &*destination.context as *const AudioContext != &*self.context as *const AudioContext {
+            return Err(Error::InvalidAccess);
+        }
+        // Steps 2-3.
+        if output >= self.number_of_outputs || input >= destination.number_of_inputs {
+            return Err(Error::IndexSize);
+        }
+        let mut outputs = self.outputs.borrow_mut();
+        if !outputs.iter().any(|node| &**node as *const AudioNode == destination as *const AudioNode) {
+            outputs.push(JS::from_ref(destination));
+            self.context.send(AudioRenderMsg::Connect(self.node_id, destination.node_id));
+        }
+        Ok(Root::from_ref(destination))
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audionode-connect-destinationparam-output
+    fn Connect_(&self, destination: &AudioParam, output: u32) -> ErrorResult {
+        // Step 1.
+        if &*destination.context() as *const AudioContext != &*self.context as *const AudioContext {
+            return Err(Error::InvalidAccess);
+        }
+        // Step 2.
+        if output >= self.number_of_outputs {
+            return Err(Error::IndexSize);
+        }
+        self.context.send(AudioRenderMsg::ConnectParam(self.node_id, destination.node(), destination.param()));
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audionode-disconnect
+    fn Disconnect(&self) {
+        self.outputs.borrow_mut().clear();
+        self.context.send(AudioRenderMsg::Disconnect(self.node_id, None));
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audionode-disconnect-destinationnode
+    fn Disconnect_(&self, destination: &AudioNode) -> ErrorResult {
+        let mut outputs = self.outputs.borrow_mut();
+        let position = outputs.iter().position(|node| &**node as *const AudioNode == destination as *const AudioNode);
+        match position {
+            Some(position) => {
+                outputs.remove(position);
+            },
+            None => return Err(Error::InvalidAccess),
+        }
+        self.context.send(AudioRenderMsg::Disconnect(self.node_id, Some(destination.node_id)));
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audionode-context
+    fn Context(&self) -> Root<AudioContext> {
+        Root::from_ref(&*self.context)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audionode-numberofinputs
+    fn NumberOfInputs(&self) -> u32 {
+        self.number_of_inputs
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audionode-numberofoutputs
+    fn NumberOfOutputs(&self) -> u32 {
+        self.number_of_outputs
+    }
+}
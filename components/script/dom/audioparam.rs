@@ -0,0 +1,161 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::{AudioRenderMsg, NodeId};
+use audio::param::{AutomationEvent, ParamType};
+use dom::audiocontext::AudioContext;
+use dom::bindings::codegen::Bindings::AudioParamBinding;
+use dom::bindings::codegen::Bindings::AudioParamBinding::AudioParamMethods;
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use std::cell::Cell;
+
+// https://webaudio.github.io/web-audio-api/#audioparam
+#[dom_struct]
+pub struct AudioParam {
+    reflector_: Reflector,
+    context: JS<AudioContext>,
+    /// The node whose engine holds this parameter.
+    node: NodeId,
+    #[ignore_heap_size_of = "Defined in the audio module"]
+    param: ParamType,
+    /// The value last set through `value`.
+    /// FIXME: this should be the value computed by the rendering thread for
+    /// the last block, which differs once automation events are scheduled.
+    value: Cell<f32>,
+    default_value: f32,
+    min_value: f32,
+    max_value: f32,
+}
+
+impl AudioParam {
+    fn new_inherited(context: &AudioContext,
+                     node: NodeId,
+                     param: ParamType,
+                     default_value: f32,
+                     min_value: f32,
+                     max_value: f32)
+                     -> AudioParam {
+        AudioParam {
+            reflector_: Reflector::new(),
+            context: JS::from_ref(context),
+            node: node,
+            param: param,
+            value: Cell::new(default_value),
+            default_value: default_value,
+            min_value: min_value,
+            max_value: max_value,
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               context: &AudioContext,
+               node: NodeId,
+               param: ParamType,
+               default_value: f32,
+               min_value: f32,
+               max_value: f32)
+               -> Root<AudioParam> {
+        reflect_dom_object(box AudioParam::new_inherited(context, node, param, default_value, min_value, max_value),
+                           global,
+                           AudioParamBinding::Wrap)
+    }
+
+    pub fn node(&self) -> NodeId {
+        self.node
+    }
+
+    pub fn param(&self) -> ParamType {
+        self.param
+    }
+
+    pub fn context(&self) -> Root<AudioContext> {
+        Root::from_ref(&*self.context)
+    }
+
+    fn insert_event(&self, event: AutomationEvent) -> Fallible<Root<AudioParam>> {
+        self.context.send(AudioRenderMsg::SetParam(self.node, self.param, event));
+        Ok(Root::from_ref(self))
+    }
+}
+
+/// Throws a RangeError for negative times.
+fn check_time(time: f64) -> Fallible<()> {
+    if time < 0. {
+        return Err(Error::Range("Times must not be negative".to_owned()));
+    }
+    Ok(())
+}
+
+impl AudioParamMethods for AudioParam {
+    // https://webaudio.github.io/web-audio-api/#dom-audioparam-value
+    fn Value(&self) -> Finite<f32> {
+        Finite::wrap(self.value.get())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioparam-value
+    fn SetValue(&self, value: Finite<f32>) {
+        let value = (*value).max(self.min_value).min(self.max_value);
+        self.value.set(value);
+        self.context.send(AudioRenderMsg::SetParam(self.node, self.param, AutomationEvent::SetValue(value)));
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioparam-defaultvalue
+    fn DefaultValue(&self) -> Finite<f32> {
+        Finite::wrap(self.default_value)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioparam-minvalue
+    fn MinValue(&self) -> Finite<f32> {
+        Finite::wrap(self.min_value)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioparam-maxvalue
+    fn MaxValue(&self) -> Finite<f32> {
+        Finite::wrap(self.max_value)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioparam-setvalueattime
+    fn SetValueAtTime(&self, value: Finite<f32>, start_time: Finite<f64>) -> Fallible<Root<AudioParam>> {
+        try!(check_time(*start_time));
+        self.insert_event(AutomationEvent::SetValueAtTime(*value, *start_time))
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioparam-linearramptovalueattime
+    fn LinearRampToValueAtTime(&self, value: Finite<f32>, end_time: Finite<f64>) -> Fallible<Root<AudioParam>> {
+        try!(check_time(*end_time));
+        self.insert_event(AutomationEvent::LinearRampToValueAtTime(*value, *end_time))
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioparam-exponentialramptovalueattime
+    fn ExponentialRampToValueAtTime(&self, value: Finite<f32>, end_time: Finite<f64>) -> Fallible<Root<AudioParam>> {
+        try!(check_time(*end_time));
+        if *value == 0. {
+            return Err(Error::Range("Exponential ramps can't reach zero".to_owned()));
+        }
+        self.insert_event(AutomationEvent::ExponentialRampToValueAtTime(*value, *end_time))
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioparam-settargetattime
+    fn SetTargetAtTime(&self,
+                       target: Finite<f32>,
+                       start_time: Finite<f64>,
+                       time_constant: Finite<f32>)
+                       -> Fallible<Root<AudioParam>> {
+        try!(check_time(*start_time));
+        if *time_constant < 0. {
+            return Err(Error::Range("The time constant must not be negative".to_owned()));
+        }
+        self.insert_event(AutomationEvent::SetTargetAtTime(*target, *start_time, *time_constant as f64))
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioparam-cancelscheduledvalues
+    fn CancelScheduledValues(&self, cancel_time: Finite<f64>) -> Fallible<Root<AudioParam>> {
+        try!(check_time(*cancel_time));
+        self.insert_event(AutomationEvent::CancelScheduledValues(*cancel_time))
+    }
+}
@@ -0,0 +1,74 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::{AudioRenderMsg, NodeId};
+use audio::node::NodeMessage;
+use dom::audiocontext::AudioContext;
+use dom::audionode::AudioNode;
+use dom::bindings::codegen::Bindings::AudioScheduledSourceNodeBinding::AudioScheduledSourceNodeMethods;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::error::{Error, ErrorResult};
+use dom::bindings::inheritance::Castable;
+use dom::bindings::num::Finite;
+use dom::eventtarget::EventTarget;
+use std::cell::Cell;
+
+// https://webaudio.github.io/web-audio-api/#audioscheduledsourcenode
+#[dom_struct]
+pub struct AudioScheduledSourceNode {
+    audionode: AudioNode,
+    started: Cell<bool>,
+}
+
+impl AudioScheduledSourceNode {
+    pub fn new_inherited(context: &AudioContext, node_id: NodeId) -> AudioScheduledSourceNode {
+        AudioScheduledSourceNode {
+            audionode: AudioNode::new_inherited(context, node_id, 0, 1),
+            started: Cell::new(false),
+        }
+    }
+
+    pub fn has_started(&self) -> bool {
+        self.started.get()
+    }
+
+    /// Starts playing at `when`, from `offset` seconds into the source, for
+    /// at most `duration` seconds.
+    pub fn start(&self, when: f64, offset: f64, duration: Option<f64>) -> ErrorResult {
+        let node = self.upcast::<AudioNode>();
+        if self.started.get() {
+            return Err(Error::InvalidState);
+        }
+        if when < 0. {
+            return Err(Error::Range("The start time must not be negative".to_owned()));
+        }
+        self.started.set(true);
+        node.context().add_playing_source(self);
+        node.context().send(AudioRenderMsg::NodeMessage(node.node_id(), NodeMessage::Start(when, offset, duration)));
+        Ok(())
+    }
+}
+
+impl AudioScheduledSourceNodeMethods for AudioScheduledSourceNode {
+    // https://webaudio.github.io/web-audio-api/#dom-audioscheduledsourcenode-start
+    fn Start(&self, when: Finite<f64>) -> ErrorResult {
+        self.start(*when, 0., None)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioscheduledsourcenode-stop
+    fn Stop(&self, when: Finite<f64>) -> ErrorResult {
+        if !self.started.get() {
+            return Err(Error::InvalidState);
+        }
+        if *when < 0. {
+            return Err(Error::Range("The stop time must not be negative".to_owned()));
+        }
+        let node = self.upcast::<AudioNode>();
+        node.context().send(AudioRenderMsg::NodeMessage(node.node_id(), NodeMessage::Stop(*when)));
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-audioscheduledsourcenode-onended
+    event_handler!(ended, GetOnended, SetOnended);
+}
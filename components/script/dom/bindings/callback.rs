@@ -15,6 +15,7 @@ use js::jsapi::JSAutoCompartment;
 use js::jsapi::JS_GetProperty;
 use js::jsval::{JSVal, UndefinedValue};
 use js::rust::RootedGuard;
+use script_thread::ScriptThread;
 use std::default::Default;
 use std::ffi::CString;
 use std::ptr;
@@ -167,6 +168,7 @@ impl<'a> CallSetup<'a> {
                                      -> CallSetup<'a> {
         let global = unsafe { global_root_from_object(callback.callback()) };
         let cx = global.r().get_cx();
+        ScriptThread::note_script_entered(|| global.r().get_url());
 
         exception_compartment.ptr = unsafe {
             GetGlobalForObjectCrossCompartment(callback.callback())
@@ -1418,7 +1418,7 @@ def getRetvalDeclarationForType(returnType, descriptorProvider):
                     returnType)
 
 
-def MemberCondition(pref, func):
+def MemberCondition(pref, func, exposed):
     """
     A string representing the condition for a member to actually be exposed.
     Any of the arguments can be None. If not None, they should have the
@@ -1426,14 +1426,18 @@ def MemberCondition(pref, func):
 
     pref: The name of the preference.
     func: The name of the function.
+    exposed: The bits of the globals the member is exposed in.
     """
     assert pref is None or isinstance(pref, str)
     assert func is None or isinstance(func, str)
-    assert func is None or pref is None
+    assert exposed is None or isinstance(exposed, int)
+    assert len([c for c in (pref, func, exposed) if c is not None]) <= 1
     if pref:
         return 'Condition::Pref("%s")' % pref
     if func:
         return 'Condition::Func(%s)' % func
+    if exposed is not None:
+        return 'Condition::Exposed(%#x)' % exposed
     return "Condition::Satisfied"
 
 
@@ -1476,7 +1480,21 @@ class PropertyDefiner:
             PropertyDefiner.getStringAttr(interfaceMember,
                                           "Pref"),
             PropertyDefiner.getStringAttr(interfaceMember,
-                                          "Func"))
+                                          "Func"),
+            PropertyDefiner.getExposureBits(interfaceMember, descriptor))
+
+    @staticmethod
+    def getExposureBits(interfaceMember, descriptor):
+        """
+        The Globals bits of a member that is exposed in fewer globals than its
+        interface, or None if it is exposed wherever the interface is.
+        """
+        if descriptor.interface.exposureSet <= interfaceMember.exposureSet:
+            return None
+        global_names = [d.name for d in descriptor.config.getDescriptors(isGlobal=True)]
+        return sum(2 ** global_names.index(name)
+                   for name in interfaceMember.exposureSet
+                   if name in global_names)
 
     def generateGuardedArray(self, array, name, specTemplate, specTerminator,
                              specType, getCondition, getDataTuple):
@@ -48,7 +48,7 @@ use js::glue::{RUST_JSID_IS_INT, RUST_JSID_TO_INT};
 use js::glue::{RUST_JSID_IS_STRING, RUST_JSID_TO_STRING, UnwrapObject};
 use js::jsapi::{HandleId, HandleObject, HandleValue, JSClass, JSContext};
 use js::jsapi::{JSObject, JSString, JS_GetArrayBufferViewType, JS_GetClass};
-use js::jsapi::{JS_GetLatin1StringCharsAndLength, JS_GetObjectAsArrayBufferView};
+use js::jsapi::{JS_GetLatin1StringCharsAndLength, JS_GetObjectAsArrayBuffer, JS_GetObjectAsArrayBufferView};
 use js::jsapi::{JS_GetReservedSlot, JS_GetTwoByteStringCharsAndLength, ToWindowProxyIfWindow};
 use js::jsapi::{JS_IsArrayObject, JS_NewStringCopyN, JS_StringHasLatin1Chars};
 use js::jsapi::{JS_WrapValue, MutableHandleValue, Type, IsObjectInContextCompartment};
@@ -553,6 +553,22 @@ pub fn array_buffer_view_to_vec<T: ArrayBufferViewContents>(abv: *mut JSObject)
     }
 }
 
+/// Returns a copy of the bytes held by an ArrayBuffer or an ArrayBufferView, as accepted by
+/// the WebIDL BufferSource type.
+pub fn array_buffer_or_view_to_vec(obj: *mut JSObject) -> Option<Vec<u8>> {
+    if let Some(data) = array_buffer_view_to_vec::<u8>(obj) {
+        return Some(data);
+    }
+    unsafe {
+        let mut byte_length = 0;
+        let mut ptr = ptr::null_mut();
+        if JS_GetObjectAsArrayBuffer(obj, &mut byte_length, &mut ptr).is_null() {
+            return None;
+        }
+        Some(slice::from_raw_parts(ptr, byte_length as usize).to_vec())
+    }
+}
+
 /// Returns a mutable slice of the Array Buffer View data, viewed as T, checking that the real type
 /// of it is ty.
 pub unsafe fn array_buffer_view_data_checked<'a, T: ArrayBufferViewContents>(abv: *mut JSObject)
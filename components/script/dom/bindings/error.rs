@@ -69,6 +69,16 @@ pub enum Error {
     TypeMismatch,
     /// InvalidModificationError DOMException
     InvalidModification,
+    /// OperationError DOMException
+    Operation,
+    /// DataError DOMException
+    Data,
+    /// EncodingError DOMException
+    Encoding,
+    /// NotReadableError DOMException
+    NotReadable,
+    /// NotAllowedError DOMException
+    NotAllowed,
 
     /// TypeError JavaScript Error
     Type(String),
@@ -110,6 +120,11 @@ pub unsafe fn throw_dom_exception(cx: *mut JSContext, global: GlobalRef, result:
         Error::QuotaExceeded => DOMErrorName::QuotaExceededError,
         Error::TypeMismatch => DOMErrorName::TypeMismatchError,
         Error::InvalidModification => DOMErrorName::InvalidModificationError,
+        Error::Operation => DOMErrorName::OperationError,
+        Error::Data => DOMErrorName::DataError,
+        Error::Encoding => DOMErrorName::EncodingError,
+        Error::NotReadable => DOMErrorName::NotReadableError,
+        Error::NotAllowed => DOMErrorName::NotAllowedError,
         Error::Type(message) => {
             assert!(!JS_IsExceptionPending(cx));
             throw_type_error(cx, &message);
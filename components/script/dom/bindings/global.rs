@@ -9,11 +9,13 @@
 
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
 use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::codegen::Bindings::WorkerGlobalScopeBinding::WorkerGlobalScopeMethods;
 use dom::bindings::conversions::root_from_object;
 use dom::bindings::error::ErrorInfo;
 use dom::bindings::js::Root;
 use dom::bindings::reflector::{Reflectable, Reflector};
 use dom::console::TimerSet;
+use dom::performance::Performance;
 use dom::window::{self, ScriptHelpers};
 use dom::workerglobalscope::WorkerGlobalScope;
 use ipc_channel::ipc::IpcSender;
@@ -160,6 +162,14 @@ impl<'a> GlobalRef<'a> {
         }
     }
 
+    /// Get the `Performance` object of this global scope.
+    pub fn performance(&self) -> Root<Performance> {
+        match *self {
+            GlobalRef::Window(ref window) => window.Performance(),
+            GlobalRef::Worker(ref worker) => worker.Performance(),
+        }
+    }
+
     /// Get the [base url](https://html.spec.whatwg.org/multipage/#api-base-url)
     /// for this global scope.
     pub fn api_base_url(&self) -> Url {
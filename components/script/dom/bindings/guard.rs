@@ -4,7 +4,9 @@
 
 //! Machinery to conditionally expose things.
 
-use js::jsapi::{HandleObject, JSContext};
+use dom::bindings::codegen::InterfaceObjectMap::Globals;
+use dom::bindings::interface::is_exposed_in;
+use js::jsapi::{GetGlobalForObjectCrossCompartment, HandleObject, JSContext};
 use util::prefs::PREFS;
 
 /// A container with a condition.
@@ -40,6 +42,9 @@ pub enum Condition {
     Func(unsafe fn(*mut JSContext, HandleObject) -> bool),
     /// The condition is satisfied if the preference is set.
     Pref(&'static str),
+    /// The condition is satisfied if the global of the object is one of the
+    /// globals whose flags are set in these `Globals` bits.
+    Exposed(u8),
     /// The condition is always satisfied.
     Satisfied,
 }
@@ -49,6 +54,10 @@ impl Condition {
         match *self {
             Condition::Pref(name) => PREFS.get(name).as_boolean().unwrap_or(false),
             Condition::Func(f) => f(cx, obj),
+            Condition::Exposed(bits) => {
+                rooted!(in(cx) let global = GetGlobalForObjectCrossCompartment(obj.get()));
+                is_exposed_in(global.handle(), Globals::from_bits_truncate(bits))
+            },
             Condition::Satisfied => true,
         }
     }
@@ -178,6 +178,85 @@ impl DOMString {
     pub fn bytes(&self) -> Bytes {
         self.0.bytes()
     }
+
+    /// Parses this string as a date, returning its year, month and day.
+    /// https://html.spec.whatwg.org/multipage/#parse-a-date-string
+    pub fn parse_date_string(&self) -> Result<(u32, u32, u32), ()> {
+        let (year, rest) = try!(parse_digits(&self.0, 4, None));
+        let rest = try!(expect_char(rest, '-'));
+        let (month, rest) = try!(parse_digits(rest, 2, Some(2)));
+        let rest = try!(expect_char(rest, '-'));
+        let (day, rest) = try!(parse_digits(rest, 2, Some(2)));
+        if !rest.is_empty() || year == 0 || month < 1 || month > 12 ||
+           day < 1 || day > days_in_month(year, month) {
+            return Err(());
+        }
+        Ok((year, month, day))
+    }
+
+    /// Parses this string as a time, returning its hour, minute and second.
+    /// https://html.spec.whatwg.org/multipage/#parse-a-time-string
+    pub fn parse_time_string(&self) -> Result<(u32, u32, f64), ()> {
+        let (hour, rest) = try!(parse_digits(&self.0, 2, Some(2)));
+        let rest = try!(expect_char(rest, ':'));
+        let (minute, mut rest) = try!(parse_digits(rest, 2, Some(2)));
+        let mut second = 0.;
+        if !rest.is_empty() {
+            rest = try!(expect_char(rest, ':'));
+            let (whole, after) = try!(parse_digits(rest, 2, Some(2)));
+            second = whole as f64;
+            rest = after;
+            if !rest.is_empty() {
+                rest = try!(expect_char(rest, '.'));
+                let (fraction, after) = try!(parse_digits(rest, 1, Some(3)));
+                second += fraction as f64 / 10f64.powi((rest.len() - after.len()) as i32);
+                rest = after;
+            }
+        }
+        if !rest.is_empty() || hour > 23 || minute > 59 || second >= 60. {
+            return Err(());
+        }
+        Ok((hour, minute, second))
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#valid-date-string
+    pub fn is_valid_date_string(&self) -> bool {
+        self.parse_date_string().is_ok()
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#valid-time-string
+    pub fn is_valid_time_string(&self) -> bool {
+        self.parse_time_string().is_ok()
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#valid-simple-colour
+    pub fn is_valid_simple_color_string(&self) -> bool {
+        self.0.len() == 7 && self.0.starts_with('#') &&
+            self.0[1..].chars().all(|c| c.is_digit(16))
+    }
+}
+
+/// Splits a run of at least `min` (and at most `max`) ASCII digits off the front of `s`.
+fn parse_digits(s: &str, min: usize, max: Option<usize>) -> Result<(u32, &str), ()> {
+    let len = s.find(|c: char| !c.is_digit(10)).unwrap_or(s.len());
+    if len < min || max.map_or(false, |max| len > max) {
+        return Err(());
+    }
+    s[..len].parse().map(|value| (value, &s[len..])).map_err(|_| ())
+}
+
+fn expect_char(s: &str, c: char) -> Result<&str, ()> {
+    if s.starts_with(c) { Ok(&s[c.len_utf8()..]) } else { Err(()) }
+}
+
+/// https://html.spec.whatwg.org/multipage/#number-of-days-in-month-month-of-year-year
+pub fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year % 400 == 0 || (year % 4 == 0 && year % 100 != 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
 }
 
 impl Borrow<str> for DOMString {
@@ -5,15 +5,264 @@
 //! This module implements structured cloning, as defined by [HTML]
 //! (https://html.spec.whatwg.org/multipage/#safe-passing-of-structured-data).
 
+use dom::bindings::codegen::Bindings::BlobBinding::BlobMethods;
+use dom::bindings::codegen::Bindings::FileBinding::FileMethods;
+use dom::bindings::conversions::{jsstring_to_str, root_from_handleobject};
 use dom::bindings::error::{Error, Fallible};
-use dom::bindings::global::GlobalRef;
-use js::jsapi::{HandleValue, MutableHandleValue};
-use js::jsapi::{JSContext, JS_ReadStructuredClone, JS_STRUCTURED_CLONE_VERSION};
-use js::jsapi::{JS_ClearPendingException, JS_WriteStructuredClone};
+use dom::bindings::global::{GlobalRef, global_root_from_context};
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::Reflectable;
+use dom::bindings::str::DOMString;
+use dom::bindings::utils::get_dictionary_property;
+use dom::blob::{Blob, BlobImpl};
+use dom::file::File;
+use dom::imagebitmap::ImageBitmap;
+use euclid::size::Size2D;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{HandleObject, HandleValue, HandleValueArray, JSContext};
+use js::jsapi::{JSObject, JSStructuredCloneCallbacks, JSStructuredCloneReader, JSStructuredCloneWriter};
+use js::jsapi::{JS_CallFunctionValue, JS_ClearPendingException, JS_GetClass, JS_ReadBytes};
+use js::jsapi::{JS_ReadStructuredClone, JS_ReadUint32Pair, JS_STRUCTURED_CLONE_VERSION, JS_WriteBytes};
+use js::jsapi::{JS_WriteStructuredClone, JS_WriteUint32Pair, MutableHandleValue};
+use js::jsval::{JSVal, UndefinedValue};
 use libc::size_t;
+use std::ffi::CStr;
+use std::os::raw;
 use std::ptr;
 use std::slice;
 
+/// The tags of the DOM objects that can be structurally cloned, in the range that SpiderMonkey
+/// leaves to the embedder.
+#[repr(u32)]
+enum StructuredCloneTags {
+    /// The lowest tag available to the embedder.
+    Min = 0xFFFF8000,
+    DomImageBitmap = 0xFFFF8001,
+    DomBlob = 0xFFFF8002,
+    DomFile = 0xFFFF8003,
+    Error = 0xFFFF8004,
+    Max = 0xFFFFFFFF,
+}
+
+/// Writes an `ImageBitmap` as its size, whether it is origin-clean and its pixels.
+unsafe fn write_image_bitmap(bitmap: Root<ImageBitmap>, w: *mut JSStructuredCloneWriter) -> bool {
+    let (pixels, size) = match bitmap.pixels() {
+        Some(pixels) => pixels,
+        None => return false,
+    };
+    JS_WriteUint32Pair(w, StructuredCloneTags::DomImageBitmap as u32,
+                       bitmap.origin_is_clean() as u32) &&
+    JS_WriteUint32Pair(w, size.width, size.height) &&
+    JS_WriteBytes(w, pixels.as_ptr() as *const raw::c_void, pixels.len())
+}
+
+unsafe fn read_image_bitmap(cx: *mut JSContext, r: *mut JSStructuredCloneReader, origin_clean: u32)
+                            -> *mut JSObject {
+    let (mut width, mut height) = (0, 0);
+    if !JS_ReadUint32Pair(r, &mut width, &mut height) {
+        return ptr::null_mut();
+    }
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    if !JS_ReadBytes(r, pixels.as_mut_ptr() as *mut raw::c_void, pixels.len()) {
+        return ptr::null_mut();
+    }
+    let global = global_root_from_context(cx);
+    let bitmap = ImageBitmap::new(global.r(), pixels, Size2D::new(width, height),
+                                  origin_clean != 0);
+    bitmap.reflector().get_jsobject().get()
+}
+
+/// Writes `bytes` as their length followed by the bytes themselves.
+unsafe fn write_bytes(w: *mut JSStructuredCloneWriter, bytes: &[u8]) -> bool {
+    let length = bytes.len() as u64;
+    JS_WriteUint32Pair(w, (length >> 32) as u32, length as u32) &&
+    JS_WriteBytes(w, bytes.as_ptr() as *const raw::c_void, bytes.len())
+}
+
+unsafe fn read_bytes(r: *mut JSStructuredCloneReader) -> Option<Vec<u8>> {
+    let (mut high, mut low) = (0, 0);
+    if !JS_ReadUint32Pair(r, &mut high, &mut low) {
+        return None;
+    }
+    let mut bytes = vec![0u8; ((high as u64) << 32 | low as u64) as usize];
+    if !JS_ReadBytes(r, bytes.as_mut_ptr() as *mut raw::c_void, bytes.len()) {
+        return None;
+    }
+    Some(bytes)
+}
+
+unsafe fn read_string(r: *mut JSStructuredCloneReader) -> Option<String> {
+    read_bytes(r).and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+/// Writes a `Blob`, or a `File` if `file` is given, as its type and contents, followed by the
+/// name and last modification date of the file.
+/// https://w3c.github.io/FileAPI/#blob-section
+unsafe fn write_blob(blob: &Blob, file: Option<&File>, w: *mut JSStructuredCloneWriter) -> bool {
+    let bytes = match blob.get_bytes() {
+        Ok(bytes) => bytes,
+        Err(()) => return false,
+    };
+    let tag = if file.is_some() { StructuredCloneTags::DomFile } else { StructuredCloneTags::DomBlob };
+    if !(JS_WriteUint32Pair(w, tag as u32, 0) &&
+         write_bytes(w, blob.Type().as_bytes()) &&
+         write_bytes(w, &bytes)) {
+        return false;
+    }
+    match file {
+        Some(file) => {
+            let modified = file.LastModified() as u64;
+            write_bytes(w, file.Name().as_bytes()) &&
+            JS_WriteUint32Pair(w, (modified >> 32) as u32, modified as u32)
+        },
+        None => true,
+    }
+}
+
+unsafe fn read_blob(cx: *mut JSContext, r: *mut JSStructuredCloneReader, is_file: bool) -> *mut JSObject {
+    let (type_string, bytes) = match (read_string(r), read_bytes(r)) {
+        (Some(type_string), Some(bytes)) => (type_string, bytes),
+        _ => return ptr::null_mut(),
+    };
+    let global = global_root_from_context(cx);
+    if !is_file {
+        let blob = Blob::new(global.r(), BlobImpl::new_from_bytes(bytes), type_string);
+        return blob.reflector().get_jsobject().get();
+    }
+    let name = match read_string(r) {
+        Some(name) => name,
+        None => return ptr::null_mut(),
+    };
+    let (mut high, mut low) = (0, 0);
+    if !JS_ReadUint32Pair(r, &mut high, &mut low) {
+        return ptr::null_mut();
+    }
+    let modified = ((high as u64) << 32 | low as u64) as i64;
+    let file = File::new(global.r(), BlobImpl::new_from_bytes(bytes), DOMString::from(name),
+                         Some(modified), &type_string);
+    file.reflector().get_jsobject().get()
+}
+
+/// The names of the error constructors that errors are recreated with.
+/// https://html.spec.whatwg.org/multipage/#structuredserializeinternal (step 17)
+static ERROR_NAMES: &'static [&'static str] = &[
+    "Error", "EvalError", "RangeError", "ReferenceError", "SyntaxError", "TypeError", "URIError",
+];
+
+/// Returns the string value of the `property` of `obj`, if it is a string.
+unsafe fn string_property(cx: *mut JSContext, obj: HandleObject, property: &str) -> Option<DOMString> {
+    rooted!(in(cx) let mut value = UndefinedValue());
+    match get_dictionary_property(cx, obj, property, value.handle_mut()) {
+        Ok(true) if value.is_string() => Some(jsstring_to_str(cx, value.to_string())),
+        _ => None,
+    }
+}
+
+/// Writes an `Error` object as its name, which must be that of one of the standard errors,
+/// and its message. Anything else about it, such as its stack, is not preserved.
+unsafe fn write_error(cx: *mut JSContext, obj: HandleObject, w: *mut JSStructuredCloneWriter) -> bool {
+    let name = string_property(cx, obj, "name")
+        .and_then(|name| ERROR_NAMES.iter().position(|&error_name| *name == *error_name))
+        .unwrap_or(0);
+    let message = string_property(cx, obj, "message");
+    JS_WriteUint32Pair(w, StructuredCloneTags::Error as u32, name as u32) &&
+    JS_WriteUint32Pair(w, message.is_some() as u32, 0) &&
+    write_bytes(w, message.as_ref().map_or(&[][..], |message| message.as_bytes()))
+}
+
+unsafe fn read_error(cx: *mut JSContext, r: *mut JSStructuredCloneReader, name: u32) -> *mut JSObject {
+    let (mut has_message, mut unused) = (0, 0);
+    if !JS_ReadUint32Pair(r, &mut has_message, &mut unused) {
+        return ptr::null_mut();
+    }
+    let message = match read_string(r) {
+        Some(message) => message,
+        None => return ptr::null_mut(),
+    };
+    let name = match ERROR_NAMES.get(name as usize) {
+        Some(name) => name,
+        None => return ptr::null_mut(),
+    };
+
+    // Create the error by calling the constructor of this global, with the message if any.
+    let global = global_root_from_context(cx);
+    rooted!(in(cx) let global_object = global.r().reflector().get_jsobject().get());
+    rooted!(in(cx) let mut constructor = UndefinedValue());
+    if get_dictionary_property(cx, global_object.handle(), name, constructor.handle_mut()) != Ok(true) {
+        return ptr::null_mut();
+    }
+    rooted!(in(cx) let mut message_value = UndefinedValue());
+    if has_message != 0 {
+        message.to_jsval(cx, message_value.handle_mut());
+    }
+    let args = [message_value.get()];
+    rooted!(in(cx) let mut error = UndefinedValue());
+    let args = HandleValueArray {
+        length_: args.len() as size_t,
+        elements_: args.as_ptr(),
+    };
+    if !JS_CallFunctionValue(cx, global_object.handle(), constructor.handle(), &args, error.handle_mut()) ||
+       !error.is_object() {
+        return ptr::null_mut();
+    }
+    error.to_object()
+}
+
+unsafe extern "C" fn read_callback(cx: *mut JSContext,
+                                   r: *mut JSStructuredCloneReader,
+                                   tag: u32,
+                                   data: u32,
+                                   _closure: *mut raw::c_void)
+                                   -> *mut JSObject {
+    assert!(tag > StructuredCloneTags::Min as u32 && tag < StructuredCloneTags::Max as u32,
+            "tag should be in the range of the embedder tags");
+    if tag == StructuredCloneTags::DomImageBitmap as u32 {
+        return read_image_bitmap(cx, r, data);
+    }
+    if tag == StructuredCloneTags::DomBlob as u32 || tag == StructuredCloneTags::DomFile as u32 {
+        return read_blob(cx, r, tag == StructuredCloneTags::DomFile as u32);
+    }
+    if tag == StructuredCloneTags::Error as u32 {
+        return read_error(cx, r, data);
+    }
+    ptr::null_mut()
+}
+
+/// Serializes the objects that SpiderMonkey can't by itself. SpiderMonkey already handles
+/// the primitive wrappers, `Date`, `RegExp`, `Map`, `Set`, `ArrayBuffer` and its views.
+unsafe extern "C" fn write_callback(cx: *mut JSContext,
+                                    w: *mut JSStructuredCloneWriter,
+                                    obj: HandleObject,
+                                    _closure: *mut raw::c_void)
+                                    -> bool {
+    if let Ok(bitmap) = root_from_handleobject::<ImageBitmap>(obj) {
+        return write_image_bitmap(bitmap, w);
+    }
+    if let Ok(file) = root_from_handleobject::<File>(obj) {
+        return write_blob(file.upcast::<Blob>(), Some(&file), w);
+    }
+    if let Ok(blob) = root_from_handleobject::<Blob>(obj) {
+        return write_blob(&blob, None, w);
+    }
+    if CStr::from_ptr((*JS_GetClass(obj.get())).name).to_bytes() == b"Error" {
+        return write_error(cx, obj, w);
+    }
+    false
+}
+
+unsafe extern "C" fn report_error_callback(_cx: *mut JSContext, _errorid: u32) {
+}
+
+static STRUCTURED_CLONE_CALLBACKS: JSStructuredCloneCallbacks = JSStructuredCloneCallbacks {
+    read: Some(read_callback),
+    write: Some(write_callback),
+    reportError: Some(report_error_callback),
+    readTransfer: None,
+    writeTransfer: None,
+    freeTransfer: None,
+};
+
 /// A buffer for a structured clone.
 pub enum StructuredCloneData {
     /// A non-serializable (default) variant
@@ -32,7 +281,7 @@ impl StructuredCloneData {
                                     message,
                                     &mut data,
                                     &mut nbytes,
-                                    ptr::null(),
+                                    &STRUCTURED_CLONE_CALLBACKS,
                                     ptr::null_mut(),
                                     HandleValue::undefined())
         };
@@ -45,6 +294,35 @@ impl StructuredCloneData {
         Ok(StructuredCloneData::Struct(data, nbytes))
     }
 
+    /// Writes a structured clone of `message`, then detaches the image bitmaps of `transfer`.
+    /// Returns a `DataClone` error if a bitmap of `transfer` is already detached or listed
+    /// twice, or if the clone fails.
+    /// https://html.spec.whatwg.org/multipage/#structuredserializewithtransfer
+    pub fn write_with_transfer(cx: *mut JSContext,
+                               message: HandleValue,
+                               transfer: &[Root<ImageBitmap>])
+                               -> Fallible<StructuredCloneData> {
+        for (index, bitmap) in transfer.iter().enumerate() {
+            if bitmap.is_detached() || transfer[..index].contains(bitmap) {
+                return Err(Error::DataClone);
+            }
+        }
+        let data = try!(StructuredCloneData::write(cx, message));
+        for bitmap in transfer {
+            bitmap.detach();
+        }
+        Ok(data)
+    }
+
+    /// Clones `value` into the realm of `global` by serializing and deserializing it.
+    /// https://html.spec.whatwg.org/multipage/#dom-structuredclone
+    pub fn clone_value(global: GlobalRef, cx: *mut JSContext, value: HandleValue) -> Fallible<JSVal> {
+        let data = try!(StructuredCloneData::write(cx, value));
+        rooted!(in(cx) let mut clone = UndefinedValue());
+        data.read(global, clone.handle_mut());
+        Ok(clone.get())
+    }
+
     /// Converts a StructuredCloneData to Vec<u8> for inter-thread sharing
     pub fn move_to_arraybuffer(self) -> Vec<u8> {
         match self {
@@ -67,7 +345,7 @@ impl StructuredCloneData {
                                            nbytes,
                                            JS_STRUCTURED_CLONE_VERSION,
                                            rval,
-                                           ptr::null(),
+                                           &STRUCTURED_CLONE_CALLBACKS,
                                            ptr::null_mut()));
         }
     }
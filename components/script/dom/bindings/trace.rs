@@ -29,8 +29,16 @@
 //! The `no_jsmanaged_fields!()` macro adds an empty implementation of `JSTraceable` to
 //! a datatype.
 
+use audio::{AudioRenderThread, NodeId};
+use audio::analyser::FrequencyAnalyser;
+use audio::param::ParamType;
+use body::BodyChunks;
 use canvas_traits::{CanvasGradientStop, LinearGradientStyle, RadialGradientStyle};
-use canvas_traits::{CompositionOrBlending, LineCapStyle, LineJoinStyle, RepetitionStyle};
+use canvas_traits::{CompositionOrBlending, LineCapStyle, LineJoinStyle, PathSegment, RepetitionStyle};
+use canvas_traits::webgl2::{WebGLSamplerId, WebGLTransformFeedbackId, WebGLVersion};
+use canvas_traits::webgl2::WebGLVertexArrayId;
+use canvas_traits::webgl_extensions::WebGLExtensionSupport;
+use canvas_traits::webxr::{XRFrameData, XRRigidTransformData, XRSessionId};
 use cssparser::RGBA;
 use devtools_traits::CSSError;
 use devtools_traits::WorkerId;
@@ -40,7 +48,7 @@ use dom::bindings::refcounted::{Trusted, TrustedPromise};
 use dom::bindings::reflector::{Reflectable, Reflector};
 use dom::bindings::str::{DOMString, USVString};
 use dom::bindings::utils::WindowProxyHandler;
-use encoding::types::EncodingRef;
+use encoding::types::{EncodingRef, RawDecoder};
 use euclid::{Matrix2D, Matrix4D, Point2D};
 use euclid::length::Length as EuclidLength;
 use euclid::rect::Rect;
@@ -56,8 +64,12 @@ use js::jsapi::{GCTraceKindToAscii, Heap, JSObject, JSTracer, TraceKind};
 use js::jsval::JSVal;
 use js::rust::Runtime;
 use libc;
+use media::{CaptureDevice, CaptureSettings, SinkId};
+use media::player::MediaPlayer;
+use media::recorder::RecordingFormat;
+use media::webm::{WebmParser, WebmTrack};
 use msg::constellation_msg::{FrameType, PipelineId, ReferrerPolicy, WindowSizeType};
-use net_traits::{Metadata, NetworkError, ResourceThreads};
+use net_traits::{Metadata, NetworkError, ResourceFetchTiming, ResourceThreads};
 use net_traits::filemanager_thread::RelativePos;
 use net_traits::image::base::{Image, ImageMetadata};
 use net_traits::image_cache_thread::{ImageCacheChan, ImageCacheThread};
@@ -68,10 +80,11 @@ use net_traits::storage_thread::StorageType;
 use offscreen_gl_context::GLLimits;
 use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use profile_traits::time::ProfilerChan as TimeProfilerChan;
-use script_layout_interface::OpaqueStyleAndLayoutData;
+use script_layout_interface::{CueSettings, OpaqueStyleAndLayoutData, ShownCue};
 use script_layout_interface::reporter::CSSErrorReporter;
 use script_layout_interface::rpc::LayoutRPC;
 use script_runtime::ScriptChan;
+use script_traits::{HistoryStateId, ServiceWorkerScript, UpdateViaCache};
 use script_traits::{TimerEventId, TimerSource, TouchpadPressurePhase, UntrustedNodeAddress, WindowSizeData};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
@@ -94,12 +107,18 @@ use style::element_state::*;
 use style::properties::PropertyDeclarationBlock;
 use style::selector_impl::{ElementSnapshot, PseudoElement};
 use style::values::specified::Length;
+use task_attribution::TaskAttribution;
 use time::Duration;
 use url::Origin as UrlOrigin;
 use url::Url;
+use usb_traits::UsbDeviceMsg;
 use uuid::Uuid;
 use webrender_traits::{WebGLBufferId, WebGLError, WebGLFramebufferId, WebGLProgramId};
 use webrender_traits::{WebGLRenderbufferId, WebGLShaderId, WebGLTextureId};
+use webrtc_traits::{Candidate, Certificate, ChannelParameters, DtlsState, IceCredentials};
+use webrtc_traits::RemoteParameters;
+use webrtc_traits::sdp::SessionDescription;
+use xpath::parser::Expr;
 
 /// A trait to allow tracing (only) DOM objects.
 pub trait JSTraceable {
@@ -109,7 +128,7 @@ pub trait JSTraceable {
 
 no_jsmanaged_fields!(CSSError);
 
-no_jsmanaged_fields!(EncodingRef);
+no_jsmanaged_fields!(EncodingRef, RawDecoder);
 
 no_jsmanaged_fields!(Reflector);
 
@@ -299,7 +318,7 @@ no_jsmanaged_fields!(Rect<T>);
 no_jsmanaged_fields!(Size2D<T>);
 no_jsmanaged_fields!(Arc<T>);
 no_jsmanaged_fields!(Image, ImageMetadata, ImageCacheChan, ImageCacheThread);
-no_jsmanaged_fields!(Metadata);
+no_jsmanaged_fields!(Metadata, ResourceFetchTiming);
 no_jsmanaged_fields!(NetworkError);
 no_jsmanaged_fields!(Atom, Namespace, QualName);
 no_jsmanaged_fields!(Trusted<T: Reflectable>);
@@ -310,7 +329,9 @@ no_jsmanaged_fields!(HashSet<T>);
 // in one of these make sure it is propagated properly to containing structs
 no_jsmanaged_fields!(FrameType, WindowSizeData, WindowSizeType, PipelineId);
 no_jsmanaged_fields!(TimerEventId, TimerSource);
+no_jsmanaged_fields!(HistoryStateId);
 no_jsmanaged_fields!(WorkerId);
+no_jsmanaged_fields!(ServiceWorkerScript, UpdateViaCache);
 no_jsmanaged_fields!(QuirksMode);
 no_jsmanaged_fields!(Runtime);
 no_jsmanaged_fields!(Headers, Method);
@@ -322,12 +343,22 @@ no_jsmanaged_fields!(EuclidLength<Unit, T>);
 no_jsmanaged_fields!(Matrix2D<T>);
 no_jsmanaged_fields!(Matrix4D<T>);
 no_jsmanaged_fields!(StorageType);
-no_jsmanaged_fields!(CanvasGradientStop, LinearGradientStyle, RadialGradientStyle);
+no_jsmanaged_fields!(CanvasGradientStop, LinearGradientStyle, PathSegment, RadialGradientStyle);
 no_jsmanaged_fields!(LineCapStyle, LineJoinStyle, CompositionOrBlending);
 no_jsmanaged_fields!(RepetitionStyle);
+no_jsmanaged_fields!(XRFrameData, XRRigidTransformData, XRSessionId);
+no_jsmanaged_fields!(AudioRenderThread, NodeId, ParamType, FrequencyAnalyser);
+no_jsmanaged_fields!(CaptureDevice, CaptureSettings, SinkId);
+no_jsmanaged_fields!(UsbDeviceMsg);
+no_jsmanaged_fields!(MediaPlayer);
+no_jsmanaged_fields!(WebmParser, WebmTrack);
+no_jsmanaged_fields!(RecordingFormat);
+no_jsmanaged_fields!(DtlsState, ChannelParameters, Certificate);
+no_jsmanaged_fields!(Candidate, IceCredentials, RemoteParameters, SessionDescription);
 no_jsmanaged_fields!(WebGLError, GLLimits);
 no_jsmanaged_fields!(TimeProfilerChan);
 no_jsmanaged_fields!(MemProfilerChan);
+no_jsmanaged_fields!(TaskAttribution);
 no_jsmanaged_fields!(PseudoElement);
 no_jsmanaged_fields!(Length);
 no_jsmanaged_fields!(ElementState);
@@ -338,6 +369,7 @@ no_jsmanaged_fields!(AttrValue);
 no_jsmanaged_fields!(ElementSnapshot);
 no_jsmanaged_fields!(HttpsState);
 no_jsmanaged_fields!(Request);
+no_jsmanaged_fields!(BodyChunks);
 no_jsmanaged_fields!(SharedRt);
 no_jsmanaged_fields!(TouchpadPressurePhase);
 no_jsmanaged_fields!(USVString);
@@ -352,12 +384,19 @@ no_jsmanaged_fields!(RelativePos);
 no_jsmanaged_fields!(OpaqueStyleAndLayoutData);
 no_jsmanaged_fields!(PathBuf);
 no_jsmanaged_fields!(CSSErrorReporter);
+no_jsmanaged_fields!(CueSettings, ShownCue);
 no_jsmanaged_fields!(WebGLBufferId);
 no_jsmanaged_fields!(WebGLFramebufferId);
 no_jsmanaged_fields!(WebGLProgramId);
 no_jsmanaged_fields!(WebGLRenderbufferId);
 no_jsmanaged_fields!(WebGLShaderId);
 no_jsmanaged_fields!(WebGLTextureId);
+no_jsmanaged_fields!(WebGLVertexArrayId);
+no_jsmanaged_fields!(WebGLSamplerId);
+no_jsmanaged_fields!(WebGLTransformFeedbackId);
+no_jsmanaged_fields!(WebGLVersion);
+no_jsmanaged_fields!(WebGLExtensionSupport);
+no_jsmanaged_fields!(Expr);
 
 
 impl JSTraceable for Box<ScriptChan + Send> {
@@ -0,0 +1,108 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::{AudioRenderMsg, NodeId};
+use audio::node::{AudioNodeInit, FilterType, NodeMessage};
+use audio::param::ParamType;
+use dom::audiocontext::AudioContext;
+use dom::audionode::AudioNode;
+use dom::audioparam::AudioParam;
+use dom::bindings::codegen::Bindings::BiquadFilterNodeBinding;
+use dom::bindings::codegen::Bindings::BiquadFilterNodeBinding::{BiquadFilterNodeMethods, BiquadFilterType};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::reflect_dom_object;
+use dom::oscillatornode::MAX_DETUNE;
+use std::cell::Cell;
+use std::f32;
+
+/// The largest gain of the shelf and peaking filters, in decibels.
+const MAX_GAIN: f32 = 1541.;
+
+// https://webaudio.github.io/web-audio-api/#biquadfilternode
+#[dom_struct]
+pub struct BiquadFilterNode {
+    audionode: AudioNode,
+    filter_type: Cell<BiquadFilterType>,
+    frequency: JS<AudioParam>,
+    detune: JS<AudioParam>,
+    q: JS<AudioParam>,
+    gain: JS<AudioParam>,
+}
+
+impl BiquadFilterNode {
+    fn new_inherited(context: &AudioContext,
+                     node_id: NodeId,
+                     frequency: &AudioParam,
+                     detune: &AudioParam,
+                     q: &AudioParam,
+                     gain: &AudioParam)
+                     -> BiquadFilterNode {
+        BiquadFilterNode {
+            audionode: AudioNode::new_inherited(context, node_id, 1, 1),
+            filter_type: Cell::new(BiquadFilterType::Lowpass),
+            frequency: JS::from_ref(frequency),
+            detune: JS::from_ref(detune),
+            q: JS::from_ref(q),
+            gain: JS::from_ref(gain),
+        }
+    }
+
+    pub fn new(global: GlobalRef, context: &AudioContext) -> Root<BiquadFilterNode> {
+        let node_id = context.create_node(AudioNodeInit::BiquadFilter);
+        let nyquist = context.sample_rate() / 2.;
+        let frequency = AudioParam::new(global, context, node_id, ParamType::Frequency, 350., 0., nyquist);
+        let detune = AudioParam::new(global, context, node_id, ParamType::Detune, 0., -MAX_DETUNE, MAX_DETUNE);
+        let q = AudioParam::new(global, context, node_id, ParamType::Q, 1., f32::MIN, f32::MAX);
+        let gain = AudioParam::new(global, context, node_id, ParamType::Gain, 0., f32::MIN, MAX_GAIN);
+        reflect_dom_object(box BiquadFilterNode::new_inherited(context, node_id, &frequency, &detune, &q, &gain),
+                           global,
+                           BiquadFilterNodeBinding::Wrap)
+    }
+}
+
+impl BiquadFilterNodeMethods for BiquadFilterNode {
+    // https://webaudio.github.io/web-audio-api/#dom-biquadfilternode-type
+    fn Type(&self) -> BiquadFilterType {
+        self.filter_type.get()
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-biquadfilternode-type
+    fn SetType(&self, value: BiquadFilterType) {
+        self.filter_type.set(value);
+        let filter_type = match value {
+            BiquadFilterType::Lowpass => FilterType::Lowpass,
+            BiquadFilterType::Highpass => FilterType::Highpass,
+            BiquadFilterType::Bandpass => FilterType::Bandpass,
+            BiquadFilterType::Lowshelf => FilterType::Lowshelf,
+            BiquadFilterType::Highshelf => FilterType::Highshelf,
+            BiquadFilterType::Peaking => FilterType::Peaking,
+            BiquadFilterType::Notch => FilterType::Notch,
+            BiquadFilterType::Allpass => FilterType::Allpass,
+        };
+        let node = self.upcast::<AudioNode>();
+        node.context().send(AudioRenderMsg::NodeMessage(node.node_id(), NodeMessage::SetFilterType(filter_type)));
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-biquadfilternode-frequency
+    fn Frequency(&self) -> Root<AudioParam> {
+        Root::from_ref(&*self.frequency)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-biquadfilternode-detune
+    fn Detune(&self) -> Root<AudioParam> {
+        Root::from_ref(&*self.detune)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-biquadfilternode-q
+    fn Q(&self) -> Root<AudioParam> {
+        Root::from_ref(&*self.q)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-biquadfilternode-gain
+    fn Gain(&self) -> Root<AudioParam> {
+        Root::from_ref(&*self.gain)
+    }
+}
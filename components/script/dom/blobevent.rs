@@ -0,0 +1,78 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::BlobEventBinding;
+use dom::bindings::codegen::Bindings::BlobEventBinding::BlobEventMethods;
+use dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::blob::Blob;
+use dom::event::{Event, EventBubbles, EventCancelable};
+use string_cache::Atom;
+
+// https://w3c.github.io/mediacapture-record/MediaRecorder.html#blobevent-section
+#[dom_struct]
+pub struct BlobEvent {
+    event: Event,
+    data: JS<Blob>,
+    timecode: f64,
+}
+
+impl BlobEvent {
+    fn new_inherited(data: &Blob, timecode: f64) -> BlobEvent {
+        BlobEvent {
+            event: Event::new_inherited(),
+            data: JS::from_ref(data),
+            timecode: timecode,
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               type_: Atom,
+               bubbles: EventBubbles,
+               cancelable: EventCancelable,
+               data: &Blob,
+               timecode: f64)
+               -> Root<BlobEvent> {
+        let event = reflect_dom_object(box BlobEvent::new_inherited(data, timecode),
+                                       global,
+                                       BlobEventBinding::Wrap);
+        event.upcast::<Event>().init_event(type_, bool::from(bubbles), bool::from(cancelable));
+        event
+    }
+
+    pub fn Constructor(global: GlobalRef,
+                       type_: DOMString,
+                       init: &BlobEventBinding::BlobEventInit)
+                       -> Fallible<Root<BlobEvent>> {
+        Ok(BlobEvent::new(global,
+                          Atom::from(type_),
+                          EventBubbles::from(init.parent.bubbles),
+                          EventCancelable::from(init.parent.cancelable),
+                          init.data.r(),
+                          init.timecode.map_or(0., |timecode| *timecode)))
+    }
+}
+
+impl BlobEventMethods for BlobEvent {
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-blobevent-data
+    fn Data(&self) -> Root<Blob> {
+        Root::from_ref(&*self.data)
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-blobevent-timecode
+    fn Timecode(&self) -> Finite<f64> {
+        Finite::wrap(self.timecode)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
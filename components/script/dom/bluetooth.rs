@@ -10,15 +10,22 @@ use dom::bindings::error::Error::{self, Security, Type};
 use dom::bindings::error::Fallible;
 use dom::bindings::global::GlobalRef;
 use dom::bindings::js::Root;
+use dom::bindings::refcounted::{Trusted, TrustedPromise};
 use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
 use dom::bindings::str::DOMString;
 use dom::bluetoothadvertisingdata::BluetoothAdvertisingData;
 use dom::bluetoothdevice::BluetoothDevice;
 use dom::bluetoothuuid::{BluetoothServiceUUID, BluetoothUUID};
+use dom::promise::Promise;
 use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use js::jsapi::JSAutoCompartment;
 use net_traits::bluetooth_scanfilter::{BluetoothScanfilter, BluetoothScanfilterSequence};
 use net_traits::bluetooth_scanfilter::{RequestDeviceoptions, ServiceUUIDSequence};
-use net_traits::bluetooth_thread::{BluetoothError, BluetoothMethodMsg};
+use net_traits::bluetooth_thread::{BluetoothError, BluetoothMethodMsg, BluetoothResult};
+use script_thread::Runnable;
+use std::rc::Rc;
+use task_source::TaskSource;
 
 const FILTER_EMPTY_ERROR: &'static str = "'filters' member, if present, must be nonempty to find any devices.";
 const FILTER_ERROR: &'static str = "A filter must restrict the devices in some way.";
@@ -264,6 +271,71 @@ impl From<BluetoothError> for Error {
     }
 }
 
+/// Sends the message that `message` makes with a reply sender to the bluetooth thread, and
+/// settles the returned promise with `settle` once the reply arrives, without blocking the
+/// script thread until then.
+#[allow(unrooted_must_root)]
+pub fn bluetooth_request<T, F>(target: &T, message: F, settle: fn(&T, &Promise)) -> Rc<Promise>
+    where T: Reflectable + 'static,
+          F: FnOnce(IpcSender<BluetoothResult<bool>>) -> BluetoothMethodMsg
+{
+    let global = target.global();
+    let window = global.r().as_window();
+    let (sender, receiver) = ipc::channel().unwrap();
+    if window.bluetooth_thread().send(message(sender)).is_err() {
+        return rejected_promise(global.r(), Error::Network);
+    }
+    let promise = Promise::new(global.r());
+    let task_source = window.dom_manipulation_task_source();
+    let wrapper = window.get_runnable_wrapper();
+    let mut runnable = Some(box BluetoothReplyRunnable {
+        target: Trusted::new(target),
+        promise: TrustedPromise::new(promise.clone()),
+        result: Err(BluetoothError::Network),
+        settle: settle,
+    });
+    ROUTER.add_route(receiver.to_opaque(), box move |message| {
+        if let Some(mut runnable) = runnable.take() {
+            runnable.result = message.to().unwrap_or(Err(BluetoothError::Network));
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        }
+    });
+    promise
+}
+
+/// A promise that is already rejected with `error`.
+#[allow(unrooted_must_root)]
+pub fn rejected_promise(global: GlobalRef, error: Error) -> Rc<Promise> {
+    let promise = Promise::new(global);
+    promise.reject_error(global.get_cx(), error);
+    promise
+}
+
+/// Settles the promise of a bluetooth method with the reply of the bluetooth thread.
+struct BluetoothReplyRunnable<T: Reflectable> {
+    target: Trusted<T>,
+    promise: TrustedPromise,
+    result: BluetoothResult<bool>,
+    settle: fn(&T, &Promise),
+}
+
+impl<T: Reflectable> Runnable for BluetoothReplyRunnable<T> {
+    fn name(&self) -> &'static str { "BluetoothReplyRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let target = this.target.root();
+        let promise = this.promise.root();
+        let global = target.global();
+        let _ac = JSAutoCompartment::new(global.r().get_cx(), promise.reflector().get_jsobject().get());
+        match this.result {
+            Ok(_) => (this.settle)(&target, &promise),
+            Err(error) => promise.reject_error(global.r().get_cx(), Error::from(error)),
+        }
+    }
+}
+
 impl BluetoothMethods for Bluetooth {
     // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetooth-requestdevice
     fn RequestDevice(&self, option: &RequestDeviceOptions) -> Fallible<Root<BluetoothDevice>> {
@@ -4,21 +4,38 @@
 
 use dom::bindings::codegen::Bindings::BluetoothDeviceBinding;
 use dom::bindings::codegen::Bindings::BluetoothDeviceBinding::BluetoothDeviceMethods;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, Root, MutHeap, MutNullableHeap};
-use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::bindings::refcounted::Trusted;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
 use dom::bindings::str::DOMString;
+use dom::bluetooth::bluetooth_request;
 use dom::bluetoothadvertisingdata::BluetoothAdvertisingData;
+use dom::bluetoothadvertisingevent::BluetoothAdvertisingEvent;
 use dom::bluetoothremotegattserver::BluetoothRemoteGATTServer;
+use dom::event::{Event, EventBubbles, EventCancelable};
+use dom::eventtarget::EventTarget;
+use dom::promise::Promise;
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use net_traits::bluetooth_thread::{BluetoothDeviceMsg, BluetoothEventMsg, BluetoothMethodMsg};
+use script_thread::Runnable;
+use std::cell::Cell;
+use std::rc::Rc;
+use string_cache::Atom;
+use task_source::TaskSource;
 
 // https://webbluetoothcg.github.io/web-bluetooth/#bluetoothdevice
 #[dom_struct]
 pub struct BluetoothDevice {
-    reflector_: Reflector,
+    eventtarget: EventTarget,
     id: DOMString,
     name: Option<DOMString>,
     ad_data: MutHeap<JS<BluetoothAdvertisingData>>,
     gatt: MutNullableHeap<JS<BluetoothRemoteGATTServer>>,
+    watching_advertisements: Cell<bool>,
 }
 
 impl BluetoothDevice {
@@ -27,11 +44,12 @@ impl BluetoothDevice {
                          ad_data: &BluetoothAdvertisingData)
                          -> BluetoothDevice {
         BluetoothDevice {
-            reflector_: Reflector::new(),
+            eventtarget: EventTarget::new_inherited(),
             id: id,
             name: name,
             ad_data: MutHeap::new(ad_data),
             gatt: Default::default(),
+            watching_advertisements: Cell::new(false),
         }
     }
 
@@ -46,6 +64,76 @@ impl BluetoothDevice {
                            global,
                            BluetoothDeviceBinding::Wrap)
     }
+
+    fn get_bluetooth_thread(&self) -> IpcSender<BluetoothMethodMsg> {
+        let global_root = self.global();
+        let global_ref = global_root.r();
+        global_ref.as_window().bluetooth_thread()
+    }
+
+    /// Creates the channel on which the bluetooth thread tells about the advertisements
+    /// of this device, which are delivered as `advertisementreceived` events.
+    fn advertisement_listener(&self) -> IpcSender<BluetoothEventMsg> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let global = self.global();
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let device = Trusted::new(self);
+        ROUTER.add_route(receiver.to_opaque(), box move |message| {
+            if let Ok(BluetoothEventMsg::AdvertisementReceived(advertisement)) = message.to() {
+                let runnable = box AdvertisementReceivedRunnable {
+                    device: device.clone(),
+                    advertisement: advertisement,
+                };
+                let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+            }
+        });
+        sender
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#advertising-events
+    fn handle_advertisement(&self, advertisement: BluetoothDeviceMsg) {
+        // Advertisements that arrive after unwatchAdvertisements() was called are dropped.
+        if !self.watching_advertisements.get() {
+            return;
+        }
+        let global = self.global();
+        let ad_data = BluetoothAdvertisingData::new(global.r(),
+                                                    advertisement.appearance,
+                                                    advertisement.tx_power,
+                                                    advertisement.rssi);
+        self.ad_data.set(&ad_data);
+        let event = BluetoothAdvertisingEvent::new(global.r(),
+                                                   Atom::from("advertisementreceived"),
+                                                   EventBubbles::Bubbles,
+                                                   EventCancelable::NotCancelable,
+                                                   self,
+                                                   advertisement.name.map(DOMString::from),
+                                                   advertisement.appearance,
+                                                   advertisement.tx_power,
+                                                   advertisement.rssi);
+        event.upcast::<Event>().fire(self.upcast());
+    }
+}
+
+struct AdvertisementReceivedRunnable {
+    device: Trusted<BluetoothDevice>,
+    advertisement: BluetoothDeviceMsg,
+}
+
+impl Runnable for AdvertisementReceivedRunnable {
+    fn name(&self) -> &'static str { "AdvertisementReceivedRunnable" }
+
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        this.device.root().handle_advertisement(this.advertisement);
+    }
+}
+
+fn finish_watch_advertisements(device: &BluetoothDevice, promise: &Promise) {
+    device.watching_advertisements.set(true);
+    promise.resolve_native(device.global().r().get_cx(), &());
 }
 
 impl BluetoothDeviceMethods for BluetoothDevice {
@@ -68,4 +156,40 @@ impl BluetoothDeviceMethods for BluetoothDevice {
     fn Gatt(&self) -> Root<BluetoothRemoteGATTServer> {
         self.gatt.or_init(|| BluetoothRemoteGATTServer::new(self.global().r(), self))
     }
+
+    #[allow(unrooted_must_root)]
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-watchadvertisements
+    fn WatchAdvertisements(&self) -> Rc<Promise> {
+        if self.watching_advertisements.get() {
+            let global = self.global();
+            let promise = Promise::new(global.r());
+            promise.resolve_native(global.r().get_cx(), &());
+            return promise;
+        }
+        let id = String::from(self.Id());
+        let listener = self.advertisement_listener();
+        bluetooth_request(self,
+                          move |sender| BluetoothMethodMsg::WatchAdvertisements(id, listener, sender),
+                          finish_watch_advertisements)
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-unwatchadvertisements
+    fn UnwatchAdvertisements(&self) {
+        if !self.watching_advertisements.get() {
+            return
+        }
+        self.watching_advertisements.set(false);
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.get_bluetooth_thread().send(
+            BluetoothMethodMsg::UnwatchAdvertisements(String::from(self.Id()), sender)).unwrap();
+        let _ = receiver.recv();
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdevice-watchingadvertisements
+    fn WatchingAdvertisements(&self) -> bool {
+        self.watching_advertisements.get()
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothdeviceeventhandlers-onadvertisementreceived
+    event_handler!(advertisementreceived, GetOnadvertisementreceived, SetOnadvertisementreceived);
 }
@@ -12,18 +12,29 @@ use dom::bindings::codegen::Bindings::BluetoothRemoteGATTCharacteristicBinding::
     BluetoothRemoteGATTCharacteristicMethods;
 use dom::bindings::codegen::Bindings::BluetoothRemoteGATTServerBinding::BluetoothRemoteGATTServerMethods;
 use dom::bindings::codegen::Bindings::BluetoothRemoteGATTServiceBinding::BluetoothRemoteGATTServiceMethods;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use dom::bindings::error::{ErrorResult, Fallible};
 use dom::bindings::error::Error::{self, InvalidModification, Network, NotSupported, Security};
 use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, MutHeap, Root};
-use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::bindings::refcounted::Trusted;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
 use dom::bindings::str::{ByteString, DOMString};
+use dom::bluetooth::{bluetooth_request, rejected_promise};
 use dom::bluetoothcharacteristicproperties::BluetoothCharacteristicProperties;
 use dom::bluetoothremotegattdescriptor::BluetoothRemoteGATTDescriptor;
 use dom::bluetoothremotegattservice::BluetoothRemoteGATTService;
 use dom::bluetoothuuid::{BluetoothDescriptorUUID, BluetoothUUID};
+use dom::event::{EventBubbles, EventCancelable};
+use dom::eventtarget::EventTarget;
+use dom::promise::Promise;
 use ipc_channel::ipc::{self, IpcSender};
-use net_traits::bluetooth_thread::BluetoothMethodMsg;
+use ipc_channel::router::ROUTER;
+use net_traits::bluetooth_thread::{BluetoothEventMsg, BluetoothMethodMsg};
+use script_thread::Runnable;
+use std::rc::Rc;
+use task_source::TaskSource;
 
 // Maximum length of an attribute value.
 // https://www.bluetooth.org/DocMan/handlers/DownloadDoc.ashx?doc_id=286439 (Vol. 3, page 2169)
@@ -32,7 +43,7 @@ pub const MAXIMUM_ATTRIBUTE_LENGTH: usize = 512;
 // https://webbluetoothcg.github.io/web-bluetooth/#bluetoothremotegattcharacteristic
 #[dom_struct]
 pub struct BluetoothRemoteGATTCharacteristic {
-    reflector_: Reflector,
+    eventtarget: EventTarget,
     service: MutHeap<JS<BluetoothRemoteGATTService>>,
     uuid: DOMString,
     properties: MutHeap<JS<BluetoothCharacteristicProperties>>,
@@ -47,7 +58,7 @@ impl BluetoothRemoteGATTCharacteristic {
                          instance_id: String)
                          -> BluetoothRemoteGATTCharacteristic {
         BluetoothRemoteGATTCharacteristic {
-            reflector_: Reflector::new(),
+            eventtarget: EventTarget::new_inherited(),
             service: MutHeap::new(service),
             uuid: uuid,
             properties: MutHeap::new(properties),
@@ -79,6 +90,53 @@ impl BluetoothRemoteGATTCharacteristic {
     fn get_instance_id(&self) -> String {
         self.instance_id.clone()
     }
+
+    /// Creates the channel on which the bluetooth thread sends the values this
+    /// characteristic notifies, which are delivered as `characteristicvaluechanged` events.
+    fn notification_listener(&self) -> IpcSender<BluetoothEventMsg> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let global = self.global();
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let characteristic = Trusted::new(self);
+        ROUTER.add_route(receiver.to_opaque(), box move |message| {
+            if let Ok(BluetoothEventMsg::CharacteristicValueChanged(_, value)) = message.to() {
+                let runnable = box CharacteristicValueChangedRunnable {
+                    characteristic: characteristic.clone(),
+                    value: value,
+                };
+                let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+            }
+        });
+        sender
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#notification-events
+    fn handle_value_changed(&self, value: Vec<u8>) {
+        *self.value.borrow_mut() = Some(ByteString::new(value));
+        self.upcast::<EventTarget>().fire_event("characteristicvaluechanged",
+                                                EventBubbles::Bubbles,
+                                                EventCancelable::NotCancelable);
+    }
+}
+
+struct CharacteristicValueChangedRunnable {
+    characteristic: Trusted<BluetoothRemoteGATTCharacteristic>,
+    value: Vec<u8>,
+}
+
+impl Runnable for CharacteristicValueChangedRunnable {
+    fn name(&self) -> &'static str { "CharacteristicValueChangedRunnable" }
+
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        this.characteristic.root().handle_value_changed(this.value);
+    }
+}
+
+fn resolve_with_characteristic(characteristic: &BluetoothRemoteGATTCharacteristic, promise: &Promise) {
+    promise.resolve_native(characteristic.global().r().get_cx(), &Root::from_ref(characteristic));
 }
 
 impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteristic {
@@ -212,4 +270,35 @@ impl BluetoothRemoteGATTCharacteristicMethods for BluetoothRemoteGATTCharacteris
             },
         }
     }
+
+    #[allow(unrooted_must_root)]
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-startnotifications
+    fn StartNotifications(&self) -> Rc<Promise> {
+        if uuid_is_blacklisted(self.uuid.as_ref(), Blacklist::Reads) {
+            return rejected_promise(self.global().r(), Security)
+        }
+        if !(self.Properties().Notify() || self.Properties().Indicate()) {
+            return rejected_promise(self.global().r(), NotSupported)
+        }
+        if !self.Service().Device().Gatt().Connected() {
+            return rejected_promise(self.global().r(), Network)
+        }
+        let id = self.get_instance_id();
+        let listener = self.notification_listener();
+        bluetooth_request(self,
+                          move |sender| BluetoothMethodMsg::StartNotifications(id, listener, sender),
+                          resolve_with_characteristic)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-bluetoothremotegattcharacteristic-stopnotifications
+    fn StopNotifications(&self) -> Rc<Promise> {
+        let id = self.get_instance_id();
+        bluetooth_request(self,
+                          move |sender| BluetoothMethodMsg::StopNotifications(id, sender),
+                          resolve_with_characteristic)
+    }
+
+    // https://webbluetoothcg.github.io/web-bluetooth/#dom-characteristiceventhandlers-oncharacteristicvaluechanged
+    event_handler!(characteristicvaluechanged, GetOncharacteristicvaluechanged, SetOncharacteristicvaluechanged);
 }
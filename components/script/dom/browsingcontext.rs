@@ -12,6 +12,7 @@ use dom::bindings::str::DOMString;
 use dom::bindings::trace::JSTraceable;
 use dom::bindings::utils::WindowProxyHandler;
 use dom::bindings::utils::get_array_index_from_id;
+use dom::dissimilaroriginwindow::DissimilarOriginWindow;
 use dom::document::Document;
 use dom::element::Element;
 use dom::window::Window;
@@ -93,14 +94,14 @@ impl BrowsingContext {
     pub fn init(&self, document: &Document) {
         assert!(self.history.borrow().is_empty());
         assert_eq!(self.active_index.get(), 0);
-        self.history.borrow_mut().push(SessionHistoryEntry::new(document, document.url().clone(), document.Title()));
+        self.history.borrow_mut().push(SessionHistoryEntry::new(document, document.url(), document.Title()));
     }
 
     pub fn push_history(&self, document: &Document) {
         let mut history = self.history.borrow_mut();
         // Clear all session history entries after the active index
         history.drain((self.active_index.get() + 1)..);
-        history.push(SessionHistoryEntry::new(document, document.url().clone(), document.Title()));
+        history.push(SessionHistoryEntry::new(document, document.url(), document.Title()));
         self.active_index.set(self.active_index.get() + 1);
         assert_eq!(self.active_index.get(), history.len() - 1);
     }
@@ -208,6 +209,23 @@ impl Iterator for ContextIterator {
     }
 }
 
+/// A window as seen from another one: the WindowProxy of its browsing context if it lives in the
+/// same script thread, or a `DissimilarOriginWindow` standing in for it otherwise.
+pub enum WindowProxyRef {
+    Local(Root<BrowsingContext>),
+    Remote(Root<DissimilarOriginWindow>),
+}
+
+impl ToJSValConvertible for WindowProxyRef {
+    #[allow(unsafe_code)]
+    unsafe fn to_jsval(&self, cx: *mut JSContext, rval: MutableHandleValue) {
+        match *self {
+            WindowProxyRef::Local(ref context) => context.to_jsval(cx, rval),
+            WindowProxyRef::Remote(ref window) => window.to_jsval(cx, rval),
+        }
+    }
+}
+
 // This isn't a DOM struct, just a convenience struct
 // without a reflector, so we don't mark this as #[dom_struct]
 #[must_root]
@@ -233,7 +251,7 @@ impl SessionHistoryEntry {
 unsafe fn GetSubframeWindow(cx: *mut JSContext,
                             proxy: HandleObject,
                             id: HandleId)
-                            -> Option<Root<Window>> {
+                            -> Option<WindowProxyRef> {
     let index = get_array_index_from_id(cx, id);
     if let Some(index) = index {
         rooted!(in(cx) let target = GetProxyPrivate(*proxy.ptr).to_object());
@@ -4,15 +4,19 @@
 
 use dom::bindings::codegen::Bindings::ClientBinding::{ClientMethods, Wrap};
 use dom::bindings::codegen::Bindings::ClientBinding::FrameType;
+use dom::bindings::error::ErrorResult;
 use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
 use dom::bindings::js::JS;
 use dom::bindings::js::Root;
-use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
 use dom::bindings::str::{DOMString, USVString};
+use dom::bindings::structuredclone::StructuredCloneData;
 use dom::serviceworker::ServiceWorker;
-use dom::window::Window;
-use url::Url;
-use uuid::Uuid;
+use dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use js::jsapi::{HandleValue, JSContext};
+use msg::constellation_msg::PipelineId;
+use script_traits::ClientInfo;
 
 #[dom_struct]
 pub struct Client {
@@ -20,23 +24,25 @@ pub struct Client {
     active_worker: Option<JS<ServiceWorker>>,
     url: USVString,
     frame_type: FrameType,
-    #[ignore_heap_size_of = "Defined in uuid"]
-    id: Uuid
+    id: DOMString,
+    /// The pipeline of the client's window, which receives its messages.
+    pipeline_id: PipelineId,
 }
 
 impl Client {
-    fn new_inherited(url: Url) -> Client {
+    fn new_inherited(info: ClientInfo) -> Client {
         Client {
             reflector_: Reflector::new(),
             active_worker: None,
-            url: USVString(url.as_str().to_owned()),
+            url: USVString(info.url.into_string()),
             frame_type: FrameType::None,
-            id: Uuid::new_v4()
+            id: DOMString::from(info.id),
+            pipeline_id: info.pipeline_id,
         }
     }
 
-    pub fn new(window: &Window) -> Root<Client> {
-        reflect_dom_object(box Client::new_inherited(window.get_url()), GlobalRef::Window(window), Wrap)
+    pub fn new(global: GlobalRef, info: ClientInfo) -> Root<Client> {
+        reflect_dom_object(box Client::new_inherited(info), global, Wrap)
     }
 }
 
@@ -53,7 +59,19 @@ impl ClientMethods for Client {
 
     // https://w3c.github.io/ServiceWorker/#client-id
     fn Id(&self) -> DOMString {
-        let uid_str = format!("{}", self.id);
-        DOMString::from_string(uid_str)
+        self.id.clone()
+    }
+
+    // https://w3c.github.io/ServiceWorker/#client-postmessage
+    fn PostMessage(&self, cx: *mut JSContext, message: HandleValue) -> ErrorResult {
+        // Step 3
+        let data = try!(StructuredCloneData::write(cx, message));
+        // Only service workers hold clients for now.
+        if let GlobalRef::Worker(worker) = self.global().r() {
+            if let Some(service_worker) = worker.downcast::<ServiceWorkerGlobalScope>() {
+                service_worker.post_message_to_client(self.pipeline_id, data);
+            }
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,46 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::ClientsBinding::{ClientsMethods, Wrap};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::promise::Promise;
+use dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use std::rc::Rc;
+
+#[dom_struct]
+pub struct Clients {
+    reflector_: Reflector,
+}
+
+impl Clients {
+    fn new_inherited() -> Clients {
+        Clients {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<Clients> {
+        reflect_dom_object(box Clients::new_inherited(), global, Wrap)
+    }
+}
+
+impl ClientsMethods for Clients {
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/ServiceWorker/#clients-claim
+    fn Claim(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        if let GlobalRef::Worker(worker) = global.r() {
+            if let Some(service_worker) = worker.downcast::<ServiceWorkerGlobalScope>() {
+                service_worker.claim_clients();
+            }
+        }
+        // The clients are claimed asynchronously, through the service worker manager.
+        promise.resolve_native(global.r().get_cx(), &());
+        promise
+    }
+}
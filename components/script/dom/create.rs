@@ -53,6 +53,7 @@ use dom::htmloptionelement::HTMLOptionElement;
 use dom::htmloutputelement::HTMLOutputElement;
 use dom::htmlparagraphelement::HTMLParagraphElement;
 use dom::htmlparamelement::HTMLParamElement;
+use dom::htmlpictureelement::HTMLPictureElement;
 use dom::htmlpreelement::HTMLPreElement;
 use dom::htmlprogresselement::HTMLProgressElement;
 use dom::htmlquoteelement::HTMLQuoteElement;
@@ -202,6 +203,7 @@ pub fn create_element(name: QualName,
         atom!("output")     => make!(HTMLOutputElement),
         atom!("p")          => make!(HTMLParagraphElement),
         atom!("param")      => make!(HTMLParamElement),
+        atom!("picture")    => make!(HTMLPictureElement),
         atom!("plaintext")  => make!(HTMLPreElement),
         atom!("pre")        => make!(HTMLPreElement),
         atom!("progress")   => make!(HTMLProgressElement),
@@ -9,8 +9,9 @@ use dom::bindings::codegen::Bindings::CryptoBinding::CryptoMethods;
 use dom::bindings::conversions::array_buffer_view_data;
 use dom::bindings::error::{Error, Fallible};
 use dom::bindings::global::GlobalRef;
-use dom::bindings::js::Root;
-use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::subtlecrypto::SubtleCrypto;
 use js::jsapi::{JSContext, JSObject};
 use js::jsapi::{JS_GetArrayBufferViewType, Type};
 use rand::{OsRng, Rng};
@@ -23,6 +24,7 @@ pub struct Crypto {
     reflector_: Reflector,
     #[ignore_heap_size_of = "Defined in rand"]
     rng: DOMRefCell<OsRng>,
+    subtle: MutNullableHeap<JS<SubtleCrypto>>,
 }
 
 impl Crypto {
@@ -30,6 +32,7 @@ impl Crypto {
         Crypto {
             reflector_: Reflector::new(),
             rng: DOMRefCell::new(OsRng::new().unwrap()),
+            subtle: Default::default(),
         }
     }
 
@@ -39,6 +42,11 @@ impl Crypto {
 }
 
 impl CryptoMethods for Crypto {
+    // https://w3c.github.io/webcrypto/#dom-crypto-subtle
+    fn Subtle(&self) -> Root<SubtleCrypto> {
+        self.subtle.or_init(|| SubtleCrypto::new(self.global().r()))
+    }
+
     #[allow(unsafe_code)]
     // https://dvcs.w3.org/hg/webcrypto-api/raw-file/tip/spec/Overview.html#Crypto-method-getRandomValues
     fn GetRandomValues(&self,
@@ -0,0 +1,262 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::CryptoKeyBinding;
+use dom::bindings::codegen::Bindings::CryptoKeyBinding::{CryptoKeyMethods, KeyType, KeyUsage};
+use dom::bindings::conversions::{ToJSValConvertible, array_buffer_view_data};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::bindings::utils::set_dictionary_property;
+use js::jsapi::{HandleObject, JSContext, JS_NewPlainObject, JS_NewUint8Array};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use webcrypto::{EcPrivateKey, EcPublicKey, HashAlgorithm, NamedCurve, RsaPrivateKey, RsaPublicKey};
+
+/// The block cipher mode an AES key is used with.
+#[derive(Clone, Copy, Debug, HeapSizeOf, JSTraceable, PartialEq)]
+pub enum AesMode {
+    Cbc,
+    Ctr,
+    Gcm,
+}
+
+impl AesMode {
+    pub fn from_name(name: &str) -> Option<AesMode> {
+        match name {
+            "AES-CBC" => Some(AesMode::Cbc),
+            "AES-CTR" => Some(AesMode::Ctr),
+            "AES-GCM" => Some(AesMode::Gcm),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            AesMode::Cbc => "AES-CBC",
+            AesMode::Ctr => "AES-CTR",
+            AesMode::Gcm => "AES-GCM",
+        }
+    }
+}
+
+/// The way an RSA key encrypts or signs.
+#[derive(Clone, Copy, Debug, HeapSizeOf, JSTraceable, PartialEq)]
+pub enum RsaScheme {
+    Oaep,
+    Pss,
+    Pkcs1V15,
+}
+
+impl RsaScheme {
+    pub fn from_name(name: &str) -> Option<RsaScheme> {
+        match name {
+            "RSA-OAEP" => Some(RsaScheme::Oaep),
+            "RSA-PSS" => Some(RsaScheme::Pss),
+            "RSASSA-PKCS1-v1_5" => Some(RsaScheme::Pkcs1V15),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            RsaScheme::Oaep => "RSA-OAEP",
+            RsaScheme::Pss => "RSA-PSS",
+            RsaScheme::Pkcs1V15 => "RSASSA-PKCS1-v1_5",
+        }
+    }
+}
+
+/// The way an EC key signs or agrees on a secret.
+#[derive(Clone, Copy, Debug, HeapSizeOf, JSTraceable, PartialEq)]
+pub enum EcScheme {
+    Ecdsa,
+    Ecdh,
+}
+
+impl EcScheme {
+    pub fn from_name(name: &str) -> Option<EcScheme> {
+        match name {
+            "ECDSA" => Some(EcScheme::Ecdsa),
+            "ECDH" => Some(EcScheme::Ecdh),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            EcScheme::Ecdsa => "ECDSA",
+            EcScheme::Ecdh => "ECDH",
+        }
+    }
+}
+
+/// The algorithm a key may be used with, along with its parameters.
+/// https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-algorithm
+#[derive(Clone, HeapSizeOf, JSTraceable)]
+pub enum KeyAlgorithm {
+    Aes { mode: AesMode, length: u16 },
+    Hmac { hash: HashAlgorithm, length: u32 },
+    Rsa { scheme: RsaScheme, hash: HashAlgorithm, modulus_length: u32, public_exponent: Vec<u8> },
+    Ec { scheme: EcScheme, curve: NamedCurve },
+}
+
+impl KeyAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            KeyAlgorithm::Aes { mode, .. } => mode.name(),
+            KeyAlgorithm::Hmac { .. } => "HMAC",
+            KeyAlgorithm::Rsa { scheme, .. } => scheme.name(),
+            KeyAlgorithm::Ec { scheme, .. } => scheme.name(),
+        }
+    }
+
+    /// Sets the members of the `KeyAlgorithm` dictionary (or the dictionary
+    /// derived from it) describing this algorithm on `object`.
+    #[allow(unsafe_code)]
+    unsafe fn reflect(&self, cx: *mut JSContext, object: HandleObject) {
+        set_property(cx, object, "name", &DOMString::from(self.name()));
+        match *self {
+            KeyAlgorithm::Aes { length, .. } => {
+                set_property(cx, object, "length", &length);
+            },
+            KeyAlgorithm::Hmac { hash, length } => {
+                reflect_hash(cx, object, hash);
+                set_property(cx, object, "length", &length);
+            },
+            KeyAlgorithm::Rsa { hash, modulus_length, ref public_exponent, .. } => {
+                reflect_hash(cx, object, hash);
+                set_property(cx, object, "modulusLength", &modulus_length);
+                rooted!(in(cx) let exponent = JS_NewUint8Array(cx, public_exponent.len() as u32));
+                assert!(!exponent.is_null());
+                array_buffer_view_data::<u8>(exponent.get()).unwrap().copy_from_slice(public_exponent);
+                rooted!(in(cx) let exponent = ObjectValue(&*exponent.get()));
+                set_dictionary_property(cx, object, "publicExponent", exponent.handle()).unwrap();
+            },
+            KeyAlgorithm::Ec { curve, .. } => {
+                set_property(cx, object, "namedCurve", &DOMString::from(curve.name()));
+            },
+        }
+    }
+}
+
+#[allow(unsafe_code)]
+unsafe fn set_property<T: ToJSValConvertible>(cx: *mut JSContext, object: HandleObject, name: &str, value: &T) {
+    rooted!(in(cx) let mut js_value = UndefinedValue());
+    value.to_jsval(cx, js_value.handle_mut());
+    set_dictionary_property(cx, object, name, js_value.handle()).unwrap();
+}
+
+#[allow(unsafe_code)]
+unsafe fn reflect_hash(cx: *mut JSContext, object: HandleObject, hash: HashAlgorithm) {
+    rooted!(in(cx) let hash_object = JS_NewPlainObject(cx));
+    assert!(!hash_object.is_null());
+    set_property(cx, hash_object.handle(), "name", &DOMString::from(hash.name()));
+    rooted!(in(cx) let hash_object = ObjectValue(&*hash_object.get()));
+    set_dictionary_property(cx, object, "hash", hash_object.handle()).unwrap();
+}
+
+/// The key material backing a `CryptoKey`.
+/// https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-handle
+#[derive(HeapSizeOf)]
+pub enum KeyHandle {
+    /// The raw bytes of an AES or HMAC key.
+    Secret(Vec<u8>),
+    RsaPrivate(RsaPrivateKey),
+    RsaPublic(RsaPublicKey),
+    EcPrivate(EcPrivateKey),
+    EcPublic(EcPublicKey),
+}
+
+no_jsmanaged_fields!(KeyHandle);
+
+// https://w3c.github.io/webcrypto/#cryptokey-interface
+#[dom_struct]
+pub struct CryptoKey {
+    reflector_: Reflector,
+    key_type: KeyType,
+    extractable: bool,
+    algorithm: KeyAlgorithm,
+    usages: Vec<KeyUsage>,
+    handle: KeyHandle,
+}
+
+impl CryptoKey {
+    fn new_inherited(key_type: KeyType,
+                     extractable: bool,
+                     algorithm: KeyAlgorithm,
+                     usages: Vec<KeyUsage>,
+                     handle: KeyHandle)
+                     -> CryptoKey {
+        CryptoKey {
+            reflector_: Reflector::new(),
+            key_type: key_type,
+            extractable: extractable,
+            algorithm: algorithm,
+            usages: usages,
+            handle: handle,
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               key_type: KeyType,
+               extractable: bool,
+               algorithm: KeyAlgorithm,
+               usages: Vec<KeyUsage>,
+               handle: KeyHandle)
+               -> Root<CryptoKey> {
+        reflect_dom_object(box CryptoKey::new_inherited(key_type, extractable, algorithm, usages, handle),
+                           global,
+                           CryptoKeyBinding::Wrap)
+    }
+
+    pub fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+
+    pub fn algorithm(&self) -> &KeyAlgorithm {
+        &self.algorithm
+    }
+
+    pub fn handle(&self) -> &KeyHandle {
+        &self.handle
+    }
+
+    pub fn has_usage(&self, usage: KeyUsage) -> bool {
+        self.usages.contains(&usage)
+    }
+}
+
+impl CryptoKeyMethods for CryptoKey {
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-type
+    fn Type(&self) -> KeyType {
+        self.key_type
+    }
+
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-extractable
+    fn Extractable(&self) -> bool {
+        self.extractable
+    }
+
+    // FIXME: the spec requires the same object to be returned every time.
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-algorithm
+    fn Algorithm(&self, cx: *mut JSContext) -> JSVal {
+        unsafe {
+            rooted!(in(cx) let object = JS_NewPlainObject(cx));
+            assert!(!object.is_null());
+            self.algorithm.reflect(cx, object.handle());
+            ObjectValue(&*object.get())
+        }
+    }
+
+    // FIXME: the spec requires the same frozen array to be returned every time.
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/webcrypto/#dom-cryptokey-usages
+    fn Usages(&self, cx: *mut JSContext) -> JSVal {
+        rooted!(in(cx) let mut usages = UndefinedValue());
+        unsafe { self.usages.to_jsval(cx, usages.handle_mut()) };
+        usages.get()
+    }
+}
@@ -218,9 +218,10 @@ impl CSSStyleDeclarationMethods for CSSStyleDeclaration {
 
         // Step 6
         let window = window_from_node(&*self.owner);
+        let quirks_mode = self.owner.upcast::<Node>().owner_doc().style_quirks_mode();
+        let extra_data = ParserContextExtraData::for_quirks_mode(quirks_mode);
         let declarations =
-            parse_one_declaration(&property, &value, &window.get_url(), window.css_error_reporter(),
-                                  ParserContextExtraData::default());
+            parse_one_declaration(&property, &value, &window.get_url(), window.css_error_reporter(), extra_data);
 
         // Step 7
         let declarations = if let Ok(declarations) = declarations {
@@ -361,8 +362,9 @@ impl CSSStyleDeclarationMethods for CSSStyleDeclaration {
         }
 
         // Step 3
-        let decl_block = parse_style_attribute(&value, &window.get_url(), window.css_error_reporter(),
-                                               ParserContextExtraData::default());
+        let quirks_mode = self.owner.upcast::<Node>().owner_doc().style_quirks_mode();
+        let extra_data = ParserContextExtraData::for_quirks_mode(quirks_mode);
+        let decl_block = parse_style_attribute(&value, &window.get_url(), window.css_error_reporter(), extra_data);
         *element.style_attribute().borrow_mut() = if decl_block.declarations.is_empty() {
             None // Step 2
         } else {
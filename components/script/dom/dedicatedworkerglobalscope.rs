@@ -21,6 +21,7 @@ use dom::errorevent::ErrorEvent;
 use dom::event::{Event, EventBubbles, EventCancelable};
 use dom::eventdispatcher::EventStatus;
 use dom::eventtarget::EventTarget;
+use dom::imagebitmap::ImageBitmap;
 use dom::messageevent::MessageEvent;
 use dom::worker::{TrustedWorkerAddress, WorkerErrorHandler, WorkerMessageHandler};
 use dom::workerglobalscope::WorkerGlobalScope;
@@ -403,8 +404,13 @@ unsafe extern "C" fn interrupt_callback(cx: *mut JSContext) -> bool {
 
 impl DedicatedWorkerGlobalScopeMethods for DedicatedWorkerGlobalScope {
     // https://html.spec.whatwg.org/multipage/#dom-dedicatedworkerglobalscope-postmessage
-    fn PostMessage(&self, cx: *mut JSContext, message: HandleValue) -> ErrorResult {
-        let data = try!(StructuredCloneData::write(cx, message));
+    fn PostMessage(&self,
+                   cx: *mut JSContext,
+                   message: HandleValue,
+                   transfer: Option<Vec<Root<ImageBitmap>>>)
+                   -> ErrorResult {
+        let transfer = transfer.unwrap_or_default();
+        let data = try!(StructuredCloneData::write_with_transfer(cx, message, &transfer));
         let worker = self.worker.borrow().as_ref().unwrap().clone();
         self.parent_sender
             .send(CommonScriptMsg::RunnableMsg(WorkerEvent,
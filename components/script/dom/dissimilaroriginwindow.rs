@@ -0,0 +1,84 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::DissimilarOriginWindowBinding;
+use dom::bindings::codegen::Bindings::DissimilarOriginWindowBinding::DissimilarOriginWindowMethods;
+use dom::bindings::error::{Error, ErrorResult};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::bindings::structuredclone::StructuredCloneData;
+use dom::window::Window;
+use js::jsapi::{HandleValue, JSContext};
+use msg::constellation_msg::PipelineId;
+use script_traits::ScriptMsg as ConstellationMsg;
+use url::Url;
+
+/// A window whose document lives in another script thread, as seen from `window`.
+#[dom_struct]
+pub struct DissimilarOriginWindow {
+    reflector_: Reflector,
+    /// The window this object is accessed from.
+    window: JS<Window>,
+    /// The pipeline of the window this object stands for.
+    pipeline_id: PipelineId,
+}
+
+impl DissimilarOriginWindow {
+    fn new_inherited(window: &Window, pipeline_id: PipelineId) -> DissimilarOriginWindow {
+        DissimilarOriginWindow {
+            reflector_: Reflector::new(),
+            window: JS::from_ref(window),
+            pipeline_id: pipeline_id,
+        }
+    }
+
+    pub fn new(window: &Window, pipeline_id: PipelineId) -> Root<DissimilarOriginWindow> {
+        reflect_dom_object(box DissimilarOriginWindow::new_inherited(window, pipeline_id),
+                           GlobalRef::Window(window),
+                           DissimilarOriginWindowBinding::Wrap)
+    }
+
+    pub fn pipeline_id(&self) -> PipelineId {
+        self.pipeline_id
+    }
+}
+
+impl DissimilarOriginWindowMethods for DissimilarOriginWindow {
+    // https://html.spec.whatwg.org/multipage/#dom-window
+    fn Window(&self) -> Root<DissimilarOriginWindow> {
+        Root::from_ref(self)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-self
+    fn Self_(&self) -> Root<DissimilarOriginWindow> {
+        Root::from_ref(self)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-window-postmessage
+    fn PostMessage(&self, cx: *mut JSContext, message: HandleValue, origin: DOMString) -> ErrorResult {
+        // Step 3-5.
+        let origin = match &origin[..] {
+            "*" => None,
+            "/" => {
+                // TODO(#12715): Should be the origin of the incumbent settings
+                //               object, not the accessing window's.
+                Some(self.window.get_url())
+            },
+            url => match Url::parse(&url) {
+                Ok(url) => Some(url),
+                Err(_) => return Err(Error::Syntax),
+            }
+        };
+
+        // Step 1-2, 6-8.
+        let data = try!(StructuredCloneData::write(cx, message)).move_to_arraybuffer();
+
+        // Step 9.
+        let msg = ConstellationMsg::PostMessage(self.pipeline_id, origin, data);
+        self.window.constellation_chan().send(msg).unwrap();
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use document_loader::{DocumentLoader, LoadType};
 use dom::activation::{ActivationSource, synthetic_click_activation};
 use dom::attr::Attr;
 use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
 use dom::bindings::codegen::Bindings::DOMRectBinding::DOMRectMethods;
 use dom::bindings::codegen::Bindings::DocumentBinding;
 use dom::bindings::codegen::Bindings::DocumentBinding::{DocumentMethods, DocumentReadyState};
@@ -17,8 +18,11 @@ use dom::bindings::codegen::Bindings::EventHandlerBinding::OnErrorEventHandlerNo
 use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use dom::bindings::codegen::Bindings::NodeFilterBinding::NodeFilter;
 use dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
 use dom::bindings::codegen::Bindings::TouchBinding::TouchMethods;
 use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::codegen::Bindings::XPathNSResolverBinding::XPathNSResolver;
 use dom::bindings::codegen::UnionTypes::NodeOrString;
 use dom::bindings::error::{Error, ErrorResult, Fallible};
 use dom::bindings::global::GlobalRef;
@@ -32,6 +36,7 @@ use dom::bindings::str::{DOMString, USVString};
 use dom::bindings::xmlname::{namespace_from_domstring, validate_and_extract, xml_name_type};
 use dom::bindings::xmlname::XMLName::InvalidXMLName;
 use dom::browsingcontext::BrowsingContext;
+use dom::characterdata::CharacterData;
 use dom::closeevent::CloseEvent;
 use dom::comment::Comment;
 use dom::customevent::CustomEvent;
@@ -53,29 +58,34 @@ use dom::htmlbaseelement::HTMLBaseElement;
 use dom::htmlbodyelement::HTMLBodyElement;
 use dom::htmlcollection::{CollectionFilter, HTMLCollection};
 use dom::htmlelement::HTMLElement;
+use dom::htmldialogelement::HTMLDialogElement;
 use dom::htmlembedelement::HTMLEmbedElement;
 use dom::htmlformelement::HTMLFormElement;
 use dom::htmlheadelement::HTMLHeadElement;
 use dom::htmlhtmlelement::HTMLHtmlElement;
 use dom::htmliframeelement::HTMLIFrameElement;
 use dom::htmlimageelement::HTMLImageElement;
+use dom::htmlinputelement::HTMLInputElement;
 use dom::htmllinkelement::HTMLLinkElement;
 use dom::htmlmetaelement::HTMLMetaElement;
 use dom::htmlscriptelement::HTMLScriptElement;
 use dom::htmlstyleelement::HTMLStyleElement;
+use dom::htmltextareaelement::HTMLTextAreaElement;
 use dom::htmltitleelement::HTMLTitleElement;
 use dom::keyboardevent::KeyboardEvent;
 use dom::location::Location;
 use dom::messageevent::MessageEvent;
 use dom::mouseevent::MouseEvent;
-use dom::node::{self, CloneChildrenFlag, Node, NodeDamage, window_from_node};
+use dom::node::{self, CloneChildrenFlag, Node, NodeDamage, document_from_node, window_from_node};
 use dom::nodeiterator::NodeIterator;
 use dom::nodelist::NodeList;
 use dom::pagetransitionevent::PageTransitionEvent;
+use dom::performanceservertiming::ServerTimingMetric;
 use dom::popstateevent::PopStateEvent;
 use dom::processinginstruction::ProcessingInstruction;
 use dom::progressevent::ProgressEvent;
-use dom::range::Range;
+use dom::range::{Range, bp_position};
+use dom::selection::{Selection, rendered_index_to_offset};
 use dom::storageevent::StorageEvent;
 use dom::stylesheetlist::StyleSheetList;
 use dom::text::Text;
@@ -86,6 +96,9 @@ use dom::treewalker::TreeWalker;
 use dom::uievent::UIEvent;
 use dom::webglcontextevent::WebGLContextEvent;
 use dom::window::{ReflowReason, Window};
+use dom::xpathevaluator;
+use dom::xpathexpression::XPathExpression;
+use dom::xpathresult::XPathResult;
 use encoding::EncodingRef;
 use encoding::all::UTF_8;
 use euclid::point::Point2D;
@@ -95,8 +108,8 @@ use js::jsapi::{JSContext, JSObject, JSRuntime};
 use js::jsapi::JS_GetRuntime;
 use msg::constellation_msg::{ALT, CONTROL, SHIFT, SUPER};
 use msg::constellation_msg::{Key, KeyModifiers, KeyState};
-use msg::constellation_msg::{PipelineId, ReferrerPolicy};
-use net_traits::{AsyncResponseTarget, FetchResponseMsg, IpcSend, PendingAsyncLoad};
+use msg::constellation_msg::{LoadData, PipelineId, ReferrerPolicy};
+use net_traits::{AsyncResponseTarget, FetchResponseMsg, IpcSend, PendingAsyncLoad, ResourceFetchTiming};
 use net_traits::CookieSource::NonHTTP;
 use net_traits::CoreResourceMsg::{GetCookiesForUrl, SetCookiesForUrl};
 use net_traits::request::RequestInit;
@@ -104,6 +117,7 @@ use net_traits::response::HttpsState;
 use num_traits::ToPrimitive;
 use origin::Origin;
 use parse::{MutNullableParserField, ParserRef, ParserRoot};
+use parse::encoding::EncodingConfidence;
 use script_layout_interface::message::{Msg, ReflowQueryType};
 use script_thread::{MainThreadScriptMsg, Runnable};
 use script_traits::{AnimationState, MouseButton, MouseEventType, MozBrowserEvent};
@@ -115,23 +129,27 @@ use std::borrow::ToOwned;
 use std::boxed::FnBox;
 use std::cell::Cell;
 use std::collections::HashMap;
+use std::cmp::Ordering;
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::default::Default;
 use std::iter::once;
 use std::mem;
+use std::ops::Range as ByteRange;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use string_cache::{Atom, QualName};
 use style::attr::AttrValue;
-use style::context::ReflowGoal;
+use style::context::{QuirksMode as StyleQuirksMode, ReflowGoal};
 use style::refcell::{Ref, RefMut};
 use style::selector_impl::ElementSnapshot;
 use style::str::{split_html_space_chars, str_join};
 use style::stylesheets::Stylesheet;
+use textinput::{is_control_key, len_of_first_n_code_units};
 use time;
 use url::Url;
 use url::percent_encoding::percent_decode;
+use util::compat;
 use util::prefs::PREFS;
 
 #[derive(JSTraceable, PartialEq, HeapSizeOf)]
@@ -158,8 +176,10 @@ pub struct Document {
     content_type: DOMString,
     last_modified: Option<String>,
     encoding: Cell<EncodingRef>,
+    /// https://html.spec.whatwg.org/multipage/#concept-encoding-confidence
+    encoding_confidence: Cell<EncodingConfidence>,
     is_html_document: bool,
-    url: Url,
+    url: DOMRefCell<Url>,
     quirks_mode: Cell<QuirksMode>,
     /// Caches for the getElement methods
     id_map: DOMRefCell<HashMap<Atom, Vec<JS<Element>>>>,
@@ -239,6 +259,10 @@ pub struct Document {
     dom_complete: Cell<u64>,
     load_event_start: Cell<u64>,
     load_event_end: Cell<u64>,
+    /// Network timestamps recorded while fetching this document.
+    fetch_timing: Cell<ResourceFetchTiming>,
+    /// The metrics of the Server-Timing headers of this document's response.
+    server_timing: DOMRefCell<Vec<ServerTimingMetric>>,
     /// https://html.spec.whatwg.org/multipage/#concept-document-https-state
     https_state: Cell<HttpsState>,
     touchpad_pressure_phase: Cell<TouchpadPressurePhase>,
@@ -250,9 +274,23 @@ pub struct Document {
     referrer: Option<String>,
     /// https://html.spec.whatwg.org/multipage/#target-element
     target_element: MutNullableHeap<JS<Element>>,
+    /// https://fullscreen.spec.whatwg.org/#top-layer
+    top_layer: DOMRefCell<Vec<JS<Element>>>,
     /// https://w3c.github.io/uievents/#event-type-dblclick
     #[ignore_heap_size_of = "Defined in std"]
     last_click_info: DOMRefCell<Option<(Instant, Point2D<f32>)>>,
+    /// https://w3c.github.io/selection-api/#dfn-selection
+    selection: MutNullableHeap<JS<Selection>>,
+    /// The byte range of the text of each text node that is painted as selected, keyed by the
+    /// address of the node.
+    selected_text: DOMRefCell<HashMap<usize, (usize, usize)>>,
+    /// The text nodes that have an entry in `selected_text`.
+    selected_text_nodes: DOMRefCell<Vec<JS<Node>>>,
+    /// Whether the selection is being extended by moving the mouse with its left button down.
+    selecting: Cell<bool>,
+    /// The URLs of `<link rel=prefetch>` elements, which are prefetched once the document
+    /// has loaded.
+    pending_prefetches: DOMRefCell<Vec<Url>>,
 }
 
 #[derive(JSTraceable, HeapSizeOf)]
@@ -364,16 +402,30 @@ impl Document {
     }
 
     // https://dom.spec.whatwg.org/#concept-document-url
-    pub fn url(&self) -> &Url {
-        &self.url
+    pub fn url(&self) -> Url {
+        self.url.borrow().clone()
+    }
+
+    pub fn set_url(&self, url: Url) {
+        *self.url.borrow_mut() = url;
     }
 
     // https://html.spec.whatwg.org/multipage/#fallback-base-url
     pub fn fallback_base_url(&self) -> Url {
-        // Step 1: iframe srcdoc (#4767).
+        // Step 1: iframe srcdoc.
+        if self.is_srcdoc_document() {
+            if let Some(iframe) = self.browsing_context().and_then(|context| context.frame_element()) {
+                return document_from_node(iframe).base_url();
+            }
+        }
         // Step 2: about:blank with a creator browsing context.
         // Step 3.
-        self.url().clone()
+        self.url()
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#an-iframe-srcdoc-document
+    pub fn is_srcdoc_document(&self) -> bool {
+        self.url().as_str() == "about:srcdoc"
     }
 
     // https://html.spec.whatwg.org/multipage/#document-base-url
@@ -425,10 +477,22 @@ impl Document {
     }
 
     pub fn set_quirks_mode(&self, mode: QuirksMode) {
+        let old_mode = self.quirks_mode.get();
         self.quirks_mode.set(mode);
 
-        if mode == Quirks {
-            self.window.layout_chan().send(Msg::SetQuirksMode).unwrap();
+        // Documents without a browsing context share the layout thread of the
+        // one whose window created them, so must not change its mode.
+        if mode != old_mode && self.browsing_context().is_some() {
+            self.window.layout_chan().send(Msg::SetQuirksMode(self.style_quirks_mode())).unwrap();
+        }
+    }
+
+    /// The mode of this document, as the style system understands it.
+    pub fn style_quirks_mode(&self) -> StyleQuirksMode {
+        match self.quirks_mode.get() {
+            Quirks => StyleQuirksMode::Quirks,
+            LimitedQuirks => StyleQuirksMode::LimitedQuirks,
+            NoQuirks => StyleQuirksMode::NoQuirks,
         }
     }
 
@@ -440,6 +504,37 @@ impl Document {
         self.encoding.set(encoding);
     }
 
+    pub fn set_encoding_confidence(&self, confidence: EncodingConfidence) {
+        self.encoding_confidence.set(confidence);
+    }
+
+    /// Switches to the encoding that a `<meta>` element found by the parser declares, after
+    /// the document started to be decoded with a guessed one, by loading the document again.
+    /// https://html.spec.whatwg.org/multipage/#changing-the-encoding-while-parsing
+    pub fn change_the_encoding(&self, encoding: EncodingRef) {
+        if self.encoding_confidence.get() != EncodingConfidence::Tentative {
+            return;
+        }
+
+        // Steps 1-3.
+        let current = self.encoding.get().whatwg_name();
+        if current == Some("utf-16be") || current == Some("utf-16le") ||
+           current == encoding.whatwg_name() {
+            self.encoding_confidence.set(EncodingConfidence::Certain);
+            return;
+        }
+
+        // Step 5.
+        if let Some(parser) = self.get_current_parser() {
+            parser.r().abort();
+        }
+        let mut load_data = LoadData::new(self.url(), self.get_referrer_policy(), None);
+        load_data.encoding = encoding.whatwg_name().map(str::to_owned);
+        self.window.main_thread_script_chan()
+                   .send(MainThreadScriptMsg::Navigate(self.window.pipeline_id(), load_data, true))
+                   .unwrap();
+    }
+
     pub fn content_and_heritage_changed(&self, node: &Node, damage: NodeDamage) {
         node.force_dirty_ancestors(damage);
     }
@@ -615,7 +710,7 @@ impl Document {
 
     /// Request that the given element receive focus once the current transaction is complete.
     pub fn request_focus(&self, elem: &Element) {
-        if elem.is_focusable_area() {
+        if elem.is_focusable_area() && !self.is_inert(elem.upcast()) {
             self.possibly_focused.set(Some(elem))
         }
     }
@@ -687,6 +782,10 @@ impl Document {
         };
         debug!("{}: at {:?}", mouse_event_type_string, client_point);
 
+        if let MouseEventType::MouseUp = mouse_event_type {
+            self.selecting.set(false);
+        }
+
         let node = match self.window.hit_test_query(client_point, false) {
             Some(node_address) => {
                 debug!("node address is {:?}", node_address);
@@ -706,6 +805,11 @@ impl Document {
             },
         };
 
+        // Inert nodes can't be the target of user interaction events.
+        if self.is_inert(el.upcast()) {
+            return;
+        }
+
         // If the target is an iframe, forward the event to the child document.
         if let Some(iframe) = el.downcast::<HTMLIFrameElement>() {
             if let Some(pipeline_id) = iframe.pipeline_id() {
@@ -721,6 +825,7 @@ impl Document {
             return;
         }
 
+        let hit_node = node;
         let node = el.upcast::<Node>();
         debug!("{} on {:?}", mouse_event_type_string, node.debug_str());
         // Prevent click event if form control element is disabled.
@@ -767,6 +872,15 @@ impl Document {
 
                 let target = node.upcast();
                 event.fire(target);
+
+                // https://w3c.github.io/selection-api/#user-interactions
+                if let MouseButton::Left = button {
+                    if !event.DefaultPrevented() && !self.is_text_control(&el) {
+                        let (node, offset) = self.selection_point_at(&hit_node, client_point);
+                        let _ = self.selection().Collapse(Some(&*node), offset);
+                        self.selecting.set(true);
+                    }
+                }
             },
             MouseEventType::MouseUp => {
                 if let Some(a) = activatable {
@@ -900,13 +1014,30 @@ impl Document {
     }
 
     pub fn fire_mouse_event(&self, client_point: Point2D<f32>, target: &EventTarget, event_name: String) {
+        self.fire_mouse_event_with_flags(client_point, target, event_name,
+                                         EventBubbles::Bubbles, EventCancelable::Cancelable);
+    }
+
+    /// Fire a `mouseenter` or `mouseleave` event, which neither bubble nor can be canceled.
+    /// https://w3c.github.io/uievents/#event-type-mouseenter
+    fn fire_mouse_boundary_event(&self, client_point: Point2D<f32>, target: &EventTarget, event_name: &str) {
+        self.fire_mouse_event_with_flags(client_point, target, event_name.to_owned(),
+                                         EventBubbles::DoesNotBubble, EventCancelable::NotCancelable);
+    }
+
+    fn fire_mouse_event_with_flags(&self,
+                                   client_point: Point2D<f32>,
+                                   target: &EventTarget,
+                                   event_name: String,
+                                   bubbles: EventBubbles,
+                                   cancelable: EventCancelable) {
         let client_x = client_point.x.to_i32().unwrap_or(0);
         let client_y = client_point.y.to_i32().unwrap_or(0);
 
         let mouse_event = MouseEvent::new(&self.window,
                                           DOMString::from(event_name),
-                                          EventBubbles::Bubbles,
-                                          EventCancelable::Cancelable,
+                                          bubbles,
+                                          cancelable,
                                           Some(&self.window),
                                           0i32,
                                           client_x,
@@ -937,12 +1068,23 @@ impl Document {
             Some(client_point) => client_point,
         };
 
-        let maybe_new_target = self.window.hit_test_query(client_point, true).and_then(|address| {
-            let node = node::from_untrusted_node_address(js_runtime, address);
+        let hit_node = self.window.hit_test_query(client_point, true).map(|address| {
+            node::from_untrusted_node_address(js_runtime, address)
+        });
+
+        // Extend the selection to the point under the mouse while its left button is down.
+        if let Some(ref node) = hit_node {
+            if self.selecting.get() {
+                let (node, offset) = self.selection_point_at(node, client_point);
+                let _ = self.selection().Extend(&node, offset);
+            }
+        }
+
+        let maybe_new_target = hit_node.and_then(|node| {
             node.inclusive_ancestors()
                 .filter_map(Root::downcast::<Element>)
                 .next()
-        });
+        }).and_then(|target| if self.is_inert(target.upcast()) { None } else { Some(target) });
 
         // Send mousemove event to topmost target, and forward it if it's an iframe
         if let Some(ref new_target) = maybe_new_target {
@@ -974,6 +1116,17 @@ impl Document {
             _   => false,
         };
 
+        // The elements the mouse has left, innermost first, and those it has
+        // entered, outermost first.
+        let left = prev_mouse_over_target.get().map_or(vec![], |old_target| {
+            elements_not_containing(&old_target, maybe_new_target.r())
+        });
+        let mut entered = maybe_new_target.as_ref().map_or(vec![], |new_target| {
+            elements_not_containing(new_target, prev_mouse_over_target.get().r())
+        });
+        entered.reverse();
+        let legacy_boundary_order = compat::legacy_mouse_boundary_order_enabled();
+
         // Here we know the target has changed, so we must update the state,
         // dispatch mouseout to the previous one, mouseover to the new one,
         if let Some(old_target) = prev_mouse_over_target.get() {
@@ -991,8 +1144,12 @@ impl Document {
             // Remove hover state to old target and its parents
             self.fire_mouse_event(client_point, old_target.upcast(), "mouseout".to_owned());
 
-            // TODO: Fire mouseleave here only if the old target is
-            // not an ancestor of the new target.
+        }
+
+        if !legacy_boundary_order {
+            for element in &left {
+                self.fire_mouse_boundary_event(client_point, element.upcast(), "mouseleave");
+            }
         }
 
         if let Some(ref new_target) = maybe_new_target {
@@ -1007,8 +1164,17 @@ impl Document {
             }
 
             self.fire_mouse_event(client_point, &new_target.upcast(), "mouseover".to_owned());
+        }
 
-            // TODO: Fire mouseenter here.
+        // Legacy engines fired the boundary events only once both mouseout and
+        // mouseover had been dispatched.
+        if legacy_boundary_order {
+            for element in &left {
+                self.fire_mouse_boundary_event(client_point, element.upcast(), "mouseleave");
+            }
+        }
+        for element in &entered {
+            self.fire_mouse_boundary_event(client_point, element.upcast(), "mouseenter");
         }
 
         // Store the current mouse over target for next frame.
@@ -1046,6 +1212,9 @@ impl Document {
                 }
             },
         };
+        if self.is_inert(el.upcast()) {
+            return false;
+        }
         let target = Root::upcast::<EventTarget>(el);
         let window = &*self.window;
 
@@ -1213,6 +1382,11 @@ impl Document {
             constellation.send(ConstellationMsg::SendKeyEvent(ch, key, state, modifiers)).unwrap();
         }
 
+        let focused_on_text_control = focused.as_ref().map_or(false, |focused| self.is_text_control(focused));
+        if !prevented && state != KeyState::Released && !focused_on_text_control {
+            self.handle_selection_key(ch, key, modifiers);
+        }
+
         // This behavior is unspecced
         // We are supposed to dispatch synthetic click activation for Space and/or Return,
         // however *when* we do it is up to us
@@ -1246,6 +1420,119 @@ impl Document {
                            ReflowReason::KeyEvent);
     }
 
+    /// Applies the default action of the keys that change or copy the selection.
+    fn handle_selection_key(&self, ch: Option<char>, key: Key, modifiers: KeyModifiers) {
+        match (ch, key) {
+            (Some('a'), _) if is_control_key(modifiers) => {
+                if let Some(body) = self.GetBody() {
+                    let _ = self.selection().SelectAllChildren(body.upcast());
+                }
+            },
+            (Some('c'), _) if is_control_key(modifiers) => {
+                let text = self.selection().Stringifier();
+                if !text.is_empty() {
+                    let msg = ConstellationMsg::SetClipboardContents(String::from(text));
+                    self.window.constellation_chan().send(msg).unwrap();
+                }
+            },
+            (_, Key::Left) if modifiers.contains(SHIFT) => self.selection().extend_by_character(false),
+            (_, Key::Right) if modifiers.contains(SHIFT) => self.selection().extend_by_character(true),
+            _ => (),
+        }
+    }
+
+    /// Whether the element handles its own text selection.
+    fn is_text_control(&self, element: &Element) -> bool {
+        element.is::<HTMLInputElement>() || element.is::<HTMLTextAreaElement>()
+    }
+
+    /// https://w3c.github.io/selection-api/#dfn-selection
+    pub fn selection(&self) -> Root<Selection> {
+        self.selection.or_init(|| Selection::new(self))
+    }
+
+    /// https://w3c.github.io/selection-api/#selectionchange-event
+    pub fn selection_changed(&self) {
+        self.update_selected_text();
+        self.window.dom_manipulation_task_source().queue_simple_event(self.upcast(),
+                                                                      Atom::from("selectionchange"),
+                                                                      &self.window);
+    }
+
+    /// The boundary point of the selection for a point over the given node: the character of a
+    /// text node under the point, or the start of any other node.
+    fn selection_point_at(&self, node: &Node, client_point: Point2D<f32>) -> (Root<Node>, u32) {
+        let text = match node.downcast::<Text>() {
+            Some(text) => text.upcast::<CharacterData>().Data(),
+            None => return (Root::from_ref(node), 0),
+        };
+        let offset = self.window.text_index_query(node.to_trusted_node_address(), client_point)
+                                .map_or(0, |index| rendered_index_to_offset(&text, index));
+        (Root::from_ref(node), offset)
+    }
+
+    /// Recomputes the parts of text nodes that are painted as selected, and marks the nodes
+    /// whose selected part changed as needing to be laid out again.
+    pub fn update_selected_text(&self) {
+        let range = self.selection.get()
+                                  .and_then(|selection| selection.range())
+                                  .and_then(|range| if range.Collapsed() { None } else { Some(range) });
+        if range.is_none() && self.selected_text_nodes.borrow().is_empty() {
+            return;
+        }
+
+        let mut selected_text = HashMap::new();
+        let mut selected_text_nodes: Vec<Root<Node>> = vec![];
+        if let Some(range) = range {
+            let (start, start_offset) = (range.StartContainer(), range.StartOffset());
+            let (end, end_offset) = (range.EndContainer(), range.EndOffset());
+            for node in range.CommonAncestorContainer().traverse_preorder() {
+                let text = match node.downcast::<Text>() {
+                    Some(text) => text.upcast::<CharacterData>().Data(),
+                    None => continue,
+                };
+                let len = node.len();
+                let begin = if node == start {
+                    start_offset
+                } else if bp_position(&node, 0, &start, start_offset) == Some(Ordering::Less) {
+                    continue;
+                } else {
+                    0
+                };
+                let finish = if node == end {
+                    end_offset
+                } else if bp_position(&node, len, &end, end_offset) == Some(Ordering::Greater) {
+                    continue;
+                } else {
+                    len
+                };
+                if begin >= finish {
+                    continue;
+                }
+                let byte_range = (len_of_first_n_code_units(&text, begin as usize),
+                                  len_of_first_n_code_units(&text, finish as usize));
+                selected_text.insert(&*node as *const Node as usize, byte_range);
+                selected_text_nodes.push(node);
+            }
+        }
+
+        let old_text = mem::replace(&mut *self.selected_text.borrow_mut(), selected_text);
+        let old_nodes: Vec<Root<Node>> = self.selected_text_nodes.borrow().iter().map(|node| {
+            Root::from_ref(&**node)
+        }).collect();
+        *self.selected_text_nodes.borrow_mut() = selected_text_nodes.iter().map(|node| {
+            JS::from_ref(&**node)
+        }).collect();
+
+        let new_text = self.selected_text.borrow();
+        for node in old_nodes.iter().chain(selected_text_nodes.iter()) {
+            let key = &**node as *const Node as usize;
+            if old_text.get(&key) != new_text.get(&key) {
+                node.dirty(NodeDamage::OtherNodeDamage);
+            }
+        }
+    }
+
     // https://dom.spec.whatwg.org/#converting-nodes-into-a-node
     pub fn node_from_nodes_and_strings(&self,
                                        mut nodes: Vec<NodeOrString>)
@@ -1447,6 +1734,25 @@ impl Document {
         loader.fetch_async(load, request, fetch_target, self, referrer_policy);
     }
 
+    /// Prefetches `url` for a likely navigation. Prefetches are low priority, so they
+    /// wait until the document has loaded.
+    /// https://html.spec.whatwg.org/multipage/#link-type-prefetch
+    pub fn prefetch(&self, url: Url) {
+        if self.load_event_end.get() == 0 {
+            self.pending_prefetches.borrow_mut().push(url);
+        } else {
+            self.loader.borrow_mut().prefetch(url, self);
+        }
+    }
+
+    /// Starts the prefetches that waited for the document to load.
+    fn start_pending_prefetches(&self) {
+        let urls = mem::replace(&mut *self.pending_prefetches.borrow_mut(), vec![]);
+        for url in urls {
+            self.loader.borrow_mut().prefetch(url, self);
+        }
+    }
+
     pub fn finish_load(&self, load: LoadType) {
         debug!("Document got finish_load: {:?}", load);
         // The parser might need the loader, so restrict the lifetime of the borrow.
@@ -1488,6 +1794,38 @@ impl Document {
         }
     }
 
+    /// Cancel the loads of the document and stop its parser, as when the user
+    /// stops loading the page.
+    /// https://html.spec.whatwg.org/multipage/#abort-a-document
+    pub fn abort(&self) {
+        // TODO: Steps 1 and 2, abort the documents of the child browsing contexts.
+
+        // Step 3
+        self.loader.borrow_mut().cancel_all_loads();
+
+        // Step 4
+        if let Some(parser) = self.get_current_parser() {
+            // https://html.spec.whatwg.org/multipage/#abort-a-parser
+            // Steps 1 and 2
+            parser.r().abort();
+            self.set_current_parser(None);
+            self.pending_parsing_blocking_script.set(None);
+            // Step 3
+            self.set_ready_state(DocumentReadyState::Interactive);
+        }
+
+        // Step 5 of aborting a parser. The load event is never fired for an
+        // aborted document, unless it is already queued.
+        if self.loader.borrow().events_inhibited() {
+            return;
+        }
+        self.loader.borrow_mut().inhibit_events();
+        if self.ready_state.get() != DocumentReadyState::Complete {
+            self.set_ready_state(DocumentReadyState::Complete);
+        }
+        self.notify_constellation_load();
+    }
+
     /// If document parsing is blocked on a script, and that script is ready to run,
     /// execute it.
     /// https://html.spec.whatwg.org/multipage/#ready-to-be-parser-executed
@@ -1630,6 +1968,29 @@ impl Document {
         self.load_event_end.get()
     }
 
+    pub fn fetch_timing(&self) -> ResourceFetchTiming {
+        self.fetch_timing.get()
+    }
+
+    pub fn set_fetch_timing(&self, timing: ResourceFetchTiming) {
+        self.fetch_timing.set(timing);
+    }
+
+    pub fn server_timing(&self) -> Vec<ServerTimingMetric> {
+        self.server_timing.borrow().clone()
+    }
+
+    pub fn set_server_timing(&self, metrics: Vec<ServerTimingMetric>) {
+        *self.server_timing.borrow_mut() = metrics;
+    }
+
+    /// Records that the last byte of this document's response was received.
+    pub fn set_response_end(&self) {
+        let mut timing = self.fetch_timing.get();
+        timing.response_end = time::precise_time_ns();
+        self.fetch_timing.set(timing);
+    }
+
     // https://html.spec.whatwg.org/multipage/#fire-a-focus-event
     fn fire_focus_event(&self, focus_event_type: FocusEventType, node: &Node, related_target: Option<&EventTarget>) {
         let (event_name, does_bubble) = match focus_event_type {
@@ -1651,7 +2012,7 @@ impl Document {
 
     /// https://html.spec.whatwg.org/multipage/#cookie-averse-document-object
     pub fn is_cookie_averse(&self) -> bool {
-        self.browsing_context.is_none() || !url_has_network_scheme(&self.url)
+        self.browsing_context.is_none() || !url_has_network_scheme(&self.url.borrow())
     }
 
     pub fn nodes_from_point(&self, client_point: &Point2D<f32>) -> Vec<UntrustedNodeAddress> {
@@ -1675,6 +2036,7 @@ pub trait LayoutDocumentHelpers {
     unsafe fn drain_modified_elements(&self) -> Vec<(LayoutJS<Element>, ElementSnapshot)>;
     unsafe fn needs_paint_from_layout(&self);
     unsafe fn will_paint(&self);
+    unsafe fn selected_text_for_layout(&self, node: LayoutJS<Node>) -> Option<ByteRange<usize>>;
 }
 
 #[allow(unsafe_code)]
@@ -1701,6 +2063,12 @@ impl LayoutDocumentHelpers for LayoutJS<Document> {
     unsafe fn will_paint(&self) {
         (*self.unsafe_get()).needs_paint.set(false)
     }
+
+    #[inline]
+    unsafe fn selected_text_for_layout(&self, node: LayoutJS<Node>) -> Option<ByteRange<usize>> {
+        let key = node.unsafe_get() as usize;
+        (*self.unsafe_get()).selected_text.borrow_for_layout().get(&key).map(|&(start, end)| start..end)
+    }
 }
 
 /// https://url.spec.whatwg.org/#network-scheme
@@ -1711,6 +2079,18 @@ fn url_has_network_scheme(url: &Url) -> bool {
     }
 }
 
+/// The inclusive ancestors of `element` that are not inclusive ancestors of
+/// `other` as well, innermost first.
+fn elements_not_containing(element: &Element, other: Option<&Element>) -> Vec<Root<Element>> {
+    element.upcast::<Node>()
+           .inclusive_ancestors()
+           .filter_map(Root::downcast::<Element>)
+           .take_while(|ancestor| {
+               other.map_or(true, |other| !ancestor.upcast::<Node>().is_inclusive_ancestor_of(other.upcast()))
+           })
+           .collect()
+}
+
 impl Document {
     pub fn new_inherited(window: &Window,
                          browsing_context: Option<&BrowsingContext>,
@@ -1733,8 +2113,17 @@ impl Document {
 
         // Incomplete implementation of Document origin specification at
         // https://html.spec.whatwg.org/multipage/#origin:document
+        let srcdoc_iframe = if url.as_str() == "about:srcdoc" {
+            browsing_context.and_then(|context| context.frame_element())
+        } else {
+            None
+        };
         let origin = if url_has_network_scheme(&url) {
             Origin::new(&url)
+        } else if let Some(iframe) = srcdoc_iframe {
+            // An iframe srcdoc document has the origin of the iframe's node document.
+            // FIXME: It should inherit its Content Security Policy too, once we have one.
+            document_from_node(iframe).origin().alias()
         } else {
             // Default to DOM standard behaviour
             Origin::opaque_identifier()
@@ -1756,11 +2145,12 @@ impl Document {
                 }),
             },
             last_modified: last_modified,
-            url: url,
+            url: DOMRefCell::new(url),
             // https://dom.spec.whatwg.org/#concept-document-quirks
             quirks_mode: Cell::new(NoQuirks),
             // https://dom.spec.whatwg.org/#concept-document-encoding
             encoding: Cell::new(UTF_8),
+            encoding_confidence: Cell::new(EncodingConfidence::Irrelevant),
             is_html_document: is_html_document == IsHTMLDocument::HTMLDocument,
             id_map: DOMRefCell::new(HashMap::new()),
             tag_map: DOMRefCell::new(HashMap::new()),
@@ -1804,13 +2194,21 @@ impl Document {
             dom_complete: Cell::new(Default::default()),
             load_event_start: Cell::new(Default::default()),
             load_event_end: Cell::new(Default::default()),
+            fetch_timing: Cell::new(Default::default()),
+            server_timing: DOMRefCell::new(vec![]),
             https_state: Cell::new(HttpsState::None),
             touchpad_pressure_phase: Cell::new(TouchpadPressurePhase::BeforeClick),
             origin: origin,
             referrer: referrer,
             referrer_policy: Cell::new(referrer_policy),
             target_element: MutNullableHeap::new(None),
+            top_layer: DOMRefCell::new(vec![]),
             last_click_info: DOMRefCell::new(None),
+            selection: MutNullableHeap::new(None),
+            selected_text: DOMRefCell::new(HashMap::new()),
+            selected_text_nodes: DOMRefCell::new(vec![]),
+            selecting: Cell::new(false),
+            pending_prefetches: DOMRefCell::new(vec![]),
         }
     }
 
@@ -1963,6 +2361,36 @@ impl Document {
         return self.referrer_policy.get();
     }
 
+    /// Adds `element` to the top of the top layer, moving it there if it was already in it.
+    /// This is shared by modal dialogs and fullscreen.
+    pub fn add_to_top_layer(&self, element: &Element) {
+        self.top_layer.borrow_mut().retain(|e| &**e != element);
+        self.top_layer.borrow_mut().push(JS::from_ref(element));
+        element.set_top_layer_state(true);
+    }
+
+    pub fn remove_from_top_layer(&self, element: &Element) {
+        self.top_layer.borrow_mut().retain(|e| &**e != element);
+        element.set_top_layer_state(false);
+    }
+
+    /// The topmost modal dialog in the top layer, which blocks the rest of the document.
+    /// https://html.spec.whatwg.org/multipage/#blocked-by-a-modal-dialog
+    pub fn active_modal_dialog(&self) -> Option<Root<Element>> {
+        self.top_layer.borrow().iter().rev()
+            .find(|element| element.is::<HTMLDialogElement>() && element.modal_state())
+            .map(|element| Root::from_ref(&**element))
+    }
+
+    /// Whether `node` is inert, because a modal dialog that does not contain it is open.
+    /// https://html.spec.whatwg.org/multipage/#inert
+    pub fn is_inert(&self, node: &Node) -> bool {
+        match self.active_modal_dialog() {
+            Some(dialog) => !dialog.upcast::<Node>().is_inclusive_ancestor_of(node),
+            None => false,
+        }
+    }
+
     pub fn set_target_element(&self, node: Option<&Element>) {
         if let Some(ref element) = self.target_element.get() {
             element.set_target_state(false);
@@ -2082,7 +2510,8 @@ impl DocumentMethods for Document {
 
     // https://dom.spec.whatwg.org/#dom-document-characterset
     fn CharacterSet(&self) -> DOMString {
-        DOMString::from(match self.encoding.get().name() {
+        let encoding = self.encoding.get();
+        DOMString::from(match encoding.whatwg_name().unwrap_or(encoding.name()) {
             "utf-8"         => "UTF-8",
             "ibm866"        => "IBM866",
             "iso-8859-2"    => "ISO-8859-2",
@@ -2380,6 +2809,15 @@ impl DocumentMethods for Document {
         Range::new_with_doc(self)
     }
 
+    // https://w3c.github.io/selection-api/#dom-document-getselection
+    fn GetSelection(&self) -> Option<Root<Selection>> {
+        if self.browsing_context().is_some() {
+            Some(self.selection())
+        } else {
+            None
+        }
+    }
+
     // https://dom.spec.whatwg.org/#dom-document-createnodeiteratorroot-whattoshow-filter
     fn CreateNodeIterator(&self,
                           root: &Node,
@@ -2426,6 +2864,30 @@ impl DocumentMethods for Document {
         TreeWalker::new(self, root, what_to_show, filter)
     }
 
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-createexpression
+    fn CreateExpression(&self,
+                        expression: DOMString,
+                        resolver: Option<Rc<XPathNSResolver>>)
+                        -> Fallible<Root<XPathExpression>> {
+        XPathExpression::create(GlobalRef::Window(&self.window), expression, resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-creatensresolver
+    fn CreateNSResolver(&self, node_resolver: &Node) -> Root<Node> {
+        Root::from_ref(node_resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-evaluate
+    fn Evaluate(&self,
+                expression: DOMString,
+                context_node: &Node,
+                resolver: Option<Rc<XPathNSResolver>>,
+                type_: u16,
+                result: Option<&XPathResult>)
+                -> Fallible<Root<XPathResult>> {
+        xpathevaluator::evaluate(GlobalRef::Window(&self.window), expression, context_node, resolver, type_, result)
+    }
+
     // https://html.spec.whatwg.org/multipage/#document.title
     fn Title(&self) -> DOMString {
         let title = self.GetDocumentElement().and_then(|root| {
@@ -2721,7 +3183,7 @@ impl DocumentMethods for Document {
 
         let url = self.url();
         let (tx, rx) = ipc::channel().unwrap();
-        let _ = self.window.resource_threads().send(GetCookiesForUrl((*url).clone(), tx, NonHTTP));
+        let _ = self.window.resource_threads().send(GetCookiesForUrl(url, tx, NonHTTP));
         let cookies = rx.recv().unwrap();
         Ok(cookies.map_or(DOMString::new(), DOMString::from))
     }
@@ -2739,7 +3201,7 @@ impl DocumentMethods for Document {
         let url = self.url();
         let _ = self.window
                     .resource_threads()
-                    .send(SetCookiesForUrl((*url).clone(), String::from(cookie), NonHTTP));
+                    .send(SetCookiesForUrl(url, String::from(cookie), NonHTTP));
         Ok(())
     }
 
@@ -3017,6 +3479,7 @@ impl DocumentProgressHandler {
                       ReflowReason::DocumentLoaded);
 
         document.notify_constellation_load();
+        document.start_pending_prefetches();
     }
 }
 
@@ -7,20 +7,31 @@ use dom::bindings::codegen::Bindings::DOMParserBinding;
 use dom::bindings::codegen::Bindings::DOMParserBinding::DOMParserMethods;
 use dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Application_xhtml_xml;
 use dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Application_xml;
+use dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Image_svg_xml;
 use dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Text_html;
 use dom::bindings::codegen::Bindings::DOMParserBinding::SupportedType::Text_xml;
 use dom::bindings::codegen::Bindings::DocumentBinding::DocumentReadyState;
+use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use dom::bindings::error::Fallible;
 use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, Root};
 use dom::bindings::reflector::{Reflector, reflect_dom_object};
 use dom::bindings::str::DOMString;
 use dom::document::{Document, IsHTMLDocument};
 use dom::document::DocumentSource;
+use dom::element::{Element, ElementCreator};
+use dom::node::Node;
+use dom::text::Text;
 use dom::window::Window;
 use parse::html::{ParseContext, parse_html};
 use parse::xml::{self, parse_xml};
+use string_cache::{Atom, Namespace, QualName};
+
+/// The namespace of the element used to report XML well-formedness errors,
+/// matching what other engines produce.
+const PARSER_ERROR_NAMESPACE: &'static str = "http://www.mozilla.org/newlayout/xml/parsererror.xml";
 
 #[dom_struct]
 pub struct DOMParser {
@@ -75,21 +86,40 @@ impl DOMParserMethods for DOMParser {
                 document.set_ready_state(DocumentReadyState::Complete);
                 Ok(document)
             }
-            Text_xml | Application_xml | Application_xhtml_xml => {
-                // FIXME: this should probably be FromParser when we actually parse the string (#3756).
+            Text_xml | Application_xml | Application_xhtml_xml | Image_svg_xml => {
                 let document = Document::new(&self.window,
                                              None,
                                              Some(url.clone()),
                                              IsHTMLDocument::NonHTMLDocument,
                                              Some(content_type),
                                              None,
-                                             DocumentSource::NotFromParser,
+                                             DocumentSource::FromParser,
                                              loader,
                                              None,
                                              None);
-                parse_xml(document.r(), s, url, xml::ParseContext::Owner(None));
+                let parser = parse_xml(document.r(), s, url, xml::ParseContext::Owner(None));
+                if let Some(error) = parser.error() {
+                    replace_with_parser_error(document.r(), error);
+                }
+                document.set_ready_state(DocumentReadyState::Complete);
                 Ok(document)
             }
         }
     }
 }
+
+/// Replace the contents of a document that failed to parse with a single
+/// `parsererror` element describing the failure.
+/// https://domparsing.spec.whatwg.org/#dom-domparser-parsefromstring
+fn replace_with_parser_error(document: &Document, error: String) {
+    let node = document.upcast::<Node>();
+    let children: Vec<_> = node.children().collect();
+    for child in children {
+        node.RemoveChild(child.r()).unwrap();
+    }
+    let name = QualName::new(Namespace(Atom::from(PARSER_ERROR_NAMESPACE)), Atom::from("parsererror"));
+    let root = Element::create(name, None, document, ElementCreator::ScriptCreated);
+    let message = Text::new(DOMString::from(format!("XML Parsing Error: {}", error)), document);
+    root.upcast::<Node>().AppendChild(message.upcast()).unwrap();
+    node.AppendChild(root.upcast()).unwrap();
+}
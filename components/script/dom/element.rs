@@ -50,6 +50,7 @@ use dom::htmllabelelement::HTMLLabelElement;
 use dom::htmllegendelement::HTMLLegendElement;
 use dom::htmlobjectelement::HTMLObjectElement;
 use dom::htmloptgroupelement::HTMLOptGroupElement;
+use dom::htmloutputelement::HTMLOutputElement;
 use dom::htmlselectelement::HTMLSelectElement;
 use dom::htmltablecellelement::{HTMLTableCellElement, HTMLTableCellElementLayoutHelpers};
 use dom::htmltableelement::{HTMLTableElement, HTMLTableElementLayoutHelpers};
@@ -57,6 +58,7 @@ use dom::htmltablerowelement::{HTMLTableRowElement, HTMLTableRowElementLayoutHel
 use dom::htmltablesectionelement::{HTMLTableSectionElement, HTMLTableSectionElementLayoutHelpers};
 use dom::htmltemplateelement::HTMLTemplateElement;
 use dom::htmltextareaelement::{HTMLTextAreaElement, LayoutHTMLTextAreaElementHelpers};
+use dom::htmlvideoelement::{HTMLVideoElement, LayoutHTMLVideoElementHelpers};
 use dom::namednodemap::NamedNodeMap;
 use dom::node::{CLICK_IN_PROGRESS, ChildrenMutation, LayoutNodeHelpers, Node};
 use dom::node::{NodeDamage, SEQUENTIALLY_FOCUSABLE, UnbindContext};
@@ -295,6 +297,8 @@ pub trait LayoutElementHelpers {
     #[allow(unsafe_code)]
     unsafe fn get_colspan(self) -> u32;
     #[allow(unsafe_code)]
+    unsafe fn get_rowspan(self) -> u32;
+    #[allow(unsafe_code)]
     unsafe fn html_element_in_html_document_for_layout(&self) -> bool;
     fn id_attribute(&self) -> *const Option<Atom>;
     fn style_attribute(&self) -> *const Option<Arc<PropertyDeclarationBlock>>;
@@ -346,6 +350,9 @@ impl LayoutElementHelpers for LayoutJS<Element> {
             this.get_background_color()
         } else if let Some(this) = self.downcast::<HTMLTableSectionElement>() {
             this.get_background_color()
+        } else if let Some(this) = self.downcast::<HTMLInputElement>() {
+            // The swatch of `<input type=color>`.
+            this.color_for_layout()
         } else {
             None
         };
@@ -432,7 +439,7 @@ impl LayoutElementHelpers for LayoutJS<Element> {
             hints.push(from_declaration(
                 PropertyDeclaration::BorderSpacing(DeclaredValue::Value(
                     border_spacing::SpecifiedValue {
-                        horizontal: width_value,
+                        horizontal: width_value.clone(),
                         vertical: width_value,
                     }))));
         }
@@ -463,8 +470,8 @@ impl LayoutElementHelpers for LayoutJS<Element> {
         if let Some(size) = size {
             let value = specified::Length::ServoCharacterWidth(specified::CharacterWidth(size));
             hints.push(from_declaration(
-                PropertyDeclaration::Width(DeclaredValue::Value(
-                    specified::LengthOrPercentageOrAuto::Length(value)))));
+                PropertyDeclaration::Width(DeclaredValue::Value(specified::Size::LengthOrPercentageOrAuto(
+                    specified::LengthOrPercentageOrAuto::Length(value))))));
         }
 
 
@@ -479,6 +486,8 @@ impl LayoutElementHelpers for LayoutJS<Element> {
         } else if let Some(this) = self.downcast::<HTMLHRElement>() {
             // https://html.spec.whatwg.org/multipage/#the-hr-element-2:attr-hr-width
             this.get_width()
+        } else if let Some(this) = self.downcast::<HTMLVideoElement>() {
+            this.get_width()
         } else {
             LengthOrPercentageOrAuto::Auto
         };
@@ -486,14 +495,14 @@ impl LayoutElementHelpers for LayoutJS<Element> {
         match width {
             LengthOrPercentageOrAuto::Auto => {}
             LengthOrPercentageOrAuto::Percentage(percentage) => {
-                let width_value =
-                    specified::LengthOrPercentageOrAuto::Percentage(specified::Percentage(percentage));
+                let width_value = specified::Size::LengthOrPercentageOrAuto(
+                    specified::LengthOrPercentageOrAuto::Percentage(specified::Percentage(percentage)));
                 hints.push(from_declaration(
                     PropertyDeclaration::Width(DeclaredValue::Value(width_value))));
             }
             LengthOrPercentageOrAuto::Length(length) => {
-                let width_value = specified::LengthOrPercentageOrAuto::Length(
-                    specified::Length::Absolute(length));
+                let width_value = specified::Size::LengthOrPercentageOrAuto(
+                    specified::LengthOrPercentageOrAuto::Length(specified::Length::Absolute(length)));
                 hints.push(from_declaration(
                     PropertyDeclaration::Width(DeclaredValue::Value(width_value))));
             }
@@ -504,6 +513,8 @@ impl LayoutElementHelpers for LayoutJS<Element> {
             this.get_height()
         } else if let Some(this) = self.downcast::<HTMLImageElement>() {
             this.get_height()
+        } else if let Some(this) = self.downcast::<HTMLVideoElement>() {
+            this.get_height()
         } else {
             LengthOrPercentageOrAuto::Auto
         };
@@ -511,20 +522,42 @@ impl LayoutElementHelpers for LayoutJS<Element> {
         match height {
             LengthOrPercentageOrAuto::Auto => {}
             LengthOrPercentageOrAuto::Percentage(percentage) => {
-                let height_value =
-                    specified::LengthOrPercentageOrAuto::Percentage(specified::Percentage(percentage));
+                let height_value = specified::Size::LengthOrPercentageOrAuto(
+                    specified::LengthOrPercentageOrAuto::Percentage(specified::Percentage(percentage)));
                 hints.push(from_declaration(
                     PropertyDeclaration::Height(DeclaredValue::Value(height_value))));
             }
             LengthOrPercentageOrAuto::Length(length) => {
-                let height_value = specified::LengthOrPercentageOrAuto::Length(
-                    specified::Length::Absolute(length));
+                let height_value = specified::Size::LengthOrPercentageOrAuto(
+                    specified::LengthOrPercentageOrAuto::Length(specified::Length::Absolute(length)));
                 hints.push(from_declaration(
                     PropertyDeclaration::Height(DeclaredValue::Value(height_value))));
             }
         }
 
 
+        // Images and videos with both dimension attributes reserve space in that ratio until
+        // their content, which has its own ratio, loads.
+        //
+        // https://html.spec.whatwg.org/multipage/#map-to-the-aspect-ratio-property-(using-dimension-rules)
+        let dimensions = if let Some(this) = self.downcast::<HTMLImageElement>() {
+            (this.get_width(), this.get_height())
+        } else if let Some(this) = self.downcast::<HTMLVideoElement>() {
+            (this.get_width(), this.get_height())
+        } else {
+            (LengthOrPercentageOrAuto::Auto, LengthOrPercentageOrAuto::Auto)
+        };
+
+        if let (LengthOrPercentageOrAuto::Length(width), LengthOrPercentageOrAuto::Length(height)) = dimensions {
+            hints.push(from_declaration(
+                PropertyDeclaration::AspectRatio(DeclaredValue::Value(
+                    longhands::aspect_ratio::SpecifiedValue {
+                        auto: true,
+                        ratio: Some((width.to_f32_px(), height.to_f32_px())),
+                    }))));
+        }
+
+
         let cols = if let Some(this) = self.downcast::<HTMLTextAreaElement>() {
             match this.get_cols() {
                 0 => None,
@@ -542,8 +575,8 @@ impl LayoutElementHelpers for LayoutJS<Element> {
             // https://html.spec.whatwg.org/multipage/#textarea-effective-width
             let value = specified::Length::ServoCharacterWidth(specified::CharacterWidth(cols));
             hints.push(from_declaration(
-                PropertyDeclaration::Width(DeclaredValue::Value(
-                    specified::LengthOrPercentageOrAuto::Length(value)))));
+                PropertyDeclaration::Width(DeclaredValue::Value(specified::Size::LengthOrPercentageOrAuto(
+                    specified::LengthOrPercentageOrAuto::Length(value))))));
         }
 
 
@@ -562,8 +595,8 @@ impl LayoutElementHelpers for LayoutJS<Element> {
             // https://html.spec.whatwg.org/multipage/#textarea-effective-height
             let value = specified::Length::FontRelative(specified::FontRelativeLength::Em(rows as CSSFloat));
             hints.push(from_declaration(
-                PropertyDeclaration::Height(DeclaredValue::Value(
-                        specified::LengthOrPercentageOrAuto::Length(value)))));
+                PropertyDeclaration::Height(DeclaredValue::Value(specified::Size::LengthOrPercentageOrAuto(
+                        specified::LengthOrPercentageOrAuto::Length(value))))));
         }
 
 
@@ -577,17 +610,27 @@ impl LayoutElementHelpers for LayoutJS<Element> {
             let width_value = specified::Length::Absolute(Au::from_px(border as i32));
             hints.push(from_declaration(
                 PropertyDeclaration::BorderTopWidth(DeclaredValue::Value(
-                    longhands::border_top_width::SpecifiedValue(width_value)))));
+                    longhands::border_top_width::SpecifiedValue(width_value.clone())))));
             hints.push(from_declaration(
                 PropertyDeclaration::BorderLeftWidth(DeclaredValue::Value(
-                    longhands::border_left_width::SpecifiedValue(width_value)))));
+                    longhands::border_left_width::SpecifiedValue(width_value.clone())))));
             hints.push(from_declaration(
                 PropertyDeclaration::BorderBottomWidth(DeclaredValue::Value(
-                    longhands::border_bottom_width::SpecifiedValue(width_value)))));
+                    longhands::border_bottom_width::SpecifiedValue(width_value.clone())))));
             hints.push(from_declaration(
                 PropertyDeclaration::BorderRightWidth(DeclaredValue::Value(
                     longhands::border_right_width::SpecifiedValue(width_value)))));
         }
+
+        // https://html.spec.whatwg.org/multipage/#the-lang-and-xml:lang-attributes
+        let lang = (*self.unsafe_get()).get_attr_val_for_layout(&ns!(xml), &atom!("lang"))
+            .or_else(|| (*self.unsafe_get()).get_attr_val_for_layout(&ns!(), &atom!("lang")));
+
+        if let Some(lang) = lang {
+            hints.push(from_declaration(
+                PropertyDeclaration::ServoLang(DeclaredValue::Value(
+                    longhands::_servo_lang::SpecifiedValue(lang.trim().to_ascii_lowercase())))));
+        }
     }
 
     #[allow(unsafe_code)]
@@ -601,6 +644,15 @@ impl LayoutElementHelpers for LayoutJS<Element> {
         }
     }
 
+    #[allow(unsafe_code)]
+    unsafe fn get_rowspan(self) -> u32 {
+        if let Some(this) = self.downcast::<HTMLTableCellElement>() {
+            this.get_rowspan().unwrap_or(1)
+        } else {
+            1
+        }
+    }
+
     #[inline]
     #[allow(unsafe_code)]
     unsafe fn html_element_in_html_document_for_layout(&self) -> bool {
@@ -2133,7 +2185,7 @@ impl VirtualMethods for Element {
                             &value,
                             &doc.base_url(),
                             win.css_error_reporter(),
-                            ParserContextExtraData::default()))
+                            ParserContextExtraData::for_quirks_mode(doc.style_quirks_mode())))
                     });
                 if node.is_in_doc() {
                     node.dirty(NodeDamage::NodeStyleDamaged);
@@ -2362,7 +2414,13 @@ impl<'a> ::selectors::Element for Root<Element> {
             NonTSPseudoClass::Indeterminate |
             NonTSPseudoClass::ReadWrite |
             NonTSPseudoClass::PlaceholderShown |
-            NonTSPseudoClass::Target =>
+            NonTSPseudoClass::Target |
+            NonTSPseudoClass::Valid |
+            NonTSPseudoClass::Invalid |
+            NonTSPseudoClass::Required |
+            NonTSPseudoClass::Optional |
+            NonTSPseudoClass::Modal |
+            NonTSPseudoClass::ServoTopLayer =>
                 Element::state(self).contains(pseudo_class.state_flag()),
         }
     }
@@ -2436,10 +2494,18 @@ impl Element {
                 let element = self.downcast::<HTMLButtonElement>().unwrap();
                 Some(element as &Validatable)
             },
+            NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLFieldSetElement)) => {
+                let element = self.downcast::<HTMLFieldSetElement>().unwrap();
+                Some(element as &Validatable)
+            },
             NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLObjectElement)) => {
                 let element = self.downcast::<HTMLObjectElement>().unwrap();
                 Some(element as &Validatable)
             },
+            NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLOutputElement)) => {
+                let element = self.downcast::<HTMLOutputElement>().unwrap();
+                Some(element as &Validatable)
+            },
             NodeTypeId::Element(ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLSelectElement)) => {
                 let element = self.downcast::<HTMLSelectElement>().unwrap();
                 Some(element as &Validatable)
@@ -2632,6 +2698,35 @@ impl Element {
     pub fn set_target_state(&self, value: bool) {
        self.set_state(IN_TARGET_STATE, value)
     }
+
+    pub fn set_valid_state(&self, value: bool) {
+        self.set_state(IN_VALID_STATE, value)
+    }
+
+    pub fn set_invalid_state(&self, value: bool) {
+        self.set_state(IN_INVALID_STATE, value)
+    }
+
+    pub fn set_required_state(&self, value: bool) {
+        self.set_state(IN_REQUIRED_STATE, value);
+        self.set_state(IN_OPTIONAL_STATE, !value)
+    }
+
+    pub fn modal_state(&self) -> bool {
+        self.state.get().contains(IN_MODAL_STATE)
+    }
+
+    pub fn set_modal_state(&self, value: bool) {
+        self.set_state(IN_MODAL_STATE, value)
+    }
+
+    pub fn top_layer_state(&self) -> bool {
+        self.state.get().contains(IN_TOP_LAYER_STATE)
+    }
+
+    pub fn set_top_layer_state(&self, value: bool) {
+        self.set_state(IN_TOP_LAYER_STATE, value)
+    }
 }
 
 impl Element {
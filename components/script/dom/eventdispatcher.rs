@@ -13,8 +13,10 @@ use dom::document::Document;
 use dom::event::{Event, EventPhase};
 use dom::eventtarget::{CompiledEventListener, EventTarget, ListenerPhase};
 use dom::node::Node;
+use dom::performance::Performance;
 use dom::virtualmethods::vtable_for;
 use dom::window::Window;
+use util::compat;
 
 struct AutoDOMEventMarker {
     window: Root<Window>,
@@ -36,6 +38,37 @@ impl Drop for AutoDOMEventMarker {
     }
 }
 
+/// Exposes the event being dispatched as the legacy `window.event` until it is
+/// dropped, when the event of any enclosing dispatch is restored.
+struct AutoCurrentEvent {
+    window: Root<Window>,
+    previous: Option<Root<Event>>,
+}
+
+impl AutoCurrentEvent {
+    fn new(target: &EventTarget, event: &Event) -> Option<AutoCurrentEvent> {
+        if !compat::window_event_enabled() {
+            return None;
+        }
+        match target.global() {
+            GlobalRoot::Window(window) => {
+                let previous = window.set_current_event(Some(event));
+                Some(AutoCurrentEvent {
+                    window: window,
+                    previous: previous,
+                })
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Drop for AutoCurrentEvent {
+    fn drop(&mut self) {
+        self.window.set_current_event(self.previous.r());
+    }
+}
+
 fn handle_event(window: Option<&Window>, listener: &CompiledEventListener,
                 current_target: &EventTarget, event: &Event) {
     let _marker;
@@ -135,6 +168,8 @@ pub fn dispatch_event(target: &EventTarget,
         return event.status();
     }
 
+    let event_timing = event_timing_start(target, event);
+
     // Step 3. The "invoke" algorithm is only used on `target` separately,
     // so we don't put it in the path.
     rooted_vec!(let mut event_path);
@@ -154,7 +189,10 @@ pub fn dispatch_event(target: &EventTarget,
     }
 
     // Steps 5-9. In a separate function to short-circuit various things easily.
-    dispatch_to_listeners(event, target, event_path.r());
+    {
+        let _current_event = AutoCurrentEvent::new(target, event);
+        dispatch_to_listeners(event, target, event_path.r());
+    }
 
     // Default action.
     let target = event.GetTarget();
@@ -168,6 +206,11 @@ pub fn dispatch_event(target: &EventTarget,
         None => {}
     }
 
+    if let Some((performance, processing_start)) = event_timing {
+        let target = target_override.unwrap_or(target);
+        performance.queue_event_timing(event, target.downcast::<Node>(), processing_start);
+    }
+
     // Step 10-12.
     event.clear_dispatching_flags();
 
@@ -175,6 +218,23 @@ pub fn dispatch_event(target: &EventTarget,
     event.status()
 }
 
+/// Returns the performance timeline of the window of `target`, and when the processing of
+/// `event` started, if its timing is to be recorded there.
+/// https://w3c.github.io/event-timing/#sec-init-event-timing
+fn event_timing_start(target: &EventTarget, event: &Event) -> Option<(Root<Performance>, u64)> {
+    let window = if let Some(node) = target.downcast::<Node>() {
+        Root::from_ref(node.owner_doc().window())
+    } else if let Some(window) = target.downcast::<Window>() {
+        Root::from_ref(window)
+    } else {
+        return None;
+    };
+    // Windows of which nothing is timed have no performance timeline yet.
+    window.performance_if_created().and_then(|performance| {
+        performance.event_timing_start(event).map(|processing_start| (performance, processing_start))
+    })
+}
+
 // https://dom.spec.whatwg.org/#concept-event-listener-invoke
 fn invoke(window: Option<&Window>,
           object: &EventTarget,
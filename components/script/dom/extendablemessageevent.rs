@@ -7,9 +7,10 @@ use dom::bindings::codegen::Bindings::ExtendableMessageEventBinding::ExtendableM
 use dom::bindings::error::Fallible;
 use dom::bindings::global::GlobalRef;
 use dom::bindings::inheritance::Castable;
-use dom::bindings::js::Root;
+use dom::bindings::js::{JS, Root};
 use dom::bindings::reflector::reflect_dom_object;
 use dom::bindings::str::DOMString;
+use dom::client::Client;
 use dom::event::Event;
 use dom::eventtarget::EventTarget;
 use dom::extendableevent::ExtendableEvent;
@@ -24,18 +25,21 @@ pub struct ExtendableMessageEvent {
     data: Heap<JSVal>,
     origin: DOMString,
     lastEventId: DOMString,
+    source: Option<JS<Client>>,
 }
 
 impl ExtendableMessageEvent {
     pub fn new(global: GlobalRef, type_: Atom,
                bubbles: bool, cancelable: bool,
-               data: HandleValue, origin: DOMString, lastEventId: DOMString)
+               data: HandleValue, origin: DOMString, lastEventId: DOMString,
+               source: Option<&Client>)
                -> Root<ExtendableMessageEvent> {
         let mut ev = box ExtendableMessageEvent {
             event: ExtendableEvent::new_inherited(),
             data: Heap::default(),
             origin: origin,
             lastEventId: lastEventId,
+            source: source.map(JS::from_ref),
         };
         ev.data.set(data.get());
         let ev = reflect_dom_object(ev, global, ExtendableMessageEventBinding::Wrap);
@@ -56,7 +60,8 @@ impl ExtendableMessageEvent {
                                              init.parent.parent.cancelable,
                                              data.handle(),
                                              init.origin.clone().unwrap(),
-                                             init.lastEventId.clone().unwrap());
+                                             init.lastEventId.clone().unwrap(),
+                                             None);
         Ok(ev)
     }
 }
@@ -64,10 +69,12 @@ impl ExtendableMessageEvent {
 impl ExtendableMessageEvent {
     pub fn dispatch_jsval(target: &EventTarget,
                           scope: GlobalRef,
-                          message: HandleValue) {
+                          message: HandleValue,
+                          origin: DOMString,
+                          source: Option<&Client>) {
         let Extendablemessageevent = ExtendableMessageEvent::new(
             scope, atom!("message"), false, false, message,
-            DOMString::new(), DOMString::new());
+            origin, DOMString::new(), source);
         Extendablemessageevent.upcast::<Event>().fire(target);
     }
 }
@@ -88,6 +95,11 @@ impl ExtendableMessageEventMethods for ExtendableMessageEvent {
         self.lastEventId.clone()
     }
 
+    // https://w3c.github.io/ServiceWorker/#extendablemessage-event-source-attribute
+    fn GetSource(&self) -> Option<Root<Client>> {
+        self.source.as_ref().map(|source| Root::from_ref(&**source))
+    }
+
     // https://dom.spec.whatwg.org/#dom-event-istrusted
     fn IsTrusted(&self) -> bool {
         self.event.IsTrusted()
@@ -0,0 +1,28 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/extensions/EXT_texture_filter_anisotropic/
+use dom::bindings::codegen::Bindings::EXTTextureFilterAnisotropicBinding;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+
+#[dom_struct]
+pub struct EXTTextureFilterAnisotropic {
+    reflector_: Reflector,
+}
+
+impl EXTTextureFilterAnisotropic {
+    fn new_inherited() -> EXTTextureFilterAnisotropic {
+        EXTTextureFilterAnisotropic {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<EXTTextureFilterAnisotropic> {
+        reflect_dom_object(box EXTTextureFilterAnisotropic::new_inherited(),
+                           global,
+                           EXTTextureFilterAnisotropicBinding::Wrap)
+    }
+}
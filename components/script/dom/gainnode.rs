@@ -0,0 +1,47 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::NodeId;
+use audio::node::AudioNodeInit;
+use audio::param::ParamType;
+use dom::audiocontext::AudioContext;
+use dom::audionode::AudioNode;
+use dom::audioparam::AudioParam;
+use dom::bindings::codegen::Bindings::GainNodeBinding;
+use dom::bindings::codegen::Bindings::GainNodeBinding::GainNodeMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::reflect_dom_object;
+use std::f32;
+
+// https://webaudio.github.io/web-audio-api/#gainnode
+#[dom_struct]
+pub struct GainNode {
+    audionode: AudioNode,
+    gain: JS<AudioParam>,
+}
+
+impl GainNode {
+    fn new_inherited(context: &AudioContext, node_id: NodeId, gain: &AudioParam) -> GainNode {
+        GainNode {
+            audionode: AudioNode::new_inherited(context, node_id, 1, 1),
+            gain: JS::from_ref(gain),
+        }
+    }
+
+    pub fn new(global: GlobalRef, context: &AudioContext) -> Root<GainNode> {
+        let node_id = context.create_node(AudioNodeInit::Gain);
+        let gain = AudioParam::new(global, context, node_id, ParamType::Gain, 1., f32::MIN, f32::MAX);
+        reflect_dom_object(box GainNode::new_inherited(context, node_id, &gain),
+                           global,
+                           GainNodeBinding::Wrap)
+    }
+}
+
+impl GainNodeMethods for GainNode {
+    // https://webaudio.github.io/web-audio-api/#dom-gainnode-gain
+    fn Gain(&self) -> Root<AudioParam> {
+        Root::from_ref(&*self.gain)
+    }
+}
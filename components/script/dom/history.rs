@@ -4,28 +4,141 @@
 
 use dom::bindings::codegen::Bindings::HistoryBinding;
 use dom::bindings::codegen::Bindings::HistoryBinding::HistoryMethods;
+use dom::bindings::codegen::Bindings::HistoryBinding::ScrollRestoration;
+use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::LocationBinding::LocationMethods;
-use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, WindowMethods};
+use dom::bindings::error::{Error, ErrorResult, Fallible};
 use dom::bindings::global::GlobalRef;
-use dom::bindings::js::{JS, Root};
-use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, MutHeapJSVal, Root};
+use dom::bindings::refcounted::Trusted;
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::bindings::str::{DOMString, USVString};
+use dom::bindings::structuredclone::StructuredCloneData;
+use dom::event::Event;
+use dom::hashchangeevent::HashChangeEvent;
+use dom::popstateevent::PopStateEvent;
 use dom::window::Window;
+use euclid::Point2D;
 use ipc_channel::ipc;
+use js::jsapi::{HandleValue, JSAutoCompartment, JSContext};
+use js::jsval::{JSVal, NullValue, UndefinedValue};
 use msg::constellation_msg::TraversalDirection;
+use script_thread::{Runnable, ScriptThread};
+use script_traits::HistoryStateId;
 use script_traits::ScriptMsg as ConstellationMsg;
+use std::cell::Cell;
+use std::collections::HashMap;
+use string_cache::Atom;
+use task_source::TaskSource;
+use url::{Position, Url};
+
+enum PushOrReplace {
+    Push,
+    Replace,
+}
 
 // https://html.spec.whatwg.org/multipage/#the-history-interface
 #[dom_struct]
 pub struct History {
     reflector_: Reflector,
     window: JS<Window>,
+    #[ignore_heap_size_of = "Defined in rust-mozjs"]
+    state: MutHeapJSVal,
+    scroll_restoration: Cell<ScrollRestoration>,
+    entries: DOMRefCell<HistoryStateEntries>,
+}
+
+/// The session history entries of a document, as far as the document knows them: which
+/// one is current, and the scroll position each was left at.
+/// https://html.spec.whatwg.org/multipage/#persisted-user-state
+#[derive(HeapSizeOf, JSTraceable)]
+pub struct HistoryStateEntries {
+    current: HistoryStateId,
+    next_id: u64,
+    scroll_positions: HashMap<HistoryStateId, Point2D<f64>>,
+}
+
+impl HistoryStateEntries {
+    pub fn new() -> HistoryStateEntries {
+        HistoryStateEntries {
+            current: HistoryStateId(0),
+            next_id: 1,
+            scroll_positions: HashMap::new(),
+        }
+    }
+
+    pub fn current(&self) -> HistoryStateId {
+        self.current
+    }
+
+    /// Adds an entry and makes it current, as `pushState` and fragment navigations do.
+    /// `scroll_position` is where the document is scrolled to as it leaves the current entry.
+    pub fn push(&mut self, scroll_position: Point2D<f64>) -> HistoryStateId {
+        self.scroll_positions.insert(self.current, scroll_position);
+        let id = HistoryStateId(self.next_id);
+        self.next_id += 1;
+        self.current = id;
+        id
+    }
+
+    /// Makes another entry current as the result of a traversal, and returns the scroll
+    /// position that entry was left at, if it was ever left.
+    pub fn traverse_to(&mut self, id: HistoryStateId, scroll_position: Point2D<f64>) -> Option<Point2D<f64>> {
+        self.scroll_positions.insert(self.current, scroll_position);
+        self.current = id;
+        self.scroll_positions.get(&id).cloned()
+    }
+}
+
+/// Whether a traversal between two entries of a document fires `hashchange` after
+/// `popstate`, which is when only their fragments differ.
+/// https://html.spec.whatwg.org/multipage/#traverse-the-history step 16
+pub fn fires_hashchange(old_url: &Url, new_url: &Url) -> bool {
+    old_url.fragment() != new_url.fragment() &&
+        old_url[..Position::AfterQuery] == new_url[..Position::AfterQuery]
+}
+
+/// Resolves the `url` argument of `pushState` or `replaceState` for a document, which
+/// may only change the path, query and fragment of its URL.
+/// https://html.spec.whatwg.org/multipage/#dom-history-pushstate steps 6.1-6.4
+pub fn resolve_state_url(document_url: &Url,
+                         base_url: &Url,
+                         url: &str,
+                         is_tuple_origin: bool) -> Fallible<Url> {
+    // Step 6.1.
+    let new_url = match base_url.join(url) {
+        Ok(new_url) => new_url,
+        // Step 6.2.
+        Err(_) => return Err(Error::Security),
+    };
+
+    // Step 6.3.
+    if new_url[..Position::BeforePath] != document_url[..Position::BeforePath] {
+        return Err(Error::Security);
+    }
+
+    // Step 6.4.
+    if !is_tuple_origin &&
+       new_url[Position::BeforePath..Position::AfterQuery] !=
+       document_url[Position::BeforePath..Position::AfterQuery] {
+        return Err(Error::Security);
+    }
+
+    Ok(new_url)
 }
 
 impl History {
     pub fn new_inherited(window: &Window) -> History {
+        let state = MutHeapJSVal::new();
+        state.set(NullValue());
         History {
             reflector_: Reflector::new(),
             window: JS::from_ref(&window),
+            state: state,
+            scroll_restoration: Cell::new(ScrollRestoration::Auto),
+            entries: DOMRefCell::new(HistoryStateEntries::new()),
         }
     }
 
@@ -42,9 +155,162 @@ impl History {
         let msg = ConstellationMsg::TraverseHistory(Some(pipeline), direction);
         let _ = self.window.constellation_chan().send(msg);
     }
+
+    /// Whether the user agent should restore the scroll position when traversing
+    /// to another entry of this document.
+    pub fn restores_scroll_position(&self) -> bool {
+        self.scroll_restoration.get() == ScrollRestoration::Auto
+    }
+
+    /// Where the document is scrolled to, to save in the entry it is leaving.
+    fn scroll_position(&self) -> Point2D<f64> {
+        Point2D::new(self.window.ScrollX() as f64, self.window.ScrollY() as f64)
+    }
+
+    /// Makes the session history entry with the given URL and serialized state
+    /// the current entry of this document, as the result of a traversal.
+    /// Returns whether the scroll position of the entry was restored.
+    /// https://html.spec.whatwg.org/multipage/#traverse-the-history
+    pub fn activate_state_entry(&self, state_id: HistoryStateId, url: Url, state: Option<Vec<u8>>) -> bool {
+        let document = self.window.Document();
+        let old_url = document.url();
+
+        // Step 6.
+        document.set_url(url.clone());
+
+        // Steps 9-10.
+        self.set_state(state);
+
+        // Step 14: restore the persisted user state of the entry.
+        let scroll_position = self.entries.borrow_mut().traverse_to(state_id, self.scroll_position());
+        let restored = match scroll_position {
+            Some(position) if self.restores_scroll_position() => {
+                self.window.scroll(position.x, position.y, ScrollBehavior::Instant);
+                true
+            },
+            _ => false,
+        };
+
+        // Step 15: fire popstate synchronously with the new state.
+        rooted!(in(self.window.get_cx()) let state = self.state.get());
+        let event = PopStateEvent::new(GlobalRef::Window(&self.window),
+                                       Atom::from("popstate"),
+                                       false,
+                                       false,
+                                       state.handle());
+        event.upcast::<Event>().fire(self.window.upcast());
+
+        // Step 16: hashchange comes after popstate, as a queued task.
+        if fires_hashchange(&old_url, &url) {
+            self.queue_hash_change(old_url, url);
+        }
+
+        restored
+    }
+
+    /// Queues a task to fire a `hashchange` event at the window.
+    pub fn queue_hash_change(&self, old_url: Url, new_url: Url) {
+        let runnable = box HashChangeEventRunnable {
+            window: Trusted::new(&*self.window),
+            old_url: old_url.into_string(),
+            new_url: new_url.into_string(),
+        };
+        let _ = self.window.dom_manipulation_task_source()
+                           .queue(runnable, GlobalRef::Window(&self.window));
+    }
+
+    /// Records a same-document navigation to a fragment as a new session history entry.
+    /// https://html.spec.whatwg.org/multipage/#scroll-to-fragid
+    pub fn navigate_to_fragment(&self, new_url: Url, replace: bool) {
+        let document = self.window.Document();
+        let old_url = document.url();
+        document.set_url(new_url.clone());
+        self.set_state(None);
+
+        let pipeline = self.window.pipeline_id();
+        let msg = if replace {
+            ConstellationMsg::ReplaceHistoryState(pipeline, new_url.clone(), None)
+        } else {
+            let state_id = self.entries.borrow_mut().push(self.scroll_position());
+            ConstellationMsg::PushHistoryState(pipeline, state_id, new_url.clone(), None)
+        };
+        let _ = self.window.constellation_chan().send(msg);
+
+        if old_url.fragment() != new_url.fragment() {
+            self.queue_hash_change(old_url, new_url);
+        }
+    }
+
+    /// Deserializes a state object into this document's realm.
+    fn set_state(&self, state: Option<Vec<u8>>) {
+        match state {
+            Some(data) => {
+                let cx = self.window.get_cx();
+                let _ac = JSAutoCompartment::new(cx, self.window.reflector().get_jsobject().get());
+                rooted!(in(cx) let mut value = UndefinedValue());
+                StructuredCloneData::Vector(data).read(GlobalRef::Window(&self.window), value.handle_mut());
+                self.state.set(value.get());
+            },
+            None => self.state.set(NullValue()),
+        }
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#dom-history-pushstate
+    /// https://html.spec.whatwg.org/multipage/#dom-history-replacestate
+    fn push_or_replace_state(&self,
+                             cx: *mut JSContext,
+                             data: HandleValue,
+                             _title: DOMString,
+                             url: Option<USVString>,
+                             push_or_replace: PushOrReplace) -> ErrorResult {
+        let document = self.window.Document();
+
+        // Step 1.
+        if !document.is_fully_active() {
+            return Err(Error::Security);
+        }
+
+        // Steps 4-5.
+        let serialized_data = try!(StructuredCloneData::write(cx, data)).move_to_arraybuffer();
+
+        // Step 6.
+        let new_url = match url {
+            Some(url) => try!(resolve_state_url(&document.url(),
+                                                &document.base_url(),
+                                                &url.0,
+                                                document.origin().is_scheme_host_port_tuple())),
+            // Step 7.
+            None => document.url(),
+        };
+
+        // Step 8.
+        let pipeline = self.window.pipeline_id();
+        let msg = match push_or_replace {
+            PushOrReplace::Push => {
+                let state_id = self.entries.borrow_mut().push(self.scroll_position());
+                ConstellationMsg::PushHistoryState(pipeline, state_id, new_url.clone(), Some(serialized_data.clone()))
+            },
+            PushOrReplace::Replace =>
+                ConstellationMsg::ReplaceHistoryState(pipeline, new_url.clone(), Some(serialized_data.clone())),
+        };
+        let _ = self.window.constellation_chan().send(msg);
+
+        // Step 9.
+        document.set_url(new_url);
+
+        // Steps 10-11: the state object is a fresh deserialization, not `data` itself.
+        self.set_state(Some(serialized_data));
+
+        Ok(())
+    }
 }
 
 impl HistoryMethods for History {
+    // https://html.spec.whatwg.org/multipage/#dom-history-state
+    fn State(&self, _cx: *mut JSContext) -> JSVal {
+        self.state.get()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-history-length
     fn Length(&self) -> u32 {
         let pipeline = self.window.pipeline_id();
@@ -54,6 +320,16 @@ impl HistoryMethods for History {
         recv.recv().unwrap()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-history-scroll-restoration
+    fn ScrollRestoration(&self) -> ScrollRestoration {
+        self.scroll_restoration.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-history-scroll-restoration
+    fn SetScrollRestoration(&self, value: ScrollRestoration) {
+        self.scroll_restoration.set(value);
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-history-go
     fn Go(&self, delta: i32) {
         let direction = if delta > 0 {
@@ -77,4 +353,44 @@ impl HistoryMethods for History {
     fn Forward(&self) {
         self.traverse_history(TraversalDirection::Forward(1));
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-history-pushstate
+    fn PushState(&self,
+                 cx: *mut JSContext,
+                 data: HandleValue,
+                 title: DOMString,
+                 url: Option<USVString>) -> ErrorResult {
+        self.push_or_replace_state(cx, data, title, url, PushOrReplace::Push)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-history-replacestate
+    fn ReplaceState(&self,
+                    cx: *mut JSContext,
+                    data: HandleValue,
+                    title: DOMString,
+                    url: Option<USVString>) -> ErrorResult {
+        self.push_or_replace_state(cx, data, title, url, PushOrReplace::Replace)
+    }
+}
+
+struct HashChangeEventRunnable {
+    window: Trusted<Window>,
+    old_url: String,
+    new_url: String,
+}
+
+impl Runnable for HashChangeEventRunnable {
+    fn name(&self) -> &'static str { "HashChangeEventRunnable" }
+
+    fn main_thread_handler(self: Box<HashChangeEventRunnable>, _: &ScriptThread) {
+        let this = *self;
+        let window = this.window.root();
+        let event = HashChangeEvent::new(GlobalRef::Window(&window),
+                                         Atom::from("hashchange"),
+                                         false,
+                                         false,
+                                         this.old_url,
+                                         this.new_url);
+        event.upcast::<Event>().fire(window.upcast());
+    }
 }
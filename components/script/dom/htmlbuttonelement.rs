@@ -7,7 +7,7 @@ use dom::attr::Attr;
 use dom::bindings::codegen::Bindings::HTMLButtonElementBinding;
 use dom::bindings::codegen::Bindings::HTMLButtonElementBinding::HTMLButtonElementMethods;
 use dom::bindings::inheritance::Castable;
-use dom::bindings::js::Root;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
 use dom::bindings::str::DOMString;
 use dom::document::Document;
 use dom::element::{AttributeMutation, Element};
@@ -18,9 +18,9 @@ use dom::htmlfieldsetelement::HTMLFieldSetElement;
 use dom::htmlformelement::{FormControl, FormDatum, FormDatumValue};
 use dom::htmlformelement::{FormSubmitter, ResetFrom, SubmittedFrom};
 use dom::htmlformelement::HTMLFormElement;
-use dom::node::{Node, UnbindContext, document_from_node, window_from_node};
+use dom::node::{Node, UnbindContext, document_from_node};
 use dom::nodelist::NodeList;
-use dom::validation::Validatable;
+use dom::validation::{Validatable, is_barred_by_datalist_ancestor};
 use dom::validitystate::ValidityState;
 use dom::virtualmethods::VirtualMethods;
 use std::cell::Cell;
@@ -39,7 +39,8 @@ enum ButtonType {
 #[dom_struct]
 pub struct HTMLButtonElement {
     htmlelement: HTMLElement,
-    button_type: Cell<ButtonType>
+    button_type: Cell<ButtonType>,
+    validity_state: MutNullableHeap<JS<ValidityState>>,
 }
 
 impl HTMLButtonElement {
@@ -48,9 +49,10 @@ impl HTMLButtonElement {
                      document: &Document) -> HTMLButtonElement {
         HTMLButtonElement {
             htmlelement:
-                HTMLElement::new_inherited_with_state(IN_ENABLED_STATE,
+                HTMLElement::new_inherited_with_state(IN_ENABLED_STATE | IN_VALID_STATE,
                                                       local_name, prefix, document),
-            button_type: Cell::new(ButtonType::Submit)
+            button_type: Cell::new(ButtonType::Submit),
+            validity_state: MutNullableHeap::new(None),
         }
     }
 
@@ -65,12 +67,6 @@ impl HTMLButtonElement {
 }
 
 impl HTMLButtonElementMethods for HTMLButtonElement {
-    // https://html.spec.whatwg.org/multipage/#dom-cva-validity
-    fn Validity(&self) -> Root<ValidityState> {
-        let window = window_from_node(self);
-        ValidityState::new(window.r(), self.upcast())
-    }
-
     // https://html.spec.whatwg.org/multipage/#dom-fe-disabled
     make_bool_getter!(Disabled, "disabled");
 
@@ -137,6 +133,36 @@ impl HTMLButtonElementMethods for HTMLButtonElement {
     fn Labels(&self) -> Root<NodeList> {
         self.upcast::<HTMLElement>().labels()
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-willvalidate
+    fn WillValidate(&self) -> bool {
+        self.will_validate()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validity
+    fn Validity(&self) -> Root<ValidityState> {
+        self.validity_state()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    fn ValidationMessage(&self) -> DOMString {
+        self.validation_message()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.check_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.report_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity
+    fn SetCustomValidity(&self, error: DOMString) {
+        self.validity_state().set_custom_error_message(error);
+    }
 }
 
 impl HTMLButtonElement {
@@ -213,6 +239,8 @@ impl VirtualMethods for HTMLButtonElement {
             }
             _ => {},
         }
+
+        self.update_validity_states();
     }
 
     fn bind_to_tree(&self, tree_in_doc: bool) {
@@ -221,6 +249,7 @@ impl VirtualMethods for HTMLButtonElement {
         }
 
         self.upcast::<Element>().check_ancestors_disabled_state_for_form_control();
+        self.update_validity_states();
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -233,12 +262,28 @@ impl VirtualMethods for HTMLButtonElement {
         } else {
             el.check_disabled_attribute();
         }
+        self.update_validity_states();
     }
 }
 
 impl FormControl for HTMLButtonElement {}
 
-impl Validatable for HTMLButtonElement {}
+impl Validatable for HTMLButtonElement {
+    fn as_element(&self) -> &Element {
+        self.upcast()
+    }
+
+    fn validity_state_slot(&self) -> &MutNullableHeap<JS<ValidityState>> {
+        &self.validity_state
+    }
+
+    fn is_instance_validatable(&self) -> bool {
+        // https://html.spec.whatwg.org/multipage/#the-button-element:barred-from-constraint-validation
+        let el = self.upcast::<Element>();
+        self.button_type.get() == ButtonType::Submit &&
+        !(el.disabled_state() || is_barred_by_datalist_ancestor(el))
+    }
+}
 
 impl Activatable for HTMLButtonElement {
     fn as_element(&self) -> &Element {
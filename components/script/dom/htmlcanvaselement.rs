@@ -2,40 +2,56 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use canvas_traits::{CanvasMsg, FromLayoutMsg, CanvasData};
+use canvas_traits::{Canvas2dMsg, CanvasMsg, FromLayoutMsg, CanvasData};
+use canvas_traits::{ImageEncoding, encode_image};
+use canvas_traits::webgl2::WebGLVersion;
 use dom::attr::Attr;
 use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::CanvasRenderingContext2DMethods;
+use dom::bindings::callback::ExceptionHandling;
 use dom::bindings::codegen::Bindings::HTMLCanvasElementBinding;
-use dom::bindings::codegen::Bindings::HTMLCanvasElementBinding::HTMLCanvasElementMethods;
+use dom::bindings::codegen::Bindings::HTMLCanvasElementBinding::{BlobCallback, HTMLCanvasElementMethods};
 use dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLContextAttributes;
 use dom::bindings::codegen::UnionTypes::CanvasRenderingContext2DOrWebGLRenderingContext;
 use dom::bindings::conversions::ConversionResult;
-use dom::bindings::error::{Error, Fallible};
+use dom::bindings::error::{Error, ErrorResult, Fallible};
 use dom::bindings::global::GlobalRef;
 use dom::bindings::inheritance::Castable;
-use dom::bindings::js::{HeapGCValue, JS, LayoutJS, Root};
+use dom::bindings::js::{HeapGCValue, JS, LayoutJS, Root, RootedReference};
 use dom::bindings::num::Finite;
+use dom::bindings::refcounted::Trusted;
 use dom::bindings::str::DOMString;
+use dom::blob::{Blob, BlobImpl};
 use dom::canvasrenderingcontext2d::{CanvasRenderingContext2D, LayoutCanvasRenderingContext2DHelpers};
 use dom::document::Document;
 use dom::element::{AttributeMutation, Element, RawLayoutElementHelpers};
 use dom::htmlelement::HTMLElement;
+use dom::mediastream::MediaStream;
+use dom::mediastreamtrack::MediaStreamTrack;
 use dom::node::{Node, window_from_node};
 use dom::virtualmethods::VirtualMethods;
+use dom::webgl2renderingcontext::WebGL2RenderingContext;
 use dom::webglrenderingcontext::{LayoutCanvasWebGLRenderingContextHelpers, WebGLRenderingContext};
 use euclid::size::Size2D;
-use image::ColorType;
-use image::png::PNGEncoder;
 use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
 use js::error::throw_type_error;
 use js::jsapi::{HandleValue, JSContext};
+use media::{CaptureSettings, CaptureThread};
+use media::canvas::{self, CanvasCapturer};
 use offscreen_gl_context::GLContextAttributes;
 use rustc_serialize::base64::{STANDARD, ToBase64};
 use script_layout_interface::HTMLCanvasData;
+use script_thread::Runnable;
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::iter::repeat;
+use std::rc::Rc;
 use string_cache::Atom;
 use style::attr::AttrValue;
+use task_source::TaskSource;
+use util::prefs::PREFS;
+use util::thread::spawn_named;
 
 const DEFAULT_WIDTH: u32 = 300;
 const DEFAULT_HEIGHT: u32 = 150;
@@ -53,6 +69,10 @@ impl HeapGCValue for CanvasContext {}
 pub struct HTMLCanvasElement {
     htmlelement: HTMLElement,
     context: DOMRefCell<Option<CanvasContext>>,
+    /// The callbacks of the `toBlob()` calls in progress, by id.
+    #[ignore_heap_size_of = "Rc has unclear ownership semantics"]
+    blob_callbacks: DOMRefCell<HashMap<u32, Rc<BlobCallback>>>,
+    next_blob_callback_id: Cell<u32>,
 }
 
 impl HTMLCanvasElement {
@@ -62,6 +82,8 @@ impl HTMLCanvasElement {
         HTMLCanvasElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
             context: DOMRefCell::new(None),
+            blob_callbacks: DOMRefCell::new(HashMap::new()),
+            next_blob_callback_id: Cell::new(0),
         }
     }
 
@@ -94,6 +116,20 @@ impl HTMLCanvasElement {
             _ => true,
         }
     }
+
+    /// Calls the callback of a `toBlob()` call with the encoded file, or with null if the
+    /// canvas had no pixels.
+    /// https://html.spec.whatwg.org/multipage/#dom-canvas-toblob
+    fn finish_to_blob(&self, id: u32, encoding: ImageEncoding, result: Option<Vec<u8>>) {
+        let callback = self.blob_callbacks.borrow_mut().remove(&id).unwrap();
+        let window = window_from_node(self);
+        let blob = result.map(|bytes| {
+            Blob::new(GlobalRef::Window(window.r()),
+                      BlobImpl::new_from_bytes(bytes),
+                      encoding.mime_type().to_owned())
+        });
+        let _ = callback.Call__(blob.r(), ExceptionHandling::Report);
+    }
 }
 
 pub trait LayoutHTMLCanvasElementHelpers {
@@ -122,6 +158,7 @@ impl LayoutHTMLCanvasElementHelpers for LayoutJS<HTMLCanvasElement> {
                 ipc_renderer: ipc_renderer,
                 width: width_attr.map_or(DEFAULT_WIDTH, |val| val.as_uint()),
                 height: height_attr.map_or(DEFAULT_HEIGHT, |val| val.as_uint()),
+                cues: vec![],
             }
         }
     }
@@ -155,7 +192,8 @@ impl HTMLCanvasElement {
     #[allow(unsafe_code)]
     pub fn get_or_init_webgl_context(&self,
                                  cx: *mut JSContext,
-                                 attrs: Option<HandleValue>) -> Option<Root<WebGLRenderingContext>> {
+                                 attrs: Option<HandleValue>,
+                                 version: WebGLVersion) -> Option<Root<WebGLRenderingContext>> {
         if self.context.borrow().is_none() {
             let window = window_from_node(self);
             let size = self.get_size();
@@ -177,12 +215,22 @@ impl HTMLCanvasElement {
                 GLContextAttributes::default()
             };
 
-            let maybe_ctx = WebGLRenderingContext::new(GlobalRef::Window(window.r()), self, size, attrs);
+            let global = GlobalRef::Window(window.r());
+            let maybe_ctx = match version {
+                WebGLVersion::WebGL1 => WebGLRenderingContext::new(global, self, size, attrs),
+                WebGLVersion::WebGL2 => {
+                    WebGL2RenderingContext::new(global, self, size, attrs).map(Root::upcast)
+                }
+            };
 
             *self.context.borrow_mut() = maybe_ctx.map( |ctx| CanvasContext::WebGL(JS::from_ref(&*ctx)));
         }
 
+        // A canvas only ever has one WebGL context, so asking for the other version fails.
         if let Some(CanvasContext::WebGL(ref context)) = *self.context.borrow() {
+            if context.webgl_version() != version {
+                return None;
+            }
             Some(Root::from_ref(&*context))
         } else {
             None
@@ -246,7 +294,11 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
                     .map(CanvasRenderingContext2DOrWebGLRenderingContext::CanvasRenderingContext2D)
             }
             "webgl" | "experimental-webgl" => {
-                self.get_or_init_webgl_context(cx, attributes.get(0).cloned())
+                self.get_or_init_webgl_context(cx, attributes.get(0).cloned(), WebGLVersion::WebGL1)
+                    .map(CanvasRenderingContext2DOrWebGLRenderingContext::WebGLRenderingContext)
+            }
+            "webgl2" if PREFS.get("dom.webgl2.enabled").as_boolean().unwrap_or(false) => {
+                self.get_or_init_webgl_context(cx, attributes.get(0).cloned(), WebGLVersion::WebGL2)
                     .map(CanvasRenderingContext2DOrWebGLRenderingContext::WebGLRenderingContext)
             }
             _ => None
@@ -256,8 +308,8 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
     // https://html.spec.whatwg.org/multipage/#dom-canvas-todataurl
     fn ToDataURL(&self,
                  _context: *mut JSContext,
-                 _mime_type: Option<DOMString>,
-                 _arguments: Vec<HandleValue>) -> Fallible<DOMString> {
+                 mime_type: Option<DOMString>,
+                 arguments: Vec<HandleValue>) -> Fallible<DOMString> {
         // Step 1.
         if let Some(CanvasContext::Context2d(ref context)) = *self.context.borrow() {
             if !context.origin_is_clean() {
@@ -285,17 +337,127 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
             _ => return Err(Error::NotSupported) // WebGL
         };
 
-        // Only handle image/png for now.
-        let mime_type = "image/png";
+        let quality = arguments.get(0).and_then(|quality| {
+            if quality.get().is_number() { Some(quality.get().to_number()) } else { None }
+        });
+        let encoding = ImageEncoding::from_mime_type(&mime_type.unwrap_or_default(), quality);
+        let encoded = encode_image(&raw_data, Size2D::new(self.Width(), self.Height()), encoding);
 
-        let mut encoded = Vec::new();
-        {
-            let encoder: PNGEncoder<&mut Vec<u8>> = PNGEncoder::new(&mut encoded);
-            encoder.encode(&raw_data, self.Width(), self.Height(), ColorType::RGBA(8)).unwrap();
+        let encoded = encoded.to_base64(STANDARD);
+        Ok(DOMString::from(format!("data:{};base64,{}", encoding.mime_type(), encoded)))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-canvas-toblob
+    fn ToBlob(&self,
+              _cx: *mut JSContext,
+              callback: Rc<BlobCallback>,
+              mime_type: Option<DOMString>,
+              quality: HandleValue) -> ErrorResult {
+        // Step 1.
+        if !self.origin_is_clean() {
+            return Err(Error::Security);
+        }
+        if let Some(CanvasContext::WebGL(_)) = *self.context.borrow() {
+            return Err(Error::NotSupported);
         }
 
-        let encoded = encoded.to_base64(STANDARD);
-        Ok(DOMString::from(format!("data:{};base64,{}", mime_type, encoded)))
+        let id = self.next_blob_callback_id.get();
+        self.next_blob_callback_id.set(id + 1);
+        self.blob_callbacks.borrow_mut().insert(id, callback);
+
+        let quality = if quality.get().is_number() { Some(quality.get().to_number()) } else { None };
+        let encoding = ImageEncoding::from_mime_type(&mime_type.unwrap_or_default(), quality);
+        let window = window_from_node(self);
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let mut runnable = box ToBlobRunnable {
+            canvas: Trusted::new(self),
+            id: id,
+            encoding: encoding,
+            result: None,
+        };
+
+        // Step 2.
+        let size = self.get_size();
+        if size.width == 0 || size.height == 0 {
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+            return Ok(());
+        }
+
+        // Steps 3-4. The file is encoded off the script thread, by the canvas paint thread if
+        // there is one.
+        match *self.context.borrow() {
+            Some(CanvasContext::Context2d(ref context)) => {
+                let (sender, receiver) = ipc::channel().unwrap();
+                let mut runnable = Some(runnable);
+                ROUTER.add_route(receiver.to_opaque(), box move |message| {
+                    if let Some(mut runnable) = runnable.take() {
+                        runnable.result = message.to().ok();
+                        let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+                    }
+                });
+                let msg = CanvasMsg::Canvas2d(Canvas2dMsg::Encode(encoding, sender));
+                context.ipc_renderer().send(msg).unwrap();
+            }
+            _ => {
+                let size = Size2D::new(size.width as u32, size.height as u32);
+                spawn_named("CanvasEncoder".to_owned(), move || {
+                    // Each pixel is fully-transparent black.
+                    let pixels = vec![0; size.width as usize * size.height as usize * 4];
+                    runnable.result = Some(encode_image(&pixels, size, encoding));
+                    let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // https://w3c.github.io/mediacapture-fromelement/#dom-htmlcanvaselement-capturestream
+    fn CaptureStream(&self, frame_rate: Option<Finite<f64>>) -> Fallible<Root<MediaStream>> {
+        if !self.origin_is_clean() {
+            return Err(Error::Security);
+        }
+        let renderer = match *self.context.borrow() {
+            Some(CanvasContext::Context2d(ref context)) => Some(context.ipc_renderer()),
+            None => None,
+            _ => return Err(Error::NotSupported) // WebGL
+        };
+
+        let mut settings = CaptureSettings::default();
+        if let Some(frame_rate) = frame_rate {
+            if *frame_rate < 0. {
+                return Err(Error::NotSupported);
+            }
+            settings.frame_rate = *frame_rate;
+        }
+        // FIXME: Frames should be captured whenever the canvas is painted,
+        // rather than at a fixed rate when no frame rate is given.
+        let size = self.get_size();
+        let capturer = CanvasCapturer::new(renderer, size.width as u32, size.height as u32);
+        let source = match CaptureThread::start_with_backend(canvas::device(), box capturer, &settings) {
+            Ok(source) => source,
+            Err(()) => return Err(Error::NotSupported),
+        };
+        let window = window_from_node(self);
+        let track = MediaStreamTrack::new(GlobalRef::Window(window.r()), source);
+        Ok(MediaStream::new(GlobalRef::Window(window.r()), &[track]))
+    }
+}
+
+/// Delivers the encoded file of a `toBlob()` call.
+struct ToBlobRunnable {
+    canvas: Trusted<HTMLCanvasElement>,
+    id: u32,
+    encoding: ImageEncoding,
+    result: Option<Vec<u8>>,
+}
+
+impl Runnable for ToBlobRunnable {
+    fn name(&self) -> &'static str { "ToBlobRunnable" }
+
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        this.canvas.root().finish_to_blob(this.id, this.encoding, this.result);
     }
 }
 
@@ -2,17 +2,20 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use dom::attr::Attr;
 use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::HTMLDialogElementBinding;
 use dom::bindings::codegen::Bindings::HTMLDialogElementBinding::HTMLDialogElementMethods;
+use dom::bindings::error::{Error, ErrorResult};
 use dom::bindings::inheritance::Castable;
 use dom::bindings::js::Root;
 use dom::bindings::str::DOMString;
-use dom::document::Document;
-use dom::element::Element;
+use dom::document::{Document, FocusType};
+use dom::element::{AttributeMutation, Element};
 use dom::eventtarget::EventTarget;
 use dom::htmlelement::HTMLElement;
-use dom::node::{Node, window_from_node};
+use dom::node::{Node, UnbindContext, document_from_node, window_from_node};
+use dom::virtualmethods::VirtualMethods;
 use string_cache::Atom;
 
 #[dom_struct]
@@ -40,6 +43,29 @@ impl HTMLDialogElement {
                            document,
                            HTMLDialogElementBinding::Wrap)
     }
+
+    // https://html.spec.whatwg.org/multipage/#dialog-focusing-steps
+    fn run_focusing_steps(&self) {
+        let doc = document_from_node(self);
+        let control = self.upcast::<Node>().traverse_preorder()
+            .filter_map(Root::downcast::<Element>)
+            .find(|element| element.is_focusable_area() && !doc.is_inert(element.upcast()));
+        doc.begin_focus_transaction();
+        if let Some(control) = control {
+            doc.request_focus(&control);
+        }
+        doc.commit_focus_transaction(FocusType::Element);
+    }
+
+    // Takes the dialog out of the top layer, when it stops being modal.
+    fn remove_from_top_layer(&self) {
+        let element = self.upcast::<Element>();
+        if !element.modal_state() {
+            return;
+        }
+        element.set_modal_state(false);
+        document_from_node(self).remove_from_top_layer(element);
+    }
 }
 
 impl HTMLDialogElementMethods for HTMLDialogElement {
@@ -60,6 +86,52 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
         *self.return_value.borrow_mut() = return_value;
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-dialog-show
+    fn Show(&self) {
+        let element = self.upcast::<Element>();
+
+        // Step 1
+        if element.has_attribute(&atom!("open")) {
+            return;
+        }
+
+        // Step 2
+        element.set_bool_attribute(&atom!("open"), true);
+
+        // Step 4
+        self.run_focusing_steps();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-dialog-showmodal
+    fn ShowModal(&self) -> ErrorResult {
+        let element = self.upcast::<Element>();
+
+        // Step 1
+        if element.has_attribute(&atom!("open")) {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2
+        if !self.upcast::<Node>().is_in_doc() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3
+        element.set_bool_attribute(&atom!("open"), true);
+
+        // Steps 4 & 5
+        element.set_modal_state(true);
+
+        // Step 6
+        document_from_node(self).add_to_top_layer(element);
+
+        // TODO: Step 7 position the dialog relative to an anchor
+
+        // Step 8
+        self.run_focusing_steps();
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-dialog-close
     fn Close(&self, return_value: Option<DOMString>) {
         let element = self.upcast::<Element>();
@@ -76,9 +148,28 @@ impl HTMLDialogElementMethods for HTMLDialogElement {
             *self.return_value.borrow_mut() = new_value;
         }
 
-        // TODO: Step 4 implement pending dialog stack removal
+        // Step 4
+        self.remove_from_top_layer();
 
         // Step 5
         win.dom_manipulation_task_source().queue_simple_event(target, atom!("close"), win.r());
     }
 }
+
+impl VirtualMethods for HTMLDialogElement {
+    fn super_type(&self) -> Option<&VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &VirtualMethods)
+    }
+
+    fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation) {
+        self.super_type().unwrap().attribute_mutated(attr, mutation);
+        if *attr.local_name() == atom!("open") && mutation == AttributeMutation::Removed {
+            self.remove_from_top_layer();
+        }
+    }
+
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        self.super_type().unwrap().unbind_from_tree(context);
+        self.remove_from_top_layer();
+    }
+}
@@ -36,6 +36,7 @@ use dom::htmloutputelement::HTMLOutputElement;
 use dom::htmlselectelement::HTMLSelectElement;
 use dom::htmltextareaelement::HTMLTextAreaElement;
 use dom::node::{Node, document_from_node, window_from_node};
+use dom::urlhelper::UrlHelper;
 use dom::virtualmethods::VirtualMethods;
 use encoding::EncodingRef;
 use encoding::all::UTF_8;
@@ -162,6 +163,16 @@ impl HTMLFormElementMethods for HTMLFormElement {
         self.reset(ResetFrom::FromForm);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-form-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.static_validation().is_ok()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-form-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.interactive_validation().is_ok()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-form-elements
     fn Elements(&self) -> Root<HTMLFormControlsCollection> {
         if let Some(elements) = self.elements.get() {
@@ -302,8 +313,6 @@ impl HTMLFormElement {
            !submitter.no_validate(self)
         {
             if self.interactive_validation().is_err() {
-                // TODO: Implement event handlers on all form control elements
-                self.upcast::<EventTarget>().fire_simple_event("invalid");
                 return;
             }
         }
@@ -342,7 +351,7 @@ impl HTMLFormElement {
         let _target = submitter.target();
         // TODO: Handle browsing contexts, partially loaded documents (step 16-17)
 
-        let mut load_data = LoadData::new(action_components, doc.get_referrer_policy(), Some(doc.url().clone()));
+        let mut load_data = LoadData::new(action_components, doc.get_referrer_policy(), Some(doc.url()));
 
         // Step 18
         match (&*scheme, method) {
@@ -381,10 +390,10 @@ impl HTMLFormElement {
     fn mutate_action_url(&self, form_data: &mut Vec<FormDatum>, mut load_data: LoadData, encoding: EncodingRef) {
         let charset = &*encoding.whatwg_name().unwrap();
 
-        load_data.url.query_pairs_mut().clear()
-                 .encoding_override(Some(self.pick_encoding()))
-                 .extend_pairs(form_data.into_iter()
-                                        .map(|field| (field.name.clone(), field.replace_value(charset))));
+        let query = UrlHelper::SerializePairs(form_data.into_iter()
+                                                       .map(|field| (field.name.clone(), field.replace_value(charset))),
+                                              Some(self.pick_encoding()));
+        load_data.url.set_query(Some(&query));
 
         self.plan_to_navigate(load_data);
     }
@@ -395,16 +404,12 @@ impl HTMLFormElement {
         let boundary = generate_boundary();
         let bytes = match enctype {
             FormEncType::UrlEncoded => {
-                let mut url = load_data.url.clone();
                 let charset = &*encoding.whatwg_name().unwrap();
                 load_data.headers.set(ContentType::form_url_encoded());
 
-                url.query_pairs_mut().clear()
-                   .encoding_override(Some(self.pick_encoding()))
-                   .extend_pairs(form_data.into_iter()
-                                          .map(|field| (field.name.clone(), field.replace_value(charset))));
-
-                url.query().unwrap_or("").to_string().into_bytes()
+                UrlHelper::SerializePairs(form_data.into_iter()
+                                                   .map(|field| (field.name.clone(), field.replace_value(charset))),
+                                          Some(self.pick_encoding())).into_bytes()
             }
             FormEncType::FormDataEncoded => {
                 let mime = mime!(Multipart / FormData; Boundary =(&boundary));
@@ -467,12 +472,14 @@ impl HTMLFormElement {
         //               form, refactor this when html5ever's form owner PR lands
         // Step 1-3
         let invalid_controls = node.traverse_preorder().filter_map(|field| {
-            if let Some(_el) = field.downcast::<Element>() {
-                None // Remove this line if you decide to refactor
-
-                // XXXKiChjang: Form control elements should each have a candidate_for_validation
-                //              and satisfies_constraints methods
-
+            if let Some(el) = field.downcast::<Element>() {
+                match el.as_maybe_validatable() {
+                    Some(validatable) if validatable.is_instance_validatable() &&
+                                         !validatable.satisfies_constraints() => {
+                        FormSubmittableElement::from_element(el)
+                    },
+                    _ => None,
+                }
             } else {
                 None
             }
@@ -703,6 +710,22 @@ pub enum FormSubmittableElement {
 }
 
 impl FormSubmittableElement {
+    fn from_element(element: &Element) -> Option<FormSubmittableElement> {
+        if let Some(button) = element.downcast::<HTMLButtonElement>() {
+            Some(FormSubmittableElement::ButtonElement(Root::from_ref(button)))
+        } else if let Some(input) = element.downcast::<HTMLInputElement>() {
+            Some(FormSubmittableElement::InputElement(Root::from_ref(input)))
+        } else if let Some(object) = element.downcast::<HTMLObjectElement>() {
+            Some(FormSubmittableElement::ObjectElement(Root::from_ref(object)))
+        } else if let Some(select) = element.downcast::<HTMLSelectElement>() {
+            Some(FormSubmittableElement::SelectElement(Root::from_ref(select)))
+        } else if let Some(textarea) = element.downcast::<HTMLTextAreaElement>() {
+            Some(FormSubmittableElement::TextAreaElement(Root::from_ref(textarea)))
+        } else {
+            None
+        }
+    }
+
     fn as_event_target(&self) -> &EventTarget {
         match *self {
             FormSubmittableElement::ButtonElement(ref button) => button.r().upcast(),
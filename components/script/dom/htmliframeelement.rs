@@ -11,6 +11,7 @@ use dom::bindings::codegen::Bindings::BrowserElementBinding::BrowserElementLocat
 use dom::bindings::codegen::Bindings::BrowserElementBinding::BrowserElementOpenTabEventDetail;
 use dom::bindings::codegen::Bindings::BrowserElementBinding::BrowserElementOpenWindowEventDetail;
 use dom::bindings::codegen::Bindings::BrowserElementBinding::BrowserElementSecurityChangeDetail;
+use dom::bindings::codegen::Bindings::BrowserElementBinding::BrowserElementAudioPlaybackChangeEventDetail;
 use dom::bindings::codegen::Bindings::BrowserElementBinding::BrowserElementVisibilityChangeEventDetail;
 use dom::bindings::codegen::Bindings::BrowserElementBinding::BrowserShowModalPromptEventDetail;
 use dom::bindings::codegen::Bindings::HTMLIFrameElementBinding;
@@ -21,6 +22,7 @@ use dom::bindings::error::{Error, ErrorResult, Fallible};
 use dom::bindings::global::GlobalRef;
 use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, LayoutJS, MutNullableHeap, Root};
+use dom::bindings::refcounted::Trusted;
 use dom::bindings::reflector::Reflectable;
 use dom::bindings::str::DOMString;
 use dom::browsingcontext::BrowsingContext;
@@ -41,12 +43,15 @@ use js::jsval::{NullValue, UndefinedValue};
 use msg::constellation_msg::{FrameType, LoadData, PipelineId, TraversalDirection};
 use net_traits::response::HttpsState;
 use script_layout_interface::message::ReflowQueryType;
-use script_traits::{IFrameLoadInfo, MozBrowserEvent, ScriptMsg as ConstellationMsg};
+use script_thread::{Runnable, ScriptThread};
+use script_traits::{DialogResponse, IFrameLoadInfo, IFrameSandboxState, MozBrowserEvent};
+use script_traits::ScriptMsg as ConstellationMsg;
 use script_traits::IFrameSandboxState::{IFrameSandboxed, IFrameUnsandboxed};
 use std::cell::Cell;
 use string_cache::Atom;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto};
 use style::context::ReflowGoal;
+use task_source::TaskSource;
 use url::Url;
 use util::prefs::PREFS;
 use util::servo_version;
@@ -64,14 +69,23 @@ bitflags! {
     }
 }
 
+#[derive(PartialEq)]
+pub enum ProcessingMode {
+    FirstTime,
+    NotFirstTime,
+}
+
 #[dom_struct]
 pub struct HTMLIFrameElement {
     htmlelement: HTMLElement,
     pipeline_id: Cell<Option<PipelineId>>,
+    /// The pipeline of the initial about:blank document, created when the element was inserted.
+    about_blank_pipeline_id: Cell<Option<PipelineId>>,
     sandbox: MutNullableHeap<JS<DOMTokenList>>,
     sandbox_allowance: Cell<Option<SandboxAllowance>>,
     load_blocker: DOMRefCell<Option<LoadBlocker>>,
     visibility: Cell<bool>,
+    muted: Cell<bool>,
 }
 
 impl HTMLIFrameElement {
@@ -100,13 +114,57 @@ impl HTMLIFrameElement {
         (old_pipeline_id, new_pipeline_id)
     }
 
-    pub fn navigate_or_reload_child_browsing_context(&self, load_data: Option<LoadData>, replace: bool) {
-        let sandboxed = if self.is_sandboxed() {
+    fn sandbox_state(&self) -> IFrameSandboxState {
+        if self.is_sandboxed() {
             IFrameSandboxed
         } else {
             IFrameUnsandboxed
+        }
+    }
+
+    fn frame_type(&self) -> FrameType {
+        if self.Mozbrowser() { FrameType::MozBrowserIFrame } else { FrameType::IFrame }
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#creating-a-new-browsing-context
+    ///
+    /// The initial about:blank document is built synchronously in this script thread, so
+    /// that `contentWindow` and `contentDocument` are usable as soon as the element is
+    /// inserted, before any navigation to its `src` completes.
+    fn create_nested_browsing_context(&self) {
+        let document = document_from_node(self);
+        let window = window_from_node(self);
+        let url = Url::parse("about:blank").unwrap();
+        let load_data = LoadData::new(url, document.get_referrer_policy(), Some(document.url()));
+        let (old_pipeline_id, new_pipeline_id) = self.generate_new_pipeline_id();
+
+        let load_info = IFrameLoadInfo {
+            load_data: Some(load_data),
+            parent_pipeline_id: window.pipeline_id(),
+            old_pipeline_id: old_pipeline_id,
+            new_pipeline_id: new_pipeline_id,
+            sandbox: self.sandbox_state(),
+            is_private: self.privatebrowsing(),
+            frame_type: self.frame_type(),
+            replace: false,
         };
+        let (sender, receiver) = ipc::channel().unwrap();
+        window.constellation_chan()
+              .send(ConstellationMsg::ScriptNewIFrame(load_info, sender))
+              .unwrap();
+
+        // The constellation drops the sender without replying if it could not create the
+        // pipeline, e.g. while shutting down.
+        match receiver.recv() {
+            Ok(new_layout_info) => {
+                self.about_blank_pipeline_id.set(Some(new_pipeline_id));
+                ScriptThread::process_attach_layout(new_layout_info);
+            },
+            Err(_) => self.pipeline_id.set(old_pipeline_id),
+        }
+    }
 
+    pub fn navigate_or_reload_child_browsing_context(&self, load_data: Option<LoadData>, replace: bool) {
         let document = document_from_node(self);
 
         let mut load_blocker = self.load_blocker.borrow_mut();
@@ -123,17 +181,15 @@ impl HTMLIFrameElement {
 
         let window = window_from_node(self);
         let (old_pipeline_id, new_pipeline_id) = self.generate_new_pipeline_id();
-        let private_iframe = self.privatebrowsing();
-        let frame_type = if self.Mozbrowser() { FrameType::MozBrowserIFrame } else { FrameType::IFrame };
 
         let load_info = IFrameLoadInfo {
             load_data: load_data,
             parent_pipeline_id: window.pipeline_id(),
             old_pipeline_id: old_pipeline_id,
             new_pipeline_id: new_pipeline_id,
-            sandbox: sandboxed,
-            is_private: private_iframe,
-            frame_type: frame_type,
+            sandbox: self.sandbox_state(),
+            is_private: self.privatebrowsing(),
+            frame_type: self.frame_type(),
             replace: replace,
         };
         window.constellation_chan()
@@ -146,12 +202,49 @@ impl HTMLIFrameElement {
         }
     }
 
-    pub fn process_the_iframe_attributes(&self) {
+    /// The load of an about:srcdoc document, if the element has a srcdoc attribute.
+    /// https://html.spec.whatwg.org/multipage/#process-the-iframe-attributes step 1
+    fn srcdoc_load_data(&self) -> Option<LoadData> {
+        let element = self.upcast::<Element>();
+        element.get_attribute(&ns!(), &atom!("srcdoc")).map(|srcdoc| {
+            let document = document_from_node(self);
+            let url = Url::parse("about:srcdoc").unwrap();
+            let mut load_data = LoadData::new(url, document.get_referrer_policy(), Some(document.url()));
+            load_data.srcdoc = Some(String::from(srcdoc.value().clone()));
+            load_data
+        })
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#process-the-iframe-attributes
+    pub fn process_the_iframe_attributes(&self, mode: ProcessingMode) {
+        // The first navigation away from the initial about:blank document replaces it in
+        // the session history.
+        let replace = mode == ProcessingMode::FirstTime && self.about_blank_pipeline_id.get().is_some();
+
+        // Step 1.
+        if let Some(load_data) = self.srcdoc_load_data() {
+            return self.navigate_or_reload_child_browsing_context(Some(load_data), replace);
+        }
+
+        // Step 2.1: an iframe inserted without a src keeps its about:blank document.
+        let has_src = self.upcast::<Element>().has_attribute(&atom!("src"));
+        if mode == ProcessingMode::FirstTime && !has_src {
+            if let Some(pipeline_id) = self.about_blank_pipeline_id.get() {
+                let window = window_from_node(self);
+                let task = box IFrameLoadEventStepsRunnable {
+                    element: Trusted::new(self),
+                    pipeline_id: pipeline_id,
+                };
+                let _ = window.dom_manipulation_task_source().queue(task, GlobalRef::Window(&window));
+                return;
+            }
+        }
+
         let url = self.get_url();
 
         let document = document_from_node(self);
         self.navigate_or_reload_child_browsing_context(
-            Some(LoadData::new(url, document.get_referrer_policy(), Some(document.url().clone()))), false);
+            Some(LoadData::new(url, document.get_referrer_policy(), Some(document.url()))), replace);
     }
 
     #[allow(unsafe_code)]
@@ -180,10 +273,12 @@ impl HTMLIFrameElement {
         HTMLIFrameElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
             pipeline_id: Cell::new(None),
+            about_blank_pipeline_id: Cell::new(None),
             sandbox: Default::default(),
             sandbox_allowance: Cell::new(None),
             load_blocker: DOMRefCell::new(None),
             visibility: Cell::new(true),
+            muted: Cell::new(false),
         }
     }
 
@@ -221,12 +316,37 @@ impl HTMLIFrameElement {
         }
     }
 
+    /// Mutes or unmutes the media of the browsing context of a mozbrowser iframe.
+    fn set_muted(&self, muted: bool) -> ErrorResult {
+        if !self.Mozbrowser() {
+            debug!("this frame is not mozbrowser: mozbrowser attribute missing, or not a top
+                level window, or mozbrowser preference not set (use --pref dom.mozbrowser.enabled)");
+            return Err(Error::NotSupported);
+        }
+        self.muted.set(muted);
+        if let Some(pipeline_id) = self.pipeline_id.get() {
+            let window = window_from_node(self);
+            window.constellation_chan().send(ConstellationMsg::SetMuted(pipeline_id, muted)).unwrap();
+        }
+        Ok(())
+    }
+
     /// https://html.spec.whatwg.org/multipage/#iframe-load-event-steps steps 1-4
     pub fn iframe_load_event_steps(&self, loaded_pipeline: PipelineId) {
+        // The steps for the initial about:blank document were queued when the element was
+        // inserted; that document finishing its load does not run them again.
+        if self.about_blank_pipeline_id.get() == Some(loaded_pipeline) {
+            return;
+        }
+
         // TODO(#9592): assert that the load blocker is present at all times when we
         //              can guarantee that it's created for the case of iframe.reload().
         assert_eq!(loaded_pipeline, self.pipeline_id().unwrap());
 
+        self.run_iframe_load_event_steps();
+    }
+
+    fn run_iframe_load_event_steps(&self) {
         // TODO A cross-origin child document would not be easily accessible
         //      from this script thread. It's unclear how to implement
         //      steps 2, 3, and 5 efficiently in this case.
@@ -400,6 +520,11 @@ unsafe fn build_mozbrowser_event_detail(event: MozBrowserEvent,
                 visible: Some(visibility),
             }.to_jsval(cx, rval);
         }
+        MozBrowserEvent::AudioPlaybackChange(active) => {
+            BrowserElementAudioPlaybackChangeEventDetail {
+                active: Some(active),
+            }.to_jsval(cx, rval);
+        }
     }
 }
 
@@ -492,7 +617,10 @@ impl HTMLIFrameElementMethods for HTMLIFrameElement {
     fn Reload(&self, _hard_reload: bool) -> ErrorResult {
         if self.Mozbrowser() {
             if self.upcast::<Node>().is_in_doc() {
-                self.navigate_or_reload_child_browsing_context(None, true);
+                // The constellation only knows the URL of the document, which isn't
+                // enough to load an about:srcdoc document again.
+                let load_data = self.srcdoc_load_data();
+                self.navigate_or_reload_child_browsing_context(load_data, true);
             }
             Ok(())
         } else {
@@ -525,12 +653,37 @@ impl HTMLIFrameElementMethods for HTMLIFrameElement {
         }
     }
 
+    // https://developer.mozilla.org/en-US/docs/Web/API/HTMLIFrameElement/mute
+    fn Mute(&self) -> ErrorResult {
+        self.set_muted(true)
+    }
+
+    // https://developer.mozilla.org/en-US/docs/Web/API/HTMLIFrameElement/unmute
+    fn Unmute(&self) -> ErrorResult {
+        self.set_muted(false)
+    }
+
+    // https://developer.mozilla.org/en-US/docs/Web/API/HTMLIFrameElement/getMuted
+    fn GetMuted(&self) -> Fallible<bool> {
+        if self.Mozbrowser() {
+            Ok(self.muted.get())
+        } else {
+            debug!("this frame is not mozbrowser: mozbrowser attribute missing, or not a top
+                level window, or mozbrowser preference not set (use --pref dom.mozbrowser.enabled)");
+            Err(Error::NotSupported)
+        }
+    }
 
     // https://developer.mozilla.org/en-US/docs/Web/API/HTMLIFrameElement/stop
     fn Stop(&self) -> ErrorResult {
         Err(Error::NotSupported)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-iframe-srcdoc
+    make_getter!(Srcdoc, "srcdoc");
+    // https://html.spec.whatwg.org/multipage/#dom-iframe-srcdoc
+    make_setter!(SetSrcdoc, "srcdoc");
+
     // https://html.spec.whatwg.org/multipage/#dom-dim-width
     make_getter!(Width, "width");
     // https://html.spec.whatwg.org/multipage/#dom-dim-width
@@ -555,6 +708,26 @@ impl HTMLIFrameElementMethods for HTMLIFrameElement {
             false
         }
     }
+
+    // Answers the oldest mozbrowsershowmodalprompt event of this iframe, the page of which waits
+    // for it.
+    fn AnswerModalPrompt(&self, accepted: bool, return_value: Option<DOMString>) -> ErrorResult {
+        if !self.Mozbrowser() {
+            debug!("this frame is not mozbrowser: mozbrowser attribute missing, or not a top
+                level window, or mozbrowser preference not set (use --pref dom.mozbrowser.enabled)");
+            return Err(Error::NotSupported);
+        }
+        if let Some(pipeline_id) = self.pipeline_id.get() {
+            let response = DialogResponse {
+                accepted: accepted,
+                text: if accepted { return_value.map(String::from) } else { None },
+                prevent_additional_dialogs: false,
+            };
+            let window = window_from_node(self);
+            window.constellation_chan().send(ConstellationMsg::AnswerModalPrompt(pipeline_id, response)).unwrap();
+        }
+        Ok(())
+    }
 }
 
 impl VirtualMethods for HTMLIFrameElement {
@@ -582,10 +755,18 @@ impl VirtualMethods for HTMLIFrameElement {
                     modes
                 }));
             },
+            &atom!("srcdoc") => {
+                // https://html.spec.whatwg.org/multipage/#the-iframe-element:process-the-iframe-attributes
+                if self.upcast::<Node>().is_in_doc() {
+                    self.process_the_iframe_attributes(ProcessingMode::NotFirstTime);
+                }
+            },
             &atom!("src") => {
+                // The srcdoc attribute takes precedence over src.
+                let has_srcdoc = self.upcast::<Element>().has_attribute(&atom!("srcdoc"));
                 if let AttributeMutation::Set(_) = mutation {
-                    if self.upcast::<Node>().is_in_doc() {
-                        self.process_the_iframe_attributes();
+                    if self.upcast::<Node>().is_in_doc() && !has_srcdoc {
+                        self.process_the_iframe_attributes(ProcessingMode::NotFirstTime);
                     }
                 }
             },
@@ -608,7 +789,8 @@ impl VirtualMethods for HTMLIFrameElement {
         }
 
         if tree_in_doc {
-            self.process_the_iframe_attributes();
+            self.create_nested_browsing_context();
+            self.process_the_iframe_attributes(ProcessingMode::FirstTime);
         }
     }
 
@@ -653,6 +835,25 @@ impl VirtualMethods for HTMLIFrameElement {
             // a new iframe. Without this, the constellation gets very
             // confused.
             self.pipeline_id.set(None);
+            self.about_blank_pipeline_id.set(None);
+        }
+    }
+}
+
+/// Runs the iframe load event steps for the initial about:blank document of an iframe,
+/// unless the iframe has navigated away from it in the meantime.
+struct IFrameLoadEventStepsRunnable {
+    element: Trusted<HTMLIFrameElement>,
+    pipeline_id: PipelineId,
+}
+
+impl Runnable for IFrameLoadEventStepsRunnable {
+    fn name(&self) -> &'static str { "IFrameLoadEventStepsRunnable" }
+
+    fn handler(self: Box<IFrameLoadEventStepsRunnable>) {
+        let element = self.element.root();
+        if element.pipeline_id() == Some(self.pipeline_id) {
+            element.run_iframe_load_event_steps();
         }
     }
 }
@@ -3,10 +3,12 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use app_units::Au;
+use cssparser::Parser as CssParser;
 use dom::attr::Attr;
 use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::HTMLImageElementBinding;
 use dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageElementMethods;
+use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use dom::bindings::error::Fallible;
 use dom::bindings::global::GlobalRef;
@@ -18,20 +20,32 @@ use dom::document::Document;
 use dom::element::{AttributeMutation, Element, RawLayoutElementHelpers};
 use dom::eventtarget::EventTarget;
 use dom::htmlelement::HTMLElement;
-use dom::node::{Node, NodeDamage, document_from_node, window_from_node};
+use dom::htmlpictureelement::HTMLPictureElement;
+use dom::htmlsourceelement::HTMLSourceElement;
+use dom::node::{Node, NodeDamage, UnbindContext, document_from_node, window_from_node};
+use dom::performanceresourcetiming::initiator_type;
 use dom::values::UNSIGNED_LONG_MAX;
 use dom::virtualmethods::VirtualMethods;
+use euclid::size::{Size2D, TypedSize2D};
 use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
+use net_traits::Metadata;
 use net_traits::image::base::{Image, ImageMetadata};
 use net_traits::image_cache_thread::{ImageResponder, ImageResponse};
+use net_traits::request::Destination;
 use script_runtime::CommonScriptMsg;
 use script_runtime::ScriptThreadEventCategory::UpdateReplacedElement;
 use script_thread::Runnable;
+use std::ascii::AsciiExt;
 use std::sync::Arc;
 use string_cache::Atom;
-use style::attr::{AttrValue, LengthOrPercentageOrAuto};
+use style::attr::{AttrValue, LengthOrPercentageOrAuto, parse_double, parse_unsigned_integer};
+use style::media_queries::{Device, MediaType, parse_media_query_list};
+use style::str::char_is_whitespace;
+use style::values::FONT_MEDIUM_PX;
+use style::values::specified::{CalcLengthOrPercentage, Length};
 use task_source::TaskSource;
+use time;
 use url::Url;
 
 #[derive(JSTraceable, HeapSizeOf)]
@@ -49,6 +63,11 @@ struct ImageRequest {
     source_url: Option<DOMString>,
     image: Option<Arc<Image>>,
     metadata: Option<ImageMetadata>,
+    /// The pixel density of the image source that was selected.
+    /// https://html.spec.whatwg.org/multipage/#current-pixel-density
+    current_pixel_density: f64,
+    /// When the image finished loading, as a `time::precise_time_ns` value.
+    load_time: Option<u64>,
 }
 #[dom_struct]
 pub struct HTMLImageElement {
@@ -61,20 +80,52 @@ impl HTMLImageElement {
     pub fn get_url(&self) -> Option<Url> {
         self.current_request.borrow().parsed_url.clone()
     }
+
+    /// When the image of the current request finished loading, if it did.
+    pub fn load_time(&self) -> Option<u64> {
+        self.current_request.borrow().load_time
+    }
+}
+
+/// The descriptor of an image candidate in a srcset attribute.
+/// https://html.spec.whatwg.org/multipage/#image-candidate-string
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Descriptor {
+    /// A width descriptor, in CSS pixels, such as `400w`.
+    Width(u32),
+    /// A pixel density descriptor, such as `2x`.
+    Density(f64),
+}
+
+/// An image candidate out of a srcset attribute, or the src attribute.
+/// https://html.spec.whatwg.org/multipage/#image-source
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageSource {
+    pub url: String,
+    /// The candidate's descriptor, if it had one. A candidate without one has a density of 1.
+    pub descriptor: Option<Descriptor>,
 }
 
 
 struct ImageResponseHandlerRunnable {
     element: Trusted<HTMLImageElement>,
     image: ImageResponse,
+    /// The metadata of the network response, if this request started the load.
+    metadata: Option<Metadata>,
+    /// The URL and pixel density that the image was requested for.
+    url: Url,
+    density: f64,
 }
 
 impl ImageResponseHandlerRunnable {
-    fn new(element: Trusted<HTMLImageElement>, image: ImageResponse)
-           -> ImageResponseHandlerRunnable {
+    fn new(element: Trusted<HTMLImageElement>, image: ImageResponse, metadata: Option<Metadata>,
+           url: Url, density: f64) -> ImageResponseHandlerRunnable {
         ImageResponseHandlerRunnable {
             element: element,
             image: image,
+            metadata: metadata,
+            url: url,
+            density: density,
         }
     }
 }
@@ -86,6 +137,20 @@ impl Runnable for ImageResponseHandlerRunnable {
         // Update the image field
         let element = self.element.root();
         let element_ref = element.r();
+
+        // The fetch happened whether or not its image is still wanted.
+        if let Some(ref metadata) = self.metadata {
+            window_from_node(element_ref).Performance().queue_resource_timing(&self.url,
+                                                                              initiator_type(Destination::Image),
+                                                                              metadata);
+        }
+
+        // Responses for a source that is no longer selected, because the selection changed
+        // while it was loading, must not replace the image of the current one.
+        if !element_ref.is_current_request(&self.url, self.density) {
+            return;
+        }
+
         let (image, metadata, trigger_image_load, trigger_image_error) = match self.image {
             ImageResponse::Loaded(image) | ImageResponse::PlaceholderLoaded(image) => {
                 (Some(image.clone()), Some(ImageMetadata { height: image.height, width: image.width } ), true, false)
@@ -97,6 +162,9 @@ impl Runnable for ImageResponseHandlerRunnable {
         };
         element_ref.current_request.borrow_mut().image = image;
         element_ref.current_request.borrow_mut().metadata = metadata;
+        if trigger_image_load {
+            element_ref.current_request.borrow_mut().load_time = Some(time::precise_time_ns());
+        }
 
         // Mark the node dirty
         let document = document_from_node(&*element);
@@ -119,9 +187,9 @@ impl Runnable for ImageResponseHandlerRunnable {
 }
 
 impl HTMLImageElement {
-    /// Makes the local `image` member match the status of the `src` attribute and starts
-    /// prefetching the image. This method must be called after `src` is changed.
-    fn update_image(&self, value: Option<(DOMString, Url)>) {
+    /// Makes the local `image` member match the selected image source and starts
+    /// prefetching the image. This method must be called after the selection changed.
+    fn update_image(&self, value: Option<(DOMString, f64, Url)>) {
         let document = document_from_node(self);
         let window = document.window();
         let image_cache = window.image_cache_thread();
@@ -130,23 +198,27 @@ impl HTMLImageElement {
                 self.current_request.borrow_mut().parsed_url = None;
                 self.current_request.borrow_mut().source_url = None;
                 self.current_request.borrow_mut().image = None;
+                self.current_request.borrow_mut().load_time = None;
             }
-            Some((src, base_url)) => {
+            Some((src, density, base_url)) => {
+                self.current_request.borrow_mut().current_pixel_density = density;
+                self.current_request.borrow_mut().load_time = None;
                 let img_url = base_url.join(&src);
                 if let Ok(img_url) = img_url {
                     self.current_request.borrow_mut().parsed_url = Some(img_url.clone());
                     self.current_request.borrow_mut().source_url = Some(src);
 
                     let trusted_node = Trusted::new(self);
+                    let request_url = img_url.clone();
                     let (responder_sender, responder_receiver) = ipc::channel().unwrap();
                     let script_chan = window.networking_task_source();
                     let wrapper = window.get_runnable_wrapper();
                     ROUTER.add_route(responder_receiver.to_opaque(), box move |message| {
                         // Return the image via a message to the script thread, which marks the element
                         // as dirty and triggers a reflow.
-                        let image_response = message.to().unwrap();
+                        let (image_response, metadata) = message.to().unwrap();
                         let runnable = box ImageResponseHandlerRunnable::new(
-                            trusted_node.clone(), image_response);
+                            trusted_node.clone(), image_response, metadata, request_url.clone(), density);
                         let runnable = wrapper.wrap_runnable(runnable);
                         let _ = script_chan.send(CommonScriptMsg::RunnableMsg(
                             UpdateReplacedElement, runnable));
@@ -194,6 +266,116 @@ impl HTMLImageElement {
             }
         }
     }
+
+    /// Whether `url`, selected for `density`, is the image source of the current request.
+    fn is_current_request(&self, url: &Url, density: f64) -> bool {
+        let current = self.current_request.borrow();
+        current.parsed_url.as_ref() == Some(url) && current.current_pixel_density == density
+    }
+
+    /// The device that `sizes` and the media of `<source>` elements are evaluated against,
+    /// and the device pixel ratio that an image candidate is chosen for.
+    fn environment(&self) -> (Device, f64) {
+        match window_from_node(self).window_size() {
+            Some(size) => (Device::new(MediaType::Screen, size.initial_viewport),
+                           size.device_pixel_ratio.get() as f64),
+            None => (Device::new(MediaType::Screen, TypedSize2D::new(0., 0.)), 1.),
+        }
+    }
+
+    /// The image candidates to select from, and the sizes attribute that goes with them:
+    /// those of the first matching `<source>` if this image is in a `<picture>`, otherwise
+    /// this element's own srcset and src.
+    /// https://html.spec.whatwg.org/multipage/#update-the-source-set
+    fn source_set(&self, device: &Device) -> (Vec<ImageSource>, DOMString) {
+        let node = self.upcast::<Node>();
+        if let Some(parent) = node.GetParentNode() {
+            if parent.is::<HTMLPictureElement>() {
+                for child in parent.children() {
+                    if child.r() == node {
+                        break;
+                    }
+                    let source_set = child.downcast::<HTMLSourceElement>()
+                                          .and_then(|source| source.source_set(device));
+                    if let Some(source_set) = source_set {
+                        return source_set;
+                    }
+                }
+            }
+        }
+
+        let element = self.upcast::<Element>();
+        let mut candidates = parse_a_srcset_attribute(&element.get_string_attribute(&atom!("srcset")));
+        let src = element.get_string_attribute(&atom!("src"));
+        let has_1x_or_width = candidates.iter().any(|candidate| match candidate.descriptor {
+            Some(Descriptor::Density(density)) => density == 1.,
+            Some(Descriptor::Width(_)) | None => true,
+        });
+        if !src.is_empty() && !has_1x_or_width {
+            candidates.push(ImageSource {
+                url: String::from(src),
+                descriptor: None,
+            });
+        }
+        (candidates, element.get_string_attribute(&atom!("sizes")))
+    }
+
+    /// Returns the URL and pixel density of the image source to use in the current environment.
+    /// https://html.spec.whatwg.org/multipage/#select-an-image-source
+    fn select_image_source(&self) -> Option<(DOMString, f64)> {
+        let (device, device_pixel_ratio) = self.environment();
+        let (candidates, sizes) = self.source_set(&device);
+        let source_size = evaluate_sizes_attribute(&sizes,
+                                                   device.viewport_size.width,
+                                                   device.viewport_size.height);
+        choose_image_candidate(&candidates, source_size as f64, device_pixel_ratio)
+            .map(|(candidate, density)| (DOMString::from(&*candidate.url), density))
+    }
+
+    /// Selects an image source and fetches it. This must be called after any of the
+    /// relevant mutations of this element.
+    /// https://html.spec.whatwg.org/multipage/#update-the-image-data
+    pub fn update_the_image_data(&self) {
+        let base_url = document_from_node(self).base_url();
+        self.update_image(self.select_image_source().map(|(src, density)| (src, density, base_url)));
+    }
+
+    /// Selects an image source again after the viewport size or the device pixel ratio
+    /// changed, and fetches it if it is not the current one.
+    /// https://html.spec.whatwg.org/multipage/#reacting-to-environment-changes
+    pub fn react_to_environment_changes(&self) {
+        let selected = self.select_image_source();
+        {
+            let current = self.current_request.borrow();
+            let unchanged = match selected {
+                Some((ref src, density)) => {
+                    current.source_url.as_ref() == Some(src) && current.current_pixel_density == density
+                }
+                None => current.source_url.is_none(),
+            };
+            if unchanged {
+                return;
+            }
+        }
+        let base_url = document_from_node(self).base_url();
+
+        // The image cache holds one decoded image per URL whatever density it was selected
+        // for, so when only the density of an image that was already fetched changed there
+        // is nothing to fetch, but its intrinsic size changes.
+        if let Some((ref src, density)) = selected {
+            let same_image = {
+                let current = self.current_request.borrow();
+                current.image.is_some() && current.parsed_url == base_url.join(src).ok()
+            };
+            if same_image {
+                self.current_request.borrow_mut().current_pixel_density = density;
+                self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+                return;
+            }
+        }
+        self.update_image(selected.map(|(src, density)| (src, density, base_url)));
+    }
+
     fn new_inherited(local_name: Atom, prefix: Option<DOMString>, document: &Document) -> HTMLImageElement {
         HTMLImageElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
@@ -202,14 +384,18 @@ impl HTMLImageElement {
                 parsed_url: None,
                 source_url: None,
                 image: None,
-                metadata: None
+                metadata: None,
+                current_pixel_density: 1.,
+                load_time: None,
             }),
             pending_request: DOMRefCell::new(ImageRequest {
                 state: State::Unavailable,
                 parsed_url: None,
                 source_url: None,
                 image: None,
-                metadata: None
+                metadata: None,
+                current_pixel_density: 1.,
+                load_time: None,
             }),
         }
     }
@@ -246,6 +432,9 @@ pub trait LayoutHTMLImageElementHelpers {
     #[allow(unsafe_code)]
     unsafe fn image_url(&self) -> Option<Url>;
 
+    #[allow(unsafe_code)]
+    unsafe fn image_density(&self) -> f64;
+
     fn get_width(&self) -> LengthOrPercentageOrAuto;
     fn get_height(&self) -> LengthOrPercentageOrAuto;
 }
@@ -261,6 +450,11 @@ impl LayoutHTMLImageElementHelpers for LayoutJS<HTMLImageElement> {
         (*self.unsafe_get()).current_request.borrow_for_layout().parsed_url.clone()
     }
 
+    #[allow(unsafe_code)]
+    unsafe fn image_density(&self) -> f64 {
+        (*self.unsafe_get()).current_request.borrow_for_layout().current_pixel_density
+    }
+
     #[allow(unsafe_code)]
     fn get_width(&self) -> LengthOrPercentageOrAuto {
         unsafe {
@@ -295,6 +489,16 @@ impl HTMLImageElementMethods for HTMLImageElement {
     // https://html.spec.whatwg.org/multipage/#dom-img-src
     make_setter!(SetSrc, "src");
 
+    // https://html.spec.whatwg.org/multipage/#dom-img-srcset
+    make_getter!(Srcset, "srcset");
+    // https://html.spec.whatwg.org/multipage/#dom-img-srcset
+    make_setter!(SetSrcset, "srcset");
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-sizes
+    make_getter!(Sizes, "sizes");
+    // https://html.spec.whatwg.org/multipage/#dom-img-sizes
+    make_setter!(SetSizes, "sizes");
+
     // https://html.spec.whatwg.org/multipage/#dom-img-crossOrigin
     make_enumerated_getter!(CrossOrigin, "crossorigin", "anonymous", ("use-credentials"));
     // https://html.spec.whatwg.org/multipage/#dom-img-crossOrigin
@@ -336,20 +540,20 @@ impl HTMLImageElementMethods for HTMLImageElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-img-naturalwidth
     fn NaturalWidth(&self) -> u32 {
-        let ref metadata = self.current_request.borrow().metadata;
+        let request = self.current_request.borrow();
 
-        match *metadata {
-            Some(ref metadata) => metadata.width,
+        match request.metadata {
+            Some(ref metadata) => (metadata.width as f64 / request.current_pixel_density) as u32,
             None => 0,
         }
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-img-naturalheight
     fn NaturalHeight(&self) -> u32 {
-        let ref metadata = self.current_request.borrow().metadata;
+        let request = self.current_request.borrow();
 
-        match *metadata {
-            Some(ref metadata) => metadata.height,
+        match request.metadata {
+            Some(ref metadata) => (metadata.height as f64 / request.current_pixel_density) as u32,
             None => 0,
         }
     }
@@ -362,10 +566,11 @@ impl HTMLImageElementMethods for HTMLImageElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-img-currentsrc
     fn CurrentSrc(&self) -> DOMString {
-        let ref url = self.current_request.borrow().source_url;
-        match *url {
-            Some(ref url) => url.clone(),
-            None => DOMString::from(""),
+        let request = self.current_request.borrow();
+        match (&request.parsed_url, &request.source_url) {
+            (&Some(ref url), _) => DOMString::from(url.as_str()),
+            (&None, &Some(ref url)) => url.clone(),
+            (&None, &None) => DOMString::from(""),
         }
     }
 
@@ -414,16 +619,33 @@ impl VirtualMethods for HTMLImageElement {
     fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation) {
         self.super_type().unwrap().attribute_mutated(attr, mutation);
         match attr.local_name() {
-            &atom!("src") => {
-                self.update_image(mutation.new_value(attr).map(|value| {
-                    // FIXME(ajeffrey): convert directly from AttrValue to DOMString
-                    (DOMString::from(&**value), document_from_node(self).base_url())
-                }));
-            },
+            &atom!("src") | &atom!("srcset") | &atom!("sizes") => self.update_the_image_data(),
             _ => {},
         }
     }
 
+    fn bind_to_tree(&self, tree_in_doc: bool) {
+        if let Some(ref s) = self.super_type() {
+            s.bind_to_tree(tree_in_doc);
+        }
+
+        // Being inserted into a <picture> is a relevant mutation.
+        let in_picture = self.upcast::<Node>().GetParentNode()
+                             .map_or(false, |parent| parent.is::<HTMLPictureElement>());
+        if in_picture {
+            self.update_the_image_data();
+        }
+    }
+
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        self.super_type().unwrap().unbind_from_tree(context);
+
+        // So is being removed from one.
+        if context.parent.is::<HTMLPictureElement>() {
+            self.update_the_image_data();
+        }
+    }
+
     fn parse_plain_attribute(&self, name: &Atom, value: DOMString) -> AttrValue {
         match name {
             &atom!("name") => AttrValue::from_atomic(value.into()),
@@ -446,3 +668,303 @@ fn image_dimension_setter(element: &Element, attr: Atom, value: u32) {
     let value = AttrValue::Dimension(value.to_string(), dim);
     element.set_attribute(&attr, value);
 }
+
+/// https://html.spec.whatwg.org/multipage/#parse-a-srcset-attribute
+pub fn parse_a_srcset_attribute(input: &str) -> Vec<ImageSource> {
+    let mut candidates = vec![];
+    let mut rest = input;
+    loop {
+        // Steps 4-5: skip whitespace and commas.
+        rest = rest.trim_left_matches(|c| char_is_whitespace(c) || c == ',');
+        if rest.is_empty() {
+            return candidates;
+        }
+
+        // Step 6: collect the URL.
+        let url_end = rest.find(char_is_whitespace).unwrap_or(rest.len());
+        let url = &rest[..url_end];
+        rest = &rest[url_end..];
+
+        // Steps 7-8: a URL that ends in commas has no descriptors.
+        let descriptors = if url.ends_with(',') {
+            vec![]
+        } else {
+            tokenize_descriptors(&mut rest)
+        };
+        let url = url.trim_right_matches(',');
+
+        // Steps 9-14: a candidate with invalid descriptors is dropped.
+        if let Ok(descriptor) = parse_descriptors(&descriptors) {
+            candidates.push(ImageSource {
+                url: url.to_owned(),
+                descriptor: descriptor,
+            });
+        }
+    }
+}
+
+/// Splits the descriptors of one image candidate off the start of `input`, up to the comma
+/// that ends the candidate. Whitespace and commas inside parentheses don't end a descriptor.
+/// https://html.spec.whatwg.org/multipage/#parse-a-srcset-attribute (step 8)
+fn tokenize_descriptors<'a>(input: &mut &'a str) -> Vec<&'a str> {
+    let mut descriptors = vec![];
+    let mut start = None;
+    let mut in_parens = false;
+    for (i, c) in input.char_indices() {
+        match c {
+            ')' if in_parens => in_parens = false,
+            _ if in_parens => {},
+            ',' => {
+                if let Some(start) = start {
+                    descriptors.push(&input[start..i]);
+                }
+                *input = &input[i + 1..];
+                return descriptors;
+            },
+            c if char_is_whitespace(c) => {
+                if let Some(start) = start.take() {
+                    descriptors.push(&input[start..i]);
+                }
+            },
+            c => {
+                if c == '(' {
+                    in_parens = true;
+                }
+                if start.is_none() {
+                    start = Some(i);
+                }
+            },
+        }
+    }
+    if let Some(start) = start {
+        descriptors.push(&input[start..]);
+    }
+    *input = "";
+    descriptors
+}
+
+/// https://html.spec.whatwg.org/multipage/#parse-a-srcset-attribute (steps 9-13)
+fn parse_descriptors(descriptors: &[&str]) -> Result<Option<Descriptor>, ()> {
+    let mut width = None;
+    let mut density = None;
+    let mut future_compat_height = None;
+    for descriptor in descriptors {
+        let (last, kind) = match descriptor.char_indices().last() {
+            Some(last) => last,
+            None => return Err(()),
+        };
+        let value = &descriptor[..last];
+        match kind {
+            'w' => {
+                if width.is_some() || density.is_some() || !is_valid_non_negative_integer(value) {
+                    return Err(());
+                }
+                match parse_unsigned_integer(value.chars()) {
+                    Ok(value) if value > 0 => width = Some(value),
+                    _ => return Err(()),
+                }
+            },
+            'x' => {
+                if width.is_some() || density.is_some() || future_compat_height.is_some() ||
+                   !is_valid_floating_point_number(value) {
+                    return Err(());
+                }
+                match parse_double(value) {
+                    Ok(value) if value >= 0. => density = Some(value),
+                    _ => return Err(()),
+                }
+            },
+            'h' => {
+                if future_compat_height.is_some() || density.is_some() ||
+                   !is_valid_non_negative_integer(value) {
+                    return Err(());
+                }
+                match parse_unsigned_integer(value.chars()) {
+                    Ok(value) if value > 0 => future_compat_height = Some(value),
+                    _ => return Err(()),
+                }
+            },
+            _ => return Err(()),
+        }
+    }
+
+    // A height descriptor is only allowed alongside a width descriptor, and is ignored.
+    if future_compat_height.is_some() && width.is_none() {
+        return Err(());
+    }
+
+    Ok(width.map(Descriptor::Width).or(density.map(Descriptor::Density)))
+}
+
+/// https://html.spec.whatwg.org/multipage/#valid-non-negative-integer
+fn is_valid_non_negative_integer(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b'0' <= b && b <= b'9')
+}
+
+/// https://html.spec.whatwg.org/multipage/#valid-floating-point-number
+fn is_valid_floating_point_number(value: &str) -> bool {
+    fn digits(value: &str) -> usize {
+        value.bytes().take_while(|&b| b'0' <= b && b <= b'9').count()
+    }
+
+    let value = if value.starts_with('-') { &value[1..] } else { value };
+    let integer = digits(value);
+    let mut rest = &value[integer..];
+    let mut fraction = 0;
+    if rest.starts_with('.') {
+        fraction = digits(&rest[1..]);
+        if fraction == 0 {
+            return false;
+        }
+        rest = &rest[fraction + 1..];
+    }
+    if integer == 0 && fraction == 0 {
+        return false;
+    }
+    if rest.starts_with('e') || rest.starts_with('E') {
+        rest = &rest[1..];
+        if rest.starts_with('-') || rest.starts_with('+') {
+            rest = &rest[1..];
+        }
+        let exponent = digits(rest);
+        if exponent == 0 {
+            return false;
+        }
+        rest = &rest[exponent..];
+    }
+    rest.is_empty()
+}
+
+/// Returns the size in CSS pixels that the sizes attribute `value` gives image candidates
+/// with width descriptors, in a viewport of the given size. This is the size of the first
+/// source size whose media condition matches, or the viewport width if none does.
+/// https://html.spec.whatwg.org/multipage/#parse-a-sizes-attribute
+pub fn evaluate_sizes_attribute(value: &str, viewport_width: f32, viewport_height: f32) -> f32 {
+    let device = Device::new(MediaType::Screen, TypedSize2D::new(viewport_width, viewport_height));
+    let viewport = device.au_viewport_size();
+    for source_size in value.split(',') {
+        let (condition, size) = split_source_size(source_size);
+        let mut input = CssParser::new(size);
+        let size = match Length::parse_non_negative(&mut input) {
+            Ok(ref size) if input.is_exhausted() => source_size_to_au(size, viewport),
+            _ => None,
+        };
+        if let Some(size) = size {
+            if parse_media_query_list(&mut CssParser::new(condition)).evaluate(&device) {
+                return size.to_f32_px();
+            }
+        }
+    }
+    viewport_width
+}
+
+/// Splits a source size into its media condition and its size, which is its last component
+/// value.
+fn split_source_size(source_size: &str) -> (&str, &str) {
+    let source_size = source_size.trim_right_matches(char_is_whitespace);
+    let is_separator = |c| char_is_whitespace(c) || c == ')';
+    let mut size_start = source_size.len();
+    if source_size.ends_with(')') {
+        // The size is a function such as calc(), which starts at the name before its
+        // matching opening parenthesis.
+        let mut depth = 0;
+        for (i, c) in source_size.char_indices().rev() {
+            match c {
+                ')' => depth += 1,
+                '(' => depth -= 1,
+                _ => {},
+            }
+            if depth == 0 {
+                size_start = i;
+                break;
+            }
+        }
+    }
+    let size_start = source_size[..size_start].rfind(is_separator).map_or(0, |i| i + 1);
+    source_size.split_at(size_start)
+}
+
+/// Computes a source size. Font-relative lengths are relative to the initial font size,
+/// and percentages are not allowed.
+fn source_size_to_au(size: &Length, viewport: Size2D<Au>) -> Option<Au> {
+    let font_size = Au::from_px(FONT_MEDIUM_PX);
+    match *size {
+        Length::Absolute(length) => Some(length),
+        Length::FontRelative(length) => Some(length.to_computed_value(font_size, font_size)),
+        Length::ViewportPercentage(length) => Some(length.to_computed_value(viewport)),
+        Length::Calc(ref calc, _) => calc_source_size_to_au(calc, viewport),
+        Length::ContainerPercentage(_) | Length::ServoCharacterWidth(_) => None,
+    }
+}
+
+fn calc_source_size_to_au(calc: &CalcLengthOrPercentage, viewport: Size2D<Au>) -> Option<Au> {
+    let font_size = Au::from_px(FONT_MEDIUM_PX);
+    if calc.percentage.is_some() || calc.cqw.is_some() || calc.cqh.is_some() {
+        return None;
+    }
+    let viewport_lengths = [calc.vw, calc.vh, calc.vmin, calc.vmax];
+    let font_lengths = [calc.em, calc.ex, calc.ch, calc.rem];
+    let mut length = calc.absolute.unwrap_or(Au(0));
+    for viewport_length in viewport_lengths.iter().filter_map(|length| *length) {
+        length = length + viewport_length.to_computed_value(viewport);
+    }
+    for font_length in font_lengths.iter().filter_map(|length| *length) {
+        length = length + font_length.to_computed_value(font_size, font_size);
+    }
+    for comparison in &calc.comparisons {
+        let mut arguments = Vec::with_capacity(comparison.arguments.len());
+        for argument in &comparison.arguments {
+            match calc_source_size_to_au(argument, viewport) {
+                Some(argument) => arguments.push(argument),
+                None => return None,
+            }
+        }
+        length = length + comparison.function.apply(&arguments);
+    }
+    Some(length)
+}
+
+/// Chooses the image candidate to use at `device_pixel_ratio`, given the source size in CSS
+/// pixels that width descriptors are relative to. Returns the candidate and its pixel density.
+/// https://html.spec.whatwg.org/multipage/#select-an-image-source
+pub fn choose_image_candidate(candidates: &[ImageSource],
+                              source_size: f64,
+                              device_pixel_ratio: f64)
+                              -> Option<(&ImageSource, f64)> {
+    let densities = candidates.iter().map(|candidate| {
+        let density = match candidate.descriptor {
+            Some(Descriptor::Width(width)) => width as f64 / source_size,
+            Some(Descriptor::Density(density)) => density,
+            None => 1.,
+        };
+        (candidate, density)
+    });
+
+    // Prefer the lowest density that is still enough for the device, falling back to the
+    // highest one. When several candidates have the same density the first one wins.
+    let mut best: Option<(&ImageSource, f64)> = None;
+    for (candidate, density) in densities {
+        best = match best {
+            None => Some((candidate, density)),
+            Some((_, best_density)) => {
+                let better = if best_density >= device_pixel_ratio {
+                    density >= device_pixel_ratio && density < best_density
+                } else {
+                    density > best_density
+                };
+                if better { Some((candidate, density)) } else { best }
+            },
+        };
+    }
+    best
+}
+
+/// Whether images of the MIME type `mime` can be decoded, for the type of `<source>` elements.
+pub fn is_supported_image_type(mime: &str) -> bool {
+    let essence = mime.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    match &*essence {
+        "" | "image/bmp" | "image/gif" | "image/jpeg" | "image/png" |
+        "image/vnd.microsoft.icon" | "image/x-icon" => true,
+        _ => false,
+    }
+}
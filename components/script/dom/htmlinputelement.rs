@@ -3,6 +3,7 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use caseless::compatibility_caseless_match_str;
+use cssparser::RGBA;
 use dom::activation::{Activatable, ActivationSource, synthetic_click_activation};
 use dom::attr::Attr;
 use dom::bindings::cell::DOMRefCell;
@@ -14,7 +15,7 @@ use dom::bindings::codegen::Bindings::KeyboardEventBinding::KeyboardEventMethods
 use dom::bindings::error::{Error, ErrorResult};
 use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, LayoutJS, MutNullableHeap, Root, RootedReference};
-use dom::bindings::str::DOMString;
+use dom::bindings::str::{DOMString, days_in_month};
 use dom::document::Document;
 use dom::element::{AttributeMutation, Element, LayoutElementHelpers, RawLayoutElementHelpers};
 use dom::event::{Event, EventBubbles, EventCancelable};
@@ -29,7 +30,8 @@ use dom::keyboardevent::KeyboardEvent;
 use dom::node::{Node, NodeDamage, UnbindContext};
 use dom::node::{document_from_node, window_from_node};
 use dom::nodelist::NodeList;
-use dom::validation::Validatable;
+use dom::validation::{Validatable, is_barred_by_datalist_ancestor};
+use dom::validitystate::*;
 use dom::virtualmethods::VirtualMethods;
 use ipc_channel::ipc::{self, IpcSender};
 use mime_guess;
@@ -37,21 +39,33 @@ use msg::constellation_msg::Key;
 use net_traits::{CoreResourceMsg, IpcSend};
 use net_traits::blob_url_store::get_blob_origin;
 use net_traits::filemanager_thread::{FileManagerThreadMsg, FilterPattern};
+use regex::Regex;
+use script_traits::InputPickerKind;
 use script_traits::ScriptMsg as ConstellationMsg;
+use std::ascii::AsciiExt;
 use std::borrow::ToOwned;
 use std::cell::Cell;
 use std::ops::Range;
 use string_cache::Atom;
-use style::attr::AttrValue;
+use style::attr::{AttrValue, parse_legacy_color};
 use style::element_state::*;
 use style::str::split_commas;
 use textinput::{SelectionDirection, TextInput};
 use textinput::KeyReaction::{DispatchInput, Nothing, RedrawSelection, TriggerDefaultAction};
 use textinput::Lines::Single;
+use url::Url;
 
 const DEFAULT_SUBMIT_VALUE: &'static str = "Submit";
 const DEFAULT_RESET_VALUE: &'static str = "Reset";
 const PASSWORD_REPLACEMENT_CHAR: char = '●';
+const DATE_PLACEHOLDER: &'static str = "yyyy-mm-dd";
+const TIME_PLACEHOLDER: &'static str = "--:--";
+const DEFAULT_COLOR_VALUE: &'static str = "#000000";
+const MS_PER_DAY: f64 = 86400000.;
+// https://html.spec.whatwg.org/multipage/#valid-e-mail-address
+const VALID_EMAIL_REGEX: &'static str = "^[a-zA-Z0-9.!#$%&'*+/=?^_`{|}~-]+@\
+                                         [a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?\
+                                         (?:\\.[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?)*$";
 
 #[derive(JSTraceable, PartialEq, Copy, Clone)]
 #[allow(dead_code)]
@@ -65,7 +79,10 @@ enum InputType {
     InputImage,
     InputCheckbox,
     InputRadio,
-    InputPassword
+    InputPassword,
+    InputDate,
+    InputTime,
+    InputColor,
 }
 
 #[derive(Debug, PartialEq)]
@@ -93,6 +110,7 @@ pub struct HTMLInputElement {
     value_dirty: Cell<bool>,
 
     filelist: MutNullableHeap<JS<FileList>>,
+    validity_state: MutNullableHeap<JS<ValidityState>>,
 }
 
 #[derive(JSTraceable)]
@@ -131,7 +149,8 @@ impl HTMLInputElement {
         let chan = document.window().constellation_chan().clone();
         HTMLInputElement {
             htmlelement:
-                HTMLElement::new_inherited_with_state(IN_ENABLED_STATE | IN_READ_WRITE_STATE,
+                HTMLElement::new_inherited_with_state(IN_ENABLED_STATE | IN_READ_WRITE_STATE |
+                                                      IN_VALID_STATE | IN_OPTIONAL_STATE,
                                                       local_name, prefix, document),
             input_type: Cell::new(InputType::InputText),
             placeholder: DOMRefCell::new(DOMString::new()),
@@ -149,6 +168,7 @@ impl HTMLInputElement {
             activation_state: DOMRefCell::new(InputActivationState::new()),
             value_dirty: Cell::new(false),
             filelist: MutNullableHeap::new(None),
+            validity_state: MutNullableHeap::new(None),
         }
     }
 
@@ -177,7 +197,10 @@ impl HTMLInputElement {
             InputType::InputCheckbox |
             InputType::InputRadio => ValueMode::DefaultOn,
             InputType::InputPassword |
-            InputType::InputText => ValueMode::Value,
+            InputType::InputText |
+            InputType::InputDate |
+            InputType::InputTime |
+            InputType::InputColor => ValueMode::Value,
             InputType::InputFile => ValueMode::Filename,
         }
     }
@@ -194,6 +217,8 @@ pub trait LayoutHTMLInputElementHelpers {
     unsafe fn checked_state_for_layout(self) -> bool;
     #[allow(unsafe_code)]
     unsafe fn indeterminate_state_for_layout(self) -> bool;
+    #[allow(unsafe_code)]
+    unsafe fn color_for_layout(self) -> Option<RGBA>;
 }
 
 #[allow(unsafe_code)]
@@ -219,6 +244,18 @@ impl LayoutHTMLInputElementHelpers for LayoutJS<HTMLInputElement> {
             InputType::InputButton => get_raw_attr_value(self, ""),
             InputType::InputSubmit => get_raw_attr_value(self, DEFAULT_SUBMIT_VALUE),
             InputType::InputReset => get_raw_attr_value(self, DEFAULT_RESET_VALUE),
+            // The swatch of a color control is its background, see `color_for_layout`.
+            InputType::InputColor => String::new(),
+            InputType::InputDate | InputType::InputTime => {
+                let text = get_raw_textinput_value(self);
+                if !text.is_empty() {
+                    String::from(text)
+                } else if (*self.unsafe_get()).input_type.get() == InputType::InputDate {
+                    String::from(DATE_PLACEHOLDER)
+                } else {
+                    String::from(TIME_PLACEHOLDER)
+                }
+            },
             InputType::InputPassword => {
                 let text = get_raw_textinput_value(self);
                 if !text.is_empty() {
@@ -281,6 +318,15 @@ impl LayoutHTMLInputElementHelpers for LayoutJS<HTMLInputElement> {
     unsafe fn indeterminate_state_for_layout(self) -> bool {
         self.upcast::<Element>().get_state_for_layout().contains(IN_INDETERMINATE_STATE)
     }
+
+    #[allow(unrooted_must_root)]
+    #[allow(unsafe_code)]
+    unsafe fn color_for_layout(self) -> Option<RGBA> {
+        if (*self.unsafe_get()).input_type.get() != InputType::InputColor {
+            return None;
+        }
+        parse_legacy_color(&get_raw_textinput_value(self)).ok()
+    }
 }
 
 impl HTMLInputElementMethods for HTMLInputElement {
@@ -402,6 +448,7 @@ impl HTMLInputElementMethods for HTMLInputElement {
         match self.value_mode() {
             ValueMode::Value => {
                 self.textinput.borrow_mut().set_content(value);
+                self.sanitize_value();
                 self.value_dirty.set(true);
             }
             ValueMode::Default |
@@ -420,6 +467,7 @@ impl HTMLInputElementMethods for HTMLInputElement {
         }
 
         self.value_changed.set(true);
+        self.update_validity_states();
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
         Ok(())
     }
@@ -598,6 +646,46 @@ impl HTMLInputElementMethods for HTMLInputElement {
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-input-stepup
+    fn StepUp(&self, n: i32) -> ErrorResult {
+        self.step_up_or_down(n)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-input-stepdown
+    fn StepDown(&self, n: i32) -> ErrorResult {
+        self.step_up_or_down(-n)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-willvalidate
+    fn WillValidate(&self) -> bool {
+        self.will_validate()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validity
+    fn Validity(&self) -> Root<ValidityState> {
+        self.validity_state()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    fn ValidationMessage(&self) -> DOMString {
+        self.validation_message()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.check_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.report_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity
+    fn SetCustomValidity(&self, error: DOMString) {
+        self.validity_state().set_custom_error_message(error);
+    }
+
     // Select the files based on filepaths passed in,
     // enabled by dom.htmlinputelement.select_files.enabled,
     // used for test purpose.
@@ -737,6 +825,18 @@ impl HTMLInputElement {
             .map(|name| name.value().as_atom().clone())
     }
 
+    // The other radio buttons in this element's radio button group.
+    fn radio_group_members(&self) -> Vec<Root<HTMLInputElement>> {
+        let owner = self.form_owner();
+        let group = self.radio_group_name();
+        let doc = document_from_node(self);
+        let members = doc.upcast::<Node>().query_selector_iter(DOMString::from("input[type=radio]")).unwrap()
+            .filter_map(Root::downcast::<HTMLInputElement>)
+            .filter(|r| in_same_group(r.r(), owner.r(), group.as_ref()) && self != r.r())
+            .collect();
+        members
+    }
+
     fn update_checked_state(&self, checked: bool, dirty: bool) {
         self.upcast::<Element>().set_state(IN_CHECKED_STATE, checked);
 
@@ -749,6 +849,14 @@ impl HTMLInputElement {
                                     self.radio_group_name().as_ref());
         }
 
+        if self.input_type.get() == InputType::InputRadio {
+            // Whether a required radio button is missing its value depends on its whole group.
+            for radio in self.radio_group_members() {
+                radio.update_validity_states();
+            }
+        }
+        self.update_validity_states();
+
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
         //TODO: dispatch change event
     }
@@ -760,6 +868,127 @@ impl HTMLInputElement {
         !(self.upcast::<Element>().disabled_state() || self.ReadOnly())
     }
 
+    // Whether the readonly attribute applies to the element's current type.
+    fn readonly_applies(&self) -> bool {
+        match self.input_type.get() {
+            InputType::InputText | InputType::InputPassword |
+            InputType::InputDate | InputType::InputTime => true,
+            _ => false,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-being-missing
+    fn suffers_from_being_missing(&self, value: &DOMString) -> bool {
+        let el = self.upcast::<Element>();
+        if el.disabled_state() || (self.ReadOnly() && self.readonly_applies()) {
+            return false;
+        }
+        match self.input_type.get() {
+            // https://html.spec.whatwg.org/multipage/#radio-button-state-(type=radio):suffering-from-being-missing
+            InputType::InputRadio => {
+                let members = self.radio_group_members();
+                let required = self.Required() || members.iter().any(|r| r.Required());
+                let checked = self.Checked() || members.iter().any(|r| r.Checked());
+                required && !checked
+            },
+            _ if !self.Required() => false,
+            InputType::InputText | InputType::InputPassword |
+            InputType::InputDate | InputType::InputTime => value.is_empty(),
+            // https://html.spec.whatwg.org/multipage/#checkbox-state-(type=checkbox):suffering-from-being-missing
+            InputType::InputCheckbox => !self.Checked(),
+            // https://html.spec.whatwg.org/multipage/#file-upload-state-(type=file):suffering-from-being-missing
+            InputType::InputFile => self.filelist.get().map_or(true, |files| files.Length() == 0),
+            _ => false,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-a-type-mismatch
+    fn suffers_from_type_mismatch(&self, value: &DOMString) -> bool {
+        if value.is_empty() {
+            return false;
+        }
+        let ty = self.type_();
+        if ty.eq_ignore_ascii_case("url") {
+            // https://html.spec.whatwg.org/multipage/#url-state-(type=url):suffering-from-a-type-mismatch
+            Url::parse(value).is_err()
+        } else if ty.eq_ignore_ascii_case("email") {
+            // https://html.spec.whatwg.org/multipage/#e-mail-state-(type=email):suffering-from-a-type-mismatch
+            let regex = Regex::new(VALID_EMAIL_REGEX).unwrap();
+            if self.Multiple() {
+                split_commas(value).any(|email| !regex.is_match(email.trim()))
+            } else {
+                !regex.is_match(value)
+            }
+        } else {
+            false
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-a-pattern-mismatch
+    fn suffers_from_pattern_mismatch(&self, value: &DOMString) -> bool {
+        match self.input_type.get() {
+            InputType::InputText | InputType::InputPassword => {},
+            _ => return false,
+        }
+        if value.is_empty() || !self.upcast::<Element>().has_attribute(&atom!("pattern")) {
+            return false;
+        }
+        // The pattern has to match the entire value.
+        let regex = match Regex::new(&format!("^(?:{})$", self.Pattern())) {
+            Ok(regex) => regex,
+            // An invalid pattern is ignored.
+            Err(_) => return false,
+        };
+        if self.type_().eq_ignore_ascii_case("email") && self.Multiple() {
+            split_commas(value).any(|email| !regex.is_match(email.trim()))
+        } else {
+            !regex.is_match(value)
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#suffering-from-being-too-long
+    // https://html.spec.whatwg.org/multipage/#suffering-from-being-too-short
+    fn length_flags(&self, value: &DOMString) -> ValidationFlags {
+        let mut flags = ValidationFlags::empty();
+        match self.input_type.get() {
+            // Only values that were changed can be too long or too short.
+            InputType::InputText | InputType::InputPassword
+                if self.value_changed.get() && !value.is_empty() => {},
+            _ => return flags,
+        }
+        let length = value.encode_utf16().count();
+        let textinput = self.textinput.borrow();
+        if textinput.max_length.map_or(false, |max_length| length > max_length) {
+            flags.insert(TOO_LONG);
+        }
+        if textinput.min_length.map_or(false, |min_length| length < min_length) {
+            flags.insert(TOO_SHORT);
+        }
+        flags
+    }
+
+    // https://html.spec.whatwg.org/multipage/#the-min-and-max-attributes
+    // https://html.spec.whatwg.org/multipage/#the-step-attribute
+    fn range_flags(&self, value: &DOMString) -> ValidationFlags {
+        let mut flags = ValidationFlags::empty();
+        let number = match self.convert_string_to_number(value) {
+            Some(number) => number,
+            None => return flags,
+        };
+        if self.convert_string_to_number(&self.Min()).map_or(false, |min| number < min) {
+            flags.insert(RANGE_UNDERFLOW);
+        }
+        if self.convert_string_to_number(&self.Max()).map_or(false, |max| number > max) {
+            flags.insert(RANGE_OVERFLOW);
+        }
+        if let Some(step) = self.allowed_value_step() {
+            if ((number - self.step_base()) / step).fract() != 0. {
+                flags.insert(STEP_MISMATCH);
+            }
+        }
+        flags
+    }
+
     // https://html.spec.whatwg.org/multipage/#the-input-element:concept-form-reset-control
     pub fn reset(&self) {
         match self.input_type.get() {
@@ -778,6 +1007,184 @@ impl HTMLInputElement {
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
+    // https://html.spec.whatwg.org/multipage/#value-sanitization-algorithm
+    fn sanitize_value(&self) {
+        let mut textinput = self.textinput.borrow_mut();
+        let value = textinput.get_content();
+        let sanitized = match self.input_type.get() {
+            // https://html.spec.whatwg.org/multipage/#date-state-(type=date):value-sanitization-algorithm
+            InputType::InputDate if !value.is_valid_date_string() => DOMString::new(),
+            // https://html.spec.whatwg.org/multipage/#time-state-(type=time):value-sanitization-algorithm
+            InputType::InputTime if !value.is_valid_time_string() => DOMString::new(),
+            // https://html.spec.whatwg.org/multipage/#color-state-(type=color):value-sanitization-algorithm
+            InputType::InputColor if value.is_valid_simple_color_string() => {
+                DOMString::from(value.to_ascii_lowercase())
+            },
+            InputType::InputColor => DOMString::from(DEFAULT_COLOR_VALUE),
+            _ => return,
+        };
+        if sanitized != value {
+            textinput.set_content(sanitized);
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-value-string-number
+    fn convert_string_to_number(&self, value: &DOMString) -> Option<f64> {
+        match self.input_type.get() {
+            // https://html.spec.whatwg.org/multipage/#date-state-(type=date):concept-input-value-string-number
+            InputType::InputDate => value.parse_date_string().ok().map(|(year, month, day)| {
+                days_from_civil(year, month, day) as f64 * MS_PER_DAY
+            }),
+            // https://html.spec.whatwg.org/multipage/#time-state-(type=time):concept-input-value-string-number
+            InputType::InputTime => value.parse_time_string().ok().map(|(hour, minute, second)| {
+                ((hour * 60 + minute) as f64 * 60. + second) * 1000.
+            }),
+            _ => None,
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-value-number-string
+    fn convert_number_to_string(&self, value: f64) -> DOMString {
+        match self.input_type.get() {
+            // https://html.spec.whatwg.org/multipage/#date-state-(type=date):concept-input-value-number-string
+            InputType::InputDate => {
+                let (year, month, day) = civil_from_days((value / MS_PER_DAY).floor() as i64);
+                DOMString::from(format!("{:04}-{:02}-{:02}", year, month, day))
+            },
+            // https://html.spec.whatwg.org/multipage/#time-state-(type=time):concept-input-value-number-string
+            InputType::InputTime => {
+                let ms = value.round() % MS_PER_DAY;
+                let ms = (if ms < 0. { ms + MS_PER_DAY } else { ms }) as u64;
+                let (hour, minute) = (ms / 3600000, ms / 60000 % 60);
+                let (second, millisecond) = (ms / 1000 % 60, ms % 1000);
+                DOMString::from(match (second, millisecond) {
+                    (0, 0) => format!("{:02}:{:02}", hour, minute),
+                    (_, 0) => format!("{:02}:{:02}:{:02}", hour, minute, second),
+                    _ => format!("{:02}:{:02}:{:02}.{:03}", hour, minute, second, millisecond),
+                })
+            },
+            _ => DOMString::new(),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-step
+    fn allowed_value_step(&self) -> Option<f64> {
+        let (default_step, step_scale_factor) = match self.input_type.get() {
+            // https://html.spec.whatwg.org/multipage/#date-state-(type=date):concept-input-step-default
+            InputType::InputDate => (1., MS_PER_DAY),
+            // https://html.spec.whatwg.org/multipage/#time-state-(type=time):concept-input-step-default
+            InputType::InputTime => (60., 1000.),
+            _ => return None,
+        };
+        let step = self.Step();
+        if step.eq_ignore_ascii_case("any") {
+            return None;
+        }
+        let step = match step.trim().parse::<f64>() {
+            Ok(step) if step > 0. && step.is_finite() => step,
+            _ => default_step,
+        };
+        if self.input_type.get() == InputType::InputDate {
+            // Dates only step by whole days.
+            return Some(step.round().max(1.) * step_scale_factor);
+        }
+        Some(step * step_scale_factor)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-input-min-zero
+    fn step_base(&self) -> f64 {
+        self.convert_string_to_number(&self.Min())
+            .or_else(|| self.convert_string_to_number(&self.DefaultValue()))
+            .unwrap_or(0.)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-input-stepup
+    fn step_up_or_down(&self, n: i32) -> ErrorResult {
+        // Steps 1-2.
+        let step = match self.allowed_value_step() {
+            Some(step) => step,
+            None => return Err(Error::InvalidState),
+        };
+
+        // Step 3.
+        let min = self.convert_string_to_number(&self.Min());
+        let max = self.convert_string_to_number(&self.Max());
+        if let (Some(min), Some(max)) = (min, max) {
+            if max < min {
+                return Ok(());
+            }
+        }
+
+        let step_base = self.step_base();
+
+        // Steps 5-6.
+        let value_before_stepping = self.convert_string_to_number(&self.Value()).unwrap_or(0.);
+
+        // Step 7.
+        let steps = (value_before_stepping - step_base) / step;
+        let mut value = if steps.fract() != 0. {
+            step_base + if n > 0 { steps.ceil() } else { steps.floor() } * step
+        } else {
+            value_before_stepping + step * n as f64
+        };
+
+        // Step 8.
+        if let Some(min) = min {
+            if value < min {
+                value = step_base + ((min - step_base) / step).ceil() * step;
+            }
+        }
+        if let Some(max) = max {
+            if value > max {
+                value = step_base + ((max - step_base) / step).floor() * step;
+            }
+        }
+
+        // Step 9.
+        if (n < 0 && value > value_before_stepping) || (n > 0 && value < value_before_stepping) {
+            return Ok(());
+        }
+
+        // Step 10.
+        self.SetValue(self.convert_number_to_string(value))
+    }
+
+    // Asks the embedder to show its date, time or color picker, and takes on the picked value.
+    fn show_picker(&self) {
+        let kind = match self.input_type.get() {
+            InputType::InputDate => InputPickerKind::Date,
+            InputType::InputTime => InputPickerKind::Time,
+            InputType::InputColor => InputPickerKind::Color,
+            _ => return,
+        };
+
+        let window = window_from_node(self);
+        let (sender, receiver) = ipc::channel().expect("Error initializing channel");
+        let msg = ConstellationMsg::ShowInputPicker(window.pipeline_id(), kind, String::from(self.Value()), sender);
+        window.constellation_chan().send(msg).unwrap();
+
+        let picked = match receiver.recv() {
+            Ok(Some(picked)) => DOMString::from(picked),
+            _ => return,
+        };
+        if picked == self.Value() {
+            return;
+        }
+
+        self.SetValue(picked).expect("Failed to set input value to the picked value.");
+        self.value_changed.set(true);
+        self.update_placeholder_shown_state();
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+
+        let target = self.upcast::<EventTarget>();
+        target.fire_event("input",
+                          EventBubbles::Bubbles,
+                          EventCancelable::NotCancelable);
+        target.fire_event("change",
+                          EventBubbles::Bubbles,
+                          EventCancelable::NotCancelable);
+    }
+
     fn update_placeholder_shown_state(&self) {
         match self.input_type.get() {
             InputType::InputText | InputType::InputPassword => {},
@@ -846,6 +1253,7 @@ impl HTMLInputElement {
         } else {
             let filelist = FileList::new(window.r(), files);
             self.filelist.set(Some(&filelist));
+            self.update_validity_states();
 
             target.fire_event("input",
                               EventBubbles::Bubbles,
@@ -914,6 +1322,9 @@ impl VirtualMethods for HTMLInputElement {
                             &atom!("radio") => InputType::InputRadio,
                             &atom!("checkbox") => InputType::InputCheckbox,
                             &atom!("password") => InputType::InputPassword,
+                            &atom!("date") => InputType::InputDate,
+                            &atom!("time") => InputType::InputTime,
+                            &atom!("color") => InputType::InputColor,
                             _ => InputType::InputText,
                         };
 
@@ -968,7 +1379,8 @@ impl VirtualMethods for HTMLInputElement {
                                 self.radio_group_name().as_ref());
                         }
 
-                        // TODO: Step 6 - value sanitization
+                        // Step 6
+                        self.sanitize_value();
                     },
                     AttributeMutation::Removed => {
                         if self.input_type.get() == InputType::InputRadio {
@@ -990,6 +1402,7 @@ impl VirtualMethods for HTMLInputElement {
                 let value = mutation.new_value(attr).map(|value| (**value).to_owned());
                 self.textinput.borrow_mut().set_content(
                     value.map_or(DOMString::new(), DOMString::from));
+                self.sanitize_value();
                 self.update_placeholder_shown_state();
             },
             &atom!("name") if self.input_type.get() == InputType::InputRadio => {
@@ -1041,9 +1454,19 @@ impl VirtualMethods for HTMLInputElement {
                         el.set_read_write_state(!el.disabled_state());
                     }
                 }
-            }
+            },
+            &atom!("required") => {
+                self.upcast::<Element>().set_required_state(mutation.new_value(attr).is_some());
+                if self.input_type.get() == InputType::InputRadio {
+                    for radio in self.radio_group_members() {
+                        radio.update_validity_states();
+                    }
+                }
+            },
             _ => {},
         }
+
+        self.update_validity_states();
     }
 
     fn parse_plain_attribute(&self, name: &Atom, value: DOMString) -> AttrValue {
@@ -1064,6 +1487,7 @@ impl VirtualMethods for HTMLInputElement {
         }
 
         self.upcast::<Element>().check_ancestors_disabled_state_for_form_control();
+        self.update_validity_states();
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -1076,6 +1500,7 @@ impl VirtualMethods for HTMLInputElement {
         } else {
             el.check_disabled_attribute();
         }
+        self.update_validity_states();
     }
 
     fn handle_event(&self, event: &Event) {
@@ -1115,6 +1540,7 @@ impl VirtualMethods for HTMLInputElement {
                         DispatchInput => {
                             self.value_changed.set(true);
                             self.update_placeholder_shown_state();
+                            self.update_validity_states();
 
                             if event.IsTrusted() {
                                 let window = window_from_node(self);
@@ -1142,7 +1568,52 @@ impl VirtualMethods for HTMLInputElement {
 
 impl FormControl for HTMLInputElement {}
 
-impl Validatable for HTMLInputElement {}
+impl Validatable for HTMLInputElement {
+    fn as_element(&self) -> &Element {
+        self.upcast()
+    }
+
+    fn validity_state_slot(&self) -> &MutNullableHeap<JS<ValidityState>> {
+        &self.validity_state
+    }
+
+    fn is_instance_validatable(&self) -> bool {
+        match self.input_type.get() {
+            // https://html.spec.whatwg.org/multipage/#reset-button-state-(type=reset):barred-from-constraint-validation
+            // https://html.spec.whatwg.org/multipage/#button-state-(type=button):barred-from-constraint-validation
+            InputType::InputReset | InputType::InputButton => return false,
+            // https://html.spec.whatwg.org/multipage/#hidden-state-(type=hidden):barred-from-constraint-validation
+            _ if self.type_().eq_ignore_ascii_case("hidden") => return false,
+            _ => {},
+        }
+        let el = self.upcast::<Element>();
+        // https://html.spec.whatwg.org/multipage/#the-readonly-attribute:barred-from-constraint-validation
+        let barred_by_readonly = self.ReadOnly() && self.readonly_applies();
+        !(el.disabled_state() || barred_by_readonly || is_barred_by_datalist_ancestor(el))
+    }
+
+    fn perform_validation(&self, validate_flags: ValidationFlags) -> ValidationFlags {
+        let mut failed_flags = ValidationFlags::empty();
+        let value = self.Value();
+
+        if validate_flags.contains(VALUE_MISSING) && self.suffers_from_being_missing(&value) {
+            failed_flags.insert(VALUE_MISSING);
+        }
+        if validate_flags.contains(TYPE_MISMATCH) && self.suffers_from_type_mismatch(&value) {
+            failed_flags.insert(TYPE_MISMATCH);
+        }
+        if validate_flags.contains(PATTERN_MISMATCH) && self.suffers_from_pattern_mismatch(&value) {
+            failed_flags.insert(PATTERN_MISMATCH);
+        }
+        if validate_flags.intersects(TOO_LONG | TOO_SHORT) {
+            failed_flags.insert(self.length_flags(&value));
+        }
+        if validate_flags.intersects(RANGE_UNDERFLOW | RANGE_OVERFLOW | STEP_MISMATCH) {
+            failed_flags.insert(self.range_flags(&value));
+        }
+        failed_flags & validate_flags
+    }
+}
 
 impl Activatable for HTMLInputElement {
     fn as_element(&self) -> &Element {
@@ -1156,7 +1627,8 @@ impl Activatable for HTMLInputElement {
             // https://html.spec.whatwg.org/multipage/#checkbox-state-%28type=checkbox%29:activation-behaviour-2
             // https://html.spec.whatwg.org/multipage/#radio-button-state-%28type=radio%29:activation-behaviour-2
             InputType::InputSubmit | InputType::InputReset | InputType::InputFile
-            | InputType::InputCheckbox | InputType::InputRadio => self.is_mutable(),
+            | InputType::InputCheckbox | InputType::InputRadio | InputType::InputDate
+            | InputType::InputTime | InputType::InputColor => self.is_mutable(),
             _ => false
         }
     }
@@ -1296,6 +1768,7 @@ impl Activatable for HTMLInputElement {
                                   EventCancelable::NotCancelable);
             },
             InputType::InputFile => self.select_files(None),
+            InputType::InputDate | InputType::InputTime | InputType::InputColor => self.show_picker(),
             _ => ()
         }
     }
@@ -1371,3 +1844,29 @@ fn filter_from_accept(s: &DOMString) -> Vec<FilterPattern> {
 
     filter
 }
+
+// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: u32, month: u32, day: u32) -> i64 {
+    debug_assert!(day <= days_in_month(year, month));
+    let year = year as i64 - if month <= 2 { 1 } else { 0 };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// The proleptic Gregorian date that is `days` days after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = (if days >= 0 { days } else { days - 146096 }) / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month as u32, day as u32)
+}
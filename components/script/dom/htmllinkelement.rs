@@ -9,6 +9,7 @@ use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::DOMTokenListBinding::DOMTokenListMethods;
 use dom::bindings::codegen::Bindings::HTMLLinkElementBinding;
 use dom::bindings::codegen::Bindings::HTMLLinkElementBinding::HTMLLinkElementMethods;
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, MutNullableHeap, Root, RootedReference};
 use dom::bindings::refcounted::Trusted;
@@ -19,6 +20,7 @@ use dom::element::{AttributeMutation, Element, ElementCreator};
 use dom::eventtarget::EventTarget;
 use dom::htmlelement::HTMLElement;
 use dom::node::{Node, document_from_node, window_from_node};
+use dom::performanceresourcetiming::initiator_type;
 use dom::virtualmethods::VirtualMethods;
 use encoding::EncodingRef;
 use encoding::all::UTF_8;
@@ -27,8 +29,9 @@ use hyper::mime::{Mime, TopLevel, SubLevel};
 use hyper_serde::Serde;
 use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
-use msg::constellation_msg::ReferrerPolicy;
+use msg::constellation_msg::{LoadData, ReferrerPolicy};
 use net_traits::{AsyncResponseListener, AsyncResponseTarget, Metadata, NetworkError};
+use net_traits::request::Destination;
 use network_listener::{NetworkListener, PreInvoke};
 use script_layout_interface::message::Msg;
 use script_traits::{MozBrowserEvent, ScriptMsg as ConstellationMsg};
@@ -45,6 +48,7 @@ use style::parser::ParserContextExtraData;
 use style::str::HTML_SPACE_CHARACTERS;
 use style::stylesheets::{Stylesheet, Origin};
 use url::Url;
+use util::prefs::PREFS;
 
 no_jsmanaged_fields!(Stylesheet);
 
@@ -124,6 +128,23 @@ fn is_favicon(value: &Option<String>) -> bool {
     }
 }
 
+/// https://html.spec.whatwg.org/multipage/#link-type-prefetch
+fn is_prefetch(value: &Option<String>) -> bool {
+    has_link_type(value, "prefetch")
+}
+
+/// https://www.w3.org/TR/resource-hints/#prerender
+fn is_prerender(value: &Option<String>) -> bool {
+    has_link_type(value, "prerender")
+}
+
+fn has_link_type(value: &Option<String>, link_type: &str) -> bool {
+    match *value {
+        Some(ref value) => value.split(HTML_SPACE_CHARACTERS).any(|s| s.eq_ignore_ascii_case(link_type)),
+        None => false,
+    }
+}
+
 impl VirtualMethods for HTMLLinkElement {
     fn super_type(&self) -> Option<&VirtualMethods> {
         Some(self.upcast::<HTMLElement>() as &VirtualMethods)
@@ -143,6 +164,10 @@ impl VirtualMethods for HTMLLinkElement {
                 } else if is_favicon(&rel) {
                     let sizes = get_attr(self.upcast(), &atom!("sizes"));
                     self.handle_favicon_url(rel.as_ref().unwrap(), &attr.value(), &sizes);
+                } else if is_prefetch(&rel) {
+                    self.handle_prefetch_url(&attr.value());
+                } else if is_prerender(&rel) {
+                    self.handle_prerender_url(&attr.value());
                 }
             },
             &atom!("sizes") => {
@@ -187,6 +212,12 @@ impl VirtualMethods for HTMLLinkElement {
                 Some(ref href) if is_favicon(&rel) => {
                     self.handle_favicon_url(rel.as_ref().unwrap(), href, &sizes);
                 }
+                Some(ref href) if is_prefetch(&rel) => {
+                    self.handle_prefetch_url(href);
+                }
+                Some(ref href) if is_prerender(&rel) => {
+                    self.handle_prerender_url(href);
+                }
                 _ => {}
             }
         }
@@ -269,6 +300,39 @@ impl HTMLLinkElement {
             Err(e) => debug!("Parsing url {} failed: {}", href, e)
         }
     }
+
+    /// https://html.spec.whatwg.org/multipage/#link-type-prefetch
+    fn handle_prefetch_url(&self, href: &str) {
+        let document = document_from_node(self);
+        if document.browsing_context().is_none() {
+            return;
+        }
+        match document.base_url().join(href) {
+            Ok(url) => document.prefetch(url),
+            Err(e) => debug!("Parsing url {} failed: {}", href, e)
+        }
+    }
+
+    /// Asks the constellation to load `href` in a hidden pipeline, for a likely navigation.
+    /// https://www.w3.org/TR/resource-hints/#prerender
+    fn handle_prerender_url(&self, href: &str) {
+        if !PREFS.get("dom.prerender.enabled").as_boolean().unwrap_or(false) {
+            return;
+        }
+        let document = document_from_node(self);
+        if document.browsing_context().is_none() {
+            return;
+        }
+        match document.base_url().join(href) {
+            Ok(url) => {
+                let window = document.window();
+                let load_data = LoadData::new(url, document.get_referrer_policy(), Some(document.url()));
+                let event = ConstellationMsg::Prerender(window.pipeline_id(), load_data);
+                window.constellation_chan().send(event).unwrap();
+            }
+            Err(e) => debug!("Parsing url {} failed: {}", href, e)
+        }
+    }
 }
 
 /// The context required for asynchronously loading an external stylesheet.
@@ -307,6 +371,12 @@ impl AsyncResponseListener for StylesheetContext {
         let document = document_from_node(&*elem);
         let mut successful = false;
 
+        if let (&Ok(()), Some(metadata)) = (&status, self.metadata.as_ref()) {
+            document.window().Performance().queue_resource_timing(&self.url,
+                                                                  initiator_type(Destination::Style),
+                                                                  metadata);
+        }
+
         if status.is_ok() {
             let metadata = match self.metadata.take() {
                 Some(meta) => meta,
@@ -327,7 +397,8 @@ impl AsyncResponseListener for StylesheetContext {
             let mut sheet = Stylesheet::from_bytes(&data, final_url, protocol_encoding_label,
                                                    Some(environment_encoding), Origin::Author,
                                                    win.css_error_reporter(),
-                                                   ParserContextExtraData::default());
+                                                   ParserContextExtraData::for_quirks_mode(
+                                                       document.style_quirks_mode()));
             let media = self.media.take().unwrap();
             sheet.set_media(Some(media));
             let sheet = Arc::new(sheet);
@@ -2,7 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use audio::decoder::DecodeError;
 use audio_video_metadata;
+use canvas_traits::{Canvas2dMsg, CanvasCommonMsg, CanvasMsg};
 use document_loader::LoadType;
 use dom::attr::Attr;
 use dom::bindings::cell::DOMRefCell;
@@ -10,27 +12,51 @@ use dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use dom::bindings::codegen::Bindings::HTMLMediaElementBinding::CanPlayTypeResult;
 use dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementConstants::*;
 use dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
+use dom::bindings::codegen::Bindings::HTMLTrackElementBinding::HTMLTrackElementMethods;
 use dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorConstants::*;
 use dom::bindings::codegen::Bindings::MediaErrorBinding::MediaErrorMethods;
+use dom::bindings::codegen::Bindings::MediaSourceBinding::{EndOfStreamError, MediaSourceMethods, ReadyState};
+use dom::bindings::codegen::Bindings::TextTrackBinding::{TextTrackKind, TextTrackMethods, TextTrackMode};
+use dom::bindings::codegen::Bindings::TextTrackCueBinding::TextTrackCueMethods;
 use dom::bindings::global::GlobalRef;
 use dom::bindings::inheritance::Castable;
-use dom::bindings::js::{Root, MutNullableHeap, JS};
+use dom::bindings::js::{LayoutJS, Root, MutNullableHeap, JS};
+use dom::bindings::num::Finite;
 use dom::bindings::refcounted::Trusted;
 use dom::bindings::str::DOMString;
 use dom::document::Document;
 use dom::element::{Element, AttributeMutation};
 use dom::event::{Event, EventBubbles, EventCancelable};
+use dom::eventtarget::EventTarget;
 use dom::htmlelement::HTMLElement;
 use dom::htmlsourceelement::HTMLSourceElement;
+use dom::htmlvideoelement::HTMLVideoElement;
 use dom::mediaerror::MediaError;
-use dom::node::{window_from_node, document_from_node, Node, UnbindContext};
+use dom::mediasource::MediaSource;
+use dom::mediastream::MediaStream;
+use dom::mediastreamtrack::MediaStreamTrack;
+use dom::node::{window_from_node, document_from_node, Node, NodeDamage, UnbindContext};
+use dom::texttrack::TextTrack;
+use dom::texttrackcue::TextTrackCue;
+use dom::texttracklist::TextTrackList;
+use dom::timeranges::TimeRanges;
 use dom::virtualmethods::VirtualMethods;
-use ipc_channel::ipc;
+use dom::vttcue::VTTCue;
+use euclid::point::Point2D;
+use euclid::rect::Rect;
+use euclid::size::Size2D;
+use ipc_channel::ipc::{self, IpcSender, IpcSharedMemory};
 use ipc_channel::router::ROUTER;
 use net_traits::{AsyncResponseListener, AsyncResponseTarget, Metadata, NetworkError};
+use media::{MediaData, SinkId};
+use media::player::{self, MediaPlayer, PlayerEvent, PlayerMsg};
 use network_listener::{NetworkListener, PreInvoke};
+use script_layout_interface::{HTMLCanvasData, ShownCue};
 use script_thread::{Runnable, ScriptThread};
+use script_traits::ScriptMsg as ConstellationMsg;
 use std::cell::Cell;
+use std::f64;
+use std::mem;
 use std::sync::{Arc, Mutex};
 use string_cache::Atom;
 use task_source::TaskSource;
@@ -105,34 +131,37 @@ impl AsyncResponseListener for HTMLMediaElementContext {
     fn response_complete(&mut self, status: Result<(), NetworkError>) {
         let elem = self.elem.root();
 
-        // => "If the media data can be fetched but is found by inspection to be in an unsupported
-        //     format, or can otherwise not be rendered at all"
-        if !self.have_metadata {
-            elem.queue_dedicated_media_source_failure_steps();
-        }
-        // => "Once the entire media resource has been fetched..."
-        else if status.is_ok() {
-            elem.change_ready_state(HAVE_ENOUGH_DATA);
+        if status.is_ok() {
+            match elem.start_player(mem::replace(&mut self.data, vec![])) {
+                Ok(()) => {
+                    // => "Once enough of the media data has been fetched to determine the duration..."
+                    if !self.have_metadata {
+                        self.have_metadata = true;
+                        elem.change_ready_state(HAVE_METADATA);
+                    }
+
+                    // => "Once the entire media resource has been fetched..."
+                    elem.change_ready_state(HAVE_ENOUGH_DATA);
 
-            elem.fire_simple_event("progress");
+                    elem.fire_simple_event("progress");
 
-            elem.network_state.set(NETWORK_IDLE);
+                    elem.network_state.set(NETWORK_IDLE);
 
-            elem.fire_simple_event("suspend");
+                    elem.fire_simple_event("suspend");
+                },
+                // => "If the media data can be fetched but is found by inspection to be in an
+                //     unsupported format, or can otherwise not be rendered at all"
+                Err(_) if !self.have_metadata => elem.queue_dedicated_media_source_failure_steps(),
+                // => "If the media data is corrupted"
+                Err(error) => {
+                    warn!("Failed to decode {}: {:?}", self.url, error);
+                    elem.media_data_corrupted();
+                },
+            }
         }
         // => "If the connection is interrupted after some media data has been received..."
         else if elem.ready_state.get() != HAVE_NOTHING {
-            // Step 2
-            elem.error.set(Some(&*MediaError::new(&*window_from_node(&*elem),
-                                                  MEDIA_ERR_NETWORK)));
-
-            // Step 3
-            elem.network_state.set(NETWORK_IDLE);
-
-            // TODO: Step 4 - update delay load flag
-
-            // Step 5
-            elem.fire_simple_event("error");
+            elem.connection_interrupted();
         } else {
             // => "If the media data cannot be fetched at all..."
             elem.queue_dedicated_media_source_failure_steps();
@@ -177,6 +206,9 @@ impl HTMLMediaElementContext {
                     video: meta.video.unwrap_or("".to_owned()),
                     audio: meta.audio.audio,
                 });
+                if let Some(dur) = meta.audio.duration {
+                    elem.set_duration(dur.as_secs() as f64 + dur.subsec_nanos() as f64 / 1e9);
+                }
                 // Step 6
                 elem.change_ready_state(HAVE_METADATA);
                 self.have_metadata = true;
@@ -197,6 +229,15 @@ pub struct VideoMedia {
     audio: Option<String>,
 }
 
+/// The size of a video element that shows no frame.
+/// https://html.spec.whatwg.org/multipage/#the-video-element:dimension-attributes
+const DEFAULT_VIDEO_WIDTH: u32 = 300;
+const DEFAULT_VIDEO_HEIGHT: u32 = 150;
+
+/// How far past the playback position the frames of a media source have to
+/// be appended for it to play through, in seconds.
+const MEDIA_SOURCE_ENOUGH_DATA: f64 = 2.;
+
 #[dom_struct]
 pub struct HTMLMediaElement {
     htmlelement: HTMLElement,
@@ -209,6 +250,38 @@ pub struct HTMLMediaElement {
     paused: Cell<bool>,
     autoplaying: Cell<bool>,
     video: DOMRefCell<Option<VideoMedia>>,
+    /// https://html.spec.whatwg.org/multipage/#assigned-media-provider-object
+    src_object: MutNullableHeap<JS<MediaStream>>,
+    /// The track of the media provider object that a video element shows,
+    /// and the sink its frames are received with.
+    video_track: MutNullableHeap<JS<MediaStreamTrack>>,
+    video_sink: Cell<Option<SinkId>>,
+    /// Where the frames of `video_track` are painted, for layout to show.
+    #[ignore_heap_size_of = "Defined in ipc-channel"]
+    video_renderer: DOMRefCell<Option<IpcSender<CanvasMsg>>>,
+    /// The size of the latest frame of `video_track` or of the player.
+    video_size: Cell<Option<Size2D<u32>>>,
+    /// The thread playing the resource, once it was fetched.
+    #[ignore_heap_size_of = "Defined in the media module"]
+    player: DOMRefCell<Option<MediaPlayer>>,
+    /// https://html.spec.whatwg.org/multipage/#current-playback-position
+    playback_position: Cell<f64>,
+    /// https://html.spec.whatwg.org/multipage/#default-playback-start-position
+    default_playback_start_position: Cell<f64>,
+    /// https://html.spec.whatwg.org/multipage/#dom-media-duration
+    duration: Cell<f64>,
+    /// https://html.spec.whatwg.org/multipage/#dom-media-seeking
+    seeking: Cell<bool>,
+    /// The media source that the element plays, if it was loaded from the
+    /// object URL of one.
+    media_source: MutNullableHeap<JS<MediaSource>>,
+    /// https://html.spec.whatwg.org/multipage/#list-of-text-tracks
+    text_tracks: MutNullableHeap<JS<TextTrackList>>,
+    /// The playback position the last time marches on ran at.
+    last_cue_update_time: Cell<f64>,
+    /// The active cues of the showing text tracks, which layout shows over
+    /// the video.
+    shown_cues: DOMRefCell<Vec<ShownCue>>,
 }
 
 impl HTMLMediaElement {
@@ -227,6 +300,20 @@ impl HTMLMediaElement {
             paused: Cell::new(true),
             autoplaying: Cell::new(true),
             video: DOMRefCell::new(None),
+            src_object: Default::default(),
+            video_track: Default::default(),
+            video_sink: Cell::new(None),
+            video_renderer: DOMRefCell::new(None),
+            video_size: Cell::new(None),
+            player: DOMRefCell::new(None),
+            playback_position: Cell::new(0.),
+            default_playback_start_position: Cell::new(0.),
+            duration: Cell::new(f64::NAN),
+            seeking: Cell::new(false),
+            media_source: Default::default(),
+            text_tracks: Default::default(),
+            last_cue_update_time: Cell::new(0.),
+            shown_cues: DOMRefCell::new(vec![]),
         }
     }
 
@@ -250,6 +337,7 @@ impl HTMLMediaElement {
 
             // TODO 2.3 (official playback position)
         }
+        self.update_playback();
 
         // TODO step 3 (media controller)
     }
@@ -309,6 +397,13 @@ impl HTMLMediaElement {
         let _ = win.dom_manipulation_task_source().queue(task, GlobalRef::Window(&win));
     }
 
+    /// Queues a simple event at one of the text tracks of the element, or
+    /// at something related to them.
+    fn queue_fire_simple_event_at(&self, target: &EventTarget, type_: &'static str) {
+        let win = window_from_node(self);
+        win.dom_manipulation_task_source().queue_simple_event(target, Atom::from(type_), &win);
+    }
+
     fn fire_simple_event(&self, type_: &str) {
         let window = window_from_node(self);
         let event = Event::new(GlobalRef::Window(&*window),
@@ -354,7 +449,12 @@ impl HTMLMediaElement {
             (HAVE_ENOUGH_DATA, HAVE_METADATA) |
             (HAVE_FUTURE_DATA, HAVE_NOTHING) |
             (HAVE_ENOUGH_DATA, HAVE_NOTHING) => {
-                // TODO: timeupdate event logic + waiting
+                // The element was potentially playing, and has to wait for
+                // more data.
+                if !self.Paused() && !self.Ended() {
+                    self.queue_fire_simple_event("timeupdate");
+                    self.queue_fire_simple_event("waiting");
+                }
             }
 
             _ => (),
@@ -399,6 +499,7 @@ impl HTMLMediaElement {
                     self.queue_notify_about_playing();
                     // Step 5
                     self.autoplaying.set(false);
+                    self.update_playback();
                 }
 
                 self.queue_fire_simple_event("canplaythrough");
@@ -408,6 +509,8 @@ impl HTMLMediaElement {
         }
 
         // TODO Step 2: media controller
+
+        self.update_playback();
     }
 
     // https://html.spec.whatwg.org/multipage/#concept-media-load-algorithm
@@ -428,8 +531,7 @@ impl HTMLMediaElement {
         // TODO step 5 (populate pending text tracks)
 
         // Step 6
-        let mode = if false {
-            // TODO media provider object
+        let mode = if self.src_object.get().is_some() {
             ResourceSelectionMode::Object
         } else if let Some(attr) = self.upcast::<Element>().get_attribute(&ns!(), &atom!("src")) {
             ResourceSelectionMode::Attribute(attr.Value().to_string())
@@ -471,7 +573,11 @@ impl HTMLMediaElement {
                 if let Ok(url) = absolute_url {
                     *self.current_src.borrow_mut() = url.as_str().into();
                     // Step 4
-                    self.resource_fetch_algorithm(Resource::Url(url));
+                    let window = window_from_node(self);
+                    match window.media_source_for_url(url.as_str()) {
+                        Some(media_source) => self.resource_fetch_algorithm(Resource::MediaSource(media_source)),
+                        None => self.resource_fetch_algorithm(Resource::Url(url)),
+                    }
                 } else {
                     self.queue_dedicated_media_source_failure_steps();
                 }
@@ -489,7 +595,21 @@ impl HTMLMediaElement {
         // TODO step 3 (remove text tracks)
 
         // Step 4
-        if let Resource::Url(url) = resource {
+        let url = match resource {
+            Resource::Url(url) => url,
+            Resource::Object => return self.load_media_provider_object(),
+            Resource::MediaSource(media_source) => {
+                // https://w3c.github.io/media-source/#mediasource-attach
+                if media_source.attach(self) {
+                    self.media_source.set(Some(&media_source));
+                } else {
+                    self.queue_dedicated_media_source_failure_steps();
+                }
+                return;
+            },
+        };
+
+        {
             // 4.1
             if self.Preload() == "none" && !self.autoplaying.get() {
                 // 4.1.1
@@ -525,9 +645,549 @@ impl HTMLMediaElement {
             // FIXME: we're supposed to block the load event much earlier than now
             let doc = document_from_node(self);
             doc.load_async(LoadType::Media(url), response_target, None);
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-media-load-resource step 4
+    // => "If mode is local"
+    fn load_media_provider_object(&self) {
+        // The only media provider objects are media streams, which are
+        // live: their data is available as soon as they are assigned.
+        if let Some(stream) = self.src_object.get() {
+            if self.is::<HTMLVideoElement>() {
+                if let Some(track) = stream.first_video_track() {
+                    self.render_video_track(&track);
+                }
+            }
+        }
+        self.change_ready_state(HAVE_METADATA);
+        self.change_ready_state(HAVE_ENOUGH_DATA);
+        self.network_state.set(NETWORK_IDLE);
+        self.queue_fire_simple_event("suspend");
+    }
+
+    /// Paints the frames of a video track where layout shows the element.
+    fn render_video_track(&self, track: &MediaStreamTrack) {
+        // TODO: keep showing the same frame while the element is paused.
+        self.video_sink.set(track.add_sink(self.frame_painter()));
+        self.video_track.set(Some(track));
+    }
+
+    /// Makes a sink that paints the video frames it is given where layout
+    /// shows the element, on whatever thread it runs on.
+    fn frame_painter(&self) -> Box<FnMut(&MediaData) + Send> {
+        let window = window_from_node(self);
+        let renderer = {
+            let mut renderer = self.video_renderer.borrow_mut();
+            if renderer.is_none() {
+                let (sender, receiver) = ipc::channel().unwrap();
+                let size = Size2D::new(DEFAULT_VIDEO_WIDTH as i32, DEFAULT_VIDEO_HEIGHT as i32);
+                window.constellation_chan().send(ConstellationMsg::CreateCanvasPaintThread(size, sender)).unwrap();
+                *renderer = Some(receiver.recv().unwrap());
+            }
+            renderer.as_ref().unwrap().clone()
+        };
+
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let elem = Trusted::new(self);
+        let generation_id = self.generation_id.get();
+        let mut size = Size2D::zero();
+        box move |data: &MediaData| {
+            let frame = match *data {
+                MediaData::Video(ref frame) => frame,
+                MediaData::Audio(_) => return,
+            };
+            let frame_size = Size2D::new(frame.width, frame.height);
+            let resized = frame_size != size;
+            if resized {
+                size = frame_size;
+                let size = Size2D::new(size.width as i32, size.height as i32);
+                let _ = renderer.send(CanvasMsg::Common(CanvasCommonMsg::Recreate(size)));
+            }
+            let image_size = Size2D::new(size.width as f64, size.height as f64);
+            let frame_data = IpcSharedMemory::from_bytes(&frame.data);
+            let _ = renderer.send(CanvasMsg::Canvas2d(Canvas2dMsg::PutImageData(frame_data,
+                                                                                Point2D::zero(),
+                                                                                image_size,
+                                                                                Rect::new(Point2D::zero(),
+                                                                                          image_size))));
+            let runnable = box VideoFrameTask {
+                elem: elem.clone(),
+                generation_id: generation_id,
+                size: if resized { Some(size) } else { None },
+            };
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        }
+    }
+
+    /// Stops painting the frames of the video track of the media provider
+    /// object.
+    fn stop_rendering_video_track(&self) {
+        if let Some(track) = self.video_track.get() {
+            if let Some(sink) = self.video_sink.get() {
+                track.remove_sink(sink);
+            }
+        }
+        self.video_track.set(None);
+        self.video_sink.set(None);
+        self.video_size.set(None);
+        if let Some(renderer) = self.video_renderer.borrow_mut().take() {
+            let _ = renderer.send(CanvasMsg::Common(CanvasCommonMsg::Close));
+        }
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    fn video_frame_painted(&self, generation_id: u32, size: Option<Size2D<u32>>) {
+        // The frames painted for a previous resource don't matter.
+        if self.generation_id.get() != generation_id {
+            return;
+        }
+        if size.is_some() {
+            self.video_size.set(size);
+        }
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    /// The sink the player of the element shows the frames of its video
+    /// track in.
+    fn player_video_sink(&self) -> Box<FnMut(&MediaData) + Send> {
+        if self.is::<HTMLVideoElement>() {
+            self.frame_painter()
         } else {
-            // TODO local resource fetch
-            self.queue_dedicated_media_source_failure_steps();
+            box |_: &MediaData| {}
+        }
+    }
+
+    /// The handler the player of the element reports its progress to.
+    fn player_event_handler(&self) -> Box<Fn(PlayerEvent) + Send> {
+        let window = window_from_node(self);
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let elem = Trusted::new(self);
+        let generation_id = self.generation_id.get();
+        box move |event: PlayerEvent| {
+            let runnable = box PlayerEventTask {
+                elem: elem.clone(),
+                generation_id: generation_id,
+                event: event,
+            };
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        }
+    }
+
+    /// Starts a paused player for the fetched resource `data`.
+    fn start_player(&self, data: Vec<u8>) -> Result<(), DecodeError> {
+        let player = try!(MediaPlayer::start(data,
+                                             self.player_video_sink(),
+                                             window_from_node(self).audio_sink(),
+                                             self.player_event_handler()));
+        self.player_started(player);
+        Ok(())
+    }
+
+    /// Starts a paused player for the frames appended to `media_source`.
+    fn start_source_player(&self, media_source: &MediaSource) -> Result<(), DecodeError> {
+        let player = try!(MediaPlayer::start_source(media_source.shared_data(),
+                                                    self.player_video_sink(),
+                                                    window_from_node(self).audio_sink(),
+                                                    self.player_event_handler()));
+        self.player_started(player);
+        Ok(())
+    }
+
+    fn player_started(&self, player: MediaPlayer) {
+        // https://html.spec.whatwg.org/multipage/#media-data-processing-steps-list
+        // => "Once enough of the media data has been fetched to determine the duration..."
+        // Steps 3 and 4. The duration of a media source is set as its
+        // segments are appended instead.
+        let duration = player.info().duration;
+        if !duration.is_nan() {
+            self.set_duration(duration);
+        }
+        if let Some((width, height)) = player.info().video_size {
+            self.video_size.set(Some(Size2D::new(width, height)));
+        }
+
+        // Step 8, or a seek done while the resource was being fetched.
+        let start_position = match self.default_playback_start_position.get() {
+            position if position > 0. => position,
+            _ => self.playback_position.get(),
+        };
+        if start_position > 0. {
+            self.playback_position.set(start_position);
+            player.send(PlayerMsg::Seek(start_position));
+        }
+        self.default_playback_start_position.set(0.);
+
+        *self.player.borrow_mut() = Some(player);
+        self.update_playback();
+    }
+
+    /// Makes the player play while the element is potentially playing, and
+    /// pause otherwise.
+    /// https://html.spec.whatwg.org/multipage/#potentially-playing
+    fn update_playback(&self) {
+        if let Some(ref player) = *self.player.borrow() {
+            let potentially_playing = !self.Paused() && !self.Ended() &&
+                                      self.ready_state.get() >= HAVE_FUTURE_DATA;
+            player.send(if potentially_playing { PlayerMsg::Play } else { PlayerMsg::Pause });
+        }
+    }
+
+    fn handle_player_event(&self, event: PlayerEvent) {
+        match event {
+            PlayerEvent::TimeUpdate(time) => {
+                // The position of an ongoing seek wins over the old one.
+                if self.seeking.get() {
+                    return;
+                }
+                self.playback_position.set(time);
+                self.time_marches_on(true);
+                // https://html.spec.whatwg.org/multipage/#time-marches-on step 6
+                self.fire_simple_event("timeupdate");
+                self.media_source_changed();
+            },
+            PlayerEvent::Seeked(time) => {
+                self.playback_position.set(time);
+                self.seeked();
+                self.media_source_changed();
+            },
+            PlayerEvent::Waiting(time) => {
+                if !self.seeking.get() {
+                    self.playback_position.set(time);
+                }
+                self.media_source_changed();
+            },
+            PlayerEvent::Ended => self.reached_the_end(),
+        }
+    }
+
+    /// Updates the ready state of the element from the frames appended to
+    /// its media source, starting its player once the tracks are known.
+    /// https://w3c.github.io/media-source/#buffer-monitoring
+    pub fn media_source_changed(&self) {
+        let media_source = match self.media_source.get() {
+            Some(media_source) => media_source,
+            None => return,
+        };
+        if self.ready_state.get() == HAVE_NOTHING {
+            // https://w3c.github.io/media-source/#sourcebuffer-init-segment-received step 7
+            if !media_source.received_init_segments() {
+                return;
+            }
+            if self.start_source_player(&media_source).is_err() {
+                return media_source.end_of_stream(Some(EndOfStreamError::Decode));
+            }
+            self.change_ready_state(HAVE_METADATA);
+        }
+
+        let position = self.playback_position.get();
+        let ranges = media_source.buffered();
+        let range = ranges.iter().find(|&&(start, end)| start <= position && position <= end);
+        let ready_state = match range {
+            None => HAVE_METADATA,
+            Some(&(_, end)) if end <= position => HAVE_CURRENT_DATA,
+            Some(&(_, end)) if end - position > MEDIA_SOURCE_ENOUGH_DATA ||
+                               media_source.ReadyState() == ReadyState::Ended => HAVE_ENOUGH_DATA,
+            Some(_) => HAVE_FUTURE_DATA,
+        };
+        if ready_state != self.ready_state.get() {
+            self.change_ready_state(ready_state);
+        }
+    }
+
+    /// Handles the end of the stream of the media source of the element with
+    /// an error.
+    /// https://w3c.github.io/media-source/#end-of-stream-algorithm step 4
+    pub fn media_source_failed(&self, decode: bool) {
+        if self.ready_state.get() == HAVE_NOTHING {
+            self.dedicated_media_source_failure();
+        } else if decode {
+            self.media_data_corrupted();
+        } else {
+            self.connection_interrupted();
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-seek
+    fn seek(&self, time: f64) {
+        // TODO step 1 (show poster)
+
+        // Step 2
+        if self.ready_state.get() == HAVE_NOTHING {
+            return;
+        }
+
+        // TODO step 3 (abort other seek instances)
+
+        // Step 4
+        self.seeking.set(true);
+
+        // Step 5: the remaining steps run on the player thread.
+
+        // Steps 6 and 7
+        let time = time.min(self.duration.get()).max(0.);
+
+        // TODO steps 8 and 9 (seekable ranges)
+
+        // Step 10
+        self.queue_fire_simple_event("seeking");
+
+        // Step 11
+        self.playback_position.set(time);
+        match *self.player.borrow() {
+            Some(ref player) => player.send(PlayerMsg::Seek(time)),
+            // The player starts from the new position once the resource is
+            // fetched, and media provider objects are live.
+            None => self.seeked(),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-seek
+    fn seeked(&self) {
+        // Step 14
+        self.seeking.set(false);
+
+        // Step 15
+        self.time_marches_on(false);
+
+        // Step 16
+        self.queue_fire_simple_event("timeupdate");
+
+        // Step 17
+        self.queue_fire_simple_event("seeked");
+    }
+
+    // https://html.spec.whatwg.org/multipage/#reaches-the-end
+    fn reached_the_end(&self) {
+        // Step 1
+        if self.Loop() {
+            self.seek(0.);
+            return self.update_playback();
+        }
+
+        let duration = self.duration.get();
+        if !duration.is_nan() {
+            self.playback_position.set(duration);
+        }
+        self.time_marches_on(true);
+
+        // Step 3
+        self.fire_simple_event("timeupdate");
+        if !self.Paused() {
+            self.paused.set(true);
+            self.fire_simple_event("pause");
+        }
+        self.fire_simple_event("ended");
+    }
+
+    /// Sets the duration of the resource, which fires `durationchange` if it
+    /// changed.
+    /// https://html.spec.whatwg.org/multipage/#durationChange
+    pub fn set_duration(&self, duration: f64) {
+        let old_duration = self.duration.get();
+        if old_duration == duration || (old_duration.is_nan() && duration.is_nan()) {
+            return;
+        }
+        self.duration.set(duration);
+        self.queue_fire_simple_event("durationchange");
+    }
+
+    /// Adds the track of a `<track>` child to the text tracks of the element.
+    /// https://html.spec.whatwg.org/multipage/#sourcing-out-of-band-text-tracks:the-track-element
+    pub fn add_text_track(&self, track: &TextTrack) {
+        let tracks = self.TextTracks();
+        if tracks.contains(track) {
+            return;
+        }
+        tracks.push(track);
+        track.set_media_element(Some(self));
+        // TODO: fire a TrackEvent.
+        self.queue_fire_simple_event_at(tracks.upcast(), "addtrack");
+        self.honor_text_track_preferences();
+    }
+
+    /// Removes the track of a `<track>` that stopped being a child of the
+    /// element.
+    pub fn remove_text_track(&self, track: &TextTrack) {
+        let tracks = self.TextTracks();
+        if !tracks.remove(track) {
+            return;
+        }
+        track.set_media_element(None);
+        track.active_cue_list().set_cues(&[]);
+        // TODO: fire a TrackEvent.
+        self.queue_fire_simple_event_at(tracks.upcast(), "removetrack");
+        self.time_marches_on(false);
+    }
+
+    /// Shows the first track of each kind whose `<track>` asks to be shown
+    /// by default, unless a track of that kind is shown already.
+    /// https://html.spec.whatwg.org/multipage/#honor-user-preferences-for-automatic-text-track-selection
+    fn honor_text_track_preferences(&self) {
+        let tracks = self.TextTracks().tracks();
+        let is_default = |track: &TextTrack| track.track_element().map_or(false, |element| element.Default());
+        // Steps 1 to 3
+        let kind_sets = [&[TextTrackKind::Subtitles, TextTrackKind::Captions][..],
+                          &[TextTrackKind::Descriptions][..],
+                          &[TextTrackKind::Chapters][..]];
+        for kinds in &kind_sets {
+            let candidates: Vec<_> = tracks.iter().filter(|track| kinds.contains(&track.Kind())).collect();
+            if candidates.iter().any(|track| track.Mode() == TextTrackMode::Showing) {
+                continue;
+            }
+            if let Some(track) = candidates.iter().find(|track| {
+                track.Mode() == TextTrackMode::Disabled && is_default(track)
+            }) {
+                track.SetMode(TextTrackMode::Showing);
+            }
+        }
+        // Step 4
+        for track in &tracks {
+            if track.Kind() == TextTrackKind::Metadata && track.Mode() == TextTrackMode::Disabled &&
+               is_default(track) {
+                track.SetMode(TextTrackMode::Hidden);
+            }
+        }
+    }
+
+    /// Fires `change` at the text tracks and updates the cues shown once the
+    /// mode of one of them changed.
+    /// https://html.spec.whatwg.org/multipage/#dom-texttrack-mode
+    pub fn text_track_mode_changed(&self) {
+        self.queue_fire_simple_event_at(self.TextTracks().upcast(), "change");
+        self.time_marches_on(false);
+    }
+
+    /// Updates the active cues of the text tracks for the current playback
+    /// position, firing the events of the cues entered, exited or missed
+    /// since the last time, and shows the active cues of the showing tracks.
+    /// `normal_playback` is whether the position advanced by playing rather
+    /// than by seeking.
+    /// https://html.spec.whatwg.org/multipage/#time-marches-on
+    pub fn time_marches_on(&self, normal_playback: bool) {
+        let time = self.playback_position.get();
+        let last_time = self.last_cue_update_time.get();
+        self.last_cue_update_time.set(time);
+
+        let mut events: Vec<(f64, Root<TextTrackCue>, &'static str)> = vec![];
+        let mut changed_tracks = vec![];
+        let mut pause = false;
+        let mut shown_cues = vec![];
+        for track in self.TextTracks().tracks() {
+            let mode = track.Mode();
+            let cues = track.cue_list().cues();
+            let old_cues = track.active_cue_list().cues();
+            // Steps 1 and 2
+            let current_cues: Vec<_> = match mode {
+                TextTrackMode::Disabled => vec![],
+                _ => cues.iter().filter(|cue| cue.is_active_at(time)).cloned().collect(),
+            };
+            // Step 3: the cues that both started and ended since last time.
+            let missed_cues: Vec<_> = if mode != TextTrackMode::Disabled && normal_playback && last_time <= time {
+                cues.iter().filter(|cue| {
+                    !current_cues.contains(cue) && !old_cues.contains(cue) &&
+                    *cue.StartTime() >= last_time && *cue.EndTime() <= time
+                }).cloned().collect()
+            } else {
+                vec![]
+            };
+            let exited_cues: Vec<_> = old_cues.iter().filter(|cue| !current_cues.contains(cue)).cloned().collect();
+            let entered_cues: Vec<_> = current_cues.iter().filter(|cue| !old_cues.contains(cue)).cloned().collect();
+
+            if mode == TextTrackMode::Showing {
+                shown_cues.extend(current_cues.iter().filter_map(|cue| {
+                    cue.downcast::<VTTCue>().map(|cue| cue.shown_cue())
+                }));
+            }
+
+            // Step 6
+            if exited_cues.is_empty() && entered_cues.is_empty() && missed_cues.is_empty() {
+                continue;
+            }
+
+            // Step 8
+            if normal_playback && exited_cues.iter().chain(&missed_cues).any(|cue| cue.PauseOnExit()) {
+                pause = true;
+            }
+
+            // Steps 9 and 10
+            for cue in &missed_cues {
+                events.push((*cue.StartTime(), cue.clone(), "enter"));
+                events.push((*cue.EndTime(), cue.clone(), "exit"));
+            }
+            for cue in &entered_cues {
+                events.push((*cue.StartTime(), cue.clone(), "enter"));
+            }
+            for cue in &exited_cues {
+                events.push((*cue.EndTime(), cue.clone(), "exit"));
+            }
+
+            // Step 15
+            track.active_cue_list().set_cues(&current_cues);
+            changed_tracks.push(track);
+        }
+
+        if pause && !self.Paused() {
+            self.Pause();
+        }
+
+        // Steps 12 and 13
+        events.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        for &(_, ref cue, name) in &events {
+            self.queue_fire_simple_event_at(cue.upcast(), name);
+        }
+        // Step 14
+        for track in &changed_tracks {
+            self.queue_fire_simple_event_at(track.upcast(), "cuechange");
+            if let Some(element) = track.track_element() {
+                self.queue_fire_simple_event_at(element.upcast(), "cuechange");
+            }
+        }
+
+        // Step 16
+        if *self.shown_cues.borrow() != shown_cues {
+            *self.shown_cues.borrow_mut() = shown_cues;
+            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#media-data-processing-steps-list
+    // => "If the connection is interrupted after some media data has been received..."
+    fn connection_interrupted(&self) {
+        // Step 2
+        self.error.set(Some(&*MediaError::new(&*window_from_node(self), MEDIA_ERR_NETWORK)));
+
+        // Step 3
+        self.network_state.set(NETWORK_IDLE);
+
+        // TODO: Step 4 - update delay load flag
+
+        // Step 5
+        self.fire_simple_event("error");
+    }
+
+    // https://html.spec.whatwg.org/multipage/#media-data-processing-steps-list
+    // => "If the media data is corrupted"
+    fn media_data_corrupted(&self) {
+        // Step 1
+        self.error.set(Some(&*MediaError::new(&*window_from_node(self), MEDIA_ERR_DECODE)));
+
+        // TODO step 2 (forget resource tracks)
+
+        // Step 3
+        self.network_state.set(NETWORK_IDLE);
+
+        // TODO step 4 (delay load flag)
+
+        // Step 5
+        self.fire_simple_event("error");
+
+        // Step 6
+        if self.ready_state.get() == HAVE_NOTHING {
+            self.network_state.set(NETWORK_EMPTY);
+            self.fire_simple_event("emptied");
         }
     }
 
@@ -580,7 +1240,14 @@ impl HTMLMediaElement {
 
             // TODO 4.2 (abort in-progress fetch)
 
-            // TODO 4.3 (detach media provider object)
+            // 4.3
+            self.stop_rendering_video_track();
+            *self.player.borrow_mut() = None;
+            // https://w3c.github.io/media-source/#mediasource-detach
+            if let Some(media_source) = self.media_source.get() {
+                media_source.detach();
+                self.media_source.set(None);
+            }
             // TODO 4.4 (forget resource tracks)
 
             // 4.5
@@ -592,10 +1259,21 @@ impl HTMLMediaElement {
             if !self.Paused() {
                 self.paused.set(true);
             }
-            // TODO 4.7 (seeking)
-            // TODO 4.8 (playback position)
+            // 4.7
+            self.seeking.set(false);
+
+            // 4.8
+            let position_changed = self.playback_position.get() != 0.;
+            self.playback_position.set(0.);
+            self.time_marches_on(false);
+            if position_changed {
+                self.queue_fire_simple_event("timeupdate");
+            }
+
             // TODO 4.9 (timeline offset)
-            // TODO 4.10 (duration)
+
+            // 4.10
+            self.set_duration(f64::NAN);
         }
 
         // TODO step 5 (playback rate)
@@ -637,20 +1315,49 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
     // https://html.spec.whatwg.org/multipage/#attr-media-preload
     make_setter!(SetPreload, "preload");
 
+    // https://html.spec.whatwg.org/multipage/#dom-media-srcobject
+    fn GetSrcObject(&self) -> Option<Root<MediaStream>> {
+        self.src_object.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-srcobject
+    fn SetSrcObject(&self, value: Option<&MediaStream>) {
+        self.src_object.set(value);
+        self.media_element_load_algorithm();
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-media-currentsrc
     fn CurrentSrc(&self) -> DOMString {
         DOMString::from(self.current_src.borrow().clone())
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-media-buffered
+    fn Buffered(&self) -> Root<TimeRanges> {
+        let duration = self.duration.get();
+        let ranges = if let Some(media_source) = self.media_source.get() {
+            media_source.buffered()
+        } else if self.player.borrow().is_some() && duration.is_finite() {
+            // Fetched resources are played once they are complete.
+            vec![(0., duration)]
+        } else {
+            vec![]
+        };
+        TimeRanges::new(&window_from_node(self), ranges)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-media-load
     fn Load(&self) {
         self.media_element_load_algorithm();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-canplaytype
-    fn CanPlayType(&self, _type_: DOMString) -> CanPlayTypeResult {
+    fn CanPlayType(&self, type_: DOMString) -> CanPlayTypeResult {
         // TODO: application/octet-stream
-        CanPlayTypeResult::Maybe
+        match player::can_play_type(&type_) {
+            Some(true) => CanPlayTypeResult::Probably,
+            Some(false) => CanPlayTypeResult::Maybe,
+            None => CanPlayTypeResult::_empty,
+        }
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-media-error
@@ -675,7 +1382,10 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
             self.invoke_resource_selection_algorithm();
         }
 
-        // TODO step 5 (seek backwards)
+        // Step 5
+        if self.Ended() {
+            self.seek(0.);
+        }
 
         // TODO step 6 (media controller)
 
@@ -707,6 +1417,7 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
 
         // Step 9
         self.autoplaying.set(false);
+        self.update_playback();
 
         // TODO step 10 (media controller)
 
@@ -728,6 +1439,61 @@ impl HTMLMediaElementMethods for HTMLMediaElement {
     fn Paused(&self) -> bool {
         self.paused.get()
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-seeking
+    fn Seeking(&self) -> bool {
+        self.seeking.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-currenttime
+    fn CurrentTime(&self) -> Finite<f64> {
+        if self.ready_state.get() == HAVE_NOTHING {
+            return Finite::wrap(self.default_playback_start_position.get());
+        }
+        Finite::wrap(self.playback_position.get())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-currenttime
+    fn SetCurrentTime(&self, time: Finite<f64>) {
+        if self.ready_state.get() == HAVE_NOTHING {
+            self.default_playback_start_position.set(*time);
+        } else {
+            self.seek(*time);
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-duration
+    fn Duration(&self) -> f64 {
+        self.duration.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-ended
+    fn Ended(&self) -> bool {
+        // https://html.spec.whatwg.org/multipage/#ended-playback
+        self.ready_state.get() >= HAVE_METADATA &&
+        self.playback_position.get() >= self.duration.get() &&
+        !self.Loop()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-loop
+    make_bool_getter!(Loop, "loop");
+    // https://html.spec.whatwg.org/multipage/#dom-media-loop
+    make_bool_setter!(SetLoop, "loop");
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-texttracks
+    fn TextTracks(&self) -> Root<TextTrackList> {
+        self.text_tracks.or_init(|| TextTrackList::new(&window_from_node(self)))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-media-addtexttrack
+    fn AddTextTrack(&self, kind: TextTrackKind, label: DOMString, language: DOMString) -> Root<TextTrack> {
+        // Steps 1 to 3
+        let track = TextTrack::new(&window_from_node(self), kind, label, language, TextTrackMode::Hidden);
+        // Step 4
+        self.add_text_track(&track);
+        // Step 5
+        track
+    }
 }
 
 impl VirtualMethods for HTMLMediaElement {
@@ -781,6 +1547,42 @@ impl Runnable for FireSimpleEventTask {
     }
 }
 
+/// Makes layout show the latest video frame of an element.
+struct VideoFrameTask {
+    elem: Trusted<HTMLMediaElement>,
+    /// The generation of the element when the frames started to be painted.
+    generation_id: u32,
+    /// The new size of the frames, if it changed.
+    size: Option<Size2D<u32>>,
+}
+
+impl Runnable for VideoFrameTask {
+    fn name(&self) -> &'static str { "VideoFrameTask" }
+
+    fn handler(self: Box<VideoFrameTask>) {
+        self.elem.root().video_frame_painted(self.generation_id, self.size);
+    }
+}
+
+/// Tells an element how the playback of its resource progresses.
+struct PlayerEventTask {
+    elem: Trusted<HTMLMediaElement>,
+    /// The generation of the element when the player started.
+    generation_id: u32,
+    event: PlayerEvent,
+}
+
+impl Runnable for PlayerEventTask {
+    fn name(&self) -> &'static str { "PlayerEventTask" }
+
+    fn handler(self: Box<PlayerEventTask>) {
+        let elem = self.elem.root();
+        if elem.generation_id.get() == self.generation_id {
+            elem.handle_player_event(self.event);
+        }
+    }
+}
+
 struct ResourceSelectionTask {
     elem: Trusted<HTMLMediaElement>,
     base_url: Url,
@@ -855,4 +1657,25 @@ enum ResourceSelectionMode {
 enum Resource {
     Object,
     Url(Url),
+    MediaSource(Root<MediaSource>),
+}
+
+pub trait LayoutHTMLMediaElementHelpers {
+    fn video_data(&self) -> HTMLCanvasData;
+}
+
+impl LayoutHTMLMediaElementHelpers for LayoutJS<HTMLMediaElement> {
+    #[allow(unsafe_code)]
+    fn video_data(&self) -> HTMLCanvasData {
+        unsafe {
+            let elem = &*self.unsafe_get();
+            let size = elem.video_size.get().unwrap_or(Size2D::new(DEFAULT_VIDEO_WIDTH, DEFAULT_VIDEO_HEIGHT));
+            HTMLCanvasData {
+                ipc_renderer: elem.video_renderer.borrow_for_layout().clone(),
+                width: size.width,
+                height: size.height,
+                cues: elem.shown_cues.borrow_for_layout().clone(),
+            }
+        }
+    }
 }
@@ -16,6 +16,7 @@ use dom::htmlelement::HTMLElement;
 use dom::htmlheadelement::HTMLHeadElement;
 use dom::node::{Node, UnbindContext, document_from_node};
 use dom::virtualmethods::VirtualMethods;
+use parse::encoding::{extract_encoding_from_content, get_an_encoding};
 use std::ascii::AsciiExt;
 use std::sync::Arc;
 use string_cache::Atom;
@@ -113,6 +114,31 @@ impl HTMLMetaElement {
             }
         }
     }
+
+    /// Changes the encoding of the document being parsed to the one that this element
+    /// declares, with a charset attribute or as the content of a Content-Type pragma.
+    /// https://html.spec.whatwg.org/multipage/#parsing-main-inhead
+    fn process_charset(&self) {
+        let document = document_from_node(self);
+        if document.get_current_parser().is_none() {
+            return;
+        }
+        let element = self.upcast::<Element>();
+        let charset = element.get_attribute(&ns!(), &atom!("charset"))
+                             .and_then(|charset| get_an_encoding(&charset.value()));
+        let encoding = charset.or_else(|| {
+            let is_content_type = element.get_attribute(&ns!(), &atom!("http-equiv"))
+                                         .map_or(false, |pragma| pragma.value().eq_ignore_ascii_case("content-type"));
+            if !is_content_type {
+                return None;
+            }
+            element.get_attribute(&ns!(), &atom!("content"))
+                   .and_then(|content| extract_encoding_from_content(&content.value()))
+        });
+        if let Some(encoding) = encoding {
+            document.change_the_encoding(encoding);
+        }
+    }
 }
 
 impl HTMLMetaElementMethods for HTMLMetaElement {
@@ -127,6 +153,7 @@ impl HTMLMetaElementMethods for HTMLMetaElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-meta-content
     make_setter!(SetContent, "content");
+
 }
 
 impl VirtualMethods for HTMLMetaElement {
@@ -141,6 +168,7 @@ impl VirtualMethods for HTMLMetaElement {
 
         if tree_in_doc {
             self.process_attributes();
+            self.process_charset();
         }
     }
 
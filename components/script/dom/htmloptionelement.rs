@@ -21,6 +21,7 @@ use dom::htmlscriptelement::HTMLScriptElement;
 use dom::htmlselectelement::HTMLSelectElement;
 use dom::node::{Node, UnbindContext};
 use dom::text::Text;
+use dom::validation::Validatable;
 use dom::virtualmethods::VirtualMethods;
 use std::cell::Cell;
 use string_cache::Atom;
@@ -72,6 +73,7 @@ impl HTMLOptionElement {
                 select.pick_option(self);
             }
             select.ask_for_reset();
+            select.update_validity_states();
         }
     }
 }
@@ -232,6 +234,7 @@ impl VirtualMethods for HTMLOptionElement {
                 .filter_map(Root::downcast::<HTMLSelectElement>)
                 .next() {
             select.ask_for_reset();
+            select.update_validity_states();
         }
 
         let node = self.upcast::<Node>();
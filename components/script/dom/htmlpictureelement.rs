@@ -0,0 +1,36 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::HTMLPictureElementBinding;
+use dom::bindings::js::Root;
+use dom::bindings::str::DOMString;
+use dom::document::Document;
+use dom::htmlelement::HTMLElement;
+use dom::node::Node;
+use string_cache::Atom;
+
+#[dom_struct]
+pub struct HTMLPictureElement {
+    htmlelement: HTMLElement
+}
+
+impl HTMLPictureElement {
+    fn new_inherited(local_name: Atom,
+                     prefix: Option<DOMString>,
+                     document: &Document) -> HTMLPictureElement {
+        HTMLPictureElement {
+            htmlelement:
+                HTMLElement::new_inherited(local_name, prefix, document)
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(local_name: Atom,
+               prefix: Option<DOMString>,
+               document: &Document) -> Root<HTMLPictureElement> {
+        Node::reflect_node(box HTMLPictureElement::new_inherited(local_name, prefix, document),
+                           document,
+                           HTMLPictureElementBinding::Wrap)
+    }
+}
@@ -10,6 +10,7 @@ use dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use dom::bindings::codegen::Bindings::HTMLScriptElementBinding;
 use dom::bindings::codegen::Bindings::HTMLScriptElementBinding::HTMLScriptElementMethods;
 use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use dom::bindings::global::GlobalRef;
 use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, Root};
@@ -24,6 +25,7 @@ use dom::eventdispatcher::EventStatus;
 use dom::htmlelement::HTMLElement;
 use dom::node::{ChildrenMutation, CloneChildrenFlag, Node};
 use dom::node::{document_from_node, window_from_node};
+use dom::performanceresourcetiming::initiator_type;
 use dom::virtualmethods::VirtualMethods;
 use dom::window::ScriptHelpers;
 use encoding::label::encoding_from_whatwg_label;
@@ -32,7 +34,7 @@ use html5ever::tree_builder::NextParserState;
 use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
 use js::jsval::UndefinedValue;
-use net_traits::{FetchMetadata, FetchResponseListener, Metadata, NetworkError};
+use net_traits::{FetchMetadata, FetchResponseListener, IpcBytes, Metadata, NetworkError};
 use net_traits::request::{CORSSettings, CredentialsMode, Destination, RequestInit, RequestMode, Type as RequestType};
 use network_listener::{NetworkListener, PreInvoke};
 use std::ascii::AsciiExt;
@@ -181,15 +183,23 @@ impl FetchResponseListener for ScriptContext {
         };
     }
 
-    fn process_response_chunk(&mut self, mut chunk: Vec<u8>) {
+    fn process_response_chunk(&mut self, chunk: IpcBytes) {
         if self.status.is_ok() {
-            self.data.append(&mut chunk);
+            self.data.extend_from_slice(&chunk);
         }
     }
 
     /// https://html.spec.whatwg.org/multipage/#fetch-a-classic-script
     /// step 4-9
     fn process_response_eof(&mut self, response: Result<(), NetworkError>) {
+        let elem = self.elem.root();
+        if let (&Ok(()), Some(metadata)) = (&response, self.metadata.as_ref()) {
+            window_from_node(elem.r()).Performance()
+                                      .queue_resource_timing(&self.url,
+                                                             initiator_type(Destination::Script),
+                                                             metadata);
+        }
+
         // Step 5.
         let load = response.and(self.status.clone()).map(|_| {
             let metadata = self.metadata.take().unwrap();
@@ -207,7 +217,6 @@ impl FetchResponseListener for ScriptContext {
         // Step 9.
         // https://html.spec.whatwg.org/multipage/#prepare-a-script
         // Step 18.6 (When the chosen algorithm asynchronously completes).
-        let elem = self.elem.root();
         *elem.load.borrow_mut() = Some(load);
         elem.ready_to_be_parser_executed.set(true);
 
@@ -242,11 +251,11 @@ fn fetch_a_classic_script(script: &HTMLScriptElement,
             Some(CORSSettings::Anonymous) => CredentialsMode::CredentialsSameOrigin,
             _ => CredentialsMode::Include,
         },
-        origin: doc.url().clone(),
+        origin: doc.url(),
         pipeline_id: Some(script.global().r().pipeline_id()),
         // FIXME: Set to true for now, discussion in https://github.com/whatwg/fetch/issues/381
         same_origin_data: true,
-        referrer_url: Some(doc.url().clone()),
+        referrer_url: Some(doc.url()),
         referrer_policy: doc.get_referrer_policy(),
         .. RequestInit::default()
     };
@@ -446,6 +455,10 @@ impl HTMLScriptElement {
             self.ready_to_be_parser_executed.set(true);
             *self.load.borrow_mut() = Some(Ok(ScriptOrigin::internal(text, base_url)));
             self.execute();
+            // The script may have stopped the document's load, aborting the parser.
+            if was_parser_inserted && doc.get_current_parser().is_none() {
+                return NextParserState::Suspend;
+            }
             return NextParserState::Continue;
         }
 
@@ -27,8 +27,8 @@ use dom::htmloptionelement::HTMLOptionElement;
 use dom::htmloptionscollection::HTMLOptionsCollection;
 use dom::node::{Node, UnbindContext, window_from_node};
 use dom::nodelist::NodeList;
-use dom::validation::Validatable;
-use dom::validitystate::ValidityState;
+use dom::validation::{Validatable, is_barred_by_datalist_ancestor};
+use dom::validitystate::{VALUE_MISSING, ValidationFlags, ValidityState};
 use dom::virtualmethods::VirtualMethods;
 use string_cache::Atom;
 use style::attr::AttrValue;
@@ -59,6 +59,7 @@ impl CollectionFilter for OptionsFilter {
 pub struct HTMLSelectElement {
     htmlelement: HTMLElement,
     options: MutNullableHeap<JS<HTMLOptionsCollection>>,
+    validity_state: MutNullableHeap<JS<ValidityState>>,
 }
 
 static DEFAULT_SELECT_SIZE: u32 = 0;
@@ -69,9 +70,10 @@ impl HTMLSelectElement {
                      document: &Document) -> HTMLSelectElement {
         HTMLSelectElement {
             htmlelement:
-                HTMLElement::new_inherited_with_state(IN_ENABLED_STATE,
+                HTMLElement::new_inherited_with_state(IN_ENABLED_STATE | IN_VALID_STATE | IN_OPTIONAL_STATE,
                                                       local_name, prefix, document),
-                options: Default::default()
+                options: Default::default(),
+                validity_state: Default::default(),
         }
     }
 
@@ -146,6 +148,18 @@ impl HTMLSelectElement {
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#placeholder-label-option
+    fn placeholder_label_option(&self) -> Option<Root<HTMLOptionElement>> {
+        if self.Multiple() || self.display_size() != 1 {
+            return None;
+        }
+        let node = self.upcast::<Node>();
+        node.children().filter_map(Root::downcast::<HTMLOptionElement>).next()
+            .and_then(|first_option| {
+                if first_option.Value().is_empty() { Some(first_option) } else { None }
+            })
+    }
+
     // https://html.spec.whatwg.org/multipage/#concept-select-size
     fn display_size(&self) -> u32 {
          if self.Size() == 0 {
@@ -161,12 +175,6 @@ impl HTMLSelectElement {
 }
 
 impl HTMLSelectElementMethods for HTMLSelectElement {
-    // https://html.spec.whatwg.org/multipage/#dom-cva-validity
-    fn Validity(&self) -> Root<ValidityState> {
-        let window = window_from_node(self);
-        ValidityState::new(window.r(), self.upcast())
-    }
-
     // Note: this function currently only exists for union.html.
     // https://html.spec.whatwg.org/multipage/#dom-select-add
     fn Add(&self, _element: HTMLOptionElementOrHTMLOptGroupElement, _before: Option<HTMLElementOrLong>) {
@@ -195,6 +203,12 @@ impl HTMLSelectElementMethods for HTMLSelectElement {
     // https://html.spec.whatwg.org/multipage/#dom-fe-name
     make_setter!(SetName, "name");
 
+    // https://html.spec.whatwg.org/multipage/#dom-select-required
+    make_bool_getter!(Required, "required");
+
+    // https://html.spec.whatwg.org/multipage/#dom-select-required
+    make_bool_setter!(SetRequired, "required");
+
     // https://html.spec.whatwg.org/multipage/#dom-select-size
     make_uint_getter!(Size, "size", DEFAULT_SELECT_SIZE);
 
@@ -258,6 +272,36 @@ impl HTMLSelectElementMethods for HTMLSelectElement {
     fn Remove(&self) {
         self.upcast::<Element>().Remove()
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-willvalidate
+    fn WillValidate(&self) -> bool {
+        self.will_validate()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validity
+    fn Validity(&self) -> Root<ValidityState> {
+        self.validity_state()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    fn ValidationMessage(&self) -> DOMString {
+        self.validation_message()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.check_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.report_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity
+    fn SetCustomValidity(&self, error: DOMString) {
+        self.validity_state().set_custom_error_message(error);
+    }
 }
 
 impl VirtualMethods for HTMLSelectElement {
@@ -267,20 +311,28 @@ impl VirtualMethods for HTMLSelectElement {
 
     fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation) {
         self.super_type().unwrap().attribute_mutated(attr, mutation);
-        if attr.local_name() == &atom!("disabled") {
-            let el = self.upcast::<Element>();
-            match mutation {
-                AttributeMutation::Set(_) => {
-                    el.set_disabled_state(true);
-                    el.set_enabled_state(false);
-                },
-                AttributeMutation::Removed => {
-                    el.set_disabled_state(false);
-                    el.set_enabled_state(true);
-                    el.check_ancestors_disabled_state_for_form_control();
+        match attr.local_name() {
+            &atom!("disabled") => {
+                let el = self.upcast::<Element>();
+                match mutation {
+                    AttributeMutation::Set(_) => {
+                        el.set_disabled_state(true);
+                        el.set_enabled_state(false);
+                    },
+                    AttributeMutation::Removed => {
+                        el.set_disabled_state(false);
+                        el.set_enabled_state(true);
+                        el.check_ancestors_disabled_state_for_form_control();
+                    }
                 }
-            }
+            },
+            &atom!("required") => {
+                self.upcast::<Element>().set_required_state(mutation.new_value(attr).is_some());
+            },
+            _ => {},
         }
+
+        self.update_validity_states();
     }
 
     fn bind_to_tree(&self, tree_in_doc: bool) {
@@ -289,6 +341,7 @@ impl VirtualMethods for HTMLSelectElement {
         }
 
         self.upcast::<Element>().check_ancestors_disabled_state_for_form_control();
+        self.update_validity_states();
     }
 
     fn unbind_from_tree(&self, context: &UnbindContext) {
@@ -301,6 +354,7 @@ impl VirtualMethods for HTMLSelectElement {
         } else {
             el.check_disabled_attribute();
         }
+        self.update_validity_states();
     }
 
     fn parse_plain_attribute(&self, local_name: &Atom, value: DOMString) -> AttrValue {
@@ -313,4 +367,33 @@ impl VirtualMethods for HTMLSelectElement {
 
 impl FormControl for HTMLSelectElement {}
 
-impl Validatable for HTMLSelectElement {}
+impl Validatable for HTMLSelectElement {
+    fn as_element(&self) -> &Element {
+        self.upcast()
+    }
+
+    fn validity_state_slot(&self) -> &MutNullableHeap<JS<ValidityState>> {
+        &self.validity_state
+    }
+
+    fn is_instance_validatable(&self) -> bool {
+        let el = self.upcast::<Element>();
+        !(el.disabled_state() || is_barred_by_datalist_ancestor(el))
+    }
+
+    fn perform_validation(&self, validate_flags: ValidationFlags) -> ValidationFlags {
+        let mut failed_flags = ValidationFlags::empty();
+        // https://html.spec.whatwg.org/multipage/#the-select-element:suffering-from-being-missing
+        if validate_flags.contains(VALUE_MISSING) && self.Required() {
+            let placeholder = self.placeholder_label_option();
+            let node = self.upcast::<Node>();
+            let has_selected_option = node.traverse_preorder()
+                .filter_map(Root::downcast::<HTMLOptionElement>)
+                .any(|opt| opt.Selected() && Some(&opt) != placeholder.as_ref());
+            if !has_selected_option {
+                failed_flags.insert(VALUE_MISSING);
+            }
+        }
+        failed_flags
+    }
+}
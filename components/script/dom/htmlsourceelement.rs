@@ -2,13 +2,24 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use cssparser::Parser as CssParser;
+use dom::attr::Attr;
 use dom::bindings::codegen::Bindings::HTMLSourceElementBinding;
+use dom::bindings::codegen::Bindings::HTMLSourceElementBinding::HTMLSourceElementMethods;
+use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use dom::bindings::inheritance::Castable;
 use dom::bindings::js::Root;
 use dom::bindings::str::DOMString;
 use dom::document::Document;
+use dom::element::{AttributeMutation, Element};
 use dom::htmlelement::HTMLElement;
-use dom::node::Node;
+use dom::htmlimageelement::{HTMLImageElement, ImageSource, is_supported_image_type};
+use dom::htmlimageelement::parse_a_srcset_attribute;
+use dom::htmlpictureelement::HTMLPictureElement;
+use dom::node::{Node, UnbindContext};
+use dom::virtualmethods::VirtualMethods;
 use string_cache::Atom;
+use style::media_queries::{Device, parse_media_query_list};
 
 #[dom_struct]
 pub struct HTMLSourceElement {
@@ -33,4 +44,108 @@ impl HTMLSourceElement {
                            document,
                            HTMLSourceElementBinding::Wrap)
     }
+
+    /// The image candidates and sizes attribute of this element, if it is one that an image
+    /// in a `<picture>` would select its source from on `device`.
+    /// https://html.spec.whatwg.org/multipage/#update-the-source-set (step 3)
+    pub fn source_set(&self, device: &Device) -> Option<(Vec<ImageSource>, DOMString)> {
+        let element = self.upcast::<Element>();
+        let srcset = match element.get_attribute(&ns!(), &atom!("srcset")) {
+            Some(srcset) => srcset,
+            None => return None,
+        };
+        let candidates = parse_a_srcset_attribute(&srcset.value());
+        if candidates.is_empty() {
+            return None;
+        }
+
+        if let Some(media) = element.get_attribute(&ns!(), &atom!("media")) {
+            let media = parse_media_query_list(&mut CssParser::new(&media.value()));
+            if !media.evaluate(device) {
+                return None;
+            }
+        }
+
+        if let Some(mime) = element.get_attribute(&ns!(), &atom!("type")) {
+            if !is_supported_image_type(&mime.value()) {
+                return None;
+            }
+        }
+
+        Some((candidates, element.get_string_attribute(&atom!("sizes"))))
+    }
+
+    /// Runs the image selection of the images in `parent` again, if it is a `<picture>`,
+    /// since a change to one of its `<source>` elements is a relevant mutation for them.
+    fn picture_changed(parent: &Node) {
+        if !parent.is::<HTMLPictureElement>() {
+            return;
+        }
+        for child in parent.children() {
+            if let Some(image) = child.downcast::<HTMLImageElement>() {
+                image.update_the_image_data();
+            }
+        }
+    }
+}
+
+impl HTMLSourceElementMethods for HTMLSourceElement {
+    // https://html.spec.whatwg.org/multipage/#dom-source-src
+    make_url_getter!(Src, "src");
+    // https://html.spec.whatwg.org/multipage/#dom-source-src
+    make_setter!(SetSrc, "src");
+
+    // https://html.spec.whatwg.org/multipage/#dom-source-type
+    make_getter!(Type, "type");
+    // https://html.spec.whatwg.org/multipage/#dom-source-type
+    make_setter!(SetType, "type");
+
+    // https://html.spec.whatwg.org/multipage/#dom-source-srcset
+    make_getter!(Srcset, "srcset");
+    // https://html.spec.whatwg.org/multipage/#dom-source-srcset
+    make_setter!(SetSrcset, "srcset");
+
+    // https://html.spec.whatwg.org/multipage/#dom-source-sizes
+    make_getter!(Sizes, "sizes");
+    // https://html.spec.whatwg.org/multipage/#dom-source-sizes
+    make_setter!(SetSizes, "sizes");
+
+    // https://html.spec.whatwg.org/multipage/#dom-source-media
+    make_getter!(Media, "media");
+    // https://html.spec.whatwg.org/multipage/#dom-source-media
+    make_setter!(SetMedia, "media");
+}
+
+impl VirtualMethods for HTMLSourceElement {
+    fn super_type(&self) -> Option<&VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &VirtualMethods)
+    }
+
+    fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation) {
+        self.super_type().unwrap().attribute_mutated(attr, mutation);
+        match attr.local_name() {
+            &atom!("srcset") | &atom!("sizes") | &atom!("media") | &atom!("type") => {
+                if let Some(parent) = self.upcast::<Node>().GetParentNode() {
+                    HTMLSourceElement::picture_changed(&parent);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn bind_to_tree(&self, tree_in_doc: bool) {
+        if let Some(ref s) = self.super_type() {
+            s.bind_to_tree(tree_in_doc);
+        }
+
+        if let Some(parent) = self.upcast::<Node>().GetParentNode() {
+            HTMLSourceElement::picture_changed(&parent);
+        }
+    }
+
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        self.super_type().unwrap().unbind_from_tree(context);
+
+        HTMLSourceElement::picture_changed(context.parent);
+    }
 }
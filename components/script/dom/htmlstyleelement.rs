@@ -61,8 +61,8 @@ impl HTMLStyleElement {
         };
 
         let data = node.GetTextContent().expect("Element.textContent must be a string");
-        let mut sheet = Stylesheet::from_str(&data, url, Origin::Author, win.css_error_reporter(),
-                                             ParserContextExtraData::default());
+        let extra_data = ParserContextExtraData::for_quirks_mode(document_from_node(node).style_quirks_mode());
+        let mut sheet = Stylesheet::from_str(&data, url, Origin::Author, win.css_error_reporter(), extra_data);
         let mut css_parser = CssParser::new(&mq_str);
         let media = parse_media_query_list(&mut css_parser);
         sheet.set_media(Some(media));
@@ -18,6 +18,7 @@ use string_cache::Atom;
 use style::attr::{AttrValue, LengthOrPercentageOrAuto};
 
 const DEFAULT_COLSPAN: u32 = 1;
+const DEFAULT_ROWSPAN: u32 = 1;
 
 #[dom_struct]
 pub struct HTMLTableCellElement {
@@ -47,6 +48,12 @@ impl HTMLTableCellElementMethods for HTMLTableCellElement {
     // https://html.spec.whatwg.org/multipage/#dom-tdth-colspan
     make_uint_setter!(SetColSpan, "colspan", DEFAULT_COLSPAN);
 
+    // https://html.spec.whatwg.org/multipage/#dom-tdth-rowspan
+    make_uint_getter!(RowSpan, "rowspan", DEFAULT_ROWSPAN);
+
+    // https://html.spec.whatwg.org/multipage/#dom-tdth-rowspan
+    make_uint_setter!(SetRowSpan, "rowspan", DEFAULT_ROWSPAN);
+
     // https://html.spec.whatwg.org/multipage/#dom-tdth-bgcolor
     make_getter!(BgColor, "bgcolor");
 
@@ -80,6 +87,7 @@ impl HTMLTableCellElementMethods for HTMLTableCellElement {
 pub trait HTMLTableCellElementLayoutHelpers {
     fn get_background_color(&self) -> Option<RGBA>;
     fn get_colspan(&self) -> Option<u32>;
+    fn get_rowspan(&self) -> Option<u32>;
     fn get_width(&self) -> LengthOrPercentageOrAuto;
 }
 
@@ -102,6 +110,14 @@ impl HTMLTableCellElementLayoutHelpers for LayoutJS<HTMLTableCellElement> {
         }
     }
 
+    fn get_rowspan(&self) -> Option<u32> {
+        unsafe {
+            (&*self.upcast::<Element>().unsafe_get())
+                .get_attr_for_layout(&ns!(), &atom!("rowspan"))
+                .map(AttrValue::as_uint)
+        }
+    }
+
     fn get_width(&self) -> LengthOrPercentageOrAuto {
         unsafe {
             (&*self.upcast::<Element>().unsafe_get())
@@ -121,6 +137,7 @@ impl VirtualMethods for HTMLTableCellElement {
     fn parse_plain_attribute(&self, local_name: &Atom, value: DOMString) -> AttrValue {
         match *local_name {
             atom!("colspan") => AttrValue::from_u32(value.into(), DEFAULT_COLSPAN),
+            atom!("rowspan") => AttrValue::from_u32(value.into(), DEFAULT_ROWSPAN),
             atom!("bgcolor") => AttrValue::from_legacy_color(value.into()),
             atom!("width") => AttrValue::from_nonzero_dimension(value.into()),
             _ => self.super_type().unwrap().parse_plain_attribute(local_name, value),
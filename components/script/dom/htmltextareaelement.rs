@@ -9,7 +9,7 @@ use dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding;
 use dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
 use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use dom::bindings::inheritance::Castable;
-use dom::bindings::js::{LayoutJS, Root};
+use dom::bindings::js::{JS, LayoutJS, MutNullableHeap, Root};
 use dom::bindings::str::DOMString;
 use dom::document::Document;
 use dom::element::{AttributeMutation, Element};
@@ -22,7 +22,8 @@ use dom::keyboardevent::KeyboardEvent;
 use dom::node::{ChildrenMutation, Node, NodeDamage, UnbindContext};
 use dom::node::{document_from_node, window_from_node};
 use dom::nodelist::NodeList;
-use dom::validation::Validatable;
+use dom::validation::{Validatable, is_barred_by_datalist_ancestor};
+use dom::validitystate::{VALUE_MISSING, ValidationFlags, ValidityState};
 use dom::virtualmethods::VirtualMethods;
 use ipc_channel::ipc::IpcSender;
 use script_traits::ScriptMsg as ConstellationMsg;
@@ -40,6 +41,7 @@ pub struct HTMLTextAreaElement {
     textinput: DOMRefCell<TextInput<IpcSender<ConstellationMsg>>>,
     // https://html.spec.whatwg.org/multipage/#concept-textarea-dirty
     value_changed: Cell<bool>,
+    validity_state: MutNullableHeap<JS<ValidityState>>,
 }
 
 pub trait LayoutHTMLTextAreaElementHelpers {
@@ -102,11 +104,13 @@ impl HTMLTextAreaElement {
         let chan = document.window().constellation_chan().clone();
         HTMLTextAreaElement {
             htmlelement:
-                HTMLElement::new_inherited_with_state(IN_ENABLED_STATE | IN_READ_WRITE_STATE,
+                HTMLElement::new_inherited_with_state(IN_ENABLED_STATE | IN_READ_WRITE_STATE |
+                                                      IN_VALID_STATE | IN_OPTIONAL_STATE,
                                                       local_name, prefix, document),
             textinput: DOMRefCell::new(TextInput::new(
                     Lines::Multiple, DOMString::new(), chan, None, None, SelectionDirection::None)),
             value_changed: Cell::new(false),
+            validity_state: MutNullableHeap::new(None),
         }
     }
 
@@ -208,6 +212,7 @@ impl HTMLTextAreaElementMethods for HTMLTextAreaElement {
         // TODO move the cursor to the end of the field
         self.textinput.borrow_mut().set_content(value);
         self.value_changed.set(true);
+        self.update_validity_states();
 
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
@@ -265,6 +270,36 @@ impl HTMLTextAreaElementMethods for HTMLTextAreaElement {
             window.r());
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-willvalidate
+    fn WillValidate(&self) -> bool {
+        self.will_validate()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validity
+    fn Validity(&self) -> Root<ValidityState> {
+        self.validity_state()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    fn ValidationMessage(&self) -> DOMString {
+        self.validation_message()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-checkvalidity
+    fn CheckValidity(&self) -> bool {
+        self.check_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-reportvalidity
+    fn ReportValidity(&self) -> bool {
+        self.report_validity()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity
+    fn SetCustomValidity(&self, error: DOMString) {
+        self.validity_state().set_custom_error_message(error);
+    }
 }
 
 
@@ -320,9 +355,14 @@ impl VirtualMethods for HTMLTextAreaElement {
                         el.set_read_write_state(!el.disabled_state());
                     }
                 }
-            }
+            },
+            atom!("required") => {
+                self.upcast::<Element>().set_required_state(mutation.new_value(attr).is_some());
+            },
             _ => {},
         }
+
+        self.update_validity_states();
     }
 
     fn bind_to_tree(&self, tree_in_doc: bool) {
@@ -331,6 +371,7 @@ impl VirtualMethods for HTMLTextAreaElement {
         }
 
         self.upcast::<Element>().check_ancestors_disabled_state_for_form_control();
+        self.update_validity_states();
     }
 
     fn parse_plain_attribute(&self, name: &Atom, value: DOMString) -> AttrValue {
@@ -351,6 +392,7 @@ impl VirtualMethods for HTMLTextAreaElement {
         } else {
             el.check_disabled_attribute();
         }
+        self.update_validity_states();
     }
 
     fn children_changed(&self, mutation: &ChildrenMutation) {
@@ -378,6 +420,7 @@ impl VirtualMethods for HTMLTextAreaElement {
                     KeyReaction::TriggerDefaultAction => (),
                     KeyReaction::DispatchInput => {
                         self.value_changed.set(true);
+                        self.update_validity_states();
 
                         if event.IsTrusted() {
                             let window = window_from_node(self);
@@ -405,4 +448,28 @@ impl VirtualMethods for HTMLTextAreaElement {
 
 impl FormControl for HTMLTextAreaElement {}
 
-impl Validatable for HTMLTextAreaElement {}
+impl Validatable for HTMLTextAreaElement {
+    fn as_element(&self) -> &Element {
+        self.upcast()
+    }
+
+    fn validity_state_slot(&self) -> &MutNullableHeap<JS<ValidityState>> {
+        &self.validity_state
+    }
+
+    fn is_instance_validatable(&self) -> bool {
+        let el = self.upcast::<Element>();
+        // https://html.spec.whatwg.org/multipage/#the-textarea-element:barred-from-constraint-validation
+        !(el.disabled_state() || self.ReadOnly() || is_barred_by_datalist_ancestor(el))
+    }
+
+    fn perform_validation(&self, validate_flags: ValidationFlags) -> ValidationFlags {
+        let mut failed_flags = ValidationFlags::empty();
+        // https://html.spec.whatwg.org/multipage/#the-textarea-element:suffering-from-being-missing
+        if validate_flags.contains(VALUE_MISSING) && self.Required() && self.mutable() &&
+           self.textinput.borrow().is_empty() {
+            failed_flags.insert(VALUE_MISSING);
+        }
+        failed_flags
+    }
+}
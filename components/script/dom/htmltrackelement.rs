@@ -2,23 +2,62 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use document_loader::LoadType;
+use dom::attr::Attr;
 use dom::bindings::codegen::Bindings::HTMLTrackElementBinding;
-use dom::bindings::js::Root;
+use dom::bindings::codegen::Bindings::HTMLTrackElementBinding::HTMLTrackElementConstants::*;
+use dom::bindings::codegen::Bindings::HTMLTrackElementBinding::HTMLTrackElementMethods;
+use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use dom::bindings::codegen::Bindings::TextTrackBinding::{TextTrackKind, TextTrackMethods, TextTrackMode};
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::refcounted::Trusted;
 use dom::bindings::str::DOMString;
 use dom::document::Document;
+use dom::element::{AttributeMutation, Element};
 use dom::htmlelement::HTMLElement;
-use dom::node::Node;
+use dom::htmlmediaelement::HTMLMediaElement;
+use dom::node::{Node, UnbindContext, document_from_node, window_from_node};
+use dom::texttrack::TextTrack;
+use dom::texttrackcue::TextTrackCue;
+use dom::virtualmethods::VirtualMethods;
+use dom::vttcue::VTTCue;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use media::webvtt;
+use net_traits::{AsyncResponseListener, AsyncResponseTarget, Metadata, NetworkError};
+use network_listener::{NetworkListener, PreInvoke};
+use std::ascii::AsciiExt;
+use std::cell::Cell;
+use std::mem;
+use std::sync::{Arc, Mutex};
 use string_cache::Atom;
+use task_source::TaskSource;
+use url::Url;
 
 #[dom_struct]
 pub struct HTMLTrackElement {
     htmlelement: HTMLElement,
+    /// https://html.spec.whatwg.org/multipage/#text-track-readiness-state
+    ready_state: Cell<u16>,
+    track: JS<TextTrack>,
+    /// Counts the fetches of the resource of the track, so that those
+    /// superseded by a new `src` are ignored.
+    generation_id: Cell<u32>,
 }
 
 impl HTMLTrackElement {
     fn new_inherited(local_name: Atom, prefix: Option<DOMString>, document: &Document) -> HTMLTrackElement {
+        let track = TextTrack::new(document.window(),
+                                   TextTrackKind::Subtitles,
+                                   DOMString::new(),
+                                   DOMString::new(),
+                                   TextTrackMode::Disabled);
         HTMLTrackElement {
-            htmlelement: HTMLElement::new_inherited(local_name, prefix, document)
+            htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
+            ready_state: Cell::new(NONE),
+            track: JS::from_ref(&*track),
+            generation_id: Cell::new(0),
         }
     }
 
@@ -26,8 +65,241 @@ impl HTMLTrackElement {
     pub fn new(local_name: Atom,
                prefix: Option<DOMString>,
                document: &Document) -> Root<HTMLTrackElement> {
-        Node::reflect_node(box HTMLTrackElement::new_inherited(local_name, prefix, document),
-                           document,
-                           HTMLTrackElementBinding::Wrap)
+        let element = Node::reflect_node(box HTMLTrackElement::new_inherited(local_name, prefix, document),
+                                         document,
+                                         HTMLTrackElementBinding::Wrap);
+        element.track.set_track_element(&element);
+        element
+    }
+
+    /// The kind of the track, from the `kind` attribute.
+    /// https://html.spec.whatwg.org/multipage/#attr-track-kind
+    fn kind(&self) -> TextTrackKind {
+        let kind = match self.upcast::<Element>().get_attribute(&ns!(), &atom!("kind")) {
+            Some(kind) => kind.value().to_ascii_lowercase(),
+            None => return TextTrackKind::Subtitles,
+        };
+        match &*kind {
+            "subtitles" => TextTrackKind::Subtitles,
+            "captions" => TextTrackKind::Captions,
+            "descriptions" => TextTrackKind::Descriptions,
+            "chapters" => TextTrackKind::Chapters,
+            _ => TextTrackKind::Metadata,
+        }
+    }
+
+    fn media_element(&self) -> Option<Root<HTMLMediaElement>> {
+        self.upcast::<Node>().GetParentNode().and_then(|parent| Root::downcast::<HTMLMediaElement>(parent))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#start-the-track-processing-model
+    pub fn start_track_processing_model(&self) {
+        // Steps 1 and 2
+        if self.track.Mode() == TextTrackMode::Disabled || self.ready_state.get() != NONE {
+            return;
+        }
+        // Step 3
+        if self.media_element().is_none() {
+            return;
+        }
+        // Step 8
+        self.ready_state.set(LOADING);
+        // Steps 9 and 10
+        let url = self.upcast::<Element>().get_attribute(&ns!(), &atom!("src")).and_then(|src| {
+            document_from_node(self).base_url().join(&src.value()).ok()
+        });
+        match url {
+            Some(url) => self.fetch(url),
+            None => self.queue_failure(),
+        }
+    }
+
+    fn fetch(&self, url: Url) {
+        let context = Arc::new(Mutex::new(HTMLTrackElementContext {
+            elem: Trusted::new(self),
+            data: vec![],
+            generation_id: self.generation_id.get(),
+            url: url.clone(),
+            failed: false,
+        }));
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        let window = window_from_node(self);
+        let listener = box NetworkListener {
+            context: context,
+            script_chan: window.networking_task_source(),
+            wrapper: Some(window.get_runnable_wrapper()),
+        };
+        ROUTER.add_route(action_receiver.to_opaque(), box move |message| {
+            listener.notify_action(message.to().unwrap());
+        });
+        let response_target = AsyncResponseTarget {
+            sender: action_sender,
+        };
+        document_from_node(self).load_async(LoadType::Media(url), response_target, None);
+    }
+
+    /// Adds the cues of the fetched resource to the track.
+    fn resource_loaded(&self, data: &[u8]) {
+        let cues = match webvtt::parse(&String::from_utf8_lossy(data)) {
+            Ok(cues) => cues,
+            Err(()) => return self.queue_failure(),
+        };
+        let window = window_from_node(self);
+        let cues: Vec<Root<TextTrackCue>> = cues.into_iter().map(|cue| {
+            Root::upcast(VTTCue::from_webvtt_cue(&window, cue))
+        }).collect();
+        self.track.add_cues(&cues);
+        self.ready_state.set(LOADED);
+        self.queue_simple_event("load");
+    }
+
+    fn queue_failure(&self) {
+        self.ready_state.set(ERROR);
+        self.queue_simple_event("error");
+    }
+
+    fn queue_simple_event(&self, name: &'static str) {
+        let window = window_from_node(self);
+        window.dom_manipulation_task_source().queue_simple_event(self.upcast(), Atom::from(name), &window);
+    }
+
+    /// Forgets the resource of the track, and loads the new one, when `src`
+    /// changes.
+    /// https://html.spec.whatwg.org/multipage/#sourcing-out-of-band-text-tracks:attr-track-src-4
+    fn src_changed(&self) {
+        if self.ready_state.get() == NONE {
+            return;
+        }
+        self.generation_id.set(self.generation_id.get() + 1);
+        self.track.clear_cues();
+        self.ready_state.set(NONE);
+        self.start_track_processing_model();
+    }
+}
+
+impl HTMLTrackElementMethods for HTMLTrackElement {
+    // https://html.spec.whatwg.org/multipage/#dom-track-kind
+    fn Kind(&self) -> DOMString {
+        DOMString::from(match self.kind() {
+            TextTrackKind::Subtitles => "subtitles",
+            TextTrackKind::Captions => "captions",
+            TextTrackKind::Descriptions => "descriptions",
+            TextTrackKind::Chapters => "chapters",
+            TextTrackKind::Metadata => "metadata",
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-track-kind
+    make_setter!(SetKind, "kind");
+
+    // https://html.spec.whatwg.org/multipage/#dom-track-src
+    make_url_getter!(Src, "src");
+    // https://html.spec.whatwg.org/multipage/#dom-track-src
+    make_setter!(SetSrc, "src");
+
+    // https://html.spec.whatwg.org/multipage/#dom-track-srclang
+    make_getter!(Srclang, "srclang");
+    // https://html.spec.whatwg.org/multipage/#dom-track-srclang
+    make_setter!(SetSrclang, "srclang");
+
+    // https://html.spec.whatwg.org/multipage/#dom-track-label
+    make_getter!(Label, "label");
+    // https://html.spec.whatwg.org/multipage/#dom-track-label
+    make_setter!(SetLabel, "label");
+
+    // https://html.spec.whatwg.org/multipage/#dom-track-default
+    make_bool_getter!(Default, "default");
+    // https://html.spec.whatwg.org/multipage/#dom-track-default
+    make_bool_setter!(SetDefault, "default");
+
+    // https://html.spec.whatwg.org/multipage/#dom-track-readystate
+    fn ReadyState(&self) -> u16 {
+        self.ready_state.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-track-track
+    fn Track(&self) -> Root<TextTrack> {
+        Root::from_ref(&*self.track)
+    }
+}
+
+impl VirtualMethods for HTMLTrackElement {
+    fn super_type(&self) -> Option<&VirtualMethods> {
+        Some(self.upcast::<HTMLElement>() as &VirtualMethods)
+    }
+
+    fn attribute_mutated(&self, attr: &Attr, mutation: AttributeMutation) {
+        self.super_type().unwrap().attribute_mutated(attr, mutation);
+        let value = || mutation.new_value(attr).map_or(DOMString::new(), |value| DOMString::from(&**value));
+        match attr.local_name() {
+            &atom!("kind") => self.track.set_kind(self.kind()),
+            &atom!("label") => self.track.set_label(value()),
+            &atom!("srclang") => self.track.set_language(value()),
+            &atom!("src") => self.src_changed(),
+            _ => {},
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#sourcing-out-of-band-text-tracks:the-track-element
+    fn bind_to_tree(&self, tree_in_doc: bool) {
+        if let Some(ref s) = self.super_type() {
+            s.bind_to_tree(tree_in_doc);
+        }
+
+        if let Some(media_element) = self.media_element() {
+            media_element.add_text_track(&self.track);
+            self.start_track_processing_model();
+        }
+    }
+
+    fn unbind_from_tree(&self, context: &UnbindContext) {
+        self.super_type().unwrap().unbind_from_tree(context);
+
+        if let Some(media_element) = context.parent.downcast::<HTMLMediaElement>() {
+            media_element.remove_text_track(&self.track);
+        }
+    }
+}
+
+struct HTMLTrackElementContext {
+    elem: Trusted<HTMLTrackElement>,
+    /// The response body received to date.
+    data: Vec<u8>,
+    /// The generation of the element when this fetch started.
+    generation_id: u32,
+    url: Url,
+    /// Whether the response is an error, whose body is ignored.
+    failed: bool,
+}
+
+impl AsyncResponseListener for HTMLTrackElementContext {
+    fn headers_available(&mut self, metadata: Result<Metadata, NetworkError>) {
+        self.failed = match metadata {
+            Ok(metadata) => metadata.status.map_or(false, |(status, _)| status < 200 || status >= 300),
+            Err(_) => true,
+        };
+    }
+
+    fn data_available(&mut self, mut payload: Vec<u8>) {
+        if !self.failed {
+            self.data.append(&mut payload);
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#start-the-track-processing-model step 10
+    fn response_complete(&mut self, status: Result<(), NetworkError>) {
+        let elem = self.elem.root();
+        if status.is_ok() && !self.failed {
+            elem.resource_loaded(&mem::replace(&mut self.data, vec![]));
+        } else {
+            elem.queue_failure();
+        }
+        document_from_node(&*elem).finish_load(LoadType::Media(self.url.clone()));
+    }
+}
+
+impl PreInvoke for HTMLTrackElementContext {
+    fn should_invoke(&self) -> bool {
+        self.elem.root().generation_id.get() == self.generation_id
     }
 }
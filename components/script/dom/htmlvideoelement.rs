@@ -3,12 +3,16 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::codegen::Bindings::HTMLVideoElementBinding;
-use dom::bindings::js::Root;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{LayoutJS, Root};
 use dom::bindings::str::DOMString;
 use dom::document::Document;
+use dom::element::{Element, RawLayoutElementHelpers};
 use dom::htmlmediaelement::HTMLMediaElement;
 use dom::node::Node;
+use dom::virtualmethods::VirtualMethods;
 use string_cache::Atom;
+use style::attr::{AttrValue, LengthOrPercentageOrAuto};
 
 #[dom_struct]
 pub struct HTMLVideoElement {
@@ -32,3 +36,45 @@ impl HTMLVideoElement {
                            HTMLVideoElementBinding::Wrap)
     }
 }
+
+pub trait LayoutHTMLVideoElementHelpers {
+    fn get_width(&self) -> LengthOrPercentageOrAuto;
+    fn get_height(&self) -> LengthOrPercentageOrAuto;
+}
+
+impl LayoutHTMLVideoElementHelpers for LayoutJS<HTMLVideoElement> {
+    #[allow(unsafe_code)]
+    fn get_width(&self) -> LengthOrPercentageOrAuto {
+        unsafe {
+            (*self.upcast::<Element>().unsafe_get())
+                .get_attr_for_layout(&ns!(), &atom!("width"))
+                .map(AttrValue::as_dimension)
+                .cloned()
+                .unwrap_or(LengthOrPercentageOrAuto::Auto)
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn get_height(&self) -> LengthOrPercentageOrAuto {
+        unsafe {
+            (*self.upcast::<Element>().unsafe_get())
+                .get_attr_for_layout(&ns!(), &atom!("height"))
+                .map(AttrValue::as_dimension)
+                .cloned()
+                .unwrap_or(LengthOrPercentageOrAuto::Auto)
+        }
+    }
+}
+
+impl VirtualMethods for HTMLVideoElement {
+    fn super_type(&self) -> Option<&VirtualMethods> {
+        Some(self.upcast::<HTMLMediaElement>() as &VirtualMethods)
+    }
+
+    fn parse_plain_attribute(&self, name: &Atom, value: DOMString) -> AttrValue {
+        match name {
+            &atom!("width") | &atom!("height") => AttrValue::from_dimension(value.into()),
+            _ => self.super_type().unwrap().parse_plain_attribute(name, value),
+        }
+    }
+}
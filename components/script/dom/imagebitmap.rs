@@ -0,0 +1,430 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::{byte_swap, byte_swap_and_premultiply};
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::CanvasRenderingContext2DMethods;
+use dom::bindings::codegen::Bindings::ImageBitmapBinding;
+use dom::bindings::codegen::Bindings::ImageBitmapBinding::{ImageBitmapMethods, ImageBitmapOptions};
+use dom::bindings::codegen::Bindings::ImageBitmapBinding::{ImageOrientation, ResizeQuality};
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::codegen::UnionTypes::HTMLImageElementOrHTMLCanvasElementOrCanvasRenderingContext2DOrBlobOrImageDataOrImageBitmap
+    as ImageBitmapSource;
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::refcounted::TrustedPromise;
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::htmlcanvaselement::HTMLCanvasElement;
+use dom::htmlcanvaselement::utils as canvas_utils;
+use dom::node::window_from_node;
+use dom::promise::Promise;
+use euclid::point::Point2D;
+use euclid::rect::Rect;
+use euclid::size::Size2D;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use js::jsapi::JSAutoCompartment;
+use net_traits::image::base::{Image, PixelFormat, load_from_memory};
+use net_traits::image_cache_thread::ImageResponse;
+use script_runtime::CommonScriptMsg;
+use script_runtime::ScriptThreadEventCategory::ImageCacheMsg;
+use script_thread::Runnable;
+use std::cell::Cell;
+use std::rc::Rc;
+use util::opts;
+use util::thread::spawn_named;
+
+// https://html.spec.whatwg.org/multipage/#imagebitmap
+#[dom_struct]
+pub struct ImageBitmap {
+    reflector_: Reflector,
+    width: Cell<u32>,
+    height: Cell<u32>,
+    /// The bitmap data, as premultiplied RGBA pixels, or `None` once the bitmap has been closed
+    /// or transferred.
+    data: DOMRefCell<Option<Vec<u8>>>,
+    /// https://html.spec.whatwg.org/multipage/#concept-canvas-origin-clean
+    origin_clean: bool,
+}
+
+impl ImageBitmap {
+    fn new_inherited(data: Vec<u8>, size: Size2D<u32>, origin_clean: bool) -> ImageBitmap {
+        ImageBitmap {
+            reflector_: Reflector::new(),
+            width: Cell::new(size.width),
+            height: Cell::new(size.height),
+            data: DOMRefCell::new(Some(data)),
+            origin_clean: origin_clean,
+        }
+    }
+
+    pub fn new(global: GlobalRef, data: Vec<u8>, size: Size2D<u32>, origin_clean: bool)
+               -> Root<ImageBitmap> {
+        reflect_dom_object(box ImageBitmap::new_inherited(data, size, origin_clean),
+                           global,
+                           ImageBitmapBinding::Wrap)
+    }
+
+    /// Returns a copy of the pixels of this bitmap and its size, unless it has been closed.
+    pub fn pixels(&self) -> Option<(Vec<u8>, Size2D<u32>)> {
+        self.data.borrow().as_ref().map(|data| {
+            (data.clone(), Size2D::new(self.width.get(), self.height.get()))
+        })
+    }
+
+    /// Takes the pixels out of this bitmap, which is left closed, for a transfer.
+    /// https://html.spec.whatwg.org/multipage/#imagebitmap:transfer-steps
+    pub fn detach(&self) -> Option<(Vec<u8>, Size2D<u32>)> {
+        let size = Size2D::new(self.width.get(), self.height.get());
+        self.width.set(0);
+        self.height.set(0);
+        self.data.borrow_mut().take().map(|data| (data, size))
+    }
+
+    pub fn is_detached(&self) -> bool {
+        self.data.borrow().is_none()
+    }
+
+    pub fn origin_is_clean(&self) -> bool {
+        self.origin_clean
+    }
+}
+
+impl ImageBitmapMethods for ImageBitmap {
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-width
+    fn Width(&self) -> u32 {
+        self.width.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-height
+    fn Height(&self) -> u32 {
+        self.height.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-close
+    fn Close(&self) {
+        self.detach();
+    }
+}
+
+/// The filter used to scale the source image to the size of the bitmap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScaleFilter {
+    Nearest,
+    Bilinear,
+}
+
+/// The part of the source image that becomes the bitmap, and how it is transformed.
+#[derive(Clone, Copy)]
+struct BitmapParams {
+    crop: Option<Rect<i32>>,
+    resize_width: Option<u32>,
+    resize_height: Option<u32>,
+    filter: ScaleFilter,
+    flip_y: bool,
+}
+
+impl BitmapParams {
+    /// Crops and scales the premultiplied RGBA `pixels` of a source image of the given size.
+    fn apply(&self, pixels: &[u8], size: Size2D<u32>) -> (Vec<u8>, Size2D<u32>) {
+        let crop = self.crop.unwrap_or_else(|| {
+            Rect::new(Point2D::zero(), Size2D::new(size.width as i32, size.height as i32))
+        });
+        let crop_size = Size2D::new(crop.size.width as u32, crop.size.height as u32);
+        let output = output_size(crop_size, self.resize_width, self.resize_height);
+        (crop_and_scale(pixels, size, crop, output, self.filter, self.flip_y), output)
+    }
+}
+
+/// Returns the size of a bitmap cropped to the given size, given the `resizeWidth` and
+/// `resizeHeight` options. A missing dimension keeps the aspect ratio of the crop.
+/// https://html.spec.whatwg.org/multipage/#output-bitmap-dimensions
+pub fn output_size(crop: Size2D<u32>, resize_width: Option<u32>, resize_height: Option<u32>)
+                   -> Size2D<u32> {
+    let scale = |length: u32, from: u32, to: u32| {
+        (length as f64 * to as f64 / from as f64).ceil() as u32
+    };
+    match (resize_width, resize_height) {
+        (Some(width), Some(height)) => Size2D::new(width, height),
+        (Some(width), None) => Size2D::new(width, scale(crop.height, crop.width, width)),
+        (None, Some(height)) => Size2D::new(scale(crop.width, crop.height, height), height),
+        (None, None) => crop,
+    }
+}
+
+/// Copies the `crop` rectangle of an image of the given size, made of premultiplied RGBA
+/// `pixels`, into a new image of the `output` size. The parts of the rectangle outside of the
+/// image are transparent black.
+pub fn crop_and_scale(pixels: &[u8],
+                      size: Size2D<u32>,
+                      crop: Rect<i32>,
+                      output: Size2D<u32>,
+                      filter: ScaleFilter,
+                      flip_y: bool)
+                      -> Vec<u8> {
+    let mut result = vec![0; output.width as usize * output.height as usize * 4];
+    if crop.size.width <= 0 || crop.size.height <= 0 {
+        return result;
+    }
+
+    let pixel = |x: i32, y: i32| -> [f64; 4] {
+        if x < 0 || y < 0 || x >= size.width as i32 || y >= size.height as i32 {
+            return [0.; 4];
+        }
+        let index = (y as usize * size.width as usize + x as usize) * 4;
+        [pixels[index] as f64, pixels[index + 1] as f64,
+         pixels[index + 2] as f64, pixels[index + 3] as f64]
+    };
+
+    let scale_x = crop.size.width as f64 / output.width as f64;
+    let scale_y = crop.size.height as f64 / output.height as f64;
+    for y in 0..output.height {
+        // The source coordinates of the center of this pixel.
+        let source_y = crop.origin.y as f64 + (y as f64 + 0.5) * scale_y;
+        let row = if flip_y { output.height - 1 - y } else { y };
+        for x in 0..output.width {
+            let source_x = crop.origin.x as f64 + (x as f64 + 0.5) * scale_x;
+            let color = match filter {
+                ScaleFilter::Nearest => pixel(source_x.floor() as i32, source_y.floor() as i32),
+                ScaleFilter::Bilinear => {
+                    // Interpolate between the four closest pixels, without sampling outside of
+                    // the crop rectangle.
+                    let clamp = |value: f64, min: i32, length: i32| {
+                        (value as i32).max(min).min(min + length - 1)
+                    };
+                    let (fx, fy) = (source_x - 0.5, source_y - 0.5);
+                    let (tx, ty) = (fx - fx.floor(), fy - fy.floor());
+                    let x0 = clamp(fx.floor(), crop.origin.x, crop.size.width);
+                    let x1 = clamp(fx.floor() + 1., crop.origin.x, crop.size.width);
+                    let y0 = clamp(fy.floor(), crop.origin.y, crop.size.height);
+                    let y1 = clamp(fy.floor() + 1., crop.origin.y, crop.size.height);
+                    let (top_left, top_right) = (pixel(x0, y0), pixel(x1, y0));
+                    let (bottom_left, bottom_right) = (pixel(x0, y1), pixel(x1, y1));
+                    let mut color = [0.; 4];
+                    for i in 0..4 {
+                        let top = top_left[i] + (top_right[i] - top_left[i]) * tx;
+                        let bottom = bottom_left[i] + (bottom_right[i] - bottom_left[i]) * tx;
+                        color[i] = top + (bottom - top) * ty;
+                    }
+                    color
+                }
+            };
+            let index = (row as usize * output.width as usize + x as usize) * 4;
+            for i in 0..4 {
+                result[index + i] = color[i].round() as u8;
+            }
+        }
+    }
+    result
+}
+
+/// Premultiplies RGBA pixels by their alpha.
+fn premultiply(data: &mut [u8]) {
+    for pixel in data.chunks_mut(4) {
+        let alpha = pixel[3] as u32;
+        for channel in &mut pixel[..3] {
+            *channel = (*channel as u32 * alpha / 255) as u8;
+        }
+    }
+}
+
+/// Converts an image from the image cache, in BGRA order, to premultiplied RGBA pixels, as the
+/// canvas does for `drawImage()`.
+fn image_to_rgba(image: &Image) -> Vec<u8> {
+    let mut data = match image.format {
+        PixelFormat::RGBA8 => image.bytes.to_vec(),
+        PixelFormat::K8 => panic!("K8 color type not supported"),
+        PixelFormat::RGB8 => panic!("RGB8 color type not supported"),
+        PixelFormat::KA8 => panic!("KA8 color type not supported"),
+    };
+    if opts::get().use_webrender {
+        // Webrender doesn't pre-multiply alpha when decoding images.
+        byte_swap_and_premultiply(&mut data);
+    } else {
+        byte_swap(&mut data);
+    }
+    data
+}
+
+/// The pixels of an image bitmap source.
+enum SourceImage {
+    /// Premultiplied RGBA pixels of the given size, and whether they are origin-clean.
+    Pixels(Vec<u8>, Size2D<u32>, bool),
+    /// An encoded image, which has to be decoded first.
+    Encoded(Vec<u8>),
+}
+
+/// https://html.spec.whatwg.org/multipage/#check-the-usability-of-the-image-argument
+fn source_image(source: &ImageBitmapSource) -> Fallible<SourceImage> {
+    match *source {
+        ImageBitmapSource::HTMLImageElement(ref image) => {
+            let url = try!(image.get_url().ok_or(Error::InvalidState));
+            let window = window_from_node(&**image);
+            let origin_clean = url.origin() == window.Document().url().origin();
+            match canvas_utils::request_image_from_cache(&window, url) {
+                ImageResponse::Loaded(image) => {
+                    let size = Size2D::new(image.width, image.height);
+                    Ok(SourceImage::Pixels(image_to_rgba(&image), size, origin_clean))
+                }
+                ImageResponse::PlaceholderLoaded(_) |
+                ImageResponse::MetadataLoaded(_) |
+                ImageResponse::None => Err(Error::InvalidState),
+            }
+        }
+        ImageBitmapSource::HTMLCanvasElement(ref canvas) => canvas_image(canvas),
+        ImageBitmapSource::CanvasRenderingContext2D(ref context) => canvas_image(&context.Canvas()),
+        ImageBitmapSource::Blob(ref blob) => {
+            let bytes = try!(blob.get_bytes().map_err(|_| Error::InvalidState));
+            Ok(SourceImage::Encoded(bytes))
+        }
+        ImageBitmapSource::ImageData(ref image_data) => {
+            let mut data = image_data.get_data_array();
+            premultiply(&mut data);
+            let size = image_data.get_size();
+            Ok(SourceImage::Pixels(data, Size2D::new(size.width as u32, size.height as u32), true))
+        }
+        ImageBitmapSource::ImageBitmap(ref bitmap) => {
+            let (data, size) = try!(bitmap.pixels().ok_or(Error::InvalidState));
+            Ok(SourceImage::Pixels(data, size, bitmap.origin_is_clean()))
+        }
+    }
+}
+
+fn canvas_image(canvas: &HTMLCanvasElement) -> Fallible<SourceImage> {
+    if !canvas.is_valid() {
+        return Err(Error::InvalidState);
+    }
+    let (mut data, size) = try!(canvas.fetch_all_data().ok_or(Error::InvalidState));
+    // The canvas keeps its pixels premultiplied, in BGRA order.
+    byte_swap(&mut data);
+    let size = Size2D::new(size.width as u32, size.height as u32);
+    Ok(SourceImage::Pixels(data, size, canvas.origin_is_clean()))
+}
+
+/// Implements both overloads of `createImageBitmap()`, with `crop` holding the `sx`, `sy`, `sw`
+/// and `sh` arguments of the second one.
+/// https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+#[allow(unrooted_must_root)]
+pub fn create_image_bitmap(global: GlobalRef,
+                           image: ImageBitmapSource,
+                           crop: Option<(i32, i32, i32, i32)>,
+                           options: &ImageBitmapOptions)
+                           -> Rc<Promise> {
+    let promise = Promise::new(global);
+    let cx = global.get_cx();
+
+    // Step 1.
+    let crop = match crop {
+        Some((_, _, 0, _)) | Some((_, _, _, 0)) => {
+            promise.reject_error(cx, Error::Range("The crop rectangle is empty".to_owned()));
+            return promise;
+        }
+        // A negative width or height extends the rectangle to the left or up from the origin.
+        Some((sx, sy, sw, sh)) => {
+            let (x, width) = if sw < 0 { (sx.saturating_add(sw), -sw) } else { (sx, sw) };
+            let (y, height) = if sh < 0 { (sy.saturating_add(sh), -sh) } else { (sy, sh) };
+            Some(Rect::new(Point2D::new(x, y), Size2D::new(width, height)))
+        }
+        None => None,
+    };
+
+    // Step 2.
+    if options.resizeWidth == Some(0) || options.resizeHeight == Some(0) {
+        promise.reject_error(cx, Error::InvalidState);
+        return promise;
+    }
+
+    // Bitmaps are always kept premultiplied, in sRGB, since that is what the canvas expects,
+    // so the premultiplyAlpha and colorSpaceConversion options have no effect.
+    let params = BitmapParams {
+        crop: crop,
+        resize_width: options.resizeWidth,
+        resize_height: options.resizeHeight,
+        filter: match options.resizeQuality {
+            ResizeQuality::Pixelated => ScaleFilter::Nearest,
+            ResizeQuality::Low | ResizeQuality::Medium | ResizeQuality::High => {
+                ScaleFilter::Bilinear
+            }
+        },
+        flip_y: options.imageOrientation == ImageOrientation::FlipY,
+    };
+
+    // Step 3.
+    match source_image(&image) {
+        Err(error) => promise.reject_error(cx, error),
+        Ok(SourceImage::Pixels(pixels, size, origin_clean)) => {
+            let (data, size) = params.apply(&pixels, size);
+            let bitmap = ImageBitmap::new(global, data, size, origin_clean);
+            promise.resolve_native(cx, &bitmap);
+        }
+        Ok(SourceImage::Encoded(bytes)) => decode_image_bitmap(global, &promise, bytes, params),
+    }
+    promise
+}
+
+/// Decodes the bytes of a `Blob` source off the script thread, on the image cache thread if
+/// there is one, and settles the promise once that is done.
+fn decode_image_bitmap(global: GlobalRef, promise: &Rc<Promise>, bytes: Vec<u8>,
+                       params: BitmapParams) {
+    let (sender, receiver) = ipc::channel().unwrap();
+    let script_chan = global.networking_task_source();
+    let wrapper = global.get_runnable_wrapper();
+    let mut runnable = Some(box DecodeImageBitmapRunnable {
+        promise: TrustedPromise::new(promise.clone()),
+        params: params,
+        image: None,
+    });
+    ROUTER.add_route(receiver.to_opaque(), box move |message| {
+        if let Some(mut runnable) = runnable.take() {
+            runnable.image = message.to().unwrap();
+            let _ = script_chan.send(CommonScriptMsg::RunnableMsg(
+                ImageCacheMsg, wrapper.wrap_runnable(runnable)));
+        }
+    });
+
+    match global {
+        GlobalRef::Window(window) => window.image_cache_thread().decode_image(bytes, sender),
+        GlobalRef::Worker(_) => {
+            spawn_named("ImageBitmapDecoder".to_owned(), move || {
+                let _ = sender.send(load_from_memory(&bytes));
+            });
+        }
+    }
+}
+
+/// Delivers the decoded image of a `createImageBitmap()` call with a `Blob`.
+struct DecodeImageBitmapRunnable {
+    promise: TrustedPromise,
+    params: BitmapParams,
+    image: Option<Image>,
+}
+
+impl Runnable for DecodeImageBitmapRunnable {
+    fn name(&self) -> &'static str { "DecodeImageBitmapRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let promise = this.promise.root();
+        let global = promise.global();
+        let cx = global.r().get_cx();
+        let _ac = JSAutoCompartment::new(cx, promise.reflector().get_jsobject().get());
+        match this.image {
+            Some(image) => {
+                // Images decoded from memory are always premultiplied, in BGRA order.
+                let mut pixels = image.bytes.to_vec();
+                byte_swap(&mut pixels);
+                let size = Size2D::new(image.width, image.height);
+                let (data, size) = this.params.apply(&pixels, size);
+                let bitmap = ImageBitmap::new(global.r(), data, size, true);
+                promise.resolve_native(cx, &bitmap);
+            }
+            // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+            // If the image data is not in a supported file format, reject with an
+            // InvalidStateError.
+            None => promise.reject_error(cx, Error::InvalidState),
+        }
+    }
+}
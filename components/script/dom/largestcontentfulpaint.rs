@@ -0,0 +1,106 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use dom::bindings::codegen::Bindings::LargestContentfulPaintBinding;
+use dom::bindings::codegen::Bindings::LargestContentfulPaintBinding::LargestContentfulPaintMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::element::Element;
+use dom::node::Node;
+use dom::performance::DOMHighResTimeStamp;
+use dom::performanceentry::PerformanceEntry;
+
+// https://w3c.github.io/largest-contentful-paint/#sec-largest-contentful-paint-interface
+#[dom_struct]
+pub struct LargestContentfulPaint {
+    entry: PerformanceEntry,
+    render_time: f64,
+    load_time: f64,
+    size: u32,
+    id: DOMString,
+    url: DOMString,
+    element: JS<Element>,
+}
+
+impl LargestContentfulPaint {
+    fn new_inherited(render_time: f64,
+                     load_time: f64,
+                     size: u32,
+                     url: DOMString,
+                     element: &Element)
+                     -> LargestContentfulPaint {
+        // The entry starts when it was painted, or when its image loaded for the images
+        // whose render time is not exposed.
+        let start_time = if render_time != 0. { render_time } else { load_time };
+        LargestContentfulPaint {
+            entry: PerformanceEntry::new_inherited(DOMString::new(),
+                                                   DOMString::from("largest-contentful-paint"),
+                                                   start_time,
+                                                   0.),
+            render_time: render_time,
+            load_time: load_time,
+            size: size,
+            id: element.Id(),
+            url: url,
+            element: JS::from_ref(element),
+        }
+    }
+
+    /// Creates an entry for `element`, which was painted at `render_time` with `size`
+    /// square pixels of its text or image showing. `load_time` is when its image loaded,
+    /// or 0 for text.
+    pub fn new(global: GlobalRef,
+               render_time: f64,
+               load_time: f64,
+               size: u32,
+               url: DOMString,
+               element: &Element)
+               -> Root<LargestContentfulPaint> {
+        reflect_dom_object(box LargestContentfulPaint::new_inherited(render_time, load_time, size, url, element),
+                           global,
+                           LargestContentfulPaintBinding::Wrap)
+    }
+}
+
+impl LargestContentfulPaintMethods for LargestContentfulPaint {
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-rendertime
+    fn RenderTime(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.render_time)
+    }
+
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-loadtime
+    fn LoadTime(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.load_time)
+    }
+
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-size
+    fn Size(&self) -> u32 {
+        self.size
+    }
+
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-id
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-url
+    fn Url(&self) -> DOMString {
+        self.url.clone()
+    }
+
+    // https://w3c.github.io/largest-contentful-paint/#dom-largestcontentfulpaint-element
+    fn GetElement(&self) -> Option<Root<Element>> {
+        // Elements that have since been removed from the document are not exposed.
+        if self.element.upcast::<Node>().is_in_doc() {
+            Some(Root::from_ref(&*self.element))
+        } else {
+            None
+        }
+    }
+}
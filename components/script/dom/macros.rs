@@ -135,7 +135,7 @@ macro_rules! make_string_or_document_url_getter(
 
             if val.is_empty() {
                 let doc = document_from_node(self);
-                DOMString::from(doc.url().clone().into_string())
+                DOMString::from(doc.url().into_string())
             } else {
                 val
             }
@@ -195,7 +195,7 @@ macro_rules! make_url_setter(
             use dom::bindings::inheritance::Castable;
             use dom::element::Element;
             use dom::node::document_from_node;
-            let value = AttrValue::from_url(document_from_node(self).url(),
+            let value = AttrValue::from_url(&document_from_node(self).url(),
                                             value.into());
             let element = self.upcast::<Element>();
             element.set_attribute(&atom!($htmlname), value);
@@ -466,6 +466,7 @@ macro_rules! global_event_handlers(
         event_handler!(seeked, GetOnseeked, SetOnseeked);
         event_handler!(seeking, GetOnseeking, SetOnseeking);
         event_handler!(select, GetOnselect, SetOnselect);
+        event_handler!(selectionchange, GetOnselectionchange, SetOnselectionchange);
         event_handler!(show, GetOnshow, SetOnshow);
         event_handler!(stalled, GetOnstalled, SetOnstalled);
         event_handler!(submit, GetOnsubmit, SetOnsubmit);
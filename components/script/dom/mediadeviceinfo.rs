@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::MediaDeviceInfoBinding;
+use dom::bindings::codegen::Bindings::MediaDeviceInfoBinding::{MediaDeviceInfoMethods, MediaDeviceKind};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+
+// https://w3c.github.io/mediacapture-main/#device-info
+#[dom_struct]
+pub struct MediaDeviceInfo {
+    reflector_: Reflector,
+    device_id: DOMString,
+    kind: MediaDeviceKind,
+    label: DOMString,
+    group_id: DOMString,
+}
+
+impl MediaDeviceInfo {
+    fn new_inherited(device_id: DOMString,
+                     kind: MediaDeviceKind,
+                     label: DOMString,
+                     group_id: DOMString)
+                     -> MediaDeviceInfo {
+        MediaDeviceInfo {
+            reflector_: Reflector::new(),
+            device_id: device_id,
+            kind: kind,
+            label: label,
+            group_id: group_id,
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               device_id: DOMString,
+               kind: MediaDeviceKind,
+               label: DOMString,
+               group_id: DOMString)
+               -> Root<MediaDeviceInfo> {
+        reflect_dom_object(box MediaDeviceInfo::new_inherited(device_id, kind, label, group_id),
+                           global,
+                           MediaDeviceInfoBinding::Wrap)
+    }
+}
+
+impl MediaDeviceInfoMethods for MediaDeviceInfo {
+    // https://w3c.github.io/mediacapture-main/#dom-mediadeviceinfo-deviceid
+    fn DeviceId(&self) -> DOMString {
+        self.device_id.clone()
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediadeviceinfo-kind
+    fn Kind(&self) -> MediaDeviceKind {
+        self.kind
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediadeviceinfo-label
+    fn Label(&self) -> DOMString {
+        self.label.clone()
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediadeviceinfo-groupid
+    fn GroupId(&self) -> DOMString {
+        self.group_id.clone()
+    }
+}
@@ -0,0 +1,284 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::MediaDeviceInfoBinding::MediaDeviceKind;
+use dom::bindings::codegen::Bindings::MediaDevicesBinding;
+use dom::bindings::codegen::Bindings::MediaDevicesBinding::{MediaDevicesMethods, MediaStreamConstraints};
+use dom::bindings::codegen::Bindings::MediaTrackConstraintsBinding::MediaTrackConstraints;
+use dom::bindings::conversions::ConversionResult;
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::refcounted::{Trusted, TrustedPromise};
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::eventtarget::EventTarget;
+use dom::mediadeviceinfo::MediaDeviceInfo;
+use dom::mediastream::MediaStream;
+use dom::mediastreamtrack::MediaStreamTrack;
+use dom::promise::Promise;
+use ipc_channel::ipc::{self, IpcSender};
+use js::jsapi::{HandleValue, JSAutoCompartment, JSContext};
+use js::rust::ToBoolean;
+use media::{self, CaptureDevice, CaptureSettings, CaptureThread, MediaKind};
+use msg::constellation_msg::PipelineId;
+use script_thread::Runnable;
+use script_traits::ScriptMsg as ConstellationMsg;
+use std::cell::Cell;
+use std::rc::Rc;
+use task_source::TaskSource;
+use util::thread::spawn_named;
+
+// https://w3c.github.io/mediacapture-main/#mediadevices
+#[dom_struct]
+pub struct MediaDevices {
+    eventtarget: EventTarget,
+    /// Whether the user granted a `getUserMedia()` call of this document,
+    /// after which the devices are no longer hidden from it.
+    permission_granted: Cell<bool>,
+}
+
+/// A device of a given kind that `getUserMedia()` has to open.
+struct TrackRequest {
+    kind: MediaKind,
+    device_id: Option<String>,
+    settings: CaptureSettings,
+}
+
+impl MediaDevices {
+    fn new_inherited() -> MediaDevices {
+        MediaDevices {
+            eventtarget: EventTarget::new_inherited(),
+            permission_granted: Cell::new(false),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<MediaDevices> {
+        reflect_dom_object(box MediaDevices::new_inherited(),
+                           global,
+                           MediaDevicesBinding::Wrap)
+    }
+
+    /// Resolves an `enumerateDevices()` call, once the devices are known.
+    /// https://w3c.github.io/mediacapture-main/#dfn-create-a-list-of-device-info-objects
+    fn finish_enumerate_devices(&self, promise: &Promise, devices: Vec<CaptureDevice>) {
+        let global = self.global();
+        let granted = self.permission_granted.get();
+        let mut has_camera = false;
+        let mut has_microphone = false;
+        let infos: Vec<_> = devices.into_iter().filter_map(|device| {
+            let kind = match device.kind {
+                MediaKind::Audio => MediaDeviceKind::Audioinput,
+                MediaKind::Video => MediaDeviceKind::Videoinput,
+            };
+            if granted {
+                return Some(MediaDeviceInfo::new(global.r(),
+                                                 DOMString::from(device.id),
+                                                 kind,
+                                                 DOMString::from(device.label),
+                                                 DOMString::new()));
+            }
+            // Without permission, only whether there is a device of each
+            // kind is exposed.
+            let seen = match device.kind {
+                MediaKind::Audio => &mut has_microphone,
+                MediaKind::Video => &mut has_camera,
+            };
+            if *seen {
+                return None;
+            }
+            *seen = true;
+            Some(MediaDeviceInfo::new(global.r(), DOMString::new(), kind, DOMString::new(), DOMString::new()))
+        }).collect();
+        promise.resolve_native(global.r().get_cx(), &infos);
+    }
+
+    fn finish_get_user_media(&self, promise: &Promise, result: Result<Vec<CaptureThread>, Error>) {
+        let global = self.global();
+        let cx = global.r().get_cx();
+        match result {
+            Ok(sources) => {
+                self.permission_granted.set(true);
+                let tracks: Vec<_> = sources.into_iter()
+                                            .map(|source| MediaStreamTrack::new(global.r(), source))
+                                            .collect();
+                promise.resolve_native(cx, &MediaStream::new(global.r(), &tracks));
+            },
+            Err(error) => promise.reject_error(cx, error),
+        }
+    }
+}
+
+/// Converts a member of `MediaStreamConstraints`, which is a boolean or a
+/// `MediaTrackConstraints`, to the device it requests if any.
+#[allow(unsafe_code)]
+unsafe fn track_request(cx: *mut JSContext, kind: MediaKind, value: HandleValue) -> Fallible<Option<TrackRequest>> {
+    let constraints = if value.get().is_object() {
+        match MediaTrackConstraints::new(cx, value) {
+            Ok(ConversionResult::Success(constraints)) => constraints,
+            Ok(ConversionResult::Failure(error)) => return Err(Error::Type(error.into_owned())),
+            Err(()) => return Err(Error::JSFailed),
+        }
+    } else if ToBoolean(value) {
+        MediaTrackConstraints::empty(cx)
+    } else {
+        return Ok(None);
+    };
+
+    // The constraints are all ideal values, which the device settles for as
+    // best as it can.
+    let default = CaptureSettings::default();
+    Ok(Some(TrackRequest {
+        kind: kind,
+        device_id: constraints.deviceId.map(String::from),
+        settings: CaptureSettings {
+            width: constraints.width.unwrap_or(default.width),
+            height: constraints.height.unwrap_or(default.height),
+            frame_rate: constraints.frameRate.map_or(default.frame_rate, |rate| *rate),
+            sample_rate: constraints.sampleRate.map_or(default.sample_rate, |rate| rate as f32),
+            channel_count: constraints.channelCount.unwrap_or(default.channel_count),
+        },
+    }))
+}
+
+/// The steps of `getUserMedia()` that block on the user and the devices,
+/// and therefore run off the script thread.
+/// https://w3c.github.io/mediacapture-main/#dom-mediadevices-getusermedia
+fn open_devices(requests: Vec<TrackRequest>,
+                pipeline_id: PipelineId,
+                constellation_chan: IpcSender<ConstellationMsg>)
+                -> Result<Vec<CaptureThread>, Error> {
+    // Not having a device of a requested kind rejects with a NotFoundError.
+    let devices = media::enumerate_devices();
+    let mut selected = vec![];
+    for request in &requests {
+        let preferred = request.device_id.as_ref().and_then(|id| {
+            devices.iter().find(|device| device.kind == request.kind && device.id == *id)
+        });
+        let device = preferred.or_else(|| devices.iter().find(|device| device.kind == request.kind));
+        match device {
+            Some(device) => selected.push((device.clone(), request.settings)),
+            None => return Err(Error::NotFound),
+        }
+    }
+
+    // Ask the user for permission, through the embedder.
+    let audio = requests.iter().any(|request| request.kind == MediaKind::Audio);
+    let video = requests.iter().any(|request| request.kind == MediaKind::Video);
+    let (sender, receiver) = ipc::channel().unwrap();
+    constellation_chan.send(ConstellationMsg::RequestMediaPermission(pipeline_id, audio, video, sender)).unwrap();
+    if !receiver.recv().unwrap_or(false) {
+        return Err(Error::NotAllowed);
+    }
+
+    // A device that can't be opened rejects with a NotReadableError.
+    selected.into_iter().map(|(device, settings)| {
+        CaptureThread::start(device, &settings).map_err(|()| Error::NotReadable)
+    }).collect()
+}
+
+impl MediaDevicesMethods for MediaDevices {
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/mediacapture-main/#dom-mediadevices-enumeratedevices
+    fn EnumerateDevices(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let mut runnable = box EnumerateDevicesRunnable {
+            devices: Trusted::new(self),
+            promise: TrustedPromise::new(promise.clone()),
+            result: vec![],
+        };
+        spawn_named("MediaDeviceEnumerator".to_owned(), move || {
+            runnable.result = media::enumerate_devices();
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        });
+        promise
+    }
+
+    #[allow(unrooted_must_root, unsafe_code)]
+    // https://w3c.github.io/mediacapture-main/#dom-mediadevices-getusermedia
+    fn GetUserMedia(&self, cx: *mut JSContext, constraints: &MediaStreamConstraints) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+
+        rooted!(in(cx) let audio = constraints.audio);
+        rooted!(in(cx) let video = constraints.video);
+        let requests = unsafe {
+            (track_request(cx, MediaKind::Audio, audio.handle()),
+             track_request(cx, MediaKind::Video, video.handle()))
+        };
+        let requests = match requests {
+            (Ok(audio), Ok(video)) => audio.into_iter().chain(video).collect::<Vec<_>>(),
+            (Err(error), _) | (_, Err(error)) => {
+                promise.reject_error(cx, error);
+                return promise;
+            },
+        };
+        if requests.is_empty() {
+            promise.reject_error(cx, Error::Type("No media type is requested".to_owned()));
+            return promise;
+        }
+
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let pipeline_id = window.pipeline_id();
+        let constellation_chan = window.constellation_chan().clone();
+        let mut runnable = box GetUserMediaRunnable {
+            devices: Trusted::new(self),
+            promise: TrustedPromise::new(promise.clone()),
+            result: Err(Error::Abort),
+        };
+        spawn_named("GetUserMedia".to_owned(), move || {
+            runnable.result = open_devices(requests, pipeline_id, constellation_chan);
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        });
+        promise
+    }
+}
+
+/// Delivers the devices found for an `enumerateDevices()` call.
+struct EnumerateDevicesRunnable {
+    devices: Trusted<MediaDevices>,
+    promise: TrustedPromise,
+    result: Vec<CaptureDevice>,
+}
+
+impl Runnable for EnumerateDevicesRunnable {
+    fn name(&self) -> &'static str { "EnumerateDevicesRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let devices = this.devices.root();
+        let promise = this.promise.root();
+        let global = devices.global();
+        let _ac = JSAutoCompartment::new(global.r().get_cx(), promise.reflector().get_jsobject().get());
+        devices.finish_enumerate_devices(&promise, this.result);
+    }
+}
+
+/// Delivers the devices opened for a `getUserMedia()` call.
+struct GetUserMediaRunnable {
+    devices: Trusted<MediaDevices>,
+    promise: TrustedPromise,
+    result: Result<Vec<CaptureThread>, Error>,
+}
+
+impl Runnable for GetUserMediaRunnable {
+    fn name(&self) -> &'static str { "GetUserMediaRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let devices = this.devices.root();
+        let promise = this.promise.root();
+        let global = devices.global();
+        let _ac = JSAutoCompartment::new(global.r().get_cx(), promise.reflector().get_jsobject().get());
+        devices.finish_get_user_media(&promise, this.result);
+    }
+}
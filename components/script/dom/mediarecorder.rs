@@ -0,0 +1,336 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::MediaRecorderBinding;
+use dom::bindings::codegen::Bindings::MediaRecorderBinding::{MediaRecorderMethods, MediaRecorderOptions};
+use dom::bindings::codegen::Bindings::MediaRecorderBinding::RecordingState;
+use dom::bindings::codegen::Bindings::MediaStreamBinding::MediaStreamMethods;
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::refcounted::Trusted;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::blob::{Blob, BlobImpl};
+use dom::blobevent::BlobEvent;
+use dom::event::{Event, EventBubbles, EventCancelable};
+use dom::eventtarget::EventTarget;
+use dom::mediastream::MediaStream;
+use dom::mediastreamtrack::MediaStreamTrack;
+use media::{MediaKind, SinkId};
+use media::recorder::{RecorderEvent, RecorderMsg, RecorderOptions, RecorderThread, RecordingFormat};
+use script_thread::Runnable;
+use std::cell::Cell;
+use std::time::Duration;
+use string_cache::Atom;
+use task_source::TaskSource;
+
+const DEFAULT_AUDIO_BITS_PER_SECOND: u32 = 128000;
+const DEFAULT_VIDEO_BITS_PER_SECOND: u32 = 2500000;
+/// The shortest timeslice that data is delivered at, in milliseconds.
+const MIN_TIMESLICE: i32 = 10;
+
+// https://w3c.github.io/mediacapture-record/MediaRecorder.html#mediarecorder-api
+#[dom_struct]
+pub struct MediaRecorder {
+    eventtarget: EventTarget,
+    stream: JS<MediaStream>,
+    /// The format given to the constructor, if any.
+    requested_format: Option<RecordingFormat>,
+    mime_type: DOMRefCell<DOMString>,
+    state: Cell<RecordingState>,
+    audio_bits_per_second: u32,
+    video_bits_per_second: u32,
+    #[ignore_heap_size_of = "Defined in the media module"]
+    recorder: DOMRefCell<Option<RecorderThread>>,
+    /// The tracks being recorded, with the sinks added to them.
+    recorded_tracks: DOMRefCell<Vec<(JS<MediaStreamTrack>, SinkId)>>,
+    /// Counts the recordings, so that the notifications of one that stopped
+    /// don't affect the next.
+    generation: Cell<u32>,
+}
+
+impl MediaRecorder {
+    fn new_inherited(stream: &MediaStream,
+                     requested_format: Option<RecordingFormat>,
+                     audio_bits_per_second: u32,
+                     video_bits_per_second: u32)
+                     -> MediaRecorder {
+        MediaRecorder {
+            eventtarget: EventTarget::new_inherited(),
+            stream: JS::from_ref(stream),
+            requested_format: requested_format,
+            mime_type: DOMRefCell::new(DOMString::from(requested_format.map_or(String::new(),
+                                                                               |format| format.mime_type()))),
+            state: Cell::new(RecordingState::Inactive),
+            audio_bits_per_second: audio_bits_per_second,
+            video_bits_per_second: video_bits_per_second,
+            recorder: DOMRefCell::new(None),
+            recorded_tracks: DOMRefCell::new(vec![]),
+            generation: Cell::new(0),
+        }
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-mediarecorder
+    pub fn Constructor(global: GlobalRef,
+                       stream: &MediaStream,
+                       options: &MediaRecorderOptions)
+                       -> Fallible<Root<MediaRecorder>> {
+        let requested_format = match options.mimeType {
+            Some(ref mime_type) if !mime_type.is_empty() => {
+                match RecordingFormat::from_mime_type(mime_type) {
+                    Some(format) => Some(format),
+                    None => return Err(Error::NotSupported),
+                }
+            },
+            _ => None,
+        };
+        let recorder = box MediaRecorder::new_inherited(stream,
+                                                        requested_format,
+                                                        options.audioBitsPerSecond
+                                                               .unwrap_or(DEFAULT_AUDIO_BITS_PER_SECOND),
+                                                        options.videoBitsPerSecond
+                                                               .unwrap_or(DEFAULT_VIDEO_BITS_PER_SECOND));
+        Ok(reflect_dom_object(recorder, global, MediaRecorderBinding::Wrap))
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-istypesupported
+    pub fn IsTypeSupported(_: GlobalRef, type_: DOMString) -> bool {
+        RecordingFormat::from_mime_type(&type_).is_some()
+    }
+
+    /// Stops gathering data, after which the data that is left is delivered
+    /// and `stop` is fired.
+    fn stop_recording(&self) {
+        self.state.set(RecordingState::Inactive);
+        for (track, sink) in self.recorded_tracks.borrow_mut().drain(..) {
+            track.remove_sink(sink);
+        }
+        // Dropping the thread makes it send the last data, and end.
+        self.recorder.borrow_mut().take();
+    }
+
+    fn queue_simple_event(&self, name: &'static str) {
+        let global = self.global();
+        let window = global.r().as_window();
+        window.dom_manipulation_task_source().queue_simple_event(self.upcast(), Atom::from(name), window);
+    }
+
+    /// https://w3c.github.io/mediacapture-record/MediaRecorder.html#dfn-fire-a-blob-event
+    fn fire_data_available(&self, data: Vec<u8>, timecode: f64) {
+        let global = self.global();
+        let blob = Blob::new(global.r(), BlobImpl::new_from_bytes(data), self.mime_type.borrow().to_string());
+        let event = BlobEvent::new(global.r(),
+                                   Atom::from("dataavailable"),
+                                   EventBubbles::DoesNotBubble,
+                                   EventCancelable::NotCancelable,
+                                   &blob,
+                                   timecode);
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    fn handle_recorder_event(&self, generation: u32, event: RecorderEvent) {
+        match event {
+            RecorderEvent::Data(data, timecode) => self.fire_data_available(data, timecode),
+            RecorderEvent::Stopped(data, timecode) => {
+                self.fire_data_available(data, timecode);
+                self.upcast::<EventTarget>().fire_simple_event("stop");
+            },
+            RecorderEvent::TracksEnded => {
+                // The recording stops once all its tracks ended.
+                if generation == self.generation.get() && self.state.get() != RecordingState::Inactive {
+                    self.stop_recording();
+                }
+            },
+        }
+    }
+}
+
+impl MediaRecorderMethods for MediaRecorder {
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-stream
+    fn Stream(&self) -> Root<MediaStream> {
+        Root::from_ref(&*self.stream)
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-mimetype
+    fn MimeType(&self) -> DOMString {
+        self.mime_type.borrow().clone()
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-state
+    fn State(&self) -> RecordingState {
+        self.state.get()
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-onstart
+    event_handler!(start, GetOnstart, SetOnstart);
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-onstop
+    event_handler!(stop, GetOnstop, SetOnstop);
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-ondataavailable
+    event_handler!(dataavailable, GetOndataavailable, SetOndataavailable);
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-onpause
+    event_handler!(pause, GetOnpause, SetOnpause);
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-onresume
+    event_handler!(resume, GetOnresume, SetOnresume);
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-videobitspersecond
+    fn VideoBitsPerSecond(&self) -> u32 {
+        self.video_bits_per_second
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-audiobitspersecond
+    fn AudioBitsPerSecond(&self) -> u32 {
+        self.audio_bits_per_second
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-start
+    fn Start(&self, timeslice: Option<i32>) -> ErrorResult {
+        if self.state.get() != RecordingState::Inactive {
+            return Err(Error::InvalidState);
+        }
+        if !self.stream.Active() {
+            return Err(Error::NotSupported);
+        }
+
+        // The first live track of each kind that the format can hold is
+        // recorded.
+        let has_video = !self.stream.GetVideoTracks().is_empty();
+        let format = self.requested_format.unwrap_or(RecordingFormat { audio: true, video: has_video });
+        let live_track = |tracks: Vec<Root<MediaStreamTrack>>| tracks.into_iter().find(|track| !track.is_ended());
+        let mut tracks = vec![];
+        if format.audio {
+            tracks.extend(live_track(self.stream.GetAudioTracks()));
+        }
+        if format.video {
+            tracks.extend(live_track(self.stream.GetVideoTracks()));
+        }
+        if tracks.is_empty() {
+            return Err(Error::NotSupported);
+        }
+
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        let global = self.global();
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let trusted = Trusted::new(self);
+        let event_handler = move |event: RecorderEvent| {
+            let runnable = box RecorderEventRunnable {
+                recorder: trusted.clone(),
+                generation: generation,
+                event: event,
+            };
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        };
+        let options = RecorderOptions {
+            audio_bit_rate: self.audio_bits_per_second,
+            video_bit_rate: self.video_bits_per_second,
+            timeslice: timeslice.map(|timeslice| Duration::from_millis(timeslice.max(MIN_TIMESLICE) as u64)),
+        };
+        let descriptions: Vec<_> = tracks.iter().map(|track| (track.kind(), *track.settings())).collect();
+        let recorder = match RecorderThread::start(&descriptions, &options, box event_handler) {
+            Ok(recorder) => recorder,
+            Err(()) => return Err(Error::NotSupported),
+        };
+
+        if self.requested_format.is_none() {
+            let format = RecordingFormat {
+                audio: tracks.iter().any(|track| track.kind() == MediaKind::Audio),
+                video: tracks.iter().any(|track| track.kind() == MediaKind::Video),
+            };
+            *self.mime_type.borrow_mut() = DOMString::from(format.mime_type());
+        }
+        {
+            let mut recorded_tracks = self.recorded_tracks.borrow_mut();
+            for (index, track) in tracks.iter().enumerate() {
+                // A sink that can't be added is dropped, which tells the
+                // recorder that its track ended.
+                if let Some(sink) = track.add_sink(recorder.sink(index)) {
+                    recorded_tracks.push((JS::from_ref(&**track), sink));
+                }
+            }
+        }
+        *self.recorder.borrow_mut() = Some(recorder);
+        self.state.set(RecordingState::Recording);
+        self.queue_simple_event("start");
+        Ok(())
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-stop
+    fn Stop(&self) -> ErrorResult {
+        if self.state.get() == RecordingState::Inactive {
+            return Err(Error::InvalidState);
+        }
+        self.stop_recording();
+        Ok(())
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-pause
+    fn Pause(&self) -> ErrorResult {
+        match self.state.get() {
+            RecordingState::Inactive => return Err(Error::InvalidState),
+            RecordingState::Paused => return Ok(()),
+            RecordingState::Recording => {},
+        }
+        self.state.set(RecordingState::Paused);
+        if let Some(ref recorder) = *self.recorder.borrow() {
+            recorder.send(RecorderMsg::Pause);
+        }
+        self.queue_simple_event("pause");
+        Ok(())
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-resume
+    fn Resume(&self) -> ErrorResult {
+        match self.state.get() {
+            RecordingState::Inactive => return Err(Error::InvalidState),
+            RecordingState::Recording => return Ok(()),
+            RecordingState::Paused => {},
+        }
+        self.state.set(RecordingState::Recording);
+        if let Some(ref recorder) = *self.recorder.borrow() {
+            recorder.send(RecorderMsg::Resume);
+        }
+        self.queue_simple_event("resume");
+        Ok(())
+    }
+
+    // https://w3c.github.io/mediacapture-record/MediaRecorder.html#dom-mediarecorder-requestdata
+    fn RequestData(&self) -> ErrorResult {
+        if self.state.get() == RecordingState::Inactive {
+            return Err(Error::InvalidState);
+        }
+        if let Some(ref recorder) = *self.recorder.borrow() {
+            recorder.send(RecorderMsg::RequestData);
+        }
+        Ok(())
+    }
+}
+
+/// Delivers a notification of the encoder thread of a recorder.
+struct RecorderEventRunnable {
+    recorder: Trusted<MediaRecorder>,
+    generation: u32,
+    event: RecorderEvent,
+}
+
+impl Runnable for RecorderEventRunnable {
+    fn name(&self) -> &'static str { "RecorderEventRunnable" }
+
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        this.recorder.root().handle_recorder_event(this.generation, this.event);
+    }
+}
@@ -0,0 +1,320 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::MediaSourceBinding::{self, EndOfStreamError, MediaSourceMethods};
+use dom::bindings::codegen::Bindings::MediaSourceBinding::ReadyState;
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::eventtarget::EventTarget;
+use dom::htmlmediaelement::HTMLMediaElement;
+use dom::sourcebuffer::SourceBuffer;
+use dom::sourcebufferlist::SourceBufferList;
+use dom::window::Window;
+use media::player::can_append_type;
+use media::source::{SharedSourceData, SourceData, TimeRange};
+use std::cell::Cell;
+use std::f64;
+use std::sync::{Arc, Mutex, MutexGuard};
+use string_cache::Atom;
+
+// https://w3c.github.io/media-source/#mediasource
+#[dom_struct]
+pub struct MediaSource {
+    eventtarget: EventTarget,
+    source_buffers: JS<SourceBufferList>,
+    active_source_buffers: JS<SourceBufferList>,
+    ready_state: Cell<ReadyState>,
+    duration: Cell<f64>,
+    /// The element the source is attached to, if any.
+    media_element: MutNullableHeap<JS<HTMLMediaElement>>,
+    /// The frames appended to the source buffers, which the player of the
+    /// media element reads.
+    #[ignore_heap_size_of = "Defined in the media module"]
+    data: SharedSourceData,
+}
+
+impl MediaSource {
+    fn new_inherited(window: &Window) -> MediaSource {
+        MediaSource {
+            eventtarget: EventTarget::new_inherited(),
+            source_buffers: JS::from_ref(&*SourceBufferList::new(window)),
+            active_source_buffers: JS::from_ref(&*SourceBufferList::new(window)),
+            ready_state: Cell::new(ReadyState::Closed),
+            duration: Cell::new(f64::NAN),
+            media_element: Default::default(),
+            data: Arc::new(Mutex::new(SourceData::new())),
+        }
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-constructor
+    pub fn Constructor(global: GlobalRef) -> Fallible<Root<MediaSource>> {
+        let window = global.as_window();
+        Ok(reflect_dom_object(box MediaSource::new_inherited(window), global, MediaSourceBinding::Wrap))
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-istypesupported
+    pub fn IsTypeSupported(_: GlobalRef, type_: DOMString) -> bool {
+        can_append_type(&type_)
+    }
+
+    pub fn data(&self) -> MutexGuard<SourceData> {
+        self.data.lock().unwrap()
+    }
+
+    /// The data of the source, for a player to read.
+    pub fn shared_data(&self) -> SharedSourceData {
+        self.data.clone()
+    }
+
+    pub fn media_element(&self) -> Option<Root<HTMLMediaElement>> {
+        self.media_element.get()
+    }
+
+    /// Whether all source buffers received an initialization segment, after
+    /// which the element knows the tracks it plays.
+    pub fn received_init_segments(&self) -> bool {
+        let buffers = self.source_buffers.buffers();
+        !buffers.is_empty() && buffers.iter().all(|buffer| buffer.received_init_segment())
+    }
+
+    /// The time ranges that all source buffers have frames for.
+    /// https://w3c.github.io/media-source/#htmlmediaelement-extensions-buffered
+    pub fn buffered(&self) -> Vec<TimeRange> {
+        if self.active_source_buffers.Length() == 0 {
+            return vec![];
+        }
+        self.data().all_buffered()
+    }
+
+    fn queue_simple_event(&self, target: &EventTarget, name: &'static str) {
+        let global = self.global();
+        let window = global.r().as_window();
+        window.dom_manipulation_task_source().queue_simple_event(target, Atom::from(name), window);
+    }
+
+    /// Attaches the source to a media element that loads it, unless it is
+    /// attached to one already.
+    /// https://w3c.github.io/media-source/#mediasource-attach
+    pub fn attach(&self, element: &HTMLMediaElement) -> bool {
+        if self.ready_state.get() != ReadyState::Closed {
+            return false;
+        }
+        self.media_element.set(Some(element));
+        self.ready_state.set(ReadyState::Open);
+        self.queue_simple_event(self.upcast(), "sourceopen");
+        true
+    }
+
+    // https://w3c.github.io/media-source/#mediasource-detach
+    pub fn detach(&self) {
+        // Steps 2 and 3
+        self.ready_state.set(ReadyState::Closed);
+        self.duration.set(f64::NAN);
+        // Steps 4 and 5
+        for buffer in self.active_source_buffers.buffers() {
+            self.active_source_buffers.remove(&buffer);
+            self.queue_simple_event(self.active_source_buffers.upcast(), "removesourcebuffer");
+        }
+        for buffer in self.source_buffers.buffers() {
+            self.source_buffers.remove(&buffer);
+            buffer.detach();
+            self.queue_simple_event(self.source_buffers.upcast(), "removesourcebuffer");
+        }
+        *self.data() = SourceData::new();
+        self.media_element.set(None);
+        // Step 6
+        self.queue_simple_event(self.upcast(), "sourceclose");
+    }
+
+    /// Opens a source again once something is appended after its end.
+    /// https://w3c.github.io/media-source/#sourcebuffer-prepare-append (step 4)
+    pub fn reopen_if_ended(&self) {
+        if self.ready_state.get() == ReadyState::Ended {
+            self.ready_state.set(ReadyState::Open);
+            self.data().ended = false;
+            self.queue_simple_event(self.upcast(), "sourceopen");
+        }
+    }
+
+    /// Adds a source buffer that received its first initialization segment
+    /// to the active ones.
+    pub fn activate_source_buffer(&self, buffer: &SourceBuffer) {
+        if !self.active_source_buffers.contains(buffer) {
+            self.active_source_buffers.push(buffer);
+            self.queue_simple_event(self.active_source_buffers.upcast(), "addsourcebuffer");
+        }
+    }
+
+    /// Tells the element what the source buffers hold now.
+    pub fn buffers_changed(&self) {
+        if let Some(element) = self.media_element.get() {
+            element.media_source_changed();
+        }
+    }
+
+    // https://w3c.github.io/media-source/#duration-change-algorithm
+    pub fn change_duration(&self, duration: f64) {
+        // Step 1
+        if self.duration.get() == duration {
+            return;
+        }
+        // Steps 4 and 5
+        self.duration.set(duration);
+        // Step 6
+        if let Some(element) = self.media_element.get() {
+            element.set_duration(duration);
+        }
+    }
+
+    // https://w3c.github.io/media-source/#end-of-stream-algorithm
+    pub fn end_of_stream(&self, error: Option<EndOfStreamError>) {
+        // Steps 1 and 2
+        self.ready_state.set(ReadyState::Ended);
+        self.queue_simple_event(self.upcast(), "sourceended");
+        // Step 3
+        let element = self.media_element.get();
+        match error {
+            None => {
+                let highest_end = self.source_buffers.buffers().iter().filter_map(|buffer| {
+                    self.data().buffered(buffer.index()).last().map(|&(_, end)| end)
+                }).fold(0., f64::max);
+                self.change_duration(highest_end);
+                self.data().ended = true;
+                self.buffers_changed();
+            },
+            Some(EndOfStreamError::Network) => {
+                if let Some(element) = element {
+                    element.media_source_failed(false);
+                }
+            },
+            Some(EndOfStreamError::Decode) => {
+                if let Some(element) = element {
+                    element.media_source_failed(true);
+                }
+            },
+        }
+    }
+
+    fn is_updating(&self) -> bool {
+        self.source_buffers.buffers().iter().any(|buffer| buffer.is_updating())
+    }
+}
+
+impl MediaSourceMethods for MediaSource {
+    // https://w3c.github.io/media-source/#dom-mediasource-sourcebuffers
+    fn SourceBuffers(&self) -> Root<SourceBufferList> {
+        Root::from_ref(&*self.source_buffers)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-activesourcebuffers
+    fn ActiveSourceBuffers(&self) -> Root<SourceBufferList> {
+        Root::from_ref(&*self.active_source_buffers)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-readystate
+    fn ReadyState(&self) -> ReadyState {
+        self.ready_state.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-duration
+    fn Duration(&self) -> f64 {
+        if self.ready_state.get() == ReadyState::Closed {
+            return f64::NAN;
+        }
+        self.duration.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-duration
+    fn SetDuration(&self, value: f64) -> ErrorResult {
+        // Step 1
+        if value < 0. || value.is_nan() {
+            return Err(Error::Type("The duration can't be negative".to_owned()));
+        }
+        // Steps 2 and 3
+        if self.ready_state.get() != ReadyState::Open || self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+        // https://w3c.github.io/media-source/#duration-change-algorithm step 2
+        if value < self.data().highest_time() {
+            return Err(Error::InvalidState);
+        }
+        // Step 4
+        self.change_duration(value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceopen
+    event_handler!(sourceopen, GetOnsourceopen, SetOnsourceopen);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceended
+    event_handler!(sourceended, GetOnsourceended, SetOnsourceended);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-onsourceclose
+    event_handler!(sourceclose, GetOnsourceclose, SetOnsourceclose);
+
+    // https://w3c.github.io/media-source/#dom-mediasource-addsourcebuffer
+    fn AddSourceBuffer(&self, type_: DOMString) -> Fallible<Root<SourceBuffer>> {
+        // Step 1
+        if type_.is_empty() {
+            return Err(Error::Type("The type is empty".to_owned()));
+        }
+        // Step 2
+        if !can_append_type(&type_) {
+            return Err(Error::NotSupported);
+        }
+        // TODO step 3 (quota)
+        // Step 4
+        if self.ready_state.get() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+        // Step 5
+        let index = self.data().add_buffer();
+        let global = self.global();
+        let buffer = SourceBuffer::new(global.r().as_window(), self, index);
+        // Step 8
+        self.source_buffers.push(&buffer);
+        self.queue_simple_event(self.source_buffers.upcast(), "addsourcebuffer");
+        // Step 9
+        Ok(buffer)
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-removesourcebuffer
+    fn RemoveSourceBuffer(&self, buffer: &SourceBuffer) -> ErrorResult {
+        // Step 1
+        if !self.source_buffers.contains(buffer) {
+            return Err(Error::NotFound);
+        }
+        // Step 2
+        buffer.abort_update();
+        // TODO steps 3 to 6 (track lists)
+        // Step 7
+        if self.active_source_buffers.remove(buffer) {
+            self.queue_simple_event(self.active_source_buffers.upcast(), "removesourcebuffer");
+        }
+        // Step 8
+        self.source_buffers.remove(buffer);
+        self.queue_simple_event(self.source_buffers.upcast(), "removesourcebuffer");
+        // Step 9
+        self.data().remove_buffer(buffer.index());
+        buffer.detach();
+        self.buffers_changed();
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-mediasource-endofstream
+    fn EndOfStream(&self, error: Option<EndOfStreamError>) -> ErrorResult {
+        // Steps 1 and 2
+        if self.ready_state.get() != ReadyState::Open || self.is_updating() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3
+        self.end_of_stream(error);
+        Ok(())
+    }
+}
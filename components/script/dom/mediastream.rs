@@ -0,0 +1,128 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::MediaStreamBinding;
+use dom::bindings::codegen::Bindings::MediaStreamBinding::MediaStreamMethods;
+use dom::bindings::codegen::Bindings::MediaStreamTrackBinding::MediaStreamTrackMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::eventtarget::EventTarget;
+use dom::mediastreamtrack::MediaStreamTrack;
+use media::MediaKind;
+use uuid::Uuid;
+
+// https://w3c.github.io/mediacapture-main/#mediastream
+#[dom_struct]
+pub struct MediaStream {
+    eventtarget: EventTarget,
+    id: DOMString,
+    /// The track set of the stream, in the order the tracks were added.
+    tracks: DOMRefCell<Vec<JS<MediaStreamTrack>>>,
+}
+
+impl MediaStream {
+    fn new_inherited() -> MediaStream {
+        MediaStream {
+            eventtarget: EventTarget::new_inherited(),
+            id: DOMString::from(Uuid::new_v4().to_string()),
+            tracks: DOMRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: GlobalRef, tracks: &[Root<MediaStreamTrack>]) -> Root<MediaStream> {
+        let stream = reflect_dom_object(box MediaStream::new_inherited(),
+                                        global,
+                                        MediaStreamBinding::Wrap);
+        for track in tracks {
+            stream.add_track(track);
+        }
+        stream
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-constructor
+    pub fn Constructor(global: GlobalRef) -> Fallible<Root<MediaStream>> {
+        Ok(MediaStream::new(global, &[]))
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-constructor
+    pub fn Constructor_(global: GlobalRef, stream: &MediaStream) -> Fallible<Root<MediaStream>> {
+        Ok(MediaStream::new(global, &stream.GetTracks()))
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-constructor
+    pub fn Constructor__(global: GlobalRef, tracks: Vec<Root<MediaStreamTrack>>) -> Fallible<Root<MediaStream>> {
+        Ok(MediaStream::new(global, &tracks))
+    }
+
+    fn add_track(&self, track: &MediaStreamTrack) {
+        let mut tracks = self.tracks.borrow_mut();
+        if !tracks.iter().any(|t| &**t as *const MediaStreamTrack == track as *const MediaStreamTrack) {
+            tracks.push(JS::from_ref(track));
+        }
+    }
+
+    fn tracks_of_kind(&self, kind: MediaKind) -> Vec<Root<MediaStreamTrack>> {
+        self.tracks.borrow().iter()
+            .filter(|track| track.kind() == kind)
+            .map(|track| Root::from_ref(&**track))
+            .collect()
+    }
+
+    /// The first video track of the stream, which media elements render.
+    pub fn first_video_track(&self) -> Option<Root<MediaStreamTrack>> {
+        self.tracks_of_kind(MediaKind::Video).into_iter().next()
+    }
+}
+
+impl MediaStreamMethods for MediaStream {
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-id
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-getaudiotracks
+    fn GetAudioTracks(&self) -> Vec<Root<MediaStreamTrack>> {
+        self.tracks_of_kind(MediaKind::Audio)
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-getvideotracks
+    fn GetVideoTracks(&self) -> Vec<Root<MediaStreamTrack>> {
+        self.tracks_of_kind(MediaKind::Video)
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-gettracks
+    fn GetTracks(&self) -> Vec<Root<MediaStreamTrack>> {
+        self.tracks.borrow().iter().map(|track| Root::from_ref(&**track)).collect()
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-gettrackbyid
+    fn GetTrackById(&self, track_id: DOMString) -> Option<Root<MediaStreamTrack>> {
+        self.tracks.borrow().iter().find(|track| track.Id() == track_id).map(|track| Root::from_ref(&**track))
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-addtrack
+    fn AddTrack(&self, track: &MediaStreamTrack) {
+        self.add_track(track);
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-removetrack
+    fn RemoveTrack(&self, track: &MediaStreamTrack) {
+        self.tracks.borrow_mut().retain(|t| &**t as *const MediaStreamTrack != track as *const MediaStreamTrack);
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-clone
+    fn Clone(&self) -> Root<MediaStream> {
+        let tracks: Vec<_> = self.tracks.borrow().iter().map(|track| track.Clone()).collect();
+        MediaStream::new(self.global().r(), &tracks)
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastream-active
+    fn Active(&self) -> bool {
+        self.tracks.borrow().iter().any(|track| !track.is_ended())
+    }
+}
@@ -0,0 +1,259 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::MediaStreamTrackBinding;
+use dom::bindings::codegen::Bindings::MediaStreamTrackBinding::{MediaStreamTrackMethods, MediaStreamTrackState};
+use dom::bindings::codegen::Bindings::MediaTrackConstraintsBinding::MediaTrackSettings;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::Root;
+use dom::bindings::num::Finite;
+use dom::bindings::refcounted::Trusted;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::eventtarget::EventTarget;
+use media::{CaptureDevice, CaptureMsg, CaptureSettings, CaptureThread, MediaData, MediaKind, SinkId};
+use script_thread::Runnable;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use task_source::TaskSource;
+use uuid::Uuid;
+
+// https://w3c.github.io/mediacapture-main/#mediastreamtrack
+#[dom_struct]
+pub struct MediaStreamTrack {
+    eventtarget: EventTarget,
+    id: DOMString,
+    device: CaptureDevice,
+    settings: CaptureSettings,
+    /// The capture thread of the device, shared with the clones of the track,
+    /// until the track ends.
+    #[ignore_heap_size_of = "Defined in the media module"]
+    source: DOMRefCell<Option<Rc<CaptureThread>>>,
+    /// The sinks added to the source for the consumers of this track.
+    sinks: DOMRefCell<Vec<SinkId>>,
+    /// Read by the sinks on the capture thread, which output black frames or
+    /// silence while the track is disabled.
+    #[ignore_heap_size_of = "Arc"]
+    enabled: Arc<AtomicBool>,
+    ended: Cell<bool>,
+}
+
+impl MediaStreamTrack {
+    fn new_inherited(source: Option<Rc<CaptureThread>>,
+                     device: CaptureDevice,
+                     settings: CaptureSettings,
+                     enabled: bool)
+                     -> MediaStreamTrack {
+        MediaStreamTrack {
+            eventtarget: EventTarget::new_inherited(),
+            id: DOMString::from(Uuid::new_v4().to_string()),
+            device: device,
+            settings: settings,
+            ended: Cell::new(source.is_none()),
+            source: DOMRefCell::new(source),
+            sinks: DOMRefCell::new(vec![]),
+            enabled: Arc::new(AtomicBool::new(enabled)),
+        }
+    }
+
+    /// Creates a track consuming the output of `source`, or an ended track if
+    /// there is none.
+    fn new_with_source(global: GlobalRef,
+                       source: Option<Rc<CaptureThread>>,
+                       device: CaptureDevice,
+                       settings: CaptureSettings,
+                       enabled: bool)
+                       -> Root<MediaStreamTrack> {
+        let track = reflect_dom_object(box MediaStreamTrack::new_inherited(source.clone(), device, settings, enabled),
+                                       global,
+                                       MediaStreamTrackBinding::Wrap);
+        if let Some(source) = source {
+            let window = global.as_window();
+            let task_source = window.dom_manipulation_task_source();
+            let wrapper = window.get_runnable_wrapper();
+            let trusted = Trusted::new(&*track);
+            let ended_handler = move || {
+                let runnable = box TrackEndedRunnable {
+                    track: trusted.clone(),
+                };
+                let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+            };
+            source.send(CaptureMsg::AddEndedHandler(box ended_handler));
+        }
+        track
+    }
+
+    pub fn new(global: GlobalRef, source: CaptureThread) -> Root<MediaStreamTrack> {
+        let device = source.device().clone();
+        let settings = *source.settings();
+        MediaStreamTrack::new_with_source(global, Some(Rc::new(source)), device, settings, true)
+    }
+
+    pub fn kind(&self) -> MediaKind {
+        self.device.kind
+    }
+
+    pub fn is_ended(&self) -> bool {
+        self.ended.get()
+    }
+
+    pub fn settings(&self) -> &CaptureSettings {
+        &self.settings
+    }
+
+    /// Makes `sink` consume the output of the track, on the capture thread,
+    /// until the track ends or the sink is removed. Returns `None` if the
+    /// track already ended.
+    pub fn add_sink(&self, sink: Box<FnMut(&MediaData) + Send>) -> Option<SinkId> {
+        let source = self.source.borrow();
+        let source = match *source {
+            Some(ref source) => source,
+            None => return None,
+        };
+        let enabled = self.enabled.clone();
+        let mut sink = sink;
+        let id = source.add_sink(box move |data: &MediaData| {
+            if enabled.load(Ordering::Relaxed) {
+                sink(data)
+            } else {
+                sink(&data.blanked())
+            }
+        });
+        self.sinks.borrow_mut().push(id);
+        Some(id)
+    }
+
+    pub fn remove_sink(&self, id: SinkId) {
+        self.sinks.borrow_mut().retain(|&sink_id| sink_id != id);
+        if let Some(ref source) = *self.source.borrow() {
+            source.send(CaptureMsg::RemoveSink(id));
+        }
+    }
+
+    /// Ends the track and detaches it from its source, which is stopped once
+    /// no other track uses it.
+    /// https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-stop
+    fn end(&self) {
+        self.ended.set(true);
+        if let Some(source) = self.source.borrow_mut().take() {
+            for id in self.sinks.borrow_mut().drain(..) {
+                source.send(CaptureMsg::RemoveSink(id));
+            }
+        }
+    }
+
+    /// https://w3c.github.io/mediacapture-main/#track-ended
+    fn device_ended(&self) {
+        // Step 1.
+        if self.ended.get() {
+            return;
+        }
+        // Step 2.
+        self.end();
+        // Step 4.
+        self.upcast::<EventTarget>().fire_simple_event("ended");
+    }
+}
+
+impl MediaStreamTrackMethods for MediaStreamTrack {
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-kind
+    fn Kind(&self) -> DOMString {
+        DOMString::from(match self.device.kind {
+            MediaKind::Audio => "audio",
+            MediaKind::Video => "video",
+        })
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-id
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-label
+    fn Label(&self) -> DOMString {
+        DOMString::from(self.device.label.clone())
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-enabled
+    fn Enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-enabled
+    fn SetEnabled(&self, value: bool) {
+        self.enabled.store(value, Ordering::Relaxed);
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-muted
+    fn Muted(&self) -> bool {
+        false
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-readystate
+    fn ReadyState(&self) -> MediaStreamTrackState {
+        if self.ended.get() {
+            MediaStreamTrackState::Ended
+        } else {
+            MediaStreamTrackState::Live
+        }
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-onended
+    event_handler!(ended, GetOnended, SetOnended);
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-clone
+    fn Clone(&self) -> Root<MediaStreamTrack> {
+        MediaStreamTrack::new_with_source(self.global().r(),
+                                          self.source.borrow().clone(),
+                                          self.device.clone(),
+                                          self.settings,
+                                          self.Enabled())
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-stop
+    fn Stop(&self) {
+        // Step 2.
+        if self.ended.get() {
+            return;
+        }
+        // Steps 3-4.
+        self.end();
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-mediastreamtrack-getsettings
+    fn GetSettings(&self) -> MediaTrackSettings {
+        let settings = &self.settings;
+        let (video, audio) = match self.device.kind {
+            MediaKind::Video => (true, false),
+            MediaKind::Audio => (false, true),
+        };
+        MediaTrackSettings {
+            width: if video { Some(settings.width as i32) } else { None },
+            height: if video { Some(settings.height as i32) } else { None },
+            frameRate: if video { Some(Finite::wrap(settings.frame_rate)) } else { None },
+            sampleRate: if audio { Some(settings.sample_rate as i32) } else { None },
+            channelCount: if audio { Some(settings.channel_count as i32) } else { None },
+            deviceId: Some(DOMString::from(self.device.id.clone())),
+            groupId: Some(DOMString::new()),
+        }
+    }
+}
+
+/// Ends a track whose device stopped on its own.
+struct TrackEndedRunnable {
+    track: Trusted<MediaStreamTrack>,
+}
+
+impl Runnable for TrackEndedRunnable {
+    fn name(&self) -> &'static str { "TrackEndedRunnable" }
+
+    fn handler(self: Box<Self>) {
+        self.track.root().device_ended();
+    }
+}
@@ -215,15 +215,28 @@ pub mod types {
 pub mod abstractworker;
 pub mod abstractworkerglobalscope;
 pub mod activation;
+pub mod analysernode;
+pub mod angleinstancedarrays;
+pub mod animationevent;
 pub mod attr;
+pub mod audiobuffer;
+pub mod audiobuffersourcenode;
+pub mod audiocontext;
+pub mod audiodestinationnode;
+pub mod audionode;
+pub mod audioparam;
+pub mod audioscheduledsourcenode;
 pub mod beforeunloadevent;
+pub mod biquadfilternode;
 mod create;
 #[allow(unsafe_code)]
 #[deny(missing_docs, non_snake_case)]
 pub mod bindings;
 pub mod blob;
+pub mod blobevent;
 pub mod bluetooth;
 pub mod bluetoothadvertisingdata;
+pub mod bluetoothadvertisingevent;
 pub mod bluetoothcharacteristicproperties;
 pub mod bluetoothdevice;
 pub mod bluetoothremotegattcharacteristic;
@@ -237,14 +250,17 @@ pub mod canvaspattern;
 pub mod canvasrenderingcontext2d;
 pub mod characterdata;
 pub mod client;
+pub mod clients;
 pub mod closeevent;
 pub mod comment;
 pub mod console;
 pub mod crypto;
+pub mod cryptokey;
 pub mod css;
 pub mod cssstyledeclaration;
 pub mod customevent;
 pub mod dedicatedworkerglobalscope;
+pub mod dissimilaroriginwindow;
 pub mod document;
 pub mod documentfragment;
 pub mod documenttype;
@@ -269,6 +285,7 @@ pub mod eventsource;
 pub mod eventtarget;
 pub mod extendableevent;
 pub mod extendablemessageevent;
+pub mod exttexturefilteranisotropic;
 pub mod file;
 pub mod filelist;
 pub mod filereader;
@@ -276,6 +293,7 @@ pub mod filereadersync;
 pub mod focusevent;
 pub mod forcetouchevent;
 pub mod formdata;
+pub mod gainnode;
 pub mod hashchangeevent;
 pub mod headers;
 pub mod history;
@@ -328,6 +346,7 @@ pub mod htmloptionscollection;
 pub mod htmloutputelement;
 pub mod htmlparagraphelement;
 pub mod htmlparamelement;
+pub mod htmlpictureelement;
 pub mod htmlpreelement;
 pub mod htmlprogresselement;
 pub mod htmlquoteelement;
@@ -352,10 +371,18 @@ pub mod htmltrackelement;
 pub mod htmlulistelement;
 pub mod htmlunknownelement;
 pub mod htmlvideoelement;
+pub mod imagebitmap;
 pub mod imagedata;
 pub mod keyboardevent;
+pub mod largestcontentfulpaint;
 pub mod location;
+pub mod mediadeviceinfo;
+pub mod mediadevices;
 pub mod mediaerror;
+pub mod mediarecorder;
+pub mod mediasource;
+pub mod mediastream;
+pub mod mediastreamtrack;
 pub mod messageevent;
 pub mod mimetype;
 pub mod mimetypearray;
@@ -366,8 +393,24 @@ pub mod navigatorinfo;
 pub mod node;
 pub mod nodeiterator;
 pub mod nodelist;
+pub mod oeselementindexuint;
+pub mod oestexturefloat;
+pub mod oesvertexarrayobject;
+pub mod offscreencanvas;
+pub mod oscillatornode;
 pub mod pagetransitionevent;
+pub mod path2d;
 pub mod performance;
+pub mod performanceentry;
+pub mod performanceeventtiming;
+pub mod performancemark;
+pub mod performancemeasure;
+pub mod performancenavigationtiming;
+pub mod performanceobserver;
+pub mod performanceobserverentrylist;
+pub mod performancepainttiming;
+pub mod performanceresourcetiming;
+pub mod performanceservertiming;
 pub mod performancetiming;
 pub mod plugin;
 pub mod pluginarray;
@@ -380,17 +423,28 @@ pub mod radionodelist;
 pub mod range;
 pub mod request;
 pub mod response;
+pub mod rtcdatachannel;
+pub mod rtcdatachannelevent;
+pub mod rtcicecandidate;
+pub mod rtcpeerconnection;
+pub mod rtcpeerconnectioniceevent;
+pub mod rtcsessiondescription;
 pub mod screen;
+pub mod selection;
 pub mod serviceworker;
 pub mod serviceworkercontainer;
 pub mod serviceworkerglobalscope;
 pub mod serviceworkerregistration;
 pub mod servohtmlparser;
 pub mod servoxmlparser;
+pub mod sourcebuffer;
+pub mod sourcebufferlist;
 pub mod storage;
 pub mod storageevent;
+pub mod storagemanager;
 pub mod stylesheet;
 pub mod stylesheetlist;
+pub mod subtlecrypto;
 pub mod testbinding;
 pub mod testbindingiterable;
 pub mod testbindingpairiterable;
@@ -398,6 +452,11 @@ pub mod testbindingproxy;
 pub mod text;
 pub mod textdecoder;
 pub mod textencoder;
+pub mod texttrack;
+pub mod texttrackcue;
+pub mod texttrackcuelist;
+pub mod texttracklist;
+pub mod timeranges;
 pub mod touch;
 pub mod touchevent;
 pub mod touchlist;
@@ -406,11 +465,18 @@ pub mod uievent;
 pub mod url;
 pub mod urlhelper;
 pub mod urlsearchparams;
+pub mod usb;
+pub mod usbdevice;
+pub mod usbintransferresult;
+pub mod usbouttransferresult;
 pub mod userscripts;
 pub mod validation;
 pub mod validitystate;
 pub mod values;
 pub mod virtualmethods;
+pub mod vttcue;
+pub mod webgl2renderingcontext;
+pub mod webgl_extensions;
 pub mod webgl_validations;
 pub mod webglactiveinfo;
 pub mod webglbuffer;
@@ -420,10 +486,13 @@ pub mod webglobject;
 pub mod webglprogram;
 pub mod webglrenderbuffer;
 pub mod webglrenderingcontext;
+pub mod webglsampler;
 pub mod webglshader;
 pub mod webglshaderprecisionformat;
 pub mod webgltexture;
+pub mod webgltransformfeedback;
 pub mod webgluniformlocation;
+pub mod webglvertexarrayobject;
 pub mod websocket;
 pub mod window;
 pub mod worker;
@@ -434,3 +503,19 @@ pub mod xmldocument;
 pub mod xmlhttprequest;
 pub mod xmlhttprequesteventtarget;
 pub mod xmlhttprequestupload;
+pub mod xmlserializer;
+pub mod xpathevaluator;
+pub mod xpathexpression;
+pub mod xpathresult;
+pub mod xrframe;
+pub mod xrpose;
+pub mod xrreferencespace;
+pub mod xrrenderstate;
+pub mod xrrigidtransform;
+pub mod xrsession;
+pub mod xrspace;
+pub mod xrsystem;
+pub mod xrview;
+pub mod xrviewerpose;
+pub mod xrviewport;
+pub mod xrwebgllayer;
@@ -2,18 +2,39 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use dom::bindings::codegen::Bindings::BlobBinding::BlobMethods;
 use dom::bindings::codegen::Bindings::NavigatorBinding;
-use dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
+use dom::bindings::codegen::Bindings::NavigatorBinding::{NavigatorMethods, ShareData};
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::error::Error;
 use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::refcounted::{Trusted, TrustedPromise};
 use dom::bindings::reflector::{Reflector, Reflectable, reflect_dom_object};
 use dom::bindings::str::DOMString;
+use dom::blob::Blob;
 use dom::bluetooth::Bluetooth;
+use dom::mediadevices::MediaDevices;
 use dom::mimetypearray::MimeTypeArray;
 use dom::navigatorinfo;
 use dom::pluginarray::PluginArray;
+use dom::promise::Promise;
 use dom::serviceworkercontainer::ServiceWorkerContainer;
+use dom::storagemanager::StorageManager;
+use dom::usb::USB;
 use dom::window::Window;
+use dom::xrsystem::XRSystem;
+use ipc_channel::ipc;
+use js::jsapi::JSAutoCompartment;
+use script_thread::Runnable;
+use script_traits::{ScriptMsg as ConstellationMsg, ShareError, SharedFile};
+use script_traits::ShareData as EmbedderShareData;
+use std::cell::Cell;
+use std::rc::Rc;
+use task_source::TaskSource;
+use url::Url;
+use util::thread::spawn_named;
 
 #[dom_struct]
 pub struct Navigator {
@@ -22,6 +43,12 @@ pub struct Navigator {
     plugins: MutNullableHeap<JS<PluginArray>>,
     mime_types: MutNullableHeap<JS<MimeTypeArray>>,
     service_worker: MutNullableHeap<JS<ServiceWorkerContainer>>,
+    xr: MutNullableHeap<JS<XRSystem>>,
+    media_devices: MutNullableHeap<JS<MediaDevices>>,
+    storage: MutNullableHeap<JS<StorageManager>>,
+    usb: MutNullableHeap<JS<USB>>,
+    /// Whether a `share()` call is waiting for the embedder.
+    share_in_progress: Cell<bool>,
 }
 
 impl Navigator {
@@ -32,6 +59,11 @@ impl Navigator {
             plugins: Default::default(),
             mime_types: Default::default(),
             service_worker: Default::default(),
+            xr: Default::default(),
+            media_devices: Default::default(),
+            storage: Default::default(),
+            usb: Default::default(),
+            share_in_progress: Cell::new(false),
         }
     }
 
@@ -42,6 +74,36 @@ impl Navigator {
     }
 }
 
+impl Navigator {
+    /// Checks that `data` can be shared, and resolves its URL against the base URL of
+    /// the document.
+    /// https://w3c.github.io/web-share/#dfn-validate-share-data
+    fn validate_share_data(&self, data: &ShareData) -> Result<Option<Url>, ()> {
+        let has_files = data.files.as_ref().map_or(false, |files| !files.is_empty());
+        if data.title.is_none() && data.text.is_none() && data.url.is_none() && !has_files {
+            return Err(());
+        }
+        match data.url {
+            Some(ref url) => {
+                let document = self.global().r().as_window().Document();
+                document.base_url().join(&url.0).map(Some).map_err(|_| ())
+            },
+            None => Ok(None),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    fn finish_share(&self, promise: &Promise, result: Result<(), ShareError>) {
+        self.share_in_progress.set(false);
+        let global = self.global();
+        match result {
+            Ok(()) => promise.resolve_native(global.r().get_cx(), &()),
+            Err(ShareError::Canceled) => promise.reject_error(global.r().get_cx(), Error::Abort),
+            Err(ShareError::Failed) => promise.reject_error(global.r().get_cx(), Error::Data),
+        }
+    }
+}
+
 impl NavigatorMethods for Navigator {
     // https://html.spec.whatwg.org/multipage/#dom-navigator-product
     fn Product(&self) -> DOMString {
@@ -70,7 +132,7 @@ impl NavigatorMethods for Navigator {
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-useragent
     fn UserAgent(&self) -> DOMString {
-        navigatorinfo::UserAgent()
+        navigatorinfo::UserAgent(&self.global().r().get_url())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-appversion
@@ -108,9 +170,124 @@ impl NavigatorMethods for Navigator {
         self.service_worker.or_init(|| ServiceWorkerContainer::new(self.global().r()))
     }
 
+    // https://immersive-web.github.io/webxr/#dom-navigator-xr
+    fn Xr(&self) -> Root<XRSystem> {
+        self.xr.or_init(|| XRSystem::new(self.global().r()))
+    }
+
+    // https://storage.spec.whatwg.org/#dom-navigatorstorage-storage
+    fn Storage(&self) -> Root<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(self.global().r()))
+    }
+
+    // https://wicg.github.io/webusb/#dom-navigator-usb
+    fn Usb(&self) -> Root<USB> {
+        self.usb.or_init(|| USB::new(self.global().r()))
+    }
+
+    // https://w3c.github.io/mediacapture-main/#dom-navigator-mediadevices
+    fn MediaDevices(&self) -> Root<MediaDevices> {
+        self.media_devices.or_init(|| MediaDevices::new(self.global().r()))
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/web-share/#share-method
+    fn Share(&self, data: &ShareData) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let window = global.r().as_window();
+
+        // Steps 1-2.
+        if self.share_in_progress.get() || !window.Document().is_fully_active() {
+            promise.reject_error(global.r().get_cx(), Error::InvalidState);
+            return promise;
+        }
+
+        // TODO: Step 3, reject without transient activation, which is not tracked yet.
+
+        // Step 4.
+        let url = match self.validate_share_data(data) {
+            Ok(url) => url,
+            Err(()) => {
+                promise.reject_error(global.r().get_cx(), Error::Type("Invalid share data".to_owned()));
+                return promise;
+            },
+        };
+
+        let mut files = vec![];
+        for file in data.files.iter().flat_map(|files| files.iter()) {
+            let blob = file.upcast::<Blob>();
+            let bytes = match blob.get_bytes() {
+                Ok(bytes) => bytes,
+                Err(()) => {
+                    promise.reject_error(global.r().get_cx(), Error::Data);
+                    return promise;
+                },
+            };
+            files.push(SharedFile {
+                name: String::from(file.name().clone()),
+                mime_type: String::from(blob.Type()),
+                data: bytes,
+            });
+        }
+        let share_data = EmbedderShareData {
+            title: data.title.as_ref().map(|title| title.0.clone()),
+            text: data.text.as_ref().map(|text| text.0.clone()),
+            url: url.map(Url::into_string),
+            files: files,
+        };
+
+        // Steps 5-8: the embedder shows the share targets to the user, which is waited
+        // for off the script thread.
+        self.share_in_progress.set(true);
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let pipeline_id = window.pipeline_id();
+        let constellation_chan = window.constellation_chan().clone();
+        let mut runnable = box ShareRunnable {
+            navigator: Trusted::new(self),
+            promise: TrustedPromise::new(promise.clone()),
+            result: Err(ShareError::Failed),
+        };
+        spawn_named("Share".to_owned(), move || {
+            let (sender, receiver) = ipc::channel().unwrap();
+            if constellation_chan.send(ConstellationMsg::Share(pipeline_id, share_data, sender)).is_ok() {
+                runnable.result = receiver.recv().unwrap_or(Err(ShareError::Failed));
+            }
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        });
+        promise
+    }
+
+    // https://w3c.github.io/web-share/#canshare-method
+    fn CanShare(&self, data: &ShareData) -> bool {
+        self.validate_share_data(data).is_ok()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-navigator-cookieenabled
     fn CookieEnabled(&self) -> bool {
         true
     }
 
 }
+
+/// Delivers the outcome of a `share()` call.
+struct ShareRunnable {
+    navigator: Trusted<Navigator>,
+    promise: TrustedPromise,
+    result: Result<(), ShareError>,
+}
+
+impl Runnable for ShareRunnable {
+    fn name(&self) -> &'static str { "ShareRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let navigator = this.navigator.root();
+        let promise = this.promise.root();
+        let global = navigator.global();
+        let _ac = JSAutoCompartment::new(global.r().get_cx(), promise.reflector().get_jsobject().get());
+        navigator.finish_share(&promise, this.result);
+    }
+}
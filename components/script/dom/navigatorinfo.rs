@@ -3,6 +3,8 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::str::DOMString;
+use url::Url;
+use util::compat;
 use util::opts;
 
 pub fn Product() -> DOMString {
@@ -36,8 +38,8 @@ pub fn Platform() -> DOMString {
     DOMString::from("Mac")
 }
 
-pub fn UserAgent() -> DOMString {
-    DOMString::from(&*opts::get().user_agent)
+pub fn UserAgent(url: &Url) -> DOMString {
+    DOMString::from(compat::user_agent_for_url(url, &opts::get().user_agent))
 }
 
 pub fn AppVersion() -> DOMString {
@@ -22,7 +22,7 @@ use dom::bindings::conversions::{self, DerivedFrom};
 use dom::bindings::error::{Error, ErrorResult, Fallible};
 use dom::bindings::global::GlobalRef;
 use dom::bindings::inheritance::{Castable, CharacterDataTypeId, ElementTypeId};
-use dom::bindings::inheritance::{EventTargetTypeId, HTMLElementTypeId, NodeTypeId};
+use dom::bindings::inheritance::{EventTargetTypeId, HTMLElementTypeId, HTMLMediaElementTypeId, NodeTypeId};
 use dom::bindings::js::{JS, LayoutJS, MutNullableHeap};
 use dom::bindings::js::Root;
 use dom::bindings::js::RootedReference;
@@ -30,18 +30,19 @@ use dom::bindings::reflector::{Reflectable, reflect_dom_object};
 use dom::bindings::str::{DOMString, USVString};
 use dom::bindings::xmlname::namespace_from_domstring;
 use dom::characterdata::{CharacterData, LayoutCharacterDataHelpers};
-use dom::document::{Document, DocumentSource, IsHTMLDocument};
+use dom::document::{Document, DocumentSource, IsHTMLDocument, LayoutDocumentHelpers};
 use dom::documentfragment::DocumentFragment;
 use dom::documenttype::DocumentType;
 use dom::element::{Element, ElementCreator};
 use dom::eventtarget::EventTarget;
 use dom::htmlbodyelement::HTMLBodyElement;
-use dom::htmlcanvaselement::LayoutHTMLCanvasElementHelpers;
+use dom::htmlcanvaselement::{HTMLCanvasElement, LayoutHTMLCanvasElementHelpers};
 use dom::htmlcollection::HTMLCollection;
 use dom::htmlelement::HTMLElement;
 use dom::htmliframeelement::{HTMLIFrameElement, HTMLIFrameElementLayoutMethods};
 use dom::htmlimageelement::{HTMLImageElement, LayoutHTMLImageElementHelpers};
 use dom::htmlinputelement::{HTMLInputElement, LayoutHTMLInputElementHelpers};
+use dom::htmlmediaelement::{HTMLMediaElement, LayoutHTMLMediaElementHelpers};
 use dom::htmltextareaelement::{HTMLTextAreaElement, LayoutHTMLTextAreaElementHelpers};
 use dom::nodelist::NodeList;
 use dom::processinginstruction::ProcessingInstruction;
@@ -954,6 +955,7 @@ pub trait LayoutNodeHelpers {
     fn text_content(&self) -> String;
     fn selection(&self) -> Option<Range<usize>>;
     fn image_url(&self) -> Option<Url>;
+    fn image_density(&self) -> f64;
     fn canvas_data(&self) -> Option<HTMLCanvasData>;
     fn iframe_pipeline_id(&self) -> PipelineId;
     fn opaque(&self) -> OpaqueNode;
@@ -1075,6 +1077,10 @@ impl LayoutNodeHelpers for LayoutJS<Node> {
             return unsafe { input.selection_for_layout() };
         }
 
+        if self.downcast::<Text>().is_some() {
+            return unsafe { self.owner_doc_for_layout().selected_text_for_layout(*self) };
+        }
+
         None
     }
 
@@ -1087,9 +1093,19 @@ impl LayoutNodeHelpers for LayoutJS<Node> {
         }
     }
 
+    #[allow(unsafe_code)]
+    fn image_density(&self) -> f64 {
+        unsafe {
+            self.downcast::<HTMLImageElement>()
+                .expect("not an image!")
+                .image_density()
+        }
+    }
+
     fn canvas_data(&self) -> Option<HTMLCanvasData> {
-        self.downcast()
+        self.downcast::<HTMLCanvasElement>()
             .map(|canvas| canvas.data())
+            .or_else(|| self.downcast::<HTMLMediaElement>().map(|media| media.video_data()))
     }
 
     fn iframe_pipeline_id(&self) -> PipelineId {
@@ -1719,7 +1735,7 @@ impl Node {
                 let window = document.window();
                 let loader = DocumentLoader::new(&*document.loader());
                 let document = Document::new(window, None,
-                                             Some((*document.url()).clone()),
+                                             Some(document.url()),
                                              is_html_doc, None,
                                              None, DocumentSource::NotFromParser, loader,
                                              None, None);
@@ -2687,6 +2703,8 @@ impl Into<LayoutElementType> for ElementTypeId {
                 LayoutElementType::HTMLTableSectionElement,
             ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLTextAreaElement) =>
                 LayoutElementType::HTMLTextAreaElement,
+            ElementTypeId::HTMLElement(HTMLElementTypeId::HTMLMediaElement(HTMLMediaElementTypeId::HTMLVideoElement)) =>
+                LayoutElementType::HTMLVideoElement,
             _ => LayoutElementType::Element,
         }
     }
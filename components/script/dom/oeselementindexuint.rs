@@ -0,0 +1,26 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/extensions/OES_element_index_uint/
+use dom::bindings::codegen::Bindings::OESElementIndexUintBinding;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+
+#[dom_struct]
+pub struct OESElementIndexUint {
+    reflector_: Reflector,
+}
+
+impl OESElementIndexUint {
+    fn new_inherited() -> OESElementIndexUint {
+        OESElementIndexUint {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<OESElementIndexUint> {
+        reflect_dom_object(box OESElementIndexUint::new_inherited(), global, OESElementIndexUintBinding::Wrap)
+    }
+}
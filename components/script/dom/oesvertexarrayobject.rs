@@ -0,0 +1,55 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/extensions/OES_vertex_array_object/
+use dom::bindings::codegen::Bindings::OESVertexArrayObjectBinding;
+use dom::bindings::codegen::Bindings::OESVertexArrayObjectBinding::OESVertexArrayObjectMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::webglrenderingcontext::WebGLRenderingContext;
+use dom::webglvertexarrayobject::WebGLVertexArrayObject;
+
+#[dom_struct]
+pub struct OESVertexArrayObject {
+    reflector_: Reflector,
+    ctx: JS<WebGLRenderingContext>,
+}
+
+impl OESVertexArrayObject {
+    fn new_inherited(ctx: &WebGLRenderingContext) -> OESVertexArrayObject {
+        OESVertexArrayObject {
+            reflector_: Reflector::new(),
+            ctx: JS::from_ref(ctx),
+        }
+    }
+
+    pub fn new(global: GlobalRef, ctx: &WebGLRenderingContext) -> Root<OESVertexArrayObject> {
+        reflect_dom_object(box OESVertexArrayObject::new_inherited(ctx),
+                           global,
+                           OESVertexArrayObjectBinding::Wrap)
+    }
+}
+
+impl OESVertexArrayObjectMethods for OESVertexArrayObject {
+    // https://www.khronos.org/registry/webgl/extensions/OES_vertex_array_object/
+    fn CreateVertexArrayOES(&self) -> Option<Root<WebGLVertexArrayObject>> {
+        self.ctx.create_vertex_array()
+    }
+
+    // https://www.khronos.org/registry/webgl/extensions/OES_vertex_array_object/
+    fn DeleteVertexArrayOES(&self, vertex_array: Option<&WebGLVertexArrayObject>) {
+        self.ctx.delete_vertex_array(vertex_array)
+    }
+
+    // https://www.khronos.org/registry/webgl/extensions/OES_vertex_array_object/
+    fn IsVertexArrayOES(&self, vertex_array: Option<&WebGLVertexArrayObject>) -> bool {
+        self.ctx.is_vertex_array(vertex_array)
+    }
+
+    // https://www.khronos.org/registry/webgl/extensions/OES_vertex_array_object/
+    fn BindVertexArrayOES(&self, vertex_array: Option<&WebGLVertexArrayObject>) {
+        self.ctx.bind_vertex_array(vertex_array)
+    }
+}
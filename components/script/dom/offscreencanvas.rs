@@ -0,0 +1,132 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::{ImageEncoding, encode_image};
+use dom::bindings::codegen::Bindings::OffscreenCanvasBinding;
+use dom::bindings::codegen::Bindings::OffscreenCanvasBinding::{ImageEncodeOptions, OffscreenCanvasMethods};
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::refcounted::TrustedPromise;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::blob::{Blob, BlobImpl};
+use dom::eventtarget::EventTarget;
+use dom::promise::Promise;
+use euclid::size::Size2D;
+use js::jsapi::JSAutoCompartment;
+use script_runtime::CommonScriptMsg;
+use script_runtime::ScriptThreadEventCategory::ScriptEvent;
+use script_thread::Runnable;
+use std::cell::Cell;
+use std::rc::Rc;
+use util::thread::spawn_named;
+
+// https://html.spec.whatwg.org/multipage/#the-offscreencanvas-interface
+#[dom_struct]
+pub struct OffscreenCanvas {
+    eventtarget: EventTarget,
+    width: Cell<u64>,
+    height: Cell<u64>,
+}
+
+impl OffscreenCanvas {
+    fn new_inherited(width: u64, height: u64) -> OffscreenCanvas {
+        OffscreenCanvas {
+            eventtarget: EventTarget::new_inherited(),
+            width: Cell::new(width),
+            height: Cell::new(height),
+        }
+    }
+
+    pub fn new(global: GlobalRef, width: u64, height: u64) -> Root<OffscreenCanvas> {
+        reflect_dom_object(box OffscreenCanvas::new_inherited(width, height),
+                           global,
+                           OffscreenCanvasBinding::Wrap)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas
+    pub fn Constructor(global: GlobalRef, width: u64, height: u64) -> Fallible<Root<OffscreenCanvas>> {
+        Ok(OffscreenCanvas::new(global, width, height))
+    }
+}
+
+impl OffscreenCanvasMethods for OffscreenCanvas {
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-width
+    fn Width(&self) -> u64 {
+        self.width.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-width
+    fn SetWidth(&self, value: u64) {
+        self.width.set(value);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-height
+    fn Height(&self) -> u64 {
+        self.height.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-height
+    fn SetHeight(&self, value: u64) {
+        self.height.set(value);
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-converttoblob
+    fn ConvertToBlob(&self, options: &ImageEncodeOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+
+        // Step 3.
+        let (width, height) = (self.width.get(), self.height.get());
+        if width == 0 || height == 0 || width > u32::max_value() as u64 ||
+           height > u32::max_value() as u64 {
+            promise.reject_error(global.r().get_cx(), Error::IndexSize);
+            return promise;
+        }
+
+        // Steps 4-7. Without a rendering context, every pixel of the bitmap is transparent
+        // black. The file is encoded off the script thread.
+        let encoding = ImageEncoding::from_mime_type(&options.type_, options.quality);
+        let size = Size2D::new(width as u32, height as u32);
+        let script_chan = global.r().script_chan();
+        let wrapper = global.r().get_runnable_wrapper();
+        let mut runnable = box ConvertToBlobRunnable {
+            promise: TrustedPromise::new(promise.clone()),
+            encoding: encoding,
+            result: vec![],
+        };
+        spawn_named("OffscreenCanvasEncoder".to_owned(), move || {
+            let pixels = vec![0; size.width as usize * size.height as usize * 4];
+            runnable.result = encode_image(&pixels, size, encoding);
+            let _ = script_chan.send(CommonScriptMsg::RunnableMsg(ScriptEvent,
+                                                                  wrapper.wrap_runnable(runnable)));
+        });
+        promise
+    }
+}
+
+/// Delivers the encoded file of a `convertToBlob()` call.
+struct ConvertToBlobRunnable {
+    promise: TrustedPromise,
+    encoding: ImageEncoding,
+    result: Vec<u8>,
+}
+
+impl Runnable for ConvertToBlobRunnable {
+    fn name(&self) -> &'static str { "ConvertToBlobRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let promise = this.promise.root();
+        let global = promise.global();
+        let cx = global.r().get_cx();
+        let _ac = JSAutoCompartment::new(cx, promise.reflector().get_jsobject().get());
+        let blob = Blob::new(global.r(),
+                             BlobImpl::new_from_bytes(this.result),
+                             this.encoding.mime_type().to_owned());
+        promise.resolve_native(cx, &blob);
+    }
+}
@@ -0,0 +1,91 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use audio::{AudioRenderMsg, NodeId};
+use audio::node::{self, AudioNodeInit, NodeMessage};
+use audio::param::ParamType;
+use dom::audiocontext::AudioContext;
+use dom::audionode::AudioNode;
+use dom::audioparam::AudioParam;
+use dom::audioscheduledsourcenode::AudioScheduledSourceNode;
+use dom::bindings::codegen::Bindings::OscillatorNodeBinding;
+use dom::bindings::codegen::Bindings::OscillatorNodeBinding::{OscillatorNodeMethods, OscillatorType};
+use dom::bindings::error::{Error, ErrorResult};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::reflect_dom_object;
+use std::cell::Cell;
+
+/// The range of detunes, in cents, that keeps frequencies finite.
+pub const MAX_DETUNE: f32 = 153600.;
+
+// https://webaudio.github.io/web-audio-api/#oscillatornode
+#[dom_struct]
+pub struct OscillatorNode {
+    source_node: AudioScheduledSourceNode,
+    oscillator_type: Cell<OscillatorType>,
+    frequency: JS<AudioParam>,
+    detune: JS<AudioParam>,
+}
+
+impl OscillatorNode {
+    fn new_inherited(context: &AudioContext,
+                     node_id: NodeId,
+                     frequency: &AudioParam,
+                     detune: &AudioParam)
+                     -> OscillatorNode {
+        OscillatorNode {
+            source_node: AudioScheduledSourceNode::new_inherited(context, node_id),
+            oscillator_type: Cell::new(OscillatorType::Sine),
+            frequency: JS::from_ref(frequency),
+            detune: JS::from_ref(detune),
+        }
+    }
+
+    pub fn new(global: GlobalRef, context: &AudioContext) -> Root<OscillatorNode> {
+        let node_id = context.create_node(AudioNodeInit::Oscillator);
+        let nyquist = context.sample_rate() / 2.;
+        let frequency = AudioParam::new(global, context, node_id, ParamType::Frequency, 440., -nyquist, nyquist);
+        let detune = AudioParam::new(global, context, node_id, ParamType::Detune, 0., -MAX_DETUNE, MAX_DETUNE);
+        reflect_dom_object(box OscillatorNode::new_inherited(context, node_id, &frequency, &detune),
+                           global,
+                           OscillatorNodeBinding::Wrap)
+    }
+}
+
+impl OscillatorNodeMethods for OscillatorNode {
+    // https://webaudio.github.io/web-audio-api/#dom-oscillatornode-type
+    fn Type(&self) -> OscillatorType {
+        self.oscillator_type.get()
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-oscillatornode-type
+    fn SetType(&self, value: OscillatorType) -> ErrorResult {
+        let oscillator_type = match value {
+            OscillatorType::Sine => node::OscillatorType::Sine,
+            OscillatorType::Square => node::OscillatorType::Square,
+            OscillatorType::Sawtooth => node::OscillatorType::Sawtooth,
+            OscillatorType::Triangle => node::OscillatorType::Triangle,
+            // Custom waveforms are set with setPeriodicWave(), which isn't
+            // supported yet.
+            OscillatorType::Custom => return Err(Error::InvalidState),
+        };
+        self.oscillator_type.set(value);
+        let node = self.upcast::<AudioNode>();
+        node.context().send(AudioRenderMsg::NodeMessage(node.node_id(),
+                                                        NodeMessage::SetOscillatorType(oscillator_type)));
+        Ok(())
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-oscillatornode-frequency
+    fn Frequency(&self) -> Root<AudioParam> {
+        Root::from_ref(&*self.frequency)
+    }
+
+    // https://webaudio.github.io/web-audio-api/#dom-oscillatornode-detune
+    fn Detune(&self) -> Root<AudioParam> {
+        Root::from_ref(&*self.detune)
+    }
+}
@@ -0,0 +1,532 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::PathSegment;
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::CanvasPathMethods;
+use dom::bindings::codegen::Bindings::DOMMatrixBinding::DOMMatrixInit;
+use dom::bindings::codegen::Bindings::Path2DBinding;
+use dom::bindings::codegen::Bindings::Path2DBinding::Path2DMethods;
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::dommatrixreadonly::dommatrixinit_to_matrix;
+use euclid::matrix2d::Matrix2D;
+use euclid::point::Point2D;
+use euclid::rect::Rect;
+use euclid::size::Size2D;
+use std::ascii::AsciiExt;
+use std::f32::consts::PI;
+
+// https://html.spec.whatwg.org/multipage/#path2d-objects
+#[dom_struct]
+pub struct Path2D {
+    reflector_: Reflector,
+    #[ignore_heap_size_of = "Defined in canvas_traits"]
+    segments: DOMRefCell<Vec<PathSegment>>,
+}
+
+impl Path2D {
+    fn new_inherited(segments: Vec<PathSegment>) -> Path2D {
+        Path2D {
+            reflector_: Reflector::new(),
+            segments: DOMRefCell::new(segments),
+        }
+    }
+
+    pub fn new(global: GlobalRef, segments: Vec<PathSegment>) -> Root<Path2D> {
+        reflect_dom_object(box Path2D::new_inherited(segments),
+                           global,
+                           Path2DBinding::Wrap)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-path2d
+    pub fn Constructor(global: GlobalRef) -> Fallible<Root<Path2D>> {
+        Ok(Path2D::new(global, vec![]))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-path2d-copy
+    pub fn Constructor_(global: GlobalRef, path: &Path2D) -> Fallible<Root<Path2D>> {
+        Ok(Path2D::new(global, path.segments()))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-path2d-withdata
+    pub fn Constructor__(global: GlobalRef, data: DOMString) -> Fallible<Root<Path2D>> {
+        Ok(Path2D::new(global, parse_path_data(&data)))
+    }
+
+    /// The segments of the path, which are sent to the canvas that draws it.
+    pub fn segments(&self) -> Vec<PathSegment> {
+        self.segments.borrow().clone()
+    }
+
+    fn push(&self, segment: PathSegment) {
+        self.segments.borrow_mut().push(segment);
+    }
+}
+
+impl Path2DMethods for Path2D {
+    // https://html.spec.whatwg.org/multipage/#dom-path2d-addpath
+    fn AddPath(&self, path: &Path2D, transform: &DOMMatrixInit) -> ErrorResult {
+        let (_, matrix) = try!(dommatrixinit_to_matrix(transform));
+        let values = [matrix.m11, matrix.m12, matrix.m21, matrix.m22, matrix.m41, matrix.m42];
+        if !values.iter().all(|value| value.is_finite()) {
+            return Ok(());
+        }
+        let matrix = Matrix2D::row_major(matrix.m11 as f32, matrix.m12 as f32,
+                                         matrix.m21 as f32, matrix.m22 as f32,
+                                         matrix.m41 as f32, matrix.m42 as f32);
+        // The segments are copied first, as the path may be added to itself.
+        let segments = path.segments();
+        self.push(PathSegment::AddPath(segments, matrix));
+        Ok(())
+    }
+}
+
+impl CanvasPathMethods for Path2D {
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-closepath
+    fn ClosePath(&self) {
+        self.push(PathSegment::ClosePath);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-moveto
+    fn MoveTo(&self, x: f64, y: f64) {
+        if !(x.is_finite() && y.is_finite()) {
+            return;
+        }
+
+        self.push(PathSegment::MoveTo(Point2D::new(x as f32, y as f32)));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-lineto
+    fn LineTo(&self, x: f64, y: f64) {
+        if !(x.is_finite() && y.is_finite()) {
+            return;
+        }
+
+        self.push(PathSegment::LineTo(Point2D::new(x as f32, y as f32)));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-quadraticcurveto
+    fn QuadraticCurveTo(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        if !(cpx.is_finite() && cpy.is_finite() && x.is_finite() && y.is_finite()) {
+            return;
+        }
+
+        self.push(PathSegment::QuadraticCurveTo(Point2D::new(cpx as f32, cpy as f32),
+                                                Point2D::new(x as f32, y as f32)));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-beziercurveto
+    fn BezierCurveTo(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        if !(cp1x.is_finite() && cp1y.is_finite() && cp2x.is_finite() && cp2y.is_finite() &&
+             x.is_finite() && y.is_finite()) {
+            return;
+        }
+
+        self.push(PathSegment::BezierCurveTo(Point2D::new(cp1x as f32, cp1y as f32),
+                                             Point2D::new(cp2x as f32, cp2y as f32),
+                                             Point2D::new(x as f32, y as f32)));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-arcto
+    fn ArcTo(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, r: f64) -> ErrorResult {
+        if !([cp1x, cp1y, cp2x, cp2y, r].iter().all(|x| x.is_finite())) {
+            return Ok(());
+        }
+        if r < 0.0 {
+            return Err(Error::IndexSize);
+        }
+
+        self.push(PathSegment::ArcTo(Point2D::new(cp1x as f32, cp1y as f32),
+                                     Point2D::new(cp2x as f32, cp2y as f32),
+                                     r as f32));
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-rect
+    fn Rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        if [x, y, width, height].iter().all(|val| val.is_finite()) {
+            self.push(PathSegment::Rect(Rect::new(Point2D::new(x as f32, y as f32),
+                                                  Size2D::new(width as f32, height as f32))));
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-arc
+    fn Arc(&self, x: f64, y: f64, r: f64, start: f64, end: f64, ccw: bool) -> ErrorResult {
+        if !([x, y, r, start, end].iter().all(|x| x.is_finite())) {
+            return Ok(());
+        }
+
+        if r < 0.0 {
+            return Err(Error::IndexSize);
+        }
+
+        self.push(PathSegment::Arc(Point2D::new(x as f32, y as f32), r as f32, start as f32, end as f32, ccw));
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-context-2d-ellipse
+    fn Ellipse(&self, x: f64, y: f64, rx: f64, ry: f64, rotation: f64, start: f64, end: f64, ccw: bool)
+               -> ErrorResult {
+        if !([x, y, rx, ry, rotation, start, end].iter().all(|x| x.is_finite())) {
+            return Ok(());
+        }
+
+        if rx < 0.0 || ry < 0.0 {
+            return Err(Error::IndexSize);
+        }
+
+        self.push(PathSegment::Ellipse(Point2D::new(x as f32, y as f32),
+                                       rx as f32,
+                                       ry as f32,
+                                       rotation as f32,
+                                       start as f32,
+                                       end as f32,
+                                       ccw));
+        Ok(())
+    }
+}
+
+/// Parses SVG path data into the segments of a path, keeping the segments
+/// before the first error.
+/// https://www.w3.org/TR/SVG/paths.html#PathDataBNF
+pub fn parse_path_data(data: &str) -> Vec<PathSegment> {
+    let mut parser = PathDataParser {
+        bytes: data.as_bytes(),
+        position: 0,
+    };
+    let mut segments = vec![];
+    let mut current_point = Point2D::zero();
+    let mut subpath_start = Point2D::zero();
+    // The second control point of the previous curve, which `S` and `T`
+    // reflect, and whether it was a cubic one.
+    let mut previous_control: Option<(Point2D<f32>, bool)> = None;
+    let mut previous_command = None;
+
+    parser.skip_whitespace();
+    while !parser.is_exhausted() {
+        let command = match parser.command() {
+            Some(command) => command,
+            None => match previous_command {
+                // A command is repeated when its arguments are, and further
+                // points after a move are lines.
+                Some(b'M') if parser.has_number() => b'L',
+                Some(b'm') if parser.has_number() => b'l',
+                Some(command) if command != b'Z' && command != b'z' && parser.has_number() => command,
+                _ => break,
+            },
+        };
+        if previous_command.is_none() && command != b'M' && command != b'm' {
+            break;
+        }
+        let relative = b'a' <= command && command <= b'z';
+        let origin = if relative { current_point } else { Point2D::zero() };
+        let point = |x: f32, y: f32| Point2D::new(origin.x + x, origin.y + y);
+
+        let mut control = None;
+        let segment = match command.to_ascii_uppercase() {
+            b'Z' => {
+                current_point = subpath_start;
+                PathSegment::ClosePath
+            }
+            b'M' => match parser.numbers(2) {
+                Some(values) => {
+                    current_point = point(values[0], values[1]);
+                    subpath_start = current_point;
+                    PathSegment::MoveTo(current_point)
+                }
+                None => break,
+            },
+            b'L' => match parser.numbers(2) {
+                Some(values) => {
+                    current_point = point(values[0], values[1]);
+                    PathSegment::LineTo(current_point)
+                }
+                None => break,
+            },
+            b'H' => match parser.numbers(1) {
+                Some(values) => {
+                    current_point = Point2D::new(origin.x + values[0], current_point.y);
+                    PathSegment::LineTo(current_point)
+                }
+                None => break,
+            },
+            b'V' => match parser.numbers(1) {
+                Some(values) => {
+                    current_point = Point2D::new(current_point.x, origin.y + values[0]);
+                    PathSegment::LineTo(current_point)
+                }
+                None => break,
+            },
+            b'C' => match parser.numbers(6) {
+                Some(values) => {
+                    let cp2 = point(values[2], values[3]);
+                    control = Some((cp2, true));
+                    current_point = point(values[4], values[5]);
+                    PathSegment::BezierCurveTo(point(values[0], values[1]), cp2, current_point)
+                }
+                None => break,
+            },
+            b'S' => match parser.numbers(4) {
+                Some(values) => {
+                    let cp1 = reflect(current_point, previous_control, true);
+                    let cp2 = point(values[0], values[1]);
+                    control = Some((cp2, true));
+                    current_point = point(values[2], values[3]);
+                    PathSegment::BezierCurveTo(cp1, cp2, current_point)
+                }
+                None => break,
+            },
+            b'Q' => match parser.numbers(4) {
+                Some(values) => {
+                    let cp = point(values[0], values[1]);
+                    control = Some((cp, false));
+                    current_point = point(values[2], values[3]);
+                    PathSegment::QuadraticCurveTo(cp, current_point)
+                }
+                None => break,
+            },
+            b'T' => match parser.numbers(2) {
+                Some(values) => {
+                    let cp = reflect(current_point, previous_control, false);
+                    control = Some((cp, false));
+                    current_point = point(values[0], values[1]);
+                    PathSegment::QuadraticCurveTo(cp, current_point)
+                }
+                None => break,
+            },
+            b'A' => match parser.arc_arguments() {
+                Some((rx, ry, x_axis_rotation, large_arc, sweep, x, y)) => {
+                    let start = current_point;
+                    current_point = point(x, y);
+                    match arc_segment(start, rx, ry, x_axis_rotation, large_arc, sweep, current_point) {
+                        Some(segment) => segment,
+                        None => {
+                            previous_command = Some(command);
+                            previous_control = None;
+                            parser.skip_comma_whitespace();
+                            continue;
+                        }
+                    }
+                }
+                None => break,
+            },
+            _ => break,
+        };
+        segments.push(segment);
+        previous_command = Some(command);
+        previous_control = control;
+        parser.skip_comma_whitespace();
+    }
+    segments
+}
+
+/// The first control point of a smooth curve, which is the reflection of
+/// the second control point of the previous curve if it was of the same kind.
+fn reflect(current_point: Point2D<f32>, previous_control: Option<(Point2D<f32>, bool)>, cubic: bool)
+           -> Point2D<f32> {
+    match previous_control {
+        Some((control, was_cubic)) if was_cubic == cubic => {
+            Point2D::new(2. * current_point.x - control.x, 2. * current_point.y - control.y)
+        }
+        _ => current_point,
+    }
+}
+
+/// Converts an SVG elliptical arc from its endpoint parameterization to the
+/// center one of `ellipse()`, or to a line if a radius is zero. The arc is
+/// omitted when its endpoints are the same.
+/// https://www.w3.org/TR/SVG/implnote.html#ArcConversionEndpointToCenter
+fn arc_segment(start: Point2D<f32>,
+               rx: f32,
+               ry: f32,
+               x_axis_rotation: f32,
+               large_arc: bool,
+               sweep: bool,
+               end: Point2D<f32>)
+               -> Option<PathSegment> {
+    if start == end {
+        return None;
+    }
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0. || ry == 0. {
+        return Some(PathSegment::LineTo(end));
+    }
+
+    let rotation = x_axis_rotation.to_radians();
+    let (sin, cos) = rotation.sin_cos();
+    let dx = (start.x - end.x) / 2.;
+    let dy = (start.y - end.y) / 2.;
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    // Radii that are too small are scaled up so that the arc reaches the end.
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1. {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coefficient = (numerator / denominator).max(0.).sqrt();
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+    let cx1 = coefficient * rx * y1 / ry;
+    let cy1 = -coefficient * ry * x1 / rx;
+    let center = Point2D::new(cos * cx1 - sin * cy1 + (start.x + end.x) / 2.,
+                              sin * cx1 + cos * cy1 + (start.y + end.y) / 2.);
+
+    let start_angle = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+    let end_angle = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+    let mut delta = end_angle - start_angle;
+    if !sweep && delta > 0. {
+        delta -= 2. * PI;
+    } else if sweep && delta < 0. {
+        delta += 2. * PI;
+    }
+
+    Some(PathSegment::Ellipse(center, rx, ry, rotation, start_angle, start_angle + delta, !sweep))
+}
+
+struct PathDataParser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PathDataParser<'a> {
+    fn is_exhausted(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\x0C') | Some(b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn skip_comma_whitespace(&mut self) {
+        self.skip_whitespace();
+        if self.peek() == Some(b',') {
+            self.position += 1;
+            self.skip_whitespace();
+        }
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        match self.peek() {
+            Some(byte @ b'A'...b'Z') | Some(byte @ b'a'...b'z') => {
+                self.position += 1;
+                self.skip_whitespace();
+                Some(byte)
+            }
+            _ => None,
+        }
+    }
+
+    fn has_number(&self) -> bool {
+        match self.peek() {
+            Some(b'0'...b'9') | Some(b'+') | Some(b'-') | Some(b'.') => true,
+            _ => false,
+        }
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.position;
+        while let Some(b'0'...b'9') = self.peek() {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    /// https://www.w3.org/TR/SVG/paths.html#PathDataBNF
+    fn number(&mut self) -> Option<f32> {
+        let start = self.position;
+        if let Some(b'+') | Some(b'-') = self.peek() {
+            self.position += 1;
+        }
+        let mut digits = self.digits();
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            digits += self.digits();
+        }
+        if digits == 0 {
+            self.position = start;
+            return None;
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            let mantissa_end = self.position;
+            self.position += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.position += 1;
+            }
+            if self.digits() == 0 {
+                self.position = mantissa_end;
+            }
+        }
+        let number = ::std::str::from_utf8(&self.bytes[start..self.position]).ok()
+            .and_then(|number| number.parse::<f32>().ok());
+        match number {
+            Some(number) if number.is_finite() => Some(number),
+            _ => {
+                self.position = start;
+                None
+            }
+        }
+    }
+
+    /// Parses the arguments of a command, separated by commas or whitespace.
+    fn numbers(&mut self, count: usize) -> Option<Vec<f32>> {
+        let mut values = Vec::with_capacity(count);
+        for i in 0..count {
+            if i > 0 {
+                self.skip_comma_whitespace();
+            }
+            match self.number() {
+                Some(value) => values.push(value),
+                None => return None,
+            }
+        }
+        Some(values)
+    }
+
+    fn flag(&mut self) -> Option<bool> {
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return None,
+        };
+        self.position += 1;
+        Some(flag)
+    }
+
+    /// The radii, the x-axis rotation, the large-arc and sweep flags and the
+    /// end point of an arc, where flags need no separator after them.
+    fn arc_arguments(&mut self) -> Option<(f32, f32, f32, bool, bool, f32, f32)> {
+        let values = match self.numbers(3) {
+            Some(values) => values,
+            None => return None,
+        };
+        self.skip_comma_whitespace();
+        let large_arc = match self.flag() {
+            Some(flag) => flag,
+            None => return None,
+        };
+        self.skip_comma_whitespace();
+        let sweep = match self.flag() {
+            Some(flag) => flag,
+            None => return None,
+        };
+        self.skip_comma_whitespace();
+        self.numbers(2).map(|end| (values[0], values[1], values[2], large_arc, sweep, end[0], end[1]))
+    }
+}
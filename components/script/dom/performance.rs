@@ -2,57 +2,536 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
 use dom::bindings::codegen::Bindings::PerformanceBinding;
 use dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use dom::bindings::codegen::Bindings::PerformanceEntryBinding::PerformanceEntryMethods;
+use dom::bindings::codegen::Bindings::PerformanceTimingBinding::PerformanceTimingMethods;
+use dom::bindings::error::{Error, ErrorResult, Fallible};
 use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, Root};
 use dom::bindings::num::Finite;
-use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::refcounted::Trusted;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::element::Element;
+use dom::event::Event;
+use dom::eventtarget::EventTarget;
+use dom::htmlimageelement::HTMLImageElement;
+use dom::largestcontentfulpaint::LargestContentfulPaint;
+use dom::node::Node;
+use dom::performanceentry::PerformanceEntry;
+use dom::performanceeventtiming::{EventTimes, PerformanceEventTiming};
+use dom::performancemark::PerformanceMark;
+use dom::performancemeasure::PerformanceMeasure;
+use dom::performancenavigationtiming::PerformanceNavigationTiming;
+use dom::performanceobserver::PerformanceObserver;
+use dom::performancepainttiming::PerformancePaintTiming;
+use dom::performanceresourcetiming::PerformanceResourceTiming;
+use dom::performanceservertiming::server_timing_metrics;
 use dom::performancetiming::PerformanceTiming;
-use dom::window::Window;
+use net_traits::{Metadata, ResourceFetchTiming};
+use profile_traits::time::{ProfilerCategory, TimerMetadata, TimerMetadataFrameType};
+use profile_traits::time::{TimerMetadataReflowType, send_profile_data};
+use script_runtime::{CommonScriptMsg, ScriptThreadEventCategory};
+use script_thread::Runnable;
+use script_traits::PaintMetricType;
+use std::cell::Cell;
+use std::str;
 use time;
+use url::{Origin, Url};
 
 pub type DOMHighResTimeStamp = Finite<f64>;
 
+/// https://w3c.github.io/resource-timing/#dom-performance-setresourcetimingbuffersize
+const DEFAULT_RESOURCE_TIMING_BUFFER_SIZE: usize = 250;
+
+/// The types of the events caused by user input whose timing is recorded.
+/// https://w3c.github.io/event-timing/#sec-events-exposed
+const TIMED_EVENT_TYPES: &'static [&'static str] = &[
+    "auxclick", "click", "contextmenu", "dblclick", "mousedown", "mouseenter", "mouseleave",
+    "mouseout", "mouseover", "mouseup", "pointerover", "pointerenter", "pointerdown", "pointerup",
+    "pointercancel", "pointerout", "pointerleave", "gotpointercapture", "lostpointercapture",
+    "touchstart", "touchend", "touchcancel", "keydown", "keypress", "keyup", "beforeinput",
+    "input", "compositionstart", "compositionupdate", "compositionend", "dragstart", "dragend",
+    "dragenter", "dragleave", "dragover", "drop",
+];
+
+/// The types of the events that can be the first input of a page.
+/// https://w3c.github.io/event-timing/#sec-fin-event-timing
+const FIRST_INPUT_EVENT_TYPES: &'static [&'static str] = &["click", "keydown", "mousedown", "pointerdown"];
+
+/// The `PerformanceTiming` attributes, which are reserved as mark names in windows.
+/// https://w3c.github.io/user-timing/#dfn-convert-a-name-to-a-timestamp
+const NAVIGATION_TIMING_ATTRIBUTES: &'static [&'static str] = &[
+    "navigationStart",
+    "unloadEventStart",
+    "unloadEventEnd",
+    "redirectStart",
+    "redirectEnd",
+    "fetchStart",
+    "domainLookupStart",
+    "domainLookupEnd",
+    "connectStart",
+    "connectEnd",
+    "secureConnectionStart",
+    "requestStart",
+    "responseStart",
+    "responseEnd",
+    "domLoading",
+    "domInteractive",
+    "domContentLoadedEventStart",
+    "domContentLoadedEventEnd",
+    "domComplete",
+    "loadEventStart",
+    "loadEventEnd",
+];
+
 #[dom_struct]
 pub struct Performance {
-    reflector_: Reflector,
-    timing: JS<PerformanceTiming>,
+    eventtarget: EventTarget,
+    /// Only windows have navigation timing.
+    timing: Option<JS<PerformanceTiming>>,
+    /// https://w3c.github.io/hr-time/#dfn-time-origin
+    navigation_start_precise: f64,
+    /// https://w3c.github.io/performance-timeline/#dfn-performance-entry-buffer
+    entries: DOMRefCell<Vec<JS<PerformanceEntry>>>,
+    /// https://w3c.github.io/resource-timing/#dfn-resource-timing-buffer-size-limit
+    resource_timing_buffer_size_limit: Cell<usize>,
+    /// https://w3c.github.io/resource-timing/#dfn-resource-timing-buffer-full-flag
+    resource_timing_buffer_full: Cell<bool>,
+    /// https://w3c.github.io/performance-timeline/#dfn-registered-performance-observer-list
+    observers: DOMRefCell<Vec<JS<PerformanceObserver>>>,
+    /// https://w3c.github.io/performance-timeline/#dfn-performance-observer-task-queued-flag
+    pending_notification_observers_task: Cell<bool>,
+    /// When the input being handled was received, for the events it causes to be
+    /// timed from.
+    input_time: Cell<Option<u64>>,
+    /// https://w3c.github.io/event-timing/#has-dispatched-input-event
+    has_dispatched_input_event: Cell<bool>,
 }
 
 impl Performance {
-    fn new_inherited(window: &Window,
+    #[allow(unrooted_must_root)]
+    fn new_inherited(global: GlobalRef,
                      navigation_start: u64,
                      navigation_start_precise: f64) -> Performance {
+        let (timing, entries) = match global {
+            GlobalRef::Window(window) => {
+                let timing = PerformanceTiming::new(window, navigation_start, navigation_start_precise);
+                let navigation = PerformanceNavigationTiming::new(window,
+                                                                  navigation_start,
+                                                                  navigation_start_precise);
+                (Some(JS::from_ref(&*timing)), vec![JS::from_ref(navigation.upcast())])
+            },
+            GlobalRef::Worker(_) => (None, vec![]),
+        };
         Performance {
-            reflector_: Reflector::new(),
-            timing: JS::from_ref(&*PerformanceTiming::new(window,
-                                                            navigation_start,
-                                                            navigation_start_precise)),
+            eventtarget: EventTarget::new_inherited(),
+            timing: timing,
+            navigation_start_precise: navigation_start_precise,
+            entries: DOMRefCell::new(entries),
+            resource_timing_buffer_size_limit: Cell::new(DEFAULT_RESOURCE_TIMING_BUFFER_SIZE),
+            resource_timing_buffer_full: Cell::new(false),
+            observers: DOMRefCell::new(vec![]),
+            pending_notification_observers_task: Cell::new(false),
+            input_time: Cell::new(None),
+            has_dispatched_input_event: Cell::new(false),
         }
     }
 
-    pub fn new(window: &Window,
+    pub fn new(global: GlobalRef,
                navigation_start: u64,
                navigation_start_precise: f64) -> Root<Performance> {
-        reflect_dom_object(box Performance::new_inherited(window,
+        reflect_dom_object(box Performance::new_inherited(global,
                                                           navigation_start,
                                                           navigation_start_precise),
-                           GlobalRef::Window(window),
+                           global,
                            PerformanceBinding::Wrap)
     }
+
+    /// Delivers an entry to the interested observers and adds it to the
+    /// performance entry buffer.
+    pub fn queue_entry(&self, entry: &PerformanceEntry) {
+        self.notify_observers_of(entry);
+        self.entries.borrow_mut().push(JS::from_ref(entry));
+    }
+
+    /// https://w3c.github.io/performance-timeline/#dfn-queue-a-performanceentry
+    fn notify_observers_of(&self, entry: &PerformanceEntry) {
+        let mut observed = false;
+        for observer in self.observers.borrow().iter() {
+            if observer.observes_entry(entry) {
+                observer.queue_entry(entry);
+                observed = true;
+            }
+        }
+        if observed {
+            self.queue_observer_notification();
+        }
+    }
+
+    /// Queues a task to invoke the callbacks of the observers with pending
+    /// entries, unless one is queued already.
+    pub fn queue_observer_notification(&self) {
+        if self.pending_notification_observers_task.get() {
+            return;
+        }
+        self.pending_notification_observers_task.set(true);
+        let global = self.global();
+        let runnable = box NotifyPerformanceObserversRunnable {
+            owner: Trusted::new(self),
+        };
+        let wrapper = global.r().get_runnable_wrapper();
+        let _ = global.r().script_chan().send(CommonScriptMsg::RunnableMsg(
+            ScriptThreadEventCategory::DomEvent, wrapper.wrap_runnable(runnable)));
+    }
+
+    fn notify_observers(&self) {
+        self.pending_notification_observers_task.set(false);
+        let observers: Vec<Root<PerformanceObserver>> = self.observers.borrow().iter()
+            .map(|observer| Root::from_ref(&**observer))
+            .collect();
+        for observer in observers {
+            observer.notify();
+        }
+    }
+
+    pub fn add_observer(&self, observer: &PerformanceObserver) {
+        let mut observers = self.observers.borrow_mut();
+        if !observers.iter().any(|registered| &**registered == observer) {
+            observers.push(JS::from_ref(observer));
+        }
+    }
+
+    pub fn remove_observer(&self, observer: &PerformanceObserver) {
+        self.observers.borrow_mut().retain(|registered| &**registered != observer);
+    }
+
+    /// Records the time layout first handed a display list of the given kind to
+    /// the compositor, both as a paint timing entry and with the time profiler.
+    /// https://w3c.github.io/paint-timing/#mark-paint-timing
+    pub fn queue_paint_timing(&self, metric_type: PaintMetricType, paint_time: u64) {
+        let global = self.global();
+        let category = match metric_type {
+            PaintMetricType::FirstPaint => ProfilerCategory::TimeToFirstPaint,
+            PaintMetricType::FirstContentfulPaint => ProfilerCategory::TimeToFirstContentfulPaint,
+        };
+        let metadata = TimerMetadata {
+            url: global.r().get_url().as_str().into(),
+            iframe: if global.r().as_window().is_top_level() {
+                TimerMetadataFrameType::RootWindow
+            } else {
+                TimerMetadataFrameType::IFrame
+            },
+            incremental: TimerMetadataReflowType::FirstReflow,
+        };
+        send_profile_data(category,
+                          Some(metadata),
+                          global.r().time_profiler_chan().clone(),
+                          self.navigation_start_precise as u64,
+                          paint_time,
+                          0,
+                          0);
+
+        let start_time = (paint_time as f64 - self.navigation_start_precise) / 1000000.;
+        let entry = PerformancePaintTiming::new(global.r(), metric_type, start_time);
+        self.queue_entry(entry.upcast());
+    }
+
+    /// Records that layout painted a larger text or image than any before it, from `node`,
+    /// at `render_time`. Nothing is recorded once the user interacted with the page.
+    /// https://w3c.github.io/largest-contentful-paint/#sec-add-lcp-entry
+    pub fn queue_largest_contentful_paint(&self, node: &Node, size: f64, render_time: u64) {
+        if self.has_dispatched_input_event.get() {
+            return;
+        }
+        // Text is attributed to the element that contains it.
+        let element = match node.downcast::<Element>() {
+            Some(element) => Root::from_ref(element),
+            None => match node.GetParentElement() {
+                Some(element) => element,
+                None => return,
+            },
+        };
+        let relative = |time: u64| (time as f64 - self.navigation_start_precise) / 1000000.;
+        let (url, load_time) = match element.downcast::<HTMLImageElement>() {
+            Some(image) => {
+                let url = image.get_url().map_or(DOMString::new(), |url| DOMString::from(url.as_str()));
+                (url, image.load_time().map_or(0., |time| relative(time)))
+            },
+            None => (DOMString::new(), 0.),
+        };
+
+        let global = self.global();
+        let entry = LargestContentfulPaint::new(global.r(),
+                                                relative(render_time),
+                                                load_time,
+                                                size.round() as u32,
+                                                url,
+                                                &element);
+        self.queue_entry(entry.upcast());
+    }
+
+    /// Sets when the input being handled was received, or `None` once it is handled.
+    pub fn set_input_time(&self, input_time: Option<u64>) {
+        self.input_time.set(input_time);
+    }
+
+    /// Returns when the processing of an event starts, if it is caused by user input and
+    /// its timing is to be recorded.
+    /// https://w3c.github.io/event-timing/#sec-init-event-timing
+    pub fn event_timing_start(&self, event: &Event) -> Option<u64> {
+        if self.input_time.get().is_none() || !event.IsTrusted() ||
+           !TIMED_EVENT_TYPES.contains(&&*event.type_()) {
+            return None;
+        }
+        Some(time::precise_time_ns())
+    }
+
+    /// Records the timing of an event whose processing started at `processing_start`
+    /// and just ended. Only observers are told about `event` entries, and the entry of
+    /// the first input of the page is also added to the performance entry buffer.
+    /// https://w3c.github.io/event-timing/#sec-fin-event-timing
+    pub fn queue_event_timing(&self, event: &Event, target: Option<&Node>, processing_start: u64) {
+        let processing_end = time::precise_time_ns();
+        let input_time = self.input_time.get().unwrap_or(processing_start);
+        let relative = |time: u64| (time as f64 - self.navigation_start_precise) / 1000000.;
+        // The frame that shows the effects of the event isn't known to script, so the
+        // event lasts until its processing ends. Durations are rounded to 8ms.
+        let start_time = relative(input_time);
+        let times = EventTimes {
+            start_time: start_time,
+            processing_start: relative(processing_start),
+            processing_end: relative(processing_end),
+            duration: ((relative(processing_end) - start_time) / 8.).round() * 8.,
+        };
+
+        let global = self.global();
+        let event_type = DOMString::from(&*event.type_());
+        let cancelable = event.Cancelable();
+        let entry = PerformanceEventTiming::new(global.r(), "event", event_type.clone(), times, cancelable, target);
+        self.notify_observers_of(entry.upcast());
+
+        if !self.has_dispatched_input_event.get() && FIRST_INPUT_EVENT_TYPES.contains(&&*event_type) {
+            self.has_dispatched_input_event.set(true);
+            let entry = PerformanceEventTiming::new(global.r(), "first-input", event_type, times, cancelable, target);
+            self.queue_entry(entry.upcast());
+        }
+    }
+
+    /// https://w3c.github.io/user-timing/#dfn-convert-a-name-to-a-timestamp
+    fn convert_name_to_timestamp(&self, name: &DOMString) -> Fallible<f64> {
+        if NAVIGATION_TIMING_ATTRIBUTES.contains(&&**name) {
+            let timing = match self.timing {
+                Some(ref timing) => timing,
+                None => return Err(Error::Type(format!("{} is only available in windows", name))),
+            };
+            let value = match &**name {
+                "navigationStart" => timing.NavigationStart(),
+                "domLoading" => timing.DomLoading(),
+                "domInteractive" => timing.DomInteractive(),
+                "domContentLoadedEventStart" => timing.DomContentLoadedEventStart(),
+                "domContentLoadedEventEnd" => timing.DomContentLoadedEventEnd(),
+                "domComplete" => timing.DomComplete(),
+                "loadEventStart" => timing.LoadEventStart(),
+                "loadEventEnd" => timing.LoadEventEnd(),
+                _ => 0,
+            };
+            if value == 0 {
+                return Err(Error::InvalidAccess);
+            }
+            return Ok((value - timing.NavigationStart()) as f64);
+        }
+
+        let entries = self.entries.borrow();
+        entries.iter()
+               .rev()
+               .find(|entry| &**entry.entry_type() == "mark" && entry.name() == name)
+               .map(|entry| *entry.StartTime())
+               .ok_or(Error::Syntax)
+    }
+
+    fn clear_entries(&self, entry_type: &str, name: Option<DOMString>) {
+        self.entries.borrow_mut().retain(|entry| {
+            &**entry.entry_type() != entry_type || name.as_ref().map_or(false, |name| entry.name() != name)
+        });
+    }
+
+    fn resource_timing_entry_count(&self) -> usize {
+        self.entries.borrow().iter().filter(|entry| &**entry.entry_type() == "resource").count()
+    }
+
+    /// Records a `PerformanceResourceTiming` entry for a fetch whose response has
+    /// just been fully received.
+    /// https://w3c.github.io/resource-timing/#dfn-mark-resource-timing
+    pub fn queue_resource_timing(&self, url: &Url, initiator_type: DOMString, metadata: &Metadata) {
+        let mut timing = metadata.timing;
+        timing.response_end = time::precise_time_ns();
+
+        let global = self.global();
+        let origin = global.r().get_url().origin();
+        let mut server_timing = server_timing_metrics(metadata);
+        if !timing_allow_check(url, metadata, &origin) {
+            timing = ResourceFetchTiming {
+                fetch_start: timing.fetch_start,
+                response_end: timing.response_end,
+                ..Default::default()
+            };
+            server_timing.clear();
+        }
+
+        // https://w3c.github.io/resource-timing/#dfn-add-a-performanceresourcetiming-entry
+        // Observers see every entry, even those that do not fit in the buffer.
+        let entry = PerformanceResourceTiming::new(global.r(),
+                                                   DOMString::from(url.as_str()),
+                                                   initiator_type,
+                                                   self.navigation_start_precise,
+                                                   timing,
+                                                   server_timing);
+        self.notify_observers_of(entry.upcast());
+
+        if self.resource_timing_entry_count() >= self.resource_timing_buffer_size_limit.get() {
+            if !self.resource_timing_buffer_full.get() {
+                self.resource_timing_buffer_full.set(true);
+                self.upcast::<EventTarget>().fire_simple_event("resourcetimingbufferfull");
+            }
+            // Listeners may have made room by clearing or growing the buffer.
+            if self.resource_timing_entry_count() >= self.resource_timing_buffer_size_limit.get() {
+                return;
+            }
+        }
+        self.entries.borrow_mut().push(JS::from_ref(entry.upcast()));
+    }
+}
+
+/// Roots the entries accepted by `filter`, sorted by start time.
+pub fn entries_matching<F>(entries: &[JS<PerformanceEntry>], filter: F) -> Vec<Root<PerformanceEntry>>
+    where F: Fn(&PerformanceEntry) -> bool
+{
+    let mut entries: Vec<Root<PerformanceEntry>> = entries.iter()
+        .filter(|entry| filter(entry))
+        .map(|entry| Root::from_ref(&**entry))
+        .collect();
+    entries.sort_by(|a, b| a.StartTime().partial_cmp(&*b.StartTime()).unwrap());
+    entries
+}
+
+/// Whether detailed network timestamps of a resource may be exposed to `origin`.
+/// https://w3c.github.io/resource-timing/#dfn-timing-allow-check
+fn timing_allow_check(url: &Url, metadata: &Metadata, origin: &Origin) -> bool {
+    if url.origin() == *origin && metadata.final_url.origin() == *origin {
+        return true;
+    }
+    let serialized_origin = origin.ascii_serialization();
+    let values = match metadata.headers.as_ref().and_then(|headers| headers.get_raw("Timing-Allow-Origin")) {
+        Some(values) => values,
+        None => return false,
+    };
+    values.iter()
+          .filter_map(|value| str::from_utf8(value).ok())
+          .flat_map(|value| value.split(','))
+          .map(|value| value.trim())
+          .any(|value| value == "*" || value == serialized_origin)
 }
 
 impl PerformanceMethods for Performance {
     // https://dvcs.w3.org/hg/webperf/raw-file/tip/specs/NavigationTiming/Overview.html#performance-timing-attribute
     fn Timing(&self) -> Root<PerformanceTiming> {
-        Root::from_ref(&*self.timing)
+        Root::from_ref(self.timing.as_ref().expect("timing is only exposed on Window"))
     }
 
     // https://dvcs.w3.org/hg/webperf/raw-file/tip/specs/HighResolutionTime/Overview.html#dom-performance-now
     fn Now(&self) -> DOMHighResTimeStamp {
-        let nav_start = self.timing.navigation_start_precise();
-        let now = (time::precise_time_ns() as f64 - nav_start) / 1000000 as f64;
+        let now = (time::precise_time_ns() as f64 - self.navigation_start_precise) / 1000000 as f64;
         Finite::wrap(now)
     }
+
+    // https://w3c.github.io/performance-timeline/#dom-performance-getentries
+    fn GetEntries(&self) -> Vec<Root<PerformanceEntry>> {
+        entries_matching(&self.entries.borrow(), |_| true)
+    }
+
+    // https://w3c.github.io/performance-timeline/#dom-performance-getentriesbytype
+    fn GetEntriesByType(&self, entry_type: DOMString) -> Vec<Root<PerformanceEntry>> {
+        entries_matching(&self.entries.borrow(), |entry| *entry.entry_type() == entry_type)
+    }
+
+    // https://w3c.github.io/performance-timeline/#dom-performance-getentriesbyname
+    fn GetEntriesByName(&self, name: DOMString, entry_type: Option<DOMString>) -> Vec<Root<PerformanceEntry>> {
+        entries_matching(&self.entries.borrow(), |entry| {
+            *entry.name() == name && entry_type.as_ref().map_or(true, |t| entry.entry_type() == t)
+        })
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performance-clearresourcetimings
+    fn ClearResourceTimings(&self) {
+        self.entries.borrow_mut().retain(|entry| &**entry.entry_type() != "resource");
+        self.resource_timing_buffer_full.set(false);
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performance-setresourcetimingbuffersize
+    fn SetResourceTimingBufferSize(&self, max_size: u32) {
+        self.resource_timing_buffer_size_limit.set(max_size as usize);
+        self.resource_timing_buffer_full.set(false);
+    }
+
+    // https://w3c.github.io/user-timing/#dom-performance-mark
+    fn Mark(&self, mark_name: DOMString) -> ErrorResult {
+        if self.timing.is_some() && NAVIGATION_TIMING_ATTRIBUTES.contains(&&*mark_name) {
+            return Err(Error::Syntax);
+        }
+        let global = self.global();
+        let mark = PerformanceMark::new(global.r(), mark_name, *self.Now());
+        self.queue_entry(mark.upcast());
+        Ok(())
+    }
+
+    // https://w3c.github.io/user-timing/#dom-performance-clearmarks
+    fn ClearMarks(&self, mark_name: Option<DOMString>) {
+        self.clear_entries("mark", mark_name);
+    }
+
+    // https://w3c.github.io/user-timing/#dom-performance-measure
+    fn Measure(&self,
+               measure_name: DOMString,
+               start_mark: Option<DOMString>,
+               end_mark: Option<DOMString>) -> ErrorResult {
+        let end_time = match end_mark {
+            Some(ref end_mark) => try!(self.convert_name_to_timestamp(end_mark)),
+            None => *self.Now(),
+        };
+        let start_time = match start_mark {
+            Some(ref start_mark) => try!(self.convert_name_to_timestamp(start_mark)),
+            None => 0.,
+        };
+        let global = self.global();
+        let measure = PerformanceMeasure::new(global.r(), measure_name, start_time, end_time - start_time);
+        self.queue_entry(measure.upcast());
+        Ok(())
+    }
+
+    // https://w3c.github.io/user-timing/#dom-performance-clearmeasures
+    fn ClearMeasures(&self, measure_name: Option<DOMString>) {
+        self.clear_entries("measure", measure_name);
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performance-onresourcetimingbufferfull
+    event_handler!(resourcetimingbufferfull, GetOnresourcetimingbufferfull, SetOnresourcetimingbufferfull);
+}
+
+struct NotifyPerformanceObserversRunnable {
+    owner: Trusted<Performance>,
+}
+
+impl Runnable for NotifyPerformanceObserversRunnable {
+    fn name(&self) -> &'static str { "NotifyPerformanceObserversRunnable" }
+
+    fn handler(self: Box<NotifyPerformanceObserversRunnable>) {
+        self.owner.root().notify_observers();
+    }
 }
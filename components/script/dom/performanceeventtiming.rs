@@ -0,0 +1,95 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::PerformanceEventTimingBinding;
+use dom::bindings::codegen::Bindings::PerformanceEventTimingBinding::PerformanceEventTimingMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::node::Node;
+use dom::performance::DOMHighResTimeStamp;
+use dom::performanceentry::PerformanceEntry;
+
+/// The times of an event, relative to the time origin, in milliseconds.
+#[derive(Clone, Copy)]
+pub struct EventTimes {
+    /// When the input that caused the event was received.
+    pub start_time: f64,
+    pub processing_start: f64,
+    pub processing_end: f64,
+    /// How long it took from the input until the event was processed.
+    pub duration: f64,
+}
+
+// https://w3c.github.io/event-timing/#sec-performance-event-timing
+#[dom_struct]
+pub struct PerformanceEventTiming {
+    entry: PerformanceEntry,
+    processing_start: f64,
+    processing_end: f64,
+    cancelable: bool,
+    target: Option<JS<Node>>,
+}
+
+impl PerformanceEventTiming {
+    fn new_inherited(entry_type: DOMString,
+                     event_type: DOMString,
+                     times: EventTimes,
+                     cancelable: bool,
+                     target: Option<&Node>)
+                     -> PerformanceEventTiming {
+        PerformanceEventTiming {
+            entry: PerformanceEntry::new_inherited(event_type, entry_type, times.start_time, times.duration),
+            processing_start: times.processing_start,
+            processing_end: times.processing_end,
+            cancelable: cancelable,
+            target: target.map(JS::from_ref),
+        }
+    }
+
+    /// Creates an `event` entry, or a `first-input` one for the first input of a page.
+    pub fn new(global: GlobalRef,
+               entry_type: &str,
+               event_type: DOMString,
+               times: EventTimes,
+               cancelable: bool,
+               target: Option<&Node>)
+               -> Root<PerformanceEventTiming> {
+        reflect_dom_object(box PerformanceEventTiming::new_inherited(DOMString::from(entry_type),
+                                                                     event_type,
+                                                                     times,
+                                                                     cancelable,
+                                                                     target),
+                           global,
+                           PerformanceEventTimingBinding::Wrap)
+    }
+}
+
+impl PerformanceEventTimingMethods for PerformanceEventTiming {
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-processingstart
+    fn ProcessingStart(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.processing_start)
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-processingend
+    fn ProcessingEnd(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.processing_end)
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-cancelable
+    fn Cancelable(&self) -> bool {
+        self.cancelable
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-target
+    fn GetTarget(&self) -> Option<Root<Node>> {
+        // Nodes that have since been removed from the document are not exposed.
+        match self.target {
+            Some(ref target) if target.is_in_doc() => Some(Root::from_ref(&**target)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::PerformanceMarkBinding;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::performanceentry::PerformanceEntry;
+
+// https://w3c.github.io/user-timing/#performancemark
+#[dom_struct]
+pub struct PerformanceMark {
+    entry: PerformanceEntry,
+}
+
+impl PerformanceMark {
+    fn new_inherited(name: DOMString, start_time: f64) -> PerformanceMark {
+        PerformanceMark {
+            entry: PerformanceEntry::new_inherited(name, DOMString::from("mark"), start_time, 0.),
+        }
+    }
+
+    pub fn new(global: GlobalRef, name: DOMString, start_time: f64) -> Root<PerformanceMark> {
+        reflect_dom_object(box PerformanceMark::new_inherited(name, start_time),
+                           global,
+                           PerformanceMarkBinding::Wrap)
+    }
+}
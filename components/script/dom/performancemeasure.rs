@@ -0,0 +1,33 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::PerformanceMeasureBinding;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::performanceentry::PerformanceEntry;
+
+// https://w3c.github.io/user-timing/#performancemeasure
+#[dom_struct]
+pub struct PerformanceMeasure {
+    entry: PerformanceEntry,
+}
+
+impl PerformanceMeasure {
+    fn new_inherited(name: DOMString, start_time: f64, duration: f64) -> PerformanceMeasure {
+        PerformanceMeasure {
+            entry: PerformanceEntry::new_inherited(name, DOMString::from("measure"), start_time, duration),
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               name: DOMString,
+               start_time: f64,
+               duration: f64) -> Root<PerformanceMeasure> {
+        reflect_dom_object(box PerformanceMeasure::new_inherited(name, start_time, duration),
+                           global,
+                           PerformanceMeasureBinding::Wrap)
+    }
+}
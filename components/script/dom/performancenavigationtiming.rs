@@ -0,0 +1,125 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::PerformanceNavigationTimingBinding;
+use dom::bindings::codegen::Bindings::PerformanceNavigationTimingBinding::NavigationType;
+use dom::bindings::codegen::Bindings::PerformanceNavigationTimingBinding::PerformanceNavigationTimingMethods;
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::document::Document;
+use dom::performance::DOMHighResTimeStamp;
+use dom::performanceresourcetiming::PerformanceResourceTiming;
+use dom::window::Window;
+
+// https://w3c.github.io/navigation-timing/#sec-PerformanceNavigationTiming
+#[dom_struct]
+pub struct PerformanceNavigationTiming {
+    resourcetiming: PerformanceResourceTiming,
+    /// The time the navigation started, in milliseconds since the epoch.
+    navigation_start: u64,
+    document: JS<Document>,
+}
+
+impl PerformanceNavigationTiming {
+    fn new_inherited(navigation_start: u64,
+                     navigation_start_precise: f64,
+                     document: &Document) -> PerformanceNavigationTiming {
+        let name = DOMString::from(document.url().into_string());
+        PerformanceNavigationTiming {
+            resourcetiming: PerformanceResourceTiming::new_inherited(name,
+                                                                     DOMString::from("navigation"),
+                                                                     DOMString::from("navigation"),
+                                                                     navigation_start_precise,
+                                                                     Default::default(),
+                                                                     vec![],
+                                                                     Some(document)),
+            navigation_start: navigation_start,
+            document: JS::from_ref(document),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(window: &Window,
+               navigation_start: u64,
+               navigation_start_precise: f64) -> Root<PerformanceNavigationTiming> {
+        let timing = PerformanceNavigationTiming::new_inherited(navigation_start,
+                                                                navigation_start_precise,
+                                                                window.Document().r());
+        reflect_dom_object(box timing,
+                           GlobalRef::Window(window),
+                           PerformanceNavigationTimingBinding::Wrap)
+    }
+
+    /// The document's timestamps are milliseconds since the epoch; zero means
+    /// the event hasn't happened yet.
+    fn to_high_res(&self, time: u64) -> DOMHighResTimeStamp {
+        if time == 0 {
+            return Finite::wrap(0.);
+        }
+        Finite::wrap(time.saturating_sub(self.navigation_start) as f64)
+    }
+
+    // https://w3c.github.io/navigation-timing/#duration-attribute
+    pub fn duration(&self) -> f64 {
+        *self.LoadEventEnd()
+    }
+}
+
+impl PerformanceNavigationTimingMethods for PerformanceNavigationTiming {
+    // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-unloadeventstart
+    fn UnloadEventStart(&self) -> DOMHighResTimeStamp {
+        // TODO: record the unload of the previous document.
+        Finite::wrap(0.)
+    }
+
+    // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-unloadeventend
+    fn UnloadEventEnd(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(0.)
+    }
+
+    // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-dominteractive
+    fn DomInteractive(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.document.get_dom_interactive())
+    }
+
+    // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-domcontentloadedeventstart
+    fn DomContentLoadedEventStart(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.document.get_dom_content_loaded_event_start())
+    }
+
+    // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-domcontentloadedeventend
+    fn DomContentLoadedEventEnd(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.document.get_dom_content_loaded_event_end())
+    }
+
+    // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-domcomplete
+    fn DomComplete(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.document.get_dom_complete())
+    }
+
+    // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-loadeventstart
+    fn LoadEventStart(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.document.get_load_event_start())
+    }
+
+    // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-loadeventend
+    fn LoadEventEnd(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.document.get_load_event_end())
+    }
+
+    // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-type
+    fn Type(&self) -> NavigationType {
+        // TODO: distinguish reloads and history traversals.
+        NavigationType::Navigate
+    }
+
+    // https://w3c.github.io/navigation-timing/#dom-performancenavigationtiming-redirectcount
+    fn RedirectCount(&self) -> u16 {
+        self.resourcetiming.fetch_timing().redirect_count
+    }
+}
@@ -0,0 +1,213 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::callback::ExceptionHandling;
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use dom::bindings::codegen::Bindings::PerformanceEntryBinding::PerformanceEntryMethods;
+use dom::bindings::codegen::Bindings::PerformanceObserverBinding;
+use dom::bindings::codegen::Bindings::PerformanceObserverBinding::PerformanceObserverCallback;
+use dom::bindings::codegen::Bindings::PerformanceObserverBinding::PerformanceObserverInit;
+use dom::bindings::codegen::Bindings::PerformanceObserverBinding::PerformanceObserverMethods;
+use dom::bindings::conversions::ToJSValConvertible;
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::performanceentry::PerformanceEntry;
+use dom::performanceobserverentrylist::PerformanceObserverEntryList;
+use js::jsapi::JSContext;
+use js::jsval::{JSVal, UndefinedValue};
+use std::cell::Cell;
+use std::mem;
+use std::rc::Rc;
+
+/// The entry types that are queued to observers in this implementation.
+/// https://w3c.github.io/performance-timeline/#supportedentrytypes-attribute
+const SUPPORTED_ENTRY_TYPES: &'static [&'static str] = &[
+    "event",
+    "first-input",
+    "largest-contentful-paint",
+    "mark",
+    "measure",
+    "navigation",
+    "paint",
+    "resource",
+];
+
+/// The shortest event an observer is told about, unless it asks for shorter ones.
+/// https://w3c.github.io/event-timing/#sec-modifications-perf-timeline
+const DEFAULT_DURATION_THRESHOLD: f64 = 104.;
+
+/// The shortest event an observer can ask to be told about.
+const MINIMUM_DURATION_THRESHOLD: f64 = 16.;
+
+/// https://w3c.github.io/performance-timeline/#dfn-observer-type
+#[derive(Clone, Copy, HeapSizeOf, JSTraceable, PartialEq)]
+enum ObserverType {
+    Undefined,
+    Single,
+    Multiple,
+}
+
+// https://w3c.github.io/performance-timeline/#the-performanceobserver-interface
+#[dom_struct]
+pub struct PerformanceObserver {
+    reflector_: Reflector,
+    #[ignore_heap_size_of = "can't measure Rc values"]
+    callback: Rc<PerformanceObserverCallback>,
+    /// https://w3c.github.io/performance-timeline/#dfn-observer-buffer
+    entries: DOMRefCell<Vec<JS<PerformanceEntry>>>,
+    /// The entry types this observer was registered for by `observe()`.
+    entry_types: DOMRefCell<Vec<DOMString>>,
+    observer_type: Cell<ObserverType>,
+    /// The shortest `event` entry this observer is told about.
+    duration_threshold: Cell<f64>,
+}
+
+impl PerformanceObserver {
+    fn new_inherited(callback: Rc<PerformanceObserverCallback>) -> PerformanceObserver {
+        PerformanceObserver {
+            reflector_: Reflector::new(),
+            callback: callback,
+            entries: DOMRefCell::new(vec![]),
+            entry_types: DOMRefCell::new(vec![]),
+            observer_type: Cell::new(ObserverType::Undefined),
+            duration_threshold: Cell::new(DEFAULT_DURATION_THRESHOLD),
+        }
+    }
+
+    pub fn new(global: GlobalRef, callback: Rc<PerformanceObserverCallback>) -> Root<PerformanceObserver> {
+        reflect_dom_object(box PerformanceObserver::new_inherited(callback),
+                           global,
+                           PerformanceObserverBinding::Wrap)
+    }
+
+    pub fn Constructor(global: GlobalRef, callback: Rc<PerformanceObserverCallback>)
+                       -> Fallible<Root<PerformanceObserver>> {
+        Ok(PerformanceObserver::new(global, callback))
+    }
+
+    // https://w3c.github.io/performance-timeline/#dom-performanceobserver-supportedentrytypes
+    #[allow(unsafe_code)]
+    pub fn SupportedEntryTypes(cx: *mut JSContext, _: GlobalRef) -> JSVal {
+        let entry_types: Vec<DOMString> = SUPPORTED_ENTRY_TYPES.iter()
+            .map(|entry_type| DOMString::from(*entry_type))
+            .collect();
+        rooted!(in(cx) let mut value = UndefinedValue());
+        unsafe { entry_types.to_jsval(cx, value.handle_mut()) };
+        value.get()
+    }
+
+    /// Whether entries of this type are delivered to this observer.
+    pub fn observes(&self, entry_type: &DOMString) -> bool {
+        self.entry_types.borrow().iter().any(|observed| observed == entry_type)
+    }
+
+    /// Whether this observer is told about `entry`: it observes its type, and an
+    /// event took as long as it asked for.
+    pub fn observes_entry(&self, entry: &PerformanceEntry) -> bool {
+        self.observes(entry.entry_type()) &&
+        (&**entry.entry_type() != "event" || *entry.Duration() >= self.duration_threshold.get())
+    }
+
+    /// Appends an entry to the observer buffer.
+    pub fn queue_entry(&self, entry: &PerformanceEntry) {
+        self.entries.borrow_mut().push(JS::from_ref(entry));
+    }
+
+    /// Invokes the callback with the entries buffered since the last notification.
+    /// https://w3c.github.io/performance-timeline/#dfn-report-the-performance-timeline-task
+    #[allow(unrooted_must_root)]
+    pub fn notify(&self) {
+        let entries = mem::replace(&mut *self.entries.borrow_mut(), vec![]);
+        if entries.is_empty() {
+            return;
+        }
+        let global = self.global();
+        let entry_list = PerformanceObserverEntryList::new(global.r(), entries);
+        let _ = self.callback.Call_(self, &entry_list, self, ExceptionHandling::Report);
+    }
+}
+
+fn is_supported_entry_type(entry_type: &DOMString) -> bool {
+    SUPPORTED_ENTRY_TYPES.iter().any(|supported| **entry_type == **supported)
+}
+
+impl PerformanceObserverMethods for PerformanceObserver {
+    // https://w3c.github.io/performance-timeline/#dom-performanceobserver-observe
+    fn Observe(&self, options: &PerformanceObserverInit) -> ErrorResult {
+        // Steps 3-4.
+        let observer_type = match (&options.entryTypes, &options.type_) {
+            (&Some(_), &None) => ObserverType::Multiple,
+            (&None, &Some(_)) => ObserverType::Single,
+            _ => return Err(Error::Type("exactly one of entryTypes and type must be given".to_owned())),
+        };
+
+        // Steps 5-6.
+        if self.observer_type.get() == ObserverType::Undefined {
+            self.observer_type.set(observer_type);
+        } else if self.observer_type.get() != observer_type {
+            return Err(Error::InvalidModification);
+        }
+
+        let global = self.global();
+        let performance = global.r().performance();
+        match (&options.entryTypes, &options.type_) {
+            // Step 7.
+            (&Some(ref entry_types), _) => {
+                let entry_types: Vec<DOMString> = entry_types.iter()
+                    .filter(|entry_type| is_supported_entry_type(entry_type))
+                    .cloned()
+                    .collect();
+                if entry_types.is_empty() {
+                    return Ok(());
+                }
+                *self.entry_types.borrow_mut() = entry_types;
+            },
+            // Step 8.
+            (_, &Some(ref entry_type)) => {
+                if !is_supported_entry_type(entry_type) {
+                    return Ok(());
+                }
+                if !self.observes(entry_type) {
+                    self.entry_types.borrow_mut().push(entry_type.clone());
+                }
+                if let Some(threshold) = options.durationThreshold {
+                    self.duration_threshold.set((*threshold).max(MINIMUM_DURATION_THRESHOLD));
+                }
+                if options.buffered {
+                    let buffered = performance.GetEntriesByType(entry_type.clone());
+                    if !buffered.is_empty() {
+                        for entry in &buffered {
+                            self.queue_entry(entry);
+                        }
+                        performance.queue_observer_notification();
+                    }
+                }
+            },
+            _ => unreachable!(),
+        }
+
+        performance.add_observer(self);
+        Ok(())
+    }
+
+    // https://w3c.github.io/performance-timeline/#dom-performanceobserver-disconnect
+    fn Disconnect(&self) {
+        self.global().r().performance().remove_observer(self);
+        self.entries.borrow_mut().clear();
+        self.entry_types.borrow_mut().clear();
+        self.observer_type.set(ObserverType::Undefined);
+        self.duration_threshold.set(DEFAULT_DURATION_THRESHOLD);
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/performance-timeline/#dom-performanceobserver-takerecords
+    fn TakeRecords(&self) -> Vec<Root<PerformanceEntry>> {
+        let entries = mem::replace(&mut *self.entries.borrow_mut(), vec![]);
+        entries.iter().map(|entry| Root::from_ref(&**entry)).collect()
+    }
+}
@@ -0,0 +1,54 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::PerformanceObserverEntryListBinding;
+use dom::bindings::codegen::Bindings::PerformanceObserverEntryListBinding::PerformanceObserverEntryListMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::performance::entries_matching;
+use dom::performanceentry::PerformanceEntry;
+
+// https://w3c.github.io/performance-timeline/#performanceobserverentrylist-interface
+#[dom_struct]
+pub struct PerformanceObserverEntryList {
+    reflector_: Reflector,
+    entries: Vec<JS<PerformanceEntry>>,
+}
+
+impl PerformanceObserverEntryList {
+    fn new_inherited(entries: Vec<JS<PerformanceEntry>>) -> PerformanceObserverEntryList {
+        PerformanceObserverEntryList {
+            reflector_: Reflector::new(),
+            entries: entries,
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(global: GlobalRef, entries: Vec<JS<PerformanceEntry>>) -> Root<PerformanceObserverEntryList> {
+        reflect_dom_object(box PerformanceObserverEntryList::new_inherited(entries),
+                           global,
+                           PerformanceObserverEntryListBinding::Wrap)
+    }
+}
+
+impl PerformanceObserverEntryListMethods for PerformanceObserverEntryList {
+    // https://w3c.github.io/performance-timeline/#dom-performanceobserverentrylist-getentries
+    fn GetEntries(&self) -> Vec<Root<PerformanceEntry>> {
+        entries_matching(&self.entries, |_| true)
+    }
+
+    // https://w3c.github.io/performance-timeline/#dom-performanceobserverentrylist-getentriesbytype
+    fn GetEntriesByType(&self, entry_type: DOMString) -> Vec<Root<PerformanceEntry>> {
+        entries_matching(&self.entries, |entry| *entry.entry_type() == entry_type)
+    }
+
+    // https://w3c.github.io/performance-timeline/#dom-performanceobserverentrylist-getentriesbyname
+    fn GetEntriesByName(&self, name: DOMString, entry_type: Option<DOMString>) -> Vec<Root<PerformanceEntry>> {
+        entries_matching(&self.entries, |entry| {
+            *entry.name() == name && entry_type.as_ref().map_or(true, |t| entry.entry_type() == t)
+        })
+    }
+}
@@ -0,0 +1,35 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::PerformancePaintTimingBinding;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::performanceentry::PerformanceEntry;
+use script_traits::PaintMetricType;
+
+// https://w3c.github.io/paint-timing/#sec-PerformancePaintTiming
+#[dom_struct]
+pub struct PerformancePaintTiming {
+    entry: PerformanceEntry,
+}
+
+impl PerformancePaintTiming {
+    fn new_inherited(metric_type: PaintMetricType, start_time: f64) -> PerformancePaintTiming {
+        let name = match metric_type {
+            PaintMetricType::FirstPaint => DOMString::from("first-paint"),
+            PaintMetricType::FirstContentfulPaint => DOMString::from("first-contentful-paint"),
+        };
+        PerformancePaintTiming {
+            entry: PerformanceEntry::new_inherited(name, DOMString::from("paint"), start_time, 0.),
+        }
+    }
+
+    pub fn new(global: GlobalRef, metric_type: PaintMetricType, start_time: f64) -> Root<PerformancePaintTiming> {
+        reflect_dom_object(box PerformancePaintTiming::new_inherited(metric_type, start_time),
+                           global,
+                           PerformancePaintTimingBinding::Wrap)
+    }
+}
@@ -0,0 +1,216 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::PerformanceEntryBinding::PerformanceEntryMethods;
+use dom::bindings::codegen::Bindings::PerformanceResourceTimingBinding;
+use dom::bindings::codegen::Bindings::PerformanceResourceTimingBinding::PerformanceResourceTimingMethods;
+use dom::bindings::conversions::ToJSValConvertible;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::document::Document;
+use dom::performance::DOMHighResTimeStamp;
+use dom::performanceentry::PerformanceEntry;
+use dom::performancenavigationtiming::PerformanceNavigationTiming;
+use dom::performanceservertiming::{PerformanceServerTiming, ServerTimingMetric};
+use js::jsapi::JSContext;
+use js::jsval::{JSVal, UndefinedValue};
+use net_traits::ResourceFetchTiming;
+use net_traits::request::Destination;
+
+#[dom_struct]
+pub struct PerformanceResourceTiming {
+    entry: PerformanceEntry,
+    initiator_type: DOMString,
+    /// The `time::precise_time_ns` value the timestamps are relative to.
+    navigation_start_precise: f64,
+    timing: ResourceFetchTiming,
+    server_timing: Vec<ServerTimingMetric>,
+    /// For navigation entries, the document whose fetch is being timed; its
+    /// timing is read live since the response may still be arriving.
+    document: Option<JS<Document>>,
+}
+
+impl PerformanceResourceTiming {
+    pub fn new_inherited(name: DOMString,
+                         entry_type: DOMString,
+                         initiator_type: DOMString,
+                         navigation_start_precise: f64,
+                         timing: ResourceFetchTiming,
+                         server_timing: Vec<ServerTimingMetric>,
+                         document: Option<&Document>) -> PerformanceResourceTiming {
+        let start_time = match document {
+            Some(_) => 0.,
+            None => relative_time(timing.fetch_start, navigation_start_precise),
+        };
+        PerformanceResourceTiming {
+            entry: PerformanceEntry::new_inherited(name, entry_type, start_time, 0.),
+            initiator_type: initiator_type,
+            navigation_start_precise: navigation_start_precise,
+            timing: timing,
+            server_timing: server_timing,
+            document: document.map(JS::from_ref),
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    pub fn new(global: GlobalRef,
+               name: DOMString,
+               initiator_type: DOMString,
+               navigation_start_precise: f64,
+               timing: ResourceFetchTiming,
+               server_timing: Vec<ServerTimingMetric>) -> Root<PerformanceResourceTiming> {
+        let resource = PerformanceResourceTiming::new_inherited(name,
+                                                                DOMString::from("resource"),
+                                                                initiator_type,
+                                                                navigation_start_precise,
+                                                                timing,
+                                                                server_timing,
+                                                                None);
+        reflect_dom_object(box resource, global, PerformanceResourceTimingBinding::Wrap)
+    }
+
+    pub fn fetch_timing(&self) -> ResourceFetchTiming {
+        match self.document {
+            Some(ref document) => document.fetch_timing(),
+            None => self.timing,
+        }
+    }
+
+    fn server_timing(&self) -> Vec<ServerTimingMetric> {
+        match self.document {
+            Some(ref document) => document.server_timing(),
+            None => self.server_timing.clone(),
+        }
+    }
+
+    fn to_high_res(&self, time: u64) -> DOMHighResTimeStamp {
+        Finite::wrap(relative_time(time, self.navigation_start_precise))
+    }
+
+    /// https://w3c.github.io/resource-timing/#dom-performanceentry-duration
+    pub fn duration(&self) -> f64 {
+        if let Some(navigation) = self.downcast::<PerformanceNavigationTiming>() {
+            return navigation.duration();
+        }
+        let end = relative_time(self.fetch_timing().response_end, self.navigation_start_precise);
+        if end == 0. {
+            return 0.;
+        }
+        end - *self.entry.StartTime()
+    }
+}
+
+/// The `initiatorType` of a fetch with the given request destination.
+/// https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-initiatortype
+pub fn initiator_type(destination: Destination) -> DOMString {
+    DOMString::from(match destination {
+        Destination::None => "fetch",
+        Destination::Script => "script",
+        Destination::Image => "img",
+        Destination::Style => "link",
+        Destination::Font => "css",
+        Destination::Embed => "embed",
+        Destination::Object => "object",
+        _ => "other",
+    })
+}
+
+/// Converts a `time::precise_time_ns` value to milliseconds since `origin`,
+/// keeping zero (a step that didn't happen) as zero.
+fn relative_time(time: u64, origin: f64) -> f64 {
+    if time == 0 {
+        return 0.;
+    }
+    (time as f64 - origin) / 1000000 as f64
+}
+
+impl PerformanceResourceTimingMethods for PerformanceResourceTiming {
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-initiatortype
+    fn InitiatorType(&self) -> DOMString {
+        self.initiator_type.clone()
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-nexthopprotocol
+    fn NextHopProtocol(&self) -> DOMString {
+        // Our HTTP stack only speaks HTTP/1.1.
+        DOMString::from("http/1.1")
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-workerstart
+    fn WorkerStart(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(0.)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-redirectstart
+    fn RedirectStart(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().redirect_start)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-redirectend
+    fn RedirectEnd(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().redirect_end)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-fetchstart
+    fn FetchStart(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().fetch_start)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-domainlookupstart
+    fn DomainLookupStart(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().domain_lookup_start)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-domainlookupend
+    fn DomainLookupEnd(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().domain_lookup_end)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-connectstart
+    fn ConnectStart(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().connect_start)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-connectend
+    fn ConnectEnd(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().connect_end)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-secureconnectionstart
+    fn SecureConnectionStart(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().secure_connection_start)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-requeststart
+    fn RequestStart(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().request_start)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-responsestart
+    fn ResponseStart(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().response_start)
+    }
+
+    // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-responseend
+    fn ResponseEnd(&self) -> DOMHighResTimeStamp {
+        self.to_high_res(self.fetch_timing().response_end)
+    }
+
+    // FIXME: the spec requires the same frozen array to be returned every time.
+    #[allow(unsafe_code)]
+    // https://w3c.github.io/server-timing/#dom-performanceresourcetiming-servertiming
+    fn ServerTiming(&self, cx: *mut JSContext) -> JSVal {
+        let global = self.global();
+        let entries: Vec<Root<PerformanceServerTiming>> = self.server_timing().into_iter()
+            .map(|metric| PerformanceServerTiming::new(global.r(), metric))
+            .collect();
+        rooted!(in(cx) let mut value = UndefinedValue());
+        unsafe { entries.to_jsval(cx, value.handle_mut()) };
+        value.get()
+    }
+}
@@ -0,0 +1,162 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::PerformanceServerTimingBinding;
+use dom::bindings::codegen::Bindings::PerformanceServerTimingBinding::PerformanceServerTimingMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::performance::DOMHighResTimeStamp;
+use net_traits::Metadata;
+use std::ascii::AsciiExt;
+
+/// One metric of a Server-Timing header.
+/// https://w3c.github.io/server-timing/#the-server-timing-header-field
+#[derive(Clone, Debug, HeapSizeOf, JSTraceable, PartialEq)]
+pub struct ServerTimingMetric {
+    pub name: String,
+    /// In milliseconds.
+    pub duration: f64,
+    pub description: String,
+}
+
+/// Reads the metrics out of every Server-Timing header of a response.
+///
+/// FIXME: Metrics sent in HTTP trailers are not included, since our HTTP stack
+/// drops the trailers of chunked responses.
+pub fn server_timing_metrics(metadata: &Metadata) -> Vec<ServerTimingMetric> {
+    let values = match metadata.headers.as_ref().and_then(|headers| headers.get_raw("Server-Timing")) {
+        Some(values) => values,
+        None => return vec![],
+    };
+    values.iter()
+          .flat_map(|value| parse_server_timing(&String::from_utf8_lossy(value)))
+          .collect()
+}
+
+/// Parses the value of a Server-Timing header. Metrics with an invalid name are
+/// skipped, and only the first `dur` and `desc` parameters of a metric are used.
+/// https://w3c.github.io/server-timing/#the-server-timing-header-field
+pub fn parse_server_timing(value: &str) -> Vec<ServerTimingMetric> {
+    split_unquoted(value, ',').into_iter().filter_map(|metric| {
+        let mut parts = split_unquoted(metric, ';').into_iter();
+        let name = parts.next().unwrap_or("").trim();
+        if !is_token(name) {
+            return None;
+        }
+        let mut duration = None;
+        let mut description = None;
+        for param in parts {
+            let (param_name, param_value) = match param.find('=') {
+                Some(index) => (&param[..index], unquote(param[index + 1..].trim())),
+                None => (param, String::new()),
+            };
+            match &*param_name.trim().to_ascii_lowercase() {
+                "dur" if duration.is_none() => {
+                    let value: f64 = param_value.parse().unwrap_or(0.);
+                    duration = Some(if value.is_finite() { value } else { 0. });
+                }
+                "desc" if description.is_none() => {
+                    description = Some(param_value);
+                }
+                _ => {}
+            }
+        }
+        Some(ServerTimingMetric {
+            name: name.to_owned(),
+            duration: duration.unwrap_or(0.),
+            description: description.unwrap_or_else(String::new),
+        })
+    }).collect()
+}
+
+/// Splits `value` at each `separator` that isn't inside a quoted string.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (index, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if in_quotes && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            in_quotes = !in_quotes;
+        } else if c == separator && !in_quotes {
+            parts.push(&value[start..index]);
+            start = index + c.len_utf8();
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+/// https://tools.ietf.org/html/rfc7230#section-3.2.6
+fn is_token(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| {
+        match b {
+            b'a'...b'z' | b'A'...b'Z' | b'0'...b'9' => true,
+            _ => b"!#$%&'*+-.^_`|~".contains(&b),
+        }
+    })
+}
+
+/// Removes the quotes and escapes of a quoted string; tokens are returned as they are.
+fn unquote(value: &str) -> String {
+    if !value.starts_with('"') {
+        return value.to_owned();
+    }
+    let mut result = String::new();
+    let mut chars = value[1..].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => result.extend(chars.next()),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+// https://w3c.github.io/server-timing/#the-performanceservertiming-interface
+#[dom_struct]
+pub struct PerformanceServerTiming {
+    reflector_: Reflector,
+    metric: ServerTimingMetric,
+}
+
+impl PerformanceServerTiming {
+    fn new_inherited(metric: ServerTimingMetric) -> PerformanceServerTiming {
+        PerformanceServerTiming {
+            reflector_: Reflector::new(),
+            metric: metric,
+        }
+    }
+
+    pub fn new(global: GlobalRef, metric: ServerTimingMetric) -> Root<PerformanceServerTiming> {
+        reflect_dom_object(box PerformanceServerTiming::new_inherited(metric),
+                           global,
+                           PerformanceServerTimingBinding::Wrap)
+    }
+}
+
+impl PerformanceServerTimingMethods for PerformanceServerTiming {
+    // https://w3c.github.io/server-timing/#dom-performanceservertiming-name
+    fn Name(&self) -> DOMString {
+        DOMString::from(self.metric.name.clone())
+    }
+
+    // https://w3c.github.io/server-timing/#dom-performanceservertiming-duration
+    fn Duration(&self) -> DOMHighResTimeStamp {
+        Finite::wrap(self.metric.duration)
+    }
+
+    // https://w3c.github.io/server-timing/#dom-performanceservertiming-description
+    fn Description(&self) -> DOMString {
+        DOMString::from(self.metric.description.clone())
+    }
+}
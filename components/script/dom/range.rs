@@ -14,7 +14,7 @@ use dom::bindings::error::{Error, ErrorResult, Fallible};
 use dom::bindings::global::GlobalRef;
 use dom::bindings::inheritance::{CharacterDataTypeId, NodeTypeId};
 use dom::bindings::inheritance::Castable;
-use dom::bindings::js::{JS, MutHeap, Root, RootedReference};
+use dom::bindings::js::{JS, MutHeap, MutNullableHeap, Root, RootedReference};
 use dom::bindings::reflector::{Reflector, reflect_dom_object};
 use dom::bindings::str::DOMString;
 use dom::bindings::trace::JSTraceable;
@@ -26,6 +26,7 @@ use dom::element::Element;
 use dom::htmlbodyelement::HTMLBodyElement;
 use dom::htmlscriptelement::HTMLScriptElement;
 use dom::node::{Node, UnbindContext};
+use dom::selection::Selection;
 use dom::text::Text;
 use heapsize::HeapSizeOf;
 use js::jsapi::JSTracer;
@@ -37,6 +38,8 @@ pub struct Range {
     reflector_: Reflector,
     start: BoundaryPoint,
     end: BoundaryPoint,
+    /// The selection this range is associated with, if any.
+    selection: MutNullableHeap<JS<Selection>>,
 }
 
 impl Range {
@@ -46,6 +49,7 @@ impl Range {
             reflector_: Reflector::new(),
             start: BoundaryPoint::new(start_container, start_offset),
             end: BoundaryPoint::new(end_container, end_offset),
+            selection: MutNullableHeap::new(None),
         }
     }
 
@@ -131,6 +135,18 @@ impl Range {
         Ok((first_contained_child, last_contained_child, contained_children))
     }
 
+    /// Associates this range with a selection, or dissociates it if `selection` is `None`.
+    pub fn set_selection(&self, selection: Option<&Selection>) {
+        self.selection.set(selection);
+    }
+
+    // https://w3c.github.io/selection-api/#responding-to-dom-mutations
+    fn notify_selection(&self) {
+        if let Some(selection) = self.selection.get() {
+            selection.range_changed();
+        }
+    }
+
     // https://dom.spec.whatwg.org/#concept-range-bp-set
     fn set_start(&self, node: &Node, offset: u32) {
         if &self.start.node != node {
@@ -143,6 +159,7 @@ impl Range {
             }
         }
         self.start.set(node, offset);
+        self.notify_selection();
     }
 
     // https://dom.spec.whatwg.org/#concept-range-bp-set
@@ -157,6 +174,7 @@ impl Range {
             }
         }
         self.end.set(node, offset);
+        self.notify_selection();
     }
 
     // https://dom.spec.whatwg.org/#dom-range-comparepointnode-offset
@@ -982,7 +1000,7 @@ impl PartialEq for BoundaryPoint {
 }
 
 // https://dom.spec.whatwg.org/#concept-range-bp-position
-fn bp_position(a_node: &Node, a_offset: u32,
+pub fn bp_position(a_node: &Node, a_offset: u32,
                b_node: &Node, b_offset: u32)
                -> Option<Ordering> {
     if a_node as *const Node == b_node as *const Node {
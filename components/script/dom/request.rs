@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use body::{BodyOperations, BodyType, consume_body};
+use body::{BodyChunks, BodyOperations, BodyType, consume_body};
 use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::HeadersBinding::{HeadersInit, HeadersMethods};
 use dom::bindings::codegen::Bindings::RequestBinding;
@@ -412,6 +412,14 @@ impl Request {
 }
 
 impl Request {
+    /// The request to hand to the fetch algorithm, with the header list held by
+    /// this object's `Headers`.
+    pub fn get_request(&self) -> NetTraitsRequest {
+        let request = self.request.borrow().clone();
+        *request.headers.borrow_mut() = self.Headers().get_headers_list();
+        request
+    }
+
     fn from_net_request(global: GlobalRef,
                         is_service_worker_global_scope: bool,
                         net_request: NetTraitsRequest) -> Root<Request> {
@@ -664,13 +672,13 @@ impl BodyOperations for Request {
         self.locked()
     }
 
-    fn take_body(&self) -> Option<Vec<u8>> {
+    fn take_body(&self) -> Option<BodyChunks> {
         let ref mut net_traits_req = *self.request.borrow_mut();
         let body: Option<Vec<u8>> = mem::replace(&mut *net_traits_req.body.borrow_mut(), None);
         match body {
-            Some(_) => {
+            Some(bytes) => {
                 self.body_used.set(true);
-                body
+                Some(BodyChunks::from_vec(bytes))
             },
             _ => None,
         }
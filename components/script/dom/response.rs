@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use body::{BodyOperations, BodyType, consume_body};
+use body::{BodyChunks, BodyOperations, BodyType, consume_body};
 use core::cell::Cell;
 use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::HeadersBinding::HeadersMethods;
@@ -18,8 +18,9 @@ use dom::headers::{Headers, Guard};
 use dom::headers::{is_vchar, is_obs_text};
 use dom::promise::Promise;
 use dom::xmlhttprequest::Extractable;
+use hyper::header::Headers as HyperHeaders;
 use hyper::status::StatusCode;
-use net_traits::response::{ResponseBody as NetTraitsResponseBody};
+use hyper_serde::Serde;
 use std::mem;
 use std::rc::Rc;
 use std::str::FromStr;
@@ -40,8 +41,8 @@ pub struct Response {
     response_type: DOMRefCell<DOMResponseType>,
     url: DOMRefCell<Option<Url>>,
     url_list: DOMRefCell<Vec<Url>>,
-    // For now use the existing NetTraitsResponseBody enum
-    body: DOMRefCell<NetTraitsResponseBody>,
+    #[ignore_heap_size_of = "May be backed by shared memory"]
+    body: DOMRefCell<Option<BodyChunks>>,
 }
 
 impl Response {
@@ -56,7 +57,7 @@ impl Response {
             response_type: DOMRefCell::new(DOMResponseType::Default),
             url: DOMRefCell::new(None),
             url_list: DOMRefCell::new(vec![]),
-            body: DOMRefCell::new(NetTraitsResponseBody::Empty),
+            body: DOMRefCell::new(None),
         }
     }
 
@@ -108,7 +109,7 @@ impl Response {
 
             // Step 7.3
             let (extracted_body, content_type) = body.extract();
-            *r.body.borrow_mut() = NetTraitsResponseBody::Done(extracted_body);
+            *r.body.borrow_mut() = Some(BodyChunks::from_vec(extracted_body));
 
             // Step 7.4
             if let Some(content_type_contents) = content_type {
@@ -185,6 +186,32 @@ impl Response {
         // for now.
         false
     }
+
+    pub fn set_type(&self, new_response_type: DOMResponseType) {
+        *self.response_type.borrow_mut() = new_response_type;
+    }
+
+    pub fn set_headers(&self, option_hyper_headers: Option<Serde<HyperHeaders>>) {
+        self.Headers().set_headers(match option_hyper_headers {
+            Some(hyper_headers) => hyper_headers.into_inner(),
+            None => HyperHeaders::new(),
+        });
+        *self.mime_type.borrow_mut() = self.Headers().extract_mime_type();
+    }
+
+    pub fn set_raw_status(&self, status: Option<(u16, Vec<u8>)>) {
+        *self.status.borrow_mut() = status.as_ref().map(|&(code, _)| StatusCode::from_u16(code));
+        *self.raw_status.borrow_mut() = status;
+    }
+
+    pub fn set_final_url(&self, final_url: Url) {
+        self.url_list.borrow_mut().push(final_url.clone());
+        *self.url.borrow_mut() = Some(final_url);
+    }
+
+    pub fn set_body(&self, body: BodyChunks) {
+        *self.body.borrow_mut() = Some(body);
+    }
 }
 
 impl BodyOperations for Response {
@@ -196,15 +223,12 @@ impl BodyOperations for Response {
         self.locked()
     }
 
-    fn take_body(&self) -> Option<Vec<u8>> {
-        let body: NetTraitsResponseBody = mem::replace(&mut *self.body.borrow_mut(), NetTraitsResponseBody::Empty);
-        match body {
-            NetTraitsResponseBody::Done(bytes) | NetTraitsResponseBody::Receiving(bytes) => {
-                self.body_used.set(true);
-                Some(bytes)
-            },
-            _ => None,
+    fn take_body(&self) -> Option<BodyChunks> {
+        let body = mem::replace(&mut *self.body.borrow_mut(), None);
+        if body.is_some() {
+            self.body_used.set(true);
         }
+        body
     }
 
     fn get_mime_type(&self) -> Ref<Vec<u8>> {
@@ -300,7 +324,7 @@ impl ResponseMethods for Response {
         *new_response.url.borrow_mut() = self.url.borrow().clone();
         *new_response.url_list.borrow_mut() = self.url_list.borrow().clone();
 
-        if *self.body.borrow() != NetTraitsResponseBody::Empty {
+        if self.body.borrow().is_some() {
             *new_response.body.borrow_mut() = self.body.borrow().clone();
         }
 
@@ -0,0 +1,296 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::BlobBinding::BlobMethods;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::RTCDataChannelBinding;
+use dom::bindings::codegen::Bindings::RTCDataChannelBinding::{RTCDataChannelMethods, RTCDataChannelState};
+use dom::bindings::conversions::ToJSValConvertible;
+use dom::bindings::error::{Error, ErrorResult};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::bindings::str::{DOMString, USVString};
+use dom::blob::{Blob, BlobImpl};
+use dom::eventtarget::EventTarget;
+use dom::messageevent::MessageEvent;
+use dom::rtcpeerconnection::RTCPeerConnection;
+use js::jsapi::{JSAutoCompartment, JS_GetArrayBufferData, JS_NewArrayBuffer};
+use js::jsval::UndefinedValue;
+use libc::{uint32_t, uint8_t};
+use std::cell::Cell;
+use std::ptr;
+use webrtc_traits::{ChannelMessage, ChannelParameters};
+
+// https://w3c.github.io/webrtc-pc/#rtcdatachannel
+#[dom_struct]
+pub struct RTCDataChannel {
+    eventtarget: EventTarget,
+    connection: JS<RTCPeerConnection>,
+    parameters: ChannelParameters,
+    negotiated: bool,
+    /// The stream of the channel, which is chosen once the DTLS role of the
+    /// connection is known unless the channel was negotiated.
+    id: Cell<Option<u16>>,
+    ready_state: Cell<RTCDataChannelState>,
+    buffered_amount: Cell<u64>,
+    buffered_amount_low_threshold: Cell<u64>,
+    binary_type: DOMRefCell<DOMString>,
+}
+
+impl RTCDataChannel {
+    fn new_inherited(connection: &RTCPeerConnection,
+                     parameters: ChannelParameters,
+                     negotiated: bool,
+                     id: Option<u16>)
+                     -> RTCDataChannel {
+        RTCDataChannel {
+            eventtarget: EventTarget::new_inherited(),
+            connection: JS::from_ref(connection),
+            parameters: parameters,
+            negotiated: negotiated,
+            id: Cell::new(id),
+            ready_state: Cell::new(RTCDataChannelState::Connecting),
+            buffered_amount: Cell::new(0),
+            buffered_amount_low_threshold: Cell::new(0),
+            binary_type: DOMRefCell::new(DOMString::from("blob")),
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               connection: &RTCPeerConnection,
+               parameters: ChannelParameters,
+               negotiated: bool,
+               id: Option<u16>)
+               -> Root<RTCDataChannel> {
+        reflect_dom_object(box RTCDataChannel::new_inherited(connection, parameters, negotiated, id),
+                           global,
+                           RTCDataChannelBinding::Wrap)
+    }
+
+    pub fn parameters(&self) -> &ChannelParameters {
+        &self.parameters
+    }
+
+    pub fn is_negotiated(&self) -> bool {
+        self.negotiated
+    }
+
+    pub fn id(&self) -> Option<u16> {
+        self.id.get()
+    }
+
+    pub fn set_id(&self, id: u16) {
+        self.id.set(Some(id));
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.ready_state.get() == RTCDataChannelState::Closed
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#announcing-a-data-channel-as-open
+    pub fn opened(&self) {
+        if self.ready_state.get() != RTCDataChannelState::Connecting {
+            return;
+        }
+        self.ready_state.set(RTCDataChannelState::Open);
+        self.upcast::<EventTarget>().fire_simple_event("open");
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#receiving-messages-on-a-data-channel
+    #[allow(unsafe_code)]
+    pub fn message_received(&self, message: ChannelMessage) {
+        if self.ready_state.get() != RTCDataChannelState::Open {
+            return;
+        }
+        let global = self.global();
+        // global.get_cx() returns a valid `JSContext` pointer, so this is safe.
+        unsafe {
+            let cx = global.r().get_cx();
+            let _ac = JSAutoCompartment::new(cx, self.reflector().get_jsobject().get());
+            rooted!(in(cx) let mut data = UndefinedValue());
+            match message {
+                ChannelMessage::Text(text) => DOMString::from(text).to_jsval(cx, data.handle_mut()),
+                ChannelMessage::Binary(bytes) => {
+                    if &*self.binary_type.borrow() == "arraybuffer" {
+                        let length = bytes.len() as uint32_t;
+                        let buffer = JS_NewArrayBuffer(cx, length);
+                        let mut is_shared = false;
+                        let buffer_data: *mut uint8_t = JS_GetArrayBufferData(buffer, &mut is_shared, ptr::null());
+                        assert!(!is_shared);
+                        ptr::copy_nonoverlapping(bytes.as_ptr(), buffer_data, length as usize);
+                        buffer.to_jsval(cx, data.handle_mut());
+                    } else {
+                        let blob = Blob::new(global.r(), BlobImpl::new_from_bytes(bytes), "".to_owned());
+                        blob.to_jsval(cx, data.handle_mut());
+                    }
+                },
+            }
+            MessageEvent::dispatch_jsval(self.upcast(), global.r(), data.handle());
+        }
+    }
+
+    /// Takes off `bufferedAmount` the bytes that were sent, firing
+    /// `bufferedamountlow` when it goes down to the threshold.
+    /// https://w3c.github.io/webrtc-pc/#dom-datachannel-bufferedamount
+    pub fn buffered_amount_decreased(&self, bytes: usize) {
+        let before = self.buffered_amount.get();
+        let after = before.saturating_sub(bytes as u64);
+        self.buffered_amount.set(after);
+        let threshold = self.buffered_amount_low_threshold.get();
+        if before > threshold && after <= threshold {
+            self.upcast::<EventTarget>().fire_simple_event("bufferedamountlow");
+        }
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#announcing-a-data-channel-as-closed
+    pub fn closed(&self) {
+        if self.is_closed() {
+            return;
+        }
+        self.ready_state.set(RTCDataChannelState::Closed);
+        self.upcast::<EventTarget>().fire_simple_event("close");
+    }
+
+    /// Closes the channel along with its connection, which fires no event.
+    pub fn connection_closed(&self) {
+        self.ready_state.set(RTCDataChannelState::Closed);
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send
+    fn send_message(&self, message: ChannelMessage) -> ErrorResult {
+        if self.ready_state.get() != RTCDataChannelState::Open {
+            return Err(Error::InvalidState);
+        }
+        let size = message.size() as u64;
+        if size > self.connection.max_message_size() {
+            return Err(Error::Type("The message is larger than the other peer takes".to_owned()));
+        }
+        self.buffered_amount.set(self.buffered_amount.get() + size);
+        if let Some(id) = self.id.get() {
+            self.connection.send_channel_message(id, message);
+        }
+        Ok(())
+    }
+}
+
+impl RTCDataChannelMethods for RTCDataChannel {
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-label
+    fn Label(&self) -> USVString {
+        USVString(self.parameters.label.clone())
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-ordered
+    fn Ordered(&self) -> bool {
+        self.parameters.ordered
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-maxpacketlifetime
+    fn GetMaxPacketLifeTime(&self) -> Option<u16> {
+        self.parameters.max_packet_life_time
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-maxretransmits
+    fn GetMaxRetransmits(&self) -> Option<u16> {
+        self.parameters.max_retransmits
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-protocol
+    fn Protocol(&self) -> USVString {
+        USVString(self.parameters.protocol.clone())
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-negotiated
+    fn Negotiated(&self) -> bool {
+        self.negotiated
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-id
+    fn GetId(&self) -> Option<u16> {
+        self.id.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-readystate
+    fn ReadyState(&self) -> RTCDataChannelState {
+        self.ready_state.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-bufferedamount
+    fn BufferedAmount(&self) -> u64 {
+        self.buffered_amount.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-bufferedamountlowthreshold
+    fn BufferedAmountLowThreshold(&self) -> u64 {
+        self.buffered_amount_low_threshold.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-bufferedamountlowthreshold
+    fn SetBufferedAmountLowThreshold(&self, threshold: u64) {
+        self.buffered_amount_low_threshold.set(threshold);
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-onopen
+    event_handler!(open, GetOnopen, SetOnopen);
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-onbufferedamountlow
+    event_handler!(bufferedamountlow, GetOnbufferedamountlow, SetOnbufferedamountlow);
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-onclosing
+    event_handler!(closing, GetOnclosing, SetOnclosing);
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-onclose
+    event_handler!(close, GetOnclose, SetOnclose);
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-close
+    fn Close(&self) {
+        match self.ready_state.get() {
+            RTCDataChannelState::Closing | RTCDataChannelState::Closed => return,
+            RTCDataChannelState::Connecting | RTCDataChannelState::Open => {},
+        }
+        self.ready_state.set(RTCDataChannelState::Closing);
+        match self.id.get() {
+            Some(id) => self.connection.close_channel(id),
+            None => self.closed(),
+        }
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-onmessage
+    event_handler!(message, GetOnmessage, SetOnmessage);
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-binarytype
+    fn BinaryType(&self) -> DOMString {
+        self.binary_type.borrow().clone()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-datachannel-binarytype
+    fn SetBinaryType(&self, binary_type: DOMString) -> ErrorResult {
+        if &*binary_type != "blob" && &*binary_type != "arraybuffer" {
+            return Err(Error::Syntax);
+        }
+        *self.binary_type.borrow_mut() = binary_type;
+        Ok(())
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send
+    fn Send(&self, data: USVString) -> ErrorResult {
+        self.send_message(ChannelMessage::Text(data.0))
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-send-blob
+    fn Send_(&self, blob: &Blob) -> ErrorResult {
+        if self.ready_state.get() != RTCDataChannelState::Open {
+            return Err(Error::InvalidState);
+        }
+        if blob.Size() > self.connection.max_message_size() {
+            return Err(Error::Type("The message is larger than the other peer takes".to_owned()));
+        }
+        self.send_message(ChannelMessage::Binary(blob.get_bytes().unwrap_or(vec![])))
+    }
+}
@@ -0,0 +1,68 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use dom::bindings::codegen::Bindings::RTCDataChannelEventBinding;
+use dom::bindings::codegen::Bindings::RTCDataChannelEventBinding::RTCDataChannelEventMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::event::{Event, EventBubbles, EventCancelable};
+use dom::rtcdatachannel::RTCDataChannel;
+use string_cache::Atom;
+
+// https://w3c.github.io/webrtc-pc/#rtcdatachannelevent
+#[dom_struct]
+pub struct RTCDataChannelEvent {
+    event: Event,
+    channel: JS<RTCDataChannel>,
+}
+
+impl RTCDataChannelEvent {
+    fn new_inherited(channel: &RTCDataChannel) -> RTCDataChannelEvent {
+        RTCDataChannelEvent {
+            event: Event::new_inherited(),
+            channel: JS::from_ref(channel),
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               type_: Atom,
+               bubbles: EventBubbles,
+               cancelable: EventCancelable,
+               channel: &RTCDataChannel)
+               -> Root<RTCDataChannelEvent> {
+        let event = reflect_dom_object(box RTCDataChannelEvent::new_inherited(channel),
+                                       global,
+                                       RTCDataChannelEventBinding::Wrap);
+        event.upcast::<Event>().init_event(type_, bool::from(bubbles), bool::from(cancelable));
+        event
+    }
+
+    pub fn Constructor(global: GlobalRef,
+                       type_: DOMString,
+                       init: &RTCDataChannelEventBinding::RTCDataChannelEventInit)
+                       -> Fallible<Root<RTCDataChannelEvent>> {
+        Ok(RTCDataChannelEvent::new(global,
+                                    Atom::from(type_),
+                                    EventBubbles::from(init.parent.bubbles),
+                                    EventCancelable::from(init.parent.cancelable),
+                                    init.channel.r()))
+    }
+}
+
+impl RTCDataChannelEventMethods for RTCDataChannelEvent {
+    // https://w3c.github.io/webrtc-pc/#dom-datachannelevent-channel
+    fn Channel(&self) -> Root<RTCDataChannel> {
+        Root::from_ref(&*self.channel)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
@@ -0,0 +1,152 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::RTCIceCandidateBinding;
+use dom::bindings::codegen::Bindings::RTCIceCandidateBinding::{RTCIceCandidateInit, RTCIceCandidateMethods};
+use dom::bindings::codegen::Bindings::RTCIceCandidateBinding::{RTCIceCandidateType, RTCIceProtocol};
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use webrtc_traits::{Candidate, CandidateType};
+
+// https://w3c.github.io/webrtc-pc/#rtcicecandidate-interface
+#[dom_struct]
+pub struct RTCIceCandidate {
+    reflector_: Reflector,
+    candidate: DOMString,
+    sdp_mid: Option<DOMString>,
+    sdp_m_line_index: Option<u16>,
+    username_fragment: Option<DOMString>,
+    /// The candidate that `candidate` describes, if it could be parsed.
+    #[ignore_heap_size_of = "Defined in net_traits"]
+    parsed: Option<Candidate>,
+}
+
+impl RTCIceCandidate {
+    fn new_inherited(candidate: DOMString,
+                     sdp_mid: Option<DOMString>,
+                     sdp_m_line_index: Option<u16>,
+                     username_fragment: Option<DOMString>)
+                     -> RTCIceCandidate {
+        let parsed = Candidate::parse(&candidate).ok();
+        RTCIceCandidate {
+            reflector_: Reflector::new(),
+            candidate: candidate,
+            sdp_mid: sdp_mid,
+            sdp_m_line_index: sdp_m_line_index,
+            username_fragment: username_fragment,
+            parsed: parsed,
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               candidate: DOMString,
+               sdp_mid: Option<DOMString>,
+               sdp_m_line_index: Option<u16>,
+               username_fragment: Option<DOMString>)
+               -> Root<RTCIceCandidate> {
+        reflect_dom_object(box RTCIceCandidate::new_inherited(candidate,
+                                                              sdp_mid,
+                                                              sdp_m_line_index,
+                                                              username_fragment),
+                           global,
+                           RTCIceCandidateBinding::Wrap)
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-constructor
+    pub fn Constructor(global: GlobalRef, init: &RTCIceCandidateInit) -> Fallible<Root<RTCIceCandidate>> {
+        if init.sdpMid.is_none() && init.sdpMLineIndex.is_none() {
+            return Err(Error::Type("sdpMid and sdpMLineIndex are both null".to_owned()));
+        }
+        Ok(RTCIceCandidate::new(global,
+                                init.candidate.clone(),
+                                init.sdpMid.clone(),
+                                init.sdpMLineIndex,
+                                init.usernameFragment.clone()))
+    }
+}
+
+impl RTCIceCandidateMethods for RTCIceCandidate {
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-candidate
+    fn Candidate(&self) -> DOMString {
+        self.candidate.clone()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-sdpmid
+    fn GetSdpMid(&self) -> Option<DOMString> {
+        self.sdp_mid.clone()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-sdpmlineindex
+    fn GetSdpMLineIndex(&self) -> Option<u16> {
+        self.sdp_m_line_index
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-foundation
+    fn GetFoundation(&self) -> Option<DOMString> {
+        self.parsed.as_ref().map(|candidate| DOMString::from(&*candidate.foundation))
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-priority
+    fn GetPriority(&self) -> Option<u32> {
+        self.parsed.as_ref().map(|candidate| candidate.priority)
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-address
+    fn GetAddress(&self) -> Option<DOMString> {
+        self.parsed.as_ref().map(|candidate| DOMString::from(candidate.address.ip().to_string()))
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-protocol
+    fn GetProtocol(&self) -> Option<RTCIceProtocol> {
+        // Only UDP candidates are parsed.
+        self.parsed.as_ref().map(|_| RTCIceProtocol::Udp)
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-port
+    fn GetPort(&self) -> Option<u16> {
+        self.parsed.as_ref().map(|candidate| candidate.address.port())
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-type
+    fn GetType(&self) -> Option<RTCIceCandidateType> {
+        self.parsed.as_ref().map(|candidate| {
+            match candidate.candidate_type {
+                CandidateType::Host => RTCIceCandidateType::Host,
+                CandidateType::ServerReflexive => RTCIceCandidateType::Srflx,
+                CandidateType::PeerReflexive => RTCIceCandidateType::Prflx,
+                CandidateType::Relay => RTCIceCandidateType::Relay,
+            }
+        })
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-relatedaddress
+    fn GetRelatedAddress(&self) -> Option<DOMString> {
+        self.parsed.as_ref()
+                   .and_then(|candidate| candidate.related_address)
+                   .map(|address| DOMString::from(address.ip().to_string()))
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-relatedport
+    fn GetRelatedPort(&self) -> Option<u16> {
+        self.parsed.as_ref().and_then(|candidate| candidate.related_address).map(|address| address.port())
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-usernamefragment
+    fn GetUsernameFragment(&self) -> Option<DOMString> {
+        self.username_fragment.clone()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcicecandidate-tojson
+    fn ToJSON(&self) -> RTCIceCandidateInit {
+        RTCIceCandidateInit {
+            candidate: self.candidate.clone(),
+            sdpMid: self.sdp_mid.clone(),
+            sdpMLineIndex: self.sdp_m_line_index,
+            usernameFragment: self.username_fragment.clone(),
+        }
+    }
+}
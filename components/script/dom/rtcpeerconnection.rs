@@ -0,0 +1,796 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::RTCDataChannelBinding::RTCDataChannelInit;
+use dom::bindings::codegen::Bindings::RTCIceCandidateBinding::RTCIceCandidateInit;
+use dom::bindings::codegen::Bindings::RTCPeerConnectionBinding;
+use dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::{RTCConfiguration, RTCOfferOptions};
+use dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::{RTCIceConnectionState, RTCIceGatheringState};
+use dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::{RTCPeerConnectionMethods, RTCPeerConnectionState};
+use dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::RTCSignalingState;
+use dom::bindings::codegen::Bindings::RTCSessionDescriptionBinding::{RTCSdpType, RTCSessionDescriptionInit};
+use dom::bindings::codegen::UnionTypes::StringOrStringSequence;
+use dom::bindings::conversions::ToJSValConvertible;
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::refcounted::Trusted;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::bindings::str::{DOMString, USVString};
+use dom::event::{Event, EventBubbles, EventCancelable};
+use dom::eventtarget::EventTarget;
+use dom::promise::Promise;
+use dom::rtcdatachannel::RTCDataChannel;
+use dom::rtcdatachannelevent::RTCDataChannelEvent;
+use dom::rtcicecandidate::RTCIceCandidate;
+use dom::rtcpeerconnectioniceevent::RTCPeerConnectionIceEvent;
+use dom::rtcsessiondescription::RTCSessionDescription;
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use rand::random;
+use script_thread::Runnable;
+use std::cell::Cell;
+use std::rc::Rc;
+use string_cache::Atom;
+use task_source::TaskSource;
+use webrtc_traits::{Candidate, Certificate, ChannelMessage, ChannelParameters, DtlsState};
+use webrtc_traits::{Fingerprint, IceConnectionState, IceCredentials, RemoteParameters, Setup};
+use webrtc_traits::{TransportConfig, TransportEvent, TransportMsg, WebRTCMsg, parse_stun_url};
+use webrtc_traits::sdp::{self, DEFAULT_MAX_MESSAGE_SIZE, LocalParameters, SessionDescription};
+
+/// The largest message that is sent or received on a data channel.
+const MAX_MESSAGE_SIZE: u64 = 262144;
+
+// https://w3c.github.io/webrtc-pc/#interface-definition
+#[dom_struct]
+pub struct RTCPeerConnection {
+    eventtarget: EventTarget,
+    /// The `stun:` URLs of the configured ICE servers.
+    stun_urls: Vec<String>,
+    certificate: Certificate,
+    #[ignore_heap_size_of = "Defined in net_traits"]
+    ice_credentials: IceCredentials,
+    session_id: u64,
+    session_version: Cell<u64>,
+    signaling_state: Cell<RTCSignalingState>,
+    ice_gathering_state: Cell<RTCIceGatheringState>,
+    ice_connection_state: Cell<RTCIceConnectionState>,
+    dtls_state: Cell<DtlsState>,
+    connection_state: Cell<RTCPeerConnectionState>,
+    is_closed: Cell<bool>,
+    /// The last offer or answer that was created, which is what setting an
+    /// empty description of its type sets.
+    last_created: DOMRefCell<Option<(RTCSdpType, DOMString)>>,
+    #[ignore_heap_size_of = "Defined in the media module"]
+    local_description: DOMRefCell<Option<(RTCSdpType, SessionDescription)>>,
+    #[ignore_heap_size_of = "Defined in the media module"]
+    remote_description: DOMRefCell<Option<(RTCSdpType, SessionDescription)>>,
+    /// The candidates gathered so far, which the local description lists.
+    #[ignore_heap_size_of = "Defined in net_traits"]
+    local_candidates: DOMRefCell<Vec<Candidate>>,
+    #[ignore_heap_size_of = "Defined in net_traits"]
+    remote_parameters: DOMRefCell<Option<RemoteParameters>>,
+    /// The remote candidates added before the transport started.
+    #[ignore_heap_size_of = "Defined in net_traits"]
+    pending_candidates: DOMRefCell<Vec<Candidate>>,
+    /// The channel to the transport in the WebRTC thread, once it started.
+    #[ignore_heap_size_of = "channels are hard"]
+    transport: DOMRefCell<Option<IpcSender<TransportMsg>>>,
+    channels: DOMRefCell<Vec<JS<RTCDataChannel>>>,
+    /// Whether the local peer is the DTLS client, once both descriptions
+    /// are set. The client opens channels on even streams, and the server
+    /// on odd ones.
+    dtls_client: Cell<Option<bool>>,
+    negotiation_needed: Cell<bool>,
+}
+
+impl RTCPeerConnection {
+    fn new_inherited(stun_urls: Vec<String>, certificate: Certificate) -> RTCPeerConnection {
+        RTCPeerConnection {
+            eventtarget: EventTarget::new_inherited(),
+            stun_urls: stun_urls,
+            certificate: certificate,
+            ice_credentials: IceCredentials::generate(),
+            // The session id has to fit in 63 bits.
+            // https://tools.ietf.org/html/rfc8829#section-5.2.1
+            session_id: random::<u64>() >> 1,
+            session_version: Cell::new(0),
+            signaling_state: Cell::new(RTCSignalingState::Stable),
+            ice_gathering_state: Cell::new(RTCIceGatheringState::New),
+            ice_connection_state: Cell::new(RTCIceConnectionState::New),
+            dtls_state: Cell::new(DtlsState::New),
+            connection_state: Cell::new(RTCPeerConnectionState::New),
+            is_closed: Cell::new(false),
+            last_created: DOMRefCell::new(None),
+            local_description: DOMRefCell::new(None),
+            remote_description: DOMRefCell::new(None),
+            local_candidates: DOMRefCell::new(vec![]),
+            remote_parameters: DOMRefCell::new(None),
+            pending_candidates: DOMRefCell::new(vec![]),
+            transport: DOMRefCell::new(None),
+            channels: DOMRefCell::new(vec![]),
+            dtls_client: Cell::new(None),
+            negotiation_needed: Cell::new(false),
+        }
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection
+    pub fn Constructor(global: GlobalRef, configuration: &RTCConfiguration) -> Fallible<Root<RTCPeerConnection>> {
+        let mut stun_urls = vec![];
+        for server in configuration.iceServers.iter().flat_map(|servers| servers) {
+            let urls = match server.urls {
+                StringOrStringSequence::String(ref url) => vec![url.clone()],
+                StringOrStringSequence::StringSequence(ref urls) => urls.clone(),
+            };
+            for url in urls {
+                if parse_stun_url(&url).is_some() {
+                    stun_urls.push(String::from(url));
+                } else if url.starts_with("turn:") || url.starts_with("turns:") {
+                    warn!("Ignored the TURN server {}, since relaying isn't supported", url);
+                } else {
+                    return Err(Error::Syntax);
+                }
+            }
+        }
+        let (sender, receiver) = ipc::channel().unwrap();
+        global.as_window().webrtc_thread().send(WebRTCMsg::GenerateCertificate(sender)).unwrap();
+        let certificate = try!(receiver.recv().unwrap_or(Err(())).map_err(|()| Error::Operation));
+        Ok(reflect_dom_object(box RTCPeerConnection::new_inherited(stun_urls, certificate),
+                              global,
+                              RTCPeerConnectionBinding::Wrap))
+    }
+
+    /// The size of the largest message that the other peer takes.
+    pub fn max_message_size(&self) -> u64 {
+        match *self.remote_parameters.borrow() {
+            // A size of 0 means that messages of any size are taken.
+            Some(ref remote) if remote.max_message_size != 0 => remote.max_message_size.min(MAX_MESSAGE_SIZE),
+            Some(_) => MAX_MESSAGE_SIZE,
+            None => DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    pub fn send_channel_message(&self, id: u16, message: ChannelMessage) {
+        self.send(TransportMsg::Send(id, message));
+    }
+
+    pub fn close_channel(&self, id: u16) {
+        self.send(TransportMsg::CloseChannel(id));
+    }
+
+    fn send(&self, msg: TransportMsg) {
+        if let Some(ref transport) = *self.transport.borrow() {
+            // The transport is gone once it failed, and messages to it are
+            // then meaningless.
+            let _ = transport.send(msg);
+        }
+    }
+
+    fn local_parameters<'a>(&'a self, fingerprint: &'a Fingerprint, setup: Setup) -> LocalParameters<'a> {
+        LocalParameters {
+            ice_ufrag: &self.ice_credentials.ufrag,
+            ice_pwd: &self.ice_credentials.pwd,
+            fingerprint: fingerprint,
+            setup: setup,
+            max_message_size: MAX_MESSAGE_SIZE,
+        }
+    }
+
+    fn next_session_version(&self) -> u64 {
+        let version = self.session_version.get() + 1;
+        self.session_version.set(version);
+        version
+    }
+
+    /// Whether a description that was set has a data channel section.
+    fn has_data_channel_media(&self) -> bool {
+        let has_media = |description: &Option<(RTCSdpType, SessionDescription)>| {
+            description.as_ref().map_or(false, |&(_, ref description)| description.data_channel_media().is_some())
+        };
+        has_media(&*self.local_description.borrow()) || has_media(&*self.remote_description.borrow())
+    }
+
+    fn set_signaling_state(&self, state: RTCSignalingState) {
+        if self.signaling_state.get() != state {
+            self.signaling_state.set(state);
+            self.upcast::<EventTarget>().fire_simple_event("signalingstatechange");
+        }
+    }
+
+    fn set_ice_gathering_state(&self, state: RTCIceGatheringState) {
+        if self.ice_gathering_state.get() != state {
+            self.ice_gathering_state.set(state);
+            self.upcast::<EventTarget>().fire_simple_event("icegatheringstatechange");
+        }
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-connection-state
+    fn update_connection_state(&self) {
+        let ice = self.ice_connection_state.get();
+        let dtls = self.dtls_state.get();
+        let state = if self.is_closed.get() {
+            RTCPeerConnectionState::Closed
+        } else if ice == RTCIceConnectionState::Failed || dtls == DtlsState::Failed {
+            RTCPeerConnectionState::Failed
+        } else if ice == RTCIceConnectionState::Disconnected || dtls == DtlsState::Closed {
+            RTCPeerConnectionState::Disconnected
+        } else if ice == RTCIceConnectionState::New && dtls == DtlsState::New {
+            RTCPeerConnectionState::New
+        } else if dtls == DtlsState::Connected {
+            RTCPeerConnectionState::Connected
+        } else {
+            RTCPeerConnectionState::Connecting
+        };
+        if self.connection_state.get() != state {
+            self.connection_state.set(state);
+            self.upcast::<EventTarget>().fire_simple_event("connectionstatechange");
+        }
+    }
+
+    /// Fires `negotiationneeded` if creating a channel needs a data channel
+    /// section that no description has.
+    /// https://w3c.github.io/webrtc-pc/#dfn-update-the-negotiation-needed-flag
+    fn update_negotiation_needed(&self) {
+        if self.negotiation_needed.get() || self.has_data_channel_media() {
+            return;
+        }
+        self.negotiation_needed.set(true);
+        let global = self.global();
+        let window = global.r().as_window();
+        window.dom_manipulation_task_source().queue_simple_event(self.upcast(),
+                                                                 Atom::from("negotiationneeded"),
+                                                                 window);
+    }
+
+    /// Starts the transport, once there is a local description.
+    fn start_transport(&self, controlling: bool) -> Result<(), Error> {
+        if self.transport.borrow().is_some() {
+            return Ok(());
+        }
+        let global = self.global();
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let trusted = Trusted::new(self);
+        let (event_sender, event_receiver) = ipc::channel().unwrap();
+        ROUTER.add_route(event_receiver.to_opaque(), box move |message| {
+            if let Ok(event) = message.to() {
+                let runnable = box TransportEventRunnable {
+                    connection: trusted.clone(),
+                    event: event,
+                };
+                let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+            }
+        });
+        let config = TransportConfig {
+            credentials: self.ice_credentials.clone(),
+            controlling: controlling,
+            certificate: self.certificate.clone(),
+            stun_urls: self.stun_urls.clone(),
+        };
+        let (sender, receiver) = ipc::channel().unwrap();
+        window.webrtc_thread().send(WebRTCMsg::StartTransport(config, event_sender, sender)).unwrap();
+        let transport = try!(receiver.recv().unwrap_or(Err(())).map_err(|()| Error::Operation));
+        if let Some(ref remote) = *self.remote_parameters.borrow() {
+            let _ = transport.send(TransportMsg::SetRemote(remote.clone()));
+        }
+        for candidate in self.pending_candidates.borrow_mut().drain(..) {
+            let _ = transport.send(TransportMsg::AddRemoteCandidate(candidate));
+        }
+        *self.transport.borrow_mut() = Some(transport);
+        self.set_ice_gathering_state(RTCIceGatheringState::Gathering);
+        Ok(())
+    }
+
+    /// Chooses the streams of the channels that weren't negotiated, and
+    /// opens them, once both descriptions are set.
+    /// https://w3c.github.io/webrtc-pc/#dom-rtcdatachannel-id
+    fn open_channels(&self) {
+        let client = match *self.remote_parameters.borrow() {
+            // The answerer is the DTLS client, as it answers with
+            // `a=setup:active`.
+            Some(ref remote) => remote.setup != Setup::Active,
+            None => return,
+        };
+        if self.transport.borrow().is_none() {
+            return;
+        }
+        self.dtls_client.set(Some(client));
+        let channels: Vec<_> = self.channels.borrow().iter().map(|channel| Root::from_ref(&**channel)).collect();
+        for channel in channels {
+            if channel.is_closed() {
+                continue;
+            }
+            let id = match channel.id().or_else(|| self.next_channel_id()) {
+                Some(id) => id,
+                None => {
+                    channel.closed();
+                    continue;
+                },
+            };
+            channel.set_id(id);
+            // The transport ignores the channels that it already opened.
+            self.send(TransportMsg::OpenChannel(id, channel.parameters().clone(), channel.is_negotiated()));
+        }
+    }
+
+    /// The first stream of the parity of the DTLS role that no channel
+    /// uses.
+    fn next_channel_id(&self) -> Option<u16> {
+        let first = match self.dtls_client.get() {
+            Some(true) => 0,
+            Some(false) => 1,
+            None => return None,
+        };
+        let channels = self.channels.borrow();
+        (first..u16::max_value()).filter(|id| id % 2 == first % 2).find(|&id| {
+            channels.iter().all(|channel| channel.id() != Some(id) || channel.is_closed())
+        })
+    }
+
+    fn find_channel(&self, id: u16) -> Option<Root<RTCDataChannel>> {
+        self.channels.borrow()
+                     .iter()
+                     .find(|channel| channel.id() == Some(id) && !channel.is_closed())
+                     .map(|channel| Root::from_ref(&**channel))
+    }
+
+    /// The `sdpMid` and `sdpMLineIndex` that candidates belong to, which
+    /// are those of the data channel section.
+    fn candidate_media(&self) -> (Option<DOMString>, Option<u16>) {
+        let local = self.local_description.borrow();
+        let description = match *local {
+            Some((_, ref description)) => description,
+            None => return (None, None),
+        };
+        match description.media.iter().position(|media| media.is_data_channel() && media.port != 0) {
+            Some(index) => (description.media[index].mid().map(DOMString::from), Some(index as u16)),
+            None => (None, None),
+        }
+    }
+
+    fn fire_ice_candidate(&self, candidate: Option<&RTCIceCandidate>) {
+        let global = self.global();
+        let event = RTCPeerConnectionIceEvent::new(global.r(),
+                                                   Atom::from("icecandidate"),
+                                                   EventBubbles::DoesNotBubble,
+                                                   EventCancelable::NotCancelable,
+                                                   candidate);
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    fn handle_transport_event(&self, event: TransportEvent) {
+        if self.is_closed.get() {
+            return;
+        }
+        let global = self.global();
+        match event {
+            TransportEvent::IceCandidate(candidate) => {
+                let value = DOMString::from(format!("candidate:{}", candidate.to_sdp_value()));
+                self.local_candidates.borrow_mut().push(candidate);
+                let (sdp_mid, sdp_m_line_index) = self.candidate_media();
+                let ufrag = Some(DOMString::from(&*self.ice_credentials.ufrag));
+                let candidate = RTCIceCandidate::new(global.r(), value, sdp_mid, sdp_m_line_index, ufrag);
+                self.fire_ice_candidate(Some(&*candidate));
+            },
+            TransportEvent::IceGatheringComplete => {
+                self.set_ice_gathering_state(RTCIceGatheringState::Complete);
+                self.fire_ice_candidate(None);
+            },
+            TransportEvent::IceStateChanged(state) => {
+                let state = match state {
+                    IceConnectionState::New => RTCIceConnectionState::New,
+                    IceConnectionState::Checking => RTCIceConnectionState::Checking,
+                    IceConnectionState::Connected => RTCIceConnectionState::Connected,
+                    IceConnectionState::Disconnected => RTCIceConnectionState::Disconnected,
+                    IceConnectionState::Failed => RTCIceConnectionState::Failed,
+                    IceConnectionState::Closed => RTCIceConnectionState::Closed,
+                };
+                if self.ice_connection_state.get() != state {
+                    self.ice_connection_state.set(state);
+                    self.upcast::<EventTarget>().fire_simple_event("iceconnectionstatechange");
+                }
+                self.update_connection_state();
+            },
+            TransportEvent::DtlsStateChanged(state) => {
+                self.dtls_state.set(state);
+                self.update_connection_state();
+            },
+            TransportEvent::ChannelOpened(id) => {
+                if let Some(channel) = self.find_channel(id) {
+                    channel.opened();
+                }
+            },
+            TransportEvent::IncomingChannel(id, parameters) => {
+                let channel = RTCDataChannel::new(global.r(), self, parameters, false, Some(id));
+                self.channels.borrow_mut().push(JS::from_ref(&*channel));
+                let event = RTCDataChannelEvent::new(global.r(),
+                                                     Atom::from("datachannel"),
+                                                     EventBubbles::DoesNotBubble,
+                                                     EventCancelable::NotCancelable,
+                                                     &channel);
+                event.upcast::<Event>().fire(self.upcast());
+                channel.opened();
+            },
+            TransportEvent::ChannelMessage(id, message) => {
+                if let Some(channel) = self.find_channel(id) {
+                    channel.message_received(message);
+                }
+            },
+            TransportEvent::ChannelBufferedAmountDecreased(id, bytes) => {
+                if let Some(channel) = self.find_channel(id) {
+                    channel.buffered_amount_decreased(bytes);
+                }
+            },
+            TransportEvent::ChannelClosed(id) => {
+                if let Some(channel) = self.find_channel(id) {
+                    channel.closed();
+                }
+                self.channels.borrow_mut().retain(|channel| !channel.is_closed());
+            },
+        }
+    }
+
+    /// Applies a description, returning the signaling state it leads to.
+    /// https://w3c.github.io/webrtc-pc/#set-description
+    fn set_description(&self, description: &RTCSessionDescriptionInit, remote: bool) -> Result<(), Error> {
+        if self.is_closed.get() {
+            return Err(Error::InvalidState);
+        }
+        let state = self.signaling_state.get();
+        let next_state = match (description.type_, remote, state) {
+            (RTCSdpType::Offer, false, RTCSignalingState::Stable) |
+            (RTCSdpType::Offer, false, RTCSignalingState::Have_local_offer) => RTCSignalingState::Have_local_offer,
+            (RTCSdpType::Offer, true, RTCSignalingState::Stable) |
+            (RTCSdpType::Offer, true, RTCSignalingState::Have_remote_offer) => RTCSignalingState::Have_remote_offer,
+            (RTCSdpType::Answer, false, RTCSignalingState::Have_remote_offer) |
+            (RTCSdpType::Answer, false, RTCSignalingState::Have_local_pranswer) |
+            (RTCSdpType::Answer, true, RTCSignalingState::Have_local_offer) |
+            (RTCSdpType::Answer, true, RTCSignalingState::Have_remote_pranswer) => RTCSignalingState::Stable,
+            (RTCSdpType::Pranswer, false, RTCSignalingState::Have_remote_offer) |
+            (RTCSdpType::Pranswer, false, RTCSignalingState::Have_local_pranswer) => {
+                RTCSignalingState::Have_local_pranswer
+            },
+            (RTCSdpType::Pranswer, true, RTCSignalingState::Have_local_offer) |
+            (RTCSdpType::Pranswer, true, RTCSignalingState::Have_remote_pranswer) => {
+                RTCSignalingState::Have_remote_pranswer
+            },
+            (RTCSdpType::Rollback, false, RTCSignalingState::Have_local_offer) |
+            (RTCSdpType::Rollback, true, RTCSignalingState::Have_remote_offer) => {
+                // FIXME: rolling back doesn't stop a transport that started.
+                let stored = if remote { &self.remote_description } else { &self.local_description };
+                *stored.borrow_mut() = None;
+                self.set_signaling_state(RTCSignalingState::Stable);
+                return Ok(());
+            },
+            _ => return Err(Error::InvalidState),
+        };
+
+        // An empty description is the last one that was created.
+        let sdp = if description.sdp.is_empty() && !remote {
+            match *self.last_created.borrow() {
+                Some((type_, ref sdp)) if type_ == description.type_ => sdp.clone(),
+                _ => return Err(Error::InvalidModification),
+            }
+        } else {
+            description.sdp.clone()
+        };
+        let parsed = try!(SessionDescription::parse(&sdp).map_err(|()| Error::Operation));
+
+        if remote {
+            let parameters = try!(sdp::remote_parameters(&parsed).map_err(|()| Error::Operation));
+            if let Some(ref parameters) = parameters {
+                self.send(TransportMsg::SetRemote(parameters.clone()));
+            }
+            if parameters.is_some() || self.remote_parameters.borrow().is_none() {
+                *self.remote_parameters.borrow_mut() = parameters;
+            }
+            *self.remote_description.borrow_mut() = Some((description.type_, parsed));
+        } else {
+            *self.local_description.borrow_mut() = Some((description.type_, parsed));
+            // The offerer is the controlling agent.
+            try!(self.start_transport(description.type_ == RTCSdpType::Offer));
+        }
+
+        if next_state == RTCSignalingState::Stable {
+            self.negotiation_needed.set(false);
+            if self.has_data_channel_media() {
+                self.open_channels();
+            }
+        }
+        self.set_signaling_state(next_state);
+        Ok(())
+    }
+
+    #[allow(unrooted_must_root)]
+    fn settle<T: ToJSValConvertible>(&self, result: Result<T, Error>) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let cx = global.r().get_cx();
+        match result {
+            Ok(value) => promise.resolve_native(cx, &value),
+            Err(error) => promise.reject_error(cx, error),
+        }
+        promise
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dfn-create-an-offer
+    fn create_offer(&self) -> Result<RTCSessionDescriptionInit, Error> {
+        if self.is_closed.get() {
+            return Err(Error::InvalidState);
+        }
+        let fingerprint = &self.certificate.fingerprint;
+        let data_channels = !self.channels.borrow().is_empty() || self.has_data_channel_media();
+        let offer = sdp::create_offer(self.session_id,
+                                      self.next_session_version(),
+                                      data_channels,
+                                      &self.local_parameters(fingerprint, Setup::ActPass));
+        let sdp = DOMString::from(offer.to_string());
+        *self.last_created.borrow_mut() = Some((RTCSdpType::Offer, sdp.clone()));
+        Ok(RTCSessionDescriptionInit {
+            type_: RTCSdpType::Offer,
+            sdp: sdp,
+        })
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dfn-create-an-answer
+    fn create_answer(&self) -> Result<RTCSessionDescriptionInit, Error> {
+        if self.is_closed.get() {
+            return Err(Error::InvalidState);
+        }
+        match self.signaling_state.get() {
+            RTCSignalingState::Have_remote_offer | RTCSignalingState::Have_local_pranswer => {},
+            _ => return Err(Error::InvalidState),
+        }
+        let fingerprint = &self.certificate.fingerprint;
+        let version = self.next_session_version();
+        let remote = self.remote_description.borrow();
+        let offer = match *remote {
+            Some((_, ref offer)) => offer,
+            None => return Err(Error::InvalidState),
+        };
+        let answer = sdp::create_answer(self.session_id,
+                                        version,
+                                        offer,
+                                        &self.local_parameters(fingerprint, Setup::Active));
+        let sdp = DOMString::from(answer.to_string());
+        *self.last_created.borrow_mut() = Some((RTCSdpType::Answer, sdp.clone()));
+        Ok(RTCSessionDescriptionInit {
+            type_: RTCSdpType::Answer,
+            sdp: sdp,
+        })
+    }
+
+    /// https://w3c.github.io/webrtc-pc/#dom-peerconnection-addicecandidate
+    fn add_ice_candidate(&self, init: &RTCIceCandidateInit) -> Result<(), Error> {
+        if self.is_closed.get() {
+            return Err(Error::InvalidState);
+        }
+        let remote = self.remote_description.borrow();
+        let description = match *remote {
+            Some((_, ref description)) => description,
+            None => return Err(Error::InvalidState),
+        };
+        // An empty candidate ends the candidates, which the agent doesn't
+        // need to know.
+        if init.candidate.is_empty() {
+            return Ok(());
+        }
+        if init.sdpMid.is_none() && init.sdpMLineIndex.is_none() {
+            return Err(Error::Type("sdpMid and sdpMLineIndex are both null".to_owned()));
+        }
+        let index = match description.find_media(init.sdpMid.as_ref().map(|mid| &**mid), init.sdpMLineIndex) {
+            Some(index) => index,
+            None => return Err(Error::Operation),
+        };
+        if !description.media[index].is_data_channel() {
+            return Ok(());
+        }
+        // Candidates of kinds that aren't supported, e.g. TCP, are ignored.
+        let candidate = match Candidate::parse(&init.candidate) {
+            Ok(candidate) => candidate,
+            Err(()) => return Ok(()),
+        };
+        match *self.transport.borrow() {
+            Some(ref transport) => {
+                let _ = transport.send(TransportMsg::AddRemoteCandidate(candidate));
+            },
+            None => self.pending_candidates.borrow_mut().push(candidate),
+        }
+        Ok(())
+    }
+
+    /// The description as it is now, with the candidates gathered so far.
+    fn description(&self, description: &Option<(RTCSdpType, SessionDescription)>, local: bool)
+                   -> Option<Root<RTCSessionDescription>> {
+        description.as_ref().map(|&(type_, ref description)| {
+            let mut description = description.clone();
+            if local {
+                let complete = self.ice_gathering_state.get() == RTCIceGatheringState::Complete;
+                sdp::add_candidates(&mut description, &self.local_candidates.borrow(), complete);
+            }
+            let global = self.global();
+            RTCSessionDescription::new(global.r(), type_, DOMString::from(description.to_string()))
+        })
+    }
+}
+
+impl RTCPeerConnectionMethods for RTCPeerConnection {
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-createoffer
+    fn CreateOffer(&self, _options: &RTCOfferOptions) -> Rc<Promise> {
+        // FIXME: iceRestart isn't supported.
+        self.settle(self.create_offer())
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnection-createanswer
+    fn CreateAnswer(&self) -> Rc<Promise> {
+        self.settle(self.create_answer())
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-setlocaldescription
+    fn SetLocalDescription(&self, description: &RTCSessionDescriptionInit) -> Rc<Promise> {
+        self.settle(self.set_description(description, false))
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-localdescription
+    fn GetLocalDescription(&self) -> Option<Root<RTCSessionDescription>> {
+        self.description(&self.local_description.borrow(), true)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-setremotedescription
+    fn SetRemoteDescription(&self, description: &RTCSessionDescriptionInit) -> Rc<Promise> {
+        self.settle(self.set_description(description, true))
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-remotedescription
+    fn GetRemoteDescription(&self) -> Option<Root<RTCSessionDescription>> {
+        self.description(&self.remote_description.borrow(), false)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-addicecandidate
+    fn AddIceCandidate(&self, candidate: &RTCIceCandidateInit) -> Rc<Promise> {
+        self.settle(self.add_ice_candidate(candidate))
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-signaling-state
+    fn SignalingState(&self) -> RTCSignalingState {
+        self.signaling_state.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-icegatheringstate
+    fn IceGatheringState(&self) -> RTCIceGatheringState {
+        self.ice_gathering_state.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-ice-connection-state
+    fn IceConnectionState(&self) -> RTCIceConnectionState {
+        self.ice_connection_state.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-connection-state
+    fn ConnectionState(&self) -> RTCPeerConnectionState {
+        self.connection_state.get()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-cantrickleicecandidates
+    fn GetCanTrickleIceCandidates(&self) -> Option<bool> {
+        self.remote_description.borrow().as_ref().map(|&(_, ref description)| {
+            description.attribute("ice-options").map_or(false, |options| {
+                options.split_whitespace().any(|option| option == "trickle")
+            }) || description.media.iter().any(|media| {
+                description.media_attribute(media, "ice-options").map_or(false, |options| {
+                    options.split_whitespace().any(|option| option == "trickle")
+                })
+            })
+        })
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-close
+    fn Close(&self) {
+        if self.is_closed.get() {
+            return;
+        }
+        self.is_closed.set(true);
+        self.signaling_state.set(RTCSignalingState::Closed);
+        for channel in self.channels.borrow_mut().drain(..) {
+            channel.connection_closed();
+        }
+        if let Some(transport) = self.transport.borrow_mut().take() {
+            let _ = transport.send(TransportMsg::Close);
+        }
+        self.ice_connection_state.set(RTCIceConnectionState::Closed);
+        self.connection_state.set(RTCPeerConnectionState::Closed);
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-onnegotiationneeded
+    event_handler!(negotiationneeded, GetOnnegotiationneeded, SetOnnegotiationneeded);
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-onicecandidate
+    event_handler!(icecandidate, GetOnicecandidate, SetOnicecandidate);
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-onsignalingstatechange
+    event_handler!(signalingstatechange, GetOnsignalingstatechange, SetOnsignalingstatechange);
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-oniceconnectionstatechange
+    event_handler!(iceconnectionstatechange, GetOniceconnectionstatechange, SetOniceconnectionstatechange);
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-onicegatheringstatechange
+    event_handler!(icegatheringstatechange, GetOnicegatheringstatechange, SetOnicegatheringstatechange);
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-onconnectionstatechange
+    event_handler!(connectionstatechange, GetOnconnectionstatechange, SetOnconnectionstatechange);
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-createdatachannel
+    fn CreateDataChannel(&self, label: USVString, init: &RTCDataChannelInit) -> Fallible<Root<RTCDataChannel>> {
+        if self.is_closed.get() {
+            return Err(Error::InvalidState);
+        }
+        if label.0.len() > 65535 || init.protocol.0.len() > 65535 {
+            return Err(Error::Type("The label or the protocol is longer than 65535 bytes".to_owned()));
+        }
+        if init.maxPacketLifeTime.is_some() && init.maxRetransmits.is_some() {
+            return Err(Error::Type("maxPacketLifeTime and maxRetransmits are both set".to_owned()));
+        }
+        let id = if init.negotiated {
+            match init.id {
+                Some(id) if id != u16::max_value() => Some(id),
+                Some(_) => return Err(Error::Type("The id is out of range".to_owned())),
+                None => return Err(Error::Type("A negotiated channel needs an id".to_owned())),
+            }
+        } else {
+            self.next_channel_id()
+        };
+        if let Some(id) = id {
+            if self.find_channel(id).is_some() {
+                return Err(Error::Operation);
+            }
+        }
+        let parameters = ChannelParameters {
+            label: label.0,
+            protocol: init.protocol.0.clone(),
+            ordered: init.ordered,
+            max_packet_life_time: init.maxPacketLifeTime,
+            max_retransmits: init.maxRetransmits,
+            priority: 0,
+        };
+        let global = self.global();
+        let channel = RTCDataChannel::new(global.r(), self, parameters.clone(), init.negotiated, id);
+        self.channels.borrow_mut().push(JS::from_ref(&*channel));
+        // Channels are opened once both peers agreed on the data channel
+        // section.
+        if self.signaling_state.get() == RTCSignalingState::Stable && self.dtls_client.get().is_some() {
+            if let Some(id) = id {
+                self.send(TransportMsg::OpenChannel(id, parameters, init.negotiated));
+            }
+        }
+        self.update_negotiation_needed();
+        Ok(channel)
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-peerconnection-ondatachannel
+    event_handler!(datachannel, GetOndatachannel, SetOndatachannel);
+}
+
+/// Delivers a notification of the transport thread of a connection.
+struct TransportEventRunnable {
+    connection: Trusted<RTCPeerConnection>,
+    event: TransportEvent,
+}
+
+impl Runnable for TransportEventRunnable {
+    fn name(&self) -> &'static str { "TransportEventRunnable" }
+
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        this.connection.root().handle_transport_event(this.event);
+    }
+}
@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use dom::bindings::codegen::Bindings::RTCPeerConnectionIceEventBinding;
+use dom::bindings::codegen::Bindings::RTCPeerConnectionIceEventBinding::RTCPeerConnectionIceEventInit;
+use dom::bindings::codegen::Bindings::RTCPeerConnectionIceEventBinding::RTCPeerConnectionIceEventMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::event::{Event, EventBubbles, EventCancelable};
+use dom::rtcicecandidate::RTCIceCandidate;
+use string_cache::Atom;
+
+// https://w3c.github.io/webrtc-pc/#rtcpeerconnectioniceevent
+#[dom_struct]
+pub struct RTCPeerConnectionIceEvent {
+    event: Event,
+    candidate: Option<JS<RTCIceCandidate>>,
+}
+
+impl RTCPeerConnectionIceEvent {
+    fn new_inherited(candidate: Option<&RTCIceCandidate>) -> RTCPeerConnectionIceEvent {
+        RTCPeerConnectionIceEvent {
+            event: Event::new_inherited(),
+            candidate: candidate.map(JS::from_ref),
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               type_: Atom,
+               bubbles: EventBubbles,
+               cancelable: EventCancelable,
+               candidate: Option<&RTCIceCandidate>)
+               -> Root<RTCPeerConnectionIceEvent> {
+        let event = reflect_dom_object(box RTCPeerConnectionIceEvent::new_inherited(candidate),
+                                       global,
+                                       RTCPeerConnectionIceEventBinding::Wrap);
+        event.upcast::<Event>().init_event(type_, bool::from(bubbles), bool::from(cancelable));
+        event
+    }
+
+    pub fn Constructor(global: GlobalRef,
+                       type_: DOMString,
+                       init: &RTCPeerConnectionIceEventInit)
+                       -> Fallible<Root<RTCPeerConnectionIceEvent>> {
+        Ok(RTCPeerConnectionIceEvent::new(global,
+                                          Atom::from(type_),
+                                          EventBubbles::from(init.parent.bubbles),
+                                          EventCancelable::from(init.parent.cancelable),
+                                          init.candidate.r()))
+    }
+}
+
+impl RTCPeerConnectionIceEventMethods for RTCPeerConnectionIceEvent {
+    // https://w3c.github.io/webrtc-pc/#dom-rtcpeerconnectioniceevent-candidate
+    fn GetCandidate(&self) -> Option<Root<RTCIceCandidate>> {
+        self.candidate.as_ref().map(|candidate| Root::from_ref(&**candidate))
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
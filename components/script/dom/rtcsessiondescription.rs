@@ -0,0 +1,63 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::RTCSessionDescriptionBinding;
+use dom::bindings::codegen::Bindings::RTCSessionDescriptionBinding::{RTCSdpType, RTCSessionDescriptionInit};
+use dom::bindings::codegen::Bindings::RTCSessionDescriptionBinding::RTCSessionDescriptionMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+
+// https://w3c.github.io/webrtc-pc/#rtcsessiondescription-class
+#[dom_struct]
+pub struct RTCSessionDescription {
+    reflector_: Reflector,
+    type_: RTCSdpType,
+    sdp: DOMString,
+}
+
+impl RTCSessionDescription {
+    fn new_inherited(type_: RTCSdpType, sdp: DOMString) -> RTCSessionDescription {
+        RTCSessionDescription {
+            reflector_: Reflector::new(),
+            type_: type_,
+            sdp: sdp,
+        }
+    }
+
+    pub fn new(global: GlobalRef, type_: RTCSdpType, sdp: DOMString) -> Root<RTCSessionDescription> {
+        reflect_dom_object(box RTCSessionDescription::new_inherited(type_, sdp),
+                           global,
+                           RTCSessionDescriptionBinding::Wrap)
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-sessiondescription
+    pub fn Constructor(global: GlobalRef,
+                       init: &RTCSessionDescriptionInit)
+                       -> Fallible<Root<RTCSessionDescription>> {
+        Ok(RTCSessionDescription::new(global, init.type_, init.sdp.clone()))
+    }
+}
+
+impl RTCSessionDescriptionMethods for RTCSessionDescription {
+    // https://w3c.github.io/webrtc-pc/#dom-rtcsessiondescription-type
+    fn Type(&self) -> RTCSdpType {
+        self.type_
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcsessiondescription-sdp
+    fn Sdp(&self) -> DOMString {
+        self.sdp.clone()
+    }
+
+    // https://w3c.github.io/webrtc-pc/#dom-rtcsessiondescription-tojson
+    fn ToJSON(&self) -> RTCSessionDescriptionInit {
+        RTCSessionDescriptionInit {
+            type_: self.type_,
+            sdp: self.sdp.clone(),
+        }
+    }
+}
@@ -0,0 +1,396 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use dom::bindings::codegen::Bindings::SelectionBinding;
+use dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::document::Document;
+use dom::node::Node;
+use dom::range::{Range, bp_position};
+use dom::text::Text;
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, HeapSizeOf, JSTraceable, PartialEq)]
+enum Direction {
+    Forwards,
+    Backwards,
+    Directionless,
+}
+
+// https://w3c.github.io/selection-api/#selection-interface
+#[dom_struct]
+pub struct Selection {
+    reflector_: Reflector,
+    document: JS<Document>,
+    range: MutNullableHeap<JS<Range>>,
+    direction: Cell<Direction>,
+}
+
+impl Selection {
+    fn new_inherited(document: &Document) -> Selection {
+        Selection {
+            reflector_: Reflector::new(),
+            document: JS::from_ref(document),
+            range: MutNullableHeap::new(None),
+            direction: Cell::new(Direction::Directionless),
+        }
+    }
+
+    pub fn new(document: &Document) -> Root<Selection> {
+        reflect_dom_object(box Selection::new_inherited(document),
+                           GlobalRef::Window(document.window()),
+                           SelectionBinding::Wrap)
+    }
+
+    /// The range of this selection, if it has one.
+    pub fn range(&self) -> Option<Root<Range>> {
+        self.range.get()
+    }
+
+    fn set_range(&self, range: Option<&Range>, direction: Direction) {
+        if let Some(old_range) = self.range.get() {
+            old_range.set_selection(None);
+        }
+        if let Some(range) = range {
+            range.set_selection(Some(self));
+        }
+        self.range.set(range);
+        self.direction.set(direction);
+        self.document.selection_changed();
+    }
+
+    /// Called by the range of this selection whenever one of its boundary points changes.
+    pub fn range_changed(&self) {
+        self.document.selection_changed();
+    }
+
+    fn is_in_document(&self, node: &Node) -> bool {
+        node.is_in_doc() && &*node.owner_doc() == &*self.document
+    }
+
+    fn new_range(&self, start: &Node, start_offset: u32, end: &Node, end_offset: u32) -> Root<Range> {
+        Range::new(&self.document, start, start_offset, end, end_offset)
+    }
+
+    /// Moves the focus of this selection by one character, as for Shift+Left or Shift+Right.
+    pub fn extend_by_character(&self, forward: bool) {
+        let (node, offset) = match (self.GetFocusNode(), self.range.get()) {
+            (Some(node), Some(_)) => (node, self.FocusOffset()),
+            _ => return,
+        };
+        if !node.is::<Text>() {
+            return;
+        }
+
+        let len = node.len();
+        let target = if forward && offset < len {
+            Some((node, offset + 1))
+        } else if !forward && offset > 0 {
+            Some((node, offset - 1))
+        } else {
+            let root = self.document.upcast::<Node>();
+            let adjacent = if forward {
+                node.following_nodes(root).find(|node| node.is::<Text>() && node.len() > 0)
+            } else {
+                node.preceding_nodes(root).find(|node| node.is::<Text>() && node.len() > 0)
+            };
+            adjacent.map(|adjacent| {
+                let offset = if forward { 1 } else { adjacent.len() - 1 };
+                (adjacent, offset)
+            })
+        };
+        if let Some((node, offset)) = target {
+            let _ = self.Extend(&node, offset);
+        }
+    }
+}
+
+impl SelectionMethods for Selection {
+    // https://w3c.github.io/selection-api/#dom-selection-anchornode
+    fn GetAnchorNode(&self) -> Option<Root<Node>> {
+        self.range.get().map(|range| match self.direction.get() {
+            Direction::Backwards => range.EndContainer(),
+            _ => range.StartContainer(),
+        })
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-anchoroffset
+    fn AnchorOffset(&self) -> u32 {
+        self.range.get().map_or(0, |range| match self.direction.get() {
+            Direction::Backwards => range.EndOffset(),
+            _ => range.StartOffset(),
+        })
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-focusnode
+    fn GetFocusNode(&self) -> Option<Root<Node>> {
+        self.range.get().map(|range| match self.direction.get() {
+            Direction::Backwards => range.StartContainer(),
+            _ => range.EndContainer(),
+        })
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-focusoffset
+    fn FocusOffset(&self) -> u32 {
+        self.range.get().map_or(0, |range| match self.direction.get() {
+            Direction::Backwards => range.StartOffset(),
+            _ => range.EndOffset(),
+        })
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-iscollapsed
+    fn IsCollapsed(&self) -> bool {
+        self.range.get().map_or(true, |range| range.Collapsed())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-rangecount
+    fn RangeCount(&self) -> u32 {
+        if self.range.get().is_some() { 1 } else { 0 }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-type
+    fn Type(&self) -> DOMString {
+        DOMString::from(match self.range.get() {
+            None => "None",
+            Some(ref range) if range.Collapsed() => "Caret",
+            Some(_) => "Range",
+        })
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-getrangeat
+    fn GetRangeAt(&self, index: u32) -> Fallible<Root<Range>> {
+        match self.range.get() {
+            Some(range) if index == 0 => Ok(range),
+            _ => Err(Error::IndexSize),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-addrange
+    fn AddRange(&self, range: &Range) {
+        // Step 1.
+        if !self.is_in_document(&range.StartContainer()) {
+            return;
+        }
+        // Step 2.
+        if self.range.get().is_some() {
+            return;
+        }
+        // Steps 3-4.
+        self.set_range(Some(range), Direction::Forwards);
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-removerange
+    fn RemoveRange(&self, range: &Range) -> ErrorResult {
+        match self.range.get() {
+            Some(ref current) if &**current == range => {
+                self.set_range(None, Direction::Directionless);
+                Ok(())
+            },
+            _ => Err(Error::NotFound),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-removeallranges
+    fn RemoveAllRanges(&self) {
+        if self.range.get().is_some() {
+            self.set_range(None, Direction::Directionless);
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-empty
+    fn Empty(&self) {
+        self.RemoveAllRanges();
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-collapse
+    fn Collapse(&self, node: Option<&Node>, offset: u32) -> ErrorResult {
+        // Step 1.
+        let node = match node {
+            Some(node) => node,
+            None => {
+                self.RemoveAllRanges();
+                return Ok(());
+            },
+        };
+        // Step 2.
+        if node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        // Step 3.
+        if offset > node.len() {
+            return Err(Error::IndexSize);
+        }
+        // Step 4.
+        if !self.is_in_document(node) {
+            return Ok(());
+        }
+        // Steps 5-6.
+        let range = self.new_range(node, offset, node, offset);
+        self.set_range(Some(&range), Direction::Forwards);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-setposition
+    fn SetPosition(&self, node: Option<&Node>, offset: u32) -> ErrorResult {
+        self.Collapse(node, offset)
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-collapsetostart
+    fn CollapseToStart(&self) -> ErrorResult {
+        let current = try!(self.range.get().ok_or(Error::InvalidState));
+        let (node, offset) = (current.StartContainer(), current.StartOffset());
+        let range = self.new_range(&node, offset, &node, offset);
+        self.set_range(Some(&range), Direction::Forwards);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-collapsetoend
+    fn CollapseToEnd(&self) -> ErrorResult {
+        let current = try!(self.range.get().ok_or(Error::InvalidState));
+        let (node, offset) = (current.EndContainer(), current.EndOffset());
+        let range = self.new_range(&node, offset, &node, offset);
+        self.set_range(Some(&range), Direction::Forwards);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-extend
+    fn Extend(&self, node: &Node, offset: u32) -> ErrorResult {
+        // Step 1.
+        if !self.is_in_document(node) {
+            return Ok(());
+        }
+        // Step 2.
+        if self.range.get().is_none() {
+            return Err(Error::InvalidState);
+        }
+        if node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        if offset > node.len() {
+            return Err(Error::IndexSize);
+        }
+        // Steps 3-4.
+        let anchor = self.GetAnchorNode().unwrap();
+        let anchor_offset = self.AnchorOffset();
+        // Steps 5-8.
+        let (range, direction) = match bp_position(&anchor, anchor_offset, node, offset) {
+            Some(Ordering::Greater) => {
+                (self.new_range(node, offset, &anchor, anchor_offset), Direction::Backwards)
+            },
+            Some(_) => (self.new_range(&anchor, anchor_offset, node, offset), Direction::Forwards),
+            None => (self.new_range(node, offset, node, offset), Direction::Forwards),
+        };
+        // Steps 9-10.
+        self.set_range(Some(&range), direction);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-setbaseandextent
+    fn SetBaseAndExtent(&self,
+                        anchor_node: &Node,
+                        anchor_offset: u32,
+                        focus_node: &Node,
+                        focus_offset: u32)
+                        -> ErrorResult {
+        // Step 1.
+        if anchor_offset > anchor_node.len() || focus_offset > focus_node.len() {
+            return Err(Error::IndexSize);
+        }
+        if anchor_node.is_doctype() || focus_node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        // Step 2.
+        if !self.is_in_document(anchor_node) || !self.is_in_document(focus_node) {
+            return Ok(());
+        }
+        // Steps 3-7.
+        let position = bp_position(anchor_node, anchor_offset, focus_node, focus_offset);
+        let (range, direction) = if position == Some(Ordering::Greater) {
+            (self.new_range(focus_node, focus_offset, anchor_node, anchor_offset), Direction::Backwards)
+        } else {
+            (self.new_range(anchor_node, anchor_offset, focus_node, focus_offset), Direction::Forwards)
+        };
+        self.set_range(Some(&range), direction);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-selectallchildren
+    fn SelectAllChildren(&self, node: &Node) -> ErrorResult {
+        // Step 1.
+        if node.is_doctype() {
+            return Err(Error::InvalidNodeType);
+        }
+        // Step 2.
+        if !self.is_in_document(node) {
+            return Ok(());
+        }
+        // Steps 3-6.
+        let child_count = node.children_count();
+        let range = self.new_range(node, 0, node, child_count);
+        self.set_range(Some(&range), Direction::Forwards);
+        Ok(())
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-deletefromdocument
+    fn DeleteFromDocument(&self) -> ErrorResult {
+        match self.range.get() {
+            Some(range) => range.DeleteContents(),
+            None => Ok(()),
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-containsnode
+    fn ContainsNode(&self, node: &Node, allow_partial_containment: bool) -> bool {
+        let range = match self.range.get() {
+            Some(range) => range,
+            None => return false,
+        };
+        if !self.is_in_document(node) {
+            return false;
+        }
+        let (start, start_offset) = (range.StartContainer(), range.StartOffset());
+        let (end, end_offset) = (range.EndContainer(), range.EndOffset());
+        let len = node.len();
+        if allow_partial_containment {
+            bp_position(&start, start_offset, node, len) != Some(Ordering::Greater) &&
+            bp_position(&end, end_offset, node, 0) != Some(Ordering::Less)
+        } else {
+            bp_position(&start, start_offset, node, 0) != Some(Ordering::Greater) &&
+            bp_position(&end, end_offset, node, len) != Some(Ordering::Less)
+        }
+    }
+
+    // https://w3c.github.io/selection-api/#dom-selection-stringifier
+    fn Stringifier(&self) -> DOMString {
+        self.range.get().map_or(DOMString::new(), |range| range.Stringifier())
+    }
+}
+
+/// Converts the number of characters layout rendered for a text before a point into an offset in
+/// UTF-16 code units into the text, counting each run of whitespace as a single character as it
+/// would be rendered by the default `white-space: normal`.
+pub fn rendered_index_to_offset(text: &str, rendered_index: usize) -> u32 {
+    let mut rendered = 0;
+    let mut offset = 0;
+    let mut previous_was_whitespace = false;
+    for character in text.chars() {
+        let is_whitespace = character.is_whitespace();
+        if !(is_whitespace && previous_was_whitespace) {
+            if rendered == rendered_index {
+                break;
+            }
+            rendered += 1;
+        }
+        previous_was_whitespace = is_whitespace;
+        offset += character.len_utf16() as u32;
+    }
+    offset
+}
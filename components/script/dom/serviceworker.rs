@@ -67,6 +67,10 @@ impl ServiceWorker {
     pub fn get_script_url(&self) -> Url {
         Url::parse(&self.script_url.borrow().clone()).unwrap()
     }
+
+    pub fn scope_url(&self) -> &Url {
+        &self.scope_url
+    }
 }
 
 impl ServiceWorkerMethods for ServiceWorker {
@@ -89,8 +93,15 @@ impl ServiceWorkerMethods for ServiceWorker {
         // Step 7
         let data = try!(StructuredCloneData::write(cx, message));
         let msg_vec = DOMMessage(data.move_to_arraybuffer());
-        let _ = self.global().r().constellation_chan().send(ScriptMsg::ForwardDOMMessage(msg_vec,
-                                                                                         self.scope_url.clone()));
+        // Step 8: only window clients can be the source of the message for now.
+        let global = self.global();
+        let client = match global.r() {
+            GlobalRef::Window(window) => Some(window.client_info()),
+            GlobalRef::Worker(_) => None,
+        };
+        let _ = global.r().constellation_chan().send(ScriptMsg::ForwardDOMMessage(msg_vec,
+                                                                                  client,
+                                                                                  self.scope_url.clone()));
         Ok(())
     }
 
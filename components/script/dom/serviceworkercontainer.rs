@@ -2,25 +2,39 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use dom::bindings::codegen::Bindings::ServiceWorkerContainerBinding::{ServiceWorkerContainerMethods, Wrap};
+use dom::bindings::codegen::Bindings::ServiceWorkerBinding::ServiceWorkerState;
 use dom::bindings::codegen::Bindings::ServiceWorkerContainerBinding::RegistrationOptions;
+use dom::bindings::cell::DOMRefCell;
 use dom::bindings::error::{Error, Fallible};
 use dom::bindings::global::GlobalRef;
 use dom::bindings::inheritance::Castable;
 use dom::bindings::js::{JS, MutNullableHeap, Root};
 use dom::bindings::reflector::{Reflectable, reflect_dom_object};
-use dom::bindings::str::USVString;
+use dom::bindings::str::{DOMString, USVString};
+use dom::bindings::structuredclone::StructuredCloneData;
+use dom::event::Event;
 use dom::eventtarget::EventTarget;
+use dom::messageevent::MessageEvent;
+use dom::promise::Promise;
 use dom::serviceworker::ServiceWorker;
-use dom::serviceworkerregistration::ServiceWorkerRegistration;
+use dom::serviceworkerregistration::{ServiceWorkerRegistration, longest_prefix_match};
+use js::jsapi::JSAutoCompartment;
+use js::jsval::UndefinedValue;
 use script_thread::ScriptThread;
 use std::ascii::AsciiExt;
 use std::default::Default;
+use std::rc::Rc;
+use url::Url;
 
 #[dom_struct]
 pub struct ServiceWorkerContainer {
     eventtarget: EventTarget,
     controller: MutNullableHeap<JS<ServiceWorker>>,
+    /// https://w3c.github.io/ServiceWorker/#navigator-service-worker-ready
+    #[ignore_heap_size_of = "Rc"]
+    ready_promise: DOMRefCell<Option<Rc<Promise>>>,
 }
 
 impl ServiceWorkerContainer {
@@ -28,6 +42,7 @@ impl ServiceWorkerContainer {
         ServiceWorkerContainer {
             eventtarget: EventTarget::new_inherited(),
             controller: Default::default(),
+            ready_promise: DOMRefCell::new(None),
         }
     }
 
@@ -47,6 +62,67 @@ impl Controllable for ServiceWorkerContainer {
     }
 }
 
+impl ServiceWorkerContainer {
+    #[allow(unrooted_must_root)]
+    fn ready_promise(&self) -> Rc<Promise> {
+        let mut ready_promise = self.ready_promise.borrow_mut();
+        if ready_promise.is_none() {
+            *ready_promise = Some(Promise::new(self.global().r()));
+        }
+        ready_promise.as_ref().unwrap().clone()
+    }
+
+    /// Resolves the ready promise with `registration`, if it is the registration whose
+    /// scope the client's URL matches.
+    #[allow(unrooted_must_root)]
+    fn resolve_ready(&self, registration: &ServiceWorkerRegistration) {
+        let scope = match Url::parse(&registration.Scope().0) {
+            Ok(scope) => scope,
+            Err(_) => return,
+        };
+        if !longest_prefix_match(&scope, &self.global().r().get_url()) {
+            return;
+        }
+        let global = self.global();
+        self.ready_promise().resolve_native(global.r().get_cx(), registration.reflector());
+    }
+
+    /// Makes the active worker of the registration for `scope_url` control this client,
+    /// firing controllerchange if the controller changed.
+    /// https://w3c.github.io/ServiceWorker/#clients-claim
+    pub fn claim(&self,
+                 script_url: Url,
+                 scope_url: Url,
+                 registration: Option<&ServiceWorkerRegistration>) {
+        let unchanged = self.controller.get().map_or(false, |controller| {
+            controller.get_script_url() == script_url && *controller.scope_url() == scope_url
+        });
+        if !unchanged {
+            let worker = ServiceWorker::install_serviceworker(self.global().r(), script_url, scope_url, true);
+            worker.set_transition_state(ServiceWorkerState::Activated);
+            self.set_controller(&worker);
+        }
+        if let Some(registration) = registration {
+            self.resolve_ready(registration);
+        }
+    }
+
+    /// Dispatches a message posted by the service worker of the given scope
+    /// through `Client.postMessage`.
+    /// https://w3c.github.io/ServiceWorker/#client-postmessage
+    pub fn deliver_message(&self, data: StructuredCloneData, scope_url: &Url) {
+        let global = self.global();
+        let cx = global.r().get_cx();
+        let _ac = JSAutoCompartment::new(cx, global.r().reflector().get_jsobject().get());
+        rooted!(in(cx) let mut message = UndefinedValue());
+        data.read(global.r(), message.handle_mut());
+        let event = MessageEvent::new(global.r(), atom!("message"), false, false, message.handle(),
+                                      DOMString::from(scope_url.origin().ascii_serialization()),
+                                      DOMString::new());
+        event.upcast::<Event>().fire(self.upcast());
+    }
+}
+
 impl ServiceWorkerContainerMethods for ServiceWorkerContainer {
     // https://w3c.github.io/ServiceWorker/#service-worker-container-controller-attribute
     fn GetController(&self) -> Option<Root<ServiceWorker>> {
@@ -98,8 +174,22 @@ impl ServiceWorkerContainerMethods for ServiceWorkerContainer {
         let worker_registration = ServiceWorkerRegistration::new(self.global().r(),
                                                                  script_url,
                                                                  scope.clone(),
+                                                                 options.updateViaCache,
                                                                  self);
         ScriptThread::set_registration(scope, &*worker_registration, self.global().r().pipeline_id());
+        self.resolve_ready(&worker_registration);
         Ok(worker_registration)
     }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/ServiceWorker/#navigator-service-worker-ready
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready_promise()
+    }
+
+    // https://w3c.github.io/ServiceWorker/#service-worker-container-oncontrollerchange-attribute
+    event_handler!(controllerchange, GetOncontrollerchange, SetOncontrollerchange);
+
+    // https://w3c.github.io/ServiceWorker/#service-worker-container-onmessage-attribute
+    event_handler!(message, GetOnmessage, SetOnmessage);
 }
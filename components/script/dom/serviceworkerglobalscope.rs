@@ -5,29 +5,38 @@
 use devtools;
 use devtools_traits::DevtoolScriptControlMsg;
 use dom::abstractworker::WorkerScriptMsg;
+use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding;
 use dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding::ServiceWorkerGlobalScopeMethods;
 use dom::bindings::global::{GlobalRef, global_root_from_context};
 use dom::bindings::inheritance::Castable;
-use dom::bindings::js::{Root, RootCollection};
+use dom::bindings::js::{JS, MutNullableHeap, Root, RootCollection, RootedReference};
 use dom::bindings::reflector::Reflectable;
 use dom::bindings::str::DOMString;
+use dom::bindings::structuredclone::StructuredCloneData;
+use dom::client::Client;
+use dom::clients::Clients;
 use dom::event::Event;
 use dom::eventtarget::EventTarget;
 use dom::extendableevent::ExtendableEvent;
 use dom::extendablemessageevent::ExtendableMessageEvent;
+use dom::serviceworkerregistration::scope_allowed;
 use dom::workerglobalscope::WorkerGlobalScope;
+use hyper::header::{CacheControl, CacheDirective, Headers};
 use ipc_channel::ipc::{self, IpcSender, IpcReceiver};
 use ipc_channel::router::ROUTER;
 use js::jsapi::{JS_SetInterruptCallback, JSAutoCompartment, JSContext};
 use js::jsval::UndefinedValue;
 use js::rust::Runtime;
 use msg::constellation_msg::PipelineId;
-use net_traits::{LoadContext, load_whole_resource, IpcSend, CustomResponseMediator};
+use net_traits::{CoreResourceThread, CustomResponseMediator, IpcSend, LoadContext, LoadOrigin, NetworkError};
+use net_traits::load_whole_resource_with_headers;
 use rand::random;
 use script_runtime::{CommonScriptMsg, StackRootTLS, get_reports, new_rt_and_cx, ScriptChan};
+use script_traits::{ClientInfo, DOMMessage, ServiceWorkerScript, UpdateViaCache};
 use script_traits::{TimerEvent, WorkerGlobalScopeInit, ScopeThings, ServiceWorkerMsg};
+use std::collections::HashMap;
 use std::sync::mpsc::{Receiver, RecvError, Select, Sender, channel};
 use std::thread;
 use std::time::Duration;
@@ -41,7 +50,9 @@ pub enum ServiceWorkerScriptMsg {
     /// Message common to all workers
     CommonWorker(WorkerScriptMsg),
     // Message to request a custom response by the service worker
-    Response(CustomResponseMediator)
+    Response(CustomResponseMediator),
+    /// Message posted to the service worker, with the window client that sent it
+    Message(StructuredCloneData, Option<ClientInfo>)
 }
 
 pub enum MixedMessage {
@@ -69,6 +80,122 @@ impl ScriptChan for ServiceWorkerChan {
     }
 }
 
+/// The scripts a service worker runs, and how they may be obtained.
+/// https://w3c.github.io/ServiceWorker/#dfn-script-resource-map
+#[derive(JSTraceable)]
+pub struct ServiceWorkerScripts {
+    /// The registration's script resource map when this worker started.
+    stored: HashMap<Url, ServiceWorkerScript>,
+    /// The scripts used by this worker, which become the registration's new map.
+    resource_map: HashMap<Url, ServiceWorkerScript>,
+    /// Whether this run checks the network for updated scripts.
+    check_for_updates: bool,
+    /// Whether the update check must ignore stored copies, e.g. because the
+    /// last check was more than 24 hours ago.
+    bypass_cache: bool,
+    update_via_cache: UpdateViaCache,
+    /// Whether any script was fetched from the network.
+    changed: bool,
+    /// Whether the main script has finished its initial evaluation.
+    evaluated: bool,
+}
+
+impl ServiceWorkerScripts {
+    pub fn new(stored: HashMap<Url, ServiceWorkerScript>,
+               check_for_updates: bool,
+               bypass_cache: bool,
+               update_via_cache: UpdateViaCache) -> ServiceWorkerScripts {
+        let resource_map = if check_for_updates { HashMap::new() } else { stored.clone() };
+        ServiceWorkerScripts {
+            stored: stored,
+            resource_map: resource_map,
+            check_for_updates: check_for_updates,
+            bypass_cache: bypass_cache,
+            update_via_cache: update_via_cache,
+            changed: false,
+            evaluated: false,
+        }
+    }
+
+    /// Whether a stored copy of the script may be used instead of the network.
+    /// https://w3c.github.io/ServiceWorker/#update-algorithm
+    fn may_use_stored(&self, is_main_script: bool) -> bool {
+        if !self.check_for_updates {
+            return true;
+        }
+        if self.bypass_cache {
+            return false;
+        }
+        match self.update_via_cache {
+            UpdateViaCache::All => true,
+            UpdateViaCache::Imports => !is_main_script,
+            UpdateViaCache::None => false,
+        }
+    }
+
+    /// Obtains a script from the script resource map or the network, recording
+    /// it in the map this worker will hand back to its registration.
+    pub fn fetch(&mut self,
+                 url: &Url,
+                 is_main_script: bool,
+                 core_resource_thread: &CoreResourceThread,
+                 load_origin: &LoadOrigin) -> Result<ServiceWorkerScript, NetworkError> {
+        if let Some(script) = self.resource_map.get(url) {
+            return Ok(script.clone());
+        }
+
+        let may_use_stored = self.may_use_stored(is_main_script);
+        if may_use_stored {
+            if let Some(script) = self.stored.get(url).cloned() {
+                self.resource_map.insert(url.clone(), script.clone());
+                return Ok(script);
+            }
+        }
+
+        // https://w3c.github.io/ServiceWorker/#importscripts step 2: an installed
+        // worker can only import scripts already in its script resource map.
+        if self.evaluated && !is_main_script {
+            return Err(NetworkError::Internal(format!("{} is not in the script resource map", url)));
+        }
+
+        let mut headers = Headers::new();
+        if is_main_script {
+            headers.set_raw("Service-Worker", vec![b"script".to_vec()]);
+        }
+        if !may_use_stored {
+            headers.set(CacheControl(vec![CacheDirective::NoCache]));
+        }
+        let (metadata, source) = try!(load_whole_resource_with_headers(LoadContext::Script,
+                                                                       core_resource_thread,
+                                                                       url.clone(),
+                                                                       load_origin,
+                                                                       headers));
+        let service_worker_allowed = metadata.headers.as_ref()
+            .and_then(|headers| headers.get_raw("Service-Worker-Allowed"))
+            .and_then(|values| values.first())
+            .and_then(|value| String::from_utf8(value.clone()).ok());
+        let script = ServiceWorkerScript {
+            final_url: metadata.final_url,
+            source: source,
+            service_worker_allowed: service_worker_allowed,
+        };
+        self.resource_map.insert(url.clone(), script.clone());
+        self.changed = true;
+        Ok(script)
+    }
+
+    /// Marks the main script as evaluated, returning the script resource map to
+    /// store in the registration if it changed during this run.
+    pub fn finish_evaluation(&mut self) -> Option<HashMap<Url, ServiceWorkerScript>> {
+        self.evaluated = true;
+        if self.check_for_updates || self.changed {
+            Some(self.resource_map.clone())
+        } else {
+            None
+        }
+    }
+}
+
 #[dom_struct]
 pub struct ServiceWorkerGlobalScope {
     workerglobalscope: WorkerGlobalScope,
@@ -82,6 +209,9 @@ pub struct ServiceWorkerGlobalScope {
     #[ignore_heap_size_of = "Defined in std"]
     swmanager_sender: IpcSender<ServiceWorkerMsg>,
     scope_url: Url,
+    #[ignore_heap_size_of = "Defined in script_traits"]
+    scripts: DOMRefCell<ServiceWorkerScripts>,
+    clients: MutNullableHeap<JS<Clients>>,
 }
 
 impl ServiceWorkerGlobalScope {
@@ -95,7 +225,8 @@ impl ServiceWorkerGlobalScope {
                      timer_event_chan: IpcSender<TimerEvent>,
                      timer_event_port: Receiver<()>,
                      swmanager_sender: IpcSender<ServiceWorkerMsg>,
-                     scope_url: Url)
+                     scope_url: Url,
+                     scripts: ServiceWorkerScripts)
                      -> ServiceWorkerGlobalScope {
         ServiceWorkerGlobalScope {
             workerglobalscope: WorkerGlobalScope::new_inherited(init,
@@ -109,7 +240,9 @@ impl ServiceWorkerGlobalScope {
             timer_event_port: timer_event_port,
             own_sender: own_sender,
             swmanager_sender: swmanager_sender,
-            scope_url: scope_url
+            scope_url: scope_url,
+            scripts: DOMRefCell::new(scripts),
+            clients: Default::default(),
         }
     }
 
@@ -123,7 +256,8 @@ impl ServiceWorkerGlobalScope {
                timer_event_chan: IpcSender<TimerEvent>,
                timer_event_port: Receiver<()>,
                swmanager_sender: IpcSender<ServiceWorkerMsg>,
-               scope_url: Url)
+               scope_url: Url,
+               scripts: ServiceWorkerScripts)
                -> Root<ServiceWorkerGlobalScope> {
         let cx = runtime.cx();
         let scope = box ServiceWorkerGlobalScope::new_inherited(init,
@@ -136,7 +270,8 @@ impl ServiceWorkerGlobalScope {
                                                                   timer_event_chan,
                                                                   timer_event_port,
                                                                   swmanager_sender,
-                                                                  scope_url);
+                                                                  scope_url,
+                                                                  scripts);
         ServiceWorkerGlobalScopeBinding::Wrap(cx, scope)
     }
 
@@ -146,7 +281,8 @@ impl ServiceWorkerGlobalScope {
                             receiver: Receiver<ServiceWorkerScriptMsg>,
                             devtools_receiver: IpcReceiver<DevtoolScriptControlMsg>,
                             swmanager_sender: IpcSender<ServiceWorkerMsg>,
-                            scope_url: Url) {
+                            scope_url: Url,
+                            mut scripts: ServiceWorkerScripts) {
         let ScopeThings { script_url,
                           pipeline_id,
                           init,
@@ -158,16 +294,22 @@ impl ServiceWorkerGlobalScope {
             thread_state::initialize(SCRIPT | IN_WORKER);
             let roots = RootCollection::new();
             let _stack_roots_tls = StackRootTLS::new(&roots);
-            let (url, source) = match load_whole_resource(LoadContext::Script,
-                                                          &init.resource_threads.sender(),
-                                                          script_url,
-                                                          &worker_load_origin) {
+            let (url, source) = match scripts.fetch(&script_url,
+                                                    true,
+                                                    &init.resource_threads.sender(),
+                                                    &worker_load_origin) {
                 Err(_) => {
                     println!("error loading script {}", serialized_worker_url);
                     return;
                 }
-                Ok((metadata, bytes)) => {
-                    (metadata.final_url, String::from_utf8(bytes).unwrap())
+                Ok(script) => {
+                    let allowed = script.service_worker_allowed.as_ref().map(|allowed| &**allowed);
+                    if !scope_allowed(&scope_url, &script_url, allowed) {
+                        warn!("{} is not allowed to control {}", serialized_worker_url, scope_url);
+                        let _ = swmanager_sender.send(ServiceWorkerMsg::RejectRegistration(scope_url));
+                        return;
+                    }
+                    (script.final_url, String::from_utf8(script.source).unwrap())
                 }
             };
 
@@ -181,7 +323,7 @@ impl ServiceWorkerGlobalScope {
             let global = ServiceWorkerGlobalScope::new(
                 init, url, pipeline_id, devtools_mpsc_port, runtime,
                 own_sender, receiver,
-                timer_ipc_chan, timer_port, swmanager_sender, scope_url);
+                timer_ipc_chan, timer_port, swmanager_sender, scope_url, scripts);
             let scope = global.upcast::<WorkerGlobalScope>();
 
             unsafe {
@@ -190,6 +332,7 @@ impl ServiceWorkerGlobalScope {
             }
 
             scope.execute_script(DOMString::from(source));
+            global.store_scripts();
             // Service workers are time limited
             spawn_named("SWTimeoutThread".to_owned(), move || {
                 let sw_lifetime_timeout = PREFS.get("dom.serviceworker.timeout_seconds").as_u64().unwrap();
@@ -239,13 +382,23 @@ impl ServiceWorkerGlobalScope {
         use self::ServiceWorkerScriptMsg::*;
 
         match msg {
-            CommonWorker(WorkerScriptMsg::DOMMessage(data)) => {
+            Message(data, client) => {
                 let scope = self.upcast::<WorkerGlobalScope>();
                 let target = self.upcast();
                 let _ac = JSAutoCompartment::new(scope.get_cx(), scope.reflector().get_jsobject().get());
                 rooted!(in(scope.get_cx()) let mut message = UndefinedValue());
                 data.read(GlobalRef::Worker(scope), message.handle_mut());
-                ExtendableMessageEvent::dispatch_jsval(target, GlobalRef::Worker(scope), message.handle());
+                // https://w3c.github.io/ServiceWorker/#service-worker-postmessage steps 8-9
+                let origin = client.as_ref().map_or(&self.scope_url, |client| &client.url).origin();
+                let source = client.map(|client| Client::new(GlobalRef::Worker(scope), client));
+                ExtendableMessageEvent::dispatch_jsval(target,
+                                                       GlobalRef::Worker(scope),
+                                                       message.handle(),
+                                                       DOMString::from(origin.ascii_serialization()),
+                                                       source.r());
+            },
+            CommonWorker(WorkerScriptMsg::DOMMessage(_)) => {
+                panic!("unexpected service worker event message!")
             },
             CommonWorker(WorkerScriptMsg::Common(CommonScriptMsg::RunnableMsg(_, runnable))) => {
                 runnable.handler()
@@ -302,6 +455,38 @@ impl ServiceWorkerGlobalScope {
         self.id
     }
 
+    /// https://w3c.github.io/ServiceWorker/#importscripts
+    pub fn load_imported_script(&self, url: &Url) -> Result<(Url, Vec<u8>), NetworkError> {
+        let scope = self.upcast::<WorkerGlobalScope>();
+        let script = try!(self.scripts.borrow_mut().fetch(url, false, &scope.resource_threads().sender(), scope));
+        Ok((script.final_url, script.source))
+    }
+
+    /// Sends a message to the window client in the given pipeline, through the
+    /// service worker manager.
+    /// https://w3c.github.io/ServiceWorker/#client-postmessage
+    pub fn post_message_to_client(&self, pipeline_id: PipelineId, data: StructuredCloneData) {
+        let msg = DOMMessage(data.move_to_arraybuffer());
+        let _ = self.swmanager_sender.send(ServiceWorkerMsg::PostMessageToClient(pipeline_id,
+                                                                                 msg,
+                                                                                 self.scope_url.clone()));
+    }
+
+    /// Asks the service worker manager to make this worker the controller of the
+    /// windows in its scope.
+    /// https://w3c.github.io/ServiceWorker/#clients-claim
+    pub fn claim_clients(&self) {
+        let _ = self.swmanager_sender.send(ServiceWorkerMsg::ClaimClients(self.scope_url.clone()));
+    }
+
+    /// Hands the scripts used by the initial evaluation back to the registration.
+    fn store_scripts(&self) {
+        if let Some(resource_map) = self.scripts.borrow_mut().finish_evaluation() {
+            let msg = ServiceWorkerMsg::StoreScripts(self.scope_url.clone(), resource_map);
+            let _ = self.swmanager_sender.send(msg);
+        }
+    }
+
     pub fn process_event(&self, msg: CommonScriptMsg) {
         self.handle_script_event(ServiceWorkerScriptMsg::CommonWorker(WorkerScriptMsg::Common(msg)));
     }
@@ -334,6 +519,11 @@ unsafe extern "C" fn interrupt_callback(cx: *mut JSContext) -> bool {
 }
 
 impl ServiceWorkerGlobalScopeMethods for ServiceWorkerGlobalScope {
+    // https://w3c.github.io/ServiceWorker/#service-worker-global-scope-clients
+    fn Clients(&self) -> Root<Clients> {
+        self.clients.or_init(|| Clients::new(GlobalRef::Worker(self.upcast::<WorkerGlobalScope>())))
+    }
+
     // https://w3c.github.io/ServiceWorker/#service-worker-global-scope-onmessage-attribute
     event_handler!(message, GetOnmessage, SetOnmessage);
 }
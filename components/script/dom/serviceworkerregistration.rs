@@ -4,6 +4,7 @@
 
 use dom::bindings::codegen::Bindings::ServiceWorkerBinding::ServiceWorkerState;
 use dom::bindings::codegen::Bindings::ServiceWorkerRegistrationBinding::{ServiceWorkerRegistrationMethods, Wrap};
+use dom::bindings::codegen::Bindings::ServiceWorkerRegistrationBinding::ServiceWorkerUpdateViaCache;
 use dom::bindings::global::GlobalRef;
 use dom::bindings::js::{JS, Root};
 use dom::bindings::reflector::reflect_dom_object;
@@ -12,7 +13,7 @@ use dom::eventtarget::EventTarget;
 use dom::serviceworker::ServiceWorker;
 use dom::serviceworkercontainer::Controllable;
 use dom::workerglobalscope::prepare_workerscope_init;
-use script_traits::{WorkerScriptLoadOrigin, ScopeThings};
+use script_traits::{WorkerScriptLoadOrigin, ScopeThings, UpdateViaCache};
 use url::Url;
 
 #[dom_struct]
@@ -21,35 +22,52 @@ pub struct ServiceWorkerRegistration {
     active: Option<JS<ServiceWorker>>,
     installing: Option<JS<ServiceWorker>>,
     waiting: Option<JS<ServiceWorker>>,
-    scope: String
+    scope: String,
+    update_via_cache: ServiceWorkerUpdateViaCache,
 }
 
 impl ServiceWorkerRegistration {
-    fn new_inherited(active_sw: &ServiceWorker, scope: Url) -> ServiceWorkerRegistration {
+    fn new_inherited(active_sw: &ServiceWorker,
+                     scope: Url,
+                     update_via_cache: ServiceWorkerUpdateViaCache) -> ServiceWorkerRegistration {
         ServiceWorkerRegistration {
             eventtarget: EventTarget::new_inherited(),
             active: Some(JS::from_ref(active_sw)),
             installing: None,
             waiting: None,
             scope: scope.as_str().to_owned(),
+            update_via_cache: update_via_cache,
         }
     }
     #[allow(unrooted_must_root)]
     pub fn new(global: GlobalRef,
                script_url: Url,
                scope: Url,
+               update_via_cache: ServiceWorkerUpdateViaCache,
                container: &Controllable) -> Root<ServiceWorkerRegistration> {
         let active_worker = ServiceWorker::install_serviceworker(global, script_url.clone(), scope.clone(), true);
         active_worker.set_transition_state(ServiceWorkerState::Installed);
         container.set_controller(&*active_worker.clone());
-        reflect_dom_object(box ServiceWorkerRegistration::new_inherited(&*active_worker, scope), global, Wrap)
+        reflect_dom_object(box ServiceWorkerRegistration::new_inherited(&*active_worker, scope, update_via_cache),
+                           global,
+                           Wrap)
     }
 
     pub fn get_installed(&self) -> &ServiceWorker {
         self.active.as_ref().unwrap()
     }
 
-    pub fn create_scope_things(global: GlobalRef, script_url: Url) -> ScopeThings {
+    pub fn update_via_cache(&self) -> UpdateViaCache {
+        match self.update_via_cache {
+            ServiceWorkerUpdateViaCache::Imports => UpdateViaCache::Imports,
+            ServiceWorkerUpdateViaCache::All => UpdateViaCache::All,
+            ServiceWorkerUpdateViaCache::None => UpdateViaCache::None,
+        }
+    }
+
+    pub fn create_scope_things(global: GlobalRef,
+                               script_url: Url,
+                               update_via_cache: UpdateViaCache) -> ScopeThings {
         let worker_load_origin = WorkerScriptLoadOrigin {
             referrer_url: None,
             referrer_policy: None,
@@ -64,7 +82,8 @@ impl ServiceWorkerRegistration {
             init: init,
             worker_load_origin: worker_load_origin,
             devtools_chan: global.devtools_chan(),
-            worker_id: worker_id
+            worker_id: worker_id,
+            update_via_cache: update_via_cache,
         }
     }
 }
@@ -82,6 +101,28 @@ pub fn longest_prefix_match(stored_scope: &Url, potential_match: &Url) -> bool {
     stored_scope.path().chars().zip(potential_match.path().chars()).all(|(scope, matched)| scope == matched)
 }
 
+/// Returns the scope out of `scopes` that `url` matches, preferring the longest one.
+/// https://w3c.github.io/ServiceWorker/#scope-match-algorithm
+pub fn longest_matching_scope<'a, I>(scopes: I, url: &Url) -> Option<&'a Url>
+    where I: IntoIterator<Item=&'a Url>
+{
+    scopes.into_iter()
+          .filter(|scope| longest_prefix_match(scope, url))
+          .max_by_key(|scope| scope.path().len())
+}
+
+/// Whether a worker whose main script is `script_url` may control `scope`. The scope
+/// must be within the script's directory, unless the script's response widened the
+/// maximum scope with a Service-Worker-Allowed header.
+/// https://w3c.github.io/ServiceWorker/#update-algorithm
+pub fn scope_allowed(scope: &Url, script_url: &Url, service_worker_allowed: Option<&str>) -> bool {
+    let max_scope = match script_url.join(service_worker_allowed.unwrap_or("./")) {
+        Ok(url) => url,
+        Err(_) => return false,
+    };
+    longest_prefix_match(&max_scope, scope)
+}
+
 impl ServiceWorkerRegistrationMethods for ServiceWorkerRegistration {
     // https://w3c.github.io/ServiceWorker/#service-worker-registration-installing-attribute
     fn GetInstalling(&self) -> Option<Root<ServiceWorker>> {
@@ -102,4 +143,9 @@ impl ServiceWorkerRegistrationMethods for ServiceWorkerRegistration {
     fn Scope(&self) -> USVString {
         USVString(self.scope.clone())
     }
+
+    // https://w3c.github.io/ServiceWorker/#service-worker-registration-updateviacache
+    fn UpdateViaCache(&self) -> ServiceWorkerUpdateViaCache {
+        self.update_via_cache
+    }
 }
@@ -22,7 +22,7 @@ use dom::htmlimageelement::HTMLImageElement;
 use dom::node::Node;
 use dom::window::Window;
 use encoding::all::UTF_8;
-use encoding::types::{DecoderTrap, Encoding};
+use encoding::types::{DecoderTrap, Encoding, EncodingRef};
 use html5ever::tokenizer;
 use html5ever::tree_builder;
 use html5ever::tree_builder::{TreeBuilder, TreeBuilderOpts};
@@ -34,11 +34,13 @@ use msg::constellation_msg::PipelineId;
 use net_traits::{AsyncResponseListener, Metadata, NetworkError};
 use network_listener::PreInvoke;
 use parse::{Parser, ParserRef, TrustedParser};
+use parse::encoding::{DocumentDecoder, EncodingConfidence, PRESCAN_LENGTH, get_an_encoding, prescan, sniff_bom};
 use profile_traits::time::{TimerMetadata, TimerMetadataFrameType, TimerMetadataReflowType, profile};
 use profile_traits::time::ProfilerCategory;
 use script_thread::ScriptThread;
 use std::cell::Cell;
 use std::default::Default;
+use std::mem;
 use url::Url;
 use util::resource_files::read_resource_file;
 
@@ -69,15 +71,18 @@ pub struct ParserContext {
     id: PipelineId,
     /// The URL for this document.
     url: Url,
+    /// The encoding to decode this document with instead of determining it, if any.
+    encoding_override: Option<EncodingRef>,
 }
 
 impl ParserContext {
-    pub fn new(id: PipelineId, url: Url) -> ParserContext {
+    pub fn new(id: PipelineId, url: Url, encoding_override: Option<EncodingRef>) -> ParserContext {
         ParserContext {
             parser: None,
             is_synthesized_document: false,
             id: id,
             url: url,
+            encoding_override: encoding_override,
         }
     }
 }
@@ -98,6 +103,8 @@ impl AsyncResponseListener for ParserContext {
         };
         let content_type =
             metadata.clone().and_then(|meta| meta.content_type).map(Serde::into_inner);
+        let transport_encoding =
+            metadata.as_ref().and_then(|meta| meta.charset.as_ref()).and_then(|charset| get_an_encoding(charset));
         let parser = match ScriptThread::page_headers_available(&self.id,
                                                                 metadata) {
             Some(parser) => parser,
@@ -105,6 +112,11 @@ impl AsyncResponseListener for ParserContext {
         };
 
         let parser = parser.r();
+        if let ParserRef::HTML(parser) = parser {
+            if let Some(encoding) = self.encoding_override.or(transport_encoding) {
+                parser.set_known_encoding(encoding);
+            }
+        }
         self.parser = Some(match parser {
             ParserRef::HTML(parser) => TrustedParser::HTML(
                                         Trusted::new(parser)),
@@ -166,13 +178,18 @@ impl AsyncResponseListener for ParserContext {
 
     fn data_available(&mut self, payload: Vec<u8>) {
         if !self.is_synthesized_document {
-            // FIXME: use Vec<u8> (html5ever #34)
-            let data = UTF_8.decode(&payload, DecoderTrap::Replace).unwrap();
             let parser = match self.parser.as_ref() {
                 Some(parser) => parser.root(),
                 None => return,
             };
-            parser.r().parse_chunk(data);
+            match parser.r() {
+                ParserRef::HTML(parser) => parser.parse_bytes(&payload),
+                ParserRef::XML(parser) => {
+                    // FIXME: use Vec<u8> (html5ever #34)
+                    let data = UTF_8.decode(&payload, DecoderTrap::Replace).unwrap();
+                    parser.parse_chunk(data);
+                },
+            }
         }
     }
 
@@ -182,6 +199,12 @@ impl AsyncResponseListener for ParserContext {
             None => return,
         };
 
+        if !self.is_synthesized_document {
+            if let ParserRef::HTML(parser) = parser.r() {
+                parser.finish_bytes();
+            }
+        }
+
         if let Err(NetworkError::Internal(ref reason)) = status {
             // Show an error page for network errors,
             // certificate errors are handled earlier.
@@ -197,6 +220,7 @@ impl AsyncResponseListener for ParserContext {
             debug!("Failed to load page URL {}, error: {:?}", self.url, err);
         }
 
+        parser.r().document().set_response_end();
         parser.r().document().finish_load(LoadType::PageSource(self.url.clone()));
 
         parser.r().last_chunk_received().set(true);
@@ -222,13 +246,27 @@ pub struct ServoHTMLParser {
     suspended: Cell<bool>,
     /// Whether to expect any further input from the associated network request.
     last_chunk_received: Cell<bool>,
+    /// True if this parser was aborted, and discards any further input.
+    aborted: Cell<bool>,
     /// The pipeline associated with this parse, unavailable if this parse does not
     /// correspond to a page load.
     pipeline: Option<PipelineId>,
+    /// The bytes of the document that were received before its encoding was determined.
+    network_input: DOMRefCell<Vec<u8>>,
+    /// The decoder of the document, once its encoding was determined.
+    #[ignore_heap_size_of = "Defined in rust-encoding"]
+    network_decoder: DOMRefCell<Option<DocumentDecoder>>,
+    /// The encoding that the document is known to be in, if it doesn't start with a byte
+    /// order mark, from the transport layer or because it is loaded again in that encoding.
+    #[ignore_heap_size_of = "Defined in rust-encoding"]
+    known_encoding: Cell<Option<EncodingRef>>,
 }
 
 impl<'a> Parser for &'a ServoHTMLParser {
     fn parse_chunk(self, input: String) {
+        if self.aborted.get() {
+            return;
+        }
         self.document.set_current_parser(Some(ParserRef::HTML(self)));
         self.pending_input.borrow_mut().push(input);
         if !self.is_suspended() {
@@ -274,7 +312,11 @@ impl ServoHTMLParser {
             document: JS::from_ref(document),
             suspended: Cell::new(false),
             last_chunk_received: Cell::new(false),
+            aborted: Cell::new(false),
             pipeline: pipeline,
+            network_input: DOMRefCell::new(vec![]),
+            network_decoder: DOMRefCell::new(None),
+            known_encoding: Cell::new(None),
         };
 
         reflect_dom_object(box parser, GlobalRef::Window(document.window()),
@@ -311,7 +353,11 @@ impl ServoHTMLParser {
             document: JS::from_ref(document),
             suspended: Cell::new(false),
             last_chunk_received: Cell::new(true),
+            aborted: Cell::new(false),
             pipeline: None,
+            network_input: DOMRefCell::new(vec![]),
+            network_decoder: DOMRefCell::new(None),
+            known_encoding: Cell::new(None),
         };
 
         reflect_dom_object(box parser, GlobalRef::Window(document.window()),
@@ -335,6 +381,54 @@ impl ServoHTMLParser {
         &self.pending_input
     }
 
+    pub fn set_known_encoding(&self, encoding: EncodingRef) {
+        self.known_encoding.set(Some(encoding));
+    }
+
+    /// Decodes and parses the next bytes of the document. They are kept until enough of
+    /// them were received to determine the encoding of the document.
+    pub fn parse_bytes(&self, bytes: &[u8]) {
+        if self.network_decoder.borrow().is_none() {
+            self.network_input.borrow_mut().extend_from_slice(bytes);
+            if self.network_input.borrow().len() >= PRESCAN_LENGTH {
+                self.determine_encoding();
+            }
+            return;
+        }
+        let chunk = self.network_decoder.borrow_mut().as_mut().unwrap().decode(bytes);
+        self.parse_chunk(chunk);
+    }
+
+    /// Decodes and parses what is left of the document at its end.
+    pub fn finish_bytes(&self) {
+        if self.network_decoder.borrow().is_none() {
+            self.determine_encoding();
+        }
+        let chunk = self.network_decoder.borrow_mut().as_mut().unwrap().finish();
+        if !chunk.is_empty() {
+            self.parse_chunk(chunk);
+        }
+    }
+
+    /// Determines the encoding of the document from the bytes received so far, and parses
+    /// them.
+    /// https://html.spec.whatwg.org/multipage/#encoding-sniffing-algorithm
+    fn determine_encoding(&self) {
+        let input = mem::replace(&mut *self.network_input.borrow_mut(), vec![]);
+        let (encoding, confidence, start) = match (sniff_bom(&input), self.known_encoding.get()) {
+            (Some((encoding, length)), _) => (encoding, EncodingConfidence::Certain, length),
+            (None, Some(encoding)) => (encoding, EncodingConfidence::Certain, 0),
+            // Without any declaration, documents are assumed to be in UTF-8.
+            (None, None) => (prescan(&input).unwrap_or(UTF_8), EncodingConfidence::Tentative, 0),
+        };
+        self.document.set_encoding(encoding);
+        self.document.set_encoding_confidence(confidence);
+
+        let mut decoder = DocumentDecoder::new(encoding);
+        let chunk = decoder.decode(&input[start..]);
+        *self.network_decoder.borrow_mut() = Some(decoder);
+        self.parse_chunk(chunk);
+    }
 }
 
 impl ServoHTMLParser {
@@ -351,6 +445,10 @@ impl ServoHTMLParser {
     }
 
     fn do_parse_sync(&self) {
+        if self.aborted.get() {
+            return;
+        }
+
         // This parser will continue to parse while there is either pending input or
         // the parser remains unsuspended.
         loop {
@@ -363,6 +461,12 @@ impl ServoHTMLParser {
                 self.tokenizer.borrow_mut().run();
             }
 
+            // Document parsing was aborted by a script.
+            if self.aborted.get() {
+                pending_input.clear();
+                return;
+            }
+
             // Document parsing is blocked on an external resource.
             if self.suspended.get() {
                 return;
@@ -397,6 +501,14 @@ impl ServoHTMLParser {
         self.suspended.get()
     }
 
+    /// Stop parsing, and discard any further input.
+    /// https://html.spec.whatwg.org/multipage/#abort-a-parser
+    pub fn abort(&self) {
+        self.aborted.set(true);
+        self.suspended.set(false);
+        self.last_chunk_received.set(true);
+    }
+
     pub fn document(&self) -> &Document {
         &self.document
     }
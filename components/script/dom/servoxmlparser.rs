@@ -27,6 +27,8 @@ pub type Tokenizer = tokenizer::XmlTokenizer<XmlTreeBuilder<JS<Node>, Sink>>;
 pub struct Sink {
     pub base_url: Option<Url>,
     pub document: JS<Document>,
+    /// The first well-formedness error reported by the tree builder, if any.
+    pub error: Option<String>,
 }
 
 #[must_root]
@@ -43,6 +45,8 @@ pub struct ServoXMLParser {
     suspended: Cell<bool>,
     /// Whether to expect any further input from the associated network request.
     last_chunk_received: Cell<bool>,
+    /// True if this parser was aborted, and discards any further input.
+    aborted: Cell<bool>,
     /// The pipeline associated with this parse, unavailable if this parse does not
     /// correspond to a page load.
     pipeline: Option<PipelineId>,
@@ -50,6 +54,9 @@ pub struct ServoXMLParser {
 
 impl<'a> Parser for &'a ServoXMLParser {
     fn parse_chunk(self, input: String) {
+        if self.aborted.get() {
+            return;
+        }
         self.document.set_current_parser(Some(ParserRef::XML(self)));
         self.pending_input.borrow_mut().push(input);
         if !self.is_suspended() {
@@ -79,6 +86,7 @@ impl ServoXMLParser {
         let sink = Sink {
             base_url: base_url,
             document: JS::from_ref(document),
+            error: None,
         };
 
         let tb = XmlTreeBuilder::new(sink);
@@ -92,6 +100,7 @@ impl ServoXMLParser {
             document: JS::from_ref(document),
             suspended: Cell::new(false),
             last_chunk_received: Cell::new(false),
+            aborted: Cell::new(false),
             pipeline: pipeline,
         };
 
@@ -118,7 +127,19 @@ impl ServoXMLParser {
         self.suspended.get()
     }
 
+    /// Stop parsing, and discard any further input.
+    /// https://html.spec.whatwg.org/multipage/#abort-a-parser
+    pub fn abort(&self) {
+        self.aborted.set(true);
+        self.suspended.set(false);
+        self.last_chunk_received.set(true);
+    }
+
     pub fn parse_sync(&self) {
+        if self.aborted.get() {
+            return;
+        }
+
         // This parser will continue to parse while there is either pending input or
         // the parser remains unsuspended.
         loop {
@@ -131,6 +152,12 @@ impl ServoXMLParser {
                 self.tokenizer.borrow_mut().run();
             }
 
+            // Document parsing was aborted by a script.
+            if self.aborted.get() {
+                pending_input.clear();
+                return;
+            }
+
             // Document parsing is blocked on an external resource.
             if self.suspended.get() {
                 return;
@@ -169,6 +196,11 @@ impl ServoXMLParser {
     pub fn tokenizer(&self) -> &DOMRefCell<Tokenizer> {
         &self.tokenizer
     }
+
+    /// The first parse error encountered so far; any error makes an XML document not well-formed.
+    pub fn error(&self) -> Option<String> {
+        self.tokenizer.borrow().sink().sink().error.clone()
+    }
 }
 
 struct Tracer {
@@ -0,0 +1,380 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::HTMLMediaElementBinding::HTMLMediaElementMethods;
+use dom::bindings::codegen::Bindings::MediaSourceBinding::{EndOfStreamError, MediaSourceMethods, ReadyState};
+use dom::bindings::codegen::Bindings::SourceBufferBinding::{self, SourceBufferMethods};
+use dom::bindings::conversions::array_buffer_or_view_to_vec;
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::refcounted::Trusted;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::eventtarget::EventTarget;
+use dom::mediasource::MediaSource;
+use dom::timeranges::TimeRanges;
+use dom::window::Window;
+use js::jsapi::{JSContext, JSObject};
+use media::webm::{WebmParser, WebmSegment, WebmTrack};
+use script_thread::Runnable;
+use std::cell::Cell;
+use std::f64;
+use string_cache::Atom;
+use task_source::TaskSource;
+
+// https://w3c.github.io/media-source/#sourcebuffer
+#[dom_struct]
+pub struct SourceBuffer {
+    eventtarget: EventTarget,
+    /// The media source the buffer belongs to, until it is removed from it.
+    media_source: MutNullableHeap<JS<MediaSource>>,
+    /// The index of the buffer in the data of its media source.
+    index: usize,
+    updating: Cell<bool>,
+    /// Whether the update going on is a range removal, which can't be
+    /// aborted.
+    removing: Cell<bool>,
+    timestamp_offset: Cell<f64>,
+    #[ignore_heap_size_of = "Defined in the media module"]
+    parser: DOMRefCell<WebmParser>,
+    /// The tracks of the first initialization segment, once it was received.
+    /// https://w3c.github.io/media-source/#first-init-segment-received-flag
+    #[ignore_heap_size_of = "Defined in the media module"]
+    tracks: DOMRefCell<Option<Vec<(u64, WebmTrack)>>>,
+    /// Counts the updates, so that the one that was aborted doesn't run.
+    generation: Cell<u32>,
+}
+
+impl SourceBuffer {
+    fn new_inherited(media_source: &MediaSource, index: usize) -> SourceBuffer {
+        SourceBuffer {
+            eventtarget: EventTarget::new_inherited(),
+            media_source: MutNullableHeap::new(Some(media_source)),
+            index: index,
+            updating: Cell::new(false),
+            removing: Cell::new(false),
+            timestamp_offset: Cell::new(0.),
+            parser: DOMRefCell::new(WebmParser::new()),
+            tracks: DOMRefCell::new(None),
+            generation: Cell::new(0),
+        }
+    }
+
+    pub fn new(window: &Window, media_source: &MediaSource, index: usize) -> Root<SourceBuffer> {
+        reflect_dom_object(box SourceBuffer::new_inherited(media_source, index),
+                           GlobalRef::Window(window),
+                           SourceBufferBinding::Wrap)
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn is_updating(&self) -> bool {
+        self.updating.get()
+    }
+
+    pub fn received_init_segment(&self) -> bool {
+        self.tracks.borrow().is_some()
+    }
+
+    /// Forgets the media source the buffer was removed from.
+    pub fn detach(&self) {
+        self.media_source.set(None);
+    }
+
+    fn queue_simple_event(&self, name: &'static str) {
+        let global = self.global();
+        let window = global.r().as_window();
+        window.dom_manipulation_task_source().queue_simple_event(self.upcast(), Atom::from(name), window);
+    }
+
+    /// The media source of a buffer that wasn't removed from it.
+    fn attached_media_source(&self) -> Fallible<Root<MediaSource>> {
+        self.media_source.get().ok_or(Error::InvalidState)
+    }
+
+    /// Aborts the append going on, if any.
+    /// https://w3c.github.io/media-source/#dom-sourcebuffer-abort (step 4)
+    pub fn abort_update(&self) {
+        if !self.updating.get() {
+            return;
+        }
+        self.generation.set(self.generation.get() + 1);
+        self.updating.set(false);
+        self.removing.set(false);
+        self.queue_simple_event("abort");
+        self.queue_simple_event("updateend");
+    }
+
+    /// Starts an update, which runs in a task of its own.
+    fn start_update(&self, update: Update) {
+        self.updating.set(true);
+        self.queue_simple_event("updatestart");
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        let task = box SourceBufferUpdateTask {
+            buffer: Trusted::new(self),
+            generation: generation,
+            update: update,
+        };
+        let global = self.global();
+        let window = global.r().as_window();
+        let _ = window.dom_manipulation_task_source().queue(task, GlobalRef::Window(window));
+    }
+
+    fn run_update(&self, generation: u32, update: Update) {
+        if generation != self.generation.get() || !self.updating.get() {
+            return;
+        }
+        let media_source = match self.media_source.get() {
+            Some(media_source) => media_source,
+            None => return,
+        };
+        match update {
+            Update::Append(data) => {
+                if self.buffer_append(&media_source, &data).is_err() {
+                    return self.append_error(&media_source);
+                }
+            },
+            // https://w3c.github.io/media-source/#sourcebuffer-range-removal
+            Update::Remove(start, end) => {
+                media_source.data().remove_frames(self.index, start, end);
+            },
+        }
+        self.updating.set(false);
+        self.removing.set(false);
+        self.queue_simple_event("update");
+        self.queue_simple_event("updateend");
+        media_source.buffers_changed();
+    }
+
+    /// Parses the data appended, and adds the frames of its media segments
+    /// to the track buffers.
+    /// https://w3c.github.io/media-source/#sourcebuffer-buffer-append
+    fn buffer_append(&self, media_source: &MediaSource, data: &[u8]) -> Result<(), ()> {
+        // https://w3c.github.io/media-source/#sourcebuffer-segment-parser-loop
+        let segments = try!(self.parser.borrow_mut().append(data));
+        for segment in segments {
+            match segment {
+                WebmSegment::Init(duration, tracks) => {
+                    try!(self.init_segment_received(media_source, duration, tracks));
+                },
+                WebmSegment::Frames(mut frames) => {
+                    // https://w3c.github.io/media-source/#sourcebuffer-coded-frame-processing
+                    let offset = self.timestamp_offset.get();
+                    for frame in &mut frames {
+                        frame.time += offset;
+                    }
+                    frames.retain(|frame| frame.time >= 0.);
+                    media_source.data().add_frames(self.index, frames);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#sourcebuffer-init-segment-received
+    fn init_segment_received(&self,
+                             media_source: &MediaSource,
+                             duration: Option<f64>,
+                             tracks: Vec<(u64, WebmTrack)>)
+                             -> Result<(), ()> {
+        // Step 1
+        if media_source.Duration().is_nan() {
+            media_source.change_duration(duration.unwrap_or(f64::INFINITY));
+        }
+
+        // Step 2
+        if tracks.is_empty() {
+            return Err(());
+        }
+
+        // Step 3: the tracks of the next segments have to be the same.
+        if let Some(ref first_tracks) = *self.tracks.borrow() {
+            let same_tracks = first_tracks.len() == tracks.len() &&
+                              first_tracks.iter().zip(tracks.iter()).all(|(&(a, ref a_track), &(b, ref b_track))| {
+                a == b && same_codec(a_track, b_track)
+            });
+            return if same_tracks { Ok(()) } else { Err(()) };
+        }
+
+        // Step 5
+        media_source.data().set_tracks(self.index, tracks.clone());
+        *self.tracks.borrow_mut() = Some(tracks);
+        media_source.activate_source_buffer(self);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#sourcebuffer-append-error
+    fn append_error(&self, media_source: &MediaSource) {
+        // Step 1
+        self.parser.borrow_mut().reset();
+        // Step 2
+        self.updating.set(false);
+        // Steps 3 and 4
+        self.queue_simple_event("error");
+        self.queue_simple_event("updateend");
+        // Step 5
+        media_source.end_of_stream(Some(EndOfStreamError::Decode));
+    }
+
+    // https://w3c.github.io/media-source/#sourcebuffer-prepare-append
+    fn prepare_append(&self) -> Fallible<Root<MediaSource>> {
+        // Step 1
+        let media_source = try!(self.attached_media_source());
+        // Step 2
+        if self.updating.get() {
+            return Err(Error::InvalidState);
+        }
+        // Step 3
+        if media_source.media_element().map_or(false, |element| element.GetError().is_some()) {
+            return Err(Error::InvalidState);
+        }
+        // Step 4
+        media_source.reopen_if_ended();
+        // TODO step 5 (coded frame eviction)
+        Ok(media_source)
+    }
+}
+
+/// Whether two tracks are of the same codec.
+fn same_codec(a: &WebmTrack, b: &WebmTrack) -> bool {
+    match (a, b) {
+        (&WebmTrack::Vp8 { .. }, &WebmTrack::Vp8 { .. }) |
+        (&WebmTrack::Opus { .. }, &WebmTrack::Opus { .. }) => true,
+        _ => false,
+    }
+}
+
+impl SourceBufferMethods for SourceBuffer {
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-updating
+    fn Updating(&self) -> bool {
+        self.updating.get()
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-buffered
+    fn Buffered(&self) -> Fallible<Root<TimeRanges>> {
+        let media_source = try!(self.attached_media_source());
+        let ranges = media_source.data().buffered(self.index);
+        let global = self.global();
+        Ok(TimeRanges::new(global.r().as_window(), ranges))
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset
+    fn TimestampOffset(&self) -> Finite<f64> {
+        Finite::wrap(self.timestamp_offset.get())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-timestampoffset
+    fn SetTimestampOffset(&self, value: Finite<f64>) -> ErrorResult {
+        // Steps 2 and 3
+        let media_source = try!(self.attached_media_source());
+        if self.updating.get() {
+            return Err(Error::InvalidState);
+        }
+        // Step 4
+        media_source.reopen_if_ended();
+        // Step 7
+        self.timestamp_offset.set(*value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdatestart
+    event_handler!(updatestart, GetOnupdatestart, SetOnupdatestart);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdate
+    event_handler!(update, GetOnupdate, SetOnupdate);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onupdateend
+    event_handler!(updateend, GetOnupdateend, SetOnupdateend);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-appendbuffer
+    fn AppendBuffer(&self, _cx: *mut JSContext, data: *mut JSObject) -> ErrorResult {
+        let data = try!(array_buffer_or_view_to_vec(data).ok_or(
+            Error::Type("Argument is not an ArrayBuffer or ArrayBufferView".to_owned())));
+        // Step 1
+        try!(self.prepare_append());
+        // Steps 2 to 5
+        self.start_update(Update::Append(data));
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-abort
+    fn Abort(&self) -> ErrorResult {
+        // Steps 1 and 2
+        let media_source = try!(self.attached_media_source());
+        if media_source.ReadyState() != ReadyState::Open {
+            return Err(Error::InvalidState);
+        }
+        // Step 3
+        if self.removing.get() {
+            return Err(Error::InvalidState);
+        }
+        // Step 4
+        self.abort_update();
+        // Step 5
+        self.parser.borrow_mut().reset();
+        Ok(())
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebuffer-remove
+    fn Remove(&self, start: Finite<f64>, end: f64) -> ErrorResult {
+        // Steps 1 and 2
+        let media_source = try!(self.attached_media_source());
+        if self.updating.get() {
+            return Err(Error::InvalidState);
+        }
+        // Steps 3 to 5
+        let duration = media_source.Duration();
+        if duration.is_nan() {
+            return Err(Error::Type("The duration of the media source is unknown".to_owned()));
+        }
+        if *start < 0. || *start > duration {
+            return Err(Error::Type("The start of the range is out of the media source".to_owned()));
+        }
+        if end.is_nan() || end <= *start {
+            return Err(Error::Type("The end of the range is before its start".to_owned()));
+        }
+        // Step 6
+        media_source.reopen_if_ended();
+        // Step 7
+        self.removing.set(true);
+        self.start_update(Update::Remove(*start, end));
+        Ok(())
+    }
+}
+
+enum Update {
+    /// https://w3c.github.io/media-source/#sourcebuffer-buffer-append
+    Append(Vec<u8>),
+    /// https://w3c.github.io/media-source/#sourcebuffer-range-removal
+    Remove(f64, f64),
+}
+
+/// Runs the asynchronous part of an append or a removal.
+struct SourceBufferUpdateTask {
+    buffer: Trusted<SourceBuffer>,
+    generation: u32,
+    update: Update,
+}
+
+impl Runnable for SourceBufferUpdateTask {
+    fn name(&self) -> &'static str { "SourceBufferUpdateTask" }
+
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        this.buffer.root().run_update(this.generation, this.update);
+    }
+}
@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::SourceBufferListBinding::{self, SourceBufferListMethods};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::reflect_dom_object;
+use dom::eventtarget::EventTarget;
+use dom::sourcebuffer::SourceBuffer;
+use dom::window::Window;
+
+// https://w3c.github.io/media-source/#sourcebufferlist
+#[dom_struct]
+pub struct SourceBufferList {
+    eventtarget: EventTarget,
+    buffers: DOMRefCell<Vec<JS<SourceBuffer>>>,
+}
+
+impl SourceBufferList {
+    fn new_inherited() -> SourceBufferList {
+        SourceBufferList {
+            eventtarget: EventTarget::new_inherited(),
+            buffers: DOMRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(window: &Window) -> Root<SourceBufferList> {
+        reflect_dom_object(box SourceBufferList::new_inherited(),
+                           GlobalRef::Window(window),
+                           SourceBufferListBinding::Wrap)
+    }
+
+    pub fn buffers(&self) -> Vec<Root<SourceBuffer>> {
+        self.buffers.borrow().iter().map(|buffer| Root::from_ref(&**buffer)).collect()
+    }
+
+    pub fn contains(&self, buffer: &SourceBuffer) -> bool {
+        self.buffers.borrow().iter().any(|other| *other == JS::from_ref(buffer))
+    }
+
+    pub fn push(&self, buffer: &SourceBuffer) {
+        self.buffers.borrow_mut().push(JS::from_ref(buffer));
+    }
+
+    /// Removes a buffer, returning whether it was in the list.
+    pub fn remove(&self, buffer: &SourceBuffer) -> bool {
+        let mut buffers = self.buffers.borrow_mut();
+        let length = buffers.len();
+        buffers.retain(|other| *other != JS::from_ref(buffer));
+        buffers.len() != length
+    }
+}
+
+impl SourceBufferListMethods for SourceBufferList {
+    // https://w3c.github.io/media-source/#dom-sourcebufferlist-length
+    fn Length(&self) -> u32 {
+        self.buffers.borrow().len() as u32
+    }
+
+    // https://w3c.github.io/media-source/#dom-sourcebufferlist-onaddsourcebuffer
+    event_handler!(addsourcebuffer, GetOnaddsourcebuffer, SetOnaddsourcebuffer);
+
+    // https://w3c.github.io/media-source/#dom-sourcebufferlist-onremovesourcebuffer
+    event_handler!(removesourcebuffer, GetOnremovesourcebuffer, SetOnremovesourcebuffer);
+
+    // https://w3c.github.io/media-source/#dfn-sourcebufferlist-getter
+    fn IndexedGetter(&self, index: u32) -> Option<Root<SourceBuffer>> {
+        self.buffers.borrow().get(index as usize).map(|buffer| Root::from_ref(&**buffer))
+    }
+}
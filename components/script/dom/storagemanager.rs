@@ -0,0 +1,159 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::StorageManagerBinding;
+use dom::bindings::codegen::Bindings::StorageManagerBinding::{StorageEstimate, StorageManagerMethods};
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::error::Error;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::refcounted::{Trusted, TrustedPromise};
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::promise::Promise;
+use ipc_channel::ipc::{self, IpcSender};
+use js::jsapi::JSAutoCompartment;
+use net_traits::IpcSend;
+use net_traits::storage_thread::StorageThreadMsg;
+use script_thread::Runnable;
+use script_traits::ScriptMsg as ConstellationMsg;
+use std::rc::Rc;
+use task_source::TaskSource;
+use url::Url;
+use util::thread::spawn_named;
+
+// https://storage.spec.whatwg.org/#storagemanager
+#[dom_struct]
+pub struct StorageManager {
+    reflector_: Reflector,
+}
+
+impl StorageManager {
+    fn new_inherited() -> StorageManager {
+        StorageManager {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<StorageManager> {
+        reflect_dom_object(box StorageManager::new_inherited(), global, StorageManagerBinding::Wrap)
+    }
+
+    fn get_storage_thread(&self) -> IpcSender<StorageThreadMsg> {
+        self.global().r().as_window().resource_threads().sender()
+    }
+
+    /// Creates the promise of a method, which is rejected straight away when the
+    /// document has an opaque origin and so no storage.
+    #[allow(unrooted_must_root)]
+    fn new_promise(&self) -> (Rc<Promise>, bool) {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let has_storage = global.r().as_window().Document().origin().is_scheme_host_port_tuple();
+        if !has_storage {
+            promise.reject_error(global.r().get_cx(), Error::Type("The origin has no storage".to_owned()));
+        }
+        (promise, has_storage)
+    }
+
+    fn persisted(&self) -> bool {
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.get_storage_thread().send(StorageThreadMsg::Persisted(sender, self.global().r().get_url())).unwrap();
+        receiver.recv().unwrap()
+    }
+}
+
+impl StorageManagerMethods for StorageManager {
+    #[allow(unrooted_must_root)]
+    // https://storage.spec.whatwg.org/#dom-storagemanager-persisted
+    fn Persisted(&self) -> Rc<Promise> {
+        let (promise, has_storage) = self.new_promise();
+        if has_storage {
+            promise.resolve_native(self.global().r().get_cx(), &self.persisted());
+        }
+        promise
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://storage.spec.whatwg.org/#dom-storagemanager-persist
+    fn Persist(&self) -> Rc<Promise> {
+        let (promise, has_storage) = self.new_promise();
+        if !has_storage {
+            return promise;
+        }
+        if self.persisted() {
+            promise.resolve_native(self.global().r().get_cx(), &true);
+            return promise;
+        }
+
+        // Whether the storage may be made persistent is up to the embedder, which may
+        // have to ask the user, so the answer is waited for off the script thread.
+        let global = self.global();
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let pipeline_id = window.pipeline_id();
+        let constellation_chan = window.constellation_chan().clone();
+        let storage_thread = self.get_storage_thread();
+        let url = global.r().get_url();
+        let mut runnable = box PersistRunnable {
+            manager: Trusted::new(self),
+            promise: TrustedPromise::new(promise.clone()),
+            persisted: false,
+        };
+        spawn_named("StoragePersist".to_owned(), move || {
+            let (sender, receiver) = ipc::channel().unwrap();
+            constellation_chan.send(ConstellationMsg::RequestPersistentStorage(pipeline_id, sender)).unwrap();
+            if receiver.recv().unwrap_or(false) {
+                runnable.persisted = persist(storage_thread, url);
+            }
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        });
+        promise
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://storage.spec.whatwg.org/#dom-storagemanager-estimate
+    fn Estimate(&self) -> Rc<Promise> {
+        let (promise, has_storage) = self.new_promise();
+        if has_storage {
+            let (sender, receiver) = ipc::channel().unwrap();
+            self.get_storage_thread().send(StorageThreadMsg::Estimate(sender, self.global().r().get_url())).unwrap();
+            let estimate = receiver.recv().unwrap();
+            let estimate = StorageEstimate {
+                usage: Some(estimate.usage),
+                quota: Some(estimate.quota),
+            };
+            promise.resolve_native(self.global().r().get_cx(), &estimate);
+        }
+        promise
+    }
+}
+
+/// Makes the storage of the origin of `url` persistent, and returns whether it is.
+fn persist(storage_thread: IpcSender<StorageThreadMsg>, url: Url) -> bool {
+    let (sender, receiver) = ipc::channel().unwrap();
+    storage_thread.send(StorageThreadMsg::Persist(sender, url)).unwrap();
+    receiver.recv().unwrap_or(false)
+}
+
+/// Delivers the outcome of a `persist()` call.
+struct PersistRunnable {
+    manager: Trusted<StorageManager>,
+    promise: TrustedPromise,
+    persisted: bool,
+}
+
+impl Runnable for PersistRunnable {
+    fn name(&self) -> &'static str { "PersistRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let manager = this.manager.root();
+        let promise = this.promise.root();
+        let global = manager.global();
+        let _ac = JSAutoCompartment::new(global.r().get_cx(), promise.reflector().get_jsobject().get());
+        promise.resolve_native(global.r().get_cx(), &this.persisted);
+    }
+}
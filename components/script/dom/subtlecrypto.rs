@@ -0,0 +1,807 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::CryptoKeyBinding::{CryptoKeyPair, KeyType, KeyUsage};
+use dom::bindings::codegen::Bindings::SubtleCryptoBinding;
+use dom::bindings::codegen::Bindings::SubtleCryptoBinding::{KeyFormat, SubtleCryptoMethods};
+use dom::bindings::conversions::{ConversionBehavior, ConversionResult, FromJSValConvertible};
+use dom::bindings::conversions::{StringificationBehavior, array_buffer_or_view_to_vec, root_from_handlevalue};
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::bindings::utils::get_dictionary_property;
+use dom::cryptokey::{AesMode, CryptoKey, EcScheme, KeyAlgorithm, KeyHandle, RsaScheme};
+use dom::promise::Promise;
+use js::jsapi::{HandleObject, HandleValue, JSContext, JSObject, JS_GetArrayBufferData, JS_NewArrayBuffer};
+use js::jsapi::MutableHandleObject;
+use js::jsval::{ObjectValue, UndefinedValue};
+use std::ascii::AsciiExt;
+use std::ptr;
+use std::rc::Rc;
+use webcrypto::{self, EcPrivateKey, EcPublicKey, HashAlgorithm, NamedCurve, RsaPrivateKey, RsaPublicKey};
+
+/// The algorithms `normalize_algorithm` recognizes, spelled as they are
+/// registered. Each operation rejects the ones it doesn't support.
+/// https://w3c.github.io/webcrypto/#algorithm-overview
+const RECOGNIZED_ALGORITHMS: &'static [&'static str] = &[
+    "AES-CBC",
+    "AES-CTR",
+    "AES-GCM",
+    "ECDH",
+    "ECDSA",
+    "HMAC",
+    "RSA-OAEP",
+    "RSA-PSS",
+    "RSASSA-PKCS1-v1_5",
+    "SHA-1",
+    "SHA-256",
+    "SHA-384",
+    "SHA-512",
+];
+
+/// The usages an AES or RSA-OAEP key can be generated or imported with.
+const ENCRYPTION_USAGES: &'static [KeyUsage] = &[
+    KeyUsage::Encrypt,
+    KeyUsage::Decrypt,
+    KeyUsage::WrapKey,
+    KeyUsage::UnwrapKey,
+];
+
+/// The usages of an RSA-OAEP key pair that belong to the public key.
+const PUBLIC_ENCRYPTION_USAGES: &'static [KeyUsage] = &[
+    KeyUsage::Encrypt,
+    KeyUsage::WrapKey,
+];
+
+/// The usages an HMAC, ECDSA, RSA-PSS or RSASSA-PKCS1-v1_5 key can be
+/// generated or imported with.
+const SIGNATURE_USAGES: &'static [KeyUsage] = &[
+    KeyUsage::Sign,
+    KeyUsage::Verify,
+];
+
+/// The usages of a signing key pair that belong to the public key.
+const PUBLIC_SIGNATURE_USAGES: &'static [KeyUsage] = &[
+    KeyUsage::Verify,
+];
+
+/// The usages an ECDH key can be generated or imported with.
+const KEY_AGREEMENT_USAGES: &'static [KeyUsage] = &[
+    KeyUsage::DeriveKey,
+    KeyUsage::DeriveBits,
+];
+
+/// The result of `generateKey()`.
+enum KeyOrKeyPair {
+    Key(Root<CryptoKey>),
+    KeyPair(CryptoKeyPair),
+}
+
+// https://w3c.github.io/webcrypto/#subtlecrypto-interface
+#[dom_struct]
+pub struct SubtleCrypto {
+    reflector_: Reflector,
+}
+
+impl SubtleCrypto {
+    fn new_inherited() -> SubtleCrypto {
+        SubtleCrypto {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<SubtleCrypto> {
+        reflect_dom_object(box SubtleCrypto::new_inherited(), global, SubtleCryptoBinding::Wrap)
+    }
+
+    /// Settles a new promise with the outcome of an operation producing bytes,
+    /// which are exposed to script as an ArrayBuffer.
+    #[allow(unrooted_must_root, unsafe_code)]
+    fn bytes_promise(&self, cx: *mut JSContext, result: Fallible<Vec<u8>>) -> Rc<Promise> {
+        let promise = Promise::new(self.global().r());
+        match result {
+            Ok(bytes) => unsafe {
+                let len = bytes.len() as u32;
+                rooted!(in(cx) let buffer = JS_NewArrayBuffer(cx, len));
+                assert!(!buffer.is_null());
+                let mut is_shared = false;
+                let buffer_data = JS_GetArrayBufferData(buffer.get(), &mut is_shared, ptr::null());
+                assert!(!is_shared);
+                ptr::copy_nonoverlapping(bytes.as_ptr(), buffer_data, len as usize);
+                rooted!(in(cx) let value = ObjectValue(&*buffer.get()));
+                promise.resolve(cx, value.handle());
+            },
+            Err(error) => promise.reject_error(cx, error),
+        }
+        promise
+    }
+
+    #[allow(unsafe_code)]
+    fn encrypt(&self, cx: *mut JSContext, algorithm: HandleValue, key: &CryptoKey, data: *mut JSObject,
+               usage: KeyUsage)
+               -> Fallible<Vec<u8>> {
+        rooted!(in(cx) let mut params = ptr::null_mut());
+        let name = try!(unsafe { normalize_algorithm(cx, algorithm, params.handle_mut()) });
+        let data = try!(buffer_source_bytes(data));
+        let encryption = try!(unsafe { encryption_params(cx, name, params.handle()) });
+        try!(check_key(key, name, usage));
+
+        let encrypt = usage == KeyUsage::Encrypt;
+        match (encryption, key.algorithm(), key.handle()) {
+            (EncryptionParams::AesCbc { ref iv }, _, &KeyHandle::Secret(ref bytes)) => {
+                if encrypt {
+                    webcrypto::aes_cbc_encrypt(bytes, iv, &data)
+                } else {
+                    webcrypto::aes_cbc_decrypt(bytes, iv, &data)
+                }
+            },
+            (EncryptionParams::AesCtr { ref counter, length }, _, &KeyHandle::Secret(ref bytes)) => {
+                webcrypto::aes_ctr(bytes, counter, length as u32, &data)
+            },
+            (EncryptionParams::AesGcm { ref iv, ref additional_data, tag_length }, _,
+             &KeyHandle::Secret(ref bytes)) => {
+                if encrypt {
+                    webcrypto::aes_gcm_encrypt(bytes, iv, additional_data, tag_length, &data)
+                } else {
+                    webcrypto::aes_gcm_decrypt(bytes, iv, additional_data, tag_length, &data)
+                }
+            },
+            (EncryptionParams::RsaOaep { ref label }, &KeyAlgorithm::Rsa { hash, .. },
+             &KeyHandle::RsaPublic(ref public_key)) if encrypt => {
+                webcrypto::rsa_oaep_encrypt(public_key, hash, label, &data)
+            },
+            (EncryptionParams::RsaOaep { ref label }, &KeyAlgorithm::Rsa { hash, .. },
+             &KeyHandle::RsaPrivate(ref private_key)) if !encrypt => {
+                webcrypto::rsa_oaep_decrypt(private_key, hash, label, &data)
+            },
+            _ => Err(Error::InvalidAccess),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn sign(&self, cx: *mut JSContext, algorithm: HandleValue, key: &CryptoKey, data: *mut JSObject)
+            -> Fallible<Vec<u8>> {
+        rooted!(in(cx) let mut params = ptr::null_mut());
+        let name = try!(unsafe { normalize_algorithm(cx, algorithm, params.handle_mut()) });
+        let data = try!(buffer_source_bytes(data));
+        let signing = try!(unsafe { signature_params(cx, name, params.handle()) });
+        try!(check_key(key, name, KeyUsage::Sign));
+
+        match (signing, key.algorithm(), key.handle()) {
+            (SignatureParams::Hmac, &KeyAlgorithm::Hmac { hash, .. }, &KeyHandle::Secret(ref bytes)) => {
+                Ok(webcrypto::hmac_sign(hash, bytes, &data))
+            },
+            (SignatureParams::RsaPss(salt_length), &KeyAlgorithm::Rsa { hash, .. },
+             &KeyHandle::RsaPrivate(ref private_key)) => {
+                webcrypto::rsa_pss_sign(private_key, hash, salt_length as usize, &data)
+            },
+            (SignatureParams::RsaPkcs1V15, &KeyAlgorithm::Rsa { hash, .. },
+             &KeyHandle::RsaPrivate(ref private_key)) => {
+                webcrypto::rsa_pkcs1_v1_5_sign(private_key, hash, &data)
+            },
+            (SignatureParams::Ecdsa(hash), _, &KeyHandle::EcPrivate(ref private_key)) => {
+                webcrypto::ecdsa_sign(private_key, hash, &data)
+            },
+            _ => Err(Error::InvalidAccess),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn verify(&self, cx: *mut JSContext, algorithm: HandleValue, key: &CryptoKey, signature: *mut JSObject,
+              data: *mut JSObject)
+              -> Fallible<bool> {
+        rooted!(in(cx) let mut params = ptr::null_mut());
+        let name = try!(unsafe { normalize_algorithm(cx, algorithm, params.handle_mut()) });
+        let signature = try!(buffer_source_bytes(signature));
+        let data = try!(buffer_source_bytes(data));
+        let verification = try!(unsafe { signature_params(cx, name, params.handle()) });
+        try!(check_key(key, name, KeyUsage::Verify));
+
+        match (verification, key.algorithm(), key.handle()) {
+            (SignatureParams::Hmac, &KeyAlgorithm::Hmac { hash, .. }, &KeyHandle::Secret(ref bytes)) => {
+                Ok(webcrypto::hmac_verify(hash, bytes, &data, &signature))
+            },
+            (SignatureParams::RsaPss(salt_length), &KeyAlgorithm::Rsa { hash, .. },
+             &KeyHandle::RsaPublic(ref public_key)) => {
+                Ok(webcrypto::rsa_pss_verify(public_key, hash, salt_length as usize, &data, &signature))
+            },
+            (SignatureParams::RsaPkcs1V15, &KeyAlgorithm::Rsa { hash, .. },
+             &KeyHandle::RsaPublic(ref public_key)) => {
+                Ok(webcrypto::rsa_pkcs1_v1_5_verify(public_key, hash, &data, &signature))
+            },
+            (SignatureParams::Ecdsa(hash), _, &KeyHandle::EcPublic(ref public_key)) => {
+                Ok(webcrypto::ecdsa_verify(public_key, hash, &data, &signature))
+            },
+            _ => Err(Error::InvalidAccess),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn digest(&self, cx: *mut JSContext, algorithm: HandleValue, data: *mut JSObject) -> Fallible<Vec<u8>> {
+        rooted!(in(cx) let mut params = ptr::null_mut());
+        let name = try!(unsafe { normalize_algorithm(cx, algorithm, params.handle_mut()) });
+        let hash = try!(HashAlgorithm::from_name(name).ok_or(Error::NotSupported));
+        let data = try!(buffer_source_bytes(data));
+        Ok(webcrypto::digest(hash, &data))
+    }
+
+    #[allow(unsafe_code)]
+    fn derive_bits(&self, cx: *mut JSContext, algorithm: HandleValue, base_key: &CryptoKey, length: u32)
+                   -> Fallible<Vec<u8>> {
+        rooted!(in(cx) let mut params = ptr::null_mut());
+        let name = try!(unsafe { normalize_algorithm(cx, algorithm, params.handle_mut()) });
+        if name != "ECDH" {
+            return Err(Error::NotSupported);
+        }
+        let public_key = try!(unsafe { public_key_member(cx, params.handle()) });
+        try!(check_key(base_key, name, KeyUsage::DeriveBits));
+
+        match (base_key.handle(), public_key.algorithm(), public_key.handle()) {
+            (&KeyHandle::EcPrivate(ref private_key), &KeyAlgorithm::Ec { scheme: EcScheme::Ecdh, .. },
+             &KeyHandle::EcPublic(ref public_key)) if public_key.curve() == private_key.curve() => {
+                webcrypto::ecdh_derive_bits(private_key, public_key, length)
+            },
+            _ => Err(Error::InvalidAccess),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn generate_key(&self, cx: *mut JSContext, algorithm: HandleValue, extractable: bool,
+                    key_usages: &[DOMString])
+                    -> Fallible<KeyOrKeyPair> {
+        rooted!(in(cx) let mut params = ptr::null_mut());
+        let name = try!(unsafe { normalize_algorithm(cx, algorithm, params.handle_mut()) });
+        let usages = try!(parse_usages(key_usages));
+        let global = self.global();
+
+        if let Some(mode) = AesMode::from_name(name) {
+            let length: u16 = try!(unsafe {
+                required_member(cx, params.handle(), "length", ConversionBehavior::EnforceRange)
+            });
+            try!(check_usages(&usages, ENCRYPTION_USAGES));
+            try!(aes_key_length(length as usize, Error::Operation));
+            let bytes = webcrypto::random_bytes(length as usize / 8);
+            let algorithm = KeyAlgorithm::Aes { mode: mode, length: length };
+            return Ok(KeyOrKeyPair::Key(try!(secret_key(global.r(), extractable, algorithm, usages, bytes))));
+        }
+
+        if let Some(scheme) = RsaScheme::from_name(name) {
+            let (modulus_length, public_exponent, hash) = try!(unsafe { rsa_key_gen_params(cx, params.handle()) });
+            try!(check_usages(&usages, rsa_usages(scheme)));
+            let private_key = try!(RsaPrivateKey::generate(modulus_length, &public_exponent));
+            let public_key = try!(private_key.public_key());
+            let (public_usages, private_usages) = split_usages(&usages, rsa_public_usages(scheme));
+            try!(check_not_empty(&private_usages));
+            let algorithm = KeyAlgorithm::Rsa {
+                scheme: scheme,
+                hash: hash,
+                modulus_length: modulus_length,
+                public_exponent: private_key.public_exponent().to_vec(),
+            };
+            return Ok(KeyOrKeyPair::KeyPair(CryptoKeyPair {
+                publicKey: Some(CryptoKey::new(global.r(), KeyType::Public, true, algorithm.clone(),
+                                               public_usages, KeyHandle::RsaPublic(public_key))),
+                privateKey: Some(CryptoKey::new(global.r(), KeyType::Private, extractable, algorithm,
+                                                private_usages, KeyHandle::RsaPrivate(private_key))),
+            }));
+        }
+
+        if let Some(scheme) = EcScheme::from_name(name) {
+            let curve = try!(unsafe { named_curve(cx, params.handle()) });
+            try!(check_usages(&usages, ec_usages(scheme)));
+            let private_key = try!(EcPrivateKey::generate(curve));
+            let public_key = try!(private_key.public_key());
+            let (public_usages, private_usages) = split_usages(&usages, ec_public_usages(scheme));
+            try!(check_not_empty(&private_usages));
+            let algorithm = KeyAlgorithm::Ec { scheme: scheme, curve: curve };
+            return Ok(KeyOrKeyPair::KeyPair(CryptoKeyPair {
+                publicKey: Some(CryptoKey::new(global.r(), KeyType::Public, true, algorithm.clone(),
+                                               public_usages, KeyHandle::EcPublic(public_key))),
+                privateKey: Some(CryptoKey::new(global.r(), KeyType::Private, extractable, algorithm,
+                                                private_usages, KeyHandle::EcPrivate(private_key))),
+            }));
+        }
+
+        match name {
+            "HMAC" => {
+                let (hash, length) = try!(unsafe { hmac_params(cx, params.handle()) });
+                try!(check_usages(&usages, SIGNATURE_USAGES));
+                let length = length.unwrap_or(hash.block_len());
+                if length == 0 {
+                    return Err(Error::Operation);
+                }
+                if length % 8 != 0 {
+                    return Err(Error::NotSupported);
+                }
+                let bytes = webcrypto::random_bytes(length as usize / 8);
+                let key = try!(secret_key(global.r(), extractable,
+                                          KeyAlgorithm::Hmac { hash: hash, length: length }, usages, bytes));
+                Ok(KeyOrKeyPair::Key(key))
+            },
+            _ => Err(Error::NotSupported),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn import_key(&self, cx: *mut JSContext, format: KeyFormat, key_data: *mut JSObject, algorithm: HandleValue,
+                  extractable: bool, key_usages: &[DOMString])
+                  -> Fallible<Root<CryptoKey>> {
+        rooted!(in(cx) let mut params = ptr::null_mut());
+        let name = try!(unsafe { normalize_algorithm(cx, algorithm, params.handle_mut()) });
+        let usages = try!(parse_usages(key_usages));
+        // FIXME: JSON Web Keys aren't supported.
+        if format == KeyFormat::Jwk {
+            return Err(Error::NotSupported);
+        }
+        let data = try!(buffer_source_bytes(key_data));
+        let global = self.global();
+
+        if let Some(mode) = AesMode::from_name(name) {
+            if format != KeyFormat::Raw {
+                return Err(Error::NotSupported);
+            }
+            try!(check_usages(&usages, ENCRYPTION_USAGES));
+            let length = try!(aes_key_length(data.len() * 8, Error::Data));
+            return secret_key(global.r(), extractable, KeyAlgorithm::Aes { mode: mode, length: length }, usages,
+                              data);
+        }
+
+        if let Some(scheme) = RsaScheme::from_name(name) {
+            let hash = try!(unsafe { required_hash(cx, params.handle()) });
+            let (key_type, modulus_length, public_exponent, handle) = match format {
+                KeyFormat::Spki => {
+                    try!(check_usages(&usages, rsa_public_usages(scheme)));
+                    let public_key = try!(RsaPublicKey::from_spki(&data).ok_or(Error::Data));
+                    let modulus_length = public_key.modulus_length();
+                    let public_exponent = public_key.public_exponent().to_vec();
+                    (KeyType::Public, modulus_length, public_exponent, KeyHandle::RsaPublic(public_key))
+                },
+                KeyFormat::Pkcs8 => {
+                    let (_, private_usages) = split_usages(rsa_usages(scheme), rsa_public_usages(scheme));
+                    try!(check_usages(&usages, &private_usages));
+                    try!(check_not_empty(&usages));
+                    let private_key = try!(RsaPrivateKey::from_pkcs8(&data).ok_or(Error::Data));
+                    let modulus_length = private_key.modulus_length();
+                    let public_exponent = private_key.public_exponent().to_vec();
+                    (KeyType::Private, modulus_length, public_exponent, KeyHandle::RsaPrivate(private_key))
+                },
+                _ => return Err(Error::NotSupported),
+            };
+            let algorithm = KeyAlgorithm::Rsa {
+                scheme: scheme,
+                hash: hash,
+                modulus_length: modulus_length,
+                public_exponent: public_exponent,
+            };
+            return Ok(CryptoKey::new(global.r(), key_type, extractable, algorithm, usages, handle));
+        }
+
+        if let Some(scheme) = EcScheme::from_name(name) {
+            let curve = try!(unsafe { named_curve(cx, params.handle()) });
+            let (key_type, handle) = match format {
+                KeyFormat::Spki => {
+                    try!(check_usages(&usages, ec_public_usages(scheme)));
+                    let public_key = try!(EcPublicKey::from_spki(&data, curve).ok_or(Error::Data));
+                    (KeyType::Public, KeyHandle::EcPublic(public_key))
+                },
+                KeyFormat::Raw => {
+                    try!(check_usages(&usages, ec_public_usages(scheme)));
+                    let public_key = try!(EcPublicKey::from_raw(&data, curve).ok_or(Error::Data));
+                    (KeyType::Public, KeyHandle::EcPublic(public_key))
+                },
+                KeyFormat::Pkcs8 => {
+                    let (_, private_usages) = split_usages(ec_usages(scheme), ec_public_usages(scheme));
+                    try!(check_usages(&usages, &private_usages));
+                    try!(check_not_empty(&usages));
+                    let private_key = try!(EcPrivateKey::from_pkcs8(&data, curve).ok_or(Error::Data));
+                    (KeyType::Private, KeyHandle::EcPrivate(private_key))
+                },
+                _ => return Err(Error::NotSupported),
+            };
+            let algorithm = KeyAlgorithm::Ec { scheme: scheme, curve: curve };
+            return Ok(CryptoKey::new(global.r(), key_type, extractable, algorithm, usages, handle));
+        }
+
+        match (name, format) {
+            ("HMAC", KeyFormat::Raw) => {
+                let (hash, length) = try!(unsafe { hmac_params(cx, params.handle()) });
+                try!(check_usages(&usages, SIGNATURE_USAGES));
+                let data_length = data.len() as u32 * 8;
+                if data_length == 0 {
+                    return Err(Error::Data);
+                }
+                let length = length.unwrap_or(data_length);
+                if length > data_length || length + 8 <= data_length {
+                    return Err(Error::Data);
+                }
+                secret_key(global.r(), extractable, KeyAlgorithm::Hmac { hash: hash, length: length }, usages, data)
+            },
+            _ => Err(Error::NotSupported),
+        }
+    }
+}
+
+impl SubtleCryptoMethods for SubtleCrypto {
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-encrypt
+    fn Encrypt(&self, cx: *mut JSContext, algorithm: HandleValue, key: &CryptoKey, data: *mut JSObject)
+               -> Rc<Promise> {
+        let result = self.encrypt(cx, algorithm, key, data, KeyUsage::Encrypt);
+        self.bytes_promise(cx, result)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-decrypt
+    fn Decrypt(&self, cx: *mut JSContext, algorithm: HandleValue, key: &CryptoKey, data: *mut JSObject)
+               -> Rc<Promise> {
+        let result = self.encrypt(cx, algorithm, key, data, KeyUsage::Decrypt);
+        self.bytes_promise(cx, result)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-sign
+    fn Sign(&self, cx: *mut JSContext, algorithm: HandleValue, key: &CryptoKey, data: *mut JSObject)
+            -> Rc<Promise> {
+        let result = self.sign(cx, algorithm, key, data);
+        self.bytes_promise(cx, result)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-verify
+    fn Verify(&self, cx: *mut JSContext, algorithm: HandleValue, key: &CryptoKey, signature: *mut JSObject,
+              data: *mut JSObject)
+              -> Rc<Promise> {
+        let promise = Promise::new(self.global().r());
+        match self.verify(cx, algorithm, key, signature, data) {
+            Ok(verified) => promise.resolve_native(cx, &verified),
+            Err(error) => promise.reject_error(cx, error),
+        }
+        promise
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-digest
+    fn Digest(&self, cx: *mut JSContext, algorithm: HandleValue, data: *mut JSObject) -> Rc<Promise> {
+        let result = self.digest(cx, algorithm, data);
+        self.bytes_promise(cx, result)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-generateKey
+    fn GenerateKey(&self, cx: *mut JSContext, algorithm: HandleValue, extractable: bool,
+                   key_usages: Vec<DOMString>)
+                   -> Rc<Promise> {
+        let promise = Promise::new(self.global().r());
+        match self.generate_key(cx, algorithm, extractable, &key_usages) {
+            Ok(KeyOrKeyPair::Key(key)) => promise.resolve_native(cx, &key),
+            Ok(KeyOrKeyPair::KeyPair(key_pair)) => promise.resolve_native(cx, &key_pair),
+            Err(error) => promise.reject_error(cx, error),
+        }
+        promise
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-deriveBits
+    fn DeriveBits(&self, cx: *mut JSContext, algorithm: HandleValue, base_key: &CryptoKey, length: u32)
+                  -> Rc<Promise> {
+        let result = self.derive_bits(cx, algorithm, base_key, length);
+        self.bytes_promise(cx, result)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://w3c.github.io/webcrypto/#dfn-SubtleCrypto-method-importKey
+    fn ImportKey(&self, cx: *mut JSContext, format: KeyFormat, key_data: *mut JSObject, algorithm: HandleValue,
+                 extractable: bool, key_usages: Vec<DOMString>)
+                 -> Rc<Promise> {
+        let promise = Promise::new(self.global().r());
+        match self.import_key(cx, format, key_data, algorithm, extractable, &key_usages) {
+            Ok(key) => promise.resolve_native(cx, &key),
+            Err(error) => promise.reject_error(cx, error),
+        }
+        promise
+    }
+}
+
+/// The parameters of an algorithm passed to `encrypt()` or `decrypt()`.
+enum EncryptionParams {
+    AesCbc { iv: Vec<u8> },
+    /// The length is the number of bits of the counter block that are incremented.
+    AesCtr { counter: Vec<u8>, length: u8 },
+    /// The tag length is in bits.
+    AesGcm { iv: Vec<u8>, additional_data: Vec<u8>, tag_length: u8 },
+    RsaOaep { label: Vec<u8> },
+}
+
+#[allow(unsafe_code)]
+unsafe fn encryption_params(cx: *mut JSContext, name: &str, params: HandleObject) -> Fallible<EncryptionParams> {
+    match name {
+        "AES-CBC" => {
+            let iv = try!(required_buffer_member(cx, params, "iv"));
+            Ok(EncryptionParams::AesCbc { iv: iv })
+        },
+        "AES-CTR" => {
+            let counter = try!(required_buffer_member(cx, params, "counter"));
+            let length = try!(required_member(cx, params, "length", ConversionBehavior::EnforceRange));
+            Ok(EncryptionParams::AesCtr { counter: counter, length: length })
+        },
+        "AES-GCM" => {
+            let iv = try!(required_buffer_member(cx, params, "iv"));
+            let additional_data = try!(buffer_member(cx, params, "additionalData"));
+            let tag_length: Option<u8> = try!(optional_member(cx, params, "tagLength",
+                                                              ConversionBehavior::EnforceRange));
+            Ok(EncryptionParams::AesGcm {
+                iv: iv,
+                additional_data: additional_data.unwrap_or(vec![]),
+                tag_length: tag_length.unwrap_or(128),
+            })
+        },
+        "RSA-OAEP" => {
+            let label = try!(buffer_member(cx, params, "label"));
+            Ok(EncryptionParams::RsaOaep { label: label.unwrap_or(vec![]) })
+        },
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// The parameters of an algorithm passed to `sign()` or `verify()`.
+enum SignatureParams {
+    Hmac,
+    /// The salt length, in bytes.
+    RsaPss(u32),
+    RsaPkcs1V15,
+    Ecdsa(HashAlgorithm),
+}
+
+#[allow(unsafe_code)]
+unsafe fn signature_params(cx: *mut JSContext, name: &str, params: HandleObject) -> Fallible<SignatureParams> {
+    match name {
+        "HMAC" => Ok(SignatureParams::Hmac),
+        "RSA-PSS" => {
+            let salt_length = try!(required_member(cx, params, "saltLength", ConversionBehavior::EnforceRange));
+            Ok(SignatureParams::RsaPss(salt_length))
+        },
+        "RSASSA-PKCS1-v1_5" => Ok(SignatureParams::RsaPkcs1V15),
+        "ECDSA" => Ok(SignatureParams::Ecdsa(try!(required_hash(cx, params)))),
+        _ => Err(Error::NotSupported),
+    }
+}
+
+/// Checks that `key` may be used for `usage` with the algorithm `name`.
+fn check_key(key: &CryptoKey, name: &str, usage: KeyUsage) -> Fallible<()> {
+    if key.algorithm().name() != name || !key.has_usage(usage) {
+        return Err(Error::InvalidAccess);
+    }
+    Ok(())
+}
+
+/// Checks that `length` (in bits) is a valid AES key length, returning
+/// `error` if it isn't.
+fn aes_key_length(length: usize, error: Error) -> Fallible<u16> {
+    match length {
+        128 | 192 | 256 => Ok(length as u16),
+        _ => Err(error),
+    }
+}
+
+/// The usages a key pair of the RSA `scheme` can be generated with.
+fn rsa_usages(scheme: RsaScheme) -> &'static [KeyUsage] {
+    match scheme {
+        RsaScheme::Oaep => ENCRYPTION_USAGES,
+        RsaScheme::Pss | RsaScheme::Pkcs1V15 => SIGNATURE_USAGES,
+    }
+}
+
+/// The usages of `rsa_usages` that belong to the public key of a pair.
+fn rsa_public_usages(scheme: RsaScheme) -> &'static [KeyUsage] {
+    match scheme {
+        RsaScheme::Oaep => PUBLIC_ENCRYPTION_USAGES,
+        RsaScheme::Pss | RsaScheme::Pkcs1V15 => PUBLIC_SIGNATURE_USAGES,
+    }
+}
+
+/// The usages a key pair of the EC `scheme` can be generated with.
+fn ec_usages(scheme: EcScheme) -> &'static [KeyUsage] {
+    match scheme {
+        EcScheme::Ecdsa => SIGNATURE_USAGES,
+        EcScheme::Ecdh => KEY_AGREEMENT_USAGES,
+    }
+}
+
+/// The usages of `ec_usages` that belong to the public key of a pair. An
+/// ECDH public key has none.
+fn ec_public_usages(scheme: EcScheme) -> &'static [KeyUsage] {
+    match scheme {
+        EcScheme::Ecdsa => PUBLIC_SIGNATURE_USAGES,
+        EcScheme::Ecdh => &[],
+    }
+}
+
+fn secret_key(global: GlobalRef, extractable: bool, algorithm: KeyAlgorithm, usages: Vec<KeyUsage>,
+              bytes: Vec<u8>)
+              -> Fallible<Root<CryptoKey>> {
+    try!(check_not_empty(&usages));
+    Ok(CryptoKey::new(global, KeyType::Secret, extractable, algorithm, usages, KeyHandle::Secret(bytes)))
+}
+
+fn parse_usages(key_usages: &[DOMString]) -> Fallible<Vec<KeyUsage>> {
+    let mut usages = vec![];
+    for key_usage in key_usages {
+        let usage = match &**key_usage {
+            "encrypt" => KeyUsage::Encrypt,
+            "decrypt" => KeyUsage::Decrypt,
+            "sign" => KeyUsage::Sign,
+            "verify" => KeyUsage::Verify,
+            "deriveKey" => KeyUsage::DeriveKey,
+            "deriveBits" => KeyUsage::DeriveBits,
+            "wrapKey" => KeyUsage::WrapKey,
+            "unwrapKey" => KeyUsage::UnwrapKey,
+            _ => return Err(Error::Type(format!("'{}' is not a valid key usage", key_usage))),
+        };
+        if !usages.contains(&usage) {
+            usages.push(usage);
+        }
+    }
+    Ok(usages)
+}
+
+/// Throws a SyntaxError if `usages` has a usage outside of `allowed`.
+fn check_usages(usages: &[KeyUsage], allowed: &[KeyUsage]) -> Fallible<()> {
+    if usages.iter().any(|usage| !allowed.contains(usage)) {
+        return Err(Error::Syntax);
+    }
+    Ok(())
+}
+
+/// Secret and private keys can't be created without usages.
+fn check_not_empty(usages: &[KeyUsage]) -> Fallible<()> {
+    if usages.is_empty() {
+        return Err(Error::Syntax);
+    }
+    Ok(())
+}
+
+/// Splits the usages of a key pair between the public key, which gets those
+/// in `public_usages`, and the private key, which gets the rest.
+fn split_usages(usages: &[KeyUsage], public_usages: &[KeyUsage]) -> (Vec<KeyUsage>, Vec<KeyUsage>) {
+    usages.iter().cloned().partition(|usage| public_usages.contains(usage))
+}
+
+fn buffer_source_bytes(object: *mut JSObject) -> Fallible<Vec<u8>> {
+    array_buffer_or_view_to_vec(object)
+        .ok_or(Error::Type("Argument is not an ArrayBuffer or ArrayBufferView".to_owned()))
+}
+
+/// Reads the name of an `AlgorithmIdentifier`, storing the dictionary it was
+/// given as (if any) in `params`.
+/// https://w3c.github.io/webcrypto/#algorithm-normalization-normalize-an-algorithm
+#[allow(unsafe_code)]
+unsafe fn normalize_algorithm(cx: *mut JSContext, algorithm: HandleValue, params: MutableHandleObject)
+                              -> Fallible<&'static str> {
+    let name = if algorithm.get().is_string() {
+        match DOMString::from_jsval(cx, algorithm, StringificationBehavior::Default) {
+            Ok(ConversionResult::Success(name)) => name,
+            _ => return Err(Error::JSFailed),
+        }
+    } else if algorithm.get().is_object() {
+        params.set(algorithm.get().to_object());
+        try!(required_member::<DOMString>(cx, params.handle(), "name", StringificationBehavior::Default))
+    } else {
+        return Err(Error::Type("Algorithm is not an object or a string".to_owned()));
+    };
+    RECOGNIZED_ALGORITHMS.iter()
+        .find(|recognized| recognized.eq_ignore_ascii_case(&name))
+        .cloned()
+        .ok_or(Error::NotSupported)
+}
+
+#[allow(unsafe_code)]
+unsafe fn optional_member<T: FromJSValConvertible>(cx: *mut JSContext, params: HandleObject, name: &str,
+                                                   config: T::Config)
+                                                   -> Fallible<Option<T>> {
+    rooted!(in(cx) let mut value = UndefinedValue());
+    match get_dictionary_property(cx, params, name, value.handle_mut()) {
+        Ok(true) if !value.is_undefined() => {},
+        Ok(_) => return Ok(None),
+        Err(()) => return Err(Error::JSFailed),
+    }
+    match T::from_jsval(cx, value.handle(), config) {
+        Ok(ConversionResult::Success(value)) => Ok(Some(value)),
+        Ok(ConversionResult::Failure(error)) => Err(Error::Type(error.into_owned())),
+        Err(()) => Err(Error::JSFailed),
+    }
+}
+
+#[allow(unsafe_code)]
+unsafe fn required_member<T: FromJSValConvertible>(cx: *mut JSContext, params: HandleObject, name: &str,
+                                                   config: T::Config)
+                                                   -> Fallible<T> {
+    match try!(optional_member(cx, params, name, config)) {
+        Some(value) => Ok(value),
+        None => Err(Error::Type(format!("Missing required member {}", name))),
+    }
+}
+
+#[allow(unsafe_code)]
+unsafe fn buffer_member(cx: *mut JSContext, params: HandleObject, name: &str) -> Fallible<Option<Vec<u8>>> {
+    rooted!(in(cx) let mut value = UndefinedValue());
+    match get_dictionary_property(cx, params, name, value.handle_mut()) {
+        Ok(true) if !value.is_undefined() => {},
+        Ok(_) => return Ok(None),
+        Err(()) => return Err(Error::JSFailed),
+    }
+    if !value.is_object() {
+        return Err(Error::Type(format!("{} is not an ArrayBuffer or ArrayBufferView", name)));
+    }
+    buffer_source_bytes(value.to_object()).map(Some)
+}
+
+#[allow(unsafe_code)]
+unsafe fn required_buffer_member(cx: *mut JSContext, params: HandleObject, name: &str) -> Fallible<Vec<u8>> {
+    match try!(buffer_member(cx, params, name)) {
+        Some(value) => Ok(value),
+        None => Err(Error::Type(format!("Missing required member {}", name))),
+    }
+}
+
+/// Reads the `hash` member, which is itself an `AlgorithmIdentifier`.
+#[allow(unsafe_code)]
+unsafe fn hash_member(cx: *mut JSContext, params: HandleObject) -> Fallible<Option<HashAlgorithm>> {
+    rooted!(in(cx) let mut value = UndefinedValue());
+    match get_dictionary_property(cx, params, "hash", value.handle_mut()) {
+        Ok(true) if !value.is_undefined() => {},
+        Ok(_) => return Ok(None),
+        Err(()) => return Err(Error::JSFailed),
+    }
+    rooted!(in(cx) let mut hash_params = ptr::null_mut());
+    let name = try!(normalize_algorithm(cx, value.handle(), hash_params.handle_mut()));
+    HashAlgorithm::from_name(name).map(Some).ok_or(Error::NotSupported)
+}
+
+#[allow(unsafe_code)]
+unsafe fn required_hash(cx: *mut JSContext, params: HandleObject) -> Fallible<HashAlgorithm> {
+    match try!(hash_member(cx, params)) {
+        Some(hash) => Ok(hash),
+        None => Err(Error::Type("Missing required member hash".to_owned())),
+    }
+}
+
+/// Reads the members of `HmacImportParams` and `HmacKeyGenParams`.
+#[allow(unsafe_code)]
+unsafe fn hmac_params(cx: *mut JSContext, params: HandleObject) -> Fallible<(HashAlgorithm, Option<u32>)> {
+    let hash = try!(required_hash(cx, params));
+    let length = try!(optional_member(cx, params, "length", ConversionBehavior::EnforceRange));
+    Ok((hash, length))
+}
+
+/// Reads the members of `RsaHashedKeyGenParams`: the modulus length in bits,
+/// the public exponent and the hash.
+#[allow(unsafe_code)]
+unsafe fn rsa_key_gen_params(cx: *mut JSContext, params: HandleObject) -> Fallible<(u32, Vec<u8>, HashAlgorithm)> {
+    let modulus_length = try!(required_member(cx, params, "modulusLength", ConversionBehavior::EnforceRange));
+    let public_exponent = try!(required_buffer_member(cx, params, "publicExponent"));
+    let hash = try!(required_hash(cx, params));
+    Ok((modulus_length, public_exponent, hash))
+}
+
+/// Reads the `namedCurve` member of `EcKeyGenParams` and `EcKeyImportParams`.
+#[allow(unsafe_code)]
+unsafe fn named_curve(cx: *mut JSContext, params: HandleObject) -> Fallible<NamedCurve> {
+    let name: DOMString = try!(required_member(cx, params, "namedCurve", StringificationBehavior::Default));
+    // FIXME: P-521 isn't supported.
+    NamedCurve::from_name(&name).ok_or(Error::NotSupported)
+}
+
+/// Reads the `public` member of `EcdhKeyDeriveParams`.
+#[allow(unsafe_code)]
+unsafe fn public_key_member(cx: *mut JSContext, params: HandleObject) -> Fallible<Root<CryptoKey>> {
+    rooted!(in(cx) let mut value = UndefinedValue());
+    match get_dictionary_property(cx, params, "public", value.handle_mut()) {
+        Ok(true) if !value.is_undefined() => {},
+        Ok(_) => return Err(Error::Type("Missing required member public".to_owned())),
+        Err(()) => return Err(Error::JSFailed),
+    }
+    root_from_handlevalue(value.handle()).map_err(|()| Error::Type("public is not a CryptoKey".to_owned()))
+}
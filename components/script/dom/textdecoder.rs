@@ -2,8 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::TextDecoderBinding;
-use dom::bindings::codegen::Bindings::TextDecoderBinding::TextDecoderMethods;
+use dom::bindings::codegen::Bindings::TextDecoderBinding::{TextDecodeOptions, TextDecoderMethods};
 use dom::bindings::conversions::array_buffer_view_data;
 use dom::bindings::error::{Error, Fallible};
 use dom::bindings::global::GlobalRef;
@@ -11,9 +12,10 @@ use dom::bindings::js::Root;
 use dom::bindings::reflector::{Reflector, reflect_dom_object};
 use dom::bindings::str::{DOMString, USVString};
 use encoding::label::encoding_from_whatwg_label;
-use encoding::types::{DecoderTrap, EncodingRef};
+use encoding::types::{EncodingRef, RawDecoder};
 use js::jsapi::{JSContext, JSObject};
 use std::borrow::ToOwned;
+use std::cell::Cell;
 
 #[dom_struct]
 pub struct TextDecoder {
@@ -21,14 +23,27 @@ pub struct TextDecoder {
     #[ignore_heap_size_of = "Defined in rust-encoding"]
     encoding: EncodingRef,
     fatal: bool,
+    ignore_bom: bool,
+    /// The state of the decoding of the current stream, which is kept between the calls to
+    /// `decode()` that have the `stream` option set.
+    #[ignore_heap_size_of = "Defined in rust-encoding"]
+    decoder: DOMRefCell<Box<RawDecoder>>,
+    /// https://encoding.spec.whatwg.org/#textdecoder-do-not-flush-flag
+    do_not_flush: Cell<bool>,
+    /// https://encoding.spec.whatwg.org/#textdecoder-bom-seen-flag
+    bom_seen: Cell<bool>,
 }
 
 impl TextDecoder {
-    fn new_inherited(encoding: EncodingRef, fatal: bool) -> TextDecoder {
+    fn new_inherited(encoding: EncodingRef, fatal: bool, ignore_bom: bool) -> TextDecoder {
         TextDecoder {
             reflector_: Reflector::new(),
             encoding: encoding,
             fatal: fatal,
+            ignore_bom: ignore_bom,
+            decoder: DOMRefCell::new(encoding.raw_decoder()),
+            do_not_flush: Cell::new(false),
+            bom_seen: Cell::new(false),
         }
     }
 
@@ -36,8 +51,8 @@ impl TextDecoder {
         Err(Error::Range("The given encoding is not supported.".to_owned()))
     }
 
-    pub fn new(global: GlobalRef, encoding: EncodingRef, fatal: bool) -> Root<TextDecoder> {
-        reflect_dom_object(box TextDecoder::new_inherited(encoding, fatal),
+    pub fn new(global: GlobalRef, encoding: EncodingRef, fatal: bool, ignore_bom: bool) -> Root<TextDecoder> {
+        reflect_dom_object(box TextDecoder::new_inherited(encoding, fatal, ignore_bom),
                            global,
                            TextDecoderBinding::Wrap)
     }
@@ -60,10 +75,73 @@ impl TextDecoder {
             Some("replacement") => return TextDecoder::make_range_error(),
             _ => ()
         };
-        Ok(TextDecoder::new(global, encoding, options.fatal))
+        Ok(TextDecoder::new(global, encoding, options.fatal, options.ignoreBOM))
+    }
+
+    /// Decodes the next chunk of the current stream, and the end of the stream unless
+    /// `stream` is set. Returns an error, and starts a new stream, if the input is invalid
+    /// and this decoder is fatal.
+    /// https://encoding.spec.whatwg.org/#dom-textdecoder-decode (steps 1-6)
+    pub fn decode_chunk(&self, input: &[u8], stream: bool) -> Result<String, ()> {
+        // Step 1.
+        if !self.do_not_flush.get() {
+            *self.decoder.borrow_mut() = self.encoding.raw_decoder();
+            self.bom_seen.set(false);
+        }
+
+        // Step 2.
+        self.do_not_flush.set(stream);
+
+        // Steps 3-5.
+        let mut output = String::new();
+        let result = decode_to(&mut **self.decoder.borrow_mut(), input, !stream, self.fatal, &mut output);
+        if result.is_err() {
+            self.do_not_flush.set(false);
+            return Err(());
+        }
+
+        // https://encoding.spec.whatwg.org/#concept-td-serialize
+        if !self.bom_seen.get() && !output.is_empty() {
+            self.bom_seen.set(true);
+            let strips_bom = match self.encoding.whatwg_name() {
+                Some("utf-8") | Some("utf-16le") | Some("utf-16be") => !self.ignore_bom,
+                _ => false,
+            };
+            if strips_bom && output.starts_with('\u{FEFF}') {
+                output.remove(0);
+            }
+        }
+        Ok(output)
     }
 }
 
+/// Feeds `input` to `decoder`, replacing the invalid sequences with U+FFFD unless `fatal`
+/// is set, in which case the first one is an error. Flushes the decoder if `finish` is set.
+fn decode_to(decoder: &mut RawDecoder, input: &[u8], finish: bool, fatal: bool, output: &mut String)
+             -> Result<(), ()> {
+    let mut remaining = 0;
+    loop {
+        let (_, error) = decoder.raw_feed(&input[remaining..], output);
+        match error {
+            None => break,
+            Some(_) if fatal => return Err(()),
+            Some(error) => {
+                output.push('\u{FFFD}');
+                remaining = (remaining as isize + error.upto) as usize;
+            }
+        }
+    }
+    if finish {
+        if let Some(_) = decoder.raw_finish(output) {
+            if fatal {
+                return Err(());
+            }
+            output.push('\u{FFFD}');
+        }
+    }
+    Ok(())
+}
+
 
 impl TextDecoderMethods for TextDecoder {
     // https://encoding.spec.whatwg.org/#dom-textdecoder-encoding
@@ -76,31 +154,28 @@ impl TextDecoderMethods for TextDecoder {
         self.fatal
     }
 
+    // https://encoding.spec.whatwg.org/#dom-textdecoder-ignorebom
+    fn IgnoreBOM(&self) -> bool {
+        self.ignore_bom
+    }
+
     #[allow(unsafe_code)]
     // https://encoding.spec.whatwg.org/#dom-textdecoder-decode
-    fn Decode(&self, _cx: *mut JSContext, input: Option<*mut JSObject>)
+    fn Decode(&self, _cx: *mut JSContext, input: Option<*mut JSObject>, options: &TextDecodeOptions)
               -> Fallible<USVString> {
-        let input = match input {
-            Some(input) => input,
-            None => return Ok(USVString("".to_owned())),
-        };
-
-        let data = match unsafe { array_buffer_view_data::<u8>(input) } {
-            Some(data) => data,
-            None => {
-                return Err(Error::Type("Argument to TextDecoder.decode is not an ArrayBufferView".to_owned()));
-            }
-        };
-
-        let trap = if self.fatal {
-            DecoderTrap::Strict
-        } else {
-            DecoderTrap::Replace
+        let data = match input {
+            Some(input) => match unsafe { array_buffer_view_data::<u8>(input) } {
+                Some(data) => data,
+                None => {
+                    return Err(Error::Type("Argument to TextDecoder.decode is not an ArrayBufferView".to_owned()));
+                }
+            },
+            None => &[][..],
         };
 
-        match self.encoding.decode(data, trap) {
+        match self.decode_chunk(data, options.stream) {
             Ok(s) => Ok(USVString(s)),
-            Err(_) => Err(Error::Type("Decoding failed".to_owned())),
+            Err(()) => Err(Error::Type("Decoding failed".to_owned())),
         }
     }
 }
@@ -0,0 +1,230 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::TextTrackBinding::{self, TextTrackKind, TextTrackMethods, TextTrackMode};
+use dom::bindings::codegen::Bindings::TextTrackCueBinding::TextTrackCueMethods;
+use dom::bindings::error::{Error, ErrorResult};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::element::Element;
+use dom::eventtarget::EventTarget;
+use dom::htmlmediaelement::HTMLMediaElement;
+use dom::htmltrackelement::HTMLTrackElement;
+use dom::texttrackcue::TextTrackCue;
+use dom::texttrackcuelist::TextTrackCueList;
+use dom::window::Window;
+use std::cell::Cell;
+
+// https://html.spec.whatwg.org/multipage/#texttrack
+#[dom_struct]
+pub struct TextTrack {
+    eventtarget: EventTarget,
+    kind: Cell<TextTrackKind>,
+    label: DOMRefCell<DOMString>,
+    language: DOMRefCell<DOMString>,
+    mode: Cell<TextTrackMode>,
+    /// https://html.spec.whatwg.org/multipage/#text-track-list-of-cues
+    cues: JS<TextTrackCueList>,
+    /// The cues of `cues` that are shown at the current playback position of
+    /// the media element, as of the last time marches on.
+    /// https://html.spec.whatwg.org/multipage/#text-track-cue-active-flag
+    active_cues: JS<TextTrackCueList>,
+    /// The media element whose list of text tracks the track is in, if any.
+    media_element: MutNullableHeap<JS<HTMLMediaElement>>,
+    /// The element the track is for, if it isn't one added by script.
+    track_element: MutNullableHeap<JS<HTMLTrackElement>>,
+}
+
+impl TextTrack {
+    fn new_inherited(window: &Window,
+                     kind: TextTrackKind,
+                     label: DOMString,
+                     language: DOMString,
+                     mode: TextTrackMode)
+                     -> TextTrack {
+        TextTrack {
+            eventtarget: EventTarget::new_inherited(),
+            kind: Cell::new(kind),
+            label: DOMRefCell::new(label),
+            language: DOMRefCell::new(language),
+            mode: Cell::new(mode),
+            cues: JS::from_ref(&*TextTrackCueList::new(window)),
+            active_cues: JS::from_ref(&*TextTrackCueList::new(window)),
+            media_element: Default::default(),
+            track_element: Default::default(),
+        }
+    }
+
+    pub fn new(window: &Window,
+               kind: TextTrackKind,
+               label: DOMString,
+               language: DOMString,
+               mode: TextTrackMode)
+               -> Root<TextTrack> {
+        reflect_dom_object(box TextTrack::new_inherited(window, kind, label, language, mode),
+                           GlobalRef::Window(window),
+                           TextTrackBinding::Wrap)
+    }
+
+    pub fn set_kind(&self, kind: TextTrackKind) {
+        self.kind.set(kind);
+    }
+
+    pub fn set_label(&self, label: DOMString) {
+        *self.label.borrow_mut() = label;
+    }
+
+    pub fn set_language(&self, language: DOMString) {
+        *self.language.borrow_mut() = language;
+    }
+
+    pub fn cue_list(&self) -> Root<TextTrackCueList> {
+        Root::from_ref(&*self.cues)
+    }
+
+    pub fn active_cue_list(&self) -> Root<TextTrackCueList> {
+        Root::from_ref(&*self.active_cues)
+    }
+
+    pub fn set_media_element(&self, element: Option<&HTMLMediaElement>) {
+        self.media_element.set(element);
+    }
+
+    pub fn track_element(&self) -> Option<Root<HTMLTrackElement>> {
+        self.track_element.get()
+    }
+
+    pub fn set_track_element(&self, element: &HTMLTrackElement) {
+        self.track_element.set(Some(element));
+    }
+
+    /// Adds the cues parsed from the resource of the track element.
+    pub fn add_cues(&self, cues: &[Root<TextTrackCue>]) {
+        for cue in cues {
+            cue.set_track(Some(self));
+            self.cues.add(cue);
+        }
+        self.cues_changed();
+    }
+
+    /// Removes all the cues, as when the resource of the track element
+    /// changes.
+    pub fn clear_cues(&self) {
+        for cue in self.cues.cues() {
+            cue.set_track(None);
+        }
+        self.cues.set_cues(&[]);
+        self.cues_changed();
+    }
+
+    /// Keeps a cue whose timings or text changed in text track cue order,
+    /// and shows the change.
+    pub fn cue_changed(&self, cue: &TextTrackCue) {
+        if self.cues.remove(cue) {
+            self.cues.add(cue);
+        }
+        self.cues_changed();
+    }
+
+    /// Updates the cues the media element of the track shows.
+    fn cues_changed(&self) {
+        if let Some(element) = self.media_element.get() {
+            element.time_marches_on(false);
+        }
+    }
+}
+
+impl TextTrackMethods for TextTrack {
+    // https://html.spec.whatwg.org/multipage/#dom-texttrack-kind
+    fn Kind(&self) -> TextTrackKind {
+        self.kind.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrack-label
+    fn Label(&self) -> DOMString {
+        self.label.borrow().clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrack-language
+    fn Language(&self) -> DOMString {
+        self.language.borrow().clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrack-id
+    fn Id(&self) -> DOMString {
+        match self.track_element.get() {
+            Some(element) => element.upcast::<Element>().Id(),
+            None => DOMString::new(),
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrack-mode
+    fn Mode(&self) -> TextTrackMode {
+        self.mode.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrack-mode
+    fn SetMode(&self, value: TextTrackMode) {
+        if self.mode.get() == value {
+            return;
+        }
+        self.mode.set(value);
+        // https://html.spec.whatwg.org/multipage/#start-the-track-processing-model
+        if let Some(element) = self.track_element.get() {
+            element.start_track_processing_model();
+        }
+        if let Some(element) = self.media_element.get() {
+            element.text_track_mode_changed();
+        }
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrack-cues
+    fn GetCues(&self) -> Option<Root<TextTrackCueList>> {
+        if self.mode.get() == TextTrackMode::Disabled {
+            return None;
+        }
+        Some(self.cue_list())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrack-activecues
+    fn GetActiveCues(&self) -> Option<Root<TextTrackCueList>> {
+        if self.mode.get() == TextTrackMode::Disabled {
+            return None;
+        }
+        Some(self.active_cue_list())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrack-addcue
+    fn AddCue(&self, cue: &TextTrackCue) {
+        // Step 2
+        if let Some(track) = cue.GetTrack() {
+            let _ = track.RemoveCue(cue);
+        }
+        // Step 3
+        cue.set_track(Some(self));
+        self.cues.add(cue);
+        self.cues_changed();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrack-removecue
+    fn RemoveCue(&self, cue: &TextTrackCue) -> ErrorResult {
+        // Step 1
+        if !self.cues.remove(cue) {
+            return Err(Error::NotFound);
+        }
+        // Step 2
+        cue.set_track(None);
+        self.cues_changed();
+        Ok(())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#handler-texttrack-oncuechange
+    event_handler!(cuechange, GetOncuechange, SetOncuechange);
+}
@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::TextTrackCueBinding::TextTrackCueMethods;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::str::DOMString;
+use dom::eventtarget::EventTarget;
+use dom::texttrack::TextTrack;
+use std::cell::Cell;
+
+// https://html.spec.whatwg.org/multipage/#texttrackcue
+#[dom_struct]
+pub struct TextTrackCue {
+    eventtarget: EventTarget,
+    id: DOMRefCell<DOMString>,
+    start_time: Cell<f64>,
+    end_time: Cell<f64>,
+    pause_on_exit: Cell<bool>,
+    /// The track whose list of cues the cue is in, if any.
+    track: MutNullableHeap<JS<TextTrack>>,
+}
+
+impl TextTrackCue {
+    pub fn new_inherited(id: DOMString, start_time: f64, end_time: f64) -> TextTrackCue {
+        TextTrackCue {
+            eventtarget: EventTarget::new_inherited(),
+            id: DOMRefCell::new(id),
+            start_time: Cell::new(start_time),
+            end_time: Cell::new(end_time),
+            pause_on_exit: Cell::new(false),
+            track: Default::default(),
+        }
+    }
+
+    pub fn set_track(&self, track: Option<&TextTrack>) {
+        self.track.set(track);
+    }
+
+    /// Whether the cue is shown at `time`.
+    /// https://html.spec.whatwg.org/multipage/#current-cues
+    pub fn is_active_at(&self, time: f64) -> bool {
+        self.start_time.get() <= time && time < self.end_time.get()
+    }
+
+    /// Tells the track of the cue that its timings or its text changed.
+    pub fn changed(&self) {
+        if let Some(track) = self.track.get() {
+            track.cue_changed(self);
+        }
+    }
+}
+
+impl TextTrackCueMethods for TextTrackCue {
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcue-track
+    fn GetTrack(&self) -> Option<Root<TextTrack>> {
+        self.track.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcue-id
+    fn Id(&self) -> DOMString {
+        self.id.borrow().clone()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcue-id
+    fn SetId(&self, value: DOMString) {
+        *self.id.borrow_mut() = value;
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcue-starttime
+    fn StartTime(&self) -> Finite<f64> {
+        Finite::wrap(self.start_time.get())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcue-starttime
+    fn SetStartTime(&self, value: Finite<f64>) {
+        self.start_time.set(*value);
+        self.changed();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcue-endtime
+    fn EndTime(&self) -> Finite<f64> {
+        Finite::wrap(self.end_time.get())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcue-endtime
+    fn SetEndTime(&self, value: Finite<f64>) {
+        self.end_time.set(*value);
+        self.changed();
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcue-pauseonexit
+    fn PauseOnExit(&self) -> bool {
+        self.pause_on_exit.get()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcue-pauseonexit
+    fn SetPauseOnExit(&self, value: bool) {
+        self.pause_on_exit.set(value);
+    }
+
+    // https://html.spec.whatwg.org/multipage/#handler-texttrackcue-onenter
+    event_handler!(enter, GetOnenter, SetOnenter);
+
+    // https://html.spec.whatwg.org/multipage/#handler-texttrackcue-onexit
+    event_handler!(exit, GetOnexit, SetOnexit);
+}
@@ -0,0 +1,89 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::TextTrackCueBinding::TextTrackCueMethods;
+use dom::bindings::codegen::Bindings::TextTrackCueListBinding::{self, TextTrackCueListMethods};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::texttrackcue::TextTrackCue;
+use dom::window::Window;
+
+// https://html.spec.whatwg.org/multipage/#texttrackcuelist
+#[dom_struct]
+pub struct TextTrackCueList {
+    reflector_: Reflector,
+    /// The cues, in text track cue order.
+    cues: DOMRefCell<Vec<JS<TextTrackCue>>>,
+}
+
+impl TextTrackCueList {
+    fn new_inherited() -> TextTrackCueList {
+        TextTrackCueList {
+            reflector_: Reflector::new(),
+            cues: DOMRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(window: &Window) -> Root<TextTrackCueList> {
+        reflect_dom_object(box TextTrackCueList::new_inherited(),
+                           GlobalRef::Window(window),
+                           TextTrackCueListBinding::Wrap)
+    }
+
+    pub fn cues(&self) -> Vec<Root<TextTrackCue>> {
+        self.cues.borrow().iter().map(|cue| Root::from_ref(&**cue)).collect()
+    }
+
+    pub fn contains(&self, cue: &TextTrackCue) -> bool {
+        self.cues.borrow().iter().any(|other| *other == JS::from_ref(cue))
+    }
+
+    /// Adds a cue after those that start before it, or start at the same
+    /// time and end after it.
+    /// https://html.spec.whatwg.org/multipage/#text-track-cue-order
+    pub fn add(&self, cue: &TextTrackCue) {
+        let mut cues = self.cues.borrow_mut();
+        let (start, end) = (*cue.StartTime(), *cue.EndTime());
+        let index = cues.iter().position(|other| {
+            let (other_start, other_end) = (*other.StartTime(), *other.EndTime());
+            other_start > start || (other_start == start && other_end < end)
+        }).unwrap_or(cues.len());
+        cues.insert(index, JS::from_ref(cue));
+    }
+
+    /// Removes a cue, returning whether it was in the list.
+    pub fn remove(&self, cue: &TextTrackCue) -> bool {
+        let mut cues = self.cues.borrow_mut();
+        let length = cues.len();
+        cues.retain(|other| *other != JS::from_ref(cue));
+        cues.len() != length
+    }
+
+    pub fn set_cues(&self, cues: &[Root<TextTrackCue>]) {
+        *self.cues.borrow_mut() = cues.iter().map(|cue| JS::from_ref(&**cue)).collect();
+    }
+}
+
+impl TextTrackCueListMethods for TextTrackCueList {
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcuelist-length
+    fn Length(&self) -> u32 {
+        self.cues.borrow().len() as u32
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcuelist-item
+    fn IndexedGetter(&self, index: u32) -> Option<Root<TextTrackCue>> {
+        self.cues.borrow().get(index as usize).map(|cue| Root::from_ref(&**cue))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttrackcuelist-getcuebyid
+    fn GetCueById(&self, id: DOMString) -> Option<Root<TextTrackCue>> {
+        if id.is_empty() {
+            return None;
+        }
+        self.cues.borrow().iter().find(|cue| cue.Id() == id).map(|cue| Root::from_ref(&**cue))
+    }
+}
@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::TextTrackBinding::TextTrackMethods;
+use dom::bindings::codegen::Bindings::TextTrackListBinding::{self, TextTrackListMethods};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::eventtarget::EventTarget;
+use dom::texttrack::TextTrack;
+use dom::window::Window;
+
+// https://html.spec.whatwg.org/multipage/#texttracklist
+#[dom_struct]
+pub struct TextTrackList {
+    eventtarget: EventTarget,
+    tracks: DOMRefCell<Vec<JS<TextTrack>>>,
+}
+
+impl TextTrackList {
+    fn new_inherited() -> TextTrackList {
+        TextTrackList {
+            eventtarget: EventTarget::new_inherited(),
+            tracks: DOMRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(window: &Window) -> Root<TextTrackList> {
+        reflect_dom_object(box TextTrackList::new_inherited(),
+                           GlobalRef::Window(window),
+                           TextTrackListBinding::Wrap)
+    }
+
+    pub fn tracks(&self) -> Vec<Root<TextTrack>> {
+        self.tracks.borrow().iter().map(|track| Root::from_ref(&**track)).collect()
+    }
+
+    pub fn contains(&self, track: &TextTrack) -> bool {
+        self.tracks.borrow().iter().any(|other| *other == JS::from_ref(track))
+    }
+
+    pub fn push(&self, track: &TextTrack) {
+        self.tracks.borrow_mut().push(JS::from_ref(track));
+    }
+
+    /// Removes a track, returning whether it was in the list.
+    pub fn remove(&self, track: &TextTrack) -> bool {
+        let mut tracks = self.tracks.borrow_mut();
+        let length = tracks.len();
+        tracks.retain(|other| *other != JS::from_ref(track));
+        tracks.len() != length
+    }
+}
+
+impl TextTrackListMethods for TextTrackList {
+    // https://html.spec.whatwg.org/multipage/#dom-texttracklist-length
+    fn Length(&self) -> u32 {
+        self.tracks.borrow().len() as u32
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttracklist-item
+    fn IndexedGetter(&self, index: u32) -> Option<Root<TextTrack>> {
+        self.tracks.borrow().get(index as usize).map(|track| Root::from_ref(&**track))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-texttracklist-gettrackbyid
+    fn GetTrackById(&self, id: DOMString) -> Option<Root<TextTrack>> {
+        if id.is_empty() {
+            return None;
+        }
+        self.tracks.borrow().iter().find(|track| track.Id() == id).map(|track| Root::from_ref(&**track))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#handler-texttracklist-onchange
+    event_handler!(change, GetOnchange, SetOnchange);
+
+    // https://html.spec.whatwg.org/multipage/#handler-texttracklist-onaddtrack
+    event_handler!(addtrack, GetOnaddtrack, SetOnaddtrack);
+
+    // https://html.spec.whatwg.org/multipage/#handler-texttracklist-onremovetrack
+    event_handler!(removetrack, GetOnremovetrack, SetOnremovetrack);
+}
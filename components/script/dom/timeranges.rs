@@ -0,0 +1,56 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::TimeRangesBinding::{self, TimeRangesMethods};
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::window::Window;
+use media::source::TimeRange;
+
+// https://html.spec.whatwg.org/multipage/#time-ranges
+#[dom_struct]
+pub struct TimeRanges {
+    reflector_: Reflector,
+    /// The ranges, sorted and disjoint.
+    ranges: Vec<TimeRange>,
+}
+
+impl TimeRanges {
+    fn new_inherited(ranges: Vec<TimeRange>) -> TimeRanges {
+        TimeRanges {
+            reflector_: Reflector::new(),
+            ranges: ranges,
+        }
+    }
+
+    pub fn new(window: &Window, ranges: Vec<TimeRange>) -> Root<TimeRanges> {
+        reflect_dom_object(box TimeRanges::new_inherited(ranges),
+                           GlobalRef::Window(window),
+                           TimeRangesBinding::Wrap)
+    }
+
+    fn range(&self, index: u32) -> Fallible<TimeRange> {
+        self.ranges.get(index as usize).cloned().ok_or(Error::IndexSize)
+    }
+}
+
+impl TimeRangesMethods for TimeRanges {
+    // https://html.spec.whatwg.org/multipage/#dom-timeranges-length
+    fn Length(&self) -> u32 {
+        self.ranges.len() as u32
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-timeranges-start
+    fn Start(&self, index: u32) -> Fallible<Finite<f64>> {
+        self.range(index).map(|(start, _)| Finite::wrap(start))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-timeranges-end
+    fn End(&self, index: u32) -> Fallible<Finite<f64>> {
+        self.range(index).map(|(_, end)| Finite::wrap(end))
+    }
+}
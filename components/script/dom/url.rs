@@ -11,6 +11,7 @@ use dom::bindings::js::{JS, MutNullableHeap, Root};
 use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
 use dom::bindings::str::{DOMString, USVString};
 use dom::blob::Blob;
+use dom::mediasource::MediaSource;
 use dom::urlhelper::UrlHelper;
 use dom::urlsearchparams::URLSearchParams;
 use ipc_channel::ipc;
@@ -54,8 +55,7 @@ impl URL {
     }
 
     pub fn set_query_pairs(&self, pairs: &[(String, String)]) {
-        let mut url = self.url.borrow_mut();
-        url.query_pairs_mut().clear().extend_pairs(pairs);
+        UrlHelper::SetQueryPairs(&mut self.url.borrow_mut(), pairs);
     }
 }
 
@@ -130,6 +130,16 @@ impl URL {
         DOMString::from(URL::unicode_serialization_blob_url(&origin, &id))
     }
 
+    // https://w3c.github.io/media-source/#dom-url-createobjecturl
+    pub fn CreateObjectURL_(global: GlobalRef, media_source: &MediaSource) -> DOMString {
+        // Media sources are only exposed to windows.
+        let window = global.as_window();
+        let origin = get_blob_origin(&global.get_url());
+        let url = URL::unicode_serialization_blob_url(&origin, &Uuid::new_v4());
+        window.add_media_source_url(url.clone(), media_source);
+        DOMString::from(url)
+    }
+
     // https://w3c.github.io/FileAPI/#dfn-revokeObjectURL
     pub fn RevokeObjectURL(global: GlobalRef, url: DOMString) {
         /*
@@ -143,6 +153,10 @@ impl URL {
         */
         let origin = get_blob_origin(&global.get_url());
 
+        if let GlobalRef::Window(window) = global {
+            window.revoke_media_source_url(&url);
+        }
+
         if let Ok(url) = Url::parse(&url) {
              if let Ok((id, _, _)) = parse_blob_url(&url) {
                 let resource_threads = global.resource_threads();
@@ -214,7 +228,10 @@ impl URLMethods for URL {
         match Url::parse(&value.0) {
             Ok(url) => {
                 *self.url.borrow_mut() = url;
-                self.search_params.set(None);  // To be re-initialized in the SearchParams getter.
+                // Keep an existing searchParams object alive, reflecting the new query.
+                if let Some(search_params) = self.search_params.get() {
+                    search_params.set_list(self.query_pairs());
+                }
                 Ok(())
             },
             Err(error) => {
@@ -277,7 +294,7 @@ impl URLMethods for URL {
     fn SetSearch(&self, value: USVString) {
         UrlHelper::SetSearch(&mut self.url.borrow_mut(), value);
         if let Some(search_params) = self.search_params.get() {
-            search_params.set_list(self.url.borrow().query_pairs().into_owned().collect());
+            search_params.set_list(self.query_pairs());
         }
     }
 
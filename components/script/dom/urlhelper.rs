@@ -3,8 +3,9 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::str::USVString;
-use std::borrow::ToOwned;
-use url::{Url, quirks};
+use encoding::types::EncodingRef;
+use std::borrow::{Borrow, ToOwned};
+use url::{Url, form_urlencoded, quirks};
 
 #[derive(HeapSizeOf)]
 pub struct UrlHelper;
@@ -31,4 +32,28 @@ impl UrlHelper {
     pub fn SetPassword(url: &mut Url, value: USVString) { let _ = quirks::set_password(url, &value.0); }
     pub fn SetProtocol(url: &mut Url, value: USVString) { let _ = quirks::set_protocol(url, &value.0); }
     pub fn SetUsername(url: &mut Url, value: USVString) { let _ = quirks::set_username(url, &value.0); }
+
+    // https://url.spec.whatwg.org/#concept-urlencoded-parser
+    pub fn ParseQuery(query: &str) -> Vec<(String, String)> {
+        form_urlencoded::parse(query.as_bytes()).into_owned().collect()
+    }
+
+    // https://url.spec.whatwg.org/#concept-urlencoded-serializer
+    pub fn SerializePairs<I, K, V>(pairs: I, encoding: Option<EncodingRef>) -> String
+        where I: IntoIterator, I::Item: Borrow<(K, V)>, K: AsRef<str>, V: AsRef<str> {
+        form_urlencoded::Serializer::new(String::new())
+            .encoding_override(encoding)
+            .extend_pairs(pairs)
+            .finish()
+    }
+
+    // https://url.spec.whatwg.org/#concept-urlsearchparams-update
+    pub fn SetQueryPairs(url: &mut Url, pairs: &[(String, String)]) {
+        // An empty list clears the query entirely rather than leaving a lone "?".
+        if pairs.is_empty() {
+            url.set_query(None);
+        } else {
+            url.set_query(Some(&UrlHelper::SerializePairs(pairs, None)));
+        }
+    }
 }
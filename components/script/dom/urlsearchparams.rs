@@ -3,18 +3,19 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use dom::bindings::cell::DOMRefCell;
-use dom::bindings::codegen::Bindings::URLSearchParamsBinding;
 use dom::bindings::codegen::Bindings::URLSearchParamsBinding::URLSearchParamsMethods;
+use dom::bindings::codegen::Bindings::URLSearchParamsBinding::URLSearchParamsWrap;
 use dom::bindings::codegen::UnionTypes::USVStringOrURLSearchParams;
 use dom::bindings::error::Fallible;
 use dom::bindings::global::GlobalRef;
+use dom::bindings::iterable::Iterable;
 use dom::bindings::js::Root;
 use dom::bindings::reflector::{Reflector, reflect_dom_object};
 use dom::bindings::str::{DOMString, USVString};
 use dom::bindings::weakref::MutableWeakRef;
 use dom::url::URL;
+use dom::urlhelper::UrlHelper;
 use encoding::types::EncodingRef;
-use url::form_urlencoded;
 
 // https://url.spec.whatwg.org/#interface-urlsearchparams
 #[dom_struct]
@@ -37,7 +38,7 @@ impl URLSearchParams {
 
     pub fn new(global: GlobalRef, url: Option<&URL>) -> Root<URLSearchParams> {
         reflect_dom_object(box URLSearchParams::new_inherited(url), global,
-                           URLSearchParamsBinding::Wrap)
+                           URLSearchParamsWrap)
     }
 
     // https://url.spec.whatwg.org/#dom-urlsearchparams-urlsearchparams
@@ -48,8 +49,8 @@ impl URLSearchParams {
         match init {
             Some(USVStringOrURLSearchParams::USVString(init)) => {
                 // Step 2.
-                *query.list.borrow_mut() = form_urlencoded::parse(init.0.as_bytes())
-                    .into_owned().collect();
+                let init = if init.0.starts_with('?') { &init.0[1..] } else { &*init.0 };
+                *query.list.borrow_mut() = UrlHelper::ParseQuery(init);
             },
             Some(USVStringOrURLSearchParams::URLSearchParams(init)) => {
                 // Step 3.
@@ -67,6 +68,11 @@ impl URLSearchParams {
 }
 
 impl URLSearchParamsMethods for URLSearchParams {
+    // https://url.spec.whatwg.org/#dom-urlsearchparams-size
+    fn Size(&self) -> u32 {
+        self.list.borrow().len() as u32
+    }
+
     // https://url.spec.whatwg.org/#dom-urlsearchparams-append
     fn Append(&self, name: USVString, value: USVString) {
         // Step 1.
@@ -136,6 +142,14 @@ impl URLSearchParamsMethods for URLSearchParams {
         self.update_steps();
     }
 
+    // https://url.spec.whatwg.org/#dom-urlsearchparams-sort
+    fn Sort(&self) {
+        // Step 1: a stable sort, comparing names by UTF-16 code units.
+        self.list.borrow_mut().sort_by(|&(ref a, _), &(ref b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+        // Step 2.
+        self.update_steps();
+    }
+
     // https://url.spec.whatwg.org/#stringification-behavior
     fn Stringifier(&self) -> DOMString {
         DOMString::from(self.serialize(None))
@@ -146,11 +160,7 @@ impl URLSearchParamsMethods for URLSearchParams {
 impl URLSearchParams {
     // https://url.spec.whatwg.org/#concept-urlencoded-serializer
     pub fn serialize(&self, encoding: Option<EncodingRef>) -> String {
-        let list = self.list.borrow();
-        form_urlencoded::Serializer::new(String::new())
-            .encoding_override(encoding)
-            .extend_pairs(&*list)
-            .finish()
+        UrlHelper::SerializePairs(&*self.list.borrow(), encoding)
     }
 }
 
@@ -163,3 +173,22 @@ impl URLSearchParams {
         }
     }
 }
+
+impl Iterable for URLSearchParams {
+    type Key = USVString;
+    type Value = USVString;
+
+    fn get_iterable_length(&self) -> u32 {
+        self.list.borrow().len() as u32
+    }
+
+    fn get_value_at_index(&self, n: u32) -> USVString {
+        let value = self.list.borrow()[n as usize].1.clone();
+        USVString(value)
+    }
+
+    fn get_key_at_index(&self, n: u32) -> USVString {
+        let key = self.list.borrow()[n as usize].0.clone();
+        USVString(key)
+    }
+}
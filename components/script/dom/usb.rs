@@ -0,0 +1,240 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::USBBinding::{self, USBDeviceFilter, USBDeviceRequestOptions, USBMethods};
+use dom::bindings::error::Error;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::refcounted::{Trusted, TrustedPromise};
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::promise::Promise;
+use dom::usbdevice::USBDevice;
+use ipc_channel::ipc::{self, IpcSender};
+use js::jsapi::JSAutoCompartment;
+use msg::constellation_msg::PipelineId;
+use script_thread::Runnable;
+use script_traits::ScriptMsg as ConstellationMsg;
+use std::rc::Rc;
+use task_source::TaskSource;
+use usb_traits::{UsbDeviceFilter, UsbDeviceMsg, UsbError, UsbMethodMsg};
+use util::thread::spawn_named;
+
+// https://wicg.github.io/webusb/#usb
+#[dom_struct]
+pub struct USB {
+    reflector_: Reflector,
+    /// The `USBDevice` objects handed out so far, so that each device has only one.
+    devices: DOMRefCell<Vec<JS<USBDevice>>>,
+}
+
+impl USB {
+    fn new_inherited() -> USB {
+        USB {
+            reflector_: Reflector::new(),
+            devices: DOMRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<USB> {
+        reflect_dom_object(box USB::new_inherited(),
+                           global,
+                           USBBinding::Wrap)
+    }
+
+    fn get_usb_thread(&self) -> IpcSender<UsbMethodMsg> {
+        self.global().r().as_window().usb_thread()
+    }
+
+    fn origin(&self) -> String {
+        self.global().r().get_url().origin().unicode_serialization()
+    }
+
+    /// The `USBDevice` object that represents `device`.
+    fn device_object(&self, device: UsbDeviceMsg) -> Root<USBDevice> {
+        if let Some(object) = self.devices.borrow().iter().find(|object| object.id() == device.id) {
+            return Root::from_ref(&**object);
+        }
+        let object = USBDevice::new(self.global().r(), device);
+        self.devices.borrow_mut().push(JS::from_ref(&*object));
+        object
+    }
+
+    fn finish_get_devices(&self, promise: &Promise, result: Result<Vec<UsbDeviceMsg>, Error>) {
+        let global = self.global();
+        match result {
+            Ok(devices) => {
+                let devices: Vec<_> = devices.into_iter().map(|device| self.device_object(device)).collect();
+                promise.resolve_native(global.r().get_cx(), &devices);
+            },
+            Err(error) => promise.reject_error(global.r().get_cx(), error),
+        }
+    }
+
+    fn finish_request_device(&self, promise: &Promise, result: Result<UsbDeviceMsg, Error>) {
+        let global = self.global();
+        match result {
+            Ok(device) => promise.resolve_native(global.r().get_cx(), &self.device_object(device)),
+            Err(error) => promise.reject_error(global.r().get_cx(), error),
+        }
+    }
+}
+
+impl From<UsbError> for Error {
+    fn from(error: UsbError) -> Self {
+        match error {
+            UsbError::InvalidState => Error::InvalidState,
+            UsbError::Network => Error::Network,
+            UsbError::NotFound => Error::NotFound,
+            UsbError::NotSupported => Error::NotSupported,
+            UsbError::Security => Error::Security,
+        }
+    }
+}
+
+fn convert_filter(filter: &USBDeviceFilter) -> UsbDeviceFilter {
+    UsbDeviceFilter {
+        vendor_id: filter.vendorId,
+        product_id: filter.productId,
+        class_code: filter.classCode,
+        subclass_code: filter.subclassCode,
+        protocol_code: filter.protocolCode,
+        serial_number: filter.serialNumber.as_ref().map(|serial| String::from(serial.clone())),
+    }
+}
+
+/// How a device is shown to the user in the chooser.
+fn device_label(device: &UsbDeviceMsg) -> String {
+    let name = match (device.product_name.as_ref(), device.manufacturer_name.as_ref()) {
+        (Some(product), Some(manufacturer)) => format!("{} ({})", product, manufacturer),
+        (Some(product), None) => product.clone(),
+        _ => "Unknown device".to_owned(),
+    };
+    format!("{} [{:04x}:{:04x}]", name, device.vendor_id, device.product_id)
+}
+
+/// The steps of `requestDevice()` that block on the user, and therefore run off the
+/// script thread.
+/// https://wicg.github.io/webusb/#request-the-usb-permission-storage
+fn request_device(usb_thread: IpcSender<UsbMethodMsg>,
+                  constellation_chan: IpcSender<ConstellationMsg>,
+                  pipeline_id: PipelineId,
+                  origin: String,
+                  filters: Vec<UsbDeviceFilter>)
+                  -> Result<UsbDeviceMsg, Error> {
+    // Step 3.
+    let (sender, receiver) = ipc::channel().unwrap();
+    usb_thread.send(UsbMethodMsg::MatchingDevices(filters, sender)).unwrap();
+    let devices = try!(receiver.recv().unwrap_or(Err(UsbError::Network)));
+
+    // Step 4: ask the user to choose one, through the embedder.
+    let choices = devices.iter().map(|device| (device.id.clone(), device_label(device))).collect();
+    let (sender, receiver) = ipc::channel().unwrap();
+    constellation_chan.send(ConstellationMsg::ChooseUsbDevice(pipeline_id, choices, sender)).unwrap();
+    let device_id = match receiver.recv().unwrap_or(None) {
+        Some(device_id) => device_id,
+        // Step 5.
+        None => return Err(Error::NotFound),
+    };
+
+    // Steps 6-7.
+    let (sender, receiver) = ipc::channel().unwrap();
+    usb_thread.send(UsbMethodMsg::AllowDevice(origin, device_id, sender)).unwrap();
+    receiver.recv().unwrap_or(Err(UsbError::Network)).map_err(Error::from)
+}
+
+impl USBMethods for USB {
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usb-getdevices
+    fn GetDevices(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let usb_thread = self.get_usb_thread();
+        let origin = self.origin();
+        let mut runnable = box GetDevicesRunnable {
+            usb: Trusted::new(self),
+            promise: TrustedPromise::new(promise.clone()),
+            result: Ok(vec![]),
+        };
+        spawn_named("UsbGetDevices".to_owned(), move || {
+            let (sender, receiver) = ipc::channel().unwrap();
+            runnable.result = match usb_thread.send(UsbMethodMsg::GetDevices(origin, sender)) {
+                Ok(()) => receiver.recv().unwrap_or(Err(UsbError::Network)).map_err(Error::from),
+                Err(_) => Err(Error::Network),
+            };
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        });
+        promise
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usb-requestdevice
+    fn RequestDevice(&self, options: &USBDeviceRequestOptions) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let filters = options.filters.iter().map(convert_filter).collect();
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let pipeline_id = window.pipeline_id();
+        let constellation_chan = window.constellation_chan().clone();
+        let usb_thread = self.get_usb_thread();
+        let origin = self.origin();
+        let mut runnable = box RequestDeviceRunnable {
+            usb: Trusted::new(self),
+            promise: TrustedPromise::new(promise.clone()),
+            result: Err(Error::NotFound),
+        };
+        spawn_named("UsbRequestDevice".to_owned(), move || {
+            runnable.result = request_device(usb_thread, constellation_chan, pipeline_id, origin, filters);
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        });
+        promise
+    }
+}
+
+/// Delivers the allowed devices found for a `getDevices()` call.
+struct GetDevicesRunnable {
+    usb: Trusted<USB>,
+    promise: TrustedPromise,
+    result: Result<Vec<UsbDeviceMsg>, Error>,
+}
+
+impl Runnable for GetDevicesRunnable {
+    fn name(&self) -> &'static str { "GetDevicesRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let usb = this.usb.root();
+        let promise = this.promise.root();
+        let global = usb.global();
+        let _ac = JSAutoCompartment::new(global.r().get_cx(), promise.reflector().get_jsobject().get());
+        usb.finish_get_devices(&promise, this.result);
+    }
+}
+
+/// Delivers the device the user chose for a `requestDevice()` call.
+struct RequestDeviceRunnable {
+    usb: Trusted<USB>,
+    promise: TrustedPromise,
+    result: Result<UsbDeviceMsg, Error>,
+}
+
+impl Runnable for RequestDeviceRunnable {
+    fn name(&self) -> &'static str { "RequestDeviceRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let usb = this.usb.root();
+        let promise = this.promise.root();
+        let global = usb.global();
+        let _ac = JSAutoCompartment::new(global.r().get_cx(), promise.reflector().get_jsobject().get());
+        usb.finish_request_device(&promise, this.result);
+    }
+}
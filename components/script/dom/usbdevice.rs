@@ -0,0 +1,301 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::USBDeviceBinding;
+use dom::bindings::codegen::Bindings::USBDeviceBinding::{USBControlTransferParameters, USBDeviceMethods};
+use dom::bindings::codegen::Bindings::USBDeviceBinding::{USBRecipient, USBRequestType};
+use dom::bindings::error::Error;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::refcounted::{Trusted, TrustedPromise};
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::promise::Promise;
+use dom::usbintransferresult::USBInTransferResult;
+use dom::usbouttransferresult::USBOutTransferResult;
+use ipc_channel::ipc::{self, IpcSender};
+use js::jsapi::JSAutoCompartment;
+use script_thread::Runnable;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::rc::Rc;
+use task_source::TaskSource;
+use usb_traits::{UsbControlSetup, UsbDeviceMsg, UsbError, UsbInTransferMsg, UsbMethodMsg};
+use usb_traits::{UsbOutTransferMsg, UsbRecipient, UsbRequestType, UsbResult};
+use util::thread::spawn_named;
+
+// https://wicg.github.io/webusb/#usbdevice
+#[dom_struct]
+pub struct USBDevice {
+    reflector_: Reflector,
+    #[ignore_heap_size_of = "Defined in net_traits"]
+    device: UsbDeviceMsg,
+    opened: Cell<bool>,
+}
+
+impl USBDevice {
+    fn new_inherited(device: UsbDeviceMsg) -> USBDevice {
+        USBDevice {
+            reflector_: Reflector::new(),
+            device: device,
+            opened: Cell::new(false),
+        }
+    }
+
+    pub fn new(global: GlobalRef, device: UsbDeviceMsg) -> Root<USBDevice> {
+        reflect_dom_object(box USBDevice::new_inherited(device),
+                           global,
+                           USBDeviceBinding::Wrap)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.device.id
+    }
+
+    /// Sends the message that `message` makes with a reply sender to the USB thread, and
+    /// settles the returned promise with `settle` once the reply arrives. Transfers may take
+    /// until the device answers, so the reply is waited for off the script thread.
+    #[allow(unrooted_must_root)]
+    fn request<T, F>(&self, message: F, settle: fn(&USBDevice, &Promise, T)) -> Rc<Promise>
+        where T: Deserialize + Serialize + Send + 'static,
+              F: FnOnce(IpcSender<UsbResult<T>>) -> UsbMethodMsg
+    {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let window = global.r().as_window();
+        let task_source = window.dom_manipulation_task_source();
+        let wrapper = window.get_runnable_wrapper();
+        let (sender, receiver) = ipc::channel().unwrap();
+        if window.usb_thread().send(message(sender)).is_err() {
+            promise.reject_error(global.r().get_cx(), Error::Network);
+            return promise;
+        }
+        let mut runnable = box UsbReplyRunnable {
+            device: Trusted::new(self),
+            promise: TrustedPromise::new(promise.clone()),
+            result: Err(UsbError::Network),
+            settle: settle,
+        };
+        spawn_named("UsbRequest".to_owned(), move || {
+            runnable.result = receiver.recv().unwrap_or(Err(UsbError::Network));
+            let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+        });
+        promise
+    }
+}
+
+fn control_setup(setup: &USBControlTransferParameters) -> UsbControlSetup {
+    UsbControlSetup {
+        request_type: match setup.requestType {
+            USBRequestType::Standard => UsbRequestType::Standard,
+            USBRequestType::Class => UsbRequestType::Class,
+            USBRequestType::Vendor => UsbRequestType::Vendor,
+        },
+        recipient: match setup.recipient {
+            USBRecipient::Device => UsbRecipient::Device,
+            USBRecipient::Interface => UsbRecipient::Interface,
+            USBRecipient::Endpoint => UsbRecipient::Endpoint,
+            USBRecipient::Other => UsbRecipient::Other,
+        },
+        request: setup.request,
+        value: setup.value,
+        index: setup.index,
+    }
+}
+
+fn finish_open(device: &USBDevice, promise: &Promise, _: bool) {
+    device.opened.set(true);
+    promise.resolve_native(device.global().r().get_cx(), &());
+}
+
+fn finish_close(device: &USBDevice, promise: &Promise, _: bool) {
+    device.opened.set(false);
+    promise.resolve_native(device.global().r().get_cx(), &());
+}
+
+fn finish(device: &USBDevice, promise: &Promise, _: bool) {
+    promise.resolve_native(device.global().r().get_cx(), &());
+}
+
+fn finish_transfer_in(device: &USBDevice, promise: &Promise, result: UsbInTransferMsg) {
+    let global = device.global();
+    promise.resolve_native(global.r().get_cx(), &USBInTransferResult::new(global.r(), result));
+}
+
+fn finish_transfer_out(device: &USBDevice, promise: &Promise, result: UsbOutTransferMsg) {
+    let global = device.global();
+    promise.resolve_native(global.r().get_cx(), &USBOutTransferResult::new(global.r(), result));
+}
+
+impl USBDeviceMethods for USBDevice {
+    // https://wicg.github.io/webusb/#dom-usbdevice-usbversionmajor
+    fn UsbVersionMajor(&self) -> u8 {
+        (self.device.usb_version >> 8) as u8
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-usbversionminor
+    fn UsbVersionMinor(&self) -> u8 {
+        ((self.device.usb_version >> 4) & 0xF) as u8
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-usbversionsubminor
+    fn UsbVersionSubminor(&self) -> u8 {
+        (self.device.usb_version & 0xF) as u8
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-deviceclass
+    fn DeviceClass(&self) -> u8 {
+        self.device.device_class
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-devicesubclass
+    fn DeviceSubclass(&self) -> u8 {
+        self.device.device_subclass
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-deviceprotocol
+    fn DeviceProtocol(&self) -> u8 {
+        self.device.device_protocol
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-vendorid
+    fn VendorId(&self) -> u16 {
+        self.device.vendor_id
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-productid
+    fn ProductId(&self) -> u16 {
+        self.device.product_id
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-deviceversionmajor
+    fn DeviceVersionMajor(&self) -> u8 {
+        (self.device.device_version >> 8) as u8
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-deviceversionminor
+    fn DeviceVersionMinor(&self) -> u8 {
+        ((self.device.device_version >> 4) & 0xF) as u8
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-deviceversionsubminor
+    fn DeviceVersionSubminor(&self) -> u8 {
+        (self.device.device_version & 0xF) as u8
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-manufacturername
+    fn GetManufacturerName(&self) -> Option<DOMString> {
+        self.device.manufacturer_name.clone().map(DOMString::from)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-productname
+    fn GetProductName(&self) -> Option<DOMString> {
+        self.device.product_name.clone().map(DOMString::from)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-serialnumber
+    fn GetSerialNumber(&self) -> Option<DOMString> {
+        self.device.serial_number.clone().map(DOMString::from)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-opened
+    fn Opened(&self) -> bool {
+        self.opened.get()
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usbdevice-open
+    fn Open(&self) -> Rc<Promise> {
+        let id = self.device.id.clone();
+        self.request(move |sender| UsbMethodMsg::Open(id, sender), finish_open)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usbdevice-close
+    fn Close(&self) -> Rc<Promise> {
+        let id = self.device.id.clone();
+        self.request(move |sender| UsbMethodMsg::Close(id, sender), finish_close)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usbdevice-selectconfiguration
+    fn SelectConfiguration(&self, configuration_value: u8) -> Rc<Promise> {
+        let id = self.device.id.clone();
+        self.request(move |sender| UsbMethodMsg::SelectConfiguration(id, configuration_value, sender), finish)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usbdevice-claiminterface
+    fn ClaimInterface(&self, interface_number: u8) -> Rc<Promise> {
+        let id = self.device.id.clone();
+        self.request(move |sender| UsbMethodMsg::ClaimInterface(id, interface_number, sender), finish)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usbdevice-releaseinterface
+    fn ReleaseInterface(&self, interface_number: u8) -> Rc<Promise> {
+        let id = self.device.id.clone();
+        self.request(move |sender| UsbMethodMsg::ReleaseInterface(id, interface_number, sender), finish)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usbdevice-controltransferin
+    fn ControlTransferIn(&self, setup: &USBControlTransferParameters, length: u16) -> Rc<Promise> {
+        let id = self.device.id.clone();
+        let setup = control_setup(setup);
+        self.request(move |sender| UsbMethodMsg::ControlTransferIn(id, setup, length, sender),
+                     finish_transfer_in)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usbdevice-controltransferout
+    fn ControlTransferOut(&self, setup: &USBControlTransferParameters, data: Option<Vec<u8>>) -> Rc<Promise> {
+        let id = self.device.id.clone();
+        let setup = control_setup(setup);
+        let data = data.unwrap_or(vec![]);
+        self.request(move |sender| UsbMethodMsg::ControlTransferOut(id, setup, data, sender),
+                     finish_transfer_out)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usbdevice-transferin
+    fn TransferIn(&self, endpoint_number: u8, length: u32) -> Rc<Promise> {
+        let id = self.device.id.clone();
+        self.request(move |sender| UsbMethodMsg::TransferIn(id, endpoint_number, length, sender),
+                     finish_transfer_in)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://wicg.github.io/webusb/#dom-usbdevice-transferout
+    fn TransferOut(&self, endpoint_number: u8, data: Vec<u8>) -> Rc<Promise> {
+        let id = self.device.id.clone();
+        self.request(move |sender| UsbMethodMsg::TransferOut(id, endpoint_number, data, sender),
+                     finish_transfer_out)
+    }
+}
+
+/// Settles the promise of a `USBDevice` method with the reply of the USB thread.
+struct UsbReplyRunnable<T> {
+    device: Trusted<USBDevice>,
+    promise: TrustedPromise,
+    result: UsbResult<T>,
+    settle: fn(&USBDevice, &Promise, T),
+}
+
+impl<T> Runnable for UsbReplyRunnable<T> {
+    fn name(&self) -> &'static str { "UsbReplyRunnable" }
+
+    #[allow(unrooted_must_root)]
+    fn handler(self: Box<Self>) {
+        let this = *self;
+        let device = this.device.root();
+        let promise = this.promise.root();
+        let global = device.global();
+        let _ac = JSAutoCompartment::new(global.r().get_cx(), promise.reflector().get_jsobject().get());
+        match this.result {
+            Ok(value) => (this.settle)(&device, &promise, value),
+            Err(error) => promise.reject_error(global.r().get_cx(), Error::from(error)),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::USBInTransferResultBinding;
+use dom::bindings::codegen::Bindings::USBInTransferResultBinding::{USBInTransferResultMethods, USBTransferStatus};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::ByteString;
+use usb_traits::{UsbInTransferMsg, UsbTransferStatus};
+
+// https://wicg.github.io/webusb/#usbintransferresult
+#[dom_struct]
+pub struct USBInTransferResult {
+    reflector_: Reflector,
+    data: Option<ByteString>,
+    status: USBTransferStatus,
+}
+
+/// The `USBTransferStatus` of a transfer that the USB thread reported.
+pub fn transfer_status(status: UsbTransferStatus) -> USBTransferStatus {
+    match status {
+        UsbTransferStatus::Ok => USBTransferStatus::Ok,
+        UsbTransferStatus::Stall => USBTransferStatus::Stall,
+        UsbTransferStatus::Babble => USBTransferStatus::Babble,
+    }
+}
+
+impl USBInTransferResult {
+    fn new_inherited(result: UsbInTransferMsg) -> USBInTransferResult {
+        USBInTransferResult {
+            reflector_: Reflector::new(),
+            // A stalled transfer has no data.
+            data: match result.status {
+                UsbTransferStatus::Stall => None,
+                _ => Some(ByteString::new(result.data)),
+            },
+            status: transfer_status(result.status),
+        }
+    }
+
+    pub fn new(global: GlobalRef, result: UsbInTransferMsg) -> Root<USBInTransferResult> {
+        reflect_dom_object(box USBInTransferResult::new_inherited(result),
+                           global,
+                           USBInTransferResultBinding::Wrap)
+    }
+}
+
+impl USBInTransferResultMethods for USBInTransferResult {
+    // https://wicg.github.io/webusb/#dom-usbintransferresult-data
+    fn GetData(&self) -> Option<ByteString> {
+        self.data.clone()
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbintransferresult-status
+    fn Status(&self) -> USBTransferStatus {
+        self.status
+    }
+}
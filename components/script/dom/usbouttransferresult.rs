@@ -0,0 +1,48 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::USBInTransferResultBinding::USBTransferStatus;
+use dom::bindings::codegen::Bindings::USBOutTransferResultBinding;
+use dom::bindings::codegen::Bindings::USBOutTransferResultBinding::USBOutTransferResultMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::usbintransferresult::transfer_status;
+use usb_traits::UsbOutTransferMsg;
+
+// https://wicg.github.io/webusb/#usbouttransferresult
+#[dom_struct]
+pub struct USBOutTransferResult {
+    reflector_: Reflector,
+    bytes_written: u32,
+    status: USBTransferStatus,
+}
+
+impl USBOutTransferResult {
+    fn new_inherited(result: UsbOutTransferMsg) -> USBOutTransferResult {
+        USBOutTransferResult {
+            reflector_: Reflector::new(),
+            bytes_written: result.bytes_written,
+            status: transfer_status(result.status),
+        }
+    }
+
+    pub fn new(global: GlobalRef, result: UsbOutTransferMsg) -> Root<USBOutTransferResult> {
+        reflect_dom_object(box USBOutTransferResult::new_inherited(result),
+                           global,
+                           USBOutTransferResultBinding::Wrap)
+    }
+}
+
+impl USBOutTransferResultMethods for USBOutTransferResult {
+    // https://wicg.github.io/webusb/#dom-usbouttransferresult-byteswritten
+    fn BytesWritten(&self) -> u32 {
+        self.bytes_written
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbouttransferresult-status
+    fn Status(&self) -> USBTransferStatus {
+        self.status
+    }
+}
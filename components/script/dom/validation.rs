@@ -2,4 +2,108 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-pub trait Validatable {}
+use dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::str::DOMString;
+use dom::element::Element;
+use dom::event::{EventBubbles, EventCancelable};
+use dom::eventtarget::EventTarget;
+use dom::htmldatalistelement::HTMLDataListElement;
+use dom::node::{Node, window_from_node};
+use dom::validitystate::{CUSTOM_ERROR, ValidationFlags, ValidityState};
+
+// https://html.spec.whatwg.org/multipage/#the-constraint-validation-api
+pub trait Validatable {
+    fn as_element(&self) -> &Element;
+
+    // Where the element keeps its `ValidityState`, which is only created once it is asked for.
+    fn validity_state_slot(&self) -> &MutNullableHeap<JS<ValidityState>>;
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validity
+    fn validity_state(&self) -> Root<ValidityState> {
+        self.validity_state_slot().or_init(|| {
+            let window = window_from_node(self.as_element());
+            ValidityState::new(window.r(), self.as_element())
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#candidate-for-constraint-validation
+    fn is_instance_validatable(&self) -> bool;
+
+    // Returns the states out of `validate_flags` that the element suffers from, other than
+    // the custom error, which is kept by the element's `ValidityState`.
+    fn perform_validation(&self, _validate_flags: ValidationFlags) -> ValidationFlags {
+        ValidationFlags::empty()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#validity-states
+    fn validate(&self, validate_flags: ValidationFlags) -> ValidationFlags {
+        let mut failed_flags = self.perform_validation(validate_flags);
+        if validate_flags.contains(CUSTOM_ERROR) {
+            let has_custom_error = self.validity_state_slot().get().map_or(false, |validity| {
+                !validity.custom_error_message().is_empty()
+            });
+            if has_custom_error {
+                failed_flags.insert(CUSTOM_ERROR);
+            }
+        }
+        failed_flags
+    }
+
+    // https://html.spec.whatwg.org/multipage/#concept-fv-valid
+    fn satisfies_constraints(&self) -> bool {
+        self.validate(ValidationFlags::all()).is_empty()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-willvalidate
+    fn will_validate(&self) -> bool {
+        self.is_instance_validatable()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#check-validity-steps
+    fn check_validity(&self) -> bool {
+        if !self.is_instance_validatable() || self.satisfies_constraints() {
+            return true;
+        }
+        self.as_element().upcast::<EventTarget>()
+            .fire_event("invalid", EventBubbles::DoesNotBubble, EventCancelable::Cancelable);
+        false
+    }
+
+    // https://html.spec.whatwg.org/multipage/#report-validity-steps
+    fn report_validity(&self) -> bool {
+        if !self.is_instance_validatable() || self.satisfies_constraints() {
+            return true;
+        }
+        let event = self.as_element().upcast::<EventTarget>()
+            .fire_event("invalid", EventBubbles::DoesNotBubble, EventCancelable::Cancelable);
+        if !event.DefaultPrevented() {
+            // TODO: Show the problem to the user, through the embedder.
+            warn!("Validation error: {}", self.validity_state().validation_message());
+        }
+        false
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    fn validation_message(&self) -> DOMString {
+        if !self.is_instance_validatable() {
+            return DOMString::new();
+        }
+        self.validity_state().validation_message()
+    }
+
+    // Keeps the element's :valid and :invalid states in sync with its constraints.
+    fn update_validity_states(&self) {
+        let candidate = self.is_instance_validatable();
+        let valid = !candidate || self.satisfies_constraints();
+        let el = self.as_element();
+        el.set_valid_state(candidate && valid);
+        el.set_invalid_state(candidate && !valid);
+    }
+}
+
+// https://html.spec.whatwg.org/multipage/#the-datalist-element:barred-from-constraint-validation
+pub fn is_barred_by_datalist_ancestor(element: &Element) -> bool {
+    element.upcast::<Node>().ancestors().any(|ancestor| ancestor.is::<HTMLDataListElement>())
+}
@@ -2,29 +2,31 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::ValidityStateBinding;
 use dom::bindings::codegen::Bindings::ValidityStateBinding::ValidityStateMethods;
 use dom::bindings::global::GlobalRef;
 use dom::bindings::js::{JS, Root};
 use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
 use dom::element::Element;
 use dom::window::Window;
+use std::cell::Ref;
 
 // https://html.spec.whatwg.org/multipage/#validity-states
-#[derive_JSTraceable]
-#[derive_HeapSizeOf]
-pub enum ValidityStatus {
-    ValueMissing,
-    TypeMismatch,
-    PatternMismatch,
-    TooLong,
-    TooShort,
-    RangeUnderflow,
-    RangeOverflow,
-    StepMismatch,
-    BadInput,
-    CustomError,
-    Valid
+bitflags! {
+    pub flags ValidationFlags: u32 {
+        const VALUE_MISSING    = 0x001,
+        const TYPE_MISMATCH    = 0x002,
+        const PATTERN_MISMATCH = 0x004,
+        const TOO_LONG         = 0x008,
+        const TOO_SHORT        = 0x010,
+        const RANGE_UNDERFLOW  = 0x020,
+        const RANGE_OVERFLOW   = 0x040,
+        const STEP_MISMATCH    = 0x080,
+        const BAD_INPUT        = 0x100,
+        const CUSTOM_ERROR     = 0x200,
+    }
 }
 
 // https://html.spec.whatwg.org/multipage/#validitystate
@@ -32,16 +34,16 @@ pub enum ValidityStatus {
 pub struct ValidityState {
     reflector_: Reflector,
     element: JS<Element>,
-    state: ValidityStatus
+    // https://html.spec.whatwg.org/multipage/#custom-validity-error-message
+    custom_error_message: DOMRefCell<DOMString>,
 }
 
-
 impl ValidityState {
     fn new_inherited(element: &Element) -> ValidityState {
         ValidityState {
             reflector_: Reflector::new(),
             element: JS::from_ref(element),
-            state: ValidityStatus::Valid
+            custom_error_message: DOMRefCell::new(DOMString::new()),
         }
     }
 
@@ -50,61 +52,109 @@ impl ValidityState {
                            GlobalRef::Window(window),
                            ValidityStateBinding::Wrap)
     }
+
+    pub fn custom_error_message(&self) -> Ref<DOMString> {
+        self.custom_error_message.borrow()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-setcustomvalidity
+    pub fn set_custom_error_message(&self, error: DOMString) {
+        *self.custom_error_message.borrow_mut() = error;
+        if let Some(validatable) = self.element.as_maybe_validatable() {
+            validatable.update_validity_states();
+        }
+    }
+
+    // The validity states the element is in, out of those in `flags`.
+    fn invalid_flags(&self, flags: ValidationFlags) -> ValidationFlags {
+        self.element.as_maybe_validatable()
+            .map_or(ValidationFlags::empty(), |validatable| validatable.validate(flags))
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-cva-validationmessage
+    pub fn validation_message(&self) -> DOMString {
+        let flags = self.invalid_flags(ValidationFlags::all());
+        if flags.contains(CUSTOM_ERROR) {
+            return self.custom_error_message.borrow().clone();
+        }
+        let message = if flags.contains(VALUE_MISSING) {
+            "Please fill out this field."
+        } else if flags.contains(TYPE_MISMATCH) {
+            "Please enter a value of the expected type."
+        } else if flags.contains(PATTERN_MISMATCH) {
+            "Please match the requested format."
+        } else if flags.contains(TOO_LONG) {
+            "Please shorten this text."
+        } else if flags.contains(TOO_SHORT) {
+            "Please lengthen this text."
+        } else if flags.contains(RANGE_UNDERFLOW) {
+            "Please select a value that is no earlier than the minimum."
+        } else if flags.contains(RANGE_OVERFLOW) {
+            "Please select a value that is no later than the maximum."
+        } else if flags.contains(STEP_MISMATCH) {
+            "Please select a valid value."
+        } else if flags.contains(BAD_INPUT) {
+            "Please enter a valid value."
+        } else {
+            ""
+        };
+        DOMString::from(message)
+    }
 }
 
 impl ValidityStateMethods for ValidityState {
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-valuemissing
     fn ValueMissing(&self) -> bool {
-        false
+        !self.invalid_flags(VALUE_MISSING).is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-typemismatch
     fn TypeMismatch(&self) -> bool {
-        false
+        !self.invalid_flags(TYPE_MISMATCH).is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-patternmismatch
     fn PatternMismatch(&self) -> bool {
-        false
+        !self.invalid_flags(PATTERN_MISMATCH).is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-toolong
     fn TooLong(&self) -> bool {
-        false
+        !self.invalid_flags(TOO_LONG).is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-tooshort
     fn TooShort(&self) -> bool {
-        false
+        !self.invalid_flags(TOO_SHORT).is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-rangeunderflow
     fn RangeUnderflow(&self) -> bool {
-        false
+        !self.invalid_flags(RANGE_UNDERFLOW).is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-rangeoverflow
     fn RangeOverflow(&self) -> bool {
-        false
+        !self.invalid_flags(RANGE_OVERFLOW).is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-stepmismatch
     fn StepMismatch(&self) -> bool {
-        false
+        !self.invalid_flags(STEP_MISMATCH).is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-badinput
     fn BadInput(&self) -> bool {
-        false
+        !self.invalid_flags(BAD_INPUT).is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-customerror
     fn CustomError(&self) -> bool {
-        false
+        !self.invalid_flags(CUSTOM_ERROR).is_empty()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-validitystate-valid
     fn Valid(&self) -> bool {
-        false
+        self.invalid_flags(ValidationFlags::all()).is_empty()
     }
 }
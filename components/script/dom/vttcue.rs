@@ -0,0 +1,147 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::VTTCueBinding::{self, AlignSetting, VTTCueMethods};
+use dom::bindings::error::{Error, ErrorResult, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::Root;
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::bindings::str::DOMString;
+use dom::texttrackcue::TextTrackCue;
+use dom::window::Window;
+use media::webvtt::{self, WebVttCue, cue_text_to_plain_text};
+use script_layout_interface::{CueAlign, CueSettings, ShownCue};
+use std::cell::Cell;
+
+// https://w3c.github.io/webvtt/#vttcue
+#[dom_struct]
+pub struct VTTCue {
+    texttrackcue: TextTrackCue,
+    /// The cue text, with its markup.
+    text: DOMRefCell<DOMString>,
+    /// Where the cue is shown over the video.
+    settings: Cell<CueSettings>,
+}
+
+impl VTTCue {
+    fn new_inherited(id: DOMString,
+                     start_time: f64,
+                     end_time: f64,
+                     text: DOMString,
+                     settings: CueSettings)
+                     -> VTTCue {
+        VTTCue {
+            texttrackcue: TextTrackCue::new_inherited(id, start_time, end_time),
+            text: DOMRefCell::new(text),
+            settings: Cell::new(settings),
+        }
+    }
+
+    fn new(window: &Window,
+           id: DOMString,
+           start_time: f64,
+           end_time: f64,
+           text: DOMString,
+           settings: CueSettings)
+           -> Root<VTTCue> {
+        reflect_dom_object(box VTTCue::new_inherited(id, start_time, end_time, text, settings),
+                           GlobalRef::Window(window),
+                           VTTCueBinding::Wrap)
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-vttcue
+    pub fn Constructor(global: GlobalRef,
+                       start_time: Finite<f64>,
+                       end_time: Finite<f64>,
+                       text: DOMString)
+                       -> Fallible<Root<VTTCue>> {
+        Ok(VTTCue::new(global.as_window(), DOMString::new(), *start_time, *end_time, text, CueSettings::default()))
+    }
+
+    /// Makes the cue of a WebVTT file that a `<track>` loaded.
+    pub fn from_webvtt_cue(window: &Window, cue: WebVttCue) -> Root<VTTCue> {
+        let settings = webvtt::parse_settings(&cue.settings);
+        VTTCue::new(window, DOMString::from(cue.id), cue.start_time, cue.end_time, DOMString::from(cue.text), settings)
+    }
+
+    /// The text the cue shows and where, for layout.
+    pub fn shown_cue(&self) -> ShownCue {
+        ShownCue {
+            text: cue_text_to_plain_text(&self.text.borrow()),
+            settings: self.settings.get(),
+        }
+    }
+
+    fn set_settings<F: FnOnce(&mut CueSettings)>(&self, f: F) {
+        let mut settings = self.settings.get();
+        f(&mut settings);
+        if settings != self.settings.get() {
+            self.settings.set(settings);
+            self.upcast::<TextTrackCue>().changed();
+        }
+    }
+}
+
+impl VTTCueMethods for VTTCue {
+    // https://w3c.github.io/webvtt/#dom-vttcue-text
+    fn Text(&self) -> DOMString {
+        self.text.borrow().clone()
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-text
+    fn SetText(&self, value: DOMString) {
+        *self.text.borrow_mut() = value;
+        self.upcast::<TextTrackCue>().changed();
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-snaptolines
+    fn SnapToLines(&self) -> bool {
+        self.settings.get().snap_to_lines
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-snaptolines
+    fn SetSnapToLines(&self, value: bool) {
+        self.set_settings(|settings| settings.snap_to_lines = value);
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-size
+    fn Size(&self) -> f64 {
+        self.settings.get().size
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-size
+    fn SetSize(&self, value: f64) -> ErrorResult {
+        if value < 0. || value > 100. {
+            return Err(Error::IndexSize);
+        }
+        self.set_settings(|settings| settings.size = value);
+        Ok(())
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-align
+    fn Align(&self) -> AlignSetting {
+        match self.settings.get().align {
+            CueAlign::Start => AlignSetting::Start,
+            CueAlign::Center => AlignSetting::Center,
+            CueAlign::End => AlignSetting::End,
+            CueAlign::Left => AlignSetting::Left,
+            CueAlign::Right => AlignSetting::Right,
+        }
+    }
+
+    // https://w3c.github.io/webvtt/#dom-vttcue-align
+    fn SetAlign(&self, value: AlignSetting) {
+        let align = match value {
+            AlignSetting::Start => CueAlign::Start,
+            AlignSetting::Center => CueAlign::Center,
+            AlignSetting::End => CueAlign::End,
+            AlignSetting::Left => CueAlign::Left,
+            AlignSetting::Right => CueAlign::Right,
+        };
+        self.set_settings(|settings| settings.align = align);
+    }
+}
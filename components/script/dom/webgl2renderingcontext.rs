@@ -0,0 +1,515 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl2.idl
+use canvas_traits::CanvasMsg;
+use canvas_traits::webgl2::{WebGL2Command, WebGLVersion};
+use dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding;
+use dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants;
+use dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextMethods;
+use dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants1;
+use dom::bindings::conversions::{array_buffer_view_data_checked, array_buffer_view_to_vec};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::htmlcanvaselement::HTMLCanvasElement;
+use dom::webgl_validations::types::{TexDataType, TexFormat};
+use dom::webglbuffer::WebGLBuffer;
+use dom::webglprogram::WebGLProgram;
+use dom::webglrenderingcontext::WebGLRenderingContext;
+use dom::webglsampler::WebGLSampler;
+use dom::webgltransformfeedback::WebGLTransformFeedback;
+use dom::webglvertexarrayobject::WebGLVertexArrayObject;
+use euclid::size::Size2D;
+use ipc_channel::ipc;
+use js::jsapi::{JSContext, JSObject};
+use offscreen_gl_context::GLContextAttributes;
+use std::cell::Cell;
+use webrender_traits::WebGLError;
+use webrender_traits::WebGLError::*;
+
+// The OpenGL ES 3.0 limits on the indexed binding points. The GL backend may support more, but
+// these are what every WebGL 2.0 implementation guarantees.
+const MAX_UNIFORM_BUFFER_BINDINGS: u32 = 24;
+const MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS: u32 = 4;
+const MAX_COMBINED_TEXTURE_IMAGE_UNITS: u32 = 32;
+
+#[dom_struct]
+pub struct WebGL2RenderingContext {
+    base: WebGLRenderingContext,
+    bound_transform_feedback: MutNullableHeap<JS<WebGLTransformFeedback>>,
+    transform_feedback_active: Cell<bool>,
+    transform_feedback_paused: Cell<bool>,
+}
+
+impl WebGL2RenderingContext {
+    #[allow(unrooted_must_root)]
+    pub fn new(global: GlobalRef, canvas: &HTMLCanvasElement, size: Size2D<i32>, attrs: GLContextAttributes)
+               -> Option<Root<WebGL2RenderingContext>> {
+        match WebGLRenderingContext::new_inherited(global, canvas, size, attrs, WebGLVersion::WebGL2) {
+            Ok(base) => {
+                let ctx = WebGL2RenderingContext {
+                    base: base,
+                    bound_transform_feedback: MutNullableHeap::new(None),
+                    transform_feedback_active: Cell::new(false),
+                    transform_feedback_paused: Cell::new(false),
+                };
+                Some(reflect_dom_object(box ctx, global, WebGL2RenderingContextBinding::Wrap))
+            }
+            Err(msg) => {
+                WebGLRenderingContext::fire_context_creation_error(global, canvas, msg);
+                None
+            }
+        }
+    }
+
+    fn base(&self) -> &WebGLRenderingContext {
+        self.upcast::<WebGLRenderingContext>()
+    }
+
+    fn webgl_error(&self, err: WebGLError) {
+        self.base().webgl_error(err)
+    }
+
+    fn send_command(&self, command: WebGL2Command) {
+        self.base().ipc_renderer().send(CanvasMsg::WebGL2(command)).unwrap();
+    }
+
+    /// Validates the arguments shared by `texImage3D` and `texSubImage3D`, returning the number
+    /// of bytes the pixel data should have.
+    #[allow(unsafe_code)]
+    fn validate_tex_image_3d(&self,
+                             target: u32,
+                             level: i32,
+                             width: i32,
+                             height: i32,
+                             depth: i32,
+                             format: u32,
+                             data_type: u32,
+                             data: Option<*mut JSObject>)
+                             -> Result<usize, ()> {
+        match target {
+            constants::TEXTURE_3D | constants::TEXTURE_2D_ARRAY => {}
+            _ => {
+                self.webgl_error(InvalidEnum);
+                return Err(());
+            }
+        }
+
+        if self.base().bound_texture(target).is_none() {
+            self.webgl_error(InvalidOperation);
+            return Err(());
+        }
+
+        if level < 0 || width < 0 || height < 0 || depth < 0 {
+            self.webgl_error(InvalidValue);
+            return Err(());
+        }
+
+        let (format, data_type) = match (TexFormat::from_gl_constant(format),
+                                         TexDataType::from_gl_constant(data_type)) {
+            (Some(format), Some(data_type)) => (format, data_type),
+            _ => {
+                self.webgl_error(InvalidEnum);
+                return Err(());
+            }
+        };
+
+        // As with texImage2D, the type of the array must match the size of
+        // the elements of `data_type`.
+        let received_size = match data {
+            Some(data) if unsafe { array_buffer_view_data_checked::<f32>(data).is_some() } => 4,
+            Some(data) if unsafe { array_buffer_view_data_checked::<u16>(data).is_some() } => 2,
+            Some(data) if unsafe { array_buffer_view_data_checked::<u8>(data).is_some() } => 1,
+            Some(_) => 0,
+            None => data_type.element_size(),
+        };
+        if received_size != data_type.element_size() {
+            self.webgl_error(InvalidOperation);
+            return Err(());
+        }
+
+        let texels = width as usize * height as usize * depth as usize;
+        let expected_byte_length = texels * data_type.element_size() as usize *
+                                   format.components() as usize /
+                                   data_type.components_per_element() as usize;
+        Ok(expected_byte_length)
+    }
+
+    /// Returns the pixels in `data`, or a zeroed buffer of `byte_length` bytes when there are
+    /// none, as required by the WebGL 2.0 specification.
+    #[allow(unsafe_code)]
+    fn pixels_or_zeroes(&self, data: Option<*mut JSObject>, byte_length: usize) -> Result<Vec<u8>, ()> {
+        let pixels = match data {
+            Some(data) => unsafe {
+                array_buffer_view_to_vec::<u8>(data)
+                    .expect("Can't reach here without being an ArrayBufferView!")
+            },
+            None => vec![0u8; byte_length],
+        };
+
+        if pixels.len() != byte_length {
+            self.webgl_error(InvalidOperation);
+            return Err(());
+        }
+        Ok(pixels)
+    }
+
+    fn validate_indexed_buffer_target(&self, target: u32, index: u32) -> bool {
+        let max_bindings = match target {
+            constants::UNIFORM_BUFFER => MAX_UNIFORM_BUFFER_BINDINGS,
+            constants::TRANSFORM_FEEDBACK_BUFFER => MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS,
+            _ => {
+                self.webgl_error(InvalidEnum);
+                return false;
+            }
+        };
+
+        if index >= max_bindings {
+            self.webgl_error(InvalidValue);
+            return false;
+        }
+        true
+    }
+
+    fn validate_sampler_parameter(&self, name: u32) -> bool {
+        match name {
+            constants1::TEXTURE_MAG_FILTER |
+            constants1::TEXTURE_MIN_FILTER |
+            constants1::TEXTURE_WRAP_S |
+            constants1::TEXTURE_WRAP_T |
+            constants::TEXTURE_WRAP_R |
+            constants::TEXTURE_MIN_LOD |
+            constants::TEXTURE_MAX_LOD |
+            constants::TEXTURE_COMPARE_MODE |
+            constants::TEXTURE_COMPARE_FUNC => true,
+            _ => {
+                self.webgl_error(InvalidEnum);
+                false
+            }
+        }
+    }
+}
+
+impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn TexImage3D(&self,
+                  _cx: *mut JSContext,
+                  target: u32,
+                  level: i32,
+                  internal_format: i32,
+                  width: i32,
+                  height: i32,
+                  depth: i32,
+                  border: i32,
+                  format: u32,
+                  data_type: u32,
+                  data: Option<*mut JSObject>) {
+        if border != 0 {
+            return self.webgl_error(InvalidValue);
+        }
+
+        let byte_length = match self.validate_tex_image_3d(target, level, width, height, depth,
+                                                          format, data_type, data) {
+            Ok(result) => result,
+            Err(_) => return,
+        };
+
+        let pixels = match self.pixels_or_zeroes(data, byte_length) {
+            Ok(pixels) => pixels,
+            Err(_) => return,
+        };
+
+        self.send_command(WebGL2Command::TexImage3D(target, level, internal_format, width, height,
+                                                    depth, format, data_type, pixels));
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.6
+    fn TexSubImage3D(&self,
+                     _cx: *mut JSContext,
+                     target: u32,
+                     level: i32,
+                     xoffset: i32,
+                     yoffset: i32,
+                     zoffset: i32,
+                     width: i32,
+                     height: i32,
+                     depth: i32,
+                     format: u32,
+                     data_type: u32,
+                     data: Option<*mut JSObject>) {
+        if xoffset < 0 || yoffset < 0 || zoffset < 0 {
+            return self.webgl_error(InvalidValue);
+        }
+
+        let byte_length = match self.validate_tex_image_3d(target, level, width, height, depth,
+                                                          format, data_type, data) {
+            Ok(result) => result,
+            Err(_) => return,
+        };
+
+        // Unlike texImage3D, there is nothing to initialize here.
+        if data.is_none() {
+            return self.webgl_error(InvalidValue);
+        }
+
+        let pixels = match self.pixels_or_zeroes(data, byte_length) {
+            Ok(pixels) => pixels,
+            Err(_) => return,
+        };
+
+        self.send_command(WebGL2Command::TexSubImage3D(target, level, xoffset, yoffset, zoffset,
+                                                       width, height, depth, format, data_type,
+                                                       pixels));
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn CreateSampler(&self) -> Option<Root<WebGLSampler>> {
+        WebGLSampler::maybe_new(self.global().r(), self.base().ipc_renderer())
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn DeleteSampler(&self, sampler: Option<&WebGLSampler>) {
+        if let Some(sampler) = sampler {
+            sampler.delete()
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn IsSampler(&self, sampler: Option<&WebGLSampler>) -> bool {
+        sampler.map_or(false, |s| !s.is_deleted())
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn BindSampler(&self, unit: u32, sampler: Option<&WebGLSampler>) {
+        if unit >= MAX_COMBINED_TEXTURE_IMAGE_UNITS {
+            return self.webgl_error(InvalidValue);
+        }
+
+        match sampler {
+            Some(sampler) if sampler.is_deleted() => self.webgl_error(InvalidOperation),
+            Some(sampler) => sampler.bind(unit),
+            None => self.send_command(WebGL2Command::BindSampler(unit, None)),
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn SamplerParameteri(&self, sampler: Option<&WebGLSampler>, name: u32, value: i32) {
+        if let Some(sampler) = sampler {
+            if self.validate_sampler_parameter(name) {
+                sampler.parameter_i(name, value)
+            }
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13
+    fn SamplerParameterf(&self, sampler: Option<&WebGLSampler>, name: u32, value: f32) {
+        if let Some(sampler) = sampler {
+            if self.validate_sampler_parameter(name) {
+                sampler.parameter_f(name, value)
+            }
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn CreateTransformFeedback(&self) -> Option<Root<WebGLTransformFeedback>> {
+        WebGLTransformFeedback::maybe_new(self.global().r(), self.base().ipc_renderer())
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn DeleteTransformFeedback(&self, tf: Option<&WebGLTransformFeedback>) {
+        if let Some(tf) = tf {
+            if let Some(bound) = self.bound_transform_feedback.get() {
+                if bound.id() == tf.id() {
+                    // The active transform feedback object can't be deleted.
+                    if self.transform_feedback_active.get() {
+                        return self.webgl_error(InvalidOperation);
+                    }
+                    self.bound_transform_feedback.set(None);
+                }
+            }
+            tf.delete()
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn IsTransformFeedback(&self, tf: Option<&WebGLTransformFeedback>) -> bool {
+        tf.map_or(false, |tf| !tf.is_deleted() && tf.ever_bound())
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn BindTransformFeedback(&self, target: u32, tf: Option<&WebGLTransformFeedback>) {
+        if target != constants::TRANSFORM_FEEDBACK {
+            return self.webgl_error(InvalidEnum);
+        }
+
+        // The bound transform feedback object can't change while it's active and not paused.
+        if self.transform_feedback_active.get() && !self.transform_feedback_paused.get() {
+            return self.webgl_error(InvalidOperation);
+        }
+
+        match tf {
+            Some(tf) if tf.is_deleted() => self.webgl_error(InvalidOperation),
+            Some(tf) => {
+                self.bound_transform_feedback.set(Some(tf));
+                tf.bind(target);
+            }
+            None => {
+                self.bound_transform_feedback.set(None);
+                self.send_command(WebGL2Command::BindTransformFeedback(target, None));
+            }
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn BeginTransformFeedback(&self, primitive_mode: u32) {
+        match primitive_mode {
+            constants1::POINTS | constants1::LINES | constants1::TRIANGLES => {}
+            _ => return self.webgl_error(InvalidEnum),
+        }
+
+        if self.transform_feedback_active.get() {
+            return self.webgl_error(InvalidOperation);
+        }
+
+        self.transform_feedback_active.set(true);
+        self.transform_feedback_paused.set(false);
+        self.send_command(WebGL2Command::BeginTransformFeedback(primitive_mode));
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn EndTransformFeedback(&self) {
+        if !self.transform_feedback_active.get() {
+            return self.webgl_error(InvalidOperation);
+        }
+
+        self.transform_feedback_active.set(false);
+        self.transform_feedback_paused.set(false);
+        self.send_command(WebGL2Command::EndTransformFeedback);
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn TransformFeedbackVaryings(&self,
+                                 program: Option<&WebGLProgram>,
+                                 varyings: Vec<DOMString>,
+                                 buffer_mode: u32) {
+        let program = match program {
+            Some(program) if !program.is_deleted() => program,
+            Some(_) => return self.webgl_error(InvalidOperation),
+            None => return,
+        };
+
+        match buffer_mode {
+            constants::INTERLEAVED_ATTRIBS | constants::SEPARATE_ATTRIBS => {}
+            _ => return self.webgl_error(InvalidEnum),
+        }
+
+        let varyings = varyings.into_iter().map(String::from).collect();
+        self.send_command(WebGL2Command::TransformFeedbackVaryings(program.id(), varyings, buffer_mode));
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn PauseTransformFeedback(&self) {
+        if !self.transform_feedback_active.get() || self.transform_feedback_paused.get() {
+            return self.webgl_error(InvalidOperation);
+        }
+
+        self.transform_feedback_paused.set(true);
+        self.send_command(WebGL2Command::PauseTransformFeedback);
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15
+    fn ResumeTransformFeedback(&self) {
+        if !self.transform_feedback_active.get() || !self.transform_feedback_paused.get() {
+            return self.webgl_error(InvalidOperation);
+        }
+
+        self.transform_feedback_paused.set(false);
+        self.send_command(WebGL2Command::ResumeTransformFeedback);
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn BindBufferBase(&self, target: u32, index: u32, buffer: Option<&WebGLBuffer>) {
+        if !self.validate_indexed_buffer_target(target, index) {
+            return;
+        }
+
+        match buffer {
+            Some(buffer) if buffer.is_deleted() => self.webgl_error(InvalidOperation),
+            buffer => {
+                let id = buffer.map(|buffer| buffer.id());
+                self.send_command(WebGL2Command::BindBufferBase(target, index, id));
+            }
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn BindBufferRange(&self, target: u32, index: u32, buffer: Option<&WebGLBuffer>, offset: i64, size: i64) {
+        if !self.validate_indexed_buffer_target(target, index) {
+            return;
+        }
+
+        if offset < 0 || (buffer.is_some() && size <= 0) {
+            return self.webgl_error(InvalidValue);
+        }
+
+        match buffer {
+            Some(buffer) if buffer.is_deleted() => self.webgl_error(InvalidOperation),
+            buffer => {
+                let id = buffer.map(|buffer| buffer.id());
+                let cmd = WebGL2Command::BindBufferRange(target, index, id, offset as isize, size as isize);
+                self.send_command(cmd);
+            }
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn GetUniformBlockIndex(&self, program: Option<&WebGLProgram>, name: DOMString) -> u32 {
+        let program = match program {
+            Some(program) if !program.is_deleted() => program,
+            Some(_) => {
+                self.webgl_error(InvalidOperation);
+                return constants::INVALID_INDEX;
+            }
+            None => return constants::INVALID_INDEX,
+        };
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.send_command(WebGL2Command::GetUniformBlockIndex(program.id(), String::from(name), sender));
+        receiver.recv().unwrap()
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16
+    fn UniformBlockBinding(&self, program: Option<&WebGLProgram>, index: u32, binding: u32) {
+        match program {
+            Some(program) if program.is_deleted() => self.webgl_error(InvalidOperation),
+            Some(program) => {
+                self.send_command(WebGL2Command::UniformBlockBinding(program.id(), index, binding))
+            }
+            None => {}
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.17
+    fn CreateVertexArray(&self) -> Option<Root<WebGLVertexArrayObject>> {
+        self.base().create_vertex_array()
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.17
+    fn DeleteVertexArray(&self, vertex_array: Option<&WebGLVertexArrayObject>) {
+        self.base().delete_vertex_array(vertex_array)
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.17
+    fn IsVertexArray(&self, vertex_array: Option<&WebGLVertexArrayObject>) -> bool {
+        self.base().is_vertex_array(vertex_array)
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.17
+    fn BindVertexArray(&self, vertex_array: Option<&WebGLVertexArrayObject>) {
+        self.base().bind_vertex_array(vertex_array)
+    }
+}
@@ -0,0 +1,125 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.14
+use canvas_traits::webgl2::WebGLVersion;
+use canvas_traits::webgl_extensions::WebGLExtensionSupport;
+use core::nonzero::NonZero;
+use dom::angleinstancedarrays::ANGLEInstancedArrays;
+use dom::bindings::js::{JS, MutNullableHeap};
+use dom::bindings::reflector::Reflectable;
+use dom::exttexturefilteranisotropic::EXTTextureFilterAnisotropic;
+use dom::oeselementindexuint::OESElementIndexUint;
+use dom::oestexturefloat::OESTextureFloat;
+use dom::oesvertexarrayobject::OESVertexArrayObject;
+use dom::webglrenderingcontext::WebGLRenderingContext;
+use js::jsapi::JSObject;
+use std::ascii::AsciiExt;
+
+const EXTENSION_NAMES: &'static [&'static str] = &[
+    "ANGLE_instanced_arrays",
+    "EXT_texture_filter_anisotropic",
+    "OES_element_index_uint",
+    "OES_texture_float",
+    "OES_vertex_array_object",
+];
+
+/// The extensions of a WebGL context. An extension is supported when the GL driver behind the
+/// WebGL thread can back it, and enabled once content has asked for it with `getExtension`.
+#[must_root]
+#[derive(JSTraceable, HeapSizeOf)]
+pub struct WebGLExtensions {
+    support: WebGLExtensionSupport,
+    version: WebGLVersion,
+    instanced_arrays: MutNullableHeap<JS<ANGLEInstancedArrays>>,
+    texture_filter_anisotropic: MutNullableHeap<JS<EXTTextureFilterAnisotropic>>,
+    element_index_uint: MutNullableHeap<JS<OESElementIndexUint>>,
+    texture_float: MutNullableHeap<JS<OESTextureFloat>>,
+    vertex_array_object: MutNullableHeap<JS<OESVertexArrayObject>>,
+}
+
+impl WebGLExtensions {
+    pub fn new(support: WebGLExtensionSupport, version: WebGLVersion) -> WebGLExtensions {
+        WebGLExtensions {
+            support: support,
+            version: version,
+            instanced_arrays: MutNullableHeap::new(None),
+            texture_filter_anisotropic: MutNullableHeap::new(None),
+            element_index_uint: MutNullableHeap::new(None),
+            texture_float: MutNullableHeap::new(None),
+            vertex_array_object: MutNullableHeap::new(None),
+        }
+    }
+
+    fn is_supported(&self, name: &str) -> bool {
+        // Everything but anisotropic filtering is part of WebGL 2.0 itself.
+        let is_webgl1 = self.version == WebGLVersion::WebGL1;
+        match name {
+            "ANGLE_instanced_arrays" => is_webgl1 && self.support.instanced_arrays,
+            "EXT_texture_filter_anisotropic" => self.support.max_texture_anisotropy.is_some(),
+            "OES_element_index_uint" => is_webgl1 && self.support.element_index_uint,
+            "OES_texture_float" => is_webgl1 && self.support.texture_float,
+            "OES_vertex_array_object" => is_webgl1 && self.support.vertex_array_object,
+            _ => false,
+        }
+    }
+
+    pub fn supported_names(&self) -> Vec<&'static str> {
+        EXTENSION_NAMES.iter().cloned().filter(|name| self.is_supported(name)).collect()
+    }
+
+    /// Enables the extension called `name`, which is matched case-insensitively, and returns its
+    /// object. Asking for the same extension again returns the same object.
+    #[allow(unsafe_code)]
+    pub fn get_or_init(&self, name: &str, ctx: &WebGLRenderingContext) -> Option<NonZero<*mut JSObject>> {
+        let name = match EXTENSION_NAMES.iter().find(|known| known.eq_ignore_ascii_case(name)) {
+            Some(name) if self.is_supported(name) => *name,
+            _ => return None,
+        };
+
+        let global = ctx.global();
+        let global = global.r();
+        let object = match name {
+            "ANGLE_instanced_arrays" => {
+                self.instanced_arrays.or_init(|| ANGLEInstancedArrays::new(global, ctx))
+                    .reflector().get_jsobject().get()
+            }
+            "EXT_texture_filter_anisotropic" => {
+                self.texture_filter_anisotropic.or_init(|| EXTTextureFilterAnisotropic::new(global))
+                    .reflector().get_jsobject().get()
+            }
+            "OES_element_index_uint" => {
+                self.element_index_uint.or_init(|| OESElementIndexUint::new(global))
+                    .reflector().get_jsobject().get()
+            }
+            "OES_texture_float" => {
+                self.texture_float.or_init(|| OESTextureFloat::new(global))
+                    .reflector().get_jsobject().get()
+            }
+            "OES_vertex_array_object" => {
+                self.vertex_array_object.or_init(|| OESVertexArrayObject::new(global, ctx))
+                    .reflector().get_jsobject().get()
+            }
+            _ => unreachable!(),
+        };
+        Some(unsafe { NonZero::new(object) })
+    }
+
+    /// Returns `MAX_TEXTURE_MAX_ANISOTROPY_EXT` if `EXT_texture_filter_anisotropic` is enabled.
+    pub fn max_texture_anisotropy(&self) -> Option<f32> {
+        self.texture_filter_anisotropic.get().and(self.support.max_texture_anisotropy)
+    }
+
+    pub fn is_element_index_uint_enabled(&self) -> bool {
+        self.version == WebGLVersion::WebGL2 || self.element_index_uint.get().is_some()
+    }
+
+    pub fn is_texture_float_enabled(&self) -> bool {
+        self.texture_float.get().is_some()
+    }
+
+    pub fn is_vertex_array_object_enabled(&self) -> bool {
+        self.version == WebGLVersion::WebGL2 || self.vertex_array_object.get().is_some()
+    }
+}
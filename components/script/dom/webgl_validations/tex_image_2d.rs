@@ -304,6 +304,12 @@ impl<'a> WebGLValidator for TexImage2DValidator<'a> {
             },
         };
 
+        // FLOAT is only accepted once OES_texture_float has been enabled.
+        if data_type == TexDataType::Float && !context.extensions().is_texture_float_enabled() {
+            context.webgl_error(InvalidEnum);
+            return Err(TexImageValidationError::InvalidDataType);
+        }
+
         let format = match TexFormat::from_gl_constant(self.format) {
             Some(format) => format,
             None => {
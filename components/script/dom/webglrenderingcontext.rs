@@ -3,7 +3,10 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use canvas_traits::{CanvasCommonMsg, CanvasMsg, byte_swap};
+use canvas_traits::webgl2::{WebGL2Command, WebGLVersion};
 use core::nonzero::NonZero;
+use dom::bindings::codegen::Bindings::EXTTextureFilterAnisotropicBinding::EXTTextureFilterAnisotropicConstants;
+use dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants2;
 use dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::{self, WebGLContextAttributes};
 use dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants;
 use dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextMethods;
@@ -19,6 +22,7 @@ use dom::event::{Event, EventBubbles, EventCancelable};
 use dom::htmlcanvaselement::HTMLCanvasElement;
 use dom::htmlcanvaselement::utils as canvas_utils;
 use dom::node::{Node, NodeDamage, window_from_node};
+use dom::webgl_extensions::WebGLExtensions;
 use dom::webgl_validations::WebGLValidator;
 use dom::webgl_validations::tex_image_2d::{CommonTexImage2DValidator, CommonTexImage2DValidatorResult};
 use dom::webgl_validations::tex_image_2d::{TexImage2DValidator, TexImage2DValidatorResult};
@@ -32,6 +36,7 @@ use dom::webglrenderbuffer::WebGLRenderbuffer;
 use dom::webglshader::WebGLShader;
 use dom::webgltexture::{TexParameterValue, WebGLTexture};
 use dom::webgluniformlocation::WebGLUniformLocation;
+use dom::webglvertexarrayobject::WebGLVertexArrayObject;
 use euclid::size::Size2D;
 use ipc_channel::ipc::{self, IpcSender};
 use js::jsapi::{JSContext, JSObject, JS_GetArrayBufferViewType, Type};
@@ -41,10 +46,27 @@ use net_traits::image_cache_thread::ImageResponse;
 use offscreen_gl_context::{GLContextAttributes, GLLimits};
 use script_traits::ScriptMsg as ConstellationMsg;
 use std::cell::Cell;
+use std::mem;
 use webrender_traits::{WebGLCommand, WebGLError, WebGLFramebufferBindingRequest, WebGLParameter};
 use webrender_traits::WebGLError::*;
 
 type ImagePixelResult = Result<(Vec<u8>, Size2D<i32>), ()>;
+
+// Image sources always hold 8-bit components, which have to be normalized when
+// they are uploaded as an OES_texture_float texture.
+#[allow(unsafe_code)]
+fn image_pixels_as_data_type(pixels: Vec<u8>, data_type: TexDataType) -> Vec<u8> {
+    if data_type != TexDataType::Float {
+        return pixels;
+    }
+
+    let mut floats = Vec::with_capacity(pixels.len() * mem::size_of::<f32>());
+    for component in pixels {
+        let bytes: [u8; 4] = unsafe { mem::transmute(component as f32 / 255.) };
+        floats.extend_from_slice(&bytes);
+    }
+    floats
+}
 pub const MAX_UNIFORM_AND_ATTRIBUTE_LEN: usize = 256;
 
 macro_rules! handle_potential_webgl_error {
@@ -111,6 +133,8 @@ pub struct WebGLRenderingContext {
     ipc_renderer: IpcSender<CanvasMsg>,
     #[ignore_heap_size_of = "Defined in offscreen_gl_context"]
     limits: GLLimits,
+    webgl_version: WebGLVersion,
+    extensions: WebGLExtensions,
     canvas: JS<HTMLCanvasElement>,
     #[ignore_heap_size_of = "Defined in webrender_traits"]
     last_error: Cell<Option<WebGLError>>,
@@ -121,28 +145,38 @@ pub struct WebGLRenderingContext {
     bound_texture_cube_map: MutNullableHeap<JS<WebGLTexture>>,
     bound_buffer_array: MutNullableHeap<JS<WebGLBuffer>>,
     bound_buffer_element_array: MutNullableHeap<JS<WebGLBuffer>>,
+    /// The WebGL 2.0 bindings, which can only be set when `webgl_version` is `WebGL2`.
+    bound_texture_3d: MutNullableHeap<JS<WebGLTexture>>,
+    bound_texture_2d_array: MutNullableHeap<JS<WebGLTexture>>,
+    bound_buffer_uniform: MutNullableHeap<JS<WebGLBuffer>>,
+    bound_buffer_transform_feedback: MutNullableHeap<JS<WebGLBuffer>>,
+    /// Set by WebGL 2.0 contexts and by OES_vertex_array_object.
+    bound_vertex_array: MutNullableHeap<JS<WebGLVertexArrayObject>>,
     current_program: MutNullableHeap<JS<WebGLProgram>>,
     #[ignore_heap_size_of = "Because it's small"]
     current_vertex_attrib_0: Cell<(f32, f32, f32, f32)>,
 }
 
 impl WebGLRenderingContext {
-    fn new_inherited(global: GlobalRef,
-                     canvas: &HTMLCanvasElement,
-                     size: Size2D<i32>,
-                     attrs: GLContextAttributes)
-                     -> Result<WebGLRenderingContext, String> {
+    pub fn new_inherited(global: GlobalRef,
+                         canvas: &HTMLCanvasElement,
+                         size: Size2D<i32>,
+                         attrs: GLContextAttributes,
+                         version: WebGLVersion)
+                         -> Result<WebGLRenderingContext, String> {
         let (sender, receiver) = ipc::channel().unwrap();
         let constellation_chan = global.constellation_chan();
-        constellation_chan.send(ConstellationMsg::CreateWebGLPaintThread(size, attrs, sender))
-                          .unwrap();
+        let msg = ConstellationMsg::CreateWebGLPaintThread(size, attrs, version, sender);
+        constellation_chan.send(msg).unwrap();
         let result = receiver.recv().unwrap();
 
-        result.map(|(ipc_renderer, context_limits)| {
+        result.map(|(ipc_renderer, context_limits, extension_support)| {
             WebGLRenderingContext {
                 reflector_: Reflector::new(),
                 ipc_renderer: ipc_renderer,
                 limits: context_limits,
+                webgl_version: version,
+                extensions: WebGLExtensions::new(extension_support, version),
                 canvas: JS::from_ref(canvas),
                 last_error: Cell::new(None),
                 texture_unpacking_settings: Cell::new(CONVERT_COLORSPACE),
@@ -151,6 +185,11 @@ impl WebGLRenderingContext {
                 bound_texture_cube_map: MutNullableHeap::new(None),
                 bound_buffer_array: MutNullableHeap::new(None),
                 bound_buffer_element_array: MutNullableHeap::new(None),
+                bound_texture_3d: MutNullableHeap::new(None),
+                bound_texture_2d_array: MutNullableHeap::new(None),
+                bound_buffer_uniform: MutNullableHeap::new(None),
+                bound_buffer_transform_feedback: MutNullableHeap::new(None),
+                bound_vertex_array: MutNullableHeap::new(None),
                 bound_renderbuffer: MutNullableHeap::new(None),
                 current_program: MutNullableHeap::new(None),
                 current_vertex_attrib_0: Cell::new((0f32, 0f32, 0f32, 1f32)),
@@ -161,26 +200,150 @@ impl WebGLRenderingContext {
     #[allow(unrooted_must_root)]
     pub fn new(global: GlobalRef, canvas: &HTMLCanvasElement, size: Size2D<i32>, attrs: GLContextAttributes)
                -> Option<Root<WebGLRenderingContext>> {
-        match WebGLRenderingContext::new_inherited(global, canvas, size, attrs) {
+        match WebGLRenderingContext::new_inherited(global, canvas, size, attrs, WebGLVersion::WebGL1) {
             Ok(ctx) => Some(reflect_dom_object(box ctx, global,
                                                WebGLRenderingContextBinding::Wrap)),
             Err(msg) => {
-                error!("Couldn't create WebGLRenderingContext: {}", msg);
-                let event = WebGLContextEvent::new(global,
-                                                   atom!("webglcontextcreationerror"),
-                                                   EventBubbles::DoesNotBubble,
-                                                   EventCancelable::Cancelable,
-                                                   DOMString::from(msg));
-                event.upcast::<Event>().fire(canvas.upcast());
+                WebGLRenderingContext::fire_context_creation_error(global, canvas, msg);
                 None
             }
         }
     }
 
+    // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.15.3
+    pub fn fire_context_creation_error(global: GlobalRef, canvas: &HTMLCanvasElement, msg: String) {
+        error!("Couldn't create WebGLRenderingContext: {}", msg);
+        let event = WebGLContextEvent::new(global,
+                                           atom!("webglcontextcreationerror"),
+                                           EventBubbles::DoesNotBubble,
+                                           EventCancelable::Cancelable,
+                                           DOMString::from(msg));
+        event.upcast::<Event>().fire(canvas.upcast());
+    }
+
     pub fn limits(&self) -> &GLLimits {
         &self.limits
     }
 
+    pub fn webgl_version(&self) -> WebGLVersion {
+        self.webgl_version
+    }
+
+    fn buffer_slot(&self, target: u32) -> Option<&MutNullableHeap<JS<WebGLBuffer>>> {
+        match target {
+            constants::ARRAY_BUFFER => Some(&self.bound_buffer_array),
+            constants::ELEMENT_ARRAY_BUFFER => Some(&self.bound_buffer_element_array),
+            constants2::UNIFORM_BUFFER if self.webgl_version == WebGLVersion::WebGL2 => {
+                Some(&self.bound_buffer_uniform)
+            }
+            constants2::TRANSFORM_FEEDBACK_BUFFER if self.webgl_version == WebGLVersion::WebGL2 => {
+                Some(&self.bound_buffer_transform_feedback)
+            }
+            _ => None,
+        }
+    }
+
+    fn texture_slot(&self, target: u32) -> Option<&MutNullableHeap<JS<WebGLTexture>>> {
+        match target {
+            constants::TEXTURE_2D => Some(&self.bound_texture_2d),
+            constants::TEXTURE_CUBE_MAP => Some(&self.bound_texture_cube_map),
+            constants2::TEXTURE_3D if self.webgl_version == WebGLVersion::WebGL2 => {
+                Some(&self.bound_texture_3d)
+            }
+            constants2::TEXTURE_2D_ARRAY if self.webgl_version == WebGLVersion::WebGL2 => {
+                Some(&self.bound_texture_2d_array)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the texture bound to `target`, which is one of the texture binding points (rather
+    /// than one of the cube map faces).
+    pub fn bound_texture(&self, target: u32) -> Option<Root<WebGLTexture>> {
+        self.texture_slot(target).and_then(|slot| slot.get())
+    }
+
+    pub fn extensions(&self) -> &WebGLExtensions {
+        &self.extensions
+    }
+
+    // https://www.khronos.org/registry/webgl/extensions/ANGLE_instanced_arrays/
+    pub fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, primcount: i32) {
+        if primcount < 0 {
+            return self.webgl_error(InvalidValue);
+        }
+
+        if self.validate_draw_arrays(mode, first, count) {
+            let cmd = WebGL2Command::DrawArraysInstanced(mode, first, count, primcount);
+            self.ipc_renderer.send(CanvasMsg::WebGL2(cmd)).unwrap();
+            self.mark_as_dirty();
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/extensions/ANGLE_instanced_arrays/
+    pub fn draw_elements_instanced(&self, mode: u32, count: i32, type_: u32, offset: i64, primcount: i32) {
+        if primcount < 0 {
+            return self.webgl_error(InvalidValue);
+        }
+
+        if self.validate_draw_elements(mode, count, type_, offset) {
+            let cmd = WebGL2Command::DrawElementsInstanced(mode, count, type_, offset, primcount);
+            self.ipc_renderer.send(CanvasMsg::WebGL2(cmd)).unwrap();
+            self.mark_as_dirty();
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/extensions/ANGLE_instanced_arrays/
+    pub fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        if index >= self.limits.max_vertex_attribs {
+            return self.webgl_error(InvalidValue);
+        }
+
+        let cmd = WebGL2Command::VertexAttribDivisor(index, divisor);
+        self.ipc_renderer.send(CanvasMsg::WebGL2(cmd)).unwrap();
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.17
+    pub fn create_vertex_array(&self) -> Option<Root<WebGLVertexArrayObject>> {
+        WebGLVertexArrayObject::maybe_new(self.global().r(), self.ipc_renderer.clone())
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.17
+    pub fn delete_vertex_array(&self, vertex_array: Option<&WebGLVertexArrayObject>) {
+        if let Some(vertex_array) = vertex_array {
+            // Deleting the bound vertex array object binds the default one again.
+            if let Some(bound) = self.bound_vertex_array.get() {
+                if bound.id() == vertex_array.id() {
+                    self.bound_vertex_array.set(None);
+                    let cmd = WebGL2Command::BindVertexArray(None);
+                    self.ipc_renderer.send(CanvasMsg::WebGL2(cmd)).unwrap();
+                }
+            }
+            vertex_array.delete()
+        }
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.17
+    pub fn is_vertex_array(&self, vertex_array: Option<&WebGLVertexArrayObject>) -> bool {
+        vertex_array.map_or(false, |va| !va.is_deleted() && va.ever_bound())
+    }
+
+    // https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.17
+    pub fn bind_vertex_array(&self, vertex_array: Option<&WebGLVertexArrayObject>) {
+        match vertex_array {
+            Some(vertex_array) if vertex_array.is_deleted() => self.webgl_error(InvalidOperation),
+            Some(vertex_array) => {
+                self.bound_vertex_array.set(Some(vertex_array));
+                vertex_array.bind();
+            }
+            None => {
+                self.bound_vertex_array.set(None);
+                let cmd = WebGL2Command::BindVertexArray(None);
+                self.ipc_renderer.send(CanvasMsg::WebGL2(cmd)).unwrap();
+            }
+        }
+    }
+
     pub fn bound_texture_for_target(&self, target: &TexImageTarget) -> Option<Root<WebGLTexture>> {
         match *target {
             TexImageTarget::Texture2D => self.bound_texture_2d.get(),
@@ -245,11 +408,28 @@ impl WebGLRenderingContext {
     }
 
     fn tex_parameter(&self, target: u32, name: u32, value: TexParameterValue) {
-        let texture = match target {
-            constants::TEXTURE_2D => self.bound_texture_2d.get(),
-            constants::TEXTURE_CUBE_MAP => self.bound_texture_cube_map.get(),
-            _ => return self.webgl_error(InvalidEnum),
+        let texture = match self.texture_slot(target) {
+            Some(slot) => slot.get(),
+            None => return self.webgl_error(InvalidEnum),
         };
+
+        // https://www.khronos.org/registry/webgl/extensions/EXT_texture_filter_anisotropic/
+        if name == EXTTextureFilterAnisotropicConstants::TEXTURE_MAX_ANISOTROPY_EXT &&
+           self.extensions.max_texture_anisotropy().is_some() {
+            let value = match value {
+                TexParameterValue::Float(value) => value,
+                TexParameterValue::Int(value) => value as f32,
+            };
+            if value < 1. {
+                return self.webgl_error(InvalidValue);
+            }
+            if texture.is_none() {
+                return self.webgl_error(InvalidOperation);
+            }
+            let cmd = WebGLCommand::TexParameterf(target, name, value);
+            return self.ipc_renderer.send(CanvasMsg::WebGL(cmd)).unwrap();
+        }
+
         if let Some(texture) = texture {
             handle_potential_webgl_error!(self, texture.tex_parameter(target, name, value));
         } else {
@@ -257,6 +437,84 @@ impl WebGLRenderingContext {
         }
     }
 
+    fn validate_draw_mode(&self, mode: u32) -> bool {
+        match mode {
+            constants::POINTS | constants::LINE_STRIP |
+            constants::LINE_LOOP | constants::LINES |
+            constants::TRIANGLE_STRIP | constants::TRIANGLE_FAN |
+            constants::TRIANGLES => true,
+            _ => {
+                self.webgl_error(InvalidEnum);
+                false
+            }
+        }
+    }
+
+    // Returns whether a drawArrays call with these arguments should reach the GL, setting the
+    // error if it should not.
+    fn validate_draw_arrays(&self, mode: u32, first: i32, count: i32) -> bool {
+        if !self.validate_draw_mode(mode) {
+            return false;
+        }
+
+        if self.current_program.get().is_none() {
+            self.webgl_error(InvalidOperation);
+            return false;
+        }
+
+        if first < 0 || count < 0 {
+            self.webgl_error(InvalidValue);
+            return false;
+        }
+
+        self.validate_framebuffer_complete()
+    }
+
+    // Returns whether a drawElements call with these arguments should reach the GL, setting the
+    // error if it should not.
+    fn validate_draw_elements(&self, mode: u32, count: i32, type_: u32, offset: i64) -> bool {
+        // From the GLES 2.0.25 spec, page 21:
+        //
+        //     "type must be one of UNSIGNED_BYTE or UNSIGNED_SHORT"
+        //
+        // OES_element_index_uint and WebGL 2.0 add UNSIGNED_INT.
+        let type_size = match type_ {
+            constants::UNSIGNED_BYTE => 1,
+            constants::UNSIGNED_SHORT => 2,
+            constants::UNSIGNED_INT if self.extensions.is_element_index_uint_enabled() => 4,
+            _ => {
+                self.webgl_error(InvalidEnum);
+                return false;
+            }
+        };
+
+        if offset % type_size != 0 {
+            self.webgl_error(InvalidOperation);
+            return false;
+        }
+
+        if count <= 0 {
+            self.webgl_error(InvalidOperation);
+            return false;
+        }
+
+        if offset < 0 {
+            self.webgl_error(InvalidValue);
+            return false;
+        }
+
+        if self.current_program.get().is_none() || self.bound_buffer_element_array.get().is_none() {
+            self.webgl_error(InvalidOperation);
+            return false;
+        }
+
+        if !self.validate_framebuffer_complete() {
+            return false;
+        }
+
+        self.validate_draw_mode(mode)
+    }
+
     fn mark_as_dirty(&self) {
         self.canvas.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
@@ -406,10 +664,13 @@ impl WebGLRenderingContext {
         // data to be read.
         // If it is UNSIGNED_BYTE, a Uint8Array must be supplied;
         // if it is UNSIGNED_SHORT_5_6_5, UNSIGNED_SHORT_4_4_4_4,
-        // or UNSIGNED_SHORT_5_5_5_1, a Uint16Array must be supplied.
+        // or UNSIGNED_SHORT_5_5_5_1, a Uint16Array must be supplied;
+        // if it is FLOAT, a Float32Array must be supplied.
         // If the types do not match, an INVALID_OPERATION error is generated.
         let received_size = if let Some(data) = data {
-            if unsafe { array_buffer_view_data_checked::<u16>(data).is_some() } {
+            if unsafe { array_buffer_view_data_checked::<f32>(data).is_some() } {
+                4
+            } else if unsafe { array_buffer_view_data_checked::<u16>(data).is_some() } {
                 2
             } else if unsafe { array_buffer_view_data_checked::<u8>(data).is_some() } {
                 1
@@ -518,6 +779,7 @@ impl WebGLRenderingContext {
             constants::BLEND | constants::CULL_FACE | constants::DEPTH_TEST | constants::DITHER |
             constants::POLYGON_OFFSET_FILL | constants::SAMPLE_ALPHA_TO_COVERAGE | constants::SAMPLE_COVERAGE |
             constants::SAMPLE_COVERAGE_INVERT | constants::SCISSOR_TEST => true,
+            constants2::RASTERIZER_DISCARD if self.webgl_version == WebGLVersion::WebGL2 => true,
             _ => {
                 self.webgl_error(InvalidEnum);
                 false
@@ -608,6 +870,13 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
                 return object_binding_to_js_or_null!(cx, &self.bound_texture_2d),
             constants::TEXTURE_BINDING_CUBE_MAP =>
                 return object_binding_to_js_or_null!(cx, &self.bound_texture_cube_map),
+            constants2::VERTEX_ARRAY_BINDING if self.extensions.is_vertex_array_object_enabled() =>
+                return object_binding_to_js_or_null!(cx, &self.bound_vertex_array),
+            EXTTextureFilterAnisotropicConstants::MAX_TEXTURE_MAX_ANISOTROPY_EXT => {
+                if let Some(max) = self.extensions.max_texture_anisotropy() {
+                    return DoubleValue(max as f64);
+                }
+            }
             _ => {}
         }
 
@@ -674,13 +943,13 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.14
     fn GetSupportedExtensions(&self) -> Option<Vec<DOMString>> {
-        Some(vec![])
+        Some(self.extensions.supported_names().into_iter().map(DOMString::from).collect())
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.14
-    fn GetExtension(&self, _cx: *mut JSContext, _name: DOMString)
+    fn GetExtension(&self, _cx: *mut JSContext, name: DOMString)
                     -> Option<NonZero<*mut JSObject>> {
-        None
+        self.extensions.get_or_init(&name, self)
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.3
@@ -746,11 +1015,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn BindBuffer(&self, target: u32, buffer: Option<&WebGLBuffer>) {
-        let slot = match target {
-            constants::ARRAY_BUFFER => &self.bound_buffer_array,
-            constants::ELEMENT_ARRAY_BUFFER => &self.bound_buffer_element_array,
-
-            _ => return self.webgl_error(InvalidEnum),
+        let slot = match self.buffer_slot(target) {
+            Some(slot) => slot,
+            None => return self.webgl_error(InvalidEnum),
         };
 
         if let Some(buffer) = buffer {
@@ -809,10 +1076,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
     fn BindTexture(&self, target: u32, texture: Option<&WebGLTexture>) {
-        let slot = match target {
-            constants::TEXTURE_2D => &self.bound_texture_2d,
-            constants::TEXTURE_CUBE_MAP => &self.bound_texture_cube_map,
-            _ => return self.webgl_error(InvalidEnum),
+        let slot = match self.texture_slot(target) {
+            Some(slot) => slot,
+            None => return self.webgl_error(InvalidEnum),
         };
 
         if let Some(texture) = texture {
@@ -831,11 +1097,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.8
     fn GenerateMipmap(&self, target: u32) {
-        let slot = match target {
-            constants::TEXTURE_2D => &self.bound_texture_2d,
-            constants::TEXTURE_CUBE_MAP => &self.bound_texture_cube_map,
-
-            _ => return self.webgl_error(InvalidEnum),
+        let slot = match self.texture_slot(target) {
+            Some(slot) => slot,
+            None => return self.webgl_error(InvalidEnum),
         };
 
         match slot.get() {
@@ -847,10 +1111,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
     #[allow(unsafe_code)]
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn BufferData(&self, _cx: *mut JSContext, target: u32, data: Option<*mut JSObject>, usage: u32) {
-        let bound_buffer = match target {
-            constants::ARRAY_BUFFER => self.bound_buffer_array.get(),
-            constants::ELEMENT_ARRAY_BUFFER => self.bound_buffer_element_array.get(),
-            _ => return self.webgl_error(InvalidEnum),
+        let bound_buffer = match self.buffer_slot(target) {
+            Some(slot) => slot.get(),
+            None => return self.webgl_error(InvalidEnum),
         };
 
         let bound_buffer = match bound_buffer {
@@ -882,10 +1145,9 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
     #[allow(unsafe_code)]
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
     fn BufferSubData(&self, _cx: *mut JSContext, target: u32, offset: i64, data: Option<*mut JSObject>) {
-        let bound_buffer = match target {
-            constants::ARRAY_BUFFER => self.bound_buffer_array.get(),
-            constants::ELEMENT_ARRAY_BUFFER => self.bound_buffer_element_array.get(),
-            _ => return self.webgl_error(InvalidEnum),
+        let bound_buffer = match self.buffer_slot(target) {
+            Some(slot) => slot.get(),
+            None => return self.webgl_error(InvalidEnum),
         };
         let bound_buffer = match bound_buffer {
             Some(bound_buffer) => bound_buffer,
@@ -1186,7 +1448,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
                 return None;
             }
         }
-        WebGLShader::maybe_new(self.global().r(), self.ipc_renderer.clone(), shader_type)
+        WebGLShader::maybe_new(self.global().r(), self.ipc_renderer.clone(), shader_type, self.webgl_version)
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.5
@@ -1194,6 +1456,8 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         if let Some(buffer) = buffer {
             handle_object_deletion!(self.bound_buffer_array, buffer);
             handle_object_deletion!(self.bound_buffer_element_array, buffer);
+            handle_object_deletion!(self.bound_buffer_uniform, buffer);
+            handle_object_deletion!(self.bound_buffer_transform_feedback, buffer);
             buffer.delete()
         }
     }
@@ -1219,6 +1483,8 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
         if let Some(texture) = texture {
             handle_object_deletion!(self.bound_texture_2d, texture);
             handle_object_deletion!(self.bound_texture_cube_map, texture);
+            handle_object_deletion!(self.bound_texture_3d, texture);
+            handle_object_deletion!(self.bound_texture_2d_array, texture);
             texture.delete()
         }
     }
@@ -1240,74 +1506,21 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11
     fn DrawArrays(&self, mode: u32, first: i32, count: i32) {
-        match mode {
-            constants::POINTS | constants::LINE_STRIP |
-            constants::LINE_LOOP | constants::LINES |
-            constants::TRIANGLE_STRIP | constants::TRIANGLE_FAN |
-            constants::TRIANGLES => {
-                if self.current_program.get().is_none() {
-                    return self.webgl_error(InvalidOperation);
-                }
-
-                if first < 0 || count < 0 {
-                    return self.webgl_error(InvalidValue);
-                }
-
-                if !self.validate_framebuffer_complete() {
-                    return;
-                }
-
-                self.ipc_renderer
-                    .send(CanvasMsg::WebGL(WebGLCommand::DrawArrays(mode, first, count)))
-                    .unwrap();
-                self.mark_as_dirty();
-            },
-            _ => self.webgl_error(InvalidEnum),
+        if self.validate_draw_arrays(mode, first, count) {
+            self.ipc_renderer
+                .send(CanvasMsg::WebGL(WebGLCommand::DrawArrays(mode, first, count)))
+                .unwrap();
+            self.mark_as_dirty();
         }
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11
     fn DrawElements(&self, mode: u32, count: i32, type_: u32, offset: i64) {
-        // From the GLES 2.0.25 spec, page 21:
-        //
-        //     "type must be one of UNSIGNED_BYTE or UNSIGNED_SHORT"
-        let type_size = match type_ {
-            constants::UNSIGNED_BYTE => 1,
-            constants::UNSIGNED_SHORT => 2,
-            _ => return self.webgl_error(InvalidEnum),
-        };
-
-        if offset % type_size != 0 {
-            return self.webgl_error(InvalidOperation);
-        }
-
-        if count <= 0 {
-            return self.webgl_error(InvalidOperation);
-        }
-
-        if offset < 0 {
-            return self.webgl_error(InvalidValue);
-        }
-
-        if self.current_program.get().is_none() || self.bound_buffer_element_array.get().is_none() {
-            return self.webgl_error(InvalidOperation);
-        }
-
-        if !self.validate_framebuffer_complete() {
-            return;
-        }
-
-        match mode {
-            constants::POINTS | constants::LINE_STRIP |
-            constants::LINE_LOOP | constants::LINES |
-            constants::TRIANGLE_STRIP | constants::TRIANGLE_FAN |
-            constants::TRIANGLES => {
-                self.ipc_renderer
-                    .send(CanvasMsg::WebGL(WebGLCommand::DrawElements(mode, count, type_, offset)))
-                    .unwrap();
-                self.mark_as_dirty();
-            },
-            _ => self.webgl_error(InvalidEnum),
+        if self.validate_draw_elements(mode, count, type_, offset) {
+            self.ipc_renderer
+                .send(CanvasMsg::WebGL(WebGLCommand::DrawElements(mode, count, type_, offset)))
+                .unwrap();
+            self.mark_as_dirty();
         }
     }
 
@@ -2126,6 +2339,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             Err(_) => return, // NB: The validator sets the correct error for us.
         };
 
+        let pixels = image_pixels_as_data_type(pixels, data_type);
         self.tex_image_2d(texture, target, data_type, format,
                           level, width, height, border, pixels);
     }
@@ -2217,6 +2431,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
             Err(_) => return, // NB: The validator sets the correct error for us.
         };
 
+        let pixels = image_pixels_as_data_type(pixels, data_type);
         self.tex_sub_image_2d(texture, target, level, xoffset, yoffset,
                               width, height, format, data_type, pixels);
     }
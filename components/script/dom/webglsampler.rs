@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl2.idl
+use canvas_traits::CanvasMsg;
+use canvas_traits::webgl2::{WebGL2Command, WebGLSamplerId};
+use dom::bindings::codegen::Bindings::WebGLSamplerBinding;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::webglobject::WebGLObject;
+use ipc_channel::ipc::{self, IpcSender};
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct WebGLSampler {
+    webgl_object: WebGLObject,
+    id: WebGLSamplerId,
+    is_deleted: Cell<bool>,
+    #[ignore_heap_size_of = "Defined in ipc-channel"]
+    renderer: IpcSender<CanvasMsg>,
+}
+
+impl WebGLSampler {
+    fn new_inherited(renderer: IpcSender<CanvasMsg>,
+                     id: WebGLSamplerId)
+                     -> WebGLSampler {
+        WebGLSampler {
+            webgl_object: WebGLObject::new_inherited(),
+            id: id,
+            is_deleted: Cell::new(false),
+            renderer: renderer,
+        }
+    }
+
+    pub fn maybe_new(global: GlobalRef, renderer: IpcSender<CanvasMsg>)
+                     -> Option<Root<WebGLSampler>> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        renderer.send(CanvasMsg::WebGL2(WebGL2Command::CreateSampler(sender))).unwrap();
+
+        let result = receiver.recv().unwrap();
+        result.map(|sampler_id| WebGLSampler::new(global, renderer, sampler_id))
+    }
+
+    pub fn new(global: GlobalRef,
+               renderer: IpcSender<CanvasMsg>,
+               id: WebGLSamplerId)
+               -> Root<WebGLSampler> {
+        reflect_dom_object(box WebGLSampler::new_inherited(renderer, id),
+                           global,
+                           WebGLSamplerBinding::Wrap)
+    }
+}
+
+
+impl WebGLSampler {
+    pub fn id(&self) -> WebGLSamplerId {
+        self.id
+    }
+
+    pub fn bind(&self, unit: u32) {
+        let msg = CanvasMsg::WebGL2(WebGL2Command::BindSampler(unit, Some(self.id)));
+        self.renderer.send(msg).unwrap();
+    }
+
+    pub fn parameter_i(&self, name: u32, value: i32) {
+        let msg = CanvasMsg::WebGL2(WebGL2Command::SamplerParameteri(self.id, name, value));
+        self.renderer.send(msg).unwrap();
+    }
+
+    pub fn parameter_f(&self, name: u32, value: f32) {
+        let msg = CanvasMsg::WebGL2(WebGL2Command::SamplerParameterf(self.id, name, value));
+        self.renderer.send(msg).unwrap();
+    }
+
+    pub fn delete(&self) {
+        if !self.is_deleted.get() {
+            self.is_deleted.set(true);
+            let _ = self.renderer.send(CanvasMsg::WebGL2(WebGL2Command::DeleteSampler(self.id)));
+        }
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.is_deleted.get()
+    }
+}
@@ -3,8 +3,9 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 // https://www.khronos.org/registry/webgl/specs/latest/1.0/webgl.idl
-use angle::hl::{BuiltInResources, Output, ShaderValidator};
+use angle::hl::{BuiltInResources, Output, ShaderSpec, ShaderValidator};
 use canvas_traits::CanvasMsg;
+use canvas_traits::webgl2::WebGLVersion;
 use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::WebGLShaderBinding;
 use dom::bindings::global::GlobalRef;
@@ -29,6 +30,7 @@ pub struct WebGLShader {
     webgl_object: WebGLObject,
     id: WebGLShaderId,
     gl_type: u32,
+    webgl_version: WebGLVersion,
     source: DOMRefCell<Option<DOMString>>,
     info_log: DOMRefCell<Option<String>>,
     is_deleted: Cell<bool>,
@@ -49,13 +51,15 @@ static GLSLANG_INITIALIZATION: Once = ONCE_INIT;
 impl WebGLShader {
     fn new_inherited(renderer: IpcSender<CanvasMsg>,
                      id: WebGLShaderId,
-                     shader_type: u32)
+                     shader_type: u32,
+                     webgl_version: WebGLVersion)
                      -> WebGLShader {
         GLSLANG_INITIALIZATION.call_once(|| ::angle::hl::initialize().unwrap());
         WebGLShader {
             webgl_object: WebGLObject::new_inherited(),
             id: id,
             gl_type: shader_type,
+            webgl_version: webgl_version,
             source: DOMRefCell::new(None),
             info_log: DOMRefCell::new(None),
             is_deleted: Cell::new(false),
@@ -67,20 +71,22 @@ impl WebGLShader {
 
     pub fn maybe_new(global: GlobalRef,
                      renderer: IpcSender<CanvasMsg>,
-                     shader_type: u32) -> Option<Root<WebGLShader>> {
+                     shader_type: u32,
+                     webgl_version: WebGLVersion) -> Option<Root<WebGLShader>> {
         let (sender, receiver) = ipc::channel().unwrap();
         renderer.send(CanvasMsg::WebGL(WebGLCommand::CreateShader(shader_type, sender))).unwrap();
 
         let result = receiver.recv().unwrap();
-        result.map(|shader_id| WebGLShader::new(global, renderer, shader_id, shader_type))
+        result.map(|shader_id| WebGLShader::new(global, renderer, shader_id, shader_type, webgl_version))
     }
 
     pub fn new(global: GlobalRef,
                renderer: IpcSender<CanvasMsg>,
                id: WebGLShaderId,
-               shader_type: u32)
+               shader_type: u32,
+               webgl_version: WebGLVersion)
                -> Root<WebGLShader> {
-        reflect_dom_object(box WebGLShader::new_inherited(renderer, id, shader_type),
+        reflect_dom_object(box WebGLShader::new_inherited(renderer, id, shader_type, webgl_version),
                            global,
                            WebGLShaderBinding::Wrap)
     }
@@ -103,9 +109,15 @@ impl WebGLShader {
         }
 
         if let Some(ref source) = *self.source.borrow() {
-            let validator = ShaderValidator::for_webgl(self.gl_type,
-                                                       SHADER_OUTPUT_FORMAT,
-                                                       &BuiltInResources::default()).unwrap();
+            // WebGL 2.0 contexts accept GLSL ES 3.00 shaders in addition to GLSL ES 1.00 ones.
+            let spec = match self.webgl_version {
+                WebGLVersion::WebGL1 => ShaderSpec::WebGL,
+                WebGLVersion::WebGL2 => ShaderSpec::WebGL2,
+            };
+            let validator = ShaderValidator::new(self.gl_type,
+                                                 spec,
+                                                 SHADER_OUTPUT_FORMAT,
+                                                 &BuiltInResources::default()).unwrap();
             match validator.compile_and_translate(&[source]) {
                 Ok(translated_source) => {
                     debug!("Shader translated: {}", translated_source);
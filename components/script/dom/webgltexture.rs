@@ -5,6 +5,7 @@
 // https://www.khronos.org/registry/webgl/specs/latest/1.0/webgl.idl
 use canvas_traits::CanvasMsg;
 use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::WebGL2RenderingContextBinding::WebGL2RenderingContextConstants as constants2;
 use dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextConstants as constants;
 use dom::bindings::codegen::Bindings::WebGLTextureBinding;
 use dom::bindings::global::GlobalRef;
@@ -98,7 +99,9 @@ impl WebGLTexture {
         } else {
             // This is the first time binding
             let face_count = match target {
-                constants::TEXTURE_2D => 1,
+                constants::TEXTURE_2D |
+                constants2::TEXTURE_3D |
+                constants2::TEXTURE_2D_ARRAY => 1,
                 constants::TEXTURE_CUBE_MAP => 6,
                 _ => return Err(WebGLError::InvalidOperation)
             };
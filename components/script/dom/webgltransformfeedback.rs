@@ -0,0 +1,84 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+// https://www.khronos.org/registry/webgl/specs/latest/2.0/webgl2.idl
+use canvas_traits::CanvasMsg;
+use canvas_traits::webgl2::{WebGL2Command, WebGLTransformFeedbackId};
+use dom::bindings::codegen::Bindings::WebGLTransformFeedbackBinding;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::webglobject::WebGLObject;
+use ipc_channel::ipc::{self, IpcSender};
+use std::cell::Cell;
+
+#[dom_struct]
+pub struct WebGLTransformFeedback {
+    webgl_object: WebGLObject,
+    id: WebGLTransformFeedbackId,
+    ever_bound: Cell<bool>,
+    is_deleted: Cell<bool>,
+    #[ignore_heap_size_of = "Defined in ipc-channel"]
+    renderer: IpcSender<CanvasMsg>,
+}
+
+impl WebGLTransformFeedback {
+    fn new_inherited(renderer: IpcSender<CanvasMsg>,
+                     id: WebGLTransformFeedbackId)
+                     -> WebGLTransformFeedback {
+        WebGLTransformFeedback {
+            webgl_object: WebGLObject::new_inherited(),
+            id: id,
+            ever_bound: Cell::new(false),
+            is_deleted: Cell::new(false),
+            renderer: renderer,
+        }
+    }
+
+    pub fn maybe_new(global: GlobalRef, renderer: IpcSender<CanvasMsg>)
+                     -> Option<Root<WebGLTransformFeedback>> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        renderer.send(CanvasMsg::WebGL2(WebGL2Command::CreateTransformFeedback(sender))).unwrap();
+
+        let result = receiver.recv().unwrap();
+        result.map(|transform_feedback_id| WebGLTransformFeedback::new(global, renderer, transform_feedback_id))
+    }
+
+    pub fn new(global: GlobalRef,
+               renderer: IpcSender<CanvasMsg>,
+               id: WebGLTransformFeedbackId)
+               -> Root<WebGLTransformFeedback> {
+        reflect_dom_object(box WebGLTransformFeedback::new_inherited(renderer, id),
+                           global,
+                           WebGLTransformFeedbackBinding::Wrap)
+    }
+}
+
+
+impl WebGLTransformFeedback {
+    pub fn id(&self) -> WebGLTransformFeedbackId {
+        self.id
+    }
+
+    pub fn bind(&self, target: u32) {
+        self.ever_bound.set(true);
+        let msg = CanvasMsg::WebGL2(WebGL2Command::BindTransformFeedback(target, Some(self.id)));
+        self.renderer.send(msg).unwrap();
+    }
+
+    pub fn delete(&self) {
+        if !self.is_deleted.get() {
+            self.is_deleted.set(true);
+            let _ = self.renderer.send(CanvasMsg::WebGL2(WebGL2Command::DeleteTransformFeedback(self.id)));
+        }
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.is_deleted.get()
+    }
+
+    pub fn ever_bound(&self) -> bool {
+        self.ever_bound.get()
+    }
+}
@@ -3,6 +3,8 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use app_units::Au;
+use audio::sink::{AudioSink, PipelineAudio, PipelineSink, default_sink};
+use canvas_traits::webxr::WebXRMsg;
 use devtools_traits::{ScriptToDevtoolsControlMsg, TimelineMarker, TimelineMarkerType, WorkerId};
 use dom::bindings::callback::ExceptionHandling;
 use dom::bindings::cell::DOMRefCell;
@@ -11,9 +13,15 @@ use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
 use dom::bindings::codegen::Bindings::EventHandlerBinding::OnBeforeUnloadEventHandlerNonNull;
 use dom::bindings::codegen::Bindings::EventHandlerBinding::OnErrorEventHandlerNonNull;
 use dom::bindings::codegen::Bindings::FunctionBinding::Function;
+use dom::bindings::codegen::Bindings::HTMLIFrameElementBinding::HTMLIFrameElementMethods;
 use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use dom::bindings::codegen::Bindings::ImageBitmapBinding::ImageBitmapOptions;
+use dom::bindings::codegen::Bindings::RequestBinding::{RequestInfo, RequestInit};
 use dom::bindings::codegen::Bindings::WindowBinding::{self, FrameRequestCallback, WindowMethods};
 use dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, ScrollToOptions};
+use dom::bindings::codegen::UnionTypes::HTMLImageElementOrHTMLCanvasElementOrCanvasRenderingContext2DOrBlobOrImageDataOrImageBitmap
+    as ImageBitmapSource;
+use dom::bindings::conversions::ToJSValConvertible;
 use dom::bindings::error::{Error, ErrorInfo, ErrorResult, Fallible, report_pending_exception};
 use dom::bindings::global::{GlobalRef, global_root_from_object};
 use dom::bindings::inheritance::Castable;
@@ -24,30 +32,36 @@ use dom::bindings::reflector::Reflectable;
 use dom::bindings::str::DOMString;
 use dom::bindings::structuredclone::StructuredCloneData;
 use dom::bindings::utils::{GlobalStaticData, WindowProxyHandler};
-use dom::browsingcontext::BrowsingContext;
+use dom::browsingcontext::{BrowsingContext, WindowProxyRef};
 use dom::console::TimerSet;
 use dom::crypto::Crypto;
 use dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration};
+use dom::dissimilaroriginwindow::DissimilarOriginWindow;
 use dom::document::Document;
 use dom::element::Element;
 use dom::errorevent::ErrorEvent;
 use dom::event::{Event, EventBubbles, EventCancelable};
 use dom::eventtarget::EventTarget;
 use dom::history::History;
-use dom::htmliframeelement::build_mozbrowser_custom_event;
+use dom::htmliframeelement::{HTMLIFrameElement, build_mozbrowser_custom_event};
+use dom::imagebitmap;
 use dom::location::Location;
+use dom::mediasource::MediaSource;
 use dom::messageevent::MessageEvent;
 use dom::navigator::Navigator;
 use dom::node::{Node, from_untrusted_node_address, window_from_node};
 use dom::performance::Performance;
+use dom::promise::Promise;
 use dom::screen::Screen;
+use dom::selection::Selection;
 use dom::storage::Storage;
 use euclid::{Point2D, Rect, Size2D};
+use fetch;
 use gfx_traits::LayerId;
 use ipc_channel::ipc::{self, IpcSender};
 use js::jsapi::{Evaluate2, HandleObject, HandleValue, JSAutoCompartment, JSContext};
 use js::jsapi::{JS_GC, JS_GetRuntime, MutableHandleValue, SetWindowProxy};
-use js::jsval::UndefinedValue;
+use js::jsval::{JSVal, UndefinedValue};
 use js::rust::CompileOptionsWrapper;
 use js::rust::Runtime;
 use libc;
@@ -70,9 +84,9 @@ use script_layout_interface::rpc::{ContentBoxResponse, ContentBoxesResponse, Lay
 use script_layout_interface::rpc::{MarginStyleResponse, ResolvedStyleResponse};
 use script_runtime::{CommonScriptMsg, ScriptChan, ScriptPort, ScriptThreadEventCategory, maybe_take_panic_result};
 use script_thread::{MainThreadScriptChan, MainThreadScriptMsg, Runnable, RunnableWrapper};
-use script_thread::SendableMainThreadScriptChan;
-use script_traits::{ConstellationControlMsg, MozBrowserEvent, UntrustedNodeAddress};
-use script_traits::{DocumentState, MsDuration, TimerEvent, TimerEventId};
+use script_thread::{ScriptThread, SendableMainThreadScriptChan};
+use script_traits::{ClientInfo, ConstellationControlMsg, MozBrowserEvent, UntrustedNodeAddress};
+use script_traits::{DialogResponse, DocumentState, MsDuration, SimpleDialog, TimerEvent, TimerEventId};
 use script_traits::{ScriptMsg as ConstellationMsg, TimerEventRequest, TimerSource, WindowSizeData};
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
 use std::ascii::AsciiExt;
@@ -99,15 +113,15 @@ use task_source::file_reading::FileReadingTaskSource;
 use task_source::history_traversal::HistoryTraversalTaskSource;
 use task_source::networking::NetworkingTaskSource;
 use task_source::user_interaction::UserInteractionTaskSource;
-use time;
 use timers::{IsInterval, OneshotTimerCallback, OneshotTimerHandle, OneshotTimers, TimerCallback};
-#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
-use tinyfiledialogs::{self, MessageBoxIcon};
 use url::Url;
+use usb_traits::UsbMethodMsg;
 use util::geometry::{self, max_rect};
 use util::opts;
 use util::prefs::PREFS;
+use uuid::Uuid;
 use webdriver_handlers::jsval_to_webdriver;
+use webrtc_traits::WebRTCMsg;
 
 /// Current state of the window object
 #[derive(JSTraceable, Copy, Clone, Debug, PartialEq, HeapSizeOf)]
@@ -235,6 +249,18 @@ pub struct Window {
     #[ignore_heap_size_of = "channels are hard"]
     bluetooth_thread: IpcSender<BluetoothMethodMsg>,
 
+    /// A handle for communicating messages to the USB thread.
+    #[ignore_heap_size_of = "channels are hard"]
+    usb_thread: IpcSender<UsbMethodMsg>,
+
+    /// A handle for communicating messages to the WebRTC thread.
+    #[ignore_heap_size_of = "channels are hard"]
+    webrtc_thread: IpcSender<WebRTCMsg>,
+
+    /// A handle for communicating messages to the WebXR thread.
+    #[ignore_heap_size_of = "channels are hard"]
+    webxr_thread: IpcSender<WebXRMsg>,
+
     /// A handle for communicating messages to the constellation thread.
     #[ignore_heap_size_of = "channels are hard"]
     constellation_chan: IpcSender<ConstellationMsg>,
@@ -277,6 +303,29 @@ pub struct Window {
 
     /// Timers used by the Console API.
     console_timers: TimerSet,
+
+    /// The id of this window's environment, as seen by service workers.
+    /// https://html.spec.whatwg.org/multipage/#concept-environment-id
+    #[ignore_heap_size_of = "Defined in uuid"]
+    client_id: Uuid,
+
+    /// The media sources that object URLs were created for, by URL.
+    /// https://w3c.github.io/media-source/#dom-url-createobjecturl
+    media_source_urls: DOMRefCell<HashMap<String, JS<MediaSource>>>,
+
+    /// The audio output of the media elements and audio contexts of this
+    /// window, which tells the constellation whether it is making sound.
+    #[ignore_heap_size_of = "Defined in the audio module"]
+    audio: Arc<PipelineAudio>,
+
+    /// The objects standing in for the windows of other script threads that were accessed from
+    /// this one, by pipeline, so that each of them is always represented by the same object.
+    dissimilar_origin_windows: DOMRefCell<HashMap<PipelineId, JS<DissimilarOriginWindow>>>,
+
+    /// The event being dispatched to listeners in this window, exposed as the
+    /// legacy `window.event`.
+    /// https://dom.spec.whatwg.org/#window-current-event
+    current_event: MutNullableHeap<JS<Event>>,
 }
 
 impl Window {
@@ -333,6 +382,11 @@ impl Window {
         self.id
     }
 
+    /// The `Performance` object of this window, if script or timing created it.
+    pub fn performance_if_created(&self) -> Option<Root<Performance>> {
+        self.performance.get()
+    }
+
     pub fn parent_info(&self) -> Option<(PipelineId, FrameType)> {
         self.parent_info
     }
@@ -354,6 +408,18 @@ impl Window {
         self.bluetooth_thread.clone()
     }
 
+    pub fn usb_thread(&self) -> IpcSender<UsbMethodMsg> {
+        self.usb_thread.clone()
+    }
+
+    pub fn webrtc_thread(&self) -> IpcSender<WebRTCMsg> {
+        self.webrtc_thread.clone()
+    }
+
+    pub fn webxr_thread(&self) -> IpcSender<WebXRMsg> {
+        self.webxr_thread.clone()
+    }
+
     pub fn css_error_reporter(&self) -> Box<ParseErrorReporter + Send> {
         self.error_reporter.clone()
     }
@@ -364,16 +430,48 @@ impl Window {
     pub fn set_scroll_offsets(&self, offsets: HashMap<UntrustedNodeAddress, Point2D<f32>>) {
         *self.scroll_offsets.borrow_mut() = offsets
     }
-}
 
-#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
-fn display_alert_dialog(message: &str) {
-    tinyfiledialogs::message_box_ok("Alert!", message, MessageBoxIcon::Warning);
-}
+    pub fn add_media_source_url(&self, url: String, media_source: &MediaSource) {
+        self.media_source_urls.borrow_mut().insert(url, JS::from_ref(media_source));
+    }
+
+    pub fn revoke_media_source_url(&self, url: &str) {
+        self.media_source_urls.borrow_mut().remove(url);
+    }
+
+    /// The media source an object URL was created for, if it wasn't revoked.
+    pub fn media_source_for_url(&self, url: &str) -> Option<Root<MediaSource>> {
+        self.media_source_urls.borrow().get(url).map(|media_source| Root::from_ref(&**media_source))
+    }
 
-#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
-fn display_alert_dialog(_message: &str) {
-    // tinyfiledialogs not supported on Android
+    /// A sink for the audio of a media element or an audio context, which is
+    /// muted along with the top-level browsing context.
+    pub fn audio_sink(&self) -> Box<AudioSink> {
+        box PipelineSink::new(default_sink(), self.audio.clone())
+    }
+
+    /// Mutes or unmutes all the media of this window, as part of muting its
+    /// top-level browsing context.
+    pub fn set_muted(&self, muted: bool) {
+        self.audio.set_muted(muted);
+    }
+
+    /// Asks the embedder to show `dialog`, and pauses until the user answers it.
+    /// https://html.spec.whatwg.org/multipage/#simple-dialogs
+    fn show_dialog(&self, dialog: SimpleDialog) -> DialogResponse {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let msg = ConstellationMsg::ShowDialog(self.pipeline_id(), dialog, sender);
+        self.constellation_chan().send(msg).unwrap();
+
+        // https://html.spec.whatwg.org/multipage/#pause
+        // TODO: keep running the event loops of other documents while paused.
+        let response = receiver.recv().unwrap_or_else(|_| DialogResponse::dismissed());
+        if response.prevent_additional_dialogs {
+            let msg = ConstellationMsg::PreventAdditionalDialogs(self.pipeline_id());
+            self.constellation_chan().send(msg).unwrap();
+        }
+        response
+    }
 }
 
 // https://html.spec.whatwg.org/multipage/#atob
@@ -465,13 +563,21 @@ impl WindowMethods for Window {
             stderr.flush().unwrap();
         }
 
-        let (sender, receiver) = ipc::channel().unwrap();
-        self.constellation_chan().send(ConstellationMsg::Alert(self.pipeline_id(), s.to_string(), sender)).unwrap();
+        self.show_dialog(SimpleDialog::Alert(s.into()));
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-confirm
+    fn Confirm(&self, message: DOMString) -> bool {
+        self.show_dialog(SimpleDialog::Confirm(message.into())).accepted
+    }
 
-        let should_display_alert_dialog = receiver.recv().unwrap();
-        if should_display_alert_dialog {
-            display_alert_dialog(&s);
+    // https://html.spec.whatwg.org/multipage/#dom-prompt
+    fn Prompt(&self, message: DOMString, default: DOMString) -> Option<DOMString> {
+        let response = self.show_dialog(SimpleDialog::Prompt(message.into(), default.into()));
+        if !response.accepted {
+            return None;
         }
+        Some(response.text.map_or_else(DOMString::new, DOMString::from))
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-window-close
@@ -479,6 +585,21 @@ impl WindowMethods for Window {
         self.main_thread_script_chan().send(MainThreadScriptMsg::ExitWindow(self.id.clone())).unwrap();
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-window-stop
+    fn Stop(&self) {
+        // https://html.spec.whatwg.org/multipage/#stop-document-loading
+        // Step 3
+        let msg = ConstellationMsg::CancelNavigation(self.pipeline_id());
+        self.constellation_chan().send(msg).unwrap();
+        // Step 4
+        self.Document().abort();
+    }
+
+    // https://w3c.github.io/selection-api/#dom-window-getselection
+    fn GetSelection(&self) -> Option<Root<Selection>> {
+        self.Document().GetSelection()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-2
     fn Document(&self) -> Root<Document> {
         self.browsing_context().active_document()
@@ -509,9 +630,42 @@ impl WindowMethods for Window {
         self.crypto.or_init(|| Crypto::new(GlobalRef::Window(self)))
     }
 
+    #[allow(unrooted_must_root)]
+    // https://fetch.spec.whatwg.org/#fetch-method
+    fn Fetch(&self, input: RequestInfo, init: &RequestInit) -> Rc<Promise> {
+        fetch::Fetch(GlobalRef::Window(self), input, init)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+    fn CreateImageBitmap(&self, image: ImageBitmapSource, options: &ImageBitmapOptions)
+                         -> Rc<Promise> {
+        imagebitmap::create_image_bitmap(GlobalRef::Window(self), image, None, options)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+    fn CreateImageBitmap_(&self, image: ImageBitmapSource, sx: i32, sy: i32, sw: i32, sh: i32,
+                          options: &ImageBitmapOptions)
+                          -> Rc<Promise> {
+        imagebitmap::create_image_bitmap(GlobalRef::Window(self), image, Some((sx, sy, sw, sh)), options)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-frameelement
     fn GetFrameElement(&self) -> Option<Root<Element>> {
-        self.browsing_context().frame_element().map(Root::from_ref)
+        // Steps 1-3.
+        let container = match self.browsing_context().frame_element() {
+            Some(container) => Root::from_ref(container),
+            None => return None,
+        };
+        // Step 4.
+        // TODO(#12715): Should be the origin of the current settings object, not self's.
+        let container_doc = container.upcast::<Node>().owner_doc();
+        if !container_doc.origin().same_origin(self.Document().origin()) {
+            return None;
+        }
+        // Step 5.
+        Some(container)
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator
@@ -584,28 +738,64 @@ impl WindowMethods for Window {
         self.browsing_context()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-length
+    fn Length(&self) -> u32 {
+        self.child_browsing_context_containers().len() as u32
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-parent
-    fn Parent(&self) -> Root<BrowsingContext> {
-        match  self.parent() {
-            Some(window) => window.browsing_context(),
-            None => self.Window()
-        }
+    #[allow(unsafe_code)]
+    fn Parent(&self, cx: *mut JSContext) -> JSVal {
+        let parent = match self.parent() {
+            Some(window) => WindowProxyRef::Local(window.browsing_context()),
+            None => match self.parent_info {
+                // The parent browsing context lives in another script thread.
+                Some((parent_id, FrameType::IFrame)) => {
+                    WindowProxyRef::Remote(self.dissimilar_origin_window(parent_id))
+                },
+                _ => WindowProxyRef::Local(self.browsing_context()),
+            },
+        };
+        rooted!(in(cx) let mut rval = UndefinedValue());
+        unsafe { parent.to_jsval(cx, rval.handle_mut()) };
+        rval.get()
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-top
-    fn Top(&self) -> Root<BrowsingContext> {
+    #[allow(unsafe_code)]
+    fn Top(&self, cx: *mut JSContext) -> JSVal {
         let mut window = Root::from_ref(self);
         while let Some(parent) = window.parent() {
             window = parent;
         }
-        window.browsing_context()
+        let top = if window.is_top_level() {
+            WindowProxyRef::Local(window.browsing_context())
+        } else {
+            // The top-level browsing context lives in another script thread.
+            let (sender, receiver) = ipc::channel().unwrap();
+            let msg = ConstellationMsg::GetTopLevelPipeline(window.pipeline_id(), sender);
+            self.constellation_chan().send(msg).unwrap();
+            WindowProxyRef::Remote(self.dissimilar_origin_window(receiver.recv().unwrap()))
+        };
+        rooted!(in(cx) let mut rval = UndefinedValue());
+        unsafe { top.to_jsval(cx, rval.handle_mut()) };
+        rval.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-window-event
+    #[allow(unsafe_code)]
+    fn Event(&self, cx: *mut JSContext) -> JSVal {
+        rooted!(in(cx) let mut rval = UndefinedValue());
+        if let Some(event) = self.current_event.get() {
+            unsafe { event.to_jsval(cx, rval.handle_mut()) };
+        }
+        rval.get()
     }
 
-    // https://dvcs.w3.org/hg/webperf/raw-file/tip/specs/
-    // NavigationTiming/Overview.html#sec-window.performance-attribute
+    // https://w3c.github.io/hr-time/#dom-globalperformance-performance
     fn Performance(&self) -> Root<Performance> {
         self.performance.or_init(|| {
-            Performance::new(self, self.navigation_start,
+            Performance::new(GlobalRef::Window(self), self.navigation_start,
                              self.navigation_start_precise)
         })
     }
@@ -631,6 +821,11 @@ impl WindowMethods for Window {
         base64_atob(atob)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-structuredclone
+    fn StructuredClone(&self, cx: *mut JSContext, value: HandleValue) -> Fallible<JSVal> {
+        StructuredCloneData::clone_value(GlobalRef::Window(self), cx, value)
+    }
+
     /// https://html.spec.whatwg.org/multipage/#dom-window-requestanimationframe
     fn RequestAnimationFrame(&self, callback: Rc<FrameRequestCallback>) -> u32 {
         let doc = self.Document();
@@ -675,10 +870,7 @@ impl WindowMethods for Window {
         let data = try!(StructuredCloneData::write(cx, message));
 
         // Step 9.
-        let runnable = PostMessageHandler::new(self, origin, data);
-        let msg = CommonScriptMsg::RunnableMsg(ScriptThreadEventCategory::DomEvent, box runnable);
-        // TODO(#12718): Use the "posted message task source".
-        let _ = self.script_chan.send(msg);
+        self.post_message(origin, data);
         Ok(())
     }
 
@@ -921,6 +1113,7 @@ impl<'a, T: Reflectable> ScriptHelpers for &'a T {
     fn evaluate_script_on_global_with_result(self, code: &str, filename: &str,
                                              rval: MutableHandleValue) {
         let global = self.global();
+        ScriptThread::note_script_entered(|| global.r().get_url());
         let metadata = TimerMetadata {
             url: if filename.is_empty() {
                 global.r().get_url().as_str().into()
@@ -1111,6 +1304,9 @@ impl Window {
 
         debug!("script: performing reflow for goal {:?} reason {:?}", goal, reason);
 
+        // The boundary points of the selection may have moved with mutations of the document.
+        self.Document().update_selected_text();
+
         let marker = if self.need_emit_timeline_marker(TimelineMarkerType::Reflow) {
             Some(TimelineMarker::start("Reflow".to_owned()))
         } else {
@@ -1399,6 +1595,20 @@ impl Window {
         self.layout_rpc.margin_style()
     }
 
+    /// The number of characters rendered for a text node before the given client point.
+    pub fn text_index_query(&self, node: TrustedNodeAddress, client_point: Point2D<f32>) -> Option<usize> {
+        let translated_point =
+            Point2D::new(client_point.x + self.PageXOffset() as f32,
+                         client_point.y + self.PageYOffset() as f32);
+
+        if !self.reflow(ReflowGoal::ForScriptQuery,
+                        ReflowQueryType::TextIndexQuery(node, translated_point),
+                        ReflowReason::Query) {
+            return None;
+        }
+        self.layout_rpc.text_index().0
+    }
+
     #[allow(unsafe_code)]
     pub fn init_browsing_context(&self, browsing_context: &BrowsingContext) {
         assert!(self.browsing_context.get().is_none());
@@ -1416,7 +1626,7 @@ impl Window {
 
         self.main_thread_script_chan().send(
             MainThreadScriptMsg::Navigate(self.id,
-                LoadData::new(url, referrer_policy, Some(doc.url().clone())),
+                LoadData::new(url, referrer_policy, Some(doc.url())),
                 replace)).unwrap();
     }
 
@@ -1444,7 +1654,7 @@ impl Window {
     }
 
     pub fn get_url(&self) -> Url {
-        (*self.Document().url()).clone()
+        self.Document().url()
     }
 
     pub fn resource_threads(&self) -> &ResourceThreads {
@@ -1539,8 +1749,52 @@ impl Window {
     }
 
     // https://html.spec.whatwg.org/multipage/#accessing-other-browsing-contexts
-    pub fn IndexedGetter(&self, _index: u32, _found: &mut bool) -> Option<Root<Window>> {
-        None
+    pub fn IndexedGetter(&self, index: u32, found: &mut bool) -> Option<WindowProxyRef> {
+        let container = match self.child_browsing_context_containers().into_iter().nth(index as usize) {
+            Some(container) => container,
+            None => return None,
+        };
+        *found = true;
+        Some(match container.GetContentWindow() {
+            Some(context) => WindowProxyRef::Local(context),
+            None => WindowProxyRef::Remote(self.dissimilar_origin_window(container.pipeline_id().unwrap())),
+        })
+    }
+
+    /// The iframes of the document of this window that have a nested browsing context, in tree
+    /// order.
+    /// https://html.spec.whatwg.org/multipage/#document-tree-child-browsing-context
+    fn child_browsing_context_containers(&self) -> Vec<Root<HTMLIFrameElement>> {
+        self.Document().upcast::<Node>()
+                       .traverse_preorder()
+                       .filter_map(Root::downcast::<HTMLIFrameElement>)
+                       .filter(|iframe| iframe.pipeline_id().is_some())
+                       .collect()
+    }
+
+    /// Set the event exposed as `window.event`, returning the one it replaces.
+    pub fn set_current_event(&self, event: Option<&Event>) -> Option<Root<Event>> {
+        let previous = self.current_event.get();
+        self.current_event.set(event);
+        previous
+    }
+
+    /// The object standing in for the window of a pipeline that lives in another script thread.
+    fn dissimilar_origin_window(&self, pipeline_id: PipelineId) -> Root<DissimilarOriginWindow> {
+        let mut windows = self.dissimilar_origin_windows.borrow_mut();
+        let window = windows.entry(pipeline_id).or_insert_with(|| {
+            JS::from_ref(&*DissimilarOriginWindow::new(self, pipeline_id))
+        });
+        Root::from_ref(&**window)
+    }
+
+    /// Queues a task to dispatch a message posted to this window, if its document has the given
+    /// origin.
+    pub fn post_message(&self, origin: Option<Origin>, data: StructuredCloneData) {
+        let runnable = PostMessageHandler::new(self, origin, data);
+        let msg = CommonScriptMsg::RunnableMsg(ScriptThreadEventCategory::DomEvent, box runnable);
+        // TODO(#12718): Use the "posted message task source".
+        let _ = self.script_chan.send(msg);
     }
 
     pub fn thaw(&self) {
@@ -1657,6 +1911,9 @@ impl Window {
                image_cache_thread: ImageCacheThread,
                resource_threads: ResourceThreads,
                bluetooth_thread: IpcSender<BluetoothMethodMsg>,
+               usb_thread: IpcSender<UsbMethodMsg>,
+               webrtc_thread: IpcSender<WebRTCMsg>,
+               webxr_thread: IpcSender<WebXRMsg>,
                mem_profiler_chan: mem::ProfilerChan,
                time_profiler_chan: ProfilerChan,
                devtools_chan: Option<IpcSender<ScriptToDevtoolsControlMsg>>,
@@ -1667,7 +1924,9 @@ impl Window {
                layout_chan: Sender<Msg>,
                id: PipelineId,
                parent_info: Option<(PipelineId, FrameType)>,
-               window_size: Option<WindowSizeData>)
+               window_size: Option<WindowSizeData>,
+               navigation_start: u64,
+               navigation_start_precise: f64)
                -> Root<Window> {
         let layout_rpc: Box<LayoutRPC> = {
             let (rpc_send, rpc_recv) = channel();
@@ -1678,7 +1937,7 @@ impl Window {
             pipelineid: id,
             script_chan: Arc::new(Mutex::new(control_chan)),
         };
-        let current_time = time::get_time();
+        let audio_chan = constellation_chan.clone();
         let win = box Window {
             eventtarget: EventTarget::new_inherited(),
             script_chan: script_chan,
@@ -1697,8 +1956,8 @@ impl Window {
             history: Default::default(),
             browsing_context: Default::default(),
             performance: Default::default(),
-            navigation_start: (current_time.sec * 1000 + current_time.nsec as i64 / 1000000) as u64,
-            navigation_start_precise: time::precise_time_ns() as f64,
+            navigation_start: navigation_start,
+            navigation_start_precise: navigation_start_precise,
             screen: Default::default(),
             session_storage: Default::default(),
             local_storage: Default::default(),
@@ -1712,6 +1971,9 @@ impl Window {
             js_runtime: DOMRefCell::new(Some(runtime.clone())),
             resource_threads: resource_threads,
             bluetooth_thread: bluetooth_thread,
+            usb_thread: usb_thread,
+            webrtc_thread: webrtc_thread,
+            webxr_thread: webxr_thread,
             constellation_chan: constellation_chan,
             page_clip_rect: Cell::new(max_rect()),
             fragment_name: DOMRefCell::new(None),
@@ -1733,6 +1995,13 @@ impl Window {
             scroll_offsets: DOMRefCell::new(HashMap::new()),
             in_error_reporting_mode: Cell::new(false),
             console_timers: TimerSet::new(),
+            client_id: Uuid::new_v4(),
+            media_source_urls: DOMRefCell::new(HashMap::new()),
+            audio: PipelineAudio::new(box move |audible| {
+                let _ = audio_chan.send(ConstellationMsg::AudioPlaybackChanged(id, audible));
+            }),
+            dissimilar_origin_windows: DOMRefCell::new(HashMap::new()),
+            current_event: Default::default(),
         };
 
         WindowBinding::Wrap(runtime.cx(), win)
@@ -1742,6 +2011,15 @@ impl Window {
         &self.console_timers
     }
 
+    /// Describes this window to the service workers it posts messages to.
+    pub fn client_info(&self) -> ClientInfo {
+        ClientInfo {
+            id: self.client_id.to_string(),
+            url: self.get_url(),
+            pipeline_id: self.id,
+        }
+    }
+
     pub fn live_devtools_updates(&self) -> bool {
         return self.devtools_wants_updates.get();
     }
@@ -1813,6 +2091,7 @@ fn debug_reflow_events(id: PipelineId, goal: &ReflowGoal, query_type: &ReflowQue
         ReflowQueryType::ResolvedStyleQuery(_, _, _) => "\tResolvedStyleQuery",
         ReflowQueryType::OffsetParentQuery(_n) => "\tOffsetParentQuery",
         ReflowQueryType::MarginStyleQuery(_n) => "\tMarginStyleQuery",
+        ReflowQueryType::TextIndexQuery(..) => "\tTextIndexQuery",
     });
 
     debug_msg.push_str(match *reason {
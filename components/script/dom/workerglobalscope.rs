@@ -5,7 +5,11 @@
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
 use dom::bindings::codegen::Bindings::EventHandlerBinding::OnErrorEventHandlerNonNull;
 use dom::bindings::codegen::Bindings::FunctionBinding::Function;
+use dom::bindings::codegen::Bindings::ImageBitmapBinding::ImageBitmapOptions;
+use dom::bindings::codegen::Bindings::RequestBinding::{RequestInfo, RequestInit};
 use dom::bindings::codegen::Bindings::WorkerGlobalScopeBinding::WorkerGlobalScopeMethods;
+use dom::bindings::codegen::UnionTypes::HTMLImageElementOrHTMLCanvasElementOrCanvasRenderingContext2DOrBlobOrImageDataOrImageBitmap
+    as ImageBitmapSource;
 use dom::bindings::error::{Error, ErrorResult, Fallible, report_pending_exception, ErrorInfo};
 use dom::bindings::global::{GlobalRef, GlobalRoot};
 use dom::bindings::inheritance::Castable;
@@ -13,17 +17,22 @@ use dom::bindings::js::{JS, MutNullableHeap, Root};
 use dom::bindings::refcounted::Trusted;
 use dom::bindings::reflector::Reflectable;
 use dom::bindings::str::DOMString;
+use dom::bindings::structuredclone::StructuredCloneData;
 use dom::console::TimerSet;
 use dom::crypto::Crypto;
 use dom::dedicatedworkerglobalscope::DedicatedWorkerGlobalScope;
 use dom::eventtarget::EventTarget;
+use dom::imagebitmap;
+use dom::performance::Performance;
+use dom::promise::Promise;
 use dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
 use dom::window::{base64_atob, base64_btoa};
 use dom::workerlocation::WorkerLocation;
 use dom::workernavigator::WorkerNavigator;
+use fetch;
 use ipc_channel::ipc::IpcSender;
 use js::jsapi::{HandleValue, JSAutoCompartment, JSContext, JSRuntime};
-use js::jsval::UndefinedValue;
+use js::jsval::{JSVal, UndefinedValue};
 use js::rust::Runtime;
 use msg::constellation_msg::{PipelineId, ReferrerPolicy};
 use net_traits::{IpcSend, LoadOrigin};
@@ -83,8 +92,14 @@ pub struct WorkerGlobalScope {
     location: MutNullableHeap<JS<WorkerLocation>>,
     navigator: MutNullableHeap<JS<WorkerNavigator>>,
     crypto: MutNullableHeap<JS<Crypto>>,
+    performance: MutNullableHeap<JS<Performance>>,
     timers: OneshotTimers,
 
+    /// The time this worker was created, in milliseconds since the epoch.
+    navigation_start: u64,
+    /// The time this worker was created, as a `time::precise_time_ns` value.
+    navigation_start_precise: f64,
+
     #[ignore_heap_size_of = "Defined in std"]
     mem_profiler_chan: mem::ProfilerChan,
     #[ignore_heap_size_of = "Defined in std"]
@@ -126,6 +141,7 @@ impl WorkerGlobalScope {
                          timer_event_chan: IpcSender<TimerEvent>,
                          closing: Option<Arc<AtomicBool>>)
                          -> WorkerGlobalScope {
+        let current_time = ::time::get_time();
         WorkerGlobalScope {
             eventtarget: EventTarget::new_inherited(),
             next_worker_id: Cell::new(WorkerId(0)),
@@ -137,7 +153,10 @@ impl WorkerGlobalScope {
             location: Default::default(),
             navigator: Default::default(),
             crypto: Default::default(),
+            performance: Default::default(),
             timers: OneshotTimers::new(timer_event_chan, init.scheduler_chan.clone()),
+            navigation_start: (current_time.sec * 1000 + current_time.nsec as i64 / 1000000) as u64,
+            navigation_start_precise: ::time::precise_time_ns() as f64,
             mem_profiler_chan: init.mem_profiler_chan,
             time_profiler_chan: init.time_profiler_chan,
             to_devtools_sender: init.to_devtools_sender,
@@ -295,14 +314,14 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
 
         rooted!(in(self.runtime.cx()) let mut rval = UndefinedValue());
         for url in urls {
-            let (url, source) = match load_whole_resource(LoadContext::Script,
-                                                          &self.resource_threads.sender(),
-                                                          url,
-                                                          self) {
+            let loaded = match self.downcast::<ServiceWorkerGlobalScope>() {
+                Some(service_worker) => service_worker.load_imported_script(&url),
+                None => load_whole_resource(LoadContext::Script, &self.resource_threads.sender(), url, self)
+                            .map(|(metadata, bytes)| (metadata.final_url, bytes)),
+            };
+            let (url, source) = match loaded {
                 Err(_) => return Err(Error::Network),
-                Ok((metadata, bytes)) => {
-                    (metadata.final_url, String::from_utf8(bytes).unwrap())
-                }
+                Ok((url, bytes)) => (url, String::from_utf8(bytes).unwrap()),
             };
 
             let result = self.runtime.evaluate_script(
@@ -334,6 +353,35 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
         self.crypto.or_init(|| Crypto::new(GlobalRef::Worker(self)))
     }
 
+    // https://w3c.github.io/hr-time/#dom-globalperformance-performance
+    fn Performance(&self) -> Root<Performance> {
+        self.performance.or_init(|| {
+            Performance::new(GlobalRef::Worker(self), self.navigation_start,
+                             self.navigation_start_precise)
+        })
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://fetch.spec.whatwg.org/#fetch-method
+    fn Fetch(&self, input: RequestInfo, init: &RequestInit) -> Rc<Promise> {
+        fetch::Fetch(GlobalRef::Worker(self), input, init)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+    fn CreateImageBitmap(&self, image: ImageBitmapSource, options: &ImageBitmapOptions)
+                         -> Rc<Promise> {
+        imagebitmap::create_image_bitmap(GlobalRef::Worker(self), image, None, options)
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
+    fn CreateImageBitmap_(&self, image: ImageBitmapSource, sx: i32, sy: i32, sw: i32, sh: i32,
+                          options: &ImageBitmapOptions)
+                          -> Rc<Promise> {
+        imagebitmap::create_image_bitmap(GlobalRef::Worker(self), image, Some((sx, sy, sw, sh)), options)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowbase64-btoa
     fn Btoa(&self, btoa: DOMString) -> Fallible<DOMString> {
         base64_btoa(btoa)
@@ -344,6 +392,11 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
         base64_atob(atob)
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-structuredclone
+    fn StructuredClone(&self, cx: *mut JSContext, value: HandleValue) -> Fallible<JSVal> {
+        StructuredCloneData::clone_value(GlobalRef::Worker(self), cx, value)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowtimers-setinterval
     fn SetTimeout(&self, _cx: *mut JSContext, callback: Rc<Function>, timeout: i32, args: Vec<HandleValue>) -> i32 {
         self.timers.set_timeout_or_interval(GlobalRef::Worker(self),
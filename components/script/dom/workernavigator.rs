@@ -6,7 +6,7 @@ use dom::bindings::codegen::Bindings::WorkerNavigatorBinding;
 use dom::bindings::codegen::Bindings::WorkerNavigatorBinding::WorkerNavigatorMethods;
 use dom::bindings::global::GlobalRef;
 use dom::bindings::js::Root;
-use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::reflector::{Reflector, Reflectable, reflect_dom_object};
 use dom::bindings::str::DOMString;
 use dom::navigatorinfo;
 use dom::workerglobalscope::WorkerGlobalScope;
@@ -59,7 +59,7 @@ impl WorkerNavigatorMethods for WorkerNavigator {
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-useragent
     fn UserAgent(&self) -> DOMString {
-        navigatorinfo::UserAgent()
+        navigatorinfo::UserAgent(&self.global().r().get_url())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-navigator-appversion
@@ -46,7 +46,7 @@ use js::jsapi::JS_ClearPendingException;
 use js::jsval::{JSVal, NullValue, UndefinedValue};
 use msg::constellation_msg::{PipelineId, ReferrerPolicy};
 use net_traits::{CoreResourceThread, FetchMetadata, FilteredMetadata};
-use net_traits::{FetchResponseListener, LoadOrigin, NetworkError};
+use net_traits::{FetchResponseListener, IpcBytes, LoadOrigin, Metadata, NetworkError};
 use net_traits::CoreResourceMsg::Fetch;
 use net_traits::request::{CredentialsMode, Destination, RequestInit, RequestMode};
 use net_traits::trim_http_whitespace;
@@ -85,6 +85,10 @@ struct XHRContext {
     gen_id: GenerationId,
     buf: DOMRefCell<Vec<u8>>,
     sync_status: DOMRefCell<Option<ErrorResult>>,
+    /// The initial URL requested.
+    url: Url,
+    /// The unfiltered response metadata, for resource timing.
+    metadata: Option<Metadata>,
 }
 
 #[derive(Clone)]
@@ -157,7 +161,7 @@ impl XMLHttpRequest {
         //TODO - update this when referrer policy implemented for workers
         let (referrer_url, referrer_policy) = if let GlobalRef::Window(window) = global {
             let document = window.Document();
-            (Some(document.url().clone()), document.get_referrer_policy())
+            (Some(document.url()), document.get_referrer_policy())
         } else {
             (None, None)
         };
@@ -229,6 +233,10 @@ impl XMLHttpRequest {
 
             fn process_response(&mut self,
                                 metadata: Result<FetchMetadata, NetworkError>) {
+                self.metadata = metadata.as_ref().ok().map(|meta| match *meta {
+                    FetchMetadata::Unfiltered(ref m) => m.clone(),
+                    FetchMetadata::Filtered { ref unsafe_, .. } => unsafe_.clone()
+                });
                 let xhr = self.xhr.root();
                 let rv = xhr.process_headers_available(self.gen_id, metadata);
                 if rv.is_err() {
@@ -236,12 +244,19 @@ impl XMLHttpRequest {
                 }
             }
 
-            fn process_response_chunk(&mut self, mut chunk: Vec<u8>) {
-                self.buf.borrow_mut().append(&mut chunk);
+            fn process_response_chunk(&mut self, chunk: IpcBytes) {
+                self.buf.borrow_mut().extend_from_slice(&chunk);
                 self.xhr.root().process_data_available(self.gen_id, self.buf.borrow().clone());
             }
 
             fn process_response_eof(&mut self, response: Result<(), NetworkError>) {
+                if let (&Ok(()), Some(metadata)) = (&response, self.metadata.as_ref()) {
+                    if let GlobalRoot::Window(ref window) = self.xhr.root().global() {
+                        window.Performance().queue_resource_timing(&self.url,
+                                                                   DOMString::from("xmlhttprequest"),
+                                                                   metadata);
+                    }
+                }
                 let rv = match response {
                     Ok(()) => {
                         self.xhr.root().process_response_complete(self.gen_id, Ok(()))
@@ -269,7 +284,7 @@ impl XMLHttpRequest {
         ROUTER.add_route(action_receiver.to_opaque(), box move |message| {
             listener.notify_fetch(message.to().unwrap());
         });
-        core_resource_thread.send(Fetch(init, action_sender)).unwrap();
+        core_resource_thread.send(Fetch(init, action_sender, None)).unwrap();
     }
 }
 
@@ -1294,6 +1309,8 @@ impl XMLHttpRequest {
             gen_id: self.generation_id.get(),
             buf: DOMRefCell::new(vec!()),
             sync_status: DOMRefCell::new(None),
+            url: init.url.clone(),
+            metadata: None,
         }));
 
         let (script_chan, script_port) = if self.sync.get() {
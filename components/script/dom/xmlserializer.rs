@@ -0,0 +1,359 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::HTMLTemplateElementBinding::HTMLTemplateElementMethods;
+use dom::bindings::codegen::Bindings::XMLSerializerBinding;
+use dom::bindings::codegen::Bindings::XMLSerializerBinding::XMLSerializerMethods;
+use dom::bindings::error::Fallible;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::{Castable, CharacterDataTypeId, NodeTypeId};
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::characterdata::CharacterData;
+use dom::documenttype::DocumentType;
+use dom::element::Element;
+use dom::htmltemplateelement::HTMLTemplateElement;
+use dom::node::Node;
+use dom::processinginstruction::ProcessingInstruction;
+use std::collections::HashMap;
+use string_cache::{Atom, Namespace};
+
+/// Maps a namespace to every prefix that is currently bound to it.
+/// https://w3c.github.io/DOM-Parsing/#dfn-namespace-prefix-map
+pub type NamespacePrefixMap = HashMap<Namespace, Vec<String>>;
+
+/// Elements in the HTML namespace that are serialized as `<name />`.
+static VOID_ELEMENTS: &'static [&'static str] = &[
+    "area", "base", "basefont", "bgsound", "br", "col", "embed", "frame", "hr", "img",
+    "input", "keygen", "link", "menuitem", "meta", "param", "source", "track", "wbr",
+];
+
+#[dom_struct]
+pub struct XMLSerializer {
+    reflector_: Reflector,
+}
+
+impl XMLSerializer {
+    fn new_inherited() -> XMLSerializer {
+        XMLSerializer {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<XMLSerializer> {
+        reflect_dom_object(box XMLSerializer::new_inherited(),
+                           global,
+                           XMLSerializerBinding::Wrap)
+    }
+
+    // https://w3c.github.io/DOM-Parsing/#dom-xmlserializer-constructor
+    pub fn Constructor(global: GlobalRef) -> Fallible<Root<XMLSerializer>> {
+        Ok(XMLSerializer::new(global))
+    }
+}
+
+impl XMLSerializerMethods for XMLSerializer {
+    // https://w3c.github.io/DOM-Parsing/#dom-xmlserializer-serializetostring
+    fn SerializeToString(&self, root: &Node) -> Fallible<DOMString> {
+        Ok(DOMString::from(serialize_xml(root)))
+    }
+}
+
+/// Produce an XML serialization of `node`, without the require well-formed flag.
+/// https://w3c.github.io/DOM-Parsing/#dfn-xml-serialization
+pub fn serialize_xml(node: &Node) -> String {
+    let mut map = NamespacePrefixMap::new();
+    add_prefix(&mut map, &ns!(xml), "xml");
+    let mut serializer = XmlSerializer {
+        output: String::new(),
+        prefix_index: 1,
+    };
+    serializer.serialize_node(node, &ns!(), &map);
+    serializer.output
+}
+
+struct XmlSerializer {
+    output: String,
+    /// The counter used to generate fresh `nsN` prefixes.
+    prefix_index: u32,
+}
+
+impl XmlSerializer {
+    // https://w3c.github.io/DOM-Parsing/#dfn-xml-serialization-algorithm
+    fn serialize_node(&mut self, node: &Node, context_namespace: &Namespace, map: &NamespacePrefixMap) {
+        match node.type_id() {
+            NodeTypeId::Element(_) => {
+                self.serialize_element(node.downcast::<Element>().unwrap(), context_namespace, map);
+            },
+            NodeTypeId::Document(_) | NodeTypeId::DocumentFragment => {
+                for child in node.children() {
+                    self.serialize_node(&child, context_namespace, map);
+                }
+            },
+            NodeTypeId::DocumentType => {
+                self.serialize_doctype(node.downcast::<DocumentType>().unwrap());
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::Text) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                self.output.push_str(&escape_text(&data));
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::Comment) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                self.output.push_str("<!--");
+                self.output.push_str(&data);
+                self.output.push_str("-->");
+            },
+            NodeTypeId::CharacterData(CharacterDataTypeId::ProcessingInstruction) => {
+                let pi = node.downcast::<ProcessingInstruction>().unwrap();
+                let data = pi.upcast::<CharacterData>().data();
+                self.output.push_str("<?");
+                self.output.push_str(pi.target());
+                self.output.push_str(" ");
+                self.output.push_str(&data);
+                self.output.push_str("?>");
+            },
+        }
+    }
+
+    // https://w3c.github.io/DOM-Parsing/#xml-serializing-an-element-node
+    fn serialize_element(&mut self, element: &Element, context_namespace: &Namespace, map: &NamespacePrefixMap) {
+        let mut map = map.clone();
+        let mut local_prefixes = HashMap::new();
+        let local_default_namespace = record_namespace_information(element, &mut map, &mut local_prefixes);
+        let mut inherited_namespace = context_namespace.clone();
+        let mut ignore_namespace_definition_attribute = false;
+        let namespace = element.namespace();
+        let local_name = element.local_name();
+
+        self.output.push('<');
+        let qualified_name = if inherited_namespace == *namespace {
+            if local_default_namespace.is_some() {
+                ignore_namespace_definition_attribute = true;
+            }
+            let qualified_name = if *namespace == ns!(xml) {
+                format!("xml:{}", local_name)
+            } else {
+                String::from(&**local_name)
+            };
+            self.output.push_str(&qualified_name);
+            qualified_name
+        } else {
+            let prefix = element.prefix().as_ref().map(|prefix| &**prefix);
+            let candidate_prefix = match prefix {
+                Some("xmlns") => prefix.map(String::from),
+                _ => retrieve_preferred_prefix(&map, namespace, prefix),
+            };
+            let inherit_local_default = |inherited_namespace: &mut Namespace| {
+                if let Some(ref default) = local_default_namespace {
+                    if *default != ns!(xml) {
+                        *inherited_namespace = default.clone();
+                    }
+                }
+            };
+            if let Some(candidate_prefix) = candidate_prefix {
+                let qualified_name = format!("{}:{}", candidate_prefix, local_name);
+                inherit_local_default(&mut inherited_namespace);
+                self.output.push_str(&qualified_name);
+                qualified_name
+            } else if let Some(prefix) = prefix {
+                let prefix = if local_prefixes.contains_key(prefix) {
+                    generate_prefix(&mut map, &mut local_prefixes, namespace, &mut self.prefix_index)
+                } else {
+                    add_prefix(&mut map, namespace, prefix);
+                    String::from(prefix)
+                };
+                let qualified_name = format!("{}:{}", prefix, local_name);
+                self.output.push_str(&qualified_name);
+                self.output.push_str(&format!(" xmlns:{}=\"{}\"", prefix, escape_attribute(&namespace.0)));
+                inherit_local_default(&mut inherited_namespace);
+                qualified_name
+            } else {
+                let qualified_name = String::from(&**local_name);
+                self.output.push_str(&qualified_name);
+                if local_default_namespace.as_ref() != Some(namespace) {
+                    ignore_namespace_definition_attribute = true;
+                    self.output.push_str(&format!(" xmlns=\"{}\"", escape_attribute(&namespace.0)));
+                }
+                inherited_namespace = namespace.clone();
+                qualified_name
+            }
+        };
+
+        self.serialize_attributes(element, &mut map, &local_prefixes, ignore_namespace_definition_attribute);
+
+        let node = element.upcast::<Node>();
+        if node.children_count() == 0 {
+            if *namespace == ns!(html) {
+                if VOID_ELEMENTS.contains(&&**local_name) {
+                    self.output.push_str(" />");
+                    return;
+                }
+            } else {
+                self.output.push_str("/>");
+                return;
+            }
+        }
+        self.output.push('>');
+
+        if let Some(template) = element.downcast::<HTMLTemplateElement>() {
+            let content = template.Content();
+            for child in content.upcast::<Node>().children() {
+                self.serialize_node(&child, &inherited_namespace, &map);
+            }
+        } else {
+            for child in node.children() {
+                self.serialize_node(&child, &inherited_namespace, &map);
+            }
+        }
+
+        self.output.push_str("</");
+        self.output.push_str(&qualified_name);
+        self.output.push('>');
+    }
+
+    // https://w3c.github.io/DOM-Parsing/#dfn-serializing-an-element-s-attributes
+    fn serialize_attributes(&mut self,
+                            element: &Element,
+                            map: &mut NamespacePrefixMap,
+                            local_prefixes: &HashMap<String, Namespace>,
+                            ignore_namespace_definition_attribute: bool) {
+        let mut local_prefixes = local_prefixes.clone();
+        for attr in element.attrs().iter() {
+            let namespace = attr.namespace();
+            let local_name = attr.local_name();
+            let value = attr.value();
+            let mut candidate_prefix = None;
+            if *namespace != ns!() {
+                let prefix = attr.prefix().as_ref().map(|prefix| &**prefix);
+                candidate_prefix = retrieve_preferred_prefix(map, namespace, prefix);
+                if *namespace == ns!(xmlns) {
+                    let declared = Namespace(Atom::from(&**value));
+                    let redundant = match prefix {
+                        None => ignore_namespace_definition_attribute,
+                        Some(_) => match local_prefixes.get(&**local_name) {
+                            None => true,
+                            Some(ns) => *ns != declared && prefix_is_bound(map, &declared, local_name),
+                        },
+                    };
+                    if declared == ns!(xml) || redundant {
+                        continue;
+                    }
+                    if prefix == Some("xmlns") {
+                        candidate_prefix = Some(String::from("xmlns"));
+                    }
+                } else if candidate_prefix.is_none() {
+                    let prefix = generate_prefix(map, &mut local_prefixes, namespace, &mut self.prefix_index);
+                    self.output.push_str(&format!(" xmlns:{}=\"{}\"", prefix, escape_attribute(&namespace.0)));
+                    candidate_prefix = Some(prefix);
+                }
+            }
+            self.output.push(' ');
+            if let Some(prefix) = candidate_prefix {
+                self.output.push_str(&prefix);
+                self.output.push(':');
+            }
+            self.output.push_str(local_name);
+            self.output.push_str("=\"");
+            self.output.push_str(&escape_attribute(&value));
+            self.output.push('"');
+        }
+    }
+
+    // https://w3c.github.io/DOM-Parsing/#xml-serializing-a-documenttype-node
+    fn serialize_doctype(&mut self, doctype: &DocumentType) {
+        self.output.push_str("<!DOCTYPE ");
+        self.output.push_str(doctype.name());
+        if !doctype.public_id().is_empty() {
+            self.output.push_str(" PUBLIC \"");
+            self.output.push_str(doctype.public_id());
+            self.output.push('"');
+        }
+        if !doctype.system_id().is_empty() {
+            if doctype.public_id().is_empty() {
+                self.output.push_str(" SYSTEM");
+            }
+            self.output.push_str(" \"");
+            self.output.push_str(doctype.system_id());
+            self.output.push('"');
+        }
+        self.output.push('>');
+    }
+}
+
+/// Record the namespace declarations made by `element`'s attributes, returning
+/// its local default namespace if it declares one.
+/// https://w3c.github.io/DOM-Parsing/#recording-the-namespace-information
+fn record_namespace_information(element: &Element,
+                                map: &mut NamespacePrefixMap,
+                                local_prefixes: &mut HashMap<String, Namespace>)
+                                -> Option<Namespace> {
+    let mut default_namespace = None;
+    for attr in element.attrs().iter() {
+        if *attr.namespace() != ns!(xmlns) {
+            continue;
+        }
+        let declared = Namespace(Atom::from(&**attr.value()));
+        if attr.prefix().is_none() {
+            default_namespace = Some(declared);
+            continue;
+        }
+        let prefix = attr.local_name();
+        if declared == ns!(xml) || prefix_is_bound(map, &declared, prefix) {
+            continue;
+        }
+        add_prefix(map, &declared, prefix);
+        local_prefixes.insert(String::from(&**prefix), declared);
+    }
+    default_namespace
+}
+
+// https://w3c.github.io/DOM-Parsing/#dfn-retrieving-a-preferred-prefix-string
+pub fn retrieve_preferred_prefix(map: &NamespacePrefixMap,
+                                 namespace: &Namespace,
+                                 preferred: Option<&str>)
+                                 -> Option<String> {
+    map.get(namespace).and_then(|candidates| {
+        candidates.iter()
+                  .find(|candidate| Some(&***candidate) == preferred)
+                  .or(candidates.last())
+                  .cloned()
+    })
+}
+
+// https://w3c.github.io/DOM-Parsing/#dfn-generating-a-prefix
+pub fn generate_prefix(map: &mut NamespacePrefixMap,
+                       local_prefixes: &mut HashMap<String, Namespace>,
+                       namespace: &Namespace,
+                       prefix_index: &mut u32)
+                       -> String {
+    let prefix = format!("ns{}", prefix_index);
+    *prefix_index += 1;
+    add_prefix(map, namespace, &prefix);
+    local_prefixes.insert(prefix.clone(), namespace.clone());
+    prefix
+}
+
+fn add_prefix(map: &mut NamespacePrefixMap, namespace: &Namespace, prefix: &str) {
+    map.entry(namespace.clone()).or_insert(vec![]).push(String::from(prefix));
+}
+
+fn prefix_is_bound(map: &NamespacePrefixMap, namespace: &Namespace, prefix: &str) -> bool {
+    map.get(namespace).map_or(false, |prefixes| prefixes.iter().any(|p| p == prefix))
+}
+
+// https://w3c.github.io/DOM-Parsing/#dfn-serializing-an-attribute-value
+pub fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;")
+         .replace('"', "&quot;")
+         .replace('<', "&lt;")
+         .replace('>', "&gt;")
+}
+
+// https://w3c.github.io/DOM-Parsing/#xml-serializing-a-text-node
+pub fn escape_text(data: &str) -> String {
+    data.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::XPathEvaluatorBinding;
+use dom::bindings::codegen::Bindings::XPathEvaluatorBinding::XPathEvaluatorMethods;
+use dom::bindings::codegen::Bindings::XPathExpressionBinding::XPathExpressionMethods;
+use dom::bindings::codegen::Bindings::XPathNSResolverBinding::XPathNSResolver;
+use dom::bindings::error::Fallible;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::node::Node;
+use dom::xpathexpression::XPathExpression;
+use dom::xpathresult::XPathResult;
+use std::rc::Rc;
+
+// https://dom.spec.whatwg.org/#interface-xpathevaluator
+#[dom_struct]
+pub struct XPathEvaluator {
+    reflector_: Reflector,
+}
+
+impl XPathEvaluator {
+    fn new_inherited() -> XPathEvaluator {
+        XPathEvaluator {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<XPathEvaluator> {
+        reflect_dom_object(box XPathEvaluator::new_inherited(),
+                           global,
+                           XPathEvaluatorBinding::Wrap)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluator-xpathevaluator
+    pub fn Constructor(global: GlobalRef) -> Fallible<Root<XPathEvaluator>> {
+        Ok(XPathEvaluator::new(global))
+    }
+}
+
+/// The `evaluate()` method shared by `XPathEvaluator` and `Document`.
+/// https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-evaluate
+pub fn evaluate(global: GlobalRef,
+                expression: DOMString,
+                context_node: &Node,
+                resolver: Option<Rc<XPathNSResolver>>,
+                type_: u16,
+                result: Option<&XPathResult>)
+                -> Fallible<Root<XPathResult>> {
+    let expression = try!(XPathExpression::create(global, expression, resolver));
+    expression.Evaluate(context_node, type_, result)
+}
+
+impl XPathEvaluatorMethods for XPathEvaluator {
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-createexpression
+    fn CreateExpression(&self,
+                        expression: DOMString,
+                        resolver: Option<Rc<XPathNSResolver>>)
+                        -> Fallible<Root<XPathExpression>> {
+        XPathExpression::create(self.global().r(), expression, resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-creatensresolver
+    fn CreateNSResolver(&self, node_resolver: &Node) -> Root<Node> {
+        Root::from_ref(node_resolver)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-evaluate
+    fn Evaluate(&self,
+                expression: DOMString,
+                context_node: &Node,
+                resolver: Option<Rc<XPathNSResolver>>,
+                type_: u16,
+                result: Option<&XPathResult>)
+                -> Fallible<Root<XPathResult>> {
+        evaluate(self.global().r(), expression, context_node, resolver, type_, result)
+    }
+}
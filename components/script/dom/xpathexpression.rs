@@ -0,0 +1,82 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::callback::ExceptionHandling::Rethrow;
+use dom::bindings::codegen::Bindings::XPathExpressionBinding;
+use dom::bindings::codegen::Bindings::XPathExpressionBinding::XPathExpressionMethods;
+use dom::bindings::codegen::Bindings::XPathNSResolverBinding::XPathNSResolver;
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::node::Node;
+use dom::xpathresult::XPathResult;
+use std::rc::Rc;
+use xpath::eval;
+use xpath::parser::{self, Expr, NameTest};
+
+// https://dom.spec.whatwg.org/#interface-xpathexpression
+#[dom_struct]
+pub struct XPathExpression {
+    reflector_: Reflector,
+    #[ignore_heap_size_of = "Defined in the xpath module"]
+    expr: Expr,
+}
+
+impl XPathExpression {
+    fn new_inherited(expr: Expr) -> XPathExpression {
+        XPathExpression {
+            reflector_: Reflector::new(),
+            expr: expr,
+        }
+    }
+
+    /// Parse `expression` and resolve the namespace of each of its prefixes
+    /// with `resolver`, failing if any of them is unbound.
+    /// https://dom.spec.whatwg.org/#dom-xpathevaluatorbase-createexpression
+    pub fn create(global: GlobalRef,
+                  expression: DOMString,
+                  resolver: Option<Rc<XPathNSResolver>>)
+                  -> Fallible<Root<XPathExpression>> {
+        let mut expr = try!(parser::parse(&expression).map_err(|_| Error::Syntax));
+        try!(parser::for_each_prefixed_name_test(&mut expr, &mut |test: &mut NameTest| {
+            let prefix = test.prefix.clone().unwrap();
+            let namespace = if prefix == "xml" {
+                Some(DOMString::from(&*ns!(xml).0))
+            } else {
+                match resolver {
+                    Some(ref resolver) => {
+                        try!(resolver.LookupNamespaceURI__(Some(DOMString::from(prefix)), Rethrow))
+                    },
+                    None => None,
+                }
+            };
+            match namespace {
+                Some(ref namespace) if !namespace.is_empty() => {
+                    test.namespace = Some(String::from(&**namespace));
+                    Ok(())
+                },
+                _ => Err(Error::Namespace),
+            }
+        }));
+        Ok(reflect_dom_object(box XPathExpression::new_inherited(expr),
+                              global,
+                              XPathExpressionBinding::Wrap))
+    }
+}
+
+impl XPathExpressionMethods for XPathExpression {
+    // https://dom.spec.whatwg.org/#dom-xpathexpression-evaluate
+    fn Evaluate(&self,
+                context_node: &Node,
+                type_: u16,
+                _result: Option<&XPathResult>)
+                -> Fallible<Root<XPathResult>> {
+        // A new result is always returned rather than the one passed in being
+        // reused, as the specification allows.
+        let value = try!(eval::evaluate(&self.expr, context_node));
+        XPathResult::new(self.global().r(), type_, value, &context_node.owner_doc())
+    }
+}
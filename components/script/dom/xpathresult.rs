@@ -0,0 +1,181 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::XPathResultBinding;
+use dom::bindings::codegen::Bindings::XPathResultBinding::XPathResultConstants;
+use dom::bindings::codegen::Bindings::XPathResultBinding::XPathResultMethods;
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::bindings::str::DOMString;
+use dom::document::Document;
+use dom::node::Node;
+use std::cell::Cell;
+use xpath::eval::{Value, XPathNode};
+
+// https://dom.spec.whatwg.org/#interface-xpathresult
+#[dom_struct]
+pub struct XPathResult {
+    reflector_: Reflector,
+    result_type: u16,
+    number: f64,
+    string: DOMString,
+    boolean: bool,
+    nodes: Vec<JS<Node>>,
+    iterator_position: Cell<usize>,
+    /// The document the nodes were found in, and its version at that point.
+    /// Iterators are invalidated by any later mutation of the document.
+    document: JS<Document>,
+    version: u64,
+}
+
+impl XPathResult {
+    #[allow(unrooted_must_root)]
+    fn new_inherited(result_type: u16,
+                     value: &Value,
+                     nodes: Vec<Root<Node>>,
+                     document: &Document)
+                     -> XPathResult {
+        XPathResult {
+            reflector_: Reflector::new(),
+            result_type: result_type,
+            number: if result_type == XPathResultConstants::NUMBER_TYPE { value.number() } else { 0. },
+            string: if result_type == XPathResultConstants::STRING_TYPE {
+                DOMString::from(value.string())
+            } else {
+                DOMString::new()
+            },
+            boolean: result_type == XPathResultConstants::BOOLEAN_TYPE && value.boolean(),
+            nodes: nodes.iter().map(|node| JS::from_ref(&**node)).collect(),
+            iterator_position: Cell::new(0),
+            document: JS::from_ref(document),
+            version: document.upcast::<Node>().inclusive_descendants_version(),
+        }
+    }
+
+    /// Create the result of evaluating an expression to `value`, converted to
+    /// the requested `result_type`.
+    pub fn new(global: GlobalRef,
+               result_type: u16,
+               value: Value,
+               document: &Document)
+               -> Fallible<Root<XPathResult>> {
+        let result_type = if result_type != XPathResultConstants::ANY_TYPE {
+            if result_type > XPathResultConstants::FIRST_ORDERED_NODE_TYPE {
+                return Err(Error::NotSupported);
+            }
+            result_type
+        } else {
+            match value {
+                Value::NodeSet(_) => XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE,
+                Value::Number(_) => XPathResultConstants::NUMBER_TYPE,
+                Value::String(_) => XPathResultConstants::STRING_TYPE,
+                Value::Boolean(_) => XPathResultConstants::BOOLEAN_TYPE,
+            }
+        };
+        let nodes: Vec<Root<Node>> = match value {
+            // Attributes are nodes to XPath but not to this DOM, so they can't
+            // be returned and are left out of the result.
+            Value::NodeSet(ref nodes) => nodes.iter().filter_map(|node| match *node {
+                XPathNode::Node(ref node) => Some(node.clone()),
+                XPathNode::Attribute(_) => None,
+            }).collect(),
+            _ if result_type >= XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE => {
+                return Err(Error::Type("The expression does not evaluate to a node-set".to_owned()));
+            },
+            _ => vec![],
+        };
+        let nodes = match result_type {
+            XPathResultConstants::ANY_UNORDERED_NODE_TYPE |
+            XPathResultConstants::FIRST_ORDERED_NODE_TYPE => nodes.into_iter().take(1).collect(),
+            _ => nodes,
+        };
+        Ok(reflect_dom_object(box XPathResult::new_inherited(result_type, &value, nodes, document),
+                              global,
+                              XPathResultBinding::Wrap))
+    }
+
+    fn is_iterator(&self) -> bool {
+        self.result_type == XPathResultConstants::UNORDERED_NODE_ITERATOR_TYPE ||
+        self.result_type == XPathResultConstants::ORDERED_NODE_ITERATOR_TYPE
+    }
+
+    fn is_snapshot(&self) -> bool {
+        self.result_type == XPathResultConstants::UNORDERED_NODE_SNAPSHOT_TYPE ||
+        self.result_type == XPathResultConstants::ORDERED_NODE_SNAPSHOT_TYPE
+    }
+
+    fn check_type(&self, matches: bool) -> Fallible<()> {
+        if matches {
+            Ok(())
+        } else {
+            Err(Error::Type("The result is not of the requested type".to_owned()))
+        }
+    }
+}
+
+impl XPathResultMethods for XPathResult {
+    // https://dom.spec.whatwg.org/#dom-xpathresult-resulttype
+    fn ResultType(&self) -> u16 {
+        self.result_type
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-numbervalue
+    fn GetNumberValue(&self) -> Fallible<f64> {
+        try!(self.check_type(self.result_type == XPathResultConstants::NUMBER_TYPE));
+        Ok(self.number)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-stringvalue
+    fn GetStringValue(&self) -> Fallible<DOMString> {
+        try!(self.check_type(self.result_type == XPathResultConstants::STRING_TYPE));
+        Ok(self.string.clone())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-booleanvalue
+    fn GetBooleanValue(&self) -> Fallible<bool> {
+        try!(self.check_type(self.result_type == XPathResultConstants::BOOLEAN_TYPE));
+        Ok(self.boolean)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-singlenodevalue
+    fn GetSingleNodeValue(&self) -> Fallible<Option<Root<Node>>> {
+        try!(self.check_type(self.result_type == XPathResultConstants::ANY_UNORDERED_NODE_TYPE ||
+                             self.result_type == XPathResultConstants::FIRST_ORDERED_NODE_TYPE));
+        Ok(self.nodes.first().map(|node| Root::from_ref(&**node)))
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-invaliditeratorstate
+    fn InvalidIteratorState(&self) -> bool {
+        self.is_iterator() && self.document.upcast::<Node>().inclusive_descendants_version() != self.version
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-snapshotlength
+    fn GetSnapshotLength(&self) -> Fallible<u32> {
+        try!(self.check_type(self.is_snapshot()));
+        Ok(self.nodes.len() as u32)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-iteratenext
+    fn IterateNext(&self) -> Fallible<Option<Root<Node>>> {
+        try!(self.check_type(self.is_iterator()));
+        if self.InvalidIteratorState() {
+            return Err(Error::InvalidState);
+        }
+        let position = self.iterator_position.get();
+        let node = self.nodes.get(position).map(|node| Root::from_ref(&**node));
+        if node.is_some() {
+            self.iterator_position.set(position + 1);
+        }
+        Ok(node)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-xpathresult-snapshotitem
+    fn SnapshotItem(&self, index: u32) -> Fallible<Option<Root<Node>>> {
+        try!(self.check_type(self.is_snapshot()));
+        Ok(self.nodes.get(index as usize).map(|node| Root::from_ref(&**node)))
+    }
+}
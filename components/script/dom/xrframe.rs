@@ -0,0 +1,86 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::webxr::{XRFrameData, XRRigidTransformData};
+use canvas_traits::webxr::XREye as DeviceEye;
+use dom::bindings::codegen::Bindings::XRFrameBinding;
+use dom::bindings::codegen::Bindings::XRFrameBinding::XRFrameMethods;
+use dom::bindings::codegen::Bindings::XRSessionBinding::XRReferenceSpaceType;
+use dom::bindings::codegen::Bindings::XRViewBinding::XREye;
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::xrreferencespace::XRReferenceSpace;
+use dom::xrrigidtransform::XRRigidTransform;
+use dom::xrsession::XRSession;
+use dom::xrview::XRView;
+use dom::xrviewerpose::XRViewerPose;
+use std::cell::Cell;
+
+// https://immersive-web.github.io/webxr/#xrframe
+#[dom_struct]
+pub struct XRFrame {
+    reflector_: Reflector,
+    session: JS<XRSession>,
+    #[ignore_heap_size_of = "Defined in canvas_traits"]
+    data: XRFrameData,
+    /// https://immersive-web.github.io/webxr/#xrframe-active
+    active: Cell<bool>,
+}
+
+impl XRFrame {
+    fn new_inherited(session: &XRSession, data: XRFrameData) -> XRFrame {
+        XRFrame {
+            reflector_: Reflector::new(),
+            session: JS::from_ref(session),
+            data: data,
+            active: Cell::new(true),
+        }
+    }
+
+    pub fn new(global: GlobalRef, session: &XRSession, data: XRFrameData) -> Root<XRFrame> {
+        reflect_dom_object(box XRFrame::new_inherited(session, data),
+                           global,
+                           XRFrameBinding::Wrap)
+    }
+
+    /// Called once the frame's animation frame callbacks have run.
+    pub fn set_inactive(&self) {
+        self.active.set(false);
+    }
+}
+
+impl XRFrameMethods for XRFrame {
+    // https://immersive-web.github.io/webxr/#dom-xrframe-session
+    fn Session(&self) -> Root<XRSession> {
+        Root::from_ref(&*self.session)
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrframe-getviewerpose
+    fn GetViewerPose(&self, reference_space: &XRReferenceSpace) -> Fallible<Option<Root<XRViewerPose>>> {
+        // Step 2.
+        if !self.active.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Poses are reported by the device relative to its local space.
+        let local_to_space = match reference_space.space_type() {
+            XRReferenceSpaceType::Local => XRRigidTransformData::identity(),
+            XRReferenceSpaceType::Viewer => self.data.viewer.inverse(),
+        };
+        let global = self.global();
+        let views: Vec<Root<XRView>> = self.data.views.iter().map(|view| {
+            let eye = match view.eye {
+                DeviceEye::None => XREye::None,
+                DeviceEye::Left => XREye::Left,
+                DeviceEye::Right => XREye::Right,
+            };
+            let transform = XRRigidTransform::new(global.r(), local_to_space.then(&view.transform));
+            XRView::new(global.r(), eye, &view.projection, &transform)
+        }).collect();
+        let transform = XRRigidTransform::new(global.r(), local_to_space.then(&self.data.viewer));
+        Ok(Some(XRViewerPose::new(global.r(), &transform, &views)))
+    }
+}
@@ -0,0 +1,31 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::XRPoseBinding::XRPoseMethods;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::Reflector;
+use dom::xrrigidtransform::XRRigidTransform;
+
+// https://immersive-web.github.io/webxr/#xrpose
+#[dom_struct]
+pub struct XRPose {
+    reflector_: Reflector,
+    transform: JS<XRRigidTransform>,
+}
+
+impl XRPose {
+    pub fn new_inherited(transform: &XRRigidTransform) -> XRPose {
+        XRPose {
+            reflector_: Reflector::new(),
+            transform: JS::from_ref(transform),
+        }
+    }
+}
+
+impl XRPoseMethods for XRPose {
+    // https://immersive-web.github.io/webxr/#dom-xrpose-transform
+    fn Transform(&self) -> Root<XRRigidTransform> {
+        Root::from_ref(&*self.transform)
+    }
+}
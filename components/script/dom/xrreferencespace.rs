@@ -0,0 +1,36 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::XRReferenceSpaceBinding;
+use dom::bindings::codegen::Bindings::XRSessionBinding::XRReferenceSpaceType;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::reflect_dom_object;
+use dom::xrspace::XRSpace;
+
+// https://immersive-web.github.io/webxr/#xrreferencespace
+#[dom_struct]
+pub struct XRReferenceSpace {
+    xrspace: XRSpace,
+    space_type: XRReferenceSpaceType,
+}
+
+impl XRReferenceSpace {
+    fn new_inherited(space_type: XRReferenceSpaceType) -> XRReferenceSpace {
+        XRReferenceSpace {
+            xrspace: XRSpace::new_inherited(),
+            space_type: space_type,
+        }
+    }
+
+    pub fn new(global: GlobalRef, space_type: XRReferenceSpaceType) -> Root<XRReferenceSpace> {
+        reflect_dom_object(box XRReferenceSpace::new_inherited(space_type),
+                           global,
+                           XRReferenceSpaceBinding::Wrap)
+    }
+
+    pub fn space_type(&self) -> XRReferenceSpaceType {
+        self.space_type
+    }
+}
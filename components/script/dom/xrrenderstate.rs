@@ -0,0 +1,66 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::XRRenderStateBinding;
+use dom::bindings::codegen::Bindings::XRRenderStateBinding::XRRenderStateMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::num::Finite;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::xrwebgllayer::XRWebGLLayer;
+
+// https://immersive-web.github.io/webxr/#xrrenderstate
+#[dom_struct]
+pub struct XRRenderState {
+    reflector_: Reflector,
+    depth_near: f64,
+    depth_far: f64,
+    base_layer: Option<JS<XRWebGLLayer>>,
+}
+
+impl XRRenderState {
+    fn new_inherited(depth_near: f64, depth_far: f64, base_layer: Option<&XRWebGLLayer>) -> XRRenderState {
+        XRRenderState {
+            reflector_: Reflector::new(),
+            depth_near: depth_near,
+            depth_far: depth_far,
+            base_layer: base_layer.map(JS::from_ref),
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               depth_near: f64,
+               depth_far: f64,
+               base_layer: Option<&XRWebGLLayer>)
+               -> Root<XRRenderState> {
+        reflect_dom_object(box XRRenderState::new_inherited(depth_near, depth_far, base_layer),
+                           global,
+                           XRRenderStateBinding::Wrap)
+    }
+
+    pub fn depth_near(&self) -> f64 {
+        self.depth_near
+    }
+
+    pub fn depth_far(&self) -> f64 {
+        self.depth_far
+    }
+}
+
+impl XRRenderStateMethods for XRRenderState {
+    // https://immersive-web.github.io/webxr/#dom-xrrenderstate-depthnear
+    fn DepthNear(&self) -> Finite<f64> {
+        Finite::wrap(self.depth_near)
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrrenderstate-depthfar
+    fn DepthFar(&self) -> Finite<f64> {
+        Finite::wrap(self.depth_far)
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrrenderstate-baselayer
+    fn GetBaseLayer(&self) -> Option<Root<XRWebGLLayer>> {
+        self.base_layer.as_ref().map(|layer| Root::from_ref(&**layer))
+    }
+}
@@ -0,0 +1,95 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::webxr::XRRigidTransformData;
+use core::nonzero::NonZero;
+use dom::bindings::codegen::Bindings::XRRigidTransformBinding;
+use dom::bindings::codegen::Bindings::XRRigidTransformBinding::XRRigidTransformMethods;
+use dom::bindings::conversions::array_buffer_view_data;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, MutNullableHeap, Root};
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::dompointreadonly::DOMPointReadOnly;
+use js::jsapi::{JSContext, JSObject, JS_NewFloat32Array};
+
+/// Creates a `Float32Array` holding a copy of `values`.
+#[allow(unsafe_code)]
+pub fn new_float32_array(cx: *mut JSContext, values: &[f32]) -> NonZero<*mut JSObject> {
+    unsafe {
+        let array = JS_NewFloat32Array(cx, values.len() as u32);
+        assert!(!array.is_null());
+        array_buffer_view_data::<f32>(array).unwrap().copy_from_slice(values);
+        NonZero::new(array)
+    }
+}
+
+// https://immersive-web.github.io/webxr/#xrrigidtransform
+#[dom_struct]
+pub struct XRRigidTransform {
+    reflector_: Reflector,
+    #[ignore_heap_size_of = "Defined in canvas_traits"]
+    transform: XRRigidTransformData,
+    position: MutNullableHeap<JS<DOMPointReadOnly>>,
+    orientation: MutNullableHeap<JS<DOMPointReadOnly>>,
+    inverse: MutNullableHeap<JS<XRRigidTransform>>,
+}
+
+impl XRRigidTransform {
+    fn new_inherited(transform: XRRigidTransformData) -> XRRigidTransform {
+        XRRigidTransform {
+            reflector_: Reflector::new(),
+            transform: transform,
+            position: Default::default(),
+            orientation: Default::default(),
+            inverse: Default::default(),
+        }
+    }
+
+    pub fn new(global: GlobalRef, transform: XRRigidTransformData) -> Root<XRRigidTransform> {
+        reflect_dom_object(box XRRigidTransform::new_inherited(transform),
+                           global,
+                           XRRigidTransformBinding::Wrap)
+    }
+
+    pub fn transform(&self) -> &XRRigidTransformData {
+        &self.transform
+    }
+}
+
+impl XRRigidTransformMethods for XRRigidTransform {
+    // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-position
+    fn Position(&self) -> Root<DOMPointReadOnly> {
+        self.position.or_init(|| {
+            let position = &self.transform.position;
+            DOMPointReadOnly::new(self.global().r(),
+                                  position[0] as f64,
+                                  position[1] as f64,
+                                  position[2] as f64,
+                                  1.)
+        })
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-orientation
+    fn Orientation(&self) -> Root<DOMPointReadOnly> {
+        self.orientation.or_init(|| {
+            let orientation = &self.transform.orientation;
+            DOMPointReadOnly::new(self.global().r(),
+                                  orientation[0] as f64,
+                                  orientation[1] as f64,
+                                  orientation[2] as f64,
+                                  orientation[3] as f64)
+        })
+    }
+
+    // FIXME: the spec requires the same array to be returned every time.
+    // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-matrix
+    fn Matrix(&self, cx: *mut JSContext) -> NonZero<*mut JSObject> {
+        new_float32_array(cx, &self.transform.to_matrix())
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrrigidtransform-inverse
+    fn Inverse(&self) -> Root<XRRigidTransform> {
+        self.inverse.or_init(|| XRRigidTransform::new(self.global().r(), self.transform.inverse()))
+    }
+}
@@ -0,0 +1,307 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::webxr::{WebXRMsg, WebXRResult, XREye, XRFrameData, XRRigidTransformData};
+use canvas_traits::webxr::{XRSessionId, XRViewData, perspective_projection};
+use dom::bindings::callback::ExceptionHandling;
+use dom::bindings::cell::DOMRefCell;
+use dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMethods;
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::codegen::Bindings::XRRenderStateBinding::XRRenderStateMethods;
+use dom::bindings::codegen::Bindings::XRSessionBinding;
+use dom::bindings::codegen::Bindings::XRSessionBinding::{XRFrameRequestCallback, XRReferenceSpaceType};
+use dom::bindings::codegen::Bindings::XRSessionBinding::{XRRenderStateInit, XRSessionMethods};
+use dom::bindings::error::{Error, ErrorResult};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::inheritance::Castable;
+use dom::bindings::js::{JS, MutHeap, MutNullableHeap, OptionalRootedReference, Root};
+use dom::bindings::refcounted::Trusted;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::eventtarget::EventTarget;
+use dom::promise::Promise;
+use dom::xrframe::XRFrame;
+use dom::xrreferencespace::XRReferenceSpace;
+use dom::xrrenderstate::XRRenderState;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use script_thread::Runnable;
+use std::cell::Cell;
+use std::f32::consts::PI;
+use std::mem;
+use std::rc::Rc;
+use string_cache::Atom;
+use task_source::TaskSource;
+
+/// The vertical field of view of the single view of an inline session.
+const INLINE_VERTICAL_FOV: f32 = PI / 2.;
+
+// https://immersive-web.github.io/webxr/#xrsession
+#[dom_struct]
+pub struct XRSession {
+    eventtarget: EventTarget,
+    /// The session on the WebXR thread, for immersive sessions. Inline
+    /// sessions don't need a device, and are driven by the document's
+    /// animation frames.
+    session_id: Option<XRSessionId>,
+    /// https://immersive-web.github.io/webxr/#dom-xrsession-renderstate
+    render_state: MutHeap<JS<XRRenderState>>,
+    /// https://immersive-web.github.io/webxr/#xrsession-pending-render-state
+    pending_render_state: MutNullableHeap<JS<XRRenderState>>,
+    /// https://immersive-web.github.io/webxr/#xrsession-list-of-animation-frame-callbacks
+    #[ignore_heap_size_of = "can't measure Rc values"]
+    frame_callbacks: DOMRefCell<Vec<(i32, Option<Rc<XRFrameRequestCallback>>)>>,
+    frame_callback_handle: Cell<i32>,
+    /// Whether the next frame has been requested.
+    frame_requested: Cell<bool>,
+    /// https://immersive-web.github.io/webxr/#xrsession-ended
+    ended: Cell<bool>,
+}
+
+impl XRSession {
+    fn new_inherited(session_id: Option<XRSessionId>, render_state: &XRRenderState) -> XRSession {
+        XRSession {
+            eventtarget: EventTarget::new_inherited(),
+            session_id: session_id,
+            render_state: MutHeap::new(render_state),
+            pending_render_state: Default::default(),
+            frame_callbacks: DOMRefCell::new(vec![]),
+            frame_callback_handle: Cell::new(0),
+            frame_requested: Cell::new(false),
+            ended: Cell::new(false),
+        }
+    }
+
+    pub fn new(global: GlobalRef, session_id: Option<XRSessionId>) -> Root<XRSession> {
+        // https://immersive-web.github.io/webxr/#initialize-the-render-state
+        let render_state = XRRenderState::new(global, 0.1, 1000., None);
+        reflect_dom_object(box XRSession::new_inherited(session_id, &render_state),
+                           global,
+                           XRSessionBinding::Wrap)
+    }
+
+    pub fn is_ended(&self) -> bool {
+        self.ended.get()
+    }
+
+    /// Asks for the next frame: from the headset for immersive sessions, and
+    /// from the compositor's animation ticks for inline ones.
+    fn request_frame(&self) {
+        if self.frame_requested.get() || self.ended.get() {
+            return;
+        }
+        self.frame_requested.set(true);
+
+        let global = self.global();
+        let window = global.r().as_window();
+        let session = Trusted::new(self);
+        match self.session_id {
+            Some(session_id) => {
+                let (sender, receiver) = ipc::channel().unwrap();
+                let task_source = window.dom_manipulation_task_source();
+                let wrapper = window.get_runnable_wrapper();
+                ROUTER.add_route(receiver.to_opaque(), box move |message| {
+                    let runnable = box XRFrameRunnable {
+                        session: session.clone(),
+                        frame: message.to().unwrap(),
+                    };
+                    let _ = task_source.queue_with_wrapper(runnable, &wrapper);
+                });
+                window.webxr_thread().send(WebXRMsg::RequestFrame(session_id, sender)).unwrap();
+            },
+            None => {
+                let callback = move |_now: f64| {
+                    session.root().run_frame(None);
+                };
+                window.Document().request_animation_frame(Box::new(callback));
+            },
+        }
+    }
+
+    /// The single view of an inline session, through the base layer's canvas.
+    fn inline_frame(&self) -> XRFrameData {
+        let render_state = self.render_state.get();
+        let aspect = render_state.GetBaseLayer().map_or(1., |layer| layer.aspect_ratio());
+        XRFrameData {
+            viewer: XRRigidTransformData::identity(),
+            views: vec![XRViewData {
+                eye: XREye::None,
+                projection: perspective_projection(INLINE_VERTICAL_FOV,
+                                                   aspect,
+                                                   render_state.depth_near() as f32,
+                                                   render_state.depth_far() as f32),
+                transform: XRRigidTransformData::identity(),
+            }],
+        }
+    }
+
+    /// Runs the animation frame callbacks with the poses of a new frame, which
+    /// is `None` for inline sessions.
+    /// https://immersive-web.github.io/webxr/#xr-animation-frame
+    #[allow(unrooted_must_root)]
+    fn run_frame(&self, frame: Option<XRFrameData>) {
+        self.frame_requested.set(false);
+        if self.ended.get() {
+            return;
+        }
+
+        // https://immersive-web.github.io/webxr/#apply-the-pending-render-state
+        if let Some(pending_render_state) = self.pending_render_state.get() {
+            self.render_state.set(&pending_render_state);
+            self.pending_render_state.set(None);
+        }
+
+        // Step 2: frames can't be rendered until the page provides a layer.
+        let base_layer = match self.render_state.get().GetBaseLayer() {
+            Some(base_layer) => base_layer,
+            None => {
+                if !self.frame_callbacks.borrow().is_empty() {
+                    self.request_frame();
+                }
+                return;
+            },
+        };
+
+        let frame = frame.unwrap_or_else(|| self.inline_frame());
+        let global = self.global();
+        let window = global.r().as_window();
+        let now = window.Performance().Now();
+        let frame = XRFrame::new(global.r(), self, frame);
+
+        // Callbacks requested while these run are called on the next frame.
+        let callbacks = mem::replace(&mut *self.frame_callbacks.borrow_mut(), vec![]);
+        for (_, callback) in callbacks {
+            if let Some(callback) = callback {
+                let _ = callback.Call__(now, &frame, ExceptionHandling::Report);
+            }
+        }
+        frame.set_inactive();
+
+        // Inline sessions are composited with the rest of the page, like any
+        // other WebGL canvas.
+        if let Some(session_id) = self.session_id {
+            let renderer = base_layer.context().ipc_renderer();
+            window.webxr_thread().send(WebXRMsg::SubmitFrame(session_id, renderer)).unwrap();
+        }
+    }
+
+    /// https://immersive-web.github.io/webxr/#shut-down-the-session
+    fn shut_down(&self) {
+        if self.ended.get() {
+            return;
+        }
+        // Step 2.
+        self.ended.set(true);
+        self.frame_callbacks.borrow_mut().clear();
+
+        let global = self.global();
+        let window = global.r().as_window();
+        // Step 4.
+        if let Some(session_id) = self.session_id {
+            window.webxr_thread().send(WebXRMsg::EndSession(session_id)).unwrap();
+        }
+        // Step 5.
+        window.dom_manipulation_task_source().queue_simple_event(self.upcast(), Atom::from("end"), window);
+    }
+}
+
+impl XRSessionMethods for XRSession {
+    // https://immersive-web.github.io/webxr/#dom-xrsession-renderstate
+    fn RenderState(&self) -> Root<XRRenderState> {
+        self.render_state.get()
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrsession-updaterenderstate
+    fn UpdateRenderState(&self, state: &XRRenderStateInit) -> ErrorResult {
+        // Step 2.
+        if self.ended.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 3.
+        if let Some(Some(ref base_layer)) = state.baseLayer {
+            if base_layer.session() as *const XRSession != self as *const XRSession {
+                return Err(Error::InvalidState);
+            }
+        }
+
+        // Steps 5-9.
+        let current = self.pending_render_state.get().unwrap_or_else(|| self.render_state.get());
+        let depth_near = state.depthNear.map_or(current.depth_near(), |depth_near| *depth_near);
+        let depth_far = state.depthFar.map_or(current.depth_far(), |depth_far| *depth_far);
+        let base_layer = match state.baseLayer {
+            Some(ref base_layer) => base_layer.as_ref().map(|layer| Root::from_ref(&**layer)),
+            None => current.GetBaseLayer(),
+        };
+        let pending = XRRenderState::new(self.global().r(), depth_near, depth_far, base_layer.r());
+        self.pending_render_state.set(Some(&pending));
+        Ok(())
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://immersive-web.github.io/webxr/#dom-xrsession-requestreferencespace
+    fn RequestReferenceSpace(&self, space_type: XRReferenceSpaceType) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let cx = global.r().get_cx();
+        if self.ended.get() {
+            promise.reject_error(cx, Error::InvalidState);
+        } else {
+            let space = XRReferenceSpace::new(global.r(), space_type);
+            promise.resolve_native(cx, &space);
+        }
+        promise
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrsession-requestanimationframe
+    fn RequestAnimationFrame(&self, callback: Rc<XRFrameRequestCallback>) -> i32 {
+        // Steps 2-3.
+        let handle = self.frame_callback_handle.get() + 1;
+        self.frame_callback_handle.set(handle);
+        // Step 4.
+        self.frame_callbacks.borrow_mut().push((handle, Some(callback)));
+        self.request_frame();
+        handle
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrsession-cancelanimationframe
+    fn CancelAnimationFrame(&self, handle: i32) {
+        let mut callbacks = self.frame_callbacks.borrow_mut();
+        if let Some(pair) = callbacks.iter_mut().find(|pair| pair.0 == handle) {
+            pair.1 = None;
+        }
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://immersive-web.github.io/webxr/#dom-xrsession-end
+    fn End(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        self.shut_down();
+        promise.resolve_native(global.r().get_cx(), &());
+        promise
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrsession-onend
+    event_handler!(end, GetOnend, SetOnend);
+}
+
+/// Delivers a frame from the headset to an immersive session.
+struct XRFrameRunnable {
+    session: Trusted<XRSession>,
+    frame: WebXRResult<XRFrameData>,
+}
+
+impl Runnable for XRFrameRunnable {
+    fn name(&self) -> &'static str { "XRFrameRunnable" }
+
+    fn handler(self: Box<Self>) {
+        let session = self.session.root();
+        match self.frame {
+            Ok(frame) => session.run_frame(Some(frame)),
+            // The session was ended on the WebXR thread.
+            Err(_) => session.frame_requested.set(false),
+        }
+    }
+}
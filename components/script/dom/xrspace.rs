@@ -0,0 +1,19 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::eventtarget::EventTarget;
+
+// https://immersive-web.github.io/webxr/#xrspace
+#[dom_struct]
+pub struct XRSpace {
+    eventtarget: EventTarget,
+}
+
+impl XRSpace {
+    pub fn new_inherited() -> XRSpace {
+        XRSpace {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::webxr::{WebXRError, WebXRMsg, XRSessionMode as DeviceSessionMode};
+use dom::bindings::codegen::Bindings::XRSystemBinding;
+use dom::bindings::codegen::Bindings::XRSystemBinding::{XRSessionMode, XRSystemMethods};
+use dom::bindings::error::Error;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflectable, reflect_dom_object};
+use dom::eventtarget::EventTarget;
+use dom::promise::Promise;
+use dom::xrsession::XRSession;
+use ipc_channel::ipc;
+use std::rc::Rc;
+
+// https://immersive-web.github.io/webxr/#xrsystem-interface
+#[dom_struct]
+pub struct XRSystem {
+    eventtarget: EventTarget,
+}
+
+impl XRSystem {
+    fn new_inherited() -> XRSystem {
+        XRSystem {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(global: GlobalRef) -> Root<XRSystem> {
+        reflect_dom_object(box XRSystem::new_inherited(),
+                           global,
+                           XRSystemBinding::Wrap)
+    }
+
+    fn send(&self, msg: WebXRMsg) {
+        self.global().r().as_window().webxr_thread().send(msg).unwrap();
+    }
+}
+
+impl From<WebXRError> for Error {
+    fn from(error: WebXRError) -> Self {
+        match error {
+            WebXRError::NotSupported => Error::NotSupported,
+            WebXRError::InvalidState => Error::InvalidState,
+        }
+    }
+}
+
+impl XRSystemMethods for XRSystem {
+    #[allow(unrooted_must_root)]
+    // https://immersive-web.github.io/webxr/#dom-xrsystem-issessionsupported
+    fn IsSessionSupported(&self, mode: XRSessionMode) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let supported = match mode {
+            // Inline sessions don't need a device.
+            XRSessionMode::Inline => true,
+            XRSessionMode::Immersive_vr => {
+                let (sender, receiver) = ipc::channel().unwrap();
+                self.send(WebXRMsg::IsSessionSupported(DeviceSessionMode::ImmersiveVR, sender));
+                receiver.recv().unwrap()
+            },
+        };
+        promise.resolve_native(global.r().get_cx(), &supported);
+        promise
+    }
+
+    #[allow(unrooted_must_root)]
+    // https://immersive-web.github.io/webxr/#dom-xrsystem-requestsession
+    fn RequestSession(&self, mode: XRSessionMode) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(global.r());
+        let cx = global.r().get_cx();
+        // TODO: Steps 2-4: immersive sessions should require user activation.
+        let session_id = match mode {
+            XRSessionMode::Inline => Ok(None),
+            XRSessionMode::Immersive_vr => {
+                // Note: the check for an active immersive session (step 2) is
+                // done by the WebXR thread, since it knows which devices are
+                // presenting.
+                let (sender, receiver) = ipc::channel().unwrap();
+                self.send(WebXRMsg::RequestSession(DeviceSessionMode::ImmersiveVR, sender));
+                receiver.recv().unwrap().map(Some)
+            },
+        };
+        match session_id {
+            Ok(session_id) => promise.resolve_native(cx, &XRSession::new(global.r(), session_id)),
+            Err(error) => promise.reject_error(cx, Error::from(error)),
+        }
+        promise
+    }
+}
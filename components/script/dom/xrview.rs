@@ -0,0 +1,60 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use core::nonzero::NonZero;
+use dom::bindings::codegen::Bindings::XRViewBinding;
+use dom::bindings::codegen::Bindings::XRViewBinding::{XREye, XRViewMethods};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+use dom::xrrigidtransform::{XRRigidTransform, new_float32_array};
+use js::jsapi::{JSContext, JSObject};
+
+// https://immersive-web.github.io/webxr/#xrview
+#[dom_struct]
+pub struct XRView {
+    reflector_: Reflector,
+    eye: XREye,
+    projection: Vec<f32>,
+    transform: JS<XRRigidTransform>,
+}
+
+impl XRView {
+    fn new_inherited(eye: XREye, projection: &[f32; 16], transform: &XRRigidTransform) -> XRView {
+        XRView {
+            reflector_: Reflector::new(),
+            eye: eye,
+            projection: projection.to_vec(),
+            transform: JS::from_ref(transform),
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               eye: XREye,
+               projection: &[f32; 16],
+               transform: &XRRigidTransform)
+               -> Root<XRView> {
+        reflect_dom_object(box XRView::new_inherited(eye, projection, transform),
+                           global,
+                           XRViewBinding::Wrap)
+    }
+}
+
+impl XRViewMethods for XRView {
+    // https://immersive-web.github.io/webxr/#dom-xrview-eye
+    fn Eye(&self) -> XREye {
+        self.eye
+    }
+
+    // FIXME: the spec requires the same array to be returned every time.
+    // https://immersive-web.github.io/webxr/#dom-xrview-projectionmatrix
+    fn ProjectionMatrix(&self, cx: *mut JSContext) -> NonZero<*mut JSObject> {
+        new_float32_array(cx, &self.projection)
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrview-transform
+    fn Transform(&self) -> Root<XRRigidTransform> {
+        Root::from_ref(&*self.transform)
+    }
+}
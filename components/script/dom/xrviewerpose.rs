@@ -0,0 +1,52 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::XRViewerPoseBinding;
+use dom::bindings::codegen::Bindings::XRViewerPoseBinding::XRViewerPoseMethods;
+use dom::bindings::conversions::ToJSValConvertible;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::reflect_dom_object;
+use dom::xrpose::XRPose;
+use dom::xrrigidtransform::XRRigidTransform;
+use dom::xrview::XRView;
+use js::jsapi::JSContext;
+use js::jsval::{JSVal, UndefinedValue};
+
+// https://immersive-web.github.io/webxr/#xrviewerpose
+#[dom_struct]
+pub struct XRViewerPose {
+    pose: XRPose,
+    views: Vec<JS<XRView>>,
+}
+
+impl XRViewerPose {
+    fn new_inherited(transform: &XRRigidTransform, views: &[Root<XRView>]) -> XRViewerPose {
+        XRViewerPose {
+            pose: XRPose::new_inherited(transform),
+            views: views.iter().map(|view| JS::from_ref(&**view)).collect(),
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               transform: &XRRigidTransform,
+               views: &[Root<XRView>])
+               -> Root<XRViewerPose> {
+        reflect_dom_object(box XRViewerPose::new_inherited(transform, views),
+                           global,
+                           XRViewerPoseBinding::Wrap)
+    }
+}
+
+impl XRViewerPoseMethods for XRViewerPose {
+    // FIXME: the spec requires the same frozen array to be returned every time.
+    #[allow(unsafe_code)]
+    // https://immersive-web.github.io/webxr/#dom-xrviewerpose-views
+    fn Views(&self, cx: *mut JSContext) -> JSVal {
+        let views: Vec<Root<XRView>> = self.views.iter().map(|view| Root::from_ref(&**view)).collect();
+        rooted!(in(cx) let mut value = UndefinedValue());
+        unsafe { views.to_jsval(cx, value.handle_mut()) };
+        value.get()
+    }
+}
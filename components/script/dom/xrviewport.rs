@@ -0,0 +1,59 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::XRViewportBinding;
+use dom::bindings::codegen::Bindings::XRViewportBinding::XRViewportMethods;
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::Root;
+use dom::bindings::reflector::{Reflector, reflect_dom_object};
+
+// https://immersive-web.github.io/webxr/#xrviewport
+#[dom_struct]
+pub struct XRViewport {
+    reflector_: Reflector,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl XRViewport {
+    fn new_inherited(x: i32, y: i32, width: i32, height: i32) -> XRViewport {
+        XRViewport {
+            reflector_: Reflector::new(),
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+
+    pub fn new(global: GlobalRef, x: i32, y: i32, width: i32, height: i32) -> Root<XRViewport> {
+        reflect_dom_object(box XRViewport::new_inherited(x, y, width, height),
+                           global,
+                           XRViewportBinding::Wrap)
+    }
+}
+
+impl XRViewportMethods for XRViewport {
+    // https://immersive-web.github.io/webxr/#dom-xrviewport-x
+    fn X(&self) -> i32 {
+        self.x
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrviewport-y
+    fn Y(&self) -> i32 {
+        self.y
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrviewport-width
+    fn Width(&self) -> i32 {
+        self.width
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrviewport-height
+    fn Height(&self) -> i32 {
+        self.height
+    }
+}
@@ -0,0 +1,106 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dom::bindings::codegen::Bindings::WebGLRenderingContextBinding::WebGLRenderingContextMethods;
+use dom::bindings::codegen::Bindings::XRViewBinding::{XREye, XRViewMethods};
+use dom::bindings::codegen::Bindings::XRWebGLLayerBinding;
+use dom::bindings::codegen::Bindings::XRWebGLLayerBinding::XRWebGLLayerMethods;
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::global::GlobalRef;
+use dom::bindings::js::{JS, Root};
+use dom::bindings::reflector::{Reflectable, Reflector, reflect_dom_object};
+use dom::webglframebuffer::WebGLFramebuffer;
+use dom::webglrenderingcontext::WebGLRenderingContext;
+use dom::xrsession::XRSession;
+use dom::xrview::XRView;
+use dom::xrviewport::XRViewport;
+
+// https://immersive-web.github.io/webxr/#xrwebgllayer
+#[dom_struct]
+pub struct XRWebGLLayer {
+    reflector_: Reflector,
+    session: JS<XRSession>,
+    context: JS<WebGLRenderingContext>,
+}
+
+impl XRWebGLLayer {
+    fn new_inherited(session: &XRSession, context: &WebGLRenderingContext) -> XRWebGLLayer {
+        XRWebGLLayer {
+            reflector_: Reflector::new(),
+            session: JS::from_ref(session),
+            context: JS::from_ref(context),
+        }
+    }
+
+    pub fn new(global: GlobalRef,
+               session: &XRSession,
+               context: &WebGLRenderingContext)
+               -> Root<XRWebGLLayer> {
+        reflect_dom_object(box XRWebGLLayer::new_inherited(session, context),
+                           global,
+                           XRWebGLLayerBinding::Wrap)
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrwebgllayer-xrwebgllayer
+    pub fn Constructor(global: GlobalRef,
+                       session: &XRSession,
+                       context: &WebGLRenderingContext)
+                       -> Fallible<Root<XRWebGLLayer>> {
+        // Step 1.
+        if session.is_ended() {
+            return Err(Error::InvalidState);
+        }
+        Ok(XRWebGLLayer::new(global, session, context))
+    }
+
+    pub fn session(&self) -> &XRSession {
+        &self.session
+    }
+
+    pub fn context(&self) -> &WebGLRenderingContext {
+        &self.context
+    }
+
+    /// The width of the drawing buffer divided by its height.
+    pub fn aspect_ratio(&self) -> f32 {
+        let height = self.context.DrawingBufferHeight();
+        if height == 0 {
+            return 1.;
+        }
+        self.context.DrawingBufferWidth() as f32 / height as f32
+    }
+}
+
+impl XRWebGLLayerMethods for XRWebGLLayer {
+    // Frames are always rendered to the context's default framebuffer. For
+    // immersive sessions, its drawing buffer is handed to the headset once
+    // the frame's callbacks have run.
+    // https://immersive-web.github.io/webxr/#dom-xrwebgllayer-framebuffer
+    fn GetFramebuffer(&self) -> Option<Root<WebGLFramebuffer>> {
+        None
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrwebgllayer-framebufferwidth
+    fn FramebufferWidth(&self) -> u32 {
+        self.context.DrawingBufferWidth() as u32
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrwebgllayer-framebufferheight
+    fn FramebufferHeight(&self) -> u32 {
+        self.context.DrawingBufferHeight() as u32
+    }
+
+    // https://immersive-web.github.io/webxr/#dom-xrwebgllayer-getviewport
+    fn GetViewport(&self, view: &XRView) -> Option<Root<XRViewport>> {
+        let width = self.context.DrawingBufferWidth();
+        let height = self.context.DrawingBufferHeight();
+        // Stereo views each render to one half of the drawing buffer.
+        let (x, width) = match view.Eye() {
+            XREye::None => (0, width),
+            XREye::Left => (0, width / 2),
+            XREye::Right => (width / 2, width - width / 2),
+        };
+        Some(XRViewport::new(self.global().r(), x, 0, width, height))
+    }
+}
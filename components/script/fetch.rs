@@ -0,0 +1,217 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The `fetch()` method shared by window and worker globals.
+
+use body::BodyChunks;
+use dom::bindings::codegen::Bindings::RequestBinding::RequestInfo;
+use dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
+use dom::bindings::codegen::Bindings::ResponseBinding::ResponseMethods;
+use dom::bindings::codegen::Bindings::ResponseBinding::ResponseType as DOMResponseType;
+use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use dom::bindings::error::Error;
+use dom::bindings::global::{GlobalRef, GlobalRoot};
+use dom::bindings::refcounted::{Trusted, TrustedPromise};
+use dom::bindings::reflector::Reflectable;
+use dom::headers::Guard;
+use dom::performanceresourcetiming::initiator_type;
+use dom::promise::Promise;
+use dom::request::Request;
+use dom::response::Response;
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use net_traits::{FetchMetadata, FetchResponseListener, FilteredMetadata, IpcBytes, Metadata, NetworkError};
+use net_traits::CoreResourceMsg::Fetch as NetTraitsFetch;
+use net_traits::request::Destination;
+use net_traits::request::Referrer as NetTraitsRequestReferrer;
+use net_traits::request::Request as NetTraitsRequest;
+use net_traits::request::RequestInit as NetTraitsRequestInit;
+use network_listener::{NetworkListener, PreInvoke};
+use std::mem;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use url::{Origin, Url};
+
+struct FetchContext {
+    fetch_promise: Option<TrustedPromise>,
+    response_object: Trusted<Response>,
+    /// The origin of the global that called `fetch()`.
+    origin: Origin,
+    body: BodyChunks,
+    /// Tells the network how many body bytes have been taken in, so that it can keep
+    /// sending.
+    acknowledgements: IpcSender<usize>,
+    /// The URL that was requested, and the metadata of its response once it arrives, to
+    /// record the fetch in the resource timing buffer.
+    url: Url,
+    metadata: Option<Metadata>,
+}
+
+fn from_referrer_to_referrer_url(request: &NetTraitsRequest, client_url: &Url) -> Option<Url> {
+    match *request.referrer.borrow() {
+        NetTraitsRequestReferrer::NoReferrer => None,
+        NetTraitsRequestReferrer::Client => Some(client_url.clone()),
+        NetTraitsRequestReferrer::ReferrerUrl(ref url) => Some(url.clone()),
+    }
+}
+
+fn request_init_from_request(request: NetTraitsRequest, client_url: Url) -> NetTraitsRequestInit {
+    NetTraitsRequestInit {
+        method: request.method.borrow().clone(),
+        url: request.url(),
+        headers: request.headers.borrow().clone(),
+        unsafe_request: request.unsafe_request,
+        same_origin_data: request.same_origin_data.get(),
+        body: request.body.borrow().clone(),
+        type_: request.type_,
+        destination: request.destination,
+        synchronous: request.synchronous,
+        mode: request.mode,
+        use_cors_preflight: request.use_cors_preflight,
+        credentials_mode: request.credentials_mode,
+        use_url_credentials: request.use_url_credentials,
+        referrer_url: from_referrer_to_referrer_url(&request, &client_url),
+        // NetTraitsRequestInit stores the client's url as the origin.
+        origin: client_url,
+        referrer_policy: request.referrer_policy.get(),
+        pipeline_id: request.pipeline_id.get(),
+    }
+}
+
+// https://fetch.spec.whatwg.org/#fetch-method
+#[allow(unrooted_must_root)]
+pub fn Fetch(global: GlobalRef, input: RequestInfo, init: &RequestInit) -> Rc<Promise> {
+    let core_resource_thread = global.core_resource_thread();
+
+    // Step 1
+    let promise = Promise::new(global);
+    let response = Response::new(global);
+
+    // Step 2
+    let request = match Request::Constructor(global, input, init) {
+        Err(e) => {
+            promise.reject_error(promise.global().r().get_cx(), e);
+            return promise;
+        },
+        Ok(r) => r.get_request(),
+    };
+    let client_url = global.get_url();
+    let origin = client_url.origin();
+    let url = request.url();
+    let request_init = request_init_from_request(request, client_url);
+
+    // Step 3
+    response.Headers().set_guard(Guard::Immutable);
+
+    // Step 4
+    let (action_sender, action_receiver) = ipc::channel().unwrap();
+    let (ack_sender, ack_receiver) = ipc::channel().unwrap();
+    let fetch_context = Arc::new(Mutex::new(FetchContext {
+        fetch_promise: Some(TrustedPromise::new(promise.clone())),
+        response_object: Trusted::new(&*response),
+        origin: origin,
+        body: BodyChunks::new(),
+        acknowledgements: ack_sender,
+        url: url,
+        metadata: None,
+    }));
+    let listener = NetworkListener {
+        context: fetch_context,
+        script_chan: global.networking_task_source(),
+        wrapper: None,
+    };
+
+    ROUTER.add_route(action_receiver.to_opaque(), box move |message| {
+        listener.notify_fetch(message.to().unwrap());
+    });
+    core_resource_thread.send(NetTraitsFetch(request_init, action_sender, Some(ack_receiver))).unwrap();
+
+    promise
+}
+
+impl PreInvoke for FetchContext {}
+
+impl FetchContext {
+    fn fill_response(&self, response: &Response, metadata: Metadata, response_type: DOMResponseType) {
+        response.set_type(response_type);
+        response.set_headers(metadata.headers);
+        response.set_raw_status(metadata.status);
+        response.set_final_url(metadata.final_url);
+    }
+
+    fn reject(&mut self, error: Error) {
+        if let Some(promise) = self.fetch_promise.take() {
+            let promise = promise.root();
+            promise.reject_error(promise.global().r().get_cx(), error);
+        }
+    }
+}
+
+impl FetchResponseListener for FetchContext {
+    fn process_request_body(&mut self) {
+        // TODO
+    }
+
+    fn process_request_eof(&mut self) {
+        // TODO
+    }
+
+    fn process_response(&mut self, fetch_metadata: Result<FetchMetadata, NetworkError>) {
+        let response = self.response_object.root();
+        self.metadata = fetch_metadata.as_ref().ok().map(|meta| match *meta {
+            FetchMetadata::Unfiltered(ref m) => m.clone(),
+            FetchMetadata::Filtered { ref unsafe_, .. } => unsafe_.clone()
+        });
+        match fetch_metadata {
+            // Step 4.1
+            Err(_) => {
+                response.set_type(DOMResponseType::Error);
+                self.reject(Error::Type("Network error occurred".to_string()));
+            },
+            // Step 4.2
+            Ok(FetchMetadata::Unfiltered(metadata)) => {
+                self.fill_response(&response, metadata, DOMResponseType::Basic);
+            },
+            Ok(FetchMetadata::Filtered { filtered: FilteredMetadata::Transparent(metadata), .. }) => {
+                let response_type = if metadata.final_url.origin() == self.origin {
+                    DOMResponseType::Basic
+                } else {
+                    DOMResponseType::Cors
+                };
+                self.fill_response(&response, metadata, response_type);
+            },
+            Ok(FetchMetadata::Filtered { filtered: FilteredMetadata::Opaque, .. }) => {
+                response.set_type(DOMResponseType::Opaque);
+                response.set_raw_status(Some((0, vec![])));
+            },
+        }
+    }
+
+    fn process_response_chunk(&mut self, chunk: IpcBytes) {
+        let _ = self.acknowledgements.send(chunk.len());
+        self.body.push(chunk);
+    }
+
+    // Without a ReadableStream the body can only be read once it has been
+    // received in full, so the promise is resolved at the end of the response.
+    // The chunks are kept as they arrived and only copied when the body is consumed.
+    fn process_response_eof(&mut self, response: Result<(), NetworkError>) {
+        if let (&Ok(()), Some(metadata)) = (&response, self.metadata.as_ref()) {
+            if let GlobalRoot::Window(ref window) = self.response_object.root().global() {
+                window.Performance().queue_resource_timing(&self.url, initiator_type(Destination::None), metadata);
+            }
+        }
+        match response {
+            Ok(()) => {
+                if let Some(promise) = self.fetch_promise.take() {
+                    let promise = promise.root();
+                    let response = self.response_object.root();
+                    response.set_body(mem::replace(&mut self.body, BodyChunks::new()));
+                    promise.resolve_native(promise.global().r().get_cx(), &response);
+                }
+            },
+            Err(_) => self.reject(Error::Type("Network error occurred".to_string())),
+        }
+    }
+}
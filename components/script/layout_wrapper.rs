@@ -637,7 +637,13 @@ impl<'le> ::selectors::Element for ServoLayoutElement<'le> {
             NonTSPseudoClass::Indeterminate |
             NonTSPseudoClass::ReadWrite |
             NonTSPseudoClass::PlaceholderShown |
-            NonTSPseudoClass::Target =>
+            NonTSPseudoClass::Target |
+            NonTSPseudoClass::Valid |
+            NonTSPseudoClass::Invalid |
+            NonTSPseudoClass::Required |
+            NonTSPseudoClass::Optional |
+            NonTSPseudoClass::Modal |
+            NonTSPseudoClass::ServoTopLayer =>
                 self.element.get_state_for_layout().contains(pseudo_class.state_flag())
         }
     }
@@ -855,6 +861,11 @@ impl<'ln> ThreadSafeLayoutNode for ServoThreadSafeLayoutNode<'ln> {
         this.image_url()
     }
 
+    fn image_density(&self) -> f64 {
+        let this = unsafe { self.get_jsmanaged() };
+        this.image_density()
+    }
+
     fn canvas_data(&self) -> Option<HTMLCanvasData> {
         let this = unsafe { self.get_jsmanaged() };
         this.canvas_data()
@@ -871,6 +882,12 @@ impl<'ln> ThreadSafeLayoutNode for ServoThreadSafeLayoutNode<'ln> {
         }
     }
 
+    fn get_rowspan(&self) -> u32 {
+        unsafe {
+            self.get_jsmanaged().downcast::<Element>().unwrap().get_rowspan()
+        }
+    }
+
     fn get_style_data(&self) -> Option<&RefCell<PartialStyleAndLayoutData>> {
         self.node.get_style_data()
     }
@@ -1001,6 +1018,10 @@ impl<'le> ThreadSafeLayoutElement for ServoThreadSafeLayoutElement<'le> {
     fn get_namespace(&self) -> &Namespace {
         self.element.namespace()
     }
+
+    fn is_in_top_layer(&self) -> bool {
+        self.element.state().contains(IN_TOP_LAYER_STATE)
+    }
 }
 
 /// This implementation of `::selectors::Element` is used for implementing lazy
@@ -56,6 +56,7 @@ extern crate js;
 extern crate libc;
 #[macro_use]
 extern crate log;
+extern crate media_platform;
 #[macro_use]
 extern crate mime;
 extern crate mime_guess;
@@ -64,6 +65,7 @@ extern crate net_traits;
 extern crate num_traits;
 extern crate offscreen_gl_context;
 extern crate open;
+extern crate openssl;
 extern crate phf;
 #[macro_use]
 extern crate profile_traits;
@@ -81,16 +83,17 @@ extern crate smallvec;
 #[macro_use]
 extern crate style;
 extern crate time;
-#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
-extern crate tinyfiledialogs;
 extern crate url;
+extern crate usb_traits;
 #[macro_use]
 extern crate util;
 extern crate uuid;
 extern crate webrender_traits;
+extern crate webrtc_traits;
 extern crate websocket;
 extern crate xml5ever;
 
+pub mod audio;
 pub mod bluetooth_blacklist;
 mod body;
 pub mod clipboard_provider;
@@ -98,7 +101,9 @@ mod devtools;
 pub mod document_loader;
 #[macro_use]
 pub mod dom;
+pub mod fetch;
 pub mod layout_wrapper;
+pub mod media;
 mod mem;
 mod network_listener;
 pub mod origin;
@@ -107,11 +112,14 @@ pub mod script_runtime;
 #[allow(unsafe_code)]
 pub mod script_thread;
 mod serviceworker_manager;
+pub mod task_attribution;
 mod task_source;
 pub mod textinput;
 mod timers;
 mod unpremultiplytable;
 mod webdriver_handlers;
+pub mod webcrypto;
+pub mod xpath;
 
 use dom::bindings::codegen::RegisterBindings;
 use dom::bindings::proxyhandler;
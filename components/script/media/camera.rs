@@ -0,0 +1,179 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Video capture.
+
+use media::{CaptureBackend, CaptureDevice, CaptureSettings, MediaData, MediaKind, VideoFrame};
+use media_platform::camera::{self, Camera};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub fn fake_device() -> CaptureDevice {
+    CaptureDevice {
+        id: "fake-camera".to_owned(),
+        kind: MediaKind::Video,
+        label: "Fake camera".to_owned(),
+    }
+}
+
+/// The cameras of the platform.
+pub fn devices() -> Vec<CaptureDevice> {
+    camera::devices().into_iter().map(|camera| {
+        CaptureDevice {
+            id: camera.path,
+            kind: MediaKind::Video,
+            label: camera.label,
+        }
+    }).collect()
+}
+
+pub fn backend(device: &CaptureDevice, fake: bool) -> Box<CaptureBackend> {
+    if fake {
+        box FakeCamera::new()
+    } else {
+        box DeviceCamera::new(&device.id)
+    }
+}
+
+/// Converts a frame of packed 4:2:2 YUV, where every two pixels are stored
+/// as Y0 U Y1 V, to RGBA.
+pub fn yuyv_to_rgba(width: u32, height: u32, yuyv: &[u8]) -> Vec<u8> {
+    fn clamp(value: i32) -> u8 {
+        (value >> 8).max(0).min(255) as u8
+    }
+
+    let pixels = (width * height) as usize;
+    let mut rgba = Vec::with_capacity(pixels * 4);
+    for macropixel in yuyv[..pixels * 2].chunks(4) {
+        let u = macropixel[1] as i32 - 128;
+        let v = macropixel[3] as i32 - 128;
+        for &y in &[macropixel[0], macropixel[2]] {
+            // ITU-R BT.601, with studio swing.
+            let c = 298 * (y as i32 - 16) + 128;
+            rgba.push(clamp(c + 409 * v));
+            rgba.push(clamp(c - 100 * u - 208 * v));
+            rgba.push(clamp(c + 516 * u));
+            rgba.push(255);
+        }
+    }
+    rgba
+}
+
+/// Generates moving color bars, at the pace of a real camera.
+pub struct FakeCamera {
+    settings: CaptureSettings,
+    frame_duration: Duration,
+    next_frame: Option<Instant>,
+    frame_count: u32,
+}
+
+impl FakeCamera {
+    pub fn new() -> FakeCamera {
+        FakeCamera {
+            settings: CaptureSettings::default(),
+            frame_duration: Duration::from_millis(0),
+            next_frame: None,
+            frame_count: 0,
+        }
+    }
+}
+
+impl CaptureBackend for FakeCamera {
+    fn open(&mut self, settings: &CaptureSettings) -> Result<CaptureSettings, ()> {
+        self.settings = *settings;
+        let nanoseconds = 1e9 / settings.frame_rate.max(1.);
+        self.frame_duration = Duration::new(0, nanoseconds as u32);
+        self.next_frame = None;
+        Ok(self.settings)
+    }
+
+    fn read(&mut self) -> Option<MediaData> {
+        const BARS: [[u8; 3]; 7] = [[192, 192, 192], [192, 192, 0], [0, 192, 192], [0, 192, 0],
+                                    [192, 0, 192], [192, 0, 0], [0, 0, 192]];
+
+        let now = Instant::now();
+        let next_frame = self.next_frame.unwrap_or(now);
+        if next_frame > now {
+            thread::sleep(next_frame - now);
+        }
+        self.next_frame = Some(next_frame + self.frame_duration);
+
+        let (width, height) = (self.settings.width, self.settings.height);
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for _ in 0..height {
+            for x in 0..width {
+                let bar = ((x + self.frame_count) % width) * BARS.len() as u32 / width;
+                data.extend_from_slice(&BARS[bar as usize]);
+                data.push(255);
+            }
+        }
+        self.frame_count = self.frame_count.wrapping_add(4);
+        Some(MediaData::Video(Arc::new(VideoFrame {
+            width: width,
+            height: height,
+            data: data,
+        })))
+    }
+
+    fn close(&mut self) {
+        self.next_frame = None;
+    }
+}
+
+/// Captures from a camera of the platform.
+pub struct DeviceCamera {
+    path: String,
+    camera: Option<Camera>,
+}
+
+impl DeviceCamera {
+    pub fn new(path: &str) -> DeviceCamera {
+        DeviceCamera {
+            path: path.to_owned(),
+            camera: None,
+        }
+    }
+}
+
+impl CaptureBackend for DeviceCamera {
+    fn open(&mut self, settings: &CaptureSettings) -> Result<CaptureSettings, ()> {
+        self.close();
+        let camera = try!(Camera::open(&self.path, settings.width, settings.height));
+        let settings = CaptureSettings {
+            width: camera.width(),
+            height: camera.height(),
+            ..*settings
+        };
+        self.camera = Some(camera);
+        Ok(settings)
+    }
+
+    fn read(&mut self) -> Option<MediaData> {
+        let camera = match self.camera {
+            Some(ref mut camera) => camera,
+            None => return None,
+        };
+        let (width, height) = (camera.width(), camera.height());
+        let data = camera.read(|yuyv| {
+            if yuyv.len() >= (width * height * 2) as usize {
+                yuyv_to_rgba(width, height, yuyv)
+            } else {
+                // Frames the driver didn't fill completely come out black.
+                vec![0; (width * height * 4) as usize]
+            }
+        });
+        data.ok().map(|data| {
+            MediaData::Video(Arc::new(VideoFrame {
+                width: width,
+                height: height,
+                data: data,
+            }))
+        })
+    }
+
+    fn close(&mut self) {
+        self.camera = None;
+    }
+}
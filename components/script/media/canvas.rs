@@ -0,0 +1,96 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Capture of the content of a canvas, for `captureStream()`.
+
+use canvas_traits::{Canvas2dMsg, CanvasMsg};
+use euclid::point::Point2D;
+use euclid::rect::Rect;
+use euclid::size::Size2D;
+use ipc_channel::ipc::{self, IpcSender};
+use media::{CaptureBackend, CaptureDevice, CaptureSettings, MediaData, MediaKind, VideoFrame};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub fn device() -> CaptureDevice {
+    CaptureDevice {
+        id: "canvas".to_owned(),
+        kind: MediaKind::Video,
+        label: String::new(),
+    }
+}
+
+/// Reads the pixels of a 2D canvas from its paint thread, at a fixed rate
+/// and at the size the canvas had when its capture started.
+pub struct CanvasCapturer {
+    /// The paint thread of the canvas, if it has a context. A canvas without
+    /// one is transparent black.
+    renderer: Option<IpcSender<CanvasMsg>>,
+    width: u32,
+    height: u32,
+    frame_duration: Duration,
+    next_frame: Option<Instant>,
+}
+
+impl CanvasCapturer {
+    pub fn new(renderer: Option<IpcSender<CanvasMsg>>, width: u32, height: u32) -> CanvasCapturer {
+        CanvasCapturer {
+            renderer: renderer,
+            width: width,
+            height: height,
+            frame_duration: Duration::from_millis(0),
+            next_frame: None,
+        }
+    }
+}
+
+impl CaptureBackend for CanvasCapturer {
+    fn open(&mut self, settings: &CaptureSettings) -> Result<CaptureSettings, ()> {
+        let nanoseconds = 1e9 / settings.frame_rate.max(1.);
+        self.frame_duration = Duration::new(0, nanoseconds as u32);
+        self.next_frame = None;
+        Ok(CaptureSettings {
+            width: self.width,
+            height: self.height,
+            ..*settings
+        })
+    }
+
+    fn read(&mut self) -> Option<MediaData> {
+        let now = Instant::now();
+        let next_frame = self.next_frame.unwrap_or(now);
+        if next_frame > now {
+            thread::sleep(next_frame - now);
+        }
+        self.next_frame = Some(next_frame + self.frame_duration);
+
+        let data = match self.renderer {
+            Some(ref renderer) => {
+                let (sender, receiver) = ipc::channel().unwrap();
+                let size = Size2D::new(self.width as i32, self.height as i32);
+                let canvas_size = Size2D::new(self.width as f64, self.height as f64);
+                let msg = Canvas2dMsg::GetImageData(Rect::new(Point2D::zero(), size), canvas_size, sender);
+                // The paint thread is gone with the canvas.
+                if renderer.send(CanvasMsg::Canvas2d(msg)).is_err() {
+                    return None;
+                }
+                match receiver.recv() {
+                    Ok(data) => data.to_vec(),
+                    Err(_) => return None,
+                }
+            },
+            None => vec![0; (self.width * self.height * 4) as usize],
+        };
+        Some(MediaData::Video(Arc::new(VideoFrame {
+            width: self.width,
+            height: self.height,
+            data: data,
+        })))
+    }
+
+    fn close(&mut self) {
+        self.next_frame = None;
+    }
+}
@@ -0,0 +1,159 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Audio capture.
+
+use media::{AudioChunk, CaptureBackend, CaptureDevice, CaptureSettings, MediaData, MediaKind};
+use media_platform::audio::{self, AudioInput};
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long the chunks read from a microphone are, in seconds.
+const CHUNK_DURATION: f32 = 0.01;
+
+pub fn fake_device() -> CaptureDevice {
+    CaptureDevice {
+        id: "fake-microphone".to_owned(),
+        kind: MediaKind::Audio,
+        label: "Fake microphone".to_owned(),
+    }
+}
+
+/// The microphones of the platform, which is the default input device if
+/// there is one.
+pub fn devices() -> Vec<CaptureDevice> {
+    if !audio::has_input_device() {
+        return vec![];
+    }
+    vec![CaptureDevice {
+        id: "default".to_owned(),
+        kind: MediaKind::Audio,
+        label: "Default microphone".to_owned(),
+    }]
+}
+
+pub fn backend(device: &CaptureDevice, fake: bool) -> Box<CaptureBackend> {
+    if fake {
+        box FakeMicrophone::new()
+    } else {
+        box DeviceMicrophone::new(&device.id)
+    }
+}
+
+fn chunk_frames(sample_rate: f32) -> usize {
+    (sample_rate * CHUNK_DURATION) as usize
+}
+
+/// Generates a 440Hz tone, at the pace of a real microphone.
+pub struct FakeMicrophone {
+    settings: CaptureSettings,
+    next_chunk: Option<Instant>,
+    frame_count: u64,
+}
+
+impl FakeMicrophone {
+    pub fn new() -> FakeMicrophone {
+        FakeMicrophone {
+            settings: CaptureSettings::default(),
+            next_chunk: None,
+            frame_count: 0,
+        }
+    }
+}
+
+impl CaptureBackend for FakeMicrophone {
+    fn open(&mut self, settings: &CaptureSettings) -> Result<CaptureSettings, ()> {
+        self.settings = *settings;
+        self.next_chunk = None;
+        Ok(self.settings)
+    }
+
+    fn read(&mut self) -> Option<MediaData> {
+        let now = Instant::now();
+        let next_chunk = self.next_chunk.unwrap_or(now);
+        if next_chunk > now {
+            thread::sleep(next_chunk - now);
+        }
+        let sample_rate = self.settings.sample_rate;
+        let frames = chunk_frames(sample_rate);
+        let nanoseconds = frames as f64 / sample_rate as f64 * 1e9;
+        self.next_chunk = Some(next_chunk + Duration::new(0, nanoseconds as u32));
+
+        let start = self.frame_count;
+        let channel: Vec<f32> = (0..frames).map(|frame| {
+            let time = (start + frame as u64) as f32 / sample_rate;
+            0.5 * (2. * PI * 440. * time).sin()
+        }).collect();
+        self.frame_count += frames as u64;
+        Some(MediaData::Audio(Arc::new(AudioChunk {
+            sample_rate: sample_rate,
+            channels: vec![channel; self.settings.channel_count as usize],
+        })))
+    }
+
+    fn close(&mut self) {
+        self.next_chunk = None;
+    }
+}
+
+/// Captures from an input device of the platform.
+pub struct DeviceMicrophone {
+    name: String,
+    input: Option<AudioInput>,
+    settings: CaptureSettings,
+    samples: Vec<f32>,
+}
+
+impl DeviceMicrophone {
+    pub fn new(name: &str) -> DeviceMicrophone {
+        DeviceMicrophone {
+            name: name.to_owned(),
+            input: None,
+            settings: CaptureSettings::default(),
+            samples: vec![],
+        }
+    }
+}
+
+impl CaptureBackend for DeviceMicrophone {
+    fn open(&mut self, settings: &CaptureSettings) -> Result<CaptureSettings, ()> {
+        self.close();
+        let input = try!(AudioInput::open(&self.name,
+                                          settings.sample_rate as u32,
+                                          settings.channel_count as usize));
+        self.input = Some(input);
+        self.settings = *settings;
+        Ok(self.settings)
+    }
+
+    fn read(&mut self) -> Option<MediaData> {
+        let channel_count = self.settings.channel_count as usize;
+        let frames = chunk_frames(self.settings.sample_rate);
+        self.samples.resize(frames * channel_count, 0.);
+        match self.input {
+            Some(ref mut input) => {
+                if input.read(&mut self.samples).is_err() {
+                    return None;
+                }
+            },
+            None => return None,
+        }
+        let mut channels = vec![Vec::with_capacity(frames); channel_count];
+        for frame in self.samples.chunks(channel_count) {
+            for (channel, &sample) in channels.iter_mut().zip(frame) {
+                channel.push(sample);
+            }
+        }
+        Some(MediaData::Audio(Arc::new(AudioChunk {
+            sample_rate: self.settings.sample_rate,
+            channels: channels,
+        })))
+    }
+
+    fn close(&mut self) {
+        self.input = None;
+    }
+}
@@ -0,0 +1,270 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Capture of cameras and microphones, for `getUserMedia`, recording of what
+//! they capture, for `MediaRecorder`, and the playback of media resources,
+//! for media elements. Peer connections are described and run by the
+//! `webrtc_traits` and `webrtc` crates.
+//!
+//! Every opened device is read by a capture thread, which hands each video
+//! frame or audio chunk it reads to the sinks the tracks of the device added
+//! to it. The sinks run on the capture thread, so that whatever consumes a
+//! track—a media element, a recorder—doesn't have to wait for the script
+//! thread to get a frame.
+
+pub mod camera;
+pub mod canvas;
+pub mod microphone;
+pub mod opus;
+pub mod player;
+pub mod recorder;
+pub mod source;
+pub mod vp8;
+pub mod webm;
+pub mod webvtt;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError, channel};
+use util::prefs::PREFS;
+use util::thread::spawn_named;
+
+#[derive(Clone, Copy, Debug, Eq, HeapSizeOf, PartialEq)]
+pub enum MediaKind {
+    Audio,
+    Video,
+}
+
+/// A camera or a microphone.
+#[derive(Clone, Debug, HeapSizeOf, PartialEq)]
+pub struct CaptureDevice {
+    /// Identifies the device to the backend of its kind, e.g. `/dev/video0`.
+    pub id: String,
+    pub kind: MediaKind,
+    pub label: String,
+}
+
+/// A frame of a camera, decoded to RGBA.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VideoFrame {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+/// Samples of a microphone, in one vector per channel like `AudioBufferData`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioChunk {
+    pub sample_rate: f32,
+    pub channels: Vec<Vec<f32>>,
+}
+
+#[derive(Clone, Debug)]
+pub enum MediaData {
+    Video(Arc<VideoFrame>),
+    Audio(Arc<AudioChunk>),
+}
+
+impl MediaData {
+    /// What a disabled track outputs instead: black frames or silence.
+    /// https://w3c.github.io/mediacapture-main/#life-cycle-and-media-flow
+    pub fn blanked(&self) -> MediaData {
+        match *self {
+            MediaData::Video(ref frame) => {
+                let mut data = vec![0; frame.data.len()];
+                for pixel in data.chunks_mut(4) {
+                    pixel[3] = 255;
+                }
+                MediaData::Video(Arc::new(VideoFrame {
+                    width: frame.width,
+                    height: frame.height,
+                    data: data,
+                }))
+            },
+            MediaData::Audio(ref chunk) => {
+                MediaData::Audio(Arc::new(AudioChunk {
+                    sample_rate: chunk.sample_rate,
+                    channels: chunk.channels.iter().map(|channel| vec![0.; channel.len()]).collect(),
+                }))
+            },
+        }
+    }
+}
+
+/// The settings a device is asked to capture with, and those it actually
+/// captures with once opened.
+#[derive(Clone, Copy, Debug, HeapSizeOf, PartialEq)]
+pub struct CaptureSettings {
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: f64,
+    pub sample_rate: f32,
+    pub channel_count: u32,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> CaptureSettings {
+        CaptureSettings {
+            width: 640,
+            height: 480,
+            frame_rate: 30.,
+            sample_rate: 48000.,
+            channel_count: 1,
+        }
+    }
+}
+
+/// How a capture thread reads its device.
+pub trait CaptureBackend: Send {
+    /// Opens the device, returning the settings it settled for.
+    fn open(&mut self, settings: &CaptureSettings) -> Result<CaptureSettings, ()>;
+
+    /// Reads the next frame or chunk, blocking until the device has one.
+    /// Returns `None` once the device is gone.
+    fn read(&mut self) -> Option<MediaData>;
+
+    fn close(&mut self);
+}
+
+/// Whether to use fake devices rather than those of the platform, which
+/// tests can't rely on.
+fn fake_devices() -> bool {
+    PREFS.get("dom.mediadevices.test").as_boolean().unwrap_or(false)
+}
+
+/// The cameras and microphones that can be captured from.
+pub fn enumerate_devices() -> Vec<CaptureDevice> {
+    if fake_devices() {
+        return vec![camera::fake_device(), microphone::fake_device()];
+    }
+    let mut devices = camera::devices();
+    devices.extend(microphone::devices());
+    devices
+}
+
+fn backend(device: &CaptureDevice) -> Box<CaptureBackend> {
+    match device.kind {
+        MediaKind::Video => camera::backend(device, fake_devices()),
+        MediaKind::Audio => microphone::backend(device, fake_devices()),
+    }
+}
+
+/// Identifies a sink of a capture thread.
+#[derive(Clone, Copy, Debug, Eq, HeapSizeOf, PartialEq)]
+pub struct SinkId(pub usize);
+
+pub enum CaptureMsg {
+    AddSink(SinkId, Box<FnMut(&MediaData) + Send>),
+    RemoveSink(SinkId),
+    /// Adds a function to call on the capture thread when the device stops
+    /// delivering data on its own, e.g. because it was unplugged.
+    AddEndedHandler(Box<Fn() + Send>),
+    /// Closes the device and ends the thread.
+    Stop,
+}
+
+/// The handle to the thread capturing from a device, which is stopped when
+/// the handle is dropped.
+pub struct CaptureThread {
+    sender: Sender<CaptureMsg>,
+    device: CaptureDevice,
+    settings: CaptureSettings,
+    next_sink_id: AtomicUsize,
+}
+
+impl CaptureThread {
+    /// Opens `device` and starts capturing from it.
+    pub fn start(device: CaptureDevice, settings: &CaptureSettings) -> Result<CaptureThread, ()> {
+        let backend = backend(&device);
+        CaptureThread::start_with_backend(device, backend, settings)
+    }
+
+    /// Starts capturing from a source that isn't a device of the platform,
+    /// e.g. a canvas.
+    pub fn start_with_backend(device: CaptureDevice,
+                              mut backend: Box<CaptureBackend>,
+                              settings: &CaptureSettings)
+                              -> Result<CaptureThread, ()> {
+        let settings = try!(backend.open(settings));
+        let (sender, receiver) = channel();
+        spawn_named(format!("CaptureThread for {}", device.id), move || {
+            Capturer {
+                receiver: receiver,
+                backend: backend,
+                sinks: vec![],
+                ended_handlers: vec![],
+            }.run();
+        });
+        Ok(CaptureThread {
+            sender: sender,
+            device: device,
+            settings: settings,
+            next_sink_id: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn device(&self) -> &CaptureDevice {
+        &self.device
+    }
+
+    pub fn settings(&self) -> &CaptureSettings {
+        &self.settings
+    }
+
+    pub fn add_sink(&self, sink: Box<FnMut(&MediaData) + Send>) -> SinkId {
+        let id = SinkId(self.next_sink_id.fetch_add(1, Ordering::Relaxed));
+        self.send(CaptureMsg::AddSink(id, sink));
+        id
+    }
+
+    pub fn send(&self, msg: CaptureMsg) {
+        // The thread is gone once the device ended, and messages to it are
+        // then meaningless.
+        let _ = self.sender.send(msg);
+    }
+}
+
+impl Drop for CaptureThread {
+    fn drop(&mut self) {
+        self.send(CaptureMsg::Stop);
+    }
+}
+
+struct Capturer {
+    receiver: Receiver<CaptureMsg>,
+    backend: Box<CaptureBackend>,
+    sinks: Vec<(SinkId, Box<FnMut(&MediaData) + Send>)>,
+    ended_handlers: Vec<Box<Fn() + Send>>,
+}
+
+impl Capturer {
+    fn run(&mut self) {
+        loop {
+            loop {
+                match self.receiver.try_recv() {
+                    Ok(CaptureMsg::AddSink(id, sink)) => self.sinks.push((id, sink)),
+                    Ok(CaptureMsg::RemoveSink(id)) => self.sinks.retain(|&(sink_id, _)| sink_id != id),
+                    Ok(CaptureMsg::AddEndedHandler(handler)) => self.ended_handlers.push(handler),
+                    Ok(CaptureMsg::Stop) |
+                    Err(TryRecvError::Disconnected) => return self.backend.close(),
+                    Err(TryRecvError::Empty) => break,
+                }
+            }
+            match self.backend.read() {
+                Some(data) => {
+                    for &mut (_, ref mut sink) in &mut self.sinks {
+                        sink(&data);
+                    }
+                },
+                None => {
+                    self.backend.close();
+                    for ended_handler in &self.ended_handlers {
+                        ended_handler();
+                    }
+                    return;
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Audio encoding and decoding with Opus, in the packets and headers that
+//! containers store.
+
+use media_platform::opus::{Decoder, Encoder};
+
+/// The sample rates libopus can encode from.
+const SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+/// How many packets are encoded per second, i.e. 20ms frames.
+const PACKETS_PER_SECOND: u32 = 50;
+/// The largest packet libopus is allowed to output, as recommended.
+const MAX_PACKET_SIZE: usize = 4000;
+/// The number of frames in the longest packets, of 120ms at 48kHz.
+const MAX_PACKET_FRAMES: usize = 5760;
+
+/// Whether audio of `sample_rate` can be encoded without resampling it.
+pub fn is_supported_sample_rate(sample_rate: f32) -> bool {
+    SAMPLE_RATES.iter().any(|&rate| rate as f32 == sample_rate)
+}
+
+/// Encodes audio of one or two channels into 20ms packets.
+pub struct OpusEncoder {
+    encoder: Encoder,
+    sample_rate: u32,
+    channel_count: usize,
+    /// How many frames the first packets are ahead of the audio.
+    lookahead: u32,
+    /// The interleaved samples left over from the last chunk, which don't
+    /// make a whole packet yet.
+    pending: Vec<f32>,
+    /// The number of frames encoded so far.
+    frame_count: u64,
+}
+
+impl OpusEncoder {
+    pub fn new(sample_rate: u32, channel_count: usize, bit_rate: u32) -> Result<OpusEncoder, ()> {
+        if !is_supported_sample_rate(sample_rate as f32) || channel_count == 0 || channel_count > 2 {
+            return Err(());
+        }
+        let encoder = try!(Encoder::new(sample_rate, channel_count, bit_rate));
+        Ok(OpusEncoder {
+            lookahead: encoder.lookahead(),
+            encoder: encoder,
+            sample_rate: sample_rate,
+            channel_count: channel_count,
+            pending: vec![],
+            frame_count: 0,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.channel_count
+    }
+
+    /// The number of frames in every packet.
+    pub fn frames_per_packet(&self) -> usize {
+        (self.sample_rate / PACKETS_PER_SECOND) as usize
+    }
+
+    /// How many frames at 48kHz a decoder has to drop from the start of the
+    /// stream.
+    pub fn pre_skip(&self) -> u16 {
+        (self.lookahead * 48000 / self.sample_rate) as u16
+    }
+
+    /// The identification header of the stream, which containers store as
+    /// the private data of the codec.
+    /// https://tools.ietf.org/html/rfc7845#section-5.1
+    pub fn identification_header(&self) -> Vec<u8> {
+        let pre_skip = self.pre_skip();
+        let mut header = b"OpusHead".to_vec();
+        header.push(1);
+        header.push(self.channel_count as u8);
+        header.extend_from_slice(&[pre_skip as u8, (pre_skip >> 8) as u8]);
+        for i in 0..4 {
+            header.push((self.sample_rate >> (8 * i)) as u8);
+        }
+        // No output gain, and the channel mapping of mono and stereo.
+        header.extend_from_slice(&[0, 0, 0]);
+        header
+    }
+
+    /// Encodes `channels`, returning the packets that could be completed
+    /// along with the frame they start at. A missing second channel is
+    /// encoded as silence, and further channels are dropped.
+    pub fn encode(&mut self, channels: &[Vec<f32>]) -> Vec<(u64, Vec<u8>)> {
+        let frames = channels.first().map_or(0, |channel| channel.len());
+        for frame in 0..frames {
+            for channel in 0..self.channel_count {
+                let sample = channels.get(channel).and_then(|samples| samples.get(frame)).cloned();
+                self.pending.push(sample.unwrap_or(0.));
+            }
+        }
+
+        let packet_samples = self.frames_per_packet() * self.channel_count;
+        let mut packets = vec![];
+        let mut start = 0;
+        while self.pending.len() - start >= packet_samples {
+            let mut packet = vec![0; MAX_PACKET_SIZE];
+            let size = self.encoder.encode(&self.pending[start..start + packet_samples], &mut packet);
+            let first_frame = self.frame_count;
+            start += packet_samples;
+            self.frame_count += self.frames_per_packet() as u64;
+            if let Ok(size) = size {
+                packet.truncate(size);
+                packets.push((first_frame, packet));
+            }
+        }
+        self.pending.drain(..start);
+        packets
+    }
+}
+
+/// Decodes the packets of a stream to audio at 48kHz, which is what Opus
+/// always encodes.
+pub struct OpusDecoder {
+    decoder: Decoder,
+    channel_count: usize,
+    /// How many frames at the start of the stream are still to be dropped.
+    pre_skip: usize,
+}
+
+impl OpusDecoder {
+    /// Makes a decoder for the stream with the identification header
+    /// `header`, of mono or stereo audio.
+    /// https://tools.ietf.org/html/rfc7845#section-5.1
+    pub fn new(header: &[u8]) -> Result<OpusDecoder, ()> {
+        if header.len() < 19 || &header[..8] != b"OpusHead" || header[18] != 0 {
+            return Err(());
+        }
+        let channel_count = header[9] as usize;
+        if channel_count == 0 || channel_count > 2 {
+            return Err(());
+        }
+        Ok(OpusDecoder {
+            decoder: try!(Decoder::new(channel_count)),
+            channel_count: channel_count,
+            pre_skip: (header[10] as u16 | (header[11] as u16) << 8) as usize,
+        })
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.channel_count
+    }
+
+    /// Starts decoding from somewhere else than the start of the stream,
+    /// which has no frames to drop.
+    pub fn reset(&mut self) {
+        self.pre_skip = 0;
+    }
+
+    /// Decodes a packet, returning its samples in one vector per channel.
+    pub fn decode(&mut self, packet: &[u8]) -> Vec<Vec<f32>> {
+        let mut samples = vec![0.; MAX_PACKET_FRAMES * self.channel_count];
+        let frames = match self.decoder.decode(packet, &mut samples) {
+            Ok(frames) => frames,
+            Err(()) => return vec![vec![]; self.channel_count],
+        };
+        let skipped = self.pre_skip.min(frames);
+        self.pre_skip -= skipped;
+        (0..self.channel_count).map(|channel| {
+            (skipped..frames).map(|frame| samples[frame * self.channel_count + channel]).collect()
+        }).collect()
+    }
+}
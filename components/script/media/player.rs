@@ -0,0 +1,673 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Playback of media resources, for `<audio>` and `<video>`.
+//!
+//! Every playing resource has a player thread, which decodes it a frame at
+//! a time and hands each video frame to a sink like those of capture threads,
+//! when the frame is due, and writes its audio to an `AudioSink`, which sets
+//! the pace of the playback as it does for audio contexts.
+//!
+//! WebM files with VP8 video and Opus audio, and WAVE files, can be played,
+//! as can the WebM streams appended to the source buffers of media sources,
+//! which the player waits for when it gets to the end of what was appended.
+
+use audio::FRAMES_PER_BLOCK;
+use audio::block::Block;
+use audio::decoder::{DecodeError, decode_audio_data};
+use audio::sink::AudioSink;
+use media::{AudioChunk, MediaData};
+use media::opus::OpusDecoder;
+use media::source::{SharedSourceData, TrackId};
+use media::vp8::Vp8Decoder;
+use media::webm::{WebmFile, WebmFrame, WebmTrack, read_webm};
+use std::ascii::AsciiExt;
+use std::f64;
+use std::mem;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, TryRecvError, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+use util::thread::spawn_named;
+
+/// The sample rate that all audio is played at.
+const SAMPLE_RATE: f32 = 48000.;
+
+/// How many frames of a WAVE file are played at once.
+const WAVE_CHUNK_FRAMES: usize = 4800;
+
+/// How often the playback position is reported while playing, in
+/// milliseconds, within the 15 to 250 that the spec allows.
+/// https://html.spec.whatwg.org/multipage/#time-marches-on
+const TIME_UPDATE_INTERVAL: u64 = 250;
+
+/// How often a player that waits for frames to be appended to its media
+/// source checks for them, in milliseconds.
+const WAIT_INTERVAL: u64 = 20;
+
+/// What is known about a resource once its header was read.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MediaInfo {
+    /// The duration of the resource, in seconds.
+    pub duration: f64,
+    /// The size of the frames of its video track, if it has one.
+    pub video_size: Option<(u32, u32)>,
+    /// The number of channels of its audio track, if it has one.
+    pub audio_channels: Option<usize>,
+}
+
+/// Whether `mime_type` is the type of a resource that can be played, in the
+/// terms of `canPlayType()`: `Some(true)` for "probably", `Some(false)` for
+/// "maybe", and `None` for the empty string.
+/// https://html.spec.whatwg.org/multipage/#dom-navigator-canplaytype
+pub fn can_play_type(mime_type: &str) -> Option<bool> {
+    let mut parts = mime_type.split(';');
+    let essence = parts.next().unwrap_or("").trim().to_lowercase();
+    let codecs = parts.filter_map(|parameter| {
+        let mut parameter = parameter.splitn(2, '=');
+        match (parameter.next(), parameter.next()) {
+            (Some(name), Some(value)) if name.trim().eq_ignore_ascii_case("codecs") => {
+                Some(value.trim().trim_matches('"').to_lowercase())
+            },
+            _ => None,
+        }
+    }).next();
+    let supported_codecs: &[&str] = match &*essence {
+        "video/webm" => &["vp8", "opus"],
+        "audio/webm" => &["opus"],
+        "audio/wav" | "audio/wave" | "audio/x-wav" => &["1"],
+        _ => return None,
+    };
+    match codecs {
+        Some(codecs) => {
+            if codecs.split(',').all(|codec| supported_codecs.contains(&codec.trim())) {
+                Some(true)
+            } else {
+                None
+            }
+        },
+        None => Some(false),
+    }
+}
+
+/// Whether `mime_type` is the type of a stream that can be appended to the
+/// source buffers of a media source, which WebM ones of the codecs that can
+/// be played can.
+/// https://w3c.github.io/media-source/#dom-mediasource-istypesupported
+pub fn can_append_type(mime_type: &str) -> bool {
+    let essence = mime_type.split(';').next().unwrap_or("").trim();
+    (essence.eq_ignore_ascii_case("video/webm") || essence.eq_ignore_ascii_case("audio/webm")) &&
+    can_play_type(mime_type).is_some()
+}
+
+/// Decodes a resource a frame at a time.
+trait MediaDecoder: Send {
+    fn info(&self) -> MediaInfo;
+
+    /// Moves to `time`, so that the next frames are the first ones shown at
+    /// or after it.
+    fn seek(&mut self, time: f64);
+
+    /// Decodes the next frame or chunk of audio.
+    fn next(&mut self) -> Decoded;
+}
+
+enum Decoded {
+    /// A frame or chunk of audio, along with the time it is shown at in
+    /// seconds.
+    Data(f64, MediaData),
+    /// The next frame wasn't appended to the media source yet.
+    Waiting,
+    /// The end of the resource was reached.
+    End,
+}
+
+fn decoder(data: Vec<u8>) -> Result<Box<MediaDecoder>, DecodeError> {
+    if data.starts_with(b"RIFF") {
+        let audio = try!(decode_audio_data(&data, SAMPLE_RATE));
+        return Ok(box WaveDecoder {
+            channels: audio.channels,
+            position: 0,
+        });
+    }
+    if data.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        let file = try!(read_webm(&data).map_err(|()| DecodeError::Malformed));
+        return WebmDecoder::new(file).map(|decoder| box decoder as Box<MediaDecoder>);
+    }
+    Err(DecodeError::UnsupportedFormat)
+}
+
+/// Plays a WAVE file, which is decoded all at once.
+struct WaveDecoder {
+    channels: Vec<Vec<f32>>,
+    /// The next frame to play.
+    position: usize,
+}
+
+impl WaveDecoder {
+    fn len(&self) -> usize {
+        self.channels.first().map_or(0, |channel| channel.len())
+    }
+}
+
+impl MediaDecoder for WaveDecoder {
+    fn info(&self) -> MediaInfo {
+        MediaInfo {
+            duration: self.len() as f64 / SAMPLE_RATE as f64,
+            video_size: None,
+            audio_channels: Some(self.channels.len()),
+        }
+    }
+
+    fn seek(&mut self, time: f64) {
+        self.position = ((time * SAMPLE_RATE as f64) as usize).min(self.len());
+    }
+
+    fn next(&mut self) -> Decoded {
+        if self.position >= self.len() {
+            return Decoded::End;
+        }
+        let start = self.position;
+        let end = (start + WAVE_CHUNK_FRAMES).min(self.len());
+        self.position = end;
+        let chunk = AudioChunk {
+            sample_rate: SAMPLE_RATE,
+            channels: self.channels.iter().map(|channel| channel[start..end].to_vec()).collect(),
+        };
+        Decoded::Data(start as f64 / SAMPLE_RATE as f64, MediaData::Audio(Arc::new(chunk)))
+    }
+}
+
+/// Plays the first VP8 and Opus tracks of a WebM file.
+struct WebmDecoder {
+    file: WebmFile,
+    video: Option<(u64, Vp8Decoder)>,
+    audio: Option<(u64, OpusDecoder)>,
+    /// The index of the next frame of the file to decode.
+    next_frame: usize,
+    /// The frames before this time are only decoded for the ones after it to
+    /// be, after a seek.
+    seek_time: f64,
+}
+
+impl WebmDecoder {
+    fn new(file: WebmFile) -> Result<WebmDecoder, DecodeError> {
+        let (video, audio) = try!(webm_decoders(&file.tracks));
+        Ok(WebmDecoder {
+            file: file,
+            video: video,
+            audio: audio,
+            next_frame: 0,
+            seek_time: 0.,
+        })
+    }
+}
+
+impl MediaDecoder for WebmDecoder {
+    fn info(&self) -> MediaInfo {
+        let last_frame = self.file.frames.last().map_or(0., |frame| frame.time);
+        let video_size = self.video.as_ref().and_then(|&(number, _)| {
+            self.file.tracks.iter().filter_map(|&(track_number, ref track)| match *track {
+                WebmTrack::Vp8 { width, height } if track_number == number => Some((width, height)),
+                _ => None,
+            }).next()
+        });
+        MediaInfo {
+            duration: self.file.duration.unwrap_or(last_frame),
+            video_size: video_size,
+            audio_channels: self.audio.as_ref().map(|&(_, ref decoder)| decoder.channel_count()),
+        }
+    }
+
+    fn seek(&mut self, time: f64) {
+        // Video can only be decoded from a key frame on, and Opus packets
+        // can all be decoded on their own.
+        let video_track = self.video.as_ref().map(|&(number, _)| number);
+        let start = self.file.frames.iter().rposition(|frame| {
+            frame.time <= time && (video_track.is_none() || (Some(frame.track) == video_track && frame.key_frame))
+        });
+        self.next_frame = start.unwrap_or(0);
+        self.seek_time = time;
+        if let Some((_, ref mut decoder)) = self.audio {
+            if time > 0. {
+                decoder.reset();
+            }
+        }
+    }
+
+    fn next(&mut self) -> Decoded {
+        while let Some(frame) = self.file.frames.get(self.next_frame) {
+            self.next_frame += 1;
+            let mut data = None;
+            if let Some((number, ref mut decoder)) = self.video {
+                if number == frame.track {
+                    data = decode_video(decoder, frame);
+                }
+            }
+            if let Some((number, ref mut decoder)) = self.audio {
+                if number == frame.track {
+                    data = Some(decode_audio(decoder, frame));
+                }
+            }
+            if frame.time < self.seek_time {
+                continue;
+            }
+            if let Some(data) = data {
+                return Decoded::Data(frame.time, data);
+            }
+        }
+        Decoded::End
+    }
+}
+
+fn decode_video(decoder: &mut Vp8Decoder, frame: &WebmFrame) -> Option<MediaData> {
+    decoder.decode(&frame.data).map(|frame| MediaData::Video(Arc::new(frame)))
+}
+
+fn decode_audio(decoder: &mut OpusDecoder, frame: &WebmFrame) -> MediaData {
+    let chunk = AudioChunk {
+        sample_rate: SAMPLE_RATE,
+        channels: decoder.decode(&frame.data),
+    };
+    MediaData::Audio(Arc::new(chunk))
+}
+
+/// The decoders of the first VP8 and Opus tracks of a WebM stream, along with
+/// their track numbers.
+fn webm_decoders<T: Copy>(tracks: &[(T, WebmTrack)])
+                          -> Result<(Option<(T, Vp8Decoder)>, Option<(T, OpusDecoder)>), DecodeError> {
+    let mut video = None;
+    let mut audio = None;
+    for &(number, ref track) in tracks {
+        match *track {
+            WebmTrack::Vp8 { .. } if video.is_none() => {
+                let decoder = try!(Vp8Decoder::new().map_err(|()| DecodeError::UnsupportedFormat));
+                video = Some((number, decoder));
+            },
+            WebmTrack::Opus { ref identification_header, .. } if audio.is_none() => {
+                let decoder = try!(OpusDecoder::new(identification_header)
+                                       .map_err(|()| DecodeError::UnsupportedFormat));
+                audio = Some((number, decoder));
+            },
+            _ => {},
+        }
+    }
+    if video.is_none() && audio.is_none() {
+        return Err(DecodeError::UnsupportedFormat);
+    }
+    Ok((video, audio))
+}
+
+/// Plays the first VP8 and Opus tracks appended to the source buffers of a
+/// media source, as they are appended.
+struct SourceDecoder {
+    data: SharedSourceData,
+    video_size: Option<(u32, u32)>,
+    video: Option<(TrackId, Vp8Decoder)>,
+    audio: Option<(TrackId, OpusDecoder)>,
+    /// The time of the last frame decoded of each track, after which the
+    /// next one is, or the time the next one is at or after.
+    video_position: (f64, bool),
+    audio_position: (f64, bool),
+    /// Frames of video before the next key frame can't be decoded.
+    needs_key_frame: bool,
+    /// The frames before this time are only decoded for the ones after it to
+    /// be, after a seek.
+    seek_time: f64,
+}
+
+impl SourceDecoder {
+    fn new(data: SharedSourceData) -> Result<SourceDecoder, DecodeError> {
+        let tracks = data.lock().unwrap().tracks();
+        let (video, audio) = try!(webm_decoders(&tracks));
+        let video_size = video.as_ref().and_then(|&(id, _)| {
+            tracks.iter().filter_map(|&(track_id, ref track)| match *track {
+                WebmTrack::Vp8 { width, height } if track_id == id => Some((width, height)),
+                _ => None,
+            }).next()
+        });
+        Ok(SourceDecoder {
+            data: data,
+            video_size: video_size,
+            video: video,
+            audio: audio,
+            video_position: (0., true),
+            audio_position: (0., true),
+            needs_key_frame: true,
+            seek_time: 0.,
+        })
+    }
+}
+
+impl MediaDecoder for SourceDecoder {
+    fn info(&self) -> MediaInfo {
+        MediaInfo {
+            // The duration is the one of the media source.
+            duration: f64::NAN,
+            video_size: self.video_size,
+            audio_channels: self.audio.as_ref().map(|&(_, ref decoder)| decoder.channel_count()),
+        }
+    }
+
+    fn seek(&mut self, time: f64) {
+        let data = self.data.lock().unwrap();
+        self.video_position = match self.video {
+            Some((id, _)) => data.key_frame_before(id, time).map_or((time, true), |frame| (frame.time, true)),
+            None => (time, true),
+        };
+        self.needs_key_frame = true;
+        self.audio_position = (time, true);
+        self.seek_time = time;
+        if let Some((_, ref mut decoder)) = self.audio {
+            if time > 0. {
+                decoder.reset();
+            }
+        }
+    }
+
+    fn next(&mut self) -> Decoded {
+        loop {
+            let (frame, is_video) = {
+                let data = self.data.lock().unwrap();
+                let video = self.video.as_ref().map(|&(id, _)| {
+                    data.next_frame(id, self.video_position.0, self.video_position.1)
+                });
+                let audio = self.audio.as_ref().map(|&(id, _)| {
+                    data.next_frame(id, self.audio_position.0, self.audio_position.1)
+                });
+                // The tracks are played together, so that one that wasn't
+                // appended as far as the others waits for them.
+                let missing = |frame: Option<Option<&WebmFrame>>| frame.map_or(false, |frame| frame.is_none());
+                if !data.ended && (missing(video) || missing(audio)) {
+                    return Decoded::Waiting;
+                }
+                match (video.and_then(|frame| frame), audio.and_then(|frame| frame)) {
+                    (Some(video), Some(audio)) if audio.time < video.time => (audio.clone(), false),
+                    (Some(video), _) => (video.clone(), true),
+                    (None, Some(audio)) => (audio.clone(), false),
+                    (None, None) => return Decoded::End,
+                }
+            };
+
+            let data = if is_video {
+                self.video_position = (frame.time, false);
+                if self.needs_key_frame && !frame.key_frame {
+                    continue;
+                }
+                self.needs_key_frame = false;
+                self.video.as_mut().and_then(|&mut (_, ref mut decoder)| decode_video(decoder, &frame))
+            } else {
+                self.audio_position = (frame.time, false);
+                self.audio.as_mut().map(|&mut (_, ref mut decoder)| decode_audio(decoder, &frame))
+            };
+            if frame.time < self.seek_time {
+                continue;
+            }
+            if let Some(data) = data {
+                return Decoded::Data(frame.time, data);
+            }
+        }
+    }
+}
+
+pub enum PlayerMsg {
+    Play,
+    Pause,
+    /// Moves the playback position to the given time in seconds.
+    Seek(f64),
+    /// Stops playing and ends the thread.
+    Stop,
+}
+
+/// Notifications from the player thread to its media element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayerEvent {
+    /// The playback position moved on to the given time while playing.
+    TimeUpdate(f64),
+    /// A seek to the given time is done.
+    Seeked(f64),
+    /// The end of the resource was reached, and playing stopped.
+    Ended,
+    /// Playing stopped at the given time to wait for more frames to be
+    /// appended to the media source, and goes on once they are.
+    Waiting(f64),
+}
+
+/// The handle to the thread playing a resource, which is stopped when the
+/// handle is dropped.
+pub struct MediaPlayer {
+    sender: Sender<PlayerMsg>,
+    info: MediaInfo,
+}
+
+impl MediaPlayer {
+    /// Reads the header of `data`, and starts a paused player for it, which
+    /// gives its video frames to `video_sink`, plays its audio to
+    /// `audio_sink` and notifies `event_handler` of its progress, all on the
+    /// player thread.
+    pub fn start(data: Vec<u8>,
+                 video_sink: Box<FnMut(&MediaData) + Send>,
+                 audio_sink: Box<AudioSink>,
+                 event_handler: Box<Fn(PlayerEvent) + Send>)
+                 -> Result<MediaPlayer, DecodeError> {
+        let decoder = try!(decoder(data));
+        Ok(MediaPlayer::spawn(decoder, video_sink, audio_sink, event_handler))
+    }
+
+    /// Starts a paused player for the tracks that were appended to the source
+    /// buffers of a media source, which plays the frames appended to them
+    /// as they come.
+    pub fn start_source(data: SharedSourceData,
+                        video_sink: Box<FnMut(&MediaData) + Send>,
+                        audio_sink: Box<AudioSink>,
+                        event_handler: Box<Fn(PlayerEvent) + Send>)
+                        -> Result<MediaPlayer, DecodeError> {
+        let decoder = try!(SourceDecoder::new(data));
+        Ok(MediaPlayer::spawn(box decoder, video_sink, audio_sink, event_handler))
+    }
+
+    fn spawn(decoder: Box<MediaDecoder>,
+             video_sink: Box<FnMut(&MediaData) + Send>,
+             audio_sink: Box<AudioSink>,
+             event_handler: Box<Fn(PlayerEvent) + Send>)
+             -> MediaPlayer {
+        let info = decoder.info();
+        let (sender, receiver) = channel();
+        spawn_named("MediaPlayer".to_owned(), move || {
+            Player {
+                receiver: receiver,
+                decoder: decoder,
+                video_sink: video_sink,
+                audio_sink: audio_sink,
+                audio_channels: info.audio_channels,
+                audio_started: false,
+                pending_audio: vec![],
+                event_handler: event_handler,
+                playing: false,
+                waiting: false,
+                position: 0.,
+                clock: None,
+                next_time_update: Instant::now(),
+            }.run();
+        });
+        MediaPlayer {
+            sender: sender,
+            info: info,
+        }
+    }
+
+    pub fn info(&self) -> &MediaInfo {
+        &self.info
+    }
+
+    pub fn send(&self, msg: PlayerMsg) {
+        // The thread only ends once stopped.
+        let _ = self.sender.send(msg);
+    }
+}
+
+impl Drop for MediaPlayer {
+    fn drop(&mut self) {
+        self.send(PlayerMsg::Stop);
+    }
+}
+
+struct Player {
+    receiver: Receiver<PlayerMsg>,
+    decoder: Box<MediaDecoder>,
+    video_sink: Box<FnMut(&MediaData) + Send>,
+    audio_sink: Box<AudioSink>,
+    audio_channels: Option<usize>,
+    /// Whether the audio sink was started since playing last started.
+    audio_started: bool,
+    /// The audio that doesn't make a whole block yet, by channel.
+    pending_audio: Vec<Vec<f32>>,
+    event_handler: Box<Fn(PlayerEvent) + Send>,
+    playing: bool,
+    /// Whether playing waits for frames to be appended to the media source.
+    waiting: bool,
+    /// The time of the latest frame played.
+    position: f64,
+    /// When playing last started, and the time of the first frame shown
+    /// since, which the times of the next video frames are relative to.
+    clock: Option<(Instant, f64)>,
+    next_time_update: Instant,
+}
+
+impl Player {
+    fn run(&mut self) {
+        loop {
+            let msg = if self.waiting {
+                match self.receiver.recv_timeout(Duration::from_millis(WAIT_INTERVAL)) {
+                    Ok(msg) => Some(msg),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            } else if self.playing {
+                match self.receiver.try_recv() {
+                    Ok(msg) => Some(msg),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => return self.stop_audio(),
+                }
+            } else {
+                match self.receiver.recv() {
+                    Ok(msg) => Some(msg),
+                    Err(_) => return,
+                }
+            };
+            match msg {
+                Some(PlayerMsg::Play) => {
+                    self.playing = true;
+                    self.clock = None;
+                },
+                Some(PlayerMsg::Pause) => {
+                    self.playing = false;
+                    self.waiting = false;
+                    self.stop_audio();
+                },
+                Some(PlayerMsg::Seek(time)) => {
+                    self.decoder.seek(time);
+                    self.position = time;
+                    self.waiting = false;
+                    self.clock = None;
+                    self.pending_audio.clear();
+                    // TODO: show the frame at the new position while paused.
+                    (self.event_handler)(PlayerEvent::Seeked(time));
+                },
+                Some(PlayerMsg::Stop) => return self.stop_audio(),
+                None => {
+                    match self.decoder.next() {
+                        Decoded::Data(time, data) => {
+                            if self.waiting {
+                                // The frames after the wait are due from
+                                // when it ended.
+                                self.waiting = false;
+                                self.clock = None;
+                            }
+                            self.present(time, data);
+                        },
+                        Decoded::Waiting => {
+                            if !self.waiting {
+                                self.waiting = true;
+                                self.stop_audio();
+                                (self.event_handler)(PlayerEvent::Waiting(self.position));
+                            }
+                        },
+                        Decoded::End => {
+                            self.playing = false;
+                            self.waiting = false;
+                            self.stop_audio();
+                            (self.event_handler)(PlayerEvent::Ended);
+                        },
+                    }
+                },
+            }
+        }
+    }
+
+    /// Shows a video frame once it is due, or plays a chunk of audio.
+    fn present(&mut self, time: f64, data: MediaData) {
+        self.position = time;
+        let now = Instant::now();
+        let (start, start_time) = match self.clock {
+            Some(clock) => clock,
+            None => {
+                self.clock = Some((now, time));
+                (now, time)
+            },
+        };
+        match data {
+            MediaData::Video(_) => {
+                let offset = (time - start_time).max(0.);
+                let due = start + Duration::new(offset as u64, (offset.fract() * 1e9) as u32);
+                if due > now {
+                    thread::sleep(due - now);
+                }
+                (self.video_sink)(&data);
+            },
+            MediaData::Audio(ref chunk) => self.play_audio(chunk),
+        }
+
+        let now = Instant::now();
+        if now >= self.next_time_update {
+            (self.event_handler)(PlayerEvent::TimeUpdate(time));
+            self.next_time_update = now + Duration::from_millis(TIME_UPDATE_INTERVAL);
+        }
+    }
+
+    /// Writes a chunk of audio to the output a block at a time, which blocks
+    /// until the output is ready for it.
+    fn play_audio(&mut self, chunk: &AudioChunk) {
+        let channel_count = match self.audio_channels {
+            Some(channel_count) => channel_count,
+            None => return,
+        };
+        if !self.audio_started {
+            if self.audio_sink.start(SAMPLE_RATE, channel_count).is_err() {
+                warn!("Failed to open the audio output device");
+            }
+            self.audio_started = true;
+        }
+        if self.pending_audio.len() != channel_count {
+            self.pending_audio = vec![vec![]; channel_count];
+        }
+        for (pending, samples) in self.pending_audio.iter_mut().zip(chunk.channels.iter()) {
+            pending.extend_from_slice(samples);
+        }
+        while self.pending_audio[0].len() >= FRAMES_PER_BLOCK {
+            let channels = self.pending_audio.iter_mut().map(|pending| {
+                let rest = pending.split_off(FRAMES_PER_BLOCK);
+                mem::replace(pending, rest)
+            }).collect();
+            self.audio_sink.push(&Block { channels: channels });
+        }
+    }
+
+    fn stop_audio(&mut self) {
+        if self.audio_started {
+            self.audio_sink.stop();
+            self.audio_started = false;
+        }
+    }
+}
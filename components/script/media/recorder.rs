@@ -0,0 +1,346 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Encoding of tracks to WebM, for `MediaRecorder`.
+//!
+//! The sinks a recorder adds to its tracks send what they get to an encoder
+//! thread, which muxes the encoded frames and hands the WebM data to its
+//! recorder whenever it is asked for, or once per timeslice.
+
+use audio::AudioBufferData;
+use audio::decoder::resample;
+use media::{CaptureSettings, MediaData, MediaKind};
+use media::opus::{self, OpusEncoder};
+use media::vp8::Vp8Encoder;
+use media::webm::{WebmMuxer, WebmTrack};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, channel};
+use std::time::{Duration, Instant};
+use util::thread::spawn_named;
+
+/// What the tracks of a recording are encoded to, always in WebM.
+#[derive(Clone, Copy, Debug, Eq, HeapSizeOf, PartialEq)]
+pub struct RecordingFormat {
+    /// Whether audio is recorded, with Opus.
+    pub audio: bool,
+    /// Whether video is recorded, with VP8.
+    pub video: bool,
+}
+
+impl RecordingFormat {
+    /// Parses a MIME type like `video/webm;codecs="vp8,opus"`, returning
+    /// `None` if it can't be recorded to.
+    pub fn from_mime_type(mime_type: &str) -> Option<RecordingFormat> {
+        let mime_type = mime_type.to_lowercase();
+        let mut parts = mime_type.split(';').map(str::trim);
+        let video = match parts.next() {
+            Some("video/webm") => true,
+            Some("audio/webm") => false,
+            _ => return None,
+        };
+        let codecs = parts.filter_map(|parameter| {
+            let mut parameter = parameter.splitn(2, '=');
+            match (parameter.next().map(str::trim), parameter.next()) {
+                (Some("codecs"), Some(value)) => Some(value.trim().trim_matches('"').to_owned()),
+                _ => None,
+            }
+        }).last();
+        let codecs = match codecs {
+            Some(codecs) => codecs,
+            None => return Some(RecordingFormat { audio: true, video: video }),
+        };
+
+        let mut format = RecordingFormat { audio: false, video: false };
+        for codec in codecs.split(',').map(str::trim) {
+            match codec {
+                "opus" => format.audio = true,
+                "vp8" if video => format.video = true,
+                _ => return None,
+            }
+        }
+        Some(format)
+    }
+
+    pub fn mime_type(&self) -> String {
+        match (self.video, self.audio) {
+            (true, true) => "video/webm;codecs=vp8,opus",
+            (true, false) => "video/webm;codecs=vp8",
+            (false, _) => "audio/webm;codecs=opus",
+        }.to_owned()
+    }
+}
+
+pub struct RecorderOptions {
+    pub audio_bit_rate: u32,
+    pub video_bit_rate: u32,
+    /// How often the data recorded so far is delivered, if ever before the
+    /// recording stops.
+    pub timeslice: Option<Duration>,
+}
+
+pub enum RecorderMsg {
+    /// Data of the track at the given index, in the tracks that the thread
+    /// was started with.
+    Data(usize, MediaData),
+    /// The track at the given index stopped delivering data.
+    TrackEnded(usize),
+    Pause,
+    Resume,
+    /// Delivers the data recorded so far.
+    RequestData,
+    /// Delivers the data that is left and ends the thread.
+    Stop,
+}
+
+/// Notifications from the encoder thread to its recorder.
+pub enum RecorderEvent {
+    /// Data recorded since the last one, with the time it starts at in
+    /// milliseconds.
+    Data(Vec<u8>, f64),
+    /// Every track ended, and there is nothing left to record.
+    TracksEnded,
+    /// The last data of the recording, after a `Stop` message.
+    Stopped(Vec<u8>, f64),
+}
+
+enum TrackEncoder {
+    Audio(OpusEncoder),
+    Video(Vp8Encoder),
+}
+
+/// The handle to the thread encoding a recording, which is stopped when the
+/// handle is dropped.
+pub struct RecorderThread {
+    sender: Sender<RecorderMsg>,
+}
+
+impl RecorderThread {
+    /// Starts encoding tracks of the given kinds and settings, returning an
+    /// error if an encoder can't be created for one.
+    pub fn start(tracks: &[(MediaKind, CaptureSettings)],
+                 options: &RecorderOptions,
+                 event_handler: Box<Fn(RecorderEvent) + Send>)
+                 -> Result<RecorderThread, ()> {
+        let mut encoders = vec![];
+        let mut webm_tracks = vec![];
+        for &(kind, ref settings) in tracks {
+            match kind {
+                MediaKind::Audio => {
+                    // Opus supports a few rates only, to which the others
+                    // are resampled.
+                    let sample_rate = if opus::is_supported_sample_rate(settings.sample_rate) {
+                        settings.sample_rate as u32
+                    } else {
+                        48000
+                    };
+                    let channel_count = (settings.channel_count as usize).max(1).min(2);
+                    let encoder = try!(OpusEncoder::new(sample_rate, channel_count, options.audio_bit_rate));
+                    webm_tracks.push(WebmTrack::Opus {
+                        sample_rate: sample_rate,
+                        channel_count: channel_count,
+                        identification_header: encoder.identification_header(),
+                        codec_delay: encoder.pre_skip() as u64 * 1000000000 / 48000,
+                    });
+                    encoders.push(TrackEncoder::Audio(encoder));
+                },
+                MediaKind::Video => {
+                    let encoder = try!(Vp8Encoder::new(settings.width,
+                                                       settings.height,
+                                                       settings.frame_rate,
+                                                       options.video_bit_rate));
+                    webm_tracks.push(WebmTrack::Vp8 {
+                        width: settings.width,
+                        height: settings.height,
+                    });
+                    encoders.push(TrackEncoder::Video(encoder));
+                },
+            }
+        }
+
+        let (sender, receiver) = channel();
+        let timeslice = options.timeslice;
+        spawn_named("MediaRecorderEncoder".to_owned(), move || {
+            Encoder {
+                receiver: receiver,
+                event_handler: event_handler,
+                ended: vec![false; encoders.len()],
+                encoders: encoders,
+                muxer: WebmMuxer::new(&webm_tracks),
+                timeslice: timeslice,
+                start: Instant::now(),
+                paused_since: None,
+                paused_duration: Duration::from_millis(0),
+                data_start: 0,
+            }.run();
+        });
+        Ok(RecorderThread {
+            sender: sender,
+        })
+    }
+
+    /// A sink for the track at `index`, to add to its source. The thread is
+    /// told that the track ended once the sink is dropped.
+    pub fn sink(&self, index: usize) -> Box<FnMut(&MediaData) + Send> {
+        let guard = SinkGuard {
+            sender: self.sender.clone(),
+            index: index,
+        };
+        box move |data: &MediaData| {
+            let _ = guard.sender.send(RecorderMsg::Data(guard.index, data.clone()));
+        }
+    }
+
+    pub fn send(&self, msg: RecorderMsg) {
+        // The thread is gone once the recording stopped.
+        let _ = self.sender.send(msg);
+    }
+}
+
+impl Drop for RecorderThread {
+    fn drop(&mut self) {
+        self.send(RecorderMsg::Stop);
+    }
+}
+
+struct SinkGuard {
+    sender: Sender<RecorderMsg>,
+    index: usize,
+}
+
+impl Drop for SinkGuard {
+    fn drop(&mut self) {
+        let _ = self.sender.send(RecorderMsg::TrackEnded(self.index));
+    }
+}
+
+struct Encoder {
+    receiver: Receiver<RecorderMsg>,
+    event_handler: Box<Fn(RecorderEvent) + Send>,
+    encoders: Vec<TrackEncoder>,
+    ended: Vec<bool>,
+    muxer: WebmMuxer,
+    timeslice: Option<Duration>,
+    start: Instant,
+    paused_since: Option<Instant>,
+    /// How long the recording was paused for, which isn't part of it.
+    paused_duration: Duration,
+    /// The time that the data not delivered yet starts at.
+    data_start: u64,
+}
+
+impl Encoder {
+    fn run(&mut self) {
+        let mut deadline = self.timeslice.map(|timeslice| Instant::now() + timeslice);
+        loop {
+            let msg = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if deadline > now {
+                        self.receiver.recv_timeout(deadline - now)
+                    } else {
+                        Err(RecvTimeoutError::Timeout)
+                    }
+                },
+                None => self.receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match msg {
+                Ok(RecorderMsg::Data(index, data)) => {
+                    if self.paused_since.is_none() {
+                        self.encode(index, &data);
+                    }
+                },
+                Ok(RecorderMsg::TrackEnded(index)) => {
+                    self.ended[index] = true;
+                    if self.ended.iter().all(|&ended| ended) {
+                        (self.event_handler)(RecorderEvent::TracksEnded);
+                    }
+                },
+                Ok(RecorderMsg::Pause) => {
+                    if self.paused_since.is_none() {
+                        self.paused_since = Some(Instant::now());
+                    }
+                    deadline = None;
+                },
+                Ok(RecorderMsg::Resume) => {
+                    if let Some(paused_since) = self.paused_since.take() {
+                        self.paused_duration += paused_since.elapsed();
+                    }
+                    deadline = self.timeslice.map(|timeslice| Instant::now() + timeslice);
+                },
+                Ok(RecorderMsg::RequestData) => {
+                    let (data, timecode) = self.take_data();
+                    (self.event_handler)(RecorderEvent::Data(data, timecode));
+                    if self.paused_since.is_none() {
+                        deadline = self.timeslice.map(|timeslice| Instant::now() + timeslice);
+                    }
+                },
+                Err(RecvTimeoutError::Timeout) => {
+                    let (data, timecode) = self.take_data();
+                    (self.event_handler)(RecorderEvent::Data(data, timecode));
+                    deadline = match (deadline, self.timeslice) {
+                        (Some(deadline), Some(timeslice)) => Some(deadline + timeslice),
+                        _ => None,
+                    };
+                },
+                Ok(RecorderMsg::Stop) |
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.flush();
+                    let (data, timecode) = self.take_data();
+                    (self.event_handler)(RecorderEvent::Stopped(data, timecode));
+                    return;
+                },
+            }
+        }
+    }
+
+    /// The time spent recording so far, in milliseconds.
+    fn recording_time(&self) -> u64 {
+        let time = self.start.elapsed() - self.paused_duration;
+        time.as_secs() * 1000 + (time.subsec_nanos() / 1000000) as u64
+    }
+
+    fn take_data(&mut self) -> (Vec<u8>, f64) {
+        let timecode = self.data_start;
+        self.data_start = self.recording_time();
+        (self.muxer.take_data(), timecode as f64)
+    }
+
+    fn encode(&mut self, index: usize, data: &MediaData) {
+        let time = self.recording_time();
+        match (&mut self.encoders[index], data) {
+            (&mut TrackEncoder::Audio(ref mut encoder), &MediaData::Audio(ref chunk)) => {
+                let sample_rate = encoder.sample_rate();
+                let packets = if chunk.sample_rate == sample_rate as f32 {
+                    encoder.encode(&chunk.channels)
+                } else {
+                    let data = AudioBufferData {
+                        channels: chunk.channels.clone(),
+                        sample_rate: chunk.sample_rate,
+                    };
+                    encoder.encode(&resample(data, sample_rate as f32).channels)
+                };
+                for (first_frame, packet) in packets {
+                    let timecode = first_frame * 1000 / sample_rate as u64;
+                    self.muxer.add_frame(index, timecode, true, &packet);
+                }
+            },
+            (&mut TrackEncoder::Video(ref mut encoder), &MediaData::Video(ref frame)) => {
+                for frame in encoder.encode(frame, time, false) {
+                    self.muxer.add_frame(index, frame.timestamp, frame.key_frame, &frame.data);
+                }
+            },
+            _ => warn!("Ignored data of another kind than its track"),
+        }
+    }
+
+    /// Writes the frames the encoders still hold.
+    fn flush(&mut self) {
+        for (index, encoder) in self.encoders.iter_mut().enumerate() {
+            if let TrackEncoder::Video(ref mut encoder) = *encoder {
+                for frame in encoder.flush() {
+                    self.muxer.add_frame(index, frame.timestamp, frame.key_frame, &frame.data);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The frames appended to the source buffers of a `MediaSource`, which its
+//! media element's player reads as they come.
+//! https://w3c.github.io/media-source/#track-buffers
+
+use media::webm::{WebmFrame, WebmTrack};
+use std::f64;
+use std::sync::{Arc, Mutex};
+
+/// How long the first frame of a range may last, in seconds, as frames
+/// don't give their durations.
+const MAX_FRAME_DURATION: f64 = 1.;
+
+/// A time range, in seconds, as `TimeRanges` hold them.
+pub type TimeRange = (f64, f64);
+
+/// The frames of the tracks of a source buffer, sorted by time.
+struct TrackBuffers {
+    tracks: Vec<(u64, WebmTrack)>,
+    frames: Vec<WebmFrame>,
+}
+
+/// A track of a source buffer: the index of the buffer, and the number of
+/// the track in it.
+pub type TrackId = (usize, u64);
+
+pub struct SourceData {
+    /// The track buffers of each source buffer, by index, or `None` for the
+    /// ones removed.
+    buffers: Vec<Option<TrackBuffers>>,
+    /// Whether the end of the stream was signalled, after which the player
+    /// won't wait for more frames.
+    pub ended: bool,
+}
+
+/// The data of a media source, shared with the thread playing it.
+pub type SharedSourceData = Arc<Mutex<SourceData>>;
+
+impl SourceData {
+    pub fn new() -> SourceData {
+        SourceData {
+            buffers: vec![],
+            ended: false,
+        }
+    }
+
+    pub fn add_buffer(&mut self) -> usize {
+        self.buffers.push(Some(TrackBuffers {
+            tracks: vec![],
+            frames: vec![],
+        }));
+        self.buffers.len() - 1
+    }
+
+    pub fn remove_buffer(&mut self, buffer: usize) {
+        self.buffers[buffer] = None;
+    }
+
+    /// The tracks of the buffers that received an initialization segment,
+    /// with the buffer they belong to.
+    pub fn tracks(&self) -> Vec<(TrackId, WebmTrack)> {
+        self.buffers.iter().enumerate().filter_map(|(index, buffers)| buffers.as_ref().map(|buffers| {
+            buffers.tracks.iter().map(|&(number, ref track)| ((index, number), track.clone())).collect::<Vec<_>>()
+        })).flat_map(|tracks| tracks).collect()
+    }
+
+    /// Sets the tracks of a buffer, from its first initialization segment.
+    pub fn set_tracks(&mut self, buffer: usize, tracks: Vec<(u64, WebmTrack)>) {
+        if let Some(ref mut buffers) = self.buffers[buffer] {
+            buffers.tracks = tracks;
+        }
+    }
+
+    /// Adds coded frames to a buffer, replacing those of the same track that
+    /// are shown between the first and the last of them.
+    /// https://w3c.github.io/media-source/#sourcebuffer-coded-frame-processing
+    pub fn add_frames(&mut self, buffer: usize, frames: Vec<WebmFrame>) {
+        let buffers = match self.buffers[buffer] {
+            Some(ref mut buffers) => buffers,
+            None => return,
+        };
+        for &(number, _) in &buffers.tracks {
+            let mut times = frames.iter().filter(|frame| frame.track == number).map(|frame| frame.time);
+            let first = match times.next() {
+                Some(first) => first,
+                None => continue,
+            };
+            let (start, end) = times.fold((first, first), |(start, end), time| (start.min(time), end.max(time)));
+            buffers.frames.retain(|frame| frame.track != number || frame.time < start || frame.time > end);
+        }
+        buffers.frames.extend(frames);
+        // The sort is stable, so that the frames of a track shown at the same
+        // time stay in the order they were appended in.
+        buffers.frames.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+    }
+
+    /// Removes the frames of a buffer shown from `start` until `end`, and the
+    /// frames of its video tracks that depend on them.
+    /// https://w3c.github.io/media-source/#sourcebuffer-coded-frame-removal
+    pub fn remove_frames(&mut self, buffer: usize, start: f64, end: f64) {
+        let buffers = match self.buffers[buffer] {
+            Some(ref mut buffers) => buffers,
+            None => return,
+        };
+        for &(number, ref track) in &buffers.tracks {
+            let mut remove_end = end;
+            if let WebmTrack::Vp8 { .. } = *track {
+                if let Some(key_frame) = buffers.frames.iter().find(|frame| {
+                    frame.track == number && frame.key_frame && frame.time >= end
+                }) {
+                    remove_end = key_frame.time;
+                } else {
+                    remove_end = f64::INFINITY;
+                }
+            }
+            buffers.frames.retain(|frame| frame.track != number || frame.time < start || frame.time >= remove_end);
+        }
+    }
+
+    /// The time ranges a buffer has frames of all its tracks for.
+    /// https://w3c.github.io/media-source/#dom-sourcebuffer-buffered
+    pub fn buffered(&self, buffer: usize) -> Vec<TimeRange> {
+        let buffers = match self.buffers[buffer] {
+            Some(ref buffers) => buffers,
+            None => return vec![],
+        };
+        buffers.tracks.iter().map(|&(number, _)| {
+            track_ranges(buffers.frames.iter().filter(|frame| frame.track == number).map(|frame| frame.time))
+        }).fold(None, |ranges: Option<Vec<TimeRange>>, track_ranges| match ranges {
+            Some(ranges) => Some(intersect_ranges(&ranges, &track_ranges)),
+            None => Some(track_ranges),
+        }).unwrap_or(vec![])
+    }
+
+    /// The time ranges all buffers have frames for, which, once the stream
+    /// ended, stretch to the end of the buffer that ends last.
+    /// https://w3c.github.io/media-source/#htmlmediaelement-extensions-buffered
+    pub fn all_buffered(&self) -> Vec<TimeRange> {
+        let ranges: Vec<_> = (0..self.buffers.len()).filter(|&buffer| self.buffers[buffer].is_some())
+                                                    .map(|buffer| self.buffered(buffer))
+                                                    .collect();
+        let highest_end = ranges.iter().filter_map(|ranges| ranges.last()).fold(0., |end, &(_, range_end)| {
+            f64::max(end, range_end)
+        });
+        ranges.into_iter().map(|mut ranges| {
+            if self.ended {
+                if let Some(last) = ranges.last_mut() {
+                    last.1 = highest_end;
+                }
+            }
+            ranges
+        }).fold(None, |all: Option<Vec<TimeRange>>, ranges| match all {
+            Some(all) => Some(intersect_ranges(&all, &ranges)),
+            None => Some(ranges),
+        }).unwrap_or(vec![])
+    }
+
+    /// The time of the last frame shown of all buffers.
+    pub fn highest_time(&self) -> f64 {
+        self.buffers.iter().filter_map(|buffers| buffers.as_ref()).flat_map(|buffers| buffers.frames.last())
+                           .fold(0., |time, frame| f64::max(time, frame.time))
+    }
+
+    /// The first frame of a track shown after `time`, or at it if `inclusive`.
+    pub fn next_frame(&self, (buffer, number): TrackId, time: f64, inclusive: bool) -> Option<&WebmFrame> {
+        self.buffers[buffer].as_ref().and_then(|buffers| {
+            buffers.frames.iter().find(|frame| {
+                frame.track == number && (frame.time > time || (inclusive && frame.time == time))
+            })
+        })
+    }
+
+    /// The last key frame of a track shown at or before `time`.
+    pub fn key_frame_before(&self, (buffer, number): TrackId, time: f64) -> Option<&WebmFrame> {
+        self.buffers[buffer].as_ref().and_then(|buffers| {
+            buffers.frames.iter().rev().find(|frame| {
+                frame.track == number && frame.key_frame && frame.time <= time
+            })
+        })
+    }
+}
+
+/// The time ranges covered by the frames of a track shown at `times`, in
+/// order. A frame lasts until the next one, and a gap of more than twice the
+/// duration of the frame before starts a new range.
+/// https://w3c.github.io/media-source/#sourcebuffer-coded-frame-processing (step 6)
+pub fn track_ranges<I: Iterator<Item = f64>>(times: I) -> Vec<TimeRange> {
+    let mut ranges: Vec<TimeRange> = vec![];
+    let mut last_duration = None;
+    for time in times {
+        if let Some(range) = ranges.last_mut() {
+            let duration = time - range.1;
+            if duration <= last_duration.map_or(MAX_FRAME_DURATION, |last_duration| 2. * last_duration) {
+                range.1 = time;
+                if duration > 0. {
+                    last_duration = Some(duration);
+                }
+                continue;
+            }
+        }
+        // The range ends after its last frame, which lasts as long as the
+        // one before it.
+        if let (Some(range), Some(duration)) = (ranges.last_mut(), last_duration) {
+            range.1 += duration;
+        }
+        ranges.push((time, time));
+        last_duration = None;
+    }
+    if let (Some(range), Some(duration)) = (ranges.last_mut(), last_duration) {
+        range.1 += duration;
+    }
+    ranges
+}
+
+/// The intersection of two sets of sorted and disjoint time ranges.
+pub fn intersect_ranges(a: &[TimeRange], b: &[TimeRange]) -> Vec<TimeRange> {
+    let mut ranges = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let start = a[i].0.max(b[j].0);
+        let end = a[i].1.min(b[j].1);
+        if start < end {
+            ranges.push((start, end));
+        }
+        if a[i].1 < b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    ranges
+}
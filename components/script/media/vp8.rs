@@ -0,0 +1,161 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Video encoding and decoding with VP8, from and to RGBA frames.
+
+use media::VideoFrame;
+pub use media_platform::vpx::EncodedFrame;
+use media_platform::vpx::{Decoder, Encoder};
+
+/// Converts an RGBA frame to the planes of I420, i.e. YUV with chroma
+/// subsampled in both directions, using ITU-R BT.601 with studio swing.
+pub fn rgba_to_i420(width: u32, height: u32, rgba: &[u8]) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let (width, height) = (width as usize, height as usize);
+    let (chroma_width, chroma_height) = ((width + 1) / 2, (height + 1) / 2);
+    let pixel = |x: usize, y: usize| {
+        let offset = (y * width + x) * 4;
+        (rgba[offset] as i32, rgba[offset + 1] as i32, rgba[offset + 2] as i32)
+    };
+
+    let mut luma = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let (r, g, b) = pixel(x, y);
+            luma.push((((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8);
+        }
+    }
+
+    let mut u_plane = Vec::with_capacity(chroma_width * chroma_height);
+    let mut v_plane = Vec::with_capacity(chroma_width * chroma_height);
+    for y in 0..chroma_height {
+        for x in 0..chroma_width {
+            // Average the pixels the chroma sample covers, of which there are
+            // fewer than four on the odd edges.
+            let (mut r, mut g, mut b, mut count) = (0, 0, 0, 0);
+            for &(dx, dy) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let (px, py) = (x * 2 + dx, y * 2 + dy);
+                if px < width && py < height {
+                    let (pr, pg, pb) = pixel(px, py);
+                    r += pr;
+                    g += pg;
+                    b += pb;
+                    count += 1;
+                }
+            }
+            let (r, g, b) = (r / count, g / count, b / count);
+            u_plane.push((((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8);
+            v_plane.push((((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8);
+        }
+    }
+    (luma, u_plane, v_plane)
+}
+
+/// Converts the planes of an I420 frame to RGBA, using ITU-R BT.601 with
+/// studio swing like `rgba_to_i420`. Each plane is given with its stride.
+pub fn i420_to_rgba(width: u32, height: u32, planes: [(&[u8], usize); 3]) -> Vec<u8> {
+    fn clamp(value: i32) -> u8 {
+        (value >> 8).max(0).min(255) as u8
+    }
+
+    let (width, height) = (width as usize, height as usize);
+    let ((luma, luma_stride), (u_plane, u_stride), (v_plane, v_stride)) = (planes[0], planes[1], planes[2]);
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        for x in 0..width {
+            let c = 298 * (luma[y * luma_stride + x] as i32 - 16) + 128;
+            let u = u_plane[y / 2 * u_stride + x / 2] as i32 - 128;
+            let v = v_plane[y / 2 * v_stride + x / 2] as i32 - 128;
+            rgba.push(clamp(c + 409 * v));
+            rgba.push(clamp(c - 100 * u - 208 * v));
+            rgba.push(clamp(c + 516 * u));
+            rgba.push(255);
+        }
+    }
+    rgba
+}
+
+/// Encodes frames of a fixed size, with a timebase of milliseconds.
+pub struct Vp8Encoder {
+    encoder: Encoder,
+    width: u32,
+    height: u32,
+    frame_duration: u64,
+    last_timestamp: Option<u64>,
+}
+
+impl Vp8Encoder {
+    pub fn new(width: u32, height: u32, frame_rate: f64, bit_rate: u32) -> Result<Vp8Encoder, ()> {
+        if width == 0 || height == 0 {
+            return Err(());
+        }
+        Ok(Vp8Encoder {
+            encoder: try!(Encoder::new(width, height, bit_rate)),
+            width: width,
+            height: height,
+            frame_duration: (1000. / frame_rate.max(1.)) as u64,
+            last_timestamp: None,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Encodes `frame`, which is shown at `timestamp` in milliseconds,
+    /// returning the frames the encoder output.
+    pub fn encode(&mut self, frame: &VideoFrame, timestamp: u64, force_key_frame: bool) -> Vec<EncodedFrame> {
+        if frame.width != self.width || frame.height != self.height {
+            warn!("Dropped a video frame of another size than the first ones");
+            return vec![];
+        }
+        // The timestamps of the frames given to libvpx have to increase.
+        let timestamp = match self.last_timestamp {
+            Some(last) if timestamp <= last => last + 1,
+            _ => timestamp,
+        };
+        self.last_timestamp = Some(timestamp);
+
+        let (luma, u_plane, v_plane) = rgba_to_i420(self.width, self.height, &frame.data);
+        let planes = [&luma[..], &u_plane[..], &v_plane[..]];
+        match self.encoder.encode(planes, timestamp, self.frame_duration, force_key_frame) {
+            Ok(frames) => frames,
+            Err(()) => vec![],
+        }
+    }
+
+    /// Returns the frames the encoder still holds.
+    pub fn flush(&mut self) -> Vec<EncodedFrame> {
+        self.encoder.flush()
+    }
+}
+
+/// Decodes VP8 frames to RGBA.
+pub struct Vp8Decoder {
+    decoder: Decoder,
+}
+
+impl Vp8Decoder {
+    pub fn new() -> Result<Vp8Decoder, ()> {
+        Ok(Vp8Decoder {
+            decoder: try!(Decoder::new()),
+        })
+    }
+
+    /// Decodes a compressed frame, returning the frame to show if it has one.
+    pub fn decode(&mut self, data: &[u8]) -> Option<VideoFrame> {
+        let mut frame = None;
+        let decoded = self.decoder.decode(data, |width, height, planes| {
+            frame = Some(VideoFrame {
+                width: width,
+                height: height,
+                data: i420_to_rgba(width, height, planes),
+            });
+        });
+        decoded.ok().and_then(|()| frame)
+    }
+}
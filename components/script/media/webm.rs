@@ -0,0 +1,728 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Writing of WebM files as they are recorded, i.e. with a segment of
+//! unknown size and no cues, and reading of whole WebM files for playback.
+//! https://www.webmproject.org/docs/container/
+
+use std::i16;
+use std::mem;
+
+const EBML: u32 = 0x1A45DFA3;
+const EBML_VERSION: u32 = 0x4286;
+const EBML_READ_VERSION: u32 = 0x42F7;
+const EBML_MAX_ID_LENGTH: u32 = 0x42F2;
+const EBML_MAX_SIZE_LENGTH: u32 = 0x42F3;
+const DOC_TYPE: u32 = 0x4282;
+const DOC_TYPE_VERSION: u32 = 0x4287;
+const DOC_TYPE_READ_VERSION: u32 = 0x4285;
+const SEGMENT: u32 = 0x18538067;
+const INFO: u32 = 0x1549A966;
+const TIMECODE_SCALE: u32 = 0x2AD7B1;
+const DURATION: u32 = 0x4489;
+const MUXING_APP: u32 = 0x4D80;
+const WRITING_APP: u32 = 0x5741;
+const TRACKS: u32 = 0x1654AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_UID: u32 = 0x73C5;
+const TRACK_TYPE: u32 = 0x83;
+const CODEC_ID: u32 = 0x86;
+const CODEC_PRIVATE: u32 = 0x63A2;
+const CODEC_DELAY: u32 = 0x56AA;
+const SEEK_PRE_ROLL: u32 = 0x56BB;
+const VIDEO: u32 = 0xE0;
+const PIXEL_WIDTH: u32 = 0xB0;
+const PIXEL_HEIGHT: u32 = 0xBA;
+const AUDIO: u32 = 0xE1;
+const SAMPLING_FREQUENCY: u32 = 0xB5;
+const CHANNELS: u32 = 0x9F;
+const CLUSTER: u32 = 0x1F43B675;
+const TIMECODE: u32 = 0xE7;
+const SIMPLE_BLOCK: u32 = 0xA3;
+const BLOCK_GROUP: u32 = 0xA0;
+const BLOCK: u32 = 0xA1;
+const REFERENCE_BLOCK: u32 = 0xFB;
+
+/// The default duration of a tick of the timecodes, in nanoseconds.
+const DEFAULT_TIMECODE_SCALE: u64 = 1000000;
+
+const TRACK_TYPE_VIDEO: u64 = 1;
+const TRACK_TYPE_AUDIO: u64 = 2;
+
+/// The size of an element that is still being written.
+const UNKNOWN_SIZE: [u8; 8] = [0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+
+/// How long a cluster may get, in milliseconds, as the timecodes of the
+/// blocks within it are 16-bit offsets.
+const MAX_CLUSTER_DURATION: i64 = 30000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WebmTrack {
+    Vp8 {
+        width: u32,
+        height: u32,
+    },
+    Opus {
+        sample_rate: u32,
+        channel_count: usize,
+        /// The identification header of the stream.
+        identification_header: Vec<u8>,
+        /// How many nanoseconds the decoder has to skip at the start.
+        codec_delay: u64,
+    },
+}
+
+fn write_id(output: &mut Vec<u8>, id: u32) {
+    // Ids are stored with their length marker, in as many bytes as needed.
+    let length = 4 - (id.leading_zeros() / 8) as usize;
+    for i in (0..length).rev() {
+        output.push((id >> (8 * i)) as u8);
+    }
+}
+
+/// Writes `size` as a variable-length integer.
+fn write_size(output: &mut Vec<u8>, size: u64) {
+    // All bits set is reserved for unknown sizes.
+    let length = (1..8).find(|&length| size < (1 << (7 * length)) - 1).unwrap_or(8);
+    let marked = size | (1 << (7 * length));
+    for i in (0..length).rev() {
+        output.push((marked >> (8 * i)) as u8);
+    }
+}
+
+fn write_element(output: &mut Vec<u8>, id: u32, body: &[u8]) {
+    write_id(output, id);
+    write_size(output, body.len() as u64);
+    output.extend_from_slice(body);
+}
+
+fn write_uint(output: &mut Vec<u8>, id: u32, value: u64) {
+    let length = 8 - (value.leading_zeros() / 8).min(7) as usize;
+    let body: Vec<u8> = (0..length).rev().map(|i| (value >> (8 * i)) as u8).collect();
+    write_element(output, id, &body);
+}
+
+#[allow(unsafe_code)]
+fn write_float(output: &mut Vec<u8>, id: u32, value: f64) {
+    let bits = unsafe { mem::transmute::<f64, u64>(value) };
+    let body: Vec<u8> = (0..8).rev().map(|i| (bits >> (8 * i)) as u8).collect();
+    write_element(output, id, &body);
+}
+
+fn write_string(output: &mut Vec<u8>, id: u32, value: &str) {
+    write_element(output, id, value.as_bytes());
+}
+
+/// Writes the frames of a set of tracks, numbered from 1 in the order they
+/// are given, as a WebM stream.
+pub struct WebmMuxer {
+    /// Whether each track is a video track.
+    video_tracks: Vec<bool>,
+    output: Vec<u8>,
+    /// The blocks of the cluster being written, and its timecode.
+    cluster: Option<(u64, Vec<u8>)>,
+}
+
+impl WebmMuxer {
+    pub fn new(tracks: &[WebmTrack]) -> WebmMuxer {
+        let mut output = vec![];
+
+        let mut header = vec![];
+        write_uint(&mut header, EBML_VERSION, 1);
+        write_uint(&mut header, EBML_READ_VERSION, 1);
+        write_uint(&mut header, EBML_MAX_ID_LENGTH, 4);
+        write_uint(&mut header, EBML_MAX_SIZE_LENGTH, 8);
+        write_string(&mut header, DOC_TYPE, "webm");
+        write_uint(&mut header, DOC_TYPE_VERSION, 4);
+        write_uint(&mut header, DOC_TYPE_READ_VERSION, 2);
+        write_element(&mut output, EBML, &header);
+
+        write_id(&mut output, SEGMENT);
+        output.extend_from_slice(&UNKNOWN_SIZE);
+
+        let mut info = vec![];
+        // Timecodes are in milliseconds.
+        write_uint(&mut info, TIMECODE_SCALE, 1000000);
+        write_string(&mut info, MUXING_APP, "Servo");
+        write_string(&mut info, WRITING_APP, "Servo");
+        write_element(&mut output, INFO, &info);
+
+        let mut entries = vec![];
+        for (index, track) in tracks.iter().enumerate() {
+            let mut entry = vec![];
+            write_uint(&mut entry, TRACK_NUMBER, index as u64 + 1);
+            write_uint(&mut entry, TRACK_UID, index as u64 + 1);
+            match *track {
+                WebmTrack::Vp8 { width, height } => {
+                    write_uint(&mut entry, TRACK_TYPE, TRACK_TYPE_VIDEO);
+                    write_string(&mut entry, CODEC_ID, "V_VP8");
+                    let mut video = vec![];
+                    write_uint(&mut video, PIXEL_WIDTH, width as u64);
+                    write_uint(&mut video, PIXEL_HEIGHT, height as u64);
+                    write_element(&mut entry, VIDEO, &video);
+                },
+                WebmTrack::Opus { sample_rate, channel_count, ref identification_header, codec_delay } => {
+                    write_uint(&mut entry, TRACK_TYPE, TRACK_TYPE_AUDIO);
+                    write_string(&mut entry, CODEC_ID, "A_OPUS");
+                    write_element(&mut entry, CODEC_PRIVATE, identification_header);
+                    write_uint(&mut entry, CODEC_DELAY, codec_delay);
+                    // The 80ms that Opus needs to converge after seeking.
+                    write_uint(&mut entry, SEEK_PRE_ROLL, 80000000);
+                    let mut audio = vec![];
+                    write_float(&mut audio, SAMPLING_FREQUENCY, sample_rate as f64);
+                    write_uint(&mut audio, CHANNELS, channel_count as u64);
+                    write_element(&mut entry, AUDIO, &audio);
+                },
+            }
+            write_element(&mut entries, TRACK_ENTRY, &entry);
+        }
+        write_element(&mut output, TRACKS, &entries);
+
+        WebmMuxer {
+            video_tracks: tracks.iter().map(|track| match *track {
+                WebmTrack::Vp8 { .. } => true,
+                WebmTrack::Opus { .. } => false,
+            }).collect(),
+            output: output,
+            cluster: None,
+        }
+    }
+
+    /// Adds a frame of the track at `index`, shown at `timecode` in
+    /// milliseconds.
+    pub fn add_frame(&mut self, index: usize, timecode: u64, key_frame: bool, data: &[u8]) {
+        // Clusters start at the key frames of video, so that players can
+        // seek to them.
+        let new_cluster = match self.cluster {
+            Some((cluster_timecode, ref blocks)) => {
+                let relative_timecode = timecode as i64 - cluster_timecode as i64;
+                relative_timecode < i16::MIN as i64 ||
+                relative_timecode > MAX_CLUSTER_DURATION ||
+                (key_frame && self.video_tracks[index] && !blocks.is_empty())
+            },
+            None => true,
+        };
+        if new_cluster {
+            self.end_cluster();
+            self.cluster = Some((timecode, vec![]));
+        }
+
+        let (cluster_timecode, ref mut blocks) = *self.cluster.as_mut().unwrap();
+        let mut block = vec![];
+        write_size(&mut block, index as u64 + 1);
+        // Blocks can start a little before their cluster, as the frames of
+        // the tracks are interleaved as they come.
+        let relative_timecode = (timecode as i64 - cluster_timecode as i64) as i16;
+        block.extend_from_slice(&[(relative_timecode >> 8) as u8, relative_timecode as u8]);
+        block.push(if key_frame { 0x80 } else { 0 });
+        block.extend_from_slice(data);
+        write_element(blocks, SIMPLE_BLOCK, &block);
+    }
+
+    fn end_cluster(&mut self) {
+        if let Some((timecode, blocks)) = self.cluster.take() {
+            let mut cluster = vec![];
+            write_uint(&mut cluster, TIMECODE, timecode);
+            cluster.extend_from_slice(&blocks);
+            write_element(&mut self.output, CLUSTER, &cluster);
+        }
+    }
+
+    /// Takes what was written so far, and ends the current cluster so that
+    /// the data taken is made of whole elements.
+    pub fn take_data(&mut self) -> Vec<u8> {
+        self.end_cluster();
+        mem::replace(&mut self.output, vec![])
+    }
+}
+
+/// A frame of a WebM file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebmFrame {
+    /// The number of the track the frame belongs to.
+    pub track: u64,
+    /// The presentation time of the frame, in seconds.
+    pub time: f64,
+    pub key_frame: bool,
+    pub data: Vec<u8>,
+}
+
+/// The contents of a WebM file.
+pub struct WebmFile {
+    /// The duration of the file in seconds, if its header has one.
+    pub duration: Option<f64>,
+    /// The tracks that can be decoded, by track number.
+    pub tracks: Vec<(u64, WebmTrack)>,
+    /// The frames of all tracks, in the order they are stored.
+    pub frames: Vec<WebmFrame>,
+}
+
+/// Reads an element id at `offset`, returning it with its length marker as
+/// the constants above are, and its length.
+fn read_id(data: &[u8], offset: usize) -> Result<(u32, usize), ()> {
+    let first = try!(data.get(offset).ok_or(()));
+    let length = first.leading_zeros() as usize + 1;
+    if length > 4 || offset + length > data.len() {
+        return Err(());
+    }
+    let id = data[offset..offset + length].iter().fold(0, |id, &byte| id << 8 | byte as u32);
+    Ok((id, length))
+}
+
+/// Reads a variable-length integer at `offset`, returning it without its
+/// length marker, whether all of its bits were set, and its length.
+fn read_vint(data: &[u8], offset: usize) -> Result<(u64, bool, usize), ()> {
+    let first = try!(data.get(offset).ok_or(()));
+    let length = first.leading_zeros() as usize + 1;
+    if length > 8 || offset + length > data.len() {
+        return Err(());
+    }
+    let mut value = (*first as u64) & ((1 << (8 - length)) - 1);
+    for &byte in &data[offset + 1..offset + length] {
+        value = value << 8 | byte as u64;
+    }
+    let all_ones = value == (1 << (7 * length)) - 1;
+    Ok((value, all_ones, length))
+}
+
+fn read_uint(body: &[u8]) -> u64 {
+    body.iter().fold(0, |value, &byte| value << 8 | byte as u64)
+}
+
+#[allow(unsafe_code)]
+fn read_float(body: &[u8]) -> Result<f64, ()> {
+    match body.len() {
+        4 => Ok(unsafe { mem::transmute::<u32, f32>(read_uint(body) as u32) } as f64),
+        8 => Ok(unsafe { mem::transmute::<u64, f64>(read_uint(body)) }),
+        _ => Err(()),
+    }
+}
+
+/// An element of a file, with the range of its body.
+struct Element {
+    id: u32,
+    start: usize,
+    end: usize,
+}
+
+/// Reads the elements in `data[start..end]`, which may end early for the
+/// children of an element of unknown size.
+struct Elements<'a> {
+    data: &'a [u8],
+    offset: usize,
+    end: usize,
+    /// Whether the elements are the children of an element of unknown size,
+    /// which end where an element of the top level starts.
+    unknown_size: bool,
+}
+
+impl<'a> Elements<'a> {
+    fn new(data: &'a [u8], start: usize, end: usize, unknown_size: bool) -> Elements<'a> {
+        Elements {
+            data: data,
+            offset: start,
+            end: end,
+            unknown_size: unknown_size,
+        }
+    }
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = Result<Element, ()>;
+
+    fn next(&mut self) -> Option<Result<Element, ()>> {
+        if self.offset >= self.end {
+            return None;
+        }
+        let (id, id_length) = match read_id(self.data, self.offset) {
+            Ok(id) => id,
+            Err(()) => return Some(Err(())),
+        };
+        // The ids of the top level elements are the only ones with four
+        // bytes, as the children of a cluster have shorter ones.
+        if self.unknown_size && id_length == 4 {
+            return None;
+        }
+        let (size, unknown, size_length) = match read_vint(self.data, self.offset + id_length) {
+            Ok(size) => size,
+            Err(()) => return Some(Err(())),
+        };
+        let start = self.offset + id_length + size_length;
+        let end = if unknown {
+            // Only segments and clusters are written with unknown sizes.
+            if id != SEGMENT && id != CLUSTER {
+                return Some(Err(()));
+            }
+            let children = Elements::new(self.data, start, self.end, id == CLUSTER);
+            let mut end = start;
+            for child in children {
+                match child {
+                    Ok(child) => end = child.end,
+                    Err(()) => return Some(Err(())),
+                }
+            }
+            end
+        } else {
+            start + size as usize
+        };
+        if end > self.data.len() || end < start {
+            return Some(Err(()));
+        }
+        self.offset = end;
+        Some(Ok(Element {
+            id: id,
+            start: start,
+            end: end,
+        }))
+    }
+}
+
+/// Reads the track of a `TrackEntry`, if it is of a codec that can be decoded.
+fn read_track(data: &[u8], entry: &Element) -> Result<Option<(u64, WebmTrack)>, ()> {
+    let (mut number, mut codec, mut private) = (None, None, None);
+    let (mut width, mut height) = (0, 0);
+    let (mut sample_rate, mut channel_count) = (8000., 1);
+    let mut codec_delay = 0;
+    for element in Elements::new(data, entry.start, entry.end, false) {
+        let element = try!(element);
+        let body = &data[element.start..element.end];
+        match element.id {
+            TRACK_NUMBER => number = Some(read_uint(body)),
+            CODEC_ID => codec = Some(body.to_vec()),
+            CODEC_PRIVATE => private = Some(body.to_vec()),
+            CODEC_DELAY => codec_delay = read_uint(body),
+            VIDEO => {
+                for setting in Elements::new(data, element.start, element.end, false) {
+                    let setting = try!(setting);
+                    let value = read_uint(&data[setting.start..setting.end]) as u32;
+                    match setting.id {
+                        PIXEL_WIDTH => width = value,
+                        PIXEL_HEIGHT => height = value,
+                        _ => {},
+                    }
+                }
+            },
+            AUDIO => {
+                for setting in Elements::new(data, element.start, element.end, false) {
+                    let setting = try!(setting);
+                    let body = &data[setting.start..setting.end];
+                    match setting.id {
+                        SAMPLING_FREQUENCY => sample_rate = try!(read_float(body)),
+                        CHANNELS => channel_count = read_uint(body) as usize,
+                        _ => {},
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    let number = try!(number.ok_or(()));
+    let track = match codec.as_ref().map(|codec| &**codec) {
+        Some(b"V_VP8") => WebmTrack::Vp8 {
+            width: width,
+            height: height,
+        },
+        Some(b"A_OPUS") => WebmTrack::Opus {
+            sample_rate: sample_rate as u32,
+            channel_count: channel_count,
+            identification_header: try!(private.ok_or(())),
+            codec_delay: codec_delay,
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some((number, track)))
+}
+
+/// Reads a `SimpleBlock` or a `Block` of a cluster at `timecode`.
+fn read_block(body: &[u8], timecode: u64, timecode_scale: u64, key_frame: Option<bool>) -> Result<WebmFrame, ()> {
+    let (track, _, length) = try!(read_vint(body, 0));
+    if body.len() < length + 3 {
+        return Err(());
+    }
+    let relative_timecode = ((body[length] as u16) << 8 | body[length + 1] as u16) as i16;
+    let flags = body[length + 2];
+    // TODO: laced blocks, which hold several frames.
+    if flags & 0x06 != 0 {
+        return Err(());
+    }
+    let timecode = (timecode as i64 + relative_timecode as i64).max(0) as u64;
+    Ok(WebmFrame {
+        track: track,
+        time: (timecode * timecode_scale) as f64 / 1e9,
+        key_frame: key_frame.unwrap_or(flags & 0x80 != 0),
+        data: body[length + 3..].to_vec(),
+    })
+}
+
+/// Reads the timecode scale of an `Info` element, and the duration it gives
+/// in seconds, if any.
+fn read_info(data: &[u8], element: &Element) -> Result<(u64, Option<f64>), ()> {
+    let (mut timecode_scale, mut duration) = (DEFAULT_TIMECODE_SCALE, None);
+    for info in Elements::new(data, element.start, element.end, false) {
+        let info = try!(info);
+        let body = &data[info.start..info.end];
+        match info.id {
+            TIMECODE_SCALE => timecode_scale = read_uint(body),
+            DURATION => duration = Some(try!(read_float(body))),
+            _ => {},
+        }
+    }
+    Ok((timecode_scale, duration.map(|duration| duration * timecode_scale as f64 / 1e9)))
+}
+
+/// Reads the tracks of a `Tracks` element that can be decoded.
+fn read_tracks(data: &[u8], element: &Element) -> Result<Vec<(u64, WebmTrack)>, ()> {
+    let mut tracks = vec![];
+    for entry in Elements::new(data, element.start, element.end, false) {
+        let entry = try!(entry);
+        if entry.id == TRACK_ENTRY {
+            tracks.extend(try!(read_track(data, &entry)));
+        }
+    }
+    Ok(tracks)
+}
+
+/// Reads the frame of a child of a cluster at `timecode`, if it is a block.
+/// Blocks that can't be read are skipped.
+fn read_cluster_block(data: &[u8], child: &Element, timecode: u64, timecode_scale: u64)
+                      -> Result<Option<WebmFrame>, ()> {
+    let body = &data[child.start..child.end];
+    let frame = match child.id {
+        SIMPLE_BLOCK => read_block(body, timecode, timecode_scale, None),
+        BLOCK_GROUP => {
+            let (mut block, mut key_frame) = (None, true);
+            for member in Elements::new(data, child.start, child.end, false) {
+                let member = try!(member);
+                match member.id {
+                    BLOCK => block = Some(&data[member.start..member.end]),
+                    REFERENCE_BLOCK => key_frame = false,
+                    _ => {},
+                }
+            }
+            match block {
+                Some(block) => read_block(block, timecode, timecode_scale, Some(key_frame)),
+                None => return Ok(None),
+            }
+        },
+        _ => return Ok(None),
+    };
+    match frame {
+        Ok(frame) => Ok(Some(frame)),
+        Err(()) => {
+            warn!("Skipped a WebM block that couldn't be read");
+            Ok(None)
+        },
+    }
+}
+
+/// Reads a whole WebM file, keeping the frames of the tracks it can decode.
+pub fn read_webm(data: &[u8]) -> Result<WebmFile, ()> {
+    let mut elements = Elements::new(data, 0, data.len(), false);
+    match elements.next() {
+        Some(Ok(Element { id: EBML, .. })) => {},
+        _ => return Err(()),
+    }
+    let segment = match elements.next() {
+        Some(Ok(segment)) => segment,
+        _ => return Err(()),
+    };
+    if segment.id != SEGMENT {
+        return Err(());
+    }
+
+    let mut info = (DEFAULT_TIMECODE_SCALE, None);
+    let mut tracks = vec![];
+    let mut frames = vec![];
+    for element in Elements::new(data, segment.start, segment.end, false) {
+        let element = try!(element);
+        match element.id {
+            INFO => info = try!(read_info(data, &element)),
+            TRACKS => tracks = try!(read_tracks(data, &element)),
+            CLUSTER => {
+                let mut timecode = 0;
+                for child in Elements::new(data, element.start, element.end, false) {
+                    let child = try!(child);
+                    if child.id == TIMECODE {
+                        timecode = read_uint(&data[child.start..child.end]);
+                        continue;
+                    }
+                    if let Some(frame) = try!(read_cluster_block(data, &child, timecode, info.0)) {
+                        if tracks.iter().any(|&(number, _)| number == frame.track) {
+                            frames.push(frame);
+                        }
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    Ok(WebmFile {
+        duration: info.1,
+        tracks: tracks,
+        frames: frames,
+    })
+}
+
+/// What a `WebmParser` reads from the data appended to it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WebmSegment {
+    /// The header of a stream: its duration in seconds, if it gives one, and
+    /// the tracks that can be decoded.
+    Init(Option<f64>, Vec<(u64, WebmTrack)>),
+    /// Frames of the tracks of the latest header, in the order they are
+    /// stored.
+    Frames(Vec<WebmFrame>),
+}
+
+/// Reads a WebM stream in the pieces it is appended in, as the source buffers
+/// of media sources do, rather than as a whole file.
+/// https://w3c.github.io/media-source/webm-byte-stream-format.html
+pub struct WebmParser {
+    /// The data that doesn't make a whole element yet.
+    input: Vec<u8>,
+    timecode_scale: u64,
+    duration: Option<f64>,
+    /// The tracks of the latest header, once one was read.
+    tracks: Option<Vec<(u64, WebmTrack)>>,
+    /// The timecode of the cluster being read, and the size of its body that
+    /// is left to read, unless its size is unknown.
+    cluster: Option<(u64, Option<u64>)>,
+}
+
+impl WebmParser {
+    pub fn new() -> WebmParser {
+        WebmParser {
+            input: vec![],
+            timecode_scale: DEFAULT_TIMECODE_SCALE,
+            duration: None,
+            tracks: None,
+            cluster: None,
+        }
+    }
+
+    /// Drops the data that wasn't read yet and the cluster being read, so
+    /// that the next data starts a new header or cluster.
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.cluster = None;
+    }
+
+    /// Reads what it can of the data appended so far. The frames of a cluster
+    /// are read as its blocks come, rather than once it is whole.
+    pub fn append(&mut self, data: &[u8]) -> Result<Vec<WebmSegment>, ()> {
+        self.input.extend_from_slice(data);
+        let mut segments = vec![];
+        let mut frames = vec![];
+        let mut offset = 0;
+        while let Some((id, id_length, size, header_length)) = try!(read_header(&self.input[offset..])) {
+            // An element of the top level ends the cluster of unknown size
+            // before it.
+            if id_length == 4 {
+                match self.cluster {
+                    Some((_, None)) => self.cluster = None,
+                    Some((_, Some(_))) => return Err(()),
+                    None => {},
+                }
+            }
+            match id {
+                // The children of segments are read as they come.
+                SEGMENT => {
+                    offset += header_length;
+                    continue;
+                },
+                CLUSTER => {
+                    if self.tracks.is_none() {
+                        return Err(());
+                    }
+                    self.cluster = Some((0, size));
+                    offset += header_length;
+                    continue;
+                },
+                _ => {},
+            }
+
+            let size = try!(size.ok_or(()));
+            if ((self.input.len() - offset - header_length) as u64) < size {
+                break;
+            }
+            let element = Element {
+                id: id,
+                start: offset + header_length,
+                end: offset + header_length + size as usize,
+            };
+            offset = element.end;
+
+            if let Some((ref mut timecode, ref mut size_left)) = self.cluster {
+                if id == TIMECODE {
+                    *timecode = read_uint(&self.input[element.start..element.end]);
+                } else if let Some(frame) = try!(read_cluster_block(&self.input,
+                                                                    &element,
+                                                                    *timecode,
+                                                                    self.timecode_scale)) {
+                    let tracks = self.tracks.as_ref().unwrap();
+                    if tracks.iter().any(|&(number, _)| number == frame.track) {
+                        frames.push(frame);
+                    }
+                }
+                if let Some(ref mut size_left) = *size_left {
+                    let length = (header_length as u64) + size;
+                    if length > *size_left {
+                        return Err(());
+                    }
+                    *size_left -= length;
+                }
+            } else {
+                match id {
+                    INFO => {
+                        let (timecode_scale, duration) = try!(read_info(&self.input, &element));
+                        self.timecode_scale = timecode_scale;
+                        self.duration = duration;
+                    },
+                    TRACKS => {
+                        let tracks = try!(read_tracks(&self.input, &element));
+                        if !frames.is_empty() {
+                            segments.push(WebmSegment::Frames(mem::replace(&mut frames, vec![])));
+                        }
+                        segments.push(WebmSegment::Init(self.duration, tracks.clone()));
+                        self.tracks = Some(tracks);
+                    },
+                    _ => {},
+                }
+            }
+            if let Some((_, Some(0))) = self.cluster {
+                self.cluster = None;
+            }
+        }
+        self.input.drain(..offset);
+        if !frames.is_empty() {
+            segments.push(WebmSegment::Frames(frames));
+        }
+        Ok(segments)
+    }
+}
+
+/// Reads the header of the element at the start of `data`: its id, the
+/// length of its id, its size unless it is unknown, and the length of the
+/// whole header. Returns `None` if `data` doesn't hold all of it yet.
+fn read_header(data: &[u8]) -> Result<Option<(u32, usize, Option<u64>, usize)>, ()> {
+    let id_length = match data.first() {
+        Some(first) => first.leading_zeros() as usize + 1,
+        None => return Ok(None),
+    };
+    let size_length = match data.get(id_length) {
+        Some(first) => first.leading_zeros() as usize + 1,
+        None => return Ok(None),
+    };
+    if id_length > 4 || size_length > 8 {
+        return Err(());
+    }
+    if data.len() < id_length + size_length {
+        return Ok(None);
+    }
+    let (id, _) = try!(read_id(data, 0));
+    let (size, unknown, _) = try!(read_vint(data, id_length));
+    Ok(Some((id, id_length, if unknown { None } else { Some(size) }, id_length + size_length)))
+}
@@ -0,0 +1,285 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The parser of the WebVTT files that `<track>` elements load.
+//! https://w3c.github.io/webvtt/#file-parsing
+
+use script_layout_interface::{CueAlign, CueSettings};
+
+/// A cue of a WebVTT file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebVttCue {
+    pub id: String,
+    /// The times the cue is shown from and until, in seconds.
+    pub start_time: f64,
+    pub end_time: f64,
+    /// The settings after the timings, unparsed, e.g. `line:0 align:start`.
+    pub settings: String,
+    /// The cue text, with its markup.
+    pub text: String,
+}
+
+/// Parses a WebVTT file, failing if it doesn't start with its signature.
+/// Cues with bad timings are skipped.
+pub fn parse(input: &str) -> Result<Vec<WebVttCue>, ()> {
+    // Steps 1 to 3
+    let input = input.trim_left_matches('\u{FEFF}').replace("\r\n", "\n").replace('\r', "\n");
+    let mut lines = input.split('\n').peekable();
+
+    // Steps 5 to 8
+    match lines.next() {
+        Some(line) if line.starts_with("WEBVTT") => {
+            match line[6..].chars().next() {
+                None | Some(' ') | Some('\t') => {},
+                Some(_) => return Err(()),
+            }
+        },
+        _ => return Err(()),
+    }
+
+    // Steps 9 and 10: skip the header.
+    while let Some(line) = lines.next() {
+        if line.is_empty() {
+            break;
+        }
+    }
+
+    let mut cues = vec![];
+    loop {
+        // Step 11: skip the blank lines between blocks.
+        while lines.peek() == Some(&"") {
+            lines.next();
+        }
+        let first = match lines.next() {
+            Some(line) => line,
+            None => break,
+        };
+
+        // https://w3c.github.io/webvtt/#collect-a-webvtt-block
+        let (id, timings) = if first.contains("-->") {
+            ("", Some(first))
+        } else {
+            match lines.peek() {
+                Some(line) if line.contains("-->") => (first, lines.next()),
+                _ => (first, None),
+            }
+        };
+        let timings = match timings {
+            Some(timings) => timings,
+            None => {
+                // Comments, style and region blocks, and anything else that
+                // isn't a cue.
+                while let Some(line) = lines.next() {
+                    if line.is_empty() {
+                        break;
+                    }
+                }
+                continue;
+            },
+        };
+
+        // A line with an arrow starts the next cue rather than being text.
+        let mut text = vec![];
+        while let Some(&line) = lines.peek() {
+            if line.is_empty() || line.contains("-->") {
+                break;
+            }
+            text.push(line);
+            lines.next();
+        }
+
+        if let Some((start_time, end_time, settings)) = parse_timings(timings) {
+            cues.push(WebVttCue {
+                id: id.to_owned(),
+                start_time: start_time,
+                end_time: end_time,
+                settings: settings.to_owned(),
+                text: text.join("\n"),
+            });
+        }
+    }
+    Ok(cues)
+}
+
+/// Parses the timings of a cue and splits off its settings.
+/// https://w3c.github.io/webvtt/#collect-webvtt-cue-timings-and-settings
+fn parse_timings(line: &str) -> Option<(f64, f64, &str)> {
+    let is_whitespace = |c: char| c == ' ' || c == '\t';
+    let arrow = match line.find("-->") {
+        Some(arrow) => arrow,
+        None => return None,
+    };
+    let start_time = match parse_timestamp(line[..arrow].trim_matches(is_whitespace)) {
+        Some(start_time) => start_time,
+        None => return None,
+    };
+    let rest = line[arrow + 3..].trim_left_matches(is_whitespace);
+    let end = rest.find(is_whitespace).unwrap_or(rest.len());
+    let end_time = match parse_timestamp(&rest[..end]) {
+        Some(end_time) => end_time,
+        None => return None,
+    };
+    Some((start_time, end_time, rest[end..].trim_matches(is_whitespace)))
+}
+
+/// Parses the settings of a cue, like `line:-2 position:10% size:50% align:start`.
+/// Settings that aren't valid are ignored, as are the `vertical` and `region` ones.
+/// https://w3c.github.io/webvtt/#parse-the-webvtt-cue-settings
+pub fn parse_settings(input: &str) -> CueSettings {
+    let mut settings = CueSettings::default();
+    for setting in input.split(|c| c == ' ' || c == '\t').filter(|setting| !setting.is_empty()) {
+        let (name, value) = match setting.find(':') {
+            Some(colon) if colon != 0 && colon != setting.len() - 1 => {
+                (&setting[..colon], &setting[colon + 1..])
+            },
+            _ => continue,
+        };
+        match name {
+            "line" => {
+                let (position, alignment) = split_alignment(value);
+                if !alignment.map_or(true, |alignment| ["start", "center", "end"].contains(&alignment)) {
+                    continue;
+                }
+                if let Some(percentage) = parse_percentage(position) {
+                    settings.line = Some(percentage);
+                    settings.snap_to_lines = false;
+                    continue;
+                }
+                let digits = position.trim_left_matches('-');
+                if position.len() - digits.len() > 1 || digits.is_empty() ||
+                   !digits.chars().all(|c| c.is_digit(10)) {
+                    continue;
+                }
+                if let Ok(number) = position.parse() {
+                    settings.line = Some(number);
+                    settings.snap_to_lines = true;
+                }
+            },
+            "position" => {
+                let (position, alignment) = split_alignment(value);
+                if !alignment.map_or(true, |alignment| ["line-left", "center", "line-right"].contains(&alignment)) {
+                    continue;
+                }
+                if let Some(percentage) = parse_percentage(position) {
+                    settings.position = Some(percentage);
+                }
+            },
+            "size" => {
+                if let Some(percentage) = parse_percentage(value) {
+                    settings.size = percentage;
+                }
+            },
+            "align" => {
+                settings.align = match value {
+                    "start" => CueAlign::Start,
+                    "center" => CueAlign::Center,
+                    "end" => CueAlign::End,
+                    "left" => CueAlign::Left,
+                    "right" => CueAlign::Right,
+                    _ => continue,
+                };
+            },
+            _ => {},
+        }
+    }
+    settings
+}
+
+/// Splits a `line` or `position` setting into its value and its alignment after a comma.
+fn split_alignment(value: &str) -> (&str, Option<&str>) {
+    match value.find(',') {
+        Some(comma) => (&value[..comma], Some(&value[comma + 1..])),
+        None => (value, None),
+    }
+}
+
+/// Parses a percentage between 0% and 100%.
+/// https://w3c.github.io/webvtt/#parse-a-percentage-string
+fn parse_percentage(value: &str) -> Option<f64> {
+    if !value.ends_with('%') {
+        return None;
+    }
+    let number = &value[..value.len() - 1];
+    let mut parts = number.splitn(2, '.');
+    let integer = parts.next().unwrap_or("");
+    let fraction = parts.next();
+    if integer.is_empty() || !integer.chars().all(|c| c.is_digit(10)) ||
+       !fraction.map_or(true, |fraction| !fraction.is_empty() && fraction.chars().all(|c| c.is_digit(10))) {
+        return None;
+    }
+    match number.parse() {
+        Ok(percentage) if percentage <= 100. => Some(percentage),
+        _ => None,
+    }
+}
+
+/// Parses a timestamp like `01:02:03.456` or `02:03.456`, in seconds.
+/// https://w3c.github.io/webvtt/#collect-a-webvtt-timestamp
+pub fn parse_timestamp(timestamp: &str) -> Option<f64> {
+    let (time, fraction) = match timestamp.find('.') {
+        Some(dot) => (&timestamp[..dot], &timestamp[dot + 1..]),
+        None => return None,
+    };
+    if fraction.len() != 3 || !fraction.chars().all(|c| c.is_digit(10)) {
+        return None;
+    }
+    let fields: Vec<&str> = time.split(':').collect();
+    if fields.iter().any(|field| field.is_empty() || !field.chars().all(|c| c.is_digit(10))) {
+        return None;
+    }
+    let (hours, minutes, seconds) = match fields.len() {
+        // Minutes and seconds, unless the first field can only be hours.
+        2 if fields[0].len() == 2 => ("0", fields[0], fields[1]),
+        3 => (fields[0], fields[1], fields[2]),
+        _ => return None,
+    };
+    if minutes.len() != 2 || seconds.len() != 2 {
+        return None;
+    }
+    let (hours, minutes, seconds): (u64, u64, u64) = match (hours.parse(), minutes.parse(), seconds.parse()) {
+        (Ok(hours), Ok(minutes), Ok(seconds)) => (hours, minutes, seconds),
+        _ => return None,
+    };
+    if minutes > 59 || seconds > 59 {
+        return None;
+    }
+    let milliseconds: u64 = fraction.parse().unwrap();
+    Some((hours * 3600 + minutes * 60 + seconds) as f64 + milliseconds as f64 / 1000.)
+}
+
+/// The text a cue shows, without the tags of its markup and with its
+/// character references resolved.
+/// https://w3c.github.io/webvtt/#cue-text-parsing-rules
+pub fn cue_text_to_plain_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(|c| c == '<' || c == '&') {
+        plain.push_str(&rest[..index]);
+        rest = &rest[index..];
+        if rest.starts_with('<') {
+            // An unclosed tag runs to the end of the text.
+            rest = match rest.find('>') {
+                Some(end) => &rest[end + 1..],
+                None => "",
+            };
+            continue;
+        }
+        let reference = [("&amp;", "&"), ("&lt;", "<"), ("&gt;", ">"), ("&lrm;", "\u{200E}"),
+                         ("&rlm;", "\u{200F}"), ("&nbsp;", "\u{A0}")]
+            .iter()
+            .find(|&&(name, _)| rest.starts_with(name));
+        match reference {
+            Some(&(name, value)) => {
+                plain.push_str(value);
+                rest = &rest[name.len()..];
+            },
+            None => {
+                plain.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    plain.push_str(rest);
+    plain
+}
@@ -0,0 +1,307 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Determining the character encoding of HTML documents, and decoding them incrementally.
+//! https://html.spec.whatwg.org/multipage/#determining-the-character-encoding
+
+use encoding::all::{UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use encoding::label::encoding_from_whatwg_label;
+use encoding::types::{EncodingRef, RawDecoder};
+use std::ascii::AsciiExt;
+use std::cmp::min;
+
+/// How sure the parser is of the encoding of a document.
+/// https://html.spec.whatwg.org/multipage/#concept-encoding-confidence
+#[derive(Clone, Copy, Debug, HeapSizeOf, JSTraceable, PartialEq)]
+pub enum EncodingConfidence {
+    /// The encoding was guessed, and a later `<meta charset>` may change it.
+    Tentative,
+    /// The encoding was given by a byte order mark, the transport layer or the user.
+    Certain,
+    /// The document was not decoded from bytes, such as one created by script.
+    Irrelevant,
+}
+
+/// The number of bytes that are buffered to look for a `<meta charset>` in before the
+/// encoding of a document is decided.
+/// https://html.spec.whatwg.org/multipage/#prescan-a-byte-stream-to-determine-its-encoding
+pub const PRESCAN_LENGTH: usize = 1024;
+
+/// Returns the encoding given by the byte order mark at the start of `bytes`, if any, and
+/// the length of that byte order mark.
+/// https://encoding.spec.whatwg.org/#bom-sniff
+pub fn sniff_bom(bytes: &[u8]) -> Option<(EncodingRef, usize)> {
+    if bytes.starts_with(b"\xEF\xBB\xBF") {
+        Some((UTF_8 as EncodingRef, 3))
+    } else if bytes.starts_with(b"\xFE\xFF") {
+        Some((UTF_16BE as EncodingRef, 2))
+    } else if bytes.starts_with(b"\xFF\xFE") {
+        Some((UTF_16LE as EncodingRef, 2))
+    } else {
+        None
+    }
+}
+
+/// Returns the encoding of `label`, as a document declares it with a `<meta>` element or
+/// the transport layer gives it. Such a declaration can't be UTF-16, since it was read
+/// as ASCII, and x-user-defined is windows-1252 for documents.
+pub fn get_an_encoding(label: &str) -> Option<EncodingRef> {
+    encoding_from_whatwg_label(label).map(|encoding| {
+        match encoding.whatwg_name() {
+            Some("utf-16be") | Some("utf-16le") => UTF_8 as EncodingRef,
+            Some("x-user-defined") => WINDOWS_1252 as EncodingRef,
+            _ => encoding,
+        }
+    })
+}
+
+fn is_space(byte: u8) -> bool {
+    match byte {
+        b'\t' | b'\n' | b'\x0C' | b'\r' | b' ' => true,
+        _ => false,
+    }
+}
+
+/// Whether `bytes` start with `<` and a letter or `</` and a letter.
+fn starts_with_tag(bytes: &[u8]) -> bool {
+    fn is_letter(byte: Option<&u8>) -> bool {
+        match byte {
+            Some(&byte) => (byte >= b'a' && byte <= b'z') || (byte >= b'A' && byte <= b'Z'),
+            None => false,
+        }
+    }
+    bytes.get(0) == Some(&b'<') &&
+    (is_letter(bytes.get(1)) || (bytes.get(1) == Some(&b'/') && is_letter(bytes.get(2))))
+}
+
+/// Returns the encoding named by the `content` attribute of a `<meta http-equiv>`, such as
+/// `text/html; charset=utf-8`.
+/// https://html.spec.whatwg.org/multipage/#algorithm-for-extracting-a-character-encoding-from-a-meta-element
+pub fn extract_encoding_from_content(content: &str) -> Option<EncodingRef> {
+    let bytes = content.as_bytes();
+    let mut position = 0;
+    loop {
+        // Step 2.
+        let found = bytes[position..].windows(7)
+                                     .position(|window| window.eq_ignore_ascii_case(b"charset"));
+        position = match found {
+            Some(offset) => position + offset + 7,
+            None => return None,
+        };
+
+        // Steps 3-4.
+        while position < bytes.len() && is_space(bytes[position]) {
+            position += 1;
+        }
+        if bytes.get(position) == Some(&b'=') {
+            break;
+        }
+    }
+
+    // Step 5.
+    position += 1;
+    while position < bytes.len() && is_space(bytes[position]) {
+        position += 1;
+    }
+
+    // Step 6.
+    let value = match bytes.get(position) {
+        None => return None,
+        Some(&quote) if quote == b'"' || quote == b'\'' => {
+            let value = &bytes[position + 1..];
+            match value.iter().position(|&byte| byte == quote) {
+                Some(end) => &value[..end],
+                None => return None,
+            }
+        },
+        Some(_) => {
+            let value = &bytes[position..];
+            let end = value.iter()
+                           .position(|&byte| is_space(byte) || byte == b';')
+                           .unwrap_or(value.len());
+            &value[..end]
+        },
+    };
+    String::from_utf8(value.to_vec()).ok().and_then(|label| get_an_encoding(&label))
+}
+
+/// Parses an attribute of a tag at `*position` in `bytes`, and moves past it. Returns `None`
+/// at the end of the tag. Names and unquoted values are lowercased.
+/// https://html.spec.whatwg.org/multipage/#concept-get-attributes-when-sniffing
+fn get_an_attribute(bytes: &[u8], position: &mut usize) -> Option<(Vec<u8>, Vec<u8>)> {
+    // Step 1.
+    while *position < bytes.len() && (is_space(bytes[*position]) || bytes[*position] == b'/') {
+        *position += 1;
+    }
+
+    // Steps 2-3.
+    let mut name = vec![];
+    let mut value = vec![];
+    match bytes.get(*position) {
+        None | Some(&b'>') => return None,
+        _ => {},
+    }
+
+    // Step 4.
+    loop {
+        match bytes.get(*position) {
+            None => return Some((name, value)),
+            Some(&b'=') if !name.is_empty() => {
+                *position += 1;
+                break;
+            },
+            Some(&byte) if is_space(byte) => {
+                // Steps 5-6.
+                while *position < bytes.len() && is_space(bytes[*position]) {
+                    *position += 1;
+                }
+                if bytes.get(*position) != Some(&b'=') {
+                    return Some((name, value));
+                }
+                *position += 1;
+                break;
+            },
+            Some(&b'/') | Some(&b'>') => return Some((name, value)),
+            Some(&byte) => {
+                name.push(byte.to_ascii_lowercase());
+                *position += 1;
+            },
+        }
+    }
+
+    // Step 9.
+    while *position < bytes.len() && is_space(bytes[*position]) {
+        *position += 1;
+    }
+
+    // Step 10.
+    match bytes.get(*position) {
+        Some(&quote) if quote == b'"' || quote == b'\'' => {
+            *position += 1;
+            while let Some(&byte) = bytes.get(*position) {
+                *position += 1;
+                if byte == quote {
+                    return Some((name, value));
+                }
+                value.push(byte.to_ascii_lowercase());
+            }
+            Some((name, value))
+        },
+        Some(&b'>') => Some((name, value)),
+        _ => {
+            // Step 11.
+            while let Some(&byte) = bytes.get(*position) {
+                if is_space(byte) || byte == b'>' {
+                    break;
+                }
+                value.push(byte.to_ascii_lowercase());
+                *position += 1;
+            }
+            Some((name, value))
+        },
+    }
+}
+
+/// Looks for a `<meta>` element that declares the encoding of a document in its first bytes.
+/// https://html.spec.whatwg.org/multipage/#prescan-a-byte-stream-to-determine-its-encoding
+pub fn prescan(bytes: &[u8]) -> Option<EncodingRef> {
+    let bytes = &bytes[..min(bytes.len(), PRESCAN_LENGTH)];
+    let mut position = 0;
+    while position < bytes.len() {
+        let rest = &bytes[position..];
+        if rest.starts_with(b"<!--") {
+            position += match rest[2..].windows(3).position(|window| window == b"-->") {
+                Some(end) => 2 + end + 2,
+                None => return None,
+            };
+        } else if rest.len() >= 6 && rest[..5].eq_ignore_ascii_case(b"<meta") &&
+                  (is_space(rest[5]) || rest[5] == b'/') {
+            position += 6;
+            if let Some(encoding) = meta_encoding(bytes, &mut position) {
+                return Some(encoding);
+            }
+        } else if starts_with_tag(rest) {
+            // Skip any other tag and its attributes.
+            while position < bytes.len() && !is_space(bytes[position]) && bytes[position] != b'>' {
+                position += 1;
+            }
+            while get_an_attribute(bytes, &mut position).is_some() {}
+        } else if rest.starts_with(b"<!") || rest.starts_with(b"</") || rest.starts_with(b"<?") {
+            position += match rest.iter().position(|&byte| byte == b'>') {
+                Some(end) => end,
+                None => return None,
+            };
+        }
+        position += 1;
+    }
+    None
+}
+
+/// The encoding declared by the attributes of a `<meta>` tag that starts at `*position`.
+/// https://html.spec.whatwg.org/multipage/#prescan-a-byte-stream-to-determine-its-encoding
+/// (step 2 of the `<meta` case)
+fn meta_encoding(bytes: &[u8], position: &mut usize) -> Option<EncodingRef> {
+    let mut attribute_list = vec![];
+    let mut got_pragma = false;
+    let mut need_pragma = None;
+    let mut charset = None;
+    while let Some((name, value)) = get_an_attribute(bytes, position) {
+        if attribute_list.contains(&name) {
+            continue;
+        }
+        let value = String::from_utf8_lossy(&value);
+        if &name[..] == &b"http-equiv"[..] {
+            got_pragma |= value == "content-type";
+        } else if &name[..] == &b"content"[..] && charset.is_none() {
+            if let Some(encoding) = extract_encoding_from_content(&value) {
+                charset = Some(encoding);
+                need_pragma = Some(true);
+            }
+        } else if &name[..] == &b"charset"[..] {
+            charset = get_an_encoding(&value);
+            need_pragma = Some(false);
+        }
+        attribute_list.push(name);
+    }
+    match need_pragma {
+        None => None,
+        Some(true) if !got_pragma => None,
+        _ => charset,
+    }
+}
+
+/// Decodes the bytes of a document incrementally, replacing invalid sequences with U+FFFD.
+#[derive(JSTraceable)]
+pub struct DocumentDecoder {
+    decoder: Box<RawDecoder>,
+}
+
+impl DocumentDecoder {
+    pub fn new(encoding: EncodingRef) -> DocumentDecoder {
+        DocumentDecoder {
+            decoder: encoding.raw_decoder(),
+        }
+    }
+
+    /// Decodes the next chunk of the document. A sequence that is split between two
+    /// chunks is decoded with the later one.
+    pub fn decode(&mut self, input: &[u8]) -> String {
+        let mut output = String::new();
+        let mut remaining = 0;
+        while let (_, Some(error)) = self.decoder.raw_feed(&input[remaining..], &mut output) {
+            output.push('\u{FFFD}');
+            remaining = (remaining as isize + error.upto) as usize;
+        }
+        output
+    }
+
+    /// Decodes what is left of the document at its end.
+    pub fn finish(&mut self) -> String {
+        let mut output = String::new();
+        if self.decoder.raw_finish(&mut output).is_some() {
+            output.push('\u{FFFD}');
+        }
+        output
+    }
+}
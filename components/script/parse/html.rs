@@ -256,8 +256,15 @@ pub fn parse_html(document: &Document,
                   url: Url,
                   context: ParseContext) {
     let parser = match context {
-        ParseContext::Owner(owner) =>
-            ServoHTMLParser::new(Some(url), document, owner),
+        ParseContext::Owner(owner) => {
+            let parser = ServoHTMLParser::new(Some(url), document, owner);
+            if owner.is_none() {
+                // Without a pipeline the whole input is available up front, so
+                // finish parsing as soon as it has been consumed.
+                parser.last_chunk_received().set(true);
+            }
+            parser
+        },
         ParseContext::Fragment(fc) =>
             ServoHTMLParser::new_for_fragment(Some(url), document, fc),
     };
@@ -13,6 +13,7 @@ use std::cell::Cell;
 use std::cell::UnsafeCell;
 use std::ptr;
 
+pub mod encoding;
 pub mod html;
 pub mod xml;
 
@@ -139,6 +140,13 @@ impl<'a> ParserRef<'a> {
         }
     }
 
+    pub fn abort(&self) {
+        match *self {
+            ParserRef::HTML(parser) => parser.abort(),
+            ParserRef::XML(parser) => parser.abort(),
+        }
+    }
+
     pub fn pending_input(&self) -> &DOMRefCell<Vec<String>> {
         match *self {
             ParserRef::HTML(parser) => parser.pending_input(),
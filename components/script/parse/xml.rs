@@ -33,6 +33,9 @@ impl<'a> TreeSink for servoxmlparser::Sink {
 
     fn parse_error(&mut self, msg: Cow<'static, str>) {
         debug!("Parse error: {}", msg);
+        if self.error.is_none() {
+            self.error = Some(msg.into_owned());
+        }
     }
 
     fn get_document(&mut self) -> JS<Node> {
@@ -60,11 +63,12 @@ impl<'a> TreeSink for servoxmlparser::Sink {
                                    ElementCreator::ParserCreated);
 
         for attr in attrs {
+            let prefix = if attr.name.prefix == atom!("") { None } else { Some(attr.name.prefix) };
             let name = QualName {
                 ns: Namespace(attr.name.namespace_url),
                 local: attr.name.local,
             };
-            elem.set_attribute_from_parser(name, DOMString::from(String::from(attr.value)), None);
+            elem.set_attribute_from_parser(name, DOMString::from(String::from(attr.value)), prefix);
         }
 
         JS::from_ref(elem.upcast())
@@ -132,10 +136,19 @@ pub enum ParseContext {
 pub fn parse_xml(document: &Document,
                  input: DOMString,
                  url: Url,
-                 context: ParseContext) {
+                 context: ParseContext)
+                 -> Root<ServoXMLParser> {
     let parser = match context {
-        ParseContext::Owner(owner) =>
-            ServoXMLParser::new(Some(url), document, owner),
+        ParseContext::Owner(owner) => {
+            let parser = ServoXMLParser::new(Some(url), document, owner);
+            if owner.is_none() {
+                // Without a pipeline the whole input is available up front, so
+                // finish parsing as soon as it has been consumed.
+                parser.last_chunk_received().set(true);
+            }
+            parser
+        },
     };
     parser.parse_chunk(String::from(input));
+    parser
 }
@@ -17,6 +17,7 @@
 //! a page runs its course and the script thread returns to processing events in the main event
 //! loop.
 
+use canvas_traits::webxr::WebXRMsg;
 use devtools;
 use devtools_traits::{DevtoolScriptControlMsg, DevtoolsPageInfo};
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
@@ -25,29 +26,38 @@ use document_loader::DocumentLoader;
 use dom::bindings::cell::DOMRefCell;
 use dom::bindings::codegen::Bindings::DocumentBinding::{DocumentMethods, DocumentReadyState};
 use dom::bindings::codegen::Bindings::LocationBinding::LocationMethods;
+use dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
 use dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use dom::bindings::conversions::{ConversionResult, FromJSValConvertible, StringificationBehavior};
 use dom::bindings::global::{GlobalRef, GlobalRoot};
 use dom::bindings::inheritance::Castable;
+use dom::bindings::num::Finite;
 use dom::bindings::js::{JS, MutNullableHeap, Root, RootCollection};
 use dom::bindings::js::{RootCollectionPtr, RootedReference};
 use dom::bindings::refcounted::Trusted;
 use dom::bindings::reflector::Reflectable;
 use dom::bindings::str::DOMString;
+use dom::bindings::structuredclone::StructuredCloneData;
 use dom::bindings::trace::JSTraceable;
 use dom::bindings::utils::WRAP_CALLBACKS;
+use dom::animationevent::AnimationEvent;
 use dom::browsingcontext::BrowsingContext;
 use dom::document::{Document, DocumentProgressHandler, DocumentSource, FocusType, IsHTMLDocument};
 use dom::element::Element;
 use dom::event::{Event, EventBubbles, EventCancelable};
 use dom::htmlanchorelement::HTMLAnchorElement;
-use dom::node::{Node, NodeDamage, window_from_node};
+use dom::htmlimageelement::HTMLImageElement;
+use dom::node::{Node, NodeDamage, from_untrusted_node_address, window_from_node};
+use dom::performanceservertiming::server_timing_metrics;
 use dom::serviceworker::TrustedServiceWorkerAddress;
-use dom::serviceworkerregistration::ServiceWorkerRegistration;
+use dom::serviceworkerregistration::{ServiceWorkerRegistration, longest_matching_scope};
 use dom::servohtmlparser::ParserContext;
 use dom::uievent::UIEvent;
 use dom::window::{ReflowReason, ScriptHelpers, Window};
 use dom::worker::TrustedWorkerAddress;
+use encoding::EncodingRef;
+use encoding::all::UTF_8;
+use encoding::label::encoding_from_whatwg_label;
 use euclid::Rect;
 use euclid::point::Point2D;
 use gfx_traits::LayerId;
@@ -66,22 +76,25 @@ use js::rust::Runtime;
 use mem::heap_size_of_self_and_children;
 use msg::constellation_msg::{FrameType, LoadData, PipelineId, PipelineNamespace};
 use msg::constellation_msg::{ReferrerPolicy, WindowSizeType};
-use net_traits::{AsyncResponseTarget, CoreResourceMsg, LoadConsumer, LoadContext, Metadata, ResourceThreads};
-use net_traits::{IpcSend, LoadData as NetLoadData};
+use net_traits::{AsyncResponseListener, AsyncResponseTarget, CoreResourceMsg, LoadConsumer, LoadContext};
+use net_traits::{IpcBytes, IpcSend, LoadData as NetLoadData, Metadata, ResourceThreads, ResponseAction};
 use net_traits::bluetooth_thread::BluetoothMethodMsg;
 use net_traits::image_cache_thread::{ImageCacheChan, ImageCacheResult, ImageCacheThread};
 use network_listener::NetworkListener;
+use origin::Origin;
 use parse::ParserRoot;
 use parse::html::{ParseContext, parse_html};
 use parse::xml::{self, parse_xml};
 use profile_traits::mem::{self, OpaqueSender, Report, ReportKind, ReportsChan};
-use profile_traits::time::{self, ProfilerCategory, profile};
+use profile_traits::energy::read_energy_uj;
+use profile_traits::time::{self, ProfilerCategory, TimerMetadata, TimerMetadataFrameType};
+use profile_traits::time::{TimerMetadataReflowType, send_profile_data};
 use script_layout_interface::message::{self, NewLayoutThreadInfo, ReflowQueryType};
 use script_runtime::{CommonScriptMsg, ScriptChan, ScriptThreadEventCategory, EnqueuedPromiseCallback};
 use script_runtime::{ScriptPort, StackRootTLS, get_reports, new_rt_and_cx, PromiseJobQueue};
-use script_traits::{CompositorEvent, ConstellationControlMsg, EventResult};
-use script_traits::{InitialScriptState, MouseButton, MouseEventType, MozBrowserEvent};
-use script_traits::{NewLayoutInfo, ScriptMsg as ConstellationMsg};
+use script_traits::{AnimationEventType, CompositorEvent, ConstellationControlMsg, DOMMessage, EventResult};
+use script_traits::{HistoryStateId, InitialScriptState, MouseButton, MouseEventType, MozBrowserEvent};
+use script_traits::{NewLayoutInfo, PaintMetricType, ScriptMsg as ConstellationMsg};
 use script_traits::{ScriptThreadFactory, TimerEvent, TimerEventRequest, TimerSource};
 use script_traits::{TouchEventType, TouchId, UntrustedNodeAddress, WindowSizeData};
 use script_traits::CompositorEvent::{KeyEvent, MouseButtonEvent, MouseMoveEvent, ResizeEvent};
@@ -97,19 +110,23 @@ use std::result::Result;
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Select, Sender, channel};
+use string_cache::Atom;
 use style::context::ReflowGoal;
 use style::thread_state;
+use task_attribution::{TaskAttribution, TaskSourceKind, origin_of};
 use task_source::TaskSource;
 use task_source::dom_manipulation::{DOMManipulationTask, DOMManipulationTaskSource};
 use task_source::file_reading::FileReadingTaskSource;
 use task_source::history_traversal::HistoryTraversalTaskSource;
 use task_source::networking::NetworkingTaskSource;
 use task_source::user_interaction::{UserInteractionTask, UserInteractionTaskSource};
-use time::Tm;
+use time::{Tm, get_time, precise_time_ns};
 use url::{Position, Url};
+use usb_traits::UsbMethodMsg;
 use util::opts;
 use util::thread;
 use webdriver_handlers;
+use webrtc_traits::WebRTCMsg;
 
 thread_local!(pub static STACK_ROOTS: Cell<Option<RootCollectionPtr>> = Cell::new(None));
 thread_local!(static SCRIPT_THREAD_ROOT: Cell<Option<*const ScriptThread>> = Cell::new(None));
@@ -143,8 +160,14 @@ struct InProgressLoad {
     is_frozen: bool,
     /// Window is visible.
     is_visible: bool,
+    /// The media of the window is muted.
+    is_muted: bool,
     /// The requested URL of the load.
     url: Url,
+    /// The time the navigation started, in milliseconds since the epoch.
+    navigation_start: u64,
+    /// The time the navigation started, as a `time::precise_time_ns` value.
+    navigation_start_precise: f64,
 }
 
 impl InProgressLoad {
@@ -154,6 +177,7 @@ impl InProgressLoad {
            layout_chan: Sender<message::Msg>,
            window_size: Option<WindowSizeData>,
            url: Url) -> InProgressLoad {
+        let current_time = get_time();
         InProgressLoad {
             pipeline_id: id,
             parent_info: parent_info,
@@ -162,7 +186,10 @@ impl InProgressLoad {
             clip_rect: None,
             is_frozen: false,
             is_visible: true,
+            is_muted: false,
             url: url,
+            navigation_start: (current_time.sec * 1000 + current_time.nsec as i64 / 1000000) as u64,
+            navigation_start_precise: precise_time_ns() as f64,
         }
     }
 }
@@ -334,6 +361,12 @@ pub struct ScriptThread {
     resource_threads: ResourceThreads,
     /// A handle to the bluetooth thread.
     bluetooth_thread: IpcSender<BluetoothMethodMsg>,
+    /// A handle to the USB thread.
+    usb_thread: IpcSender<UsbMethodMsg>,
+    /// A handle to the WebRTC thread.
+    webrtc_thread: IpcSender<WebRTCMsg>,
+    /// A handle to the WebXR thread.
+    webxr_thread: IpcSender<WebXRMsg>,
 
     /// The port on which the script thread receives messages (load URL, exit, etc.)
     port: Receiver<MainThreadScriptMsg>,
@@ -373,6 +406,9 @@ pub struct ScriptThread {
     /// For providing contact with the memory profiler.
     mem_profiler_chan: mem::ProfilerChan,
 
+    /// The CPU time spent running tasks, per task source and origin.
+    task_attribution: DOMRefCell<TaskAttribution>,
+
     /// For providing instructions to an optional devtools server.
     devtools_chan: Option<IpcSender<ScriptToDevtoolsControlMsg>>,
     /// For receiving commands from an optional devtools server. Will be ignored if
@@ -495,6 +531,15 @@ impl ScriptThread {
         })
     }
 
+    /// Attaches the layout of a new iframe's pipeline and builds its initial about:blank
+    /// document before returning, so the nested browsing context is usable right away.
+    pub fn process_attach_layout(new_layout_info: NewLayoutInfo) {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            let script_thread = unsafe { &*root.get().unwrap() };
+            script_thread.handle_new_about_blank_layout(new_layout_info);
+        })
+    }
+
     // stores a service worker registration
     pub fn set_registration(scope_url: Url, registration:&ServiceWorkerRegistration, pipeline_id: PipelineId) {
         SCRIPT_THREAD_ROOT.with(|root| {
@@ -572,6 +617,9 @@ impl ScriptThread {
 
             resource_threads: state.resource_threads,
             bluetooth_thread: state.bluetooth_thread,
+            usb_thread: state.usb_thread,
+            webrtc_thread: state.webrtc_thread,
+            webxr_thread: state.webxr_thread,
 
             port: port,
 
@@ -587,6 +635,7 @@ impl ScriptThread {
             constellation_chan: state.constellation_chan,
             time_profiler_chan: state.time_profiler_chan,
             mem_profiler_chan: state.mem_profiler_chan,
+            task_attribution: DOMRefCell::new(TaskAttribution::new()),
 
             devtools_chan: state.devtools_chan,
             devtools_port: devtools_port,
@@ -705,22 +754,23 @@ impl ScriptThread {
                 // child list yet, causing the find() to fail.
                 FromConstellation(ConstellationControlMsg::AttachLayout(
                         new_layout_info)) => {
-                    self.profile_event(ScriptThreadEventCategory::AttachLayout, || {
+                    let parent_id = new_layout_info.parent_pipeline_id;
+                    self.profile_event(ScriptThreadEventCategory::AttachLayout, Some(parent_id), || {
                         self.handle_new_layout(new_layout_info);
                     })
                 }
                 FromConstellation(ConstellationControlMsg::Resize(id, size, size_type)) => {
-                    self.profile_event(ScriptThreadEventCategory::Resize, || {
+                    self.profile_event(ScriptThreadEventCategory::Resize, Some(id), || {
                         self.handle_resize(id, size, size_type);
                     })
                 }
                 FromConstellation(ConstellationControlMsg::Viewport(id, rect)) => {
-                    self.profile_event(ScriptThreadEventCategory::SetViewport, || {
+                    self.profile_event(ScriptThreadEventCategory::SetViewport, Some(id), || {
                         self.handle_viewport(id, rect);
                     })
                 }
                 FromConstellation(ConstellationControlMsg::SetScrollState(id, scroll_state)) => {
-                    self.profile_event(ScriptThreadEventCategory::SetScrollState, || {
+                    self.profile_event(ScriptThreadEventCategory::SetScrollState, Some(id), || {
                         self.handle_set_scroll_state(id, &scroll_state);
                     })
                 }
@@ -733,7 +783,8 @@ impl ScriptThread {
                 }
                 FromConstellation(ConstellationControlMsg::SendEvent(
                         _,
-                        MouseMoveEvent(_))) => {
+                        MouseMoveEvent(_),
+                        _)) => {
                     match mouse_move_event_index {
                         None => {
                             mouse_move_event_index = Some(sequential.len());
@@ -773,8 +824,14 @@ impl ScriptThread {
         // Process the gathered events.
         for msg in sequential {
             let category = self.categorize_msg(&msg);
+            let source = match msg {
+                FromConstellation(ConstellationControlMsg::TickAllAnimations(_)) =>
+                    TaskSourceKind::AnimationFrame,
+                _ => TaskSourceKind::of(category),
+            };
+            let pipeline_id = self.pipeline_of_msg(&msg);
 
-            let result = self.profile_event(category, move || {
+            let result = self.profile_task(category, source, pipeline_id, move || {
                 match msg {
                     FromConstellation(ConstellationControlMsg::ExitPipeline(id)) => {
                         if self.handle_exit_pipeline_msg(id) {
@@ -827,7 +884,7 @@ impl ScriptThread {
         match *msg {
             MixedMessage::FromConstellation(ref inner_msg) => {
                 match *inner_msg {
-                    ConstellationControlMsg::SendEvent(_, _) =>
+                    ConstellationControlMsg::SendEvent(..) =>
                         ScriptThreadEventCategory::DomEvent,
                     _ => ScriptThreadEventCategory::ConstellationMsg
                 }
@@ -845,47 +902,107 @@ impl ScriptThread {
         }
     }
 
-    fn profile_event<F, R>(&self, category: ScriptThreadEventCategory, f: F) -> R
-        where F: FnOnce() -> R {
-        if opts::get().profile_script_events {
-            let profiler_cat = match category {
-                ScriptThreadEventCategory::AttachLayout => ProfilerCategory::ScriptAttachLayout,
-                ScriptThreadEventCategory::ConstellationMsg => ProfilerCategory::ScriptConstellationMsg,
-                ScriptThreadEventCategory::DevtoolsMsg => ProfilerCategory::ScriptDevtoolsMsg,
-                ScriptThreadEventCategory::DocumentEvent => ProfilerCategory::ScriptDocumentEvent,
-                ScriptThreadEventCategory::DomEvent => ProfilerCategory::ScriptDomEvent,
-                ScriptThreadEventCategory::FileRead => ProfilerCategory::ScriptFileRead,
-                ScriptThreadEventCategory::FormPlannedNavigation => ProfilerCategory::ScriptPlannedNavigation,
-                ScriptThreadEventCategory::ImageCacheMsg => ProfilerCategory::ScriptImageCacheMsg,
-                ScriptThreadEventCategory::InputEvent => ProfilerCategory::ScriptInputEvent,
-                ScriptThreadEventCategory::NetworkEvent => ProfilerCategory::ScriptNetworkEvent,
-                ScriptThreadEventCategory::Resize => ProfilerCategory::ScriptResize,
-                ScriptThreadEventCategory::ScriptEvent => ProfilerCategory::ScriptEvent,
-                ScriptThreadEventCategory::SetScrollState => {
-                    ProfilerCategory::ScriptSetScrollState
-                }
-                ScriptThreadEventCategory::UpdateReplacedElement => {
-                    ProfilerCategory::ScriptUpdateReplacedElement
+    /// The pipeline a message is for, when the message says so.
+    fn pipeline_of_msg(&self, msg: &MixedMessage) -> Option<PipelineId> {
+        match *msg {
+            MixedMessage::FromConstellation(ref inner_msg) => {
+                match *inner_msg {
+                    ConstellationControlMsg::SendEvent(id, _, _) |
+                    ConstellationControlMsg::TickAllAnimations(id) |
+                    ConstellationControlMsg::WebFontLoaded(id) |
+                    ConstellationControlMsg::Reload(id) |
+                    ConstellationControlMsg::StopLoading(id) |
+                    ConstellationControlMsg::PaintMetric(id, _, _) |
+                    ConstellationControlMsg::LargestContentfulPaint(id, _, _, _) |
+                    ConstellationControlMsg::AnimationEvent(id, _, _, _, _) |
+                    ConstellationControlMsg::PostMessageFromServiceWorker(id, _, _) |
+                    ConstellationControlMsg::PostMessage(id, _, _) |
+                    ConstellationControlMsg::ClaimedByServiceWorker(id, _, _, _) |
+                    ConstellationControlMsg::UpdateHistoryState(id, ..) => Some(id),
+                    _ => None,
                 }
-                ScriptThreadEventCategory::StylesheetLoad => ProfilerCategory::ScriptStylesheetLoad,
-                ScriptThreadEventCategory::SetViewport => ProfilerCategory::ScriptSetViewport,
-                ScriptThreadEventCategory::TimerEvent => ProfilerCategory::ScriptTimerEvent,
-                ScriptThreadEventCategory::WebSocketEvent => ProfilerCategory::ScriptWebSocketEvent,
-                ScriptThreadEventCategory::WorkerEvent => ProfilerCategory::ScriptWorkerEvent,
-                ScriptThreadEventCategory::ServiceWorkerEvent => ProfilerCategory::ScriptServiceWorkerEvent
-            };
-            profile(profiler_cat, None, self.time_profiler_chan.clone(), f)
+            },
+            MixedMessage::FromScript(MainThreadScriptMsg::DocumentLoadsComplete(id)) |
+            MixedMessage::FromScript(MainThreadScriptMsg::Navigate(id, _, _)) => Some(id),
+            MixedMessage::FromScheduler(TimerEvent(TimerSource::FromWindow(id), _)) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Called when script starts running in a global, so that the running task can be
+    /// attributed to its origin if the task did not say which pipeline it was for.
+    pub fn note_script_entered<F: FnOnce() -> Url>(url: F) {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            if let Some(script_thread) = root.get() {
+                let script_thread = unsafe { &*script_thread };
+                script_thread.task_attribution.borrow_mut().note_script_entered(url);
+            }
+        });
+    }
+
+    fn profile_event<F, R>(&self,
+                           category: ScriptThreadEventCategory,
+                           pipeline_id: Option<PipelineId>,
+                           f: F)
+                           -> R
+        where F: FnOnce() -> R {
+        self.profile_task(category, TaskSourceKind::of(category), pipeline_id, f)
+    }
+
+    /// Runs the task `f`, accounting its CPU time to `source` and the origin it ran for, and
+    /// reporting it to the time profiler if script events are being profiled.
+    fn profile_task<F, R>(&self,
+                          category: ScriptThreadEventCategory,
+                          source: TaskSourceKind,
+                          pipeline_id: Option<PipelineId>,
+                          f: F)
+                          -> R
+        where F: FnOnce() -> R {
+        let context = pipeline_id.and_then(|id| self.find_child_context(id));
+        let origin = context.as_ref().map(|context| origin_of(&context.active_document().url()));
+        let attributed = self.task_attribution.borrow_mut().begin_task(source, origin);
+
+        let profiling = opts::get().profile_script_events;
+        let (start_time, start_energy) = if profiling {
+            (precise_time_ns(), read_energy_uj())
         } else {
-            f()
+            (0, 0)
+        };
+        let result = f();
+
+        let origin = if attributed {
+            self.task_attribution.borrow_mut().end_task()
+        } else {
+            None
+        };
+        if profiling {
+            let is_iframe = context.map_or(false, |context| !context.active_window().is_top_level());
+            let metadata = origin.map(|origin| TimerMetadata {
+                url: origin,
+                iframe: if is_iframe {
+                    TimerMetadataFrameType::IFrame
+                } else {
+                    TimerMetadataFrameType::RootWindow
+                },
+                incremental: TimerMetadataReflowType::Incremental,
+            });
+            send_profile_data(profiler_category(category),
+                              metadata,
+                              self.time_profiler_chan.clone(),
+                              start_time,
+                              precise_time_ns(),
+                              start_energy,
+                              read_energy_uj());
         }
+        result
     }
 
     fn handle_msg_from_constellation(&self, msg: ConstellationControlMsg) {
         match msg {
             ConstellationControlMsg::Navigate(parent_pipeline_id, pipeline_id, load_data, replace) =>
                 self.handle_navigate(parent_pipeline_id, Some(pipeline_id), load_data, replace),
-            ConstellationControlMsg::SendEvent(id, event) =>
-                self.handle_event(id, event),
+            ConstellationControlMsg::SendEvent(id, event, input_time) =>
+                self.handle_event(id, event, input_time),
             ConstellationControlMsg::ResizeInactive(id, new_size) =>
                 self.handle_resize_inactive_msg(id, new_size),
             ConstellationControlMsg::GetTitle(pipeline_id) =>
@@ -898,6 +1015,8 @@ impl ScriptThread {
                 self.handle_visibility_change_msg(pipeline_id, visible),
             ConstellationControlMsg::NotifyVisibilityChange(parent_pipeline_id, pipeline_id, visible) =>
                 self.handle_visibility_change_complete_msg(parent_pipeline_id, pipeline_id, visible),
+            ConstellationControlMsg::SetMuted(pipeline_id, muted) =>
+                self.handle_set_muted_msg(pipeline_id, muted),
             ConstellationControlMsg::MozBrowserEvent(parent_pipeline_id,
                                                      pipeline_id,
                                                      event) =>
@@ -927,6 +1046,22 @@ impl ScriptThread {
                 self.handle_css_error_reporting(pipeline_id, filename, line, column, msg),
             ConstellationControlMsg::Reload(pipeline_id) =>
                 self.handle_reload(pipeline_id),
+            ConstellationControlMsg::StopLoading(pipeline_id) =>
+                self.handle_stop_loading(pipeline_id),
+            ConstellationControlMsg::UpdateHistoryState(pipeline_id, state_id, url, state) =>
+                self.handle_update_history_state_msg(pipeline_id, state_id, url, state),
+            ConstellationControlMsg::PostMessageFromServiceWorker(pipeline_id, msg, scope_url) =>
+                self.handle_serviceworker_message(pipeline_id, msg, scope_url),
+            ConstellationControlMsg::ClaimedByServiceWorker(pipeline_id, script_url, scope_url, scopes) =>
+                self.handle_serviceworker_claim(pipeline_id, script_url, scope_url, scopes),
+            ConstellationControlMsg::PaintMetric(pipeline_id, metric_type, paint_time) =>
+                self.handle_paint_metric(pipeline_id, metric_type, paint_time),
+            ConstellationControlMsg::LargestContentfulPaint(pipeline_id, node_address, size, paint_time) =>
+                self.handle_largest_contentful_paint(pipeline_id, node_address, size, paint_time),
+            ConstellationControlMsg::AnimationEvent(pipeline_id, node_address, event_type, name, elapsed_time) =>
+                self.handle_animation_event(pipeline_id, node_address, event_type, name, elapsed_time),
+            ConstellationControlMsg::PostMessage(pipeline_id, origin, data) =>
+                self.handle_post_message_msg(pipeline_id, origin, data),
             msg @ ConstellationControlMsg::AttachLayout(..) |
             msg @ ConstellationControlMsg::Viewport(..) |
             msg @ ConstellationControlMsg::SetScrollState(..) |
@@ -1019,7 +1154,7 @@ impl ScriptThread {
     }
 
     fn handle_msg_from_image_cache(&self, msg: ImageCacheResult) {
-        msg.responder.unwrap().respond(msg.image_response);
+        msg.responder.unwrap().respond(msg.image_response, msg.metadata);
     }
 
     fn handle_webdriver_msg(&self, pipeline_id: PipelineId, msg: WebDriverScriptCommand) {
@@ -1129,10 +1264,12 @@ impl ScriptThread {
         window.set_scroll_offsets(scroll_offsets)
     }
 
-    fn handle_new_layout(&self, new_layout_info: NewLayoutInfo) {
+    /// Spawns the layout thread of a new child pipeline, through the layout thread of its parent.
+    fn attach_layout(&self, new_layout_info: NewLayoutInfo) -> (InProgressLoad, LoadData) {
         let NewLayoutInfo {
             parent_pipeline_id,
             new_pipeline_id,
+            old_pipeline_id,
             frame_type,
             load_data,
             paint_chan,
@@ -1147,6 +1284,7 @@ impl ScriptThread {
 
         let layout_creation_info = NewLayoutThreadInfo {
             id: new_pipeline_id,
+            old_pipeline_id: old_pipeline_id,
             url: load_data.url.clone(),
             is_parent: false,
             layout_pair: layout_pair,
@@ -1170,20 +1308,31 @@ impl ScriptThread {
                      .send(message::Msg::CreateLayoutThread(layout_creation_info))
                      .unwrap();
 
-        // Kick off the fetch for the new resource.
         let new_load = InProgressLoad::new(new_pipeline_id, Some((parent_pipeline_id, frame_type)),
                                            layout_chan, parent_window.window_size(),
                                            load_data.url.clone());
+        (new_load, load_data)
+    }
+
+    fn handle_new_layout(&self, new_layout_info: NewLayoutInfo) {
+        // Kick off the fetch for the new resource.
+        let (new_load, load_data) = self.attach_layout(new_layout_info);
         self.start_page_load(new_load, load_data);
     }
 
+    fn handle_new_about_blank_layout(&self, new_layout_info: NewLayoutInfo) {
+        let (new_load, load_data) = self.attach_layout(new_layout_info);
+        self.load_about_blank(new_load, load_data);
+    }
+
     fn handle_loads_complete(&self, pipeline: PipelineId) {
         let doc = match self.root_browsing_context().find(pipeline) {
             Some(browsing_context) => browsing_context.active_document(),
             None => return warn!("Message sent to closed pipeline {}.", pipeline),
         };
         let doc = doc.r();
-        if doc.loader().is_blocked() {
+        // The document may have been aborted since its last load finished.
+        if doc.loader().is_blocked() || doc.loader().events_inhibited() {
             return;
         }
 
@@ -1235,6 +1384,7 @@ impl ScriptThread {
         }
         let path_seg = format!("url({})", urls.join(", "));
         reports.extend(get_reports(self.get_cx(), path_seg));
+        reports.extend(self.task_attribution.borrow().reports());
         reports_chan.send(reports);
     }
 
@@ -1287,6 +1437,21 @@ impl ScriptThread {
         warn!("change visibility message sent to nonexistent pipeline");
     }
 
+    /// Mutes or unmutes the media of a window
+    fn handle_set_muted_msg(&self, id: PipelineId, muted: bool) {
+        if let Some(root_context) = self.browsing_context.get() {
+            if let Some(ref inner_context) = root_context.find(id) {
+                return inner_context.active_window().set_muted(muted);
+            }
+        }
+        let mut loads = self.incomplete_loads.borrow_mut();
+        if let Some(ref mut load) = loads.iter_mut().find(|load| load.pipeline_id == id) {
+            load.is_muted = muted;
+            return;
+        }
+        warn!("mute message sent to nonexistent pipeline");
+    }
+
     /// Handles freeze message
     fn handle_freeze_msg(&self, id: PipelineId) {
         if let Some(root_context) = self.browsing_context.get() {
@@ -1452,7 +1617,9 @@ impl ScriptThread {
             let window = context.active_window();
             let global_ref = GlobalRef::Window(window.r());
             let script_url = maybe_registration.get_installed().get_script_url();
-            let scope_things = ServiceWorkerRegistration::create_scope_things(global_ref, script_url);
+            let scope_things = ServiceWorkerRegistration::create_scope_things(global_ref,
+                                                                              script_url,
+                                                                              maybe_registration.update_via_cache());
             let _ = self.constellation_chan.send(ConstellationMsg::RegisterServiceWorker(scope_things, scope));
         } else {
             warn!("Registration failed for {}", scope);
@@ -1602,6 +1769,9 @@ impl ScriptThread {
                                  self.image_cache_thread.clone(),
                                  self.resource_threads.clone(),
                                  self.bluetooth_thread.clone(),
+                                 self.usb_thread.clone(),
+                                 self.webrtc_thread.clone(),
+                                 self.webxr_thread.clone(),
                                  self.mem_profiler_chan.clone(),
                                  self.time_profiler_chan.clone(),
                                  self.devtools_chan.clone(),
@@ -1612,7 +1782,9 @@ impl ScriptThread {
                                  incomplete.layout_chan,
                                  incomplete.pipeline_id,
                                  incomplete.parent_info,
-                                 incomplete.window_size);
+                                 incomplete.window_size,
+                                 incomplete.navigation_start,
+                                 incomplete.navigation_start_precise);
         let frame_element = frame_element.r().map(Castable::upcast);
 
         enum ContextToRemove {
@@ -1789,6 +1961,8 @@ impl ScriptThread {
         };
 
         document.set_https_state(metadata.https_state);
+        document.set_fetch_timing(metadata.timing);
+        document.set_server_timing(server_timing_metrics(&metadata));
 
         let is_xml = match metadata.content_type {
             Some(Serde(ContentType(Mime(TopLevel::Application, SubLevel::Ext(ref sub_level), _))))
@@ -1820,6 +1994,10 @@ impl ScriptThread {
             self.alter_resource_utilization(browsing_context.pipeline_id(), false);
         }
 
+        if incomplete.is_muted {
+            window.set_muted(true);
+        }
+
         context_remover.neuter();
 
         document.get_current_parser().unwrap()
@@ -1870,14 +2048,29 @@ impl ScriptThread {
     }
 
     /// This is the main entry point for receiving and dispatching DOM events.
-    ///
-    /// TODO: Actually perform DOM event dispatch.
-    fn handle_event(&self, pipeline_id: PipelineId, event: CompositorEvent) {
+    /// The events caused by the input are timed from `input_time`.
+    fn handle_event(&self, pipeline_id: PipelineId, event: CompositorEvent, input_time: u64) {
         // DOM events can only be handled if there's a root browsing context.
         if !self.root_browsing_context_exists() {
             return;
         }
 
+        let performance = self.root_browsing_context().find(pipeline_id).map(|context| {
+            context.active_window().Performance()
+        });
+        if let Some(ref performance) = performance {
+            performance.set_input_time(Some(input_time));
+        }
+        self.dispatch_compositor_event(pipeline_id, event);
+        if let Some(ref performance) = performance {
+            performance.set_input_time(None);
+        }
+    }
+
+    /// Dispatches the DOM events that an input causes.
+    ///
+    /// TODO: Actually perform DOM event dispatch.
+    fn dispatch_compositor_event(&self, pipeline_id: PipelineId, event: CompositorEvent) {
         match event {
             ResizeEvent(new_size, size_type) => {
                 self.handle_resize_event(pipeline_id, new_size, size_type);
@@ -2020,6 +2213,9 @@ impl ScriptThread {
                 let url = document.url();
                 if &url[..Position::AfterQuery] == &nurl[..Position::AfterQuery] &&
                     load_data.method == Method::Get {
+                    // Navigating to the current URL replaces the entry rather than adding one.
+                    let replace = replace || url == *nurl;
+                    document.window().History().navigate_to_fragment(nurl.clone(), replace);
                     self.check_and_scroll_fragment(fragment, parent_pipeline_id, document.r());
                     return;
                 }
@@ -2052,11 +2248,17 @@ impl ScriptThread {
         };
         let window = context.active_window();
         window.set_window_size(new_size);
+
+        // https://html.spec.whatwg.org/multipage/#reacting-to-environment-changes
+        let document = context.active_document();
+        for image in document.upcast::<Node>().traverse_preorder().filter_map(Root::downcast::<HTMLImageElement>) {
+            image.react_to_environment_changes();
+        }
+
         window.force_reflow(ReflowGoal::ForDisplay,
                             ReflowQueryType::NoQuery,
                             ReflowReason::WindowResize);
 
-        let document = context.active_document();
         let fragment_node = window.steal_fragment_name()
                                   .and_then(|name| document.find_fragment_node(&*name));
         match fragment_node {
@@ -2079,13 +2281,34 @@ impl ScriptThread {
     fn start_page_load(&self, incomplete: InProgressLoad, mut load_data: LoadData) {
         let id = incomplete.pipeline_id.clone();
 
-        let context = Arc::new(Mutex::new(ParserContext::new(id, load_data.url.clone())));
-        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        // https://html.spec.whatwg.org/multipage/#an-iframe-srcdoc-document is always UTF-8.
+        let encoding_override = if load_data.srcdoc.is_some() {
+            Some(UTF_8 as EncodingRef)
+        } else {
+            load_data.encoding.as_ref().and_then(|label| encoding_from_whatwg_label(label))
+        };
+        let context = Arc::new(Mutex::new(ParserContext::new(id, load_data.url.clone(), encoding_override)));
         let listener = NetworkListener {
             context: context,
             script_chan: self.chan.clone(),
             wrapper: None,
         };
+
+        // https://html.spec.whatwg.org/multipage/#process-the-iframe-attributes step 1:
+        // an about:srcdoc document is parsed from the srcdoc attribute, not fetched.
+        if let Some(srcdoc) = load_data.srcdoc.take() {
+            let mut metadata = Metadata::default(load_data.url);
+            metadata.set_content_type(Some(&Mime(TopLevel::Text, SubLevel::Html, vec![])));
+            metadata.referrer = load_data.referrer_url;
+            metadata.timing.fetch_start = precise_time_ns();
+            listener.notify_action(ResponseAction::HeadersAvailable(Ok(metadata)));
+            listener.notify_action(ResponseAction::DataAvailable(IpcBytes::from_vec(srcdoc.into_bytes())));
+            listener.notify_action(ResponseAction::ResponseComplete(Ok(())));
+            self.incomplete_loads.borrow_mut().push(incomplete);
+            return;
+        }
+
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
         ROUTER.add_route(action_receiver.to_opaque(), box move |message| {
             listener.notify_action(message.to().unwrap());
         });
@@ -2114,6 +2337,20 @@ impl ScriptThread {
         self.incomplete_loads.borrow_mut().push(incomplete);
     }
 
+    /// Creates the document of an initial about:blank load without going through the
+    /// network or the event loop: the empty response is fed straight to the parser.
+    /// https://html.spec.whatwg.org/multipage/#creating-a-new-browsing-context
+    fn load_about_blank(&self, incomplete: InProgressLoad, load_data: LoadData) {
+        let mut context = ParserContext::new(incomplete.pipeline_id, load_data.url.clone(), None);
+        let mut metadata = Metadata::default(load_data.url);
+        metadata.set_content_type(Some(&Mime(TopLevel::Text, SubLevel::Html, vec![])));
+        metadata.referrer = load_data.referrer_url;
+        metadata.timing.fetch_start = precise_time_ns();
+        self.incomplete_loads.borrow_mut().push(incomplete);
+        context.headers_available(Ok(metadata));
+        context.response_complete(Ok(()));
+    }
+
     fn handle_parsing_complete(&self, id: PipelineId) {
         let parent_context = self.root_browsing_context();
         let context = match parent_context.find(id) {
@@ -2179,6 +2416,129 @@ impl ScriptThread {
         }
     }
 
+    /// https://html.spec.whatwg.org/multipage/#stop-document-loading step 4; the
+    /// constellation already cancelled the navigations of the browsing context.
+    fn handle_stop_loading(&self, pipeline_id: PipelineId) {
+        if let Some(context) = self.find_child_context(pipeline_id) {
+            context.active_document().abort();
+        }
+    }
+
+    /// https://html.spec.whatwg.org/multipage/#traverse-the-history
+    fn handle_update_history_state_msg(&self,
+                                       pipeline_id: PipelineId,
+                                       state_id: HistoryStateId,
+                                       url: Url,
+                                       state: Option<Vec<u8>>) {
+        let context = match self.root_browsing_context().find(pipeline_id) {
+            Some(context) => context,
+            None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+        };
+        let window = context.active_window();
+        let history = window.History();
+        let restored = history.activate_state_entry(state_id, url.clone(), state);
+
+        // An entry that was never scrolled away from scrolls to its fragment, unless the
+        // page opted out of scroll restoration.
+        if !restored && history.restores_scroll_position() {
+            if let Some(fragment) = url.fragment() {
+                self.check_and_scroll_fragment(fragment, pipeline_id, &context.active_document());
+            }
+        }
+    }
+
+    /// Delivers a message from a service worker to the window it was posted to.
+    fn handle_serviceworker_message(&self, pipeline_id: PipelineId, msg: DOMMessage, scope_url: Url) {
+        let context = match self.root_browsing_context().find(pipeline_id) {
+            Some(context) => context,
+            None => return warn!("Service worker message sent to closed pipeline {}.", pipeline_id),
+        };
+        let DOMMessage(data) = msg;
+        let container = context.active_window().Navigator().ServiceWorker();
+        container.deliver_message(StructuredCloneData::Vector(data), &scope_url);
+    }
+
+    /// Delivers a message posted from another script thread to the window of a pipeline.
+    fn handle_post_message_msg(&self, pipeline_id: PipelineId, origin: Option<Url>, data: Vec<u8>) {
+        let context = match self.root_browsing_context().find(pipeline_id) {
+            Some(context) => context,
+            None => return warn!("postMessage sent to closed pipeline {}.", pipeline_id),
+        };
+        let origin = origin.map(|url| Origin::new(&url));
+        context.active_window().post_message(origin, StructuredCloneData::Vector(data));
+    }
+
+    /// Makes the active worker of the registration for `scope_url` the controller of the window
+    /// in the pipeline, unless the window's URL belongs to a registration with a longer scope.
+    /// https://w3c.github.io/ServiceWorker/#clients-claim
+    fn handle_serviceworker_claim(&self,
+                                  pipeline_id: PipelineId,
+                                  script_url: Url,
+                                  scope_url: Url,
+                                  scopes: Vec<Url>) {
+        let context = match self.root_browsing_context().find(pipeline_id) {
+            Some(context) => context,
+            None => return warn!("Service worker claim sent to closed pipeline {}.", pipeline_id),
+        };
+        let window = context.active_window();
+        let client_url = window.get_url();
+        if longest_matching_scope(&scopes, &client_url) != Some(&scope_url) {
+            return;
+        }
+        let registration = self.registration_map.borrow().get(&scope_url).map(|r| Root::from_ref(&**r));
+        let container = window.Navigator().ServiceWorker();
+        container.claim(script_url, scope_url, registration.r());
+    }
+
+    fn handle_paint_metric(&self, pipeline_id: PipelineId, metric_type: PaintMetricType, paint_time: u64) {
+        let context = match self.root_browsing_context().find(pipeline_id) {
+            Some(context) => context,
+            None => return warn!("Paint metric sent to closed pipeline {}.", pipeline_id),
+        };
+        context.active_window().Performance().queue_paint_timing(metric_type, paint_time);
+    }
+
+    fn handle_largest_contentful_paint(&self,
+                                       pipeline_id: PipelineId,
+                                       node_address: UntrustedNodeAddress,
+                                       size: f64,
+                                       paint_time: u64) {
+        let context = match self.root_browsing_context().find(pipeline_id) {
+            Some(context) => context,
+            None => return warn!("Largest contentful paint sent to closed pipeline {}.", pipeline_id),
+        };
+        let node = from_untrusted_node_address(self.js_runtime.rt(), node_address);
+        context.active_window().Performance().queue_largest_contentful_paint(&node, size, paint_time);
+    }
+
+    /// https://drafts.csswg.org/css-animations/#events
+    fn handle_animation_event(&self,
+                              pipeline_id: PipelineId,
+                              node_address: UntrustedNodeAddress,
+                              event_type: AnimationEventType,
+                              animation_name: String,
+                              elapsed_time: f64) {
+        let context = match self.root_browsing_context().find(pipeline_id) {
+            Some(context) => context,
+            None => return warn!("Animation event sent to closed pipeline {}.", pipeline_id),
+        };
+        let window = context.active_window();
+        let node = from_untrusted_node_address(self.js_runtime.rt(), node_address);
+        let type_ = match event_type {
+            AnimationEventType::AnimationStart => Atom::from("animationstart"),
+            AnimationEventType::AnimationIteration => Atom::from("animationiteration"),
+            AnimationEventType::AnimationEnd => Atom::from("animationend"),
+        };
+        let event = AnimationEvent::new(GlobalRef::Window(window.r()),
+                                        type_,
+                                        true,
+                                        false,
+                                        DOMString::from(animation_name),
+                                        Finite::wrap(elapsed_time as f32),
+                                        DOMString::new());
+        event.upcast::<Event>().fire(node.upcast());
+    }
+
     pub fn enqueue_promise_job(job: EnqueuedPromiseCallback, global: GlobalRef) {
         SCRIPT_THREAD_ROOT.with(|root| {
             let script_thread = unsafe { &*root.get().unwrap() };
@@ -2249,3 +2609,29 @@ fn shut_down_layout(context_tree: &BrowsingContext) {
 fn dom_last_modified(tm: &Tm) -> String {
     tm.to_local().strftime("%m/%d/%Y %H:%M:%S").unwrap().to_string()
 }
+
+/// The time profiler category that script thread events of `category` are reported in.
+fn profiler_category(category: ScriptThreadEventCategory) -> ProfilerCategory {
+    match category {
+        ScriptThreadEventCategory::AttachLayout => ProfilerCategory::ScriptAttachLayout,
+        ScriptThreadEventCategory::ConstellationMsg => ProfilerCategory::ScriptConstellationMsg,
+        ScriptThreadEventCategory::DevtoolsMsg => ProfilerCategory::ScriptDevtoolsMsg,
+        ScriptThreadEventCategory::DocumentEvent => ProfilerCategory::ScriptDocumentEvent,
+        ScriptThreadEventCategory::DomEvent => ProfilerCategory::ScriptDomEvent,
+        ScriptThreadEventCategory::FileRead => ProfilerCategory::ScriptFileRead,
+        ScriptThreadEventCategory::FormPlannedNavigation => ProfilerCategory::ScriptPlannedNavigation,
+        ScriptThreadEventCategory::ImageCacheMsg => ProfilerCategory::ScriptImageCacheMsg,
+        ScriptThreadEventCategory::InputEvent => ProfilerCategory::ScriptInputEvent,
+        ScriptThreadEventCategory::NetworkEvent => ProfilerCategory::ScriptNetworkEvent,
+        ScriptThreadEventCategory::Resize => ProfilerCategory::ScriptResize,
+        ScriptThreadEventCategory::ScriptEvent => ProfilerCategory::ScriptEvent,
+        ScriptThreadEventCategory::SetScrollState => ProfilerCategory::ScriptSetScrollState,
+        ScriptThreadEventCategory::UpdateReplacedElement => ProfilerCategory::ScriptUpdateReplacedElement,
+        ScriptThreadEventCategory::StylesheetLoad => ProfilerCategory::ScriptStylesheetLoad,
+        ScriptThreadEventCategory::SetViewport => ProfilerCategory::ScriptSetViewport,
+        ScriptThreadEventCategory::TimerEvent => ProfilerCategory::ScriptTimerEvent,
+        ScriptThreadEventCategory::WebSocketEvent => ProfilerCategory::ScriptWebSocketEvent,
+        ScriptThreadEventCategory::WorkerEvent => ProfilerCategory::ScriptWorkerEvent,
+        ScriptThreadEventCategory::ServiceWorkerEvent => ProfilerCategory::ScriptServiceWorkerEvent,
+    }
+}
@@ -5,19 +5,21 @@
 //! The service worker manager persists the descriptor of any registered service workers.
 //! It also stores an active workers map, which holds descriptors of running service workers.
 //! If an active service worker timeouts, then it removes the descriptor entry from its
-//! active_workers map
+//! active_workers map. It also keeps each registration's script resource map, so
+//! that woken-up workers run their stored scripts instead of fetching them again.
 
 use devtools_traits::{DevtoolsPageInfo, ScriptToDevtoolsControlMsg};
-use dom::abstractworker::WorkerScriptMsg;
 use dom::bindings::structuredclone::StructuredCloneData;
-use dom::serviceworkerglobalscope::{ServiceWorkerGlobalScope, ServiceWorkerScriptMsg};
-use dom::serviceworkerregistration::longest_prefix_match;
+use dom::serviceworkerglobalscope::{ServiceWorkerGlobalScope, ServiceWorkerScriptMsg, ServiceWorkerScripts};
+use dom::serviceworkerregistration::longest_matching_scope;
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use net_traits::{CustomResponseMediator, CoreResourceMsg};
 use script_traits::{ServiceWorkerMsg, ScopeThings, SWManagerMsg, SWManagerSenders, DOMMessage};
+use script_traits::{ClientInfo, ServiceWorkerScript};
 use std::collections::HashMap;
 use std::sync::mpsc::{channel, Sender, Receiver, RecvError};
+use time::{self, Timespec};
 use url::Url;
 use util::prefs::PREFS;
 use util::thread::spawn_named;
@@ -27,13 +29,32 @@ enum Message {
     FromConstellation(ServiceWorkerMsg)
 }
 
+/// Registrations whose scripts haven't been checked for this long must check the
+/// network, bypassing any stored copies.
+/// https://w3c.github.io/ServiceWorker/#soft-update
+const UPDATE_CHECK_INTERVAL_SECS: i64 = 24 * 60 * 60;
+
+/// The scripts stored for a registration.
+#[derive(Default)]
+struct ScriptResourceMap {
+    scripts: HashMap<Url, ServiceWorkerScript>,
+    /// https://w3c.github.io/ServiceWorker/#dfn-last-update-check-time
+    last_update_check: Option<Timespec>,
+    /// Set when the registration was replaced, so the next run checks for updates.
+    force_bypass_cache: bool,
+}
+
 pub struct ServiceWorkerManager {
     // map of registered service worker descriptors
     registered_workers: HashMap<Url, ScopeThings>,
     // map of active service worker descriptors
     active_workers: HashMap<Url, Sender<ServiceWorkerScriptMsg>>,
+    // map of the scripts stored for each registration
+    script_resource_maps: HashMap<Url, ScriptResourceMap>,
     // own sender to send messages here
     own_sender: IpcSender<ServiceWorkerMsg>,
+    // sender to the constellation, for messages to clients
+    constellation_sender: IpcSender<SWManagerMsg>,
     // receiver to receive messages from constellation
     own_port: Receiver<ServiceWorkerMsg>,
     // to receive resource messages
@@ -42,12 +63,15 @@ pub struct ServiceWorkerManager {
 
 impl ServiceWorkerManager {
     fn new(own_sender: IpcSender<ServiceWorkerMsg>,
+           constellation_sender: IpcSender<SWManagerMsg>,
            from_constellation_receiver: Receiver<ServiceWorkerMsg>,
            resource_port: Receiver<CustomResponseMediator>) -> ServiceWorkerManager {
         ServiceWorkerManager {
             registered_workers: HashMap::new(),
             active_workers: HashMap::new(),
+            script_resource_maps: HashMap::new(),
             own_sender: own_sender,
+            constellation_sender: constellation_sender,
             own_port: from_constellation_receiver,
             resource_receiver: resource_port
         }
@@ -62,23 +86,34 @@ impl ServiceWorkerManager {
         let _ = sw_senders.swmanager_sender.send(SWManagerMsg::OwnSender(own_sender.clone()));
         spawn_named("ServiceWorkerManager".to_owned(), move || {
             ServiceWorkerManager::new(own_sender,
+                                      sw_senders.swmanager_sender,
                                       from_constellation,
                                       resource_port).handle_message();
         });
     }
 
     pub fn get_matching_scope(&self, load_url: &Url) -> Option<Url> {
-        for scope in self.registered_workers.keys() {
-            if longest_prefix_match(&scope, load_url) {
-                return Some(scope.clone());
-            }
-        }
-        None
+        longest_matching_scope(self.registered_workers.keys(), load_url).cloned()
+    }
+
+    fn scripts_for_run(&mut self, scope_url: &Url, scope_things: &ScopeThings) -> ServiceWorkerScripts {
+        let map = self.script_resource_maps.entry(scope_url.clone()).or_insert_with(Default::default);
+        let overdue = map.last_update_check.map_or(false, |last_check| {
+            (time::get_time() - last_check).num_seconds() > UPDATE_CHECK_INTERVAL_SECS
+        });
+        let bypass_cache = overdue || map.force_bypass_cache;
+        let check_for_updates = bypass_cache || map.scripts.is_empty();
+        map.force_bypass_cache = false;
+        ServiceWorkerScripts::new(map.scripts.clone(),
+                                  check_for_updates,
+                                  bypass_cache,
+                                  scope_things.update_via_cache)
     }
 
     pub fn wakeup_serviceworker(&mut self, scope_url: Url) -> Option<Sender<ServiceWorkerScriptMsg>> {
-        let scope_things = self.registered_workers.get(&scope_url);
+        let scope_things = self.registered_workers.get(&scope_url).cloned();
         if let Some(scope_things) = scope_things {
+            let scripts = self.scripts_for_run(&scope_url, &scope_things);
             let (sender, receiver) = channel();
             let (devtools_sender, devtools_receiver) = ipc::channel().unwrap();
             if let Some(ref chan) = scope_things.devtools_chan {
@@ -92,12 +127,13 @@ impl ServiceWorkerManager {
                                                                          devtools_sender,
                                                                          page_info));
             };
-            ServiceWorkerGlobalScope::run_serviceworker_scope(scope_things.clone(),
+            ServiceWorkerGlobalScope::run_serviceworker_scope(scope_things,
                                                               sender.clone(),
                                                               receiver,
                                                               devtools_receiver,
                                                               self.own_sender.clone(),
-                                                              scope_url.clone());
+                                                              scope_url.clone(),
+                                                              scripts);
             // We store the activated worker
             self.active_workers.insert(scope_url, sender.clone());
             return Some(sender);
@@ -123,19 +159,48 @@ impl ServiceWorkerManager {
         }
     }
 
-    fn forward_message(&self, msg: DOMMessage, sender: &Sender<ServiceWorkerScriptMsg>) {
+    fn forward_message(&self,
+                       msg: DOMMessage,
+                       client: Option<ClientInfo>,
+                       sender: &Sender<ServiceWorkerScriptMsg>) {
         let DOMMessage(data) = msg;
         let data = StructuredCloneData::Vector(data);
-        let _ = sender.send(ServiceWorkerScriptMsg::CommonWorker(WorkerScriptMsg::DOMMessage(data)));
+        let _ = sender.send(ServiceWorkerScriptMsg::Message(data, client));
     }
 
     fn handle_message_from_constellation(&mut self, msg: ServiceWorkerMsg) -> bool {
         match msg {
             ServiceWorkerMsg::RegisterServiceWorker(scope_things, scope) => {
                 if self.registered_workers.contains_key(&scope) {
-                    warn!("ScopeThings for {:?} already stored in SW-Manager", scope);
-                } else {
-                    self.registered_workers.insert(scope, scope_things);
+                    // Registering again replaces the registration's settings and
+                    // makes its next run check for updated scripts.
+                    // https://w3c.github.io/ServiceWorker/#register-algorithm
+                    if let Some(map) = self.script_resource_maps.get_mut(&scope) {
+                        map.force_bypass_cache = true;
+                    }
+                }
+                self.registered_workers.insert(scope, scope_things);
+                true
+            }
+            ServiceWorkerMsg::StoreScripts(scope, scripts) => {
+                let map = self.script_resource_maps.entry(scope).or_insert_with(Default::default);
+                map.scripts = scripts;
+                map.last_update_check = Some(time::get_time());
+                true
+            }
+            ServiceWorkerMsg::RejectRegistration(scope) => {
+                // The main script's response didn't allow the registration's scope.
+                // https://w3c.github.io/ServiceWorker/#update-algorithm
+                self.registered_workers.remove(&scope);
+                self.script_resource_maps.remove(&scope);
+                self.active_workers.remove(&scope);
+                true
+            }
+            ServiceWorkerMsg::ClaimClients(scope) => {
+                if let Some(scope_things) = self.registered_workers.get(&scope) {
+                    let scopes = self.registered_workers.keys().cloned().collect();
+                    let msg = SWManagerMsg::ClaimClients(scope_things.script_url.clone(), scope.clone(), scopes);
+                    let _ = self.constellation_sender.send(msg);
                 }
                 true
             }
@@ -147,18 +212,23 @@ impl ServiceWorkerManager {
                 }
                 true
             },
-            ServiceWorkerMsg::ForwardDOMMessage(msg, scope_url) => {
+            ServiceWorkerMsg::ForwardDOMMessage(msg, client, scope_url) => {
                 if self.active_workers.contains_key(&scope_url) {
                     if let Some(ref sender) = self.active_workers.get(&scope_url) {
-                        self.forward_message(msg, &sender);
+                        self.forward_message(msg, client, &sender);
                     }
                 } else {
                     if let Some(ref sender) = self.wakeup_serviceworker(scope_url) {
-                        self.forward_message(msg, &sender);
+                        self.forward_message(msg, client, &sender);
                     }
                 }
                 true
             }
+            ServiceWorkerMsg::PostMessageToClient(pipeline_id, msg, scope_url) => {
+                let msg = SWManagerMsg::PostMessageToClient(pipeline_id, msg, scope_url);
+                let _ = self.constellation_sender.send(msg);
+                true
+            }
             ServiceWorkerMsg::Exit => false
         }
     }
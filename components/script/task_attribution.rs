@@ -0,0 +1,183 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Attribution of the CPU time spent running tasks on the script thread to the source of each
+//! task and the origin it ran for, so that heavy pages and third-party frames can be found.
+//!
+//! The origin of a task is the origin of the pipeline it was sent to, when the message says so.
+//! Otherwise it is the origin of the first global that script was run in during the task.
+
+use profile_traits::mem::{Report, ReportKind};
+use script_runtime::ScriptThreadEventCategory;
+use std::collections::HashMap;
+use time::precise_time_ns;
+use url::Url;
+
+/// The origin that tasks are attributed to when no script ran and no pipeline was known.
+pub const UNATTRIBUTED: &'static str = "unattributed";
+
+/// The kinds of task sources that time is accounted to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TaskSourceKind {
+    Timer,
+    Event,
+    Network,
+    AnimationFrame,
+    Other,
+}
+
+impl TaskSourceKind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            TaskSourceKind::Timer => "timer",
+            TaskSourceKind::Event => "event",
+            TaskSourceKind::Network => "network",
+            TaskSourceKind::AnimationFrame => "animation-frame",
+            TaskSourceKind::Other => "other",
+        }
+    }
+
+    pub fn of(category: ScriptThreadEventCategory) -> TaskSourceKind {
+        match category {
+            ScriptThreadEventCategory::TimerEvent => TaskSourceKind::Timer,
+            ScriptThreadEventCategory::DocumentEvent |
+            ScriptThreadEventCategory::DomEvent |
+            ScriptThreadEventCategory::InputEvent |
+            ScriptThreadEventCategory::Resize |
+            ScriptThreadEventCategory::SetScrollState |
+            ScriptThreadEventCategory::SetViewport => TaskSourceKind::Event,
+            ScriptThreadEventCategory::FileRead |
+            ScriptThreadEventCategory::ImageCacheMsg |
+            ScriptThreadEventCategory::NetworkEvent |
+            ScriptThreadEventCategory::StylesheetLoad |
+            ScriptThreadEventCategory::WebSocketEvent => TaskSourceKind::Network,
+            ScriptThreadEventCategory::AttachLayout |
+            ScriptThreadEventCategory::ConstellationMsg |
+            ScriptThreadEventCategory::DevtoolsMsg |
+            ScriptThreadEventCategory::FormPlannedNavigation |
+            ScriptThreadEventCategory::ScriptEvent |
+            ScriptThreadEventCategory::UpdateReplacedElement |
+            ScriptThreadEventCategory::WorkerEvent |
+            ScriptThreadEventCategory::ServiceWorkerEvent => TaskSourceKind::Other,
+        }
+    }
+}
+
+/// Returns the serialization of the origin of `url`, which is what time is accounted to.
+pub fn origin_of(url: &Url) -> String {
+    url.origin().ascii_serialization()
+}
+
+/// The CPU time used by this thread so far, in nanoseconds. Falls back to the wall clock where
+/// per-thread CPU time is not available.
+#[cfg(target_os = "linux")]
+#[allow(unsafe_code)]
+pub fn thread_cpu_time_ns() -> u64 {
+    use libc::{CLOCK_THREAD_CPUTIME_ID, clock_gettime, timespec};
+    let mut ts = timespec { tv_sec: 0, tv_nsec: 0 };
+    if unsafe { clock_gettime(CLOCK_THREAD_CPUTIME_ID, &mut ts) } != 0 {
+        return precise_time_ns();
+    }
+    ts.tv_sec as u64 * 1000000000 + ts.tv_nsec as u64
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn thread_cpu_time_ns() -> u64 {
+    precise_time_ns()
+}
+
+/// The number of tasks run for one origin from one task source, and the CPU time they took.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TaskTimes {
+    pub tasks: u64,
+    pub cpu_time_ns: u64,
+}
+
+struct RunningTask {
+    source: TaskSourceKind,
+    origin: Option<String>,
+    start_cpu_time_ns: u64,
+}
+
+/// The accumulated CPU time of the tasks run on a script thread, per origin and task source.
+pub struct TaskAttribution {
+    per_origin: HashMap<String, HashMap<TaskSourceKind, TaskTimes>>,
+    running: Option<RunningTask>,
+}
+
+impl TaskAttribution {
+    pub fn new() -> TaskAttribution {
+        TaskAttribution {
+            per_origin: HashMap::new(),
+            running: None,
+        }
+    }
+
+    /// Starts timing a task from `source`, which runs for `origin` if it is already known.
+    /// A task that starts while another is running, e.g. a nested event loop, is accounted
+    /// as part of the outer one.
+    pub fn begin_task(&mut self, source: TaskSourceKind, origin: Option<String>) -> bool {
+        if self.running.is_some() {
+            return false;
+        }
+        self.running = Some(RunningTask {
+            source: source,
+            origin: origin,
+            start_cpu_time_ns: thread_cpu_time_ns(),
+        });
+        true
+    }
+
+    /// Attributes the running task to the origin `url()`, unless it already has one.
+    pub fn note_script_entered<F: FnOnce() -> Url>(&mut self, url: F) {
+        if let Some(ref mut task) = self.running {
+            if task.origin.is_none() {
+                task.origin = Some(origin_of(&url()));
+            }
+        }
+    }
+
+    /// Stops timing the running task and accounts its CPU time. Returns the origin the task
+    /// was attributed to.
+    pub fn end_task(&mut self) -> Option<String> {
+        self.running.take().map(|task| {
+            let origin = task.origin.unwrap_or_else(|| UNATTRIBUTED.to_owned());
+            let elapsed = thread_cpu_time_ns().saturating_sub(task.start_cpu_time_ns);
+            self.record(&origin, task.source, elapsed);
+            origin
+        })
+    }
+
+    /// Accounts one task from `source` that took `cpu_time_ns` to `origin`.
+    pub fn record(&mut self, origin: &str, source: TaskSourceKind, cpu_time_ns: u64) {
+        let times = self.per_origin.entry(origin.to_owned()).or_insert_with(HashMap::new)
+                                   .entry(source).or_insert_with(TaskTimes::default);
+        times.tasks += 1;
+        times.cpu_time_ns += cpu_time_ns;
+    }
+
+    /// The time accounted to `origin` for tasks from `source`.
+    pub fn times(&self, origin: &str, source: TaskSourceKind) -> TaskTimes {
+        self.per_origin.get(origin)
+            .and_then(|sources| sources.get(&source))
+            .cloned()
+            .unwrap_or(TaskTimes::default())
+    }
+
+    /// Reports of the CPU time accounted to each origin and task source, for the memory
+    /// profiler's report tree.
+    pub fn reports(&self) -> Vec<Report> {
+        let mut reports = vec![];
+        for (origin, sources) in &self.per_origin {
+            for (source, times) in sources {
+                reports.push(Report {
+                    path: path!["script-cpu-time", format!("origin({})", origin), source.name()],
+                    kind: ReportKind::CpuTime,
+                    size: (times.cpu_time_ns / 1000) as usize,
+                });
+            }
+        }
+        reports
+    }
+}
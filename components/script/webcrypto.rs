@@ -0,0 +1,1086 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The cryptographic operations behind `SubtleCrypto`, all done by OpenSSL.
+//!
+//! The openssl crate provides the hash functions, HMAC and random bytes. The
+//! AES ciphers and the RSA and EC keys go through the EVP interfaces of
+//! libcrypto that its bindings don't expose yet, which are declared in `ffi`.
+
+#![allow(unsafe_code)]
+
+use dom::bindings::error::{Error, Fallible};
+use libc::{c_int, c_long, c_uchar, c_void, size_t};
+use openssl::crypto::hash::{self, Type as HashType};
+use openssl::crypto::hmac::hmac;
+use openssl::crypto::memcmp;
+use openssl::crypto::rand::rand_bytes;
+use std::cmp;
+use std::ptr;
+
+/// A hash function that can be used with `digest()` or as the `hash` member
+/// of an algorithm.
+/// https://w3c.github.io/webcrypto/#sha
+#[derive(Clone, Copy, Debug, HeapSizeOf, JSTraceable, PartialEq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    pub fn from_name(name: &str) -> Option<HashAlgorithm> {
+        match name {
+            "SHA-1" => Some(HashAlgorithm::Sha1),
+            "SHA-256" => Some(HashAlgorithm::Sha256),
+            "SHA-384" => Some(HashAlgorithm::Sha384),
+            "SHA-512" => Some(HashAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            HashAlgorithm::Sha1 => "SHA-1",
+            HashAlgorithm::Sha256 => "SHA-256",
+            HashAlgorithm::Sha384 => "SHA-384",
+            HashAlgorithm::Sha512 => "SHA-512",
+        }
+    }
+
+    /// The length of the hash function's input block, in bits. This is the
+    /// default length of generated HMAC keys.
+    pub fn block_len(&self) -> u32 {
+        match *self {
+            HashAlgorithm::Sha1 | HashAlgorithm::Sha256 => 512,
+            HashAlgorithm::Sha384 | HashAlgorithm::Sha512 => 1024,
+        }
+    }
+
+    fn hash_type(&self) -> HashType {
+        match *self {
+            HashAlgorithm::Sha1 => HashType::SHA1,
+            HashAlgorithm::Sha256 => HashType::SHA256,
+            HashAlgorithm::Sha384 => HashType::SHA384,
+            HashAlgorithm::Sha512 => HashType::SHA512,
+        }
+    }
+
+    fn md(&self) -> *const ffi::EVP_MD {
+        unsafe {
+            match *self {
+                HashAlgorithm::Sha1 => ffi::EVP_sha1(),
+                HashAlgorithm::Sha256 => ffi::EVP_sha256(),
+                HashAlgorithm::Sha384 => ffi::EVP_sha384(),
+                HashAlgorithm::Sha512 => ffi::EVP_sha512(),
+            }
+        }
+    }
+}
+
+/// An elliptic curve that ECDSA and ECDH keys can be on.
+/// https://w3c.github.io/webcrypto/#dfn-NamedCurve
+#[derive(Clone, Copy, Debug, HeapSizeOf, JSTraceable, PartialEq)]
+pub enum NamedCurve {
+    P256,
+    P384,
+}
+
+impl NamedCurve {
+    pub fn from_name(name: &str) -> Option<NamedCurve> {
+        match name {
+            "P-256" => Some(NamedCurve::P256),
+            "P-384" => Some(NamedCurve::P384),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            NamedCurve::P256 => "P-256",
+            NamedCurve::P384 => "P-384",
+        }
+    }
+
+    fn from_nid(nid: c_int) -> Option<NamedCurve> {
+        match nid {
+            ffi::NID_X9_62_PRIME256V1 => Some(NamedCurve::P256),
+            ffi::NID_SECP384R1 => Some(NamedCurve::P384),
+            _ => None,
+        }
+    }
+
+    fn nid(&self) -> c_int {
+        match *self {
+            NamedCurve::P256 => ffi::NID_X9_62_PRIME256V1,
+            NamedCurve::P384 => ffi::NID_SECP384R1,
+        }
+    }
+
+    /// The length of the coordinates of a point, and of the two integers of
+    /// a signature, in bytes.
+    fn coordinate_len(&self) -> usize {
+        match *self {
+            NamedCurve::P256 => 32,
+            NamedCurve::P384 => 48,
+        }
+    }
+}
+
+pub fn digest(hash: HashAlgorithm, data: &[u8]) -> Vec<u8> {
+    hash::hash(hash.hash_type(), data)
+}
+
+pub fn hmac_sign(hash: HashAlgorithm, key: &[u8], data: &[u8]) -> Vec<u8> {
+    hmac(hash.hash_type(), key, data)
+}
+
+pub fn hmac_verify(hash: HashAlgorithm, key: &[u8], data: &[u8], signature: &[u8]) -> bool {
+    let mac = hmac_sign(hash, key, data);
+    mac.len() == signature.len() && memcmp::eq(&mac, signature)
+}
+
+pub fn random_bytes(len: usize) -> Vec<u8> {
+    rand_bytes(len)
+}
+
+/// Turns the result of a libcrypto function that returns 1 on success into a
+/// `Fallible`, clearing the errors it queued if it failed.
+fn check(result: c_int) -> Fallible<()> {
+    if result == 1 {
+        return Ok(());
+    }
+    unsafe { ffi::ERR_clear_error() };
+    Err(Error::Operation)
+}
+
+/// Like `check`, for a libcrypto function that returns null on failure.
+fn check_ptr<T>(pointer: *mut T) -> Fallible<*mut T> {
+    if pointer.is_null() {
+        unsafe { ffi::ERR_clear_error() };
+        return Err(Error::Operation);
+    }
+    Ok(pointer)
+}
+
+fn c_len(data: &[u8]) -> Fallible<c_int> {
+    if data.len() > c_int::max_value() as usize {
+        return Err(Error::Operation);
+    }
+    Ok(data.len() as c_int)
+}
+
+type Cipher = unsafe extern "C" fn() -> *const ffi::EVP_CIPHER;
+
+/// The AES cipher for a key of the length of `key`.
+fn aes_cipher(key: &[u8], aes_128: Cipher, aes_192: Cipher, aes_256: Cipher) -> Fallible<*const ffi::EVP_CIPHER> {
+    let cipher = match key.len() {
+        16 => aes_128,
+        24 => aes_192,
+        32 => aes_256,
+        _ => return Err(Error::Operation),
+    };
+    Ok(unsafe { cipher() })
+}
+
+/// An EVP cipher context, freed when dropped.
+struct CipherContext(*mut ffi::EVP_CIPHER_CTX);
+
+impl CipherContext {
+    /// Starts encrypting or decrypting with `cipher`. The key and IV are set
+    /// by `init`, after any parameter that must be set before them.
+    fn new(cipher: *const ffi::EVP_CIPHER, encrypt: bool) -> Fallible<CipherContext> {
+        let context = CipherContext(try!(check_ptr(unsafe { ffi::EVP_CIPHER_CTX_new() })));
+        try!(check(unsafe {
+            ffi::EVP_CipherInit_ex(context.0, cipher, ptr::null_mut(), ptr::null(), ptr::null(), encrypt as c_int)
+        }));
+        Ok(context)
+    }
+
+    fn init(&self, key: &[u8], iv: &[u8]) -> Fallible<()> {
+        check(unsafe { ffi::EVP_CipherInit_ex(self.0, ptr::null(), ptr::null_mut(), key.as_ptr(), iv.as_ptr(), -1) })
+    }
+
+    fn ctrl(&self, kind: c_int, arg: c_int, value: *mut c_void) -> Fallible<()> {
+        check(unsafe { ffi::EVP_CIPHER_CTX_ctrl(self.0, kind, arg, value) })
+    }
+
+    /// Adds data that an AEAD cipher authenticates without encrypting it.
+    fn update_aad(&self, data: &[u8]) -> Fallible<()> {
+        let mut len = 0;
+        check(unsafe { ffi::EVP_CipherUpdate(self.0, ptr::null_mut(), &mut len, data.as_ptr(), try!(c_len(data))) })
+    }
+
+    fn update(&self, input: &[u8], output: &mut Vec<u8>) -> Fallible<()> {
+        let start = output.len();
+        output.resize(start + input.len() + 16, 0);
+        let mut len = 0;
+        try!(check(unsafe {
+            ffi::EVP_CipherUpdate(self.0, output[start..].as_mut_ptr(), &mut len, input.as_ptr(), try!(c_len(input)))
+        }));
+        output.truncate(start + len as usize);
+        Ok(())
+    }
+
+    /// Writes the last block, checking the padding or the authentication tag
+    /// when decrypting.
+    fn finish(&self, output: &mut Vec<u8>) -> Fallible<()> {
+        let start = output.len();
+        output.resize(start + 16, 0);
+        let mut len = 0;
+        try!(check(unsafe { ffi::EVP_CipherFinal_ex(self.0, output[start..].as_mut_ptr(), &mut len) }));
+        output.truncate(start + len as usize);
+        Ok(())
+    }
+}
+
+impl Drop for CipherContext {
+    fn drop(&mut self) {
+        unsafe { ffi::EVP_CIPHER_CTX_free(self.0) }
+    }
+}
+
+/// Encrypts or decrypts `input` in one go with `cipher`, which takes an IV of
+/// 16 bytes.
+fn cipher(cipher: *const ffi::EVP_CIPHER, encrypt: bool, key: &[u8], iv: &[u8], input: &[u8])
+          -> Fallible<Vec<u8>> {
+    if iv.len() != 16 {
+        return Err(Error::Operation);
+    }
+    let context = try!(CipherContext::new(cipher, encrypt));
+    try!(context.init(key, iv));
+    let mut output = vec![];
+    try!(context.update(input, &mut output));
+    try!(context.finish(&mut output));
+    Ok(output)
+}
+
+/// https://w3c.github.io/webcrypto/#aes-cbc-operations
+pub fn aes_cbc_encrypt(key: &[u8], iv: &[u8], plaintext: &[u8]) -> Fallible<Vec<u8>> {
+    let aes_cbc = try!(aes_cipher(key, ffi::EVP_aes_128_cbc, ffi::EVP_aes_192_cbc, ffi::EVP_aes_256_cbc));
+    cipher(aes_cbc, true, key, iv, plaintext)
+}
+
+/// https://w3c.github.io/webcrypto/#aes-cbc-operations
+pub fn aes_cbc_decrypt(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Fallible<Vec<u8>> {
+    let aes_cbc = try!(aes_cipher(key, ffi::EVP_aes_128_cbc, ffi::EVP_aes_192_cbc, ffi::EVP_aes_256_cbc));
+    cipher(aes_cbc, false, key, iv, ciphertext)
+}
+
+/// Encrypts or decrypts `data` in counter mode, incrementing the rightmost
+/// `length` bits of `counter` for each block.
+/// https://w3c.github.io/webcrypto/#aes-ctr-operations
+pub fn aes_ctr(key: &[u8], counter: &[u8], length: u32, data: &[u8]) -> Fallible<Vec<u8>> {
+    if counter.len() != 16 || length == 0 || length > 128 {
+        return Err(Error::Operation);
+    }
+    let blocks = (data.len() as u64 + 15) / 16;
+    if length < 64 && blocks > 1 << length {
+        return Err(Error::Operation);
+    }
+    let aes_ctr = try!(aes_cipher(key, ffi::EVP_aes_128_ctr, ffi::EVP_aes_192_ctr, ffi::EVP_aes_256_ctr));
+    // OpenSSL increments the whole counter block, so the blocks after the
+    // counter bits wrap around are encrypted from a counter of zero.
+    let before_wrap = cmp::min(data.len() as u64, blocks_until_wrap(counter, length).saturating_mul(16));
+    let (before, after) = data.split_at(before_wrap as usize);
+    let mut output = try!(cipher(aes_ctr, true, key, counter, before));
+    if !after.is_empty() {
+        output.extend(try!(cipher(aes_ctr, true, key, &wrapped_counter(counter, length), after)));
+    }
+    Ok(output)
+}
+
+/// The number of blocks that can be encrypted before the rightmost `length`
+/// bits of `counter` wrap around, saturating at `u64::MAX`.
+fn blocks_until_wrap(counter: &[u8], length: u32) -> u64 {
+    let (high, low) = (read_u64(&counter[..8]), read_u64(&counter[8..]));
+    if length < 64 {
+        return (1 << length) - (low & ((1 << length) - 1));
+    }
+    let high_mask = if length == 128 { !0 } else { (1 << (length - 64)) - 1 };
+    if high & high_mask != high_mask || low == 0 {
+        return u64::max_value();
+    }
+    (!low).wrapping_add(1)
+}
+
+/// `counter` with its rightmost `length` bits set to zero.
+fn wrapped_counter(counter: &[u8], length: u32) -> Vec<u8> {
+    counter.iter().enumerate().map(|(index, byte)| {
+        let lowest_bit = 120 - 8 * index as u32;
+        if length <= lowest_bit {
+            *byte
+        } else if length >= lowest_bit + 8 {
+            0
+        } else {
+            byte & (0xff << (length - lowest_bit))
+        }
+    }).collect()
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |value, byte| (value << 8) | *byte as u64)
+}
+
+/// Starts an AES-GCM operation, checking the parameters.
+fn aes_gcm(key: &[u8], iv: &[u8], additional_data: &[u8], tag_length: u8, encrypt: bool)
+           -> Fallible<CipherContext> {
+    match tag_length {
+        32 | 64 | 96 | 104 | 112 | 120 | 128 => {},
+        _ => return Err(Error::Operation),
+    }
+    if iv.is_empty() {
+        return Err(Error::Operation);
+    }
+    let aes_gcm = try!(aes_cipher(key, ffi::EVP_aes_128_gcm, ffi::EVP_aes_192_gcm, ffi::EVP_aes_256_gcm));
+    let context = try!(CipherContext::new(aes_gcm, encrypt));
+    try!(context.ctrl(ffi::EVP_CTRL_GCM_SET_IVLEN, try!(c_len(iv)), ptr::null_mut()));
+    try!(context.init(key, iv));
+    try!(context.update_aad(additional_data));
+    Ok(context)
+}
+
+/// Returns the ciphertext followed by the authentication tag.
+/// https://w3c.github.io/webcrypto/#aes-gcm-operations
+pub fn aes_gcm_encrypt(key: &[u8], iv: &[u8], additional_data: &[u8], tag_length: u8, plaintext: &[u8])
+                       -> Fallible<Vec<u8>> {
+    let context = try!(aes_gcm(key, iv, additional_data, tag_length, true));
+    let mut ciphertext = vec![];
+    try!(context.update(plaintext, &mut ciphertext));
+    try!(context.finish(&mut ciphertext));
+    let mut tag = vec![0; tag_length as usize / 8];
+    try!(context.ctrl(ffi::EVP_CTRL_GCM_GET_TAG, tag.len() as c_int, tag.as_mut_ptr() as *mut c_void));
+    ciphertext.extend(tag);
+    Ok(ciphertext)
+}
+
+/// https://w3c.github.io/webcrypto/#aes-gcm-operations
+pub fn aes_gcm_decrypt(key: &[u8], iv: &[u8], additional_data: &[u8], tag_length: u8, data: &[u8])
+                       -> Fallible<Vec<u8>> {
+    let tag_len = tag_length as usize / 8;
+    if data.len() < tag_len {
+        return Err(Error::Operation);
+    }
+    let (ciphertext, tag) = data.split_at(data.len() - tag_len);
+    let context = try!(aes_gcm(key, iv, additional_data, tag_length, false));
+    let mut plaintext = vec![];
+    try!(context.update(ciphertext, &mut plaintext));
+    try!(context.ctrl(ffi::EVP_CTRL_GCM_SET_TAG, tag_len as c_int, tag.as_ptr() as *mut c_void));
+    try!(context.finish(&mut plaintext));
+    Ok(plaintext)
+}
+
+/// An RSA or EC key held by OpenSSL, freed when dropped.
+struct PKey(*mut ffi::EVP_PKEY);
+
+impl PKey {
+    fn new() -> Fallible<PKey> {
+        Ok(PKey(try!(check_ptr(unsafe { ffi::EVP_PKEY_new() }))))
+    }
+
+    /// Reads a DER-encoded SubjectPublicKeyInfo (RFC 5280).
+    fn from_spki(input: &[u8]) -> Option<PKey> {
+        let mut data = input.as_ptr();
+        let key = unsafe { ffi::d2i_PUBKEY(ptr::null_mut(), &mut data, input.len() as c_long) };
+        match check_ptr(key) {
+            Ok(key) => PKey::whole_input(PKey(key), input, data),
+            Err(_) => None,
+        }
+    }
+
+    /// Reads a DER-encoded PKCS #8 PrivateKeyInfo (RFC 5208).
+    fn from_pkcs8(input: &[u8]) -> Option<PKey> {
+        let mut data = input.as_ptr();
+        unsafe {
+            let info = match check_ptr(ffi::d2i_PKCS8_PRIV_KEY_INFO(ptr::null_mut(), &mut data,
+                                                                    input.len() as c_long)) {
+                Ok(info) => info,
+                Err(_) => return None,
+            };
+            let key = check_ptr(ffi::EVP_PKCS82PKEY(info));
+            ffi::PKCS8_PRIV_KEY_INFO_free(info);
+            match key {
+                Ok(key) => PKey::whole_input(PKey(key), input, data),
+                Err(_) => None,
+            }
+        }
+    }
+
+    /// Rejects a key read from the start of `input` if it was followed by
+    /// anything, `end` being where reading it stopped.
+    fn whole_input(key: PKey, input: &[u8], end: *const c_uchar) -> Option<PKey> {
+        if end as usize - input.as_ptr() as usize != input.len() {
+            return None;
+        }
+        Some(key)
+    }
+
+    /// The public half of this key, as a key of its own.
+    fn public_key(&self) -> Fallible<PKey> {
+        let len = unsafe { ffi::i2d_PUBKEY(self.0, ptr::null_mut()) };
+        try!(check((len > 0) as c_int));
+        let mut spki = vec![0; len as usize];
+        try!(check((unsafe { ffi::i2d_PUBKEY(self.0, &mut spki.as_mut_ptr()) } == len) as c_int));
+        PKey::from_spki(&spki).ok_or(Error::Operation)
+    }
+
+    /// Makes this key hold `key` of the given type, taking ownership of it.
+    fn assign(&self, kind: c_int, key: *mut c_void) -> Fallible<()> {
+        check(unsafe { ffi::EVP_PKEY_assign(self.0, kind, key) })
+    }
+
+    fn context(&self) -> Fallible<PKeyContext> {
+        Ok(PKeyContext(try!(check_ptr(unsafe { ffi::EVP_PKEY_CTX_new(self.0, ptr::null_mut()) }))))
+    }
+
+    /// Signs the digest of `message`, after `configure` has set the padding
+    /// of the operation.
+    fn sign<F>(&self, hash: HashAlgorithm, message: &[u8], configure: F) -> Fallible<Vec<u8>>
+        where F: FnOnce(&PKeyContext) -> Fallible<()>
+    {
+        let context = try!(self.context());
+        try!(check(unsafe { ffi::EVP_PKEY_sign_init(context.0) }));
+        try!(configure(&context));
+        try!(context.ctrl(ffi::EVP_PKEY_CTRL_MD, 0, hash.md() as *mut c_void));
+        let digest = digest(hash, message);
+        output_of(|signature, len| unsafe {
+            ffi::EVP_PKEY_sign(context.0, signature, len, digest.as_ptr(), digest.len())
+        })
+    }
+
+    fn verify<F>(&self, hash: HashAlgorithm, message: &[u8], signature: &[u8], configure: F) -> bool
+        where F: FnOnce(&PKeyContext) -> Fallible<()>
+    {
+        let context = match self.context() {
+            Ok(context) => context,
+            Err(_) => return false,
+        };
+        let configured = check(unsafe { ffi::EVP_PKEY_verify_init(context.0) })
+            .and_then(|_| configure(&context))
+            .and_then(|_| context.ctrl(ffi::EVP_PKEY_CTRL_MD, 0, hash.md() as *mut c_void));
+        if configured.is_err() {
+            return false;
+        }
+        let digest = digest(hash, message);
+        let verified = unsafe {
+            ffi::EVP_PKEY_verify(context.0, signature.as_ptr(), signature.len(), digest.as_ptr(), digest.len())
+        };
+        check(verified).is_ok()
+    }
+}
+
+impl Drop for PKey {
+    fn drop(&mut self) {
+        unsafe { ffi::EVP_PKEY_free(self.0) }
+    }
+}
+
+/// The context of an operation with a `PKey`, freed when dropped.
+struct PKeyContext(*mut ffi::EVP_PKEY_CTX);
+
+impl PKeyContext {
+    fn ctrl(&self, kind: c_int, arg: c_int, value: *mut c_void) -> Fallible<()> {
+        // The key and operation types are left for OpenSSL to check, since
+        // the values of the latter differ between its versions.
+        check(unsafe { ffi::EVP_PKEY_CTX_ctrl(self.0, -1, -1, kind, arg, value) })
+    }
+
+    fn set_rsa_padding(&self, padding: c_int) -> Fallible<()> {
+        self.ctrl(ffi::EVP_PKEY_CTRL_RSA_PADDING, padding, ptr::null_mut())
+    }
+
+    fn set_rsa_oaep(&self, hash: HashAlgorithm, label: &[u8]) -> Fallible<()> {
+        try!(self.set_rsa_padding(ffi::RSA_PKCS1_OAEP_PADDING));
+        try!(self.ctrl(ffi::EVP_PKEY_CTRL_RSA_OAEP_MD, 0, hash.md() as *mut c_void));
+        if label.is_empty() {
+            return Ok(());
+        }
+        // The context takes ownership of the label, so OpenSSL must allocate it.
+        unsafe {
+            let copy = try!(check_ptr(ffi::CRYPTO_malloc(label.len(), ptr::null(), 0)));
+            ptr::copy_nonoverlapping(label.as_ptr(), copy as *mut u8, label.len());
+            let result = self.ctrl(ffi::EVP_PKEY_CTRL_RSA_OAEP_LABEL, try!(c_len(label)), copy);
+            if result.is_err() {
+                ffi::CRYPTO_free(copy, ptr::null(), 0);
+            }
+            result
+        }
+    }
+}
+
+impl Drop for PKeyContext {
+    fn drop(&mut self) {
+        unsafe { ffi::EVP_PKEY_CTX_free(self.0) }
+    }
+}
+
+/// Calls a libcrypto function that writes its output to a buffer, first
+/// without one to learn how large it needs to be.
+fn output_of<F>(function: F) -> Fallible<Vec<u8>>
+    where F: Fn(*mut c_uchar, *mut size_t) -> c_int
+{
+    let mut len = 0;
+    try!(check(function(ptr::null_mut(), &mut len)));
+    let mut output = vec![0; len];
+    try!(check(function(output.as_mut_ptr(), &mut len)));
+    output.truncate(len);
+    Ok(output)
+}
+
+/// The modulus length, in bits, and the public exponent of an RSA key, or
+/// None if `key` is another kind of key.
+fn rsa_parameters(key: &PKey) -> Option<(u32, Vec<u8>)> {
+    unsafe {
+        let rsa = match check_ptr(ffi::EVP_PKEY_get1_RSA(key.0)) {
+            Ok(rsa) => rsa,
+            Err(_) => return None,
+        };
+        let len = ffi::i2d_RSAPublicKey(rsa, ptr::null_mut());
+        let mut der = vec![0; cmp::max(len, 0) as usize];
+        let written = len > 0 && ffi::i2d_RSAPublicKey(rsa, &mut der.as_mut_ptr()) == len;
+        ffi::RSA_free(rsa);
+        if check(written as c_int).is_err() {
+            return None;
+        }
+        // RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+        let public_key = match der_read(&der, DER_SEQUENCE) {
+            Some((public_key, _)) => public_key,
+            None => return None,
+        };
+        let (modulus, rest) = match der_read_unsigned(public_key) {
+            Some((ref modulus, _)) if modulus.is_empty() => return None,
+            Some(read) => read,
+            None => return None,
+        };
+        der_read_unsigned(rest).map(|(public_exponent, _)| {
+            let modulus_length = modulus.len() as u32 * 8 - modulus[0].leading_zeros();
+            (modulus_length, public_exponent)
+        })
+    }
+}
+
+/// An RSA public key.
+#[derive(HeapSizeOf)]
+pub struct RsaPublicKey {
+    #[ignore_heap_size_of = "Owned by OpenSSL"]
+    key: PKey,
+    modulus_length: u32,
+    public_exponent: Vec<u8>,
+}
+
+impl RsaPublicKey {
+    fn from_pkey(key: PKey) -> Option<RsaPublicKey> {
+        rsa_parameters(&key).map(|(modulus_length, public_exponent)| {
+            RsaPublicKey {
+                key: key,
+                modulus_length: modulus_length,
+                public_exponent: public_exponent,
+            }
+        })
+    }
+
+    /// Reads a DER-encoded SubjectPublicKeyInfo holding an RSA key.
+    pub fn from_spki(input: &[u8]) -> Option<RsaPublicKey> {
+        PKey::from_spki(input).and_then(RsaPublicKey::from_pkey)
+    }
+
+    /// The length of the modulus, in bits.
+    pub fn modulus_length(&self) -> u32 {
+        self.modulus_length
+    }
+
+    /// The public exponent, as an unsigned big-endian integer.
+    pub fn public_exponent(&self) -> &[u8] {
+        &self.public_exponent
+    }
+}
+
+/// An RSA private key.
+#[derive(HeapSizeOf)]
+pub struct RsaPrivateKey {
+    #[ignore_heap_size_of = "Owned by OpenSSL"]
+    key: PKey,
+    modulus_length: u32,
+    public_exponent: Vec<u8>,
+}
+
+impl RsaPrivateKey {
+    fn from_pkey(key: PKey) -> Option<RsaPrivateKey> {
+        rsa_parameters(&key).map(|(modulus_length, public_exponent)| {
+            RsaPrivateKey {
+                key: key,
+                modulus_length: modulus_length,
+                public_exponent: public_exponent,
+            }
+        })
+    }
+
+    /// Generates a key with a modulus of `modulus_length` bits.
+    /// https://w3c.github.io/webcrypto/#rsa-oaep-operations
+    pub fn generate(modulus_length: u32, public_exponent: &[u8]) -> Fallible<RsaPrivateKey> {
+        if modulus_length < 256 || modulus_length > 16384 || modulus_length % 8 != 0 {
+            return Err(Error::Operation);
+        }
+        let start = public_exponent.iter().position(|byte| *byte != 0).unwrap_or(public_exponent.len());
+        let exponent = &public_exponent[start..];
+        // The exponent must be odd and greater than one.
+        if exponent.is_empty() || exponent.len() > 8 || exponent[exponent.len() - 1] & 1 == 0 || exponent == [1] {
+            return Err(Error::Operation);
+        }
+        let key = try!(PKey::new());
+        unsafe {
+            let e = try!(check_ptr(ffi::BN_bin2bn(exponent.as_ptr(), exponent.len() as c_int, ptr::null_mut())));
+            let rsa = ffi::RSA_new();
+            let generated = check_ptr(rsa).and_then(|rsa| {
+                check(ffi::RSA_generate_key_ex(rsa, modulus_length as c_int, e, ptr::null_mut()))
+            });
+            ffi::BN_free(e);
+            if let Err(error) = generated.and_then(|_| key.assign(ffi::EVP_PKEY_RSA, rsa as *mut c_void)) {
+                ffi::RSA_free(rsa);
+                return Err(error);
+            }
+        }
+        RsaPrivateKey::from_pkey(key).ok_or(Error::Operation)
+    }
+
+    /// Reads a DER-encoded PKCS #8 PrivateKeyInfo holding an RSA key.
+    pub fn from_pkcs8(input: &[u8]) -> Option<RsaPrivateKey> {
+        PKey::from_pkcs8(input).and_then(RsaPrivateKey::from_pkey)
+    }
+
+    pub fn public_key(&self) -> Fallible<RsaPublicKey> {
+        let key = try!(self.key.public_key());
+        Ok(RsaPublicKey {
+            key: key,
+            modulus_length: self.modulus_length,
+            public_exponent: self.public_exponent.clone(),
+        })
+    }
+
+    /// The length of the modulus, in bits.
+    pub fn modulus_length(&self) -> u32 {
+        self.modulus_length
+    }
+
+    /// The public exponent, as an unsigned big-endian integer.
+    pub fn public_exponent(&self) -> &[u8] {
+        &self.public_exponent
+    }
+}
+
+/// RSAES-OAEP-ENCRYPT, with the same hash function for MGF1.
+/// https://tools.ietf.org/html/rfc8017#section-7.1.1
+pub fn rsa_oaep_encrypt(key: &RsaPublicKey, hash: HashAlgorithm, label: &[u8], message: &[u8])
+                        -> Fallible<Vec<u8>> {
+    let context = try!(key.key.context());
+    try!(check(unsafe { ffi::EVP_PKEY_encrypt_init(context.0) }));
+    try!(context.set_rsa_oaep(hash, label));
+    output_of(|ciphertext, len| unsafe {
+        ffi::EVP_PKEY_encrypt(context.0, ciphertext, len, message.as_ptr(), message.len())
+    })
+}
+
+/// RSAES-OAEP-DECRYPT.
+/// https://tools.ietf.org/html/rfc8017#section-7.1.2
+pub fn rsa_oaep_decrypt(key: &RsaPrivateKey, hash: HashAlgorithm, label: &[u8], ciphertext: &[u8])
+                        -> Fallible<Vec<u8>> {
+    let context = try!(key.key.context());
+    try!(check(unsafe { ffi::EVP_PKEY_decrypt_init(context.0) }));
+    try!(context.set_rsa_oaep(hash, label));
+    output_of(|plaintext, len| unsafe {
+        ffi::EVP_PKEY_decrypt(context.0, plaintext, len, ciphertext.as_ptr(), ciphertext.len())
+    })
+}
+
+/// Sets up an RSASSA-PSS operation, with the same hash function for MGF1.
+fn rsa_pss(context: &PKeyContext, salt_length: usize) -> Fallible<()> {
+    if salt_length > c_int::max_value() as usize {
+        return Err(Error::Operation);
+    }
+    try!(context.set_rsa_padding(ffi::RSA_PKCS1_PSS_PADDING));
+    context.ctrl(ffi::EVP_PKEY_CTRL_RSA_PSS_SALTLEN, salt_length as c_int, ptr::null_mut())
+}
+
+/// RSASSA-PSS-SIGN.
+/// https://tools.ietf.org/html/rfc8017#section-8.1.1
+pub fn rsa_pss_sign(key: &RsaPrivateKey, hash: HashAlgorithm, salt_length: usize, message: &[u8])
+                    -> Fallible<Vec<u8>> {
+    key.key.sign(hash, message, |context| rsa_pss(context, salt_length))
+}
+
+/// RSASSA-PSS-VERIFY.
+/// https://tools.ietf.org/html/rfc8017#section-8.1.2
+pub fn rsa_pss_verify(key: &RsaPublicKey, hash: HashAlgorithm, salt_length: usize, message: &[u8],
+                      signature: &[u8])
+                      -> bool {
+    key.key.verify(hash, message, signature, |context| rsa_pss(context, salt_length))
+}
+
+/// RSASSA-PKCS1-V1_5-SIGN.
+/// https://tools.ietf.org/html/rfc8017#section-8.2.1
+pub fn rsa_pkcs1_v1_5_sign(key: &RsaPrivateKey, hash: HashAlgorithm, message: &[u8]) -> Fallible<Vec<u8>> {
+    key.key.sign(hash, message, |context| context.set_rsa_padding(ffi::RSA_PKCS1_PADDING))
+}
+
+/// RSASSA-PKCS1-V1_5-VERIFY.
+/// https://tools.ietf.org/html/rfc8017#section-8.2.2
+pub fn rsa_pkcs1_v1_5_verify(key: &RsaPublicKey, hash: HashAlgorithm, message: &[u8], signature: &[u8]) -> bool {
+    key.key.verify(hash, message, signature, |context| context.set_rsa_padding(ffi::RSA_PKCS1_PADDING))
+}
+
+/// A new EC key on `curve`, without its points yet.
+fn ec_key(curve: NamedCurve) -> Fallible<*mut ffi::EC_KEY> {
+    let key = try!(check_ptr(unsafe { ffi::EC_KEY_new_by_curve_name(curve.nid()) }));
+    // Public keys are encoded with the name of their curve, rather than with
+    // its parameters.
+    unsafe { ffi::EC_KEY_set_asn1_flag(key, ffi::OPENSSL_EC_NAMED_CURVE) };
+    Ok(key)
+}
+
+/// Makes a `PKey` of an EC key, taking ownership of it.
+fn ec_pkey(key: *mut ffi::EC_KEY) -> Fallible<PKey> {
+    let pkey = PKey::new().and_then(|pkey| pkey.assign(ffi::EVP_PKEY_EC, key as *mut c_void).map(|_| pkey));
+    if pkey.is_err() {
+        unsafe { ffi::EC_KEY_free(key) };
+    }
+    pkey
+}
+
+/// The curve of an EC key, or None if `key` is another kind of key or is on
+/// another curve.
+fn ec_curve(key: &PKey) -> Option<NamedCurve> {
+    unsafe {
+        let ec_key = match check_ptr(ffi::EVP_PKEY_get1_EC_KEY(key.0)) {
+            Ok(ec_key) => ec_key,
+            Err(_) => return None,
+        };
+        let nid = ffi::EC_GROUP_get_curve_name(ffi::EC_KEY_get0_group(ec_key));
+        ffi::EC_KEY_free(ec_key);
+        NamedCurve::from_nid(nid)
+    }
+}
+
+/// An ECDSA or ECDH public key.
+#[derive(HeapSizeOf)]
+pub struct EcPublicKey {
+    #[ignore_heap_size_of = "Owned by OpenSSL"]
+    key: PKey,
+    curve: NamedCurve,
+}
+
+impl EcPublicKey {
+    /// Reads a DER-encoded SubjectPublicKeyInfo holding an EC key on `curve`.
+    pub fn from_spki(input: &[u8], curve: NamedCurve) -> Option<EcPublicKey> {
+        PKey::from_spki(input).and_then(|key| {
+            if ec_curve(&key) != Some(curve) {
+                return None;
+            }
+            Some(EcPublicKey {
+                key: key,
+                curve: curve,
+            })
+        })
+    }
+
+    /// Reads an uncompressed point on `curve` (SEC 1, section 2.3.3).
+    pub fn from_raw(input: &[u8], curve: NamedCurve) -> Option<EcPublicKey> {
+        if input.len() != 1 + 2 * curve.coordinate_len() || input[0] != 4 {
+            return None;
+        }
+        let mut key = match ec_key(curve) {
+            Ok(key) => key,
+            Err(_) => return None,
+        };
+        let mut data = input.as_ptr();
+        if check_ptr(unsafe { ffi::o2i_ECPublicKey(&mut key, &mut data, input.len() as c_long) }).is_err() {
+            unsafe { ffi::EC_KEY_free(key) };
+            return None;
+        }
+        ec_pkey(key).ok().map(|key| {
+            EcPublicKey {
+                key: key,
+                curve: curve,
+            }
+        })
+    }
+
+    pub fn curve(&self) -> NamedCurve {
+        self.curve
+    }
+}
+
+/// An ECDSA or ECDH private key.
+#[derive(HeapSizeOf)]
+pub struct EcPrivateKey {
+    #[ignore_heap_size_of = "Owned by OpenSSL"]
+    key: PKey,
+    curve: NamedCurve,
+}
+
+impl EcPrivateKey {
+    pub fn generate(curve: NamedCurve) -> Fallible<EcPrivateKey> {
+        let key = try!(ec_key(curve));
+        if let Err(error) = check(unsafe { ffi::EC_KEY_generate_key(key) }) {
+            unsafe { ffi::EC_KEY_free(key) };
+            return Err(error);
+        }
+        Ok(EcPrivateKey {
+            key: try!(ec_pkey(key)),
+            curve: curve,
+        })
+    }
+
+    /// Reads a DER-encoded PKCS #8 PrivateKeyInfo holding an EC key on
+    /// `curve`.
+    pub fn from_pkcs8(input: &[u8], curve: NamedCurve) -> Option<EcPrivateKey> {
+        PKey::from_pkcs8(input).and_then(|key| {
+            if ec_curve(&key) != Some(curve) {
+                return None;
+            }
+            Some(EcPrivateKey {
+                key: key,
+                curve: curve,
+            })
+        })
+    }
+
+    pub fn public_key(&self) -> Fallible<EcPublicKey> {
+        Ok(EcPublicKey {
+            key: try!(self.key.public_key()),
+            curve: self.curve,
+        })
+    }
+
+    pub fn curve(&self) -> NamedCurve {
+        self.curve
+    }
+}
+
+/// Signs `message`, returning the two integers of the signature as
+/// big-endian numbers of the length of the curve's coordinates.
+/// https://w3c.github.io/webcrypto/#ecdsa-operations
+pub fn ecdsa_sign(key: &EcPrivateKey, hash: HashAlgorithm, message: &[u8]) -> Fallible<Vec<u8>> {
+    let signature = try!(key.key.sign(hash, message, |_| Ok(())));
+    // ECDSA-Sig-Value ::= SEQUENCE { r INTEGER, s INTEGER }
+    let len = key.curve.coordinate_len();
+    let integers = try!(der_read(&signature, DER_SEQUENCE).ok_or(Error::Operation)).0;
+    let (r, rest) = try!(der_read_unsigned(integers).ok_or(Error::Operation));
+    let (s, _) = try!(der_read_unsigned(rest).ok_or(Error::Operation));
+    if r.len() > len || s.len() > len {
+        return Err(Error::Operation);
+    }
+    let mut raw = vec![0; 2 * len];
+    raw[len - r.len()..len].copy_from_slice(&r);
+    raw[2 * len - s.len()..].copy_from_slice(&s);
+    Ok(raw)
+}
+
+/// https://w3c.github.io/webcrypto/#ecdsa-operations
+pub fn ecdsa_verify(key: &EcPublicKey, hash: HashAlgorithm, message: &[u8], signature: &[u8]) -> bool {
+    let len = key.curve.coordinate_len();
+    if signature.len() != 2 * len {
+        return false;
+    }
+    let mut integers = der_unsigned(&signature[..len]);
+    integers.extend(der_unsigned(&signature[len..]));
+    key.key.verify(hash, message, &der(DER_SEQUENCE, &integers), |_| Ok(()))
+}
+
+/// Derives the first `length` bits of the secret shared by the owners of
+/// `key` and `public_key`, which must be on the same curve.
+/// https://w3c.github.io/webcrypto/#ecdh-operations
+pub fn ecdh_derive_bits(key: &EcPrivateKey, public_key: &EcPublicKey, length: u32) -> Fallible<Vec<u8>> {
+    let context = try!(key.key.context());
+    try!(check(unsafe { ffi::EVP_PKEY_derive_init(context.0) }));
+    try!(check(unsafe { ffi::EVP_PKEY_derive_set_peer(context.0, public_key.key.0) }));
+    let mut secret = try!(output_of(|secret, len| unsafe { ffi::EVP_PKEY_derive(context.0, secret, len) }));
+    if length as usize > secret.len() * 8 {
+        return Err(Error::Operation);
+    }
+    secret.truncate((length as usize + 7) / 8);
+    if length % 8 != 0 {
+        let last = secret.len() - 1;
+        secret[last] &= 0xff << (8 - length % 8);
+    }
+    Ok(secret)
+}
+
+const DER_INTEGER: u8 = 0x02;
+const DER_SEQUENCE: u8 = 0x30;
+
+/// Reads a DER element with the given tag from the start of `input`,
+/// returning its contents and the rest of the input.
+fn der_read(input: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    if input.len() < 2 || input[0] != tag {
+        return None;
+    }
+    let (length, header) = match input[1] {
+        length if length < 0x80 => (length as usize, 2),
+        0x81 if input.len() >= 3 => (input[2] as usize, 3),
+        0x82 if input.len() >= 4 => (((input[2] as usize) << 8) | input[3] as usize, 4),
+        _ => return None,
+    };
+    if input.len() < header + length {
+        return None;
+    }
+    Some((&input[header..header + length], &input[header + length..]))
+}
+
+/// Reads a DER integer that is known to be positive, without its leading zeros.
+fn der_read_unsigned(input: &[u8]) -> Option<(Vec<u8>, &[u8])> {
+    der_read(input, DER_INTEGER).map(|(integer, rest)| {
+        let start = integer.iter().position(|byte| *byte != 0).unwrap_or(integer.len());
+        (integer[start..].to_vec(), rest)
+    })
+}
+
+/// Encodes a DER element of fewer than 256 bytes.
+fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut element = vec![tag];
+    if contents.len() >= 0x80 {
+        element.push(0x81);
+    }
+    element.push(contents.len() as u8);
+    element.extend_from_slice(contents);
+    element
+}
+
+/// Encodes an unsigned big-endian number as a DER integer.
+fn der_unsigned(number: &[u8]) -> Vec<u8> {
+    let start = number.iter().position(|byte| *byte != 0).unwrap_or(number.len());
+    let mut integer = vec![];
+    if number.get(start).map_or(true, |byte| *byte >= 0x80) {
+        integer.push(0);
+    }
+    integer.extend_from_slice(&number[start..]);
+    der(DER_INTEGER, &integer)
+}
+
+/// The parts of libcrypto that the openssl crate doesn't bind. OpenSSL
+/// provides some of them as macros around the `ctrl` functions, whose
+/// commands are defined here instead.
+#[allow(non_camel_case_types)]
+mod ffi {
+    use libc::{c_char, c_int, c_long, c_uchar, c_void, size_t};
+
+    pub enum BIGNUM {}
+    pub enum EC_GROUP {}
+    pub enum EC_KEY {}
+    pub enum ENGINE {}
+    pub enum EVP_CIPHER {}
+    pub enum EVP_CIPHER_CTX {}
+    pub enum EVP_MD {}
+    pub enum EVP_PKEY {}
+    pub enum EVP_PKEY_CTX {}
+    pub enum PKCS8_PRIV_KEY_INFO {}
+    pub enum RSA {}
+
+    pub const EVP_PKEY_RSA: c_int = 6;
+    pub const EVP_PKEY_EC: c_int = 408;
+
+    pub const NID_X9_62_PRIME256V1: c_int = 415;
+    pub const NID_SECP384R1: c_int = 715;
+    pub const OPENSSL_EC_NAMED_CURVE: c_int = 1;
+
+    pub const EVP_CTRL_GCM_SET_IVLEN: c_int = 0x9;
+    pub const EVP_CTRL_GCM_GET_TAG: c_int = 0x10;
+    pub const EVP_CTRL_GCM_SET_TAG: c_int = 0x11;
+
+    pub const EVP_PKEY_CTRL_MD: c_int = 1;
+    const EVP_PKEY_ALG_CTRL: c_int = 0x1000;
+    pub const EVP_PKEY_CTRL_RSA_PADDING: c_int = EVP_PKEY_ALG_CTRL + 1;
+    pub const EVP_PKEY_CTRL_RSA_PSS_SALTLEN: c_int = EVP_PKEY_ALG_CTRL + 2;
+    pub const EVP_PKEY_CTRL_RSA_OAEP_MD: c_int = EVP_PKEY_ALG_CTRL + 9;
+    pub const EVP_PKEY_CTRL_RSA_OAEP_LABEL: c_int = EVP_PKEY_ALG_CTRL + 10;
+
+    pub const RSA_PKCS1_PADDING: c_int = 1;
+    pub const RSA_PKCS1_OAEP_PADDING: c_int = 4;
+    pub const RSA_PKCS1_PSS_PADDING: c_int = 6;
+
+    extern "C" {
+        pub fn ERR_clear_error();
+        pub fn CRYPTO_malloc(num: size_t, file: *const c_char, line: c_int) -> *mut c_void;
+        pub fn CRYPTO_free(address: *mut c_void, file: *const c_char, line: c_int);
+
+        pub fn EVP_sha1() -> *const EVP_MD;
+        pub fn EVP_sha256() -> *const EVP_MD;
+        pub fn EVP_sha384() -> *const EVP_MD;
+        pub fn EVP_sha512() -> *const EVP_MD;
+
+        pub fn EVP_aes_128_cbc() -> *const EVP_CIPHER;
+        pub fn EVP_aes_192_cbc() -> *const EVP_CIPHER;
+        pub fn EVP_aes_256_cbc() -> *const EVP_CIPHER;
+        pub fn EVP_aes_128_ctr() -> *const EVP_CIPHER;
+        pub fn EVP_aes_192_ctr() -> *const EVP_CIPHER;
+        pub fn EVP_aes_256_ctr() -> *const EVP_CIPHER;
+        pub fn EVP_aes_128_gcm() -> *const EVP_CIPHER;
+        pub fn EVP_aes_192_gcm() -> *const EVP_CIPHER;
+        pub fn EVP_aes_256_gcm() -> *const EVP_CIPHER;
+
+        pub fn EVP_CIPHER_CTX_new() -> *mut EVP_CIPHER_CTX;
+        pub fn EVP_CIPHER_CTX_free(ctx: *mut EVP_CIPHER_CTX);
+        pub fn EVP_CIPHER_CTX_ctrl(ctx: *mut EVP_CIPHER_CTX, kind: c_int, arg: c_int, ptr: *mut c_void) -> c_int;
+        pub fn EVP_CipherInit_ex(ctx: *mut EVP_CIPHER_CTX, cipher: *const EVP_CIPHER, engine: *mut ENGINE,
+                                 key: *const c_uchar, iv: *const c_uchar, enc: c_int)
+                                 -> c_int;
+        pub fn EVP_CipherUpdate(ctx: *mut EVP_CIPHER_CTX, out: *mut c_uchar, outl: *mut c_int, input: *const c_uchar,
+                                inl: c_int)
+                                -> c_int;
+        pub fn EVP_CipherFinal_ex(ctx: *mut EVP_CIPHER_CTX, out: *mut c_uchar, outl: *mut c_int) -> c_int;
+
+        pub fn EVP_PKEY_new() -> *mut EVP_PKEY;
+        pub fn EVP_PKEY_free(key: *mut EVP_PKEY);
+        pub fn EVP_PKEY_assign(key: *mut EVP_PKEY, kind: c_int, inner: *mut c_void) -> c_int;
+        pub fn EVP_PKEY_get1_RSA(key: *mut EVP_PKEY) -> *mut RSA;
+        pub fn EVP_PKEY_get1_EC_KEY(key: *mut EVP_PKEY) -> *mut EC_KEY;
+        pub fn d2i_PUBKEY(key: *mut *mut EVP_PKEY, input: *mut *const c_uchar, len: c_long) -> *mut EVP_PKEY;
+        pub fn i2d_PUBKEY(key: *mut EVP_PKEY, output: *mut *mut c_uchar) -> c_int;
+        pub fn d2i_PKCS8_PRIV_KEY_INFO(info: *mut *mut PKCS8_PRIV_KEY_INFO, input: *mut *const c_uchar,
+                                       len: c_long)
+                                       -> *mut PKCS8_PRIV_KEY_INFO;
+        pub fn PKCS8_PRIV_KEY_INFO_free(info: *mut PKCS8_PRIV_KEY_INFO);
+        pub fn EVP_PKCS82PKEY(info: *const PKCS8_PRIV_KEY_INFO) -> *mut EVP_PKEY;
+
+        pub fn EVP_PKEY_CTX_new(key: *mut EVP_PKEY, engine: *mut ENGINE) -> *mut EVP_PKEY_CTX;
+        pub fn EVP_PKEY_CTX_free(ctx: *mut EVP_PKEY_CTX);
+        pub fn EVP_PKEY_CTX_ctrl(ctx: *mut EVP_PKEY_CTX, keytype: c_int, optype: c_int, cmd: c_int, p1: c_int,
+                                 p2: *mut c_void)
+                                 -> c_int;
+        pub fn EVP_PKEY_sign_init(ctx: *mut EVP_PKEY_CTX) -> c_int;
+        pub fn EVP_PKEY_sign(ctx: *mut EVP_PKEY_CTX, sig: *mut c_uchar, siglen: *mut size_t, tbs: *const c_uchar,
+                             tbslen: size_t)
+                             -> c_int;
+        pub fn EVP_PKEY_verify_init(ctx: *mut EVP_PKEY_CTX) -> c_int;
+        pub fn EVP_PKEY_verify(ctx: *mut EVP_PKEY_CTX, sig: *const c_uchar, siglen: size_t, tbs: *const c_uchar,
+                               tbslen: size_t)
+                               -> c_int;
+        pub fn EVP_PKEY_encrypt_init(ctx: *mut EVP_PKEY_CTX) -> c_int;
+        pub fn EVP_PKEY_encrypt(ctx: *mut EVP_PKEY_CTX, out: *mut c_uchar, outlen: *mut size_t,
+                                input: *const c_uchar, inlen: size_t)
+                                -> c_int;
+        pub fn EVP_PKEY_decrypt_init(ctx: *mut EVP_PKEY_CTX) -> c_int;
+        pub fn EVP_PKEY_decrypt(ctx: *mut EVP_PKEY_CTX, out: *mut c_uchar, outlen: *mut size_t,
+                                input: *const c_uchar, inlen: size_t)
+                                -> c_int;
+        pub fn EVP_PKEY_derive_init(ctx: *mut EVP_PKEY_CTX) -> c_int;
+        pub fn EVP_PKEY_derive_set_peer(ctx: *mut EVP_PKEY_CTX, peer: *mut EVP_PKEY) -> c_int;
+        pub fn EVP_PKEY_derive(ctx: *mut EVP_PKEY_CTX, key: *mut c_uchar, keylen: *mut size_t) -> c_int;
+
+        pub fn BN_bin2bn(s: *const c_uchar, len: c_int, ret: *mut BIGNUM) -> *mut BIGNUM;
+        pub fn BN_free(bn: *mut BIGNUM);
+        pub fn RSA_new() -> *mut RSA;
+        pub fn RSA_free(rsa: *mut RSA);
+        pub fn RSA_generate_key_ex(rsa: *mut RSA, bits: c_int, e: *mut BIGNUM, cb: *mut c_void) -> c_int;
+        pub fn i2d_RSAPublicKey(rsa: *const RSA, output: *mut *mut c_uchar) -> c_int;
+
+        pub fn EC_KEY_new_by_curve_name(nid: c_int) -> *mut EC_KEY;
+        pub fn EC_KEY_free(key: *mut EC_KEY);
+        pub fn EC_KEY_generate_key(key: *mut EC_KEY) -> c_int;
+        pub fn EC_KEY_set_asn1_flag(key: *mut EC_KEY, flag: c_int);
+        pub fn EC_KEY_get0_group(key: *const EC_KEY) -> *const EC_GROUP;
+        pub fn EC_GROUP_get_curve_name(group: *const EC_GROUP) -> c_int;
+        pub fn o2i_ECPublicKey(key: *mut *mut EC_KEY, input: *mut *const c_uchar, len: c_long) -> *mut EC_KEY;
+    }
+}
@@ -207,7 +207,7 @@ pub fn handle_get_cookies(context: &BrowsingContext,
     let url = document.url();
     let (sender, receiver) = ipc::channel().unwrap();
     let _ = document.window().resource_threads().send(
-        GetCookiesDataForUrl(url.clone(), sender, NonHTTP)
+        GetCookiesDataForUrl(url, sender, NonHTTP)
         );
     let cookies = receiver.recv().unwrap();
     reply.send(cookies).unwrap();
@@ -222,7 +222,7 @@ pub fn handle_get_cookie(context: &BrowsingContext,
     let url = document.url();
     let (sender, receiver) = ipc::channel().unwrap();
     let _ = document.window().resource_threads().send(
-        GetCookiesDataForUrl(url.clone(), sender, NonHTTP)
+        GetCookiesDataForUrl(url, sender, NonHTTP)
         );
     let cookies = receiver.recv().unwrap();
     reply.send(cookies.into_iter().filter(|c| c.name == &*name).collect()).unwrap();
@@ -361,7 +361,7 @@ pub fn handle_get_url(context: &BrowsingContext,
                       reply: IpcSender<Url>) {
     let document = context.active_document();
     let url = document.url();
-    reply.send((*url).clone()).unwrap();
+    reply.send(url).unwrap();
 }
 
 pub fn handle_is_enabled(context: &BrowsingContext,
@@ -0,0 +1,691 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Evaluation of parsed XPath 1.0 expressions against the DOM.
+//! https://www.w3.org/TR/xpath/#data-model
+
+use dom::attr::Attr;
+use dom::bindings::codegen::Bindings::NodeBinding::NodeConstants;
+use dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use dom::bindings::error::{Error, Fallible};
+use dom::bindings::inheritance::{Castable, CharacterDataTypeId, NodeTypeId};
+use dom::bindings::js::Root;
+use dom::characterdata::CharacterData;
+use dom::element::Element;
+use dom::node::Node;
+use dom::processinginstruction::ProcessingInstruction;
+use dom::text::Text;
+use std::ascii::AsciiExt;
+use std::cmp::Ordering;
+use std::f64;
+use string_cache::{Atom, Namespace};
+use xpath::parser::{Axis, BinaryOp, Expr, LocationPath, NameTest, NodeTest, Step};
+
+/// A node of the XPath data model. Attributes are nodes in XPath, but not in
+/// this DOM, so they are kept apart.
+#[derive(Clone)]
+pub enum XPathNode {
+    Node(Root<Node>),
+    Attribute(Root<Attr>),
+}
+
+impl XPathNode {
+    fn is_same(&self, other: &XPathNode) -> bool {
+        match (self, other) {
+            (&XPathNode::Node(ref a), &XPathNode::Node(ref b)) => a == b,
+            (&XPathNode::Attribute(ref a), &XPathNode::Attribute(ref b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// The node this node sorts with in document order, and its position among
+    /// the attributes of that node, counting from 1, if it is an attribute.
+    fn document_order_key(&self) -> Option<(Root<Node>, usize)> {
+        match *self {
+            XPathNode::Node(ref node) => Some((node.clone(), 0)),
+            XPathNode::Attribute(ref attr) => attr.owner().map(|owner| {
+                let index = owner.attrs().iter().position(|a| Root::from_ref(&**a) == *attr).unwrap_or(0);
+                (Root::upcast(owner), index + 1)
+            }),
+        }
+    }
+
+    /// https://www.w3.org/TR/xpath/#dt-string-value
+    pub fn string_value(&self) -> String {
+        let node = match *self {
+            XPathNode::Attribute(ref attr) => return String::from(&**attr.value()),
+            XPathNode::Node(ref node) => node,
+        };
+        match node.type_id() {
+            NodeTypeId::CharacterData(_) => {
+                let data = node.downcast::<CharacterData>().unwrap().data();
+                String::from(&**data)
+            },
+            NodeTypeId::DocumentType => String::new(),
+            _ => {
+                let mut value = String::new();
+                for text in node.traverse_preorder().filter_map(Root::downcast::<Text>) {
+                    value.push_str(&text.upcast::<CharacterData>().data());
+                }
+                value
+            },
+        }
+    }
+
+    /// The local name, namespace URI and qualified name of this node.
+    /// https://www.w3.org/TR/xpath/#dt-expanded-name
+    fn expanded_name(&self) -> (String, String, String) {
+        match *self {
+            XPathNode::Attribute(ref attr) => {
+                (String::from(&**attr.local_name()), String::from(&*attr.namespace().0), String::from(&**attr.name()))
+            },
+            XPathNode::Node(ref node) => {
+                if let Some(element) = node.downcast::<Element>() {
+                    let local = String::from(&**element.local_name());
+                    let qualified = match *element.prefix() {
+                        Some(ref prefix) => format!("{}:{}", prefix, local),
+                        None => local.clone(),
+                    };
+                    (local, String::from(&*element.namespace().0), qualified)
+                } else if let Some(pi) = node.downcast::<ProcessingInstruction>() {
+                    let target = String::from(&**pi.target());
+                    (target.clone(), String::new(), target)
+                } else {
+                    (String::new(), String::new(), String::new())
+                }
+            },
+        }
+    }
+
+    /// The root of the tree this node is in.
+    fn root(&self) -> XPathNode {
+        let node = match *self {
+            XPathNode::Node(ref node) => node.clone(),
+            XPathNode::Attribute(ref attr) => match attr.owner() {
+                Some(owner) => Root::upcast(owner),
+                None => return self.clone(),
+            },
+        };
+        XPathNode::Node(node.inclusive_ancestors().last().unwrap())
+    }
+
+    /// The element this node is, or the element it is an attribute of.
+    fn element(&self) -> Option<Root<Element>> {
+        match *self {
+            XPathNode::Node(ref node) => Root::downcast(node.clone()),
+            XPathNode::Attribute(ref attr) => attr.owner(),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/xpath/#section-Introduction
+pub enum Value {
+    NodeSet(Vec<XPathNode>),
+    Boolean(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    // https://www.w3.org/TR/xpath/#function-boolean
+    pub fn boolean(&self) -> bool {
+        match *self {
+            Value::NodeSet(ref nodes) => !nodes.is_empty(),
+            Value::Boolean(boolean) => boolean,
+            Value::Number(number) => number != 0. && !number.is_nan(),
+            Value::String(ref string) => !string.is_empty(),
+        }
+    }
+
+    // https://www.w3.org/TR/xpath/#function-number
+    pub fn number(&self) -> f64 {
+        match *self {
+            Value::Boolean(true) => 1.,
+            Value::Boolean(false) => 0.,
+            Value::Number(number) => number,
+            _ => string_to_number(&self.string()),
+        }
+    }
+
+    // https://www.w3.org/TR/xpath/#function-string
+    pub fn string(&self) -> String {
+        match *self {
+            Value::NodeSet(ref nodes) => nodes.first().map_or(String::new(), XPathNode::string_value),
+            Value::Boolean(boolean) => boolean.to_string(),
+            Value::Number(number) => number_to_string(number),
+            Value::String(ref string) => string.clone(),
+        }
+    }
+}
+
+fn is_xml_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\r' || c == '\n'
+}
+
+/// Convert a string to a number, which is NaN unless the string is a plain
+/// decimal number surrounded by whitespace.
+/// https://www.w3.org/TR/xpath/#function-number
+pub fn string_to_number(string: &str) -> f64 {
+    let string = string.trim_matches(is_xml_whitespace);
+    let unsigned = if string.starts_with('-') { &string[1..] } else { string };
+    let digits = unsigned.chars().filter(|c| c.is_digit(10)).count();
+    let dots = unsigned.chars().filter(|&c| c == '.').count();
+    if digits == 0 || dots > 1 || digits + dots != unsigned.chars().count() {
+        return f64::NAN;
+    }
+    string.parse().unwrap_or(f64::NAN)
+}
+
+/// https://www.w3.org/TR/xpath/#function-string
+pub fn number_to_string(number: f64) -> String {
+    if number.is_nan() {
+        "NaN".to_owned()
+    } else if number.is_infinite() {
+        if number > 0. { "Infinity" } else { "-Infinity" }.to_owned()
+    } else if number == 0. {
+        // Negative zero is written without its sign too.
+        "0".to_owned()
+    } else {
+        // Floats are never written with an exponent, nor integers with a fraction.
+        number.to_string()
+    }
+}
+
+/// https://www.w3.org/TR/xpath/#function-round
+pub fn round(number: f64) -> f64 {
+    if number.is_nan() || number.is_infinite() || number == 0. {
+        number
+    } else if number < 0. && number >= -0.5 {
+        -0.
+    } else {
+        (number + 0.5).floor()
+    }
+}
+
+fn compare_document_order(a: &XPathNode, b: &XPathNode) -> Ordering {
+    let (a, b) = match (a.document_order_key(), b.document_order_key()) {
+        (Some(a), Some(b)) => (a, b),
+        (Some(_), None) => return Ordering::Less,
+        (None, Some(_)) => return Ordering::Greater,
+        (None, None) => return Ordering::Equal,
+    };
+    if a.0 == b.0 {
+        return a.1.cmp(&b.1);
+    }
+    if a.0.CompareDocumentPosition(&b.0) & NodeConstants::DOCUMENT_POSITION_FOLLOWING != 0 {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+/// Sort `nodes` into document order and remove duplicates.
+fn sort_and_deduplicate(nodes: Vec<XPathNode>) -> Vec<XPathNode> {
+    let mut nodes = nodes;
+    nodes.sort_by(compare_document_order);
+    let mut unique: Vec<XPathNode> = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        if unique.last().map_or(true, |last| !last.is_same(&node)) {
+            unique.push(node);
+        }
+    }
+    unique
+}
+
+fn node_list<I: Iterator<Item=Root<Node>>>(nodes: I) -> Vec<XPathNode> {
+    nodes.map(XPathNode::Node).collect()
+}
+
+/// The nodes following `node` in document order, excluding its descendants.
+fn following(node: &Node) -> Vec<XPathNode> {
+    let mut nodes = vec![];
+    for ancestor in node.inclusive_ancestors() {
+        for sibling in ancestor.following_siblings() {
+            nodes.extend(node_list(sibling.traverse_preorder()));
+        }
+    }
+    nodes
+}
+
+/// The nodes preceding `node` in reverse document order, excluding its ancestors.
+fn preceding(node: &Node) -> Vec<XPathNode> {
+    let mut nodes = vec![];
+    for ancestor in node.inclusive_ancestors() {
+        for sibling in ancestor.preceding_siblings() {
+            let mut subtree = node_list(sibling.traverse_preorder());
+            subtree.reverse();
+            nodes.extend(subtree);
+        }
+    }
+    nodes
+}
+
+/// The nodes along `axis` from `node`, nearest first.
+/// https://www.w3.org/TR/xpath/#axes
+fn axis_nodes(axis: Axis, node: &XPathNode) -> Vec<XPathNode> {
+    let element = match *node {
+        XPathNode::Node(ref element) => element,
+        XPathNode::Attribute(ref attr) => {
+            let owner = attr.owner().map(Root::upcast::<Node>);
+            return match axis {
+                Axis::Self_ => vec![node.clone()],
+                Axis::Parent => node_list(owner.into_iter()),
+                Axis::Ancestor => owner.map_or(vec![], |owner| node_list(owner.inclusive_ancestors())),
+                Axis::AncestorOrSelf => {
+                    let mut nodes = vec![node.clone()];
+                    nodes.extend(owner.map_or(vec![], |owner| node_list(owner.inclusive_ancestors())));
+                    nodes
+                },
+                // An attribute comes before the children of its element.
+                Axis::Following => owner.map_or(vec![], |owner| {
+                    let mut nodes = node_list(owner.traverse_preorder().skip(1));
+                    nodes.extend(following(&owner));
+                    nodes
+                }),
+                Axis::Preceding => owner.map_or(vec![], |owner| preceding(&owner)),
+                _ => vec![],
+            };
+        },
+    };
+    match axis {
+        Axis::Ancestor => node_list(element.ancestors()),
+        Axis::AncestorOrSelf => node_list(element.inclusive_ancestors()),
+        Axis::Attribute => match element.downcast::<Element>() {
+            Some(element) => {
+                element.attrs()
+                       .iter()
+                       .filter(|attr| *attr.namespace() != ns!(xmlns))
+                       .map(|attr| XPathNode::Attribute(Root::from_ref(&**attr)))
+                       .collect()
+            },
+            None => vec![],
+        },
+        Axis::Child => node_list(element.children()),
+        Axis::Descendant => node_list(element.traverse_preorder().skip(1)),
+        Axis::DescendantOrSelf => node_list(element.traverse_preorder()),
+        Axis::Following => following(element),
+        Axis::FollowingSibling => node_list(element.following_siblings()),
+        // Namespace nodes are not supported.
+        Axis::Namespace => vec![],
+        Axis::Parent => node_list(element.GetParentNode().into_iter()),
+        Axis::Preceding => preceding(element),
+        Axis::PrecedingSibling => node_list(element.preceding_siblings()),
+        Axis::Self_ => vec![node.clone()],
+    }
+}
+
+/// Whether a name test matches a name. In HTML documents an unprefixed name
+/// test matches HTML elements regardless of case.
+/// https://html.spec.whatwg.org/multipage/#interactions-with-xpath-and-xslt
+fn name_test_matches(test: &NameTest, local_name: &Atom, namespace: &Namespace, html: bool) -> bool {
+    if test.prefix.is_some() {
+        let namespace_matches = test.namespace.as_ref().map_or(false, |ns| &**ns == &*namespace.0);
+        return namespace_matches && test.local.as_ref().map_or(true, |local| &**local == &**local_name);
+    }
+    match test.local {
+        None => true,
+        Some(ref local) if html => local.to_ascii_lowercase() == &**local_name,
+        Some(ref local) => *namespace == ns!() && &**local == &**local_name,
+    }
+}
+
+// https://www.w3.org/TR/xpath/#node-tests
+fn node_test_matches(test: &NodeTest, axis: Axis, node: &XPathNode, html_document: bool) -> bool {
+    let type_id = match *node {
+        XPathNode::Node(ref node) => Some(node.type_id()),
+        XPathNode::Attribute(_) => None,
+    };
+    match *test {
+        NodeTest::Node => true,
+        NodeTest::Text => type_id == Some(NodeTypeId::CharacterData(CharacterDataTypeId::Text)),
+        NodeTest::Comment => type_id == Some(NodeTypeId::CharacterData(CharacterDataTypeId::Comment)),
+        NodeTest::ProcessingInstruction(ref target) => match *node {
+            XPathNode::Node(ref node) => node.downcast::<ProcessingInstruction>().map_or(false, |pi| {
+                target.as_ref().map_or(true, |target| &**target == &**pi.target())
+            }),
+            XPathNode::Attribute(_) => false,
+        },
+        // The principal node type of the attribute axis is the attribute, and
+        // that of every other axis the element.
+        NodeTest::Name(ref test) => match *node {
+            XPathNode::Attribute(ref attr) => {
+                let html = html_document && attr.owner().map_or(false, |owner| *owner.namespace() == ns!(html));
+                axis == Axis::Attribute &&
+                name_test_matches(test, attr.local_name(), attr.namespace(), html && *attr.namespace() == ns!())
+            },
+            XPathNode::Node(ref node) => {
+                axis != Axis::Attribute && axis != Axis::Namespace &&
+                node.downcast::<Element>().map_or(false, |element| {
+                    let html = html_document && *element.namespace() == ns!(html);
+                    name_test_matches(test, element.local_name(), element.namespace(), html)
+                })
+            },
+        },
+    }
+}
+
+struct Context {
+    node: XPathNode,
+    position: usize,
+    size: usize,
+    /// Whether the context node belongs to an HTML document, which changes how
+    /// name tests match.
+    html_document: bool,
+}
+
+impl Context {
+    fn with_node(&self, node: XPathNode, position: usize, size: usize) -> Context {
+        Context {
+            node: node,
+            position: position,
+            size: size,
+            html_document: self.html_document,
+        }
+    }
+}
+
+/// Evaluate `expr` with `node` as the context node.
+pub fn evaluate(expr: &Expr, node: &Node) -> Fallible<Value> {
+    let context = Context {
+        node: XPathNode::Node(Root::from_ref(node)),
+        position: 1,
+        size: 1,
+        html_document: node.owner_doc().is_html_document(),
+    };
+    eval(expr, &context)
+}
+
+fn eval_node_set(expr: &Expr, context: &Context) -> Fallible<Vec<XPathNode>> {
+    match try!(eval(expr, context)) {
+        Value::NodeSet(nodes) => Ok(nodes),
+        _ => Err(Error::Type("The expression does not evaluate to a node-set".to_owned())),
+    }
+}
+
+fn eval(expr: &Expr, context: &Context) -> Fallible<Value> {
+    Ok(match *expr {
+        Expr::Binary(ref lhs, op, ref rhs) => try!(eval_binary(lhs, op, rhs, context)),
+        Expr::Negate(ref expr) => Value::Number(-try!(eval(expr, context)).number()),
+        Expr::Literal(ref literal) => Value::String(literal.clone()),
+        Expr::Number(number) => Value::Number(number),
+        Expr::Function(ref name, ref arguments) => try!(call_function(name, arguments, context)),
+        Expr::Filter(ref primary, ref predicates) => {
+            let nodes = try!(eval_node_set(primary, context));
+            Value::NodeSet(try!(apply_predicates(predicates, nodes, context)))
+        },
+        Expr::Path(ref filter, ref path) => {
+            let nodes = match *filter {
+                Some(ref filter) => try!(eval_node_set(filter, context)),
+                None if path.absolute => vec![context.node.root()],
+                None => vec![context.node.clone()],
+            };
+            Value::NodeSet(try!(eval_location_path(path, nodes, context)))
+        },
+    })
+}
+
+fn eval_location_path(path: &LocationPath, nodes: Vec<XPathNode>, context: &Context) -> Fallible<Vec<XPathNode>> {
+    let mut nodes = nodes;
+    for step in &path.steps {
+        nodes = try!(eval_step(step, &nodes, context));
+    }
+    Ok(nodes)
+}
+
+// https://www.w3.org/TR/xpath/#section-Location-Steps
+fn eval_step(step: &Step, inputs: &[XPathNode], context: &Context) -> Fallible<Vec<XPathNode>> {
+    let mut result = vec![];
+    for input in inputs {
+        let candidates = axis_nodes(step.axis, input).into_iter().filter(|node| {
+            node_test_matches(&step.test, step.axis, node, context.html_document)
+        }).collect();
+        result.extend(try!(apply_predicates(&step.predicates, candidates, context)));
+    }
+    if inputs.len() == 1 {
+        // The nodes along a single axis are already distinct and only need to
+        // be put in document order.
+        if step.axis.is_reverse() {
+            result.reverse();
+        }
+        Ok(result)
+    } else {
+        Ok(sort_and_deduplicate(result))
+    }
+}
+
+// https://www.w3.org/TR/xpath/#predicates
+fn apply_predicates(predicates: &[Expr], nodes: Vec<XPathNode>, context: &Context) -> Fallible<Vec<XPathNode>> {
+    let mut nodes = nodes;
+    for predicate in predicates {
+        let size = nodes.len();
+        let mut kept = vec![];
+        for (index, node) in nodes.into_iter().enumerate() {
+            let position = index + 1;
+            let keep = match try!(eval(predicate, &context.with_node(node.clone(), position, size))) {
+                Value::Number(number) => number == position as f64,
+                value => value.boolean(),
+            };
+            if keep {
+                kept.push(node);
+            }
+        }
+        nodes = kept;
+    }
+    Ok(nodes)
+}
+
+fn eval_binary(lhs: &Expr, op: BinaryOp, rhs: &Expr, context: &Context) -> Fallible<Value> {
+    let lhs = try!(eval(lhs, context));
+    let rhs = match op {
+        BinaryOp::Or if lhs.boolean() => return Ok(Value::Boolean(true)),
+        BinaryOp::And if !lhs.boolean() => return Ok(Value::Boolean(false)),
+        _ => try!(eval(rhs, context)),
+    };
+    Ok(match op {
+        BinaryOp::Or | BinaryOp::And => Value::Boolean(rhs.boolean()),
+        BinaryOp::Equal | BinaryOp::NotEqual | BinaryOp::Less |
+        BinaryOp::LessOrEqual | BinaryOp::Greater | BinaryOp::GreaterOrEqual => {
+            Value::Boolean(compare(op, &lhs, &rhs))
+        },
+        BinaryOp::Add => Value::Number(lhs.number() + rhs.number()),
+        BinaryOp::Subtract => Value::Number(lhs.number() - rhs.number()),
+        BinaryOp::Multiply => Value::Number(lhs.number() * rhs.number()),
+        BinaryOp::Divide => Value::Number(lhs.number() / rhs.number()),
+        BinaryOp::Modulo => Value::Number(lhs.number() % rhs.number()),
+        BinaryOp::Union => match (lhs, rhs) {
+            (Value::NodeSet(mut lhs), Value::NodeSet(rhs)) => {
+                lhs.extend(rhs);
+                Value::NodeSet(sort_and_deduplicate(lhs))
+            },
+            _ => return Err(Error::Type("The operands of '|' must be node-sets".to_owned())),
+        },
+    })
+}
+
+/// Compare two values, where a node-set compares true if any of its nodes does.
+/// https://www.w3.org/TR/xpath/#booleans
+fn compare(op: BinaryOp, lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (&Value::NodeSet(ref lhs), &Value::NodeSet(ref rhs)) => lhs.iter().any(|a| {
+            let a = Value::String(a.string_value());
+            rhs.iter().any(|b| compare_values(op, &a, &Value::String(b.string_value())))
+        }),
+        (&Value::NodeSet(ref nodes), &Value::Boolean(_)) => {
+            compare_values(op, &Value::Boolean(!nodes.is_empty()), rhs)
+        },
+        (&Value::Boolean(_), &Value::NodeSet(ref nodes)) => {
+            compare_values(op, lhs, &Value::Boolean(!nodes.is_empty()))
+        },
+        (&Value::NodeSet(ref nodes), _) => nodes.iter().any(|node| {
+            compare_values(op, &Value::String(node.string_value()), rhs)
+        }),
+        (_, &Value::NodeSet(ref nodes)) => nodes.iter().any(|node| {
+            compare_values(op, lhs, &Value::String(node.string_value()))
+        }),
+        _ => compare_values(op, lhs, rhs),
+    }
+}
+
+fn compare_values(op: BinaryOp, lhs: &Value, rhs: &Value) -> bool {
+    let equal = || match (lhs, rhs) {
+        (&Value::Boolean(_), _) | (_, &Value::Boolean(_)) => lhs.boolean() == rhs.boolean(),
+        (&Value::Number(_), _) | (_, &Value::Number(_)) => lhs.number() == rhs.number(),
+        _ => lhs.string() == rhs.string(),
+    };
+    match op {
+        BinaryOp::Equal => equal(),
+        BinaryOp::NotEqual => !equal(),
+        BinaryOp::Less => lhs.number() < rhs.number(),
+        BinaryOp::LessOrEqual => lhs.number() <= rhs.number(),
+        BinaryOp::Greater => lhs.number() > rhs.number(),
+        BinaryOp::GreaterOrEqual => lhs.number() >= rhs.number(),
+        _ => unreachable!(),
+    }
+}
+
+/// Call a function of the core library. The parser has already checked the
+/// name of the function and its number of arguments.
+/// https://www.w3.org/TR/xpath/#corelib
+fn call_function(name: &str, arguments: &[Expr], context: &Context) -> Fallible<Value> {
+    let argument = |index: usize| eval(&arguments[index], context);
+    let string_argument = |index: usize| -> Fallible<String> {
+        if arguments.len() > index {
+            Ok(try!(argument(index)).string())
+        } else {
+            Ok(context.node.string_value())
+        }
+    };
+    let number_argument = |index: usize| -> Fallible<f64> { Ok(try!(argument(index)).number()) };
+
+    Ok(match name {
+        // https://www.w3.org/TR/xpath/#section-Node-Set-Functions
+        "last" => Value::Number(context.size as f64),
+        "position" => Value::Number(context.position as f64),
+        "count" => Value::Number(try!(eval_node_set(&arguments[0], context)).len() as f64),
+        "id" => {
+            let ids = match try!(argument(0)) {
+                Value::NodeSet(nodes) => {
+                    nodes.iter().map(XPathNode::string_value).collect::<Vec<_>>().join(" ")
+                },
+                value => value.string(),
+            };
+            let root = match context.node.root() {
+                XPathNode::Node(root) => root,
+                XPathNode::Attribute(_) => return Ok(Value::NodeSet(vec![])),
+            };
+            let id = Atom::from("id");
+            let mut nodes = vec![];
+            for token in ids.split(is_xml_whitespace).filter(|token| !token.is_empty()) {
+                let element = root.traverse_preorder()
+                                  .filter_map(Root::downcast::<Element>)
+                                  .find(|element| &*element.get_string_attribute(&id) == token);
+                if let Some(element) = element {
+                    nodes.push(XPathNode::Node(Root::upcast(element)));
+                }
+            }
+            Value::NodeSet(sort_and_deduplicate(nodes))
+        },
+        "local-name" | "namespace-uri" | "name" => {
+            let node = if arguments.is_empty() {
+                Some(context.node.clone())
+            } else {
+                try!(eval_node_set(&arguments[0], context)).into_iter().next()
+            };
+            let (local, namespace, qualified) = node.map_or((String::new(), String::new(), String::new()),
+                                                            |node| node.expanded_name());
+            Value::String(match name {
+                "local-name" => local,
+                "namespace-uri" => namespace,
+                _ => qualified,
+            })
+        },
+
+        // https://www.w3.org/TR/xpath/#section-String-Functions
+        "string" => Value::String(try!(string_argument(0))),
+        "concat" => {
+            let mut result = String::new();
+            for index in 0..arguments.len() {
+                result.push_str(&try!(string_argument(index)));
+            }
+            Value::String(result)
+        },
+        "starts-with" => Value::Boolean(try!(string_argument(0)).starts_with(&*try!(string_argument(1)))),
+        "contains" => Value::Boolean(try!(string_argument(0)).contains(&*try!(string_argument(1)))),
+        "substring-before" => {
+            let string = try!(string_argument(0));
+            let pattern = try!(string_argument(1));
+            Value::String(string.find(&*pattern).map_or(String::new(), |index| string[..index].to_owned()))
+        },
+        "substring-after" => {
+            let string = try!(string_argument(0));
+            let pattern = try!(string_argument(1));
+            Value::String(string.find(&*pattern).map_or(String::new(), |index| {
+                string[index + pattern.len()..].to_owned()
+            }))
+        },
+        "substring" => {
+            let string = try!(string_argument(0));
+            let start = round(try!(number_argument(1)));
+            let end = if arguments.len() > 2 {
+                start + round(try!(number_argument(2)))
+            } else {
+                f64::INFINITY
+            };
+            Value::String(string.chars().enumerate().filter(|&(index, _)| {
+                let position = (index + 1) as f64;
+                position >= start && position < end
+            }).map(|(_, c)| c).collect())
+        },
+        "string-length" => Value::Number(try!(string_argument(0)).chars().count() as f64),
+        "normalize-space" => {
+            let string = try!(string_argument(0));
+            let words: Vec<&str> = string.split(is_xml_whitespace).filter(|word| !word.is_empty()).collect();
+            Value::String(words.join(" "))
+        },
+        "translate" => {
+            let string = try!(string_argument(0));
+            let from: Vec<char> = try!(string_argument(1)).chars().collect();
+            let to: Vec<char> = try!(string_argument(2)).chars().collect();
+            Value::String(string.chars().filter_map(|c| match from.iter().position(|&f| f == c) {
+                Some(index) => to.get(index).cloned(),
+                None => Some(c),
+            }).collect())
+        },
+
+        // https://www.w3.org/TR/xpath/#section-Boolean-Functions
+        "boolean" => Value::Boolean(try!(argument(0)).boolean()),
+        "not" => Value::Boolean(!try!(argument(0)).boolean()),
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        "lang" => {
+            let lang = try!(string_argument(0)).to_ascii_lowercase();
+            let xml_lang = context.node.element().and_then(|element| {
+                element.upcast::<Node>()
+                       .inclusive_ancestors()
+                       .filter_map(Root::downcast::<Element>)
+                       .filter_map(|element| element.get_attribute(&ns!(xml), &Atom::from("lang")))
+                       .next()
+            });
+            Value::Boolean(xml_lang.map_or(false, |attr| {
+                let value = attr.value().to_ascii_lowercase();
+                value == lang || value.starts_with(&format!("{}-", lang))
+            }))
+        },
+
+        // https://www.w3.org/TR/xpath/#section-Number-Functions
+        "number" => Value::Number(if arguments.is_empty() {
+            string_to_number(&context.node.string_value())
+        } else {
+            try!(number_argument(0))
+        }),
+        "sum" => {
+            let nodes = try!(eval_node_set(&arguments[0], context));
+            Value::Number(nodes.iter().map(|node| string_to_number(&node.string_value())).fold(0., |a, b| a + b))
+        },
+        "floor" => Value::Number(try!(number_argument(0)).floor()),
+        "ceiling" => Value::Number(try!(number_argument(0)).ceil()),
+        "round" => Value::Number(round(try!(number_argument(0)))),
+        _ => unreachable!(),
+    })
+}
@@ -0,0 +1,12 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! XPath 1.0, for `document.evaluate` and `XPathEvaluator`.
+//!
+//! Expressions are parsed once into an `Expr`, whose prefixed name tests are
+//! resolved to namespaces by the `XPathExpression` that owns it, and can then
+//! be evaluated against any number of context nodes.
+
+pub mod eval;
+pub mod parser;
@@ -0,0 +1,764 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The tokenizer and recursive descent parser for XPath 1.0 expressions.
+//! https://www.w3.org/TR/xpath/#section-Expressions
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    Negate(Box<Expr>),
+    Literal(String),
+    Number(f64),
+    Function(String, Vec<Expr>),
+    /// A primary expression followed by predicates.
+    Filter(Box<Expr>, Vec<Expr>),
+    /// A location path, relative to the node-set of a filter expression if there is one.
+    Path(Option<Box<Expr>>, LocationPath),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Union,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocationPath {
+    pub absolute: bool,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub axis: Axis,
+    pub test: NodeTest,
+    pub predicates: Vec<Expr>,
+}
+
+impl Step {
+    fn any_node(axis: Axis) -> Step {
+        Step {
+            axis: axis,
+            test: NodeTest::Node,
+            predicates: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Namespace,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    Self_,
+}
+
+impl Axis {
+    fn from_name(name: &str) -> Option<Axis> {
+        Some(match name {
+            "ancestor" => Axis::Ancestor,
+            "ancestor-or-self" => Axis::AncestorOrSelf,
+            "attribute" => Axis::Attribute,
+            "child" => Axis::Child,
+            "descendant" => Axis::Descendant,
+            "descendant-or-self" => Axis::DescendantOrSelf,
+            "following" => Axis::Following,
+            "following-sibling" => Axis::FollowingSibling,
+            "namespace" => Axis::Namespace,
+            "parent" => Axis::Parent,
+            "preceding" => Axis::Preceding,
+            "preceding-sibling" => Axis::PrecedingSibling,
+            "self" => Axis::Self_,
+            _ => return None,
+        })
+    }
+
+    /// Whether positions along this axis count backwards from the context node.
+    /// https://www.w3.org/TR/xpath/#predicates
+    pub fn is_reverse(&self) -> bool {
+        match *self {
+            Axis::Ancestor | Axis::AncestorOrSelf | Axis::Preceding | Axis::PrecedingSibling => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeTest {
+    /// A name test; a `local` of `None` is a wildcard. The namespace is filled
+    /// in from the prefix once the expression's namespace resolver is known.
+    Name(NameTest),
+    Node,
+    Text,
+    Comment,
+    ProcessingInstruction(Option<String>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NameTest {
+    pub prefix: Option<String>,
+    pub local: Option<String>,
+    pub namespace: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    ColonColon,
+    Slash,
+    DoubleSlash,
+    Pipe,
+    Plus,
+    Minus,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Star,
+    Literal(String),
+    Number(f64),
+    Variable(String),
+    /// A possibly prefixed name, which may turn out to be an operator, axis,
+    /// node type or function name depending on what surrounds it.
+    Name(Option<String>, String),
+    /// `prefix:*`.
+    PrefixWildcard(String),
+}
+
+pub type ParseResult<T> = Result<T, String>;
+
+fn is_name_start_char(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c as u32 > 0x7F
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start_char(c) || c.is_digit(10) || c == '-' || c == '.'
+}
+
+fn is_xml_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\r' || c == '\n'
+}
+
+fn read_ncname(chars: &mut Peekable<Chars>) -> String {
+    let mut name = String::new();
+    while let Some(&c) = chars.peek() {
+        if !is_name_char(c) {
+            break;
+        }
+        name.push(c);
+        chars.next();
+    }
+    name
+}
+
+fn read_digits(chars: &mut Peekable<Chars>, into: &mut String) {
+    while let Some(&c) = chars.peek() {
+        if !c.is_digit(10) {
+            break;
+        }
+        into.push(c);
+        chars.next();
+    }
+}
+
+// https://www.w3.org/TR/xpath/#exprlex
+fn tokenize(input: &str) -> ParseResult<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if is_xml_whitespace(c) => continue,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '@' => Token::At,
+            ',' => Token::Comma,
+            '|' => Token::Pipe,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '=' => Token::Equal,
+            '*' => Token::Star,
+            '!' => {
+                if chars.next() != Some('=') {
+                    return Err("expected '=' after '!'".to_owned());
+                }
+                Token::NotEqual
+            },
+            '<' | '>' => {
+                let or_equal = chars.peek() == Some(&'=');
+                if or_equal {
+                    chars.next();
+                }
+                match (c, or_equal) {
+                    ('<', false) => Token::Less,
+                    ('<', true) => Token::LessOrEqual,
+                    (_, false) => Token::Greater,
+                    (_, true) => Token::GreaterOrEqual,
+                }
+            },
+            '/' => {
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    Token::DoubleSlash
+                } else {
+                    Token::Slash
+                }
+            },
+            ':' => {
+                if chars.next() != Some(':') {
+                    return Err("unexpected ':'".to_owned());
+                }
+                Token::ColonColon
+            },
+            '.' => {
+                match chars.peek().cloned() {
+                    Some('.') => {
+                        chars.next();
+                        Token::DotDot
+                    },
+                    Some(d) if d.is_digit(10) => {
+                        let mut number = "0.".to_owned();
+                        read_digits(&mut chars, &mut number);
+                        Token::Number(number.parse().unwrap())
+                    },
+                    _ => Token::Dot,
+                }
+            },
+            '"' | '\'' => {
+                let mut literal = String::new();
+                loop {
+                    match chars.next() {
+                        Some(end) if end == c => break,
+                        Some(other) => literal.push(other),
+                        None => return Err("unterminated string literal".to_owned()),
+                    }
+                }
+                Token::Literal(literal)
+            },
+            '$' => {
+                let name = read_ncname(&mut chars);
+                if name.is_empty() {
+                    return Err("expected a variable name after '$'".to_owned());
+                }
+                Token::Variable(name)
+            },
+            d if d.is_digit(10) => {
+                let mut number = d.to_string();
+                read_digits(&mut chars, &mut number);
+                if chars.peek() == Some(&'.') {
+                    number.push('.');
+                    chars.next();
+                    read_digits(&mut chars, &mut number);
+                }
+                Token::Number(number.parse().unwrap())
+            },
+            c if is_name_start_char(c) => {
+                let mut name = c.to_string();
+                name.push_str(&read_ncname(&mut chars));
+                // A single colon makes this a QName or a `prefix:*` test, while
+                // a double colon follows an axis name.
+                let mut lookahead = chars.clone();
+                if lookahead.next() == Some(':') {
+                    match lookahead.next() {
+                        Some('*') => {
+                            chars.next();
+                            chars.next();
+                            tokens.push(Token::PrefixWildcard(name));
+                            continue;
+                        },
+                        Some(c) if is_name_start_char(c) => {
+                            chars.next();
+                            let local = read_ncname(&mut chars);
+                            tokens.push(Token::Name(Some(name), local));
+                            continue;
+                        },
+                        _ => {},
+                    }
+                }
+                Token::Name(None, name)
+            },
+            other => return Err(format!("unexpected character '{}'", other)),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn is_node_type(name: &str) -> bool {
+    match name {
+        "node" | "text" | "comment" | "processing-instruction" => true,
+        _ => false,
+    }
+}
+
+/// Check that `name` is a function of the core library taking `arguments` arguments.
+/// https://www.w3.org/TR/xpath/#corelib
+fn check_function(name: &str, arguments: usize) -> ParseResult<()> {
+    let (min, max) = match name {
+        "last" | "position" | "true" | "false" => (0, Some(0)),
+        "count" | "id" | "boolean" | "not" | "lang" | "sum" | "floor" | "ceiling" | "round" => (1, Some(1)),
+        "local-name" | "namespace-uri" | "name" | "string" | "string-length" |
+        "normalize-space" | "number" => (0, Some(1)),
+        "concat" => (2, None),
+        "starts-with" | "contains" | "substring-before" | "substring-after" => (2, Some(2)),
+        "substring" => (2, Some(3)),
+        "translate" => (3, Some(3)),
+        _ => return Err(format!("unknown function {}()", name)),
+    };
+    if arguments < min || max.map_or(false, |max| arguments > max) {
+        return Err(format!("wrong number of arguments to {}()", name));
+    }
+    Ok(())
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.position + offset)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> ParseResult<()> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            Err(format!("expected {:?}, found {:?}", token, self.peek()))
+        }
+    }
+
+    /// Whether the next token is the unprefixed name `name`, which after an
+    /// operand can only be an operator.
+    fn eat_operator_name(&mut self, name: &str) -> bool {
+        let found = match self.peek() {
+            Some(&Token::Name(None, ref local)) => local == name,
+            _ => false,
+        };
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    // [21] OrExpr
+    fn parse_or(&mut self) -> ParseResult<Expr> {
+        let mut expr = try!(self.parse_and());
+        while self.eat_operator_name("or") {
+            let rhs = try!(self.parse_and());
+            expr = Expr::Binary(box expr, BinaryOp::Or, box rhs);
+        }
+        Ok(expr)
+    }
+
+    // [22] AndExpr
+    fn parse_and(&mut self) -> ParseResult<Expr> {
+        let mut expr = try!(self.parse_equality());
+        while self.eat_operator_name("and") {
+            let rhs = try!(self.parse_equality());
+            expr = Expr::Binary(box expr, BinaryOp::And, box rhs);
+        }
+        Ok(expr)
+    }
+
+    // [23] EqualityExpr
+    fn parse_equality(&mut self) -> ParseResult<Expr> {
+        let mut expr = try!(self.parse_relational());
+        loop {
+            let op = match self.peek() {
+                Some(&Token::Equal) => BinaryOp::Equal,
+                Some(&Token::NotEqual) => BinaryOp::NotEqual,
+                _ => return Ok(expr),
+            };
+            self.position += 1;
+            let rhs = try!(self.parse_relational());
+            expr = Expr::Binary(box expr, op, box rhs);
+        }
+    }
+
+    // [24] RelationalExpr
+    fn parse_relational(&mut self) -> ParseResult<Expr> {
+        let mut expr = try!(self.parse_additive());
+        loop {
+            let op = match self.peek() {
+                Some(&Token::Less) => BinaryOp::Less,
+                Some(&Token::LessOrEqual) => BinaryOp::LessOrEqual,
+                Some(&Token::Greater) => BinaryOp::Greater,
+                Some(&Token::GreaterOrEqual) => BinaryOp::GreaterOrEqual,
+                _ => return Ok(expr),
+            };
+            self.position += 1;
+            let rhs = try!(self.parse_additive());
+            expr = Expr::Binary(box expr, op, box rhs);
+        }
+    }
+
+    // [25] AdditiveExpr
+    fn parse_additive(&mut self) -> ParseResult<Expr> {
+        let mut expr = try!(self.parse_multiplicative());
+        loop {
+            let op = match self.peek() {
+                Some(&Token::Plus) => BinaryOp::Add,
+                Some(&Token::Minus) => BinaryOp::Subtract,
+                _ => return Ok(expr),
+            };
+            self.position += 1;
+            let rhs = try!(self.parse_multiplicative());
+            expr = Expr::Binary(box expr, op, box rhs);
+        }
+    }
+
+    // [26] MultiplicativeExpr
+    fn parse_multiplicative(&mut self) -> ParseResult<Expr> {
+        let mut expr = try!(self.parse_unary());
+        loop {
+            let op = if self.eat(&Token::Star) {
+                BinaryOp::Multiply
+            } else if self.eat_operator_name("div") {
+                BinaryOp::Divide
+            } else if self.eat_operator_name("mod") {
+                BinaryOp::Modulo
+            } else {
+                return Ok(expr);
+            };
+            let rhs = try!(self.parse_unary());
+            expr = Expr::Binary(box expr, op, box rhs);
+        }
+    }
+
+    // [27] UnaryExpr
+    fn parse_unary(&mut self) -> ParseResult<Expr> {
+        if self.eat(&Token::Minus) {
+            let expr = try!(self.parse_unary());
+            return Ok(Expr::Negate(box expr));
+        }
+        self.parse_union()
+    }
+
+    // [18] UnionExpr
+    fn parse_union(&mut self) -> ParseResult<Expr> {
+        let mut expr = try!(self.parse_path());
+        while self.eat(&Token::Pipe) {
+            let rhs = try!(self.parse_path());
+            expr = Expr::Binary(box expr, BinaryOp::Union, box rhs);
+        }
+        Ok(expr)
+    }
+
+    // [19] PathExpr
+    fn parse_path(&mut self) -> ParseResult<Expr> {
+        let starts_filter = match self.peek() {
+            Some(&Token::Variable(_)) | Some(&Token::LeftParen) |
+            Some(&Token::Literal(_)) | Some(&Token::Number(_)) => true,
+            Some(&Token::Name(ref prefix, ref local)) => {
+                self.peek_at(1) == Some(&Token::LeftParen) &&
+                !(prefix.is_none() && is_node_type(local))
+            },
+            _ => false,
+        };
+        if !starts_filter {
+            let path = try!(self.parse_location_path());
+            return Ok(Expr::Path(None, path));
+        }
+
+        // [20] FilterExpr
+        let primary = try!(self.parse_primary());
+        let predicates = try!(self.parse_predicates());
+        let filter = if predicates.is_empty() {
+            primary
+        } else {
+            Expr::Filter(box primary, predicates)
+        };
+
+        let mut steps = vec![];
+        if self.eat(&Token::DoubleSlash) {
+            steps.push(Step::any_node(Axis::DescendantOrSelf));
+        } else if !self.eat(&Token::Slash) {
+            return Ok(filter);
+        }
+        try!(self.parse_relative_location_path(&mut steps));
+        Ok(Expr::Path(Some(box filter), LocationPath {
+            absolute: false,
+            steps: steps,
+        }))
+    }
+
+    // [15] PrimaryExpr
+    fn parse_primary(&mut self) -> ParseResult<Expr> {
+        match self.next() {
+            Some(Token::Variable(name)) => {
+                // The DOM provides no way to bind variables.
+                Err(format!("unbound variable ${}", name))
+            },
+            Some(Token::LeftParen) => {
+                let expr = try!(self.parse_or());
+                try!(self.expect(Token::RightParen));
+                Ok(expr)
+            },
+            Some(Token::Literal(literal)) => Ok(Expr::Literal(literal)),
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::Name(prefix, local)) => {
+                if prefix.is_some() {
+                    return Err(format!("unknown function {}()", local));
+                }
+                // [16] FunctionCall
+                try!(self.expect(Token::LeftParen));
+                let mut arguments = vec![];
+                if !self.eat(&Token::RightParen) {
+                    loop {
+                        arguments.push(try!(self.parse_or()));
+                        if self.eat(&Token::RightParen) {
+                            break;
+                        }
+                        try!(self.expect(Token::Comma));
+                    }
+                }
+                try!(check_function(&local, arguments.len()));
+                Ok(Expr::Function(local, arguments))
+            },
+            other => Err(format!("unexpected {:?}", other)),
+        }
+    }
+
+    fn parse_predicates(&mut self) -> ParseResult<Vec<Expr>> {
+        let mut predicates = vec![];
+        while self.eat(&Token::LeftBracket) {
+            predicates.push(try!(self.parse_or()));
+            try!(self.expect(Token::RightBracket));
+        }
+        Ok(predicates)
+    }
+
+    fn starts_step(&self) -> bool {
+        match self.peek() {
+            Some(&Token::Dot) | Some(&Token::DotDot) | Some(&Token::At) | Some(&Token::Star) |
+            Some(&Token::PrefixWildcard(_)) | Some(&Token::Name(..)) => true,
+            _ => false,
+        }
+    }
+
+    // [1] LocationPath
+    fn parse_location_path(&mut self) -> ParseResult<LocationPath> {
+        let mut steps = vec![];
+        let absolute = if self.eat(&Token::Slash) {
+            // A lone `/` selects the root node.
+            if self.starts_step() {
+                try!(self.parse_relative_location_path(&mut steps));
+            }
+            true
+        } else if self.eat(&Token::DoubleSlash) {
+            steps.push(Step::any_node(Axis::DescendantOrSelf));
+            try!(self.parse_relative_location_path(&mut steps));
+            true
+        } else {
+            try!(self.parse_relative_location_path(&mut steps));
+            false
+        };
+        Ok(LocationPath {
+            absolute: absolute,
+            steps: steps,
+        })
+    }
+
+    // [3] RelativeLocationPath
+    fn parse_relative_location_path(&mut self, steps: &mut Vec<Step>) -> ParseResult<()> {
+        loop {
+            steps.push(try!(self.parse_step()));
+            if self.eat(&Token::DoubleSlash) {
+                steps.push(Step::any_node(Axis::DescendantOrSelf));
+            } else if !self.eat(&Token::Slash) {
+                return Ok(());
+            }
+        }
+    }
+
+    // [4] Step
+    fn parse_step(&mut self) -> ParseResult<Step> {
+        if self.eat(&Token::Dot) {
+            return Ok(Step::any_node(Axis::Self_));
+        }
+        if self.eat(&Token::DotDot) {
+            return Ok(Step::any_node(Axis::Parent));
+        }
+
+        // [5] AxisSpecifier
+        let axis = if self.eat(&Token::At) {
+            Axis::Attribute
+        } else if self.peek_at(1) == Some(&Token::ColonColon) {
+            let axis = match self.peek() {
+                Some(&Token::Name(None, ref name)) => Axis::from_name(name),
+                _ => None,
+            };
+            match axis {
+                Some(axis) => {
+                    self.position += 2;
+                    axis
+                },
+                None => return Err(format!("unknown axis {:?}", self.peek())),
+            }
+        } else {
+            Axis::Child
+        };
+
+        // [7] NodeTest
+        let test = match self.next() {
+            Some(Token::Star) => NodeTest::Name(NameTest {
+                prefix: None,
+                local: None,
+                namespace: None,
+            }),
+            Some(Token::PrefixWildcard(prefix)) => NodeTest::Name(NameTest {
+                prefix: Some(prefix),
+                local: None,
+                namespace: None,
+            }),
+            Some(Token::Name(None, ref name)) if is_node_type(name) &&
+                                                 self.peek() == Some(&Token::LeftParen) => {
+                self.position += 1;
+                let test = match &**name {
+                    "node" => NodeTest::Node,
+                    "text" => NodeTest::Text,
+                    "comment" => NodeTest::Comment,
+                    _ => {
+                        let target = match self.peek().cloned() {
+                            Some(Token::Literal(target)) => {
+                                self.position += 1;
+                                Some(target)
+                            },
+                            _ => None,
+                        };
+                        NodeTest::ProcessingInstruction(target)
+                    },
+                };
+                try!(self.expect(Token::RightParen));
+                test
+            },
+            Some(Token::Name(prefix, local)) => NodeTest::Name(NameTest {
+                prefix: prefix,
+                local: Some(local),
+                namespace: None,
+            }),
+            other => return Err(format!("expected a node test, found {:?}", other)),
+        };
+
+        let predicates = try!(self.parse_predicates());
+        Ok(Step {
+            axis: axis,
+            test: test,
+            predicates: predicates,
+        })
+    }
+}
+
+/// Parse an XPath 1.0 expression.
+pub fn parse(input: &str) -> ParseResult<Expr> {
+    let tokens = try!(tokenize(input));
+    let mut parser = Parser {
+        tokens: tokens,
+        position: 0,
+    };
+    let expr = try!(parser.parse_or());
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("unexpected {:?} after the expression", token)),
+    }
+}
+
+/// Call `f` on every name test in `expr` that has a prefix.
+pub fn for_each_prefixed_name_test<F, E>(expr: &mut Expr, f: &mut F) -> Result<(), E>
+    where F: FnMut(&mut NameTest) -> Result<(), E>
+{
+    match *expr {
+        Expr::Binary(ref mut lhs, _, ref mut rhs) => {
+            try!(for_each_prefixed_name_test(lhs, f));
+            for_each_prefixed_name_test(rhs, f)
+        },
+        Expr::Negate(ref mut expr) => for_each_prefixed_name_test(expr, f),
+        Expr::Literal(_) | Expr::Number(_) => Ok(()),
+        Expr::Function(_, ref mut arguments) => {
+            for argument in arguments {
+                try!(for_each_prefixed_name_test(argument, f));
+            }
+            Ok(())
+        },
+        Expr::Filter(ref mut primary, ref mut predicates) => {
+            try!(for_each_prefixed_name_test(primary, f));
+            for predicate in predicates {
+                try!(for_each_prefixed_name_test(predicate, f));
+            }
+            Ok(())
+        },
+        Expr::Path(ref mut filter, ref mut path) => {
+            if let Some(ref mut filter) = *filter {
+                try!(for_each_prefixed_name_test(filter, f));
+            }
+            for step in &mut path.steps {
+                if let NodeTest::Name(ref mut test) = step.test {
+                    if test.prefix.is_some() {
+                        try!(f(test));
+                    }
+                }
+                for predicate in &mut step.predicates {
+                    try!(for_each_prefixed_name_test(predicate, f));
+                }
+            }
+            Ok(())
+        },
+    }
+}
@@ -95,12 +95,63 @@ pub enum LayoutElementType {
     HTMLTableRowElement,
     HTMLTableSectionElement,
     HTMLTextAreaElement,
+    HTMLVideoElement,
 }
 
+/// The data that layout paints a canvas or a video element with.
 pub struct HTMLCanvasData {
     pub ipc_renderer: Option<IpcSender<CanvasMsg>>,
     pub width: u32,
     pub height: u32,
+    /// The cues shown over a video, in the order of their tracks and start times.
+    pub cues: Vec<ShownCue>,
+}
+
+/// How the text of a cue is aligned within its box.
+/// https://w3c.github.io/webvtt/#webvtt-cue-text-alignment
+#[derive(Clone, Copy, Debug, HeapSizeOf, PartialEq)]
+pub enum CueAlign {
+    Start,
+    Center,
+    End,
+    Left,
+    Right,
+}
+
+/// Where a cue is shown over a video.
+/// https://w3c.github.io/webvtt/#webvtt-cue-settings
+#[derive(Clone, Copy, Debug, HeapSizeOf, PartialEq)]
+pub struct CueSettings {
+    /// The line the cue is shown on, or `None` for it to be placed below the other cues.
+    /// With `snap_to_lines` it is a number of lines, from the top if it's positive and from
+    /// the bottom if it's negative, and otherwise a percentage of the height of the video.
+    pub line: Option<f64>,
+    pub snap_to_lines: bool,
+    /// Where the cue box starts horizontally, as a percentage of the width of the video, or
+    /// `None` for it to follow from the alignment.
+    pub position: Option<f64>,
+    /// The width of the cue box, as a percentage of the width of the video.
+    pub size: f64,
+    pub align: CueAlign,
+}
+
+impl Default for CueSettings {
+    fn default() -> CueSettings {
+        CueSettings {
+            line: None,
+            snap_to_lines: true,
+            position: None,
+            size: 100.,
+            align: CueAlign::Center,
+        }
+    }
+}
+
+/// A cue shown over a video: its text, without markup, and its settings.
+#[derive(Clone, Debug, HeapSizeOf, PartialEq)]
+pub struct ShownCue {
+    pub text: String,
+    pub settings: CueSettings,
 }
 
 /// The address of a node known to be valid. These are sent from script to layout.
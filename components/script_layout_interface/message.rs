@@ -17,7 +17,7 @@ use script_traits::{LayoutMsg as ConstellationMsg, StackingContextScrollState, W
 use std::sync::Arc;
 use std::sync::mpsc::{Receiver, Sender};
 use string_cache::Atom;
-use style::context::ReflowGoal;
+use style::context::{QuirksMode, ReflowGoal};
 use style::selector_impl::PseudoElement;
 use style::stylesheets::Stylesheet;
 use url::Url;
@@ -28,8 +28,9 @@ pub enum Msg {
     /// Adds the given stylesheet to the document.
     AddStylesheet(Arc<Stylesheet>),
 
-    /// Puts a document into quirks mode, causing the quirks mode stylesheet to be loaded.
-    SetQuirksMode,
+    /// Sets the mode of the document, which causes the quirks mode stylesheet to be loaded in
+    /// quirks mode and changes some layout behaviour.
+    SetQuirksMode(QuirksMode),
 
     /// Requests a reflow.
     Reflow(ScriptReflow),
@@ -105,6 +106,7 @@ pub enum ReflowQueryType {
     ResolvedStyleQuery(TrustedNodeAddress, Option<PseudoElement>, Atom),
     OffsetParentQuery(TrustedNodeAddress),
     MarginStyleQuery(TrustedNodeAddress),
+    TextIndexQuery(TrustedNodeAddress, Point2D<f32>),
 }
 
 /// Information needed for a reflow.
@@ -141,6 +143,7 @@ impl Drop for ScriptReflow {
 
 pub struct NewLayoutThreadInfo {
     pub id: PipelineId,
+    pub old_pipeline_id: Option<PipelineId>,
     pub url: Url,
     pub is_parent: bool,
     pub layout_pair: (Sender<Msg>, Receiver<Msg>),
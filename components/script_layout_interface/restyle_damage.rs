@@ -173,7 +173,7 @@ fn compute_damage(old: &ServoComputedValues, new: &ServoComputedValues) -> Resty
     add_if_not_equal!(old, new, damage,
                       [REPAINT, REPOSITION, STORE_OVERFLOW, BUBBLE_ISIZES, REFLOW_OUT_OF_FLOW,
                        REFLOW, RECONSTRUCT_FLOW], [
-        get_box.float, get_box.display, get_box.position, get_counters.content,
+        get_box.float, get_box.display, get_box.position, get_box.contain, get_counters.content,
         get_counters.counter_reset, get_counters.counter_increment,
         get_inheritedbox._servo_under_display_none,
         get_list.quotes, get_list.list_style_type,
@@ -188,7 +188,7 @@ fn compute_damage(old: &ServoComputedValues, new: &ServoComputedValues) -> Resty
         get_font.font_size, get_font.font_stretch,
         get_inheritedbox.direction, get_inheritedbox.writing_mode,
         get_inheritedbox.text_orientation,
-        get_text.text_decoration, get_text.unicode_bidi,
+        get_text.text_decoration_line, get_text.unicode_bidi,
         get_inheritedtable.empty_cells, get_inheritedtable.caption_side,
         get_column.column_width, get_column.column_count
     ]) || (new.get_box().display == display::T::inline &&
@@ -210,13 +210,17 @@ fn compute_damage(old: &ServoComputedValues, new: &ServoComputedValues) -> Resty
         get_margin.margin_bottom, get_margin.margin_left,
         get_padding.padding_top, get_padding.padding_right,
         get_padding.padding_bottom, get_padding.padding_left,
-        get_position.width, get_position.height,
+        get_position.width, get_position.height, get_position.aspect_ratio,
         get_inheritedtext.line_height,
         get_inheritedtext.text_align, get_inheritedtext.text_indent,
+        get_inheritedtext.hyphens, get_inheritedtext.hyphenate_character,
+        get_inheritedtext._servo_lang,
+        get_inheritedtext.ruby_align, get_inheritedtext.ruby_position,
         get_table.table_layout,
         get_inheritedtable.border_collapse,
         get_inheritedtable.border_spacing,
         get_column.column_gap,
+        get_position.row_gap,
         get_position.flex_direction,
         get_position.flex_wrap,
         get_position.justify_content,
@@ -226,13 +230,16 @@ fn compute_damage(old: &ServoComputedValues, new: &ServoComputedValues) -> Resty
         get_position.flex_basis,
         get_position.flex_grow,
         get_position.flex_shrink,
-        get_position.align_self
+        get_position.align_self,
+        get_box.scrollbar_width, get_box.scrollbar_gutter,
+        get_box.break_before, get_box.break_after, get_box.break_inside
     ]) || add_if_not_equal!(old, new, damage,
                             [REPAINT, REPOSITION, STORE_OVERFLOW, REFLOW_OUT_OF_FLOW], [
         get_position.top, get_position.left,
         get_position.right, get_position.bottom,
         get_effects.opacity,
         get_effects.transform, get_effects.transform_style, get_effects.transform_origin,
+        get_effects.translate, get_effects.rotate, get_effects.scale,
         get_effects.perspective, get_effects.perspective_origin
     ]) || add_if_not_equal!(old, new, damage,
                             [REPAINT], [
@@ -240,18 +247,23 @@ fn compute_damage(old: &ServoComputedValues, new: &ServoComputedValues) -> Resty
         get_background.background_image, get_background.background_position,
         get_background.background_repeat, get_background.background_attachment,
         get_background.background_clip, get_background.background_origin,
-        get_background.background_size,
+        get_background.background_size, get_background.background_blend_mode,
         get_border.border_top_color, get_border.border_right_color,
         get_border.border_bottom_color, get_border.border_left_color,
         get_border.border_top_style, get_border.border_right_style,
         get_border.border_bottom_style, get_border.border_left_style,
         get_border.border_top_left_radius, get_border.border_top_right_radius,
         get_border.border_bottom_left_radius, get_border.border_bottom_right_radius,
+        get_border.border_image_source, get_border.border_image_slice,
+        get_border.border_image_width, get_border.border_image_outset,
+        get_border.border_image_repeat,
         get_position.z_index, get_box._servo_overflow_clip_box,
         get_inheritedtext._servo_text_decorations_in_effect,
-        get_pointing.cursor, get_pointing.pointer_events,
+        get_inheritedtext.text_underline_offset, get_inheritedtext.text_decoration_skip_ink,
+        get_pointing.cursor, get_pointing.pointer_events, get_pointing.scrollbar_color,
         get_effects.box_shadow, get_effects.clip, get_inheritedtext.text_shadow, get_effects.filter,
-        get_effects.mix_blend_mode, get_inheritedbox.image_rendering,
+        get_effects.mix_blend_mode, get_box.isolation, get_inheritedbox.image_rendering,
+        get_position.object_fit, get_position.object_position,
 
         // Note: May require REFLOW et al. if `visibility: collapse` is implemented.
         get_inheritedbox.visibility
@@ -37,6 +37,8 @@ pub trait LayoutRPC {
     fn offset_parent(&self) -> OffsetParentResponse;
     /// Query layout for the resolve values of the margin properties for an element.
     fn margin_style(&self) -> MarginStyleResponse;
+    /// Requests the index of the character of a text node at the point of interest.
+    fn text_index(&self) -> TextIndexResponse;
 
     fn nodes_from_point(&self, page_point: Point2D<f32>, client_point: Point2D<f32>) -> Vec<UntrustedNodeAddress>;
 }
@@ -94,3 +96,7 @@ impl MarginStyleResponse {
         }
     }
 }
+
+/// The number of characters rendered for a text node before the point of interest, if the
+/// point is over the text of the node.
+pub struct TextIndexResponse(pub Option<usize>);
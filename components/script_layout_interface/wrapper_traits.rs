@@ -324,6 +324,41 @@ pub trait ThreadSafeLayoutNode: Clone + Copy + NodeInfo + PartialEq + Sized {
         })
     }
 
+    /// Returns the style of the `::backdrop` of this node, if it is an element in the top
+    /// layer that has one.
+    #[inline]
+    fn backdrop_style(&self) -> Option<Arc<ServoComputedValues>> {
+        if self.get_pseudo_element_type() != PseudoElementType::Normal ||
+           !self.as_element().is_in_top_layer() {
+            return None
+        }
+        self.get_style_data().unwrap().borrow().style_data.per_pseudo
+            .get(&PseudoElement::Backdrop)
+            .cloned()
+    }
+
+    /// Returns the style of the `::cue` of this node, the cues of the video it shows, or its own
+    /// style if no rule matches its `::cue`.
+    #[inline]
+    fn cue_style(&self) -> Arc<ServoComputedValues> {
+        let data = self.get_style_data().unwrap().borrow();
+        data.style_data.per_pseudo
+            .get(&PseudoElement::Cue)
+            .unwrap_or(data.style_data.style.as_ref().unwrap())
+            .clone()
+    }
+
+    /// Returns the style of the `::marker` of this list item, or its own style if no rule matches
+    /// its `::marker`.
+    #[inline]
+    fn marker_style(&self) -> Arc<ServoComputedValues> {
+        let data = self.get_style_data().unwrap().borrow();
+        data.style_data.per_pseudo
+            .get(&PseudoElement::Marker)
+            .unwrap_or(data.style_data.style.as_ref().unwrap())
+            .clone()
+    }
+
     /// Removes the style from this node.
     ///
     /// Unlike the version on TNode, this handles pseudo-elements.
@@ -367,6 +402,10 @@ pub trait ThreadSafeLayoutNode: Clone + Copy + NodeInfo + PartialEq + Sized {
     /// FIXME(pcwalton): Don't copy URLs.
     fn image_url(&self) -> Option<Url>;
 
+    /// If this is an image element, returns the pixel density of the image source that was
+    /// selected for it. If this is not an image element, fails.
+    fn image_density(&self) -> f64;
+
     fn canvas_data(&self) -> Option<HTMLCanvasData>;
 
     /// If this node is an iframe element, returns its pipeline ID. If this node is
@@ -375,6 +414,9 @@ pub trait ThreadSafeLayoutNode: Clone + Copy + NodeInfo + PartialEq + Sized {
 
     fn get_colspan(&self) -> u32;
 
+    /// The number of rows that this node spans, or zero if it spans the rest of its row group.
+    fn get_rowspan(&self) -> u32;
+
     fn layer_id(&self) -> LayerId {
         let layer_type = match self.get_pseudo_element_type() {
             PseudoElementType::Normal => LayerType::FragmentBody,
@@ -414,4 +456,8 @@ pub trait ThreadSafeLayoutElement: Clone + Copy + Sized + Debug +
 
     #[inline]
     fn get_namespace(&self) -> &Namespace;
+
+    /// Whether this element is in its document's top layer, i.e. it is a modal dialog or the
+    /// fullscreen element.
+    fn is_in_top_layer(&self) -> bool;
 }
@@ -31,12 +31,15 @@ extern crate serde;
 extern crate style_traits;
 extern crate time;
 extern crate url;
+extern crate usb_traits;
 extern crate util;
+extern crate webrtc_traits;
 
 mod script_msg;
 pub mod webdriver_msg;
 
 use app_units::Au;
+use canvas_traits::webxr::WebXRMsg;
 use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
 use euclid::Size2D;
 use euclid::length::Length;
@@ -66,11 +69,14 @@ use std::fmt;
 use std::sync::mpsc::{Receiver, Sender};
 use style_traits::{PagePx, ViewportPx};
 use url::Url;
+use usb_traits::UsbMethodMsg;
 use util::ipc::OptionalOpaqueIpcSender;
 use webdriver_msg::{LoadStatus, WebDriverScriptCommand};
+use webrtc_traits::WebRTCMsg;
 
 pub use script_msg::{LayoutMsg, ScriptMsg, EventResult, LogEntry};
 pub use script_msg::{ServiceWorkerMsg, ScopeThings, SWManagerMsg, SWManagerSenders, DOMMessage};
+pub use script_msg::{ClientInfo, ServiceWorkerScript, UpdateViaCache};
 
 /// The address of a node. Layout sends these back. They must be validated via
 /// `from_untrusted_node_address` before they can be used, because we do not trust layout.
@@ -132,6 +138,8 @@ pub struct NewLayoutInfo {
     pub parent_pipeline_id: PipelineId,
     /// Id of the newly-created pipeline.
     pub new_pipeline_id: PipelineId,
+    /// Id of the pipeline that the new one replaces by navigating, if any.
+    pub old_pipeline_id: Option<PipelineId>,
     /// Type of the new frame associated with this pipeline.
     pub frame_type: FrameType,
     /// Network request data which will be initiated by the script thread.
@@ -160,8 +168,8 @@ pub enum ConstellationControlMsg {
     ResizeInactive(PipelineId, WindowSizeData),
     /// Notifies the script that a pipeline should be closed.
     ExitPipeline(PipelineId),
-    /// Sends a DOM event.
-    SendEvent(PipelineId, CompositorEvent),
+    /// Sends a DOM event, with the time the input that caused it was received, in nanoseconds.
+    SendEvent(PipelineId, CompositorEvent, u64),
     /// Notifies script of the viewport.
     Viewport(PipelineId, Rect<f32>),
     /// Notifies script of a new set of scroll offsets.
@@ -177,6 +185,8 @@ pub enum ConstellationControlMsg {
     /// Notifies script thread that frame visibility change is complete
     /// First PipelineId is for the parent, second PipelineId is for the actual pipeline.
     NotifyVisibilityChange(PipelineId, PipelineId, bool),
+    /// Notifies script thread whether the top-level browsing context of a pipeline is muted
+    SetMuted(PipelineId, bool),
     /// Notifies script thread that a url should be loaded in this iframe.
     /// First PipelineId is for the parent, second PipelineId is for the actual pipeline.
     Navigate(PipelineId, PipelineId, LoadData, bool),
@@ -211,7 +221,31 @@ pub enum ConstellationControlMsg {
     /// Report an error from a CSS parser for the given pipeline
     ReportCSSError(PipelineId, String, usize, usize, String),
     /// Reload the given page.
-    Reload(PipelineId)
+    Reload(PipelineId),
+    /// Stop loading the given page.
+    StopLoading(PipelineId),
+    /// Notifies the script thread that the session history traversed to another entry of
+    /// the given pipeline's document, with the entry's URL and serialized state object.
+    UpdateHistoryState(PipelineId, HistoryStateId, Url, Option<Vec<u8>>),
+    /// Delivers a message posted by the service worker of the given scope to the
+    /// window of a pipeline.
+    PostMessageFromServiceWorker(PipelineId, DOMMessage, Url),
+    /// The service worker with the given script url, for the given scope, claims the window
+    /// in the pipeline if the scope is the longest of the registered scopes it matches.
+    ClaimedByServiceWorker(PipelineId, Url, Url, Vec<Url>),
+    /// Notifies the script thread that layout handed the first display list of the
+    /// given kind to the compositor, at the given `time::precise_time_ns` value.
+    PaintMetric(PipelineId, PaintMetricType, u64),
+    /// Notifies the script thread that layout handed a display list to the compositor, at
+    /// the given `time::precise_time_ns` value, whose largest text or image comes from the
+    /// given node and covers the given area of the viewport, in square pixels.
+    LargestContentfulPaint(PipelineId, UntrustedNodeAddress, f64, u64),
+    /// Notifies the script thread that a CSS animation of the given node, with the given
+    /// name, started, iterated or ended after running for the given number of seconds.
+    AnimationEvent(PipelineId, UntrustedNodeAddress, AnimationEventType, String, f64),
+    /// Delivers a message posted to the window of a pipeline from another script thread, with
+    /// the URL of the origin it may be delivered to, if restricted, and the serialized message.
+    PostMessage(PipelineId, Option<Url>, Vec<u8>),
 }
 
 impl fmt::Debug for ConstellationControlMsg {
@@ -230,6 +264,7 @@ impl fmt::Debug for ConstellationControlMsg {
             Thaw(..) => "Thaw",
             ChangeFrameVisibilityStatus(..) => "ChangeFrameVisibilityStatus",
             NotifyVisibilityChange(..) => "NotifyVisibilityChange",
+            SetMuted(..) => "SetMuted",
             Navigate(..) => "Navigate",
             MozBrowserEvent(..) => "MozBrowserEvent",
             UpdatePipelineId(..) => "UpdatePipelineId",
@@ -240,7 +275,15 @@ impl fmt::Debug for ConstellationControlMsg {
             DispatchFrameLoadEvent { .. } => "DispatchFrameLoadEvent",
             FramedContentChanged(..) => "FramedContentChanged",
             ReportCSSError(..) => "ReportCSSError",
-            Reload(..) => "Reload"
+            Reload(..) => "Reload",
+            StopLoading(..) => "StopLoading",
+            UpdateHistoryState(..) => "UpdateHistoryState",
+            PostMessageFromServiceWorker(..) => "PostMessageFromServiceWorker",
+            ClaimedByServiceWorker(..) => "ClaimedByServiceWorker",
+            PaintMetric(..) => "PaintMetric",
+            LargestContentfulPaint(..) => "LargestContentfulPaint",
+            AnimationEvent(..) => "AnimationEvent",
+            PostMessage(..) => "PostMessage",
         })
     }
 }
@@ -268,6 +311,108 @@ pub enum AnimationState {
     NoAnimationCallbacksPresent,
 }
 
+/// The paints recorded by the paint timing API.
+/// https://w3c.github.io/paint-timing/#sec-PerformancePaintTiming
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PaintMetricType {
+    /// The first display list with any display items.
+    FirstPaint,
+    /// The first display list with text, images or canvases.
+    FirstContentfulPaint,
+}
+
+/// The events fired while a CSS animation runs.
+/// https://drafts.csswg.org/css-animations/#events
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum AnimationEventType {
+    /// The animation started, after its delay.
+    AnimationStart,
+    /// An iteration of the animation ended and another one started.
+    AnimationIteration,
+    /// The animation ran all its iterations.
+    AnimationEnd,
+}
+
+/// The kind of value a form control asks the embedder to pick.
+/// https://html.spec.whatwg.org/multipage/#the-input-element
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum InputPickerKind {
+    /// A date, for `<input type=date>`.
+    Date,
+    /// A time of day, for `<input type=time>`.
+    Time,
+    /// An sRGB color, for `<input type=color>`.
+    Color,
+}
+
+/// The data that a page asks the embedder to share through the platform's share mechanism.
+/// https://w3c.github.io/web-share/#sharedata-dictionary
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ShareData {
+    /// The title of the shared content.
+    pub title: Option<String>,
+    /// The text of the shared content.
+    pub text: Option<String>,
+    /// The shared URL, resolved against the base URL of the document.
+    pub url: Option<String>,
+    /// The shared files.
+    pub files: Vec<SharedFile>,
+}
+
+/// A file that a page asks the embedder to share.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SharedFile {
+    /// The name of the file.
+    pub name: String,
+    /// The MIME type of the file, which may be empty.
+    pub mime_type: String,
+    /// The contents of the file.
+    pub data: Vec<u8>,
+}
+
+/// Why the embedder did not share the data of a page.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ShareError {
+    /// The user dismissed the share target picker.
+    Canceled,
+    /// The data could not be handed to a share target, or there is none.
+    Failed,
+}
+
+/// A simple dialog that a page asks the embedder to show.
+/// https://html.spec.whatwg.org/multipage/#simple-dialogs
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SimpleDialog {
+    /// `alert(message)`.
+    Alert(String),
+    /// `confirm(message)`.
+    Confirm(String),
+    /// `prompt(message, default)`.
+    Prompt(String, String),
+}
+
+/// How the user answered a simple dialog.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DialogResponse {
+    /// Whether the dialog was accepted rather than cancelled.
+    pub accepted: bool,
+    /// The text entered into a prompt dialog.
+    pub text: Option<String>,
+    /// Whether the user asked not to be shown any more dialogs from the origin of the page.
+    pub prevent_additional_dialogs: bool,
+}
+
+impl DialogResponse {
+    /// The answer to a dialog that wasn't shown, or was closed without an answer.
+    pub fn dismissed() -> DialogResponse {
+        DialogResponse {
+            accepted: false,
+            text: None,
+            prevent_additional_dialogs: false,
+        }
+    }
+}
+
 /// The type of input represented by a multi-touch event.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum TouchEventType {
@@ -363,6 +508,11 @@ pub enum TimerSource {
 #[derive(PartialEq, Eq, Copy, Clone, Debug, HeapSizeOf, Deserialize, Serialize)]
 pub struct TimerEventId(pub u32);
 
+/// Identifies a session history entry among the entries of the same document. The entry a
+/// document is loaded in is `HistoryStateId(0)`.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug, HeapSizeOf, Deserialize, Serialize)]
+pub struct HistoryStateId(pub u64);
+
 /// Unit of measurement.
 #[derive(Clone, Copy, HeapSizeOf)]
 pub enum Milliseconds {}
@@ -406,6 +556,12 @@ pub struct InitialScriptState {
     pub resource_threads: ResourceThreads,
     /// A channel to the bluetooth thread.
     pub bluetooth_thread: IpcSender<BluetoothMethodMsg>,
+    /// A channel to the USB thread.
+    pub usb_thread: IpcSender<UsbMethodMsg>,
+    /// A channel to the WebRTC thread.
+    pub webrtc_thread: IpcSender<WebRTCMsg>,
+    /// A channel to the WebXR thread.
+    pub webxr_thread: IpcSender<WebXRMsg>,
     /// A channel to the image cache thread.
     pub image_cache_thread: ImageCacheThread,
     /// A channel to the time profiler thread.
@@ -498,7 +654,8 @@ pub enum MozBrowserEvent {
     /// Sent when the SSL state changes within a browser `<iframe>`.
     SecurityChange(HttpsState),
     /// Sent when alert(), confirm(), or prompt() is called within a browser `<iframe>`.
-    ShowModalPrompt(String, String, String, String), // TODO(simartin): Handle unblock()
+    /// The page waits until the embedder calls `answerModalPrompt()` on the `<iframe>`.
+    ShowModalPrompt(String, String, String, String),
     /// Sent when the document.title changes within a browser `<iframe>`.
     TitleChange(String),
     /// Sent when an HTTP authentification is requested.
@@ -507,6 +664,8 @@ pub enum MozBrowserEvent {
     OpenSearch,
     /// Sent when visibility state changes.
     VisibilityChange(bool),
+    /// Sent when the page within a browser `<iframe>` starts or stops making sound.
+    AudioPlaybackChange(bool),
 }
 
 impl MozBrowserEvent {
@@ -530,6 +689,7 @@ impl MozBrowserEvent {
             MozBrowserEvent::UsernameAndPasswordRequired => "mozbrowserusernameandpasswordrequired",
             MozBrowserEvent::OpenSearch => "mozbrowseropensearch",
             MozBrowserEvent::VisibilityChange(_) => "mozbrowservisibilitychange",
+            MozBrowserEvent::AudioPlaybackChange(_) => "mozbrowseraudioplaybackchange",
         }
     }
 }
@@ -640,6 +800,10 @@ pub enum ConstellationMsg {
     WebDriverCommand(WebDriverCommandMsg),
     /// Reload the current page.
     Reload,
+    /// Stop loading the current page.
+    Stop,
+    /// Mute or unmute the root browsing context.
+    SetMuted(bool),
     /// A log entry, with the pipeline id and thread name
     LogEntry(Option<PipelineId>, Option<String>, LogEntry),
 }
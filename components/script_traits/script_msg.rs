@@ -3,23 +3,33 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use AnimationState;
+use DialogResponse;
 use DocumentState;
+use HistoryStateId;
 use IFrameLoadInfo;
+use InputPickerKind;
 use MouseButton;
 use MouseEventType;
 use MozBrowserEvent;
+use NewLayoutInfo;
+use ShareData;
+use ShareError;
+use SimpleDialog;
 use WorkerGlobalScopeInit;
 use WorkerScriptLoadOrigin;
 use canvas_traits::CanvasMsg;
+use canvas_traits::webgl2::WebGLVersion;
+use canvas_traits::webgl_extensions::WebGLExtensionSupport;
 use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
 use euclid::point::Point2D;
 use euclid::size::Size2D;
-use gfx_traits::LayerId;
+use gfx_traits::{LayerId, ScrollContainerScrollbars};
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::{Key, KeyModifiers, KeyState, LoadData};
 use msg::constellation_msg::{PipelineId, TraversalDirection};
 use net_traits::CoreResourceMsg;
 use offscreen_gl_context::{GLContextAttributes, GLLimits};
+use std::collections::HashMap;
 use style_traits::cursor::Cursor;
 use style_traits::viewport::ViewportConstraints;
 use url::Url;
@@ -33,6 +43,9 @@ pub enum LayoutMsg {
     SetCursor(Cursor),
     /// Notifies the constellation that the viewport has been constrained in some manner
     ViewportConstrained(PipelineId, ViewportConstraints),
+    /// Requests that the constellation inform the compositor of the scrollbars of a pipeline
+    /// that WebRender renders.
+    SetScrollbars(PipelineId, Vec<ScrollContainerScrollbars>),
 }
 
 /// Whether a DOM event was prevented by web content
@@ -69,7 +82,8 @@ pub enum ScriptMsg {
     /// WebGL uses the GPU and we don't want to give untrusted content access to the GPU.)
     CreateWebGLPaintThread(Size2D<i32>,
                            GLContextAttributes,
-                           IpcSender<Result<(IpcSender<CanvasMsg>, GLLimits), String>>),
+                           WebGLVersion,
+                           IpcSender<Result<(IpcSender<CanvasMsg>, GLLimits, WebGLExtensionSupport), String>>),
     /// Notifies the constellation that this frame has received focus.
     Focus(PipelineId),
     /// Re-send a mouse button event that was sent to the parent window.
@@ -94,18 +108,41 @@ pub enum ScriptMsg {
     TraverseHistory(Option<PipelineId>, TraversalDirection),
     /// Gets the length of the joint session history from the constellation.
     JointSessionHistoryLength(PipelineId, IpcSender<u32>),
+    /// Gets the pipeline of the top-level browsing context that a pipeline is nested in.
+    GetTopLevelPipeline(PipelineId, IpcSender<PipelineId>),
+    /// Posts a serialized message to the window of a pipeline in another script thread, with
+    /// the URL of the origin it may be delivered to, if restricted.
+    PostMessage(PipelineId, Option<Url>, Vec<u8>),
+    /// Adds a same-document entry to the session history, with an optional
+    /// serialized state object.
+    PushHistoryState(PipelineId, HistoryStateId, Url, Option<Vec<u8>>),
+    /// Replaces the URL and serialized state object of the current session history entry.
+    ReplaceHistoryState(PipelineId, Url, Option<Vec<u8>>),
     /// Favicon detected
     NewFavicon(Url),
+    /// A document hints that it is likely to navigate to a page, which may be loaded in a
+    /// hidden pipeline ahead of time.
+    Prerender(PipelineId, LoadData),
     /// Status message to be displayed in the chrome, eg. a link URL on mouseover.
     NodeStatus(Option<String>),
     /// Notification that this iframe should be removed.
     RemoveIFrame(PipelineId, Option<IpcSender<()>>),
+    /// Cancel the navigations that would replace a pipeline, as when its window is stopped.
+    CancelNavigation(PipelineId),
     /// Change pipeline visibility
     SetVisible(PipelineId, bool),
     /// Notifies constellation that an iframe's visibility has been changed.
     VisibilityChangeComplete(PipelineId, bool),
+    /// Mute or unmute the top-level browsing context that a pipeline is the top of.
+    SetMuted(PipelineId, bool),
+    /// Notifies the constellation whether the media of a pipeline started or stopped
+    /// making sound.
+    AudioPlaybackChanged(PipelineId, bool),
     /// A load has been requested in an IFrame.
     ScriptLoadedURLInIFrame(IFrameLoadInfo),
+    /// A newly inserted iframe needs a pipeline for its initial about:blank document, which
+    /// the script thread creates synchronously from the returned layout info.
+    ScriptNewIFrame(IFrameLoadInfo, IpcSender<NewLayoutInfo>),
     /// Requests that the constellation set the contents of the clipboard
     SetClipboardContents(String),
     /// Mark a new document as active
@@ -114,8 +151,28 @@ pub enum ScriptMsg {
     SetDocumentState(PipelineId, DocumentState),
     /// Update the pipeline Url, which can change after redirections.
     SetFinalUrl(PipelineId, Url),
-    /// Check if an alert dialog box should be presented
-    Alert(PipelineId, String, IpcSender<bool>),
+    /// Ask the embedder to show an alert, confirm or prompt dialog for a pipeline.
+    /// The user's answer is sent back once they gave it.
+    ShowDialog(PipelineId, SimpleDialog, IpcSender<DialogResponse>),
+    /// The user asked not to be shown any more dialogs from the origin of a pipeline.
+    PreventAdditionalDialogs(PipelineId),
+    /// The embedder of a mozbrowser iframe answered the oldest dialog shown in it.
+    AnswerModalPrompt(PipelineId, DialogResponse),
+    /// Ask the user whether a pipeline may capture from a microphone (first
+    /// boolean) and a camera (second boolean).
+    RequestMediaPermission(PipelineId, bool, bool, IpcSender<bool>),
+    /// Ask the embedder whether the storage of a pipeline's origin may be made persistent.
+    RequestPersistentStorage(PipelineId, IpcSender<bool>),
+    /// Ask the embedder to share data from a pipeline through the platform's share mechanism.
+    /// Whether the data was shared is sent back once the embedder is done.
+    Share(PipelineId, ShareData, IpcSender<Result<(), ShareError>>),
+    /// Ask the user to choose a USB device that a pipeline may use, from devices given by
+    /// their id and a label. The id of the chosen device is sent back, or `None`.
+    ChooseUsbDevice(PipelineId, Vec<(String, String)>, IpcSender<Option<String>>),
+    /// Ask the embedder to show a platform picker for a date, time or color
+    /// control, starting from the control's current value. The picked value is
+    /// sent back, or `None` if the picker was dismissed or is unavailable.
+    ShowInputPicker(PipelineId, InputPickerKind, String, IpcSender<Option<String>>),
     /// Scroll a page in a window
     ScrollFragmentPoint(PipelineId, LayerId, Point2D<f32>, bool),
     /// Set title of current page
@@ -139,7 +196,7 @@ pub enum ScriptMsg {
     PipelineExited(PipelineId),
     /// Send messages from postMessage calls from serviceworker
     /// to constellation for storing in service worker manager
-    ForwardDOMMessage(DOMMessage, Url),
+    ForwardDOMMessage(DOMMessage, Option<ClientInfo>, Url),
     /// Store the data required to activate a service worker for the given scope
     RegisterServiceWorker(ScopeThings, Url),
     /// Requests that the compositor shut down.
@@ -161,12 +218,48 @@ pub struct ScopeThings {
     pub devtools_chan: Option<IpcSender<ScriptToDevtoolsControlMsg>>,
     /// service worker id
     pub worker_id: WorkerId,
+    /// how the registration's scripts may be served from a cache during updates
+    pub update_via_cache: UpdateViaCache,
+}
+
+/// https://w3c.github.io/ServiceWorker/#dfn-update-via-cache
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum UpdateViaCache {
+    /// Only imported scripts may come from a cache
+    Imports,
+    /// The main and imported scripts may come from a cache
+    All,
+    /// Every script is fetched from the network
+    None,
+}
+
+/// A script stored in a registration's script resource map
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ServiceWorkerScript {
+    /// url of the response, after redirects
+    pub final_url: Url,
+    /// the script's source bytes
+    pub source: Vec<u8>,
+    /// the value of the response's Service-Worker-Allowed header, if any
+    pub service_worker_allowed: Option<String>,
 }
 
 /// Message that gets passed to service worker scope on postMessage
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct DOMMessage(pub Vec<u8>);
 
+/// The window client that posted a message to a service worker
+/// https://w3c.github.io/ServiceWorker/#client-interface
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ClientInfo {
+    /// the client's id
+    pub id: String,
+    /// the client's creation url
+    pub url: Url,
+    /// pipeline of the client's window, which receives its messages
+    pub pipeline_id: PipelineId,
+}
+
 /// Channels to allow service worker manager to communicate with constellation and resource thread
 pub struct SWManagerSenders {
     /// sender for communicating with constellation
@@ -183,7 +276,15 @@ pub enum ServiceWorkerMsg {
     /// Timeout message sent by active service workers
     Timeout(Url),
     /// Message sent by constellation to forward to a running service worker
-    ForwardDOMMessage(DOMMessage, Url),
+    ForwardDOMMessage(DOMMessage, Option<ClientInfo>, Url),
+    /// Message posted by the service worker of the given scope to one of its clients
+    PostMessageToClient(PipelineId, DOMMessage, Url),
+    /// Replace the script resource map of the registration for the given scope
+    StoreScripts(Url, HashMap<Url, ServiceWorkerScript>),
+    /// The main script of the registration for the given scope may not control that scope
+    RejectRegistration(Url),
+    /// The active worker of the registration for the given scope claims its clients
+    ClaimClients(Url),
     /// Exit the service worker manager
     Exit,
 }
@@ -192,6 +293,10 @@ pub enum ServiceWorkerMsg {
 #[derive(Deserialize, Serialize)]
 pub enum SWManagerMsg {
     /// Provide the constellation with a means of communicating with the Service Worker Manager
-    OwnSender(IpcSender<ServiceWorkerMsg>)
-
+    OwnSender(IpcSender<ServiceWorkerMsg>),
+    /// Message posted by the service worker of the given scope to the client in a pipeline
+    PostMessageToClient(PipelineId, DOMMessage, Url),
+    /// The worker with the given script url claims the clients of the given scope, which
+    /// is one of the registered scopes listed
+    ClaimClients(Url, Url, Vec<Url>),
 }
@@ -45,7 +45,11 @@ pub extern crate script_traits;
 pub extern crate script_layout_interface;
 pub extern crate style;
 pub extern crate url;
+pub extern crate usb;
+pub extern crate usb_traits;
 pub extern crate util;
+pub extern crate webrtc;
+pub extern crate webrtc_traits;
 
 #[cfg(feature = "webdriver")]
 extern crate webdriver_server;
@@ -61,6 +65,8 @@ fn webdriver(port: u16, constellation: Sender<ConstellationMsg>) {
 #[cfg(not(feature = "webdriver"))]
 fn webdriver(_port: u16, _constellation: Sender<ConstellationMsg>) { }
 
+use canvas::webxr_thread::WebXRThreadFactory;
+use canvas_traits::webxr::WebXRMsg;
 use compositing::{CompositorProxy, IOCompositor};
 use compositing::compositor_thread::InitialCompositorState;
 use compositing::windowing::WindowEvent;
@@ -88,9 +94,13 @@ use script_traits::{ConstellationMsg, SWManagerSenders, ScriptMsg};
 use std::cmp::max;
 use std::rc::Rc;
 use std::sync::mpsc::Sender;
+use usb::UsbThreadFactory;
+use usb_traits::UsbMethodMsg;
 use util::opts;
 use util::prefs::PREFS;
 use util::resource_files::resources_dir_path;
+use webrtc::WebRTCThreadFactory;
+use webrtc_traits::WebRTCMsg;
 
 pub use gleam::gl;
 
@@ -124,7 +134,8 @@ impl<Window> Browser<Window> where Window: WindowMethods + 'static {
             window.create_compositor_channel();
         let supports_clipboard = window.supports_clipboard();
         let time_profiler_chan = profile_time::Profiler::create(&opts.time_profiling,
-                                                                opts.time_profiler_trace_path.clone());
+                                                                opts.time_profiler_trace_path.clone(),
+                                                                opts.time_profiler_chrome_trace_path.clone());
         let mem_profiler_chan = profile_mem::Profiler::create(opts.mem_profiler_period);
         if let Some(port) = opts.debugger_port {
             debugger::start_server(port)
@@ -250,6 +261,9 @@ fn create_constellation(opts: opts::Opts,
                         webrender_api_sender: Option<webrender_traits::RenderApiSender>)
                         -> (Sender<ConstellationMsg>, SWManagerSenders) {
     let bluetooth_thread: IpcSender<BluetoothMethodMsg> = BluetoothThreadFactory::new();
+    let usb_thread: IpcSender<UsbMethodMsg> = UsbThreadFactory::new();
+    let webrtc_thread: IpcSender<WebRTCMsg> = WebRTCThreadFactory::new();
+    let webxr_thread: IpcSender<WebXRMsg> = WebXRThreadFactory::new();
 
     let (public_resource_threads, private_resource_threads) =
         new_resource_threads(opts.user_agent.clone(),
@@ -267,6 +281,9 @@ fn create_constellation(opts: opts::Opts,
         compositor_proxy: compositor_proxy,
         devtools_chan: devtools_chan,
         bluetooth_thread: bluetooth_thread,
+        usb_thread: usb_thread,
+        webrtc_thread: webrtc_thread,
+        webxr_thread: webxr_thread,
         image_cache_thread: image_cache_thread,
         font_cache_thread: font_cache_thread,
         public_resource_threads: public_resource_threads,
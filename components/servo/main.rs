@@ -83,6 +83,23 @@ fn install_crash_handler() {
 fn install_crash_handler() {
 }
 
+#[cfg(not(target_os = "android"))]
+fn install_trace_toggle_handler() {
+    use servo::profile::chrome_trace;
+    use sig::ffi::Sig;
+
+    fn handler(_sig: i32) {
+        chrome_trace::toggle_recording();
+    }
+
+    // `kill -USR2` pauses and resumes recording of the chrome://tracing trace.
+    signal!(Sig::USR2, handler);
+}
+
+#[cfg(target_os = "android")]
+fn install_trace_toggle_handler() {
+}
+
 fn main() {
     install_crash_handler();
 
@@ -126,6 +143,10 @@ fn main() {
 
     setup_logging();
 
+    if opts::get().time_profiler_chrome_trace_path.is_some() {
+        install_trace_toggle_handler();
+    }
+
     if let Some(token) = content_process_token {
         return servo::run_content_process(token)
     }
@@ -8,15 +8,16 @@ use bezier::Bezier;
 use context::SharedStyleContext;
 use dom::OpaqueNode;
 use euclid::point::Point2D;
-use keyframes::{KeyframesStep, KeyframesStepValue};
+use keyframes::{KeyframesAnimation, KeyframesStep, KeyframesStepValue};
 use properties::{self, ComputedValues, Importance};
 use properties::animated_properties::{AnimatedProperty, TransitionProperty};
 use properties::longhands::animation_direction::computed_value::AnimationDirection;
+use properties::longhands::animation_fill_mode::computed_value::AnimationFillMode;
 use properties::longhands::animation_iteration_count::computed_value::AnimationIterationCount;
 use properties::longhands::animation_play_state::computed_value::AnimationPlayState;
 use properties::longhands::transition_timing_function::computed_value::StartEnd;
 use properties::longhands::transition_timing_function::computed_value::TransitionTimingFunction;
-use selector_matching::ApplicableDeclarationBlock;
+use selector_matching::{ApplicableDeclarationBlock, UNLAYERED};
 use std::sync::Arc;
 use std::sync::mpsc::Sender;
 use string_cache::Atom;
@@ -25,15 +26,26 @@ use values::computed::Time;
 
 /// This structure represents a keyframes animation current iteration state.
 ///
-/// If the iteration count is infinite, there's no other state, otherwise we
-/// have to keep track the current iteration and the max iteration count.
+/// We keep track of the current iteration, and of the max iteration count if
+/// the iteration count is not infinite.
 #[derive(Debug, Clone)]
 pub enum KeyframesIterationState {
-    Infinite,
+    // current
+    Infinite(u32),
     // current, max
     Finite(u32, u32),
 }
 
+impl KeyframesIterationState {
+    /// Returns the number of iterations that have been completed.
+    pub fn current(&self) -> u32 {
+        match *self {
+            KeyframesIterationState::Infinite(current) |
+            KeyframesIterationState::Finite(current, _) => current,
+        }
+    }
+}
+
 /// This structure represents wether an animation is actually running.
 ///
 /// An animation can be running, or paused at a given time.
@@ -66,6 +78,16 @@ pub struct KeyframesAnimationState {
     pub direction: AnimationDirection,
     /// The current animation direction. This can only be `normal` or `reverse`.
     pub current_direction: AnimationDirection,
+    /// Whether the style of the animation applies before it starts and after
+    /// it finishes.
+    pub fill_mode: AnimationFillMode,
+    /// Whether the delay of this animation has elapsed, and the
+    /// `animationstart` event has been sent.
+    pub started: bool,
+    /// Whether this animation has run all its iterations. Finished animations
+    /// are kept around, so that they fill forwards and don't restart when the
+    /// node is restyled.
+    pub finished: bool,
     /// Werther this keyframe animation is outdated due to a restyle.
     pub expired: bool,
     /// The original cascade style, needed to compute the generated keyframes of
@@ -83,21 +105,28 @@ impl KeyframesAnimationState {
         debug!("KeyframesAnimationState::tick");
         debug_assert!(!self.expired);
 
-        self.started_at += self.duration + self.delay;
         match self.running_state {
             // If it's paused, don't update direction or iteration count.
-            KeyframesRunningState::Paused(_) => return true,
+            KeyframesRunningState::Paused(_) => {
+                self.started_at += self.duration;
+                return true
+            }
             KeyframesRunningState::Running => {},
         }
 
-        if let KeyframesIterationState::Finite(ref mut current, ref max) = self.iteration_state {
-            *current += 1;
-            // NB: This prevent us from updating the direction, which might be
-            // needed for the correct handling of animation-fill-mode.
-            if *current >= *max {
-                return false;
+        match self.iteration_state {
+            KeyframesIterationState::Finite(ref mut current, ref max) => {
+                *current += 1;
+                // NB: This prevent us from updating the direction and the
+                // start time, which are needed to fill forwards.
+                if *current >= *max {
+                    return false;
+                }
             }
+            KeyframesIterationState::Infinite(ref mut current) => *current += 1,
         }
+        // The delay only applies before the first iteration.
+        self.started_at += self.duration;
 
         // Update the next iteration direction if applicable.
         match self.direction {
@@ -136,6 +165,8 @@ impl KeyframesAnimationState {
         let old_direction = self.current_direction;
         let old_running_state = self.running_state.clone();
         let old_iteration_state = self.iteration_state.clone();
+        let old_started = self.started;
+        let old_finished = self.finished;
         *self = other.clone();
 
         let mut new_started_at = old_started_at;
@@ -158,19 +189,41 @@ impl KeyframesAnimationState {
 
         // Don't update the iteration count, just the iteration limit.
         // TODO: see how changing the limit affects rendering in other browsers.
-        // We might need to keep the iteration count even when it's infinite.
         match (&mut self.iteration_state, old_iteration_state) {
-            (&mut KeyframesIterationState::Finite(ref mut iters, _), KeyframesIterationState::Finite(old_iters, _))
+            (&mut KeyframesIterationState::Finite(ref mut iters, _), KeyframesIterationState::Finite(old_iters, _)) |
+            (&mut KeyframesIterationState::Infinite(ref mut iters), KeyframesIterationState::Infinite(old_iters))
                 => *iters = old_iters,
             _ => {}
         }
 
         self.current_direction = old_direction;
         self.started_at = new_started_at;
+        self.started = old_started;
+        self.finished = old_finished;
+    }
+
+    /// Returns the time this animation has been running for, excluding its
+    /// delay, as reported in animation events.
+    pub fn elapsed_time(&self) -> f64 {
+        self.duration * self.iteration_state.current() as f64
+    }
+
+    fn fills_backwards(&self) -> bool {
+        match self.fill_mode {
+            AnimationFillMode::backwards | AnimationFillMode::both => true,
+            AnimationFillMode::none | AnimationFillMode::forwards => false,
+        }
+    }
+
+    fn fills_forwards(&self) -> bool {
+        match self.fill_mode {
+            AnimationFillMode::forwards | AnimationFillMode::both => true,
+            AnimationFillMode::none | AnimationFillMode::backwards => false,
+        }
     }
 
     #[inline]
-    fn is_paused(&self) -> bool {
+    pub fn is_paused(&self) -> bool {
         match self.running_state {
             KeyframesRunningState::Paused(..) => true,
             KeyframesRunningState::Running => false,
@@ -225,6 +278,16 @@ impl Animation {
             Animation::Keyframes(_, _, ref state) => state.is_paused(),
         }
     }
+
+    /// Returns true if this is a keyframes animation that has run all its
+    /// iterations, and doesn't need any more ticks.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        match *self {
+            Animation::Transition(..) => false,
+            Animation::Keyframes(_, _, ref state) => state.finished,
+        }
+    }
 }
 
 
@@ -248,7 +311,7 @@ pub struct PropertyAnimation {
 impl PropertyAnimation {
     /// Creates a new property animation for the given transition index and old and new styles.
     /// Any number of animations may be returned, from zero (if the property did not animate) to
-    /// one (for a single transition property) to arbitrarily many (for `all`).
+    /// one (for a single transition property) to arbitrarily many (for shorthands and `all`).
     pub fn from_transition(transition_index: usize,
                            old_style: &ComputedValues,
                            new_style: &mut ComputedValues)
@@ -259,20 +322,7 @@ impl PropertyAnimation {
         let timing_function = box_style.transition_timing_function_mod(transition_index);
         let duration = box_style.transition_duration_mod(transition_index);
 
-
-        if transition_property != TransitionProperty::All {
-            if let Some(property_animation) =
-                    PropertyAnimation::from_transition_property(transition_property,
-                                                                timing_function,
-                                                                duration,
-                                                                old_style,
-                                                                new_style) {
-                result.push(property_animation)
-            }
-            return result
-        }
-
-        TransitionProperty::each(|transition_property| {
+        transition_property.each_longhand(|transition_property| {
             if let Some(property_animation) =
                     PropertyAnimation::from_transition_property(transition_property,
                                                                 timing_function,
@@ -389,8 +439,14 @@ fn compute_style_for_animation_step(context: &SharedStyleContext,
                 importance: Importance::Normal,
                 source_order: 0,
                 specificity: ::std::u32::MAX,
+                layer_order: UNLAYERED,
+                container_conditions: None,
             };
+            // FIXME: container query lengths in keyframes are relative to
+            // the viewport.
             let (computed, _) = properties::cascade(context.viewport_size,
+                                                    None,
+                                                    context.stylist.custom_property_registrations(),
                                                     &[declaration_block],
                                                     false,
                                                     Some(previous_style),
@@ -433,7 +489,7 @@ pub fn maybe_start_animations(context: &SharedStyleContext,
             let animation_start = now + delay as f64;
             let duration = box_style.animation_duration_mod(i).seconds();
             let iteration_state = match box_style.animation_iteration_count_mod(i) {
-                AnimationIterationCount::Infinite => KeyframesIterationState::Infinite,
+                AnimationIterationCount::Infinite => KeyframesIterationState::Infinite(0),
                 AnimationIterationCount::Number(n) => KeyframesIterationState::Finite(0, n),
             };
 
@@ -461,6 +517,9 @@ pub fn maybe_start_animations(context: &SharedStyleContext,
                     running_state: running_state,
                     direction: animation_direction,
                     current_direction: initial_direction,
+                    fill_mode: box_style.animation_fill_mode_mod(i),
+                    started: false,
+                    finished: false,
                     expired: false,
                     cascade_style: new_style.clone(),
                 })).unwrap();
@@ -548,11 +607,39 @@ pub fn update_style_for_animation(context: &SharedStyleContext,
                 return;
             }
 
-            let mut total_progress = (now - started_at) / total_duration;
-            if total_progress < 0. {
-                warn!("Negative progress found for animation {:?}", name);
+            // Before the animation starts and after it finishes, the animated
+            // properties take the values of the keyframe it starts or ends at
+            // if it fills that way, and their values from the cascade otherwise.
+            if state.finished || now < started_at {
+                let fills = if state.finished {
+                    state.fills_forwards()
+                } else {
+                    state.fills_backwards()
+                };
+                if !fills && !state.finished {
+                    return;
+                }
+                let keyframe_style;
+                let target_style = if fills {
+                    let starts_at_first_keyframe = state.current_direction == AnimationDirection::normal;
+                    let step = if starts_at_first_keyframe != state.finished {
+                        &animation.steps[0]
+                    } else {
+                        animation.steps.last().unwrap()
+                    };
+                    keyframe_style = compute_style_for_animation_step(context,
+                                                                      step,
+                                                                      &**style,
+                                                                      &state.cascade_style);
+                    &keyframe_style
+                } else {
+                    &*state.cascade_style
+                };
+                set_animated_properties(animation, target_style, style);
                 return;
             }
+
+            let mut total_progress = (now - started_at) / total_duration;
             if total_progress > 1. {
                 total_progress = 1.;
             }
@@ -662,6 +749,26 @@ pub fn update_style_for_animation(context: &SharedStyleContext,
     }
 }
 
+/// Sets the properties that a keyframes animation animates to their values in
+/// `target_style`.
+fn set_animated_properties(animation: &KeyframesAnimation,
+                           target_style: &ComputedValues,
+                           style: &mut Arc<ComputedValues>) {
+    let mut new_style = (*style).clone();
+    for transition_property in &animation.properties_changed {
+        let property_animation =
+            PropertyAnimation::from_transition_property(*transition_property,
+                                                        TransitionTimingFunction::Steps(1, StartEnd::Start),
+                                                        Time(1.),
+                                                        &**style,
+                                                        target_style);
+        if let Some(property_animation) = property_animation {
+            property_animation.update(Arc::make_mut(&mut new_style), 1.);
+        }
+    }
+    *style = new_style;
+}
+
 /// Update the style in the node when it finishes.
 pub fn complete_expired_transitions(node: OpaqueNode, style: &mut Arc<ComputedValues>,
                                     context: &SharedStyleContext) -> bool {
@@ -672,7 +779,8 @@ pub fn complete_expired_transitions(node: OpaqueNode, style: &mut Arc<ComputedVa
         had_animations_to_expire = animations_to_expire.is_some();
         if let Some(ref animations) = animations_to_expire {
             for animation in *animations {
-                // TODO: support animation-fill-mode
+                // Finished keyframes animations stay running until they are
+                // removed from the style, so only transitions complete here.
                 if let Animation::Transition(_, _, ref frame, _) = *animation {
                     frame.property_animation.update(Arc::make_mut(style), 1.0);
                 }
@@ -0,0 +1,434 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! [Container queries][cq].
+//!
+//! [cq]: https://drafts.csswg.org/css-contain-3/#container-queries
+
+use app_units::Au;
+use cssparser::{Parser, Token};
+use euclid::size::Size2D;
+use properties::ComputedValues;
+use std::ascii::AsciiExt;
+use std::sync::Arc;
+use string_cache::Atom;
+use values::specified;
+
+/// The prelude of an `@container` rule.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct ContainerQuery {
+    /// The name the query container must have, if any.
+    pub name: Option<Atom>,
+    pub condition: ContainerCondition,
+}
+
+/// https://drafts.csswg.org/css-contain-3/#typedef-container-condition
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum ContainerCondition {
+    Feature(SizeFeature),
+    Not(Box<ContainerCondition>),
+    And(Vec<ContainerCondition>),
+    Or(Vec<ContainerCondition>),
+}
+
+/// https://drafts.csswg.org/css-contain-3/#size-container
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum SizeFeature {
+    Length(LengthFeature, Comparison, specified::Length),
+    Orientation(Orientation),
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum LengthFeature {
+    Width,
+    Height,
+    InlineSize,
+    BlockSize,
+}
+
+/// How the size of the container compares to the value of a feature.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+/// The queries of the `@container` rules a style rule is nested in, from the
+/// innermost outwards. All of them must hold for the rule to apply.
+#[derive(Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct ContainerConditions {
+    pub query: Arc<ContainerQuery>,
+    pub outer: Option<Arc<ContainerConditions>>,
+}
+
+/// A query container, as seen from the elements it contains.
+pub struct QueryContainer<'a> {
+    pub style: &'a ComputedValues,
+    /// The size of the content box of the container, or `None` if it hasn't
+    /// been laid out yet.
+    pub size: Option<Size2D<Au>>,
+    /// The size that the container query lengths in queries against the
+    /// container are relative to: that of its own query container, or of the
+    /// viewport.
+    pub length_basis: Size2D<Au>,
+}
+
+/// The axes a query container can be queried in.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Containment {
+    None,
+    InlineSize,
+    Size,
+}
+
+#[cfg(feature = "servo")]
+fn containment(style: &ComputedValues) -> Containment {
+    use properties::longhands::container_type::computed_value::T as container_type;
+    match style.get_box().container_type {
+        container_type::normal => Containment::None,
+        container_type::inline_size => Containment::InlineSize,
+        container_type::size => Containment::Size,
+    }
+}
+
+#[cfg(feature = "servo")]
+fn has_container_name(style: &ComputedValues, name: &Atom) -> bool {
+    style.get_box().container_name.0.contains(name)
+}
+
+// Stylo doesn't support `container-type` and `container-name` yet.
+#[cfg(not(feature = "servo"))]
+fn containment(_style: &ComputedValues) -> Containment {
+    Containment::None
+}
+
+#[cfg(not(feature = "servo"))]
+fn has_container_name(_style: &ComputedValues, _name: &Atom) -> bool {
+    false
+}
+
+/// Whether an element with `style` is a query container, with the name
+/// `name` if given.
+pub fn is_query_container(style: &ComputedValues, name: Option<&Atom>) -> bool {
+    containment(style) != Containment::None && name.map_or(true, |name| has_container_name(style, name))
+}
+
+/// Returns the size that the container query lengths of the elements in
+/// `container` are relative to. This is the size of the container's content
+/// box in the axes it can be queried in, and of the viewport in the others.
+///
+/// FIXME: each axis should use the nearest container that can be queried in
+/// it, not just the nearest container.
+pub fn container_length_basis(container: &QueryContainer, viewport_size: Size2D<Au>) -> Size2D<Au> {
+    let size = match container.size {
+        Some(size) => size,
+        None => return viewport_size,
+    };
+    let vertical = container.style.writing_mode.is_vertical();
+    let containment = containment(container.style);
+    Size2D::new(if can_query(containment, !vertical) { size.width } else { viewport_size.width },
+                if can_query(containment, vertical) { size.height } else { viewport_size.height })
+}
+
+/// Whether a container with `containment` can be queried in its inline axis
+/// or its block axis.
+fn can_query(containment: Containment, inline_axis: bool) -> bool {
+    match containment {
+        Containment::None => false,
+        Containment::InlineSize => inline_axis,
+        Containment::Size => true,
+    }
+}
+
+impl ContainerQuery {
+    pub fn parse(input: &mut Parser) -> Result<ContainerQuery, ()> {
+        let name = input.try(|input| {
+            let name = try!(input.expect_ident());
+            if is_reserved_name(&name) {
+                return Err(())
+            }
+            Ok(Atom::from(&*name))
+        }).ok();
+        Ok(ContainerQuery {
+            name: name,
+            condition: try!(ContainerCondition::parse(input)),
+        })
+    }
+
+    /// Evaluates the query against the query container selected for it, if
+    /// there is one.
+    pub fn evaluate(&self, container: Option<&QueryContainer>, viewport_size: Size2D<Au>) -> bool {
+        match container {
+            // Unknown results don't match.
+            Some(container) => self.condition.evaluate(container, viewport_size) == Some(true),
+            None => false,
+        }
+    }
+}
+
+fn is_reserved_name(name: &str) -> bool {
+    ["none", "and", "or", "not"].iter().any(|keyword| name.eq_ignore_ascii_case(keyword))
+}
+
+impl ContainerCondition {
+    fn parse(input: &mut Parser) -> Result<ContainerCondition, ()> {
+        if input.try(|input| input.expect_ident_matching("not")).is_ok() {
+            return Ok(ContainerCondition::Not(Box::new(try!(ContainerCondition::parse_in_parens(input)))))
+        }
+
+        let first = try!(ContainerCondition::parse_in_parens(input));
+        let is_and = match input.try(|input| input.expect_ident()) {
+            Ok(ref operator) if operator.eq_ignore_ascii_case("and") => true,
+            Ok(ref operator) if operator.eq_ignore_ascii_case("or") => false,
+            _ => return Ok(first),
+        };
+
+        // `and` and `or` can't be mixed without parentheses.
+        let mut conditions = vec![first, try!(ContainerCondition::parse_in_parens(input))];
+        let operator = if is_and { "and" } else { "or" };
+        while input.try(|input| input.expect_ident_matching(operator)).is_ok() {
+            conditions.push(try!(ContainerCondition::parse_in_parens(input)));
+        }
+        Ok(if is_and {
+            ContainerCondition::And(conditions)
+        } else {
+            ContainerCondition::Or(conditions)
+        })
+    }
+
+    fn parse_in_parens(input: &mut Parser) -> Result<ContainerCondition, ()> {
+        try!(input.expect_parenthesis_block());
+        input.parse_nested_block(|input| {
+            if let Ok(feature) = input.try(parse_feature) {
+                return Ok(feature)
+            }
+            let condition = try!(ContainerCondition::parse(input));
+            try!(input.expect_exhausted());
+            Ok(condition)
+        })
+    }
+
+    /// Returns `None` if the result is unknown, which is the case if the
+    /// container can't be queried for a feature.
+    fn evaluate(&self, container: &QueryContainer, viewport_size: Size2D<Au>) -> Option<bool> {
+        match *self {
+            ContainerCondition::Feature(ref feature) => feature.evaluate(container, viewport_size),
+            ContainerCondition::Not(ref condition) => {
+                condition.evaluate(container, viewport_size).map(|result| !result)
+            }
+            ContainerCondition::And(ref conditions) => {
+                let mut result = Some(true);
+                for condition in conditions {
+                    match condition.evaluate(container, viewport_size) {
+                        Some(false) => return Some(false),
+                        None => result = None,
+                        Some(true) => {}
+                    }
+                }
+                result
+            }
+            ContainerCondition::Or(ref conditions) => {
+                let mut result = Some(false);
+                for condition in conditions {
+                    match condition.evaluate(container, viewport_size) {
+                        Some(true) => return Some(true),
+                        None => result = None,
+                        Some(false) => {}
+                    }
+                }
+                result
+            }
+        }
+    }
+}
+
+/// Parses the contents of a `<size-feature>`, in either the plain or the
+/// range syntax. A range with two values is parsed as two features.
+fn parse_feature(input: &mut Parser) -> Result<ContainerCondition, ()> {
+    // `<value> <op> <name>` and `<value> <op> <name> <op> <value>`.
+    if let Ok(value) = input.try(specified::Length::parse) {
+        let first_comparison = try!(parse_comparison(input));
+        let feature = try!(parse_length_feature_name(input));
+        let first = SizeFeature::Length(feature, first_comparison.reverse(), value);
+        if input.is_exhausted() {
+            return Ok(ContainerCondition::Feature(first))
+        }
+        let second_comparison = try!(parse_comparison(input));
+        // Both comparisons have to point the same way.
+        if first_comparison == Comparison::Equal || second_comparison == Comparison::Equal ||
+           first_comparison.is_less() != second_comparison.is_less() {
+            return Err(())
+        }
+        let second = SizeFeature::Length(feature, second_comparison, try!(specified::Length::parse(input)));
+        try!(input.expect_exhausted());
+        return Ok(ContainerCondition::And(vec![ContainerCondition::Feature(first),
+                                               ContainerCondition::Feature(second)]))
+    }
+
+    let name = try!(input.expect_ident());
+    let feature = if input.try(|input| input.expect_colon()).is_ok() {
+        // `<name>: <value>`
+        if name.eq_ignore_ascii_case("orientation") {
+            let orientation = match_ignore_ascii_case! { try!(input.expect_ident()),
+                "portrait" => Orientation::Portrait,
+                "landscape" => Orientation::Landscape,
+                _ => return Err(())
+            };
+            SizeFeature::Orientation(orientation)
+        } else {
+            let (name, comparison) = if starts_with_ignore_ascii_case(&name, "min-") {
+                (&name[4..], Comparison::GreaterOrEqual)
+            } else if starts_with_ignore_ascii_case(&name, "max-") {
+                (&name[4..], Comparison::LessOrEqual)
+            } else {
+                (&*name, Comparison::Equal)
+            };
+            let feature = try!(length_feature_from_name(name));
+            SizeFeature::Length(feature, comparison, try!(specified::Length::parse_non_negative(input)))
+        }
+    } else {
+        // `<name> <op> <value>`
+        let feature = try!(length_feature_from_name(&name));
+        let comparison = try!(parse_comparison(input));
+        SizeFeature::Length(feature, comparison, try!(specified::Length::parse(input)))
+    };
+    try!(input.expect_exhausted());
+    Ok(ContainerCondition::Feature(feature))
+}
+
+fn starts_with_ignore_ascii_case(string: &str, prefix: &str) -> bool {
+    string.len() >= prefix.len() && string[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+fn parse_length_feature_name(input: &mut Parser) -> Result<LengthFeature, ()> {
+    length_feature_from_name(&try!(input.expect_ident()))
+}
+
+fn length_feature_from_name(name: &str) -> Result<LengthFeature, ()> {
+    match_ignore_ascii_case! { name,
+        "width" => Ok(LengthFeature::Width),
+        "height" => Ok(LengthFeature::Height),
+        "inline-size" => Ok(LengthFeature::InlineSize),
+        "block-size" => Ok(LengthFeature::BlockSize),
+        _ => Err(())
+    }
+}
+
+/// Parses `<`, `<=`, `=`, `>=` or `>`.
+fn parse_comparison(input: &mut Parser) -> Result<Comparison, ()> {
+    let (strict, or_equal) = match try!(input.next()) {
+        Token::Delim('<') => (Comparison::Less, Comparison::LessOrEqual),
+        Token::Delim('>') => (Comparison::Greater, Comparison::GreaterOrEqual),
+        Token::Delim('=') => return Ok(Comparison::Equal),
+        _ => return Err(()),
+    };
+    // There can't be any whitespace in `<=` and `>=`.
+    if input.try(|input| {
+        match input.next_including_whitespace() {
+            Ok(Token::Delim('=')) => Ok(()),
+            _ => Err(()),
+        }
+    }).is_ok() {
+        Ok(or_equal)
+    } else {
+        Ok(strict)
+    }
+}
+
+impl Comparison {
+    /// The comparison with its operands swapped, so that `100px < width`
+    /// becomes `width > 100px`.
+    fn reverse(self) -> Comparison {
+        match self {
+            Comparison::Less => Comparison::Greater,
+            Comparison::LessOrEqual => Comparison::GreaterOrEqual,
+            Comparison::Equal => Comparison::Equal,
+            Comparison::GreaterOrEqual => Comparison::LessOrEqual,
+            Comparison::Greater => Comparison::Less,
+        }
+    }
+
+    fn is_less(self) -> bool {
+        self == Comparison::Less || self == Comparison::LessOrEqual
+    }
+
+    fn evaluate(self, size: Au, value: Au) -> bool {
+        match self {
+            Comparison::Less => size < value,
+            Comparison::LessOrEqual => size <= value,
+            Comparison::Equal => size == value,
+            Comparison::GreaterOrEqual => size >= value,
+            Comparison::Greater => size > value,
+        }
+    }
+}
+
+impl SizeFeature {
+    fn evaluate(&self, container: &QueryContainer, viewport_size: Size2D<Au>) -> Option<bool> {
+        let size = match container.size {
+            Some(size) => size,
+            None => return None,
+        };
+        let containment = containment(container.style);
+        let vertical = container.style.writing_mode.is_vertical();
+        match *self {
+            SizeFeature::Length(feature, comparison, ref value) => {
+                let (inline_axis, container_size) = match feature {
+                    LengthFeature::Width => (!vertical, size.width),
+                    LengthFeature::Height => (vertical, size.height),
+                    LengthFeature::InlineSize => (true, if vertical { size.height } else { size.width }),
+                    LengthFeature::BlockSize => (false, if vertical { size.width } else { size.height }),
+                };
+                if !can_query(containment, inline_axis) {
+                    return None
+                }
+                Some(comparison.evaluate(container_size, compute_length(value, container, viewport_size)))
+            }
+            SizeFeature::Orientation(orientation) => {
+                if containment != Containment::Size {
+                    return None
+                }
+                let portrait = size.height >= size.width;
+                Some(portrait == (orientation == Orientation::Portrait))
+            }
+        }
+    }
+}
+
+/// Computes a length in a query. Relative lengths are relative to the
+/// query container, as they would be in its computed values.
+fn compute_length(length: &specified::Length, container: &QueryContainer, viewport_size: Size2D<Au>) -> Au {
+    let font_size = container.style.get_font().clone_font_size();
+    let root_font_size = container.style.root_font_size();
+    match *length {
+        specified::Length::Absolute(value) => value,
+        specified::Length::FontRelative(value) => value.to_computed_value(font_size, root_font_size),
+        specified::Length::ViewportPercentage(value) => value.to_computed_value(viewport_size),
+        specified::Length::ContainerPercentage(value) => value.to_computed_value(container.length_basis),
+        specified::Length::Calc(ref value, range) => {
+            let value = value.compute_from_viewport_container_and_font_size(viewport_size,
+                                                                            container.length_basis,
+                                                                            font_size,
+                                                                            root_font_size);
+            range.clamp(value.length())
+        }
+        specified::Length::ServoCharacterWidth(..) => unreachable!(),
+    }
+}
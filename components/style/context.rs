@@ -62,6 +62,9 @@ pub struct SharedStyleContext {
     /// The current timer for transitions and animations. This is needed to test
     /// them.
     pub timer: Timer,
+
+    /// The content-box sizes of the query containers, as of the last layout.
+    pub container_sizes: Arc<HashMap<OpaqueNode, Size2D<Au>>>,
 }
 
 pub struct LocalStyleContext {
@@ -87,6 +90,16 @@ pub trait StyleContext<'a> {
     fn local_context(&self) -> &LocalStyleContext;
 }
 
+/// The compatibility mode of a document, which the doctype decides.
+/// https://dom.spec.whatwg.org/#concept-document-quirks
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum QuirksMode {
+    Quirks,
+    LimitedQuirks,
+    NoQuirks,
+}
+
 /// Why we're doing reflow.
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub enum ReflowGoal {
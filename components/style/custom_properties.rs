@@ -2,11 +2,15 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-//! Support for [custom properties for cascading variables][custom].
+//! Support for [custom properties for cascading variables][custom], and for registering them
+//! with [`@property`][property] rules.
 //!
 //! [custom]: https://drafts.csswg.org/css-variables/
+//! [property]: https://drafts.css-houdini.org/css-properties-values-api/#at-property-rule
 
-use cssparser::{Delimiter, Parser, SourcePosition, ToCss, Token, TokenSerializationType};
+use cssparser::{AtRuleParser, DeclarationListParser, DeclarationParser, Delimiter, Parser};
+use cssparser::{SourcePosition, ToCss, Token, TokenSerializationType};
+use parser::{ParserContext, log_css_error};
 use properties::DeclaredValue;
 use std::ascii::AsciiExt;
 use std::borrow::Cow;
@@ -14,6 +18,7 @@ use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
 use string_cache::Atom;
+use values::specified;
 
 // Does not include the `--` prefix
 pub type Name = Atom;
@@ -52,7 +57,7 @@ pub struct BorrowedSpecifiedValue<'a> {
     references: Option<&'a HashSet<Name>>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct ComputedValue {
     css: String,
@@ -108,6 +113,218 @@ impl ComputedValue {
     fn push_variable(&mut self, variable: &ComputedValue) {
         self.push(&variable.css, variable.first_token_type, variable.last_token_type)
     }
+
+    fn borrow(&self) -> BorrowedSpecifiedValue {
+        BorrowedSpecifiedValue {
+            css: &self.css,
+            first_token_type: self.first_token_type,
+            last_token_type: self.last_token_type,
+            references: None
+        }
+    }
+}
+
+/// A data type that the value of a registered custom property can be.
+///
+/// https://drafts.css-houdini.org/css-properties-values-api/#supported-names
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum SyntaxComponent {
+    Length,
+    Number,
+    Integer,
+    Percentage,
+    LengthPercentage,
+    Angle,
+    Color,
+    CustomIdent,
+}
+
+impl SyntaxComponent {
+    fn from_data_type_name(name: &str) -> Result<SyntaxComponent, ()> {
+        match name {
+            "length" => Ok(SyntaxComponent::Length),
+            "number" => Ok(SyntaxComponent::Number),
+            "integer" => Ok(SyntaxComponent::Integer),
+            "percentage" => Ok(SyntaxComponent::Percentage),
+            "length-percentage" => Ok(SyntaxComponent::LengthPercentage),
+            "angle" => Ok(SyntaxComponent::Angle),
+            "color" => Ok(SyntaxComponent::Color),
+            "custom-ident" => Ok(SyntaxComponent::CustomIdent),
+            _ => Err(()),
+        }
+    }
+
+    fn parse(&self, input: &mut Parser) -> Result<(), ()> {
+        match *self {
+            SyntaxComponent::Length => specified::Length::parse(input).map(|_| ()),
+            SyntaxComponent::Number => specified::parse_number(input).map(|_| ()),
+            SyntaxComponent::Integer => specified::parse_integer(input).map(|_| ()),
+            SyntaxComponent::Percentage => input.expect_percentage().map(|_| ()),
+            SyntaxComponent::LengthPercentage => specified::LengthOrPercentage::parse(input).map(|_| ()),
+            SyntaxComponent::Angle => specified::Angle::parse(input).map(|_| ()),
+            SyntaxComponent::Color => specified::CSSColor::parse(input).map(|_| ()),
+            SyntaxComponent::CustomIdent => {
+                let ident = try!(input.expect_ident());
+                match_ignore_ascii_case! { ident,
+                    "initial" => Err(()),
+                    "inherit" => Err(()),
+                    "unset" => Err(()),
+                    "default" => Err(()),
+                    _ => Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// The `syntax` descriptor of an `@property` rule.
+///
+/// FIXME: Multipliers (`+` and `#`) and literal identifiers are not supported.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum PropertySyntax {
+    /// `*`, which accepts any value.
+    Universal,
+    /// Data types separated by `|`, one of which the value must be.
+    DataTypes(Vec<SyntaxComponent>),
+}
+
+impl PropertySyntax {
+    pub fn parse(syntax: &str) -> Result<PropertySyntax, ()> {
+        let syntax = syntax.trim();
+        if syntax == "*" {
+            return Ok(PropertySyntax::Universal)
+        }
+        let mut components = vec![];
+        for component in syntax.split('|') {
+            let component = component.trim();
+            if component.len() < 2 || !component.starts_with('<') || !component.ends_with('>') {
+                return Err(())
+            }
+            components.push(try!(SyntaxComponent::from_data_type_name(
+                &component[1..component.len() - 1])));
+        }
+        Ok(PropertySyntax::DataTypes(components))
+    }
+
+    /// Returns true if the given computed value is valid for this syntax.
+    pub fn matches(&self, css: &str) -> bool {
+        match *self {
+            PropertySyntax::Universal => true,
+            PropertySyntax::DataTypes(ref components) => {
+                components.iter().any(|component| {
+                    let mut input = Parser::new(css);
+                    component.parse(&mut input).is_ok() && input.is_exhausted()
+                })
+            }
+        }
+    }
+}
+
+/// A custom property registered with an `@property` rule.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct PropertyRule {
+    pub name: Name,
+    pub syntax: PropertySyntax,
+    pub inherits: bool,
+    /// The value of the property when it is not set, which it otherwise doesn't have.
+    pub initial_value: Option<ComputedValue>,
+}
+
+/// The registered custom properties, by name.
+pub type Registrations = HashMap<Name, Arc<PropertyRule>>;
+
+pub fn parse_property_block(name: Name, context: &ParserContext, input: &mut Parser)
+                            -> Result<PropertyRule, ()> {
+    let mut syntax = None;
+    let mut inherits = None;
+    let mut initial_value = None;
+    let mut iter = DeclarationListParser::new(input, PropertyRuleParser);
+    while let Some(declaration) = iter.next() {
+        match declaration {
+            Err(range) => {
+                let pos = range.start;
+                let message = format!("Unsupported @property descriptor declaration: '{}'",
+                                      iter.input.slice(range));
+                log_css_error(iter.input, pos, &*message, context);
+            }
+            Ok(PropertyDescriptorDeclaration::Syntax(value)) => syntax = Some(value),
+            Ok(PropertyDescriptorDeclaration::Inherits(value)) => inherits = Some(value),
+            Ok(PropertyDescriptorDeclaration::InitialValue(value)) => initial_value = Some(value),
+            // https://drafts.css-houdini.org/css-properties-values-api/#initial-value-descriptor
+            Ok(PropertyDescriptorDeclaration::DependentInitialValue) => return Err(()),
+        }
+    }
+
+    // The rule is invalid without `syntax` and `inherits`, or without a valid initial value
+    // when the syntax does not accept any value.
+    let (syntax, inherits) = match (syntax, inherits) {
+        (Some(syntax), Some(inherits)) => (syntax, inherits),
+        _ => return Err(()),
+    };
+    match initial_value {
+        Some(ref value) if !syntax.matches(&value.css) => return Err(()),
+        None if syntax != PropertySyntax::Universal => return Err(()),
+        _ => {}
+    }
+    Ok(PropertyRule {
+        name: name,
+        syntax: syntax,
+        inherits: inherits,
+        initial_value: initial_value,
+    })
+}
+
+enum PropertyDescriptorDeclaration {
+    Syntax(PropertySyntax),
+    Inherits(bool),
+    InitialValue(ComputedValue),
+    /// An initial value that refers to other properties, which makes the rule invalid.
+    DependentInitialValue,
+}
+
+struct PropertyRuleParser;
+
+/// Default methods reject all at rules.
+impl AtRuleParser for PropertyRuleParser {
+    type Prelude = ();
+    type AtRule = PropertyDescriptorDeclaration;
+}
+
+impl DeclarationParser for PropertyRuleParser {
+    type Declaration = PropertyDescriptorDeclaration;
+
+    fn parse_value(&mut self, name: &str, input: &mut Parser) -> Result<PropertyDescriptorDeclaration, ()> {
+        match_ignore_ascii_case! { name,
+            "syntax" => {
+                let syntax = try!(input.expect_string());
+                Ok(PropertyDescriptorDeclaration::Syntax(try!(PropertySyntax::parse(&syntax))))
+            },
+            "inherits" => {
+                let ident = try!(input.expect_ident());
+                match_ignore_ascii_case! { ident,
+                    "true" => Ok(PropertyDescriptorDeclaration::Inherits(true)),
+                    "false" => Ok(PropertyDescriptorDeclaration::Inherits(false)),
+                    _ => Err(())
+                }
+            },
+            "initial-value" => {
+                // The initial value is the same everywhere, so it can't refer to other properties.
+                let value = try!(parse(input));
+                if !value.references.is_empty() {
+                    return Ok(PropertyDescriptorDeclaration::DependentInitialValue)
+                }
+                Ok(PropertyDescriptorDeclaration::InitialValue(ComputedValue {
+                    css: value.css,
+                    first_token_type: value.first_token_type,
+                    last_token_type: value.last_token_type,
+                }))
+            },
+            _ => Err(())
+        }
+    }
 }
 
 pub fn parse(input: &mut Parser) -> Result<SpecifiedValue, ()> {
@@ -306,6 +523,7 @@ fn parse_var_function<'i, 't>(input: &mut Parser<'i, 't>,
 /// unless another with the same name was already there.
 pub fn cascade<'a>(custom_properties: &mut Option<HashMap<&'a Name, BorrowedSpecifiedValue<'a>>>,
                    inherited: &'a Option<Arc<HashMap<Name, ComputedValue>>>,
+                   registrations: &'a Registrations,
                    seen: &mut HashSet<&'a Name>,
                    name: &'a Name,
                    specified_value: &'a DeclaredValue<SpecifiedValue>) {
@@ -314,17 +532,19 @@ pub fn cascade<'a>(custom_properties: &mut Option<HashMap<&'a Name, BorrowedSpec
         let map = match *custom_properties {
             Some(ref mut map) => map,
             None => {
-                *custom_properties = Some(match *inherited {
-                    Some(ref inherited) => inherited.iter().map(|(key, inherited_value)| {
-                        (key, BorrowedSpecifiedValue {
-                            css: &inherited_value.css,
-                            first_token_type: inherited_value.first_token_type,
-                            last_token_type: inherited_value.last_token_type,
-                            references: None
-                        })
-                    }).collect(),
+                let mut map: HashMap<_, _> = match *inherited {
+                    Some(ref inherited) => inherited.iter().filter(|&(key, _)| {
+                        registrations.get(key).map_or(true, |registration| registration.inherits)
+                    }).map(|(key, inherited_value)| (key, inherited_value.borrow())).collect(),
                     None => HashMap::new(),
-                });
+                };
+                // Registered properties that are not inherited start at their initial value.
+                for (key, registration) in registrations {
+                    if let Some(ref initial_value) = registration.initial_value {
+                        map.entry(key).or_insert_with(|| initial_value.borrow());
+                    }
+                }
+                *custom_properties = Some(map);
                 custom_properties.as_mut().unwrap()
             }
         };
@@ -347,13 +567,62 @@ pub fn cascade<'a>(custom_properties: &mut Option<HashMap<&'a Name, BorrowedSpec
 }
 
 pub fn finish_cascade(specified_values_map: Option<HashMap<&Name, BorrowedSpecifiedValue>>,
-                      inherited: &Option<Arc<HashMap<Name, ComputedValue>>>)
+                      inherited: &Option<Arc<HashMap<Name, ComputedValue>>>,
+                      seen: &HashSet<&Name>,
+                      registrations: &Registrations)
                       -> Option<Arc<HashMap<Name, ComputedValue>>> {
-    if let Some(mut map) = specified_values_map {
+    let computed = if let Some(mut map) = specified_values_map {
         remove_cycles(&mut map);
         Some(Arc::new(substitute_all(map, inherited)))
     } else {
         inherited.clone()
+    };
+    if registrations.is_empty() {
+        return computed
+    }
+    apply_registrations(computed.unwrap_or_else(|| Arc::new(HashMap::new())),
+                        inherited,
+                        seen,
+                        registrations)
+}
+
+/// Gives registered custom properties the values their registrations call for: the initial
+/// value when they are not set, or not inherited from the parent, and the value they would
+/// have if they were not set when their computed value does not match their syntax.
+fn apply_registrations(mut computed: Arc<HashMap<Name, ComputedValue>>,
+                       inherited: &Option<Arc<HashMap<Name, ComputedValue>>>,
+                       seen: &HashSet<&Name>,
+                       registrations: &Registrations)
+                       -> Option<Arc<HashMap<Name, ComputedValue>>> {
+    for (name, registration) in registrations {
+        let initial_value = registration.initial_value.as_ref();
+        let value = {
+            let current_value = computed.get(name);
+            let value = match (current_value, seen.contains(name)) {
+                (Some(value), true) if registration.syntax.matches(&value.css) => continue,
+                // The value was inherited from the parent, where it was checked already.
+                (Some(_), false) if registration.inherits => continue,
+                // Invalid at computed-value time, so it acts as if it was not set.
+                (Some(_), true) if registration.inherits => {
+                    inherited.as_ref().and_then(|inherited| inherited.get(name)).or(initial_value)
+                }
+                _ => initial_value,
+            };
+            if current_value == value {
+                continue
+            }
+            value.cloned()
+        };
+        let map = Arc::make_mut(&mut computed);
+        match value {
+            Some(value) => map.insert(name.clone(), value),
+            None => map.remove(name),
+        };
+    }
+    if computed.is_empty() {
+        None
+    } else {
+        Some(computed)
     }
 }
 
@@ -35,5 +35,18 @@ bitflags! {
         const IN_PLACEHOLDER_SHOWN_STATE = 0x0100,
         #[doc = "https://html.spec.whatwg.org/multipage/#selector-target"]
         const IN_TARGET_STATE = 0x0200,
+        #[doc = "https://html.spec.whatwg.org/multipage/#selector-valid"]
+        const IN_VALID_STATE = 0x0400,
+        #[doc = "https://html.spec.whatwg.org/multipage/#selector-invalid"]
+        const IN_INVALID_STATE = 0x0800,
+        #[doc = "https://html.spec.whatwg.org/multipage/#selector-required"]
+        const IN_REQUIRED_STATE = 0x1000,
+        #[doc = "https://html.spec.whatwg.org/multipage/#selector-optional"]
+        const IN_OPTIONAL_STATE = 0x2000,
+        #[doc = "https://html.spec.whatwg.org/multipage/#selector-modal"]
+        const IN_MODAL_STATE = 0x4000,
+        #[doc = "The element is in its document's top layer. \
+                 https://fullscreen.spec.whatwg.org/#top-layer"]
+        const IN_TOP_LAYER_STATE = 0x8000,
     }
 }
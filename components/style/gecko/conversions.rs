@@ -29,7 +29,9 @@ impl From<CalcLengthOrPercentage> for nsStyleCoord_CalcValue {
     fn from(other: CalcLengthOrPercentage) -> nsStyleCoord_CalcValue {
         let has_percentage = other.percentage.is_some();
         nsStyleCoord_CalcValue {
-            mLength: other.length.map_or(0, |l| l.0),
+            // Gecko's calc() values can't hold comparisons, so these are resolved as if their
+            // percentages were of zero.
+            mLength: other.to_used_value(Au(0)).0,
             mPercent: other.percentage.unwrap_or(0.0),
             mHasPercent: has_percentage,
         }
@@ -46,6 +48,7 @@ impl From<nsStyleCoord_CalcValue> for CalcLengthOrPercentage {
         CalcLengthOrPercentage {
             length: Some(Au(other.mLength)),
             percentage: percentage,
+            comparisons: None,
         }
     }
 }
@@ -7,11 +7,12 @@
 use app_units::Au;
 use cssparser::RGBA;
 use gecko_bindings::structs::{NS_RADIUS_CLOSEST_SIDE, NS_RADIUS_FARTHEST_SIDE};
+use gecko_bindings::structs::{NS_STYLE_WIDTH_FIT_CONTENT, NS_STYLE_WIDTH_MAX_CONTENT, NS_STYLE_WIDTH_MIN_CONTENT};
 use gecko_bindings::structs::nsStyleCoord;
 use gecko_bindings::sugar::ns_style_coord::{CoordData, CoordDataMut, CoordDataValue};
 use std::cmp::max;
 use values::computed::{LengthOrPercentage, LengthOrPercentageOrAuto, LengthOrPercentageOrNone};
-use values::computed::Angle;
+use values::computed::{Angle, ExtremumLength, MaxSize, MinSize, Size};
 use values::computed::basic_shape::ShapeRadius;
 
 pub trait StyleCoordHelpers {
@@ -95,6 +96,72 @@ impl GeckoStyleCoordConvertible for LengthOrPercentageOrNone {
     }
 }
 
+impl GeckoStyleCoordConvertible for ExtremumLength {
+    fn to_gecko_style_coord<T: CoordDataMut>(&self, coord: &mut T) {
+        let value = match *self {
+            ExtremumLength::MinContent => NS_STYLE_WIDTH_MIN_CONTENT,
+            ExtremumLength::MaxContent => NS_STYLE_WIDTH_MAX_CONTENT,
+            ExtremumLength::FitContent => NS_STYLE_WIDTH_FIT_CONTENT,
+        };
+        coord.set_value(CoordDataValue::Enumerated(value));
+    }
+
+    fn from_gecko_style_coord<T: CoordData>(coord: &T) -> Option<Self> {
+        match coord.as_value() {
+            CoordDataValue::Enumerated(NS_STYLE_WIDTH_MIN_CONTENT) => Some(ExtremumLength::MinContent),
+            CoordDataValue::Enumerated(NS_STYLE_WIDTH_MAX_CONTENT) => Some(ExtremumLength::MaxContent),
+            CoordDataValue::Enumerated(NS_STYLE_WIDTH_FIT_CONTENT) => Some(ExtremumLength::FitContent),
+            _ => None,
+        }
+    }
+}
+
+impl GeckoStyleCoordConvertible for Size {
+    fn to_gecko_style_coord<T: CoordDataMut>(&self, coord: &mut T) {
+        match *self {
+            Size::LengthOrPercentageOrAuto(ref length) => length.to_gecko_style_coord(coord),
+            Size::ExtremumLength(ref keyword) => keyword.to_gecko_style_coord(coord),
+        }
+    }
+
+    fn from_gecko_style_coord<T: CoordData>(coord: &T) -> Option<Self> {
+        LengthOrPercentageOrAuto::from_gecko_style_coord(coord).map(Size::LengthOrPercentageOrAuto)
+            .or_else(|| ExtremumLength::from_gecko_style_coord(coord).map(Size::ExtremumLength))
+    }
+}
+
+impl GeckoStyleCoordConvertible for MinSize {
+    fn to_gecko_style_coord<T: CoordDataMut>(&self, coord: &mut T) {
+        match *self {
+            MinSize::LengthOrPercentage(ref length) => length.to_gecko_style_coord(coord),
+            MinSize::ExtremumLength(ref keyword) => keyword.to_gecko_style_coord(coord),
+            MinSize::Auto => coord.set_value(CoordDataValue::Auto),
+        }
+    }
+
+    fn from_gecko_style_coord<T: CoordData>(coord: &T) -> Option<Self> {
+        if let CoordDataValue::Auto = coord.as_value() {
+            return Some(MinSize::Auto)
+        }
+        LengthOrPercentage::from_gecko_style_coord(coord).map(MinSize::LengthOrPercentage)
+            .or_else(|| ExtremumLength::from_gecko_style_coord(coord).map(MinSize::ExtremumLength))
+    }
+}
+
+impl GeckoStyleCoordConvertible for MaxSize {
+    fn to_gecko_style_coord<T: CoordDataMut>(&self, coord: &mut T) {
+        match *self {
+            MaxSize::LengthOrPercentageOrNone(ref length) => length.to_gecko_style_coord(coord),
+            MaxSize::ExtremumLength(ref keyword) => keyword.to_gecko_style_coord(coord),
+        }
+    }
+
+    fn from_gecko_style_coord<T: CoordData>(coord: &T) -> Option<Self> {
+        LengthOrPercentageOrNone::from_gecko_style_coord(coord).map(MaxSize::LengthOrPercentageOrNone)
+            .or_else(|| ExtremumLength::from_gecko_style_coord(coord).map(MaxSize::ExtremumLength))
+    }
+}
+
 impl GeckoStyleCoordConvertible for ShapeRadius {
     fn to_gecko_style_coord<T: CoordDataMut>(&self, coord: &mut T) {
         match *self {
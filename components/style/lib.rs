@@ -84,6 +84,7 @@ pub mod attr;
 pub mod bezier;
 pub mod cache;
 pub mod cascade_info;
+pub mod container_queries;
 pub mod context;
 pub mod custom_properties;
 pub mod data;
@@ -98,6 +99,7 @@ pub mod keyframes;
 pub mod logical_geometry;
 pub mod matching;
 pub mod media_queries;
+pub mod page;
 pub mod parallel;
 pub mod parser;
 pub mod refcell;
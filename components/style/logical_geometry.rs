@@ -71,6 +71,14 @@ impl WritingMode {
         }
     }
 
+    /// Does the inline direction of this writing mode start on the same physical side as that of
+    /// `other`? Orthogonal writing modes are treated as having the same direction.
+    #[inline]
+    pub fn has_same_inline_direction(&self, other: WritingMode) -> bool {
+        self.is_vertical() != other.is_vertical() ||
+            self.inline_start_physical_side() == other.inline_start_physical_side()
+    }
+
     #[inline]
     pub fn inline_end_physical_side(&self) -> PhysicalSide {
         match (self.is_vertical(), self.is_inline_tb(), self.is_bidi_ltr()) {
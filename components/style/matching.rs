@@ -7,16 +7,19 @@
 #![allow(unsafe_code)]
 
 use animation;
+use app_units::Au;
 use arc_ptr_eq;
 use cache::{LRUCache, SimpleHashCache};
 use cascade_info::CascadeInfo;
+use container_queries::{self, ContainerConditions, QueryContainer};
 use context::{SharedStyleContext, StyleContext};
 use data::PrivateStyleData;
 use dom::{NodeInfo, TElement, TNode, TRestyleDamage, UnsafeNode};
+use euclid::size::Size2D;
 use properties::{ComputedValues, PropertyDeclarationBlock, cascade};
 use properties::longhands::display::computed_value as display;
 use selector_impl::{PseudoElement, TheSelectorImpl};
-use selector_matching::{ApplicableDeclarationBlock, Stylist};
+use selector_matching::ApplicableDeclarationBlock;
 use selectors::{Element, MatchAttr};
 use selectors::bloom::BloomFilter;
 use selectors::matching::{AFFECTED_BY_PSEUDO_ELEMENTS, MatchingReason, StyleRelations};
@@ -371,8 +374,8 @@ pub fn common_style_affecting_attributes() -> [CommonStyleAffectingAttributeInfo
 /// Attributes that, if present, disable style sharing. All legacy HTML attributes must be in
 /// either this list or `common_style_affecting_attributes`. See the comment in
 /// `synthesize_presentational_hints_for_legacy_attributes`.
-pub fn rare_style_affecting_attributes() -> [Atom; 3] {
-    [ atom!("bgcolor"), atom!("border"), atom!("colspan") ]
+pub fn rare_style_affecting_attributes() -> [Atom; 4] {
+    [ atom!("bgcolor"), atom!("border"), atom!("colspan"), atom!("lang") ]
 }
 
 fn have_same_class<E: TElement>(element: &E,
@@ -509,16 +512,29 @@ trait PrivateMatchMethods: TNode {
                                                             &mut old_style) && cacheable;
         }
 
+        // Container query lengths are relative to the nearest query container,
+        // which the cached styles may not share.
+        let container_size = container_length_basis(self, shared_context);
+        if container_size.is_some() {
+            cacheable = false;
+        }
+
         let mut cascade_info = CascadeInfo::new();
         let (this_style, is_cacheable) = match parent_style {
             Some(ref parent_style) => {
-                let cache_entry = applicable_declarations_cache.find(applicable_declarations);
+                let cache_entry = if container_size.is_none() {
+                    applicable_declarations_cache.find(applicable_declarations)
+                } else {
+                    None
+                };
                 let cached_computed_values = match cache_entry {
                     Some(ref style) => Some(&**style),
                     None => None,
                 };
 
                 cascade(shared_context.viewport_size,
+                        container_size,
+                        shared_context.stylist.custom_property_registrations(),
                         applicable_declarations,
                         shareable,
                         Some(&***parent_style),
@@ -528,6 +544,8 @@ trait PrivateMatchMethods: TNode {
             }
             None => {
                 cascade(shared_context.viewport_size,
+                        container_size,
+                        shared_context.stylist.custom_property_registrations(),
                         applicable_declarations,
                         shareable,
                         None,
@@ -591,6 +609,10 @@ trait PrivateMatchMethods: TNode {
             animation::complete_expired_transitions(this_opaque, style, context);
 
         // Merge any running transitions into the current style, and cancel them.
+        //
+        // Keyframes animations are cancelled too, but their values aren't
+        // merged, since changes caused by animations must not start
+        // transitions. Layout applies them again once the node is restyled.
         let had_running_animations = context.running_animations
                                             .read()
                                             .unwrap()
@@ -611,9 +633,11 @@ trait PrivateMatchMethods: TNode {
                 // See #12171 and the associated PR for an example where this
                 // happened while debugging other release panic.
                 if !running_animation.is_expired() {
-                    animation::update_style_for_animation(context,
-                                                          running_animation,
-                                                          style);
+                    if let animation::Animation::Transition(..) = *running_animation {
+                        animation::update_style_for_animation(context,
+                                                              running_animation,
+                                                              style);
+                    }
                     running_animation.mark_as_expired();
                 }
             }
@@ -644,13 +668,82 @@ trait PrivateElementMatchMethods: TElement {
 
 impl<E: TElement> PrivateElementMatchMethods for E {}
 
+/// Calls `f` with the nearest ancestor of `node` that is a query container,
+/// with the name `name` if given, or with `None` if there isn't one.
+///
+/// The ancestors have already been styled, and their style data is read
+/// without touching their borrow flags, as in `cascade_node`.
+fn with_query_container<N, F, R>(node: &N, context: &SharedStyleContext, name: Option<&Atom>, f: F) -> R
+    where N: TNode, F: FnOnce(Option<&QueryContainer>) -> R
+{
+    let mut current = node.parent_node();
+    while let Some(ancestor) = current {
+        let style = unsafe {
+            ancestor.borrow_data_unchecked().and_then(|data| (*data).style.as_ref())
+        };
+        if let Some(style) = style {
+            if container_queries::is_query_container(style, name) {
+                let container = QueryContainer {
+                    style: &**style,
+                    size: context.container_sizes.get(&ancestor.opaque()).cloned(),
+                    length_basis: container_length_basis(&ancestor, context).unwrap_or(context.viewport_size),
+                };
+                return f(Some(&container))
+            }
+        }
+        current = ancestor.parent_node();
+    }
+    f(None)
+}
+
+/// Returns the size that the container query lengths of `node` are relative
+/// to, if it has a query container.
+fn container_length_basis<N: TNode>(node: &N, context: &SharedStyleContext) -> Option<Size2D<Au>> {
+    with_query_container(node, context, None, |container| {
+        container.map(|container| container_queries::container_length_basis(container, context.viewport_size))
+    })
+}
+
+/// Whether the queries of the `@container` rules a declaration block is
+/// nested in hold for `node`.
+fn container_conditions_hold<N: TNode>(node: &N,
+                                       context: &SharedStyleContext,
+                                       conditions: &ContainerConditions)
+                                       -> bool {
+    let holds = with_query_container(node, context, conditions.query.name.as_ref(), |container| {
+        conditions.query.evaluate(container, context.viewport_size)
+    });
+    holds && conditions.outer.as_ref().map_or(true, |outer| container_conditions_hold(node, context, outer))
+}
+
+/// Moves the declaration blocks whose container queries hold for `node` to
+/// the front of `blocks`, keeping their order, and returns how many there are.
+fn filter_by_container_queries<N: TNode>(node: &N,
+                                         context: &SharedStyleContext,
+                                         blocks: &mut [ApplicableDeclarationBlock])
+                                         -> usize {
+    let mut kept = 0;
+    for i in 0..blocks.len() {
+        let holds = match blocks[i].container_conditions {
+            Some(ref conditions) => container_conditions_hold(node, context, conditions),
+            None => true,
+        };
+        if holds {
+            blocks.swap(kept, i);
+            kept += 1;
+        }
+    }
+    kept
+}
+
 pub trait ElementMatchMethods : TElement {
     fn match_element(&self,
-                     stylist: &Stylist,
+                     shared_context: &SharedStyleContext,
                      parent_bf: Option<&BloomFilter>,
                      applicable_declarations: &mut ApplicableDeclarations)
                      -> StyleRelations {
         use traversal::relations_are_shareable;
+        let stylist = &*shared_context.stylist;
         let style_attribute = self.style_attribute();
 
         let mut relations =
@@ -661,15 +754,24 @@ pub trait ElementMatchMethods : TElement {
                                                  &mut applicable_declarations.normal,
                                                  MatchingReason::ForStyling);
 
+        let node = self.as_node();
+        let kept = filter_by_container_queries(&node, shared_context, &mut applicable_declarations.normal);
+        applicable_declarations.normal.truncate(kept);
+
         applicable_declarations.normal_shareable = relations_are_shareable(&relations);
 
         TheSelectorImpl::each_eagerly_cascaded_pseudo_element(|pseudo| {
+            let declarations = applicable_declarations.per_pseudo.entry(pseudo.clone()).or_insert(vec![]);
             stylist.push_applicable_declarations(self,
                                                  parent_bf,
                                                  None,
-                                                 Some(&pseudo.clone()),
-                                                 applicable_declarations.per_pseudo.entry(pseudo).or_insert(vec![]),
+                                                 Some(&pseudo),
+                                                 declarations,
                                                  MatchingReason::ForStyling);
+            // FIXME: the query container of a pseudo-element can be its
+            // originating element.
+            let kept = filter_by_container_queries(&node, shared_context, declarations);
+            declarations.truncate(kept);
         });
 
         let has_pseudos =
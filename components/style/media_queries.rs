@@ -33,14 +33,17 @@ impl Range<specified::Length> {
         // http://dev.w3.org/csswg/mediaqueries3/#units
         // em units are relative to the initial font-size.
         let initial_font_size = longhands::font_size::get_initial_value();
-        let compute_width = |&width| {
-            match width {
+        let compute_width = |width: &specified::Length| {
+            match *width {
                 specified::Length::Absolute(value) => value,
                 specified::Length::FontRelative(value)
                     => value.to_computed_value(initial_font_size, initial_font_size),
                 specified::Length::ViewportPercentage(value)
                     => value.to_computed_value(viewport_size),
-                specified::Length::Calc(val, range)
+                // There is no query container here.
+                specified::Length::ContainerPercentage(value)
+                    => value.to_computed_value(viewport_size),
+                specified::Length::Calc(ref val, range)
                     => range.clamp(
                         val.compute_from_viewport_and_font_size(viewport_size,
                                                                 initial_font_size,
@@ -70,7 +73,7 @@ impl<T: Ord> Range<T> {
 }
 
 /// http://dev.w3.org/csswg/mediaqueries-3/#media1
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub enum Expression {
     /// http://dev.w3.org/csswg/mediaqueries-3/#width
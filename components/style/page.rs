@@ -0,0 +1,519 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The [`@page`][page] at-rule, which styles the page boxes of paged media.
+//!
+//! [page]: https://drafts.csswg.org/css-page-3/#at-page-rule
+
+use app_units::Au;
+use cssparser::{AtRuleParser, AtRuleType, DeclarationListParser, DeclarationParser, Parser, Token};
+use euclid::point::Point2D;
+use euclid::rect::Rect;
+use euclid::side_offsets::SideOffsets2D;
+use euclid::size::Size2D;
+use parser::{ParserContext, log_css_error};
+use properties::ComputedValues;
+use std::ascii::AsciiExt;
+use std::sync::Arc;
+use values::computed::{self, Context, ToComputedValue};
+use values::specified::{Length, LengthOrPercentage};
+
+/// The margins of pages that no `@page` rule gives margins to.
+pub const DEFAULT_PAGE_MARGIN: Au = Au(48 * 60);
+
+/// A page selector pseudo-class.
+/// https://drafts.csswg.org/css-page-3/#page-selectors
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum PagePseudoClass {
+    First,
+    Left,
+    Right,
+}
+
+/// The selector of an `@page` rule. Named pages aren't supported, so this is
+/// just its pseudo-classes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct PageSelector {
+    pub pseudo_classes: Vec<PagePseudoClass>,
+}
+
+impl PageSelector {
+    pub fn parse(input: &mut Parser) -> Result<PageSelector, ()> {
+        let mut pseudo_classes = vec![];
+        while !input.is_exhausted() {
+            match try!(input.next()) {
+                Token::Colon => {}
+                _ => return Err(()),
+            }
+            let name = try!(input.next_including_whitespace());
+            let pseudo_class = match name {
+                Token::Ident(ref name) => match_ignore_ascii_case! { name,
+                    "first" => PagePseudoClass::First,
+                    "left" => PagePseudoClass::Left,
+                    "right" => PagePseudoClass::Right,
+                    _ => return Err(())
+                },
+                _ => return Err(()),
+            };
+            pseudo_classes.push(pseudo_class);
+        }
+        Ok(PageSelector {
+            pseudo_classes: pseudo_classes,
+        })
+    }
+
+    /// Whether this selects the page at `page_index`. The first page is a
+    /// right page.
+    pub fn matches(&self, page_index: usize) -> bool {
+        self.pseudo_classes.iter().all(|pseudo_class| {
+            match *pseudo_class {
+                PagePseudoClass::First => page_index == 0,
+                PagePseudoClass::Left => page_index % 2 == 1,
+                PagePseudoClass::Right => page_index % 2 == 0,
+            }
+        })
+    }
+
+    /// `:first` is more specific than `:left` and `:right`.
+    /// https://drafts.csswg.org/css-page-3/#cascading-and-page-context
+    pub fn specificity(&self) -> u32 {
+        self.pseudo_classes.iter().map(|pseudo_class| {
+            match *pseudo_class {
+                PagePseudoClass::First => 1 << 8,
+                PagePseudoClass::Left | PagePseudoClass::Right => 1,
+            }
+        }).sum()
+    }
+}
+
+/// The value of the `size` descriptor.
+/// https://drafts.csswg.org/css-page-3/#page-size-prop
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum PageSize {
+    /// The size of the medium, turned to the orientation if one is given.
+    Auto(Option<PageOrientation>),
+    /// An explicit or named width and height.
+    Size(Length, Length),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum PageOrientation {
+    Portrait,
+    Landscape,
+}
+
+impl PageSize {
+    pub fn parse(input: &mut Parser) -> Result<PageSize, ()> {
+        if input.try(|input| input.expect_ident_matching("auto")).is_ok() {
+            return Ok(PageSize::Auto(None))
+        }
+        if let Ok(width) = input.try(Length::parse_non_negative) {
+            let height = input.try(Length::parse_non_negative).unwrap_or(width.clone());
+            return Ok(PageSize::Size(width, height))
+        }
+
+        // A page size name and an orientation, in either order.
+        let mut named_size = None;
+        let mut orientation = None;
+        for _ in 0..2 {
+            if named_size.is_none() {
+                if let Ok(size) = input.try(parse_page_size_name) {
+                    named_size = Some(size);
+                    continue
+                }
+            }
+            if orientation.is_none() {
+                if let Ok(value) = input.try(PageOrientation::parse) {
+                    orientation = Some(value);
+                    continue
+                }
+            }
+            break
+        }
+        match (named_size, orientation) {
+            (Some((width, height)), Some(PageOrientation::Landscape)) => {
+                Ok(PageSize::Size(Length::Absolute(height), Length::Absolute(width)))
+            }
+            (Some((width, height)), _) => {
+                Ok(PageSize::Size(Length::Absolute(width), Length::Absolute(height)))
+            }
+            (None, Some(orientation)) => Ok(PageSize::Auto(Some(orientation))),
+            (None, None) => Err(()),
+        }
+    }
+}
+
+impl PageOrientation {
+    pub fn parse(input: &mut Parser) -> Result<PageOrientation, ()> {
+        let ident = try!(input.expect_ident());
+        match_ignore_ascii_case! { ident,
+            "portrait" => Ok(PageOrientation::Portrait),
+            "landscape" => Ok(PageOrientation::Landscape),
+            _ => Err(())
+        }
+    }
+}
+
+/// Parses a page size name into its portrait width and height.
+/// https://drafts.csswg.org/css-page-3/#typedef-page-size-page-size
+fn parse_page_size_name(input: &mut Parser) -> Result<(Au, Au), ()> {
+    fn mm(width: f32, height: f32) -> (Au, Au) {
+        (Au::from_f32_px(width * 96. / 25.4), Au::from_f32_px(height * 96. / 25.4))
+    }
+    fn inches(width: f32, height: f32) -> (Au, Au) {
+        (Au::from_f32_px(width * 96.), Au::from_f32_px(height * 96.))
+    }
+    let ident = try!(input.expect_ident());
+    match_ignore_ascii_case! { ident,
+        "a5" => Ok(mm(148., 210.)),
+        "a4" => Ok(mm(210., 297.)),
+        "a3" => Ok(mm(297., 420.)),
+        "b5" => Ok(mm(176., 250.)),
+        "b4" => Ok(mm(250., 353.)),
+        "jis-b5" => Ok(mm(182., 257.)),
+        "jis-b4" => Ok(mm(257., 364.)),
+        "letter" => Ok(inches(8.5, 11.)),
+        "legal" => Ok(inches(8.5, 14.)),
+        "ledger" => Ok(inches(11., 17.)),
+        _ => Err(())
+    }
+}
+
+/// The margin boxes that can hold running headers and footers.
+/// https://drafts.csswg.org/css-page-3/#margin-boxes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum PageMarginBoxType {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl PageMarginBoxType {
+    fn from_name(name: &str) -> Result<PageMarginBoxType, ()> {
+        match_ignore_ascii_case! { name,
+            "top-left" => Ok(PageMarginBoxType::TopLeft),
+            "top-center" => Ok(PageMarginBoxType::TopCenter),
+            "top-right" => Ok(PageMarginBoxType::TopRight),
+            "bottom-left" => Ok(PageMarginBoxType::BottomLeft),
+            "bottom-center" => Ok(PageMarginBoxType::BottomCenter),
+            "bottom-right" => Ok(PageMarginBoxType::BottomRight),
+            _ => Err(())
+        }
+    }
+
+    /// Whether this box is in the top margin of the page.
+    pub fn is_top(&self) -> bool {
+        match *self {
+            PageMarginBoxType::TopLeft |
+            PageMarginBoxType::TopCenter |
+            PageMarginBoxType::TopRight => true,
+            PageMarginBoxType::BottomLeft |
+            PageMarginBoxType::BottomCenter |
+            PageMarginBoxType::BottomRight => false,
+        }
+    }
+}
+
+/// An item of the `content` of a margin box.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum PageMarginContent {
+    String(String),
+    /// `counter(page)`, the number of the page, starting at 1.
+    PageNumber,
+    /// `counter(pages)`, the number of pages in the document.
+    PageCount,
+}
+
+/// Returns the text of `content` on the page at `page_index` out of
+/// `page_count`.
+pub fn margin_box_text(content: &[PageMarginContent], page_index: usize, page_count: usize) -> String {
+    let mut text = String::new();
+    for item in content {
+        match *item {
+            PageMarginContent::String(ref string) => text.push_str(string),
+            PageMarginContent::PageNumber => text.push_str(&(page_index + 1).to_string()),
+            PageMarginContent::PageCount => text.push_str(&page_count.to_string()),
+        }
+    }
+    text
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct PageMarginBox {
+    pub box_type: PageMarginBoxType,
+    pub content: Vec<PageMarginContent>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct PageRule {
+    pub selector: PageSelector,
+    pub size: Option<PageSize>,
+    pub margin_top: Option<LengthOrPercentage>,
+    pub margin_right: Option<LengthOrPercentage>,
+    pub margin_bottom: Option<LengthOrPercentage>,
+    pub margin_left: Option<LengthOrPercentage>,
+    pub margin_boxes: Vec<PageMarginBox>,
+}
+
+impl PageRule {
+    pub fn parse(selector: PageSelector, context: &ParserContext, input: &mut Parser) -> PageRule {
+        let mut rule = PageRule {
+            selector: selector,
+            size: None,
+            margin_top: None,
+            margin_right: None,
+            margin_bottom: None,
+            margin_left: None,
+            margin_boxes: vec![],
+        };
+        let mut iter = DeclarationListParser::new(input, PageRuleParser { context: context });
+        while let Some(declaration) = iter.next() {
+            match declaration {
+                Err(range) => {
+                    let pos = range.start;
+                    let message = format!("Unsupported @page descriptor declaration: '{}'",
+                                          iter.input.slice(range));
+                    log_css_error(iter.input, pos, &*message, context);
+                }
+                Ok(PageDescriptorDeclaration::Size(size)) => rule.size = Some(size),
+                Ok(PageDescriptorDeclaration::MarginTop(value)) => rule.margin_top = Some(value),
+                Ok(PageDescriptorDeclaration::MarginRight(value)) => rule.margin_right = Some(value),
+                Ok(PageDescriptorDeclaration::MarginBottom(value)) => rule.margin_bottom = Some(value),
+                Ok(PageDescriptorDeclaration::MarginLeft(value)) => rule.margin_left = Some(value),
+                Ok(PageDescriptorDeclaration::Margin(top, right, bottom, left)) => {
+                    rule.margin_top = Some(top);
+                    rule.margin_right = Some(right);
+                    rule.margin_bottom = Some(bottom);
+                    rule.margin_left = Some(left);
+                }
+                Ok(PageDescriptorDeclaration::MarginBox(margin_box)) => {
+                    rule.margin_boxes.retain(|other| other.box_type != margin_box.box_type);
+                    rule.margin_boxes.push(margin_box);
+                }
+            }
+        }
+        rule
+    }
+}
+
+enum PageDescriptorDeclaration {
+    Size(PageSize),
+    MarginTop(LengthOrPercentage),
+    MarginRight(LengthOrPercentage),
+    MarginBottom(LengthOrPercentage),
+    MarginLeft(LengthOrPercentage),
+    Margin(LengthOrPercentage, LengthOrPercentage, LengthOrPercentage, LengthOrPercentage),
+    MarginBox(PageMarginBox),
+}
+
+struct PageRuleParser<'a, 'b: 'a> {
+    context: &'a ParserContext<'b>,
+}
+
+/// Margin boxes are at-rules nested in the `@page` rule.
+impl<'a, 'b> AtRuleParser for PageRuleParser<'a, 'b> {
+    type Prelude = PageMarginBoxType;
+    type AtRule = PageDescriptorDeclaration;
+
+    fn parse_prelude(&mut self, name: &str, input: &mut Parser)
+                     -> Result<AtRuleType<PageMarginBoxType, PageDescriptorDeclaration>, ()> {
+        let box_type = try!(PageMarginBoxType::from_name(name));
+        try!(input.expect_exhausted());
+        Ok(AtRuleType::WithBlock(box_type))
+    }
+
+    fn parse_block(&mut self, box_type: PageMarginBoxType, input: &mut Parser)
+                   -> Result<PageDescriptorDeclaration, ()> {
+        let mut content = vec![];
+        let mut iter = DeclarationListParser::new(input, MarginBoxParser);
+        while let Some(declaration) = iter.next() {
+            match declaration {
+                Err(range) => {
+                    let pos = range.start;
+                    let message = format!("Unsupported margin box declaration: '{}'",
+                                          iter.input.slice(range));
+                    log_css_error(iter.input, pos, &*message, self.context);
+                }
+                Ok(value) => content = value,
+            }
+        }
+        Ok(PageDescriptorDeclaration::MarginBox(PageMarginBox {
+            box_type: box_type,
+            content: content,
+        }))
+    }
+}
+
+impl<'a, 'b> DeclarationParser for PageRuleParser<'a, 'b> {
+    type Declaration = PageDescriptorDeclaration;
+
+    fn parse_value(&mut self, name: &str, input: &mut Parser) -> Result<PageDescriptorDeclaration, ()> {
+        match_ignore_ascii_case! { name,
+            "size" => Ok(PageDescriptorDeclaration::Size(try!(PageSize::parse(input)))),
+            "margin-top" => {
+                Ok(PageDescriptorDeclaration::MarginTop(try!(LengthOrPercentage::parse(input))))
+            },
+            "margin-right" => {
+                Ok(PageDescriptorDeclaration::MarginRight(try!(LengthOrPercentage::parse(input))))
+            },
+            "margin-bottom" => {
+                Ok(PageDescriptorDeclaration::MarginBottom(try!(LengthOrPercentage::parse(input))))
+            },
+            "margin-left" => {
+                Ok(PageDescriptorDeclaration::MarginLeft(try!(LengthOrPercentage::parse(input))))
+            },
+            "margin" => {
+                let top = try!(LengthOrPercentage::parse(input));
+                let right = input.try(LengthOrPercentage::parse).unwrap_or(top.clone());
+                let bottom = input.try(LengthOrPercentage::parse).unwrap_or(top.clone());
+                let left = input.try(LengthOrPercentage::parse).unwrap_or(right.clone());
+                Ok(PageDescriptorDeclaration::Margin(top, right, bottom, left))
+            },
+            _ => Err(())
+        }
+    }
+}
+
+struct MarginBoxParser;
+
+/// Default methods reject all at rules.
+impl AtRuleParser for MarginBoxParser {
+    type Prelude = ();
+    type AtRule = Vec<PageMarginContent>;
+}
+
+impl DeclarationParser for MarginBoxParser {
+    type Declaration = Vec<PageMarginContent>;
+
+    fn parse_value(&mut self, name: &str, input: &mut Parser) -> Result<Vec<PageMarginContent>, ()> {
+        if !name.eq_ignore_ascii_case("content") {
+            return Err(())
+        }
+        if input.try(|input| input.expect_ident_matching("none")).is_ok() {
+            return Ok(vec![])
+        }
+        let mut content = vec![];
+        while !input.is_exhausted() {
+            match try!(input.next()) {
+                Token::QuotedString(value) => content.push(PageMarginContent::String(value.into_owned())),
+                Token::Function(ref name) if name.eq_ignore_ascii_case("counter") => {
+                    let item = try!(input.parse_nested_block(|input| {
+                        let ident = try!(input.expect_ident());
+                        match_ignore_ascii_case! { ident,
+                            "page" => Ok(PageMarginContent::PageNumber),
+                            "pages" => Ok(PageMarginContent::PageCount),
+                            _ => Err(())
+                        }
+                    }));
+                    content.push(item);
+                }
+                _ => return Err(()),
+            }
+        }
+        if content.is_empty() {
+            return Err(())
+        }
+        Ok(content)
+    }
+}
+
+/// The used size, margins and margin boxes of a page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageStyle {
+    pub size: Size2D<Au>,
+    pub margins: SideOffsets2D<Au>,
+    pub margin_boxes: Vec<PageMarginBox>,
+}
+
+impl PageStyle {
+    /// The area of the page that the document is laid out into.
+    pub fn content_box(&self) -> Rect<Au> {
+        Rect::new(Point2D::new(self.margins.left, self.margins.top),
+                  Size2D::new(self.size.width - self.margins.horizontal(),
+                              self.size.height - self.margins.vertical()))
+    }
+}
+
+/// Cascades the `@page` rules that apply to the page at `page_index` into its
+/// used style. `rules` are in source order, and `medium_size` is the size of
+/// pages with `size: auto`.
+pub fn page_style(rules: &[Arc<PageRule>], page_index: usize, medium_size: Size2D<Au>) -> PageStyle {
+    let mut matching: Vec<&PageRule> = rules.iter()
+        .map(|rule| &**rule)
+        .filter(|rule| rule.selector.matches(page_index))
+        .collect();
+    // The sort is stable, so rules of equal specificity stay in source order.
+    matching.sort_by_key(|rule| rule.selector.specificity());
+
+    let mut size = None;
+    let mut margins = [None, None, None, None];
+    let mut margin_boxes: Vec<PageMarginBox> = vec![];
+    for rule in matching {
+        if rule.size.is_some() {
+            size = rule.size.clone();
+        }
+        for (margin, value) in margins.iter_mut().zip(&[&rule.margin_top, &rule.margin_right,
+                                                        &rule.margin_bottom, &rule.margin_left]) {
+            if value.is_some() {
+                *margin = (*value).clone();
+            }
+        }
+        for margin_box in &rule.margin_boxes {
+            margin_boxes.retain(|other| other.box_type != margin_box.box_type);
+            margin_boxes.push(margin_box.clone());
+        }
+    }
+
+    let context = Context {
+        is_root_element: false,
+        viewport_size: medium_size,
+        container_size: None,
+        inherited_style: ComputedValues::initial_values(),
+        style: ComputedValues::initial_values().clone(),
+    };
+    let size = match size.unwrap_or(PageSize::Auto(None)) {
+        PageSize::Size(width, height) => {
+            Size2D::new(width.to_computed_value(&context), height.to_computed_value(&context))
+        }
+        PageSize::Auto(Some(orientation)) => {
+            let is_landscape = medium_size.width > medium_size.height;
+            if is_landscape == (orientation == PageOrientation::Landscape) {
+                medium_size
+            } else {
+                Size2D::new(medium_size.height, medium_size.width)
+            }
+        }
+        PageSize::Auto(None) => medium_size,
+    };
+
+    // Percentages of the horizontal margins refer to the page width, and those
+    // of the vertical margins to its height.
+    let resolve = |margin: &Option<LengthOrPercentage>, base: Au| {
+        match margin.as_ref().map(|margin| margin.to_computed_value(&context)) {
+            None => DEFAULT_PAGE_MARGIN,
+            Some(computed::LengthOrPercentage::Length(length)) => length,
+            Some(computed::LengthOrPercentage::Percentage(percentage)) => base.scale_by(percentage),
+            Some(computed::LengthOrPercentage::Calc(calc)) => calc.to_used_value(base),
+        }
+    };
+    PageStyle {
+        size: size,
+        margins: SideOffsets2D::new(resolve(&margins[0], size.height),
+                                    resolve(&margins[1], size.width),
+                                    resolve(&margins[2], size.height),
+                                    resolve(&margins[3], size.width)),
+        margin_boxes: margin_boxes,
+    }
+}
@@ -4,6 +4,7 @@
 
 //! The context within which CSS code is parsed.
 
+use context::QuirksMode;
 use cssparser::{Parser, SourcePosition};
 use error_reporting::ParseErrorReporter;
 #[cfg(feature = "gecko")]
@@ -14,24 +15,36 @@ use stylesheets::Origin;
 use url::Url;
 
 #[cfg(not(feature = "gecko"))]
-pub struct ParserContextExtraData;
+pub struct ParserContextExtraData {
+    /// The mode of the document the CSS belongs to, which allows some legacy
+    /// syntax in quirks mode.
+    pub quirks_mode: QuirksMode,
+}
 
 #[cfg(feature = "gecko")]
 pub struct ParserContextExtraData {
     pub base: Option<GeckoArcURI>,
     pub referrer: Option<GeckoArcURI>,
     pub principal: Option<GeckoArcPrincipal>,
+    pub quirks_mode: QuirksMode,
 }
 
 impl ParserContextExtraData {
     #[cfg(not(feature = "gecko"))]
     pub fn default() -> ParserContextExtraData {
-        ParserContextExtraData
+        ParserContextExtraData { quirks_mode: QuirksMode::NoQuirks }
     }
 
     #[cfg(feature = "gecko")]
     pub fn default() -> ParserContextExtraData {
-        ParserContextExtraData { base: None, referrer: None, principal: None }
+        ParserContextExtraData { base: None, referrer: None, principal: None, quirks_mode: QuirksMode::NoQuirks }
+    }
+
+    /// The extra data for CSS that belongs to a document in `quirks_mode`.
+    pub fn for_quirks_mode(quirks_mode: QuirksMode) -> ParserContextExtraData {
+        let mut extra_data = ParserContextExtraData::default();
+        extra_data.quirks_mode = quirks_mode;
+        extra_data
     }
 }
 
@@ -72,6 +85,12 @@ impl<'a> ParserContext<'a> {
         self.base_url.join(input)
             .unwrap_or_else(|_| Url::parse("about:invalid").unwrap())
     }
+
+    /// Whether the legacy syntax of quirks mode, such as unitless lengths, is
+    /// allowed. It never is in user agent and user stylesheets.
+    pub fn in_quirks_mode(&self) -> bool {
+        self.stylesheet_origin == Origin::Author && self.extra_data.quirks_mode == QuirksMode::Quirks
+    }
 }
 
 
@@ -190,6 +190,12 @@ class PropertiesData(object):
         if self.product not in products and not (self.testing and not disable_when_testing):
             return
 
+        # `animatable` may also list the products that animate the property,
+        # when its interpolation is only implemented for some of them.
+        animatable = kwargs.get("animatable")
+        if animatable not in (None, True, False, "True", "False"):
+            kwargs["animatable"] = self.product in animatable.split()
+
         longhand = Longhand(self.current_style_struct, name, **kwargs)
         self.current_style_struct.longhands.append(longhand)
         self.longhands.append(longhand)
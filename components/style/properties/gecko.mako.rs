@@ -505,8 +505,11 @@ impl Debug for ${style_struct.gecko_struct_name} {
        "LengthOrPercentage": impl_style_coord,
        "LengthOrPercentageOrAuto": impl_style_coord,
        "LengthOrPercentageOrNone": impl_style_coord,
+       "MaxSize": impl_style_coord,
+       "MinSize": impl_style_coord,
        "Number": impl_simple,
        "Opacity": impl_simple,
+       "Size": impl_style_coord,
     }
 
     keyword_longhands = [x for x in longhands if x.keyword and not x.name in force_stub]
@@ -1199,7 +1202,7 @@ fn static_assert() {
         use gecko_bindings::structs::nsStyleImageLayers_LayerType as LayerType;
         use gecko_bindings::structs::{NS_STYLE_GRADIENT_SHAPE_LINEAR, NS_STYLE_GRADIENT_SIZE_FARTHEST_CORNER};
         use gecko_bindings::structs::nsStyleCoord;
-        use values::computed::{Image, LinearGradient};
+        use values::computed::{GradientItem, Image, LinearGradient};
         use values::specified::AngleOrCorner;
         use values::specified::{HorizontalDirection, VerticalDirection};
         use cssparser::Color as CSSColor;
@@ -1214,7 +1217,7 @@ fn static_assert() {
             let gecko_gradient = unsafe {
                 Gecko_CreateGradient(NS_STYLE_GRADIENT_SHAPE_LINEAR as u8,
                                      NS_STYLE_GRADIENT_SIZE_FARTHEST_CORNER as u8,
-                                     /* repeating = */ false,
+                                     gradient.repeating,
                                      /* legacy_syntax = */ false,
                                      stop_count as u32)
             };
@@ -1248,11 +1251,25 @@ fn static_assert() {
             }
 
             let mut coord: nsStyleCoord = nsStyleCoord::null();
-            for (index, stop) in gradient.stops.iter().enumerate() {
+            for (index, item) in gradient.stops.iter().enumerate() {
+                let mut stop = unsafe {
+                    &mut (*gecko_gradient).mStops[index]
+                };
+
+                let color_stop = match *item {
+                    GradientItem::ColorStop(ref color_stop) => color_stop,
+                    GradientItem::InterpolationHint(position) => {
+                        coord.set(position);
+                        stop.mIsInterpolationHint = true;
+                        stop.mLocation.copy_from(&coord);
+                        continue;
+                    }
+                };
+
                 // NB: stops are guaranteed to be none in the gecko side by
                 // default.
-                coord.set(stop.position);
-                let color = match stop.color {
+                coord.set(color_stop.position);
+                let color = match color_stop.color {
                     CSSColor::CurrentColor => {
                         // TODO(emilio): gecko just stores an nscolor,
                         // and it doesn't seem to support currentColor
@@ -1266,10 +1283,6 @@ fn static_assert() {
                     CSSColor::RGBA(ref rgba) => convert_rgba_to_nscolor(rgba),
                 };
 
-                let mut stop = unsafe {
-                    &mut (*gecko_gradient).mStops[index]
-                };
-
                 stop.mColor = color;
                 stop.mIsInterpolationHint = false;
                 stop.mLocation.copy_from(&coord);
@@ -1302,6 +1315,9 @@ fn static_assert() {
                         Image::LinearGradient(gradient) => {
                             set_linear_gradient(gradient, &mut geckoimage.mImage)
                         },
+                        Image::ConicGradient(..) => {
+                            warn!("stylo: conic-gradient() not yet implemented");
+                        }
                         Image::Url(..) => {
                             // let utf8_bytes = url.as_bytes();
                             // Gecko_SetUrlImageValue(&mut self.gecko.mImage.mLayers.mFirstElement,
@@ -1331,7 +1347,7 @@ fn static_assert() {
     <%
         fill_fields = "mRepeat mClip mOrigin mPositionX mPositionY mImage"
         if shorthand == "background":
-            fill_fields += " mAttachment"
+            fill_fields += " mAttachment mBlendMode"
         else:
             # mSourceURI uses mImageCount
             fill_fields += " mMaskMode mComposite"
@@ -1361,7 +1377,8 @@ fn static_assert() {
 <% skip_background_longhands = """background-color background-repeat
                                   background-image background-clip
                                   background-origin background-attachment
-                                  background-size background-position""" %>
+                                  background-size background-position
+                                  background-blend-mode""" %>
 <%self:impl_trait style_struct_name="Background"
                   skip_longhands="${skip_background_longhands}"
                   skip_additionals="*">
@@ -1378,6 +1395,28 @@ fn static_assert() {
             T::local => structs::NS_STYLE_IMAGELAYER_ATTACHMENT_LOCAL as u8,
         }
     </%self:simple_image_array_property>
+
+    <%self:simple_image_array_property name="blend_mode" shorthand="background" field_name="mBlendMode">
+        use properties::longhands::background_blend_mode::single_value::computed_value::T;
+        match servo {
+            T::normal => structs::NS_STYLE_BLEND_NORMAL as u8,
+            T::multiply => structs::NS_STYLE_BLEND_MULTIPLY as u8,
+            T::screen => structs::NS_STYLE_BLEND_SCREEN as u8,
+            T::overlay => structs::NS_STYLE_BLEND_OVERLAY as u8,
+            T::darken => structs::NS_STYLE_BLEND_DARKEN as u8,
+            T::lighten => structs::NS_STYLE_BLEND_LIGHTEN as u8,
+            T::color_dodge => structs::NS_STYLE_BLEND_COLOR_DODGE as u8,
+            T::color_burn => structs::NS_STYLE_BLEND_COLOR_BURN as u8,
+            T::hard_light => structs::NS_STYLE_BLEND_HARD_LIGHT as u8,
+            T::soft_light => structs::NS_STYLE_BLEND_SOFT_LIGHT as u8,
+            T::difference => structs::NS_STYLE_BLEND_DIFFERENCE as u8,
+            T::exclusion => structs::NS_STYLE_BLEND_EXCLUSION as u8,
+            T::hue => structs::NS_STYLE_BLEND_HUE as u8,
+            T::saturation => structs::NS_STYLE_BLEND_SATURATION as u8,
+            T::color => structs::NS_STYLE_BLEND_COLOR as u8,
+            T::luminosity => structs::NS_STYLE_BLEND_LUMINOSITY as u8,
+        }
+    </%self:simple_image_array_property>
 </%self:impl_trait>
 
 <%self:impl_trait style_struct_name="List" skip_longhands="list-style-type" skip_additionals="*">
@@ -16,7 +16,11 @@
     </%call>
 </%def>
 
-<%def name="predefined_type(name, type, initial_value, parse_method='parse', **kwargs)">
+<%doc>
+    `allow_quirks` accepts numbers without a unit as lengths in pixels in
+    quirks mode, which `type` must then be convertible from.
+</%doc>
+<%def name="predefined_type(name, type, initial_value, parse_method='parse', allow_quirks=False, **kwargs)">
     <%call expr="longhand(name, predefined_type=type, **kwargs)">
         #[allow(unused_imports)]
         use app_units::Au;
@@ -26,8 +30,16 @@
             pub use values::computed::${type} as T;
         }
         #[inline] pub fn get_initial_value() -> computed_value::T { ${initial_value} }
-        #[inline] pub fn parse(_context: &ParserContext, input: &mut Parser)
+        #[inline] pub fn parse(${'context' if allow_quirks else '_context'}: &ParserContext, input: &mut Parser)
                                -> Result<SpecifiedValue, ()> {
+            % if allow_quirks:
+                if context.in_quirks_mode() {
+                    let non_negative = ${'true' if parse_method == 'parse_non_negative' else 'false'};
+                    if let Ok(length) = input.try(|input| specified::Length::parse_unitless(input, non_negative)) {
+                        return Ok(length.into())
+                    }
+                }
+            % endif
             specified::${type}::${parse_method}(input)
         }
     </%call>
@@ -205,6 +217,11 @@
                     return
                 }
                 seen.set_${property.ident}();
+                // The value depends on the custom properties of this element, which a style with
+                // the same declarations elsewhere does not share.
+                if let DeclaredValue::WithVariables { .. } = *declared_value {
+                    *cacheable = false;
+                }
                 {
                     let custom_props = context.style().custom_properties();
                     ::properties::substitute_variables_${property.ident}(
@@ -561,15 +578,34 @@
     % endif
 </%def>
 
-<%def name="four_sides_shorthand(name, sub_property_pattern, parser_function)">
+<%def name="four_sides_shorthand(name, sub_property_pattern, parser_function, allow_quirks=False)">
     <%self:shorthand name="${name}" sub_properties="${
             ' '.join(sub_property_pattern % side
                      for side in ['top', 'right', 'bottom', 'left'])}">
         use super::parse_four_sides;
         use values::specified;
 
-        pub fn parse_value(_: &ParserContext, input: &mut Parser) -> Result<Longhands, ()> {
-            let (top, right, bottom, left) = try!(parse_four_sides(input, ${parser_function}));
+        % if allow_quirks:
+            <% value_type = parser_function.rsplit("::", 1)[0] %>
+            fn parse_quirky_side(input: &mut Parser) -> Result<${value_type}, ()> {
+                let non_negative = ${'true' if parser_function.endswith('parse_non_negative') else 'false'};
+                input.try(|input| specified::Length::parse_unitless(input, non_negative))
+                     .map(${value_type}::from)
+                     .or_else(|()| ${parser_function}(input))
+            }
+        % endif
+
+        pub fn parse_value(${'context' if allow_quirks else '_'}: &ParserContext, input: &mut Parser)
+                           -> Result<Longhands, ()> {
+            % if allow_quirks:
+                let (top, right, bottom, left) = if context.in_quirks_mode() {
+                    try!(parse_four_sides(input, parse_quirky_side))
+                } else {
+                    try!(parse_four_sides(input, ${parser_function}))
+                };
+            % else:
+                let (top, right, bottom, left) = try!(parse_four_sides(input, ${parser_function}));
+            % endif
             Ok(Longhands {
                 % for side in ["top", "right", "bottom", "left"]:
                     ${to_rust_ident(sub_property_pattern % side)}: Some(${side}),
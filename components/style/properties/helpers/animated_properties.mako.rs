@@ -24,10 +24,16 @@ use super::ComputedValues;
 use values::computed::{Angle, LengthOrPercentageOrAuto, LengthOrPercentageOrNone};
 use values::computed::{BorderRadiusSize, LengthOrNone};
 use values::computed::{CalcLengthOrPercentage, LengthOrPercentage};
+use values::computed::{MaxSize, MinSize, Size};
 use values::computed::position::Position;
 
 
 
+<%
+    animatable_shorthands = [shorthand for shorthand in data.shorthands
+                             if any(sub.animatable for sub in shorthand.sub_properties)]
+%>
+
 // NB: This needs to be here because it needs all the longhands generated
 // beforehand.
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -39,10 +45,14 @@ pub enum TransitionProperty {
             ${prop.camel_case},
         % endif
     % endfor
+    // Shorthands transition their animatable longhands.
+    % for shorthand in animatable_shorthands:
+        ${shorthand.camel_case},
+    % endfor
 }
 
 impl TransitionProperty {
-    /// Iterates over each property that is not `All`.
+    /// Iterates over each longhand property.
     pub fn each<F: FnMut(TransitionProperty) -> ()>(mut cb: F) {
         % for prop in data.longhands:
             % if prop.animatable:
@@ -51,6 +61,36 @@ impl TransitionProperty {
         % endfor
     }
 
+    /// Iterates over the longhand properties this property transitions: all
+    /// of them for `All`, the animatable longhands of a shorthand, or the
+    /// property itself.
+    pub fn each_longhand<F: FnMut(TransitionProperty) -> ()>(&self, mut cb: F) {
+        match *self {
+            TransitionProperty::All => TransitionProperty::each(cb),
+            % for shorthand in animatable_shorthands:
+                TransitionProperty::${shorthand.camel_case} => {
+                    % for sub in shorthand.sub_properties:
+                        % if sub.animatable:
+                            cb(TransitionProperty::${sub.camel_case});
+                        % endif
+                    % endfor
+                }
+            % endfor
+            longhand => cb(longhand),
+        }
+    }
+
+    /// Returns true if this is `All` or a shorthand.
+    pub fn is_shorthand(&self) -> bool {
+        match *self {
+            TransitionProperty::All => true,
+            % for shorthand in animatable_shorthands:
+                TransitionProperty::${shorthand.camel_case} => true,
+            % endfor
+            _ => false,
+        }
+    }
+
     pub fn parse(input: &mut Parser) -> Result<Self, ()> {
         match_ignore_ascii_case! { try!(input.expect_ident()),
             "all" => Ok(TransitionProperty::All),
@@ -59,6 +99,9 @@ impl TransitionProperty {
                     "${prop.name}" => Ok(TransitionProperty::${prop.camel_case}),
                 % endif
             % endfor
+            % for shorthand in animatable_shorthands:
+                "${shorthand.name}" => Ok(TransitionProperty::${shorthand.camel_case}),
+            % endfor
             _ => Err(())
         }
     }
@@ -85,6 +128,9 @@ impl ToCss for TransitionProperty {
                     TransitionProperty::${prop.camel_case} => dest.write_str("${prop.name}"),
                 % endif
             % endfor
+            % for shorthand in animatable_shorthands:
+                TransitionProperty::${shorthand.camel_case} => dest.write_str("${shorthand.name}"),
+            % endfor
         }
     }
 }
@@ -131,6 +177,11 @@ impl AnimatedProperty {
                                     -> AnimatedProperty {
         match *transition_property {
             TransitionProperty::All => panic!("Can't use TransitionProperty::All here."),
+            % for shorthand in animatable_shorthands:
+                TransitionProperty::${shorthand.camel_case} => {
+                    panic!("Can't use the shorthand TransitionProperty::${shorthand.camel_case} here.")
+                }
+            % endfor
             % for prop in data.longhands:
                 % if prop.animatable:
                     TransitionProperty::${prop.camel_case} => {
@@ -209,6 +260,16 @@ impl Interpolate for i32 {
     }
 }
 
+/// https://drafts.csswg.org/css-transitions/#animtype-integer
+impl Interpolate for u32 {
+    #[inline]
+    fn interpolate(&self, other: &u32, time: f64) -> Result<Self, ()> {
+        let a = *self as f64;
+        let b = *other as f64;
+        Ok((a + (b - a) * time).round().max(0.) as u32)
+    }
+}
+
 /// https://drafts.csswg.org/css-transitions/#animtype-number
 impl Interpolate for Angle {
     #[inline]
@@ -277,16 +338,14 @@ impl Interpolate for BorderRadiusSize {
     }
 }
 
-/// https://drafts.csswg.org/css-transitions/#animtype-length
+/// https://drafts.csswg.org/css-transitions/#animtype-lpcalc
 impl Interpolate for VerticalAlign {
     #[inline]
     fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
         match (*self, *other) {
-            (VerticalAlign::LengthOrPercentage(LengthOrPercentage::Length(ref this)),
-             VerticalAlign::LengthOrPercentage(LengthOrPercentage::Length(ref other))) => {
-                this.interpolate(other, time).map(|value| {
-                    VerticalAlign::LengthOrPercentage(LengthOrPercentage::Length(value))
-                })
+            (VerticalAlign::LengthOrPercentage(ref this),
+             VerticalAlign::LengthOrPercentage(ref other)) => {
+                this.interpolate(other, time).map(VerticalAlign::LengthOrPercentage)
             }
             _ => Err(()),
         }
@@ -300,15 +359,31 @@ impl Interpolate for BackgroundSize {
 }
 
 
+/// Colors interpolate in premultiplied space, so that a transparent color
+/// doesn't tint the other one, and the result is clamped to the valid range,
+/// since timing functions may overshoot.
+///
 /// https://drafts.csswg.org/css-transitions/#animtype-color
+/// https://drafts.csswg.org/css-color/#interpolation-alpha
 impl Interpolate for RGBA {
     #[inline]
     fn interpolate(&self, other: &RGBA, time: f64) -> Result<Self, ()> {
+        fn clamp(value: f32) -> f32 {
+            value.max(0.).min(1.)
+        }
+
+        let alpha = clamp(try!(self.alpha.interpolate(&other.alpha, time)));
+        if alpha == 0. {
+            return Ok(RGBA { red: 0., green: 0., blue: 0., alpha: 0. })
+        }
+        let red = try!((self.red * self.alpha).interpolate(&(other.red * other.alpha), time));
+        let green = try!((self.green * self.alpha).interpolate(&(other.green * other.alpha), time));
+        let blue = try!((self.blue * self.alpha).interpolate(&(other.blue * other.alpha), time));
         Ok(RGBA {
-            red: try!(self.red.interpolate(&other.red, time)),
-            green: try!(self.green.interpolate(&other.green, time)),
-            blue: try!(self.blue.interpolate(&other.blue, time)),
-            alpha: try!(self.alpha.interpolate(&other.alpha, time)),
+            red: clamp(red / alpha),
+            green: clamp(green / alpha),
+            blue: clamp(blue / alpha),
+            alpha: alpha,
         })
     }
 }
@@ -346,9 +421,15 @@ impl Interpolate for CalcLengthOrPercentage {
             }
         }
 
+        // Comparisons don't interpolate, since they aren't resolved yet.
+        if self.comparisons.is_some() || other.comparisons.is_some() {
+            return Err(())
+        }
+
         Ok(CalcLengthOrPercentage {
             length: try!(interpolate_half(self.length, other.length, time)),
             percentage: try!(interpolate_half(self.percentage, other.percentage, time)),
+            comparisons: None,
         })
     }
 }
@@ -425,6 +506,46 @@ impl Interpolate for LengthOrPercentageOrNone {
     }
 }
 
+/// Sizing keywords don't interpolate.
+impl Interpolate for Size {
+    #[inline]
+    fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+        match (*self, *other) {
+            (Size::LengthOrPercentageOrAuto(ref this),
+             Size::LengthOrPercentageOrAuto(ref other)) => {
+                this.interpolate(other, time).map(Size::LengthOrPercentageOrAuto)
+            }
+            _ => Err(())
+        }
+    }
+}
+
+impl Interpolate for MinSize {
+    #[inline]
+    fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+        match (*self, *other) {
+            (MinSize::LengthOrPercentage(ref this),
+             MinSize::LengthOrPercentage(ref other)) => {
+                this.interpolate(other, time).map(MinSize::LengthOrPercentage)
+            }
+            _ => Err(())
+        }
+    }
+}
+
+impl Interpolate for MaxSize {
+    #[inline]
+    fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+        match (*self, *other) {
+            (MaxSize::LengthOrPercentageOrNone(ref this),
+             MaxSize::LengthOrPercentageOrNone(ref other)) => {
+                this.interpolate(other, time).map(MaxSize::LengthOrPercentageOrNone)
+            }
+            _ => Err(())
+        }
+    }
+}
+
 /// https://drafts.csswg.org/css-transitions/#animtype-number
 /// https://drafts.csswg.org/css-transitions/#animtype-length
 impl Interpolate for LineHeight {
@@ -620,11 +741,23 @@ impl Interpolate for LengthOrNone {
 }
 
 % if product == "servo":
+    use properties::longhands::background_image::computed_value::T as BackgroundImage;
+    use properties::longhands::background_image::computed_value::single_value::T as SingleBackgroundImage;
+    use properties::longhands::column_count::computed_value::T as ColumnCount;
+    use properties::longhands::column_gap::computed_value::T as Gap;
+    use properties::longhands::column_width::computed_value::T as ColumnWidth;
+    use properties::longhands::letter_spacing::computed_value::T as LetterSpacing;
+    use properties::longhands::rotate::computed_value::T as Rotate;
+    use properties::longhands::scale::computed_value::T as Scale;
+    use properties::longhands::translate::computed_value::T as Translate;
     use properties::longhands::transform::computed_value::ComputedMatrix;
     use properties::longhands::transform::computed_value::ComputedOperation as TransformOperation;
     use properties::longhands::transform::computed_value::T as TransformList;
+    use properties::longhands::word_spacing::computed_value::T as WordSpacing;
     use values::CSSFloat;
-    use values::specified::Angle as SpecifiedAngle;
+    use values::computed::{ColorStop, ConicGradient, GradientItem, Image, LinearGradient};
+    use values::computed::LengthOrPercentageOrAutoOrContent;
+    use values::specified::{Angle as SpecifiedAngle, AngleOrCorner};
 
     /// Check if it's possible to do a direct numerical interpolation
     /// between these two transform lists.
@@ -747,8 +880,8 @@ impl Interpolate for LengthOrNone {
                      &TransformOperation::Perspective(_td)) => {
                         let mut fd_matrix = ComputedMatrix::identity();
                         let mut td_matrix = ComputedMatrix::identity();
-                        fd_matrix.m43 = -1. / fd.to_f32_px();
-                        td_matrix.m43 = -1. / _td.to_f32_px();
+                        fd_matrix.m34 = -1. / fd.to_f32_px();
+                        td_matrix.m34 = -1. / _td.to_f32_px();
                         let interpolated = fd_matrix.interpolate(&td_matrix, time).unwrap();
                         result.push(TransformOperation::Matrix(interpolated));
                     }
@@ -759,13 +892,86 @@ impl Interpolate for LengthOrNone {
                 }
             }
         } else {
-            // TODO(gw): Implement matrix decomposition and interpolation
-            result.extend_from_slice(from_list);
+            // Lists that don't match interpolate as matrices, which needs
+            // them not to depend on the size of the box. Otherwise they switch
+            // halfway through.
+            let from_matrix = transform_list_to_matrix(from_list);
+            let to_matrix = transform_list_to_matrix(to_list);
+            match (from_matrix, to_matrix) {
+                (Some(from), Some(to)) => {
+                    let interpolated = from.interpolate(&to, time).unwrap();
+                    result.push(TransformOperation::Matrix(interpolated));
+                }
+                _ => {
+                    result.extend_from_slice(if time < 0.5 { from_list } else { to_list });
+                }
+            }
         }
 
         TransformList(Some(result))
     }
 
+    /// Returns the matrix a transform list stands for, or `None` if it has
+    /// translations by a percentage of the size of the box.
+    /// https://drafts.csswg.org/css-transforms/#transform-functions
+    fn transform_list_to_matrix(list: &[TransformOperation]) -> Option<ComputedMatrix> {
+        fn length(value: LengthOrPercentage) -> Option<f32> {
+            let calc = CalcLengthOrPercentage::from(value);
+            if calc.percentage() != 0. || calc.comparisons.is_some() {
+                return None
+            }
+            Some(calc.length().to_f32_px())
+        }
+
+        let mut matrix = ComputedMatrix::identity();
+        for operation in list {
+            let operation_matrix = match *operation {
+                TransformOperation::Matrix(operation_matrix) => operation_matrix,
+                TransformOperation::Skew(ax, ay) => {
+                    let mut operation_matrix = ComputedMatrix::identity();
+                    operation_matrix.m21 = ax.radians().tan();
+                    operation_matrix.m12 = ay.radians().tan();
+                    operation_matrix
+                }
+                TransformOperation::Translate(tx, ty, tz) => {
+                    let mut operation_matrix = ComputedMatrix::identity();
+                    match (length(tx), length(ty)) {
+                        (Some(tx), Some(ty)) => {
+                            operation_matrix.m41 = tx;
+                            operation_matrix.m42 = ty;
+                        }
+                        _ => return None,
+                    }
+                    operation_matrix.m43 = tz.to_f32_px();
+                    operation_matrix
+                }
+                TransformOperation::Scale(sx, sy, sz) => {
+                    let mut operation_matrix = ComputedMatrix::identity();
+                    operation_matrix.m11 = sx;
+                    operation_matrix.m22 = sy;
+                    operation_matrix.m33 = sz;
+                    operation_matrix
+                }
+                TransformOperation::Rotate(x, y, z, angle) => {
+                    let norm = ((x * x) + (y * y) + (z * z)).sqrt();
+                    rotate_to_matrix(x / norm, y / norm, z / norm, angle)
+                }
+                TransformOperation::Perspective(d) => {
+                    let mut operation_matrix = ComputedMatrix::identity();
+                    if d > Au(0) {
+                        operation_matrix.m34 = -1. / d.to_f32_px();
+                    }
+                    operation_matrix
+                }
+            };
+            // The last function of the list applies first.
+            matrix = multiply(operation_matrix, matrix);
+        }
+        Some(matrix)
+    }
+
+    /// Our matrices apply to row vectors, so this is the transpose of the
+    /// matrix in the spec.
     /// https://drafts.csswg.org/css-transforms/#Rotate3dDefined
     fn rotate_to_matrix(x: f32, y: f32, z: f32, a: SpecifiedAngle) -> ComputedMatrix {
         let half_rad = a.radians() / 2.0;
@@ -774,17 +980,17 @@ impl Interpolate for LengthOrNone {
 
         ComputedMatrix {
             m11: 1.0 - 2.0 * (y * y + z * z) * sq,
-            m12: 2.0 * (x * y * sq - z * sc),
-            m13: 2.0 * (x * z * sq + y * sc),
+            m12: 2.0 * (x * y * sq + z * sc),
+            m13: 2.0 * (x * z * sq - y * sc),
             m14: 0.0,
 
-            m21: 2.0 * (x * y * sq + z * sc),
+            m21: 2.0 * (x * y * sq - z * sc),
             m22: 1.0 - 2.0 * (x * x + z * z) * sq,
-            m23: 2.0 * (y * z * sq - x * sc),
+            m23: 2.0 * (y * z * sq + x * sc),
             m24: 0.0,
 
-            m31: 2.0 * (x * z * sq - y * sc),
-            m32: 2.0 * (y * z * sq + x * sc),
+            m31: 2.0 * (x * z * sq + y * sc),
+            m32: 2.0 * (y * z * sq - x * sc),
             m33: 1.0 - 2.0 * (x * x + y * y) * sq,
             m34: 0.0,
 
@@ -1039,6 +1245,52 @@ impl Interpolate for LengthOrNone {
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub struct Quaternion(f32, f32, f32, f32);
 
+    impl Quaternion {
+        fn from_rotation(x: f32, y: f32, z: f32, angle: Angle) -> Quaternion {
+            let half_rad = angle.radians() / 2.0;
+            let sin = half_rad.sin();
+            Quaternion(x * sin, y * sin, z * sin, half_rad.cos())
+        }
+
+        /// The normalized axis and the angle of the rotation.
+        fn to_rotation(&self) -> (f32, f32, f32, Angle) {
+            let w = self.3.min(1.0).max(-1.0);
+            let angle = Angle(2.0 * w.acos());
+            let sin = (1.0 - w * w).sqrt();
+            if sin < 1e-6 {
+                return (0.0, 0.0, 1.0, angle);
+            }
+            (self.0 / sin, self.1 / sin, self.2 / sin, angle)
+        }
+    }
+
+    impl Interpolate for Quaternion {
+        /// Interpolate quaternions using spherical linear interpolation (Slerp).
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            let mut product = self.0 * other.0 +
+                              self.1 * other.1 +
+                              self.2 * other.2 +
+                              self.3 * other.3;
+
+            // Clamp product to -1.0 <= product <= 1.0
+            product = product.min(1.0);
+            product = product.max(-1.0);
+
+            if product == 1.0 {
+                return Ok(*self);
+            }
+
+            let theta = product.acos();
+            let w = (time as f32 * theta).sin() * 1.0 / (1.0 - product * product).sqrt();
+            let a = (time as f32 * theta).cos() - product * w;
+
+            Ok(Quaternion(self.0 * a + other.0 * w,
+                          self.1 * a + other.1 * w,
+                          self.2 * a + other.2 * w,
+                          self.3 * a + other.3 * w))
+        }
+    }
+
     #[derive(Clone, Copy, Debug)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub struct MatrixDecomposed3D {
@@ -1269,30 +1521,7 @@ impl Interpolate for LengthOrNone {
             interpolated.skew = try!(self.skew.interpolate(&other.skew, time));
             interpolated.perspective = try!(self.perspective.interpolate(&other.perspective, time));
 
-            // Interpolate quaternions using spherical linear interpolation (Slerp).
-            let mut product = self.quaternion.0 * other.quaternion.0 +
-                              self.quaternion.1 * other.quaternion.1 +
-                              self.quaternion.2 * other.quaternion.2 +
-                              self.quaternion.3 * other.quaternion.3;
-
-            // Clamp product to -1.0 <= product <= 1.0
-            product = product.min(1.0);
-            product = product.max(-1.0);
-
-            if product == 1.0 {
-                return Ok(interpolated);
-            }
-
-            let theta = product.acos();
-            let w = (time as f32 * theta).sin() * 1.0 / (1.0 - product * product).sqrt();
-
-            let mut a = *self;
-            let mut b = *other;
-            % for i in range(4):
-                a.quaternion.${i} *= (time as f32 * theta).cos() - product * w;
-                b.quaternion.${i} *= w;
-                interpolated.quaternion.${i} = a.quaternion.${i} + b.quaternion.${i};
-            % endfor
+            interpolated.quaternion = try!(self.quaternion.interpolate(&other.quaternion, time));
 
             Ok(interpolated)
         }
@@ -1524,6 +1753,256 @@ impl Interpolate for LengthOrNone {
             Ok(result)
         }
     }
+
+    /// https://drafts.csswg.org/css-align/#column-row-gap
+    impl Interpolate for Gap {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            match (self.0, other.0) {
+                (Some(ref this), Some(ref other)) => this.interpolate(other, time).map(|gap| Gap(Some(gap))),
+                _ => Err(()),
+            }
+        }
+    }
+
+    /// https://drafts.csswg.org/css-transforms-2/#individual-transforms
+    impl Interpolate for Translate {
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            if self.0.is_none() && other.0.is_none() {
+                return Ok(Translate(None));
+            }
+            let identity = (LengthOrPercentage::zero(), LengthOrPercentage::zero(), Au(0));
+            let (fx, fy, fz) = self.0.unwrap_or(identity);
+            let (tx, ty, tz) = other.0.unwrap_or(identity);
+            Ok(Translate(Some((try!(fx.interpolate(&tx, time)),
+                               try!(fy.interpolate(&ty, time)),
+                               try!(fz.interpolate(&tz, time))))))
+        }
+    }
+
+    /// A rotation about the same axis interpolates its angle, and others
+    /// interpolate as quaternions.
+    /// https://drafts.csswg.org/css-transforms-2/#individual-transforms
+    impl Interpolate for Rotate {
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            let (from, to) = match (self.0, other.0) {
+                (None, None) => return Ok(Rotate(None)),
+                (Some(from), None) => (from, (from.0, from.1, from.2, Angle(0.0))),
+                (None, Some(to)) => ((to.0, to.1, to.2, Angle(0.0)), to),
+                (Some(from), Some(to)) => (from, to),
+            };
+            let norm_f = ((from.0 * from.0) + (from.1 * from.1) + (from.2 * from.2)).sqrt();
+            let norm_t = ((to.0 * to.0) + (to.1 * to.1) + (to.2 * to.2)).sqrt();
+            let (fx, fy, fz) = (from.0 / norm_f, from.1 / norm_f, from.2 / norm_f);
+            let (tx, ty, tz) = (to.0 / norm_t, to.1 / norm_t, to.2 / norm_t);
+            if fx == tx && fy == ty && fz == tz {
+                let angle = try!(from.3.interpolate(&to.3, time));
+                return Ok(Rotate(Some((from.0, from.1, from.2, angle))));
+            }
+            let from = Quaternion::from_rotation(fx, fy, fz, from.3);
+            let to = Quaternion::from_rotation(tx, ty, tz, to.3);
+            Ok(Rotate(Some(try!(from.interpolate(&to, time)).to_rotation())))
+        }
+    }
+
+    /// https://drafts.csswg.org/css-transforms-2/#individual-transforms
+    impl Interpolate for Scale {
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            if self.0.is_none() && other.0.is_none() {
+                return Ok(Scale(None));
+            }
+            let (fx, fy, fz) = self.0.unwrap_or((1.0, 1.0, 1.0));
+            let (tx, ty, tz) = other.0.unwrap_or((1.0, 1.0, 1.0));
+            Ok(Scale(Some((try!(fx.interpolate(&tx, time)),
+                           try!(fy.interpolate(&ty, time)),
+                           try!(fz.interpolate(&tz, time))))))
+        }
+    }
+    /// `normal` is no extra spacing.
+    /// https://drafts.csswg.org/css-text/#letter-spacing-property
+    impl Interpolate for LetterSpacing {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            let this = self.0.unwrap_or(Au(0));
+            let other = other.0.unwrap_or(Au(0));
+            this.interpolate(&other, time).map(|spacing| LetterSpacing(Some(spacing)))
+        }
+    }
+
+    /// `normal` is no extra spacing.
+    /// https://drafts.csswg.org/css-text/#word-spacing-property
+    impl Interpolate for WordSpacing {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            let this = self.0.unwrap_or(LengthOrPercentage::zero());
+            let other = other.0.unwrap_or(LengthOrPercentage::zero());
+            this.interpolate(&other, time).map(|spacing| WordSpacing(Some(spacing)))
+        }
+    }
+
+    /// https://drafts.csswg.org/css-multicol/#cw
+    impl Interpolate for ColumnWidth {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            match (self.0, other.0) {
+                (Some(ref this), Some(ref other)) => {
+                    this.interpolate(other, time).map(|width| ColumnWidth(Some(width)))
+                }
+                _ => Err(()),
+            }
+        }
+    }
+
+    /// https://drafts.csswg.org/css-multicol/#cc
+    impl Interpolate for ColumnCount {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            match (self.0, other.0) {
+                (Some(ref this), Some(ref other)) => {
+                    this.interpolate(other, time).map(|count| ColumnCount(Some(count)))
+                }
+                _ => Err(()),
+            }
+        }
+    }
+
+    /// https://drafts.csswg.org/css-flexbox/#flex-basis-property
+    impl Interpolate for LengthOrPercentageOrAutoOrContent {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            fn to_length_or_percentage(value: LengthOrPercentageOrAutoOrContent) -> Option<LengthOrPercentage> {
+                match value {
+                    LengthOrPercentageOrAutoOrContent::Length(length) => Some(LengthOrPercentage::Length(length)),
+                    LengthOrPercentageOrAutoOrContent::Percentage(percentage) => {
+                        Some(LengthOrPercentage::Percentage(percentage))
+                    }
+                    LengthOrPercentageOrAutoOrContent::Calc(calc) => Some(LengthOrPercentage::Calc(calc)),
+                    LengthOrPercentageOrAutoOrContent::Auto |
+                    LengthOrPercentageOrAutoOrContent::Content => None,
+                }
+            }
+
+            match (to_length_or_percentage(*self), to_length_or_percentage(*other)) {
+                (Some(ref this), Some(ref other)) => {
+                    this.interpolate(other, time).map(|value| match value {
+                        LengthOrPercentage::Length(length) => LengthOrPercentageOrAutoOrContent::Length(length),
+                        LengthOrPercentage::Percentage(percentage) => {
+                            LengthOrPercentageOrAutoOrContent::Percentage(percentage)
+                        }
+                        LengthOrPercentage::Calc(calc) => LengthOrPercentageOrAutoOrContent::Calc(calc),
+                    })
+                }
+                _ => Err(()),
+            }
+        }
+    }
+
+    /// Gradients interpolate when they are of the same kind and have the same
+    /// number of color stops. Other images don't.
+    /// https://drafts.csswg.org/css-images/#interpolation
+    impl Interpolate for Image {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            match (self, other) {
+                (&Image::LinearGradient(ref this), &Image::LinearGradient(ref other)) => {
+                    this.interpolate(other, time).map(Image::LinearGradient)
+                }
+                (&Image::ConicGradient(ref this), &Image::ConicGradient(ref other)) => {
+                    this.interpolate(other, time).map(Image::ConicGradient)
+                }
+                _ => Err(()),
+            }
+        }
+    }
+
+    impl Interpolate for LinearGradient {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            if self.repeating != other.repeating {
+                return Err(())
+            }
+            let angle_or_corner = match (self.angle_or_corner, other.angle_or_corner) {
+                (AngleOrCorner::Angle(ref this), AngleOrCorner::Angle(ref other)) => {
+                    AngleOrCorner::Angle(try!(this.interpolate(other, time)))
+                }
+                (this, other) if this == other => this,
+                _ => return Err(()),
+            };
+            Ok(LinearGradient {
+                angle_or_corner: angle_or_corner,
+                stops: try!(interpolate_gradient_items(&self.stops, &other.stops, time)),
+                repeating: self.repeating,
+            })
+        }
+    }
+
+    impl Interpolate for ConicGradient {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            if self.repeating != other.repeating {
+                return Err(())
+            }
+            Ok(ConicGradient {
+                angle: try!(self.angle.interpolate(&other.angle, time)),
+                position: try!(self.position.interpolate(&other.position, time)),
+                stops: try!(interpolate_gradient_items(&self.stops, &other.stops, time)),
+                repeating: self.repeating,
+            })
+        }
+    }
+
+    fn interpolate_gradient_items(from: &[GradientItem],
+                                  to: &[GradientItem],
+                                  time: f64)
+                                  -> Result<Vec<GradientItem>, ()> {
+        if from.len() != to.len() {
+            return Err(())
+        }
+        from.iter().zip(to).map(|(from, to)| from.interpolate(to, time)).collect()
+    }
+
+    impl Interpolate for GradientItem {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            match (*self, *other) {
+                (GradientItem::ColorStop(ref this), GradientItem::ColorStop(ref other)) => {
+                    let position = match (this.position, other.position) {
+                        (Some(ref this), Some(ref other)) => Some(try!(this.interpolate(other, time))),
+                        (None, None) => None,
+                        _ => return Err(()),
+                    };
+                    Ok(GradientItem::ColorStop(ColorStop {
+                        color: try!(this.color.interpolate(&other.color, time)),
+                        position: position,
+                    }))
+                }
+                (GradientItem::InterpolationHint(ref this), GradientItem::InterpolationHint(ref other)) => {
+                    this.interpolate(other, time).map(GradientItem::InterpolationHint)
+                }
+                _ => Err(()),
+            }
+        }
+    }
+
+    /// Each layer interpolates with the layer of the other list at the same
+    /// index, and lists of different lengths don't interpolate.
+    impl Interpolate for BackgroundImage {
+        #[inline]
+        fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+            if self.0.len() != other.0.len() {
+                return Err(())
+            }
+            self.0.iter().zip(&other.0).map(|(this, other)| {
+                match (&this.0, &other.0) {
+                    (&Some(ref this), &Some(ref other)) => {
+                        this.interpolate(other, time).map(|image| SingleBackgroundImage(Some(image)))
+                    }
+                    (&None, &None) => Ok(SingleBackgroundImage(None)),
+                    _ => Err(()),
+                }
+            }).collect::<Result<Vec<_>, ()>>().map(BackgroundImage)
+        }
+    }
 % endif
 
 
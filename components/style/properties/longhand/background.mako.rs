@@ -10,7 +10,7 @@ ${helpers.predefined_type("background-color", "CSSColor",
     "::cssparser::Color::RGBA(::cssparser::RGBA { red: 0., green: 0., blue: 0., alpha: 0. }) /* transparent */",
     animatable=True)}
 
-<%helpers:vector_longhand name="background-image" animatable="False">
+<%helpers:vector_longhand name="background-image" animatable="servo">
     use cssparser::ToCss;
     use std::fmt;
     use values::specified::Image;
@@ -147,6 +147,13 @@ ${helpers.single_keyword("background-origin",
                          vector=True,
                          animatable=False)}
 
+<%helpers:vector_longhand name="background-blend-mode" animatable="False">
+    // Layers take the same blend modes as elements do with `mix-blend-mode`.
+    pub use properties::longhands::mix_blend_mode::{SpecifiedValue, computed_value};
+    pub use properties::longhands::mix_blend_mode::{get_initial_specified_value, get_initial_value};
+    pub use properties::longhands::mix_blend_mode::parse;
+</%helpers:vector_longhand>
+
 <%helpers:vector_longhand name="background-size" animatable="True">
     use cssparser::{ToCss, Token};
     use std::ascii::AsciiExt;
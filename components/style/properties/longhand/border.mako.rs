@@ -45,7 +45,7 @@
 
         impl HasViewportPercentage for SpecifiedValue {
             fn has_viewport_percentage(&self) -> bool {
-                let &SpecifiedValue(length) = self;
+                let &SpecifiedValue(ref length) = self;
                 length.has_viewport_percentage()
             }
         }
@@ -91,3 +91,479 @@ ${helpers.single_keyword("-moz-float-edge", "content-box margin-box",
                          gecko_enum_prefix="StyleFloatEdge",
                          products="gecko",
                          animatable=False)}
+
+<%helpers:longhand name="border-image-source" products="servo" animatable="False">
+    // Border images take the same `none | <image>` values as background layers do.
+    pub use properties::longhands::background_image::single_value::{SpecifiedValue, computed_value};
+    pub use properties::longhands::background_image::single_value::get_initial_specified_value;
+    pub use properties::longhands::background_image::single_value::{get_initial_value, parse};
+</%helpers:longhand>
+
+<%helpers:longhand name="border-image-slice" products="servo" animatable="False">
+    use cssparser::{ToCss, Token};
+    use properties::shorthands::{parse_four_sides, serialize_four_sides};
+    use std::fmt;
+    use values::computed::ComputedValueAsSpecified;
+    use values::NoViewportPercentage;
+
+    pub use self::computed_value::T as SpecifiedValue;
+
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    pub mod computed_value {
+        use values::CSSFloat;
+
+        /// How far in from one edge of the image a slice is, in image pixels or as a fraction of
+        /// the size of the image.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub enum PercentageOrNumber {
+            Percentage(CSSFloat),
+            Number(CSSFloat),
+        }
+
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub struct T {
+            pub top: PercentageOrNumber,
+            pub right: PercentageOrNumber,
+            pub bottom: PercentageOrNumber,
+            pub left: PercentageOrNumber,
+            /// Whether the middle of the image is painted over the background.
+            pub fill: bool,
+        }
+    }
+
+    impl ToCss for computed_value::PercentageOrNumber {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                computed_value::PercentageOrNumber::Percentage(percentage) => {
+                    write!(dest, "{}%", percentage * 100.)
+                }
+                computed_value::PercentageOrNumber::Number(number) => write!(dest, "{}", number),
+            }
+        }
+    }
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            try!(serialize_four_sides(dest, &self.top, &self.right, &self.bottom, &self.left));
+            if self.fill {
+                try!(dest.write_str(" fill"));
+            }
+            Ok(())
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        let full = computed_value::PercentageOrNumber::Percentage(1.0);
+        computed_value::T { top: full, right: full, bottom: full, left: full, fill: false }
+    }
+
+    fn parse_percentage_or_number(input: &mut Parser)
+                                  -> Result<computed_value::PercentageOrNumber, ()> {
+        match try!(input.next()) {
+            Token::Percentage(ref value) if value.unit_value >= 0. => {
+                Ok(computed_value::PercentageOrNumber::Percentage(value.unit_value))
+            }
+            Token::Number(ref value) if value.value >= 0. => {
+                Ok(computed_value::PercentageOrNumber::Number(value.value))
+            }
+            _ => Err(())
+        }
+    }
+
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        let mut fill = input.try(|input| input.expect_ident_matching("fill")).is_ok();
+        let (top, right, bottom, left) = try!(parse_four_sides(input, parse_percentage_or_number));
+        if !fill {
+            fill = input.try(|input| input.expect_ident_matching("fill")).is_ok();
+        }
+        Ok(SpecifiedValue { top: top, right: right, bottom: bottom, left: left, fill: fill })
+    }
+</%helpers:longhand>
+
+<%helpers:longhand name="border-image-width" products="servo" animatable="False">
+    use cssparser::ToCss;
+    use properties::shorthands::{parse_four_sides, serialize_four_sides};
+    use std::fmt;
+    use values::{CSSFloat, HasViewportPercentage};
+
+    /// The width of one side of the border image, as a length, a multiple of the border width,
+    /// or the size of the corresponding slice of the image.
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub enum SingleSpecifiedValue {
+        LengthOrPercentage(specified::LengthOrPercentage),
+        Number(CSSFloat),
+        Auto,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub struct SpecifiedValue {
+        pub top: SingleSpecifiedValue,
+        pub right: SingleSpecifiedValue,
+        pub bottom: SingleSpecifiedValue,
+        pub left: SingleSpecifiedValue,
+    }
+
+    impl HasViewportPercentage for SpecifiedValue {
+        fn has_viewport_percentage(&self) -> bool {
+            [&self.top, &self.right, &self.bottom, &self.left].iter().any(|side| {
+                match **side {
+                    SingleSpecifiedValue::LengthOrPercentage(ref length) => {
+                        length.has_viewport_percentage()
+                    }
+                    SingleSpecifiedValue::Number(_) | SingleSpecifiedValue::Auto => false,
+                }
+            })
+        }
+    }
+
+    pub mod computed_value {
+        use values::CSSFloat;
+        use values::computed;
+
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub enum SingleComputedValue {
+            LengthOrPercentage(computed::LengthOrPercentage),
+            Number(CSSFloat),
+            Auto,
+        }
+
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub struct T {
+            pub top: SingleComputedValue,
+            pub right: SingleComputedValue,
+            pub bottom: SingleComputedValue,
+            pub left: SingleComputedValue,
+        }
+    }
+
+    impl ToCss for SingleSpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                SingleSpecifiedValue::LengthOrPercentage(ref length) => length.to_css(dest),
+                SingleSpecifiedValue::Number(number) => write!(dest, "{}", number),
+                SingleSpecifiedValue::Auto => dest.write_str("auto"),
+            }
+        }
+    }
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            serialize_four_sides(dest, &self.top, &self.right, &self.bottom, &self.left)
+        }
+    }
+
+    impl ToCss for computed_value::SingleComputedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                computed_value::SingleComputedValue::LengthOrPercentage(ref length) => length.to_css(dest),
+                computed_value::SingleComputedValue::Number(number) => write!(dest, "{}", number),
+                computed_value::SingleComputedValue::Auto => dest.write_str("auto"),
+            }
+        }
+    }
+
+    impl ToCss for computed_value::T {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            serialize_four_sides(dest, &self.top, &self.right, &self.bottom, &self.left)
+        }
+    }
+
+    impl ToComputedValue for SingleSpecifiedValue {
+        type ComputedValue = computed_value::SingleComputedValue;
+
+        #[inline]
+        fn to_computed_value(&self, context: &Context) -> computed_value::SingleComputedValue {
+            match *self {
+                SingleSpecifiedValue::LengthOrPercentage(ref length) => {
+                    computed_value::SingleComputedValue::LengthOrPercentage(
+                        length.to_computed_value(context))
+                }
+                SingleSpecifiedValue::Number(number) => {
+                    computed_value::SingleComputedValue::Number(number)
+                }
+                SingleSpecifiedValue::Auto => computed_value::SingleComputedValue::Auto,
+            }
+        }
+
+        #[inline]
+        fn from_computed_value(computed: &computed_value::SingleComputedValue) -> Self {
+            match *computed {
+                computed_value::SingleComputedValue::LengthOrPercentage(ref length) => {
+                    SingleSpecifiedValue::LengthOrPercentage(
+                        ToComputedValue::from_computed_value(length))
+                }
+                computed_value::SingleComputedValue::Number(number) => {
+                    SingleSpecifiedValue::Number(number)
+                }
+                computed_value::SingleComputedValue::Auto => SingleSpecifiedValue::Auto,
+            }
+        }
+    }
+
+    impl ToComputedValue for SpecifiedValue {
+        type ComputedValue = computed_value::T;
+
+        #[inline]
+        fn to_computed_value(&self, context: &Context) -> computed_value::T {
+            computed_value::T {
+                top: self.top.to_computed_value(context),
+                right: self.right.to_computed_value(context),
+                bottom: self.bottom.to_computed_value(context),
+                left: self.left.to_computed_value(context),
+            }
+        }
+
+        #[inline]
+        fn from_computed_value(computed: &computed_value::T) -> Self {
+            SpecifiedValue {
+                top: ToComputedValue::from_computed_value(&computed.top),
+                right: ToComputedValue::from_computed_value(&computed.right),
+                bottom: ToComputedValue::from_computed_value(&computed.bottom),
+                left: ToComputedValue::from_computed_value(&computed.left),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        let one = computed_value::SingleComputedValue::Number(1.0);
+        computed_value::T { top: one, right: one, bottom: one, left: one }
+    }
+
+    fn parse_single_value(input: &mut Parser) -> Result<SingleSpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("auto")).is_ok() {
+            return Ok(SingleSpecifiedValue::Auto)
+        }
+        if let Ok(number) = input.try(specified::parse_number) {
+            if number < 0. {
+                return Err(())
+            }
+            return Ok(SingleSpecifiedValue::Number(number))
+        }
+        specified::LengthOrPercentage::parse_non_negative(input)
+            .map(SingleSpecifiedValue::LengthOrPercentage)
+    }
+
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        let (top, right, bottom, left) = try!(parse_four_sides(input, parse_single_value));
+        Ok(SpecifiedValue { top: top, right: right, bottom: bottom, left: left })
+    }
+</%helpers:longhand>
+
+<%helpers:longhand name="border-image-outset" products="servo" animatable="False">
+    use app_units::Au;
+    use cssparser::ToCss;
+    use properties::shorthands::{parse_four_sides, serialize_four_sides};
+    use std::fmt;
+    use values::{CSSFloat, HasViewportPercentage, LocalToCss};
+
+    /// How far one side of the border image extends beyond the border box, as a length or a
+    /// multiple of the border width.
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub enum SingleSpecifiedValue {
+        Length(specified::Length),
+        Number(CSSFloat),
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub struct SpecifiedValue {
+        pub top: SingleSpecifiedValue,
+        pub right: SingleSpecifiedValue,
+        pub bottom: SingleSpecifiedValue,
+        pub left: SingleSpecifiedValue,
+    }
+
+    impl HasViewportPercentage for SpecifiedValue {
+        fn has_viewport_percentage(&self) -> bool {
+            [&self.top, &self.right, &self.bottom, &self.left].iter().any(|side| {
+                match **side {
+                    SingleSpecifiedValue::Length(ref length) => length.has_viewport_percentage(),
+                    SingleSpecifiedValue::Number(_) => false,
+                }
+            })
+        }
+    }
+
+    pub mod computed_value {
+        use app_units::Au;
+        use values::CSSFloat;
+
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub enum SingleComputedValue {
+            Length(Au),
+            Number(CSSFloat),
+        }
+
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub struct T {
+            pub top: SingleComputedValue,
+            pub right: SingleComputedValue,
+            pub bottom: SingleComputedValue,
+            pub left: SingleComputedValue,
+        }
+    }
+
+    impl ToCss for SingleSpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                SingleSpecifiedValue::Length(ref length) => length.to_css(dest),
+                SingleSpecifiedValue::Number(number) => write!(dest, "{}", number),
+            }
+        }
+    }
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            serialize_four_sides(dest, &self.top, &self.right, &self.bottom, &self.left)
+        }
+    }
+
+    impl ToCss for computed_value::SingleComputedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                computed_value::SingleComputedValue::Length(length) => length.to_css(dest),
+                computed_value::SingleComputedValue::Number(number) => write!(dest, "{}", number),
+            }
+        }
+    }
+
+    impl ToCss for computed_value::T {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            serialize_four_sides(dest, &self.top, &self.right, &self.bottom, &self.left)
+        }
+    }
+
+    impl ToComputedValue for SingleSpecifiedValue {
+        type ComputedValue = computed_value::SingleComputedValue;
+
+        #[inline]
+        fn to_computed_value(&self, context: &Context) -> computed_value::SingleComputedValue {
+            match *self {
+                SingleSpecifiedValue::Length(ref length) => {
+                    computed_value::SingleComputedValue::Length(length.to_computed_value(context))
+                }
+                SingleSpecifiedValue::Number(number) => {
+                    computed_value::SingleComputedValue::Number(number)
+                }
+            }
+        }
+
+        #[inline]
+        fn from_computed_value(computed: &computed_value::SingleComputedValue) -> Self {
+            match *computed {
+                computed_value::SingleComputedValue::Length(ref length) => {
+                    SingleSpecifiedValue::Length(ToComputedValue::from_computed_value(length))
+                }
+                computed_value::SingleComputedValue::Number(number) => {
+                    SingleSpecifiedValue::Number(number)
+                }
+            }
+        }
+    }
+
+    impl ToComputedValue for SpecifiedValue {
+        type ComputedValue = computed_value::T;
+
+        #[inline]
+        fn to_computed_value(&self, context: &Context) -> computed_value::T {
+            computed_value::T {
+                top: self.top.to_computed_value(context),
+                right: self.right.to_computed_value(context),
+                bottom: self.bottom.to_computed_value(context),
+                left: self.left.to_computed_value(context),
+            }
+        }
+
+        #[inline]
+        fn from_computed_value(computed: &computed_value::T) -> Self {
+            SpecifiedValue {
+                top: ToComputedValue::from_computed_value(&computed.top),
+                right: ToComputedValue::from_computed_value(&computed.right),
+                bottom: ToComputedValue::from_computed_value(&computed.bottom),
+                left: ToComputedValue::from_computed_value(&computed.left),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        let zero = computed_value::SingleComputedValue::Length(Au(0));
+        computed_value::T { top: zero, right: zero, bottom: zero, left: zero }
+    }
+
+    fn parse_single_value(input: &mut Parser) -> Result<SingleSpecifiedValue, ()> {
+        if let Ok(number) = input.try(specified::parse_number) {
+            if number < 0. {
+                return Err(())
+            }
+            return Ok(SingleSpecifiedValue::Number(number))
+        }
+        specified::Length::parse_non_negative(input).map(SingleSpecifiedValue::Length)
+    }
+
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        let (top, right, bottom, left) = try!(parse_four_sides(input, parse_single_value));
+        Ok(SpecifiedValue { top: top, right: right, bottom: bottom, left: left })
+    }
+</%helpers:longhand>
+
+<%helpers:longhand name="border-image-repeat" products="servo" animatable="False">
+    use cssparser::ToCss;
+    use std::fmt;
+    use values::computed::ComputedValueAsSpecified;
+    use values::NoViewportPercentage;
+
+    pub use self::computed_value::T as SpecifiedValue;
+
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    pub mod computed_value {
+        define_css_keyword_enum!(RepeatKeyword:
+                                 "stretch" => stretch,
+                                 "repeat" => repeat,
+                                 "round" => round,
+                                 "space" => space);
+
+        /// How the edges and middle of the image are filled horizontally and vertically.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub struct T(pub RepeatKeyword, pub RepeatKeyword);
+    }
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            try!(self.0.to_css(dest));
+            if self.1 != self.0 {
+                try!(dest.write_str(" "));
+                try!(self.1.to_css(dest));
+            }
+            Ok(())
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::T(computed_value::RepeatKeyword::stretch,
+                          computed_value::RepeatKeyword::stretch)
+    }
+
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        let horizontal = try!(computed_value::RepeatKeyword::parse(input));
+        let vertical = input.try(computed_value::RepeatKeyword::parse).unwrap_or(horizontal);
+        Ok(computed_value::T(horizontal, vertical))
+    }
+</%helpers:longhand>
@@ -90,8 +90,8 @@
 
 </%helpers:longhand>
 
-${helpers.single_keyword("position", "static absolute relative fixed",
-                         need_clone=True, extra_gecko_values="sticky", animatable=False)}
+${helpers.single_keyword("position", "static absolute relative fixed sticky",
+                         need_clone=True, animatable=False)}
 
 <%helpers:single_keyword_computed name="float"
                                   values="none left right"
@@ -161,14 +161,14 @@ ${helpers.single_keyword("clear", "none left right both",
   impl HasViewportPercentage for SpecifiedValue {
       fn has_viewport_percentage(&self) -> bool {
           match *self {
-              SpecifiedValue::LengthOrPercentage(length) => length.has_viewport_percentage(),
+              SpecifiedValue::LengthOrPercentage(ref length) => length.has_viewport_percentage(),
               _ => false
           }
       }
   }
 
   #[allow(non_camel_case_types)]
-  #[derive(Debug, Clone, PartialEq, Copy)]
+  #[derive(Debug, Clone, PartialEq)]
   #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
   pub enum SpecifiedValue {
       % for keyword in vertical_align_keywords:
@@ -183,7 +183,7 @@ ${helpers.single_keyword("clear", "none left right both",
               % for keyword in vertical_align_keywords:
                   SpecifiedValue::${to_rust_ident(keyword)} => dest.write_str("${keyword}"),
               % endfor
-              SpecifiedValue::LengthOrPercentage(value) => value.to_css(dest),
+              SpecifiedValue::LengthOrPercentage(ref value) => value.to_css(dest),
           }
       }
   }
@@ -240,7 +240,7 @@ ${helpers.single_keyword("clear", "none left right both",
                       computed_value::T::${to_rust_ident(keyword)}
                   }
               % endfor
-              SpecifiedValue::LengthOrPercentage(value) =>
+              SpecifiedValue::LengthOrPercentage(ref value) =>
                   computed_value::T::LengthOrPercentage(value.to_computed_value(context)),
           }
       }
@@ -273,6 +273,7 @@ ${helpers.single_keyword("overflow-clip-box", "padding-box content-box",
 
 // FIXME(pcwalton, #2742): Implement scrolling for `scroll` and `auto`.
 ${helpers.single_keyword("overflow-x", "visible hidden scroll auto",
+                         extra_servo_values="clip",
                          need_clone=True, animatable=False,
                          gecko_constant_prefix="NS_STYLE_OVERFLOW")}
 
@@ -314,6 +315,62 @@ ${helpers.single_keyword("overflow-x", "visible hidden scroll auto",
   }
 </%helpers:longhand>
 
+// https://drafts.csswg.org/css-scrollbars-1/#scrollbar-width
+${helpers.single_keyword("scrollbar-width", "auto thin none",
+                         products="servo", animatable=False)}
+
+// https://drafts.csswg.org/css-overflow-4/#scrollbar-gutter-property
+<%helpers:longhand name="scrollbar-gutter" products="servo" animatable="False">
+    use cssparser::ToCss;
+    use std::fmt;
+    use values::NoViewportPercentage;
+    use values::computed::ComputedValueAsSpecified;
+
+    pub use self::computed_value::T as SpecifiedValue;
+
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    pub mod computed_value {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub enum T {
+            Auto,
+            Stable,
+            StableBothEdges,
+        }
+    }
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                SpecifiedValue::Auto => dest.write_str("auto"),
+                SpecifiedValue::Stable => dest.write_str("stable"),
+                SpecifiedValue::StableBothEdges => dest.write_str("stable both-edges"),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::T::Auto
+    }
+
+    /// auto | stable && both-edges?
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("auto")).is_ok() {
+            return Ok(SpecifiedValue::Auto)
+        }
+        let both_edges = input.try(|input| input.expect_ident_matching("both-edges")).is_ok();
+        try!(input.expect_ident_matching("stable"));
+        if both_edges || input.try(|input| input.expect_ident_matching("both-edges")).is_ok() {
+            Ok(SpecifiedValue::StableBothEdges)
+        } else {
+            Ok(SpecifiedValue::Stable)
+        }
+    }
+</%helpers:longhand>
+
 // TODO(pcwalton): Multiple transitions.
 <%helpers:longhand name="transition-duration"
                    need_index="True"
@@ -866,7 +923,6 @@ ${helpers.single_keyword("scroll-snap-type-y",
 // http://www.w3.org/TR/compositing-1/
 ${helpers.single_keyword("isolation",
                          "auto isolate",
-                         products="gecko",
                          animatable=False)}
 
 ${helpers.single_keyword("page-break-after",
@@ -884,6 +940,220 @@ ${helpers.single_keyword("page-break-inside",
                          gecko_constant_prefix="NS_STYLE_PAGE_BREAK",
                          animatable=False)}
 
+// https://drafts.csswg.org/css-break/#break-between
+${helpers.single_keyword("break-before",
+                         "auto avoid avoid-page page left right recto verso avoid-column column",
+                         products="servo",
+                         animatable=False)}
+${helpers.single_keyword("break-after",
+                         "auto avoid avoid-page page left right recto verso avoid-column column",
+                         products="servo",
+                         animatable=False)}
+
+// https://drafts.csswg.org/css-break/#break-within
+${helpers.single_keyword("break-inside",
+                         "auto avoid avoid-page avoid-column",
+                         products="servo",
+                         animatable=False)}
+
+// CSS Containment Module Level 3
+// https://drafts.csswg.org/css-contain-3/#container-type
+${helpers.single_keyword("container-type",
+                         "normal size inline-size",
+                         products="servo",
+                         animatable=False)}
+
+<%helpers:longhand name="container-name" products="servo" animatable="False">
+    use cssparser::ToCss;
+    use std::ascii::AsciiExt;
+    use std::fmt;
+    use values::computed::ComputedValueAsSpecified;
+    use values::NoViewportPercentage;
+
+    pub mod computed_value {
+        pub type T = super::SpecifiedValue;
+    }
+
+    /// The names of a query container. `none` is the empty list.
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub struct SpecifiedValue(pub Vec<Atom>);
+
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            if self.0.is_empty() {
+                return dest.write_str("none")
+            }
+            for (i, name) in self.0.iter().enumerate() {
+                if i != 0 {
+                    try!(dest.write_str(" "));
+                }
+                try!(dest.write_str(&*name.to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        SpecifiedValue(vec![])
+    }
+
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("none")).is_ok() {
+            return Ok(SpecifiedValue(vec![]))
+        }
+        let mut names = vec![];
+        while let Ok(name) = input.try(|input| input.expect_ident()) {
+            // The keywords of container queries can't be container names.
+            if name.eq_ignore_ascii_case("none") || name.eq_ignore_ascii_case("and") ||
+               name.eq_ignore_ascii_case("or") || name.eq_ignore_ascii_case("not") {
+                return Err(())
+            }
+            names.push(Atom::from(&*name));
+        }
+        if names.is_empty() {
+            return Err(())
+        }
+        Ok(SpecifiedValue(names))
+    }
+</%helpers:longhand>
+
+// https://drafts.csswg.org/css-contain-2/#contain-property
+<%helpers:longhand name="contain" products="servo" animatable="False">
+    use cssparser::ToCss;
+    use std::fmt;
+    use values::computed::ComputedValueAsSpecified;
+    use values::NoViewportPercentage;
+
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    #[derive(PartialEq, Eq, Copy, Clone, Debug)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub struct SpecifiedValue {
+        pub size: bool,
+        pub layout: bool,
+        pub style: bool,
+        pub paint: bool,
+    }
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            if *self == computed_value::none {
+                return dest.write_str("none")
+            }
+            if *self == computed_value::strict {
+                return dest.write_str("strict")
+            }
+            if *self == computed_value::content {
+                return dest.write_str("content")
+            }
+            let mut space = false;
+            for &(name, value) in &[("size", self.size), ("layout", self.layout),
+                                   ("style", self.style), ("paint", self.paint)] {
+                if !value {
+                    continue
+                }
+                if space {
+                    try!(dest.write_str(" "));
+                }
+                try!(dest.write_str(name));
+                space = true;
+            }
+            Ok(())
+        }
+    }
+
+    pub mod computed_value {
+        pub type T = super::SpecifiedValue;
+        #[allow(non_upper_case_globals)]
+        pub const none: T = super::SpecifiedValue {
+            size: false, layout: false, style: false, paint: false
+        };
+        #[allow(non_upper_case_globals)]
+        pub const strict: T = super::SpecifiedValue {
+            size: true, layout: true, style: true, paint: true
+        };
+        #[allow(non_upper_case_globals)]
+        pub const content: T = super::SpecifiedValue {
+            size: false, layout: true, style: true, paint: true
+        };
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::none
+    }
+
+    /// none | strict | content | [ size || layout || style || paint ]
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("none")).is_ok() {
+            return Ok(computed_value::none)
+        }
+        if input.try(|input| input.expect_ident_matching("strict")).is_ok() {
+            return Ok(computed_value::strict)
+        }
+        if input.try(|input| input.expect_ident_matching("content")).is_ok() {
+            return Ok(computed_value::content)
+        }
+
+        let mut result = computed_value::none;
+        while let Ok(ident) = input.try(|input| input.expect_ident()) {
+            let value = match_ignore_ascii_case! { ident,
+                "size" => &mut result.size,
+                "layout" => &mut result.layout,
+                "style" => &mut result.style,
+                "paint" => &mut result.paint,
+                _ => return Err(())
+            };
+            if *value {
+                return Err(())
+            }
+            *value = true;
+        }
+        if result == computed_value::none {
+            return Err(())
+        }
+        Ok(result)
+    }
+</%helpers:longhand>
+
+// https://drafts.csswg.org/css-shapes/#shape-outside-property
+<%helpers:longhand name="shape-outside" products="servo" animatable="False">
+    use values::NoViewportPercentage;
+    use values::specified::basic_shape::{ShapeBox, ShapeSource};
+
+    pub mod computed_value {
+        use values::computed::basic_shape::{ShapeBox, ShapeSource};
+
+        pub type T = ShapeSource<ShapeBox>;
+    }
+
+    pub type SpecifiedValue = ShapeSource<ShapeBox>;
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        Default::default()
+    }
+
+    pub fn parse(context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        ShapeSource::parse(context, input)
+    }
+
+    impl NoViewportPercentage for SpecifiedValue {}
+</%helpers:longhand>
+
+// https://drafts.csswg.org/css-shapes/#shape-margin-property
+${helpers.predefined_type("shape-margin", "LengthOrPercentage",
+                          "computed::LengthOrPercentage::Length(Au(0))",
+                          "parse_non_negative",
+                          products="servo",
+                          animatable=False)}
+
 // CSS Basic User Interface Module Level 3
 // http://dev.w3.org/csswg/css-ui/
 ${helpers.single_keyword("resize",
@@ -973,6 +1243,7 @@ ${helpers.single_keyword("-moz-appearance",
                 base: Some(ref base),
                 referrer: Some(ref referrer),
                 principal: Some(ref principal),
+                ..
             } => {
                 let extra_data = UrlExtraData {
                     base: base.clone(),
@@ -7,7 +7,7 @@
 <% data.new_style_struct("Column", inherited=False) %>
 
 // FIXME: This prop should be animatable.
-<%helpers:longhand name="column-width" experimental="True" animatable="False">
+<%helpers:longhand name="column-width" experimental="True" animatable="servo">
     use cssparser::ToCss;
     use std::fmt;
     use values::LocalToCss;
@@ -16,13 +16,13 @@
     impl HasViewportPercentage for SpecifiedValue {
         fn has_viewport_percentage(&self) -> bool {
             match *self {
-                SpecifiedValue::Specified(length) => length.has_viewport_percentage(),
+                SpecifiedValue::Specified(ref length) => length.has_viewport_percentage(),
                 _ => false
             }
         }
     }
 
-    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub enum SpecifiedValue {
         Auto,
@@ -33,7 +33,7 @@
         fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
             match *self {
                 SpecifiedValue::Auto => dest.write_str("auto"),
-                SpecifiedValue::Specified(l) => l.to_css(dest),
+                SpecifiedValue::Specified(ref l) => l.to_css(dest),
             }
         }
     }
@@ -66,7 +66,7 @@
         fn to_computed_value(&self, context: &Context) -> computed_value::T {
             match *self {
                 SpecifiedValue::Auto => computed_value::T(None),
-                SpecifiedValue::Specified(l) =>
+                SpecifiedValue::Specified(ref l) =>
                     computed_value::T(Some(l.to_computed_value(context)))
             }
         }
@@ -90,7 +90,7 @@
 </%helpers:longhand>
 
 // FIXME: This prop should be animatable.
-<%helpers:longhand name="column-count" experimental="True" animatable="False">
+<%helpers:longhand name="column-count" experimental="True" animatable="servo">
     use cssparser::ToCss;
     use std::fmt;
     use values::NoViewportPercentage;
@@ -169,43 +169,41 @@
     }
 </%helpers:longhand>
 
-// FIXME: This prop should be animatable.
-<%helpers:longhand name="column-gap" experimental="True" products="servo" animatable="False">
+<%helpers:longhand name="column-gap" products="servo" animatable="True">
     use cssparser::ToCss;
     use std::fmt;
-    use values::LocalToCss;
     use values::HasViewportPercentage;
 
     impl HasViewportPercentage for SpecifiedValue {
         fn has_viewport_percentage(&self) -> bool {
             match *self {
-                SpecifiedValue::Specified(length) => length.has_viewport_percentage(),
+                SpecifiedValue::Specified(ref length) => length.has_viewport_percentage(),
                 _ => false
             }
         }
     }
 
-    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub enum SpecifiedValue {
         Normal,
-        Specified(specified::Length),
+        Specified(specified::LengthOrPercentage),
     }
 
     impl ToCss for SpecifiedValue {
         fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
             match *self {
                 SpecifiedValue::Normal => dest.write_str("normal"),
-                SpecifiedValue::Specified(l) => l.to_css(dest),
+                SpecifiedValue::Specified(ref l) => l.to_css(dest),
             }
         }
     }
 
     pub mod computed_value {
-        use app_units::Au;
-        #[derive(Debug, Clone, PartialEq)]
+        use values::computed::LengthOrPercentage;
+        #[derive(Debug, Clone, Copy, PartialEq)]
         #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
-        pub struct T(pub Option<Au>);
+        pub struct T(pub Option<LengthOrPercentage>);
     }
 
     impl ToCss for computed_value::T {
@@ -229,7 +227,7 @@
         fn to_computed_value(&self, context: &Context) -> computed_value::T {
             match *self {
                 SpecifiedValue::Normal => computed_value::T(None),
-                SpecifiedValue::Specified(l) =>
+                SpecifiedValue::Specified(ref l) =>
                     computed_value::T(Some(l.to_computed_value(context)))
             }
         }
@@ -243,11 +241,83 @@
         }
     }
 
+    /// `normal | <length-percentage>`, of which `normal` is no gap between
+    /// flex items and 1em between columns.
+    /// https://drafts.csswg.org/css-align/#column-row-gap
     pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
         if input.try(|input| input.expect_ident_matching("normal")).is_ok() {
             Ok(SpecifiedValue::Normal)
         } else {
-            specified::Length::parse_non_negative(input).map(SpecifiedValue::Specified)
+            specified::LengthOrPercentage::parse_non_negative(input).map(SpecifiedValue::Specified)
+        }
+    }
+</%helpers:longhand>
+
+// https://drafts.csswg.org/css-multicol/#cf
+${helpers.single_keyword("column-fill", "balance auto",
+                         products="servo",
+                         animatable=False)}
+
+// https://drafts.csswg.org/css-multicol/#column-rule-width
+<%helpers:longhand name="column-rule-width" products="servo" animatable="True">
+    use app_units::Au;
+    use cssparser::ToCss;
+    use std::fmt;
+    use values::LocalToCss;
+    use values::HasViewportPercentage;
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            self.0.to_css(dest)
+        }
+    }
+
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        specified::parse_border_width(input).map(SpecifiedValue)
+    }
+
+    impl HasViewportPercentage for SpecifiedValue {
+        fn has_viewport_percentage(&self) -> bool {
+            let &SpecifiedValue(ref length) = self;
+            length.has_viewport_percentage()
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub struct SpecifiedValue(pub specified::Length);
+    pub mod computed_value {
+        use app_units::Au;
+        pub type T = Au;
+    }
+    pub use super::border_top_width::get_initial_value;
+    impl ToComputedValue for SpecifiedValue {
+        type ComputedValue = computed_value::T;
+
+        #[inline]
+        fn to_computed_value(&self, context: &Context) -> computed_value::T {
+            self.0.to_computed_value(context)
+        }
+
+        #[inline]
+        fn from_computed_value(computed: &computed_value::T) -> Self {
+            SpecifiedValue(ToComputedValue::from_computed_value(computed))
         }
     }
 </%helpers:longhand>
+
+// https://drafts.csswg.org/css-multicol/#column-rule-style
+<%helpers:longhand name="column-rule-style" products="servo" animatable="False">
+    pub use values::specified::BorderStyle as SpecifiedValue;
+    pub fn get_initial_value() -> SpecifiedValue { SpecifiedValue::none }
+    pub mod computed_value {
+        pub use values::specified::BorderStyle as T;
+    }
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        SpecifiedValue::parse(input)
+    }
+</%helpers:longhand>
+
+// https://drafts.csswg.org/css-multicol/#column-rule-color
+${helpers.predefined_type("column-rule-color", "CSSColor", "::cssparser::Color::CurrentColor",
+                          products="servo", animatable=True)}
@@ -59,7 +59,7 @@
                         dest.write_str(")")
                     }
                     ContentItem::Counters(ref s, ref separator, ref list_style_type) => {
-                        try!(dest.write_str("counter("));
+                        try!(dest.write_str("counters("));
                         try!(cssparser::serialize_identifier(&**s, dest));
                         try!(dest.write_str(", "));
                         try!(cssparser::serialize_string(&**separator, dest));
@@ -200,6 +200,10 @@
 
     impl ToCss for SpecifiedValue {
         fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            if self.0.is_empty() {
+                return dest.write_str("none")
+            }
+
             let mut first = true;
             for pair in &self.0 {
                 if !first {
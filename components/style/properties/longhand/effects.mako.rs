@@ -127,7 +127,8 @@ ${helpers.predefined_type("opacity",
 
     pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
         use app_units::Au;
-        let mut lengths = [specified::Length::Absolute(Au(0)); 4];
+        let mut lengths = [specified::Length::Absolute(Au(0)), specified::Length::Absolute(Au(0)),
+                           specified::Length::Absolute(Au(0)), specified::Length::Absolute(Au(0))];
         let mut lengths_parsed = false;
         let mut color = None;
         let mut inset = false;
@@ -176,10 +177,10 @@ ${helpers.predefined_type("opacity",
         }
 
         Ok(SpecifiedValue {
-            offset_x: lengths[0],
-            offset_y: lengths[1],
-            blur_radius: lengths[2],
-            spread_radius: lengths[3],
+            offset_x: lengths[0].clone(),
+            offset_y: lengths[1].clone(),
+            blur_radius: lengths[2].clone(),
+            spread_radius: lengths[3].clone(),
             color: color,
             inset: inset,
         })
@@ -260,13 +261,13 @@ ${helpers.predefined_type("opacity",
     impl HasViewportPercentage for SpecifiedClipRect {
         fn has_viewport_percentage(&self) -> bool {
             self.top.has_viewport_percentage() ||
-            self.right.map_or(false, |x| x.has_viewport_percentage()) ||
-            self.bottom.map_or(false, |x| x.has_viewport_percentage()) ||
+            self.right.as_ref().map_or(false, |x| x.has_viewport_percentage()) ||
+            self.bottom.as_ref().map_or(false, |x| x.has_viewport_percentage()) ||
             self.left.has_viewport_percentage()
         }
     }
 
-    #[derive(Clone, Debug, PartialEq, Copy)]
+    #[derive(Clone, Debug, PartialEq)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub struct SpecifiedClipRect {
         pub top: specified::Length,
@@ -277,12 +278,12 @@ ${helpers.predefined_type("opacity",
 
     impl HasViewportPercentage for SpecifiedValue {
         fn has_viewport_percentage(&self) -> bool {
-            let &SpecifiedValue(clip) = self;
-            clip.map_or(false, |x| x.has_viewport_percentage())
+            let &SpecifiedValue(ref clip) = self;
+            clip.as_ref().map_or(false, |x| x.has_viewport_percentage())
         }
     }
 
-    #[derive(Clone, Debug, PartialEq, Copy)]
+    #[derive(Clone, Debug, PartialEq)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub struct SpecifiedValue(Option<SpecifiedClipRect>);
 
@@ -293,14 +294,14 @@ ${helpers.predefined_type("opacity",
             try!(self.top.to_css(dest));
             try!(dest.write_str(", "));
 
-            if let Some(right) = self.right {
+            if let Some(ref right) = self.right {
                 try!(right.to_css(dest));
                 try!(dest.write_str(", "));
             } else {
                 try!(dest.write_str("auto, "));
             }
 
-            if let Some(bottom) = self.bottom {
+            if let Some(ref bottom) = self.bottom {
                 try!(bottom.to_css(dest));
                 try!(dest.write_str(", "));
             } else {
@@ -334,10 +335,10 @@ ${helpers.predefined_type("opacity",
 
         #[inline]
         fn to_computed_value(&self, context: &Context) -> computed_value::T {
-            computed_value::T(self.0.map(|value| computed_value::ClipRect {
+            computed_value::T(self.0.as_ref().map(|value| computed_value::ClipRect {
                 top: value.top.to_computed_value(context),
-                right: value.right.map(|right| right.to_computed_value(context)),
-                bottom: value.bottom.map(|bottom| bottom.to_computed_value(context)),
+                right: value.right.as_ref().map(|right| right.to_computed_value(context)),
+                bottom: value.bottom.as_ref().map(|bottom| bottom.to_computed_value(context)),
                 left: value.left.to_computed_value(context),
             }))
         }
@@ -424,7 +425,7 @@ ${helpers.predefined_type("opacity",
     impl HasViewportPercentage for SpecifiedFilter {
         fn has_viewport_percentage(&self) -> bool {
             match *self {
-                SpecifiedFilter::Blur(length) => length.has_viewport_percentage(),
+                SpecifiedFilter::Blur(ref length) => length.has_viewport_percentage(),
                 _ => false
             }
         }
@@ -567,7 +568,7 @@ ${helpers.predefined_type("opacity",
     impl ToCss for SpecifiedFilter {
         fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
             match *self {
-                SpecifiedFilter::Blur(value) => {
+                SpecifiedFilter::Blur(ref value) => {
                     try!(dest.write_str("blur("));
                     try!(value.to_css(dest));
                     try!(dest.write_str(")"));
@@ -638,7 +639,7 @@ ${helpers.predefined_type("opacity",
         fn to_computed_value(&self, context: &Context) -> computed_value::T {
             computed_value::T{ filters: self.0.iter().map(|value| {
                 match *value {
-                    SpecifiedFilter::Blur(factor) =>
+                    SpecifiedFilter::Blur(ref factor) =>
                         computed_value::Filter::Blur(factor.to_computed_value(context)),
                     SpecifiedFilter::Brightness(factor) => computed_value::Filter::Brightness(factor),
                     SpecifiedFilter::Contrast(factor) => computed_value::Filter::Contrast(factor),
@@ -786,12 +787,12 @@ ${helpers.predefined_type("opacity",
     impl HasViewportPercentage for SpecifiedOperation {
         fn has_viewport_percentage(&self) -> bool {
             match *self {
-                SpecifiedOperation::Translate(_, l1, l2, l3) => {
+                SpecifiedOperation::Translate(_, ref l1, ref l2, ref l3) => {
                     l1.has_viewport_percentage() ||
                     l2.has_viewport_percentage() ||
                     l3.has_viewport_percentage()
                 },
-                SpecifiedOperation::Perspective(length) => length.has_viewport_percentage(),
+                SpecifiedOperation::Perspective(ref length) => length.has_viewport_percentage(),
                 _ => false
             }
         }
@@ -808,7 +809,7 @@ ${helpers.predefined_type("opacity",
                 SpecifiedOperation::Skew(_sx, _sy) => {
                     Ok(())
                 }
-                SpecifiedOperation::Translate(kind, tx, ty, tz) => {
+                SpecifiedOperation::Translate(kind, ref tx, ref ty, ref tz) => {
                     match kind {
                         TranslateKind::Translate => {
                             try!(dest.write_str("translate("));
@@ -849,7 +850,7 @@ ${helpers.predefined_type("opacity",
                 SpecifiedOperation::Rotate(_ax, _ay, _az, _angle) => {
                     Ok(())
                 }
-                SpecifiedOperation::Perspective(_p) => {
+                SpecifiedOperation::Perspective(_) => {
                     Ok(())
                 }
             }
@@ -1144,7 +1145,7 @@ ${helpers.predefined_type("opacity",
                     SpecifiedOperation::Skew(theta_x, theta_y) => {
                         result.push(computed_value::ComputedOperation::Skew(theta_x, theta_y));
                     }
-                    SpecifiedOperation::Perspective(d) => {
+                    SpecifiedOperation::Perspective(ref d) => {
                         result.push(computed_value::ComputedOperation::Perspective(d.to_computed_value(context)));
                     }
                 };
@@ -1192,6 +1193,255 @@ ${helpers.predefined_type("opacity",
     }
 </%helpers:longhand>
 
+// https://drafts.csswg.org/css-transforms-2/#individual-transforms
+<%helpers:longhand name="translate" products="servo" animatable="True">
+    use app_units::Au;
+    use cssparser::ToCss;
+    use std::fmt;
+    use values::HasViewportPercentage;
+    use values::LocalToCss;
+
+    pub mod computed_value {
+        use values::computed::{Length, LengthOrPercentage};
+
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub struct T(pub Option<(LengthOrPercentage, LengthOrPercentage, Length)>);
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub struct SpecifiedValue(Option<(specified::LengthOrPercentage,
+                                      Option<specified::LengthOrPercentage>,
+                                      Option<specified::Length>)>);
+
+    impl HasViewportPercentage for SpecifiedValue {
+        fn has_viewport_percentage(&self) -> bool {
+            match self.0 {
+                Some((ref x, ref y, ref z)) => {
+                    x.has_viewport_percentage() ||
+                    y.as_ref().map_or(false, |y| y.has_viewport_percentage()) ||
+                    z.as_ref().map_or(false, |z| z.has_viewport_percentage())
+                }
+                None => false,
+            }
+        }
+    }
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            let (x, y, z) = match self.0 {
+                Some((ref x, ref y, ref z)) => (x, y, z),
+                None => return dest.write_str("none"),
+            };
+            try!(x.to_css(dest));
+            if let Some(ref y) = *y {
+                try!(dest.write_str(" "));
+                try!(y.to_css(dest));
+            }
+            if let Some(ref z) = *z {
+                try!(dest.write_str(" "));
+                try!(z.to_css(dest));
+            }
+            Ok(())
+        }
+    }
+
+    impl ToCss for computed_value::T {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            let (x, y, z) = match self.0 {
+                Some(translation) => translation,
+                None => return dest.write_str("none"),
+            };
+            try!(x.to_css(dest));
+            if !y.is_definitely_zero() || z != Au(0) {
+                try!(dest.write_str(" "));
+                try!(y.to_css(dest));
+            }
+            if z != Au(0) {
+                try!(dest.write_str(" "));
+                try!(z.to_css(dest));
+            }
+            Ok(())
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::T(None)
+    }
+
+    /// `none | <length-percentage> [ <length-percentage> <length>? ]?`
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("none")).is_ok() {
+            return Ok(SpecifiedValue(None))
+        }
+        let x = try!(specified::LengthOrPercentage::parse(input));
+        let y = input.try(specified::LengthOrPercentage::parse).ok();
+        let z = if y.is_some() {
+            input.try(specified::Length::parse).ok()
+        } else {
+            None
+        };
+        Ok(SpecifiedValue(Some((x, y, z))))
+    }
+
+    impl ToComputedValue for SpecifiedValue {
+        type ComputedValue = computed_value::T;
+
+        #[inline]
+        fn to_computed_value(&self, context: &Context) -> computed_value::T {
+            computed_value::T(self.0.as_ref().map(|&(ref x, ref y, ref z)| {
+                (x.to_computed_value(context),
+                 y.as_ref().map_or(computed::LengthOrPercentage::zero(), |y| y.to_computed_value(context)),
+                 z.as_ref().map_or(Au(0), |z| z.to_computed_value(context)))
+            }))
+        }
+
+        #[inline]
+        fn from_computed_value(computed: &computed_value::T) -> Self {
+            SpecifiedValue(computed.0.map(|(x, y, z)| {
+                (ToComputedValue::from_computed_value(&x),
+                 Some(ToComputedValue::from_computed_value(&y)),
+                 Some(ToComputedValue::from_computed_value(&z)))
+            }))
+        }
+    }
+</%helpers:longhand>
+
+// https://drafts.csswg.org/css-transforms-2/#individual-transforms
+<%helpers:longhand name="rotate" products="servo" animatable="True">
+    use cssparser::ToCss;
+    use std::fmt;
+    use values::CSSFloat;
+    use values::NoViewportPercentage;
+    use values::computed::ComputedValueAsSpecified;
+
+    pub mod computed_value {
+        use values::CSSFloat;
+        use values::computed::Angle;
+
+        /// The axis, which is kept as specified, and the angle.
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub struct T(pub Option<(CSSFloat, CSSFloat, CSSFloat, Angle)>);
+    }
+
+    pub use self::computed_value::T as SpecifiedValue;
+
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    impl ToCss for computed_value::T {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            let (x, y, z, angle) = match self.0 {
+                Some(rotation) => rotation,
+                None => return dest.write_str("none"),
+            };
+            if (x, y, z) == (1.0, 0.0, 0.0) {
+                try!(dest.write_str("x "));
+            } else if (x, y, z) == (0.0, 1.0, 0.0) {
+                try!(dest.write_str("y "));
+            } else if (x, y, z) != (0.0, 0.0, 1.0) {
+                try!(write!(dest, "{} {} {} ", x, y, z));
+            }
+            angle.to_css(dest)
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::T(None)
+    }
+
+    fn parse_axis(input: &mut Parser) -> Result<(CSSFloat, CSSFloat, CSSFloat), ()> {
+        input.try(|input| {
+            match_ignore_ascii_case! { try!(input.expect_ident()),
+                "x" => Ok((1.0, 0.0, 0.0)),
+                "y" => Ok((0.0, 1.0, 0.0)),
+                "z" => Ok((0.0, 0.0, 1.0)),
+                _ => Err(())
+            }
+        }).or_else(|()| input.try(|input| {
+            let x = try!(specified::parse_number(input));
+            let y = try!(specified::parse_number(input));
+            let z = try!(specified::parse_number(input));
+            Ok((x, y, z))
+        }))
+    }
+
+    /// `none | <angle> | [ x | y | z | <number>{3} ] && <angle>`
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("none")).is_ok() {
+            return Ok(computed_value::T(None))
+        }
+        // The axis is tried first, since its first number could otherwise be taken for a
+        // unitless zero angle.
+        let axis = parse_axis(input);
+        let angle = try!(specified::Angle::parse(input));
+        let axis = axis.or_else(|()| parse_axis(input));
+        let (x, y, z) = axis.unwrap_or((0.0, 0.0, 1.0));
+        // An axis that can't be normalized, including one too small or too large for
+        // its length to be represented, would give a rotation matrix of NaNs.
+        let length = (x * x + y * y + z * z).sqrt();
+        if length == 0.0 || !length.is_finite() {
+            return Err(())
+        }
+        Ok(computed_value::T(Some((x, y, z, angle))))
+    }
+</%helpers:longhand>
+
+// https://drafts.csswg.org/css-transforms-2/#individual-transforms
+<%helpers:longhand name="scale" products="servo" animatable="True">
+    use cssparser::ToCss;
+    use std::fmt;
+    use values::NoViewportPercentage;
+    use values::computed::ComputedValueAsSpecified;
+
+    pub mod computed_value {
+        use values::CSSFloat;
+
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub struct T(pub Option<(CSSFloat, CSSFloat, CSSFloat)>);
+    }
+
+    pub use self::computed_value::T as SpecifiedValue;
+
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    impl ToCss for computed_value::T {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match self.0 {
+                None => dest.write_str("none"),
+                Some((x, y, z)) if z != 1.0 => write!(dest, "{} {} {}", x, y, z),
+                Some((x, y, _)) if x != y => write!(dest, "{} {}", x, y),
+                Some((x, _, _)) => write!(dest, "{}", x),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::T(None)
+    }
+
+    /// `none | <number>{1,3}`, of which a missing y is the x and a missing
+    /// z is 1.
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("none")).is_ok() {
+            return Ok(computed_value::T(None))
+        }
+        let x = try!(specified::parse_number(input));
+        let (y, z) = match input.try(specified::parse_number) {
+            Ok(y) => (y, input.try(specified::parse_number).unwrap_or(1.0)),
+            Err(()) => (x, 1.0),
+        };
+        Ok(computed_value::T(Some((x, y, z))))
+    }
+</%helpers:longhand>
+
 pub struct OriginParseResult {
     horizontal: Option<specified::LengthOrPercentage>,
     vertical: Option<specified::LengthOrPercentage>,
@@ -1328,7 +1578,7 @@ ${helpers.single_keyword("transform-style",
         }
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[derive(Clone, Debug, PartialEq)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub struct SpecifiedValue {
         horizontal: LengthOrPercentage,
@@ -1403,8 +1653,7 @@ ${helpers.predefined_type("perspective",
                           products="servo",
                           animatable=True)}
 
-// FIXME: This prop should be animatable
-<%helpers:longhand name="perspective-origin" products="servo" animatable="False">
+<%helpers:longhand name="perspective-origin" products="servo" animatable="True">
     use values::HasViewportPercentage;
     use values::specified::{LengthOrPercentage, Percentage};
 
@@ -1412,6 +1661,7 @@ ${helpers.predefined_type("perspective",
     use std::fmt;
 
     pub mod computed_value {
+        use properties::animated_properties::Interpolate;
         use values::computed::LengthOrPercentage;
 
         #[derive(Clone, Copy, Debug, PartialEq)]
@@ -1420,6 +1670,15 @@ ${helpers.predefined_type("perspective",
             pub horizontal: LengthOrPercentage,
             pub vertical: LengthOrPercentage,
         }
+
+        impl Interpolate for T {
+            fn interpolate(&self, other: &Self, time: f64) -> Result<Self, ()> {
+                Ok(T {
+                    horizontal: try!(self.horizontal.interpolate(&other.horizontal, time)),
+                    vertical: try!(self.vertical.interpolate(&other.vertical, time)),
+                })
+            }
+        }
     }
 
     impl ToCss for computed_value::T {
@@ -1436,7 +1695,7 @@ ${helpers.predefined_type("perspective",
         }
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[derive(Clone, Debug, PartialEq)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub struct SpecifiedValue {
         horizontal: LengthOrPercentage,
@@ -277,7 +277,7 @@ ${helpers.single_keyword("font-variant",
 
     impl HasViewportPercentage for SpecifiedValue {
         fn has_viewport_percentage(&self) -> bool {
-            let &SpecifiedValue(length) = self;
+            let &SpecifiedValue(ref length) = self;
             return length.has_viewport_percentage()
         }
     }
@@ -306,16 +306,21 @@ ${helpers.single_keyword("font-variant",
                 LengthOrPercentage::Length(Length::ServoCharacterWidth(value)) => {
                     value.to_computed_value(context.inherited_style().get_font().clone_font_size())
                 }
-                LengthOrPercentage::Length(l) => {
+                LengthOrPercentage::Length(ref l) => {
                     l.to_computed_value(context)
                 }
                 LengthOrPercentage::Percentage(Percentage(value)) => {
                     context.inherited_style().get_font().clone_font_size().scale_by(value)
                 }
-                LengthOrPercentage::Calc(calc) => {
-                    let calc = calc.to_computed_value(context);
-                    calc.length() + context.inherited_style().get_font().clone_font_size()
-                                           .scale_by(calc.percentage())
+                LengthOrPercentage::Calc(ref calc) => {
+                    // Font-relative lengths are relative to the parent's font size too.
+                    let parent_font_size = context.inherited_style().get_font().clone_font_size();
+                    let calc = calc.compute_from_viewport_container_and_font_size(
+                        context.viewport_size(),
+                        context.container_size(),
+                        parent_font_size,
+                        context.style().root_font_size());
+                    calc.to_used_value(parent_font_size)
                 }
             }
         }
@@ -328,9 +333,14 @@ ${helpers.single_keyword("font-variant",
         }
     }
     /// <length> | <percentage> | <absolute-size> | <relative-size>
-    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+    pub fn parse(context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
         use values::specified::{Length, LengthOrPercentage};
 
+        if context.in_quirks_mode() {
+            if let Ok(length) = input.try(|input| specified::Length::parse_unitless(input, true)) {
+                return Ok(SpecifiedValue(LengthOrPercentage::Length(length)))
+            }
+        }
         input.try(specified::LengthOrPercentage::parse_non_negative)
         .or_else(|()| {
             let ident = try!(input.expect_ident());
@@ -23,14 +23,11 @@ ${helpers.single_keyword("writing-mode",
                          need_clone=True,
                          animatable=False)}
 
-// FIXME(SimonSapin): Add 'mixed' and 'upright' (needs vertical text support)
-// FIXME(SimonSapin): initial (first) value should be 'mixed', when that's implemented
 // FIXME(bholley): sideways-right is needed as an alias to sideways in gecko.
 ${helpers.single_keyword("text-orientation",
-                         "sideways",
+                         "mixed upright sideways",
                          experimental=True,
                          need_clone=True,
-                         extra_gecko_values="mixed upright",
                          extra_servo_values="sideways-right sideways-left",
                          animatable=False)}
 
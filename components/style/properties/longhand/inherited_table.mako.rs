@@ -16,7 +16,7 @@ ${helpers.single_keyword("caption-side", "top bottom",
                          extra_gecko_values="right left top-outside bottom-outside",
                          animatable=False)}
 
-<%helpers:longhand name="border-spacing" products="servo" animatable="False">
+<%helpers:longhand name="border-spacing" products="servo" animatable="True">
     use app_units::Au;
     use values::LocalToCss;
     use values::HasViewportPercentage;
@@ -115,11 +115,11 @@ ${helpers.single_keyword("caption-side", "top bottom",
         if input.next().is_ok() {
             return Err(())
         }
-        match (lengths[0], lengths[1]) {
+        match (lengths[0].take(), lengths[1].take()) {
             (None, None) => Err(()),
             (Some(length), None) => {
                 Ok(SpecifiedValue {
-                    horizontal: length,
+                    horizontal: length.clone(),
                     vertical: length,
                 })
             }
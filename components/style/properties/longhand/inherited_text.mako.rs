@@ -16,13 +16,13 @@
     impl HasViewportPercentage for SpecifiedValue {
         fn has_viewport_percentage(&self) -> bool {
             match *self {
-                SpecifiedValue::LengthOrPercentage(length) => length.has_viewport_percentage(),
+                SpecifiedValue::LengthOrPercentage(ref length) => length.has_viewport_percentage(),
                 _ => false
             }
         }
     }
 
-    #[derive(Debug, Clone, PartialEq, Copy)]
+    #[derive(Debug, Clone, PartialEq)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub enum SpecifiedValue {
         Normal,
@@ -40,7 +40,7 @@
                 % if product == "gecko":
                     SpecifiedValue::MozBlockHeight => dest.write_str("-moz-block-height"),
                 % endif
-                SpecifiedValue::LengthOrPercentage(value) => value.to_css(dest),
+                SpecifiedValue::LengthOrPercentage(ref value) => value.to_css(dest),
                 SpecifiedValue::Number(number) => write!(dest, "{}", number),
             }
         }
@@ -109,19 +109,18 @@
                     SpecifiedValue::MozBlockHeight => computed_value::T::MozBlockHeight,
                 % endif
                 SpecifiedValue::Number(value) => computed_value::T::Number(value),
-                SpecifiedValue::LengthOrPercentage(value) => {
-                    match value {
-                        specified::LengthOrPercentage::Length(value) =>
+                SpecifiedValue::LengthOrPercentage(ref value) => {
+                    match *value {
+                        specified::LengthOrPercentage::Length(ref value) =>
                             computed_value::T::Length(value.to_computed_value(context)),
                         specified::LengthOrPercentage::Percentage(specified::Percentage(value)) => {
                             let fr = specified::Length::FontRelative(specified::FontRelativeLength::Em(value));
                             computed_value::T::Length(fr.to_computed_value(context))
                         },
-                        specified::LengthOrPercentage::Calc(calc) => {
+                        specified::LengthOrPercentage::Calc(ref calc) => {
                             let calc = calc.to_computed_value(context);
-                            let fr = specified::FontRelativeLength::Em(calc.percentage());
-                            let fr = specified::Length::FontRelative(fr);
-                            computed_value::T::Length(calc.length() + fr.to_computed_value(context))
+                            let font_size = context.style().get_font().clone_font_size();
+                            computed_value::T::Length(calc.to_used_value(font_size))
                         }
                     }
                 }
@@ -208,7 +207,7 @@
 </%helpers:longhand>
 
 // FIXME: This prop should be animatable.
-<%helpers:longhand name="letter-spacing" products="servo" animatable="False">
+<%helpers:longhand name="letter-spacing" products="servo" animatable="True">
     use cssparser::ToCss;
     use std::fmt;
     use values::LocalToCss;
@@ -217,13 +216,13 @@
     impl HasViewportPercentage for SpecifiedValue {
         fn has_viewport_percentage(&self) -> bool {
             match *self {
-                SpecifiedValue::Specified(length) => length.has_viewport_percentage(),
+                SpecifiedValue::Specified(ref length) => length.has_viewport_percentage(),
                 _ => false
             }
         }
     }
 
-    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub enum SpecifiedValue {
         Normal,
@@ -234,7 +233,7 @@
         fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
             match *self {
                 SpecifiedValue::Normal => dest.write_str("normal"),
-                SpecifiedValue::Specified(l) => l.to_css(dest),
+                SpecifiedValue::Specified(ref l) => l.to_css(dest),
             }
         }
     }
@@ -267,7 +266,7 @@
         fn to_computed_value(&self, context: &Context) -> computed_value::T {
             match *self {
                 SpecifiedValue::Normal => computed_value::T(None),
-                SpecifiedValue::Specified(l) =>
+                SpecifiedValue::Specified(ref l) =>
                     computed_value::T(Some(l.to_computed_value(context)))
             }
         }
@@ -289,7 +288,7 @@
     }
 </%helpers:longhand>
 
-<%helpers:longhand name="word-spacing" animatable="False">
+<%helpers:longhand name="word-spacing" animatable="servo">
     use cssparser::ToCss;
     use std::fmt;
     use values::LocalToCss;
@@ -298,13 +297,13 @@
     impl HasViewportPercentage for SpecifiedValue {
         fn has_viewport_percentage(&self) -> bool {
             match *self {
-                SpecifiedValue::Specified(length) => length.has_viewport_percentage(),
+                SpecifiedValue::Specified(ref length) => length.has_viewport_percentage(),
                 _ => false
             }
         }
     }
 
-    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub enum SpecifiedValue {
         Normal,
@@ -315,7 +314,7 @@
         fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
             match *self {
                 SpecifiedValue::Normal => dest.write_str("normal"),
-                SpecifiedValue::Specified(l) => l.to_css(dest),
+                SpecifiedValue::Specified(ref l) => l.to_css(dest),
             }
         }
     }
@@ -348,7 +347,7 @@
         fn to_computed_value(&self, context: &Context) -> computed_value::T {
             match *self {
                 SpecifiedValue::Normal => computed_value::T(None),
-                SpecifiedValue::Specified(l) =>
+                SpecifiedValue::Specified(ref l) =>
                     computed_value::T(Some(l.to_computed_value(context))),
             }
         }
@@ -374,6 +373,7 @@
 ${helpers.predefined_type("text-indent",
                           "LengthOrPercentage",
                           "computed::LengthOrPercentage::Length(Au(0))",
+                          allow_quirks=True,
                           animatable=True)}
 
 // Also known as "word-wrap" (which is more popular because of IE), but this is the preferred
@@ -397,10 +397,11 @@ ${helpers.single_keyword("text-justify",
                          animatable=False)}
 
 <%helpers:longhand name="-servo-text-decorations-in-effect"
-                   derived_from="display text-decoration"
+                   derived_from="display text-decoration-line text-decoration-style
+                                 text-decoration-color text-decoration-thickness"
                    need_clone="True" products="servo"
                    animatable="False">
-    use cssparser::{RGBA, ToCss};
+    use cssparser::{Color as CSSParserColor, RGBA, ToCss};
     use std::fmt;
 
     use values:: NoViewportPercentage;
@@ -409,15 +410,26 @@ ${helpers.single_keyword("text-justify",
     impl ComputedValueAsSpecified for SpecifiedValue {}
     impl NoViewportPercentage for SpecifiedValue {}
 
+    /// The line style, color and thickness of a text decoration, as specified on the decorating
+    /// box.
+    #[derive(Clone, PartialEq, Copy, Debug)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub struct Decoration {
+        pub style: super::text_decoration_style::computed_value::T,
+        pub color: RGBA,
+        pub thickness: super::text_decoration_thickness::computed_value::T,
+    }
+
     #[derive(Clone, PartialEq, Copy, Debug)]
     #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
     pub struct SpecifiedValue {
-        pub underline: Option<RGBA>,
-        pub overline: Option<RGBA>,
-        pub line_through: Option<RGBA>,
+        pub underline: Option<Decoration>,
+        pub overline: Option<Decoration>,
+        pub line_through: Option<Decoration>,
     }
 
     pub mod computed_value {
+        pub use super::Decoration;
         pub type T = super::SpecifiedValue;
     }
 
@@ -437,9 +449,18 @@ ${helpers.single_keyword("text-justify",
         }
     }
 
-    fn maybe(flag: bool, context: &Context) -> Option<RGBA> {
+    fn maybe(flag: bool, context: &Context) -> Option<Decoration> {
         if flag {
-            Some(context.style().get_color().clone_color())
+            let text = context.style().get_text();
+            let color = match text.text_decoration_color {
+                CSSParserColor::RGBA(rgba) => rgba,
+                CSSParserColor::CurrentColor => context.style().get_color().clone_color(),
+            };
+            Some(Decoration {
+                style: text.text_decoration_style,
+                color: color,
+                thickness: text.text_decoration_thickness,
+            })
         } else {
             None
         }
@@ -448,39 +469,131 @@ ${helpers.single_keyword("text-justify",
     fn derive(context: &Context) -> computed_value::T {
         // Start with no declarations if this is an atomic inline-level box; otherwise, start with the
         // declarations in effect and add in the text decorations that this block specifies.
-        let mut result = match context.style().get_box().clone_display() {
+        let result = match context.style().get_box().clone_display() {
             super::display::computed_value::T::inline_block |
-            super::display::computed_value::T::inline_table => SpecifiedValue {
-                underline: None,
-                overline: None,
-                line_through: None,
-            },
+            super::display::computed_value::T::inline_table => get_initial_value(),
             _ => context.inherited_style().get_inheritedtext().clone__servo_text_decorations_in_effect()
         };
 
-        result.underline = maybe(context.style().get_text().has_underline()
-                                 || result.underline.is_some(), context);
-        result.overline = maybe(context.style().get_text().has_overline()
-                                || result.overline.is_some(), context);
-        result.line_through = maybe(context.style().get_text().has_line_through()
-                                    || result.line_through.is_some(), context);
+        // Decorations specified on this box replace the ones it inherits, so that each line is
+        // painted with the style of its nearest decorating box.
+        let text = context.style().get_text();
+        SpecifiedValue {
+            underline: maybe(text.has_underline(), context).or(result.underline),
+            overline: maybe(text.has_overline(), context).or(result.overline),
+            line_through: maybe(text.has_line_through(), context).or(result.line_through),
+        }
+    }
+
+    % for property in ["display", "text_decoration_line", "text_decoration_style", \
+                       "text_decoration_color", "text_decoration_thickness"]:
+        #[inline]
+        pub fn derive_from_${property}(context: &mut Context) {
+            let derived = derive(context);
+            context.mutate_style().mutate_inheritedtext()
+                   .set__servo_text_decorations_in_effect(derived);
+        }
+    % endfor
+</%helpers:longhand>
+
+<%helpers:longhand name="text-underline-offset" products="servo" animatable="False">
+    use cssparser::ToCss;
+    use std::fmt;
+    use values::{HasViewportPercentage, LocalToCss};
+    use values::computed::LengthOrPercentage as ComputedLengthOrPercentage;
+
+    impl HasViewportPercentage for SpecifiedValue {
+        fn has_viewport_percentage(&self) -> bool {
+            match *self {
+                SpecifiedValue::LengthOrPercentage(ref length) => length.has_viewport_percentage(),
+                _ => false
+            }
+        }
+    }
 
-        result
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub enum SpecifiedValue {
+        Auto,
+        LengthOrPercentage(specified::LengthOrPercentage),
     }
 
-    #[inline]
-    pub fn derive_from_text_decoration(context: &mut Context) {
-        let derived = derive(context);
-        context.mutate_style().mutate_inheritedtext().set__servo_text_decorations_in_effect(derived);
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                SpecifiedValue::Auto => dest.write_str("auto"),
+                SpecifiedValue::LengthOrPercentage(ref length) => length.to_css(dest),
+            }
+        }
+    }
+
+    pub mod computed_value {
+        use app_units::Au;
+        /// The offset of the underline from the alphabetic baseline, or `None` for `auto`.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub struct T(pub Option<Au>);
+    }
+
+    impl ToCss for computed_value::T {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match self.0 {
+                None => dest.write_str("auto"),
+                Some(length) => length.to_css(dest),
+            }
+        }
     }
 
     #[inline]
-    pub fn derive_from_display(context: &mut Context) {
-        let derived = derive(context);
-        context.mutate_style().mutate_inheritedtext().set__servo_text_decorations_in_effect(derived);
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::T(None)
+    }
+
+    impl ToComputedValue for SpecifiedValue {
+        type ComputedValue = computed_value::T;
+
+        #[inline]
+        fn to_computed_value(&self, context: &Context) -> computed_value::T {
+            match *self {
+                SpecifiedValue::Auto => computed_value::T(None),
+                SpecifiedValue::LengthOrPercentage(ref length) => {
+                    // Percentages refer to 1em.
+                    let font_size = context.style().get_font().clone_font_size();
+                    computed_value::T(Some(match length.to_computed_value(context) {
+                        ComputedLengthOrPercentage::Length(length) => length,
+                        ComputedLengthOrPercentage::Percentage(percentage) =>
+                            font_size.scale_by(percentage),
+                        ComputedLengthOrPercentage::Calc(calc) =>
+                            calc.to_used_value(font_size),
+                    }))
+                }
+            }
+        }
+
+        #[inline]
+        fn from_computed_value(computed: &computed_value::T) -> Self {
+            computed.0.map(|length| {
+                let length = ComputedLengthOrPercentage::Length(length);
+                SpecifiedValue::LengthOrPercentage(ToComputedValue::from_computed_value(&length))
+            }).unwrap_or(SpecifiedValue::Auto)
+        }
+    }
+
+    /// auto | <length> | <percentage>
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("auto")).is_ok() {
+            Ok(SpecifiedValue::Auto)
+        } else {
+            specified::LengthOrPercentage::parse(input).map(SpecifiedValue::LengthOrPercentage)
+        }
     }
 </%helpers:longhand>
 
+${helpers.single_keyword("text-decoration-skip-ink",
+                         "auto none",
+                         products="servo",
+                         animatable=False)}
+
 <%helpers:single_keyword_computed name="white-space"
                                   values="normal pre nowrap pre-wrap pre-line"
                                   gecko_constant_prefix="NS_STYLE_WHITESPACE"
@@ -653,7 +766,8 @@ ${helpers.single_keyword("text-justify",
 
     fn parse_one_text_shadow(input: &mut Parser) -> Result<SpecifiedTextShadow,()> {
         use app_units::Au;
-        let mut lengths = [specified::Length::Absolute(Au(0)); 3];
+        let mut lengths = [specified::Length::Absolute(Au(0)), specified::Length::Absolute(Au(0)),
+                           specified::Length::Absolute(Au(0))];
         let mut lengths_parsed = false;
         let mut color = None;
 
@@ -695,9 +809,9 @@ ${helpers.single_keyword("text-justify",
         }
 
         Ok(SpecifiedTextShadow {
-            offset_x: lengths[0],
-            offset_y: lengths[1],
-            blur_radius: lengths[2],
+            offset_x: lengths[0].clone(),
+            offset_y: lengths[1].clone(),
+            blur_radius: lengths[2].clone(),
             color: color,
         })
     }
@@ -747,12 +861,94 @@ ${helpers.single_keyword("text-rendering",
 // CSS Text Module Level 3
 // https://www.w3.org/TR/css-text-3/
 ${helpers.single_keyword("hyphens", "none manual auto",
-                         products="gecko", animatable=False)}
+                         animatable=False)}
+
+<%helpers:longhand name="hyphenate-character" products="servo" animatable="False">
+    use cssparser::{ToCss, Token};
+    use std::borrow::Cow;
+    use std::fmt;
+    use values::NoViewportPercentage;
+    use values::computed::ComputedValueAsSpecified;
+
+    pub use self::computed_value::T as SpecifiedValue;
+
+    pub mod computed_value {
+        /// The string shown at the end of a line broken by hyphenation.
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub enum T {
+            /// The user agent picks an appropriate string for the content language.
+            auto,
+            String(String),
+        }
+    }
+
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                SpecifiedValue::auto => dest.write_str("auto"),
+                SpecifiedValue::String(ref string) => Token::QuotedString(Cow::from(&**string)).to_css(dest),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::T::auto
+    }
+
+    pub fn parse(_: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("auto")).is_ok() {
+            return Ok(SpecifiedValue::auto)
+        }
+        Ok(SpecifiedValue::String(try!(input.expect_string()).into_owned()))
+    }
+</%helpers:longhand>
+
+// The content language of an element, taken from its `lang` attribute. Layout uses it to pick
+// hyphenation dictionaries. It is empty when the language is unknown.
+<%helpers:longhand name="-servo-lang" products="servo" animatable="False" internal="True">
+    use cssparser::{ToCss, Token};
+    use std::borrow::Cow;
+    use std::fmt;
+    use values::NoViewportPercentage;
+    use values::computed::ComputedValueAsSpecified;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub struct SpecifiedValue(pub String);
+
+    pub mod computed_value {
+        pub type T = super::SpecifiedValue;
+    }
+
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            Token::QuotedString(Cow::from(&*self.0)).to_css(dest)
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        SpecifiedValue(String::new())
+    }
+
+    pub fn parse(_: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        Ok(SpecifiedValue(try!(input.expect_string()).into_owned()))
+    }
+</%helpers:longhand>
 
 // CSS Ruby Layout Module Level 1
 // https://www.w3.org/TR/css-ruby-1/
-${helpers.single_keyword("ruby-align", "start center space-between space-around",
-                         products="gecko", animatable=False)}
+${helpers.single_keyword("ruby-align", "space-around start center space-between",
+                         animatable=False)}
 
 ${helpers.single_keyword("ruby-position", "over under",
-                         products="gecko", animatable=False)}
+                         animatable=False)}
@@ -8,10 +8,9 @@
 
 ${helpers.single_keyword("list-style-position", "outside inside", animatable=False)}
 
-// TODO(pcwalton): Implement the full set of counter styles per CSS-COUNTER-STYLES [1] 6.1:
+// TODO(pcwalton): Implement the rest of the counter styles per CSS-COUNTER-STYLES [1] 6-7:
 //
-//     decimal-leading-zero, armenian, upper-armenian, lower-armenian, georgian, lower-roman,
-//     upper-roman
+//     hebrew, korean-hangul-formal, korean-hanja-informal, korean-hanja-formal, ethiopic-numeric
 //
 // TODO(bholley): Missing quite a few gecko properties here as well.
 //
@@ -22,7 +21,10 @@ ${helpers.single_keyword("list-style-type", """
                            gujarati gurmukhi kannada khmer lao malayalam mongolian
                            myanmar oriya persian telugu thai tibetan cjk-earthly-branch
                            cjk-heavenly-stem lower-greek hiragana hiragana-iroha katakana
-                           katakana-iroha""",
+                           katakana-iroha decimal-leading-zero lower-roman upper-roman
+                           lower-latin upper-latin armenian upper-armenian lower-armenian
+                           georgian japanese-informal simp-chinese-informal
+                           trad-chinese-informal cjk-ideographic""",
     gecko_constant_prefix="NS_STYLE_LIST_STYLE",
     animatable=False)}
 
@@ -9,5 +9,6 @@
 % for side in ["top", "right", "bottom", "left"]:
     ${helpers.predefined_type("margin-" + side, "LengthOrPercentageOrAuto",
                               "computed::LengthOrPercentageOrAuto::Length(Au(0))",
+                              allow_quirks=True,
                               animatable=True)}
 % endfor
@@ -10,5 +10,6 @@
     ${helpers.predefined_type("padding-" + side, "LengthOrPercentage",
                                "computed::LengthOrPercentage::Length(Au(0))",
                                "parse_non_negative",
+                               allow_quirks=True,
                                animatable=True)}
 % endfor
@@ -73,3 +73,105 @@ ${helpers.single_keyword("-moz-user-focus",
                          products="gecko", gecko_ffi_name="mUserFocus",
                          gecko_enum_prefix="StyleUserFocus",
                          animatable=False)}
+
+// https://drafts.csswg.org/css-scrollbars-1/#scrollbar-color
+<%helpers:longhand name="scrollbar-color" products="servo" animatable="False">
+    use cssparser::ToCss;
+    use std::fmt;
+    use values::NoViewportPercentage;
+
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub enum SpecifiedValue {
+        Auto,
+        Colors {
+            thumb: specified::CSSColor,
+            track: specified::CSSColor,
+        },
+    }
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                SpecifiedValue::Auto => dest.write_str("auto"),
+                SpecifiedValue::Colors { ref thumb, ref track } => {
+                    try!(thumb.to_css(dest));
+                    try!(dest.write_str(" "));
+                    track.to_css(dest)
+                }
+            }
+        }
+    }
+
+    pub mod computed_value {
+        use values::computed::CSSColor;
+
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub enum T {
+            Auto,
+            Colors {
+                thumb: CSSColor,
+                track: CSSColor,
+            },
+        }
+    }
+
+    impl ToCss for computed_value::T {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                computed_value::T::Auto => dest.write_str("auto"),
+                computed_value::T::Colors { ref thumb, ref track } => {
+                    try!(thumb.to_css(dest));
+                    try!(dest.write_str(" "));
+                    track.to_css(dest)
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::T::Auto
+    }
+
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("auto")).is_ok() {
+            return Ok(SpecifiedValue::Auto)
+        }
+        let thumb = try!(specified::CSSColor::parse(input));
+        let track = try!(specified::CSSColor::parse(input));
+        Ok(SpecifiedValue::Colors {
+            thumb: thumb,
+            track: track,
+        })
+    }
+
+    impl ToComputedValue for SpecifiedValue {
+        type ComputedValue = computed_value::T;
+
+        #[inline]
+        fn to_computed_value(&self, context: &Context) -> computed_value::T {
+            match *self {
+                SpecifiedValue::Auto => computed_value::T::Auto,
+                SpecifiedValue::Colors { ref thumb, ref track } => computed_value::T::Colors {
+                    thumb: thumb.to_computed_value(context),
+                    track: track.to_computed_value(context),
+                },
+            }
+        }
+
+        #[inline]
+        fn from_computed_value(computed: &computed_value::T) -> Self {
+            match *computed {
+                computed_value::T::Auto => SpecifiedValue::Auto,
+                computed_value::T::Colors { ref thumb, ref track } => SpecifiedValue::Colors {
+                    thumb: ToComputedValue::from_computed_value(thumb),
+                    track: ToComputedValue::from_computed_value(track),
+                },
+            }
+        }
+    }
+</%helpers:longhand>
@@ -9,6 +9,7 @@
 % for side in ["top", "right", "bottom", "left"]:
     ${helpers.predefined_type(side, "LengthOrPercentageOrAuto",
                               "computed::LengthOrPercentageOrAuto::Auto",
+                              allow_quirks=True,
                               animatable=True)}
 % endfor
 
@@ -94,6 +95,12 @@ ${helpers.single_keyword("align-content", "stretch flex-start flex-end center sp
                          products="servo",
                          animatable=False)}
 
+// https://drafts.csswg.org/css-align/#column-row-gap
+<%helpers:longhand name="row-gap" products="servo" animatable="True">
+    pub use super::column_gap::{SpecifiedValue, computed_value, get_initial_value};
+    pub use super::column_gap::parse;
+</%helpers:longhand>
+
 // Flex item properties
 ${helpers.predefined_type("flex-grow", "Number",
                           "0.0", "parse_non_negative",
@@ -131,46 +138,51 @@ ${helpers.single_keyword("align-self", "auto stretch flex-start flex-end center
     }
 </%helpers:longhand>
 
-// FIXME: This property should be animatable.
 ${helpers.predefined_type("flex-basis",
                           "LengthOrPercentageOrAutoOrContent",
                           "computed::LengthOrPercentageOrAutoOrContent::Auto",
-                          animatable=False)}
+                          animatable="servo")}
 
 ${helpers.predefined_type("width",
-                          "LengthOrPercentageOrAuto",
-                          "computed::LengthOrPercentageOrAuto::Auto",
+                          "Size",
+                          "computed::Size::auto()",
                           "parse_non_negative",
+                          allow_quirks=True,
                           animatable=True)}
 
 ${helpers.predefined_type("height",
-                          "LengthOrPercentageOrAuto",
-                          "computed::LengthOrPercentageOrAuto::Auto",
+                          "Size",
+                          "computed::Size::auto()",
                           "parse_non_negative",
+                          allow_quirks=True,
                           animatable=True)}
 
 ${helpers.predefined_type("min-width",
-                          "LengthOrPercentage",
-                          "computed::LengthOrPercentage::Length(Au(0))",
+                          "MinSize",
+                          "computed::MinSize::auto()",
                           "parse_non_negative",
+                          allow_quirks=True,
                           animatable=True)}
 
 ${helpers.predefined_type("max-width",
-                          "LengthOrPercentageOrNone",
-                          "computed::LengthOrPercentageOrNone::None",
+                          "MaxSize",
+                          "computed::MaxSize::none()",
                           "parse_non_negative",
+                          allow_quirks=True,
                           animatable=True)}
 
 ${helpers.predefined_type("min-height",
-                          "LengthOrPercentage",
-                          "computed::LengthOrPercentage::Length(Au(0))",
+                          "MinSize",
+                          "computed::MinSize::auto()",
                           "parse_non_negative",
+                          allow_quirks=True,
                           animatable=True)}
 
 ${helpers.predefined_type("max-height",
-                          "LengthOrPercentageOrNone",
-                          "computed::LengthOrPercentageOrNone::None",
+                          "MaxSize",
+                          "computed::MaxSize::none()",
                           "parse_non_negative",
+                          allow_quirks=True,
                           animatable=True)}
 
 ${helpers.single_keyword("box-sizing",
@@ -179,5 +191,128 @@ ${helpers.single_keyword("box-sizing",
 
 // CSS Image Values and Replaced Content Module Level 3
 // https://drafts.csswg.org/css-images-3/
+// https://drafts.csswg.org/css-images-3/#the-object-fit
 ${helpers.single_keyword("object-fit", "fill contain cover none scale-down",
-                         products="gecko", animatable=False)}
+                         animatable=False)}
+
+// https://drafts.csswg.org/css-images-3/#the-object-position
+<%helpers:longhand name="object-position" products="servo" animatable="True">
+    use values::specified::position::Position;
+
+    pub mod computed_value {
+        use values::computed::position::Position;
+
+        pub type T = Position;
+    }
+
+    pub type SpecifiedValue = Position;
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        use values::computed::position::Position;
+        Position {
+            horizontal: computed::LengthOrPercentage::Percentage(0.5),
+            vertical: computed::LengthOrPercentage::Percentage(0.5),
+        }
+    }
+
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        Position::parse(input)
+    }
+</%helpers:longhand>
+
+// https://drafts.csswg.org/css-sizing-4/#aspect-ratio
+<%helpers:longhand name="aspect-ratio" products="servo" animatable="False">
+    use values::NoViewportPercentage;
+    use values::computed::ComputedValueAsSpecified;
+
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+    pub type SpecifiedValue = computed_value::T;
+
+    pub mod computed_value {
+        use cssparser::ToCss;
+        use std::fmt;
+        use values::CSSFloat;
+
+        /// `auto || <ratio>`. With both, the ratio is only used for boxes
+        /// without a natural aspect ratio.
+        #[derive(PartialEq, Clone, Copy, Debug)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub struct T {
+            pub auto: bool,
+            /// The width and height of the ratio.
+            pub ratio: Option<(CSSFloat, CSSFloat)>,
+        }
+
+        impl T {
+            /// The width divided by the height, unless there is no ratio or
+            /// it is degenerate, in which case the property acts as `auto`.
+            pub fn ratio(&self) -> Option<CSSFloat> {
+                match self.ratio {
+                    Some((width, height)) if width > 0. && height > 0. => Some(width / height),
+                    _ => None,
+                }
+            }
+        }
+
+        impl ToCss for T {
+            fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+                if self.auto {
+                    try!(dest.write_str("auto"));
+                }
+                if let Some((width, height)) = self.ratio {
+                    if self.auto {
+                        try!(dest.write_str(" "));
+                    }
+                    try!(write!(dest, "{} / {}", width, height));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::T {
+            auto: true,
+            ratio: None,
+        }
+    }
+
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        let mut auto = false;
+        let mut ratio = None;
+        loop {
+            if !auto && input.try(|input| input.expect_ident_matching("auto")).is_ok() {
+                auto = true;
+                continue
+            }
+            if ratio.is_none() {
+                if let Ok(value) = input.try(parse_ratio) {
+                    ratio = Some(value);
+                    continue
+                }
+            }
+            break
+        }
+        if !auto && ratio.is_none() {
+            return Err(())
+        }
+        Ok(computed_value::T {
+            auto: auto,
+            ratio: ratio,
+        })
+    }
+
+    // https://drafts.csswg.org/css-values-4/#ratios
+    fn parse_ratio(input: &mut Parser) -> Result<(f32, f32), ()> {
+        let width = try!(specified::Number::parse_non_negative(input)).0;
+        let height = if input.try(|input| input.expect_delim('/')).is_ok() {
+            try!(specified::Number::parse_non_negative(input)).0
+        } else {
+            1.
+        };
+        Ok((width, height))
+    }
+</%helpers:longhand>
@@ -19,10 +19,9 @@ ${helpers.single_keyword("unicode-bidi",
                          animatable=False)}
 
 // FIXME: This prop should be animatable.
-<%helpers:longhand name="${'text-decoration' if product == 'servo' else 'text-decoration-line'}"
+<%helpers:longhand name="text-decoration-line"
                    custom_cascade="${product == 'servo'}"
-                   animatable="False"
-                   disable_when_testing="True">
+                   animatable="False">
     use cssparser::ToCss;
     use std::fmt;
     use values::computed::ComputedValueAsSpecified;
@@ -115,18 +114,181 @@ ${helpers.single_keyword("unicode-bidi",
                                    _seen: &mut PropertyBitField,
                                    _cacheable: &mut bool,
                                    _error_reporter: &mut StdBox<ParseErrorReporter + Send>) {
-                longhands::_servo_text_decorations_in_effect::derive_from_text_decoration(context);
+                longhands::_servo_text_decorations_in_effect
+                         ::derive_from_text_decoration_line(context);
         }
     % endif
 </%helpers:longhand>
 
-${helpers.single_keyword("text-decoration-style",
-                         "solid double dotted dashed wavy -moz-none",
-                         products="gecko",
-                         animatable=False)}
+<%helpers:single_keyword_computed name="text-decoration-style"
+                                  values="solid double dotted dashed wavy"
+                                  extra_gecko_values="-moz-none"
+                                  custom_cascade="${product == 'servo'}"
+                                  animatable="False">
+    use values::computed::ComputedValueAsSpecified;
+    use values::NoViewportPercentage;
+    impl ComputedValueAsSpecified for SpecifiedValue {}
+    impl NoViewportPercentage for SpecifiedValue {}
+
+    % if product == "servo":
+        fn cascade_property_custom(_declaration: &PropertyDeclaration,
+                                   _inherited_style: &ComputedValues,
+                                   context: &mut computed::Context,
+                                   _seen: &mut PropertyBitField,
+                                   _cacheable: &mut bool,
+                                   _error_reporter: &mut StdBox<ParseErrorReporter + Send>) {
+                longhands::_servo_text_decorations_in_effect
+                         ::derive_from_text_decoration_style(context);
+        }
+    % endif
+</%helpers:single_keyword_computed>
+
+<%helpers:longhand name="text-decoration-color"
+                   custom_cascade="${product == 'servo'}"
+                   animatable="True">
+    use cssparser::Color as CSSParserColor;
+    pub type SpecifiedValue = specified::CSSColor;
+    pub mod computed_value {
+        pub use values::computed::CSSColor as T;
+    }
+    #[inline] pub fn get_initial_value() -> computed_value::T {
+        CSSParserColor::CurrentColor
+    }
+    #[inline] pub fn parse(_context: &ParserContext, input: &mut Parser)
+                           -> Result<SpecifiedValue, ()> {
+        specified::CSSColor::parse(input)
+    }
+
+    % if product == "servo":
+        fn cascade_property_custom(_declaration: &PropertyDeclaration,
+                                   _inherited_style: &ComputedValues,
+                                   context: &mut computed::Context,
+                                   _seen: &mut PropertyBitField,
+                                   _cacheable: &mut bool,
+                                   _error_reporter: &mut StdBox<ParseErrorReporter + Send>) {
+                longhands::_servo_text_decorations_in_effect
+                         ::derive_from_text_decoration_color(context);
+        }
+    % endif
+</%helpers:longhand>
+
+<%helpers:longhand name="text-decoration-thickness"
+                   products="servo"
+                   custom_cascade="True"
+                   animatable="False">
+    use cssparser::ToCss;
+    use std::fmt;
+    use values::{HasViewportPercentage, LocalToCss};
+    use values::computed::LengthOrPercentage as ComputedLengthOrPercentage;
+
+    impl HasViewportPercentage for SpecifiedValue {
+        fn has_viewport_percentage(&self) -> bool {
+            match *self {
+                SpecifiedValue::LengthOrPercentage(ref length) => length.has_viewport_percentage(),
+                _ => false
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+    pub enum SpecifiedValue {
+        Auto,
+        FromFont,
+        LengthOrPercentage(specified::LengthOrPercentage),
+    }
+
+    impl ToCss for SpecifiedValue {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                SpecifiedValue::Auto => dest.write_str("auto"),
+                SpecifiedValue::FromFont => dest.write_str("from-font"),
+                SpecifiedValue::LengthOrPercentage(ref length) => length.to_css(dest),
+            }
+        }
+    }
+
+    pub mod computed_value {
+        use app_units::Au;
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+        pub enum T {
+            Auto,
+            FromFont,
+            Length(Au),
+        }
+    }
+
+    impl ToCss for computed_value::T {
+        fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self {
+                computed_value::T::Auto => dest.write_str("auto"),
+                computed_value::T::FromFont => dest.write_str("from-font"),
+                computed_value::T::Length(length) => length.to_css(dest),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get_initial_value() -> computed_value::T {
+        computed_value::T::Auto
+    }
+
+    impl ToComputedValue for SpecifiedValue {
+        type ComputedValue = computed_value::T;
+
+        #[inline]
+        fn to_computed_value(&self, context: &Context) -> computed_value::T {
+            match *self {
+                SpecifiedValue::Auto => computed_value::T::Auto,
+                SpecifiedValue::FromFont => computed_value::T::FromFont,
+                SpecifiedValue::LengthOrPercentage(ref length) => {
+                    // Percentages refer to 1em.
+                    let font_size = context.style().get_font().clone_font_size();
+                    computed_value::T::Length(match length.to_computed_value(context) {
+                        ComputedLengthOrPercentage::Length(length) => length,
+                        ComputedLengthOrPercentage::Percentage(percentage) =>
+                            font_size.scale_by(percentage),
+                        ComputedLengthOrPercentage::Calc(calc) =>
+                            calc.to_used_value(font_size),
+                    })
+                }
+            }
+        }
 
-${helpers.predefined_type(
-    "text-decoration-color", "CSSColor",
-    "CSSParserColor::RGBA(RGBA { red: 0.0, green: 0.0, blue: 0.0, alpha: 1.0 })",
-    products="gecko",
-    animatable=True)}
+        #[inline]
+        fn from_computed_value(computed: &computed_value::T) -> Self {
+            match *computed {
+                computed_value::T::Auto => SpecifiedValue::Auto,
+                computed_value::T::FromFont => SpecifiedValue::FromFont,
+                computed_value::T::Length(length) => {
+                    let length = ComputedLengthOrPercentage::Length(length);
+                    SpecifiedValue::LengthOrPercentage(
+                        ToComputedValue::from_computed_value(&length))
+                }
+            }
+        }
+    }
+
+    /// auto | from-font | <length> | <percentage>
+    pub fn parse(_context: &ParserContext, input: &mut Parser) -> Result<SpecifiedValue, ()> {
+        if input.try(|input| input.expect_ident_matching("auto")).is_ok() {
+            Ok(SpecifiedValue::Auto)
+        } else if input.try(|input| input.expect_ident_matching("from-font")).is_ok() {
+            Ok(SpecifiedValue::FromFont)
+        } else {
+            specified::LengthOrPercentage::parse_non_negative(input)
+                .map(SpecifiedValue::LengthOrPercentage)
+        }
+    }
+
+    fn cascade_property_custom(_declaration: &PropertyDeclaration,
+                               _inherited_style: &ComputedValues,
+                               context: &mut computed::Context,
+                               _seen: &mut PropertyBitField,
+                               _cacheable: &mut bool,
+                               _error_reporter: &mut StdBox<ParseErrorReporter + Send>) {
+            longhands::_servo_text_decorations_in_effect
+                     ::derive_from_text_decoration_thickness(context);
+    }
+</%helpers:longhand>
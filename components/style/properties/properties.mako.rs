@@ -19,7 +19,7 @@ use std::sync::Arc;
 use app_units::Au;
 #[cfg(feature = "servo")] use cssparser::{Color as CSSParserColor, RGBA};
 use cssparser::{Parser, AtRuleParser, DeclarationParser, Delimiter,
-                DeclarationListParser, parse_important, ToCss, TokenSerializationType};
+                DeclarationListParser, parse_important, ToCss, Token, TokenSerializationType};
 use error_reporting::ParseErrorReporter;
 use url::Url;
 #[cfg(feature = "servo")] use euclid::side_offsets::SideOffsets2D;
@@ -595,6 +595,74 @@ pub fn parse_property_declaration_list(context: &ParserContext, input: &mut Pars
     block
 }
 
+/// Parses the contents of a style rule's block, where declarations may be
+/// interleaved with nested rules.
+///
+/// Anything that doesn't parse as a declaration is handed to `parse_rule`,
+/// which should consume a whole rule.
+///
+/// https://drafts.csswg.org/css-nesting/#syntax
+pub fn parse_property_declaration_list_with_rules<R, F>(context: &ParserContext, input: &mut Parser,
+                                                        mut parse_rule: F)
+                                                        -> (PropertyDeclarationBlock, Vec<R>)
+    where F: FnMut(&mut Parser) -> Result<R, ()>
+{
+    let mut declarations = Vec::new();
+    let mut important_count = 0;
+    let mut rules = Vec::new();
+    let mut parser = PropertyDeclarationParser {
+        context: context,
+    };
+    loop {
+        let start = input.position();
+        let name = match input.next_including_whitespace_and_comments() {
+            Ok(Token::WhiteSpace(_)) | Ok(Token::Comment(_)) | Ok(Token::Semicolon) => continue,
+            Ok(Token::Ident(name)) => Some(name),
+            Ok(_) => None,
+            Err(()) => break,
+        };
+        if let Some(name) = name {
+            let declaration = input.parse_until_after(Delimiter::Semicolon, |input| {
+                try!(input.expect_colon());
+                parser.parse_value(&name, input)
+            });
+            if let Ok((results, importance)) = declaration {
+                if importance.important() {
+                    important_count += results.len() as u32;
+                }
+                declarations.extend(results.into_iter().map(|d| (d, importance)));
+                continue
+            }
+        }
+
+        // An identifier followed by a colon can also start a selector, like
+        // `a:hover`, so try a rule whenever a declaration fails to parse.
+        input.reset(start);
+        if let Ok(rule) = parse_rule(input) {
+            rules.push(rule);
+            continue
+        }
+
+        // Skip to the end of whatever this was.
+        input.reset(start);
+        while let Ok(token) = input.next() {
+            match token {
+                Token::Semicolon | Token::CurlyBracketBlock => break,
+                _ => {}
+            }
+        }
+        let message = format!("Unsupported property declaration: '{}'",
+                              input.slice_from(start));
+        log_css_error(input, start, &*message, &context);
+    }
+    let mut block = PropertyDeclarationBlock {
+        declarations: declarations,
+        important_count: important_count,
+    };
+    deduplicate_property_declarations(&mut block);
+    (block, rules)
+}
+
 /// Only keep the "winning" declaration for any given property, by importance then source order.
 /// The input and output are in source order
 fn deduplicate_property_declarations(block: &mut PropertyDeclarationBlock) {
@@ -1303,18 +1371,17 @@ pub mod style_structs {
                     self.outline_width != ::app_units::Au(0)
                 }
             % elif style_struct.name == "Text":
-                <% text_decoration_field = 'text_decoration' if product == 'servo' else 'text_decoration_line' %>
                 #[inline]
                 pub fn has_underline(&self) -> bool {
-                    self.${text_decoration_field}.underline
+                    self.text_decoration_line.underline
                 }
                 #[inline]
                 pub fn has_overline(&self) -> bool {
-                    self.${text_decoration_field}.overline
+                    self.text_decoration_line.overline
                 }
                 #[inline]
                 pub fn has_line_through(&self) -> bool {
-                    self.${text_decoration_field}.line_through
+                    self.text_decoration_line.line_through
                 }
             % endif
         }
@@ -1460,6 +1527,58 @@ impl ComputedValues {
         style.column_count.0.is_some() || style.column_width.0.is_some()
     }
 
+    /// Whether the `overflow` properties make this box a scroll container, which is the case
+    /// for anything but `visible` and `clip`.
+    ///
+    /// https://drafts.csswg.org/css-overflow-3/#scroll-container
+    #[inline]
+    pub fn is_scroll_container(&self) -> bool {
+        use computed_values::overflow_x::T as overflow;
+        match (self.get_box().overflow_x, self.get_box().overflow_y.0) {
+            (overflow::visible, overflow::visible) |
+            (overflow::visible, overflow::clip) |
+            (overflow::clip, overflow::visible) |
+            (overflow::clip, overflow::clip) => false,
+            _ => true,
+        }
+    }
+
+    /// Whether the box is sized as if it were empty, because of `contain: size` or of
+    /// `container-type: size`.
+    ///
+    /// https://drafts.csswg.org/css-contain-2/#size-containment
+    #[inline]
+    pub fn has_size_containment(&self) -> bool {
+        use computed_values::container_type::T as container_type;
+        self.get_box().contain.size || self.get_box().container_type == container_type::size
+    }
+
+    /// Whether the box is sized as if it were empty in the inline axis, which query containers
+    /// of either type are.
+    #[inline]
+    pub fn has_inline_size_containment(&self) -> bool {
+        use computed_values::container_type::T as container_type;
+        self.get_box().contain.size || self.get_box().container_type != container_type::normal
+    }
+
+    /// Whether the layout of the box's contents is independent of the rest of the page, which
+    /// is also the case for query containers.
+    ///
+    /// https://drafts.csswg.org/css-contain-2/#layout-containment
+    #[inline]
+    pub fn has_layout_containment(&self) -> bool {
+        use computed_values::container_type::T as container_type;
+        self.get_box().contain.layout || self.get_box().container_type != container_type::normal
+    }
+
+    /// Whether the box's contents are clipped to it and painted independently.
+    ///
+    /// https://drafts.csswg.org/css-contain-2/#paint-containment
+    #[inline]
+    pub fn has_paint_containment(&self) -> bool {
+        self.get_box().contain.paint
+    }
+
     /// Resolves the currentColor keyword.
     /// Any color value form computed values (except for the 'color' property itself)
     /// should go through this method.
@@ -1476,6 +1595,39 @@ impl ComputedValues {
 
     #[inline]
     pub fn content_inline_size(&self) -> computed::LengthOrPercentageOrAuto {
+        self.logical_inline_size().length_or_auto()
+    }
+
+    #[inline]
+    pub fn content_block_size(&self) -> computed::LengthOrPercentageOrAuto {
+        self.logical_block_size().length_or_auto()
+    }
+
+    #[inline]
+    pub fn min_inline_size(&self) -> computed::LengthOrPercentage {
+        self.logical_min_inline_size().length()
+    }
+
+    #[inline]
+    pub fn min_block_size(&self) -> computed::LengthOrPercentage {
+        self.logical_min_block_size().length()
+    }
+
+    #[inline]
+    pub fn max_inline_size(&self) -> computed::LengthOrPercentageOrNone {
+        self.logical_max_inline_size().length_or_none()
+    }
+
+    #[inline]
+    pub fn max_block_size(&self) -> computed::LengthOrPercentageOrNone {
+        self.logical_max_block_size().length_or_none()
+    }
+
+    /// The inline size, including any sizing keyword. `content_inline_size()`
+    /// treats the keywords as `auto`, which is what they are in the block
+    /// axis.
+    #[inline]
+    pub fn logical_inline_size(&self) -> computed::Size {
         let position_style = self.get_position();
         if self.writing_mode.is_vertical() {
             position_style.height
@@ -1485,31 +1637,31 @@ impl ComputedValues {
     }
 
     #[inline]
-    pub fn content_block_size(&self) -> computed::LengthOrPercentageOrAuto {
+    pub fn logical_block_size(&self) -> computed::Size {
         let position_style = self.get_position();
         if self.writing_mode.is_vertical() { position_style.width } else { position_style.height }
     }
 
     #[inline]
-    pub fn min_inline_size(&self) -> computed::LengthOrPercentage {
+    pub fn logical_min_inline_size(&self) -> computed::MinSize {
         let position_style = self.get_position();
         if self.writing_mode.is_vertical() { position_style.min_height } else { position_style.min_width }
     }
 
     #[inline]
-    pub fn min_block_size(&self) -> computed::LengthOrPercentage {
+    pub fn logical_min_block_size(&self) -> computed::MinSize {
         let position_style = self.get_position();
         if self.writing_mode.is_vertical() { position_style.min_width } else { position_style.min_height }
     }
 
     #[inline]
-    pub fn max_inline_size(&self) -> computed::LengthOrPercentageOrNone {
+    pub fn logical_max_inline_size(&self) -> computed::MaxSize {
         let position_style = self.get_position();
         if self.writing_mode.is_vertical() { position_style.max_height } else { position_style.max_width }
     }
 
     #[inline]
-    pub fn max_block_size(&self) -> computed::LengthOrPercentageOrNone {
+    pub fn logical_max_block_size(&self) -> computed::MaxSize {
         let position_style = self.get_position();
         if self.writing_mode.is_vertical() { position_style.max_width } else { position_style.max_height }
     }
@@ -1566,21 +1718,22 @@ impl ComputedValues {
 
     // http://dev.w3.org/csswg/css-transforms/#grouping-property-values
     pub fn get_used_transform_style(&self) -> computed_values::transform_style::T {
-        use computed_values::mix_blend_mode;
+        use computed_values::{isolation, mix_blend_mode};
         use computed_values::transform_style;
 
         let effects = self.get_effects();
 
-        // TODO(gw): Add clip-path, isolation, mask-image, mask-border-source when supported.
+        // TODO(gw): Add clip-path, mask-image, mask-border-source when supported.
         if effects.opacity < 1.0 ||
            !effects.filter.is_empty() ||
-           effects.clip.0.is_some() {
+           effects.clip.0.is_some() ||
            effects.mix_blend_mode != mix_blend_mode::T::normal ||
+           self.get_box().isolation == isolation::T::isolate {
             return transform_style::T::flat;
         }
 
         if effects.transform_style == transform_style::T::auto {
-            if effects.transform.0.is_some() {
+            if self.is_transformed() {
                 return transform_style::T::flat;
             }
             if effects.perspective != computed::LengthOrNone::None {
@@ -1592,10 +1745,44 @@ impl ComputedValues {
         effects.transform_style
     }
 
+    /// Whether the element is transformed, by `transform` or by one of the
+    /// individual transform properties.
+    pub fn is_transformed(&self) -> bool {
+        let effects = self.get_effects();
+        effects.transform.0.is_some() ||
+        effects.translate.0.is_some() ||
+        effects.rotate.0.is_some() ||
+        effects.scale.0.is_some()
+    }
+
+    /// The transform functions of the element, which are `translate`,
+    /// `rotate` and `scale` followed by those of `transform`.
+    /// https://drafts.csswg.org/css-transforms-2/#ctm
+    pub fn transform_operations(&self) -> Vec<computed_values::transform::ComputedOperation> {
+        use computed_values::transform::ComputedOperation;
+
+        let effects = self.get_effects();
+        let mut operations = vec![];
+        if let Some((x, y, z)) = effects.translate.0 {
+            operations.push(ComputedOperation::Translate(x, y, z));
+        }
+        if let Some((x, y, z, angle)) = effects.rotate.0 {
+            let length = (x * x + y * y + z * z).sqrt();
+            operations.push(ComputedOperation::Rotate(x / length, y / length, z / length, angle));
+        }
+        if let Some((x, y, z)) = effects.scale.0 {
+            operations.push(ComputedOperation::Scale(x, y, z));
+        }
+        if let Some(ref transform_list) = effects.transform.0 {
+            operations.extend_from_slice(transform_list);
+        }
+        operations
+    }
+
     pub fn transform_requires_layer(&self) -> bool {
         // Check if the transform matrix is 2D or 3D
-        if let Some(ref transform_list) = self.get_effects().transform.0 {
-            for transform in transform_list {
+        if self.is_transformed() {
+            for transform in &self.transform_operations() {
                 match *transform {
                     computed_values::transform::ComputedOperation::Perspective(..) => {
                         return true;
@@ -1666,14 +1853,12 @@ pub fn get_writing_mode(inheritedbox_style: &style_structs::InheritedBox) -> Wri
     % if product == "servo":
         computed_values::text_orientation::T::sideways_right => {},
         computed_values::text_orientation::T::sideways_left => {
-            flags.insert(logical_geometry::FLAG_VERTICAL_LR);
+            flags.insert(logical_geometry::FLAG_SIDEWAYS_LEFT);
         },
-    % elif product == "gecko":
-        // FIXME(bholley): Need to make sure these are correct when we add
-        // full writing-mode support.
-        computed_values::text_orientation::T::mixed => {},
-        computed_values::text_orientation::T::upright => {},
     % endif
+        // Which glyphs are set upright is decided per text run, during layout.
+        computed_values::text_orientation::T::mixed |
+        computed_values::text_orientation::T::upright => {},
         computed_values::text_orientation::T::sideways => {
             if flags.intersects(logical_geometry::FLAG_VERTICAL_LR) {
                 flags.insert(logical_geometry::FLAG_SIDEWAYS_LEFT);
@@ -1719,6 +1904,7 @@ mod lazy_static_module {
 #[allow(unused_mut, unused_imports)]
 fn cascade_with_cached_declarations(
         viewport_size: Size2D<Au>,
+        container_size: Option<Size2D<Au>>,
         applicable_declarations: &[ApplicableDeclarationBlock],
         shareable: bool,
         parent_style: &ComputedValues,
@@ -1730,6 +1916,7 @@ fn cascade_with_cached_declarations(
     let mut context = computed::Context {
         is_root_element: false,
         viewport_size: viewport_size,
+        container_size: container_size,
         inherited_style: parent_style,
         style: ComputedValues::new(
             custom_properties,
@@ -1850,6 +2037,10 @@ static CASCADE_PROPERTY: [CascadePropertyFn; ${len(data.longhands)}] = [
 ///
 ///   * `viewport_size`: The size of the initial viewport.
 ///
+///   * `container_size`: The size of the element's query container, if it has one.
+///
+///   * `registrations`: The custom properties registered with `@property` rules.
+///
 ///   * `applicable_declarations`: The list of CSS rules that matched.
 ///
 ///   * `shareable`: Whether the `ComputedValues` structure to be constructed should be considered
@@ -1864,6 +2055,8 @@ static CASCADE_PROPERTY: [CascadePropertyFn; ${len(data.longhands)}] = [
 ///
 /// Returns the computed values and a boolean indicating whether the result is cacheable.
 pub fn cascade(viewport_size: Size2D<Au>,
+               container_size: Option<Size2D<Au>>,
+               registrations: &::custom_properties::Registrations,
                applicable_declarations: &[ApplicableDeclarationBlock],
                shareable: bool,
                parent_style: Option<<&ComputedValues>,
@@ -1885,7 +2078,7 @@ pub fn cascade(viewport_size: Size2D<Au>,
             match *declaration {
                 PropertyDeclaration::Custom(ref name, ref value) => {
                     ::custom_properties::cascade(
-                        &mut custom_properties, &inherited_custom_properties,
+                        &mut custom_properties, &inherited_custom_properties, registrations,
                         &mut seen_custom, name, value)
                 }
                 _ => {}
@@ -1893,10 +2086,11 @@ pub fn cascade(viewport_size: Size2D<Au>,
         }
     }
     let custom_properties = ::custom_properties::finish_cascade(
-            custom_properties, &inherited_custom_properties);
+            custom_properties, &inherited_custom_properties, &seen_custom, registrations);
 
     if let (Some(cached_style), Some(parent_style)) = (cached_style, parent_style) {
         let style = cascade_with_cached_declarations(viewport_size,
+                                                     container_size,
                                                      applicable_declarations,
                                                      shareable,
                                                      parent_style,
@@ -1910,6 +2104,7 @@ pub fn cascade(viewport_size: Size2D<Au>,
     let mut context = computed::Context {
         is_root_element: is_root_element,
         viewport_size: viewport_size,
+        container_size: container_size,
         inherited_style: inherited_style,
         style: ComputedValues::new(
             custom_properties,
@@ -1954,8 +2149,7 @@ pub fn cascade(viewport_size: Size2D<Au>,
                         PropertyDeclaration::FontSize(_) |
                         PropertyDeclaration::Color(_) |
                         PropertyDeclaration::Position(_) |
-                        PropertyDeclaration::Float(_) |
-                        PropertyDeclaration::TextDecoration${'' if product == 'servo' else 'Line'}(_)
+                        PropertyDeclaration::Float(_)
                     );
                     if
                         % if category_to_cascade_now == "early":
@@ -2019,14 +2213,39 @@ pub fn cascade(viewport_size: Size2D<Au>,
     {
         use computed_values::overflow_x::T as overflow;
         use computed_values::overflow_y;
-        match (style.get_box().clone_overflow_x() == longhands::overflow_x::computed_value::T::visible,
-               style.get_box().clone_overflow_y().0 == longhands::overflow_x::computed_value::T::visible) {
-            (true, true) => {}
-            (true, _) => {
-                style.mutate_box().set_overflow_x(overflow::auto);
+
+        // https://drafts.csswg.org/css-overflow-3/#overflow-properties
+        //
+        // `visible` and `clip` compute to `auto` and `hidden` respectively if the other axis
+        // makes the box a scroll container.
+        fn is_scrollable(value: overflow) -> bool {
+            match value {
+                overflow::visible => false,
+                % if product == "servo":
+                    overflow::clip => false,
+                % endif
+                _ => true,
+            }
+        }
+
+        fn to_scrollable(value: overflow) -> overflow {
+            match value {
+                overflow::visible => overflow::auto,
+                % if product == "servo":
+                    overflow::clip => overflow::hidden,
+                % endif
+                value => value,
+            }
+        }
+
+        let overflow_x = style.get_box().clone_overflow_x();
+        let overflow_y = style.get_box().clone_overflow_y().0;
+        match (is_scrollable(overflow_x), is_scrollable(overflow_y)) {
+            (false, true) => {
+                style.mutate_box().set_overflow_x(to_scrollable(overflow_x));
             }
-            (_, true) => {
-                style.mutate_box().set_overflow_y(overflow_y::T(overflow::auto));
+            (true, false) => {
+                style.mutate_box().set_overflow_y(overflow_y::T(to_scrollable(overflow_y)));
             }
             _ => {}
         }
@@ -31,7 +31,7 @@ ${helpers.four_sides_shorthand("border-style", "border-%s-style",
             // can be compared via partial eq
             % for side in ["top", "right", "bottom", "left"]:
                 let ${side} = match self.border_${side}_width {
-                    &DeclaredValue::Value(ref value) => DeclaredValue::Value(value.0),
+                    &DeclaredValue::Value(ref value) => DeclaredValue::Value(value.0.clone()),
                     &DeclaredValue::WithVariables {
                         css: ref a, first_token_type: ref b, base_url: ref c, from_shorthand: ref d
                     } => DeclaredValue::WithVariables {
@@ -98,7 +98,7 @@ pub fn parse_border(context: &ParserContext, input: &mut Parser)
             border_${side}_color: color,
             border_${side}_style: style,
             border_${side}_width:
-                width.map(longhands::${to_rust_ident('border-%s-width' % side)}::SpecifiedValue),
+                width.clone().map(longhands::${to_rust_ident('border-%s-width' % side)}::SpecifiedValue),
         })
     }
 
@@ -129,7 +129,7 @@ pub fn parse_border(context: &ParserContext, input: &mut Parser)
                 border_${side}_color: color.clone(),
                 border_${side}_style: style,
                 border_${side}_width:
-                    width.map(longhands::${to_rust_ident('border-%s-width' % side)}::SpecifiedValue),
+                    width.clone().map(longhands::${to_rust_ident('border-%s-width' % side)}::SpecifiedValue),
             % endfor
         })
     }
@@ -185,3 +185,107 @@ pub fn parse_border(context: &ParserContext, input: &mut Parser)
         }
     }
 </%helpers:shorthand>
+
+<%helpers:shorthand name="border-image" products="servo" sub_properties="border-image-source
+    border-image-slice border-image-width border-image-outset border-image-repeat">
+    use properties::longhands::{border_image_outset, border_image_repeat, border_image_slice};
+    use properties::longhands::{border_image_source, border_image_width};
+
+    pub fn parse_value(context: &ParserContext, input: &mut Parser) -> Result<Longhands, ()> {
+        let mut source = None;
+        let mut slice = None;
+        let mut width = None;
+        let mut outset = None;
+        let mut repeat = None;
+        let mut any = false;
+        loop {
+            if source.is_none() {
+                if let Ok(value) = input.try(|input| border_image_source::parse(context, input)) {
+                    source = Some(value);
+                    any = true;
+                    continue
+                }
+            }
+            if slice.is_none() {
+                if let Ok(value) = input.try(|input| border_image_slice::parse(context, input)) {
+                    slice = Some(value);
+                    any = true;
+                    // The width and outset may only follow the slice, each after a slash.
+                    if input.try(|input| input.expect_delim('/')).is_ok() {
+                        width = input.try(|input| border_image_width::parse(context, input)).ok();
+                        if input.try(|input| input.expect_delim('/')).is_ok() {
+                            outset = Some(try!(border_image_outset::parse(context, input)));
+                        } else if width.is_none() {
+                            return Err(())
+                        }
+                    }
+                    continue
+                }
+            }
+            if repeat.is_none() {
+                if let Ok(value) = input.try(|input| border_image_repeat::parse(context, input)) {
+                    repeat = Some(value);
+                    any = true;
+                    continue
+                }
+            }
+            break
+        }
+        if any {
+            Ok(Longhands {
+                border_image_source: source,
+                border_image_slice: slice,
+                border_image_width: width,
+                border_image_outset: outset,
+                border_image_repeat: repeat,
+            })
+        } else {
+            Err(())
+        }
+    }
+
+    impl<'a> LonghandsToSerialize<'a>  {
+        fn to_css_declared<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            match *self.border_image_source {
+                DeclaredValue::Initial => try!(write!(dest, "none")),
+                _ => try!(self.border_image_source.to_css(dest))
+            };
+            try!(write!(dest, " "));
+
+            match *self.border_image_slice {
+                DeclaredValue::Initial => try!(write!(dest, "100%")),
+                _ => try!(self.border_image_slice.to_css(dest))
+            };
+
+            // The width and outset are left out when they have their initial values, but the
+            // outset's slash has to follow the width's even when the width is left out.
+            let has_outset = match *self.border_image_outset {
+                DeclaredValue::Initial => false,
+                _ => true,
+            };
+            match *self.border_image_width {
+                DeclaredValue::Initial => {
+                    if has_outset {
+                        try!(write!(dest, " /"));
+                    }
+                }
+                _ => {
+                    try!(write!(dest, " / "));
+                    try!(self.border_image_width.to_css(dest));
+                }
+            };
+            if has_outset {
+                try!(write!(dest, " / "));
+                try!(self.border_image_outset.to_css(dest));
+            }
+
+            match *self.border_image_repeat {
+                DeclaredValue::Initial => Ok(()),
+                _ => {
+                    try!(write!(dest, " "));
+                    self.border_image_repeat.to_css(dest)
+                }
+            }
+        }
+    }
+</%helpers:shorthand>
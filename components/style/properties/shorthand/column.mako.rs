@@ -57,3 +57,70 @@
         }
     }
 </%helpers:shorthand>
+
+// https://drafts.csswg.org/css-multicol/#column-rule
+<%helpers:shorthand name="column-rule" products="servo"
+                    sub_properties="column-rule-width column-rule-style column-rule-color">
+    use properties::longhands::column_rule_width;
+    use values::specified;
+
+    pub fn parse_value(context: &ParserContext, input: &mut Parser) -> Result<Longhands, ()> {
+        let mut width = None;
+        let mut style = None;
+        let mut color = None;
+        let mut any = false;
+        loop {
+            if width.is_none() {
+                if let Ok(value) = input.try(|input| column_rule_width::parse(context, input)) {
+                    width = Some(value);
+                    any = true;
+                    continue
+                }
+            }
+            if style.is_none() {
+                if let Ok(value) = input.try(specified::BorderStyle::parse) {
+                    style = Some(value);
+                    any = true;
+                    continue
+                }
+            }
+            if color.is_none() {
+                if let Ok(value) = input.try(specified::CSSColor::parse) {
+                    color = Some(value);
+                    any = true;
+                    continue
+                }
+            }
+            break
+        }
+        if any {
+            Ok(Longhands {
+                column_rule_width: width,
+                column_rule_style: style,
+                column_rule_color: color,
+            })
+        } else {
+            Err(())
+        }
+    }
+
+    impl<'a> LonghandsToSerialize<'a>  {
+        fn to_css_declared<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            try!(self.column_rule_width.to_css(dest));
+            try!(write!(dest, " "));
+
+            match *self.column_rule_style {
+                DeclaredValue::Initial => try!(write!(dest, "none")),
+                _ => try!(self.column_rule_style.to_css(dest))
+            };
+
+            match *self.column_rule_color {
+                DeclaredValue::Initial => Ok(()),
+                _ => {
+                    try!(write!(dest, " "));
+                    self.column_rule_color.to_css(dest)
+                }
+            }
+        }
+    }
+</%helpers:shorthand>
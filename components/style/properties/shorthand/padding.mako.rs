@@ -4,4 +4,5 @@
 
 <%namespace name="helpers" file="/helpers.mako.rs" />
 
-${helpers.four_sides_shorthand("padding", "padding-%s", "specified::LengthOrPercentage::parse")}
+${helpers.four_sides_shorthand("padding", "padding-%s", "specified::LengthOrPercentage::parse",
+                                allow_quirks=True)}
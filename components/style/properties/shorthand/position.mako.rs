@@ -119,3 +119,28 @@
         }
     }
 </%helpers:shorthand>
+
+// https://drafts.csswg.org/css-align/#gap-shorthand
+<%helpers:shorthand name="gap" sub_properties="row-gap column-gap" products="servo">
+    use properties::longhands::{column_gap, row_gap};
+
+    pub fn parse_value(context: &ParserContext, input: &mut Parser) -> Result<Longhands, ()> {
+        let row_gap = try!(row_gap::parse(context, input));
+        let column_gap = input.try(|input| column_gap::parse(context, input)).unwrap_or(row_gap.clone());
+        Ok(Longhands {
+            row_gap: Some(row_gap),
+            column_gap: Some(column_gap),
+        })
+    }
+
+    impl<'a> LonghandsToSerialize<'a>  {
+        fn to_css_declared<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+            try!(self.row_gap.to_css(dest));
+            if self.row_gap == self.column_gap {
+                return Ok(())
+            }
+            try!(write!(dest, " "));
+            self.column_gap.to_css(dest)
+        }
+    }
+</%helpers:shorthand>
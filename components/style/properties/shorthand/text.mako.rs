@@ -7,15 +7,20 @@
 <%helpers:shorthand name="text-decoration"
                     sub_properties="text-decoration-color
                                     text-decoration-line
-                                    text-decoration-style"
-                    products="gecko"
-                    disable_when_testing="True">
+                                    text-decoration-style
+                                    ${'text-decoration-thickness' if product == 'servo' else ''}">
     use cssparser::Color as CSSParserColor;
     use properties::longhands::{text_decoration_color, text_decoration_line, text_decoration_style};
+    % if product == "servo":
+        use properties::longhands::text_decoration_thickness;
+    % endif
     use values::specified::CSSColor;
 
     pub fn parse_value(context: &ParserContext, input: &mut Parser) -> Result<Longhands, ()> {
         let (mut color, mut line, mut style, mut any) = (None, None, None, false);
+        % if product == "servo":
+            let mut thickness = None;
+        % endif
         loop {
             macro_rules! parse_component {
                 ($value:ident, $module:ident) => (
@@ -32,6 +37,9 @@
             parse_component!(color, text_decoration_color);
             parse_component!(line, text_decoration_line);
             parse_component!(style, text_decoration_style);
+            % if product == "servo":
+                parse_component!(thickness, text_decoration_thickness);
+            % endif
             break;
         }
 
@@ -44,6 +52,10 @@
                                                             authored: None })),
             text_decoration_line: line.or(Some(text_decoration_line::computed_value::none)),
             text_decoration_style: style.or(Some(text_decoration_style::computed_value::T::solid)),
+            % if product == "servo":
+                text_decoration_thickness:
+                    thickness.or(Some(text_decoration_thickness::SpecifiedValue::Auto)),
+            % endif
         })
     }
 
@@ -68,6 +80,13 @@
                 try!(color.to_css(dest));
             }
 
+            % if product == "servo":
+                if let DeclaredValue::Value(ref thickness) = *self.text_decoration_thickness {
+                    try!(write!(dest, " "));
+                    try!(thickness.to_css(dest));
+                }
+            % endif
+
             Ok(())
         }
     }
@@ -4,11 +4,15 @@
 
 //! Selector matching.
 
+use container_queries::ContainerConditions;
+use context::QuirksMode;
+use custom_properties::Registrations;
 use dom::PresentationalHintsSynthetizer;
 use element_state::*;
 use error_reporting::StdoutErrorReporter;
 use keyframes::KeyframesAnimation;
-use media_queries::{Device, MediaType};
+use media_queries::Device;
+use page::{self, PageRule, PageStyle};
 use properties::{self, PropertyDeclaration, PropertyDeclarationBlock, ComputedValues, Importance};
 use quickersort::sort_by;
 use restyle_hints::{RestyleHint, DependencySet};
@@ -27,9 +31,11 @@ use std::hash::BuildHasherDefault;
 use std::hash::Hash;
 use std::slice;
 use std::sync::Arc;
+use std::u32;
 use string_cache::Atom;
 use style_traits::viewport::ViewportConstraints;
-use stylesheets::{CSSRule, CSSRuleIteratorExt, Origin, Stylesheet, UserAgentStylesheets};
+use stylesheets::{CSSRule, CSSRuleIteratorExt, ImportLayer, LayerName, Origin, Stylesheet};
+use stylesheets::UserAgentStylesheets;
 use viewport::{MaybeNew, ViewportRuleCascade};
 
 pub type FnvHashMap<K, V> = HashMap<K, V, BuildHasherDefault<::fnv::FnvHasher>>;
@@ -50,8 +56,9 @@ pub struct Stylist {
     /// Viewport constraints based on the current device.
     viewport_constraints: Option<ViewportConstraints>,
 
-    /// If true, the quirks-mode stylesheet is applied.
-    quirks_mode: bool,
+    /// The mode of the document; the quirks-mode stylesheet is applied in
+    /// quirks mode.
+    quirks_mode: QuirksMode,
 
     /// If true, the device has changed, and the stylist needs to be updated.
     is_device_dirty: bool,
@@ -67,6 +74,12 @@ pub struct Stylist {
     /// A map with all the animations indexed by name.
     animations: FnvHashMap<Atom, KeyframesAnimation>,
 
+    /// The effective `@page` rules, in source order.
+    page_rules: Vec<Arc<PageRule>>,
+
+    /// The custom properties registered with `@property` rules.
+    custom_property_registrations: Registrations,
+
     /// Applicable declarations for a given non-eagerly cascaded pseudo-element.
     /// These are eagerly computed once, and then used to resolve the new
     /// computed values on the fly on layout.
@@ -92,11 +105,13 @@ impl Stylist {
             viewport_constraints: None,
             device: device,
             is_device_dirty: true,
-            quirks_mode: false,
+            quirks_mode: QuirksMode::NoQuirks,
 
             element_map: PerPseudoElementSelectorMap::new(),
             pseudos_map: Default::default(),
             animations: Default::default(),
+            page_rules: vec![],
+            custom_property_registrations: Default::default(),
             precomputed_pseudo_element_decls: Default::default(),
             rules_source_order: 0,
             state_deps: DependencySet::new(),
@@ -126,6 +141,8 @@ impl Stylist {
         self.element_map = PerPseudoElementSelectorMap::new();
         self.pseudos_map = Default::default();
         self.animations = Default::default();
+        self.page_rules.clear();
+        self.custom_property_registrations.clear();
         TheSelectorImpl::each_eagerly_cascaded_pseudo_element(|pseudo| {
             self.pseudos_map.insert(pseudo, PerPseudoElementSelectorMap::new());
         });
@@ -137,31 +154,84 @@ impl Stylist {
         self.sibling_affecting_selectors.clear();
         self.non_common_style_affecting_attributes_selectors.clear();
 
+        // Layers are ordered by their first declaration in any stylesheet, so
+        // they all have to be known before any rule is added.
+        let mut layer_tree = LayerTree::new();
+        {
+            let mut stylesheets: Vec<&Stylesheet> = vec![];
+            if let Some(ua_stylesheets) = ua_stylesheets {
+                stylesheets.extend(ua_stylesheets.user_or_user_agent_stylesheets.iter());
+                if self.quirks_mode == QuirksMode::Quirks {
+                    stylesheets.push(&ua_stylesheets.quirks_mode_stylesheet);
+                }
+            }
+            stylesheets.extend(doc_stylesheets.iter().map(|stylesheet| &**stylesheet));
+            for stylesheet in stylesheets {
+                if stylesheet.is_effective_for_device(&self.device) {
+                    layer_tree.add_rules(&stylesheet.rules, &self.device);
+                }
+            }
+        }
+        let layers = layer_tree.into_orders();
+
         if let Some(ua_stylesheets) = ua_stylesheets {
             for stylesheet in &ua_stylesheets.user_or_user_agent_stylesheets {
-                self.add_stylesheet(&stylesheet);
+                self.add_stylesheet(&stylesheet, &layers);
             }
 
-            if self.quirks_mode {
-                self.add_stylesheet(&ua_stylesheets.quirks_mode_stylesheet);
+            if self.quirks_mode == QuirksMode::Quirks {
+                self.add_stylesheet(&ua_stylesheets.quirks_mode_stylesheet, &layers);
             }
         }
 
         for ref stylesheet in doc_stylesheets.iter() {
-            self.add_stylesheet(stylesheet);
+            self.add_stylesheet(stylesheet, &layers);
         }
 
         self.is_device_dirty = false;
         true
     }
 
-    fn add_stylesheet(&mut self, stylesheet: &Stylesheet) {
+    fn add_stylesheet(&mut self, stylesheet: &Stylesheet, layers: &LayerOrders) {
         if !stylesheet.is_effective_for_device(&self.device) {
             return;
         }
-        let mut rules_source_order = self.rules_source_order;
 
-        for rule in stylesheet.effective_rules(&self.device) {
+        self.add_rules(&stylesheet.rules, stylesheet.origin, layers, &mut vec![], UNLAYERED, None);
+
+        debug!("Stylist stats:");
+        debug!(" - Got {} sibling-affecting selectors",
+               self.sibling_affecting_selectors.len());
+        debug!(" - Got {} non-common-style-attribute-affecting selectors",
+               self.non_common_style_affecting_attributes_selectors.len());
+        debug!(" - Got {} deps for style-hint calculation",
+               self.state_deps.len());
+
+        TheSelectorImpl::each_precomputed_pseudo_element(|pseudo| {
+            // TODO: Consider not doing this and just getting the rules on the
+            // fly. It should be a bit slower, but we'd take rid of the
+            // extra field, and avoid this precomputation entirely.
+            if let Some(map) = self.pseudos_map.remove(&pseudo) {
+                let mut declarations = vec![];
+
+                map.user_agent.get_universal_rules(&mut declarations);
+
+                self.precomputed_pseudo_element_decls.insert(pseudo, declarations);
+            }
+        })
+    }
+
+    /// Adds the effective rules in `rules`, which are in the cascade layer at
+    /// `layer_path` and only apply if `container_conditions` hold, in
+    /// pre-order.
+    fn add_rules(&mut self,
+                 rules: &[CSSRule],
+                 origin: Origin,
+                 layers: &LayerOrders,
+                 layer_path: &mut LayerPath,
+                 layer_order: u32,
+                 container_conditions: Option<&Arc<ContainerConditions>>) {
+        for rule in rules {
             match *rule {
                 CSSRule::Style(ref style_rule) => {
                     for selector in &style_rule.selectors {
@@ -169,19 +239,21 @@ impl Stylist {
                             self.pseudos_map
                                 .entry(pseudo.clone())
                                 .or_insert_with(PerPseudoElementSelectorMap::new)
-                                .borrow_for_origin(&stylesheet.origin)
+                                .borrow_for_origin(&origin)
                         } else {
-                            self.element_map.borrow_for_origin(&stylesheet.origin)
+                            self.element_map.borrow_for_origin(&origin)
                         };
 
                         map.insert(Rule {
                             selector: selector.complex_selector.clone(),
                             declarations: style_rule.declarations.clone(),
                             specificity: selector.specificity,
-                            source_order: rules_source_order,
+                            source_order: self.rules_source_order,
+                            layer_order: layer_order,
+                            container_conditions: container_conditions.cloned(),
                         });
                     }
-                    rules_source_order += 1;
+                    self.rules_source_order += 1;
 
                     for selector in &style_rule.selectors {
                         self.state_deps.note_selector(&selector.complex_selector);
@@ -194,7 +266,31 @@ impl Stylist {
                         }
                     }
 
-                    self.rules_source_order = rules_source_order;
+                    self.add_rules(&style_rule.rules, origin, layers, layer_path, layer_order,
+                                   container_conditions);
+                }
+                CSSRule::Media(ref media_rule) => {
+                    if media_rule.evaluate(&self.device) {
+                        self.add_rules(&media_rule.rules, origin, layers, layer_path, layer_order,
+                                       container_conditions);
+                    }
+                }
+                CSSRule::Layer(ref layer_rule) => {
+                    let len = layer_path.len();
+                    push_layer_keys(layer_path, layer_rule.name.as_ref(), &**layer_rule as *const _ as usize);
+                    let layer_order = layers[&*layer_path];
+                    self.add_rules(&layer_rule.rules, origin, layers, layer_path, layer_order,
+                                   container_conditions);
+                    layer_path.truncate(len);
+                }
+                CSSRule::Container(ref container_rule) => {
+                    // The query is evaluated for each element when matching.
+                    let conditions = Arc::new(ContainerConditions {
+                        query: container_rule.query.clone(),
+                        outer: container_conditions.cloned(),
+                    });
+                    self.add_rules(&container_rule.rules, origin, layers, layer_path, layer_order,
+                                   Some(&conditions));
                 }
                 CSSRule::Keyframes(ref keyframes_rule) => {
                     debug!("Found valid keyframes rule: {:?}", keyframes_rule);
@@ -209,31 +305,18 @@ impl Stylist {
                         self.animations.remove(&keyframes_rule.name);
                     }
                 }
+                CSSRule::Page(ref page_rule) => {
+                    self.page_rules.push(page_rule.clone());
+                }
+                CSSRule::Property(ref property_rule) => {
+                    // The last valid registration of a name wins.
+                    self.custom_property_registrations.insert(property_rule.name.clone(),
+                                                              property_rule.clone());
+                }
                 // We don't care about any other rule.
                 _ => {}
             }
         }
-
-        debug!("Stylist stats:");
-        debug!(" - Got {} sibling-affecting selectors",
-               self.sibling_affecting_selectors.len());
-        debug!(" - Got {} non-common-style-attribute-affecting selectors",
-               self.non_common_style_affecting_attributes_selectors.len());
-        debug!(" - Got {} deps for style-hint calculation",
-               self.state_deps.len());
-
-        TheSelectorImpl::each_precomputed_pseudo_element(|pseudo| {
-            // TODO: Consider not doing this and just getting the rules on the
-            // fly. It should be a bit slower, but we'd take rid of the
-            // extra field, and avoid this precomputation entirely.
-            if let Some(map) = self.pseudos_map.remove(&pseudo) {
-                let mut declarations = vec![];
-
-                map.user_agent.get_universal_rules(&mut declarations);
-
-                self.precomputed_pseudo_element_decls.insert(pseudo, declarations);
-            }
-        })
     }
 
     /// Computes the style for a given "precomputed" pseudo-element, taking the
@@ -246,6 +329,8 @@ impl Stylist {
         if let Some(declarations) = self.precomputed_pseudo_element_decls.get(pseudo) {
             let (computed, _) =
                 properties::cascade(self.device.au_viewport_size(),
+                                    None,
+                                    &self.custom_property_registrations,
                                     &declarations, false,
                                     parent.map(|p| &**p),
                                     None,
@@ -284,6 +369,8 @@ impl Stylist {
 
         let (computed, _) =
             properties::cascade(self.device.au_viewport_size(),
+                                None,
+                                &self.custom_property_registrations,
                                 &declarations, false,
                                 Some(&**parent), None, None,
                                 Box::new(StdoutErrorReporter));
@@ -299,7 +386,7 @@ impl Stylist {
 
         self.viewport_constraints = ViewportConstraints::maybe_new(device.viewport_size, &cascaded_rule);
         if let Some(ref constraints) = self.viewport_constraints {
-            device = Device::new(MediaType::Screen, constraints.size);
+            device = Device::new(device.media_type, constraints.size);
         }
 
         self.is_device_dirty |= stylesheets.iter().any(|stylesheet| {
@@ -314,8 +401,17 @@ impl Stylist {
         &self.viewport_constraints
     }
 
-    pub fn set_quirks_mode(&mut self, enabled: bool) {
-        self.quirks_mode = enabled;
+    pub fn set_quirks_mode(&mut self, mode: QuirksMode) {
+        if self.quirks_mode != mode {
+            self.quirks_mode = mode;
+            // Rebuild the rules on the next update to add or remove the
+            // quirks-mode stylesheet.
+            self.is_device_dirty = true;
+        }
+    }
+
+    pub fn quirks_mode(&self) -> QuirksMode {
+        self.quirks_mode
     }
 
     /// Returns the applicable CSS declarations for the given element.
@@ -455,6 +551,17 @@ impl Stylist {
         &self.animations
     }
 
+    #[inline]
+    pub fn custom_property_registrations(&self) -> &Registrations {
+        &self.custom_property_registrations
+    }
+
+    /// Returns the used style of the page at `page_index` when paginating. Pages
+    /// without a `size` are the size of the viewport.
+    pub fn page_style(&self, page_index: usize) -> PageStyle {
+        page::page_style(&self.page_rules, page_index, self.device.au_viewport_size())
+    }
+
     pub fn match_same_not_common_style_affecting_attributes_rules<E>(&self,
                                                                      element: &E,
                                                                      candidate: &E) -> bool
@@ -684,7 +791,7 @@ impl SelectorMap {
 
         // Sort only the rules we just added.
         sort_by_key(&mut matching_rules_list[init_len..],
-                    |rule| (rule.specificity, rule.source_order));
+                    |rule| rule.cascade_order());
     }
 
     /// Append to `rule_list` all universal Rules (rules with selector `*|*`) in
@@ -714,7 +821,7 @@ impl SelectorMap {
         }
 
         sort_by_key(&mut matching_rules_list[init_len..],
-                    |rule| (rule.specificity, rule.source_order));
+                    |rule| rule.cascade_order());
     }
 
     fn get_matching_rules_from_hash<E, Str, BorrowedStr: ?Sized, Vector>(
@@ -843,6 +950,10 @@ pub struct Rule {
     pub declarations: Arc<PropertyDeclarationBlock>,
     pub source_order: usize,
     pub specificity: u32,
+    /// The order of the rule's cascade layer, or `UNLAYERED`.
+    pub layer_order: u32,
+    /// The queries of the `@container` rules the rule is in, if any.
+    pub container_conditions: Option<Arc<ContainerConditions>>,
 }
 
 impl Rule {
@@ -853,6 +964,8 @@ impl Rule {
             importance: importance,
             source_order: self.source_order,
             specificity: self.specificity,
+            layer_order: self.layer_order,
+            container_conditions: self.container_conditions.clone(),
         }
     }
 }
@@ -868,6 +981,10 @@ pub struct ApplicableDeclarationBlock {
     pub importance: Importance,
     pub source_order: usize,
     pub specificity: u32,
+    pub layer_order: u32,
+    /// The conditions the declarations apply under, which are checked after
+    /// selector matching.
+    pub container_conditions: Option<Arc<ContainerConditions>>,
 }
 
 impl ApplicableDeclarationBlock {
@@ -880,9 +997,26 @@ impl ApplicableDeclarationBlock {
             importance: importance,
             source_order: 0,
             specificity: 0,
+            layer_order: UNLAYERED,
+            container_conditions: None,
         }
     }
 
+    /// The key that declarations of the same origin and importance are
+    /// sorted by, so that the ones that win come last.
+    ///
+    /// Later layers win for normal declarations and earlier layers win for
+    /// important ones, and unlayered declarations act as a final layer.
+    /// https://drafts.csswg.org/css-cascade-5/#cascade-layering
+    #[inline]
+    pub fn cascade_order(&self) -> (u32, u32, usize) {
+        let layer = match self.importance {
+            Importance::Normal => self.layer_order,
+            Importance::Important => !self.layer_order,
+        };
+        (layer, self.specificity, self.source_order)
+    }
+
     pub fn iter(&self) -> ApplicableDeclarationBlockIter {
         ApplicableDeclarationBlockIter {
             iter: self.mixed_declarations.declarations.iter(),
@@ -925,3 +1059,114 @@ impl<'a> DoubleEndedIterator for ApplicableDeclarationBlockIter<'a> {
 fn find_push<Str: Eq + Hash>(map: &mut FnvHashMap<Str, Vec<Rule>>, key: Str, value: Rule) {
     map.entry(key).or_insert_with(Vec::new).push(value)
 }
+
+/// The layer order of rules that aren't in any cascade layer, which come after
+/// every layer.
+pub const UNLAYERED: u32 = u32::MAX;
+
+/// A segment of the path of a cascade layer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum LayerKey {
+    Named(Atom),
+    /// An anonymous layer, identified by the address of the rule that
+    /// declares it.
+    Anonymous(usize),
+}
+
+/// The names of a cascade layer and the layers it is nested in.
+type LayerPath = Vec<LayerKey>;
+
+type LayerOrders = FnvHashMap<LayerPath, u32>;
+
+fn push_layer_keys(path: &mut LayerPath, name: Option<&LayerName>, anonymous_id: usize) {
+    match name {
+        Some(name) => path.extend(name.0.iter().map(|name| LayerKey::Named(name.clone()))),
+        None => path.push(LayerKey::Anonymous(anonymous_id)),
+    }
+}
+
+/// The cascade layers declared by a set of stylesheets, with the sublayers of
+/// each in the order they were first declared.
+///
+/// Layer names are shared between origins, which only matters if a user agent
+/// stylesheet declares layers.
+struct LayerTree {
+    children: Vec<(LayerKey, LayerTree)>,
+}
+
+impl LayerTree {
+    fn new() -> LayerTree {
+        LayerTree { children: vec![] }
+    }
+
+    /// Returns the sublayer at `path`, declaring it and any missing ancestors.
+    fn declare(&mut self, path: &[LayerKey]) -> &mut LayerTree {
+        if path.is_empty() {
+            return self
+        }
+        let index = match self.children.iter().position(|child| child.0 == path[0]) {
+            Some(index) => index,
+            None => {
+                self.children.push((path[0].clone(), LayerTree::new()));
+                self.children.len() - 1
+            }
+        };
+        self.children[index].1.declare(&path[1..])
+    }
+
+    /// Declares the layers in the effective rules in `rules`.
+    fn add_rules(&mut self, rules: &[CSSRule], device: &Device) {
+        for rule in rules {
+            let mut path = vec![];
+            match *rule {
+                CSSRule::Style(ref style_rule) => self.add_rules(&style_rule.rules, device),
+                CSSRule::Media(ref media_rule) => {
+                    if media_rule.evaluate(device) {
+                        self.add_rules(&media_rule.rules, device);
+                    }
+                }
+                CSSRule::Container(ref container_rule) => self.add_rules(&container_rule.rules, device),
+                CSSRule::Layer(ref layer_rule) => {
+                    push_layer_keys(&mut path, layer_rule.name.as_ref(), &**layer_rule as *const _ as usize);
+                    self.declare(&path).add_rules(&layer_rule.rules, device);
+                }
+                CSSRule::LayerStatement(ref statement) => {
+                    for name in &statement.names {
+                        path.clear();
+                        push_layer_keys(&mut path, Some(name), 0);
+                        self.declare(&path);
+                    }
+                }
+                CSSRule::Import(ref import_rule) => {
+                    let name = match import_rule.layer {
+                        ImportLayer::None => continue,
+                        ImportLayer::Anonymous => None,
+                        ImportLayer::Named(ref name) => Some(name),
+                    };
+                    if import_rule.media_queries.evaluate(device) {
+                        push_layer_keys(&mut path, name, &**import_rule as *const _ as usize);
+                        self.declare(&path);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Numbers every layer so that sublayers come before their parent layer,
+    /// and each layer comes after the layers declared before it.
+    fn into_orders(self) -> LayerOrders {
+        fn number(tree: LayerTree, path: &mut LayerPath, next: &mut u32, orders: &mut LayerOrders) {
+            for (key, child) in tree.children {
+                path.push(key);
+                number(child, path, next, orders);
+                orders.insert(path.clone(), *next);
+                *next += 1;
+                path.pop();
+            }
+        }
+        let mut orders = FnvHashMap::default();
+        number(self, &mut vec![], &mut 0, &mut orders);
+        orders
+    }
+}
@@ -22,6 +22,9 @@ pub enum PseudoElement {
     DetailsSummary,
     DetailsContent,
     ServoInputText,
+    Backdrop,
+    Cue,
+    Marker,
 }
 
 impl ToCss for PseudoElement {
@@ -34,6 +37,9 @@ impl ToCss for PseudoElement {
             DetailsSummary => "::-servo-details-summary",
             DetailsContent => "::-servo-details-content",
             ServoInputText => "::-servo-input-text",
+            Backdrop => "::backdrop",
+            Cue => "::cue",
+            Marker => "::marker",
         })
     }
 }
@@ -54,7 +60,10 @@ impl PseudoElement {
         match *self {
             PseudoElement::Before |
             PseudoElement::After |
-            PseudoElement::Selection => PseudoElementCascadeType::Eager,
+            PseudoElement::Selection |
+            PseudoElement::Backdrop |
+            PseudoElement::Cue |
+            PseudoElement::Marker => PseudoElementCascadeType::Eager,
             PseudoElement::DetailsSummary => PseudoElementCascadeType::Lazy,
             PseudoElement::DetailsContent |
             PseudoElement::ServoInputText => PseudoElementCascadeType::Precomputed,
@@ -80,6 +89,12 @@ pub enum NonTSPseudoClass {
     ReadOnly,
     PlaceholderShown,
     Target,
+    Valid,
+    Invalid,
+    Required,
+    Optional,
+    Modal,
+    ServoTopLayer,
 }
 
 impl ToCss for NonTSPseudoClass {
@@ -100,6 +115,12 @@ impl ToCss for NonTSPseudoClass {
             ReadOnly => ":read-only",
             PlaceholderShown => ":placeholder-shown",
             Target => ":target",
+            Valid => ":valid",
+            Invalid => ":invalid",
+            Required => ":required",
+            Optional => ":optional",
+            Modal => ":modal",
+            ServoTopLayer => ":-servo-top-layer",
             ServoNonZeroBorder => ":-servo-nonzero-border",
         })
     }
@@ -120,6 +141,12 @@ impl NonTSPseudoClass {
             ReadOnly | ReadWrite => IN_READ_WRITE_STATE,
             PlaceholderShown => IN_PLACEHOLDER_SHOWN_STATE,
             Target => IN_TARGET_STATE,
+            Valid => IN_VALID_STATE,
+            Invalid => IN_INVALID_STATE,
+            Required => IN_REQUIRED_STATE,
+            Optional => IN_OPTIONAL_STATE,
+            Modal => IN_MODAL_STATE,
+            ServoTopLayer => IN_TOP_LAYER_STATE,
 
             AnyLink |
             Link |
@@ -155,6 +182,13 @@ impl SelectorImpl for ServoSelectorImpl {
         attr_equals_selector_is_shareable(attr_selector, value)
     }
 
+    // FIXME: :has() can't be supported yet. selectors only hands us the name of
+    // non-tree-structural pseudo-classes, so there is no way to parse its
+    // relative selector list argument here; that needs a functional
+    // pseudo-class hook in the selectors crate first. Matching (walking the
+    // subject's descendants or later siblings) and invalidation (restyling
+    // the ancestors and earlier siblings of a changed element) can then live
+    // in this crate.
     fn parse_non_ts_pseudo_class(context: &ParserContext<TheSelectorImpl>,
                                  name: &str) -> Result<NonTSPseudoClass, ()> {
         use self::NonTSPseudoClass::*;
@@ -173,6 +207,17 @@ impl SelectorImpl for ServoSelectorImpl {
             "read-only" => ReadOnly,
             "placeholder-shown" => PlaceholderShown,
             "target" => Target,
+            "valid" => Valid,
+            "invalid" => Invalid,
+            "required" => Required,
+            "optional" => Optional,
+            "modal" => Modal,
+            "-servo-top-layer" => {
+                if !context.in_user_agent_stylesheet {
+                    return Err(());
+                }
+                ServoTopLayer
+            },
             "-servo-nonzero-border" => {
                 if !context.in_user_agent_stylesheet {
                     return Err(());
@@ -192,6 +237,9 @@ impl SelectorImpl for ServoSelectorImpl {
             "before" => Before,
             "after" => After,
             "selection" => Selection,
+            "backdrop" => Backdrop,
+            "cue" => Cue,
+            "marker" => Marker,
             "-servo-details-summary" => {
                 if !context.in_user_agent_stylesheet {
                     return Err(())
@@ -232,6 +280,9 @@ impl ServoSelectorImpl {
         fun(PseudoElement::DetailsSummary);
         fun(PseudoElement::Selection);
         fun(PseudoElement::ServoInputText);
+        fun(PseudoElement::Backdrop);
+        fun(PseudoElement::Cue);
+        fun(PseudoElement::Marker);
     }
 
     #[inline]
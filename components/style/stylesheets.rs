@@ -5,17 +5,21 @@
 //! Style sheets and their CSS rules.
 
 use cssparser::{AtRuleParser, Parser, QualifiedRuleParser, decode_stylesheet_bytes};
-use cssparser::{AtRuleType, RuleListParser, Token};
+use cssparser::{AtRuleType, Delimiter, RuleListParser, Token};
+use container_queries::ContainerQuery;
+use custom_properties::{self, PropertyRule, parse_property_block};
 use encoding::EncodingRef;
 use error_reporting::ParseErrorReporter;
 use font_face::{FontFaceRule, parse_font_face_block};
 use keyframes::{Keyframe, parse_keyframe_list};
 use media_queries::{Device, MediaQueryList, parse_media_query_list};
+use page::{PageRule, PageSelector};
 use parser::{ParserContext, ParserContextExtraData, log_css_error};
-use properties::{PropertyDeclarationBlock, parse_property_declaration_list};
+use properties::{PropertyDeclarationBlock, parse_property_declaration_list_with_rules};
 use selector_impl::TheSelectorImpl;
 use selectors::parser::{Selector, parse_selector_list};
 use smallvec::SmallVec;
+use std::ascii::AsciiExt;
 use std::cell::Cell;
 use std::iter::Iterator;
 use std::slice;
@@ -74,6 +78,12 @@ pub enum CSSRule {
     FontFace(Arc<FontFaceRule>),
     Viewport(Arc<ViewportRule>),
     Keyframes(Arc<KeyframesRule>),
+    Import(Arc<ImportRule>),
+    Layer(Arc<LayerBlockRule>),
+    LayerStatement(Arc<LayerStatementRule>),
+    Container(Arc<ContainerRule>),
+    Page(Arc<PageRule>),
+    Property(Arc<PropertyRule>),
 }
 
 
@@ -107,11 +117,104 @@ impl MediaRule {
     }
 }
 
+/// An `@container` rule, whose rules only apply to elements whose query
+/// container matches the query.
+/// https://drafts.csswg.org/css-contain-3/#container-rule
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct ContainerRule {
+    pub query: Arc<ContainerQuery>,
+    pub rules: Vec<CSSRule>,
+}
+
+/// The name of a cascade layer, such as `framework.base`, relative to the
+/// layer it is declared in.
+/// https://drafts.csswg.org/css-cascade-5/#layer-names
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct LayerName(pub Vec<Atom>);
+
+impl LayerName {
+    pub fn parse(input: &mut Parser) -> Result<LayerName, ()> {
+        let mut names = match try!(input.next()) {
+            Token::Ident(ref name) => vec![try!(layer_name_segment(name))],
+            _ => return Err(()),
+        };
+        // The segments are separated by periods, without whitespace.
+        while let Ok(name) = input.try(|input| {
+            match input.next_including_whitespace() {
+                Ok(Token::Delim('.')) => {}
+                _ => return Err(()),
+            }
+            match input.next_including_whitespace() {
+                Ok(Token::Ident(ref name)) => layer_name_segment(name),
+                _ => Err(()),
+            }
+        }) {
+            names.push(name);
+        }
+        Ok(LayerName(names))
+    }
+}
+
+fn layer_name_segment(name: &str) -> Result<Atom, ()> {
+    // The CSS-wide keywords are reserved.
+    if name.eq_ignore_ascii_case("initial") || name.eq_ignore_ascii_case("inherit") ||
+       name.eq_ignore_ascii_case("unset") {
+        return Err(())
+    }
+    Ok(Atom::from(name))
+}
+
+/// An `@layer` block rule. Its rules are in the named layer, or in a new
+/// anonymous layer if it has no name.
+/// https://drafts.csswg.org/css-cascade-5/#layer-block
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct LayerBlockRule {
+    pub name: Option<LayerName>,
+    pub rules: Vec<CSSRule>,
+}
+
+/// An `@layer` statement, which declares the order of layers without
+/// assigning any rules to them.
+/// https://drafts.csswg.org/css-cascade-5/#layer-empty
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct LayerStatementRule {
+    pub names: Vec<LayerName>,
+}
+
+/// The cascade layer the rules of an imported stylesheet are placed in.
+/// https://drafts.csswg.org/css-cascade-5/#layer-import
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum ImportLayer {
+    /// The imported rules aren't layered.
+    None,
+    /// The `layer` keyword, which puts the rules in a new anonymous layer.
+    Anonymous,
+    /// `layer(<layer-name>)`.
+    Named(LayerName),
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct ImportRule {
+    pub url: Url,
+    pub layer: ImportLayer,
+    pub media_queries: Arc<MediaQueryList>,
+}
+
 #[derive(Debug, PartialEq)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct StyleRule {
     pub selectors: Vec<Selector<TheSelectorImpl>>,
     pub declarations: Arc<PropertyDeclarationBlock>,
+    /// The rules nested in this rule's block, whose selectors have already
+    /// been resolved against this rule's.
+    /// https://drafts.csswg.org/css-nesting/#nesting
+    pub rules: Vec<CSSRule>,
 }
 
 
@@ -214,7 +317,7 @@ impl Stylesheet {
 /// `CSSRule` iterator.
 ///
 /// The iteration order is pre-order. Specifically, this implies that a
-/// conditional group rule will come before its nested rules.
+/// conditional group rule or a style rule will come before its nested rules.
 pub struct Rules<'a> {
     // 2 because normal case is likely to be just one level of nesting (@media)
     stack: SmallVec<[slice::Iter<'a, CSSRule>; 2]>,
@@ -250,6 +353,23 @@ impl<'a> Iterator for Rules<'a> {
                     }
                 }
 
+                // handle cascade layers
+                if let &CSSRule::Layer(ref rule) = rule {
+                    self.stack.push(rule.rules.iter());
+                }
+
+                // handle container queries, which depend on the element
+                if let &CSSRule::Container(ref rule) = rule {
+                    self.stack.push(rule.rules.iter());
+                }
+
+                // handle nested style rules
+                if let &CSSRule::Style(ref rule) = rule {
+                    if !rule.rules.is_empty() {
+                        self.stack.push(rule.rules.iter());
+                    }
+                }
+
                 return Some(rule)
             }
 
@@ -408,6 +528,24 @@ enum AtRulePrelude {
     Viewport,
     /// A @keyframes rule, with its animation name.
     Keyframes(Atom),
+    /// A @layer rule prelude, with the names of its layers. It is a
+    /// statement if it has no block.
+    Layer(Vec<LayerName>),
+    /// A @container rule prelude, with its query.
+    Container(Arc<ContainerQuery>),
+    /// A @page rule prelude, with its page selector.
+    Page(PageSelector),
+    /// A @property rule prelude, with the name of the custom property.
+    Property(Atom),
+}
+
+
+/// The prelude of a style rule.
+struct StyleRulePrelude {
+    selectors: Vec<Selector<TheSelectorImpl>>,
+    /// The source of each selector, which the selectors of nested rules are
+    /// resolved against.
+    sources: Vec<String>,
 }
 
 
@@ -421,8 +559,26 @@ impl<'a> AtRuleParser for TopLevelRuleParser<'a> {
             "import" => {
                 if self.state.get() <= State::Imports {
                     self.state.set(State::Imports);
-                    // TODO: support @import
-                    return Err(())  // "@import is not supported yet"
+                    let url = try!(input.expect_url_or_string());
+                    let url = try!(self.context.base_url.join(&url).map_err(|_| ()));
+                    let layer = if input.try(|input| input.expect_ident_matching("layer")).is_ok() {
+                        ImportLayer::Anonymous
+                    } else if input.try(|input| input.expect_function_matching("layer")).is_ok() {
+                        ImportLayer::Named(try!(input.parse_nested_block(|input| {
+                            let name = try!(LayerName::parse(input));
+                            try!(input.expect_exhausted());
+                            Ok(name)
+                        })))
+                    } else {
+                        ImportLayer::None
+                    };
+                    // FIXME: the imported stylesheet isn't loaded yet, but
+                    // its layer still takes its place in the layer order.
+                    return Ok(AtRuleType::WithoutBlock(CSSRule::Import(Arc::new(ImportRule {
+                        url: url,
+                        layer: layer,
+                        media_queries: Arc::new(parse_media_query_list(input)),
+                    }))))
                 } else {
                     return Err(())  // "@import must be before any rule but @charset"
                 }
@@ -455,6 +611,12 @@ impl<'a> AtRuleParser for TopLevelRuleParser<'a> {
             // @charset is removed by rust-cssparser if it’s the first rule in the stylesheet
             // anything left is invalid.
             "charset" => return Err(()), // (insert appropriate error message)
+            // @layer statements may come before @import and @namespace
+            // rules; blocks move the state to `Body` in `parse_block`.
+            "layer" => {
+                return AtRuleParser::parse_prelude(&mut NestedRuleParser { context: &self.context },
+                                                   name, input)
+            },
             _ => {}
         }
 
@@ -464,23 +626,29 @@ impl<'a> AtRuleParser for TopLevelRuleParser<'a> {
 
     #[inline]
     fn parse_block(&mut self, prelude: AtRulePrelude, input: &mut Parser) -> Result<CSSRule, ()> {
+        self.state.set(State::Body);
         AtRuleParser::parse_block(&mut NestedRuleParser { context: &self.context }, prelude, input)
     }
+
+    #[inline]
+    fn rule_without_block(&mut self, prelude: AtRulePrelude) -> CSSRule {
+        AtRuleParser::rule_without_block(&mut NestedRuleParser { context: &self.context }, prelude)
+    }
 }
 
 
 impl<'a> QualifiedRuleParser for TopLevelRuleParser<'a> {
-    type Prelude = Vec<Selector<TheSelectorImpl>>;
+    type Prelude = StyleRulePrelude;
     type QualifiedRule = CSSRule;
 
     #[inline]
-    fn parse_prelude(&mut self, input: &mut Parser) -> Result<Vec<Selector<TheSelectorImpl>>, ()> {
+    fn parse_prelude(&mut self, input: &mut Parser) -> Result<StyleRulePrelude, ()> {
         self.state.set(State::Body);
         QualifiedRuleParser::parse_prelude(&mut NestedRuleParser { context: &self.context }, input)
     }
 
     #[inline]
-    fn parse_block(&mut self, prelude: Vec<Selector<TheSelectorImpl>>, input: &mut Parser)
+    fn parse_block(&mut self, prelude: StyleRulePrelude, input: &mut Parser)
                    -> Result<CSSRule, ()> {
         QualifiedRuleParser::parse_block(&mut NestedRuleParser { context: &self.context },
                                          prelude, input)
@@ -523,6 +691,28 @@ impl<'a, 'b> AtRuleParser for NestedRuleParser<'a, 'b> {
 
                 Ok(AtRuleType::WithBlock(AtRulePrelude::Keyframes(Atom::from(name))))
             },
+            "layer" => {
+                let names = if input.is_exhausted() {
+                    vec![]
+                } else {
+                    try!(input.parse_comma_separated(LayerName::parse))
+                };
+                Ok(AtRuleType::OptionalBlock(AtRulePrelude::Layer(names)))
+            },
+            "container" => {
+                let query = try!(ContainerQuery::parse(input));
+                try!(input.expect_exhausted());
+                Ok(AtRuleType::WithBlock(AtRulePrelude::Container(Arc::new(query))))
+            },
+            "page" => {
+                Ok(AtRuleType::WithBlock(AtRulePrelude::Page(try!(PageSelector::parse(input)))))
+            },
+            "property" => {
+                let name = try!(input.expect_ident());
+                let name = Atom::from(try!(custom_properties::parse_name(&name)));
+                try!(input.expect_exhausted());
+                Ok(AtRuleType::WithBlock(AtRulePrelude::Property(name)))
+            },
             _ => Err(())
         }
     }
@@ -547,23 +737,205 @@ impl<'a, 'b> AtRuleParser for NestedRuleParser<'a, 'b> {
                     keyframes: parse_keyframe_list(&self.context, input),
                 })))
             }
+            AtRulePrelude::Layer(mut names) => {
+                // A block can name at most one layer.
+                if names.len() > 1 {
+                    return Err(())
+                }
+                Ok(CSSRule::Layer(Arc::new(LayerBlockRule {
+                    name: names.pop(),
+                    rules: parse_nested_rules(self.context, input),
+                })))
+            }
+            AtRulePrelude::Container(query) => {
+                Ok(CSSRule::Container(Arc::new(ContainerRule {
+                    query: query,
+                    rules: parse_nested_rules(self.context, input),
+                })))
+            }
+            AtRulePrelude::Page(selector) => {
+                Ok(CSSRule::Page(Arc::new(PageRule::parse(selector, self.context, input))))
+            }
+            AtRulePrelude::Property(name) => {
+                Ok(CSSRule::Property(Arc::new(try!(parse_property_block(name, self.context, input)))))
+            }
+        }
+    }
+
+    fn rule_without_block(&mut self, prelude: AtRulePrelude) -> CSSRule {
+        match prelude {
+            AtRulePrelude::Layer(names) => {
+                CSSRule::LayerStatement(Arc::new(LayerStatementRule {
+                    names: names,
+                }))
+            }
+            // Only @layer rules may omit their block.
+            _ => unreachable!(),
         }
     }
 }
 
 impl<'a, 'b> QualifiedRuleParser for NestedRuleParser<'a, 'b> {
-    type Prelude = Vec<Selector<TheSelectorImpl>>;
+    type Prelude = StyleRulePrelude;
     type QualifiedRule = CSSRule;
 
-    fn parse_prelude(&mut self, input: &mut Parser) -> Result<Vec<Selector<TheSelectorImpl>>, ()> {
-        parse_selector_list(&self.context.selector_context, input)
+    fn parse_prelude(&mut self, input: &mut Parser) -> Result<StyleRulePrelude, ()> {
+        let start = input.position();
+        let selectors = try!(parse_selector_list(&self.context.selector_context, input));
+        Ok(StyleRulePrelude {
+            selectors: selectors,
+            sources: split_selector_list(input.slice_from(start)),
+        })
     }
 
-    fn parse_block(&mut self, prelude: Vec<Selector<TheSelectorImpl>>, input: &mut Parser)
+    fn parse_block(&mut self, prelude: StyleRulePrelude, input: &mut Parser)
                    -> Result<CSSRule, ()> {
-        Ok(CSSRule::Style(Arc::new(StyleRule {
-            selectors: prelude,
-            declarations: Arc::new(parse_property_declaration_list(self.context, input))
-        })))
+        Ok(parse_style_rule_block(self.context, prelude, input))
+    }
+}
+
+fn parse_style_rule_block(context: &ParserContext, prelude: StyleRulePrelude, input: &mut Parser) -> CSSRule {
+    let (declarations, rules) = {
+        let sources = &prelude.sources;
+        parse_property_declaration_list_with_rules(context, input, |input| {
+            parse_nested_style_rule(context, sources, input)
+        })
+    };
+    CSSRule::Style(Arc::new(StyleRule {
+        selectors: prelude.selectors,
+        declarations: Arc::new(declarations),
+        rules: rules,
+    }))
+}
+
+/// Parses a rule nested in the block of a style rule whose selectors are
+/// `parents`. This is either a style rule or a conditional group rule, whose
+/// contents apply to the parent selectors.
+/// https://drafts.csswg.org/css-nesting/#nested-group-rules
+fn parse_nested_style_rule(context: &ParserContext, parents: &[String], input: &mut Parser)
+                           -> Result<CSSRule, ()> {
+    let at_keyword = input.try(|input| {
+        match try!(input.next()) {
+            Token::AtKeyword(name) => Ok(name),
+            _ => Err(())
+        }
+    });
+    if let Ok(name) = at_keyword {
+        if !name.eq_ignore_ascii_case("media") && !name.eq_ignore_ascii_case("container") {
+            return Err(())
+        }
+        let at_rule = try!(input.parse_until_before(Delimiter::CurlyBracketBlock, |input| {
+            AtRuleParser::parse_prelude(&mut NestedRuleParser { context: context }, &name, input)
+        }));
+        try!(input.expect_curly_bracket_block());
+        let prelude = StyleRulePrelude {
+            selectors: try!(parse_selector_sources(context, parents)),
+            sources: parents.to_vec(),
+        };
+        let rule = try!(input.parse_nested_block(|input| {
+            Ok(parse_style_rule_block(context, prelude, input))
+        }));
+        return match at_rule {
+            AtRuleType::WithBlock(AtRulePrelude::Media(media_queries)) => {
+                Ok(CSSRule::Media(Arc::new(MediaRule {
+                    media_queries: media_queries,
+                    rules: vec![rule],
+                })))
+            }
+            AtRuleType::WithBlock(AtRulePrelude::Container(query)) => {
+                Ok(CSSRule::Container(Arc::new(ContainerRule {
+                    query: query,
+                    rules: vec![rule],
+                })))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    let sources = try!(input.parse_until_before(Delimiter::CurlyBracketBlock, |input| {
+        resolve_nested_selectors(parents, input)
+    }));
+    try!(input.expect_curly_bracket_block());
+    let prelude = StyleRulePrelude {
+        selectors: try!(parse_selector_sources(context, &sources)),
+        sources: sources,
+    };
+    input.parse_nested_block(|input| Ok(parse_style_rule_block(context, prelude, input)))
+}
+
+/// Returns the source of each selector of a selector list.
+fn split_selector_list(source: &str) -> Vec<String> {
+    let mut input = Parser::new(source);
+    let mut sources = vec![];
+    loop {
+        let start = input.position();
+        let _: Result<(), ()> = input.parse_until_before(Delimiter::Comma, |input| {
+            while input.next().is_ok() {}
+            Ok(())
+        });
+        sources.push(input.slice_from(start).trim().to_owned());
+        if input.next().is_err() {
+            return sources
+        }
+    }
+}
+
+/// Resolves the selector list of a nested style rule against the selectors of
+/// its parent, by replacing each nesting selector (`&`) with every parent
+/// selector in turn. Selectors without a nesting selector are relative to
+/// their parent, as if they started with `& `.
+///
+/// This substitutes the parent selector's source rather than matching it like
+/// `:is()` would, so for example `.a .b { .c & {} }` is treated as
+/// `.c .a .b {}`. This only differs when the nesting selector is not at the
+/// start of the nested selector and the parent selector is complex.
+///
+/// https://drafts.csswg.org/css-nesting/#nest-selector
+fn resolve_nested_selectors(parents: &[String], input: &mut Parser) -> Result<Vec<String>, ()> {
+    let mut sources = vec![];
+    loop {
+        try!(input.parse_until_before(Delimiter::Comma, |input| {
+            let start = input.position();
+            let mut nesting_selectors = vec![];
+            loop {
+                let position = input.position();
+                match input.next() {
+                    Ok(Token::Delim('&')) => nesting_selectors.push((position, input.position())),
+                    Ok(_) => {},
+                    Err(()) => break,
+                }
+            }
+            let end = input.position();
+            if input.slice(start..end).trim().is_empty() {
+                return Err(())
+            }
+
+            for parent in parents {
+                let mut source = String::new();
+                if nesting_selectors.is_empty() {
+                    source.push_str(parent);
+                    source.push(' ');
+                    source.push_str(input.slice(start..end).trim());
+                } else {
+                    let mut last = start;
+                    for &(nesting_start, nesting_end) in &nesting_selectors {
+                        source.push_str(input.slice(last..nesting_start));
+                        source.push_str(parent);
+                        last = nesting_end;
+                    }
+                    source.push_str(input.slice(last..end));
+                }
+                sources.push(source.trim().to_owned());
+            }
+            Ok(())
+        }));
+        if input.next().is_err() {
+            return Ok(sources)
+        }
     }
 }
+
+fn parse_selector_sources(context: &ParserContext, sources: &[String])
+                          -> Result<Vec<Selector<TheSelectorImpl>>, ()> {
+    parse_selector_list(&context.selector_context, &mut Parser::new(&sources.join(", ")))
+}
@@ -272,9 +272,7 @@ fn ensure_node_styled_internal<'a, N, C>(node: N,
     // nodes, at best.
     let mut applicable_declarations = ApplicableDeclarations::new();
     if let Some(element) = node.as_element() {
-        let stylist = &context.shared_context().stylist;
-
-        element.match_element(&**stylist,
+        element.match_element(context.shared_context(),
                               None,
                               &mut applicable_declarations);
     }
@@ -339,9 +337,7 @@ pub fn recalc_style_at<'a, N, C>(context: &'a C,
                         }
 
                         // Perform the CSS selector matching.
-                        let stylist = &context.shared_context().stylist;
-
-                        relations = element.match_element(&**stylist,
+                        relations = element.match_element(context.shared_context(),
                                                           Some(&*bf),
                                                           &mut applicable_declarations);
 
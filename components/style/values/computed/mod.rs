@@ -7,13 +7,15 @@ use euclid::size::Size2D;
 use ordered_float::NotNaN;
 use properties::ComputedValues;
 use std::fmt;
+use std::sync::{Arc, RwLock};
 use super::{CSSFloat, specified};
 use super::LocalToCss;
+use self::position::Position;
 use super::specified::AngleOrCorner;
 use url::Url;
 
 pub use cssparser::Color as CSSColor;
-pub use super::specified::{Angle, BorderStyle, Time, UrlExtraData};
+pub use super::specified::{Angle, BorderStyle, CalcComparisonFunction, ExtremumLength, Time, UrlExtraData};
 
 pub mod basic_shape;
 pub mod position;
@@ -21,6 +23,9 @@ pub mod position;
 pub struct Context<'a> {
     pub is_root_element: bool,
     pub viewport_size: Size2D<Au>,
+    /// The size container query lengths are relative to, if the element has
+    /// a query container.
+    pub container_size: Option<Size2D<Au>>,
     pub inherited_style: &'a ComputedValues,
 
     /// Values access through this need to be in the properties "computed early":
@@ -31,6 +36,7 @@ pub struct Context<'a> {
 impl<'a> Context<'a> {
     pub fn is_root_element(&self) -> bool { self.is_root_element }
     pub fn viewport_size(&self) -> Size2D<Au> { self.viewport_size }
+    pub fn container_size(&self) -> Size2D<Au> { self.container_size.unwrap_or(self.viewport_size) }
     pub fn inherited_style(&self) -> &ComputedValues { &self.inherited_style }
     pub fn style(&self) -> &ComputedValues { &self.style }
     pub fn mutate_style(&mut self) -> &mut ComputedValues { &mut self.style }
@@ -84,6 +90,7 @@ impl ToComputedValue for specified::CSSColor {
 }
 
 impl ComputedValueAsSpecified for specified::BorderStyle {}
+impl ComputedValueAsSpecified for specified::ExtremumLength {}
 
 impl ToComputedValue for specified::Length {
     type ComputedValue = Au;
@@ -92,12 +99,14 @@ impl ToComputedValue for specified::Length {
     fn to_computed_value(&self, context: &Context) -> Au {
         match *self {
             specified::Length::Absolute(length) => length,
-            specified::Length::Calc(calc, range) => range.clamp(calc.to_computed_value(context).length()),
+            specified::Length::Calc(ref calc, range) => range.clamp(calc.to_computed_value(context).length()),
             specified::Length::FontRelative(length) =>
                 length.to_computed_value(context.style().get_font().clone_font_size(),
                                          context.style().root_font_size()),
             specified::Length::ViewportPercentage(length) =>
                 length.to_computed_value(context.viewport_size()),
+            specified::Length::ContainerPercentage(length) =>
+                length.to_computed_value(context.container_size()),
             specified::Length::ServoCharacterWidth(length) =>
                 length.to_computed_value(context.style().get_font().clone_font_size())
         }
@@ -109,11 +118,80 @@ impl ToComputedValue for specified::Length {
     }
 }
 
+/// A `min()`, `max()` or `clamp()` with percentages in its arguments, which is resolved at
+/// used-value time.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CalcComparison {
+    pub function: CalcComparisonFunction,
+    pub arguments: Vec<CalcLengthOrPercentage>,
+}
+
+impl CalcComparison {
+    pub fn to_used_value(&self, container_length: Au) -> Au {
+        let arguments: Vec<Au> =
+            self.arguments.iter().map(|argument| argument.to_used_value(container_length)).collect();
+        self.function.apply(&arguments)
+    }
+}
+
+impl ::cssparser::ToCss for CalcComparison {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        try!(dest.write_str(match self.function {
+            CalcComparisonFunction::Min => "min(",
+            CalcComparisonFunction::Max => "max(",
+            CalcComparisonFunction::Clamp => "clamp(",
+        }));
+        for (i, argument) in self.arguments.iter().enumerate() {
+            if i > 0 {
+                try!(dest.write_str(", "));
+            }
+            try!(argument.sum_to_css(dest));
+        }
+        dest.write_str(")")
+    }
+}
+
+lazy_static! {
+    static ref CALC_COMPARISONS: RwLock<Vec<Arc<Vec<CalcComparison>>>> = RwLock::new(Vec::new());
+}
+
+/// The comparisons that are added to the sum of a computed `calc()`.
+///
+/// Computed lengths are `Copy`, so the comparisons live in a table that is shared by the
+/// whole process, and this is their index in it. Equal comparisons are only stored once, and
+/// are never removed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct CalcComparisons(usize);
+
+impl CalcComparisons {
+    pub fn new(comparisons: Vec<CalcComparison>) -> CalcComparisons {
+        let index = CALC_COMPARISONS.read().unwrap().iter().position(|stored| **stored == comparisons);
+        if let Some(index) = index {
+            return CalcComparisons(index)
+        }
+
+        let mut table = CALC_COMPARISONS.write().unwrap();
+        // Another thread may have stored them since the table was read.
+        if let Some(index) = table.iter().position(|stored| **stored == comparisons) {
+            return CalcComparisons(index)
+        }
+        table.push(Arc::new(comparisons));
+        CalcComparisons(table.len() - 1)
+    }
+
+    pub fn get(&self) -> Arc<Vec<CalcComparison>> {
+        CALC_COMPARISONS.read().unwrap()[self.0].clone()
+    }
+}
+
 #[derive(Clone, PartialEq, Copy, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct CalcLengthOrPercentage {
     pub length: Option<Au>,
     pub percentage: Option<CSSFloat>,
+    /// The comparisons that can't be resolved until the percentages are.
+    pub comparisons: Option<CalcComparisons>,
 }
 
 impl CalcLengthOrPercentage {
@@ -126,6 +204,43 @@ impl CalcLengthOrPercentage {
     pub fn percentage(&self) -> CSSFloat {
         self.percentage.unwrap_or(0.)
     }
+
+    /// Returns the length with percentages of `container_length`.
+    pub fn to_used_value(&self, container_length: Au) -> Au {
+        let sum = self.length() + container_length.scale_by(self.percentage());
+        match self.comparisons {
+            Some(comparisons) => comparisons.get().iter().fold(sum, |sum, comparison| {
+                sum + comparison.to_used_value(container_length)
+            }),
+            None => sum,
+        }
+    }
+
+    /// Serializes the terms of the sum, without wrapping them in `calc()`.
+    fn sum_to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        let mut first_value = true;
+        if let Some(length) = self.length {
+            try!(write!(dest, "{}px", Au::to_px(length)));
+            first_value = false;
+        }
+        if let Some(percentage) = self.percentage {
+            if !first_value {
+                try!(dest.write_str(" + "));
+            }
+            try!(write!(dest, "{}%", percentage * 100.));
+            first_value = false;
+        }
+        if let Some(comparisons) = self.comparisons {
+            for comparison in comparisons.get().iter() {
+                if !first_value {
+                    try!(dest.write_str(" + "));
+                }
+                try!(::cssparser::ToCss::to_css(comparison, dest));
+                first_value = false;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl From<LengthOrPercentage> for CalcLengthOrPercentage {
@@ -135,12 +250,14 @@ impl From<LengthOrPercentage> for CalcLengthOrPercentage {
                 CalcLengthOrPercentage {
                     length: None,
                     percentage: Some(this),
+                    comparisons: None,
                 }
             }
             LengthOrPercentage::Length(this) => {
                 CalcLengthOrPercentage {
                     length: Some(this),
                     percentage: None,
+                    comparisons: None,
                 }
             }
             LengthOrPercentage::Calc(this) => {
@@ -157,12 +274,14 @@ impl From<LengthOrPercentageOrAuto> for Option<CalcLengthOrPercentage> {
                 Some(CalcLengthOrPercentage {
                     length: None,
                     percentage: Some(this),
+                    comparisons: None,
                 })
             }
             LengthOrPercentageOrAuto::Length(this) => {
                 Some(CalcLengthOrPercentage {
                     length: Some(this),
                     percentage: None,
+                    comparisons: None,
                 })
             }
             LengthOrPercentageOrAuto::Calc(this) => {
@@ -177,12 +296,15 @@ impl From<LengthOrPercentageOrAuto> for Option<CalcLengthOrPercentage> {
 
 impl ::cssparser::ToCss for CalcLengthOrPercentage {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
-        match (self.length, self.percentage) {
-            (None, Some(p)) => write!(dest, "{}%", p * 100.),
-            (Some(l), None) => write!(dest, "{}px", Au::to_px(l)),
-            (Some(l), Some(p)) => write!(dest, "calc({}px + {}%)", Au::to_px(l), p * 100.),
-            _ => unreachable!()
+        let count = self.length.iter().count() + self.percentage.iter().count() +
+            self.comparisons.map_or(0, |comparisons| comparisons.get().len());
+        assert!(count > 0);
+        if count == 1 {
+            return self.sum_to_css(dest)
         }
+        try!(dest.write_str("calc("));
+        try!(self.sum_to_css(dest));
+        dest.write_str(")")
     }
 }
 
@@ -190,9 +312,10 @@ impl ToComputedValue for specified::CalcLengthOrPercentage {
     type ComputedValue = CalcLengthOrPercentage;
 
     fn to_computed_value(&self, context: &Context) -> CalcLengthOrPercentage {
-        self.compute_from_viewport_and_font_size(context.viewport_size(),
-                                                 context.style().get_font().clone_font_size(),
-                                                 context.style().root_font_size())
+        self.compute_from_viewport_container_and_font_size(context.viewport_size(),
+                                                           context.container_size(),
+                                                           context.style().get_font().clone_font_size(),
+                                                           context.style().root_font_size())
 
     }
 
@@ -201,6 +324,14 @@ impl ToComputedValue for specified::CalcLengthOrPercentage {
         specified::CalcLengthOrPercentage {
             absolute: computed.length,
             percentage: computed.percentage.map(specified::Percentage),
+            comparisons: computed.comparisons.map_or(Vec::new(), |comparisons| {
+                comparisons.get().iter().map(|comparison| {
+                    specified::CalcComparison {
+                        function: comparison.function,
+                        arguments: comparison.arguments.iter().map(ToComputedValue::from_computed_value).collect(),
+                    }
+                }).collect()
+            }),
             ..Default::default()
         }
     }
@@ -294,13 +425,13 @@ impl ToComputedValue for specified::LengthOrPercentage {
 
     fn to_computed_value(&self, context: &Context) -> LengthOrPercentage {
         match *self {
-            specified::LengthOrPercentage::Length(value) => {
+            specified::LengthOrPercentage::Length(ref value) => {
                 LengthOrPercentage::Length(value.to_computed_value(context))
             }
             specified::LengthOrPercentage::Percentage(value) => {
                 LengthOrPercentage::Percentage(value.0)
             }
-            specified::LengthOrPercentage::Calc(calc) => {
+            specified::LengthOrPercentage::Calc(ref calc) => {
                 LengthOrPercentage::Calc(calc.to_computed_value(context))
             }
         }
@@ -376,7 +507,7 @@ impl ToComputedValue for specified::LengthOrPercentageOrAuto {
     #[inline]
     fn to_computed_value(&self, context: &Context) -> LengthOrPercentageOrAuto {
         match *self {
-            specified::LengthOrPercentageOrAuto::Length(value) => {
+            specified::LengthOrPercentageOrAuto::Length(ref value) => {
                 LengthOrPercentageOrAuto::Length(value.to_computed_value(context))
             }
             specified::LengthOrPercentageOrAuto::Percentage(value) => {
@@ -385,7 +516,7 @@ impl ToComputedValue for specified::LengthOrPercentageOrAuto {
             specified::LengthOrPercentageOrAuto::Auto => {
                 LengthOrPercentageOrAuto::Auto
             }
-            specified::LengthOrPercentageOrAuto::Calc(calc) => {
+            specified::LengthOrPercentageOrAuto::Calc(ref calc) => {
                 LengthOrPercentageOrAuto::Calc(calc.to_computed_value(context))
             }
         }
@@ -452,13 +583,13 @@ impl ToComputedValue for specified::LengthOrPercentageOrAutoOrContent {
     #[inline]
     fn to_computed_value(&self, context: &Context) -> LengthOrPercentageOrAutoOrContent {
         match *self {
-            specified::LengthOrPercentageOrAutoOrContent::Length(value) => {
+            specified::LengthOrPercentageOrAutoOrContent::Length(ref value) => {
                 LengthOrPercentageOrAutoOrContent::Length(value.to_computed_value(context))
             },
             specified::LengthOrPercentageOrAutoOrContent::Percentage(value) => {
                 LengthOrPercentageOrAutoOrContent::Percentage(value.0)
             },
-            specified::LengthOrPercentageOrAutoOrContent::Calc(calc) => {
+            specified::LengthOrPercentageOrAutoOrContent::Calc(ref calc) => {
                 LengthOrPercentageOrAutoOrContent::Calc(calc.to_computed_value(context))
             },
             specified::LengthOrPercentageOrAutoOrContent::Auto => {
@@ -510,6 +641,213 @@ impl ::cssparser::ToCss for LengthOrPercentageOrAutoOrContent {
     }
 }
 
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum Size {
+    LengthOrPercentageOrAuto(LengthOrPercentageOrAuto),
+    ExtremumLength(ExtremumLength),
+}
+
+impl Size {
+    #[inline]
+    pub fn auto() -> Size {
+        Size::LengthOrPercentageOrAuto(LengthOrPercentageOrAuto::Auto)
+    }
+
+    /// The length of this size, with the sizing keywords treated as `auto`.
+    #[inline]
+    pub fn length_or_auto(&self) -> LengthOrPercentageOrAuto {
+        match *self {
+            Size::LengthOrPercentageOrAuto(length) => length,
+            Size::ExtremumLength(_) => LengthOrPercentageOrAuto::Auto,
+        }
+    }
+
+    #[inline]
+    pub fn keyword(&self) -> Option<ExtremumLength> {
+        match *self {
+            Size::LengthOrPercentageOrAuto(_) => None,
+            Size::ExtremumLength(keyword) => Some(keyword),
+        }
+    }
+}
+
+impl ToComputedValue for specified::Size {
+    type ComputedValue = Size;
+
+    #[inline]
+    fn to_computed_value(&self, context: &Context) -> Size {
+        match *self {
+            specified::Size::LengthOrPercentageOrAuto(ref length) => {
+                Size::LengthOrPercentageOrAuto(length.to_computed_value(context))
+            }
+            specified::Size::ExtremumLength(keyword) => Size::ExtremumLength(keyword),
+        }
+    }
+
+    #[inline]
+    fn from_computed_value(computed: &Size) -> Self {
+        match *computed {
+            Size::LengthOrPercentageOrAuto(ref length) => {
+                specified::Size::LengthOrPercentageOrAuto(ToComputedValue::from_computed_value(length))
+            }
+            Size::ExtremumLength(keyword) => specified::Size::ExtremumLength(keyword),
+        }
+    }
+}
+
+impl ::cssparser::ToCss for Size {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            Size::LengthOrPercentageOrAuto(ref length) => length.to_css(dest),
+            Size::ExtremumLength(ref keyword) => keyword.to_css(dest),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum MinSize {
+    LengthOrPercentage(LengthOrPercentage),
+    ExtremumLength(ExtremumLength),
+    Auto,
+}
+
+impl MinSize {
+    #[inline]
+    pub fn zero() -> MinSize {
+        MinSize::LengthOrPercentage(LengthOrPercentage::Length(Au(0)))
+    }
+
+    #[inline]
+    pub fn auto() -> MinSize {
+        MinSize::Auto
+    }
+
+    /// The length of this size, with the sizing keywords and `auto` treated as zero.
+    /// Only flex items give `auto` a size of its own.
+    #[inline]
+    pub fn length(&self) -> LengthOrPercentage {
+        match *self {
+            MinSize::LengthOrPercentage(length) => length,
+            MinSize::ExtremumLength(_) | MinSize::Auto => LengthOrPercentage::Length(Au(0)),
+        }
+    }
+
+    #[inline]
+    pub fn keyword(&self) -> Option<ExtremumLength> {
+        match *self {
+            MinSize::LengthOrPercentage(_) | MinSize::Auto => None,
+            MinSize::ExtremumLength(keyword) => Some(keyword),
+        }
+    }
+
+    #[inline]
+    pub fn is_auto(&self) -> bool {
+        *self == MinSize::Auto
+    }
+}
+
+impl ToComputedValue for specified::MinSize {
+    type ComputedValue = MinSize;
+
+    #[inline]
+    fn to_computed_value(&self, context: &Context) -> MinSize {
+        match *self {
+            specified::MinSize::LengthOrPercentage(ref length) => {
+                MinSize::LengthOrPercentage(length.to_computed_value(context))
+            }
+            specified::MinSize::ExtremumLength(keyword) => MinSize::ExtremumLength(keyword),
+            specified::MinSize::Auto => MinSize::Auto,
+        }
+    }
+
+    #[inline]
+    fn from_computed_value(computed: &MinSize) -> Self {
+        match *computed {
+            MinSize::LengthOrPercentage(ref length) => {
+                specified::MinSize::LengthOrPercentage(ToComputedValue::from_computed_value(length))
+            }
+            MinSize::ExtremumLength(keyword) => specified::MinSize::ExtremumLength(keyword),
+            MinSize::Auto => specified::MinSize::Auto,
+        }
+    }
+}
+
+impl ::cssparser::ToCss for MinSize {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            MinSize::LengthOrPercentage(ref length) => length.to_css(dest),
+            MinSize::ExtremumLength(ref keyword) => keyword.to_css(dest),
+            MinSize::Auto => dest.write_str("auto"),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum MaxSize {
+    LengthOrPercentageOrNone(LengthOrPercentageOrNone),
+    ExtremumLength(ExtremumLength),
+}
+
+impl MaxSize {
+    #[inline]
+    pub fn none() -> MaxSize {
+        MaxSize::LengthOrPercentageOrNone(LengthOrPercentageOrNone::None)
+    }
+
+    /// The length of this size, with the sizing keywords treated as `none`.
+    #[inline]
+    pub fn length_or_none(&self) -> LengthOrPercentageOrNone {
+        match *self {
+            MaxSize::LengthOrPercentageOrNone(length) => length,
+            MaxSize::ExtremumLength(_) => LengthOrPercentageOrNone::None,
+        }
+    }
+
+    #[inline]
+    pub fn keyword(&self) -> Option<ExtremumLength> {
+        match *self {
+            MaxSize::LengthOrPercentageOrNone(_) => None,
+            MaxSize::ExtremumLength(keyword) => Some(keyword),
+        }
+    }
+}
+
+impl ToComputedValue for specified::MaxSize {
+    type ComputedValue = MaxSize;
+
+    #[inline]
+    fn to_computed_value(&self, context: &Context) -> MaxSize {
+        match *self {
+            specified::MaxSize::LengthOrPercentageOrNone(ref length) => {
+                MaxSize::LengthOrPercentageOrNone(length.to_computed_value(context))
+            }
+            specified::MaxSize::ExtremumLength(keyword) => MaxSize::ExtremumLength(keyword),
+        }
+    }
+
+    #[inline]
+    fn from_computed_value(computed: &MaxSize) -> Self {
+        match *computed {
+            MaxSize::LengthOrPercentageOrNone(ref length) => {
+                specified::MaxSize::LengthOrPercentageOrNone(ToComputedValue::from_computed_value(length))
+            }
+            MaxSize::ExtremumLength(keyword) => specified::MaxSize::ExtremumLength(keyword),
+        }
+    }
+}
+
+impl ::cssparser::ToCss for MaxSize {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            MaxSize::LengthOrPercentageOrNone(ref length) => length.to_css(dest),
+            MaxSize::ExtremumLength(ref keyword) => keyword.to_css(dest),
+        }
+    }
+}
+
 #[derive(PartialEq, Clone, Copy)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub enum LengthOrPercentageOrNone {
@@ -536,13 +874,13 @@ impl ToComputedValue for specified::LengthOrPercentageOrNone {
     #[inline]
     fn to_computed_value(&self, context: &Context) -> LengthOrPercentageOrNone {
         match *self {
-            specified::LengthOrPercentageOrNone::Length(value) => {
+            specified::LengthOrPercentageOrNone::Length(ref value) => {
                 LengthOrPercentageOrNone::Length(value.to_computed_value(context))
             }
             specified::LengthOrPercentageOrNone::Percentage(value) => {
                 LengthOrPercentageOrNone::Percentage(value.0)
             }
-            specified::LengthOrPercentageOrNone::Calc(calc) => {
+            specified::LengthOrPercentageOrNone::Calc(ref calc) => {
                 LengthOrPercentageOrNone::Calc(calc.to_computed_value(context))
             }
             specified::LengthOrPercentageOrNone::None => {
@@ -606,10 +944,10 @@ impl ToComputedValue for specified::LengthOrNone {
     #[inline]
     fn to_computed_value(&self, context: &Context) -> LengthOrNone {
         match *self {
-            specified::LengthOrNone::Length(specified::Length::Calc(calc, range)) => {
+            specified::LengthOrNone::Length(specified::Length::Calc(ref calc, range)) => {
                 LengthOrNone::Length(range.clamp(calc.to_computed_value(context).length()))
             }
-            specified::LengthOrNone::Length(value) => {
+            specified::LengthOrNone::Length(ref value) => {
                 LengthOrNone::Length(value.to_computed_value(context))
             }
             specified::LengthOrNone::None => {
@@ -652,6 +990,9 @@ impl ToComputedValue for specified::Image {
             specified::Image::LinearGradient(ref linear_gradient) => {
                 Image::LinearGradient(linear_gradient.to_computed_value(context))
             }
+            specified::Image::ConicGradient(ref conic_gradient) => {
+                Image::ConicGradient(conic_gradient.to_computed_value(context))
+            }
         }
     }
 
@@ -666,6 +1007,11 @@ impl ToComputedValue for specified::Image {
                     ToComputedValue::from_computed_value(linear_gradient)
                 )
             }
+            Image::ConicGradient(ref conic_gradient) => {
+                specified::Image::ConicGradient(
+                    ToComputedValue::from_computed_value(conic_gradient)
+                )
+            }
         }
     }
 }
@@ -677,6 +1023,7 @@ impl ToComputedValue for specified::Image {
 pub enum Image {
     Url(Url, UrlExtraData),
     LinearGradient(LinearGradient),
+    ConicGradient(ConicGradient),
 }
 
 impl fmt::Debug for Image {
@@ -684,6 +1031,7 @@ impl fmt::Debug for Image {
         match *self {
             Image::Url(ref url, ref _extra_data) => write!(f, "url(\"{}\")", url),
             Image::LinearGradient(ref grad) => write!(f, "linear-gradient({:?})", grad),
+            Image::ConicGradient(ref grad) => write!(f, "conic-gradient({:?})", grad),
         }
     }
 }
@@ -695,7 +1043,8 @@ impl ::cssparser::ToCss for Image {
             Image::Url(ref url, _) => {
                 url.to_css(dest)
             }
-            Image::LinearGradient(ref gradient) => gradient.to_css(dest)
+            Image::LinearGradient(ref gradient) => gradient.to_css(dest),
+            Image::ConicGradient(ref gradient) => gradient.to_css(dest),
         }
     }
 }
@@ -707,12 +1056,18 @@ pub struct LinearGradient {
     /// The angle or corner of the gradient.
     pub angle_or_corner: AngleOrCorner,
 
-    /// The color stops.
-    pub stops: Vec<ColorStop>,
+    /// The color stops and interpolation hints.
+    pub stops: Vec<GradientItem>,
+
+    /// Whether the color stops are repeated along the gradient line.
+    pub repeating: bool,
 }
 
 impl ::cssparser::ToCss for LinearGradient {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        if self.repeating {
+            try!(dest.write_str("repeating-"));
+        }
         try!(dest.write_str("linear-gradient("));
         try!(self.angle_or_corner.to_css(dest));
         for stop in &self.stops {
@@ -734,7 +1089,52 @@ impl fmt::Debug for LinearGradient {
     }
 }
 
-/// Computed values for one color stop in a linear gradient.
+/// Computed values for a CSS conic gradient.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct ConicGradient {
+    /// The angle the gradient starts at, clockwise from the top.
+    pub angle: Angle,
+
+    /// The center of the gradient.
+    pub position: Position,
+
+    /// The color stops and interpolation hints, whose positions are percentages of a full turn.
+    pub stops: Vec<GradientItem>,
+
+    /// Whether the color stops are repeated around the center.
+    pub repeating: bool,
+}
+
+impl ::cssparser::ToCss for ConicGradient {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        if self.repeating {
+            try!(dest.write_str("repeating-"));
+        }
+        try!(dest.write_str("conic-gradient(from "));
+        try!(self.angle.to_css(dest));
+        try!(dest.write_str(" at "));
+        try!(self.position.to_css(dest));
+        for stop in &self.stops {
+            try!(dest.write_str(", "));
+            try!(stop.to_css(dest));
+        }
+        try!(dest.write_str(")"));
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ConicGradient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let _ = write!(f, "from {:?} at {:?}", self.angle, self.position);
+        for stop in &self.stops {
+            let _ = write!(f, ", {:?}", stop);
+        }
+        Ok(())
+    }
+}
+
+/// Computed values for one color stop in a gradient.
 #[derive(Clone, PartialEq, Copy)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct ColorStop {
@@ -767,6 +1167,64 @@ impl fmt::Debug for ColorStop {
     }
 }
 
+/// Computed values for an item in the list of color stops of a gradient.
+#[derive(Clone, PartialEq, Copy, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum GradientItem {
+    ColorStop(ColorStop),
+    /// The position between two color stops where their colors are mixed evenly.
+    InterpolationHint(LengthOrPercentage),
+}
+
+impl ::cssparser::ToCss for GradientItem {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            GradientItem::ColorStop(ref stop) => stop.to_css(dest),
+            GradientItem::InterpolationHint(ref position) => position.to_css(dest),
+        }
+    }
+}
+
+impl ToComputedValue for specified::GradientItem {
+    type ComputedValue = GradientItem;
+
+    #[inline]
+    fn to_computed_value(&self, context: &Context) -> GradientItem {
+        match *self {
+            specified::GradientItem::ColorStop(ref stop) => {
+                GradientItem::ColorStop(ColorStop {
+                    color: stop.color.parsed,
+                    position: match stop.position {
+                        None => None,
+                        Some(ref value) => Some(value.to_computed_value(context)),
+                    },
+                })
+            }
+            specified::GradientItem::InterpolationHint(ref position) => {
+                GradientItem::InterpolationHint(position.to_computed_value(context))
+            }
+        }
+    }
+
+    #[inline]
+    fn from_computed_value(computed: &GradientItem) -> Self {
+        match *computed {
+            GradientItem::ColorStop(ref stop) => {
+                specified::GradientItem::ColorStop(specified::ColorStop {
+                    color: ToComputedValue::from_computed_value(&stop.color),
+                    position: match stop.position {
+                        None => None,
+                        Some(value) => Some(ToComputedValue::from_computed_value(&value)),
+                    },
+                })
+            }
+            GradientItem::InterpolationHint(ref position) => {
+                specified::GradientItem::InterpolationHint(ToComputedValue::from_computed_value(position))
+            }
+        }
+    }
+}
+
 impl ToComputedValue for specified::LinearGradient {
     type ComputedValue = LinearGradient;
 
@@ -774,38 +1232,49 @@ impl ToComputedValue for specified::LinearGradient {
     fn to_computed_value(&self, context: &Context) -> LinearGradient {
         let specified::LinearGradient {
             angle_or_corner,
-            ref stops
+            ref stops,
+            repeating,
         } = *self;
         LinearGradient {
             angle_or_corner: angle_or_corner,
-            stops: stops.iter().map(|stop| {
-                ColorStop {
-                    color: stop.color.parsed,
-                    position: match stop.position {
-                        None => None,
-                        Some(value) => Some(value.to_computed_value(context)),
-                    },
-                }
-            }).collect()
+            stops: stops.iter().map(|stop| stop.to_computed_value(context)).collect(),
+            repeating: repeating,
         }
     }
     #[inline]
     fn from_computed_value(computed: &LinearGradient) -> Self {
         let LinearGradient {
             angle_or_corner,
-            ref stops
+            ref stops,
+            repeating,
         } = *computed;
         specified::LinearGradient {
             angle_or_corner: angle_or_corner,
-            stops: stops.iter().map(|stop| {
-                specified::ColorStop {
-                    color: ToComputedValue::from_computed_value(&stop.color),
-                    position: match stop.position {
-                        None => None,
-                        Some(value) => Some(ToComputedValue::from_computed_value(&value)),
-                    },
-                }
-            }).collect()
+            stops: stops.iter().map(specified::GradientItem::from_computed_value).collect(),
+            repeating: repeating,
+        }
+    }
+}
+
+impl ToComputedValue for specified::ConicGradient {
+    type ComputedValue = ConicGradient;
+
+    #[inline]
+    fn to_computed_value(&self, context: &Context) -> ConicGradient {
+        ConicGradient {
+            angle: self.angle,
+            position: self.position.to_computed_value(context),
+            stops: self.stops.iter().map(|stop| stop.to_computed_value(context)).collect(),
+            repeating: self.repeating,
+        }
+    }
+    #[inline]
+    fn from_computed_value(computed: &ConicGradient) -> Self {
+        specified::ConicGradient {
+            angle: computed.angle,
+            position: ToComputedValue::from_computed_value(&computed.position),
+            stops: computed.stops.iter().map(specified::GradientItem::from_computed_value).collect(),
+            repeating: computed.repeating,
         }
     }
 }
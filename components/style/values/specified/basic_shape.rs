@@ -168,9 +168,9 @@ impl Parse for BasicShape {
 impl ToCss for BasicShape {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         match *self {
-            BasicShape::Inset(rect) => rect.to_css(dest),
-            BasicShape::Circle(circle) => circle.to_css(dest),
-            BasicShape::Ellipse(e) => e.to_css(dest),
+            BasicShape::Inset(ref rect) => rect.to_css(dest),
+            BasicShape::Circle(ref circle) => circle.to_css(dest),
+            BasicShape::Ellipse(ref e) => e.to_css(dest),
             BasicShape::Polygon(ref poly) => poly.to_css(dest),
         }
     }
@@ -182,9 +182,10 @@ impl ToComputedValue for BasicShape {
     #[inline]
     fn to_computed_value(&self, cx: &Context) -> Self::ComputedValue {
         match *self {
-            BasicShape::Inset(rect) => computed_basic_shape::BasicShape::Inset(rect.to_computed_value(cx)),
-            BasicShape::Circle(circle) => computed_basic_shape::BasicShape::Circle(circle.to_computed_value(cx)),
-            BasicShape::Ellipse(e) => computed_basic_shape::BasicShape::Ellipse(e.to_computed_value(cx)),
+            BasicShape::Inset(ref rect) => computed_basic_shape::BasicShape::Inset(rect.to_computed_value(cx)),
+            BasicShape::Circle(ref circle) =>
+                computed_basic_shape::BasicShape::Circle(circle.to_computed_value(cx)),
+            BasicShape::Ellipse(ref e) => computed_basic_shape::BasicShape::Ellipse(e.to_computed_value(cx)),
             BasicShape::Polygon(ref poly) => computed_basic_shape::BasicShape::Polygon(poly.to_computed_value(cx)),
         }
     }
@@ -207,7 +208,7 @@ impl ToComputedValue for BasicShape {
     }
 }
 
-#[derive(Clone, PartialEq, Copy, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 /// https://drafts.csswg.org/css-shapes/#funcdef-inset
 pub struct InsetRect {
@@ -272,7 +273,7 @@ impl ToComputedValue for InsetRect {
             right: self.right.to_computed_value(cx),
             bottom: self.bottom.to_computed_value(cx),
             left: self.left.to_computed_value(cx),
-            round: self.round.map(|r| r.to_computed_value(cx)),
+            round: self.round.as_ref().map(|r| r.to_computed_value(cx)),
         }
     }
 
@@ -302,7 +303,7 @@ fn serialize_basicshape_position<W>(position: &Position, dest: &mut W)
         // keyword-percentage pairs can be folded into a single percentage
         fn fold_keyword(keyword: Option<Keyword>, length: Option<LengthOrPercentage>)
             -> Option<LengthOrPercentage> {
-            let pc = match length.map(replace_with_percent) {
+            let pc = match length.clone().map(replace_with_percent) {
                 None => Percentage(0.0), // unspecified length = 0%
                 Some(LengthOrPercentage::Percentage(pc)) => pc,
                 _ => return None
@@ -338,8 +339,8 @@ fn serialize_basicshape_position<W>(position: &Position, dest: &mut W)
             replace_with_percent(y).to_css(dest)
         }
 
-        match (position.horiz_keyword, position.horiz_position,
-               position.vert_keyword, position.vert_position) {
+        match (position.horiz_keyword, position.horiz_position.clone(),
+               position.vert_keyword, position.vert_position.clone()) {
             (Some(hk), None, Some(vk), None) => {
                 // two keywords: serialize as two lengths
                 serialize_position_pair(hk.to_length_or_percentage(),
@@ -353,7 +354,7 @@ fn serialize_basicshape_position<W>(position: &Position, dest: &mut W)
             (hk, hp, vk, vp) => {
                 // only fold if both fold; the three-value form isn't
                 // allowed here.
-                if let (Some(x), Some(y)) = (fold_keyword(hk, hp), fold_keyword(vk, vp)) {
+                if let (Some(x), Some(y)) = (fold_keyword(hk, hp.clone()), fold_keyword(vk, vp.clone())) {
                     serialize_position_pair(x, y, dest)
                 } else {
                     // We failed to reduce it to a two-value form,
@@ -361,7 +362,7 @@ fn serialize_basicshape_position<W>(position: &Position, dest: &mut W)
                     let zero = LengthOrPercentage::Percentage(Percentage(0.0));
                     try!(hk.unwrap_or(Keyword::Left).to_css(dest));
                     try!(dest.write_str(" "));
-                    try!(replace_with_percent(hp.unwrap_or(zero)).to_css(dest));
+                    try!(replace_with_percent(hp.unwrap_or(zero.clone())).to_css(dest));
                     try!(dest.write_str(" "));
                     try!(vk.unwrap_or(Keyword::Top).to_css(dest));
                     try!(dest.write_str(" "));
@@ -371,7 +372,7 @@ fn serialize_basicshape_position<W>(position: &Position, dest: &mut W)
         }
 }
 
-#[derive(Clone, PartialEq, Copy, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 /// https://drafts.csswg.org/css-shapes/#funcdef-circle
 pub struct Circle {
@@ -441,7 +442,7 @@ impl ToComputedValue for Circle {
     }
 }
 
-#[derive(Clone, PartialEq, Copy, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 /// https://drafts.csswg.org/css-shapes/#funcdef-ellipse
 pub struct Ellipse {
@@ -486,7 +487,7 @@ impl Ellipse {
 impl ToCss for Ellipse {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         try!(dest.write_str("ellipse("));
-        if (self.semiaxis_x, self.semiaxis_y) != Default::default() {
+        if self.semiaxis_x != ShapeRadius::default() || self.semiaxis_y != ShapeRadius::default() {
             try!(self.semiaxis_x.to_css(dest));
             try!(dest.write_str(" "));
             try!(self.semiaxis_y.to_css(dest));
@@ -608,7 +609,7 @@ impl ToComputedValue for Polygon {
 }
 
 /// https://drafts.csswg.org/css-shapes/#typedef-shape-radius
-#[derive(Clone, PartialEq, Copy, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub enum ShapeRadius {
     Length(LengthOrPercentage),
@@ -638,7 +639,7 @@ impl ShapeRadius {
 impl ToCss for ShapeRadius {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         match *self {
-            ShapeRadius::Length(lop) => lop.to_css(dest),
+            ShapeRadius::Length(ref lop) => lop.to_css(dest),
             ShapeRadius::ClosestSide => dest.write_str("closest-side"),
             ShapeRadius::FarthestSide => dest.write_str("farthest-side"),
         }
@@ -652,7 +653,7 @@ impl ToComputedValue for ShapeRadius {
     #[inline]
     fn to_computed_value(&self, cx: &Context) -> Self::ComputedValue {
         match *self {
-            ShapeRadius::Length(lop) => {
+            ShapeRadius::Length(ref lop) => {
                 computed_basic_shape::ShapeRadius::Length(lop.to_computed_value(cx))
             }
             ShapeRadius::ClosestSide => computed_basic_shape::ShapeRadius::ClosestSide,
@@ -673,7 +674,7 @@ impl ToComputedValue for ShapeRadius {
 }
 
 /// https://drafts.csswg.org/css-backgrounds-3/#border-radius
-#[derive(Clone, PartialEq, Copy, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct BorderRadius {
     pub top_left: BorderRadiusSize,
@@ -715,13 +716,13 @@ impl BorderRadius {
         let heights = if input.try(|input| input.expect_delim('/')).is_ok() {
             try!(parse_one_set_of_border_values(input))
         } else {
-            widths.clone()
+            [widths[0].clone(), widths[1].clone(), widths[2].clone(), widths[3].clone()]
         };
         Ok(BorderRadius {
-            top_left: BorderRadiusSize::new(widths[0], heights[0]),
-            top_right: BorderRadiusSize::new(widths[1], heights[1]),
-            bottom_right: BorderRadiusSize::new(widths[2], heights[2]),
-            bottom_left: BorderRadiusSize::new(widths[3], heights[3]),
+            top_left: BorderRadiusSize::new(widths[0].clone(), heights[0].clone()),
+            top_right: BorderRadiusSize::new(widths[1].clone(), heights[1].clone()),
+            bottom_right: BorderRadiusSize::new(widths[2].clone(), heights[2].clone()),
+            bottom_left: BorderRadiusSize::new(widths[3].clone(), heights[3].clone()),
         })
     }
 }
@@ -733,19 +734,19 @@ fn parse_one_set_of_border_values(mut input: &mut Parser)
     let b = if let Ok(b) = input.try(LengthOrPercentage::parse) {
         b
     } else {
-        return Ok([a, a, a, a])
+        return Ok([a.clone(), a.clone(), a.clone(), a])
     };
 
     let c = if let Ok(c) = input.try(LengthOrPercentage::parse) {
         c
     } else {
-        return Ok([a, b, a, b])
+        return Ok([a.clone(), b.clone(), a, b])
     };
 
     if let Ok(d) = input.try(LengthOrPercentage::parse) {
         Ok([a, b, c, d])
     } else {
-        Ok([a, b, c, b])
+        Ok([a, b.clone(), c, b])
     }
 }
 
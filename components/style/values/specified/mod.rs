@@ -10,6 +10,7 @@ use gecko_bindings::ptr::{GeckoArcPrincipal, GeckoArcURI};
 use parser::ParserContext;
 #[cfg(feature = "gecko")]
 use parser::ParserContextExtraData;
+use self::position::{Keyword, Position};
 use std::ascii::AsciiExt;
 use std::cmp;
 use std::f32::consts::PI;
@@ -161,6 +162,50 @@ impl ViewportPercentageLength {
     }
 }
 
+/// A length relative to the size of the query container of an element.
+/// https://drafts.csswg.org/css-contain-3/#container-lengths
+#[derive(Clone, PartialEq, Copy, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum ContainerPercentageLength {
+    Cqw(CSSFloat),
+    Cqh(CSSFloat),
+}
+
+impl ToCss for ContainerPercentageLength {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            ContainerPercentageLength::Cqw(length) => write!(dest, "{}cqw", length),
+            ContainerPercentageLength::Cqh(length) => write!(dest, "{}cqh", length),
+        }
+    }
+}
+
+impl ContainerPercentageLength {
+    /// `container_size` is the size of the query container, or of the
+    /// viewport if there is none.
+    pub fn to_computed_value(&self, container_size: Size2D<Au>) -> Au {
+        let value = match *self {
+            ContainerPercentageLength::Cqw(length) =>
+                length * container_size.width.to_f32_px() / 100.0,
+            ContainerPercentageLength::Cqh(length) =>
+                length * container_size.height.to_f32_px() / 100.0,
+        };
+        Au::from_f32_px(value)
+    }
+}
+
+impl Mul<CSSFloat> for ContainerPercentageLength {
+    type Output = ContainerPercentageLength;
+
+    #[inline]
+    fn mul(self, scalar: CSSFloat) -> ContainerPercentageLength {
+        match self {
+            ContainerPercentageLength::Cqw(v) => ContainerPercentageLength::Cqw(v * scalar),
+            ContainerPercentageLength::Cqh(v) => ContainerPercentageLength::Cqh(v * scalar),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Copy, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct CharacterWidth(pub i32);
@@ -177,13 +222,16 @@ impl CharacterWidth {
     }
 }
 
-#[derive(Clone, PartialEq, Copy, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub enum Length {
     Absolute(Au),  // application units
     FontRelative(FontRelativeLength),
     ViewportPercentage(ViewportPercentageLength),
 
+    /// Container query lengths.
+    ContainerPercentage(ContainerPercentageLength),
+
     /// HTML5 "character width", as defined in HTML5 § 14.5.4.
     ///
     /// This cannot be specified by the user directly and is only generated by
@@ -197,6 +245,8 @@ impl HasViewportPercentage for Length {
     fn has_viewport_percentage(&self) -> bool {
         match *self {
             Length::ViewportPercentage(_) => true,
+            // These are relative to the viewport if there is no query container.
+            Length::ContainerPercentage(_) => true,
             Length::Calc(ref calc, _) => calc.has_viewport_percentage(),
             _ => false
         }
@@ -209,6 +259,7 @@ impl ToCss for Length {
             Length::Absolute(length) => write!(dest, "{}px", length.to_f32_px()),
             Length::FontRelative(length) => length.to_css(dest),
             Length::ViewportPercentage(length) => length.to_css(dest),
+            Length::ContainerPercentage(length) => length.to_css(dest),
             Length::Calc(ref calc, _) => calc.to_css(dest),
             Length::ServoCharacterWidth(_)
             => panic!("internal CSS values should never be serialized"),
@@ -225,6 +276,7 @@ impl Mul<CSSFloat> for Length {
             Length::Absolute(Au(v)) => Length::Absolute(Au(((v as f32) * scalar) as i32)),
             Length::FontRelative(v) => Length::FontRelative(v * scalar),
             Length::ViewportPercentage(v) => Length::ViewportPercentage(v * scalar),
+            Length::ContainerPercentage(v) => Length::ContainerPercentage(v * scalar),
             Length::Calc(..) => panic!("Can't multiply Calc!"),
             Length::ServoCharacterWidth(_) => panic!("Can't multiply ServoCharacterWidth!"),
         }
@@ -292,9 +344,9 @@ impl Length {
                 Length::parse_dimension(value.value, unit),
             Token::Number(ref value) if value.value == 0. =>
                 Ok(Length::Absolute(Au(0))),
-            Token::Function(ref name) if name.eq_ignore_ascii_case("calc") =>
+            Token::Function(ref name) if is_math_function(name) =>
                 input.parse_nested_block(|input| {
-                    CalcLengthOrPercentage::parse_length(input, context)
+                    CalcLengthOrPercentage::parse_length(input, name, context)
                 }),
             _ => Err(())
         }
@@ -305,6 +357,15 @@ impl Length {
     pub fn parse_non_negative(input: &mut Parser) -> Result<Length, ()> {
         Length::parse_internal(input, AllowedNumericType::NonNegative)
     }
+    /// Parse a number without a unit as a length in pixels, which quirks mode
+    /// allows for some properties.
+    /// https://quirks.spec.whatwg.org/#the-unitless-length-quirk
+    pub fn parse_unitless(input: &mut Parser, non_negative: bool) -> Result<Length, ()> {
+        match try!(input.next()) {
+            Token::Number(ref value) if !non_negative || value.value >= 0. => Ok(Length::from_px(value.value)),
+            _ => Err(())
+        }
+    }
     pub fn parse_dimension(value: CSSFloat, unit: &str) -> Result<Length, ()> {
         match_ignore_ascii_case! { unit,
             "px" => Ok(Length::from_px(value)),
@@ -324,6 +385,9 @@ impl Length {
             "vh" => Ok(Length::ViewportPercentage(ViewportPercentageLength::Vh(value))),
             "vmin" => Ok(Length::ViewportPercentage(ViewportPercentageLength::Vmin(value))),
             "vmax" => Ok(Length::ViewportPercentage(ViewportPercentageLength::Vmax(value))),
+            // container query lengths
+            "cqw" => Ok(Length::ContainerPercentage(ContainerPercentageLength::Cqw(value))),
+            "cqh" => Ok(Length::ContainerPercentage(ContainerPercentageLength::Cqh(value))),
             _ => Err(())
         }
     }
@@ -351,6 +415,7 @@ enum CalcValueNode {
     Percentage(CSSFloat),
     Number(CSSFloat),
     Sum(Box<CalcSumNode>),
+    Comparison(CalcComparison),
 }
 
 #[derive(Clone, Debug)]
@@ -376,6 +441,7 @@ enum SimplifiedValueNode {
     Percentage(CSSFloat),
     Number(CSSFloat),
     Sum(Box<SimplifiedSumNode>),
+    Comparison(CalcComparison),
 }
 impl<'a> Mul<CSSFloat> for &'a SimplifiedValueNode {
     type Output = SimplifiedValueNode;
@@ -383,7 +449,7 @@ impl<'a> Mul<CSSFloat> for &'a SimplifiedValueNode {
     #[inline]
     fn mul(self, scalar: CSSFloat) -> SimplifiedValueNode {
         match *self {
-            SimplifiedValueNode::Length(l) => SimplifiedValueNode::Length(l * scalar),
+            SimplifiedValueNode::Length(ref l) => SimplifiedValueNode::Length(l.clone() * scalar),
             SimplifiedValueNode::Percentage(p) => SimplifiedValueNode::Percentage(p * scalar),
             SimplifiedValueNode::Angle(Angle(a)) => SimplifiedValueNode::Angle(Angle(a * scalar)),
             SimplifiedValueNode::Time(Time(t)) => SimplifiedValueNode::Time(Time(t * scalar)),
@@ -392,6 +458,7 @@ impl<'a> Mul<CSSFloat> for &'a SimplifiedValueNode {
                 let sum = &**s * scalar;
                 SimplifiedValueNode::Sum(Box::new(sum))
             }
+            SimplifiedValueNode::Comparison(ref c) => SimplifiedValueNode::Comparison(c * scalar),
         }
     }
 }
@@ -399,8 +466,10 @@ impl<'a> Mul<CSSFloat> for &'a SimplifiedValueNode {
 pub fn parse_integer(input: &mut Parser) -> Result<i32, ()> {
     match try!(input.next()) {
         Token::Number(ref value) => value.int_value.ok_or(()),
-        Token::Function(ref name) if name.eq_ignore_ascii_case("calc") => {
-            let ast = try!(input.parse_nested_block(|i| CalcLengthOrPercentage::parse_sum(i, CalcUnit::Integer)));
+        Token::Function(ref name) if is_math_function(name) => {
+            let ast = try!(input.parse_nested_block(|i| {
+                CalcLengthOrPercentage::parse_function(i, name, CalcUnit::Integer)
+            }));
 
             let mut result = None;
 
@@ -424,8 +493,10 @@ pub fn parse_integer(input: &mut Parser) -> Result<i32, ()> {
 pub fn parse_number(input: &mut Parser) -> Result<f32, ()> {
     match try!(input.next()) {
         Token::Number(ref value) => Ok(value.value),
-        Token::Function(ref name) if name.eq_ignore_ascii_case("calc") => {
-            let ast = try!(input.parse_nested_block(|i| CalcLengthOrPercentage::parse_sum(i, CalcUnit::Number)));
+        Token::Function(ref name) if is_math_function(name) => {
+            let ast = try!(input.parse_nested_block(|i| {
+                CalcLengthOrPercentage::parse_function(i, name, CalcUnit::Number)
+            }));
 
             let mut result = None;
 
@@ -446,6 +517,90 @@ pub fn parse_number(input: &mut Parser) -> Result<f32, ()> {
     }
 }
 
+/// Returns whether `name` is a math function, whose arguments are calc expressions.
+/// https://drafts.csswg.org/css-values-4/#math
+pub fn is_math_function(name: &str) -> bool {
+    name.eq_ignore_ascii_case("calc") || name.eq_ignore_ascii_case("min") ||
+        name.eq_ignore_ascii_case("max") || name.eq_ignore_ascii_case("clamp")
+}
+
+/// The unit of an argument of `min()`, `max()` or `clamp()`, which can only be compared
+/// with arguments of the same unit.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum CalcComparableUnit {
+    Number,
+    Percentage,
+    Angle,
+    Time,
+    Absolute,
+    Em,
+    Ex,
+    Ch,
+    Rem,
+    Vw,
+    Vh,
+    Vmin,
+    Vmax,
+    Cqw,
+    Cqh,
+}
+
+impl CalcComparableUnit {
+    fn from_value(node: &SimplifiedValueNode) -> Option<(CalcComparableUnit, CSSFloat)> {
+        Some(match *node {
+            SimplifiedValueNode::Number(value) => (CalcComparableUnit::Number, value),
+            SimplifiedValueNode::Percentage(value) => (CalcComparableUnit::Percentage, value),
+            SimplifiedValueNode::Angle(Angle(value)) => (CalcComparableUnit::Angle, value),
+            SimplifiedValueNode::Time(Time(value)) => (CalcComparableUnit::Time, value),
+            SimplifiedValueNode::Length(Length::Absolute(Au(value))) =>
+                (CalcComparableUnit::Absolute, value as CSSFloat),
+            SimplifiedValueNode::Length(Length::FontRelative(length)) => match length {
+                FontRelativeLength::Em(value) => (CalcComparableUnit::Em, value),
+                FontRelativeLength::Ex(value) => (CalcComparableUnit::Ex, value),
+                FontRelativeLength::Ch(value) => (CalcComparableUnit::Ch, value),
+                FontRelativeLength::Rem(value) => (CalcComparableUnit::Rem, value),
+            },
+            SimplifiedValueNode::Length(Length::ViewportPercentage(length)) => match length {
+                ViewportPercentageLength::Vw(value) => (CalcComparableUnit::Vw, value),
+                ViewportPercentageLength::Vh(value) => (CalcComparableUnit::Vh, value),
+                ViewportPercentageLength::Vmin(value) => (CalcComparableUnit::Vmin, value),
+                ViewportPercentageLength::Vmax(value) => (CalcComparableUnit::Vmax, value),
+            },
+            SimplifiedValueNode::Length(Length::ContainerPercentage(length)) => match length {
+                ContainerPercentageLength::Cqw(value) => (CalcComparableUnit::Cqw, value),
+                ContainerPercentageLength::Cqh(value) => (CalcComparableUnit::Cqh, value),
+            },
+            _ => return None
+        })
+    }
+
+    fn to_value(self, value: CSSFloat) -> CalcValueNode {
+        match self {
+            CalcComparableUnit::Number => CalcValueNode::Number(value),
+            CalcComparableUnit::Percentage => CalcValueNode::Percentage(value),
+            CalcComparableUnit::Angle => CalcValueNode::Angle(Angle(value)),
+            CalcComparableUnit::Time => CalcValueNode::Time(Time(value)),
+            CalcComparableUnit::Absolute => CalcValueNode::Length(Length::Absolute(Au(value as i32))),
+            CalcComparableUnit::Em => CalcValueNode::Length(Length::FontRelative(FontRelativeLength::Em(value))),
+            CalcComparableUnit::Ex => CalcValueNode::Length(Length::FontRelative(FontRelativeLength::Ex(value))),
+            CalcComparableUnit::Ch => CalcValueNode::Length(Length::FontRelative(FontRelativeLength::Ch(value))),
+            CalcComparableUnit::Rem => CalcValueNode::Length(Length::FontRelative(FontRelativeLength::Rem(value))),
+            CalcComparableUnit::Vw =>
+                CalcValueNode::Length(Length::ViewportPercentage(ViewportPercentageLength::Vw(value))),
+            CalcComparableUnit::Vh =>
+                CalcValueNode::Length(Length::ViewportPercentage(ViewportPercentageLength::Vh(value))),
+            CalcComparableUnit::Vmin =>
+                CalcValueNode::Length(Length::ViewportPercentage(ViewportPercentageLength::Vmin(value))),
+            CalcComparableUnit::Vmax =>
+                CalcValueNode::Length(Length::ViewportPercentage(ViewportPercentageLength::Vmax(value))),
+            CalcComparableUnit::Cqw =>
+                CalcValueNode::Length(Length::ContainerPercentage(ContainerPercentageLength::Cqw(value))),
+            CalcComparableUnit::Cqh =>
+                CalcValueNode::Length(Length::ContainerPercentage(ContainerPercentageLength::Cqh(value))),
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 enum CalcUnit {
     Number,
@@ -456,7 +611,92 @@ enum CalcUnit {
     Time,
 }
 
-#[derive(Clone, PartialEq, Copy, Debug, Default)]
+/// The function of a comparison in `calc()`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum CalcComparisonFunction {
+    Min,
+    Max,
+    Clamp,
+}
+
+impl CalcComparisonFunction {
+    fn from_name(name: &str) -> Option<CalcComparisonFunction> {
+        match_ignore_ascii_case! { name,
+            "min" => Some(CalcComparisonFunction::Min),
+            "max" => Some(CalcComparisonFunction::Max),
+            "clamp" => Some(CalcComparisonFunction::Clamp),
+            _ => None
+        }
+    }
+
+    /// Returns the value of the function for the given arguments, of which `clamp()` takes
+    /// three.
+    pub fn apply<T: PartialOrd + Copy>(self, arguments: &[T]) -> T {
+        fn min<T: PartialOrd>(a: T, b: T) -> T { if b < a { b } else { a } }
+        fn max<T: PartialOrd>(a: T, b: T) -> T { if b > a { b } else { a } }
+
+        match self {
+            CalcComparisonFunction::Min => arguments[1..].iter().fold(arguments[0], |a, &b| min(a, b)),
+            CalcComparisonFunction::Max => arguments[1..].iter().fold(arguments[0], |a, &b| max(a, b)),
+            // The minimum wins over the maximum.
+            CalcComparisonFunction::Clamp => max(arguments[0], min(arguments[1], arguments[2])),
+        }
+    }
+}
+
+/// A `min()`, `max()` or `clamp()` whose arguments are of different units, and so can only
+/// be compared once they are computed, or once their percentages are resolved at layout.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct CalcComparison {
+    pub function: CalcComparisonFunction,
+    pub arguments: Vec<CalcLengthOrPercentage>,
+}
+
+impl<'a> Mul<CSSFloat> for &'a CalcComparison {
+    type Output = CalcComparison;
+
+    fn mul(self, scalar: CSSFloat) -> CalcComparison {
+        let mut arguments: Vec<_> = self.arguments.iter().map(|argument| argument * scalar).collect();
+        if scalar >= 0. {
+            return CalcComparison { function: self.function, arguments: arguments }
+        }
+        let function = match self.function {
+            CalcComparisonFunction::Min => CalcComparisonFunction::Max,
+            CalcComparisonFunction::Max => CalcComparisonFunction::Min,
+            CalcComparisonFunction::Clamp => {
+                // -clamp(a, b, c) is min(-a, max(-b, -c)), which is no clamp() when a > c.
+                let max = CalcComparison {
+                    function: CalcComparisonFunction::Max,
+                    arguments: arguments.split_off(1),
+                };
+                arguments.push(CalcLengthOrPercentage { comparisons: vec![max], ..Default::default() });
+                CalcComparisonFunction::Min
+            }
+        };
+        CalcComparison { function: function, arguments: arguments }
+    }
+}
+
+impl ToCss for CalcComparison {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        try!(dest.write_str(match self.function {
+            CalcComparisonFunction::Min => "min(",
+            CalcComparisonFunction::Max => "max(",
+            CalcComparisonFunction::Clamp => "clamp(",
+        }));
+        for (i, argument) in self.arguments.iter().enumerate() {
+            if i > 0 {
+                try!(dest.write_str(", "));
+            }
+            try!(argument.sum_to_css(dest));
+        }
+        dest.write_str(")")
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct CalcLengthOrPercentage {
     pub absolute: Option<Au>,
@@ -464,14 +704,100 @@ pub struct CalcLengthOrPercentage {
     pub vh: Option<ViewportPercentageLength>,
     pub vmin: Option<ViewportPercentageLength>,
     pub vmax: Option<ViewportPercentageLength>,
+    pub cqw: Option<ContainerPercentageLength>,
+    pub cqh: Option<ContainerPercentageLength>,
     pub em: Option<FontRelativeLength>,
     pub ex: Option<FontRelativeLength>,
     pub ch: Option<FontRelativeLength>,
     pub rem: Option<FontRelativeLength>,
     pub percentage: Option<Percentage>,
+    pub comparisons: Vec<CalcComparison>,
+}
+
+impl<'a> Mul<CSSFloat> for &'a CalcLengthOrPercentage {
+    type Output = CalcLengthOrPercentage;
+
+    fn mul(self, scalar: CSSFloat) -> CalcLengthOrPercentage {
+        CalcLengthOrPercentage {
+            absolute: self.absolute.map(|Au(v)| Au(((v as f32) * scalar) as i32)),
+            vw: self.vw.map(|v| v * scalar),
+            vh: self.vh.map(|v| v * scalar),
+            vmin: self.vmin.map(|v| v * scalar),
+            vmax: self.vmax.map(|v| v * scalar),
+            cqw: self.cqw.map(|v| v * scalar),
+            cqh: self.cqh.map(|v| v * scalar),
+            em: self.em.map(|v| v * scalar),
+            ex: self.ex.map(|v| v * scalar),
+            ch: self.ch.map(|v| v * scalar),
+            rem: self.rem.map(|v| v * scalar),
+            percentage: self.percentage.map(|Percentage(p)| Percentage(p * scalar)),
+            comparisons: self.comparisons.iter().map(|c| c * scalar).collect(),
+        }
+    }
 }
 
 impl CalcLengthOrPercentage {
+    /// Parses the arguments of the math function `name`.
+    fn parse_function(input: &mut Parser, name: &str, expected_unit: CalcUnit) -> Result<CalcSumNode, ()> {
+        if name.eq_ignore_ascii_case("calc") {
+            return CalcLengthOrPercentage::parse_sum(input, expected_unit)
+        }
+        let value = try!(CalcLengthOrPercentage::parse_comparison(input, name, expected_unit));
+        Ok(CalcSumNode { products: vec![CalcProductNode { values: vec![value] }] })
+    }
+
+    /// Parses the arguments of `min()`, `max()` or `clamp()`. The function is resolved right
+    /// away if its arguments are all of the same unit, and is otherwise kept for lengths until
+    /// they are computed or their percentages are resolved.
+    fn parse_comparison(input: &mut Parser, name: &str, expected_unit: CalcUnit) -> Result<CalcValueNode, ()> {
+        let function = try!(CalcComparisonFunction::from_name(name).ok_or(()));
+        let arguments = try!(input.parse_comma_separated(|input| {
+            CalcLengthOrPercentage::parse_sum(input, expected_unit)
+        }));
+        if function == CalcComparisonFunction::Clamp && arguments.len() != 3 {
+            return Err(())
+        }
+
+        let comparable: Result<Vec<_>, ()> =
+            arguments.iter().map(CalcLengthOrPercentage::simplify_sum_to_comparable).collect();
+        if let Ok(comparable) = comparable {
+            let unit = comparable[0].0;
+            if comparable.iter().all(|&(argument_unit, _)| argument_unit == unit) {
+                let values: Vec<CSSFloat> = comparable.iter().map(|&(_, value)| value).collect();
+                return Ok(unit.to_value(function.apply(&values)))
+            }
+        }
+
+        match expected_unit {
+            CalcUnit::Length | CalcUnit::LengthOrPercentage => {}
+            _ => return Err(()),
+        }
+        let arguments = try!(arguments.iter().map(CalcLengthOrPercentage::simplify_sum).collect());
+        Ok(CalcValueNode::Comparison(CalcComparison {
+            function: function,
+            arguments: arguments,
+        }))
+    }
+
+    fn simplify_sum_to_comparable(node: &CalcSumNode) -> Result<(CalcComparableUnit, CSSFloat), ()> {
+        let mut result = None;
+        for product in &node.products {
+            let values = match try!(CalcLengthOrPercentage::simplify_product(product)) {
+                SimplifiedValueNode::Sum(sum) => sum.values,
+                value => vec![value],
+            };
+            for value in &values {
+                let (unit, value) = try!(CalcComparableUnit::from_value(value).ok_or(()));
+                result = match result {
+                    None => Some((unit, value)),
+                    Some((result_unit, sum)) if result_unit == unit => Some((unit, sum + value)),
+                    Some(_) => return Err(()),
+                };
+            }
+        }
+        result.ok_or(())
+    }
+
     fn parse_sum(input: &mut Parser, expected_unit: CalcUnit) -> Result<CalcSumNode, ()> {
         let mut products = Vec::new();
         products.push(try!(CalcLengthOrPercentage::parse_product(input, expected_unit)));
@@ -542,6 +868,10 @@ impl CalcLengthOrPercentage {
                 input.parse_nested_block(|i| CalcLengthOrPercentage::parse_sum(i, expected_unit))
                      .map(|result| CalcValueNode::Sum(Box::new(result)))
             },
+            (Token::Function(ref name), _) if is_math_function(name) => {
+                input.parse_nested_block(|i| CalcLengthOrPercentage::parse_function(i, name, expected_unit))
+                     .map(|result| CalcValueNode::Sum(Box::new(result)))
+            },
             _ => Err(())
         }
     }
@@ -602,10 +932,11 @@ impl CalcLengthOrPercentage {
                     node_with_unit = Some(match *node {
                         CalcValueNode::Sum(ref sum) =>
                             try!(CalcLengthOrPercentage::simplify_products_in_sum(sum)),
-                        CalcValueNode::Length(l) => SimplifiedValueNode::Length(l),
+                        CalcValueNode::Length(ref l) => SimplifiedValueNode::Length(l.clone()),
                         CalcValueNode::Angle(a) => SimplifiedValueNode::Angle(a),
                         CalcValueNode::Time(t) => SimplifiedValueNode::Time(t),
                         CalcValueNode::Percentage(p) => SimplifiedValueNode::Percentage(p),
+                        CalcValueNode::Comparison(ref c) => SimplifiedValueNode::Comparison(c.clone()),
                         _ => unreachable!("Numbers should have been handled by simplify_value_to_nubmer")
                     })
                 },
@@ -620,23 +951,28 @@ impl CalcLengthOrPercentage {
     }
 
     fn parse_length(input: &mut Parser,
+                    name: &str,
                     context: AllowedNumericType) -> Result<Length, ()> {
-        CalcLengthOrPercentage::parse(input, CalcUnit::Length).map(|calc| {
+        CalcLengthOrPercentage::parse(input, name, CalcUnit::Length).map(|calc| {
             Length::Calc(calc, context)
         })
     }
 
-    fn parse_length_or_percentage(input: &mut Parser) -> Result<CalcLengthOrPercentage, ()> {
-        CalcLengthOrPercentage::parse(input, CalcUnit::LengthOrPercentage)
+    fn parse_length_or_percentage(input: &mut Parser, name: &str) -> Result<CalcLengthOrPercentage, ()> {
+        CalcLengthOrPercentage::parse(input, name, CalcUnit::LengthOrPercentage)
     }
 
     fn parse(input: &mut Parser,
+             name: &str,
              expected_unit: CalcUnit) -> Result<CalcLengthOrPercentage, ()> {
-        let ast = try!(CalcLengthOrPercentage::parse_sum(input, expected_unit));
+        let ast = try!(CalcLengthOrPercentage::parse_function(input, name, expected_unit));
+        CalcLengthOrPercentage::simplify_sum(&ast)
+    }
 
+    fn simplify_sum(node: &CalcSumNode) -> Result<CalcLengthOrPercentage, ()> {
         let mut simplified = Vec::new();
-        for ref node in ast.products {
-            match try!(CalcLengthOrPercentage::simplify_product(node)) {
+        for product in &node.products {
+            match try!(CalcLengthOrPercentage::simplify_product(product)) {
                 SimplifiedValueNode::Sum(sum) => simplified.extend_from_slice(&sum.values),
                 value => simplified.push(value),
             }
@@ -647,12 +983,14 @@ impl CalcLengthOrPercentage {
         let mut vh = None;
         let mut vmax = None;
         let mut vmin = None;
+        let mut cqw = None;
+        let mut cqh = None;
         let mut em = None;
         let mut ex = None;
         let mut ch = None;
         let mut rem = None;
         let mut percentage = None;
-        let mut number = None;
+        let mut comparisons = Vec::new();
 
         for value in simplified {
             match value {
@@ -671,6 +1009,13 @@ impl CalcLengthOrPercentage {
                         ViewportPercentageLength::Vmax(val) =>
                             vmax = Some(vmax.unwrap_or(0.) + val),
                     },
+                SimplifiedValueNode::Length(Length::ContainerPercentage(c)) =>
+                    match c {
+                        ContainerPercentageLength::Cqw(val) =>
+                            cqw = Some(cqw.unwrap_or(0.) + val),
+                        ContainerPercentageLength::Cqh(val) =>
+                            cqh = Some(cqh.unwrap_or(0.) + val),
+                    },
                 SimplifiedValueNode::Length(Length::FontRelative(f)) =>
                     match f {
                         FontRelativeLength::Em(val) =>
@@ -682,7 +1027,8 @@ impl CalcLengthOrPercentage {
                         FontRelativeLength::Rem(val) =>
                             rem = Some(rem.unwrap_or(0.) + val),
                     },
-                SimplifiedValueNode::Number(val) => number = Some(number.unwrap_or(0.) + val),
+                SimplifiedValueNode::Comparison(comparison) => comparisons.push(comparison),
+                // A number can't be added to a length.
                 _ => return Err(()),
             }
         }
@@ -693,16 +1039,19 @@ impl CalcLengthOrPercentage {
             vh: vh.map(ViewportPercentageLength::Vh),
             vmax: vmax.map(ViewportPercentageLength::Vmax),
             vmin: vmin.map(ViewportPercentageLength::Vmin),
+            cqw: cqw.map(ContainerPercentageLength::Cqw),
+            cqh: cqh.map(ContainerPercentageLength::Cqh),
             em: em.map(FontRelativeLength::Em),
             ex: ex.map(FontRelativeLength::Ex),
             ch: ch.map(FontRelativeLength::Ch),
             rem: rem.map(FontRelativeLength::Rem),
             percentage: percentage.map(Percentage),
+            comparisons: comparisons,
         })
     }
 
-    pub fn parse_time(input: &mut Parser) -> Result<Time, ()> {
-        let ast = try!(CalcLengthOrPercentage::parse_sum(input, CalcUnit::Time));
+    pub fn parse_time(input: &mut Parser, name: &str) -> Result<Time, ()> {
+        let ast = try!(CalcLengthOrPercentage::parse_function(input, name, CalcUnit::Time));
 
         let mut simplified = Vec::new();
         for ref node in ast.products {
@@ -728,8 +1077,8 @@ impl CalcLengthOrPercentage {
         }
     }
 
-    pub fn parse_angle(input: &mut Parser) -> Result<Angle, ()> {
-        let ast = try!(CalcLengthOrPercentage::parse_sum(input, CalcUnit::Angle));
+    pub fn parse_angle(input: &mut Parser, name: &str) -> Result<Angle, ()> {
+        let ast = try!(CalcLengthOrPercentage::parse_function(input, name, CalcUnit::Angle));
 
         let mut simplified = Vec::new();
         for ref node in ast.products {
@@ -758,11 +1107,27 @@ impl CalcLengthOrPercentage {
         }
     }
 
+    /// Computes the value where there is no query container, so that container query lengths
+    /// are relative to the viewport.
     pub fn compute_from_viewport_and_font_size(&self,
                                                viewport_size: Size2D<Au>,
                                                font_size: Au,
                                                root_font_size: Au)
                                                -> computed::CalcLengthOrPercentage
+    {
+        self.compute_from_viewport_container_and_font_size(viewport_size,
+                                                           viewport_size,
+                                                           font_size,
+                                                           root_font_size)
+    }
+
+    /// `container_size` is the size that container query lengths are relative to.
+    pub fn compute_from_viewport_container_and_font_size(&self,
+                                                         viewport_size: Size2D<Au>,
+                                                         container_size: Size2D<Au>,
+                                                         font_size: Au,
+                                                         root_font_size: Au)
+                                                         -> computed::CalcLengthOrPercentage
     {
         let mut length = None;
 
@@ -777,6 +1142,13 @@ impl CalcLengthOrPercentage {
             }
         }
 
+        for val in &[self.cqw, self.cqh] {
+            if let Some(val) = *val {
+                length = Some(length.unwrap_or(Au(0)) +
+                    val.to_computed_value(container_size));
+            }
+        }
+
         for val in &[self.ch, self.em, self.ex, self.rem] {
             if let Some(val) = *val {
                 length = Some(length.unwrap_or(Au(0)) + val.to_computed_value(
@@ -784,63 +1156,110 @@ impl CalcLengthOrPercentage {
             }
         }
 
+        let mut comparisons = Vec::new();
+        for comparison in &self.comparisons {
+            let arguments: Vec<_> = comparison.arguments.iter().map(|argument| {
+                argument.compute_from_viewport_container_and_font_size(viewport_size,
+                                                                       container_size,
+                                                                       font_size,
+                                                                       root_font_size)
+            }).collect();
+            // Only the arguments with percentages have to wait for layout.
+            if arguments.iter().all(|argument| argument.percentage.is_none() && argument.comparisons.is_none()) {
+                let lengths: Vec<Au> = arguments.iter().map(|argument| argument.length()).collect();
+                length = Some(length.unwrap_or(Au(0)) + comparison.function.apply(&lengths));
+            } else {
+                comparisons.push(computed::CalcComparison {
+                    function: comparison.function,
+                    arguments: arguments,
+                });
+            }
+        }
+
         computed::CalcLengthOrPercentage {
             length: length,
             percentage: self.percentage.map(|p| p.0),
+            comparisons: if comparisons.is_empty() {
+                None
+            } else {
+                Some(computed::CalcComparisons::new(comparisons))
+            },
         }
     }
 }
 
 impl HasViewportPercentage for CalcLengthOrPercentage {
     fn has_viewport_percentage(&self) -> bool {
+        // Container query lengths are relative to the viewport if there is no query container.
         self.vw.is_some() || self.vh.is_some() ||
-            self.vmin.is_some() || self.vmax.is_some()
+            self.vmin.is_some() || self.vmax.is_some() ||
+            self.cqw.is_some() || self.cqh.is_some() ||
+            self.comparisons.iter().any(|comparison| {
+                comparison.arguments.iter().any(|argument| argument.has_viewport_percentage())
+            })
     }
 }
 
-impl ToCss for CalcLengthOrPercentage {
+impl CalcLengthOrPercentage {
+    /// Serializes the terms of the sum, without wrapping them in `calc()`.
     #[allow(unused_assignments)]
-    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
-        macro_rules! count {
-            ( $( $val:ident ),* ) => {
+    fn sum_to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        macro_rules! serialize {
+            ( $first_value:ident; $( $val:ident ),* ) => {
                 {
-                    let mut count = 0;
                     $(
-                        if let Some(_) = self.$val {
-                            count += 1;
+                        if let Some(val) = self.$val {
+                            if !$first_value {
+                                try!(write!(dest, " + "));
+                            } else {
+                                $first_value = false;
+                            }
+                            try!(val.to_css(dest));
                         }
                     )*
-                    count
                  }
             };
         }
 
-        macro_rules! serialize {
+        let mut first_value = true;
+        serialize!(first_value; ch, cqh, cqw, em, ex, absolute, rem, vh, vmax, vmin, vw, percentage);
+        for comparison in &self.comparisons {
+            if !first_value {
+                try!(write!(dest, " + "));
+            } else {
+                first_value = false;
+            }
+            try!(comparison.to_css(dest));
+        }
+        Ok(())
+    }
+}
+
+impl ToCss for CalcLengthOrPercentage {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        macro_rules! count {
             ( $( $val:ident ),* ) => {
                 {
-                    let mut first_value = true;
+                    let mut count = 0;
                     $(
-                        if let Some(val) = self.$val {
-                            if !first_value {
-                                try!(write!(dest, " + "));
-                            } else {
-                                first_value = false;
-                            }
-                            try!(val.to_css(dest));
+                        if let Some(_) = self.$val {
+                            count += 1;
                         }
                     )*
+                    count
                  }
             };
         }
 
-        let count = count!(ch, em, ex, absolute, rem, vh, vmax, vmin, vw, percentage);
+        let count = count!(ch, cqh, cqw, em, ex, absolute, rem, vh, vmax, vmin, vw, percentage) +
+            self.comparisons.len();
         assert!(count > 0);
 
         if count > 1 {
            try!(write!(dest, "calc("));
         }
 
-        serialize!(ch, em, ex, absolute, rem, vh, vmax, vmin, vw, percentage);
+        try!(self.sum_to_css(dest));
 
         if count > 1 {
            try!(write!(dest, ")"));
@@ -859,7 +1278,7 @@ impl ToCss for Percentage {
     }
 }
 
-#[derive(Clone, PartialEq, Copy, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub enum LengthOrPercentage {
     Length(Length),
@@ -880,9 +1299,9 @@ impl HasViewportPercentage for LengthOrPercentage {
 impl ToCss for LengthOrPercentage {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         match *self {
-            LengthOrPercentage::Length(length) => length.to_css(dest),
+            LengthOrPercentage::Length(ref length) => length.to_css(dest),
             LengthOrPercentage::Percentage(percentage) => percentage.to_css(dest),
-            LengthOrPercentage::Calc(calc) => calc.to_css(dest),
+            LengthOrPercentage::Calc(ref calc) => calc.to_css(dest),
         }
     }
 }
@@ -901,8 +1320,10 @@ impl LengthOrPercentage {
                 Ok(LengthOrPercentage::Percentage(Percentage(value.unit_value))),
             Token::Number(ref value) if value.value == 0. =>
                 Ok(LengthOrPercentage::Length(Length::Absolute(Au(0)))),
-            Token::Function(ref name) if name.eq_ignore_ascii_case("calc") => {
-                let calc = try!(input.parse_nested_block(CalcLengthOrPercentage::parse_length_or_percentage));
+            Token::Function(ref name) if is_math_function(name) => {
+                let calc = try!(input.parse_nested_block(|i| {
+                    CalcLengthOrPercentage::parse_length_or_percentage(i, name)
+                }));
                 Ok(LengthOrPercentage::Calc(calc))
             },
             _ => Err(())
@@ -918,7 +1339,13 @@ impl LengthOrPercentage {
     }
 }
 
-#[derive(Clone, PartialEq, Copy, Debug)]
+impl From<Length> for LengthOrPercentage {
+    fn from(length: Length) -> LengthOrPercentage {
+        LengthOrPercentage::Length(length)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub enum LengthOrPercentageOrAuto {
     Length(Length),
@@ -940,10 +1367,10 @@ impl HasViewportPercentage for LengthOrPercentageOrAuto {
 impl ToCss for LengthOrPercentageOrAuto {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         match *self {
-            LengthOrPercentageOrAuto::Length(length) => length.to_css(dest),
+            LengthOrPercentageOrAuto::Length(ref length) => length.to_css(dest),
             LengthOrPercentageOrAuto::Percentage(percentage) => percentage.to_css(dest),
             LengthOrPercentageOrAuto::Auto => dest.write_str("auto"),
-            LengthOrPercentageOrAuto::Calc(calc) => calc.to_css(dest),
+            LengthOrPercentageOrAuto::Calc(ref calc) => calc.to_css(dest),
         }
     }
 }
@@ -961,8 +1388,10 @@ impl LengthOrPercentageOrAuto {
                 Ok(LengthOrPercentageOrAuto::Length(Length::Absolute(Au(0)))),
             Token::Ident(ref value) if value.eq_ignore_ascii_case("auto") =>
                 Ok(LengthOrPercentageOrAuto::Auto),
-            Token::Function(ref name) if name.eq_ignore_ascii_case("calc") => {
-                let calc = try!(input.parse_nested_block(CalcLengthOrPercentage::parse_length_or_percentage));
+            Token::Function(ref name) if is_math_function(name) => {
+                let calc = try!(input.parse_nested_block(|i| {
+                    CalcLengthOrPercentage::parse_length_or_percentage(i, name)
+                }));
                 Ok(LengthOrPercentageOrAuto::Calc(calc))
             },
             _ => Err(())
@@ -978,7 +1407,13 @@ impl LengthOrPercentageOrAuto {
     }
 }
 
-#[derive(Clone, PartialEq, Copy, Debug)]
+impl From<Length> for LengthOrPercentageOrAuto {
+    fn from(length: Length) -> LengthOrPercentageOrAuto {
+        LengthOrPercentageOrAuto::Length(length)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub enum LengthOrPercentageOrNone {
     Length(Length),
@@ -1018,8 +1453,10 @@ impl LengthOrPercentageOrNone {
                 Ok(LengthOrPercentageOrNone::Percentage(Percentage(value.unit_value))),
             Token::Number(ref value) if value.value == 0. =>
                 Ok(LengthOrPercentageOrNone::Length(Length::Absolute(Au(0)))),
-            Token::Function(ref name) if name.eq_ignore_ascii_case("calc") => {
-                let calc = try!(input.parse_nested_block(CalcLengthOrPercentage::parse_length_or_percentage));
+            Token::Function(ref name) if is_math_function(name) => {
+                let calc = try!(input.parse_nested_block(|i| {
+                    CalcLengthOrPercentage::parse_length_or_percentage(i, name)
+                }));
                 Ok(LengthOrPercentageOrNone::Calc(calc))
             },
             Token::Ident(ref value) if value.eq_ignore_ascii_case("none") =>
@@ -1037,7 +1474,13 @@ impl LengthOrPercentageOrNone {
     }
 }
 
-#[derive(Clone, PartialEq, Copy, Debug)]
+impl From<Length> for LengthOrPercentageOrNone {
+    fn from(length: Length) -> LengthOrPercentageOrNone {
+        LengthOrPercentageOrNone::Length(length)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub enum LengthOrNone {
     Length(Length),
@@ -1056,7 +1499,7 @@ impl HasViewportPercentage for LengthOrNone {
 impl ToCss for LengthOrNone {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         match *self {
-            LengthOrNone::Length(length) => length.to_css(dest),
+            LengthOrNone::Length(ref length) => length.to_css(dest),
             LengthOrNone::None => dest.write_str("none"),
         }
     }
@@ -1070,9 +1513,9 @@ impl LengthOrNone {
                 Length::parse_dimension(value.value, unit).map(LengthOrNone::Length),
             Token::Number(ref value) if value.value == 0. =>
                 Ok(LengthOrNone::Length(Length::Absolute(Au(0)))),
-            Token::Function(ref name) if name.eq_ignore_ascii_case("calc") =>
+            Token::Function(ref name) if is_math_function(name) =>
                 input.parse_nested_block(|input| {
-                    CalcLengthOrPercentage::parse_length(input, context)
+                    CalcLengthOrPercentage::parse_length(input, name, context)
                 }).map(LengthOrNone::Length),
             Token::Ident(ref value) if value.eq_ignore_ascii_case("none") =>
                 Ok(LengthOrNone::None),
@@ -1089,7 +1532,7 @@ impl LengthOrNone {
     }
 }
 
-#[derive(Clone, PartialEq, Copy, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub enum LengthOrPercentageOrAutoOrContent {
     Length(Length),
@@ -1102,7 +1545,7 @@ pub enum LengthOrPercentageOrAutoOrContent {
 impl HasViewportPercentage for LengthOrPercentageOrAutoOrContent {
     fn has_viewport_percentage(&self) -> bool {
         match *self {
-            LengthOrPercentageOrAutoOrContent::Length(length) => length.has_viewport_percentage(),
+            LengthOrPercentageOrAutoOrContent::Length(ref length) => length.has_viewport_percentage(),
             LengthOrPercentageOrAutoOrContent::Calc(ref calc) => calc.has_viewport_percentage(),
             _ => false
         }
@@ -1112,11 +1555,11 @@ impl HasViewportPercentage for LengthOrPercentageOrAutoOrContent {
 impl ToCss for LengthOrPercentageOrAutoOrContent {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         match *self {
-            LengthOrPercentageOrAutoOrContent::Length(len) => len.to_css(dest),
+            LengthOrPercentageOrAutoOrContent::Length(ref len) => len.to_css(dest),
             LengthOrPercentageOrAutoOrContent::Percentage(perc) => perc.to_css(dest),
             LengthOrPercentageOrAutoOrContent::Auto => dest.write_str("auto"),
             LengthOrPercentageOrAutoOrContent::Content => dest.write_str("content"),
-            LengthOrPercentageOrAutoOrContent::Calc(calc) => calc.to_css(dest),
+            LengthOrPercentageOrAutoOrContent::Calc(ref calc) => calc.to_css(dest),
         }
     }
 }
@@ -1135,8 +1578,10 @@ impl LengthOrPercentageOrAutoOrContent {
                 Ok(LengthOrPercentageOrAutoOrContent::Auto),
             Token::Ident(ref value) if value.eq_ignore_ascii_case("content") =>
                 Ok(LengthOrPercentageOrAutoOrContent::Content),
-            Token::Function(ref name) if name.eq_ignore_ascii_case("calc") => {
-                let calc = try!(input.parse_nested_block(CalcLengthOrPercentage::parse_length_or_percentage));
+            Token::Function(ref name) if is_math_function(name) => {
+                let calc = try!(input.parse_nested_block(|i| {
+                    CalcLengthOrPercentage::parse_length_or_percentage(i, name)
+                }));
                 Ok(LengthOrPercentageOrAutoOrContent::Calc(calc))
             },
             _ => Err(())
@@ -1144,7 +1589,144 @@ impl LengthOrPercentageOrAutoOrContent {
     }
 }
 
-#[derive(Clone, PartialEq, Copy, Debug)]
+// https://drafts.csswg.org/css-sizing-3/#sizing-values
+define_css_keyword_enum!(ExtremumLength:
+                         "min-content" => MinContent,
+                         "max-content" => MaxContent,
+                         "fit-content" => FitContent);
+
+impl NoViewportPercentage for ExtremumLength {}
+
+/// A value of `width` or `height`.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum Size {
+    LengthOrPercentageOrAuto(LengthOrPercentageOrAuto),
+    ExtremumLength(ExtremumLength),
+}
+
+impl HasViewportPercentage for Size {
+    fn has_viewport_percentage(&self) -> bool {
+        match *self {
+            Size::LengthOrPercentageOrAuto(ref length) => length.has_viewport_percentage(),
+            Size::ExtremumLength(_) => false,
+        }
+    }
+}
+
+impl ToCss for Size {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            Size::LengthOrPercentageOrAuto(ref length) => length.to_css(dest),
+            Size::ExtremumLength(ref keyword) => keyword.to_css(dest),
+        }
+    }
+}
+
+impl Size {
+    pub fn parse_non_negative(input: &mut Parser) -> Result<Size, ()> {
+        if let Ok(keyword) = input.try(ExtremumLength::parse) {
+            return Ok(Size::ExtremumLength(keyword))
+        }
+        LengthOrPercentageOrAuto::parse_non_negative(input).map(Size::LengthOrPercentageOrAuto)
+    }
+}
+
+impl From<Length> for Size {
+    fn from(length: Length) -> Size {
+        Size::LengthOrPercentageOrAuto(length.into())
+    }
+}
+
+/// A value of `min-width` or `min-height`.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum MinSize {
+    LengthOrPercentage(LengthOrPercentage),
+    ExtremumLength(ExtremumLength),
+    /// https://drafts.csswg.org/css-sizing-3/#valdef-width-auto
+    Auto,
+}
+
+impl HasViewportPercentage for MinSize {
+    fn has_viewport_percentage(&self) -> bool {
+        match *self {
+            MinSize::LengthOrPercentage(ref length) => length.has_viewport_percentage(),
+            MinSize::ExtremumLength(_) | MinSize::Auto => false,
+        }
+    }
+}
+
+impl ToCss for MinSize {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            MinSize::LengthOrPercentage(ref length) => length.to_css(dest),
+            MinSize::ExtremumLength(ref keyword) => keyword.to_css(dest),
+            MinSize::Auto => dest.write_str("auto"),
+        }
+    }
+}
+
+impl MinSize {
+    pub fn parse_non_negative(input: &mut Parser) -> Result<MinSize, ()> {
+        if input.try(|input| input.expect_ident_matching("auto")).is_ok() {
+            return Ok(MinSize::Auto)
+        }
+        if let Ok(keyword) = input.try(ExtremumLength::parse) {
+            return Ok(MinSize::ExtremumLength(keyword))
+        }
+        LengthOrPercentage::parse_non_negative(input).map(MinSize::LengthOrPercentage)
+    }
+}
+
+impl From<Length> for MinSize {
+    fn from(length: Length) -> MinSize {
+        MinSize::LengthOrPercentage(length.into())
+    }
+}
+
+/// A value of `max-width` or `max-height`.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum MaxSize {
+    LengthOrPercentageOrNone(LengthOrPercentageOrNone),
+    ExtremumLength(ExtremumLength),
+}
+
+impl HasViewportPercentage for MaxSize {
+    fn has_viewport_percentage(&self) -> bool {
+        match *self {
+            MaxSize::LengthOrPercentageOrNone(ref length) => length.has_viewport_percentage(),
+            MaxSize::ExtremumLength(_) => false,
+        }
+    }
+}
+
+impl ToCss for MaxSize {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            MaxSize::LengthOrPercentageOrNone(ref length) => length.to_css(dest),
+            MaxSize::ExtremumLength(ref keyword) => keyword.to_css(dest),
+        }
+    }
+}
+
+impl MaxSize {
+    pub fn parse_non_negative(input: &mut Parser) -> Result<MaxSize, ()> {
+        if let Ok(keyword) = input.try(ExtremumLength::parse) {
+            return Ok(MaxSize::ExtremumLength(keyword))
+        }
+        LengthOrPercentageOrNone::parse_non_negative(input).map(MaxSize::LengthOrPercentageOrNone)
+    }
+}
+
+impl From<Length> for MaxSize {
+    fn from(length: Length) -> MaxSize {
+        MaxSize::LengthOrPercentageOrNone(length.into())
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct BorderRadiusSize(pub Size2D<LengthOrPercentage>);
 
@@ -1153,7 +1735,7 @@ impl NoViewportPercentage for BorderRadiusSize {}
 impl BorderRadiusSize {
     pub fn zero() -> BorderRadiusSize {
         let zero = LengthOrPercentage::Length(Length::Absolute(Au(0)));
-            BorderRadiusSize(Size2D::new(zero, zero))
+            BorderRadiusSize(Size2D::new(zero.clone(), zero))
     }
 
     pub fn new(width: LengthOrPercentage, height: LengthOrPercentage) -> BorderRadiusSize {
@@ -1161,13 +1743,13 @@ impl BorderRadiusSize {
     }
 
     pub fn circle(radius: LengthOrPercentage) -> BorderRadiusSize {
-        BorderRadiusSize(Size2D::new(radius, radius))
+        BorderRadiusSize(Size2D::new(radius.clone(), radius))
     }
 
     #[inline]
     pub fn parse(input: &mut Parser) -> Result<BorderRadiusSize, ()> {
         let first = try!(LengthOrPercentage::parse_non_negative(input));
-        let second = input.try(LengthOrPercentage::parse_non_negative).unwrap_or(first);
+        let second = input.try(LengthOrPercentage::parse_non_negative).unwrap_or(first.clone());
         Ok(BorderRadiusSize(Size2D::new(first, second)))
     }
 }
@@ -1213,8 +1795,8 @@ impl Angle {
         match try!(input.next()) {
             Token::Dimension(ref value, ref unit) => Angle::parse_dimension(value.value, unit),
             Token::Number(ref value) if value.value == 0. => Ok(Angle(0.)),
-            Token::Function(ref name) if name.eq_ignore_ascii_case("calc") => {
-                input.parse_nested_block(CalcLengthOrPercentage::parse_angle)
+            Token::Function(ref name) if is_math_function(name) => {
+                input.parse_nested_block(|i| CalcLengthOrPercentage::parse_angle(i, name))
             },
             _ => Err(())
         }
@@ -1255,6 +1837,7 @@ impl UrlExtraData {
                 base: Some(ref base),
                 referrer: Some(ref referrer),
                 principal: Some(ref principal),
+                ..
             } => {
                 Some(UrlExtraData {
                     base: base.clone(),
@@ -1273,6 +1856,7 @@ impl UrlExtraData {
 pub enum Image {
     Url(Url, UrlExtraData),
     LinearGradient(LinearGradient),
+    ConicGradient(ConicGradient),
 }
 
 impl ToCss for Image {
@@ -1282,7 +1866,8 @@ impl ToCss for Image {
             Image::Url(ref url, ref _extra_data) => {
                 url.to_css(dest)
             }
-            Image::LinearGradient(ref gradient) => gradient.to_css(dest)
+            Image::LinearGradient(ref gradient) => gradient.to_css(dest),
+            Image::ConicGradient(ref gradient) => gradient.to_css(dest),
         }
     }
 }
@@ -1305,7 +1890,19 @@ impl Image {
             match_ignore_ascii_case! { try!(input.expect_function()),
                 "linear-gradient" => {
                     Ok(Image::LinearGradient(try!(
-                        input.parse_nested_block(LinearGradient::parse_function))))
+                        input.parse_nested_block(|input| LinearGradient::parse_function(input, false)))))
+                },
+                "repeating-linear-gradient" => {
+                    Ok(Image::LinearGradient(try!(
+                        input.parse_nested_block(|input| LinearGradient::parse_function(input, true)))))
+                },
+                "conic-gradient" => {
+                    Ok(Image::ConicGradient(try!(
+                        input.parse_nested_block(|input| ConicGradient::parse_function(input, false)))))
+                },
+                "repeating-conic-gradient" => {
+                    Ok(Image::ConicGradient(try!(
+                        input.parse_nested_block(|input| ConicGradient::parse_function(input, true)))))
                 },
                 _ => Err(())
             }
@@ -1320,12 +1917,18 @@ pub struct LinearGradient {
     /// The angle or corner of the gradient.
     pub angle_or_corner: AngleOrCorner,
 
-    /// The color stops.
-    pub stops: Vec<ColorStop>,
+    /// The color stops and interpolation hints.
+    pub stops: Vec<GradientItem>,
+
+    /// Whether the color stops are repeated along the gradient line.
+    pub repeating: bool,
 }
 
 impl ToCss for LinearGradient {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        if self.repeating {
+            try!(dest.write_str("repeating-"));
+        }
         try!(dest.write_str("linear-gradient("));
         try!(self.angle_or_corner.to_css(dest));
         for stop in &self.stops {
@@ -1337,6 +1940,42 @@ impl ToCss for LinearGradient {
     }
 }
 
+/// Specified values for a CSS conic gradient.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub struct ConicGradient {
+    /// The angle the gradient starts at, clockwise from the top.
+    pub angle: Angle,
+
+    /// The center of the gradient.
+    pub position: Position,
+
+    /// The color stops and interpolation hints. Their angles are stored as percentages of a full
+    /// turn.
+    pub stops: Vec<GradientItem>,
+
+    /// Whether the color stops are repeated around the center.
+    pub repeating: bool,
+}
+
+impl ToCss for ConicGradient {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        if self.repeating {
+            try!(dest.write_str("repeating-"));
+        }
+        try!(dest.write_str("conic-gradient(from "));
+        try!(self.angle.to_css(dest));
+        try!(dest.write_str(" at "));
+        try!(self.position.to_css(dest));
+        for stop in &self.stops {
+            try!(dest.write_str(", "));
+            try!(stop.to_css(dest));
+        }
+        try!(dest.write_str(")"));
+        Ok(())
+    }
+}
+
 /// Specified values for an angle or a corner in a linear gradient.
 #[derive(Clone, PartialEq, Copy, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
@@ -1360,7 +1999,7 @@ impl ToCss for AngleOrCorner {
     }
 }
 
-/// Specified values for one color stop in a linear gradient.
+/// Specified values for one color stop in a gradient.
 #[derive(Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct ColorStop {
@@ -1375,7 +2014,7 @@ pub struct ColorStop {
 impl ToCss for ColorStop {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         try!(self.color.to_css(dest));
-        if let Some(position) = self.position {
+        if let Some(ref position) = self.position {
             try!(dest.write_str(" "));
             try!(position.to_css(dest));
         }
@@ -1383,19 +2022,82 @@ impl ToCss for ColorStop {
     }
 }
 
+/// Specified values for an item in the list of color stops of a gradient.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "servo", derive(HeapSizeOf))]
+pub enum GradientItem {
+    ColorStop(ColorStop),
+    /// The position between two color stops where their colors are mixed evenly.
+    InterpolationHint(LengthOrPercentage),
+}
+
+impl ToCss for GradientItem {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            GradientItem::ColorStop(ref stop) => stop.to_css(dest),
+            GradientItem::InterpolationHint(ref position) => position.to_css(dest),
+        }
+    }
+}
+
 define_css_keyword_enum!(HorizontalDirection: "left" => Left, "right" => Right);
 define_css_keyword_enum!(VerticalDirection: "top" => Top, "bottom" => Bottom);
 
-fn parse_one_color_stop(input: &mut Parser) -> Result<ColorStop, ()> {
-    Ok(ColorStop {
-        color: try!(CSSColor::parse(input)),
-        position: input.try(LengthOrPercentage::parse).ok(),
-    })
+/// Parses the position of a color stop or an interpolation hint. The position of an angular one is
+/// an angle or a percentage, and angles are turned into percentages of a full turn.
+fn parse_stop_position(input: &mut Parser, angular: bool) -> Result<LengthOrPercentage, ()> {
+    if !angular {
+        return LengthOrPercentage::parse(input)
+    }
+    if let Ok(angle) = input.try(Angle::parse) {
+        return Ok(LengthOrPercentage::Percentage(Percentage(angle.radians() / (2. * PI))))
+    }
+    match try!(input.next()) {
+        Token::Percentage(ref value) => Ok(LengthOrPercentage::Percentage(Percentage(value.unit_value))),
+        _ => Err(())
+    }
+}
+
+/// Parses the color stops and interpolation hints of a gradient. A color stop with two positions
+/// is two color stops of the same color.
+fn parse_gradient_items(input: &mut Parser, angular: bool) -> Result<Vec<GradientItem>, ()> {
+    let items = try!(input.parse_comma_separated(|input| {
+        if let Ok(position) = input.try(|input| parse_stop_position(input, angular)) {
+            return Ok(vec![GradientItem::InterpolationHint(position)])
+        }
+        let color = try!(CSSColor::parse(input));
+        let position = input.try(|input| parse_stop_position(input, angular)).ok();
+        let mut stops = vec![GradientItem::ColorStop(ColorStop {
+            color: color.clone(),
+            position: position.clone(),
+        })];
+        if position.is_some() {
+            if let Ok(second_position) = input.try(|input| parse_stop_position(input, angular)) {
+                stops.push(GradientItem::ColorStop(ColorStop {
+                    color: color,
+                    position: Some(second_position),
+                }));
+            }
+        }
+        Ok(stops)
+    }));
+    let items: Vec<GradientItem> = items.into_iter().flat_map(|items| items).collect();
+
+    // Interpolation hints go between two color stops.
+    let is_hint = |item: &GradientItem| match *item {
+        GradientItem::InterpolationHint(_) => true,
+        GradientItem::ColorStop(_) => false,
+    };
+    if items.len() < 2 || is_hint(&items[0]) || is_hint(&items[items.len() - 1]) ||
+       items.windows(2).any(|pair| is_hint(&pair[0]) && is_hint(&pair[1])) {
+        return Err(())
+    }
+    Ok(items)
 }
 
 impl LinearGradient {
     /// Parses a linear gradient from the given arguments.
-    pub fn parse_function(input: &mut Parser) -> Result<LinearGradient, ()> {
+    pub fn parse_function(input: &mut Parser, repeating: bool) -> Result<LinearGradient, ()> {
         let angle_or_corner = if input.try(|input| input.expect_ident_matching("to")).is_ok() {
             let (horizontal, vertical) =
             if let Ok(value) = input.try(HorizontalDirection::parse) {
@@ -1429,14 +2131,40 @@ impl LinearGradient {
         } else {
             AngleOrCorner::Angle(Angle(PI))
         };
-        // Parse the color stops.
-        let stops = try!(input.parse_comma_separated(parse_one_color_stop));
-        if stops.len() < 2 {
-            return Err(())
-        }
         Ok(LinearGradient {
             angle_or_corner: angle_or_corner,
-            stops: stops,
+            stops: try!(parse_gradient_items(input, false)),
+            repeating: repeating,
+        })
+    }
+}
+
+impl ConicGradient {
+    /// Parses a conic gradient from the given arguments.
+    pub fn parse_function(input: &mut Parser, repeating: bool) -> Result<ConicGradient, ()> {
+        let angle = if input.try(|input| input.expect_ident_matching("from")).is_ok() {
+            Some(try!(Angle::parse(input)))
+        } else {
+            None
+        };
+        let position = if input.try(|input| input.expect_ident_matching("at")).is_ok() {
+            Some(try!(Position::parse(input)))
+        } else {
+            None
+        };
+        if angle.is_some() || position.is_some() {
+            try!(input.expect_comma());
+        }
+        Ok(ConicGradient {
+            angle: angle.unwrap_or(Angle(0.)),
+            position: position.unwrap_or(Position {
+                horiz_keyword: Some(Keyword::Center),
+                horiz_position: None,
+                vert_keyword: Some(Keyword::Center),
+                vert_position: None,
+            }),
+            stops: try!(parse_gradient_items(input, true)),
+            repeating: repeating,
         })
     }
 }
@@ -1517,8 +2245,8 @@ impl Time {
             Ok(Token::Dimension(ref value, ref unit)) => {
                 Time::parse_dimension(value.value, &unit)
             }
-            Ok(Token::Function(ref name)) if name.eq_ignore_ascii_case("calc") => {
-                input.parse_nested_block(CalcLengthOrPercentage::parse_time)
+            Ok(Token::Function(ref name)) if is_math_function(name) => {
+                input.parse_nested_block(|i| CalcLengthOrPercentage::parse_time(i, name))
             }
             _ => Err(())
         }
@@ -15,7 +15,7 @@ use values::computed::{LengthOrPercentage as ComputedLengthOrPercentage, ToCompu
 use values::computed::position as computed_position;
 use values::specified::{LengthOrPercentage, Percentage};
 
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct Position {
     pub horiz_keyword: Option<Keyword>,
@@ -32,7 +32,7 @@ impl ToCss for Position {
             try!(dest.write_str(" "));
             space_at_last = true;
         };
-        if let Some(horiz_pos) = self.horiz_position {
+        if let Some(ref horiz_pos) = self.horiz_position {
             try!(horiz_pos.to_css(dest));
             try!(dest.write_str(" "));
             space_at_last = true;
@@ -41,7 +41,7 @@ impl ToCss for Position {
             try!(vert_key.to_css(dest));
             space_at_last = false;
         };
-        if let Some(vert_pos) = self.vert_position {
+        if let Some(ref vert_pos) = self.vert_position {
             if space_at_last == false {
                 try!(dest.write_str(" "));
             }
@@ -53,13 +53,13 @@ impl ToCss for Position {
 
 impl HasViewportPercentage for Position {
     fn has_viewport_percentage(&self) -> bool {
-        let horiz_viewport = if let Some(horiz_pos) = self.horiz_position {
+        let horiz_viewport = if let Some(ref horiz_pos) = self.horiz_position {
             horiz_pos.has_viewport_percentage()
         } else {
             false
         };
 
-        let vert_viewport = if let Some(vert_pos) = self.vert_position {
+        let vert_viewport = if let Some(ref vert_pos) = self.vert_position {
             vert_pos.has_viewport_percentage()
         } else {
             false
@@ -79,7 +79,7 @@ pub enum Keyword {
 }
 
 // http://dev.w3.org/csswg/css2/colors.html#propdef-background-position
-#[derive(Clone, PartialEq, Copy)]
+#[derive(Clone, PartialEq)]
 pub enum PositionComponent {
     Length(LengthOrPercentage),
     Keyword(Keyword),
@@ -90,23 +90,23 @@ impl Position {
                first_keyword: Option<PositionComponent>, second_keyword: Option<PositionComponent>)
             -> Result<Position, ()> {
         // Unwrap for checking if values are at right place.
-        let first_key = first_keyword.unwrap_or(PositionComponent::Keyword(Keyword::Left));
-        let second_key = second_keyword.unwrap_or(PositionComponent::Keyword(Keyword::Top));
+        let first_key = first_keyword.clone().unwrap_or(PositionComponent::Keyword(Keyword::Left));
+        let second_key = second_keyword.clone().unwrap_or(PositionComponent::Keyword(Keyword::Top));
 
         // Check if position specified after center keyword.
-        if let PositionCategory::OtherKeyword = category(first_key) {
+        if let PositionCategory::OtherKeyword = category(&first_key) {
             if let Some(_) = first_position {
                 return Err(());
             };
         };
-        if let PositionCategory::OtherKeyword = category(second_key) {
+        if let PositionCategory::OtherKeyword = category(&second_key) {
             if let Some(_) = second_position {
                 return Err(());
             };
         };
 
         // Check first and second keywords for both 2 and 4 value positions.
-        let (horiz_keyword, vert_keyword) = match (category(first_key), category(second_key)) {
+        let (horiz_keyword, vert_keyword) = match (category(&first_key), category(&second_key)) {
             // Don't allow two vertical keywords or two horizontal keywords.
             // also don't allow length/percentage values in the wrong position
             (PositionCategory::HorizontalKeyword, PositionCategory::HorizontalKeyword) |
@@ -179,12 +179,12 @@ impl Position {
                 Position::new(Some(second), Some(fourth), Some(first), Some(third))
             } else {
                 // Handle 3 value background position there are several options:
-                if let PositionCategory::LengthOrPercentage = category(first) {
+                if let PositionCategory::LengthOrPercentage = category(&first) {
                     // "length keyword length"
                     Position::new(Some(first), Some(third), None, Some(second))
                 } else {
-                    if let PositionCategory::LengthOrPercentage = category(second) {
-                        if let PositionCategory::LengthOrPercentage = category(third) {
+                    if let PositionCategory::LengthOrPercentage = category(&second) {
+                        if let PositionCategory::LengthOrPercentage = category(&third) {
                             // "keyword length length"
                             Position::new(Some(second), Some(third), Some(first), None)
                         } else {
@@ -199,14 +199,14 @@ impl Position {
             }
         } else {
             // Handle 2 value background position.
-            if let PositionCategory::LengthOrPercentage = category(first) {
-                if let PositionCategory::LengthOrPercentage = category(second) {
+            if let PositionCategory::LengthOrPercentage = category(&first) {
+                if let PositionCategory::LengthOrPercentage = category(&second) {
                     Position::new(Some(first), Some(second), None, None)
                 } else {
                     Position::new(Some(first), None, None, Some(second))
                 }
             } else {
-                if let PositionCategory::LengthOrPercentage = category(second) {
+                if let PositionCategory::LengthOrPercentage = category(&second) {
                     Position::new(None, Some(second), Some(first), None)
                 } else {
                     Position::new(None, None, Some(first), Some(second))
@@ -247,8 +247,8 @@ enum PositionCategory {
     LengthOrPercentage,
 }
 
-fn category(p: PositionComponent) -> PositionCategory {
-    if let PositionComponent::Keyword(keyword) = p {
+fn category(p: &PositionComponent) -> PositionCategory {
+    if let PositionComponent::Keyword(keyword) = *p {
         match keyword {
             Keyword::Left |
             Keyword::Right =>
@@ -275,15 +275,16 @@ impl ToComputedValue for Position {
         // Construct horizontal computed LengthOrPercentage
         let horizontal = match horiz_keyword {
             Keyword::Right => {
-                if let Some(x) = self.horiz_position {
-                    let (length, percentage) = match x {
+                if let Some(ref x) = self.horiz_position {
+                    let (length, percentage) = match *x {
                         LengthOrPercentage::Percentage(Percentage(y)) => (None, Some(1.0 - y)),
-                        LengthOrPercentage::Length(y) => (Some(-y.to_computed_value(context)), Some(1.0)),
+                        LengthOrPercentage::Length(ref y) => (Some(-y.to_computed_value(context)), Some(1.0)),
                         _ => (None, None),
                     };
                     ComputedLengthOrPercentage::Calc(CalcLengthOrPercentage {
                         length: length,
-                        percentage: percentage
+                        percentage: percentage,
+                        comparisons: None,
                     })
                 } else {
                     ComputedLengthOrPercentage::Percentage(1.0)
@@ -293,7 +294,7 @@ impl ToComputedValue for Position {
                 horiz_keyword.to_length_or_percentage().to_computed_value(context)
             },
              _ => {
-                let horiz = self.horiz_position.unwrap_or(LengthOrPercentage::Percentage(Percentage(0.0)));
+                let horiz = self.horiz_position.clone().unwrap_or(LengthOrPercentage::Percentage(Percentage(0.0)));
                 horiz.to_computed_value(context)
             },
         };
@@ -301,15 +302,16 @@ impl ToComputedValue for Position {
         // Construct vertical computed LengthOrPercentage
         let vertical = match vert_keyword {
             Keyword::Bottom => {
-                if let Some(x) = self.vert_position {
-                    let (length, percentage) = match x {
+                if let Some(ref x) = self.vert_position {
+                    let (length, percentage) = match *x {
                         LengthOrPercentage::Percentage(Percentage(y)) => (None, Some(1.0 - y)),
-                        LengthOrPercentage::Length(y) => (Some(-y.to_computed_value(context)), Some(1.0)),
+                        LengthOrPercentage::Length(ref y) => (Some(-y.to_computed_value(context)), Some(1.0)),
                         _ => (None, None),
                     };
                     ComputedLengthOrPercentage::Calc(CalcLengthOrPercentage {
                         length: length,
-                        percentage: percentage
+                        percentage: percentage,
+                        comparisons: None,
                     })
                 } else {
                     ComputedLengthOrPercentage::Percentage(1.0)
@@ -319,7 +321,7 @@ impl ToComputedValue for Position {
                 vert_keyword.to_length_or_percentage().to_computed_value(context)
             },
              _ => {
-                let vert = self.vert_position.unwrap_or(LengthOrPercentage::Percentage(Percentage(0.0)));
+                let vert = self.vert_position.clone().unwrap_or(LengthOrPercentage::Percentage(Percentage(0.0)));
                 vert.to_computed_value(context)
             },
         };
@@ -344,7 +346,7 @@ impl ToComputedValue for Position {
 impl HasViewportPercentage for PositionComponent {
     fn has_viewport_percentage(&self) -> bool {
         match *self {
-            PositionComponent::Length(length) => length.has_viewport_percentage(),
+            PositionComponent::Length(ref length) => length.has_viewport_percentage(),
             _ => false
         }
     }
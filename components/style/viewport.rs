@@ -54,7 +54,7 @@ macro_rules! declare_viewport_descriptor_inner {
         [ ]
         $number_of_variants: expr
     ) => {
-        #[derive(Copy, Clone, Debug, PartialEq)]
+        #[derive(Clone, Debug, PartialEq)]
         #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
         pub enum ViewportDescriptor {
             $(
@@ -99,7 +99,7 @@ trait FromMeta: Sized {
 // See:
 // * http://dev.w3.org/csswg/css-device-adapt/#min-max-width-desc
 // * http://dev.w3.org/csswg/css-device-adapt/#extend-to-zoom
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub enum ViewportLength {
     Specified(LengthOrPercentageOrAuto),
@@ -111,7 +111,7 @@ impl ToCss for ViewportLength {
         where W: fmt::Write
     {
         match *self {
-            ViewportLength::Specified(length) => length.to_css(dest),
+            ViewportLength::Specified(ref length) => length.to_css(dest),
             ViewportLength::ExtendToZoom => write!(dest, "extend-to-zoom"),
         }
     }
@@ -188,7 +188,7 @@ struct ViewportRuleParser<'a, 'b: 'a> {
     context: &'a ParserContext<'b>
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "servo", derive(HeapSizeOf))]
 pub struct ViewportDescriptorDeclaration {
     pub origin: Origin,
@@ -212,7 +212,7 @@ impl ViewportDescriptorDeclaration {
 fn parse_shorthand(input: &mut Parser) -> Result<[ViewportLength; 2], ()> {
     let min = try!(ViewportLength::parse(input));
     match input.try(|input| ViewportLength::parse(input)) {
-        Err(()) => Ok([min, min]),
+        Err(()) => Ok([min.clone(), min]),
         Ok(max) => Ok([min, max])
     }
 }
@@ -247,8 +247,8 @@ impl<'a, 'b> DeclarationParser for ViewportRuleParser<'a, 'b> {
                 let shorthand = try!(parse_shorthand(input));
                 let important = input.try(parse_important).is_ok();
 
-                Ok(vec![declaration!($min(value: shorthand[0], important: important)),
-                        declaration!($max(value: shorthand[1], important: important))])
+                Ok(vec![declaration!($min(value: shorthand[0].clone(), important: important)),
+                        declaration!($max(value: shorthand[1].clone(), important: important))])
             }}
         }
 
@@ -542,7 +542,7 @@ fn cascade<'a, I>(iter: I) -> Vec<ViewportDescriptorDeclaration>
 
     // sort the descriptors by order of appearance
     declarations.sort_by_key(|entry| entry.map(|(index, _)| index));
-    declarations.into_iter().filter_map(|entry| entry.map(|(_, decl)| *decl)).collect::<Vec<_>>()
+    declarations.into_iter().filter_map(|entry| entry.map(|(_, decl)| decl.clone())).collect::<Vec<_>>()
 }
 
 impl<'a, I> ViewportDescriptorDeclarationCascade for I
@@ -587,11 +587,11 @@ impl MaybeNew for ViewportConstraints {
         // collapse the list of declarations into descriptor values
         for declaration in &rule.declarations {
             match declaration.descriptor {
-                ViewportDescriptor::MinWidth(value) => min_width = Some(value),
-                ViewportDescriptor::MaxWidth(value) => max_width = Some(value),
+                ViewportDescriptor::MinWidth(ref value) => min_width = Some(value.clone()),
+                ViewportDescriptor::MaxWidth(ref value) => max_width = Some(value.clone()),
 
-                ViewportDescriptor::MinHeight(value) => min_height = Some(value),
-                ViewportDescriptor::MaxHeight(value) => max_height = Some(value),
+                ViewportDescriptor::MinHeight(ref value) => min_height = Some(value.clone()),
+                ViewportDescriptor::MaxHeight(ref value) => max_height = Some(value.clone()),
 
                 ViewportDescriptor::Zoom(value) => initial_zoom = value.to_f32(),
                 ViewportDescriptor::MinZoom(value) => min_zoom = value.to_f32(),
@@ -646,6 +646,7 @@ impl MaybeNew for ViewportConstraints {
         let context = Context {
             is_root_element: false,
             viewport_size: initial_viewport,
+            container_size: None,
             inherited_style: ComputedValues::initial_values(),
             style: ComputedValues::initial_values().clone(),
         };
@@ -674,7 +675,7 @@ impl MaybeNew for ViewportConstraints {
                             LengthOrPercentageOrAuto::Auto => None,
                             LengthOrPercentageOrAuto::Calc(calc) => {
                                 let calc = calc.to_computed_value(&context);
-                                Some(initial_viewport.$dimension.scale_by(calc.percentage()) + calc.length())
+                                Some(calc.to_used_value(initial_viewport.$dimension))
                             }
                         },
                         ViewportLength::ExtendToZoom => {
@@ -0,0 +1,89 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The USB thread, which enumerates and opens the devices that WebUSB exposes and runs their
+//! transfers.
+//!
+//! Devices are reached through libusb, which is only linked with the `libusb` feature. Without
+//! it, no device is ever connected, so pages can't be allowed to use any.
+
+#![deny(unsafe_code)]
+
+extern crate ipc_channel;
+#[cfg(feature = "libusb")]
+extern crate libusb;
+#[cfg(feature = "libusb")]
+extern crate rand;
+extern crate usb_traits;
+extern crate util;
+
+#[cfg(feature = "libusb")]
+mod manager;
+
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use std::borrow::ToOwned;
+use usb_traits::{UsbDeviceFilter, UsbDeviceMsg, UsbMethodMsg};
+#[cfg(not(feature = "libusb"))]
+use usb_traits::UsbError;
+use util::thread::spawn_named;
+
+pub trait UsbThreadFactory {
+    fn new() -> Self;
+}
+
+impl UsbThreadFactory for IpcSender<UsbMethodMsg> {
+    fn new() -> IpcSender<UsbMethodMsg> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        spawn_named("UsbThread".to_owned(), move || run(receiver));
+        sender
+    }
+}
+
+#[cfg(feature = "libusb")]
+fn run(receiver: IpcReceiver<UsbMethodMsg>) {
+    // Open devices borrow the context, so it lives on this thread's stack.
+    let context = libusb::Context::new().ok();
+    manager::UsbManager::new(receiver, context.as_ref()).start();
+}
+
+#[cfg(not(feature = "libusb"))]
+fn run(receiver: IpcReceiver<UsbMethodMsg>) {
+    // There are no devices, so none can be found by its id.
+    while let Ok(msg) = receiver.recv() {
+        match msg {
+            UsbMethodMsg::GetDevices(_, sender) | UsbMethodMsg::MatchingDevices(_, sender) => {
+                let _ = sender.send(Ok(vec!()));
+            },
+            UsbMethodMsg::AllowDevice(_, _, sender) => {
+                let _ = sender.send(Err(UsbError::NotFound));
+            },
+            UsbMethodMsg::Open(_, sender) |
+            UsbMethodMsg::Close(_, sender) |
+            UsbMethodMsg::SelectConfiguration(_, _, sender) |
+            UsbMethodMsg::ClaimInterface(_, _, sender) |
+            UsbMethodMsg::ReleaseInterface(_, _, sender) => {
+                let _ = sender.send(Err(UsbError::NotFound));
+            },
+            UsbMethodMsg::ControlTransferIn(_, _, _, sender) | UsbMethodMsg::TransferIn(_, _, _, sender) => {
+                let _ = sender.send(Err(UsbError::NotFound));
+            },
+            UsbMethodMsg::ControlTransferOut(_, _, _, sender) | UsbMethodMsg::TransferOut(_, _, _, sender) => {
+                let _ = sender.send(Err(UsbError::NotFound));
+            },
+            UsbMethodMsg::Exit => {
+                break
+            },
+        }
+    }
+}
+
+// https://wicg.github.io/webusb/#device-matches-a-filter
+pub fn matches_filter(device: &UsbDeviceMsg, filter: &UsbDeviceFilter) -> bool {
+    filter.vendor_id.map_or(true, |id| id == device.vendor_id) &&
+    filter.product_id.map_or(true, |id| id == device.product_id) &&
+    filter.class_code.map_or(true, |code| code == device.device_class) &&
+    filter.subclass_code.map_or(true, |code| code == device.device_subclass) &&
+    filter.protocol_code.map_or(true, |code| code == device.device_protocol) &&
+    filter.serial_number.as_ref().map_or(true, |serial| Some(serial) == device.serial_number.as_ref())
+}
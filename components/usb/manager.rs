@@ -0,0 +1,395 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The USB thread on top of libusb.
+
+use ipc_channel::ipc::IpcReceiver;
+use libusb::{self, Context, Device, DeviceDescriptor, DeviceHandle, Direction, TransferType};
+use matches_filter;
+use rand::{self, Rng};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use usb_traits::{UsbControlSetup, UsbDeviceFilter, UsbDeviceMsg, UsbError, UsbInTransferMsg, UsbMethodMsg};
+use usb_traits::{UsbOutTransferMsg, UsbRecipient, UsbRequestType, UsbResult, UsbTransferStatus};
+
+// A transfer that the device doesn't complete in time fails with a NetworkError.
+const TRANSFER_TIMEOUT_MS: u64 = 5000;
+// Reading the string descriptors of a device shouldn't hold up enumeration for long.
+const DESCRIPTOR_TIMEOUT_MS: u64 = 100;
+
+fn version_bcd(version: libusb::Version) -> u16 {
+    ((version.major() as u16) << 8) | ((version.minor() as u16) << 4) | version.sub_minor() as u16
+}
+
+fn usb_error(error: libusb::Error) -> UsbError {
+    match error {
+        libusb::Error::NoDevice | libusb::Error::NotFound => UsbError::NotFound,
+        libusb::Error::Access => UsbError::Security,
+        libusb::Error::Busy => UsbError::InvalidState,
+        libusb::Error::NotSupported => UsbError::NotSupported,
+        _ => UsbError::Network,
+    }
+}
+
+// A stalled endpoint and a device sending more than was asked for are reported as
+// the status of a transfer rather than as errors.
+// https://wicg.github.io/webusb/#enumdef-usbtransferstatus
+fn in_transfer_result(result: libusb::Result<usize>, mut data: Vec<u8>) -> UsbResult<UsbInTransferMsg> {
+    match result {
+        Ok(length) => {
+            data.truncate(length);
+            Ok(UsbInTransferMsg { status: UsbTransferStatus::Ok, data: data })
+        },
+        Err(libusb::Error::Pipe) => Ok(UsbInTransferMsg { status: UsbTransferStatus::Stall, data: vec!() }),
+        Err(libusb::Error::Overflow) => Ok(UsbInTransferMsg { status: UsbTransferStatus::Babble, data: data }),
+        Err(error) => Err(usb_error(error)),
+    }
+}
+
+fn out_transfer_result(result: libusb::Result<usize>) -> UsbResult<UsbOutTransferMsg> {
+    match result {
+        Ok(length) => Ok(UsbOutTransferMsg { status: UsbTransferStatus::Ok, bytes_written: length as u32 }),
+        Err(libusb::Error::Pipe) => Ok(UsbOutTransferMsg { status: UsbTransferStatus::Stall, bytes_written: 0 }),
+        Err(error) => Err(usb_error(error)),
+    }
+}
+
+fn request_type(direction: Direction, setup: &UsbControlSetup) -> u8 {
+    let request_type = match setup.request_type {
+        UsbRequestType::Standard => libusb::RequestType::Standard,
+        UsbRequestType::Class => libusb::RequestType::Class,
+        UsbRequestType::Vendor => libusb::RequestType::Vendor,
+    };
+    let recipient = match setup.recipient {
+        UsbRecipient::Device => libusb::Recipient::Device,
+        UsbRecipient::Interface => libusb::Recipient::Interface,
+        UsbRecipient::Endpoint => libusb::Recipient::Endpoint,
+        UsbRecipient::Other => libusb::Recipient::Other,
+    };
+    libusb::request_type(direction, request_type, recipient)
+}
+
+struct OpenDevice<'a> {
+    handle: DeviceHandle<'a>,
+    claimed_interfaces: HashSet<u8>,
+}
+
+pub struct UsbManager<'a> {
+    receiver: IpcReceiver<UsbMethodMsg>,
+    context: Option<&'a Context>,
+    // Devices are identified by their bus number and address while they are connected
+    address_to_id: HashMap<(u8, u8), String>,
+    // The devices each origin was allowed to use
+    allowed_devices: HashMap<String, HashSet<String>>,
+    open_devices: HashMap<String, OpenDevice<'a>>,
+}
+
+impl<'a> UsbManager<'a> {
+    pub fn new(receiver: IpcReceiver<UsbMethodMsg>, context: Option<&'a Context>) -> UsbManager<'a> {
+        UsbManager {
+            receiver: receiver,
+            context: context,
+            address_to_id: HashMap::new(),
+            allowed_devices: HashMap::new(),
+            open_devices: HashMap::new(),
+        }
+    }
+
+    pub fn start(&mut self) {
+        while let Ok(msg) = self.receiver.recv() {
+            match msg {
+                UsbMethodMsg::GetDevices(origin, sender) => {
+                    let _ = sender.send(self.get_devices(&origin));
+                },
+                UsbMethodMsg::MatchingDevices(filters, sender) => {
+                    let _ = sender.send(self.matching_devices(&filters));
+                },
+                UsbMethodMsg::AllowDevice(origin, device_id, sender) => {
+                    let _ = sender.send(self.allow_device(origin, device_id));
+                },
+                UsbMethodMsg::Open(device_id, sender) => {
+                    let _ = sender.send(self.open(device_id));
+                },
+                UsbMethodMsg::Close(device_id, sender) => {
+                    let _ = sender.send(self.close(&device_id));
+                },
+                UsbMethodMsg::SelectConfiguration(device_id, configuration_value, sender) => {
+                    let _ = sender.send(self.select_configuration(&device_id, configuration_value));
+                },
+                UsbMethodMsg::ClaimInterface(device_id, interface_number, sender) => {
+                    let _ = sender.send(self.claim_interface(&device_id, interface_number));
+                },
+                UsbMethodMsg::ReleaseInterface(device_id, interface_number, sender) => {
+                    let _ = sender.send(self.release_interface(&device_id, interface_number));
+                },
+                UsbMethodMsg::ControlTransferIn(device_id, setup, length, sender) => {
+                    let _ = sender.send(self.control_transfer_in(&device_id, setup, length));
+                },
+                UsbMethodMsg::ControlTransferOut(device_id, setup, data, sender) => {
+                    let _ = sender.send(self.control_transfer_out(&device_id, setup, data));
+                },
+                UsbMethodMsg::TransferIn(device_id, endpoint_number, length, sender) => {
+                    let _ = sender.send(self.transfer_in(&device_id, endpoint_number, length));
+                },
+                UsbMethodMsg::TransferOut(device_id, endpoint_number, data, sender) => {
+                    let _ = sender.send(self.transfer_out(&device_id, endpoint_number, data));
+                },
+                UsbMethodMsg::Exit => {
+                    break
+                },
+            }
+        }
+    }
+
+    // Device
+
+    fn generate_device_id(&mut self) -> String {
+        let mut device_id;
+        let mut rng = rand::thread_rng();
+        loop {
+            device_id = rng.gen::<u32>().to_string();
+            if !self.address_to_id.values().any(|id| *id == device_id) {
+                break;
+            }
+        }
+        device_id
+    }
+
+    fn device_id(&mut self, device: &Device<'a>) -> String {
+        let address = (device.bus_number(), device.address());
+        if let Some(id) = self.address_to_id.get(&address) {
+            return id.clone();
+        }
+        let id = self.generate_device_id();
+        self.address_to_id.insert(address, id.clone());
+        id
+    }
+
+    /// The connected devices, with their ids and descriptors.
+    fn get_and_cache_devices(&mut self) -> UsbResult<Vec<(String, Device<'a>, DeviceDescriptor)>> {
+        let context = match self.context {
+            Some(context) => context,
+            None => return Err(UsbError::NotSupported),
+        };
+        let list = try!(context.devices().map_err(usb_error));
+        let mut devices = vec!();
+        for device in list.iter() {
+            if let Ok(descriptor) = device.device_descriptor() {
+                let id = self.device_id(&device);
+                devices.push((id, device, descriptor));
+            }
+        }
+        Ok(devices)
+    }
+
+    fn get_device(&mut self, device_id: &str) -> UsbResult<(Device<'a>, DeviceDescriptor)> {
+        let devices = try!(self.get_and_cache_devices());
+        devices.into_iter()
+               .find(|&(ref id, _, _)| id == device_id)
+               .map(|(_, device, descriptor)| (device, descriptor))
+               .ok_or(UsbError::NotFound)
+    }
+
+    fn device_msg(&self, id: String, device: &Device<'a>, descriptor: &DeviceDescriptor) -> UsbDeviceMsg {
+        // The strings can only be read from an open device.
+        let timeout = Duration::from_millis(DESCRIPTOR_TIMEOUT_MS);
+        let (manufacturer_name, product_name, serial_number, configuration_value) = match device.open() {
+            Ok(handle) => {
+                let language = handle.read_languages(timeout).ok().and_then(|languages| languages.first().cloned());
+                match language {
+                    Some(language) => (handle.read_manufacturer_string(language, descriptor, timeout).ok(),
+                                       handle.read_product_string(language, descriptor, timeout).ok(),
+                                       handle.read_serial_number_string(language, descriptor, timeout).ok(),
+                                       handle.active_configuration().ok()),
+                    None => (None, None, None, handle.active_configuration().ok()),
+                }
+            },
+            Err(_) => (None, None, None, None),
+        };
+        UsbDeviceMsg {
+            id: id,
+            usb_version: version_bcd(descriptor.usb_version()),
+            device_class: descriptor.class_code(),
+            device_subclass: descriptor.sub_class_code(),
+            device_protocol: descriptor.protocol_code(),
+            vendor_id: descriptor.vendor_id(),
+            product_id: descriptor.product_id(),
+            device_version: version_bcd(descriptor.device_version()),
+            manufacturer_name: manufacturer_name,
+            product_name: product_name,
+            serial_number: serial_number,
+            configuration_value: configuration_value,
+        }
+    }
+
+    fn is_allowed(&self, origin: &str, device_id: &str) -> bool {
+        self.allowed_devices.get(origin).map_or(false, |devices| devices.contains(device_id))
+    }
+
+    // https://wicg.github.io/webusb/#dom-usb-getdevices
+    fn get_devices(&mut self, origin: &str) -> UsbResult<Vec<UsbDeviceMsg>> {
+        let devices = try!(self.get_and_cache_devices());
+        Ok(devices.into_iter()
+                  .filter(|&(ref id, _, _)| self.is_allowed(origin, id))
+                  .map(|(id, device, descriptor)| self.device_msg(id, &device, &descriptor))
+                  .collect())
+    }
+
+    // https://wicg.github.io/webusb/#request-the-usb-permission-storage
+    // Step 3: the devices the user chooses from.
+    fn matching_devices(&mut self, filters: &[UsbDeviceFilter]) -> UsbResult<Vec<UsbDeviceMsg>> {
+        let devices = try!(self.get_and_cache_devices());
+        Ok(devices.into_iter()
+                  .map(|(id, device, descriptor)| self.device_msg(id, &device, &descriptor))
+                  .filter(|device| filters.is_empty() || filters.iter().any(|filter| matches_filter(device, filter)))
+                  .collect())
+    }
+
+    fn allow_device(&mut self, origin: String, device_id: String) -> UsbResult<UsbDeviceMsg> {
+        let (device, descriptor) = try!(self.get_device(&device_id));
+        let msg = self.device_msg(device_id.clone(), &device, &descriptor);
+        self.allowed_devices.entry(origin).or_insert(HashSet::new()).insert(device_id);
+        Ok(msg)
+    }
+
+    fn get_open_device(&mut self, device_id: &str) -> UsbResult<&mut OpenDevice<'a>> {
+        self.open_devices.get_mut(device_id).ok_or(UsbError::InvalidState)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-open
+    fn open(&mut self, device_id: String) -> UsbResult<bool> {
+        if self.open_devices.contains_key(&device_id) {
+            return Ok(true);
+        }
+        let (device, _) = try!(self.get_device(&device_id));
+        let handle = try!(device.open().map_err(usb_error));
+        self.open_devices.insert(device_id, OpenDevice {
+            handle: handle,
+            claimed_interfaces: HashSet::new(),
+        });
+        Ok(true)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-close
+    fn close(&mut self, device_id: &str) -> UsbResult<bool> {
+        if let Some(mut device) = self.open_devices.remove(device_id) {
+            for interface_number in device.claimed_interfaces.drain() {
+                let _ = device.handle.release_interface(interface_number);
+            }
+        }
+        Ok(true)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-selectconfiguration
+    fn select_configuration(&mut self, device_id: &str, configuration_value: u8) -> UsbResult<bool> {
+        let device = try!(self.get_open_device(device_id));
+        try!(device.handle.set_active_configuration(configuration_value).map_err(usb_error));
+        device.claimed_interfaces.clear();
+        Ok(true)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-claiminterface
+    fn claim_interface(&mut self, device_id: &str, interface_number: u8) -> UsbResult<bool> {
+        let device = try!(self.get_open_device(device_id));
+        try!(device.handle.claim_interface(interface_number).map_err(usb_error));
+        device.claimed_interfaces.insert(interface_number);
+        Ok(true)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-releaseinterface
+    fn release_interface(&mut self, device_id: &str, interface_number: u8) -> UsbResult<bool> {
+        let device = try!(self.get_open_device(device_id));
+        if device.claimed_interfaces.remove(&interface_number) {
+            try!(device.handle.release_interface(interface_number).map_err(usb_error));
+        }
+        Ok(true)
+    }
+
+    // Transfers
+
+    /// The address and type of endpoint `endpoint_number` in direction `direction`, which
+    /// must belong to a claimed interface of the active configuration.
+    fn find_endpoint(&mut self,
+                     device_id: &str,
+                     endpoint_number: u8,
+                     direction: Direction)
+                     -> UsbResult<(u8, TransferType)> {
+        let (device, _) = try!(self.get_device(device_id));
+        let claimed = try!(self.get_open_device(device_id)).claimed_interfaces.clone();
+        let config = try!(device.active_config_descriptor().map_err(usb_error));
+        for interface in config.interfaces() {
+            if !claimed.contains(&interface.number()) {
+                continue;
+            }
+            for descriptor in interface.descriptors() {
+                for endpoint in descriptor.endpoint_descriptors() {
+                    if endpoint.number() == endpoint_number && endpoint.direction() == direction {
+                        return Ok((endpoint.address(), endpoint.transfer_type()));
+                    }
+                }
+            }
+        }
+        Err(UsbError::NotFound)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-controltransferin
+    fn control_transfer_in(&mut self,
+                           device_id: &str,
+                           setup: UsbControlSetup,
+                           length: u16)
+                           -> UsbResult<UsbInTransferMsg> {
+        let device = try!(self.get_open_device(device_id));
+        let mut data = vec![0; length as usize];
+        let result = device.handle.read_control(request_type(Direction::In, &setup),
+                                                setup.request,
+                                                setup.value,
+                                                setup.index,
+                                                &mut data,
+                                                Duration::from_millis(TRANSFER_TIMEOUT_MS));
+        in_transfer_result(result, data)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-controltransferout
+    fn control_transfer_out(&mut self,
+                            device_id: &str,
+                            setup: UsbControlSetup,
+                            data: Vec<u8>)
+                            -> UsbResult<UsbOutTransferMsg> {
+        let device = try!(self.get_open_device(device_id));
+        let result = device.handle.write_control(request_type(Direction::Out, &setup),
+                                                 setup.request,
+                                                 setup.value,
+                                                 setup.index,
+                                                 &data,
+                                                 Duration::from_millis(TRANSFER_TIMEOUT_MS));
+        out_transfer_result(result)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-transferin
+    fn transfer_in(&mut self, device_id: &str, endpoint_number: u8, length: u32) -> UsbResult<UsbInTransferMsg> {
+        let (address, transfer_type) = try!(self.find_endpoint(device_id, endpoint_number, Direction::In));
+        let device = try!(self.get_open_device(device_id));
+        let mut data = vec![0; length as usize];
+        let timeout = Duration::from_millis(TRANSFER_TIMEOUT_MS);
+        let result = match transfer_type {
+            TransferType::Bulk => device.handle.read_bulk(address, &mut data, timeout),
+            TransferType::Interrupt => device.handle.read_interrupt(address, &mut data, timeout),
+            _ => return Err(UsbError::NotSupported),
+        };
+        in_transfer_result(result, data)
+    }
+
+    // https://wicg.github.io/webusb/#dom-usbdevice-transferout
+    fn transfer_out(&mut self, device_id: &str, endpoint_number: u8, data: Vec<u8>) -> UsbResult<UsbOutTransferMsg> {
+        let (address, transfer_type) = try!(self.find_endpoint(device_id, endpoint_number, Direction::Out));
+        let device = try!(self.get_open_device(device_id));
+        let timeout = Duration::from_millis(TRANSFER_TIMEOUT_MS);
+        let result = match transfer_type {
+            TransferType::Bulk => device.handle.write_bulk(address, &data, timeout),
+            TransferType::Interrupt => device.handle.write_interrupt(address, &data, timeout),
+            _ => return Err(UsbError::NotSupported),
+        };
+        out_transfer_result(result)
+    }
+}
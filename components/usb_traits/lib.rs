@@ -0,0 +1,121 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The messages that script and the constellation exchange with the USB thread of the `usb`
+//! crate, which enumerates and opens the devices that WebUSB exposes.
+
+#![feature(custom_derive, plugin)]
+#![plugin(serde_macros)]
+
+#![deny(unsafe_code)]
+
+extern crate ipc_channel;
+extern crate serde;
+
+use ipc_channel::ipc::IpcSender;
+
+#[derive(Deserialize, Serialize)]
+pub enum UsbError {
+    InvalidState,
+    Network,
+    NotFound,
+    NotSupported,
+    Security,
+}
+
+/// The descriptors of a device, as read when it was enumerated.
+#[derive(Deserialize, Serialize)]
+pub struct UsbDeviceMsg {
+    pub id: String,
+    // Version numbers are binary-coded decimals, as in the device descriptor
+    pub usb_version: u16,
+    pub device_class: u8,
+    pub device_subclass: u8,
+    pub device_protocol: u8,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub device_version: u16,
+    pub manufacturer_name: Option<String>,
+    pub product_name: Option<String>,
+    pub serial_number: Option<String>,
+    pub configuration_value: Option<u8>,
+}
+
+/// https://wicg.github.io/webusb/#dictdef-usbdevicefilter
+#[derive(Deserialize, Serialize)]
+pub struct UsbDeviceFilter {
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    pub class_code: Option<u8>,
+    pub subclass_code: Option<u8>,
+    pub protocol_code: Option<u8>,
+    pub serial_number: Option<String>,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub enum UsbRequestType {
+    Standard,
+    Class,
+    Vendor,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub enum UsbRecipient {
+    Device,
+    Interface,
+    Endpoint,
+    Other,
+}
+
+/// https://wicg.github.io/webusb/#dictdef-usbcontroltransferparameters
+#[derive(Deserialize, Serialize)]
+pub struct UsbControlSetup {
+    pub request_type: UsbRequestType,
+    pub recipient: UsbRecipient,
+    pub request: u8,
+    pub value: u16,
+    pub index: u16,
+}
+
+/// https://wicg.github.io/webusb/#enumdef-usbtransferstatus
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub enum UsbTransferStatus {
+    Ok,
+    Stall,
+    Babble,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct UsbInTransferMsg {
+    pub status: UsbTransferStatus,
+    pub data: Vec<u8>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct UsbOutTransferMsg {
+    pub status: UsbTransferStatus,
+    pub bytes_written: u32,
+}
+
+pub type UsbResult<T> = Result<T, UsbError>;
+
+#[derive(Deserialize, Serialize)]
+pub enum UsbMethodMsg {
+    /// The devices the given origin was allowed to use that are connected.
+    GetDevices(String, IpcSender<UsbResult<Vec<UsbDeviceMsg>>>),
+    /// The connected devices that match any of the filters, for the user to choose from.
+    MatchingDevices(Vec<UsbDeviceFilter>, IpcSender<UsbResult<Vec<UsbDeviceMsg>>>),
+    /// Allows the given origin to use the device with the given id, which the user chose.
+    AllowDevice(String, String, IpcSender<UsbResult<UsbDeviceMsg>>),
+    Open(String, IpcSender<UsbResult<bool>>),
+    Close(String, IpcSender<UsbResult<bool>>),
+    SelectConfiguration(String, u8, IpcSender<UsbResult<bool>>),
+    ClaimInterface(String, u8, IpcSender<UsbResult<bool>>),
+    ReleaseInterface(String, u8, IpcSender<UsbResult<bool>>),
+    ControlTransferIn(String, UsbControlSetup, u16, IpcSender<UsbResult<UsbInTransferMsg>>),
+    ControlTransferOut(String, UsbControlSetup, Vec<u8>, IpcSender<UsbResult<UsbOutTransferMsg>>),
+    TransferIn(String, u8, u32, IpcSender<UsbResult<UsbInTransferMsg>>),
+    TransferOut(String, u8, Vec<u8>, IpcSender<UsbResult<UsbOutTransferMsg>>),
+    Exit,
+}
@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Pref-controlled workarounds for sites that depend on legacy browser behaviour.
+//!
+//! Each shim is off unless its `compat.*` or `dom.compat.*` pref is set, so the
+//! standards-compliant behaviour remains the default.
+
+use prefs::PREFS;
+use resource_files::resources_dir_path;
+use rustc_serialize::json::Json;
+use std::fs::File;
+use std::io::Read;
+use url::Url;
+
+lazy_static! {
+    static ref UA_OVERRIDES: Vec<(String, String)> = {
+        read_ua_overrides().unwrap_or_else(|_| vec![])
+    };
+}
+
+fn read_ua_overrides() -> Result<Vec<(String, String)>, ()> {
+    let mut path = try!(resources_dir_path().map_err(|_| ()));
+    path.push("ua_overrides.json");
+    let file = try!(File::open(path).map_err(|_| ()));
+    read_ua_overrides_from_file(file)
+}
+
+/// Read a JSON object mapping domains to the user agent string they should be sent.
+pub fn read_ua_overrides_from_file<T>(mut file: T) -> Result<Vec<(String, String)>, ()> where T: Read {
+    let json = try!(Json::from_reader(&mut file).or_else(|e| {
+        println!("Ignoring invalid JSON in user agent overrides: {:?}.", e);
+        Err(())
+    }));
+
+    let mut overrides = vec![];
+    if let Json::Object(obj) = json {
+        for (domain, value) in obj.into_iter() {
+            match value {
+                Json::String(user_agent) => overrides.push((domain.to_lowercase(), user_agent)),
+                _ => println!("Ignoring non-string user agent override for {:?}", domain),
+            }
+        }
+    }
+    Ok(overrides)
+}
+
+/// Find the most specific override whose domain is `host` or one of its parent domains.
+pub fn find_ua_override<'a>(host: &str, overrides: &'a [(String, String)]) -> Option<&'a str> {
+    overrides.iter()
+             .filter(|&&(ref domain, _)| {
+                 host == domain ||
+                 (host.ends_with(&**domain) && host[..host.len() - domain.len()].ends_with('.'))
+             })
+             .max_by_key(|&&(ref domain, _)| domain.len())
+             .map(|&(_, ref user_agent)| &**user_agent)
+}
+
+/// The user agent to present to `url`, which is `default` unless the
+/// `compat.ua_overrides.enabled` pref is set and the domain has an override.
+pub fn user_agent_for_url(url: &Url, default: &str) -> String {
+    if !PREFS.get("compat.ua_overrides.enabled").as_boolean().unwrap_or(false) {
+        return default.to_owned();
+    }
+    url.host_str()
+       .map(|host| host.to_lowercase())
+       .and_then(|host| find_ua_override(&host, &UA_OVERRIDES).map(str::to_owned))
+       .unwrap_or_else(|| default.to_owned())
+}
+
+/// Whether `window.event` exposes the event currently being dispatched.
+pub fn window_event_enabled() -> bool {
+    PREFS.get("dom.compat.window_event.enabled").as_boolean().unwrap_or(false)
+}
+
+/// Whether `mouseleave` and `mouseenter` are fired after both `mouseout` and
+/// `mouseover`, as legacy engines did, rather than interleaved with them.
+pub fn legacy_mouse_boundary_order_enabled() -> bool {
+    PREFS.get("dom.compat.legacy_mouse_boundary_order.enabled").as_boolean().unwrap_or(false)
+}
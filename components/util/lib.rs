@@ -28,6 +28,7 @@ extern crate url;
 extern crate xdg;
 
 pub mod basedir;
+pub mod compat;
 pub mod geometry;
 #[cfg(feature = "servo")] #[allow(unsafe_code)] pub mod ipc;
 #[allow(unsafe_code)] pub mod opts;
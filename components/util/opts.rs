@@ -51,6 +51,10 @@ pub struct Opts {
     /// visualizing the traces as a timeline.
     pub time_profiler_trace_path: Option<String>,
 
+    /// An optional path to write the profiler traces to in the Trace Event Format, for viewing
+    /// in chrome://tracing. Script events are profiled whenever this is set.
+    pub time_profiler_chrome_trace_path: Option<String>,
+
     /// `None` to disable the memory profiler or `Some` with an interval in seconds to enable it
     /// and cause it to produce output on that interval (`-m`).
     pub mem_profiler_period: Option<f64>,
@@ -147,6 +151,9 @@ pub struct Opts {
     /// Whether we're running inside the sandbox.
     pub sandbox: bool,
 
+    /// Whether documents are laid out as print media, broken into pages.
+    pub paginate: bool,
+
     /// Probability of randomly closing a pipeline,
     /// used for testing the hardening of the constellation.
     pub random_pipeline_closure_probability: Option<f32>,
@@ -498,6 +505,7 @@ pub fn default_opts() -> Opts {
         device_pixels_per_px: None,
         time_profiling: None,
         time_profiler_trace_path: None,
+        time_profiler_chrome_trace_path: None,
         mem_profiler_period: None,
         nonincremental_layout: false,
         userscripts: None,
@@ -526,6 +534,7 @@ pub fn default_opts() -> Opts {
         random_pipeline_closure_probability: None,
         random_pipeline_closure_seed: None,
         sandbox: false,
+        paginate: false,
         dump_style_tree: false,
         dump_flow_tree: false,
         dump_display_list: false,
@@ -568,6 +577,9 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
     opts.optflagopt("", "profiler-trace-path",
                     "Path to dump a self-contained HTML timeline of profiler traces",
                     "");
+    opts.optopt("", "profiler-chrome-trace",
+                "Path to write profiler traces to in the chrome://tracing format",
+                "trace.json");
     opts.optflagopt("m", "memory-profile", "Memory profiler flag and output interval", "10");
     opts.optflag("x", "exit", "Exit after load flag");
     opts.optopt("y", "layout-threads", "Number of threads to use for layout", "1");
@@ -589,6 +601,7 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
                 "NCSA Mosaic/1.0 (X11;SunOS 4.1.4 sun4m)");
     opts.optflag("M", "multiprocess", "Run in multiprocess mode");
     opts.optflag("S", "sandbox", "Run in a sandbox if multiprocess");
+    opts.optflag("", "paginate", "Lay documents out as print media, broken into pages");
     opts.optopt("",
                 "random-pipeline-closure-probability",
                 "Probability of randomly closing a pipeline (for testing constellation hardening).",
@@ -809,6 +822,7 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
         device_pixels_per_px: device_pixels_per_px,
         time_profiling: time_profiling,
         time_profiler_trace_path: opt_match.opt_str("profiler-trace-path"),
+        time_profiler_chrome_trace_path: opt_match.opt_str("profiler-chrome-trace"),
         mem_profiler_period: mem_profiler_period,
         nonincremental_layout: nonincremental_layout,
         userscripts: opt_match.opt_default("userscripts", ""),
@@ -820,7 +834,8 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
         headless: opt_match.opt_present("z"),
         hard_fail: opt_match.opt_present("f") && !opt_match.opt_present("F"),
         bubble_inline_sizes_separately: bubble_inline_sizes_separately,
-        profile_script_events: debug_options.profile_script_events,
+        profile_script_events: debug_options.profile_script_events ||
+                               opt_match.opt_present("profiler-chrome-trace"),
         profile_heartbeats: debug_options.profile_heartbeats,
         trace_layout: debug_options.trace_layout,
         debugger_port: debugger_port,
@@ -830,6 +845,7 @@ pub fn from_cmdline_args(args: &[String]) -> ArgumentParsingResult {
         user_agent: user_agent,
         multiprocess: opt_match.opt_present("M"),
         sandbox: opt_match.opt_present("S"),
+        paginate: opt_match.opt_present("paginate"),
         random_pipeline_closure_probability: random_pipeline_closure_probability,
         random_pipeline_closure_seed: random_pipeline_closure_seed,
         render_api: render_api,
@@ -0,0 +1,107 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The Data Channel Establishment Protocol, with which a peer opens a
+//! channel that the other peer didn't negotiate.
+//! https://tools.ietf.org/html/rfc8832
+
+use sctp::{PPID_BINARY, PPID_BINARY_EMPTY, PPID_STRING, PPID_STRING_EMPTY, Reliability};
+use webrtc_traits::{ChannelMessage, ChannelParameters};
+
+pub const DATA_CHANNEL_ACK: u8 = 0x02;
+pub const DATA_CHANNEL_OPEN: u8 = 0x03;
+
+const CHANNEL_RELIABLE: u8 = 0x00;
+const CHANNEL_PARTIAL_RELIABLE_REXMIT: u8 = 0x01;
+const CHANNEL_PARTIAL_RELIABLE_TIMED: u8 = 0x02;
+const CHANNEL_UNORDERED: u8 = 0x80;
+
+/// The `DATA_CHANNEL_OPEN` message that opens a channel.
+pub fn encode_open(parameters: &ChannelParameters) -> Vec<u8> {
+    let (mut channel_type, reliability) = match (parameters.max_retransmits, parameters.max_packet_life_time) {
+        (Some(max_retransmits), _) => (CHANNEL_PARTIAL_RELIABLE_REXMIT, max_retransmits as u32),
+        (None, Some(max_packet_life_time)) => (CHANNEL_PARTIAL_RELIABLE_TIMED, max_packet_life_time as u32),
+        (None, None) => (CHANNEL_RELIABLE, 0),
+    };
+    if !parameters.ordered {
+        channel_type |= CHANNEL_UNORDERED;
+    }
+    let mut bytes = vec![DATA_CHANNEL_OPEN, channel_type];
+    bytes.push((parameters.priority >> 8) as u8);
+    bytes.push(parameters.priority as u8);
+    for shift in &[24, 16, 8, 0] {
+        bytes.push((reliability >> shift) as u8);
+    }
+    for length in &[parameters.label.len(), parameters.protocol.len()] {
+        bytes.push((length >> 8) as u8);
+        bytes.push(*length as u8);
+    }
+    bytes.extend_from_slice(parameters.label.as_bytes());
+    bytes.extend_from_slice(parameters.protocol.as_bytes());
+    bytes
+}
+
+/// How the messages of a channel are sent, which is as many times as it
+/// allows when it sets both limits, as `encode_open` announces.
+pub fn reliability(parameters: &ChannelParameters) -> Reliability {
+    match (parameters.max_retransmits, parameters.max_packet_life_time) {
+        (Some(max_retransmits), _) => Reliability::MaxRetransmits(max_retransmits),
+        (None, Some(max_packet_life_time)) => Reliability::MaxPacketLifeTime(max_packet_life_time),
+        (None, None) => Reliability::Reliable,
+    }
+}
+
+pub fn decode_open(bytes: &[u8]) -> Result<ChannelParameters, ()> {
+    if bytes.len() < 12 || bytes[0] != DATA_CHANNEL_OPEN {
+        return Err(());
+    }
+    let channel_type = bytes[1];
+    let priority = (bytes[2] as u16) << 8 | bytes[3] as u16;
+    let reliability = bytes[4..8].iter().fold(0u32, |value, &byte| value << 8 | byte as u32);
+    let label_length = ((bytes[8] as usize) << 8) | bytes[9] as usize;
+    let protocol_length = ((bytes[10] as usize) << 8) | bytes[11] as usize;
+    if bytes.len() < 12 + label_length + protocol_length {
+        return Err(());
+    }
+    let label = try!(String::from_utf8(bytes[12..12 + label_length].to_vec()).map_err(|_| ()));
+    let protocol = &bytes[12 + label_length..12 + label_length + protocol_length];
+    let protocol = try!(String::from_utf8(protocol.to_vec()).map_err(|_| ()));
+    let reliability = if reliability > u16::max_value() as u32 { u16::max_value() } else { reliability as u16 };
+    let (max_retransmits, max_packet_life_time) = match channel_type & !CHANNEL_UNORDERED {
+        CHANNEL_RELIABLE => (None, None),
+        CHANNEL_PARTIAL_RELIABLE_REXMIT => (Some(reliability), None),
+        CHANNEL_PARTIAL_RELIABLE_TIMED => (None, Some(reliability)),
+        _ => return Err(()),
+    };
+    Ok(ChannelParameters {
+        label: label,
+        protocol: protocol,
+        ordered: channel_type & CHANNEL_UNORDERED == 0,
+        max_packet_life_time: max_packet_life_time,
+        max_retransmits: max_retransmits,
+        priority: priority,
+    })
+}
+
+/// The payload protocol identifier and the data of a message, which is a
+/// single byte when it is empty.
+/// https://tools.ietf.org/html/rfc8831#section-6.6
+pub fn encode_message(message: &ChannelMessage) -> (u32, Vec<u8>) {
+    match *message {
+        ChannelMessage::Text(ref text) if text.is_empty() => (PPID_STRING_EMPTY, vec![0]),
+        ChannelMessage::Text(ref text) => (PPID_STRING, text.as_bytes().to_vec()),
+        ChannelMessage::Binary(ref data) if data.is_empty() => (PPID_BINARY_EMPTY, vec![0]),
+        ChannelMessage::Binary(ref data) => (PPID_BINARY, data.clone()),
+    }
+}
+
+pub fn decode_message(ppid: u32, data: Vec<u8>) -> Option<ChannelMessage> {
+    match ppid {
+        PPID_STRING => Some(ChannelMessage::Text(String::from_utf8_lossy(&data).into_owned())),
+        PPID_STRING_EMPTY => Some(ChannelMessage::Text(String::new())),
+        PPID_BINARY => Some(ChannelMessage::Binary(data)),
+        PPID_BINARY_EMPTY => Some(ChannelMessage::Binary(vec![])),
+        _ => None,
+    }
+}
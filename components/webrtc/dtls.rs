@@ -0,0 +1,308 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! DTLS, which secures what peers send each other.
+//! https://tools.ietf.org/html/rfc5763
+//!
+//! The certificates of peers are self-signed, so that OpenSSL accepts any
+//! certificate and the handshake then checks that the one of the other peer
+//! has the fingerprint of its description. OpenSSL reads and writes
+//! datagrams through queues rather than a socket, one datagram per call.
+//!
+//! OpenSSL retransmits the handshake when it is read after its timer expired,
+//! so the endpoint reads again on a timer that doubles as that of OpenSSL
+//! does. The keys of SRTP can't be exported through the `openssl` crate, so
+//! DTLS-SRTP isn't negotiated until audio and video tracks need it.
+
+use openssl::crypto::hash::Type;
+use openssl::crypto::pkey::PKey;
+use openssl::ssl::{self, SSL_VERIFY_FAIL_IF_NO_PEER_CERT, SSL_VERIFY_PEER, SslContext, SslMethod, SslStream};
+use openssl::x509::{X509, X509Generator, X509StoreContext};
+use std::cmp;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+use webrtc_traits::{Certificate, Fingerprint};
+
+const CIPHER_LIST: &'static str = "ALL:!ADH:!LOW:!EXP:!MD5:!RC4:@STRENGTH";
+/// How long OpenSSL waits for the other peer before it retransmits, which
+/// doubles every time.
+const INITIAL_RETRANSMIT_MS: u64 = 1000;
+const MAX_RETRANSMIT_MS: u64 = 60000;
+/// What `SSL_state_string` says once the handshake is done.
+const STATE_OK: &'static str = "SSLOK ";
+
+fn hash_type(algorithm: &str) -> Option<Type> {
+    match &*algorithm.to_lowercase() {
+        "sha-1" => Some(Type::SHA1),
+        "sha-256" => Some(Type::SHA256),
+        "sha-384" => Some(Type::SHA384),
+        "sha-512" => Some(Type::SHA512),
+        _ => None,
+    }
+}
+
+/// The hash of a certificate, with a hash function named as in
+/// `a=fingerprint` lines.
+pub fn fingerprint(algorithm: &str, x509: &X509) -> Option<Fingerprint> {
+    hash_type(algorithm).and_then(|hash_type| x509.fingerprint(hash_type)).map(|value| {
+        Fingerprint {
+            algorithm: algorithm.to_lowercase(),
+            value: value,
+        }
+    })
+}
+
+/// Generates a self-signed RSA certificate, valid for 30 days.
+pub fn generate_certificate() -> Result<Certificate, ()> {
+    let generator = X509Generator::new()
+        .set_bitlength(2048)
+        .set_valid_period(30)
+        .add_name("CN".to_owned(), "WebRTC".to_owned())
+        .set_sign_hash(Type::SHA256);
+    let (x509, key) = try!(generator.generate().map_err(|_| ()));
+    let mut pem = vec![];
+    try!(x509.write_pem(&mut pem).map_err(|_| ()));
+    Ok(Certificate {
+        pem: pem,
+        private_key_der: key.save_priv(),
+        fingerprint: try!(fingerprint("sha-256", &x509).ok_or(())),
+    })
+}
+
+/// The fingerprints are checked once the handshake is done.
+fn accept_any_certificate(_preverify_ok: bool, _context: &X509StoreContext) -> bool {
+    true
+}
+
+/// The datagrams that OpenSSL reads and writes.
+pub struct Datagrams {
+    /// What the other peer sent.
+    incoming: VecDeque<Vec<u8>>,
+    /// What OpenSSL wrote, to send to the other peer.
+    outgoing: Vec<Vec<u8>>,
+}
+
+impl Read for Datagrams {
+    /// Reads a whole datagram, of which what doesn't fit in `buffer` is lost
+    /// as it would be with a socket.
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match self.incoming.pop_front() {
+            Some(datagram) => {
+                let length = cmp::min(datagram.len(), buffer.len());
+                buffer[..length].copy_from_slice(&datagram[..length]);
+                Ok(length)
+            },
+            None => Err(io::Error::new(io::ErrorKind::WouldBlock, "no datagram")),
+        }
+    }
+}
+
+impl Write for Datagrams {
+    fn write(&mut self, datagram: &[u8]) -> io::Result<usize> {
+        self.outgoing.push(datagram.to_vec());
+        Ok(datagram.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// One end of a DTLS association.
+pub struct DtlsEndpoint {
+    context: SslContext,
+    /// The connection, once the handshake started.
+    stream: Option<SslStream<Datagrams>>,
+    client: bool,
+    remote_fingerprint: Fingerprint,
+    connected: bool,
+    closed: bool,
+    /// When to read again for OpenSSL to retransmit, and how long it waits
+    /// until then.
+    retransmit: Option<(Instant, Duration)>,
+}
+
+impl DtlsEndpoint {
+    /// Creates the client or server end of an association, with the other
+    /// end having a certificate of `remote_fingerprint`.
+    pub fn new(certificate: &Certificate, client: bool, remote_fingerprint: Fingerprint) -> Result<DtlsEndpoint, ()> {
+        let x509 = try!(X509::from_pem(&mut &*certificate.pem).map_err(|_| ()));
+        let mut key = PKey::new();
+        key.load_priv(&certificate.private_key_der);
+        let mut context = try!(SslContext::new(SslMethod::Dtlsv1_2).map_err(|_| ()));
+        try!(context.set_certificate(&x509).map_err(|_| ()));
+        try!(context.set_private_key(&key).map_err(|_| ()));
+        try!(context.check_private_key().map_err(|_| ()));
+        try!(context.set_cipher_list(CIPHER_LIST).map_err(|_| ()));
+        try!(context.set_ecdh_auto(true).map_err(|_| ()));
+        context.set_verify(SSL_VERIFY_PEER | SSL_VERIFY_FAIL_IF_NO_PEER_CERT, Some(accept_any_certificate));
+        Ok(DtlsEndpoint {
+            context: context,
+            stream: None,
+            client: client,
+            remote_fingerprint: remote_fingerprint,
+            connected: false,
+            closed: false,
+            retransmit: None,
+        })
+    }
+
+    pub fn is_client(&self) -> bool {
+        self.client
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Starts the handshake, which only sends something for a client.
+    pub fn start(&mut self, now: Instant) -> Result<(), ()> {
+        if self.stream.is_some() {
+            return Ok(());
+        }
+        let datagrams = Datagrams {
+            incoming: VecDeque::new(),
+            outgoing: vec![],
+        };
+        let stream = if self.client {
+            SslStream::connect(&self.context, datagrams)
+        } else {
+            SslStream::accept(&self.context, datagrams)
+        };
+        self.stream = Some(try!(stream.map_err(|_| ())));
+        self.wrote_flight(now);
+        Ok(())
+    }
+
+    /// Restarts the timer of retransmissions if OpenSSL sent a flight of the
+    /// handshake.
+    fn wrote_flight(&mut self, now: Instant) {
+        let wrote = self.stream.as_ref().map_or(false, |stream| !stream.get_ref().outgoing.is_empty());
+        if self.connected || self.closed {
+            self.retransmit = None;
+        } else if wrote {
+            let timeout = Duration::from_millis(INITIAL_RETRANSMIT_MS);
+            self.retransmit = Some((now + timeout, timeout));
+        }
+    }
+
+    /// Reads what OpenSSL has for the application, which also continues the
+    /// handshake.
+    fn read(&mut self) -> Result<Vec<Vec<u8>>, ()> {
+        let mut data = vec![];
+        let mut buffer = vec![0u8; 65536];
+        loop {
+            let result = match self.stream {
+                Some(ref mut stream) => stream.ssl_read(&mut buffer),
+                None => return Ok(data),
+            };
+            match result {
+                Ok(0) => break,
+                Ok(length) => data.push(buffer[..length].to_vec()),
+                Err(ssl::Error::WantRead(_)) | Err(ssl::Error::WantWrite(_)) => break,
+                Err(ssl::Error::ZeroReturn) => {
+                    self.closed = true;
+                    break;
+                },
+                Err(_) => return Err(()),
+            }
+        }
+        if !self.connected && self.handshake_done() {
+            if !self.remote_fingerprint_matches() {
+                warn!("The DTLS certificate of the peer doesn't match its fingerprint");
+                return Err(());
+            }
+            self.connected = true;
+        }
+        Ok(data)
+    }
+
+    fn handshake_done(&self) -> bool {
+        self.stream.as_ref().map_or(false, |stream| stream.ssl().state_string() == STATE_OK)
+    }
+
+    fn remote_fingerprint_matches(&self) -> bool {
+        let x509 = match self.stream.as_ref().and_then(|stream| stream.ssl().peer_certificate()) {
+            Some(x509) => x509,
+            None => return false,
+        };
+        fingerprint(&self.remote_fingerprint.algorithm, &x509).map_or(false, |fingerprint| {
+            fingerprint == self.remote_fingerprint
+        })
+    }
+
+    /// Handles a datagram of the other peer, returning the data it holds.
+    pub fn handle_datagram(&mut self, datagram: &[u8], now: Instant) -> Result<Vec<Vec<u8>>, ()> {
+        if self.closed {
+            return Ok(vec![]);
+        }
+        // The other peer can start the handshake before ICE told this one
+        // that it is connected.
+        try!(self.start(now));
+        if let Some(ref mut stream) = self.stream {
+            stream.get_mut().incoming.push_back(datagram.to_vec());
+        }
+        let data = try!(self.read());
+        self.wrote_flight(now);
+        Ok(data)
+    }
+
+    /// Encrypts `data` in a record of its own.
+    pub fn send(&mut self, data: &[u8]) -> Result<(), ()> {
+        if !self.connected || self.closed {
+            return Err(());
+        }
+        match self.stream {
+            Some(ref mut stream) => {
+                let written = try!(stream.ssl_write(data).map_err(|_| ()));
+                if written == data.len() { Ok(()) } else { Err(()) }
+            },
+            None => Err(()),
+        }
+    }
+
+    /// The datagrams to send to the other peer.
+    pub fn take_datagrams(&mut self) -> Vec<Vec<u8>> {
+        match self.stream {
+            Some(ref mut stream) => stream.get_mut().outgoing.drain(..).collect(),
+            None => vec![],
+        }
+    }
+
+    /// When the handshake has to be retransmitted, if it is waiting for the
+    /// other peer.
+    pub fn next_timeout(&self) -> Option<Instant> {
+        self.retransmit.map(|(time, _)| time)
+    }
+
+    /// Lets OpenSSL retransmit the last flight of the handshake, once its
+    /// timeout expired.
+    pub fn poll(&mut self, now: Instant) -> Result<(), ()> {
+        let timeout = match self.retransmit {
+            Some((time, timeout)) if time <= now => timeout,
+            _ => return Ok(()),
+        };
+        try!(self.read());
+        self.retransmit = if self.connected || self.closed {
+            None
+        } else {
+            let timeout = cmp::min(timeout * 2, Duration::from_millis(MAX_RETRANSMIT_MS));
+            Some((now + timeout, timeout))
+        };
+        Ok(())
+    }
+
+    /// Stops the association. No close_notify alert is sent, as the
+    /// `openssl` crate can't shut a stream down, so the other peer learns it
+    /// from the association that runs over it.
+    pub fn close(&mut self) {
+        self.closed = true;
+        self.retransmit = None;
+    }
+}
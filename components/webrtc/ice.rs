@@ -0,0 +1,289 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! ICE, which finds a path between the peers, with libjuice.
+//! https://tools.ietf.org/html/rfc8445
+//!
+//! libjuice owns the UDP socket of a transport, and answers and sends the
+//! connectivity checks on a thread of its own. What the other peer sends
+//! that isn't STUN, e.g. DTLS, is handed to the agent's handler along with
+//! the candidates and states that libjuice reports.
+
+use webrtc_traits::{Candidate, IceConnectionState};
+
+pub use self::libjuice::IceAgent;
+
+/// What an agent reports, on a thread of libjuice.
+pub enum IceEvent {
+    LocalCandidate(Candidate),
+    GatheringComplete,
+    StateChanged(IceConnectionState),
+    /// A datagram of the other peer that isn't part of ICE.
+    Data(Vec<u8>),
+}
+
+/// Receives the events of an agent.
+pub type IceEventHandler = Box<Fn(IceEvent) + Send + Sync>;
+
+#[cfg(feature = "libjuice")]
+#[allow(unsafe_code)]
+mod libjuice {
+    use libc::{c_char, c_int, c_void, size_t};
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+    use super::{IceEvent, IceEventHandler};
+    use webrtc_traits::{Candidate, IceConnectionState, IceCredentials};
+
+    const JUICE_ERR_SUCCESS: c_int = 0;
+    const JUICE_CONCURRENCY_MODE_POLL: c_int = 0;
+    const JUICE_STATE_CONNECTING: c_int = 2;
+    const JUICE_STATE_CONNECTED: c_int = 3;
+    const JUICE_STATE_COMPLETED: c_int = 4;
+    const JUICE_STATE_FAILED: c_int = 5;
+    /// The size of the buffer that libjuice writes a local description to.
+    const JUICE_MAX_SDP_STRING_LEN: usize = 4096;
+
+    #[allow(non_camel_case_types)]
+    enum juice_agent_t {}
+
+    #[allow(non_camel_case_types)]
+    #[repr(C)]
+    struct juice_config_t {
+        concurrency_mode: c_int,
+        stun_server_host: *const c_char,
+        stun_server_port: u16,
+        turn_servers: *mut c_void,
+        turn_servers_count: c_int,
+        bind_address: *const c_char,
+        local_port_range_begin: u16,
+        local_port_range_end: u16,
+        cb_state_changed: extern "C" fn(*mut juice_agent_t, c_int, *mut c_void),
+        cb_candidate: extern "C" fn(*mut juice_agent_t, *const c_char, *mut c_void),
+        cb_gathering_done: extern "C" fn(*mut juice_agent_t, *mut c_void),
+        cb_recv: extern "C" fn(*mut juice_agent_t, *const c_char, size_t, *mut c_void),
+        user_ptr: *mut c_void,
+    }
+
+    #[link(name = "juice")]
+    extern "C" {
+        fn juice_create(config: *const juice_config_t) -> *mut juice_agent_t;
+        fn juice_destroy(agent: *mut juice_agent_t);
+        fn juice_set_local_ice_attributes(agent: *mut juice_agent_t,
+                                          ufrag: *const c_char,
+                                          pwd: *const c_char)
+                                          -> c_int;
+        fn juice_get_local_description(agent: *mut juice_agent_t, buffer: *mut c_char, size: size_t) -> c_int;
+        fn juice_gather_candidates(agent: *mut juice_agent_t) -> c_int;
+        fn juice_set_remote_description(agent: *mut juice_agent_t, sdp: *const c_char) -> c_int;
+        fn juice_add_remote_candidate(agent: *mut juice_agent_t, sdp: *const c_char) -> c_int;
+        fn juice_send(agent: *mut juice_agent_t, data: *const c_char, size: size_t) -> c_int;
+    }
+
+    fn check(result: c_int) -> Result<(), ()> {
+        if result == JUICE_ERR_SUCCESS { Ok(()) } else { Err(()) }
+    }
+
+    /// The description of `credentials` that libjuice takes, as the
+    /// attribute lines of a session description.
+    fn ice_attributes(credentials: &IceCredentials) -> String {
+        format!("a=ice-ufrag:{}\r\na=ice-pwd:{}\r\n", credentials.ufrag, credentials.pwd)
+    }
+
+    unsafe fn handler<'a>(user_ptr: *mut c_void) -> &'a IceEventHandler {
+        &*(user_ptr as *const IceEventHandler)
+    }
+
+    extern "C" fn on_state_changed(_agent: *mut juice_agent_t, state: c_int, user_ptr: *mut c_void) {
+        let state = match state {
+            JUICE_STATE_CONNECTING => IceConnectionState::Checking,
+            JUICE_STATE_CONNECTED | JUICE_STATE_COMPLETED => IceConnectionState::Connected,
+            JUICE_STATE_FAILED => IceConnectionState::Failed,
+            // Gathering doesn't change the state of the connection.
+            _ => return,
+        };
+        unsafe { handler(user_ptr)(IceEvent::StateChanged(state)) };
+    }
+
+    extern "C" fn on_candidate(_agent: *mut juice_agent_t, sdp: *const c_char, user_ptr: *mut c_void) {
+        let sdp = unsafe { CStr::from_ptr(sdp) }.to_string_lossy();
+        match Candidate::parse(&sdp) {
+            Ok(candidate) => unsafe { handler(user_ptr)(IceEvent::LocalCandidate(candidate)) },
+            Err(()) => warn!("Ignored the local candidate {}", sdp),
+        }
+    }
+
+    extern "C" fn on_gathering_done(_agent: *mut juice_agent_t, user_ptr: *mut c_void) {
+        unsafe { handler(user_ptr)(IceEvent::GatheringComplete) };
+    }
+
+    extern "C" fn on_recv(_agent: *mut juice_agent_t, data: *const c_char, size: size_t, user_ptr: *mut c_void) {
+        let data = unsafe { ::std::slice::from_raw_parts(data as *const u8, size) }.to_vec();
+        unsafe { handler(user_ptr)(IceEvent::Data(data)) };
+    }
+
+    pub struct IceAgent {
+        agent: *mut juice_agent_t,
+        /// What the callbacks of the agent are given, which is freed once
+        /// the agent is destroyed.
+        handler: *mut IceEventHandler,
+        controlling: bool,
+        gathering: bool,
+        /// Whether the credentials of the other peer are known, which they
+        /// have to be before its candidates are added.
+        has_remote: bool,
+        pending_candidates: Vec<Candidate>,
+    }
+
+    // The agent is only used by the thread of its transport; libjuice
+    // synchronizes with its own thread.
+    unsafe impl Send for IceAgent {}
+
+    impl IceAgent {
+        /// Creates an agent with the given credentials, which gathers a
+        /// server reflexive candidate from `stun_server` if there is one.
+        pub fn new(credentials: &IceCredentials,
+                   controlling: bool,
+                   stun_server: Option<(String, u16)>,
+                   handler: IceEventHandler)
+                   -> Result<IceAgent, ()> {
+            let (stun_server_host, stun_server_port) = match stun_server {
+                Some((host, port)) => (Some(try!(CString::new(host).map_err(|_| ()))), port),
+                None => (None, 0),
+            };
+            let ufrag = try!(CString::new(&*credentials.ufrag).map_err(|_| ()));
+            let pwd = try!(CString::new(&*credentials.pwd).map_err(|_| ()));
+            let handler = Box::into_raw(Box::new(handler));
+            let config = juice_config_t {
+                concurrency_mode: JUICE_CONCURRENCY_MODE_POLL,
+                stun_server_host: stun_server_host.as_ref().map_or(ptr::null(), |host| host.as_ptr()),
+                stun_server_port: stun_server_port,
+                turn_servers: ptr::null_mut(),
+                turn_servers_count: 0,
+                bind_address: ptr::null(),
+                local_port_range_begin: 0,
+                local_port_range_end: 0,
+                cb_state_changed: on_state_changed,
+                cb_candidate: on_candidate,
+                cb_gathering_done: on_gathering_done,
+                cb_recv: on_recv,
+                user_ptr: handler as *mut c_void,
+            };
+            // libjuice copies the configuration.
+            let agent = unsafe { juice_create(&config) };
+            if agent.is_null() {
+                unsafe { Box::from_raw(handler) };
+                return Err(());
+            }
+            let agent = IceAgent {
+                agent: agent,
+                handler: handler,
+                controlling: controlling,
+                gathering: false,
+                has_remote: false,
+                pending_candidates: vec![],
+            };
+            try!(check(unsafe { juice_set_local_ice_attributes(agent.agent, ufrag.as_ptr(), pwd.as_ptr()) }));
+            Ok(agent)
+        }
+
+        /// Starts gathering candidates. A controlled agent waits for the
+        /// credentials of the other peer, since libjuice takes the role of
+        /// whichever side of the description it learns first.
+        pub fn start(&mut self) -> Result<(), ()> {
+            if self.controlling {
+                let mut description = vec![0 as c_char; JUICE_MAX_SDP_STRING_LEN];
+                try!(check(unsafe {
+                    juice_get_local_description(self.agent, description.as_mut_ptr(), description.len())
+                }));
+                self.gather()
+            } else {
+                Ok(())
+            }
+        }
+
+        fn gather(&mut self) -> Result<(), ()> {
+            if self.gathering {
+                return Ok(());
+            }
+            self.gathering = true;
+            check(unsafe { juice_gather_candidates(self.agent) })
+        }
+
+        /// Sets the credentials of the other peer, which lets the checks
+        /// start.
+        pub fn set_remote_credentials(&mut self, credentials: &IceCredentials) -> Result<(), ()> {
+            let description = try!(CString::new(ice_attributes(credentials)).map_err(|_| ()));
+            try!(check(unsafe { juice_set_remote_description(self.agent, description.as_ptr()) }));
+            self.has_remote = true;
+            for candidate in ::std::mem::replace(&mut self.pending_candidates, vec![]) {
+                self.add_remote_candidate(candidate);
+            }
+            self.gather()
+        }
+
+        pub fn add_remote_candidate(&mut self, candidate: Candidate) {
+            if !self.has_remote {
+                return self.pending_candidates.push(candidate);
+            }
+            let sdp = match CString::new(format!("a=candidate:{}", candidate.to_sdp_value())) {
+                Ok(sdp) => sdp,
+                Err(_) => return,
+            };
+            if check(unsafe { juice_add_remote_candidate(self.agent, sdp.as_ptr()) }).is_err() {
+                warn!("Ignored the remote candidate {}", candidate.to_sdp_value());
+            }
+        }
+
+        /// Sends a datagram to the other peer, on the selected pair.
+        pub fn send(&mut self, data: &[u8]) -> Result<(), ()> {
+            check(unsafe { juice_send(self.agent, data.as_ptr() as *const c_char, data.len()) })
+        }
+    }
+
+    impl Drop for IceAgent {
+        fn drop(&mut self) {
+            // No callback runs once the agent is destroyed.
+            unsafe {
+                juice_destroy(self.agent);
+                Box::from_raw(self.handler);
+            }
+        }
+    }
+}
+
+/// There is no ICE without libjuice, so transports fail to start.
+#[cfg(not(feature = "libjuice"))]
+mod libjuice {
+    use super::IceEventHandler;
+    use webrtc_traits::{Candidate, IceCredentials};
+
+    pub enum IceAgent {}
+
+    impl IceAgent {
+        pub fn new(_credentials: &IceCredentials,
+                   _controlling: bool,
+                   _stun_server: Option<(String, u16)>,
+                   _handler: IceEventHandler)
+                   -> Result<IceAgent, ()> {
+            warn!("Peer connections need libjuice");
+            Err(())
+        }
+
+        pub fn start(&mut self) -> Result<(), ()> {
+            match *self {}
+        }
+
+        pub fn set_remote_credentials(&mut self, _credentials: &IceCredentials) -> Result<(), ()> {
+            match *self {}
+        }
+
+        pub fn add_remote_candidate(&mut self, _candidate: Candidate) {
+            match *self {}
+        }
+
+        pub fn send(&mut self, _data: &[u8]) -> Result<(), ()> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,527 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The transports of peer connections, for `RTCPeerConnection`.
+//!
+//! A transport runs ICE, DTLS and SCTP: ICE finds where the other peer can
+//! be reached, DTLS secures the path once it is found, and data channels are
+//! streams of the SCTP association that runs over DTLS. Everything is
+//! bundled on one path, and datagrams are told apart by their first byte as
+//! in RFC 7983.
+//!
+//! ICE is libjuice's and SCTP is usrsctp's, which are only linked with the
+//! `libjuice` and `usrsctp` features. Without them, transports fail to
+//! start and data channels never open. DTLS is OpenSSL's.
+//!
+//! Script asks the WebRTC thread to start the transport of a connection, and
+//! then talks to it over IPC. The certificates of connections are generated
+//! here too, next to the DTLS that uses them.
+//!
+//! Only data channels are carried yet.
+
+#![deny(unsafe_code)]
+
+extern crate ipc_channel;
+extern crate libc;
+#[macro_use]
+extern crate log;
+extern crate openssl;
+extern crate util;
+extern crate webrtc_traits;
+
+pub mod datachannel;
+pub mod dtls;
+pub mod ice;
+pub mod sctp;
+
+use datachannel::{DATA_CHANNEL_ACK, DATA_CHANNEL_OPEN};
+use dtls::DtlsEndpoint;
+use ice::{IceAgent, IceEvent};
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use sctp::{Association, AssociationEvent, PPID_DCEP, Reliability, SctpSignal};
+use std::borrow::ToOwned;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, channel};
+use std::time::Instant;
+use util::thread::spawn_named;
+use webrtc_traits::{Certificate, ChannelParameters, DEFAULT_SCTP_PORT, DtlsState, IceConnectionState};
+use webrtc_traits::{IceCredentials, RemoteParameters, Setup, TransportConfig, TransportEvent};
+use webrtc_traits::{TransportMsg, WebRTCMsg, parse_stun_url};
+
+pub trait WebRTCThreadFactory {
+    fn new() -> Self;
+}
+
+impl WebRTCThreadFactory for IpcSender<WebRTCMsg> {
+    fn new() -> IpcSender<WebRTCMsg> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        spawn_named("WebRTCThread".to_owned(), move || {
+            loop {
+                match receiver.recv() {
+                    Ok(WebRTCMsg::GenerateCertificate(sender)) => {
+                        let _ = sender.send(dtls::generate_certificate());
+                    },
+                    Ok(WebRTCMsg::StartTransport(config, event_sender, sender)) => {
+                        let _ = sender.send(start_transport(config, event_sender));
+                    },
+                    Ok(WebRTCMsg::Exit) | Err(_) => return,
+                }
+            }
+        });
+        sender
+    }
+}
+
+/// What the thread of a transport is woken up by.
+enum TransportInput {
+    Msg(TransportMsg),
+    Ice(IceEvent),
+    Sctp(SctpSignal),
+}
+
+/// Wraps what a thread of libjuice or usrsctp reports in the input of the
+/// transport.
+fn forward<T, F>(sender: &Sender<TransportInput>, wrap: F) -> Box<Fn(T) + Send + Sync>
+    where T: 'static, F: Fn(T) -> TransportInput + Send + Sync + 'static {
+    let sender = Mutex::new(sender.clone());
+    Box::new(move |input| {
+        // The transport is gone once it closed.
+        let _ = sender.lock().unwrap().send(wrap(input));
+    })
+}
+
+/// Creates the ICE agent of a transport and starts gathering candidates,
+/// returning the sender of the messages to the transport, which is closed
+/// once the sender is dropped.
+fn start_transport(config: TransportConfig, event_sender: IpcSender<TransportEvent>)
+                   -> Result<IpcSender<TransportMsg>, ()> {
+    let (sender, receiver) = channel();
+    // libjuice looks the server up itself.
+    let stun_server = config.stun_urls.iter().filter_map(|url| parse_stun_url(url)).next();
+    let mut agent = try!(IceAgent::new(&config.credentials,
+                                       config.controlling,
+                                       stun_server,
+                                       forward(&sender, TransportInput::Ice)));
+    try!(agent.start());
+    let (msg_sender, msg_receiver): (IpcSender<TransportMsg>, IpcReceiver<TransportMsg>) =
+        try!(ipc::channel().map_err(|_| ()));
+
+    let msg_input_sender = sender.clone();
+    spawn_named("WebRTCTransportMsgReader".to_owned(), move || {
+        loop {
+            match msg_receiver.recv() {
+                Ok(msg) => {
+                    if msg_input_sender.send(TransportInput::Msg(msg)).is_err() {
+                        return;
+                    }
+                },
+                Err(_) => {
+                    let _ = msg_input_sender.send(TransportInput::Msg(TransportMsg::Close));
+                    return;
+                },
+            }
+        }
+    });
+
+    spawn_named("WebRTCTransport".to_owned(), move || {
+        let mut transport = Transport {
+            sender: sender,
+            receiver: receiver,
+            event_sender: event_sender,
+            agent: agent,
+            ice_state: IceConnectionState::New,
+            certificate: config.certificate,
+            remote: None,
+            dtls: None,
+            dtls_state: DtlsState::New,
+            association: None,
+            sctp_failed: false,
+            channels: HashMap::new(),
+        };
+        transport.run();
+    });
+    Ok(msg_sender)
+}
+
+struct Channel {
+    parameters: ChannelParameters,
+    negotiated: bool,
+    /// Whether the channel was opened on the association.
+    opened: bool,
+    /// Whether the stream that is sent on was reset.
+    reset: bool,
+}
+
+struct Transport {
+    /// What the handlers of the association are created with.
+    sender: Sender<TransportInput>,
+    receiver: Receiver<TransportInput>,
+    event_sender: IpcSender<TransportEvent>,
+    agent: IceAgent,
+    ice_state: IceConnectionState,
+    certificate: Certificate,
+    remote: Option<RemoteParameters>,
+    dtls: Option<DtlsEndpoint>,
+    dtls_state: DtlsState,
+    association: Option<Association>,
+    /// Whether the association couldn't be created, so that no channel
+    /// opens.
+    sctp_failed: bool,
+    channels: HashMap<u16, Channel>,
+}
+
+impl Transport {
+    fn run(&mut self) {
+        loop {
+            // Only the handshake of DTLS is retransmitted on a timer here;
+            // libjuice and usrsctp run theirs on their own threads.
+            let input = match self.dtls.as_ref().and_then(|dtls| dtls.next_timeout()) {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if deadline > now {
+                        self.receiver.recv_timeout(deadline - now)
+                    } else {
+                        Err(RecvTimeoutError::Timeout)
+                    }
+                },
+                None => self.receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match input {
+                Ok(TransportInput::Msg(TransportMsg::Close)) |
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.close();
+                    return;
+                },
+                Ok(TransportInput::Msg(msg)) => self.handle_msg(msg),
+                Ok(TransportInput::Ice(event)) => self.handle_ice_event(event),
+                Ok(TransportInput::Sctp(signal)) => self.handle_sctp_signal(signal),
+                Err(RecvTimeoutError::Timeout) => {},
+            }
+            if let Some(ref mut dtls) = self.dtls {
+                let _ = dtls.poll(Instant::now());
+            }
+            self.flush();
+        }
+    }
+
+    fn handle_msg(&mut self, msg: TransportMsg) {
+        match msg {
+            TransportMsg::SetRemote(parameters) => self.set_remote(parameters),
+            TransportMsg::AddRemoteCandidate(candidate) => self.agent.add_remote_candidate(candidate),
+            TransportMsg::OpenChannel(id, parameters, negotiated) => {
+                if self.sctp_failed {
+                    return self.notify(TransportEvent::ChannelClosed(id));
+                }
+                if self.channels.contains_key(&id) {
+                    return;
+                }
+                self.channels.insert(id, Channel {
+                    parameters: parameters,
+                    negotiated: negotiated,
+                    opened: false,
+                    reset: false,
+                });
+                if self.association.as_ref().map_or(false, |association| association.is_established()) {
+                    self.open_channel(id);
+                }
+            },
+            TransportMsg::Send(id, message) => {
+                if let (Some(channel), Some(association)) = (self.channels.get(&id), self.association.as_mut()) {
+                    let (ppid, data) = datachannel::encode_message(&message);
+                    let reliability = datachannel::reliability(&channel.parameters);
+                    association.send(id, ppid, !channel.parameters.ordered, reliability, &data);
+                }
+            },
+            TransportMsg::CloseChannel(id) => self.close_channel(id),
+            // `run` ends the transport.
+            TransportMsg::Close => {},
+        }
+    }
+
+    fn notify(&self, event: TransportEvent) {
+        // The connection is gone once it was collected.
+        let _ = self.event_sender.send(event);
+    }
+
+    fn set_dtls_state(&mut self, state: DtlsState) {
+        if self.dtls_state != state {
+            self.dtls_state = state;
+            self.notify(TransportEvent::DtlsStateChanged(state));
+        }
+    }
+
+    fn set_remote(&mut self, parameters: RemoteParameters) {
+        if self.remote.is_some() {
+            // FIXME: ICE restarts aren't supported.
+            return;
+        }
+        let credentials = IceCredentials {
+            ufrag: parameters.ice_ufrag.clone(),
+            pwd: parameters.ice_pwd.clone(),
+        };
+        if self.agent.set_remote_credentials(&credentials).is_err() {
+            warn!("Failed to set the ICE credentials of the other peer");
+            self.notify(TransportEvent::IceStateChanged(IceConnectionState::Failed));
+        }
+        for candidate in &parameters.candidates {
+            self.agent.add_remote_candidate(candidate.clone());
+        }
+        // The answerer is the DTLS client, as it answers with
+        // `a=setup:active`.
+        // https://tools.ietf.org/html/rfc8842#section-5.3
+        let client = parameters.setup != Setup::Active;
+        match DtlsEndpoint::new(&self.certificate, client, parameters.fingerprint.clone()) {
+            Ok(dtls) => self.dtls = Some(dtls),
+            Err(()) => {
+                warn!("Failed to create a DTLS endpoint");
+                self.set_dtls_state(DtlsState::Failed);
+            },
+        }
+        self.remote = Some(parameters);
+        if self.ice_state == IceConnectionState::Connected {
+            self.ice_connected();
+        }
+    }
+
+    fn handle_ice_event(&mut self, event: IceEvent) {
+        match event {
+            IceEvent::LocalCandidate(candidate) => self.notify(TransportEvent::IceCandidate(candidate)),
+            IceEvent::GatheringComplete => self.notify(TransportEvent::IceGatheringComplete),
+            IceEvent::StateChanged(state) => {
+                if self.ice_state == state {
+                    return;
+                }
+                self.ice_state = state;
+                self.notify(TransportEvent::IceStateChanged(state));
+                if state == IceConnectionState::Connected && self.remote.is_some() {
+                    self.ice_connected();
+                }
+            },
+            IceEvent::Data(datagram) => match datagram.first() {
+                Some(&byte) if byte >= 20 && byte < 64 => self.handle_dtls(&datagram),
+                // FIXME: RTP and RTCP will be for media tracks.
+                _ => {},
+            },
+        }
+    }
+
+    fn handle_dtls(&mut self, datagram: &[u8]) {
+        let was_connected = match self.dtls {
+            Some(ref dtls) => dtls.is_connected(),
+            None => return,
+        };
+        let result = self.dtls.as_mut().unwrap().handle_datagram(datagram, Instant::now());
+        let records = match result {
+            Ok(records) => records,
+            Err(()) => {
+                warn!("A DTLS handshake failed");
+                self.dtls = None;
+                return self.set_dtls_state(DtlsState::Failed);
+            },
+        };
+        let connected = self.dtls.as_ref().map_or(false, |dtls| dtls.is_connected());
+        if connected && !was_connected {
+            self.dtls_connected();
+        }
+        if let Some(ref mut association) = self.association {
+            for record in records {
+                association.handle_packet(&record);
+            }
+        }
+        if self.dtls.as_ref().map_or(false, |dtls| dtls.is_closed()) {
+            self.set_dtls_state(DtlsState::Closed);
+        }
+    }
+
+    fn dtls_connected(&mut self) {
+        self.set_dtls_state(DtlsState::Connected);
+        let remote_port = self.remote.as_ref().map_or(DEFAULT_SCTP_PORT, |remote| remote.sctp_port);
+        match Association::new(DEFAULT_SCTP_PORT, remote_port, forward(&self.sender, TransportInput::Sctp)) {
+            Ok(mut association) => {
+                association.connect();
+                self.association = Some(association);
+            },
+            Err(()) => {
+                warn!("Failed to create an SCTP association");
+                self.sctp_failed = true;
+                let ids: Vec<_> = self.channels.drain().map(|(id, _)| id).collect();
+                for id in ids {
+                    self.notify(TransportEvent::ChannelClosed(id));
+                }
+            },
+        }
+    }
+
+    /// Starts the DTLS handshake once ICE is connected, if it is the
+    /// client.
+    fn ice_connected(&mut self) {
+        if self.dtls_state != DtlsState::New {
+            return;
+        }
+        self.set_dtls_state(DtlsState::Connecting);
+        let client = self.dtls.as_ref().map_or(false, |dtls| dtls.is_client());
+        if client && self.dtls.as_mut().unwrap().start(Instant::now()).is_err() {
+            self.dtls = None;
+            self.set_dtls_state(DtlsState::Failed);
+        }
+    }
+
+    fn handle_sctp_signal(&mut self, signal: SctpSignal) {
+        match signal {
+            SctpSignal::Packet(packet) => {
+                if let Some(ref mut dtls) = self.dtls {
+                    let _ = dtls.send(&packet);
+                }
+            },
+            SctpSignal::Ready => {
+                if let Some(ref mut association) = self.association {
+                    association.poll();
+                }
+            },
+        }
+    }
+
+    fn open_channel(&mut self, id: u16) {
+        let open = match self.channels.get_mut(&id) {
+            Some(ref mut channel) if !channel.opened => {
+                channel.opened = true;
+                if channel.negotiated { None } else { Some(datachannel::encode_open(&channel.parameters)) }
+            },
+            _ => return,
+        };
+        if let (Some(open), Some(association)) = (open, self.association.as_mut()) {
+            association.send(id, PPID_DCEP, false, Reliability::Reliable, &open);
+        }
+        self.notify(TransportEvent::ChannelOpened(id));
+    }
+
+    fn close_channel(&mut self, id: u16) {
+        let opened = match self.channels.get_mut(&id) {
+            Some(channel) => {
+                channel.reset = true;
+                channel.opened
+            },
+            None => return,
+        };
+        if let (true, Some(association)) = (opened, self.association.as_mut()) {
+            return association.reset_stream(id);
+        }
+        self.channels.remove(&id);
+        self.notify(TransportEvent::ChannelClosed(id));
+    }
+
+    fn handle_dcep(&mut self, id: u16, message: &[u8]) {
+        match message.first() {
+            Some(&DATA_CHANNEL_OPEN) => {
+                let parameters = match datachannel::decode_open(message) {
+                    Ok(parameters) => parameters,
+                    Err(()) => return warn!("Ignored an invalid DATA_CHANNEL_OPEN message"),
+                };
+                if self.channels.contains_key(&id) {
+                    return warn!("Ignored a DATA_CHANNEL_OPEN message for a stream in use");
+                }
+                self.channels.insert(id, Channel {
+                    parameters: parameters.clone(),
+                    negotiated: false,
+                    opened: true,
+                    reset: false,
+                });
+                if let Some(ref mut association) = self.association {
+                    association.send(id, PPID_DCEP, false, Reliability::Reliable, &[DATA_CHANNEL_ACK]);
+                }
+                self.notify(TransportEvent::IncomingChannel(id, parameters));
+            },
+            // Channels are opened without waiting for the acknowledgement.
+            Some(&DATA_CHANNEL_ACK) => {},
+            _ => warn!("Ignored an unknown DCEP message"),
+        }
+    }
+
+    fn handle_association_event(&mut self, event: AssociationEvent) {
+        match event {
+            AssociationEvent::Established => {
+                let ids: Vec<_> = self.channels.keys().cloned().collect();
+                for id in ids {
+                    self.open_channel(id);
+                }
+            },
+            AssociationEvent::Message { stream_id, ppid: PPID_DCEP, data } => self.handle_dcep(stream_id, &data),
+            AssociationEvent::Message { stream_id, ppid, data } => {
+                if !self.channels.contains_key(&stream_id) {
+                    return;
+                }
+                match datachannel::decode_message(ppid, data) {
+                    Some(message) => self.notify(TransportEvent::ChannelMessage(stream_id, message)),
+                    None => warn!("Ignored a message of unknown payload protocol {}", ppid),
+                }
+            },
+            AssociationEvent::Sent(stream_id, bytes) => {
+                self.notify(TransportEvent::ChannelBufferedAmountDecreased(stream_id, bytes));
+            },
+            AssociationEvent::StreamReset(stream_id) => {
+                // A channel is closed once both of its streams are reset.
+                // https://tools.ietf.org/html/rfc8831#section-6.7
+                let reset = match self.channels.remove(&stream_id) {
+                    Some(channel) => channel.reset,
+                    None => return,
+                };
+                if !reset {
+                    if let Some(ref mut association) = self.association {
+                        association.reset_stream(stream_id);
+                    }
+                }
+                self.notify(TransportEvent::ChannelClosed(stream_id));
+            },
+            AssociationEvent::Closed => {
+                let ids: Vec<_> = self.channels.drain().map(|(id, _)| id).collect();
+                for id in ids {
+                    self.notify(TransportEvent::ChannelClosed(id));
+                }
+                self.set_dtls_state(DtlsState::Closed);
+            },
+        }
+    }
+
+    /// Handles what the association has to say, and sends what DTLS has to
+    /// send.
+    fn flush(&mut self) {
+        // Handling an event of the association can make it say more.
+        loop {
+            let events = match self.association {
+                Some(ref mut association) => association.take_events(),
+                None => break,
+            };
+            if events.is_empty() {
+                break;
+            }
+            for event in events {
+                self.handle_association_event(event);
+            }
+        }
+
+        if let Some(ref mut dtls) = self.dtls {
+            for datagram in dtls.take_datagrams() {
+                let _ = self.agent.send(&datagram);
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(ref mut association) = self.association {
+            association.close();
+        }
+        // Aborting the association sends a packet, which usrsctp hands over
+        // before `close` returns.
+        while let Ok(input) = self.receiver.try_recv() {
+            if let TransportInput::Sctp(SctpSignal::Packet(packet)) = input {
+                self.handle_sctp_signal(SctpSignal::Packet(packet));
+            }
+        }
+        self.flush();
+        if let Some(ref mut dtls) = self.dtls {
+            dtls.close();
+        }
+    }
+}
@@ -0,0 +1,673 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! SCTP, whose streams are data channels, with usrsctp.
+//! https://tools.ietf.org/html/rfc8831
+//!
+//! usrsctp runs the association in user space, and hands the packets it
+//! sends to the handler of the association rather than to a socket, so that
+//! they go over DTLS. Its timers run on a thread of its own, which also tells
+//! the handler when the socket of the association may be read or written;
+//! the thread of the transport then calls back into the association.
+
+pub use self::usrsctp::Association;
+
+/// The payload protocol identifiers of data channels.
+/// https://tools.ietf.org/html/rfc8831#section-8
+pub const PPID_DCEP: u32 = 50;
+pub const PPID_STRING: u32 = 51;
+pub const PPID_BINARY: u32 = 53;
+pub const PPID_STRING_EMPTY: u32 = 56;
+pub const PPID_BINARY_EMPTY: u32 = 57;
+
+/// What usrsctp tells an association, on a thread of usrsctp.
+pub enum SctpSignal {
+    /// A packet to send to the other peer.
+    Packet(Vec<u8>),
+    /// The socket may be read or written, which `Association::poll` does.
+    Ready,
+}
+
+/// Receives the signals of an association.
+pub type SctpSignalHandler = Box<Fn(SctpSignal) + Send + Sync>;
+
+/// How long a message lives, or how often it is sent, until it is dropped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reliability {
+    Reliable,
+    MaxRetransmits(u16),
+    /// In milliseconds.
+    MaxPacketLifeTime(u16),
+}
+
+/// What `Association::poll` found.
+#[derive(Debug, PartialEq)]
+pub enum AssociationEvent {
+    Established,
+    Message { stream_id: u16, ppid: u32, data: Vec<u8> },
+    /// The bytes of a message of the stream were handed to SCTP.
+    Sent(u16, usize),
+    /// The other peer reset the stream it sends on.
+    StreamReset(u16),
+    Closed,
+}
+
+#[cfg(feature = "usrsctp")]
+#[allow(unsafe_code)]
+mod usrsctp {
+    use libc::{self, c_int, c_uint, c_void, size_t, ssize_t};
+    use std::collections::VecDeque;
+    use std::io;
+    use std::mem;
+    use std::ptr;
+    use std::sync::{ONCE_INIT, Once};
+    use super::{AssociationEvent, Reliability, SctpSignal, SctpSignalHandler};
+
+    const AF_CONN: c_int = 123;
+    const SOCK_STREAM: c_int = 1;
+    const IPPROTO_SCTP: c_int = 132;
+    const SOL_SOCKET: c_int = 1;
+    const SO_LINGER: c_int = 13;
+
+    const SCTP_INITMSG: c_int = 0x0003;
+    const SCTP_NODELAY: c_int = 0x0004;
+    const SCTP_EVENT: c_int = 0x001e;
+    const SCTP_RECVRCVINFO: c_int = 0x001f;
+    const SCTP_ENABLE_STREAM_RESET: c_int = 0x0900;
+    const SCTP_RESET_STREAMS: c_int = 0x0901;
+
+    const SCTP_ALL_ASSOC: u32 = 2;
+    const SCTP_ENABLE_RESET_STREAM_REQ: u32 = 0x0001;
+    const SCTP_STREAM_RESET_INCOMING: u16 = 0x0001;
+    const SCTP_STREAM_RESET_OUTGOING: u16 = 0x0002;
+
+    const SCTP_ASSOC_CHANGE: u16 = 0x0001;
+    const SCTP_STREAM_RESET_EVENT: u16 = 0x0009;
+    const SCTP_COMM_UP: u16 = 0x0001;
+    const SCTP_COMM_LOST: u16 = 0x0002;
+    const SCTP_SHUTDOWN_COMP: u16 = 0x0004;
+    const SCTP_CANT_STR_ASSOC: u16 = 0x0005;
+
+    const SCTP_SENDV_SPA: c_uint = 4;
+    const SCTP_SEND_SNDINFO_VALID: u32 = 0x0001;
+    const SCTP_SEND_PRINFO_VALID: u32 = 0x0002;
+    const SCTP_UNORDERED: u16 = 0x0400;
+    const SCTP_PR_SCTP_NONE: u16 = 0x0000;
+    const SCTP_PR_SCTP_TTL: u16 = 0x0001;
+    const SCTP_PR_SCTP_RTX: u16 = 0x0003;
+
+    const SCTP_RECVV_RCVINFO: c_uint = 1;
+    const MSG_NOTIFICATION: c_int = 0x2000;
+    #[cfg(target_os = "macos")]
+    const MSG_EOR: c_int = 0x8;
+    #[cfg(not(target_os = "macos"))]
+    const MSG_EOR: c_int = 0x80;
+
+    /// How many streams each direction has, which is the most that
+    /// usrsctp negotiates.
+    const STREAM_COUNT: u16 = 1024;
+
+    #[allow(non_camel_case_types)]
+    enum socket {}
+
+    #[cfg(not(target_os = "macos"))]
+    #[repr(C)]
+    struct sockaddr_conn {
+        sconn_family: u16,
+        sconn_port: u16,
+        sconn_addr: *mut c_void,
+    }
+
+    #[cfg(target_os = "macos")]
+    #[repr(C)]
+    struct sockaddr_conn {
+        sconn_len: u8,
+        sconn_family: u8,
+        sconn_port: u16,
+        sconn_addr: *mut c_void,
+    }
+
+    #[repr(C)]
+    struct sctp_initmsg {
+        sinit_num_ostreams: u16,
+        sinit_max_instreams: u16,
+        sinit_max_attempts: u16,
+        sinit_max_init_timeo: u16,
+    }
+
+    #[repr(C)]
+    struct sctp_assoc_value {
+        assoc_id: u32,
+        assoc_value: u32,
+    }
+
+    #[repr(C)]
+    struct sctp_event {
+        se_assoc_id: u32,
+        se_type: u16,
+        se_on: u8,
+    }
+
+    #[repr(C)]
+    struct sctp_reset_streams {
+        srs_assoc_id: u32,
+        srs_flags: u16,
+        srs_number_streams: u16,
+        srs_stream_list: [u16; 1],
+    }
+
+    #[repr(C)]
+    struct sctp_sndinfo {
+        snd_sid: u16,
+        snd_flags: u16,
+        snd_ppid: u32,
+        snd_context: u32,
+        snd_assoc_id: u32,
+    }
+
+    #[repr(C)]
+    struct sctp_prinfo {
+        pr_policy: u16,
+        pr_value: u32,
+    }
+
+    #[repr(C)]
+    struct sctp_authinfo {
+        auth_keynumber: u16,
+    }
+
+    #[repr(C)]
+    struct sctp_sendv_spa {
+        sendv_flags: u32,
+        sendv_sndinfo: sctp_sndinfo,
+        sendv_prinfo: sctp_prinfo,
+        sendv_authinfo: sctp_authinfo,
+    }
+
+    #[repr(C)]
+    struct sctp_rcvinfo {
+        rcv_sid: u16,
+        rcv_ssn: u16,
+        rcv_flags: u16,
+        rcv_ppid: u32,
+        rcv_tsn: u32,
+        rcv_cumtsn: u32,
+        rcv_context: u32,
+        rcv_assoc_id: u32,
+    }
+
+    #[link(name = "usrsctp")]
+    extern "C" {
+        fn usrsctp_init(port: u16,
+                        conn_output: extern "C" fn(*mut c_void, *mut c_void, size_t, u8, u8) -> c_int,
+                        debug_printf: *const c_void);
+        fn usrsctp_register_address(addr: *mut c_void);
+        fn usrsctp_deregister_address(addr: *mut c_void);
+        fn usrsctp_socket(domain: c_int,
+                          socket_type: c_int,
+                          protocol: c_int,
+                          receive_cb: *const c_void,
+                          send_cb: *const c_void,
+                          sb_threshold: u32,
+                          ulp_info: *mut c_void)
+                          -> *mut socket;
+        fn usrsctp_set_non_blocking(so: *mut socket, onoff: c_int) -> c_int;
+        fn usrsctp_set_upcall(so: *mut socket,
+                              upcall: extern "C" fn(*mut socket, *mut c_void, c_int),
+                              arg: *mut c_void)
+                              -> c_int;
+        fn usrsctp_setsockopt(so: *mut socket,
+                              level: c_int,
+                              option_name: c_int,
+                              option_value: *const c_void,
+                              option_len: libc::socklen_t)
+                              -> c_int;
+        fn usrsctp_bind(so: *mut socket, name: *const c_void, namelen: libc::socklen_t) -> c_int;
+        fn usrsctp_connect(so: *mut socket, name: *const c_void, namelen: libc::socklen_t) -> c_int;
+        fn usrsctp_conninput(addr: *mut c_void, buffer: *const c_void, length: size_t, ecn_bits: u8);
+        fn usrsctp_sendv(so: *mut socket,
+                         data: *const c_void,
+                         len: size_t,
+                         to: *mut c_void,
+                         addrcnt: c_int,
+                         info: *mut c_void,
+                         infolen: libc::socklen_t,
+                         infotype: c_uint,
+                         flags: c_int)
+                         -> ssize_t;
+        fn usrsctp_recvv(so: *mut socket,
+                         dbuf: *mut c_void,
+                         len: size_t,
+                         from: *mut c_void,
+                         fromlen: *mut libc::socklen_t,
+                         info: *mut c_void,
+                         infolen: *mut libc::socklen_t,
+                         infotype: *mut c_uint,
+                         msg_flags: *mut c_int)
+                         -> ssize_t;
+        fn usrsctp_close(so: *mut socket);
+    }
+
+    static INIT: Once = ONCE_INIT;
+
+    unsafe fn handler<'a>(pointer: *mut c_void) -> &'a SctpSignalHandler {
+        &*(pointer as *const SctpSignalHandler)
+    }
+
+    /// Sends a packet of the association registered at `addr`.
+    extern "C" fn conn_output(addr: *mut c_void, buffer: *mut c_void, length: size_t, _tos: u8, _set_df: u8)
+                              -> c_int {
+        let packet = unsafe { ::std::slice::from_raw_parts(buffer as *const u8, length) }.to_vec();
+        unsafe { handler(addr)(SctpSignal::Packet(packet)) };
+        0
+    }
+
+    extern "C" fn upcall(_so: *mut socket, arg: *mut c_void, _flags: c_int) {
+        unsafe { handler(arg)(SctpSignal::Ready) };
+    }
+
+    fn would_block() -> bool {
+        match io::Error::last_os_error().raw_os_error() {
+            Some(libc::EAGAIN) | Some(libc::EINPROGRESS) => true,
+            Some(error) => error == libc::EWOULDBLOCK,
+            None => false,
+        }
+    }
+
+    unsafe fn set_option<T>(so: *mut socket, level: c_int, name: c_int, value: &T) -> Result<(), ()> {
+        let result = usrsctp_setsockopt(so,
+                                        level,
+                                        name,
+                                        value as *const T as *const c_void,
+                                        mem::size_of::<T>() as libc::socklen_t);
+        if result == 0 { Ok(()) } else { Err(()) }
+    }
+
+    /// Reads a field of a notification, which usrsctp writes in the byte
+    /// order of the host.
+    fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+        let mut value = 0u16;
+        if bytes.len() >= offset + 2 {
+            unsafe { ptr::copy_nonoverlapping(bytes[offset..].as_ptr(), &mut value as *mut u16 as *mut u8, 2) };
+        }
+        value
+    }
+
+    /// A message waiting for room in the send buffer of the socket.
+    struct Outgoing {
+        stream_id: u16,
+        ppid: u32,
+        unordered: bool,
+        reliability: Reliability,
+        data: Vec<u8>,
+    }
+
+    pub struct Association {
+        socket: *mut socket,
+        /// The address of the association in usrsctp, which is what its
+        /// signals are given, and which is freed once it is closed.
+        handler: *mut SctpSignalHandler,
+        remote_port: u16,
+        established: bool,
+        closed: bool,
+        outgoing: VecDeque<Outgoing>,
+        /// The part of a message that was read so far.
+        incoming: Vec<u8>,
+        events: Vec<AssociationEvent>,
+    }
+
+    // The association is only used by the thread of its transport; usrsctp
+    // synchronizes with its own thread.
+    unsafe impl Send for Association {}
+
+    impl Association {
+        pub fn new(local_port: u16, remote_port: u16, handler: SctpSignalHandler) -> Result<Association, ()> {
+            INIT.call_once(|| unsafe { usrsctp_init(0, conn_output, ptr::null()) });
+            let handler = Box::into_raw(Box::new(handler));
+            unsafe { usrsctp_register_address(handler as *mut c_void) };
+            let socket = unsafe {
+                usrsctp_socket(AF_CONN, SOCK_STREAM, IPPROTO_SCTP, ptr::null(), ptr::null(), 0, ptr::null_mut())
+            };
+            let association = Association {
+                socket: socket,
+                handler: handler,
+                remote_port: remote_port,
+                established: false,
+                closed: socket.is_null(),
+                outgoing: VecDeque::new(),
+                incoming: vec![],
+                events: vec![],
+            };
+            if socket.is_null() {
+                return Err(());
+            }
+            try!(unsafe { association.configure(local_port) });
+            Ok(association)
+        }
+
+        unsafe fn configure(&self, local_port: u16) -> Result<(), ()> {
+            if usrsctp_set_non_blocking(self.socket, 1) != 0 {
+                return Err(());
+            }
+            usrsctp_set_upcall(self.socket, upcall, self.handler as *mut c_void);
+            // Closing the socket aborts the association rather than waiting
+            // for what is queued.
+            let linger = libc::linger { l_onoff: 1, l_linger: 0 };
+            try!(set_option(self.socket, SOL_SOCKET, SO_LINGER, &linger));
+            let on: c_int = 1;
+            try!(set_option(self.socket, IPPROTO_SCTP, SCTP_RECVRCVINFO, &on));
+            try!(set_option(self.socket, IPPROTO_SCTP, SCTP_NODELAY, &on));
+            let reset = sctp_assoc_value {
+                assoc_id: SCTP_ALL_ASSOC,
+                assoc_value: SCTP_ENABLE_RESET_STREAM_REQ,
+            };
+            try!(set_option(self.socket, IPPROTO_SCTP, SCTP_ENABLE_STREAM_RESET, &reset));
+            for event_type in &[SCTP_ASSOC_CHANGE, SCTP_STREAM_RESET_EVENT] {
+                let event = sctp_event {
+                    se_assoc_id: SCTP_ALL_ASSOC,
+                    se_type: *event_type,
+                    se_on: 1,
+                };
+                try!(set_option(self.socket, IPPROTO_SCTP, SCTP_EVENT, &event));
+            }
+            let init = sctp_initmsg {
+                sinit_num_ostreams: STREAM_COUNT,
+                sinit_max_instreams: STREAM_COUNT,
+                sinit_max_attempts: 0,
+                sinit_max_init_timeo: 0,
+            };
+            try!(set_option(self.socket, IPPROTO_SCTP, SCTP_INITMSG, &init));
+            let address = self.address(local_port);
+            let length = mem::size_of::<sockaddr_conn>() as libc::socklen_t;
+            if usrsctp_bind(self.socket, &address as *const sockaddr_conn as *const c_void, length) != 0 {
+                return Err(());
+            }
+            Ok(())
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        fn address(&self, port: u16) -> sockaddr_conn {
+            sockaddr_conn {
+                sconn_family: AF_CONN as u16,
+                sconn_port: port.to_be(),
+                sconn_addr: self.handler as *mut c_void,
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        fn address(&self, port: u16) -> sockaddr_conn {
+            sockaddr_conn {
+                sconn_len: mem::size_of::<sockaddr_conn>() as u8,
+                sconn_family: AF_CONN as u8,
+                sconn_port: port.to_be(),
+                sconn_addr: self.handler as *mut c_void,
+            }
+        }
+
+        /// Starts the association. Both peers connect, and SCTP resolves
+        /// the collision of their INIT chunks.
+        pub fn connect(&mut self) {
+            let address = self.address(self.remote_port);
+            let length = mem::size_of::<sockaddr_conn>() as libc::socklen_t;
+            let result = unsafe {
+                usrsctp_connect(self.socket, &address as *const sockaddr_conn as *const c_void, length)
+            };
+            if result != 0 && !would_block() {
+                self.close();
+            }
+        }
+
+        pub fn is_established(&self) -> bool {
+            self.established
+        }
+
+        /// Handles a packet that the other peer sent over DTLS.
+        pub fn handle_packet(&mut self, packet: &[u8]) {
+            if !self.closed {
+                unsafe {
+                    usrsctp_conninput(self.handler as *mut c_void,
+                                      packet.as_ptr() as *const c_void,
+                                      packet.len(),
+                                      0)
+                };
+            }
+        }
+
+        /// Queues a message, which is sent once there is room for it.
+        pub fn send(&mut self, stream_id: u16, ppid: u32, unordered: bool, reliability: Reliability, data: &[u8]) {
+            self.outgoing.push_back(Outgoing {
+                stream_id: stream_id,
+                ppid: ppid,
+                unordered: unordered,
+                reliability: reliability,
+                data: data.to_vec(),
+            });
+            self.flush();
+        }
+
+        fn flush(&mut self) {
+            while !self.closed && self.established {
+                let sent = match self.outgoing.front() {
+                    Some(message) => self.send_now(message),
+                    None => return,
+                };
+                match sent {
+                    Ok(true) => {
+                        let message = self.outgoing.pop_front().unwrap();
+                        self.events.push(AssociationEvent::Sent(message.stream_id, message.data.len()));
+                    },
+                    Ok(false) => return,
+                    Err(()) => {
+                        warn!("Failed to send a message on a data channel");
+                        self.outgoing.pop_front();
+                    },
+                }
+            }
+        }
+
+        /// Hands a message to usrsctp, returning whether there was room.
+        fn send_now(&self, message: &Outgoing) -> Result<bool, ()> {
+            let (pr_policy, pr_value) = match message.reliability {
+                Reliability::Reliable => (SCTP_PR_SCTP_NONE, 0),
+                Reliability::MaxRetransmits(count) => (SCTP_PR_SCTP_RTX, count as u32),
+                Reliability::MaxPacketLifeTime(time) => (SCTP_PR_SCTP_TTL, time as u32),
+            };
+            let mut info = sctp_sendv_spa {
+                sendv_flags: SCTP_SEND_SNDINFO_VALID | SCTP_SEND_PRINFO_VALID,
+                sendv_sndinfo: sctp_sndinfo {
+                    snd_sid: message.stream_id,
+                    snd_flags: if message.unordered { SCTP_UNORDERED } else { 0 },
+                    snd_ppid: message.ppid.to_be(),
+                    snd_context: 0,
+                    snd_assoc_id: 0,
+                },
+                sendv_prinfo: sctp_prinfo {
+                    pr_policy: pr_policy,
+                    pr_value: pr_value,
+                },
+                sendv_authinfo: sctp_authinfo {
+                    auth_keynumber: 0,
+                },
+            };
+            let sent = unsafe {
+                usrsctp_sendv(self.socket,
+                              message.data.as_ptr() as *const c_void,
+                              message.data.len(),
+                              ptr::null_mut(),
+                              0,
+                              &mut info as *mut sctp_sendv_spa as *mut c_void,
+                              mem::size_of::<sctp_sendv_spa>() as libc::socklen_t,
+                              SCTP_SENDV_SPA,
+                              0)
+            };
+            if sent >= 0 {
+                Ok(true)
+            } else if would_block() {
+                Ok(false)
+            } else {
+                Err(())
+            }
+        }
+
+        /// Resets the stream that is sent on, which closes a channel once
+        /// the other peer resets its own.
+        /// https://tools.ietf.org/html/rfc8831#section-6.7
+        pub fn reset_stream(&mut self, stream_id: u16) {
+            let reset = sctp_reset_streams {
+                srs_assoc_id: SCTP_ALL_ASSOC,
+                srs_flags: SCTP_STREAM_RESET_OUTGOING,
+                srs_number_streams: 1,
+                srs_stream_list: [stream_id],
+            };
+            if unsafe { set_option(self.socket, IPPROTO_SCTP, SCTP_RESET_STREAMS, &reset) }.is_err() {
+                warn!("Failed to reset the stream {}", stream_id);
+            }
+        }
+
+        /// Reads what the socket has, and sends what waited for room, once
+        /// usrsctp signaled that the socket is ready.
+        pub fn poll(&mut self) {
+            let mut buffer = vec![0u8; 65536];
+            while !self.closed {
+                let mut info: sctp_rcvinfo = unsafe { mem::zeroed() };
+                let mut info_length = mem::size_of::<sctp_rcvinfo>() as libc::socklen_t;
+                let mut info_type: c_uint = 0;
+                let mut flags: c_int = 0;
+                let length = unsafe {
+                    usrsctp_recvv(self.socket,
+                                  buffer.as_mut_ptr() as *mut c_void,
+                                  buffer.len(),
+                                  ptr::null_mut(),
+                                  ptr::null_mut(),
+                                  &mut info as *mut sctp_rcvinfo as *mut c_void,
+                                  &mut info_length,
+                                  &mut info_type,
+                                  &mut flags)
+                };
+                if length < 0 && would_block() {
+                    break;
+                }
+                if length <= 0 {
+                    self.close();
+                    break;
+                }
+                self.incoming.extend_from_slice(&buffer[..length as usize]);
+                if flags & MSG_EOR == 0 {
+                    continue;
+                }
+                let data = mem::replace(&mut self.incoming, vec![]);
+                if flags & MSG_NOTIFICATION != 0 {
+                    self.handle_notification(&data);
+                } else if info_type == SCTP_RECVV_RCVINFO {
+                    self.events.push(AssociationEvent::Message {
+                        stream_id: info.rcv_sid,
+                        ppid: u32::from_be(info.rcv_ppid),
+                        data: data,
+                    });
+                }
+            }
+            self.flush();
+        }
+
+        fn handle_notification(&mut self, notification: &[u8]) {
+            match read_u16(notification, 0) {
+                SCTP_ASSOC_CHANGE => match read_u16(notification, 8) {
+                    SCTP_COMM_UP => {
+                        self.established = true;
+                        self.events.push(AssociationEvent::Established);
+                    },
+                    SCTP_COMM_LOST | SCTP_SHUTDOWN_COMP | SCTP_CANT_STR_ASSOC => self.close(),
+                    _ => {},
+                },
+                SCTP_STREAM_RESET_EVENT => {
+                    if read_u16(notification, 2) & SCTP_STREAM_RESET_INCOMING == 0 {
+                        return;
+                    }
+                    // The streams follow the type, flags, length and
+                    // association id.
+                    let mut offset = 12;
+                    while offset + 2 <= notification.len() {
+                        self.events.push(AssociationEvent::StreamReset(read_u16(notification, offset)));
+                        offset += 2;
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        pub fn take_events(&mut self) -> Vec<AssociationEvent> {
+            mem::replace(&mut self.events, vec![])
+        }
+
+        /// Aborts the association.
+        pub fn close(&mut self) {
+            if self.closed {
+                return;
+            }
+            self.closed = true;
+            unsafe { usrsctp_close(self.socket) };
+            self.events.push(AssociationEvent::Closed);
+        }
+    }
+
+    impl Drop for Association {
+        fn drop(&mut self) {
+            self.close();
+            // usrsctp forgets the address before it returns, so no signal
+            // reaches the handler afterwards.
+            unsafe {
+                usrsctp_deregister_address(self.handler as *mut c_void);
+                Box::from_raw(self.handler);
+            }
+        }
+    }
+}
+
+/// There is no SCTP without usrsctp, so data channels never open.
+#[cfg(not(feature = "usrsctp"))]
+mod usrsctp {
+    use super::{AssociationEvent, Reliability, SctpSignalHandler};
+
+    pub enum Association {}
+
+    impl Association {
+        pub fn new(_local_port: u16, _remote_port: u16, _handler: SctpSignalHandler) -> Result<Association, ()> {
+            warn!("Data channels need usrsctp");
+            Err(())
+        }
+
+        pub fn connect(&mut self) {
+            match *self {}
+        }
+
+        pub fn is_established(&self) -> bool {
+            match *self {}
+        }
+
+        pub fn handle_packet(&mut self, _packet: &[u8]) {
+            match *self {}
+        }
+
+        pub fn send(&mut self, _stream_id: u16, _ppid: u32, _unordered: bool, _reliability: Reliability,
+                    _data: &[u8]) {
+            match *self {}
+        }
+
+        pub fn reset_stream(&mut self, _stream_id: u16) {
+            match *self {}
+        }
+
+        pub fn poll(&mut self) {
+            match *self {}
+        }
+
+        pub fn take_events(&mut self) -> Vec<AssociationEvent> {
+            match *self {}
+        }
+
+        pub fn close(&mut self) {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,382 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The messages that script exchanges with the WebRTC thread of the `webrtc`
+//! crate, which runs the transports of peer connections, and the session
+//! descriptions that peers exchange to set them up.
+
+#![feature(custom_attribute)]
+#![feature(custom_derive)]
+#![feature(plugin)]
+#![plugin(heapsize_plugin, serde_macros)]
+
+#![deny(unsafe_code)]
+
+extern crate heapsize;
+extern crate ipc_channel;
+extern crate rand;
+extern crate serde;
+
+pub mod sdp;
+
+use ipc_channel::ipc::IpcSender;
+use std::ascii::AsciiExt;
+use std::fmt::{self, Write};
+use std::net::{IpAddr, SocketAddr};
+
+const DEFAULT_STUN_PORT: u16 = 3478;
+/// The SCTP port of data channels, unless a description says otherwise.
+pub const DEFAULT_SCTP_PORT: u16 = 5000;
+
+/// Parses the URL of a STUN server, returning its host and port.
+/// https://tools.ietf.org/html/rfc7064#section-3.1
+pub fn parse_stun_url(url: &str) -> Option<(String, u16)> {
+    let address = if url.starts_with("stun:") {
+        &url["stun:".len()..]
+    } else if url.starts_with("stuns:") {
+        &url["stuns:".len()..]
+    } else {
+        return None;
+    };
+    let (host, port) = if address.starts_with('[') {
+        let end = match address.find(']') {
+            Some(end) => end,
+            None => return None,
+        };
+        (&address[1..end], &address[end + 1..])
+    } else {
+        match address.rfind(':') {
+            Some(colon) => (&address[..colon], &address[colon..]),
+            None => (address, ""),
+        }
+    };
+    if host.is_empty() {
+        return None;
+    }
+    let port = if port.is_empty() {
+        DEFAULT_STUN_PORT
+    } else if port.starts_with(':') {
+        match port[1..].parse() {
+            Ok(port) => port,
+            Err(_) => return None,
+        }
+    } else {
+        return None;
+    };
+    Some((host.to_owned(), port))
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CandidateType {
+    Host,
+    ServerReflexive,
+    PeerReflexive,
+    Relay,
+}
+
+impl CandidateType {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            CandidateType::Host => "host",
+            CandidateType::ServerReflexive => "srflx",
+            CandidateType::PeerReflexive => "prflx",
+            CandidateType::Relay => "relay",
+        }
+    }
+}
+
+/// An address that a peer can be reached at, over UDP.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Candidate {
+    pub foundation: String,
+    pub component: u16,
+    pub priority: u32,
+    pub address: SocketAddr,
+    pub candidate_type: CandidateType,
+    /// The address that a reflexive candidate was found from.
+    pub related_address: Option<SocketAddr>,
+}
+
+impl Candidate {
+    /// Parses the value of an `a=candidate` line, with or without the
+    /// `candidate:` prefix. Candidates for other transports than UDP, and
+    /// those with a host name rather than an address, are rejected.
+    /// https://tools.ietf.org/html/rfc8839#section-5.1
+    pub fn parse(value: &str) -> Result<Candidate, ()> {
+        let value = value.trim();
+        let value = value.trim_left_matches("a=");
+        let value = if value.starts_with("candidate:") { &value["candidate:".len()..] } else { value };
+        let fields: Vec<_> = value.split_whitespace().collect();
+        if fields.len() < 8 || fields[6] != "typ" || !fields[2].eq_ignore_ascii_case("udp") {
+            return Err(());
+        }
+        let ip: IpAddr = try!(fields[4].parse().map_err(|_| ()));
+        let port: u16 = try!(fields[5].parse().map_err(|_| ()));
+        let candidate_type = match fields[7] {
+            "host" => CandidateType::Host,
+            "srflx" => CandidateType::ServerReflexive,
+            "prflx" => CandidateType::PeerReflexive,
+            "relay" => CandidateType::Relay,
+            _ => return Err(()),
+        };
+
+        // The extensions come in name and value pairs.
+        let mut related_ip = None;
+        let mut related_port = None;
+        for extension in fields[8..].chunks(2) {
+            match (extension[0], extension.get(1)) {
+                ("raddr", Some(ip)) => related_ip = ip.parse().ok(),
+                ("rport", Some(port)) => related_port = port.parse().ok(),
+                _ => {},
+            }
+        }
+        Ok(Candidate {
+            foundation: fields[0].to_owned(),
+            component: try!(fields[1].parse().map_err(|_| ())),
+            priority: try!(fields[3].parse().map_err(|_| ())),
+            address: SocketAddr::new(ip, port),
+            candidate_type: candidate_type,
+            related_address: match (related_ip, related_port) {
+                (Some(ip), Some(port)) => Some(SocketAddr::new(ip, port)),
+                _ => None,
+            },
+        })
+    }
+
+    /// The value of the `a=candidate` line of the candidate, which follows
+    /// `candidate:` in the `candidate` attribute of an `RTCIceCandidate`.
+    pub fn to_sdp_value(&self) -> String {
+        let mut value = format!("{} {} udp {} {} {} typ {}",
+                                self.foundation,
+                                self.component,
+                                self.priority,
+                                self.address.ip(),
+                                self.address.port(),
+                                self.candidate_type.as_str());
+        if let Some(related) = self.related_address {
+            let _ = write!(value, " raddr {} rport {}", related.ip(), related.port());
+        }
+        value.push_str(" generation 0");
+        value
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum IceConnectionState {
+    New,
+    Checking,
+    Connected,
+    Disconnected,
+    Failed,
+    Closed,
+}
+
+/// The username fragment and password of an agent.
+/// https://tools.ietf.org/html/rfc8445#section-5.3
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IceCredentials {
+    pub ufrag: String,
+    pub pwd: String,
+}
+
+impl IceCredentials {
+    /// Creates random credentials, with the minimum sizes of 24 and 128 bits.
+    pub fn generate() -> IceCredentials {
+        IceCredentials {
+            ufrag: random_string(4),
+            pwd: random_string(22),
+        }
+    }
+}
+
+/// A random string of the characters of `ice-char`.
+fn random_string(length: usize) -> String {
+    const CHARS: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    (0..length).map(|_| CHARS[rand::random::<usize>() % CHARS.len()] as char).collect()
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, HeapSizeOf, PartialEq, Serialize)]
+pub enum DtlsState {
+    New,
+    Connecting,
+    Connected,
+    Closed,
+    Failed,
+}
+
+/// The hash of the certificate of a peer, from an `a=fingerprint` line.
+#[derive(Clone, Debug, Deserialize, HeapSizeOf, PartialEq, Serialize)]
+pub struct Fingerprint {
+    /// The hash function, in lower case, e.g. `sha-256`.
+    pub algorithm: String,
+    pub value: Vec<u8>,
+}
+
+impl Fingerprint {
+    pub fn parse(value: &str) -> Result<Fingerprint, ()> {
+        let mut parts = value.split_whitespace();
+        let algorithm = try!(parts.next().ok_or(())).to_lowercase();
+        let bytes = try!(parts.next().ok_or(()));
+        let value = try!(bytes.split(':').map(|byte| {
+            if byte.len() != 2 {
+                return Err(());
+            }
+            u8::from_str_radix(byte, 16).map_err(|_| ())
+        }).collect());
+        Ok(Fingerprint {
+            algorithm: algorithm,
+            value: value,
+        })
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(f.write_str(&self.algorithm));
+        for (index, byte) in self.value.iter().enumerate() {
+            try!(f.write_char(if index == 0 { ' ' } else { ':' }));
+            try!(write!(f, "{:02X}", byte));
+        }
+        Ok(())
+    }
+}
+
+/// A self-signed certificate and its private key, which are kept encoded so
+/// that they can be sent to the thread of a transport.
+/// https://w3c.github.io/webrtc-pc/#dom-rtccertificate
+#[derive(Clone, Deserialize, HeapSizeOf, Serialize)]
+pub struct Certificate {
+    /// The certificate, in PEM.
+    pub pem: Vec<u8>,
+    /// The private key, in DER.
+    pub private_key_der: Vec<u8>,
+    /// The SHA-256 fingerprint of the certificate, which goes in local
+    /// descriptions.
+    pub fingerprint: Fingerprint,
+}
+
+/// The DTLS role that a description asks for.
+/// https://tools.ietf.org/html/rfc5763#section-5
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Setup {
+    Active,
+    Passive,
+    ActPass,
+}
+
+impl Setup {
+    pub fn parse(value: &str) -> Result<Setup, ()> {
+        match value.trim() {
+            "active" => Ok(Setup::Active),
+            "passive" => Ok(Setup::Passive),
+            "actpass" => Ok(Setup::ActPass),
+            _ => Err(()),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Setup::Active => "active",
+            Setup::Passive => "passive",
+            Setup::ActPass => "actpass",
+        }
+    }
+}
+
+/// What the transport of a peer connection needs to know about the other
+/// peer, from its description.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RemoteParameters {
+    pub ice_ufrag: String,
+    pub ice_pwd: String,
+    /// Whether the peer is an ICE lite implementation, which only answers
+    /// checks.
+    pub ice_lite: bool,
+    pub fingerprint: Fingerprint,
+    pub setup: Setup,
+    pub sctp_port: u16,
+    pub max_message_size: u64,
+    pub candidates: Vec<Candidate>,
+}
+
+/// What a channel was created with.
+#[derive(Clone, Debug, Deserialize, HeapSizeOf, PartialEq, Serialize)]
+pub struct ChannelParameters {
+    pub label: String,
+    pub protocol: String,
+    pub ordered: bool,
+    pub max_packet_life_time: Option<u16>,
+    pub max_retransmits: Option<u16>,
+    pub priority: u16,
+}
+
+/// A message sent on a channel.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ChannelMessage {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl ChannelMessage {
+    /// The size of the message, as counted by `bufferedAmount`.
+    pub fn size(&self) -> usize {
+        match *self {
+            ChannelMessage::Text(ref text) => text.len(),
+            ChannelMessage::Binary(ref data) => data.len(),
+        }
+    }
+}
+
+/// What a transport is started with, from the local description.
+#[derive(Deserialize, Serialize)]
+pub struct TransportConfig {
+    pub credentials: IceCredentials,
+    /// Whether the local ICE agent is the controlling one, which the
+    /// offerer is.
+    pub controlling: bool,
+    pub certificate: Certificate,
+    /// The `stun:` URLs of the configured ICE servers.
+    pub stun_urls: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum TransportMsg {
+    /// The parameters of the remote description.
+    SetRemote(RemoteParameters),
+    AddRemoteCandidate(Candidate),
+    /// Opens the channel of the given stream, announcing it to the other
+    /// peer unless it was negotiated.
+    OpenChannel(u16, ChannelParameters, bool),
+    Send(u16, ChannelMessage),
+    CloseChannel(u16),
+    Close,
+}
+
+/// Notifications from the transport to its connection.
+#[derive(Deserialize, Serialize)]
+pub enum TransportEvent {
+    IceCandidate(Candidate),
+    IceGatheringComplete,
+    IceStateChanged(IceConnectionState),
+    DtlsStateChanged(DtlsState),
+    /// A channel that was opened locally can be sent on.
+    ChannelOpened(u16),
+    /// The other peer opened a channel.
+    IncomingChannel(u16, ChannelParameters),
+    ChannelMessage(u16, ChannelMessage),
+    /// Bytes queued on a channel were sent.
+    ChannelBufferedAmountDecreased(u16, usize),
+    ChannelClosed(u16),
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum WebRTCMsg {
+    /// Generates the certificate of a peer connection.
+    GenerateCertificate(IpcSender<Result<Certificate, ()>>),
+    /// Binds a socket for a transport and starts gathering candidates on
+    /// it. The transport sends its events to the first sender, and is
+    /// closed when the sender of its messages is dropped.
+    StartTransport(TransportConfig, IpcSender<TransportEvent>, IpcSender<Result<IpcSender<TransportMsg>, ()>>),
+    Exit,
+}
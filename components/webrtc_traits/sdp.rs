@@ -0,0 +1,384 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Session descriptions, as exchanged in offers and answers.
+//! https://tools.ietf.org/html/rfc4566
+//!
+//! Only the lines that a peer connection needs are given a meaning; the
+//! others are kept as they are, so that a description survives being parsed
+//! and written again.
+
+use {Candidate, DEFAULT_SCTP_PORT, Fingerprint, RemoteParameters, Setup};
+use std::fmt;
+
+/// The protocol of data channels, since RFC 8841.
+pub const SCTP_PROTOCOL: &'static str = "UDP/DTLS/SCTP";
+/// The protocol of data channels that older implementations still send.
+pub const LEGACY_SCTP_PROTOCOL: &'static str = "DTLS/SCTP";
+/// The size of the largest message a peer takes when it doesn't say.
+/// https://tools.ietf.org/html/rfc8841#section-6.1
+pub const DEFAULT_MAX_MESSAGE_SIZE: u64 = 65536;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attribute {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl Attribute {
+    pub fn new(name: &str, value: Option<String>) -> Attribute {
+        Attribute {
+            name: name.to_owned(),
+            value: value,
+        }
+    }
+
+    fn parse(line: &str) -> Attribute {
+        let mut parts = line.splitn(2, ':');
+        let name = parts.next().unwrap_or("");
+        Attribute::new(name, parts.next().map(|value| value.to_owned()))
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            Some(ref value) => write!(f, "a={}:{}\r\n", self.name, value),
+            None => write!(f, "a={}\r\n", self.name),
+        }
+    }
+}
+
+/// An `m=` section and the lines that follow it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaDescription {
+    /// `audio`, `video` or `application`.
+    pub kind: String,
+    /// The port, which is 0 in a rejected section.
+    pub port: u16,
+    pub protocol: String,
+    pub formats: Vec<String>,
+    /// The lines other than attributes, e.g. `c=`.
+    pub lines: Vec<String>,
+    pub attributes: Vec<Attribute>,
+}
+
+impl MediaDescription {
+    /// The value of the first attribute named `name`, or the empty string if
+    /// it has none.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+                       .find(|attribute| attribute.name == name)
+                       .map(|attribute| attribute.value.as_ref().map_or("", |value| &**value))
+    }
+
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attribute(name).is_some()
+    }
+
+    pub fn mid(&self) -> Option<&str> {
+        self.attribute("mid")
+    }
+
+    /// Whether the section carries data channels.
+    pub fn is_data_channel(&self) -> bool {
+        self.kind == "application" &&
+        (self.protocol == SCTP_PROTOCOL || self.protocol == LEGACY_SCTP_PROTOCOL)
+    }
+
+    /// The SCTP port, which legacy descriptions give as their format.
+    pub fn sctp_port(&self) -> u16 {
+        if let Some(port) = self.attribute("sctp-port").and_then(|port| port.trim().parse().ok()) {
+            return port;
+        }
+        if self.protocol == LEGACY_SCTP_PROTOCOL {
+            if let Some(port) = self.formats.first().and_then(|port| port.parse().ok()) {
+                return port;
+            }
+        }
+        DEFAULT_SCTP_PORT
+    }
+
+    pub fn max_message_size(&self) -> u64 {
+        self.attribute("max-message-size")
+            .and_then(|size| size.trim().parse().ok())
+            .unwrap_or(DEFAULT_MAX_MESSAGE_SIZE)
+    }
+
+    /// The candidates that the section lists, ignoring the ones that can't
+    /// be used.
+    pub fn candidates(&self) -> Vec<Candidate> {
+        self.attributes.iter()
+                       .filter(|attribute| attribute.name == "candidate")
+                       .filter_map(|attribute| attribute.value.as_ref())
+                       .filter_map(|value| Candidate::parse(value).ok())
+                       .collect()
+    }
+}
+
+impl fmt::Display for MediaDescription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "m={} {} {}", self.kind, self.port, self.protocol));
+        for format in &self.formats {
+            try!(write!(f, " {}", format));
+        }
+        try!(f.write_str("\r\n"));
+        for line in &self.lines {
+            try!(write!(f, "{}\r\n", line));
+        }
+        for attribute in &self.attributes {
+            try!(attribute.fmt(f));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionDescription {
+    pub session_id: u64,
+    pub session_version: u64,
+    /// The session-level attributes.
+    pub attributes: Vec<Attribute>,
+    pub media: Vec<MediaDescription>,
+}
+
+impl SessionDescription {
+    pub fn new(session_id: u64, session_version: u64) -> SessionDescription {
+        SessionDescription {
+            session_id: session_id,
+            session_version: session_version,
+            attributes: vec![],
+            media: vec![],
+        }
+    }
+
+    pub fn parse(sdp: &str) -> Result<SessionDescription, ()> {
+        let mut description = SessionDescription::new(0, 0);
+        let mut lines = sdp.lines().map(|line| line.trim_right_matches('\r')).filter(|line| !line.is_empty());
+        if lines.next() != Some("v=0") {
+            return Err(());
+        }
+        let mut has_origin = false;
+        for line in lines {
+            let mut parts = line.splitn(2, '=');
+            let (kind, value) = match (parts.next(), parts.next()) {
+                (Some(kind), Some(value)) if kind.len() == 1 => (kind, value),
+                _ => return Err(()),
+            };
+            match kind {
+                "o" => {
+                    // o=<username> <sess-id> <sess-version> <nettype> <addrtype> <address>
+                    let fields: Vec<_> = value.split_whitespace().collect();
+                    if fields.len() != 6 {
+                        return Err(());
+                    }
+                    description.session_id = try!(fields[1].parse().map_err(|_| ()));
+                    description.session_version = try!(fields[2].parse().map_err(|_| ()));
+                    has_origin = true;
+                },
+                "m" => {
+                    let mut fields = value.split_whitespace();
+                    let kind = try!(fields.next().ok_or(()));
+                    // The port can be followed by a number of ports.
+                    let port = try!(fields.next().ok_or(()));
+                    let port = try!(port.split('/').next().unwrap_or("").parse().map_err(|_| ()));
+                    let protocol = try!(fields.next().ok_or(()));
+                    description.media.push(MediaDescription {
+                        kind: kind.to_owned(),
+                        port: port,
+                        protocol: protocol.to_owned(),
+                        formats: fields.map(|format| format.to_owned()).collect(),
+                        lines: vec![],
+                        attributes: vec![],
+                    });
+                },
+                "a" => {
+                    let attribute = Attribute::parse(value);
+                    match description.media.last_mut() {
+                        Some(media) => media.attributes.push(attribute),
+                        None => description.attributes.push(attribute),
+                    }
+                },
+                _ => {
+                    if let Some(media) = description.media.last_mut() {
+                        media.lines.push(line.to_owned());
+                    }
+                },
+            }
+        }
+        if !has_origin {
+            return Err(());
+        }
+        Ok(description)
+    }
+
+    /// The value of the first session-level attribute named `name`.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+                       .find(|attribute| attribute.name == name)
+                       .map(|attribute| attribute.value.as_ref().map_or("", |value| &**value))
+    }
+
+    /// The value of an attribute of `media`, which defaults to that of the
+    /// session.
+    pub fn media_attribute<'a>(&'a self, media: &'a MediaDescription, name: &str) -> Option<&'a str> {
+        media.attribute(name).or_else(|| self.attribute(name))
+    }
+
+    /// The section that carries data channels, if any is accepted.
+    pub fn data_channel_media(&self) -> Option<&MediaDescription> {
+        self.media.iter().find(|media| media.is_data_channel() && media.port != 0)
+    }
+
+    /// The index and the mid of the section that a candidate is for, given
+    /// either of them.
+    pub fn find_media(&self, mid: Option<&str>, index: Option<u16>) -> Option<usize> {
+        if let Some(mid) = mid {
+            return self.media.iter().position(|media| media.mid() == Some(mid));
+        }
+        index.map(|index| index as usize).and_then(|index| {
+            if index < self.media.len() { Some(index) } else { None }
+        })
+    }
+}
+
+impl fmt::Display for SessionDescription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(f.write_str("v=0\r\n"));
+        try!(write!(f, "o=- {} {} IN IP4 0.0.0.0\r\n", self.session_id, self.session_version));
+        try!(f.write_str("s=-\r\n"));
+        try!(f.write_str("t=0 0\r\n"));
+        for attribute in &self.attributes {
+            try!(attribute.fmt(f));
+        }
+        for media in &self.media {
+            try!(media.fmt(f));
+        }
+        Ok(())
+    }
+}
+
+/// Reads what the transport needs to know about the other peer from the data
+/// channel section of its description, returning `Ok(None)` if there is no
+/// such section.
+pub fn remote_parameters(description: &SessionDescription) -> Result<Option<RemoteParameters>, ()> {
+    let media = match description.data_channel_media() {
+        Some(media) => media,
+        None => return Ok(None),
+    };
+    let ice_ufrag = try!(description.media_attribute(media, "ice-ufrag").ok_or(()));
+    let ice_pwd = try!(description.media_attribute(media, "ice-pwd").ok_or(()));
+    let fingerprint = try!(description.media_attribute(media, "fingerprint").ok_or(()));
+    let setup = description.media_attribute(media, "setup").map_or(Ok(Setup::ActPass), Setup::parse);
+    Ok(Some(RemoteParameters {
+        ice_ufrag: ice_ufrag.to_owned(),
+        ice_pwd: ice_pwd.to_owned(),
+        ice_lite: description.attribute("ice-lite").is_some(),
+        fingerprint: try!(Fingerprint::parse(fingerprint)),
+        setup: try!(setup),
+        sctp_port: media.sctp_port(),
+        max_message_size: media.max_message_size(),
+        candidates: media.candidates(),
+    }))
+}
+
+/// What goes in the data channel section of a local description.
+pub struct LocalParameters<'a> {
+    pub ice_ufrag: &'a str,
+    pub ice_pwd: &'a str,
+    pub fingerprint: &'a Fingerprint,
+    pub setup: Setup,
+    pub max_message_size: u64,
+}
+
+fn data_channel_media(mid: &str, protocol: &str, parameters: &LocalParameters) -> MediaDescription {
+    let mut attributes = vec![
+        Attribute::new("ice-ufrag", Some(parameters.ice_ufrag.to_owned())),
+        Attribute::new("ice-pwd", Some(parameters.ice_pwd.to_owned())),
+        Attribute::new("ice-options", Some("trickle".to_owned())),
+        Attribute::new("fingerprint", Some(parameters.fingerprint.to_string())),
+        Attribute::new("setup", Some(parameters.setup.as_str().to_owned())),
+        Attribute::new("mid", Some(mid.to_owned())),
+    ];
+    let formats = if protocol == LEGACY_SCTP_PROTOCOL {
+        attributes.push(Attribute::new("sctpmap", Some(format!("{} webrtc-datachannel 1024", DEFAULT_SCTP_PORT))));
+        vec![DEFAULT_SCTP_PORT.to_string()]
+    } else {
+        attributes.push(Attribute::new("sctp-port", Some(DEFAULT_SCTP_PORT.to_string())));
+        attributes.push(Attribute::new("max-message-size", Some(parameters.max_message_size.to_string())));
+        vec!["webrtc-datachannel".to_owned()]
+    };
+    MediaDescription {
+        kind: "application".to_owned(),
+        port: 9,
+        protocol: protocol.to_owned(),
+        formats: formats,
+        lines: vec!["c=IN IP4 0.0.0.0".to_owned()],
+        attributes: attributes,
+    }
+}
+
+/// Creates an offer, with a data channel section if `data_channels` is set.
+/// https://w3c.github.io/webrtc-pc/#dfn-create-an-offer
+pub fn create_offer(session_id: u64,
+                    session_version: u64,
+                    data_channels: bool,
+                    parameters: &LocalParameters)
+                    -> SessionDescription {
+    let mut offer = SessionDescription::new(session_id, session_version);
+    if data_channels {
+        offer.attributes.push(Attribute::new("group", Some("BUNDLE 0".to_owned())));
+        offer.media.push(data_channel_media("0", SCTP_PROTOCOL, parameters));
+    }
+    offer.attributes.push(Attribute::new("msid-semantic", Some(" WMS".to_owned())));
+    offer
+}
+
+/// Answers `offer`, accepting its first data channel section and rejecting
+/// the others, since audio and video aren't supported yet.
+/// https://w3c.github.io/webrtc-pc/#dfn-create-an-answer
+pub fn create_answer(session_id: u64,
+                     session_version: u64,
+                     offer: &SessionDescription,
+                     parameters: &LocalParameters)
+                     -> SessionDescription {
+    let mut answer = SessionDescription::new(session_id, session_version);
+    let mut accepted = None;
+    for (index, media) in offer.media.iter().enumerate() {
+        let mid = media.mid().map_or(index.to_string(), |mid| mid.to_owned());
+        if accepted.is_none() && media.is_data_channel() && media.port != 0 {
+            answer.media.push(data_channel_media(&mid, &media.protocol, parameters));
+            accepted = Some(mid);
+        } else {
+            answer.media.push(MediaDescription {
+                kind: media.kind.clone(),
+                port: 0,
+                protocol: media.protocol.clone(),
+                formats: media.formats.clone(),
+                lines: vec!["c=IN IP4 0.0.0.0".to_owned()],
+                attributes: vec![Attribute::new("mid", Some(mid))],
+            });
+        }
+    }
+    if let Some(mid) = accepted {
+        answer.attributes.push(Attribute::new("group", Some(format!("BUNDLE {}", mid))));
+    }
+    answer.attributes.push(Attribute::new("msid-semantic", Some(" WMS".to_owned())));
+    answer
+}
+
+/// Adds `candidates` to the data channel section of a local description,
+/// and says that there are no others if gathering is `complete`.
+pub fn add_candidates(description: &mut SessionDescription, candidates: &[Candidate], complete: bool) {
+    let media = match description.media.iter_mut().find(|media| media.is_data_channel() && media.port != 0) {
+        Some(media) => media,
+        None => return,
+    };
+    for candidate in candidates {
+        media.attributes.push(Attribute::new("candidate", Some(candidate.to_sdp_value())));
+    }
+    if complete {
+        media.attributes.push(Attribute::new("end-of-candidates", None));
+    }
+}
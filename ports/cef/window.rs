@@ -12,28 +12,31 @@ use core::CEF_APP;
 use eutil::Downcast;
 #[cfg(target_os="linux")]
 use interfaces::CefApp;
-use interfaces::CefBrowser;
+use interfaces::{CefBrowser, CefJSDialogCallback, cef_jsdialog_callback_t};
 use render_handler::CefRenderHandlerExtensions;
 use rustc_unicode::str::Utf16Encoder;
-use types::{cef_cursor_handle_t, cef_cursor_type_t, cef_rect_t};
+use types::{cef_cursor_handle_t, cef_cursor_type_t, cef_jsdialog_type_t, cef_rect_t, cef_string_t};
 use wrappers::CefWrap;
 
 use compositing::compositor_thread::{self, CompositorProxy, CompositorReceiver};
-use compositing::windowing::{WindowEvent, WindowMethods};
+use compositing::windowing::{DialogResponder, ShareResponder, WindowEvent, WindowMethods};
 use euclid::point::Point2D;
 use euclid::scale_factor::ScaleFactor;
 use euclid::size::{Size2D, TypedSize2D};
 use gleam::gl;
+use glutin_app::window::show_native_dialog;
 use layers::geometry::DevicePixel;
 use layers::platform::surface::NativeDisplay;
 use msg::constellation_msg::{Key, KeyModifiers};
 use net_traits::net_error_list::NetError;
+use script_traits::{DialogResponse, InputPickerKind, ShareData, ShareError, SimpleDialog};
 use std::cell::RefCell;
 use std::ffi::CString;
-use std::os::raw::{c_char, c_void};
+use std::os::raw::{c_char, c_int, c_void};
 use std::ptr;
 use std::rc::Rc;
 use std::sync::mpsc::{Sender, channel};
+use std::thread;
 use std_url::Url;
 use style_traits::cursor::Cursor;
 use util::geometry::ScreenPx;
@@ -168,6 +171,90 @@ impl Window {
     fn cursor_handle_for_cursor(&self, _: Cursor) -> cef_cursor_handle_t {
         0
     }
+
+    /// Hands a dialog to the CefJSDialogHandler of the client. The responder is given back if
+    /// the client has no handler, or left the dialog to the default implementation.
+    fn forward_dialog(&self, origin: &str, dialog: &SimpleDialog, responder: DialogResponder)
+                      -> Option<DialogResponder> {
+        let browser = self.cef_browser.borrow();
+        let browser = match *browser {
+            None => return Some(responder),
+            Some(ref browser) => browser,
+        };
+        if !check_ptr_exist!(browser.get_host().get_client(), get_jsdialog_handler) ||
+           !check_ptr_exist!(browser.get_host().get_client().get_jsdialog_handler(), on_jsdialog) {
+            return Some(responder);
+        }
+        let (dialog_type, message, default) = match *dialog {
+            SimpleDialog::Alert(ref message) => (cef_jsdialog_type_t::JSDIALOGTYPE_ALERT, message, ""),
+            SimpleDialog::Confirm(ref message) => (cef_jsdialog_type_t::JSDIALOGTYPE_CONFIRM, message, ""),
+            SimpleDialog::Prompt(ref message, ref default) => {
+                (cef_jsdialog_type_t::JSDIALOGTYPE_PROMPT, message, &**default)
+            },
+        };
+        let prompt = match *dialog {
+            SimpleDialog::Prompt(..) => true,
+            _ => false,
+        };
+        let origin: Vec<u16> = Utf16Encoder::new(origin.chars()).collect();
+        let message: Vec<u16> = Utf16Encoder::new(message.chars()).collect();
+        let default: Vec<u16> = Utf16Encoder::new(default.chars()).collect();
+        let callback = ServoCefJSDialogCallback {
+            responder: RefCell::new(Some(responder)),
+            prompt: prompt,
+        }.as_cef_interface();
+        let mut suppress_message = 0;
+        let handled = browser.get_host().get_client().get_jsdialog_handler().on_jsdialog((*browser).clone(),
+                                                                                        origin.as_slice(),
+                                                                                        &[],
+                                                                                        dialog_type,
+                                                                                        message.as_slice(),
+                                                                                        default.as_slice(),
+                                                                                        callback.clone(),
+                                                                                        &mut suppress_message);
+        if handled != 0 {
+            // The client answers through the callback.
+            return None;
+        }
+        let responder = callback.downcast().responder.borrow_mut().take();
+        if suppress_message != 0 {
+            // The client asked for the dialog to be cancelled without showing it.
+            if let Some(responder) = responder {
+                responder.respond(DialogResponse::dismissed());
+            }
+            return None;
+        }
+        responder
+    }
+}
+
+/// The callback through which the client answers a dialog it handled.
+pub struct ServoCefJSDialogCallback {
+    /// Taken by the first answer.
+    responder: RefCell<Option<DialogResponder>>,
+    /// Whether the dialog was a prompt, the only one the input of which is answered.
+    prompt: bool,
+}
+
+full_cef_class_impl! {
+    ServoCefJSDialogCallback : CefJSDialogCallback, cef_jsdialog_callback_t {
+        fn cont(&this, success: c_int [c_int], user_input: *const cef_string_t [&[u16]],) -> () {{
+            let responder = this.downcast().responder.borrow_mut().take();
+            if let Some(responder) = responder {
+                let accepted = success != 0;
+                let text = if accepted && this.downcast().prompt {
+                    Some(String::from_utf16_lossy(user_input))
+                } else {
+                    None
+                };
+                responder.respond(DialogResponse {
+                    accepted: accepted,
+                    text: text,
+                    prevent_additional_dialogs: false,
+                });
+            }
+        }}
+    }
 }
 
 impl WindowMethods for Window {
@@ -335,6 +422,43 @@ impl WindowMethods for Window {
         browser.downcast().favicons.borrow_mut().push(url.to_string().clone());
     }
 
+    fn prompt_media_permission(&self, _origin: String, _audio: bool, _video: bool) -> bool {
+        // TODO: ask the embedding application, through the CEF permission callbacks.
+        false
+    }
+
+    fn allow_persistent_storage(&self, _origin: String) -> bool {
+        // TODO: ask the embedding application, through the CEF permission callbacks.
+        false
+    }
+
+    fn choose_usb_device(&self, _origin: String, _devices: Vec<(String, String)>) -> Option<String> {
+        // TODO: ask the embedding application, which CEF has no chooser handler for yet.
+        None
+    }
+
+    fn show_input_picker(&self, _kind: InputPickerKind, _value: String) -> Option<String> {
+        // TODO: forward to the embedding application through CefDialogHandler.
+        None
+    }
+
+    fn show_dialog(&self, origin: String, dialog: SimpleDialog, responder: DialogResponder) {
+        if let Some(responder) = self.forward_dialog(&origin, &dialog, responder) {
+            thread::Builder::new().name("DialogThread".to_owned()).spawn(move || {
+                responder.respond(show_native_dialog(&origin, dialog));
+            }).expect("Thread spawning failed");
+        }
+    }
+
+    fn share(&self, _origin: String, _data: ShareData, responder: ShareResponder) {
+        // TODO: forward to the embedding application, which CEF has no share handler for yet.
+        responder.respond(Err(ShareError::Failed));
+    }
+
+    fn audio_playback_changed(&self, _audible: bool) {
+        // TODO: forward to the embedding application, which CEF has no handler for yet.
+    }
+
     fn status(&self, info: Option<String>) {
         let browser = self.cef_browser.borrow();
         let browser = match *browser {
@@ -7,6 +7,7 @@
 use app_units::Au;
 use env_logger;
 use euclid::Size2D;
+use std::collections::HashMap;
 use std::mem::transmute;
 use std::ptr;
 use std::slice;
@@ -103,6 +104,7 @@ fn restyle_subtree(node: GeckoNode, raw_data: RawServoStyleSetBorrowedMut) {
         error_reporter: Box::new(StdoutErrorReporter),
         local_context_creation_data: Mutex::new(local_context_data),
         timer: Timer::new(),
+        container_sizes: Arc::new(HashMap::new()),
     };
 
     // We ensure this is true before calling Servo_RestyleSubtree()
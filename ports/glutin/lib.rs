@@ -20,6 +20,7 @@ extern crate net_traits;
 #[cfg(any(target_os = "linux", target_os = "macos"))] extern crate osmesa_sys;
 extern crate script_traits;
 extern crate style_traits;
+#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))] extern crate tinyfiledialogs;
 extern crate url;
 extern crate util;
 #[cfg(target_os = "linux")] extern crate x11;
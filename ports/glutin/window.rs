@@ -6,7 +6,7 @@
 
 use NestedEventLoopListener;
 use compositing::compositor_thread::{self, CompositorProxy, CompositorReceiver};
-use compositing::windowing::{MouseWindowEvent, WindowNavigateMsg};
+use compositing::windowing::{DialogResponder, MouseWindowEvent, ShareResponder, WindowNavigateMsg};
 use compositing::windowing::{WindowEvent, WindowMethods};
 use euclid::{Point2D, Size2D, TypedPoint2D};
 use euclid::scale_factor::ScaleFactor;
@@ -26,6 +26,7 @@ use msg::constellation_msg::{ALT, CONTROL, KeyState, NONE, SHIFT, SUPER};
 use net_traits::net_error_list::NetError;
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 use osmesa_sys;
+use script_traits::{DialogResponse, InputPickerKind, ShareData, ShareError, SimpleDialog};
 use script_traits::{TouchEventType, TouchpadPressurePhase};
 use std::cell::{Cell, RefCell};
 #[cfg(any(target_os = "linux", target_os = "macos"))]
@@ -37,7 +38,10 @@ use std::os::raw::c_void;
 use std::ptr;
 use std::rc::Rc;
 use std::sync::mpsc::{Sender, channel};
+use std::thread;
 use style_traits::cursor::Cursor;
+#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
+use tinyfiledialogs::{self, MessageBoxIcon, OkCancel};
 use url::Url;
 #[cfg(target_os = "windows")]
 use user32;
@@ -194,6 +198,9 @@ pub struct Window {
     /// The list of keys that have been pressed but not yet released, to allow providing
     /// the equivalent ReceivedCharacter data as was received for the press event.
     pressed_key_map: RefCell<Vec<(ScanCode, char)>>,
+
+    /// Whether the user muted the page.
+    muted: Cell<bool>,
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -284,6 +291,8 @@ impl Window {
 
             pending_key_event_char: Cell::new(None),
             pressed_key_map: RefCell::new(vec![]),
+
+            muted: Cell::new(false),
         };
 
         gl::clear_color(0.6, 0.6, 0.6, 1.0);
@@ -559,7 +568,6 @@ impl Window {
 
     #[cfg(any(target_os = "linux", target_os = "android"))]
     fn handle_next_event(&self) -> bool {
-        use std::thread;
         use std::time::Duration;
 
         // WebRender can use the normal blocking event check and proper vsync,
@@ -1028,6 +1036,68 @@ impl WindowMethods for Window {
     fn set_favicon(&self, _: Url) {
     }
 
+    fn prompt_media_permission(&self, origin: String, audio: bool, video: bool) -> bool {
+        // There is no browser chrome to ask the user with, so the answer is
+        // set ahead of time with a preference.
+        let granted = PREFS.get("dom.mediadevices.permission.granted").as_boolean().unwrap_or(false);
+        debug!("{} media permission to {} (audio: {}, video: {})",
+               if granted { "Granting" } else { "Denying" }, origin, audio, video);
+        granted
+    }
+
+    fn allow_persistent_storage(&self, origin: String) -> bool {
+        let granted = PREFS.get("dom.storagemanager.persist.granted").as_boolean().unwrap_or(false);
+        debug!("{} persistent storage to {}", if granted { "Granting" } else { "Denying" }, origin);
+        granted
+    }
+
+    fn choose_usb_device(&self, origin: String, devices: Vec<(String, String)>) -> Option<String> {
+        // Without a chooser, the first device is picked if a preference allows it.
+        let chosen = if PREFS.get("dom.usb.chooser.accept_first").as_boolean().unwrap_or(false) {
+            devices.into_iter().next().map(|(id, _)| id)
+        } else {
+            None
+        };
+        debug!("Choosing USB device {:?} for {}", chosen, origin);
+        chosen
+    }
+
+    fn show_input_picker(&self, kind: InputPickerKind, value: String) -> Option<String> {
+        // Glutin has no native date, time or color dialogs; the control keeps its value.
+        debug!("No {:?} picker available (value: {})", kind, value);
+        None
+    }
+
+    fn show_dialog(&self, origin: String, dialog: SimpleDialog, responder: DialogResponder) {
+        if let WindowKind::Headless(..) = self.kind {
+            // Nobody is there to answer, so alerts are acknowledged and anything else cancelled.
+            let accepted = match dialog {
+                SimpleDialog::Alert(..) => true,
+                _ => false,
+            };
+            return responder.respond(DialogResponse { accepted: accepted, ..DialogResponse::dismissed() });
+        }
+        thread::Builder::new().name("DialogThread".to_owned()).spawn(move || {
+            responder.respond(show_native_dialog(&origin, dialog));
+        }).expect("Thread spawning failed");
+    }
+
+    fn share(&self, origin: String, data: ShareData, responder: ShareResponder) {
+        // There are no share targets to hand the data to; a preference lets tests pretend
+        // that it was shared.
+        debug!("Sharing {:?} ({:?}, {} files) from {}", data.url, data.title, data.files.len(), origin);
+        if PREFS.get("dom.webshare.accept").as_boolean().unwrap_or(false) {
+            responder.respond(Ok(()));
+        } else {
+            responder.respond(Err(ShareError::Failed));
+        }
+    }
+
+    fn audio_playback_changed(&self, audible: bool) {
+        // There is no browser chrome to show an indicator in.
+        debug!("The page {} making sound", if audible { "started" } else { "stopped" });
+    }
+
     fn prepare_for_composite(&self, _width: usize, _height: usize) -> bool {
         true
     }
@@ -1088,7 +1158,7 @@ impl WindowMethods for Window {
 
             (NONE, None, Key::Escape) => {
                 if let Some(true) = PREFS.get("shell.builtin-key-shortcuts.enabled").as_boolean() {
-                    self.event_queue.borrow_mut().push(WindowEvent::Quit);
+                    self.event_queue.borrow_mut().push(WindowEvent::Stop);
                 }
             }
 
@@ -1134,6 +1204,12 @@ impl WindowMethods for Window {
                     self.event_queue.borrow_mut().push(WindowEvent::Reload);
                 }
             }
+            (CMD_OR_CONTROL, Some('m'), _) => {
+                if let Some(true) = PREFS.get("shell.builtin-key-shortcuts.enabled").as_boolean() {
+                    self.muted.set(!self.muted.get());
+                    self.event_queue.borrow_mut().push(WindowEvent::SetMuted(self.muted.get()));
+                }
+            }
             (CMD_OR_CONTROL, Some('q'), _) => {
                 if let Some(true) = PREFS.get("shell.builtin-key-shortcuts.enabled").as_boolean() {
                     self.event_queue.borrow_mut().push(WindowEvent::Quit);
@@ -1308,3 +1384,34 @@ pub extern "C" fn glDiscardFramebufferEXT(_: isize, _: isize, _: *const ())
 {
     unimplemented!()
 }
+
+/// Shows a dialog with tinyfiledialogs, blocking until the user answered it.
+#[cfg(any(target_os = "macos", target_os = "linux", target_os = "windows"))]
+pub fn show_native_dialog(origin: &str, dialog: SimpleDialog) -> DialogResponse {
+    let (accepted, text) = match dialog {
+        SimpleDialog::Alert(message) => {
+            tinyfiledialogs::message_box_ok(origin, &message, MessageBoxIcon::Warning);
+            (true, None)
+        },
+        SimpleDialog::Confirm(message) => {
+            let answer = tinyfiledialogs::message_box_ok_cancel(origin, &message, MessageBoxIcon::Question,
+                                                                OkCancel::Cancel);
+            (answer == OkCancel::Ok, None)
+        },
+        SimpleDialog::Prompt(message, default) => {
+            let text = tinyfiledialogs::input_box(origin, &message, &default);
+            (text.is_some(), text)
+        },
+    };
+    DialogResponse {
+        accepted: accepted,
+        text: text,
+        prevent_additional_dialogs: false,
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+pub fn show_native_dialog(_origin: &str, _dialog: SimpleDialog) -> DialogResponse {
+    // tinyfiledialogs not supported on Android
+    DialogResponse::dismissed()
+}
@@ -0,0 +1,328 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! An in-process HTTP and HTTPS server with scripted responses, for testing fetch without
+//! external fixtures.
+//!
+//! A server is started with a set of `Routes`, each of which maps a path to a `Reply` or to a
+//! function of the request that returns one. Replies can be delayed, redirected and sent in
+//! chunks, and HTTPS servers can be made to fail the TLS handshake in the ways a client has
+//! to cope with. Every request the server receives is recorded, so that tests can check what
+//! was sent.
+//!
+//! ```ignore
+//! let server = TestServer::http(Routes::new()
+//!     .reply("/start", Reply::redirect(StatusCode::Found, "/end"))
+//!     .reply("/end", Reply::ok(b"done").chunked(2, Duration::from_millis(10))));
+//! let url = server.url_for("/start");
+//! ```
+//!
+//! The self-signed certificate in `certs/` is valid for `localhost` and was made with
+//! `openssl req -x509 -newkey rsa:2048 -nodes -days 36500 -subj "/CN=localhost"`.
+
+extern crate hyper;
+extern crate url;
+
+use hyper::header::{Header, HeaderFormat, Headers, Location};
+use hyper::method::Method;
+use hyper::net::{Fresh, Openssl};
+use hyper::server::{Handler, Listening, Request, Response, Server};
+use hyper::status::StatusCode;
+use hyper::uri::RequestUri;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use url::Url;
+
+/// The number of connections a server handles at the same time.
+const THREADS: usize = 8;
+
+/// A response that a server sends, and how it sends it.
+#[derive(Clone)]
+pub struct Reply {
+    status: StatusCode,
+    headers: Headers,
+    body: Vec<u8>,
+    /// How long to wait before sending the response.
+    delay: Option<Duration>,
+    /// The size of the chunks to send the body in, and how long to wait between them.
+    chunks: Option<(usize, Duration)>,
+}
+
+impl Reply {
+    pub fn new(status: StatusCode) -> Reply {
+        Reply {
+            status: status,
+            headers: Headers::new(),
+            body: vec![],
+            delay: None,
+            chunks: None,
+        }
+    }
+
+    /// A `200 OK` response with `body`.
+    pub fn ok(body: &[u8]) -> Reply {
+        Reply::new(StatusCode::Ok).body(body)
+    }
+
+    /// A redirect to `location`, which may be relative to the request URL.
+    pub fn redirect(status: StatusCode, location: &str) -> Reply {
+        Reply::new(status).header(Location(location.to_owned()))
+    }
+
+    pub fn header<H: Header + HeaderFormat>(mut self, header: H) -> Reply {
+        self.headers.set(header);
+        self
+    }
+
+    /// Sets a header that hyper has no type for, such as `Server-Timing`.
+    pub fn raw_header(mut self, name: &'static str, value: &str) -> Reply {
+        self.headers.set_raw(name, vec![value.as_bytes().to_vec()]);
+        self
+    }
+
+    pub fn body(mut self, body: &[u8]) -> Reply {
+        self.body = body.to_vec();
+        self
+    }
+
+    /// Waits for `delay` before sending anything.
+    pub fn delay(mut self, delay: Duration) -> Reply {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Sends the body with chunked transfer encoding, in chunks of `size` bytes with `interval`
+    /// between them, rather than all at once with a `Content-Length`.
+    pub fn chunked(mut self, size: usize, interval: Duration) -> Reply {
+        self.chunks = Some((size, interval));
+        self
+    }
+
+    fn send(self, mut response: Response<Fresh>) -> io::Result<()> {
+        if let Some(delay) = self.delay {
+            thread::sleep(delay);
+        }
+
+        *response.status_mut() = self.status;
+        response.headers_mut().extend(self.headers.iter());
+
+        let (size, interval) = match self.chunks {
+            Some(chunks) => chunks,
+            None => return response.send(&self.body),
+        };
+        let mut response = try!(response.start());
+        for (i, chunk) in self.body.chunks(size).enumerate() {
+            if i > 0 {
+                thread::sleep(interval);
+            }
+            try!(response.write_all(chunk));
+            try!(response.flush());
+        }
+        response.end()
+    }
+}
+
+/// A request as the server received it.
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: Method,
+    /// The path and query of the request.
+    pub path: String,
+    pub headers: Headers,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    fn read(request: &mut Request) -> RecordedRequest {
+        let path = match request.uri {
+            RequestUri::AbsolutePath(ref path) => path.clone(),
+            RequestUri::AbsoluteUri(ref url) => url[url::Position::BeforePath..].to_owned(),
+            ref uri => uri.to_string(),
+        };
+        let mut body = vec![];
+        let _ = request.read_to_end(&mut body);
+        RecordedRequest {
+            method: request.method.clone(),
+            path: path,
+            headers: request.headers.clone(),
+            body: body,
+        }
+    }
+}
+
+type Route = Box<Fn(&RecordedRequest) -> Reply + Send + Sync>;
+
+/// The replies of a server, by path. Requests for paths without a route get a `404`.
+pub struct Routes {
+    routes: Vec<(String, Route)>,
+}
+
+impl Routes {
+    pub fn new() -> Routes {
+        Routes {
+            routes: vec![],
+        }
+    }
+
+    /// Replies to every request for `path` with `reply`.
+    pub fn reply(self, path: &str, reply: Reply) -> Routes {
+        // Headers aren't Sync, so the reply can only be shared behind a lock.
+        let reply = Mutex::new(reply);
+        self.handle(path, move |_| reply.lock().unwrap().clone())
+    }
+
+    /// Replies to requests for `path` with what `handler` returns for them.
+    pub fn handle<F>(mut self, path: &str, handler: F) -> Routes
+        where F: Fn(&RecordedRequest) -> Reply + Send + Sync + 'static
+    {
+        let handler: Route = Box::new(handler);
+        self.routes.push((path.to_owned(), handler));
+        self
+    }
+
+    fn reply_to(&self, request: &RecordedRequest) -> Reply {
+        let path = request.path.split('?').next().unwrap_or("");
+        self.routes.iter()
+            .find(|&&(ref route, _)| route == path)
+            .map(|&(_, ref handler)| handler(request))
+            .unwrap_or_else(|| Reply::new(StatusCode::NotFound))
+    }
+}
+
+struct ScriptedHandler {
+    routes: Routes,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl Handler for ScriptedHandler {
+    fn handle(&self, mut request: Request, response: Response<Fresh>) {
+        let request = RecordedRequest::read(&mut request);
+        let reply = self.routes.reply_to(&request);
+        self.requests.lock().unwrap().push(request);
+        // The client going away in the middle of a reply is something tests do on purpose.
+        let _ = reply.send(response);
+    }
+}
+
+/// How an HTTPS server does the TLS handshake.
+pub enum Tls {
+    /// With the self-signed certificate for `localhost`, which clients don't trust.
+    SelfSigned,
+    /// With the certificate chain and private key in the given PEM files.
+    Certificate(PathBuf, PathBuf),
+    /// Not at all: the server answers in plain HTTP, which fails the handshake.
+    NotTls,
+}
+
+enum Listener {
+    Hyper(Listening),
+    Raw(SocketAddr, Arc<AtomicBool>),
+}
+
+/// A running server, which stops when it is dropped.
+pub struct TestServer {
+    url: Url,
+    listener: Listener,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl TestServer {
+    /// Starts an HTTP server on a free port of localhost.
+    pub fn http(routes: Routes) -> TestServer {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let handler = ScriptedHandler {
+            routes: routes,
+            requests: requests.clone(),
+        };
+        let listening = Server::http("127.0.0.1:0").unwrap().handle_threads(handler, THREADS).unwrap();
+        TestServer::new("http", Listener::Hyper(listening), requests)
+    }
+
+    /// Starts an HTTPS server on a free port of localhost.
+    pub fn https(routes: Routes, tls: Tls) -> TestServer {
+        let requests = Arc::new(Mutex::new(vec![]));
+        let (cert, key) = match tls {
+            Tls::SelfSigned => (certs_dir().join("self-signed.pem"), certs_dir().join("self-signed.key")),
+            Tls::Certificate(cert, key) => (cert, key),
+            Tls::NotTls => return TestServer::new("https", serve_plain_http(), requests),
+        };
+        let handler = ScriptedHandler {
+            routes: routes,
+            requests: requests.clone(),
+        };
+        let ssl = Openssl::with_cert_and_key(cert, key).unwrap();
+        let listening = Server::https("127.0.0.1:0", ssl).unwrap().handle_threads(handler, THREADS).unwrap();
+        TestServer::new("https", Listener::Hyper(listening), requests)
+    }
+
+    fn new(scheme: &str, listener: Listener, requests: Arc<Mutex<Vec<RecordedRequest>>>) -> TestServer {
+        let port = match listener {
+            Listener::Hyper(ref listening) => listening.socket.port(),
+            Listener::Raw(address, _) => address.port(),
+        };
+        TestServer {
+            url: Url::parse(&format!("{}://localhost:{}/", scheme, port)).unwrap(),
+            listener: listener,
+            requests: requests,
+        }
+    }
+
+    /// The URL of the root of the server.
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    /// The URL of `path` on the server.
+    pub fn url_for(&self, path: &str) -> Url {
+        self.url.join(path).unwrap()
+    }
+
+    /// The requests the server received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        match self.listener {
+            Listener::Hyper(ref mut listening) => {
+                let _ = listening.close();
+            },
+            Listener::Raw(address, ref done) => {
+                done.store(true, Ordering::SeqCst);
+                // Wake the listener up so that it sees it is done.
+                let _ = TcpStream::connect(address);
+            },
+        }
+    }
+}
+
+fn certs_dir() -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/certs"))
+}
+
+/// Listens for connections and answers each in plain HTTP, whatever the client sends.
+fn serve_plain_http() -> Listener {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let done = Arc::new(AtomicBool::new(false));
+    let done_listening = done.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            if done_listening.load(Ordering::SeqCst) {
+                break;
+            }
+            if let Ok(mut stream) = stream {
+                let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n");
+            }
+        }
+    });
+    Listener::Raw(address, done)
+}
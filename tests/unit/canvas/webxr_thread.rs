@@ -0,0 +1,132 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas::webxr_thread::{MockDevice, WebXRManager, XRDevice};
+use canvas_traits::{CanvasData, CanvasMsg, CanvasPixelData, FromLayoutMsg};
+use canvas_traits::webxr::{WebXRError, WebXRMsg, XREye, XRFrameData, XRSessionId, XRSessionMode};
+use ipc_channel::ipc::{self, IpcSender, IpcSharedMemory};
+use std::thread;
+
+fn pixels() -> CanvasData {
+    CanvasData::Pixels(CanvasPixelData {
+        image_data: IpcSharedMemory::from_bytes(&[0; 4]),
+        image_key: None,
+    })
+}
+
+/// Starts a WebXR thread with a mock headset connected, if `with_device` is set.
+fn start_webxr_thread(with_device: bool) -> IpcSender<WebXRMsg> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread::spawn(move || {
+        let mut devices: Vec<Box<XRDevice>> = vec![];
+        if with_device {
+            devices.push(Box::new(MockDevice::new()));
+        }
+        WebXRManager::new(receiver, devices).start();
+    });
+    sender
+}
+
+fn is_session_supported(webxr: &IpcSender<WebXRMsg>, mode: XRSessionMode) -> bool {
+    let (sender, receiver) = ipc::channel().unwrap();
+    webxr.send(WebXRMsg::IsSessionSupported(mode, sender)).unwrap();
+    receiver.recv().unwrap()
+}
+
+fn request_session(webxr: &IpcSender<WebXRMsg>) -> Result<XRSessionId, WebXRError> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    webxr.send(WebXRMsg::RequestSession(XRSessionMode::ImmersiveVR, sender)).unwrap();
+    receiver.recv().unwrap()
+}
+
+fn request_frame(webxr: &IpcSender<WebXRMsg>, session: XRSessionId) -> Result<XRFrameData, WebXRError> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    webxr.send(WebXRMsg::RequestFrame(session, sender)).unwrap();
+    receiver.recv().unwrap()
+}
+
+#[test]
+fn test_mock_device_presents_once() {
+    let mut device = MockDevice::new();
+    assert!(device.supports_mode(XRSessionMode::ImmersiveVR));
+    assert!(!device.supports_mode(XRSessionMode::Inline));
+
+    assert_eq!(device.start_presenting(), Ok(()));
+    assert_eq!(device.start_presenting(), Err(WebXRError::InvalidState));
+    device.submit_frame(pixels());
+    device.submit_frame(pixels());
+    assert_eq!(device.submitted_frames(), 2);
+    device.stop_presenting();
+    assert_eq!(device.start_presenting(), Ok(()));
+}
+
+#[test]
+fn test_mock_device_frames() {
+    let mut device = MockDevice::new();
+    let first = device.wait_for_frame();
+    let second = device.wait_for_frame();
+
+    assert_eq!(first.views.len(), 2);
+    assert_eq!(first.views[0].eye, XREye::Left);
+    assert_eq!(first.views[1].eye, XREye::Right);
+    let (left, right) = (first.views[0].transform.position, first.views[1].transform.position);
+    let ipd = ((left[0] - right[0]).powi(2) + (left[2] - right[2]).powi(2)).sqrt();
+    assert!((ipd - 0.064).abs() < 1e-5);
+    assert!((first.viewer.position[1] - 1.6).abs() < 1e-5);
+    // The viewer turns around between frames without moving.
+    assert_eq!(first.viewer.position, second.viewer.position);
+    assert!(first.viewer.orientation != second.viewer.orientation);
+}
+
+#[test]
+fn test_session_not_supported_without_device() {
+    let webxr = start_webxr_thread(false);
+    assert!(!is_session_supported(&webxr, XRSessionMode::ImmersiveVR));
+    assert_eq!(request_session(&webxr), Err(WebXRError::NotSupported));
+    webxr.send(WebXRMsg::Exit).unwrap();
+}
+
+#[test]
+fn test_session_lifetime() {
+    let webxr = start_webxr_thread(true);
+    assert!(is_session_supported(&webxr, XRSessionMode::ImmersiveVR));
+    assert!(!is_session_supported(&webxr, XRSessionMode::Inline));
+
+    let session = request_session(&webxr).unwrap();
+    // The device is already presenting.
+    assert_eq!(request_session(&webxr), Err(WebXRError::InvalidState));
+
+    webxr.send(WebXRMsg::EndSession(session)).unwrap();
+    assert_eq!(request_frame(&webxr, session).err(), Some(WebXRError::InvalidState));
+    let next_session = request_session(&webxr).unwrap();
+    assert!(next_session != session);
+    webxr.send(WebXRMsg::Exit).unwrap();
+}
+
+#[test]
+fn test_frame_loop() {
+    let webxr = start_webxr_thread(true);
+    let session = request_session(&webxr).unwrap();
+
+    for _ in 0..3 {
+        let frame = request_frame(&webxr, session).unwrap();
+        assert_eq!(frame.views.len(), 2);
+
+        // The WebXR thread reads the drawing buffer from the WebGL context.
+        let (renderer, requests) = ipc::channel().unwrap();
+        webxr.send(WebXRMsg::SubmitFrame(session, renderer)).unwrap();
+        match requests.recv().unwrap() {
+            CanvasMsg::FromLayout(FromLayoutMsg::SendData(sender)) => sender.send(pixels()).unwrap(),
+            _ => panic!("the WebXR thread should ask for the drawing buffer"),
+        }
+    }
+
+    webxr.send(WebXRMsg::EndSession(session)).unwrap();
+    // Frames submitted after the session ended are dropped without asking for
+    // the drawing buffer, so the thread goes on handling messages.
+    let (renderer, _requests) = ipc::channel().unwrap();
+    webxr.send(WebXRMsg::SubmitFrame(session, renderer)).unwrap();
+    assert!(is_session_supported(&webxr, XRSessionMode::ImmersiveVR));
+    webxr.send(WebXRMsg::Exit).unwrap();
+}
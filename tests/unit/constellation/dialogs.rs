@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use constellation::dialogs::ModalPrompts;
+use ipc_channel::ipc;
+use msg::constellation_msg::{PipelineId, PipelineNamespace, PipelineNamespaceId};
+use script_traits::DialogResponse;
+
+fn pipeline_ids() -> (PipelineId, PipelineId, PipelineId) {
+    PipelineNamespace::install(PipelineNamespaceId(1));
+    (PipelineId::new(), PipelineId::new(), PipelineId::new())
+}
+
+fn accepted(text: &str) -> DialogResponse {
+    DialogResponse {
+        accepted: true,
+        text: Some(text.to_owned()),
+        prevent_additional_dialogs: false,
+    }
+}
+
+#[test]
+fn test_answers_go_to_the_oldest_dialog() {
+    let (mozbrowser, first_page, second_page) = pipeline_ids();
+    let mut prompts = ModalPrompts::new();
+    let (first_sender, first_receiver) = ipc::channel().unwrap();
+    let (second_sender, second_receiver) = ipc::channel().unwrap();
+    prompts.push(mozbrowser, first_page, first_sender);
+    prompts.push(mozbrowser, second_page, second_sender);
+
+    let (pipeline_id, sender) = prompts.answer(mozbrowser).unwrap();
+    assert_eq!(pipeline_id, first_page);
+    sender.send(accepted("first")).unwrap();
+    assert_eq!(first_receiver.recv().unwrap(), accepted("first"));
+
+    let (pipeline_id, sender) = prompts.answer(mozbrowser).unwrap();
+    assert_eq!(pipeline_id, second_page);
+    sender.send(accepted("second")).unwrap();
+    assert_eq!(second_receiver.recv().unwrap(), accepted("second"));
+
+    assert!(prompts.answer(mozbrowser).is_none());
+}
+
+#[test]
+fn test_answers_only_go_to_dialogs_of_their_mozbrowser() {
+    let (mozbrowser, other_mozbrowser, page) = pipeline_ids();
+    let mut prompts = ModalPrompts::new();
+    let (sender, _receiver) = ipc::channel().unwrap();
+    prompts.push(mozbrowser, page, sender);
+
+    assert!(prompts.answer(other_mozbrowser).is_none());
+    assert!(prompts.answer(mozbrowser).is_some());
+}
+
+#[test]
+fn test_closing_the_mozbrowser_dismisses_its_dialogs() {
+    let (mozbrowser, page, _) = pipeline_ids();
+    let mut prompts = ModalPrompts::new();
+    let (sender, receiver) = ipc::channel::<DialogResponse>().unwrap();
+    prompts.push(mozbrowser, page, sender);
+
+    prompts.remove_pipeline(mozbrowser);
+    assert!(receiver.recv().is_err());
+    assert!(prompts.answer(mozbrowser).is_none());
+}
+
+#[test]
+fn test_closing_the_page_forgets_its_dialogs() {
+    let (mozbrowser, first_page, second_page) = pipeline_ids();
+    let mut prompts = ModalPrompts::new();
+    let (first_sender, first_receiver) = ipc::channel::<DialogResponse>().unwrap();
+    let (second_sender, _second_receiver) = ipc::channel().unwrap();
+    prompts.push(mozbrowser, first_page, first_sender);
+    prompts.push(mozbrowser, second_page, second_sender);
+
+    prompts.remove_pipeline(first_page);
+    assert!(first_receiver.recv().is_err());
+    let (pipeline_id, _) = prompts.answer(mozbrowser).unwrap();
+    assert_eq!(pipeline_id, second_page);
+}
@@ -0,0 +1,155 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use app_units::Au;
+use euclid::{Matrix4D, Point2D, Radians, Rect, Size2D};
+use gfx::display_list::{DisplayList, LayerInfo, StackingContext, StackingContextType};
+use gfx_traits::{FragmentType, LayerId, ScrollPolicy, ScrollbarInfo, StackingContextId, color};
+use std::f32::consts::PI;
+use style::computed_values::{backface_visibility, filter, mix_blend_mode, transform_style};
+
+fn stacking_context(id: usize, context_type: StackingContextType, z_index: i32)
+                    -> Box<StackingContext> {
+    let id = if id == 0 {
+        StackingContextId::root()
+    } else {
+        StackingContextId::new_of_type(id, FragmentType::FragmentBody)
+    };
+    box StackingContext::new(id,
+                             context_type,
+                             &Rect::zero(),
+                             &Rect::<Au>::zero(),
+                             z_index,
+                             filter::T::new(vec![]),
+                             mix_blend_mode::T::normal,
+                             false,
+                             Matrix4D::identity(),
+                             Matrix4D::identity(),
+                             false,
+                             transform_style::T::flat,
+                             backface_visibility::T::visible,
+                             false,
+                             None)
+}
+
+// CSS 2.1 Appendix E: the root's own background, then negative z-index children, then block
+// backgrounds, floats, inline content, positioned children with z-index 0 or auto in tree
+// order, and finally positive z-index children.
+#[test]
+fn test_stacking_context_paint_order() {
+    let mut root = stacking_context(0, StackingContextType::Real, 0);
+    root.set_children(vec![
+        stacking_context(1, StackingContextType::PseudoPositioned, 0),
+        stacking_context(2, StackingContextType::Real, 2),
+        stacking_context(3, StackingContextType::PseudoFloat, 0),
+        stacking_context(4, StackingContextType::Real, -1),
+        stacking_context(5, StackingContextType::Real, 0),
+        stacking_context(6, StackingContextType::Real, -3),
+    ]);
+    let display_list = DisplayList::new(*root, vec![]);
+
+    let order: Vec<_> = display_list.root_stacking_context.children().iter().map(|child| {
+        child.id
+    }).collect();
+    let expected: Vec<_> = [6, 4, 3, 1, 5, 2].iter().map(|&id| {
+        StackingContextId::new_of_type(id, FragmentType::FragmentBody)
+    }).collect();
+    assert_eq!(order, expected);
+
+    let offsets = &display_list.offsets;
+    let root = &offsets[&StackingContextId::root()];
+    let offset = |id| offsets[&StackingContextId::new_of_type(id, FragmentType::FragmentBody)].start;
+    assert!(root.start < offset(6));
+    assert!(offset(4) < root.block_backgrounds_and_borders);
+    assert!(root.block_backgrounds_and_borders < offset(3));
+    assert!(offset(3) < root.content);
+    assert!(root.content < offset(1));
+    assert!(offset(5) < offset(2));
+    assert!(offset(2) < root.outlines);
+}
+
+fn id(id: usize) -> StackingContextId {
+    StackingContextId::new_of_type(id, FragmentType::FragmentBody)
+}
+
+#[test]
+fn test_hidden_back_face() {
+    let mut turned = stacking_context(2, StackingContextType::Real, 0);
+    turned.transform = Matrix4D::create_rotation(0.0, 1.0, 0.0, Radians::new(PI));
+    turned.backface_visibility = backface_visibility::T::hidden;
+    let mut facing = stacking_context(3, StackingContextType::Real, 0);
+    facing.backface_visibility = backface_visibility::T::hidden;
+    let mut turned_but_visible = stacking_context(4, StackingContextType::Real, 0);
+    turned_but_visible.transform = Matrix4D::create_rotation(0.0, 1.0, 0.0, Radians::new(PI));
+
+    let mut scene = stacking_context(1, StackingContextType::Real, 0);
+    scene.transform_style = transform_style::T::preserve_3d;
+    scene.set_children(vec![turned, facing, turned_but_visible]);
+    let mut root = stacking_context(0, StackingContextType::Real, 0);
+    root.set_children(vec![scene]);
+    let display_list = DisplayList::new(*root, vec![]);
+
+    let scene = &display_list.root_stacking_context.children()[0];
+    let hidden: Vec<_> = scene.children().iter().map(|child| {
+        (child.id, child.is_hidden_back_face())
+    }).collect();
+    assert_eq!(hidden, vec![(id(2), true), (id(3), false), (id(4), false)]);
+}
+
+// Children sharing a 3d rendering context are painted back to front.
+#[test]
+fn test_preserve_3d_depth_order() {
+    let mut near = stacking_context(2, StackingContextType::Real, 0);
+    near.transform = Matrix4D::create_translation(0.0, 0.0, 10.0);
+    let mut far = stacking_context(3, StackingContextType::Real, 0);
+    far.transform = Matrix4D::create_translation(0.0, 0.0, -10.0);
+
+    let mut scene = stacking_context(1, StackingContextType::Real, 0);
+    scene.transform_style = transform_style::T::preserve_3d;
+    scene.set_children(vec![near, far]);
+    let mut root = stacking_context(0, StackingContextType::Real, 0);
+    root.set_children(vec![scene]);
+    let display_list = DisplayList::new(*root, vec![]);
+
+    let scene = &display_list.root_stacking_context.children()[0];
+    let order: Vec<_> = scene.children().iter().map(|child| child.id).collect();
+    assert_eq!(order, vec![id(3), id(2)]);
+}
+
+fn scroll_container(id: usize, origin: Point2D<Au>) -> Box<StackingContext> {
+    let mut scroll_container = stacking_context(id, StackingContextType::Real, 0);
+    scroll_container.bounds = Rect::new(origin, Size2D::new(Au::from_px(100), Au::from_px(100)));
+    scroll_container.scrolls_overflow_area = true;
+    let mut layer_info = LayerInfo::new(LayerId::null(), ScrollPolicy::Scrollable, None, color::transparent());
+    layer_info.scrollbars = Some(ScrollbarInfo {
+        horizontal_track: None,
+        vertical_track: Some(Rect::new(Point2D::new(90.0, 0.0), Size2D::new(10.0, 100.0))),
+        client_size: Size2D::new(100.0, 100.0),
+        content_size: Size2D::new(100.0, 400.0),
+    });
+    scroll_container.layer_info = Some(layer_info);
+    scroll_container
+}
+
+// WebRender has no layers for the compositor to hit test scrollbars in, so the display list
+// reports where they are and what scrolls them.
+#[test]
+fn test_scrollbars_for_webrender() {
+    let mut outer = scroll_container(1, Point2D::new(Au::from_px(10), Au::from_px(20)));
+    let mut float = stacking_context(2, StackingContextType::PseudoFloat, 0);
+    float.bounds = Rect::new(Point2D::new(Au::from_px(1000), Au::from_px(1000)), Size2D::zero());
+    float.set_children(vec![scroll_container(3, Point2D::new(Au::from_px(5), Au::from_px(5)))]);
+    outer.set_children(vec![float]);
+    let mut root = stacking_context(0, StackingContextType::Real, 0);
+    root.set_children(vec![outer]);
+    let display_list = DisplayList::new(*root, vec![]);
+
+    let scrollbars: Vec<_> = display_list.scrollbars().into_iter().map(|scrollbars| {
+        (scrollbars.stacking_context_id, scrollbars.scrolled_by, scrollbars.origin)
+    }).collect();
+    assert_eq!(scrollbars, vec![
+        (id(1), vec![StackingContextId::root()], Point2D::new(10.0, 20.0)),
+        (id(3), vec![StackingContextId::root(), id(1)], Point2D::new(15.0, 25.0)),
+    ]);
+}
@@ -2,8 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use gfx::font_cache_thread::FontCacheThread;
+use gfx::font_cache_thread::{FontCacheThread, FontTemplateInfo};
+use gfx::font_template::FontTemplate;
 use ipc_channel::ipc;
+use string_cache::Atom;
 use style::computed_values::font_family::FontFamily;
 use style::font_face::{FontFaceRule, Source};
 
@@ -23,3 +25,19 @@ fn test_local_web_font() {
 
     assert_eq!(out_receiver.recv().unwrap(), ());
 }
+
+#[test]
+fn test_font_data_is_sent_in_shared_memory() {
+    let bytes = b"the data of a web font".to_vec();
+    let mut template = FontTemplate::new(Atom::from("test font"), Some(bytes.clone())).unwrap();
+    let info = FontTemplateInfo {
+        font_template: template.get().unwrap(),
+        font_key: None,
+    };
+    let (sender, receiver) = ipc::channel().unwrap();
+
+    sender.send(info).unwrap();
+
+    let received: FontTemplateInfo = receiver.recv().unwrap();
+    assert_eq!(received.font_template.bytes_if_in_memory(), Some(bytes));
+}
@@ -0,0 +1,45 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use gfx::text::hyphenation::{hyphenation_opportunities, last_hyphenation_point};
+
+/// Breaks `word` as a line of `max_chars` monospace characters would, leaving room for a hyphen.
+fn break_word(word: &str, lang: Option<&str>, max_chars: usize) -> Option<(String, String)> {
+    last_hyphenation_point(word, lang, |index| word[..index].chars().count() + 1 <= max_chars).map(|index| {
+        (word[..index].to_owned(), word[index..].to_owned())
+    })
+}
+
+#[test]
+fn test_english_words_are_hyphenated_with_hyphens_auto() {
+    assert_eq!(hyphenation_opportunities("hyphenation", "en"), vec![2, 6]);
+    assert_eq!(hyphenation_opportunities("hyphenation", "en-us"), vec![2, 6]);
+
+    assert_eq!(break_word("hyphenation", Some("en"), 8),
+               Some(("hyphen".to_owned(), "ation".to_owned())));
+    assert_eq!(break_word("hyphenation", Some("en"), 6),
+               Some(("hy".to_owned(), "phenation".to_owned())));
+    assert_eq!(break_word("hyphenation", Some("en"), 2), None);
+}
+
+#[test]
+fn test_words_are_not_hyphenated_without_a_dictionary() {
+    assert!(hyphenation_opportunities("hyphenation", "").is_empty());
+    assert!(hyphenation_opportunities("hyphenation", "x-unknown").is_empty());
+
+    // `hyphens: manual`.
+    assert_eq!(break_word("hyphenation", None, 8), None);
+    assert_eq!(break_word("hyphenation", Some(""), 8), None);
+}
+
+#[test]
+fn test_soft_hyphens_are_always_hyphenation_opportunities() {
+    assert_eq!(break_word("hyphen\u{ad}ation", None, 8),
+               Some(("hyphen\u{ad}".to_owned(), "ation".to_owned())));
+    // A soft hyphen at the end of a word isn't an opportunity within it.
+    assert_eq!(break_word("hyphen\u{ad}", None, 8), None);
+    // With `hyphens: auto`, the dictionary adds its own.
+    assert_eq!(break_word("hy\u{ad}phenation", Some("en"), 8),
+               Some(("hy\u{ad}phen".to_owned(), "ation".to_owned())));
+}
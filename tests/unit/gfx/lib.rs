@@ -2,9 +2,18 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+#![feature(box_syntax)]
+
+extern crate app_units;
+extern crate euclid;
 extern crate gfx;
+extern crate gfx_traits;
 extern crate ipc_channel;
+extern crate string_cache;
 extern crate style;
 
+#[cfg(test)] mod display_list;
 #[cfg(test)] mod font_cache_thread;
+#[cfg(test)] mod hyphenation;
+#[cfg(test)] mod scrollbars;
 #[cfg(test)] mod text_util;
@@ -0,0 +1,64 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use euclid::{Point2D, Rect, Size2D};
+use gfx_traits::{ScrollbarAxis, ScrollbarInfo, ScrollbarPress};
+
+/// A 100x100 scroll container with a 10px wide vertical scrollbar and 400px of content, so that
+/// its thumb is 25px long and has 75px to move in.
+fn vertical_scrollbar() -> ScrollbarInfo {
+    ScrollbarInfo {
+        horizontal_track: None,
+        vertical_track: Some(Rect::new(Point2D::new(90.0, 0.0), Size2D::new(10.0, 100.0))),
+        client_size: Size2D::new(100.0, 100.0),
+        content_size: Size2D::new(100.0, 400.0),
+    }
+}
+
+#[test]
+fn test_hit_test() {
+    let scrollbars = vertical_scrollbar();
+    assert_eq!(scrollbars.hit_test(&Point2D::new(95.0, 50.0)), Some(ScrollbarAxis::Vertical));
+    assert_eq!(scrollbars.hit_test(&Point2D::new(50.0, 50.0)), None);
+}
+
+#[test]
+fn test_press_thumb() {
+    let scrollbars = vertical_scrollbar();
+    let press = scrollbars.press(ScrollbarAxis::Vertical, &Point2D::new(95.0, 10.0), &Point2D::zero());
+    assert_eq!(press, Some(ScrollbarPress::DragThumb));
+
+    // Scrolled halfway, the thumb is halfway down its track.
+    let scrolled = Point2D::new(0.0, 150.0);
+    let press = scrollbars.press(ScrollbarAxis::Vertical, &Point2D::new(95.0, 45.0), &scrolled);
+    assert_eq!(press, Some(ScrollbarPress::DragThumb));
+    assert_eq!(scrollbars.press(ScrollbarAxis::Horizontal, &Point2D::new(95.0, 45.0), &scrolled), None);
+}
+
+#[test]
+fn test_press_track_scrolls_by_a_page() {
+    let scrollbars = vertical_scrollbar();
+    let press = scrollbars.press(ScrollbarAxis::Vertical, &Point2D::new(95.0, 80.0), &Point2D::zero());
+    assert_eq!(press, Some(ScrollbarPress::ScrollTo(Point2D::new(0.0, 100.0))));
+
+    let scrolled = Point2D::new(0.0, 250.0);
+    let press = scrollbars.press(ScrollbarAxis::Vertical, &Point2D::new(95.0, 5.0), &scrolled);
+    assert_eq!(press, Some(ScrollbarPress::ScrollTo(Point2D::new(0.0, 150.0))));
+
+    // Paging stops at the end of the scrollable overflow.
+    let scrolled = Point2D::new(0.0, 250.0);
+    let press = scrollbars.press(ScrollbarAxis::Vertical, &Point2D::new(95.0, 99.0), &scrolled);
+    assert_eq!(press, Some(ScrollbarPress::ScrollTo(Point2D::new(0.0, 300.0))));
+}
+
+#[test]
+fn test_drag_thumb() {
+    let scrollbars = vertical_scrollbar();
+    let start = Point2D::new(0.0, 0.0);
+
+    // The content moves four times as far as the thumb.
+    assert_eq!(scrollbars.drag_thumb(ScrollbarAxis::Vertical, &start, 15.0), Point2D::new(0.0, 60.0));
+    assert_eq!(scrollbars.drag_thumb(ScrollbarAxis::Vertical, &start, 200.0), Point2D::new(0.0, 300.0));
+    assert_eq!(scrollbars.drag_thumb(ScrollbarAxis::Vertical, &start, -10.0), start);
+}
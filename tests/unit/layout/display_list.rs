@@ -0,0 +1,185 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use app_units::Au;
+use cssparser::{Color, RGBA};
+use euclid::{Matrix4D, Point2D, Rect, Size2D};
+use euclid::size::TypedSize2D;
+use gfx::display_list::{DisplayItem, StackingContext, StackingContextType};
+use gfx::font_cache_thread::FontCacheThread;
+use gfx_traits::StackingContextId;
+use ipc_channel::ipc;
+use layout::{BlockFlow, Fragment, SpecificFragmentInfo};
+use layout::context::SharedLayoutContext;
+use layout::flow::{self, Flow};
+use layout::flow_ref::{self, FlowRef};
+use layout::{parallel, sequential};
+use msg::constellation_msg::PipelineId;
+use net_traits::image_cache_thread::{ImageCacheChan, ImageCacheThread};
+use script_layout_interface::restyle_damage::RestyleDamage;
+use script_layout_interface::wrapper_traits::PseudoElementType;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::mpsc::channel;
+use style::computed_values::{backface_visibility, display, filter, mix_blend_mode, position};
+use style::computed_values::transform_style;
+use style::context::{LocalStyleContextCreationInfo, ReflowGoal, SharedStyleContext};
+use style::dom::OpaqueNode;
+use style::error_reporting::StdoutErrorReporter;
+use style::logical_geometry::{LogicalRect, WritingMode};
+use style::media_queries::{Device, MediaType};
+use style::parallel::WorkQueueData;
+use style::properties::ServoComputedValues;
+use style::selector_matching::Stylist;
+use style::thread_state;
+use style::timer::Timer;
+use style::workqueue::WorkQueue;
+
+/// A block flow with a background of `color`, the node of which is `id`, and whose border box
+/// is a 100px square `id` * 10px from the origin.
+fn block(id: usize, color: u8, display: display::T, position: position::T, opacity: f32) -> FlowRef {
+    let mut style = ServoComputedValues::initial_values().clone();
+    style.mutate_box().display = display;
+    style.mutate_box().position = position;
+    style.mutate_effects().opacity = opacity;
+    style.mutate_background().background_color = Color::RGBA(RGBA {
+        red: color as f32 / 255.0,
+        green: 0.0,
+        blue: 0.0,
+        alpha: 1.0,
+    });
+    let style = Arc::new(style);
+    let mut fragment = Fragment::from_opaque_node_and_style(OpaqueNode(id),
+                                                            PseudoElementType::Normal,
+                                                            style.clone(),
+                                                            style,
+                                                            RestyleDamage::empty(),
+                                                            SpecificFragmentInfo::Generic);
+    let offset = Au::from_px(id as i32 * 10);
+    let size = Au::from_px(100);
+    fragment.border_box = LogicalRect::new(WritingMode::empty(), offset, offset, size, size);
+    let mut flow: FlowRef = Arc::new(BlockFlow::from_fragment(fragment, None));
+    flow::mut_base(flow_ref::deref_mut(&mut flow)).stacking_relative_position_of_display_port =
+        Rect::new(Point2D::zero(), Size2D::new(Au::from_px(10000), Au::from_px(10000)));
+    flow
+}
+
+fn add_child(parent: &mut FlowRef, child: &FlowRef) {
+    flow::mut_base(flow_ref::deref_mut(parent)).children.push_back(child.clone());
+}
+
+/// A document that mixes blocks that are painted in the stacking context of their parent with
+/// ones that establish a stacking context, are painted as if they did, or are positioned.
+fn document() -> FlowRef {
+    let mut root = block(1, 10, display::T::block, position::T::static_, 1.0);
+    let first = block(2, 20, display::T::block, position::T::static_, 1.0);
+    let mut translucent = block(3, 30, display::T::block, position::T::static_, 0.5);
+    let inside_translucent = block(4, 40, display::T::block, position::T::static_, 1.0);
+    let mut flex = block(5, 50, display::T::flex, position::T::static_, 1.0);
+    let mut inside_flex = block(6, 60, display::T::block, position::T::static_, 1.0);
+    let inside_inside_flex = block(7, 70, display::T::inline_block, position::T::static_, 1.0);
+    let positioned = block(8, 80, display::T::block, position::T::relative, 1.0);
+    let last = block(9, 90, display::T::table, position::T::static_, 1.0);
+    add_child(&mut translucent, &inside_translucent);
+    add_child(&mut inside_flex, &inside_inside_flex);
+    add_child(&mut flex, &inside_flex);
+    add_child(&mut flex, &positioned);
+    add_child(&mut root, &first);
+    add_child(&mut root, &translucent);
+    add_child(&mut root, &flex);
+    add_child(&mut root, &last);
+    root
+}
+
+fn shared_layout_context() -> SharedLayoutContext {
+    let (resource_thread, _) = ipc::channel().unwrap();
+    let (image_cache_chan, _) = ipc::channel().unwrap();
+    let (image_cache_sender, _) = ipc::channel().unwrap();
+    let device = Device::new(MediaType::Screen, TypedSize2D::new(800.0, 600.0));
+    SharedLayoutContext {
+        pipeline_id: PipelineId::fake_root_pipeline_id(),
+        style_context: SharedStyleContext {
+            viewport_size: Size2D::new(Au::from_px(800), Au::from_px(600)),
+            screen_size_changed: false,
+            stylist: Arc::new(Stylist::new(device)),
+            generation: 0,
+            goal: ReflowGoal::ForDisplay,
+            running_animations: Arc::new(RwLock::new(HashMap::new())),
+            expired_animations: Arc::new(RwLock::new(HashMap::new())),
+            error_reporter: Box::new(StdoutErrorReporter),
+            local_context_creation_data: Mutex::new(LocalStyleContextCreationInfo::new(channel().0)),
+            timer: Timer::test_mode(),
+            container_sizes: Arc::new(HashMap::new()),
+        },
+        image_cache_thread: ImageCacheThread::new(image_cache_chan),
+        image_cache_sender: Mutex::new(ImageCacheChan(image_cache_sender)),
+        font_cache_thread: Mutex::new(FontCacheThread::new(resource_thread, None)),
+        visible_rects: Arc::new(HashMap::with_hasher(Default::default())),
+        webrender_image_cache: Arc::new(RwLock::new(HashMap::with_hasher(Default::default()))),
+    }
+}
+
+fn root_stacking_context() -> StackingContext {
+    StackingContext::new(StackingContextId::new(0),
+                         StackingContextType::Real,
+                         &Rect::zero(),
+                         &Rect::zero(),
+                         0,
+                         filter::T::new(Vec::new()),
+                         mix_blend_mode::T::normal,
+                         false,
+                         Matrix4D::identity(),
+                         Matrix4D::identity(),
+                         true,
+                         transform_style::T::flat,
+                         backface_visibility::T::visible,
+                         false,
+                         None)
+}
+
+/// What decides the paint order of the items of a display list.
+fn paint_order(items: &[DisplayItem]) -> Vec<String> {
+    items.iter().map(|item| {
+        format!("{:?} {:?} {:?}", item.base().stacking_context_id, item.base().section, item)
+    }).collect()
+}
+
+#[test]
+fn test_parallel_display_list_is_in_sequential_order() {
+    let shared_layout_context = shared_layout_context();
+    let mut root = document();
+
+    let sequential_items = sequential::build_display_list_for_subtree(flow_ref::deref_mut(&mut root),
+                                                                      &mut root_stacking_context(),
+                                                                      &shared_layout_context);
+
+    let mut queue: WorkQueue<SharedLayoutContext, WorkQueueData> =
+        WorkQueue::new("LayoutWorker", thread_state::LAYOUT, 4).unwrap();
+    for _ in 0..10 {
+        let parallel_items = parallel::build_display_list_for_subtree(flow_ref::deref_mut(&mut root),
+                                                                      &mut root_stacking_context(),
+                                                                      &shared_layout_context,
+                                                                      &mut queue);
+        assert_eq!(paint_order(&parallel_items), paint_order(&sequential_items));
+    }
+    queue.shutdown();
+
+    // Each of the nine blocks paints its background.
+    let backgrounds = sequential_items.iter().filter(|item| match **item {
+        DisplayItem::SolidColorClass(_) => true,
+        _ => false,
+    });
+    assert_eq!(backgrounds.count(), 9);
+}
+
+#[test]
+fn test_blocks_painted_as_stacking_contexts_are_built_on_their_own() {
+    let mut root = document();
+    flow_ref::deref_mut(&mut root).collect_stacking_contexts(StackingContextId::new(0), &mut vec![]);
+
+    let establishes_stacking_context: Vec<bool> = flow::base(&*root).children.iter().map(|kid| {
+        flow::base(kid).flags.contains(flow::ESTABLISHES_STACKING_CONTEXT)
+    }).collect();
+    assert_eq!(establishes_stacking_context, vec![false, true, true, true]);
+}
@@ -0,0 +1,74 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use layout::{BlockFlow, FloatKind, Fragment, SpecificFragmentInfo};
+use layout::flow::{self, Flow};
+use layout::flow_ref::{self, FlowRef};
+use layout::incremental::LayoutDamageComputation;
+use script_layout_interface::restyle_damage::{REFLOW, RestyleDamage};
+use script_layout_interface::wrapper_traits::PseudoElementType;
+use std::sync::Arc;
+use style::computed_values::{float, overflow_x};
+use style::dom::OpaqueNode;
+use style::properties::ServoComputedValues;
+
+/// An undamaged block flow, the node of which is `id`.
+fn block(id: usize, floating: float::T, overflow: overflow_x::T) -> FlowRef {
+    let mut style = ServoComputedValues::initial_values().clone();
+    style.mutate_box().float = floating;
+    style.mutate_box().overflow_x = overflow;
+    let style = Arc::new(style);
+    let fragment = Fragment::from_opaque_node_and_style(OpaqueNode(id),
+                                                        PseudoElementType::Normal,
+                                                        style.clone(),
+                                                        style,
+                                                        RestyleDamage::empty(),
+                                                        SpecificFragmentInfo::Generic);
+    let mut flow: FlowRef = Arc::new(BlockFlow::from_fragment(fragment, FloatKind::from_property(floating)));
+    flow::mut_base(flow_ref::deref_mut(&mut flow)).restyle_damage = RestyleDamage::empty();
+    flow
+}
+
+fn add_child(parent: &mut FlowRef, child: &FlowRef) {
+    flow::mut_base(flow_ref::deref_mut(parent)).children.push_back(child.clone());
+}
+
+fn is_reflowed(flow: &FlowRef) -> bool {
+    flow::base(&**flow).restyle_damage.contains(REFLOW)
+}
+
+/// Lays out a root containing a block with a float and a sibling after the float, and a sibling
+/// of that block after it, then damages the float.
+fn damage_float(context_overflow: overflow_x::T) -> (FlowRef, FlowRef, FlowRef) {
+    let mut root = block(1, float::T::none, overflow_x::T::visible);
+    flow_ref::deref_mut(&mut root).mark_as_root();
+    let mut context = block(2, float::T::none, context_overflow);
+    let mut floated = block(3, float::T::left, overflow_x::T::visible);
+    let inside = block(4, float::T::none, overflow_x::T::visible);
+    let outside = block(5, float::T::none, overflow_x::T::visible);
+    add_child(&mut context, &floated);
+    add_child(&mut context, &inside);
+    add_child(&mut root, &context);
+    add_child(&mut root, &outside);
+
+    flow::mut_base(flow_ref::deref_mut(&mut floated)).restyle_damage.insert(REFLOW);
+    flow_ref::deref_mut(&mut root).compute_layout_damage();
+    (root, inside, outside)
+}
+
+#[test]
+fn test_float_change_stays_in_its_formatting_context() {
+    // `overflow: hidden` makes the block the root of the formatting context of the float.
+    let (_root, inside, outside) = damage_float(overflow_x::T::hidden);
+    assert!(is_reflowed(&inside));
+    assert!(!is_reflowed(&outside));
+}
+
+#[test]
+fn test_float_change_reflows_the_document_formatting_context() {
+    // Without it, the float is in the formatting context of the root, as is everything else.
+    let (_root, inside, outside) = damage_float(overflow_x::T::visible);
+    assert!(is_reflowed(&inside));
+    assert!(is_reflowed(&outside));
+}
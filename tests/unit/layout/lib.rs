@@ -2,6 +2,18 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+extern crate app_units;
+extern crate cssparser;
+extern crate euclid;
+extern crate gfx;
+extern crate gfx_traits;
+extern crate ipc_channel;
 extern crate layout;
+extern crate msg;
+extern crate net_traits;
+extern crate script_layout_interface;
+extern crate style;
 
+#[cfg(test)] mod display_list;
+#[cfg(test)] mod incremental;
 #[cfg(all(test, target_pointer_width = "64"))] mod size_of;
@@ -0,0 +1,67 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use layout_thread::idle_threads::IdleThreads;
+use msg::constellation_msg::{PipelineId, PipelineNamespace, PipelineNamespaceId};
+use std::sync::mpsc::channel;
+use url::{Origin, Url};
+
+fn pipeline_ids() -> (PipelineId, PipelineId, PipelineId) {
+    PipelineNamespace::install(PipelineNamespaceId(1));
+    (PipelineId::new(), PipelineId::new(), PipelineId::new())
+}
+
+fn origin(url: &str) -> Origin {
+    Url::parse(url).unwrap().origin()
+}
+
+#[test]
+fn test_navigations_inherit_the_lineage_of_the_pipeline_they_replace() {
+    let (first_page, second_page, third_page) = pipeline_ids();
+    let mut idle_threads = IdleThreads::<()>::new();
+    assert_eq!(idle_threads.start_pipeline(first_page, None), first_page);
+    // The new pipeline starts before the one it replaces exits.
+    assert_eq!(idle_threads.start_pipeline(second_page, Some(first_page)), first_page);
+    idle_threads.finish_pipeline(first_page);
+    assert_eq!(idle_threads.start_pipeline(third_page, Some(second_page)), first_page);
+}
+
+#[test]
+fn test_threads_are_reused_within_a_browsing_context_and_origin() {
+    let (first_page, second_page, _) = pipeline_ids();
+    let mut idle_threads = IdleThreads::new();
+    let (sender, receiver) = channel();
+    idle_threads.add(first_page, origin("http://example.com/a"), sender);
+
+    assert!(idle_threads.take(first_page, &origin("http://example.org/")).is_none());
+    assert!(idle_threads.take(second_page, &origin("http://example.com/b")).is_none());
+
+    let sender = idle_threads.take(first_page, &origin("http://example.com/b")).unwrap();
+    sender.send("init").unwrap();
+    assert_eq!(receiver.recv().unwrap(), "init");
+    assert!(idle_threads.take(first_page, &origin("http://example.com/b")).is_none());
+}
+
+#[test]
+fn test_other_browsing_contexts_do_not_reuse_threads() {
+    let (first_page, other_page, _) = pipeline_ids();
+    let mut idle_threads = IdleThreads::<()>::new();
+    let lineage = idle_threads.start_pipeline(first_page, None);
+    assert_eq!(idle_threads.start_pipeline(other_page, None), other_page);
+    idle_threads.finish_pipeline(first_page);
+    idle_threads.add(lineage, origin("http://example.com/"), channel().0);
+
+    assert!(idle_threads.take(other_page, &origin("http://example.com/")).is_none());
+}
+
+#[test]
+fn test_threads_that_were_taken_can_not_be_removed() {
+    let (first_page, _, _) = pipeline_ids();
+    let mut idle_threads = IdleThreads::<()>::new();
+    let timed_out = idle_threads.add(first_page, origin("http://example.com/"), channel().0);
+    let taken = idle_threads.add(first_page, origin("http://example.com/"), channel().0);
+    assert!(idle_threads.remove(timed_out));
+    assert!(idle_threads.take(first_page, &origin("http://example.com/")).is_some());
+    assert!(!idle_threads.remove(taken));
+}
@@ -0,0 +1,93 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use dbus::{Message, MessageItem};
+use ipc_channel::ipc;
+use net::bluetooth_events::{Watched, properties_changed};
+use net_traits::bluetooth_thread::{BluetoothDeviceMsg, BluetoothEventMsg};
+
+const CHARACTERISTIC_PATH: &'static str = "/org/bluez/hci0/dev_00_11_22_33_44_55/service000a/char000b";
+const DEVICE_PATH: &'static str = "/org/bluez/hci0/dev_00_11_22_33_44_55";
+
+/// The signal BlueZ sends when properties of the object at `path` change.
+fn properties_changed_signal(path: &str, interface: &str, changed: Vec<(&str, MessageItem)>) -> Message {
+    let changed = changed.into_iter().map(|(name, value)| {
+        let value = MessageItem::Variant(Box::new(value));
+        MessageItem::DictEntry(Box::new(MessageItem::Str(name.to_owned())), Box::new(value))
+    }).collect();
+    let mut message = Message::new_signal(path, "org.freedesktop.DBus.Properties", "PropertiesChanged").unwrap();
+    message.append_items(&[MessageItem::Str(interface.to_owned()), MessageItem::new_array(changed).unwrap()]);
+    message
+}
+
+fn watched_device() -> BluetoothDeviceMsg {
+    BluetoothDeviceMsg {
+        id: "device".to_owned(),
+        name: Some("Heart Rate".to_owned()),
+        appearance: None,
+        tx_power: None,
+        rssi: Some(-70),
+    }
+}
+
+#[test]
+fn test_notified_value_is_sent_to_the_listener_of_the_characteristic() {
+    let (listener, events) = ipc::channel().unwrap();
+    let mut watched = Watched::default();
+    watched.characteristics.insert(CHARACTERISTIC_PATH.to_owned(), listener);
+
+    let value = MessageItem::new_array(vec![MessageItem::Byte(6), MessageItem::Byte(72)]).unwrap();
+    let signal = properties_changed_signal(CHARACTERISTIC_PATH,
+                                           "org.bluez.GattCharacteristic1",
+                                           vec![("Value", value)]);
+    properties_changed(&mut watched, &signal);
+
+    match events.try_recv().unwrap() {
+        BluetoothEventMsg::CharacteristicValueChanged(id, value) => {
+            assert_eq!(id, CHARACTERISTIC_PATH);
+            assert_eq!(value, vec![6, 72]);
+        },
+        _ => panic!("Expected a characteristic value"),
+    }
+}
+
+#[test]
+fn test_new_signal_strength_is_an_advertisement_of_the_watched_device() {
+    let (listener, events) = ipc::channel().unwrap();
+    let mut watched = Watched::default();
+    watched.devices.insert(DEVICE_PATH.to_owned(), (listener, watched_device()));
+
+    let signal = properties_changed_signal(DEVICE_PATH,
+                                           "org.bluez.Device1",
+                                           vec![("TxPower", MessageItem::Int16(4)), ("RSSI", MessageItem::Int16(-52))]);
+    properties_changed(&mut watched, &signal);
+
+    match events.try_recv().unwrap() {
+        BluetoothEventMsg::AdvertisementReceived(device) => {
+            assert_eq!(device.id, "device");
+            assert_eq!(device.name, Some("Heart Rate".to_owned()));
+            assert_eq!(device.tx_power, Some(4));
+            assert_eq!(device.rssi, Some(-52));
+        },
+        _ => panic!("Expected an advertisement"),
+    }
+}
+
+#[test]
+fn test_other_device_changes_are_not_advertisements() {
+    let (listener, events) = ipc::channel().unwrap();
+    let mut watched = Watched::default();
+    watched.devices.insert(DEVICE_PATH.to_owned(), (listener, watched_device()));
+
+    let signal = properties_changed_signal(DEVICE_PATH,
+                                           "org.bluez.Device1",
+                                           vec![("Connected", MessageItem::Bool(true))]);
+    properties_changed(&mut watched, &signal);
+    let signal = properties_changed_signal("/org/bluez/hci0/dev_66_77_88_99_AA_BB",
+                                           "org.bluez.Device1",
+                                           vec![("RSSI", MessageItem::Int16(-40))]);
+    properties_changed(&mut watched, &signal);
+
+    assert!(events.try_recv().is_err());
+}
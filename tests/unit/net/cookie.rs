@@ -133,3 +133,69 @@ fn test_sort_order() {
     assert!(CookieStorage::cookie_comparator(&a_prime, &a) == Ordering::Greater);
     assert!(CookieStorage::cookie_comparator(&a, &a) == Ordering::Equal);
 }
+
+#[test]
+fn test_domain_attribute_is_normalized() {
+    let url = &Url::parse("http://www.example.com/").unwrap();
+    let cookie = cookie_rs::Cookie::parse("baz=bar; Domain=.Example.COM").unwrap();
+    let cookie = Cookie::new_wrapped(cookie, url, CookieSource::HTTP).unwrap();
+    assert!(&cookie.cookie.domain.as_ref().unwrap()[..] == "example.com");
+    assert!(!cookie.host_only);
+}
+
+#[test]
+fn test_oversized_cookie_is_rejected() {
+    let url = &Url::parse("http://example.com/").unwrap();
+    let value: String = (0..4096).map(|_| 'a').collect();
+    let cookie = cookie_rs::Cookie::parse(&format!("baz={}", value)).unwrap();
+    assert!(Cookie::new_wrapped(cookie, url, CookieSource::HTTP).is_none());
+}
+
+#[test]
+fn test_expired_cookie_deletes_existing_cookie() {
+    let url = &Url::parse("http://example.com/").unwrap();
+    let mut storage = CookieStorage::new();
+    let cookie = cookie_rs::Cookie::parse("baz=bar").unwrap();
+    storage.push(Cookie::new_wrapped(cookie, url, CookieSource::NonHTTP).unwrap(), CookieSource::NonHTTP);
+    assert_eq!(storage.cookies_for_url(url, CookieSource::NonHTTP), Some("baz=bar".to_owned()));
+
+    let cookie = cookie_rs::Cookie::parse("baz=; Max-Age=0").unwrap();
+    storage.push(Cookie::new_wrapped(cookie, url, CookieSource::NonHTTP).unwrap(), CookieSource::NonHTTP);
+    assert_eq!(storage.cookies_for_url(url, CookieSource::NonHTTP), None);
+    assert!(storage.is_empty());
+}
+
+#[test]
+fn test_http_only_cookies_are_hidden_from_scripts() {
+    let url = &Url::parse("http://example.com/").unwrap();
+    let mut storage = CookieStorage::new();
+    let cookie = cookie_rs::Cookie::parse("secret=1; HttpOnly").unwrap();
+    storage.push(Cookie::new_wrapped(cookie, url, CookieSource::HTTP).unwrap(), CookieSource::HTTP);
+    let cookie = cookie_rs::Cookie::parse("visible=2").unwrap();
+    storage.push(Cookie::new_wrapped(cookie, url, CookieSource::HTTP).unwrap(), CookieSource::HTTP);
+
+    assert_eq!(storage.cookies_for_url(url, CookieSource::NonHTTP), Some("visible=2".to_owned()));
+
+    // A script can't overwrite an httpOnly cookie either.
+    let cookie = cookie_rs::Cookie::parse("secret=3").unwrap();
+    storage.push(Cookie::new_wrapped(cookie, url, CookieSource::NonHTTP).unwrap(), CookieSource::NonHTTP);
+    assert_eq!(storage.cookies_for_url(url, CookieSource::HTTP), Some("secret=1; visible=2".to_owned()));
+}
+
+#[test]
+fn test_cookies_per_domain_are_limited() {
+    use net::cookie_storage::MAX_COOKIES_PER_DOMAIN;
+
+    let url = &Url::parse("http://example.com/").unwrap();
+    let mut storage = CookieStorage::new();
+    for i in 0..MAX_COOKIES_PER_DOMAIN + 10 {
+        let cookie = cookie_rs::Cookie::parse(&format!("c{}=v", i)).unwrap();
+        storage.push(Cookie::new_wrapped(cookie, url, CookieSource::HTTP).unwrap(), CookieSource::HTTP);
+    }
+    assert_eq!(storage.len(), MAX_COOKIES_PER_DOMAIN);
+
+    let other = &Url::parse("http://example.org/").unwrap();
+    let cookie = cookie_rs::Cookie::parse("other=v").unwrap();
+    storage.push(Cookie::new_wrapped(cookie, other, CookieSource::HTTP).unwrap(), CookieSource::HTTP);
+    assert_eq!(storage.len(), MAX_COOKIES_PER_DOMAIN + 1);
+}
@@ -6,6 +6,7 @@ use devtools_traits::DevtoolsControlMsg;
 use devtools_traits::HttpRequest as DevtoolsHttpRequest;
 use devtools_traits::HttpResponse as DevtoolsHttpResponse;
 use http_loader::{expect_devtools_http_request, expect_devtools_http_response};
+use http_test_server::{Reply, Routes, TestServer, Tls};
 use hyper::LanguageTag;
 use hyper::header::{Accept, AccessControlAllowCredentials, AccessControlAllowHeaders, AccessControlAllowOrigin};
 use hyper::header::{AcceptEncoding, AcceptLanguage, AccessControlAllowMethods, AccessControlMaxAge};
@@ -33,6 +34,7 @@ use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{Sender, channel};
 use std::thread;
+use std::time::Duration as StdDuration;
 use time::{self, Duration};
 use unicase::UniCase;
 use url::{Origin as UrlOrigin, Url};
@@ -554,6 +556,22 @@ fn test_fetch_redirect_count_ceiling() {
     };
 }
 
+#[test]
+fn test_fetch_redirect_records_timing() {
+    static MESSAGE: &'static [u8] = b"timed";
+    let redirect_cap = 3;
+
+    let fetch_response = setup_server_and_fetch(MESSAGE, redirect_cap);
+
+    assert!(!fetch_response.is_network_error());
+    let timing = fetch_response.timing;
+    assert_eq!(timing.redirect_count, redirect_cap as u16);
+    assert!(timing.fetch_start != 0);
+    assert!(timing.redirect_start >= timing.fetch_start);
+    assert!(timing.redirect_end >= timing.redirect_start);
+    assert!(timing.response_start >= timing.redirect_end);
+}
+
 #[test]
 fn test_fetch_redirect_count_failure() {
     static MESSAGE: &'static [u8] = b"this message shouldn't be reachable";
@@ -840,3 +858,65 @@ fn test_fetch_with_devtools() {
     assert_eq!(devhttprequest, httprequest);
     assert_eq!(devhttpresponse, httpresponse);
 }
+
+fn fetch_from_test_server(url: Url) -> Response {
+    let origin = Origin::Origin(url.origin());
+    let request = Request::new(url, Some(origin), false, None);
+    *request.referrer.borrow_mut() = Referrer::NoReferrer;
+    fetch_sync(request, None)
+}
+
+#[test]
+fn test_fetch_follows_redirect_chain() {
+    static MESSAGE: &'static [u8] = b"arrived";
+    let server = TestServer::http(Routes::new()
+        .reply("/start", Reply::redirect(StatusCode::Found, "/middle"))
+        .reply("/middle", Reply::redirect(StatusCode::MovedPermanently, "/end"))
+        .reply("/end", Reply::ok(MESSAGE)));
+
+    let fetch_response = fetch_from_test_server(server.url_for("/start"));
+
+    assert!(!fetch_response.is_network_error());
+    match *fetch_response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => assert_eq!(&**body, MESSAGE),
+        _ => panic!(),
+    };
+    let paths: Vec<_> = server.requests().into_iter().map(|request| request.path).collect();
+    assert_eq!(paths, vec!["/start", "/middle", "/end"]);
+}
+
+#[test]
+fn test_fetch_delayed_chunked_response() {
+    static MESSAGE: &'static [u8] = b"sent a few bytes at a time";
+    let server = TestServer::http(Routes::new()
+        .reply("/", Reply::ok(MESSAGE)
+                          .delay(StdDuration::from_millis(50))
+                          .chunked(4, StdDuration::from_millis(10))));
+
+    let fetch_response = fetch_from_test_server(server.url());
+
+    assert!(!fetch_response.is_network_error());
+    match *fetch_response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => assert_eq!(&**body, MESSAGE),
+        _ => panic!(),
+    };
+}
+
+#[test]
+fn test_fetch_with_untrusted_certificate_is_network_error() {
+    let server = TestServer::https(Routes::new().reply("/", Reply::ok(b"secret")), Tls::SelfSigned);
+
+    let fetch_response = fetch_from_test_server(server.url());
+
+    assert!(fetch_response.is_network_error());
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_fetch_from_server_without_tls_is_network_error() {
+    let server = TestServer::https(Routes::new(), Tls::NotTls);
+
+    let fetch_response = fetch_from_test_server(server.url());
+
+    assert!(fetch_response.is_network_error());
+}
@@ -170,6 +170,18 @@ fn redirect_with_headers(host: String, mut headers: Headers) -> MockResponse {
     )
 }
 
+fn temporary_redirect_to(host: String) -> MockResponse {
+    let mut headers = Headers::new();
+    headers.set(Location(host.to_owned()));
+
+    MockResponse::new(
+        headers,
+        StatusCode::TemporaryRedirect,
+        RawStatus(307, Cow::Borrowed("Temporary Redirect")),
+        b"".to_vec()
+    )
+}
+
 fn respond_404() -> MockResponse {
     MockResponse::new(
         Headers::new(),
@@ -182,6 +194,7 @@ fn respond_404() -> MockResponse {
 enum ResponseType {
     Redirect(String),
     RedirectWithHeaders(String, Headers),
+    TemporaryRedirect(String),
     Text(Vec<u8>),
     WithHeaders(Vec<u8>, Headers),
     NeedsAuth(Headers),
@@ -206,6 +219,9 @@ fn response_for_request_type(t: ResponseType) -> Result<MockResponse, LoadError>
         ResponseType::RedirectWithHeaders(location, headers) => {
             Ok(redirect_with_headers(location, headers))
         },
+        ResponseType::TemporaryRedirect(location) => {
+            Ok(temporary_redirect_to(location))
+        },
         ResponseType::Text(b) => {
             Ok(respond_with(b))
         },
@@ -737,7 +753,7 @@ fn test_load_should_decode_the_response_as_gzip_when_response_headers_have_conte
 }
 
 #[test]
-fn test_load_doesnt_send_request_body_on_any_redirect() {
+fn test_load_doesnt_send_request_body_when_redirecting_from_a_post() {
     struct Factory;
 
     impl HttpRequestFactory for Factory {
@@ -765,6 +781,51 @@ fn test_load_doesnt_send_request_body_on_any_redirect() {
     let url = Url::parse("http://mozilla.com").unwrap();
     let mut load_data = LoadData::new(LoadContext::Browsing, url.clone(), &HttpTest);
 
+    load_data.method = Method::Post;
+    load_data.data = Some(<[_]>::to_vec("Body on POST!".as_bytes()));
+
+    let http_state = HttpState::new();
+    let ui_provider = TestProvider::new();
+
+    let _ = load(
+        &load_data, &ui_provider, &http_state,
+        None,
+        &Factory,
+        DEFAULT_USER_AGENT.to_owned(),
+        &CancellationListener::new(None),
+        None);
+}
+
+#[test]
+fn test_load_resends_request_body_on_temporary_redirect() {
+    struct Factory;
+
+    impl HttpRequestFactory for Factory {
+        type R = AssertMustHaveBodyRequest;
+
+        fn create(&self, url: Url, _: Method, _: Headers) -> Result<AssertMustHaveBodyRequest, LoadError> {
+            if url.domain().unwrap() == "mozilla.com" {
+                Ok(
+                    AssertMustHaveBodyRequest::new(
+                        ResponseType::TemporaryRedirect("http://mozilla.org".to_owned()),
+                        Some(<[_]>::to_vec("Body on POST!".as_bytes()))
+                    )
+                )
+            } else {
+                Ok(
+                    AssertMustHaveBodyRequest::new(
+                        ResponseType::Text(<[_]>::to_vec("Yay!".as_bytes())),
+                        Some(<[_]>::to_vec("Body on POST!".as_bytes()))
+                    )
+                )
+            }
+        }
+    }
+
+    let url = Url::parse("http://mozilla.com").unwrap();
+    let mut load_data = LoadData::new(LoadContext::Browsing, url.clone(), &HttpTest);
+
+    load_data.method = Method::Post;
     load_data.data = Some(<[_]>::to_vec("Body on POST!".as_bytes()));
 
     let http_state = HttpState::new();
@@ -1312,6 +1373,39 @@ fn test_load_follows_a_redirect() {
     }
 }
 
+#[test]
+fn test_load_records_fetch_timing_across_redirects() {
+    struct Factory;
+
+    impl HttpRequestFactory for Factory {
+        type R = MockRequest;
+
+        fn create(&self, url: Url, _: Method, _: Headers) -> Result<MockRequest, LoadError> {
+            if url.domain().unwrap() == "mozilla.com" {
+                Ok(MockRequest::new(ResponseType::Redirect("http://mozilla.org".to_owned())))
+            } else {
+                Ok(MockRequest::new(ResponseType::Text(<[_]>::to_vec("Yay!".as_bytes()))))
+            }
+        }
+    }
+
+    let url = Url::parse("http://mozilla.com").unwrap();
+    let load_data = LoadData::new(LoadContext::Browsing, url.clone(), &HttpTest);
+    let http_state = HttpState::new();
+    let ui_provider = TestProvider::new();
+
+    let response = load(&load_data, &ui_provider, &http_state, None, &Factory,
+                        DEFAULT_USER_AGENT.to_owned(), &CancellationListener::new(None), None).unwrap();
+    let timing = response.metadata.timing;
+    assert_eq!(timing.redirect_count, 1);
+    assert_eq!(timing.redirect_start, timing.fetch_start);
+    assert!(timing.redirect_end >= timing.redirect_start);
+    assert!(timing.connect_start >= timing.redirect_end);
+    assert!(timing.request_start >= timing.connect_end);
+    assert!(timing.response_start >= timing.request_start);
+    assert_eq!(timing.secure_connection_start, 0);
+}
+
 struct DontConnectFactory;
 
 impl HttpRequestFactory for DontConnectFactory {
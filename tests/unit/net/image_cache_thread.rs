@@ -0,0 +1,98 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use ipc_channel::ipc;
+use net::image_cache_thread::new_image_cache_thread;
+use net::resource_thread::new_core_resource_thread;
+use net_traits::image::base::Image;
+use net_traits::image_cache_thread::{ImageCacheChan, ImageCacheResult, ImageCacheThread, ImageResponse};
+use profile_traits::time::ProfilerChan;
+use std::env;
+use std::sync::Arc;
+use url::Url;
+use util::prefs::{PREFS, PrefValue};
+
+const MEMORY_LIMIT_PREF: &'static str = "image.cache.memory-limit-mb";
+
+fn test_image_url() -> Url {
+    let mut path = env::current_dir().expect("didn't get working dir");
+    path.push("test.jpeg");
+    Url::from_file_path(path).unwrap()
+}
+
+/// The test image under another URL, so that the cache keeps it separately.
+fn test_image_url_with_query(query: &str) -> Url {
+    let mut url = test_image_url();
+    url.set_query(Some(query));
+    url
+}
+
+fn new_image_cache() -> ImageCacheThread {
+    let (tx, _rx) = ipc::channel().unwrap();
+    let (resource_thread, _) = new_core_resource_thread("".to_owned(), None, ProfilerChan(tx), None);
+    new_image_cache_thread(resource_thread, None)
+}
+
+fn request_image(image_cache: &ImageCacheThread, url: Url) -> ImageCacheResult {
+    let (sender, receiver) = ipc::channel().unwrap();
+    image_cache.request_image(url, ImageCacheChan(sender), None);
+    receiver.recv().unwrap()
+}
+
+fn loaded_image(result: &ImageCacheResult) -> Arc<Image> {
+    match result.image_response {
+        ImageResponse::Loaded(ref image) => image.clone(),
+        _ => panic!("the image should have loaded"),
+    }
+}
+
+#[test]
+fn test_image_load_reports_response_metadata() {
+    let image_cache = new_image_cache();
+
+    let result = request_image(&image_cache, test_image_url());
+
+    match result.image_response {
+        ImageResponse::Loaded(_) => {}
+        _ => panic!("the image should have loaded"),
+    }
+    let metadata = result.metadata.expect("the request that started the load should get its metadata");
+    assert_eq!(metadata.final_url, test_image_url());
+}
+
+#[test]
+fn test_cached_image_does_not_report_another_fetch() {
+    let image_cache = new_image_cache();
+
+    assert!(request_image(&image_cache, test_image_url()).metadata.is_some());
+    let result = request_image(&image_cache, test_image_url());
+
+    match result.image_response {
+        ImageResponse::Loaded(_) => {}
+        _ => panic!("the cached image should be returned"),
+    }
+    assert!(result.metadata.is_none());
+}
+
+// The test image takes about 550KB decoded, so two of them don't fit in 1MB, and each load
+// evicts the decoded image before it. Its compressed bytes, about 60KB, only fit in 1MB so many
+// times. Both cases share one test, since the limit is read from a global pref.
+#[test]
+fn test_images_over_the_memory_limit_are_evicted() {
+    PREFS.set(MEMORY_LIMIT_PREF, PrefValue::Number(1.));
+    let image_cache = new_image_cache();
+    let first = loaded_image(&request_image(&image_cache, test_image_url_with_query("0")));
+    PREFS.reset(MEMORY_LIMIT_PREF);
+
+    loaded_image(&request_image(&image_cache, test_image_url_with_query("1")));
+    let result = request_image(&image_cache, test_image_url_with_query("0"));
+    assert!(result.metadata.is_none(), "an evicted image should be decoded without being fetched again");
+    assert!(&*loaded_image(&result) as *const Image != &*first as *const Image);
+
+    for i in 1..20 {
+        loaded_image(&request_image(&image_cache, test_image_url_with_query(&i.to_string())));
+    }
+    let result = request_image(&image_cache, test_image_url_with_query("0"));
+    assert!(result.metadata.is_some(), "the oldest evicted image should be fetched again");
+}
@@ -7,8 +7,11 @@
 
 extern crate content_blocker;
 extern crate cookie as cookie_rs;
+#[cfg(target_os = "linux")]
+extern crate dbus;
 extern crate devtools_traits;
 extern crate flate2;
+extern crate http_test_server;
 extern crate hyper;
 extern crate hyper_serde;
 extern crate ipc_channel;
@@ -21,15 +24,19 @@ extern crate unicase;
 extern crate url;
 extern crate util;
 
+#[cfg(all(test, target_os = "linux"))] mod bluetooth_events;
 #[cfg(test)] mod chrome_loader;
 #[cfg(test)] mod cookie;
 #[cfg(test)] mod cookie_http_state;
 #[cfg(test)] mod data_loader;
 #[cfg(test)] mod file_loader;
 #[cfg(test)] mod fetch;
+#[cfg(test)] mod image_cache_thread;
 #[cfg(test)] mod mime_classifier;
+#[cfg(test)] mod prefetch_cache;
 #[cfg(test)] mod pub_domains;
 #[cfg(test)] mod resource_thread;
 #[cfg(test)] mod hsts;
 #[cfg(test)] mod http_loader;
 #[cfg(test)] mod filemanager_thread;
+#[cfg(test)] mod storage_thread;
@@ -0,0 +1,59 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use hyper::header::{CacheControl, CacheDirective, Headers};
+use hyper_serde::Serde;
+use net::prefetch_cache::PrefetchCache;
+use net_traits::Metadata;
+use url::Url;
+
+fn metadata(url: &Url) -> Metadata {
+    Metadata::default(url.clone())
+}
+
+#[test]
+fn test_prefetched_response_is_taken_once() {
+    let url = Url::parse("http://example.com/next").unwrap();
+    let mut cache = PrefetchCache::new();
+    cache.store(&url, metadata(&url), b"next page".to_vec());
+    assert!(cache.contains(&url));
+
+    let (_, body) = cache.take(&url).unwrap();
+    assert_eq!(body, b"next page".to_vec());
+    assert!(!cache.contains(&url));
+    assert!(cache.take(&url).is_none());
+}
+
+#[test]
+fn test_no_store_response_is_not_kept() {
+    let url = Url::parse("http://example.com/private").unwrap();
+    let mut headers = Headers::new();
+    headers.set(CacheControl(vec![CacheDirective::NoStore]));
+    let mut metadata = metadata(&url);
+    metadata.headers = Some(Serde(headers));
+    assert!(!PrefetchCache::is_storable(&metadata));
+
+    let mut cache = PrefetchCache::new();
+    cache.store(&url, metadata, vec![]);
+    assert!(!cache.contains(&url));
+}
+
+#[test]
+fn test_unsuccessful_response_is_not_kept() {
+    let url = Url::parse("http://example.com/missing").unwrap();
+    let mut metadata = metadata(&url);
+    metadata.status = Some((404, b"Not Found".to_vec()));
+
+    let mut cache = PrefetchCache::new();
+    cache.store(&url, metadata, vec![]);
+    assert!(!cache.contains(&url));
+}
+
+#[test]
+fn test_fragment_is_ignored() {
+    let url = Url::parse("http://example.com/next").unwrap();
+    let mut cache = PrefetchCache::new();
+    cache.store(&url, metadata(&url), vec![]);
+    assert!(cache.take(&Url::parse("http://example.com/next#section").unwrap()).is_some());
+}
@@ -0,0 +1,76 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use ipc_channel::ipc::{self, IpcSender};
+use net::storage_thread::StorageThreadFactory;
+use net_traits::storage_thread::{StorageThreadMsg, StorageType};
+use std::iter;
+use url::Url;
+
+fn set_item(thread: &IpcSender<StorageThreadMsg>, url: &Url, storage_type: StorageType, name: &str, value: &str)
+            -> Result<(bool, Option<String>), ()> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(StorageThreadMsg::SetItem(sender, url.clone(), storage_type, name.to_owned(), value.to_owned()))
+          .unwrap();
+    receiver.recv().unwrap()
+}
+
+#[test]
+fn test_estimate_adds_up_all_storage_of_the_origin() {
+    let thread: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(None);
+    let url = Url::parse("http://example.com/a").unwrap();
+    set_item(&thread, &url, StorageType::Local, "key", "value").unwrap();
+    set_item(&thread, &url, StorageType::Session, "k", "v").unwrap();
+    set_item(&thread, &Url::parse("http://example.org/").unwrap(), StorageType::Local, "other", "origin").unwrap();
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(StorageThreadMsg::Estimate(sender, Url::parse("http://example.com/b").unwrap())).unwrap();
+    let estimate = receiver.recv().unwrap();
+    assert_eq!(estimate.usage, 10);
+    assert_eq!(estimate.quota, 5 * 1024 * 1024);
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(StorageThreadMsg::Exit(sender)).unwrap();
+    receiver.recv().unwrap();
+}
+
+#[test]
+fn test_quota_is_shared_between_storage_types() {
+    let thread: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(None);
+    let url = Url::parse("http://example.com/").unwrap();
+    let half: String = iter::repeat('x').take(3 * 1024 * 1024).collect();
+    assert!(set_item(&thread, &url, StorageType::Local, "a", &half).is_ok());
+    assert!(set_item(&thread, &url, StorageType::Session, "b", &half).is_err());
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(StorageThreadMsg::Exit(sender)).unwrap();
+    receiver.recv().unwrap();
+}
+
+#[test]
+fn test_persist() {
+    let thread: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(None);
+    let url = Url::parse("http://example.com/").unwrap();
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(StorageThreadMsg::Persisted(sender, url.clone())).unwrap();
+    assert!(!receiver.recv().unwrap());
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(StorageThreadMsg::Persist(sender, url.clone())).unwrap();
+    assert!(receiver.recv().unwrap());
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(StorageThreadMsg::Persisted(sender, Url::parse("http://example.com/other").unwrap())).unwrap();
+    assert!(receiver.recv().unwrap());
+
+    // An opaque origin has no storage to keep.
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(StorageThreadMsg::Persist(sender, Url::parse("data:text/html,").unwrap())).unwrap();
+    assert!(!receiver.recv().unwrap());
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(StorageThreadMsg::Exit(sender)).unwrap();
+    receiver.recv().unwrap();
+}
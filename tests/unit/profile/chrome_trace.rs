@@ -0,0 +1,23 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use profile::chrome_trace::TraceCategory;
+use profile_traits::time::ProfilerCategory;
+
+#[test]
+fn trace_category_names_round_trip() {
+    for category in TraceCategory::all() {
+        assert_eq!(TraceCategory::from_name(category.name()), Some(category));
+    }
+    assert_eq!(TraceCategory::from_name("frobnication"), None);
+}
+
+#[test]
+fn trace_category_of_profiler_category() {
+    assert_eq!(TraceCategory::of(ProfilerCategory::ScriptEvaluate), TraceCategory::Script);
+    assert_eq!(TraceCategory::of(ProfilerCategory::LayoutStyleRecalc), TraceCategory::Style);
+    assert_eq!(TraceCategory::of(ProfilerCategory::LayoutPerform), TraceCategory::Layout);
+    assert_eq!(TraceCategory::of(ProfilerCategory::LayoutDispListBuild), TraceCategory::DisplayList);
+    assert_eq!(TraceCategory::of(ProfilerCategory::Compositing), TraceCategory::Compositor);
+}
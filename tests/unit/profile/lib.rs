@@ -6,5 +6,7 @@ extern crate ipc_channel;
 extern crate profile;
 extern crate profile_traits;
 
+#[cfg(test)]
+mod chrome_trace;
 #[cfg(test)]
 mod time;
@@ -8,7 +8,7 @@ use profile_traits::time::ProfilerMsg;
 
 #[test]
 fn time_profiler_smoke_test() {
-    let chan = time::Profiler::create(&None, None);
+    let chan = time::Profiler::create(&None, None, None);
     assert!(true, "Can create the profiler thread");
 
     let (ipcchan, _ipcport) = ipc::channel().unwrap();
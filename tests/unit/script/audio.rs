@@ -0,0 +1,144 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::audio::{AudioBufferData, BlockInfo};
+use script::audio::analyser::fft;
+use script::audio::decoder::{DecodeError, decode_audio_data, resample};
+use script::audio::block::Block;
+use script::audio::param::{AutomationEvent, Param};
+use script::audio::sink::{AudioSink, PipelineAudio, PipelineSink};
+use std::sync::{Arc, Mutex};
+
+fn push_u16(data: &mut Vec<u8>, value: u16) {
+    data.push(value as u8);
+    data.push((value >> 8) as u8);
+}
+
+fn push_u32(data: &mut Vec<u8>, value: u32) {
+    push_u16(data, value as u16);
+    push_u16(data, (value >> 16) as u16);
+}
+
+/// A mono WAVE file with 16-bit samples.
+fn wave_file(sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+    let mut data = b"RIFF".to_vec();
+    push_u32(&mut data, 36 + samples.len() as u32 * 2);
+    data.extend_from_slice(b"WAVEfmt ");
+    push_u32(&mut data, 16);
+    push_u16(&mut data, 1);
+    push_u16(&mut data, 1);
+    push_u32(&mut data, sample_rate);
+    push_u32(&mut data, sample_rate * 2);
+    push_u16(&mut data, 2);
+    push_u16(&mut data, 16);
+    data.extend_from_slice(b"data");
+    push_u32(&mut data, samples.len() as u32 * 2);
+    for sample in samples {
+        push_u16(&mut data, *sample as u16);
+    }
+    data
+}
+
+#[test]
+fn test_decode_wave() {
+    let data = wave_file(8000, &[0, 16384, -32768]);
+    let decoded = decode_audio_data(&data, 8000.).unwrap();
+    assert_eq!(decoded, AudioBufferData {
+        channels: vec![vec![0., 0.5, -1.]],
+        sample_rate: 8000.,
+    });
+}
+
+#[test]
+fn test_decode_invalid_data() {
+    assert_eq!(decode_audio_data(b"not audio", 8000.), Err(DecodeError::UnsupportedFormat));
+    let mut data = wave_file(8000, &[0, 0]);
+    data.truncate(40);
+    assert_eq!(decode_audio_data(&data, 8000.), Err(DecodeError::Malformed));
+}
+
+#[test]
+fn test_resample() {
+    let data = AudioBufferData {
+        channels: vec![vec![0., 1.]],
+        sample_rate: 1.,
+    };
+    assert_eq!(resample(data, 2.).channels, vec![vec![0., 0.5, 1., 1.]]);
+}
+
+#[test]
+fn test_param_linear_ramp() {
+    let mut param = Param::new(0.);
+    param.insert_event(AutomationEvent::LinearRampToValueAtTime(1., 1.));
+    // A block of 128 frames lasts exactly one second.
+    let values = param.values(&BlockInfo { sample_rate: 128., time: 0. });
+    assert_eq!(values[0], 0.);
+    assert_eq!(values[64], 0.5);
+    let values = param.values(&BlockInfo { sample_rate: 128., time: 1. });
+    assert_eq!(values[0], 1.);
+    assert_eq!(values[127], 1.);
+}
+
+#[test]
+fn test_param_set_value_at_time() {
+    let mut param = Param::new(1.);
+    param.insert_event(AutomationEvent::SetValueAtTime(2., 0.5));
+    let values = param.values(&BlockInfo { sample_rate: 128., time: 0. });
+    assert_eq!(values[63], 1.);
+    assert_eq!(values[64], 2.);
+}
+
+#[test]
+fn test_fft_of_impulse() {
+    let mut real = vec![1., 0., 0., 0.];
+    let mut imaginary = vec![0.; 4];
+    fft(&mut real, &mut imaginary);
+    assert_eq!(real, vec![1.; 4]);
+    assert!(imaginary.iter().all(|value| value.abs() < 1e-6));
+}
+
+/// Records the blocks pushed to it.
+struct RecordingSink(Arc<Mutex<Vec<Block>>>);
+
+impl AudioSink for RecordingSink {
+    fn start(&mut self, _sample_rate: f32, _channels: usize) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn push(&mut self, block: &Block) {
+        self.0.lock().unwrap().push(block.clone());
+    }
+
+    fn stop(&mut self) {}
+}
+
+#[test]
+fn test_pipeline_sink() {
+    let changes = Arc::new(Mutex::new(vec![]));
+    let listener_changes = changes.clone();
+    let audio = PipelineAudio::new(Box::new(move |audible| listener_changes.lock().unwrap().push(audible)));
+    let pushed = Arc::new(Mutex::new(vec![]));
+    let mut sink = PipelineSink::new(Box::new(RecordingSink(pushed.clone())), audio.clone());
+    let sound = Block { channels: vec![vec![0.5; 128]] };
+
+    // A block of 128 frames lasts one second.
+    sink.start(128., 1).unwrap();
+    sink.push(&Block::silence(1));
+    assert_eq!(*changes.lock().unwrap(), vec![]);
+    sink.push(&sound);
+    assert_eq!(*changes.lock().unwrap(), vec![true]);
+
+    // Muted sinks output silence, and stay audible.
+    audio.set_muted(true);
+    sink.push(&sound);
+    assert_eq!(pushed.lock().unwrap()[2], Block::silence(1));
+    assert_eq!(*changes.lock().unwrap(), vec![true]);
+
+    // A second of silence, or stopping, makes a sink inaudible.
+    sink.push(&Block::silence(1));
+    assert_eq!(*changes.lock().unwrap(), vec![true, false]);
+    sink.push(&sound);
+    sink.stop();
+    assert_eq!(*changes.lock().unwrap(), vec![true, false, true, false]);
+}
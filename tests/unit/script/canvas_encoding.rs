@@ -0,0 +1,26 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::{ImageEncoding, encode_image};
+use euclid::size::Size2D;
+
+#[test]
+fn test_encoding_from_mime_type() {
+    assert_eq!(ImageEncoding::from_mime_type("image/png", None), ImageEncoding::Png);
+    assert_eq!(ImageEncoding::from_mime_type("IMAGE/JPEG", Some(0.5)), ImageEncoding::Jpeg(50));
+    // Qualities out of range use the default.
+    assert_eq!(ImageEncoding::from_mime_type("image/jpeg", Some(2.)), ImageEncoding::Jpeg(92));
+    // Unsupported types fall back to PNG.
+    assert_eq!(ImageEncoding::from_mime_type("image/webp", Some(0.5)), ImageEncoding::Png);
+    assert_eq!(ImageEncoding::from_mime_type("", None), ImageEncoding::Png);
+}
+
+#[test]
+fn test_encode_image() {
+    let pixels = vec![255; 4 * 4 * 4];
+    let png = encode_image(&pixels, Size2D::new(4, 4), ImageEncoding::Png);
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    let jpeg = encode_image(&pixels, Size2D::new(4, 4), ImageEncoding::Jpeg(92));
+    assert_eq!(&jpeg[..2], b"\xff\xd8");
+}
@@ -0,0 +1,44 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::dom::bindings::str::DOMString;
+
+#[test]
+fn test_parse_date_string() {
+    assert_eq!(DOMString::from("2016-10-15").parse_date_string(), Ok((2016, 10, 15)));
+    assert_eq!(DOMString::from("12016-02-29").parse_date_string(), Ok((12016, 2, 29)));
+    assert_eq!(DOMString::from("2000-02-29").parse_date_string(), Ok((2000, 2, 29)));
+    assert!(DOMString::from("1900-02-29").parse_date_string().is_err());
+    assert!(DOMString::from("2016-04-31").parse_date_string().is_err());
+    assert!(DOMString::from("0000-01-01").parse_date_string().is_err());
+    assert!(DOMString::from("216-01-01").parse_date_string().is_err());
+    assert!(DOMString::from("2016-1-01").parse_date_string().is_err());
+    assert!(DOMString::from("2016-13-01").parse_date_string().is_err());
+    assert!(DOMString::from("2016-10-15 ").parse_date_string().is_err());
+    assert!(DOMString::from("").parse_date_string().is_err());
+}
+
+#[test]
+fn test_parse_time_string() {
+    assert_eq!(DOMString::from("00:00").parse_time_string(), Ok((0, 0, 0.)));
+    assert_eq!(DOMString::from("23:59:59").parse_time_string(), Ok((23, 59, 59.)));
+    assert_eq!(DOMString::from("12:30:05.5").parse_time_string(), Ok((12, 30, 5.5)));
+    assert_eq!(DOMString::from("12:30:05.125").parse_time_string(), Ok((12, 30, 5.125)));
+    assert!(DOMString::from("24:00").parse_time_string().is_err());
+    assert!(DOMString::from("12:60").parse_time_string().is_err());
+    assert!(DOMString::from("12:30:60").parse_time_string().is_err());
+    assert!(DOMString::from("12:30:05.1250").parse_time_string().is_err());
+    assert!(DOMString::from("12:30:05.").parse_time_string().is_err());
+    assert!(DOMString::from("1:30").parse_time_string().is_err());
+}
+
+#[test]
+fn test_is_valid_simple_color_string() {
+    assert!(DOMString::from("#000000").is_valid_simple_color_string());
+    assert!(DOMString::from("#00FFaa").is_valid_simple_color_string());
+    assert!(!DOMString::from("#fff").is_valid_simple_color_string());
+    assert!(!DOMString::from("red").is_valid_simple_color_string());
+    assert!(!DOMString::from("#00ffgg").is_valid_simple_color_string());
+    assert!(!DOMString::from("000000").is_valid_simple_color_string());
+}
@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use encoding::types::EncodingRef;
+use script::parse::encoding::{DocumentDecoder, extract_encoding_from_content, prescan, sniff_bom};
+
+fn name(encoding: Option<EncodingRef>) -> Option<&'static str> {
+    encoding.and_then(|encoding| encoding.whatwg_name())
+}
+
+#[test]
+fn byte_order_marks_are_sniffed() {
+    assert_eq!(sniff_bom(b"\xEF\xBB\xBFa").map(|(encoding, length)| (encoding.whatwg_name(), length)),
+               Some((Some("utf-8"), 3)));
+    assert_eq!(sniff_bom(b"\xFF\xFEa\x00").map(|(encoding, length)| (encoding.whatwg_name(), length)),
+               Some((Some("utf-16le"), 2)));
+    assert!(sniff_bom(b"<html>").is_none());
+}
+
+#[test]
+fn prescan_finds_meta_charset() {
+    assert_eq!(name(prescan(b"<!doctype html><meta charset=windows-1251>")), Some("windows-1251"));
+    assert_eq!(name(prescan(b"<META CHARSET='KOI8-R'>")), Some("koi8-r"));
+    assert_eq!(name(prescan(b"<meta http-equiv=Content-Type content=\"text/html; charset=iso-8859-2\">")),
+               Some("iso-8859-2"));
+    // A content attribute only counts along with the pragma.
+    assert_eq!(name(prescan(b"<meta content=\"text/html; charset=iso-8859-2\">")), None);
+    // UTF-16 can't be declared in a document that was read as ASCII.
+    assert_eq!(name(prescan(b"<meta charset=utf-16le>")), Some("utf-8"));
+}
+
+#[test]
+fn prescan_skips_comments_and_other_tags() {
+    assert_eq!(name(prescan(b"<!-- <meta charset=gbk> --><meta charset=big5>")), Some("big5"));
+    assert_eq!(name(prescan(b"<div title='<meta charset=gbk>'><meta charset=euc-jp>")), Some("euc-jp"));
+    assert_eq!(name(prescan(b"<!--> <meta charset=shift_jis>")), Some("shift_jis"));
+}
+
+#[test]
+fn prescan_only_looks_at_the_first_bytes() {
+    let mut input = vec![b' '; 1024];
+    input.extend_from_slice(b"<meta charset=gbk>");
+    assert_eq!(name(prescan(&input)), None);
+}
+
+#[test]
+fn charset_is_extracted_from_content() {
+    assert_eq!(name(extract_encoding_from_content("text/html; CHARSET = \"euc-kr\"")), Some("euc-kr"));
+    assert_eq!(name(extract_encoding_from_content("text/html;charset=utf-8;x=y")), Some("utf-8"));
+    assert_eq!(name(extract_encoding_from_content("charset='unterminated")), None);
+    assert_eq!(name(extract_encoding_from_content("text/html")), None);
+}
+
+#[test]
+fn decoding_continues_sequences_across_chunks() {
+    let mut decoder = DocumentDecoder::new(::encoding::all::UTF_8);
+    let mut output = decoder.decode(b"caf\xC3");
+    output.push_str(&decoder.decode(b"\xA9 \xFF"));
+    output.push_str(&decoder.finish());
+    assert_eq!(output, "café \u{FFFD}");
+}
@@ -0,0 +1,82 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use euclid::Point2D;
+use script::dom::history::{HistoryStateEntries, fires_hashchange, resolve_state_url};
+use url::Url;
+
+fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+}
+
+#[test]
+fn push_state_adds_entries() {
+    let mut entries = HistoryStateEntries::new();
+    let initial = entries.current();
+    let first = entries.push(Point2D::new(0., 100.));
+    let second = entries.push(Point2D::new(0., 200.));
+    assert!(initial != first && first != second && initial != second);
+    assert_eq!(entries.current(), second);
+}
+
+#[test]
+fn popstate_restores_the_scroll_position_of_the_entry() {
+    let mut entries = HistoryStateEntries::new();
+    let initial = entries.current();
+    let pushed = entries.push(Point2D::new(0., 100.));
+
+    // Going back saves where the pushed entry was scrolled to, and restores the initial one.
+    assert_eq!(entries.traverse_to(initial, Point2D::new(0., 300.)), Some(Point2D::new(0., 100.)));
+    assert_eq!(entries.current(), initial);
+    assert_eq!(entries.traverse_to(pushed, Point2D::new(0., 50.)), Some(Point2D::new(0., 300.)));
+    assert_eq!(entries.traverse_to(initial, Point2D::new(0., 0.)), Some(Point2D::new(0., 50.)));
+}
+
+#[test]
+fn pushing_after_going_back_starts_a_new_entry() {
+    let mut entries = HistoryStateEntries::new();
+    let initial = entries.current();
+    let pushed = entries.push(Point2D::new(0., 100.));
+    entries.traverse_to(initial, Point2D::new(0., 0.));
+    let replacement = entries.push(Point2D::new(0., 0.));
+    assert!(replacement != pushed);
+    assert_eq!(entries.traverse_to(initial, Point2D::new(0., 20.)), Some(Point2D::new(0., 0.)));
+}
+
+#[test]
+fn hashchange_fires_when_only_the_fragment_changes() {
+    assert!(fires_hashchange(&url("http://example.com/a#one"), &url("http://example.com/a#two")));
+    assert!(fires_hashchange(&url("http://example.com/a"), &url("http://example.com/a#two")));
+    assert!(!fires_hashchange(&url("http://example.com/a#one"), &url("http://example.com/a#one")));
+    // Entries added by pushState with another path only fire popstate.
+    assert!(!fires_hashchange(&url("http://example.com/a#one"), &url("http://example.com/b#two")));
+    assert!(!fires_hashchange(&url("http://example.com/a?x#one"), &url("http://example.com/a?y#two")));
+}
+
+#[test]
+fn state_urls_are_resolved_against_the_base_url() {
+    let document = url("http://example.com/dir/page.html");
+    let base = url("http://example.com/dir/");
+    assert_eq!(resolve_state_url(&document, &base, "other.html?q#f", true).unwrap(),
+               url("http://example.com/dir/other.html?q#f"));
+    assert_eq!(resolve_state_url(&document, &base, "#f", true).unwrap(),
+               url("http://example.com/dir/#f"));
+}
+
+#[test]
+fn state_urls_must_be_same_origin() {
+    let document = url("http://example.com/page.html");
+    assert!(resolve_state_url(&document, &document, "http://example.org/page.html", true).is_err());
+    assert!(resolve_state_url(&document, &document, "https://example.com/page.html", true).is_err());
+    assert!(resolve_state_url(&document, &document, "http://example.com:8000/", true).is_err());
+    assert!(resolve_state_url(&document, &document, "http://[", true).is_err());
+}
+
+#[test]
+fn opaque_origins_may_only_change_the_fragment() {
+    let document = url("file:///dir/page.html");
+    assert!(resolve_state_url(&document, &document, "#f", false).is_ok());
+    assert!(resolve_state_url(&document, &document, "?q", false).is_err());
+    assert!(resolve_state_url(&document, &document, "other.html", false).is_err());
+}
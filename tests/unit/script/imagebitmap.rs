@@ -0,0 +1,54 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use euclid::point::Point2D;
+use euclid::rect::Rect;
+use euclid::size::Size2D;
+use script::dom::imagebitmap::{ScaleFilter, crop_and_scale, output_size};
+
+fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect<i32> {
+    Rect::new(Point2D::new(x, y), Size2D::new(width, height))
+}
+
+// A 2x2 image with red, green, blue and white pixels.
+const PIXELS: [u8; 16] = [255, 0, 0, 255,    0, 255, 0, 255,
+                          0, 0, 255, 255,    255, 255, 255, 255];
+
+#[test]
+fn test_output_size() {
+    let crop = Size2D::new(40, 30);
+    assert_eq!(output_size(crop, None, None), crop);
+    assert_eq!(output_size(crop, Some(10), Some(50)), Size2D::new(10, 50));
+    // A single dimension keeps the aspect ratio, rounding up.
+    assert_eq!(output_size(crop, Some(20), None), Size2D::new(20, 15));
+    assert_eq!(output_size(crop, None, Some(10)), Size2D::new(14, 10));
+}
+
+#[test]
+fn test_crop() {
+    let size = Size2D::new(2, 2);
+    assert_eq!(crop_and_scale(&PIXELS, size, rect(0, 0, 2, 2), size, ScaleFilter::Nearest, false),
+               PIXELS.to_vec());
+    assert_eq!(crop_and_scale(&PIXELS, size, rect(1, 1, 1, 1), Size2D::new(1, 1),
+                              ScaleFilter::Bilinear, false),
+               vec![255, 255, 255, 255]);
+
+    // The parts of the crop rectangle outside of the image are transparent black.
+    assert_eq!(crop_and_scale(&PIXELS, size, rect(1, -1, 2, 1), Size2D::new(2, 1),
+                              ScaleFilter::Nearest, false),
+               vec![0; 8]);
+}
+
+#[test]
+fn test_scale_and_flip() {
+    let size = Size2D::new(2, 2);
+    let scaled = crop_and_scale(&PIXELS, size, rect(0, 0, 2, 2), Size2D::new(4, 4),
+                                ScaleFilter::Nearest, false);
+    assert_eq!(&scaled[0..8], &[255, 0, 0, 255, 255, 0, 0, 255]);
+    assert_eq!(&scaled[56..64], &[255, 255, 255, 255, 255, 255, 255, 255]);
+
+    let flipped = crop_and_scale(&PIXELS, size, rect(0, 0, 2, 2), size, ScaleFilter::Nearest, true);
+    assert_eq!(&flipped[0..8], &PIXELS[8..16]);
+    assert_eq!(&flipped[8..16], &PIXELS[0..8]);
+}
@@ -5,11 +5,33 @@
 #![feature(plugin)]
 #![plugin(plugins)]
 
+extern crate canvas_traits;
+extern crate encoding;
+extern crate euclid;
 extern crate msg;
+extern crate net_traits;
 extern crate script;
+extern crate script_layout_interface;
 extern crate url;
 
+#[cfg(test)] mod audio;
+#[cfg(test)] mod canvas_encoding;
+#[cfg(test)] mod dom_string;
+#[cfg(test)] mod encoding_sniffing;
+#[cfg(test)] mod history;
+#[cfg(test)] mod imagebitmap;
+#[cfg(test)] mod media;
 #[cfg(test)] mod origin;
+#[cfg(test)] mod path2d;
+#[cfg(test)] mod resource_timing;
+#[cfg(test)] mod responsive_images;
+#[cfg(test)] mod selection;
+#[cfg(test)] mod server_timing;
+#[cfg(test)] mod serviceworker;
 #[cfg(all(test, target_pointer_width = "64"))] mod size_of;
+#[cfg(test)] mod task_attribution;
 #[cfg(test)] mod textinput;
 #[cfg(test)] mod headers;
+#[cfg(test)] mod webcrypto;
+#[cfg(test)] mod xmlserializer;
+#[cfg(test)] mod xpath;
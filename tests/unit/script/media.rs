@@ -0,0 +1,384 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::media::{AudioChunk, CaptureBackend, CaptureSettings, MediaData, VideoFrame};
+use script::media::camera::{DeviceCamera, FakeCamera, yuyv_to_rgba};
+use script::media::microphone::{DeviceMicrophone, FakeMicrophone};
+use script::media::player::{can_append_type, can_play_type};
+use script::media::recorder::RecordingFormat;
+use script::media::source::{SourceData, intersect_ranges, track_ranges};
+use script::media::vp8::{i420_to_rgba, rgba_to_i420};
+use script::media::webm::{WebmFrame, WebmMuxer, WebmParser, WebmSegment, WebmTrack, read_webm};
+use script::media::webvtt::{self, WebVttCue, cue_text_to_plain_text, parse_timestamp};
+use script_layout_interface::{CueAlign, CueSettings};
+use std::sync::Arc;
+
+#[test]
+fn test_yuyv_to_rgba() {
+    // Two black pixels, then two white ones.
+    let yuyv = [16, 128, 16, 128, 235, 128, 235, 128];
+    assert_eq!(yuyv_to_rgba(4, 1, &yuyv), vec![0, 0, 0, 255, 0, 0, 0, 255,
+                                                255, 255, 255, 255, 255, 255, 255, 255]);
+}
+
+#[test]
+fn test_blanked_video_frame() {
+    let frame = MediaData::Video(Arc::new(VideoFrame {
+        width: 2,
+        height: 1,
+        data: vec![10, 20, 30, 40, 50, 60, 70, 80],
+    }));
+    match frame.blanked() {
+        MediaData::Video(frame) => assert_eq!(frame.data, vec![0, 0, 0, 255, 0, 0, 0, 255]),
+        MediaData::Audio(_) => panic!("A blanked frame should stay a frame"),
+    }
+}
+
+#[test]
+fn test_blanked_audio_chunk() {
+    let chunk = MediaData::Audio(Arc::new(AudioChunk {
+        sample_rate: 8000.,
+        channels: vec![vec![0.5, -0.5], vec![0.25, 1.]],
+    }));
+    match chunk.blanked() {
+        MediaData::Audio(chunk) => {
+            assert_eq!(chunk.sample_rate, 8000.);
+            assert_eq!(chunk.channels, vec![vec![0., 0.], vec![0., 0.]]);
+        },
+        MediaData::Video(_) => panic!("A blanked chunk should stay a chunk"),
+    }
+}
+
+#[test]
+fn test_fake_camera() {
+    let mut camera = FakeCamera::new();
+    let settings = CaptureSettings { width: 32, height: 16, ..CaptureSettings::default() };
+    assert_eq!(camera.open(&settings), Ok(settings));
+    match camera.read() {
+        Some(MediaData::Video(frame)) => {
+            assert_eq!((frame.width, frame.height), (32, 16));
+            assert_eq!(frame.data.len(), 32 * 16 * 4);
+        },
+        _ => panic!("The fake camera should output frames"),
+    }
+    camera.close();
+}
+
+#[test]
+fn test_fake_microphone() {
+    let mut microphone = FakeMicrophone::new();
+    let settings = CaptureSettings { sample_rate: 8000., channel_count: 2, ..CaptureSettings::default() };
+    assert_eq!(microphone.open(&settings), Ok(settings));
+    match microphone.read() {
+        Some(MediaData::Audio(chunk)) => {
+            assert_eq!(chunk.sample_rate, 8000.);
+            assert_eq!(chunk.channels.len(), 2);
+            assert_eq!(chunk.channels[0].len(), 80);
+            assert!(chunk.channels[0].iter().any(|&sample| sample != 0.));
+        },
+        _ => panic!("The fake microphone should output audio"),
+    }
+    microphone.close();
+}
+
+#[test]
+fn test_missing_devices_fail_to_open() {
+    let settings = CaptureSettings::default();
+    let mut camera = DeviceCamera::new("/dev/no-such-camera");
+    assert_eq!(camera.open(&settings), Err(()));
+    assert!(camera.read().is_none());
+    let mut microphone = DeviceMicrophone::new("no-such-microphone");
+    assert_eq!(microphone.open(&settings), Err(()));
+    assert!(microphone.read().is_none());
+}
+
+#[test]
+fn test_rgba_to_i420() {
+    // A white pixel, a black one, and an odd column of red ones.
+    let rgba = [255, 255, 255, 255, 0, 0, 0, 255, 255, 0, 0, 255,
+                255, 255, 255, 255, 0, 0, 0, 255, 255, 0, 0, 255];
+    let (luma, u_plane, v_plane) = rgba_to_i420(3, 2, &rgba);
+    assert_eq!(luma, vec![235, 16, 82, 235, 16, 82]);
+    assert_eq!(u_plane, vec![128, 90]);
+    assert_eq!(v_plane, vec![128, 240]);
+}
+
+#[test]
+fn test_recording_format() {
+    let formats = [
+        ("video/webm", Some((true, true))),
+        ("audio/webm", Some((true, false))),
+        ("video/webm;codecs=vp8", Some((false, true))),
+        ("video/webm; codecs=\"vp8, opus\"", Some((true, true))),
+        ("Audio/WebM;codecs=opus", Some((true, false))),
+        ("audio/webm;codecs=vp8", None),
+        ("video/webm;codecs=h264", None),
+        ("video/mp4", None),
+        ("", None),
+    ];
+    for &(mime_type, expected) in &formats {
+        let format = RecordingFormat::from_mime_type(mime_type).map(|format| (format.audio, format.video));
+        assert!(format == expected, "Unexpected format for {}", mime_type);
+    }
+    assert_eq!(RecordingFormat { audio: true, video: false }.mime_type(), "audio/webm;codecs=opus");
+}
+
+#[test]
+fn test_webm_header() {
+    let mut muxer = WebmMuxer::new(&[WebmTrack::Vp8 { width: 2, height: 2 }]);
+    let header = muxer.take_data();
+    assert_eq!(&header[..4], &[0x1A, 0x45, 0xDF, 0xA3]);
+    assert!(header.windows(4).any(|bytes| bytes == b"webm"));
+    assert!(header.windows(5).any(|bytes| bytes == b"V_VP8"));
+    assert!(muxer.take_data().is_empty());
+}
+
+#[test]
+fn test_webm_clusters() {
+    let mut muxer = WebmMuxer::new(&[WebmTrack::Vp8 { width: 2, height: 2 }]);
+    muxer.take_data();
+
+    muxer.add_frame(0, 0, true, &[1, 2, 3]);
+    assert_eq!(muxer.take_data(), vec![0x1F, 0x43, 0xB6, 0x75, 0x8C,
+                                       0xE7, 0x81, 0x00,
+                                       0xA3, 0x87, 0x81, 0x00, 0x00, 0x80, 1, 2, 3]);
+
+    // Taking the data ends the cluster, and key frames start new ones.
+    muxer.add_frame(0, 40, false, &[4]);
+    muxer.add_frame(0, 80, true, &[5]);
+    muxer.add_frame(0, 120, false, &[6]);
+    let data = muxer.take_data();
+    assert_eq!(data.windows(4).filter(|bytes| *bytes == [0x1F, 0x43, 0xB6, 0x75]).count(), 2);
+    assert_eq!(&data[..8], &[0x1F, 0x43, 0xB6, 0x75, 0x8A, 0xE7, 0x81, 40]);
+}
+
+#[test]
+fn test_i420_to_rgba() {
+    // A white pixel and a black one over two gray ones, with padded rows.
+    let luma = [235, 16, 0, 126, 126, 0];
+    let chroma = [128, 0];
+    let rgba = i420_to_rgba(2, 2, [(&luma[..], 3), (&chroma[..], 2), (&chroma[..], 2)]);
+    assert_eq!(rgba, vec![255, 255, 255, 255, 0, 0, 0, 255,
+                          128, 128, 128, 255, 128, 128, 128, 255]);
+}
+
+#[test]
+fn test_read_webm() {
+    let header = b"OpusHead\x01\x02\x38\x01\x80\xBB\x00\x00\x00\x00\x00".to_vec();
+    let mut muxer = WebmMuxer::new(&[
+        WebmTrack::Vp8 { width: 4, height: 2 },
+        WebmTrack::Opus { sample_rate: 48000, channel_count: 2, identification_header: header.clone(), codec_delay: 0 },
+    ]);
+    muxer.add_frame(0, 0, true, &[1, 2]);
+    muxer.add_frame(1, 10, false, &[3]);
+    muxer.add_frame(0, 40, false, &[4]);
+    muxer.add_frame(0, 80, true, &[5]);
+
+    let file = read_webm(&muxer.take_data()).unwrap();
+    assert_eq!(file.duration, None);
+    assert_eq!(file.tracks.len(), 2);
+    match file.tracks[0] {
+        (1, WebmTrack::Vp8 { width: 4, height: 2 }) => {},
+        _ => panic!("The first track should be the VP8 one"),
+    }
+    match file.tracks[1] {
+        (2, WebmTrack::Opus { channel_count: 2, ref identification_header, .. }) => {
+            assert_eq!(*identification_header, header);
+        },
+        _ => panic!("The second track should be the Opus one"),
+    }
+    assert_eq!(file.frames, vec![
+        WebmFrame { track: 1, time: 0., key_frame: true, data: vec![1, 2] },
+        WebmFrame { track: 2, time: 0.01, key_frame: false, data: vec![3] },
+        WebmFrame { track: 1, time: 0.04, key_frame: false, data: vec![4] },
+        WebmFrame { track: 1, time: 0.08, key_frame: true, data: vec![5] },
+    ]);
+}
+
+#[test]
+fn test_read_truncated_webm() {
+    let mut muxer = WebmMuxer::new(&[WebmTrack::Vp8 { width: 4, height: 2 }]);
+    muxer.add_frame(0, 0, true, &[1, 2, 3, 4]);
+    let data = muxer.take_data();
+    assert!(read_webm(&data[..data.len() - 2]).is_err());
+    assert!(read_webm(b"RIFF").is_err());
+}
+
+#[test]
+fn test_can_play_type() {
+    let types = [
+        ("video/webm", Some(false)),
+        ("video/webm; codecs=\"vp8, opus\"", Some(true)),
+        ("Audio/WebM;codecs=opus", Some(true)),
+        ("audio/wav", Some(false)),
+        ("video/webm;codecs=vp9", None),
+        ("video/mp4", None),
+        ("", None),
+    ];
+    for &(mime_type, expected) in &types {
+        assert!(can_play_type(mime_type) == expected, "Unexpected answer for {}", mime_type);
+    }
+}
+
+#[test]
+fn test_can_append_type() {
+    assert!(can_append_type("video/webm; codecs=\"vp8, opus\""));
+    assert!(can_append_type("audio/webm;codecs=opus"));
+    assert!(!can_append_type("audio/wav"));
+    assert!(!can_append_type("video/mp4"));
+}
+
+#[test]
+fn test_parse_webm_in_chunks() {
+    let mut muxer = WebmMuxer::new(&[WebmTrack::Vp8 { width: 4, height: 2 }]);
+    muxer.add_frame(0, 0, true, &[1, 2]);
+    muxer.add_frame(0, 40, false, &[3]);
+    muxer.add_frame(0, 80, true, &[4]);
+    let data = muxer.take_data();
+
+    let mut parser = WebmParser::new();
+    let mut tracks = None;
+    let mut frames = vec![];
+    for chunk in data.chunks(3) {
+        for segment in parser.append(chunk).unwrap() {
+            match segment {
+                WebmSegment::Init(_, init_tracks) => tracks = Some(init_tracks),
+                WebmSegment::Frames(chunk_frames) => frames.extend(chunk_frames),
+            }
+        }
+    }
+    let file = read_webm(&data).unwrap();
+    assert_eq!(tracks, Some(file.tracks));
+    assert_eq!(frames, file.frames);
+}
+
+#[test]
+fn test_parse_webm_cluster_first() {
+    let mut muxer = WebmMuxer::new(&[WebmTrack::Vp8 { width: 4, height: 2 }]);
+    muxer.take_data();
+    muxer.add_frame(0, 0, true, &[1, 2]);
+    assert!(WebmParser::new().append(&muxer.take_data()).is_err());
+}
+
+#[test]
+fn test_track_ranges() {
+    assert_eq!(track_ranges(vec![].into_iter()), vec![]);
+    assert_eq!(track_ranges(vec![0., 0.5, 1., 1.5].into_iter()), vec![(0., 2.)]);
+    // A gap of more than twice the duration of a frame starts a new range.
+    assert_eq!(track_ranges(vec![0., 0.5, 2., 2.5].into_iter()), vec![(0., 1.), (2., 3.)]);
+}
+
+#[test]
+fn test_intersect_ranges() {
+    assert_eq!(intersect_ranges(&[(0., 2.), (3., 5.)], &[(1., 4.)]), vec![(1., 2.), (3., 4.)]);
+    assert_eq!(intersect_ranges(&[(0., 1.)], &[(1., 2.)]), vec![]);
+}
+
+fn video_frame(time: f64, key_frame: bool) -> WebmFrame {
+    WebmFrame { track: 1, time: time, key_frame: key_frame, data: vec![] }
+}
+
+#[test]
+fn test_source_data() {
+    let mut data = SourceData::new();
+    let buffer = data.add_buffer();
+    data.set_tracks(buffer, vec![(1, WebmTrack::Vp8 { width: 4, height: 2 })]);
+    data.add_frames(buffer, vec![video_frame(1., true), video_frame(1.5, false)]);
+    data.add_frames(buffer, vec![video_frame(0., true), video_frame(0.5, false)]);
+    assert_eq!(data.buffered(buffer), vec![(0., 2.)]);
+    assert_eq!(data.highest_time(), 1.5);
+
+    // Appended frames replace the ones shown at the same times.
+    data.add_frames(buffer, vec![video_frame(0.5, true)]);
+    assert_eq!(data.next_frame((buffer, 1), 0., false), Some(&video_frame(0.5, true)));
+
+    // The frames up to the next key frame depend on the removed ones.
+    data.remove_frames(buffer, 0.5, 1.);
+    assert_eq!(data.key_frame_before((buffer, 1), 1.7), Some(&video_frame(1., true)));
+    assert_eq!(data.next_frame((buffer, 1), 0., false), Some(&video_frame(1., true)));
+
+    assert_eq!(data.all_buffered(), data.buffered(buffer));
+    data.remove_buffer(buffer);
+    assert_eq!(data.all_buffered(), vec![]);
+}
+
+#[test]
+fn test_parse_webvtt() {
+    let file = "\u{FEFF}WEBVTT - Subtitles\r\nKind: captions\r\n\r\n\
+                NOTE a comment\r\nover two lines\r\n\r\n\
+                intro\r\n00:01.000 --> 00:04.500 line:0 align:start\r\nHello\r\n<b>world</b>\r\n\r\n\
+                01:00:00.000 --> 01:00:02.000\r\nBye\r\n00:00.000 -> bad\r\nSkipped\r\n\r\n\
+                00:00.000 --> 00:01.000\n";
+    assert_eq!(webvtt::parse(file), Ok(vec![
+        WebVttCue {
+            id: "intro".to_owned(),
+            start_time: 1.,
+            end_time: 4.5,
+            settings: "line:0 align:start".to_owned(),
+            text: "Hello\n<b>world</b>".to_owned(),
+        },
+        WebVttCue {
+            id: "".to_owned(),
+            start_time: 3600.,
+            end_time: 3602.,
+            settings: "".to_owned(),
+            text: "Bye\n00:00.000 -> bad\nSkipped".to_owned(),
+        },
+        WebVttCue {
+            id: "".to_owned(),
+            start_time: 0.,
+            end_time: 1.,
+            settings: "".to_owned(),
+            text: "".to_owned(),
+        },
+    ]));
+
+    assert_eq!(webvtt::parse("WEBVTTX\n\n00:00.000 --> 00:01.000\nText"), Err(()));
+    assert_eq!(webvtt::parse("Text"), Err(()));
+    assert_eq!(webvtt::parse("WEBVTT"), Ok(vec![]));
+}
+
+#[test]
+fn test_parse_webvtt_cue_settings() {
+    assert_eq!(webvtt::parse_settings(""), CueSettings::default());
+    assert_eq!(webvtt::parse_settings("line:-2 position:10%,line-left size:50% align:start"), CueSettings {
+        line: Some(-2.),
+        snap_to_lines: true,
+        position: Some(10.),
+        size: 50.,
+        align: CueAlign::Start,
+    });
+    assert_eq!(webvtt::parse_settings("line:25.5%,end\talign:right vertical:rl"), CueSettings {
+        line: Some(25.5),
+        snap_to_lines: false,
+        align: CueAlign::Right,
+        ..CueSettings::default()
+    });
+
+    // Bad values are ignored, and a later setting overrides an earlier one.
+    assert_eq!(webvtt::parse_settings("line:1-2 line:--1 line:0,top position:5 size:101% size:.5% align:middle"),
+               CueSettings::default());
+    assert_eq!(webvtt::parse_settings("size:20% size:30% :1 line:"), CueSettings {
+        size: 30.,
+        ..CueSettings::default()
+    });
+}
+
+#[test]
+fn test_parse_webvtt_timestamp() {
+    assert_eq!(parse_timestamp("00:01.500"), Some(1.5));
+    assert_eq!(parse_timestamp("02:03:04.500"), Some(7384.5));
+    assert_eq!(parse_timestamp("100:00:00.000"), Some(360000.));
+    assert_eq!(parse_timestamp("1:00.000"), None);
+    assert_eq!(parse_timestamp("00:60.000"), None);
+    assert_eq!(parse_timestamp("00:00.5"), None);
+    assert_eq!(parse_timestamp("00:00"), None);
+}
+
+#[test]
+fn test_cue_text_to_plain_text() {
+    assert_eq!(cue_text_to_plain_text("<v Bob>Hi <i>there</i></v> &amp; <c.loud>bye</c>"), "Hi there & bye");
+    assert_eq!(cue_text_to_plain_text("1 &lt; 2 &unknown; <00:00:01.000>now"), "1 < 2 &unknown; now");
+    assert_eq!(cue_text_to_plain_text("unclosed <b"), "unclosed ");
+}
@@ -0,0 +1,68 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::PathSegment;
+use euclid::point::Point2D;
+use script::dom::path2d::parse_path_data;
+use std::f32::consts::PI;
+
+fn point(x: f32, y: f32) -> Point2D<f32> {
+    Point2D::new(x, y)
+}
+
+#[test]
+fn test_parse_lines() {
+    assert_eq!(parse_path_data("M 10 20 L 30,40 h 10 v -5 Z"), vec![
+        PathSegment::MoveTo(point(10., 20.)),
+        PathSegment::LineTo(point(30., 40.)),
+        PathSegment::LineTo(point(40., 40.)),
+        PathSegment::LineTo(point(40., 35.)),
+        PathSegment::ClosePath,
+    ]);
+
+    // Further points after a move are lines.
+    assert_eq!(parse_path_data("m 10 10 20 0 0 20"), vec![
+        PathSegment::MoveTo(point(10., 10.)),
+        PathSegment::LineTo(point(30., 10.)),
+        PathSegment::LineTo(point(30., 30.)),
+    ]);
+}
+
+#[test]
+fn test_parse_smooth_curves() {
+    assert_eq!(parse_path_data("M0 0 C 0 10 10 10 10 0 S 20 -10 20 0"), vec![
+        PathSegment::MoveTo(point(0., 0.)),
+        PathSegment::BezierCurveTo(point(0., 10.), point(10., 10.), point(10., 0.)),
+        PathSegment::BezierCurveTo(point(10., -10.), point(20., -10.), point(20., 0.)),
+    ]);
+
+    assert_eq!(parse_path_data("M0 0 Q 5 10 10 0 t 10 0"), vec![
+        PathSegment::MoveTo(point(0., 0.)),
+        PathSegment::QuadraticCurveTo(point(5., 10.), point(10., 0.)),
+        PathSegment::QuadraticCurveTo(point(15., -10.), point(20., 0.)),
+    ]);
+}
+
+#[test]
+fn test_parse_arcs() {
+    let semicircle = vec![
+        PathSegment::MoveTo(point(0., 0.)),
+        PathSegment::Ellipse(point(10., 0.), 10., 10., 0., PI, 2. * PI, false),
+    ];
+    assert_eq!(parse_path_data("M 0 0 A 10 10 0 0 1 20 0"), semicircle);
+    // Flags need no separator.
+    assert_eq!(parse_path_data("M0 0A10 10 0 0120 0"), semicircle);
+
+    assert_eq!(parse_path_data("M 0 0 A 0 10 0 0 1 20 0"), vec![
+        PathSegment::MoveTo(point(0., 0.)),
+        PathSegment::LineTo(point(20., 0.)),
+    ]);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(parse_path_data("L 10 10"), vec![]);
+    assert_eq!(parse_path_data("M 10 10 L 20"), vec![PathSegment::MoveTo(point(10., 10.))]);
+    assert_eq!(parse_path_data("M1e2.5.5"), vec![PathSegment::MoveTo(point(100., 0.5))]);
+}
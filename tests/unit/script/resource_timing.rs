@@ -0,0 +1,29 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use net_traits::request::Destination;
+use script::dom::performanceresourcetiming::initiator_type;
+
+#[test]
+fn images_are_reported_as_img() {
+    assert_eq!(&*initiator_type(Destination::Image), "img");
+}
+
+#[test]
+fn element_fetches_are_named_after_their_element() {
+    assert_eq!(&*initiator_type(Destination::Script), "script");
+    assert_eq!(&*initiator_type(Destination::Style), "link");
+    assert_eq!(&*initiator_type(Destination::Embed), "embed");
+    assert_eq!(&*initiator_type(Destination::Object), "object");
+}
+
+#[test]
+fn fetches_without_a_destination_are_reported_as_fetch() {
+    assert_eq!(&*initiator_type(Destination::None), "fetch");
+}
+
+#[test]
+fn other_destinations_are_reported_as_other() {
+    assert_eq!(&*initiator_type(Destination::Worker), "other");
+}
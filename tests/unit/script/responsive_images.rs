@@ -0,0 +1,93 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::dom::htmlimageelement::{Descriptor, ImageSource, choose_image_candidate};
+use script::dom::htmlimageelement::{evaluate_sizes_attribute, parse_a_srcset_attribute};
+
+fn candidate(url: &str, descriptor: Option<Descriptor>) -> ImageSource {
+    ImageSource {
+        url: url.to_owned(),
+        descriptor: descriptor,
+    }
+}
+
+#[test]
+fn test_parse_srcset_descriptors() {
+    let candidates = parse_a_srcset_attribute(" small.png 1x,\n large.png 2x , wide.png 800w, plain.png");
+    assert_eq!(candidates, vec![
+        candidate("small.png", Some(Descriptor::Density(1.))),
+        candidate("large.png", Some(Descriptor::Density(2.))),
+        candidate("wide.png", Some(Descriptor::Width(800))),
+        candidate("plain.png", None),
+    ]);
+}
+
+#[test]
+fn test_parse_srcset_commas() {
+    let candidates = parse_a_srcset_attribute("a.png, b.png 2x,,c.png,");
+    assert_eq!(candidates, vec![
+        candidate("a.png", None),
+        candidate("b.png", Some(Descriptor::Density(2.))),
+        candidate("c.png", None),
+    ]);
+    // Commas that aren't followed by whitespace are part of the URL.
+    let candidates = parse_a_srcset_attribute("a.png,b.png 2x");
+    assert_eq!(candidates, vec![candidate("a.png,b.png", Some(Descriptor::Density(2.)))]);
+    let candidates = parse_a_srcset_attribute("data:image/png;base64,iVBORw0KGgo= 1x");
+    assert_eq!(candidates, vec![candidate("data:image/png;base64,iVBORw0KGgo=", Some(Descriptor::Density(1.)))]);
+}
+
+#[test]
+fn test_parse_srcset_drops_invalid_candidates() {
+    let candidates = parse_a_srcset_attribute("a.png 1x 2x, b.png 0w, c.png 100w 2x, d.png 1.5y, \
+                                               e.png +1x, f.png 100h, g.png 100w 50h, h.png 1.5x");
+    assert_eq!(candidates, vec![
+        candidate("g.png", Some(Descriptor::Width(100))),
+        candidate("h.png", Some(Descriptor::Density(1.5))),
+    ]);
+}
+
+#[test]
+fn test_evaluate_sizes() {
+    assert_eq!(evaluate_sizes_attribute("", 800., 600.), 800.);
+    assert_eq!(evaluate_sizes_attribute("300px", 800., 600.), 300.);
+    assert_eq!(evaluate_sizes_attribute("50vw", 800., 600.), 400.);
+    assert_eq!(evaluate_sizes_attribute("2em", 800., 600.), 32.);
+    let sizes = "(max-width: 500px) 100vw, (max-width: 1000px) 50vw, 300px";
+    assert_eq!(evaluate_sizes_attribute(sizes, 400., 600.), 400.);
+    assert_eq!(evaluate_sizes_attribute(sizes, 800., 600.), 400.);
+    assert_eq!(evaluate_sizes_attribute(sizes, 1200., 600.), 300.);
+    assert_eq!(evaluate_sizes_attribute("calc(50vw + 10px)", 800., 600.), 410.);
+    assert_eq!(evaluate_sizes_attribute("(min-width: 100px) calc(10px + 10px)", 800., 600.), 20.);
+    assert_eq!(evaluate_sizes_attribute("bogus, 10%, 200px", 800., 600.), 200.);
+}
+
+#[test]
+fn test_choose_by_density() {
+    let candidates = vec![
+        candidate("1x.png", None),
+        candidate("2x.png", Some(Descriptor::Density(2.))),
+        candidate("3x.png", Some(Descriptor::Density(3.))),
+    ];
+    let choose = |ratio| choose_image_candidate(&candidates, 800., ratio).map(|(c, d)| (c.url.clone(), d));
+    assert_eq!(choose(1.), Some(("1x.png".to_owned(), 1.)));
+    assert_eq!(choose(1.5), Some(("2x.png".to_owned(), 2.)));
+    assert_eq!(choose(2.), Some(("2x.png".to_owned(), 2.)));
+    assert_eq!(choose(4.), Some(("3x.png".to_owned(), 3.)));
+    assert_eq!(choose_image_candidate(&[], 800., 1.), None);
+}
+
+#[test]
+fn test_choose_by_width() {
+    let candidates = vec![
+        candidate("400.png", Some(Descriptor::Width(400))),
+        candidate("800.png", Some(Descriptor::Width(800))),
+        candidate("1600.png", Some(Descriptor::Width(1600))),
+    ];
+    let choose = |size, ratio| choose_image_candidate(&candidates, size, ratio).map(|(c, d)| (c.url.clone(), d));
+    assert_eq!(choose(400., 1.), Some(("400.png".to_owned(), 1.)));
+    assert_eq!(choose(400., 2.), Some(("800.png".to_owned(), 2.)));
+    assert_eq!(choose(800., 1.), Some(("800.png".to_owned(), 1.)));
+    assert_eq!(choose(1000., 1.), Some(("1600.png".to_owned(), 1.6)));
+}
@@ -0,0 +1,22 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::dom::selection::rendered_index_to_offset;
+
+#[test]
+fn rendered_index_counts_whitespace_runs_once() {
+    assert_eq!(rendered_index_to_offset("hello  world", 0), 0);
+    assert_eq!(rendered_index_to_offset("hello  world", 6), 7);
+    assert_eq!(rendered_index_to_offset("hello  world", 8), 9);
+}
+
+#[test]
+fn rendered_index_is_in_utf16_code_units() {
+    assert_eq!(rendered_index_to_offset("\u{1F600}a", 1), 2);
+}
+
+#[test]
+fn rendered_index_past_the_end_is_the_length() {
+    assert_eq!(rendered_index_to_offset("abc", 10), 3);
+}
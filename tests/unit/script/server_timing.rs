@@ -0,0 +1,37 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::dom::performanceservertiming::{ServerTimingMetric, parse_server_timing};
+
+fn metric(name: &str, duration: f64, description: &str) -> ServerTimingMetric {
+    ServerTimingMetric {
+        name: name.to_owned(),
+        duration: duration,
+        description: description.to_owned(),
+    }
+}
+
+#[test]
+fn parses_metrics_with_parameters() {
+    assert_eq!(parse_server_timing("db;dur=53.2, cache;desc=\"Cache Read\";dur=23.2, app"),
+               vec![metric("db", 53.2, ""), metric("cache", 23.2, "Cache Read"), metric("app", 0., "")]);
+}
+
+#[test]
+fn quoted_descriptions_may_contain_separators() {
+    assert_eq!(parse_server_timing("total;desc=\"a, b; \\\"c\\\"\""),
+               vec![metric("total", 0., "a, b; \"c\"")]);
+}
+
+#[test]
+fn first_occurrence_of_a_parameter_wins() {
+    assert_eq!(parse_server_timing("db;DUR=1;dur=2;desc=first;desc=second"),
+               vec![metric("db", 1., "first")]);
+}
+
+#[test]
+fn invalid_metrics_and_durations_are_ignored() {
+    assert_eq!(parse_server_timing(", bad name;dur=1, ok;dur=abc, inf;dur=NaN"),
+               vec![metric("ok", 0., ""), metric("inf", 0., "")]);
+}
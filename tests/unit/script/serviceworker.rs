@@ -0,0 +1,48 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::dom::serviceworkerregistration::{longest_matching_scope, scope_allowed};
+use url::Url;
+
+fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+}
+
+#[test]
+fn longest_scope_wins() {
+    let scopes = vec![url("https://example.com/"),
+                      url("https://example.com/app/"),
+                      url("https://example.com/app/admin/")];
+    assert_eq!(longest_matching_scope(&scopes, &url("https://example.com/app/admin/users")),
+               Some(&scopes[2]));
+    assert_eq!(longest_matching_scope(&scopes, &url("https://example.com/app/index.html")),
+               Some(&scopes[1]));
+    assert_eq!(longest_matching_scope(&scopes, &url("https://example.com/about")),
+               Some(&scopes[0]));
+}
+
+#[test]
+fn scopes_of_other_origins_do_not_match() {
+    let scopes = vec![url("https://example.com/app/")];
+    assert_eq!(longest_matching_scope(&scopes, &url("https://example.org/app/")), None);
+    assert_eq!(longest_matching_scope(&scopes, &url("http://example.com/app/")), None);
+    assert_eq!(longest_matching_scope(&scopes, &url("https://example.com/other/")), None);
+}
+
+#[test]
+fn scope_is_limited_to_the_script_directory() {
+    let script = url("https://example.com/js/sw.js");
+    assert!(scope_allowed(&url("https://example.com/js/"), &script, None));
+    assert!(scope_allowed(&url("https://example.com/js/app/"), &script, None));
+    assert!(!scope_allowed(&url("https://example.com/"), &script, None));
+}
+
+#[test]
+fn service_worker_allowed_widens_the_maximum_scope() {
+    let script = url("https://example.com/js/sw.js");
+    assert!(scope_allowed(&url("https://example.com/"), &script, Some("/")));
+    assert!(scope_allowed(&url("https://example.com/app/"), &script, Some("../app/")));
+    assert!(!scope_allowed(&url("https://example.com/"), &script, Some("/app/")));
+    assert!(!scope_allowed(&url("https://example.com/"), &script, Some("https://example.org/")));
+}
@@ -0,0 +1,65 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::task_attribution::{TaskAttribution, TaskSourceKind, TaskTimes, UNATTRIBUTED};
+use url::Url;
+
+#[test]
+fn test_record_accumulates_per_origin_and_source() {
+    let mut attribution = TaskAttribution::new();
+    attribution.record("https://example.com", TaskSourceKind::Timer, 1000);
+    attribution.record("https://example.com", TaskSourceKind::Timer, 500);
+    attribution.record("https://example.com", TaskSourceKind::Network, 200);
+    attribution.record("https://ads.example.net", TaskSourceKind::Timer, 7000);
+
+    assert_eq!(attribution.times("https://example.com", TaskSourceKind::Timer),
+               TaskTimes { tasks: 2, cpu_time_ns: 1500 });
+    assert_eq!(attribution.times("https://example.com", TaskSourceKind::Network),
+               TaskTimes { tasks: 1, cpu_time_ns: 200 });
+    assert_eq!(attribution.times("https://ads.example.net", TaskSourceKind::Timer),
+               TaskTimes { tasks: 1, cpu_time_ns: 7000 });
+    assert_eq!(attribution.times("https://ads.example.net", TaskSourceKind::Event),
+               TaskTimes::default());
+}
+
+#[test]
+fn test_task_is_attributed_to_first_script_origin() {
+    let mut attribution = TaskAttribution::new();
+    assert!(attribution.begin_task(TaskSourceKind::Event, None));
+    attribution.note_script_entered(|| Url::parse("https://example.com/page.html").unwrap());
+    attribution.note_script_entered(|| Url::parse("https://other.example.org/").unwrap());
+    assert_eq!(attribution.end_task(), Some("https://example.com".to_owned()));
+    assert_eq!(attribution.times("https://example.com", TaskSourceKind::Event).tasks, 1);
+}
+
+#[test]
+fn test_known_origin_is_kept() {
+    let mut attribution = TaskAttribution::new();
+    assert!(attribution.begin_task(TaskSourceKind::Timer, Some("https://example.com".to_owned())));
+    attribution.note_script_entered(|| Url::parse("https://other.example.org/").unwrap());
+    assert_eq!(attribution.end_task(), Some("https://example.com".to_owned()));
+}
+
+#[test]
+fn test_nested_task_is_part_of_outer_task() {
+    let mut attribution = TaskAttribution::new();
+    assert!(attribution.begin_task(TaskSourceKind::Network, None));
+    assert!(!attribution.begin_task(TaskSourceKind::Timer, None));
+    assert_eq!(attribution.end_task(), Some(UNATTRIBUTED.to_owned()));
+    assert_eq!(attribution.end_task(), None);
+    assert_eq!(attribution.times(UNATTRIBUTED, TaskSourceKind::Network).tasks, 1);
+    assert_eq!(attribution.times(UNATTRIBUTED, TaskSourceKind::Timer).tasks, 0);
+}
+
+#[test]
+fn test_reports() {
+    let mut attribution = TaskAttribution::new();
+    attribution.record("https://example.com", TaskSourceKind::AnimationFrame, 3000);
+    let reports = attribution.reports();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].path, vec!["script-cpu-time".to_owned(),
+                                     "origin(https://example.com)".to_owned(),
+                                     "animation-frame".to_owned()]);
+    assert_eq!(reports[0].size, 3);
+}
@@ -0,0 +1,261 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::webcrypto::{self, EcPrivateKey, EcPublicKey, HashAlgorithm, NamedCurve, RsaPrivateKey, RsaPublicKey};
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len() / 2).map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap()).collect()
+}
+
+/// A 1024-bit key generated with the openssl command line tool, as PKCS #8.
+const PKCS8: &'static str = "\
+    30820278020100300d06092a864886f70d0101010500048202623082025e02010002818100d3865704c92d879ab92b130e5d\
+    30338aff14cc21ba37912fe060113555943267c1239519b5166c4756d45124c12f369c9ce93d461782d8bde271f7b5e48254\
+    6d2d6575c2cb583e4c735763cdc56cf56d5099c381dd595aa8d9e6794587f2d11300532b8a830577d37744223729d3904662\
+    9a07b26445868475794df7404e90e5020301000102818079b2cf1a17cd3439d6d36b915618476f6f373e0321aa9ac3daea2a\
+    bc6ed266f5beef83f75872e0356345a0b1eedc0525b23997615e618fe62004c62a46a09d856e3e42215838ccadf922c1946b\
+    2c54d0d6159535dc59cddea9d8b0d2778ced6be4ce985264710344e92aa75e8375cad3b1ecd0edd8b391680d3a15003e79eb\
+    b9024100feb1cd6b1472c96dd35eaaa1e2bcb627c9894ca86c87dd33d947685fe4164da74ac492d5fc3abbd268c2cf7772d7\
+    6593db8b8dc1e07c85b67c34d90b33321167024100d49be46aed0c1f66587cf63a1fde828f55e491a857cb839075e7c3153e\
+    9f55a4278c17215070b594f7886bd919f5592ad4819751c4ab1a1e314e53b153a35fd302410081a85c4d9dcc4db4c63cd41a\
+    b385a4299f14715cf05b3e05ce1fe707a1e2a4c77c6d93deb197e8d3d5970eecdd1ec04c4420c81aa1c06285a2b778a295fe\
+    70130241009aa582325c9672c88918ec293f41248a8a19a5f1635aff29b6f98ba70a53ef8165daba805af880bc08894c9930\
+    0e8c7b60f39a53b9d013415eeff0648dc7ca4d024100cbfde164a68b4ae874024cedbe279b53570ea5cbb1afa0b6bb65c648\
+    4d96d97f50961d9eb02da3a9301773d764e2b5323085d4b09e0cac4aa77291c13e3acb2d";
+
+/// The public half of `PKCS8`, as a SubjectPublicKeyInfo.
+const SPKI: &'static str = "\
+    30819f300d06092a864886f70d010101050003818d0030818902818100d3865704c92d879ab92b130e5d30338aff14cc21ba\
+    37912fe060113555943267c1239519b5166c4756d45124c12f369c9ce93d461782d8bde271f7b5e482546d2d6575c2cb583e\
+    4c735763cdc56cf56d5099c381dd595aa8d9e6794587f2d11300532b8a830577d37744223729d39046629a07b26445868475\
+    794df7404e90e50203010001";
+
+/// The RSASSA-PKCS1-v1_5 signature of "servo" with `PKCS8` and SHA-256, made
+/// by the openssl command line tool.
+const PKCS1_SIGNATURE: &'static str = "\
+    75093bd8494e3a4102f5cba171568bb629c36765505098f10acfcb9a173035b8c1b1741dfb06873fc225a957480f93a41d37\
+    5ac14c15cda252821cb7eb5753ff8f556f530318722a9b00f523707559fb3b9c2b47f2567891280a82322f8ae1a0169a25b6\
+    a6f1dfc5e05911a1dafebd6a1b3709a87557008f8c15e02c885942e9";
+
+/// A P-256 key generated with the openssl command line tool, as PKCS #8.
+const EC_PKCS8: &'static str = "\
+    308187020100301306072a8648ce3d020106082a8648ce3d030107046d306b020101042039f2f6a0454598b63b5d1c039cab4e\
+    37cd2e4aeef07ac36715d1d4c6b7e1c349a144034200042474bb4ead9fb81422d8f47ca0201ad5eb119fa9c955ad3a45900fb9\
+    d0c47c2578a0b6bf8eeb28d2a9f8327799ea8b64966d843f68c0ef3368b11833ba8195c4";
+
+/// The public half of `EC_PKCS8`, as a SubjectPublicKeyInfo.
+const EC_SPKI: &'static str = "\
+    3059301306072a8648ce3d020106082a8648ce3d030107034200042474bb4ead9fb81422d8f47ca0201ad5eb119fa9c955ad3a\
+    45900fb9d0c47c2578a0b6bf8eeb28d2a9f8327799ea8b64966d843f68c0ef3368b11833ba8195c4";
+
+/// The ECDSA signature of "servo" with `EC_PKCS8` and SHA-256, made by the
+/// openssl command line tool and converted from DER to the two integers.
+const ECDSA_SIGNATURE: &'static str = "\
+    91cefd76cf081548f5e38160d7db20a7448886031af8e9c555be8cec4d59220fe35cbf0dc7258e6f1a7fc0114bb19e53576392\
+    43d0a997d4f191e6f6fd8e5d96";
+
+/// Another P-256 public key, as an uncompressed point.
+const EC_PEER_POINT: &'static str = "\
+    04b20cc1c5982fbee9a162d8f43c556ab79c71617b4e411ec48b60aed446bec57a09f5bf799e8ff59ab2b8b079c6773263ebdd\
+    ed4435c30ae409d110255b3227ba";
+
+/// The secret `EC_PKCS8` shares with `EC_PEER_POINT`, derived by the openssl
+/// command line tool.
+const ECDH_SECRET: &'static str = "48f7be405ae98a2ee6877ab0a986601870c750af3f3c57d51f3c7cd26c6c871a";
+
+#[test]
+fn test_digest() {
+    assert_eq!(webcrypto::digest(HashAlgorithm::Sha1, b"abc"), from_hex("a9993e364706816aba3e25717850c26c9cd0d89d"));
+    assert_eq!(webcrypto::digest(HashAlgorithm::Sha256, b"abc"),
+               from_hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+    assert_eq!(webcrypto::digest(HashAlgorithm::Sha384, b"").len(), 48);
+    assert_eq!(webcrypto::digest(HashAlgorithm::Sha512, b"").len(), 64);
+}
+
+#[test]
+fn test_hmac() {
+    // RFC 4231, test case 2.
+    let data = b"what do ya want for nothing?";
+    let mac = webcrypto::hmac_sign(HashAlgorithm::Sha256, b"Jefe", data);
+    assert_eq!(mac, from_hex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"));
+    assert!(webcrypto::hmac_verify(HashAlgorithm::Sha256, b"Jefe", data, &mac));
+    assert!(!webcrypto::hmac_verify(HashAlgorithm::Sha256, b"Jeff", data, &mac));
+    assert!(!webcrypto::hmac_verify(HashAlgorithm::Sha256, b"Jefe", data, &mac[..31]));
+}
+
+#[test]
+fn test_aes_cbc() {
+    // NIST SP 800-38A, F.2.1, followed by a block of padding.
+    let key = from_hex("2b7e151628aed2a6abf7158809cf4f3c");
+    let iv = from_hex("000102030405060708090a0b0c0d0e0f");
+    let plaintext = from_hex("6bc1bee22e409f96e93d7e117393172a");
+    let ciphertext = webcrypto::aes_cbc_encrypt(&key, &iv, &plaintext).unwrap();
+    assert_eq!(ciphertext, from_hex("7649abac8119b246cee98e9b12e9197d8964e0b149c10b7b682e6e39aaeb731c"));
+    assert_eq!(webcrypto::aes_cbc_decrypt(&key, &iv, &ciphertext).unwrap(), plaintext);
+
+    assert!(webcrypto::aes_cbc_decrypt(&key, &iv, &ciphertext[..16]).is_err());
+    assert!(webcrypto::aes_cbc_decrypt(&key, &iv, &[]).is_err());
+    assert!(webcrypto::aes_cbc_encrypt(&key, &iv[..8], &plaintext).is_err());
+    assert!(webcrypto::aes_cbc_encrypt(&key[..8], &iv, &plaintext).is_err());
+
+    // NIST SP 800-38A, F.2.3, with a 192-bit key.
+    let key = from_hex("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b");
+    let ciphertext = webcrypto::aes_cbc_encrypt(&key, &iv, &plaintext).unwrap();
+    assert_eq!(ciphertext[..16].to_vec(), from_hex("4f021db243bc633d7178183a9fa071e8"));
+    assert_eq!(webcrypto::aes_cbc_decrypt(&key, &iv, &ciphertext).unwrap(), plaintext);
+}
+
+#[test]
+fn test_aes_ctr() {
+    // NIST SP 800-38A, F.5.1.
+    let key = from_hex("2b7e151628aed2a6abf7158809cf4f3c");
+    let counter = from_hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+    let plaintext = from_hex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+    let ciphertext = webcrypto::aes_ctr(&key, &counter, 64, &plaintext).unwrap();
+    assert_eq!(ciphertext, from_hex("874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff"));
+    assert_eq!(webcrypto::aes_ctr(&key, &counter, 64, &ciphertext).unwrap(), plaintext);
+
+    // Only the rightmost `length` bits of the counter block are incremented.
+    let wrapped = webcrypto::aes_ctr(&key, &counter, 8, &plaintext).unwrap();
+    let mut second_counter = counter.clone();
+    second_counter[15] = 0;
+    assert_eq!(wrapped[16..].to_vec(), webcrypto::aes_ctr(&key, &second_counter, 8, &plaintext[16..]).unwrap());
+    assert!(webcrypto::aes_ctr(&key, &counter, 1, &[0; 48]).is_err());
+    assert!(webcrypto::aes_ctr(&key, &counter, 0, &plaintext).is_err());
+}
+
+#[test]
+fn test_aes_gcm() {
+    // Test cases 2, 4 and 6 of "The Galois/Counter Mode of Operation (GCM)".
+    let ciphertext = webcrypto::aes_gcm_encrypt(&[0; 16], &[0; 12], &[], 128, &[0; 16]).unwrap();
+    assert_eq!(ciphertext, from_hex("0388dace60b6a392f328c2b971b2fe78ab6e47d42cec13bdf53a67b21257bddf"));
+
+    let key = from_hex("feffe9928665731c6d6a8f9467308308");
+    let plaintext = from_hex("d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e24\
+                              49a6b525b16aedf5aa0de657ba637b39");
+    let additional_data = from_hex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+    let iv = from_hex("cafebabefacedbaddecaf888");
+    let ciphertext = webcrypto::aes_gcm_encrypt(&key, &iv, &additional_data, 128, &plaintext).unwrap();
+    assert_eq!(ciphertext, from_hex("42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b254669\
+                                     31c7d8f6a5aac84aa051ba30b396a0aac973d58e0915bc94fbc3221a5db94fae95ae7121a47"));
+    assert_eq!(webcrypto::aes_gcm_decrypt(&key, &iv, &additional_data, 128, &ciphertext).unwrap(), plaintext);
+
+    let long_iv = from_hex("9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728c3c0c95156809539fcf0e2\
+                            429a6b525416aedbf5a0de6a57a637b39b");
+    let ciphertext = webcrypto::aes_gcm_encrypt(&key, &long_iv, &additional_data, 96, &plaintext).unwrap();
+    assert_eq!(ciphertext, from_hex("8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca701e4a9a4fba43\
+                                     c90ccdcb281d48c7c6fd62875d2aca417034c34aee5619cc5aefffe0bfa462af43c"));
+
+    let mut tampered = webcrypto::aes_gcm_encrypt(&key, &iv, &additional_data, 128, &plaintext).unwrap();
+    tampered[0] ^= 1;
+    assert!(webcrypto::aes_gcm_decrypt(&key, &iv, &additional_data, 128, &tampered).is_err());
+    assert!(webcrypto::aes_gcm_decrypt(&key, &iv, &[], 128, &ciphertext).is_err());
+    assert!(webcrypto::aes_gcm_encrypt(&key, &iv, &[], 100, &plaintext).is_err());
+}
+
+#[test]
+fn test_rsa_generated_key() {
+    let private_key = RsaPrivateKey::generate(1024, &[1, 0, 1]).unwrap();
+    assert_eq!(private_key.modulus_length(), 1024);
+    assert_eq!(private_key.public_exponent(), &[1, 0, 1]);
+    let public_key = &private_key.public_key().unwrap();
+    assert_eq!(public_key.modulus_length(), 1024);
+    assert_eq!(public_key.public_exponent(), &[1, 0, 1]);
+
+    let ciphertext = webcrypto::rsa_oaep_encrypt(public_key, HashAlgorithm::Sha256, b"label", b"secret").unwrap();
+    assert_eq!(ciphertext.len(), 128);
+    assert_eq!(webcrypto::rsa_oaep_decrypt(&private_key, HashAlgorithm::Sha256, b"label", &ciphertext).unwrap(),
+               b"secret");
+    assert!(webcrypto::rsa_oaep_decrypt(&private_key, HashAlgorithm::Sha256, b"other", &ciphertext).is_err());
+
+    let signature = webcrypto::rsa_pss_sign(&private_key, HashAlgorithm::Sha256, 32, b"message").unwrap();
+    assert!(webcrypto::rsa_pss_verify(public_key, HashAlgorithm::Sha256, 32, b"message", &signature));
+    assert!(!webcrypto::rsa_pss_verify(public_key, HashAlgorithm::Sha256, 32, b"massage", &signature));
+    assert!(!webcrypto::rsa_pss_verify(public_key, HashAlgorithm::Sha256, 20, b"message", &signature));
+
+    let signature = webcrypto::rsa_pkcs1_v1_5_sign(&private_key, HashAlgorithm::Sha1, b"message").unwrap();
+    assert!(webcrypto::rsa_pkcs1_v1_5_verify(public_key, HashAlgorithm::Sha1, b"message", &signature));
+    assert!(!webcrypto::rsa_pkcs1_v1_5_verify(public_key, HashAlgorithm::Sha256, b"message", &signature));
+}
+
+#[test]
+fn test_rsa_generate_rejects_bad_parameters() {
+    assert!(RsaPrivateKey::generate(1024, &[1, 0, 0]).is_err());
+    assert!(RsaPrivateKey::generate(1028, &[1, 0, 1]).is_err());
+    assert!(RsaPrivateKey::generate(128, &[1, 0, 1]).is_err());
+}
+
+#[test]
+fn test_rsa_imported_key() {
+    let private_key = RsaPrivateKey::from_pkcs8(&from_hex(PKCS8)).unwrap();
+    let public_key = RsaPublicKey::from_spki(&from_hex(SPKI)).unwrap();
+    assert_eq!(public_key.modulus_length(), 1024);
+    assert_eq!(public_key.public_exponent(), &[1, 0, 1]);
+    assert!(RsaPublicKey::from_spki(&from_hex(PKCS8)).is_none());
+    assert!(RsaPrivateKey::from_pkcs8(&from_hex(SPKI)).is_none());
+    assert!(RsaPublicKey::from_spki(&from_hex(EC_SPKI)).is_none());
+
+    // PKCS #1 v1.5 signatures are deterministic, so they must match OpenSSL's.
+    let signature = webcrypto::rsa_pkcs1_v1_5_sign(&private_key, HashAlgorithm::Sha256, b"servo").unwrap();
+    assert_eq!(signature, from_hex(PKCS1_SIGNATURE));
+    assert!(webcrypto::rsa_pkcs1_v1_5_verify(&public_key, HashAlgorithm::Sha256, b"servo", &signature));
+
+    let ciphertext = webcrypto::rsa_oaep_encrypt(&public_key, HashAlgorithm::Sha1, b"", b"servo").unwrap();
+    assert_eq!(webcrypto::rsa_oaep_decrypt(&private_key, HashAlgorithm::Sha1, b"", &ciphertext).unwrap(), b"servo");
+}
+
+#[test]
+fn test_ecdsa_imported_key() {
+    let private_key = EcPrivateKey::from_pkcs8(&from_hex(EC_PKCS8), NamedCurve::P256).unwrap();
+    let public_key = EcPublicKey::from_spki(&from_hex(EC_SPKI), NamedCurve::P256).unwrap();
+    assert!(EcPrivateKey::from_pkcs8(&from_hex(EC_PKCS8), NamedCurve::P384).is_none());
+    assert!(EcPublicKey::from_spki(&from_hex(EC_SPKI), NamedCurve::P384).is_none());
+    assert!(EcPublicKey::from_spki(&from_hex(SPKI), NamedCurve::P256).is_none());
+    assert!(EcPrivateKey::from_pkcs8(&from_hex(PKCS8), NamedCurve::P256).is_none());
+
+    let signature = from_hex(ECDSA_SIGNATURE);
+    assert!(webcrypto::ecdsa_verify(&public_key, HashAlgorithm::Sha256, b"servo", &signature));
+    assert!(!webcrypto::ecdsa_verify(&public_key, HashAlgorithm::Sha256, b"servi", &signature));
+    assert!(!webcrypto::ecdsa_verify(&public_key, HashAlgorithm::Sha384, b"servo", &signature));
+    assert!(!webcrypto::ecdsa_verify(&public_key, HashAlgorithm::Sha256, b"servo", &signature[..63]));
+
+    let signature = webcrypto::ecdsa_sign(&private_key, HashAlgorithm::Sha512, b"message").unwrap();
+    assert_eq!(signature.len(), 64);
+    assert!(webcrypto::ecdsa_verify(&private_key.public_key().unwrap(), HashAlgorithm::Sha512, b"message",
+                                    &signature));
+}
+
+#[test]
+fn test_ecdsa_generated_key() {
+    let private_key = EcPrivateKey::generate(NamedCurve::P384).unwrap();
+    let public_key = private_key.public_key().unwrap();
+    assert_eq!(public_key.curve(), NamedCurve::P384);
+    for _ in 0..10 {
+        let signature = webcrypto::ecdsa_sign(&private_key, HashAlgorithm::Sha384, b"message").unwrap();
+        assert_eq!(signature.len(), 96);
+        assert!(webcrypto::ecdsa_verify(&public_key, HashAlgorithm::Sha384, b"message", &signature));
+        assert!(!webcrypto::ecdsa_verify(&public_key, HashAlgorithm::Sha384, b"massage", &signature));
+    }
+}
+
+#[test]
+fn test_ecdh() {
+    let private_key = EcPrivateKey::from_pkcs8(&from_hex(EC_PKCS8), NamedCurve::P256).unwrap();
+    let peer_key = EcPublicKey::from_raw(&from_hex(EC_PEER_POINT), NamedCurve::P256).unwrap();
+    assert_eq!(webcrypto::ecdh_derive_bits(&private_key, &peer_key, 256).unwrap(), from_hex(ECDH_SECRET));
+    assert_eq!(webcrypto::ecdh_derive_bits(&private_key, &peer_key, 12).unwrap(), from_hex("48f0"));
+    assert!(webcrypto::ecdh_derive_bits(&private_key, &peer_key, 264).is_err());
+
+    let mut off_curve = from_hex(EC_PEER_POINT);
+    off_curve[64] ^= 1;
+    assert!(EcPublicKey::from_raw(&off_curve, NamedCurve::P256).is_none());
+    assert!(EcPublicKey::from_raw(&from_hex(EC_PEER_POINT)[1..], NamedCurve::P256).is_none());
+
+    let first = EcPrivateKey::generate(NamedCurve::P384).unwrap();
+    let second = EcPrivateKey::generate(NamedCurve::P384).unwrap();
+    let secret = webcrypto::ecdh_derive_bits(&first, &second.public_key().unwrap(), 384).unwrap();
+    assert_eq!(secret.len(), 48);
+    assert_eq!(webcrypto::ecdh_derive_bits(&second, &first.public_key().unwrap(), 384).unwrap(), secret);
+    assert!(webcrypto::ecdh_derive_bits(&first, &peer_key, 256).is_err());
+}
@@ -0,0 +1,20 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::dom::xmlserializer::{escape_attribute, escape_text};
+
+#[test]
+fn attribute_values_escape_quotes_and_markup() {
+    assert_eq!(escape_attribute("a \"b\" <c> & d"), "a &quot;b&quot; &lt;c&gt; &amp; d");
+}
+
+#[test]
+fn text_escapes_markup_but_not_quotes() {
+    assert_eq!(escape_text("\"a\" < b && c > d"), "\"a\" &lt; b &amp;&amp; c &gt; d");
+}
+
+#[test]
+fn existing_entities_are_escaped_again() {
+    assert_eq!(escape_text("&lt;"), "&amp;lt;");
+}
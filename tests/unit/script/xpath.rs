@@ -0,0 +1,122 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use script::xpath::eval::{number_to_string, round, string_to_number};
+use script::xpath::parser::{Axis, BinaryOp, Expr, LocationPath, NameTest, NodeTest, Step, parse};
+use std::f64;
+
+fn name(local: &str) -> NodeTest {
+    NodeTest::Name(NameTest {
+        prefix: None,
+        local: Some(local.to_owned()),
+        namespace: None,
+    })
+}
+
+fn step(axis: Axis, test: NodeTest) -> Step {
+    Step {
+        axis: axis,
+        test: test,
+        predicates: vec![],
+    }
+}
+
+fn binary(lhs: Expr, op: BinaryOp, rhs: Expr) -> Expr {
+    Expr::Binary(Box::new(lhs), op, Box::new(rhs))
+}
+
+fn path(absolute: bool, steps: Vec<Step>) -> Expr {
+    Expr::Path(None, LocationPath {
+        absolute: absolute,
+        steps: steps,
+    })
+}
+
+#[test]
+fn abbreviated_steps_expand_to_axes() {
+    assert_eq!(parse("//a/@b").unwrap(), path(true, vec![
+        step(Axis::DescendantOrSelf, NodeTest::Node),
+        step(Axis::Child, name("a")),
+        step(Axis::Attribute, name("b")),
+    ]));
+    assert_eq!(parse("./..").unwrap(), path(false, vec![
+        step(Axis::Self_, NodeTest::Node),
+        step(Axis::Parent, NodeTest::Node),
+    ]));
+    assert_eq!(parse("/").unwrap(), path(true, vec![]));
+}
+
+#[test]
+fn explicit_axes_and_node_type_tests() {
+    assert_eq!(parse("following-sibling::text()").unwrap(),
+               path(false, vec![step(Axis::FollowingSibling, NodeTest::Text)]));
+    assert_eq!(parse("processing-instruction('x')").unwrap(),
+               path(false, vec![step(Axis::Child, NodeTest::ProcessingInstruction(Some("x".to_owned())))]));
+    assert!(parse("sideways::a").is_err());
+}
+
+#[test]
+fn operator_names_are_names_in_operand_position() {
+    // The first `div` is an element name and the second the operator.
+    assert_eq!(parse("div div div").unwrap(),
+               binary(path(false, vec![step(Axis::Child, name("div"))]),
+                      BinaryOp::Divide,
+                      path(false, vec![step(Axis::Child, name("div"))])));
+    match parse("* * *").unwrap() {
+        Expr::Binary(_, BinaryOp::Multiply, _) => {},
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn operators_have_xpath_precedence() {
+    let sum = binary(Expr::Number(1.), BinaryOp::Add, binary(Expr::Number(2.), BinaryOp::Multiply, Expr::Number(3.)));
+    assert_eq!(parse("1 + 2 * 3 = 7 or false()").unwrap(),
+               binary(binary(sum, BinaryOp::Equal, Expr::Number(7.)),
+                      BinaryOp::Or,
+                      Expr::Function("false".to_owned(), vec![])));
+}
+
+#[test]
+fn names_may_contain_hyphens_and_dots() {
+    assert_eq!(parse("a-b.c").unwrap(), path(false, vec![step(Axis::Child, name("a-b.c"))]));
+}
+
+#[test]
+fn invalid_expressions_are_rejected() {
+    assert!(parse("").is_err());
+    assert!(parse("a[").is_err());
+    assert!(parse("'unterminated").is_err());
+    assert!(parse("$variable").is_err());
+    assert!(parse("unknown()").is_err());
+    assert!(parse("substring('a')").is_err());
+    assert!(parse("a b").is_err());
+}
+
+#[test]
+fn strings_convert_to_numbers_strictly() {
+    assert_eq!(string_to_number(" 12.5 "), 12.5);
+    assert_eq!(string_to_number("-.5"), -0.5);
+    assert!(string_to_number("1e3").is_nan());
+    assert!(string_to_number("+1").is_nan());
+    assert!(string_to_number("").is_nan());
+    assert!(string_to_number("1.2.3").is_nan());
+}
+
+#[test]
+fn numbers_convert_to_strings_without_exponents() {
+    assert_eq!(number_to_string(1.0), "1");
+    assert_eq!(number_to_string(-0.0), "0");
+    assert_eq!(number_to_string(0.5), "0.5");
+    assert_eq!(number_to_string(1e21), "1000000000000000000000");
+    assert_eq!(number_to_string(f64::NAN), "NaN");
+    assert_eq!(number_to_string(f64::NEG_INFINITY), "-Infinity");
+}
+
+#[test]
+fn round_goes_towards_positive_infinity() {
+    assert_eq!(round(2.5), 3.);
+    assert_eq!(round(-2.5), -2.);
+    assert!(round(-0.4).is_sign_negative());
+}
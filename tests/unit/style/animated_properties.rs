@@ -0,0 +1,73 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use app_units::Au;
+use cssparser::{Parser, RGBA};
+use style::properties::animated_properties::{Interpolate, TransitionProperty};
+use style::properties::longhands::transform::computed_value::ComputedOperation as TransformOperation;
+use style::properties::longhands::transform::computed_value::T as TransformList;
+use style::values::computed::LengthOrPercentage;
+
+#[test]
+fn test_rgba_color_interpolation_is_premultiplied() {
+    let transparent = RGBA { red: 0., green: 0., blue: 0., alpha: 0. };
+    let blue = RGBA { red: 0., green: 0., blue: 1., alpha: 1. };
+    assert_eq!(transparent.interpolate(&blue, 0.5).unwrap(),
+               RGBA { red: 0., green: 0., blue: 1., alpha: 0.5 });
+}
+
+#[test]
+fn test_rgba_color_interpolation_is_clamped() {
+    let black = RGBA { red: 0., green: 0., blue: 0., alpha: 1. };
+    let white = RGBA { red: 1., green: 1., blue: 1., alpha: 1. };
+    assert_eq!(black.interpolate(&white, 1.5).unwrap(), white);
+    assert_eq!(black.interpolate(&white, -0.5).unwrap(), black);
+}
+
+#[test]
+fn test_shorthand_transition_property_expands_to_its_longhands() {
+    let property = TransitionProperty::parse(&mut Parser::new("margin")).unwrap();
+    assert!(property.is_shorthand());
+
+    let mut longhands = vec![];
+    property.each_longhand(|longhand| longhands.push(longhand));
+    assert_eq!(longhands, vec![TransitionProperty::MarginTop,
+                               TransitionProperty::MarginRight,
+                               TransitionProperty::MarginBottom,
+                               TransitionProperty::MarginLeft]);
+}
+
+#[test]
+fn test_mismatched_transform_lists_interpolate_as_matrices() {
+    let from = TransformList(Some(vec![
+        TransformOperation::Translate(LengthOrPercentage::Length(Au::from_px(100)),
+                                      LengthOrPercentage::zero(),
+                                      Au(0))
+    ]));
+    let to = TransformList(Some(vec![TransformOperation::Scale(2., 1., 1.)]));
+    let interpolated = match from.interpolate(&to, 0.5).unwrap() {
+        TransformList(Some(ref operations)) if operations.len() == 1 => operations[0].clone(),
+        list => panic!("Unexpected interpolated transform {:?}", list),
+    };
+    match interpolated {
+        TransformOperation::Matrix(matrix) => {
+            assert!((matrix.m11 - 1.5).abs() < 1e-5);
+            assert!((matrix.m22 - 1.).abs() < 1e-5);
+            assert!((matrix.m41 - 50.).abs() < 1e-5);
+        }
+        operation => panic!("Expected a matrix, got {:?}", operation),
+    }
+}
+
+#[test]
+fn test_transform_lists_with_percentages_switch_halfway() {
+    let from = TransformList(Some(vec![
+        TransformOperation::Translate(LengthOrPercentage::Percentage(0.5),
+                                      LengthOrPercentage::zero(),
+                                      Au(0))
+    ]));
+    let to = TransformList(Some(vec![TransformOperation::Scale(2., 1., 1.)]));
+    assert_eq!(from.interpolate(&to, 0.25).unwrap(), from);
+    assert_eq!(from.interpolate(&to, 0.75).unwrap(), to);
+}
@@ -17,6 +17,7 @@ extern crate style_traits;
 extern crate url;
 extern crate util;
 
+mod animated_properties;
 mod attr;
 mod cache;
 mod logical_geometry;
@@ -67,3 +67,15 @@ fn test_rect_round_trip() {
         assert!(logical.to_physical(mode, container) == physical);
     }
 }
+
+#[test]
+fn test_same_inline_direction() {
+    for &mode in modes().iter() {
+        assert!(mode.has_same_inline_direction(mode));
+    }
+    assert!(!WritingMode::empty().has_same_inline_direction(FLAG_RTL));
+    assert!(!FLAG_VERTICAL.has_same_inline_direction(FLAG_VERTICAL | FLAG_RTL));
+    assert!(!FLAG_VERTICAL.has_same_inline_direction(FLAG_VERTICAL | FLAG_SIDEWAYS_LEFT));
+    assert!(FLAG_VERTICAL.has_same_inline_direction(FLAG_VERTICAL | FLAG_SIDEWAYS_LEFT | FLAG_RTL));
+    assert!(FLAG_VERTICAL.has_same_inline_direction(FLAG_RTL));
+}
@@ -0,0 +1,24 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::Parser;
+use media_queries::CSSErrorReporterTest;
+use style::parser::ParserContext;
+use style::properties::longhands::background_blend_mode;
+use style::stylesheets::Origin;
+use url::Url;
+
+#[test]
+fn test_background_blend_mode() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let parse = |s| background_blend_mode::parse(&context, &mut Parser::new(s));
+
+    assert_roundtrip!(|input| background_blend_mode::parse(&context, input), "multiply");
+    assert_roundtrip!(|input| background_blend_mode::parse(&context, input),
+                      "normal, color-dodge, luminosity");
+    assert!(parse("").is_err());
+    assert!(parse("none").is_err());
+    assert!(parse("multiply,").is_err());
+}
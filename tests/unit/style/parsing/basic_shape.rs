@@ -2,9 +2,13 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use media_queries::CSSErrorReporterTest;
 use parsing::parse;
-use style::parser::Parse;
+use style::parser::{Parse, ParserContext};
+use style::properties::longhands::shape_outside;
+use style::stylesheets::Origin;
 use style::values::specified::basic_shape::*;
+use url::Url;
 
 // Ensure that basic-shape sub-functions parse as both basic shapes
 // and their individual components
@@ -150,3 +154,16 @@ fn test_polygon() {
 
     assert!(parse(Polygon::parse, "polygon()").is_err());
 }
+
+#[test]
+fn test_shape_outside() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| shape_outside::parse(&context, input), "none");
+    assert_roundtrip!(|input| shape_outside::parse(&context, input), "content-box");
+    assert_roundtrip!(|input| shape_outside::parse(&context, input),
+                      "padding-box inset(10px)", "inset(10px 10px 10px 10px) padding-box");
+    assert_roundtrip!(|input| shape_outside::parse(&context, input),
+                      "polygon(0px 0px, 10px 0px, 0px 10px)");
+}
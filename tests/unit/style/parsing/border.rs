@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::Parser;
+use media_queries::CSSErrorReporterTest;
+use style::parser::ParserContext;
+use style::properties::longhands::{border_image_outset, border_image_repeat, border_image_slice};
+use style::properties::longhands::{border_image_source, border_image_width};
+use style::properties::shorthands::border_image;
+use style::stylesheets::Origin;
+use url::Url;
+
+macro_rules! parse_longhand {
+    ($name:ident, $s:expr) => {{
+        let url = Url::parse("http://localhost").unwrap();
+        let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+        $name::parse(&context, &mut Parser::new($s)).unwrap()
+    }};
+}
+
+#[test]
+fn border_image_shorthand_should_parse_all_available_properties_when_specified() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let mut parser = Parser::new("linear-gradient(red, blue) 30 30% 45 fill / 20px 30px / 1 round space");
+    let result = border_image::parse_value(&context, &mut parser).unwrap();
+
+    assert_eq!(result.border_image_source.unwrap(),
+               parse_longhand!(border_image_source, "linear-gradient(red, blue)"));
+    assert_eq!(result.border_image_slice.unwrap(), parse_longhand!(border_image_slice, "30 30% 45 fill"));
+    assert_eq!(result.border_image_width.unwrap(), parse_longhand!(border_image_width, "20px 30px"));
+    assert_eq!(result.border_image_outset.unwrap(), parse_longhand!(border_image_outset, "1"));
+    assert_eq!(result.border_image_repeat.unwrap(), parse_longhand!(border_image_repeat, "round space"));
+}
+
+#[test]
+fn border_image_shorthand_should_parse_without_width_or_outset() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let mut parser = Parser::new("repeat 10% / / 2px");
+    let result = border_image::parse_value(&context, &mut parser).unwrap();
+
+    assert_eq!(result.border_image_slice.unwrap(), parse_longhand!(border_image_slice, "10%"));
+    assert_eq!(result.border_image_width, None);
+    assert_eq!(result.border_image_outset.unwrap(), parse_longhand!(border_image_outset, "2px"));
+    assert_eq!(result.border_image_repeat.unwrap(), parse_longhand!(border_image_repeat, "repeat"));
+
+    let mut parser = Parser::new("10% /");
+    assert!(border_image::parse_value(&context, &mut parser).is_err());
+}
+
+#[test]
+fn test_border_image_slice() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| border_image_slice::parse(&context, input), "10");
+    assert_roundtrip!(|input| border_image_slice::parse(&context, input), "fill 10% 20", "10% 20 fill");
+    assert_roundtrip!(|input| border_image_slice::parse(&context, input), "1 2 3 2", "1 2 3");
+    assert!(border_image_slice::parse(&context, &mut Parser::new("-1")).is_err());
+}
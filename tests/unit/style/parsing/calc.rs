@@ -0,0 +1,50 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use parsing::parse;
+use style::values::specified::{Angle, Length, LengthOrPercentage, Time, parse_integer, parse_number};
+
+#[test]
+fn test_calc() {
+    assert_roundtrip!(LengthOrPercentage::parse, "calc(10px + 50%)");
+    assert_roundtrip!(LengthOrPercentage::parse, "calc(2 * (1em + 5px) - 10px)", "calc(2em + 0px)");
+    assert_roundtrip!(Length::parse, "calc(1em + 1vw)");
+    assert_roundtrip!(Length::parse, "calc(50cqw - 1rem)", "calc(50cqw + -1rem)");
+    assert_roundtrip!(LengthOrPercentage::parse, "calc(1cqh + 2cqh + 50%)", "calc(3cqh + 50%)");
+
+    assert!(parse(Length::parse, "calc(10px + 50%)").is_err());
+    assert!(parse(LengthOrPercentage::parse, "calc(10px + 2)").is_err());
+    assert!(parse(LengthOrPercentage::parse, "calc(2)").is_err());
+}
+
+#[test]
+fn test_comparison_functions() {
+    assert_roundtrip!(LengthOrPercentage::parse, "min(10px, 20px)", "10px");
+    assert_roundtrip!(LengthOrPercentage::parse, "MAX(10%, 20%, 5%)", "20%");
+    assert_roundtrip!(LengthOrPercentage::parse, "clamp(10px, 5px, 20px)", "10px");
+    assert_roundtrip!(LengthOrPercentage::parse, "clamp(10px, 30px, 20px)", "20px");
+    assert_roundtrip!(LengthOrPercentage::parse, "clamp(20px, 15px, 10px)", "20px");
+    assert_roundtrip!(LengthOrPercentage::parse, "calc(50% + min(1em, 2em))", "calc(1em + 50%)");
+    assert_roundtrip!(LengthOrPercentage::parse, "calc(max(5px, 2px + 1px) * 2)", "10px");
+    assert_roundtrip!(Length::parse, "min(1em + 1em, 3em)", "2em");
+    assert_roundtrip!(Angle::parse, "max(1rad, 2rad)", "2rad");
+    assert_roundtrip!(Time::parse, "min(1s, 500ms)", "0.5s");
+
+    assert_eq!(parse(parse_number, "calc(min(1, 2) * 3)"), Ok(3.));
+    assert_eq!(parse(parse_integer, "max(1, 3)"), Ok(3));
+
+    // Arguments of different units are compared once percentages are resolved.
+    assert_roundtrip!(LengthOrPercentage::parse, "min(10px, 50%)");
+    assert_roundtrip!(Length::parse, "max(1px, 1em)");
+    assert_roundtrip!(LengthOrPercentage::parse, "clamp(1em, 50%, 100px)");
+    assert_roundtrip!(LengthOrPercentage::parse, "calc(10px + max(1em, 10%))");
+    assert_roundtrip!(LengthOrPercentage::parse, "calc(-1 * min(10px, 50%))", "max(-10px, -50%)");
+    assert_roundtrip!(LengthOrPercentage::parse, "calc(min(10px, 50%) * -2)", "max(-20px, -100%)");
+    assert_roundtrip!(LengthOrPercentage::parse, "calc(clamp(1px, 50%, 2px) * -1)",
+                      "min(-1px, max(-50%, -2px))");
+
+    assert!(parse(Length::parse, "min(1px, 2)").is_err());
+    assert!(parse(Length::parse, "clamp(1px, 2px)").is_err());
+    assert!(parse(Length::parse, "min()").is_err());
+}
@@ -0,0 +1,23 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::Parser;
+use media_queries::CSSErrorReporterTest;
+use style::parser::ParserContext;
+use style::properties::longhands::contain;
+use style::stylesheets::Origin;
+use url::Url;
+
+#[test]
+fn test_contain() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| contain::parse(&context, input), "none");
+    assert_roundtrip!(|input| contain::parse(&context, input), "strict");
+    assert_roundtrip!(|input| contain::parse(&context, input), "content");
+    assert_roundtrip!(|input| contain::parse(&context, input), "paint layout", "layout paint");
+    assert_roundtrip!(|input| contain::parse(&context, input), "size layout style paint", "strict");
+    assert!(contain::parse(&context, &mut Parser::new("size size")).is_err());
+}
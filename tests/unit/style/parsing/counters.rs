@@ -0,0 +1,48 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::Parser;
+use media_queries::CSSErrorReporterTest;
+use style::parser::ParserContext;
+use style::properties::longhands::{content, counter_increment, counter_reset, list_style_type};
+use style::stylesheets::Origin;
+use url::Url;
+
+#[test]
+fn test_counter_content() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| content::parse(&context, input), "counter(item)", "counter(item, decimal)");
+    assert_roundtrip!(|input| content::parse(&context, input), "counter(item, lower-roman)");
+    assert_roundtrip!(|input| content::parse(&context, input),
+                      "counters(item, \".\", upper-roman)");
+    assert_roundtrip!(|input| content::parse(&context, input),
+                      "counters(item, \".\") \" \"", "counters(item, \".\", decimal) \" \"");
+}
+
+#[test]
+fn test_counter_reset_and_increment() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| counter_reset::parse(&context, input), "none");
+    assert_roundtrip!(|input| counter_reset::parse(&context, input), "item", "item 0");
+    assert_roundtrip!(|input| counter_increment::parse(&context, input), "item", "item 1");
+    assert_roundtrip!(|input| counter_increment::parse(&context, input), "item 2 section -1");
+    assert!(counter_reset::parse(&context, &mut Parser::new("inherit 1")).is_err());
+}
+
+#[test]
+fn test_extended_list_style_type() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| list_style_type::parse(&context, input), "decimal-leading-zero");
+    assert_roundtrip!(|input| list_style_type::parse(&context, input), "upper-roman");
+    assert_roundtrip!(|input| list_style_type::parse(&context, input), "lower-latin");
+    assert_roundtrip!(|input| list_style_type::parse(&context, input), "georgian");
+    assert_roundtrip!(|input| list_style_type::parse(&context, input), "trad-chinese-informal");
+    assert_roundtrip!(|input| list_style_type::parse(&context, input), "cjk-ideographic");
+}
@@ -0,0 +1,51 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::Parser;
+use media_queries::CSSErrorReporterTest;
+use style::parser::ParserContext;
+use style::stylesheets::Origin;
+use style::values::specified::Image;
+use url::Url;
+
+#[test]
+fn test_linear_gradient() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let parse = &|input: &mut Parser| Image::parse(&context, input);
+
+    assert_roundtrip!(parse, "linear-gradient(1rad, red, blue)");
+    assert_roundtrip!(parse, "repeating-linear-gradient(to left top, red 10px, blue 20%)");
+
+    // A stop with two positions is two stops.
+    assert_roundtrip!(parse, "linear-gradient(1rad, red 10% 20%, blue)",
+                             "linear-gradient(1rad, red 10%, red 20%, blue)");
+    assert_roundtrip!(parse, "linear-gradient(1rad, red, 25%, blue)");
+}
+
+#[test]
+fn test_conic_gradient() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let parse = &|input: &mut Parser| Image::parse(&context, input);
+
+    assert_roundtrip!(parse, "conic-gradient(red, blue)",
+                             "conic-gradient(from 0rad at center center, red, blue)");
+    // Stop angles are stored as fractions of a turn.
+    assert_roundtrip!(parse, "repeating-conic-gradient(from 1rad at left top, red 0.25turn, 50%, blue 0.5turn)",
+                             "repeating-conic-gradient(from 1rad at left top, red 25%, 50%, blue 50%)");
+    assert!(super::parse(parse, "conic-gradient(red 10px, blue)").is_err());
+}
+
+#[test]
+fn test_invalid_interpolation_hints() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let parse = &|input: &mut Parser| Image::parse(&context, input);
+
+    assert!(super::parse(parse, "linear-gradient(10%, red, blue)").is_err());
+    assert!(super::parse(parse, "linear-gradient(red, blue, 10%)").is_err());
+    assert!(super::parse(parse, "linear-gradient(red, 10%, 20%, blue)").is_err());
+    assert!(super::parse(parse, "linear-gradient(red)").is_err());
+}
@@ -32,7 +32,18 @@ macro_rules! assert_roundtrip {
 }
 
 
+mod background;
 mod basic_shape;
+mod border;
+mod calc;
+mod contain;
+mod counters;
+mod image;
 mod mask;
 mod position;
+mod quirks;
+mod scrollbar;
 mod selectors;
+mod size;
+mod text;
+mod transform;
@@ -0,0 +1,46 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::{Parser, ToCss};
+use media_queries::CSSErrorReporterTest;
+use style::context::QuirksMode;
+use style::parser::{ParserContext, ParserContextExtraData};
+use style::properties::longhands::{font_size, margin_top, width};
+use style::properties::shorthands::padding;
+use style::stylesheets::Origin;
+use url::Url;
+
+fn context_for(url: &Url, origin: Origin, quirks_mode: QuirksMode) -> ParserContext {
+    ParserContext::new_with_extra_data(origin, url, Box::new(CSSErrorReporterTest),
+                                       ParserContextExtraData::for_quirks_mode(quirks_mode))
+}
+
+#[test]
+fn unitless_lengths_are_pixels_in_quirks_mode() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = context_for(&url, Origin::Author, QuirksMode::Quirks);
+
+    assert_eq!(width::parse(&context, &mut Parser::new("10")).unwrap().to_css_string(), "10px");
+    assert_eq!(margin_top::parse(&context, &mut Parser::new("-5")).unwrap().to_css_string(), "-5px");
+    assert_eq!(font_size::parse(&context, &mut Parser::new("12")).unwrap().to_css_string(), "12px");
+    assert!(width::parse(&context, &mut Parser::new("-10")).is_err());
+
+    let result = padding::parse_value(&context, &mut Parser::new("1 2px 3")).unwrap();
+    assert_eq!(result.padding_top.unwrap().to_css_string(), "1px");
+    assert_eq!(result.padding_right.unwrap().to_css_string(), "2px");
+    assert_eq!(result.padding_bottom.unwrap().to_css_string(), "3px");
+    assert_eq!(result.padding_left.unwrap().to_css_string(), "2px");
+}
+
+#[test]
+fn unitless_lengths_are_invalid_outside_quirks_mode() {
+    let url = Url::parse("http://localhost").unwrap();
+    for &(origin, quirks_mode) in &[(Origin::Author, QuirksMode::NoQuirks),
+                                    (Origin::Author, QuirksMode::LimitedQuirks),
+                                    (Origin::UserAgent, QuirksMode::Quirks)] {
+        let context = context_for(&url, origin, quirks_mode);
+        assert!(width::parse(&context, &mut Parser::new("10")).is_err());
+        assert!(padding::parse_value(&context, &mut Parser::new("1 2px")).is_err());
+    }
+}
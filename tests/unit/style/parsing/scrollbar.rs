@@ -0,0 +1,41 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::Parser;
+use media_queries::CSSErrorReporterTest;
+use style::parser::ParserContext;
+use style::properties::longhands::{overflow_x, scrollbar_color, scrollbar_gutter};
+use style::stylesheets::Origin;
+use url::Url;
+
+#[test]
+fn test_overflow_clip() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| overflow_x::parse(&context, input), "clip");
+}
+
+#[test]
+fn test_scrollbar_gutter() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| scrollbar_gutter::parse(&context, input), "auto");
+    assert_roundtrip!(|input| scrollbar_gutter::parse(&context, input), "stable");
+    assert_roundtrip!(|input| scrollbar_gutter::parse(&context, input), "stable both-edges");
+    assert_roundtrip!(|input| scrollbar_gutter::parse(&context, input),
+                      "both-edges stable", "stable both-edges");
+    assert!(scrollbar_gutter::parse(&context, &mut Parser::new("both-edges")).is_err());
+}
+
+#[test]
+fn test_scrollbar_color() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| scrollbar_color::parse(&context, input), "auto");
+    assert_roundtrip!(|input| scrollbar_color::parse(&context, input), "red blue");
+    assert!(scrollbar_color::parse(&context, &mut Parser::new("red")).is_err());
+}
@@ -0,0 +1,48 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::{Parser, ToCss};
+use media_queries::CSSErrorReporterTest;
+use parsing::parse;
+use style::parser::ParserContext;
+use style::properties::longhands::aspect_ratio;
+use style::stylesheets::Origin;
+use style::values::specified::{MaxSize, MinSize, Size};
+use url::Url;
+
+#[test]
+fn test_sizes() {
+    assert_roundtrip!(Size::parse_non_negative, "auto");
+    assert_roundtrip!(Size::parse_non_negative, "10px");
+    assert_roundtrip!(Size::parse_non_negative, "min-content");
+    assert_roundtrip!(Size::parse_non_negative, "MAX-CONTENT", "max-content");
+    assert_roundtrip!(MinSize::parse_non_negative, "fit-content");
+    assert_roundtrip!(MinSize::parse_non_negative, "50%");
+    assert_roundtrip!(MinSize::parse_non_negative, "AUTO", "auto");
+    assert_roundtrip!(MaxSize::parse_non_negative, "none");
+    assert_roundtrip!(MaxSize::parse_non_negative, "max-content");
+
+    assert!(parse(Size::parse_non_negative, "-10px").is_err());
+    assert!(parse(MinSize::parse_non_negative, "none").is_err());
+    assert!(parse(MaxSize::parse_non_negative, "content").is_err());
+}
+
+#[test]
+fn test_aspect_ratio() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let parse_aspect_ratio = |value: &str| aspect_ratio::parse(&context, &mut Parser::new(value));
+
+    assert_eq!(parse_aspect_ratio("auto").unwrap().to_css_string(), "auto");
+    assert_eq!(parse_aspect_ratio("16 / 9").unwrap().to_css_string(), "16 / 9");
+    assert_eq!(parse_aspect_ratio("2").unwrap().to_css_string(), "2 / 1");
+    assert_eq!(parse_aspect_ratio("1/1 auto").unwrap().to_css_string(), "auto 1 / 1");
+
+    assert_eq!(parse_aspect_ratio("16 / 9").unwrap().ratio(), Some(16. / 9.));
+    assert_eq!(parse_aspect_ratio("0 / 1").unwrap().ratio(), None);
+    assert_eq!(parse_aspect_ratio("auto").unwrap().ratio(), None);
+
+    assert!(parse_aspect_ratio("-1 / 2").is_err());
+    assert!(parse_aspect_ratio("none").is_err());
+}
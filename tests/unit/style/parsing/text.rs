@@ -0,0 +1,77 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::{Parser, ToCss};
+use media_queries::CSSErrorReporterTest;
+use style::parser::ParserContext;
+use style::properties::longhands::{text_decoration_style, text_decoration_thickness};
+use style::properties::longhands::{hyphenate_character, hyphens, ruby_align, ruby_position};
+use style::properties::longhands::text_underline_offset;
+use style::properties::shorthands::text_decoration;
+use style::stylesheets::Origin;
+use url::Url;
+
+#[test]
+fn text_decoration_shorthand_should_parse_all_available_properties_when_specified() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let mut parser = Parser::new("underline overline wavy red 2px");
+    let result = text_decoration::parse_value(&context, &mut parser).unwrap();
+
+    assert_eq!(result.text_decoration_line.unwrap().to_css_string(), "underline overline");
+    assert_eq!(result.text_decoration_style.unwrap(), text_decoration_style::computed_value::T::wavy);
+    assert_eq!(result.text_decoration_color.unwrap().to_css_string(), "red");
+    assert_eq!(result.text_decoration_thickness.unwrap().to_css_string(), "2px");
+}
+
+#[test]
+fn text_decoration_shorthand_should_use_initial_values_when_omitted() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let mut parser = Parser::new("line-through");
+    let result = text_decoration::parse_value(&context, &mut parser).unwrap();
+
+    assert_eq!(result.text_decoration_style.unwrap(), text_decoration_style::computed_value::T::solid);
+    assert_eq!(result.text_decoration_color.unwrap().to_css_string(), "currentColor");
+    assert_eq!(result.text_decoration_thickness.unwrap(), text_decoration_thickness::SpecifiedValue::Auto);
+}
+
+#[test]
+fn test_text_decoration_thickness_and_underline_offset() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| text_decoration_thickness::parse(&context, input), "auto");
+    assert_roundtrip!(|input| text_decoration_thickness::parse(&context, input), "from-font");
+    assert_roundtrip!(|input| text_decoration_thickness::parse(&context, input), "0.1em");
+    assert_roundtrip!(|input| text_decoration_thickness::parse(&context, input), "10%");
+    assert!(text_decoration_thickness::parse(&context, &mut Parser::new("-1px")).is_err());
+
+    assert_roundtrip!(|input| text_underline_offset::parse(&context, input), "auto");
+    assert_roundtrip!(|input| text_underline_offset::parse(&context, input), "-2px");
+    assert_roundtrip!(|input| text_underline_offset::parse(&context, input), "25%");
+}
+
+#[test]
+fn test_hyphens_and_hyphenate_character() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| hyphens::parse(&context, input), "auto");
+    assert_roundtrip!(|input| hyphens::parse(&context, input), "manual");
+    assert_roundtrip!(|input| hyphenate_character::parse(&context, input), "auto");
+    assert_roundtrip!(|input| hyphenate_character::parse(&context, input), "\"=\"");
+    assert!(hyphenate_character::parse(&context, &mut Parser::new("none")).is_err());
+}
+
+#[test]
+fn test_ruby_align_and_ruby_position() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+
+    assert_roundtrip!(|input| ruby_align::parse(&context, input), "space-around");
+    assert_roundtrip!(|input| ruby_align::parse(&context, input), "start");
+    assert_roundtrip!(|input| ruby_position::parse(&context, input), "under");
+    assert!(ruby_position::parse(&context, &mut Parser::new("inter-character")).is_err());
+}
@@ -0,0 +1,52 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::{Parser, ToCss};
+use media_queries::CSSErrorReporterTest;
+use style::parser::ParserContext;
+use style::properties::longhands::{column_gap, rotate, scale, translate};
+use style::stylesheets::Origin;
+use url::Url;
+
+#[test]
+fn test_individual_transforms() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let parse_translate = |value: &str| translate::parse(&context, &mut Parser::new(value));
+    let parse_rotate = |value: &str| rotate::parse(&context, &mut Parser::new(value));
+    let parse_scale = |value: &str| scale::parse(&context, &mut Parser::new(value));
+
+    assert_eq!(parse_translate("none").unwrap().to_css_string(), "none");
+    assert_eq!(parse_translate("10px").unwrap().to_css_string(), "10px");
+    assert_eq!(parse_translate("10px 50%").unwrap().to_css_string(), "10px 50%");
+    assert_eq!(parse_translate("10px 0px 3px").unwrap().to_css_string(), "10px 0px 3px");
+
+    assert_eq!(parse_rotate("none").unwrap(), rotate::computed_value::T(None));
+    assert_eq!(parse_rotate("1rad").unwrap().to_css_string(), "1rad");
+    assert_eq!(parse_rotate("x 1rad").unwrap().to_css_string(), "x 1rad");
+    assert_eq!(parse_rotate("1rad y").unwrap().to_css_string(), "y 1rad");
+    assert_eq!(parse_rotate("1 1 0 1rad").unwrap().to_css_string(), "1 1 0 1rad");
+    assert!(parse_rotate("0 0 0 1rad").is_err());
+    assert!(parse_rotate("1e-30 0 0 1rad").is_err());
+    assert!(parse_rotate("1e30 1e30 0 1rad").is_err());
+    assert!(parse_rotate("x").is_err());
+
+    assert_eq!(parse_scale("none").unwrap(), scale::computed_value::T(None));
+    assert_eq!(parse_scale("2").unwrap(), scale::computed_value::T(Some((2., 2., 1.))));
+    assert_eq!(parse_scale("2 3").unwrap().to_css_string(), "2 3");
+    assert_eq!(parse_scale("2 2 3").unwrap().to_css_string(), "2 2 3");
+    assert!(parse_scale("2px").is_err());
+}
+
+#[test]
+fn test_column_gap() {
+    let url = Url::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Box::new(CSSErrorReporterTest));
+    let parse_column_gap = |value: &str| column_gap::parse(&context, &mut Parser::new(value));
+
+    assert_eq!(parse_column_gap("normal").unwrap().to_css_string(), "normal");
+    assert_eq!(parse_column_gap("10px").unwrap().to_css_string(), "10px");
+    assert_eq!(parse_column_gap("5%").unwrap().to_css_string(), "5%");
+    assert!(parse_column_gap("-10px").is_err());
+}
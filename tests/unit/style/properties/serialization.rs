@@ -6,7 +6,7 @@ pub use cssparser::ToCss;
 pub use std::sync::Arc;
 pub use style::computed_values::display::T::inline_block;
 pub use style::properties::{DeclaredValue, PropertyDeclaration, PropertyDeclarationBlock, Importance};
-pub use style::values::specified::{BorderStyle, CSSColor, Length};
+pub use style::values::specified::{BorderStyle, CSSColor, Length, MinSize, Size};
 pub use style::values::specified::{LengthOrPercentage, LengthOrPercentageOrAuto, LengthOrPercentageOrAutoOrContent};
 pub use style::properties::longhands::outline_color::computed_value::T as ComputedColor;
 pub use style::values::RGBA;
@@ -20,15 +20,17 @@ fn property_declaration_block_should_serialize_correctly() {
 
     let declarations = vec![
         (PropertyDeclaration::Width(
-            DeclaredValue::Value(LengthOrPercentageOrAuto::Length(Length::from_px(70f32)))),
+            DeclaredValue::Value(Size::LengthOrPercentageOrAuto(
+                LengthOrPercentageOrAuto::Length(Length::from_px(70f32))))),
          Importance::Normal),
 
         (PropertyDeclaration::MinHeight(
-            DeclaredValue::Value(LengthOrPercentage::Length(Length::from_px(20f32)))),
+            DeclaredValue::Value(MinSize::LengthOrPercentage(LengthOrPercentage::Length(Length::from_px(20f32))))),
          Importance::Normal),
 
         (PropertyDeclaration::Height(
-            DeclaredValue::Value(LengthOrPercentageOrAuto::Length(Length::from_px(20f32)))),
+            DeclaredValue::Value(Size::LengthOrPercentageOrAuto(
+                LengthOrPercentageOrAuto::Length(Length::from_px(20f32))))),
          Importance::Important),
 
         (PropertyDeclaration::Display(
@@ -526,6 +528,24 @@ mod shorthand_serialization {
         }
     }
 
+    #[test]
+    fn column_rule_should_serialize_correctly() {
+        use style::properties::longhands::column_rule_width::SpecifiedValue as WidthContainer;
+
+        let mut properties = Vec::new();
+
+        let width = DeclaredValue::Value(WidthContainer(Length::from_px(2f32)));
+        let style = DeclaredValue::Value(BorderStyle::dashed);
+        let color = DeclaredValue::Initial;
+
+        properties.push(PropertyDeclaration::ColumnRuleWidth(width));
+        properties.push(PropertyDeclaration::ColumnRuleStyle(style));
+        properties.push(PropertyDeclaration::ColumnRuleColor(color));
+
+        let serialization = shorthand_properties_to_string(properties);
+        assert_eq!(serialization, "column-rule: 2px dashed;");
+    }
+
     #[test]
     fn columns_should_serialize_correctly() {
         use style::properties::longhands::column_count::SpecifiedValue as ColumnCount;
@@ -620,6 +640,26 @@ mod shorthand_serialization {
         assert_eq!(serialization, "flex-flow: row wrap;");
     }
 
+    #[test]
+    fn gap_should_serialize_one_value_when_both_gaps_are_equal() {
+        use style::properties::longhands::column_gap::SpecifiedValue as Gap;
+
+        let mut properties = Vec::new();
+
+        let gap = Gap::Specified(LengthOrPercentage::Length(Length::from_px(10f32)));
+        properties.push(PropertyDeclaration::RowGap(DeclaredValue::Value(gap.clone())));
+        properties.push(PropertyDeclaration::ColumnGap(DeclaredValue::Value(gap.clone())));
+        let serialization = shorthand_properties_to_string(properties);
+        assert_eq!(serialization, "gap: 10px;");
+
+        let mut properties = Vec::new();
+
+        properties.push(PropertyDeclaration::RowGap(DeclaredValue::Value(Gap::Normal)));
+        properties.push(PropertyDeclaration::ColumnGap(DeclaredValue::Value(gap)));
+        let serialization = shorthand_properties_to_string(properties);
+        assert_eq!(serialization, "gap: normal 10px;");
+    }
+
     // TODO: Populate Atom Cache for testing so that the font shorthand can be tested
     /*
     mod font {
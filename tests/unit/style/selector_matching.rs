@@ -8,7 +8,7 @@ use std::sync::Arc;
 use string_cache::Atom;
 use style::properties::{PropertyDeclarationBlock, PropertyDeclaration, DeclaredValue};
 use style::properties::{longhands, Importance};
-use style::selector_matching::{Rule, SelectorMap};
+use style::selector_matching::{Rule, SelectorMap, UNLAYERED};
 
 /// Helper method to get some Rules from selector strings.
 /// Each sublist of the result contains the Rules for one StyleRule.
@@ -29,6 +29,8 @@ fn get_mock_rules(css_selectors: &[&str]) -> Vec<Vec<Rule>> {
                 }),
                 specificity: s.specificity,
                 source_order: i,
+                layer_order: UNLAYERED,
+                container_conditions: None,
             }
         }).collect()
     }).collect()
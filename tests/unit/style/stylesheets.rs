@@ -2,21 +2,27 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
+use app_units::Au;
 use cssparser::{self, Parser, SourcePosition};
+use euclid::size::Size2D;
 use media_queries::CSSErrorReporterTest;
 use selectors::parser::*;
 use std::borrow::ToOwned;
 use std::sync::Arc;
 use std::sync::Mutex;
 use string_cache::{Atom, Namespace as NsAtom};
+use style::container_queries::{Comparison, ContainerCondition, LengthFeature, Orientation, SizeFeature};
+use style::custom_properties::{PropertySyntax, SyntaxComponent};
 use style::error_reporting::ParseErrorReporter;
 use style::keyframes::{Keyframe, KeyframeSelector, KeyframePercentage};
+use style::page::{PageMarginBoxType, PagePseudoClass, margin_box_text, page_style};
 use style::parser::ParserContextExtraData;
 use style::properties::{PropertyDeclaration, PropertyDeclarationBlock, DeclaredValue, longhands};
 use style::properties::Importance;
 use style::properties::longhands::animation_play_state;
-use style::stylesheets::{Stylesheet, NamespaceRule, CSSRule, StyleRule, KeyframesRule, Origin};
-use style::values::specified::{LengthOrPercentageOrAuto, Percentage};
+use style::stylesheets::{Stylesheet, NamespaceRule, CSSRule, CSSRuleIteratorExt, StyleRule, KeyframesRule, Origin};
+use style::stylesheets::{ImportLayer, LayerName};
+use style::values::specified::{Length, LengthOrPercentageOrAuto, Percentage, Size};
 use url::Url;
 
 #[test]
@@ -107,6 +113,7 @@ fn test_parse_stylesheet() {
                     ],
                     important_count: 2,
                 }),
+                rules: vec![],
             })),
             CSSRule::Style(Arc::new(StyleRule {
                 selectors: vec![
@@ -153,6 +160,7 @@ fn test_parse_stylesheet() {
                     ],
                     important_count: 0,
                 }),
+                rules: vec![],
             })),
             CSSRule::Style(Arc::new(StyleRule {
                 selectors: vec![
@@ -229,6 +237,7 @@ fn test_parse_stylesheet() {
                     ],
                     important_count: 0,
                 }),
+                rules: vec![],
             })),
             CSSRule::Keyframes(Arc::new(KeyframesRule {
                 name: "foo".into(),
@@ -238,8 +247,8 @@ fn test_parse_stylesheet() {
                                       vec![KeyframePercentage::new(0.)]),
                         block: Arc::new(PropertyDeclarationBlock {
                             declarations: vec![
-                                (PropertyDeclaration::Width(DeclaredValue::Value(
-                                    LengthOrPercentageOrAuto::Percentage(Percentage(0.)))),
+                                (PropertyDeclaration::Width(DeclaredValue::Value(Size::LengthOrPercentageOrAuto(
+                                    LengthOrPercentageOrAuto::Percentage(Percentage(0.))))),
                                  Importance::Normal),
                             ],
                             important_count: 0,
@@ -250,8 +259,8 @@ fn test_parse_stylesheet() {
                                       vec![KeyframePercentage::new(1.)]),
                         block: Arc::new(PropertyDeclarationBlock {
                             declarations: vec![
-                                (PropertyDeclaration::Width(DeclaredValue::Value(
-                                    LengthOrPercentageOrAuto::Percentage(Percentage(1.)))),
+                                (PropertyDeclaration::Width(DeclaredValue::Value(Size::LengthOrPercentageOrAuto(
+                                    LengthOrPercentageOrAuto::Percentage(Percentage(1.))))),
                                  Importance::Normal),
                                 (PropertyDeclaration::AnimationPlayState(DeclaredValue::Value(
                                     animation_play_state::SpecifiedValue(
@@ -268,6 +277,251 @@ fn test_parse_stylesheet() {
     });
 }
 
+fn parse_author_stylesheet(css: &str) -> Stylesheet {
+    let url = Url::parse("about::test").unwrap();
+    Stylesheet::from_str(css, url, Origin::Author, Box::new(CSSErrorReporterTest),
+                         ParserContextExtraData::default())
+}
+
+fn style_rule(rule: &CSSRule) -> &StyleRule {
+    match *rule {
+        CSSRule::Style(ref rule) => rule,
+        _ => panic!("expected a style rule, got {:?}", rule),
+    }
+}
+
+#[test]
+fn test_parse_nested_rules() {
+    let stylesheet = parse_author_stylesheet(r"
+        .a, .b {
+            color: red;
+            .c { color: blue; }
+            a:hover { color: green; }
+            &.d > & { color: black; }
+            > .e { color: white; }
+            @media screen {
+                color: gray;
+            }
+            width: 10px;
+        }");
+    let expected = parse_author_stylesheet(r"
+        .a .c, .b .c { color: blue; }
+        .a a:hover, .b a:hover { color: green; }
+        .a.d > .a, .b.d > .b { color: black; }
+        .a > .e, .b > .e { color: white; }
+        .a, .b { color: gray; }");
+
+    assert_eq!(stylesheet.rules.len(), 1);
+    let rule = style_rule(&stylesheet.rules[0]);
+    assert_eq!(rule.declarations.declarations.len(), 2);
+    assert_eq!(rule.rules.len(), 5);
+
+    for (nested, expected) in rule.rules.iter().zip(expected.rules.iter()).take(4) {
+        assert_eq!(style_rule(nested).selectors, style_rule(expected).selectors);
+        assert_eq!(style_rule(nested).declarations, style_rule(expected).declarations);
+    }
+    match rule.rules[4] {
+        CSSRule::Media(ref media) => {
+            assert_eq!(media.rules.len(), 1);
+            assert_eq!(style_rule(&media.rules[0]).selectors, style_rule(&expected.rules[4]).selectors);
+        },
+        ref rule => panic!("expected a media rule, got {:?}", rule),
+    }
+
+    // Nested rules are visited after their parent, in source order.
+    assert_eq!(stylesheet.rules().style().count(), 6);
+}
+
+fn layer_name(name: &str) -> LayerName {
+    LayerName(name.split('.').map(Atom::from).collect())
+}
+
+#[test]
+fn test_parse_layers() {
+    let css = r"
+        @layer reset, framework.base;
+        @import url(theme.css) layer(theme);
+        @import 'utilities.css' layer screen;
+        @layer framework . base { }
+        @layer framework.base {
+            a { color: red; }
+            @layer { b { color: blue; } }
+        }
+        @layer a, b { }
+        @layer initial { }
+        @import 'late.css';";
+    let url = Url::parse("http://example.com/style.css").unwrap();
+    let stylesheet = Stylesheet::from_str(css, url, Origin::Author, Box::new(CSSErrorReporterTest),
+                                          ParserContextExtraData::default());
+
+    assert_eq!(stylesheet.rules.len(), 4);
+    match stylesheet.rules[0] {
+        CSSRule::LayerStatement(ref statement) => {
+            assert_eq!(statement.names, vec![layer_name("reset"), layer_name("framework.base")]);
+        },
+        ref rule => panic!("expected a layer statement, got {:?}", rule),
+    }
+    match stylesheet.rules[1] {
+        CSSRule::Import(ref import) => {
+            assert_eq!(import.url.as_str(), "http://example.com/theme.css");
+            assert_eq!(import.layer, ImportLayer::Named(layer_name("theme")));
+        },
+        ref rule => panic!("expected an import rule, got {:?}", rule),
+    }
+    match stylesheet.rules[2] {
+        CSSRule::Import(ref import) => {
+            assert_eq!(import.layer, ImportLayer::Anonymous);
+            assert_eq!(import.media_queries.media_queries.len(), 1);
+        },
+        ref rule => panic!("expected an import rule, got {:?}", rule),
+    }
+    match stylesheet.rules[3] {
+        CSSRule::Layer(ref layer) => {
+            assert_eq!(layer.name, Some(layer_name("framework.base")));
+            assert_eq!(layer.rules.len(), 2);
+            match layer.rules[1] {
+                CSSRule::Layer(ref layer) => assert_eq!(layer.name, None),
+                ref rule => panic!("expected a layer block, got {:?}", rule),
+            }
+        },
+        ref rule => panic!("expected a layer block, got {:?}", rule),
+    }
+
+    // Rules in layers are visited like any other nested rules.
+    assert_eq!(stylesheet.rules().style().count(), 2);
+}
+
+fn length_feature(feature: LengthFeature, comparison: Comparison, px: i32) -> ContainerCondition {
+    ContainerCondition::Feature(SizeFeature::Length(feature, comparison, Length::Absolute(Au::from_px(px))))
+}
+
+#[test]
+fn test_parse_container_rules() {
+    let stylesheet = parse_author_stylesheet(r"
+        @container card (min-width: 400px) { .a { color: red; } }
+        @container (400px <= width < 800px) and (orientation: landscape) { .b { color: red; } }
+        @container not (inline-size > 30px) { .c { color: red; } }
+        @container (width > 10px) or ((height = 10px)) { }
+        @container and (width > 10px) { }
+        @container (width > 10px) and (height > 10px) or (width < 10px) { }
+        @container (400px < width > 800px) { }
+        .d { @container (block-size >= 10px) { color: red; } }");
+
+    assert_eq!(stylesheet.rules.len(), 5);
+    let conditions: Vec<_> = stylesheet.rules[..4].iter().map(|rule| {
+        match *rule {
+            CSSRule::Container(ref container) => (container.query.name.clone(), &container.query.condition),
+            ref rule => panic!("expected a container rule, got {:?}", rule),
+        }
+    }).collect();
+
+    // Style rules in `@container` rules are visited like any other nested rules.
+    assert_eq!(stylesheet.rules().style().count(), 5);
+
+    assert_eq!(conditions[0].0, Some(Atom::from("card")));
+    assert_eq!(*conditions[0].1, length_feature(LengthFeature::Width, Comparison::GreaterOrEqual, 400));
+    assert_eq!(conditions[1].0, None);
+    assert_eq!(*conditions[1].1, ContainerCondition::And(vec![
+        ContainerCondition::And(vec![
+            length_feature(LengthFeature::Width, Comparison::GreaterOrEqual, 400),
+            length_feature(LengthFeature::Width, Comparison::Less, 800),
+        ]),
+        ContainerCondition::Feature(SizeFeature::Orientation(Orientation::Landscape)),
+    ]));
+    assert_eq!(*conditions[2].1, ContainerCondition::Not(Box::new(
+        length_feature(LengthFeature::InlineSize, Comparison::Greater, 30))));
+    assert_eq!(*conditions[3].1, ContainerCondition::Or(vec![
+        length_feature(LengthFeature::Width, Comparison::Greater, 10),
+        length_feature(LengthFeature::Height, Comparison::Equal, 10),
+    ]));
+
+    // `@container` rules can be nested in style rules.
+    let rule = style_rule(&stylesheet.rules[4]);
+    match rule.rules[0] {
+        CSSRule::Container(ref container) => {
+            assert_eq!(container.query.condition,
+                       length_feature(LengthFeature::BlockSize, Comparison::GreaterOrEqual, 10));
+            assert_eq!(style_rule(&container.rules[0]).selectors, rule.selectors);
+        },
+        ref rule => panic!("expected a container rule, got {:?}", rule),
+    }
+}
+
+#[test]
+fn test_parse_page_rules() {
+    let stylesheet = parse_author_stylesheet(r#"
+        @page {
+            size: A4;
+            margin: 1in 2cm;
+            @bottom-center { content: "Page " counter(page) " of " counter(pages); }
+        }
+        @page :first { size: 100px 200px; margin-top: 2in; }
+        @page :left { size: landscape; }
+        @page :blank { margin: 0; }"#);
+
+    let rules: Vec<_> = stylesheet.rules.iter().map(|rule| {
+        match *rule {
+            CSSRule::Page(ref page) => page.clone(),
+            ref rule => panic!("expected a page rule, got {:?}", rule),
+        }
+    }).collect();
+    assert_eq!(rules.len(), 3);
+    assert_eq!(rules[1].selector.pseudo_classes, vec![PagePseudoClass::First]);
+    assert_eq!(rules[0].margin_boxes[0].box_type, PageMarginBoxType::BottomCenter);
+    assert_eq!(margin_box_text(&rules[0].margin_boxes[0].content, 1, 5), "Page 2 of 5");
+
+    let medium_size = Size2D::new(Au::from_px(800), Au::from_px(600));
+
+    // `:first` wins over the rule without a selector.
+    let first = page_style(&rules, 0, medium_size);
+    assert_eq!(first.size, Size2D::new(Au::from_px(100), Au::from_px(200)));
+    assert_eq!(first.margins.top, Au::from_px(192));
+    assert_eq!(first.margins.bottom, Au::from_px(96));
+    assert_eq!(first.margin_boxes.len(), 1);
+
+    // An orientation alone turns the size of the medium.
+    let left = page_style(&rules, 1, medium_size);
+    assert_eq!(left.size, medium_size);
+    assert_eq!(left.content_box().origin.y, Au::from_px(96));
+
+    let right = page_style(&rules, 2, medium_size);
+    assert_eq!(right.size.width, Au::from_f32_px(210. * 96. / 25.4));
+}
+
+#[test]
+fn test_parse_property_rules() {
+    let stylesheet = parse_author_stylesheet(r#"
+        @property --any { syntax: "*"; inherits: true; }
+        @property --size { syntax: "<length> | <percentage>"; inherits: false; initial-value: 10px; }
+        @property --no-initial-value { syntax: "<color>"; inherits: false; }
+        @property --invalid-initial-value { syntax: "<color>"; inherits: false; initial-value: 10px; }
+        @property --dependent { syntax: "*"; inherits: false; initial-value: var(--any); }
+        @property --multiplied { syntax: "<length>+"; inherits: false; initial-value: 10px; }"#);
+
+    let rules: Vec<_> = stylesheet.rules.iter().map(|rule| {
+        match *rule {
+            CSSRule::Property(ref property) => property.clone(),
+            ref rule => panic!("expected a property rule, got {:?}", rule),
+        }
+    }).collect();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].name, Atom::from("any"));
+    assert_eq!(rules[0].syntax, PropertySyntax::Universal);
+    assert!(rules[0].inherits);
+    assert!(rules[0].initial_value.is_none());
+
+    assert_eq!(rules[1].name, Atom::from("size"));
+    assert_eq!(rules[1].syntax,
+               PropertySyntax::DataTypes(vec![SyntaxComponent::Length, SyntaxComponent::Percentage]));
+    assert!(!rules[1].inherits);
+    assert!(rules[1].initial_value.is_some());
+
+    assert!(rules[1].syntax.matches("2em"));
+    assert!(rules[1].syntax.matches("50%"));
+    assert!(!rules[1].syntax.matches("red"));
+    assert!(!rules[1].syntax.matches("10px 10px"));
+}
+
 struct CSSError {
     pub line: usize,
     pub column: usize,
@@ -4,6 +4,7 @@
 
 use app_units::Au;
 use style::values::HasViewportPercentage;
+use style::values::computed::{CalcComparison, CalcComparisonFunction, CalcComparisons, CalcLengthOrPercentage};
 use style::values::specified::{ViewportPercentageLength, Length};
 
 #[test]
@@ -13,3 +14,20 @@ fn length_has_viewport_percentage() {
     let l = Length::Absolute(Au(100));
     assert!(!l.has_viewport_percentage());
 }
+
+#[test]
+fn calc_comparison_is_resolved_against_the_percentage_basis() {
+    let length = CalcLengthOrPercentage { length: Some(Au::from_px(10)), percentage: None, comparisons: None };
+    let percentage = CalcLengthOrPercentage { length: None, percentage: Some(0.5), comparisons: None };
+    let min = CalcComparison {
+        function: CalcComparisonFunction::Min,
+        arguments: vec![length, percentage],
+    };
+    let calc = CalcLengthOrPercentage {
+        length: Some(Au::from_px(1)),
+        percentage: None,
+        comparisons: Some(CalcComparisons::new(vec![min])),
+    };
+    assert_eq!(calc.to_used_value(Au::from_px(100)), Au::from_px(11));
+    assert_eq!(calc.to_used_value(Au::from_px(10)), Au::from_px(6));
+}
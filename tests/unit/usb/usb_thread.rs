@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use ipc_channel::ipc::{self, IpcSender};
+use usb::{UsbThreadFactory, matches_filter};
+use usb_traits::{UsbDeviceFilter, UsbDeviceMsg, UsbMethodMsg};
+
+fn device() -> UsbDeviceMsg {
+    UsbDeviceMsg {
+        id: "1".to_owned(),
+        usb_version: 0x0200,
+        device_class: 0xff,
+        device_subclass: 0x01,
+        device_protocol: 0x02,
+        vendor_id: 0x1234,
+        product_id: 0x5678,
+        device_version: 0x0100,
+        manufacturer_name: Some("Servo".to_owned()),
+        product_name: Some("Gadget".to_owned()),
+        serial_number: Some("0001".to_owned()),
+        configuration_value: Some(1),
+    }
+}
+
+fn filter() -> UsbDeviceFilter {
+    UsbDeviceFilter {
+        vendor_id: None,
+        product_id: None,
+        class_code: None,
+        subclass_code: None,
+        protocol_code: None,
+        serial_number: None,
+    }
+}
+
+#[test]
+fn test_an_empty_filter_matches_every_device() {
+    assert!(matches_filter(&device(), &filter()));
+}
+
+#[test]
+fn test_every_member_of_a_filter_must_match() {
+    let mut matching = filter();
+    matching.vendor_id = Some(0x1234);
+    matching.product_id = Some(0x5678);
+    matching.class_code = Some(0xff);
+    matching.subclass_code = Some(0x01);
+    matching.protocol_code = Some(0x02);
+    matching.serial_number = Some("0001".to_owned());
+    assert!(matches_filter(&device(), &matching));
+
+    let mut other_product = filter();
+    other_product.vendor_id = Some(0x1234);
+    other_product.product_id = Some(0x0000);
+    assert!(!matches_filter(&device(), &other_product));
+
+    let mut other_serial_number = filter();
+    other_serial_number.serial_number = Some("0002".to_owned());
+    assert!(!matches_filter(&device(), &other_serial_number));
+
+    let mut no_serial_number = device();
+    no_serial_number.serial_number = None;
+    assert!(!matches_filter(&no_serial_number, &matching));
+}
+
+#[test]
+fn test_origins_only_get_the_devices_they_were_allowed_to_use() {
+    let usb_thread: IpcSender<UsbMethodMsg> = UsbThreadFactory::new();
+    let origin = "https://example.com".to_owned();
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    usb_thread.send(UsbMethodMsg::GetDevices(origin.clone(), sender)).unwrap();
+    if let Ok(devices) = receiver.recv().unwrap() {
+        assert!(devices.is_empty());
+    }
+
+    // The user can only choose from the devices that were enumerated.
+    let (sender, receiver) = ipc::channel().unwrap();
+    usb_thread.send(UsbMethodMsg::AllowDevice(origin.clone(), "unknown".to_owned(), sender)).unwrap();
+    assert!(receiver.recv().unwrap().is_err());
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    usb_thread.send(UsbMethodMsg::Open("unknown".to_owned(), sender)).unwrap();
+    assert!(receiver.recv().unwrap().is_err());
+
+    usb_thread.send(UsbMethodMsg::Exit).unwrap();
+}
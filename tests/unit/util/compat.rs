@@ -0,0 +1,30 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use util::compat::{find_ua_override, read_ua_overrides_from_file};
+
+#[test]
+fn test_read_ua_overrides() {
+    let json_str = "{\
+  \"Example.com\": \"Mozilla/5.0 Legacy\",\
+  \"bogus.org\": 3\
+}";
+
+    let overrides = read_ua_overrides_from_file(json_str.as_bytes()).unwrap();
+    assert_eq!(overrides, vec![("example.com".to_owned(), "Mozilla/5.0 Legacy".to_owned())]);
+}
+
+#[test]
+fn test_find_ua_override() {
+    let overrides = vec![
+        ("example.com".to_owned(), "generic".to_owned()),
+        ("mail.example.com".to_owned(), "specific".to_owned()),
+    ];
+
+    assert_eq!(find_ua_override("example.com", &overrides), Some("generic"));
+    assert_eq!(find_ua_override("www.example.com", &overrides), Some("generic"));
+    assert_eq!(find_ua_override("mail.example.com", &overrides), Some("specific"));
+    assert_eq!(find_ua_override("badexample.com", &overrides), None);
+    assert_eq!(find_ua_override("example.org", &overrides), None);
+}
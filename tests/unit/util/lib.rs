@@ -6,6 +6,7 @@
 
 extern crate util;
 
+mod compat;
 mod opts;
 mod prefs;
 mod remutex;
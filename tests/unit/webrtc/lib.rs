@@ -0,0 +1,14 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(test)]
+
+extern crate ipc_channel;
+extern crate webrtc;
+extern crate webrtc_traits;
+
+#[cfg(feature = "libjuice")]
+mod loopback;
+mod sdp;
+mod transport;
@@ -0,0 +1,177 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Two transports on the same host, which script would connect through a
+//! signaling server.
+
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use std::thread;
+use std::time::{Duration, Instant};
+#[cfg(feature = "usrsctp")]
+use transport::channel_parameters;
+use webrtc::WebRTCThreadFactory;
+#[cfg(feature = "usrsctp")]
+use webrtc_traits::ChannelMessage;
+use webrtc_traits::{Certificate, DtlsState, IceConnectionState, IceCredentials, Setup, TransportConfig};
+use webrtc_traits::{TransportEvent, TransportMsg, WebRTCMsg};
+use webrtc_traits::sdp::{self, LocalParameters, SessionDescription};
+
+/// How long two transports on the same host get to do what a test expects.
+const LOOPBACK_TIMEOUT_SECS: u64 = 10;
+
+/// The transport of a peer connection, as script sees it.
+struct Peer {
+    sender: IpcSender<TransportMsg>,
+    events: IpcReceiver<TransportEvent>,
+    credentials: IceCredentials,
+    certificate: Certificate,
+}
+
+fn start_peer(webrtc: &IpcSender<WebRTCMsg>, controlling: bool) -> Result<Peer, ()> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    webrtc.send(WebRTCMsg::GenerateCertificate(sender)).unwrap();
+    let certificate = receiver.recv().unwrap().unwrap();
+    let credentials = IceCredentials::generate();
+    let config = TransportConfig {
+        credentials: credentials.clone(),
+        controlling: controlling,
+        certificate: certificate.clone(),
+        stun_urls: vec![],
+    };
+    let (event_sender, events) = ipc::channel().unwrap();
+    let (sender, receiver) = ipc::channel().unwrap();
+    webrtc.send(WebRTCMsg::StartTransport(config, event_sender, sender)).unwrap();
+    receiver.recv().unwrap().map(|sender| {
+        Peer {
+            sender: sender,
+            events: events,
+            credentials: credentials,
+            certificate: certificate,
+        }
+    })
+}
+
+fn local_parameters<'a>(peer: &'a Peer, setup: Setup) -> LocalParameters<'a> {
+    LocalParameters {
+        ice_ufrag: &peer.credentials.ufrag,
+        ice_pwd: &peer.credentials.pwd,
+        fingerprint: &peer.certificate.fingerprint,
+        setup: setup,
+        max_message_size: 262144,
+    }
+}
+
+/// Starts the transports of an offerer and an answerer, and gives each the
+/// description of the other, as parsed from what the other would send.
+fn connect_peers(webrtc: &IpcSender<WebRTCMsg>) -> (Peer, Peer) {
+    let offerer = start_peer(webrtc, true).unwrap();
+    let answerer = start_peer(webrtc, false).unwrap();
+    let offer = sdp::create_offer(1, 1, true, &local_parameters(&offerer, Setup::ActPass));
+    let offer = SessionDescription::parse(&offer.to_string()).unwrap();
+    let answer = sdp::create_answer(2, 1, &offer, &local_parameters(&answerer, Setup::Active));
+    let answer = SessionDescription::parse(&answer.to_string()).unwrap();
+    let offer = sdp::remote_parameters(&offer).unwrap().unwrap();
+    let answer = sdp::remote_parameters(&answer).unwrap().unwrap();
+    answerer.sender.send(TransportMsg::SetRemote(offer)).unwrap();
+    offerer.sender.send(TransportMsg::SetRemote(answer)).unwrap();
+    (offerer, answerer)
+}
+
+/// Passes the candidates that the peers gather to each other, and what else
+/// they report to `done` along with the index of the peer, until it returns
+/// true. Returns false if that takes too long.
+fn run_until<F>(peers: &[&Peer; 2], mut done: F) -> bool where F: FnMut(usize, TransportEvent) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(LOOPBACK_TIMEOUT_SECS);
+    while Instant::now() < deadline {
+        let mut idle = true;
+        for index in 0..2 {
+            while let Ok(event) = peers[index].events.try_recv() {
+                idle = false;
+                if let TransportEvent::IceCandidate(ref candidate) = event {
+                    let _ = peers[1 - index].sender.send(TransportMsg::AddRemoteCandidate(candidate.clone()));
+                }
+                if done(index, event) {
+                    return true;
+                }
+            }
+        }
+        if idle {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+    false
+}
+
+#[test]
+fn test_loopback_offer_and_answer_connect() {
+    let webrtc: IpcSender<WebRTCMsg> = WebRTCThreadFactory::new();
+    let (offerer, answerer) = connect_peers(&webrtc);
+    let mut ice_connected = [false, false];
+    let mut dtls_connected = [false, false];
+    assert!(run_until(&[&offerer, &answerer], |index, event| {
+        match event {
+            TransportEvent::IceStateChanged(IceConnectionState::Connected) => ice_connected[index] = true,
+            TransportEvent::IceStateChanged(IceConnectionState::Failed) => panic!("ICE failed"),
+            TransportEvent::DtlsStateChanged(DtlsState::Connected) => dtls_connected[index] = true,
+            TransportEvent::DtlsStateChanged(DtlsState::Failed) => panic!("DTLS failed"),
+            _ => {},
+        }
+        ice_connected == [true, true] && dtls_connected == [true, true]
+    }));
+    webrtc.send(WebRTCMsg::Exit).unwrap();
+}
+
+#[cfg(feature = "usrsctp")]
+#[test]
+fn test_loopback_data_channel() {
+    let webrtc: IpcSender<WebRTCMsg> = WebRTCThreadFactory::new();
+    let (offerer, answerer) = connect_peers(&webrtc);
+    let peers = [&offerer, &answerer];
+    // The answerer is the DTLS client, which opens channels on even
+    // streams.
+    answerer.sender.send(TransportMsg::OpenChannel(0, channel_parameters(), false)).unwrap();
+
+    let mut opened = false;
+    let mut incoming = false;
+    assert!(run_until(&peers, |index, event| {
+        match (index, event) {
+            (1, TransportEvent::ChannelOpened(0)) => opened = true,
+            (0, TransportEvent::IncomingChannel(0, parameters)) => {
+                assert_eq!(parameters, channel_parameters());
+                incoming = true;
+            },
+            (_, TransportEvent::ChannelClosed(_)) => panic!("The channel closed"),
+            _ => {},
+        }
+        opened && incoming
+    }));
+
+    answerer.sender.send(TransportMsg::Send(0, ChannelMessage::Text("hello".to_owned()))).unwrap();
+    assert!(run_until(&peers, |index, event| match (index, event) {
+        (0, TransportEvent::ChannelMessage(0, message)) => {
+            assert_eq!(message, ChannelMessage::Text("hello".to_owned()));
+            true
+        },
+        _ => false,
+    }));
+
+    offerer.sender.send(TransportMsg::Send(0, ChannelMessage::Binary(vec![1, 2, 3]))).unwrap();
+    assert!(run_until(&peers, |index, event| match (index, event) {
+        (1, TransportEvent::ChannelMessage(0, message)) => {
+            assert_eq!(message, ChannelMessage::Binary(vec![1, 2, 3]));
+            true
+        },
+        _ => false,
+    }));
+
+    answerer.sender.send(TransportMsg::CloseChannel(0)).unwrap();
+    let mut closed = [false, false];
+    assert!(run_until(&peers, |index, event| {
+        if let TransportEvent::ChannelClosed(0) = event {
+            closed[index] = true;
+        }
+        closed == [true, true]
+    }));
+    webrtc.send(WebRTCMsg::Exit).unwrap();
+}
@@ -0,0 +1,89 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use webrtc_traits::{Candidate, CandidateType, Fingerprint, Setup, parse_stun_url};
+use webrtc_traits::sdp::{self, LocalParameters, SessionDescription};
+
+fn local_parameters<'a>(fingerprint: &'a Fingerprint, setup: Setup) -> LocalParameters<'a> {
+    LocalParameters {
+        ice_ufrag: "ufrag",
+        ice_pwd: "passwordpasswordpassword",
+        fingerprint: fingerprint,
+        setup: setup,
+        max_message_size: 262144,
+    }
+}
+
+#[test]
+fn test_offer_and_answer() {
+    let fingerprint = Fingerprint::parse("sha-256 AB:CD:EF").unwrap();
+    let offer = sdp::create_offer(1, 1, true, &local_parameters(&fingerprint, Setup::ActPass));
+    let parsed = SessionDescription::parse(&offer.to_string()).unwrap();
+    assert_eq!(parsed, offer);
+
+    let remote = sdp::remote_parameters(&parsed).unwrap().unwrap();
+    assert_eq!(remote.ice_ufrag, "ufrag");
+    assert_eq!(remote.fingerprint, fingerprint);
+    assert_eq!(remote.setup, Setup::ActPass);
+    assert_eq!(remote.sctp_port, 5000);
+    assert_eq!(remote.max_message_size, 262144);
+
+    // The audio section is rejected, and keeps its mid.
+    let sdp = "v=0\r\n\
+               o=- 2 2 IN IP4 127.0.0.1\r\n\
+               s=-\r\n\
+               t=0 0\r\n\
+               m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
+               a=mid:audio\r\n\
+               m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n\
+               a=mid:data\r\n\
+               a=ice-ufrag:ufrag\r\n\
+               a=ice-pwd:passwordpasswordpassword\r\n\
+               a=fingerprint:sha-256 AB:CD:EF\r\n\
+               a=setup:actpass\r\n\
+               a=sctp-port:5000\r\n";
+    let offer = SessionDescription::parse(sdp).unwrap();
+    let answer = sdp::create_answer(3, 1, &offer, &local_parameters(&fingerprint, Setup::Active));
+    assert_eq!(answer.media.len(), 2);
+    assert_eq!(answer.media[0].port, 0);
+    assert_eq!(answer.media[0].mid(), Some("audio"));
+    assert!(answer.media[1].is_data_channel());
+    assert_eq!(answer.media[1].mid(), Some("data"));
+    assert_eq!(answer.attribute("group"), Some("BUNDLE data"));
+    assert_eq!(sdp::remote_parameters(&answer).unwrap().unwrap().setup, Setup::Active);
+}
+
+#[test]
+fn test_candidates_in_descriptions() {
+    let fingerprint = Fingerprint::parse("sha-256 AB:CD:EF").unwrap();
+    let mut offer = sdp::create_offer(1, 1, true, &local_parameters(&fingerprint, Setup::ActPass));
+    let candidate = Candidate::parse("candidate:1 1 udp 2122260223 192.168.1.2 54321 typ host").unwrap();
+    sdp::add_candidates(&mut offer, &[candidate.clone()], true);
+    let parsed = SessionDescription::parse(&offer.to_string()).unwrap();
+    assert!(parsed.data_channel_media().unwrap().has_attribute("end-of-candidates"));
+    assert_eq!(sdp::remote_parameters(&parsed).unwrap().unwrap().candidates, vec![candidate]);
+}
+
+#[test]
+fn test_candidate_round_trip() {
+    let value = "1 1 udp 2122260223 192.168.1.2 54321 typ srflx raddr 10.0.0.1 rport 9 generation 0";
+    let candidate = Candidate::parse(&format!("candidate:{}", value)).unwrap();
+    assert_eq!(candidate.foundation, "1");
+    assert_eq!(candidate.priority, 2122260223);
+    assert_eq!(candidate.address, "192.168.1.2:54321".parse().unwrap());
+    assert_eq!(candidate.candidate_type, CandidateType::ServerReflexive);
+    assert_eq!(candidate.related_address, Some("10.0.0.1:9".parse().unwrap()));
+    assert_eq!(candidate.to_sdp_value(), value);
+
+    assert!(Candidate::parse("candidate:1 1 tcp 1 192.168.1.2 9 typ host tcptype active").is_err());
+}
+
+#[test]
+fn test_parse_stun_url() {
+    assert_eq!(parse_stun_url("stun:stun.example.org"), Some(("stun.example.org".to_owned(), 3478)));
+    assert_eq!(parse_stun_url("stun:192.0.2.1:19302"), Some(("192.0.2.1".to_owned(), 19302)));
+    assert_eq!(parse_stun_url("stun:[2001:db8::1]:3479"), Some(("2001:db8::1".to_owned(), 3479)));
+    assert_eq!(parse_stun_url("turn:turn.example.org"), None);
+    assert_eq!(parse_stun_url("stun:host:port"), None);
+}
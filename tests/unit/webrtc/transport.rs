@@ -0,0 +1,112 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#[cfg(not(feature = "libjuice"))]
+use ipc_channel::ipc::{self, IpcSender};
+use std::time::Instant;
+#[cfg(not(feature = "libjuice"))]
+use webrtc::WebRTCThreadFactory;
+use webrtc::datachannel;
+use webrtc::dtls::{self, DtlsEndpoint};
+use webrtc::sctp::Reliability;
+use webrtc_traits::{ChannelMessage, ChannelParameters};
+#[cfg(not(feature = "libjuice"))]
+use webrtc_traits::{IceCredentials, TransportConfig, WebRTCMsg};
+
+pub fn channel_parameters() -> ChannelParameters {
+    ChannelParameters {
+        label: "chat".to_owned(),
+        protocol: "".to_owned(),
+        ordered: false,
+        max_packet_life_time: None,
+        max_retransmits: Some(3),
+        priority: 256,
+    }
+}
+
+#[test]
+fn test_data_channel_open() {
+    let parameters = channel_parameters();
+    assert_eq!(datachannel::decode_open(&datachannel::encode_open(&parameters)), Ok(parameters));
+
+    assert_eq!(datachannel::encode_message(&ChannelMessage::Text(String::new())), (56, vec![0]));
+    assert_eq!(datachannel::decode_message(56, vec![0]), Some(ChannelMessage::Text(String::new())));
+}
+
+#[test]
+fn test_data_channel_reliability() {
+    let mut parameters = channel_parameters();
+    assert_eq!(datachannel::reliability(&parameters), Reliability::MaxRetransmits(3));
+    parameters.max_retransmits = None;
+    parameters.max_packet_life_time = Some(500);
+    assert_eq!(datachannel::reliability(&parameters), Reliability::MaxPacketLifeTime(500));
+    parameters.max_packet_life_time = None;
+    assert_eq!(datachannel::reliability(&parameters), Reliability::Reliable);
+}
+
+/// Passes the datagrams of the endpoints to each other until neither has
+/// anything to send, returning what the server received.
+fn exchange(client: &mut DtlsEndpoint, server: &mut DtlsEndpoint, now: Instant) -> Result<Vec<Vec<u8>>, ()> {
+    let mut received = vec![];
+    for _ in 0..10 {
+        let datagrams = client.take_datagrams();
+        if datagrams.is_empty() {
+            break;
+        }
+        for datagram in datagrams {
+            received.extend(try!(server.handle_datagram(&datagram, now)));
+        }
+        for datagram in server.take_datagrams() {
+            try!(client.handle_datagram(&datagram, now));
+        }
+    }
+    Ok(received)
+}
+
+#[test]
+fn test_dtls_endpoints_connect() {
+    let now = Instant::now();
+    let (a_certificate, b_certificate) = (dtls::generate_certificate().unwrap(), dtls::generate_certificate().unwrap());
+    let mut a = DtlsEndpoint::new(&a_certificate, true, b_certificate.fingerprint.clone()).unwrap();
+    let mut b = DtlsEndpoint::new(&b_certificate, false, a_certificate.fingerprint.clone()).unwrap();
+    a.start(now).unwrap();
+    assert_eq!(exchange(&mut a, &mut b, now), Ok(vec![]));
+    assert!(a.is_connected());
+    assert!(b.is_connected());
+    assert_eq!(a.next_timeout(), None);
+
+    a.send(b"hello").unwrap();
+    assert_eq!(exchange(&mut a, &mut b, now), Ok(vec![b"hello".to_vec()]));
+}
+
+#[test]
+fn test_dtls_rejects_other_certificates() {
+    let now = Instant::now();
+    let (a_certificate, b_certificate) = (dtls::generate_certificate().unwrap(), dtls::generate_certificate().unwrap());
+    let mut a = DtlsEndpoint::new(&a_certificate, true, b_certificate.fingerprint.clone()).unwrap();
+    let mut b = DtlsEndpoint::new(&b_certificate, false, b_certificate.fingerprint.clone()).unwrap();
+    a.start(now).unwrap();
+    assert_eq!(exchange(&mut a, &mut b, now), Err(()));
+    assert!(!b.is_connected());
+}
+
+/// Without libjuice, script is told that a transport can't start.
+#[cfg(not(feature = "libjuice"))]
+#[test]
+fn test_transports_need_libjuice() {
+    let webrtc: IpcSender<WebRTCMsg> = WebRTCThreadFactory::new();
+    let (sender, receiver) = ipc::channel().unwrap();
+    webrtc.send(WebRTCMsg::GenerateCertificate(sender)).unwrap();
+    let config = TransportConfig {
+        credentials: IceCredentials::generate(),
+        controlling: true,
+        certificate: receiver.recv().unwrap().unwrap(),
+        stun_urls: vec![],
+    };
+    let (event_sender, _events) = ipc::channel().unwrap();
+    let (sender, receiver) = ipc::channel().unwrap();
+    webrtc.send(WebRTCMsg::StartTransport(config, event_sender, sender)).unwrap();
+    assert!(receiver.recv().unwrap().is_err());
+    webrtc.send(WebRTCMsg::Exit).unwrap();
+}